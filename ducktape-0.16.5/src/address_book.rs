@@ -0,0 +1,376 @@
+//! A local copy of Contacts.app for fast contact lookups.
+//
+// Asking Contacts.app for a person through AppleScript takes a second or more, once per name.
+// `ducktape contacts sync` copies every person's name, email addresses, phone numbers and
+// birthday to ~/.ducktape/address_book.json. Later syncs only ask for the people changed since
+// the newest modification date in the copy, plus the list of ids to drop deleted people.
+// Lookups use the copy once it exists and refresh it when it is more than a day old; names
+// not found in it still go to Contacts.app, so people added since are not missed.
+
+use crate::applescript::{Script, Template};
+use crate::calendar::{
+    ContactCandidate, MIN_CONTACT_SCORE, nickname_variants, score_contact_match,
+};
+use crate::script_runner::run_applescript_async;
+use crate::state::{AddressBookEntry, StateManager};
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, NaiveDateTime, Timelike};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime};
+use tracing::{debug, info, warn};
+
+/// Lookups refresh a copy older than this first
+const REFRESH_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// Text after "with" or "invite", where names of people are expected
+static INVITEES: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\b(?:with|invite|inviting)\s+([\w' ,-]+)").unwrap());
+
+const SYNC_SCRIPT: &str = r#"on pad(n)
+    return text -2 thru -1 of ("0" & (n as integer))
+end pad
+
+on fmt(d)
+    return (year of d as text) & "-" & my pad(month of d as integer) & "-" & my pad(day of d)
+end fmt
+
+on stamp(d)
+    set t to time of d
+    return my fmt(d) & "T" & my pad(t div 3600) & ":" & my pad((t mod 3600) div 60) & ":" & my pad(t mod 60)
+end stamp
+
+on joined(theList)
+    set AppleScript's text item delimiters to ","
+    set theText to theList as text
+    set AppleScript's text item delimiters to ""
+    return theText
+end joined
+
+${since}
+tell application "Contacts"
+    try
+        set output to "ids" & tab & my joined(id of people) & linefeed
+        repeat with p in ${people}
+            set theBirthday to ""
+            if birth date of p is not missing value then set theBirthday to my fmt(birth date of p)
+            set output to output & (id of p) & tab & (name of p) & tab & my stamp(modification date of p) & tab & my joined(value of emails of p) & tab & my joined(value of phones of p) & tab & theBirthday & linefeed
+        end repeat
+        return output
+    on error errMsg
+        return "Error: " & errMsg
+    end try
+end tell"#;
+
+/// What a sync changed in the copy
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SyncSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+    /// People in the copy afterwards
+    pub total: usize,
+}
+
+/// People read from Contacts.app by `SYNC_SCRIPT`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SyncOutput {
+    /// Every person's id, to find deleted people; `None` if Contacts.app sent none
+    pub ids: Option<HashSet<String>>,
+    /// The people changed since the last sync, or everyone
+    pub changed: Vec<AddressBookEntry>,
+}
+
+/// The script reading people changed at or after `since`, or everyone without it
+///
+/// The date is built from its parts, as AppleScript reads date strings in the Mac's locale.
+pub fn sync_script(since: Option<NaiveDateTime>) -> Result<Script> {
+    let (since, people) = match since {
+        Some(since) => (
+            Template::new(
+                "set since to current date\nset day of since to 1\nset year of since to ${year}\nset month of since to ${month}\nset day of since to ${day}\nset time of since to ${time}",
+            )
+            .number("year", since.year())
+            .number("month", since.month())
+            .number("day", since.day())
+            .number("time", since.num_seconds_from_midnight())
+            .render()?,
+            "(every person whose modification date >= since)",
+        ),
+        None => (Script::from(""), "people"),
+    };
+    Template::new(SYNC_SCRIPT)
+        .script("since", since)
+        .script("people", people)
+        .render()
+}
+
+fn split_list(field: &str) -> Vec<String> {
+    field
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty() && *value != "missing value")
+        .map(str::to_string)
+        .collect()
+}
+
+/// Parse the `ids` line and the tab separated people written by `SYNC_SCRIPT`
+pub fn parse_sync_output(output: &str) -> SyncOutput {
+    let mut parsed = SyncOutput::default();
+    for line in output.lines() {
+        let fields: Vec<&str> = line.split('\t').collect();
+        match fields.as_slice() {
+            ["ids", ids] => parsed.ids = Some(split_list(ids).into_iter().collect()),
+            [id, name, modified_at, emails, phones, rest @ ..] if !name.trim().is_empty() => {
+                parsed.changed.push(AddressBookEntry {
+                    id: id.trim().to_string(),
+                    name: name.trim().to_string(),
+                    emails: split_list(emails)
+                        .into_iter()
+                        .filter(|email| crate::calendar::validate_email(email))
+                        .collect(),
+                    phones: split_list(phones),
+                    birthday: rest
+                        .first()
+                        .map(|birthday| birthday.trim().to_string())
+                        .filter(|birthday| !birthday.is_empty()),
+                    modified_at: modified_at.trim().to_string(),
+                })
+            }
+            _ => debug!("Skipping contact line: {}", line),
+        }
+    }
+    parsed
+}
+
+/// Apply what Contacts.app sent to the copy in `entries`
+pub fn merge(entries: &mut Vec<AddressBookEntry>, output: SyncOutput) -> SyncSummary {
+    let mut summary = SyncSummary::default();
+    if let Some(ids) = &output.ids {
+        let before = entries.len();
+        entries.retain(|entry| ids.contains(&entry.id));
+        summary.removed = before - entries.len();
+    }
+    for person in output.changed {
+        match entries.iter_mut().find(|entry| entry.id == person.id) {
+            Some(entry) if *entry == person => {}
+            Some(entry) => {
+                *entry = person;
+                summary.updated += 1;
+            }
+            None => {
+                entries.push(person);
+                summary.added += 1;
+            }
+        }
+    }
+    entries.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+    summary.total = entries.len();
+    summary
+}
+
+/// The newest modification date in the copy, where the next sync starts
+pub fn last_modified(entries: &[AddressBookEntry]) -> Option<NaiveDateTime> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            NaiveDateTime::parse_from_str(&entry.modified_at, TIMESTAMP_FORMAT).ok()
+        })
+        .max()
+}
+
+/// Bring the copy up to date with Contacts.app; `full` reads every person again
+pub async fn sync(full: bool) -> Result<SyncSummary> {
+    let manager = StateManager::new()?;
+    let cached: Vec<AddressBookEntry> = manager.load()?;
+    let since = if full { None } else { last_modified(&cached) };
+    debug!("Syncing contacts changed since {:?}", since);
+
+    let output = run_applescript_async(sync_script(since)?).await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || stdout.starts_with("Error: ") {
+        let error = if stdout.starts_with("Error: ") {
+            stdout.replace("Error: ", "")
+        } else {
+            String::from_utf8_lossy(&output.stderr).to_string()
+        };
+        return Err(anyhow!("Failed to read contacts from Contacts.app: {}", error.trim()));
+    }
+    let output = parse_sync_output(&stdout);
+    let summary = manager.update(|entries: &mut Vec<AddressBookEntry>| merge(entries, output))?;
+    info!(
+        "Synced contacts: {} added, {} updated, {} removed",
+        summary.added, summary.updated, summary.removed
+    );
+    Ok(summary)
+}
+
+/// The copy of Contacts.app, refreshed first when it is old; `None` before the first sync
+pub async fn entries() -> Option<Vec<AddressBookEntry>> {
+    let manager = StateManager::new().ok()?;
+    let written = manager.last_written::<AddressBookEntry>()?;
+    let age = SystemTime::now().duration_since(written).unwrap_or_default();
+    if age > REFRESH_AFTER {
+        if let Err(e) = sync(false).await {
+            warn!("Using the contacts copy from before, as refreshing it failed: {}", e);
+        }
+    }
+    match manager.load::<AddressBookEntry>() {
+        Ok(entries) => Some(entries),
+        Err(e) => {
+            warn!("Failed to read the contacts copy: {}", e);
+            None
+        }
+    }
+}
+
+/// People in `entries` whose name could match, scored and sorted best first
+pub fn find_candidates(entries: &[AddressBookEntry], name: &str) -> Vec<ContactCandidate> {
+    let mut candidates: Vec<ContactCandidate> = entries
+        .iter()
+        .filter(|entry| !entry.emails.is_empty())
+        .map(|entry| ContactCandidate {
+            name: entry.name.clone(),
+            emails: entry.emails.clone(),
+            score: score_contact_match(name, &entry.name),
+        })
+        .filter(|candidate| candidate.score >= MIN_CONTACT_SCORE)
+        .collect();
+    candidates.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
+    candidates.dedup_by(|a, b| a.name == b.name && a.emails == b.emails);
+    candidates
+}
+
+/// Names of known people after "with" or "invite", as typed, e.g. "jane" in "lunch with jane"
+///
+/// A word counts when it is a person's full name, or a first name or nickname of one, so
+/// names typed in lowercase are found too. The list ends at the first word that is neither.
+pub fn names_in(input: &str, entries: &[AddressBookEntry]) -> Vec<String> {
+    let full_names: HashSet<String> = entries.iter().map(|e| e.name.to_lowercase()).collect();
+    let first_names: HashSet<String> = entries
+        .iter()
+        .filter_map(|e| e.name.split_whitespace().next())
+        .flat_map(|first| nickname_variants(&first.to_lowercase()))
+        .collect();
+
+    let mut names = Vec::new();
+    for caps in INVITEES.captures_iter(input) {
+        let text = caps[1].replace(',', " , ");
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let mut i = 0;
+        while i < words.len() {
+            if words[i] == "," || words[i].eq_ignore_ascii_case("and") {
+                i += 1;
+                continue;
+            }
+            let known = (1..=3.min(words.len() - i)).rev().find(|&len| {
+                let phrase = words[i..i + len].join(" ");
+                let lower = phrase.to_lowercase();
+                !phrase.contains(',')
+                    && (full_names.contains(&lower) || (len == 1 && first_names.contains(&lower)))
+            });
+            let Some(len) = known else { break };
+            names.push(words[i..i + len].join(" "));
+            i += len;
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn person(id: &str, name: &str, email: &str) -> AddressBookEntry {
+        AddressBookEntry {
+            id: id.to_string(),
+            name: name.to_string(),
+            emails: vec![email.to_string()],
+            phones: Vec::new(),
+            birthday: None,
+            modified_at: "2025-04-01T09:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_sync_output() {
+        let output = "ids\tA:ABPerson,B:ABPerson\n\
+            A:ABPerson\tJane Doe\t2025-04-20T10:15:00\tjane@example.com,missing value\t+1 555 0100\t1604-03-14\n\
+            B:ABPerson\tBob Stone\t2025-04-21T08:00:00\t\t\t\n";
+        let parsed = parse_sync_output(output);
+        assert_eq!(parsed.ids.as_ref().map(HashSet::len), Some(2));
+        assert_eq!(parsed.changed.len(), 2);
+        assert_eq!(parsed.changed[0].emails, vec!["jane@example.com"]);
+        assert_eq!(parsed.changed[0].phones, vec!["+1 555 0100"]);
+        assert_eq!(parsed.changed[0].birthday.as_deref(), Some("1604-03-14"));
+        assert!(parsed.changed[1].emails.is_empty());
+        assert_eq!(parsed.changed[1].birthday, None);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut entries = vec![
+            person("1", "Jane Doe", "jane@example.com"),
+            person("2", "Old Friend", "old@example.com"),
+        ];
+        let mut moved = person("1", "Jane Doe", "jane@work.example.com");
+        moved.modified_at = "2025-04-20T10:15:00".to_string();
+        let output = SyncOutput {
+            ids: Some(HashSet::from(["1".to_string(), "3".to_string()])),
+            changed: vec![moved, person("3", "Bob Stone", "bob@example.com")],
+        };
+        let summary = merge(&mut entries, output);
+        assert_eq!(summary, SyncSummary { added: 1, updated: 1, removed: 1, total: 2 });
+        assert_eq!(entries[1].emails, vec!["jane@work.example.com"]);
+        assert_eq!(
+            last_modified(&entries),
+            NaiveDateTime::parse_from_str("2025-04-20T10:15:00", TIMESTAMP_FORMAT).ok()
+        );
+
+        // Without the id list nobody is removed
+        let summary = merge(&mut entries, SyncOutput::default());
+        assert_eq!(summary.removed, 0);
+        assert_eq!(summary.total, 2);
+    }
+
+    #[test]
+    fn test_sync_script() {
+        assert!(sync_script(None).unwrap().as_str().contains("repeat with p in people"));
+        let since = NaiveDateTime::parse_from_str("2025-04-20T10:15:00", TIMESTAMP_FORMAT).unwrap();
+        let script = sync_script(Some(since)).unwrap();
+        let script = script.as_str();
+        assert!(script.contains("set month of since to 4"));
+        assert!(script.contains("set time of since to 36900"));
+        assert!(script.contains("whose modification date >= since"));
+    }
+
+    #[test]
+    fn test_find_candidates() {
+        let entries = vec![
+            person("1", "Robert Smith", "rob@example.com"),
+            person("2", "Roberta Jones", "roberta@example.com"),
+            person("3", "Jane Doe", "jane@example.com"),
+        ];
+        let candidates = find_candidates(&entries, "Bob Smith");
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].emails, vec!["rob@example.com"]);
+        assert!(find_candidates(&entries, "Zed").is_empty());
+    }
+
+    #[test]
+    fn test_names_in() {
+        let entries = vec![
+            person("1", "Jane Doe", "jane@example.com"),
+            person("2", "Robert Smith", "rob@example.com"),
+        ];
+        assert_eq!(names_in("lunch with jane tomorrow at noon", &entries), vec!["jane"]);
+        assert_eq!(
+            names_in("sync with jane doe and bob, on friday", &entries),
+            vec!["jane doe", "bob"]
+        );
+        assert!(names_in("meeting with the team", &entries).is_empty());
+    }
+}
@@ -0,0 +1,384 @@
+//! Stable API for using DuckTape from other Rust programs.
+//
+// The rest of the crate is organized around the command line: handlers take strings, and
+// modules come and go as commands change. This module is the part other programs can depend
+// on. Events, reminders and notes are built with typed builders that check them when they are
+// built, and a `Client` creates them with the backends of config.toml, with local files, or
+// with backends of the caller's own:
+//
+//     let client = Client::local("/tmp/ducktape");
+//     let event = EventConfig::builder("Standup")
+//         .on(NaiveDate::from_ymd_opt(2025, 5, 1).unwrap())
+//         .at(NaiveTime::from_hms_opt(9, 0, 0).unwrap())
+//         .lasting(Duration::minutes(15))
+//         .calendar("Work")
+//         .build()?;
+//     client.create_event(&event).await?;
+//
+// Unlike the command line, the client does not record items in DuckTape's state, send
+// webhooks or ask before it changes anything. Everything here returns a `DucktapeError`, so
+// callers can match on what went wrong instead of reading messages.
+
+use crate::calendar::validate_event_config;
+use crate::notes::validate_note_config;
+use crate::todo::validate_todo_config;
+use crate::validation::ValidationError;
+use chrono::{NaiveDate, NaiveDateTime};
+use std::path::Path;
+
+pub use crate::backend::{CalendarBackend, NotesBackend, PlatformError, TodoBackend};
+pub use crate::calendar::{
+    EventAvailability, EventConfig, EventConfigBuilder, RecurrenceFrequency, RecurrencePattern,
+};
+pub use crate::config::{Config, MeetingService};
+pub use crate::error::{DucktapeError, ErrorKind, Result};
+pub use crate::notes::{NoteConfig, NoteItem};
+pub use crate::todo::{TodoConfig, TodoItem, TodoPriority};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// A reminder that passed validation
+#[derive(Debug, Clone)]
+pub struct Reminder {
+    title: String,
+    lists: Vec<String>,
+    notes: Option<String>,
+    remind_at: Option<String>,
+    due: Option<String>,
+    priority: Option<TodoPriority>,
+    recurrence: Option<RecurrencePattern>,
+}
+
+impl Reminder {
+    pub fn builder(title: &str) -> ReminderBuilder {
+        ReminderBuilder { reminder: Reminder::new(title) }
+    }
+
+    fn new(title: &str) -> Self {
+        Self {
+            title: title.trim().to_string(),
+            lists: Vec::new(),
+            notes: None,
+            remind_at: None,
+            due: None,
+            priority: None,
+            recurrence: None,
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// The reminder as the reminders backends take it
+    pub fn config(&self) -> TodoConfig<'_> {
+        TodoConfig {
+            title: &self.title,
+            notes: self.notes.clone(),
+            lists: self.lists.iter().map(String::as_str).collect(),
+            reminder_time: self.remind_at.as_deref(),
+            due_date: self.due.as_deref(),
+            priority: self.priority,
+            recurrence: self.recurrence.clone(),
+        }
+    }
+}
+
+/// Builds a `Reminder`; see `Reminder::builder`
+#[derive(Debug, Clone)]
+pub struct ReminderBuilder {
+    reminder: Reminder,
+}
+
+impl ReminderBuilder {
+    /// Add the reminder to `list` too; without lists the backend's default is used
+    pub fn list(mut self, list: &str) -> Self {
+        self.reminder.lists.push(list.to_string());
+        self
+    }
+
+    pub fn notes(mut self, notes: &str) -> Self {
+        self.reminder.notes = Some(notes.to_string());
+        self
+    }
+
+    /// Alert at `at`, which is also the due date unless one is set
+    pub fn remind_at(mut self, at: NaiveDateTime) -> Self {
+        self.reminder.remind_at = Some(at.format(DATE_TIME_FORMAT).to_string());
+        self
+    }
+
+    /// Due on `date`, all day
+    pub fn due(mut self, date: NaiveDate) -> Self {
+        self.reminder.due = Some(date.format(DATE_FORMAT).to_string());
+        self
+    }
+
+    /// Due at `at`
+    pub fn due_at(mut self, at: NaiveDateTime) -> Self {
+        self.reminder.due = Some(at.format(DATE_TIME_FORMAT).to_string());
+        self
+    }
+
+    pub fn priority(mut self, priority: TodoPriority) -> Self {
+        self.reminder.priority = Some(priority);
+        self
+    }
+
+    pub fn recurrence(mut self, recurrence: RecurrencePattern) -> Self {
+        self.reminder.recurrence = Some(recurrence);
+        self
+    }
+
+    /// The reminder, or why it cannot be created
+    pub fn build(self) -> Result<Reminder> {
+        if self.reminder.recurrence.is_some()
+            && self.reminder.due.is_none()
+            && self.reminder.remind_at.is_none()
+        {
+            return Err(ValidationError::new(
+                "due date",
+                format!("repeating reminder '{}' has none", self.reminder.title),
+            )
+            .into());
+        }
+        validate_todo_config(&self.reminder.config())?;
+        Ok(self.reminder)
+    }
+}
+
+/// A note that passed validation
+#[derive(Debug, Clone)]
+pub struct Note {
+    title: String,
+    content: String,
+    folder: Option<String>,
+    markdown: bool,
+}
+
+impl Note {
+    pub fn builder(title: &str) -> NoteBuilder {
+        NoteBuilder {
+            note: Note {
+                title: title.trim().to_string(),
+                content: String::new(),
+                folder: None,
+                markdown: false,
+            },
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// The note as the notes backends take it
+    pub fn config(&self) -> NoteConfig<'_> {
+        NoteConfig {
+            title: &self.title,
+            content: &self.content,
+            folder: self.folder.as_deref(),
+            markdown: self.markdown,
+        }
+    }
+}
+
+/// Builds a `Note`; see `Note::builder`
+#[derive(Debug, Clone)]
+pub struct NoteBuilder {
+    note: Note,
+}
+
+impl NoteBuilder {
+    pub fn content(mut self, content: &str) -> Self {
+        self.note.content = content.to_string();
+        self
+    }
+
+    /// Folder of the note; the backend's default otherwise
+    pub fn folder(mut self, folder: &str) -> Self {
+        self.note.folder = Some(folder.to_string());
+        self
+    }
+
+    /// Render the content as Markdown
+    pub fn markdown(mut self) -> Self {
+        self.note.markdown = true;
+        self
+    }
+
+    /// The note, or why it cannot be created
+    pub fn build(self) -> Result<Note> {
+        validate_note_config(&self.note.config())?;
+        Ok(self.note)
+    }
+}
+
+/// Creates and reads events, reminders and notes with a set of backends
+pub struct Client {
+    calendar: Box<dyn CalendarBackend>,
+    todos: Box<dyn TodoBackend>,
+    notes: Box<dyn NotesBackend>,
+}
+
+impl Client {
+    /// The backends chosen in config.toml, as the command line uses them
+    pub fn new() -> Result<Self> {
+        Self::from_config(&Config::load()?)
+    }
+
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Ok(Self {
+            calendar: crate::backend::calendar_for(config)?,
+            todos: crate::backend::todos_for(config)?,
+            notes: crate::backend::notes_for(config)?,
+        })
+    }
+
+    /// Files in `dir`, on any platform: .ics calendars, reminders and text notes
+    pub fn local(dir: impl AsRef<Path>) -> Self {
+        use crate::backend::{file_todos, ics_store, text_notes};
+        let dir = dir.as_ref();
+        Self {
+            calendar: Box::new(ics_store::IcsCalendar::new(dir.join("calendars"))),
+            todos: Box::new(file_todos::FileTodos::new(
+                dir.join("todos"),
+                file_todos::DEFAULT_LIST,
+            )),
+            notes: Box::new(text_notes::TextNotes::new(
+                dir.join("notes"),
+                text_notes::DEFAULT_FOLDER,
+            )),
+        }
+    }
+
+    /// Backends of your own, e.g. a calendar service DuckTape does not support
+    pub fn with_backends(
+        calendar: Box<dyn CalendarBackend>,
+        todos: Box<dyn TodoBackend>,
+        notes: Box<dyn NotesBackend>,
+    ) -> Self {
+        Self { calendar, todos, notes }
+    }
+
+    pub async fn calendars(&self) -> Result<Vec<String>> {
+        Ok(self.calendar.calendars().await?)
+    }
+
+    /// Create `event` in each of its calendars, returning the UIDs in the same order
+    ///
+    /// Events from `EventConfig::builder` are already checked; others are checked here.
+    pub async fn create_event(&self, event: &EventConfig) -> Result<Vec<String>> {
+        validate_event_config(event)?;
+        let calendars = if event.calendars.is_empty() {
+            vec![self.calendar.default_calendar().await?]
+        } else {
+            event.calendars.clone()
+        };
+        let mut uids = Vec::with_capacity(calendars.len());
+        for calendar in calendars {
+            let config = EventConfig { calendars: vec![calendar], ..event.clone() };
+            uids.push(self.calendar.create_event(config).await?);
+        }
+        Ok(uids)
+    }
+
+    pub async fn lists(&self) -> Result<Vec<String>> {
+        Ok(self.todos.lists().await?)
+    }
+
+    /// Reminders of one list, or of all lists
+    pub async fn reminders(&self, list: Option<&str>) -> Result<Vec<TodoItem>> {
+        Ok(self.todos.todos(list).await?)
+    }
+
+    pub async fn create_reminder(&self, reminder: &Reminder) -> Result<()> {
+        Ok(self.todos.create_todo(reminder.config()).await?)
+    }
+
+    pub async fn delete_reminder(&self, title: &str, list: Option<&str>) -> Result<()> {
+        Ok(self.todos.delete_todo(title, list).await?)
+    }
+
+    pub async fn notes(&self) -> Result<Vec<NoteItem>> {
+        Ok(self.notes.list_notes().await?)
+    }
+
+    pub async fn search_notes(&self, keyword: &str) -> Result<Vec<NoteItem>> {
+        Ok(self.notes.search_notes(keyword).await?)
+    }
+
+    pub async fn create_note(&self, note: &Note) -> Result<()> {
+        Ok(self.notes.create_note(note.config()).await?)
+    }
+
+    pub async fn delete_note(&self, title: &str, folder: Option<&str>) -> Result<()> {
+        Ok(self.notes.delete_note(title, folder).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveTime;
+    use tempfile::tempdir;
+
+    fn may_1() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 5, 1).unwrap()
+    }
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_reminder_and_note_builders() -> Result<()> {
+        let reminder = Reminder::builder("Pay rent")
+            .list("Home")
+            .remind_at(may_1().and_time(time(9, 30)))
+            .priority(TodoPriority::High)
+            .build()?;
+        let config = reminder.config();
+        assert_eq!(config.lists, ["Home"]);
+        assert_eq!(config.reminder_time, Some("2025-05-01 09:30"));
+        assert_eq!(Reminder::builder("  ").build().unwrap_err().kind(), ErrorKind::InvalidInput);
+        let repeating = Reminder::builder("Water plants")
+            .recurrence(RecurrencePattern::new(RecurrenceFrequency::Daily))
+            .build();
+        assert!(matches!(repeating, Err(DucktapeError::Validation(_))));
+
+        let note =
+            Note::builder("Kickoff").content("# Agenda").markdown().folder("Work").build()?;
+        assert_eq!(note.config().folder, Some("Work"));
+        assert!(Note::builder("").build().is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_local_client() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let client = Client::local(dir.path());
+
+        let event = EventConfig::builder("Standup")
+            .on(may_1())
+            .at(time(9, 0))
+            .calendar("Work")
+            .build()?;
+        let uids = client.create_event(&event).await?;
+        assert_eq!(uids.len(), 1);
+        assert_eq!(client.calendars().await?, ["Work"]);
+        let ics = std::fs::read_to_string(dir.path().join("calendars").join("Work.ics"))?;
+        assert!(ics.contains("SUMMARY:Standup"));
+
+        client.create_reminder(&Reminder::builder("Pay rent").build()?).await?;
+        let reminders = client.reminders(None).await?;
+        assert!(reminders.iter().any(|r| r.title == "Pay rent"));
+        client.delete_reminder("Pay rent", None).await?;
+        assert!(client.reminders(None).await?.is_empty());
+
+        client.create_note(&Note::builder("Kickoff").content("Agenda").build()?).await?;
+        assert_eq!(client.notes().await?[0].title, "Kickoff");
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+// Request deduplication for WebSocket clients
+//
+// Clients that retry aggressively (or users double-tapping send) can deliver the same
+// natural language request several times in quick succession. Each connection keeps a
+// short sliding window of recently seen requests so that repeats are answered without
+// running the command again.
+
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How long an identical request is treated as a duplicate
+pub const DEDUPE_WINDOW: Duration = Duration::from_secs(10);
+
+/// Upper bound on remembered requests per connection
+const MAX_TRACKED_REQUESTS: usize = 256;
+
+/// Sliding window of recently seen requests for one connection
+#[derive(Debug)]
+pub struct RequestDeduplicator {
+    window: Duration,
+    seen: VecDeque<(String, Instant)>,
+}
+
+impl Default for RequestDeduplicator {
+    fn default() -> Self {
+        Self::new(DEDUPE_WINDOW)
+    }
+}
+
+impl RequestDeduplicator {
+    pub fn new(window: Duration) -> Self {
+        Self { window, seen: VecDeque::new() }
+    }
+
+    /// Record a request, returning false if it repeats one seen within the window
+    ///
+    /// When the client supplies an idempotency key only the key is compared; otherwise
+    /// the normalized message content is hashed.
+    pub fn check(&mut self, content: &str, idempotency_key: Option<&str>, now: Instant) -> bool {
+        while let Some((_, seen_at)) = self.seen.front() {
+            if now.duration_since(*seen_at) < self.window {
+                break;
+            }
+            self.seen.pop_front();
+        }
+
+        let fingerprint = request_fingerprint(content, idempotency_key);
+        if self.seen.iter().any(|(f, _)| *f == fingerprint) {
+            return false;
+        }
+
+        if self.seen.len() >= MAX_TRACKED_REQUESTS {
+            self.seen.pop_front();
+        }
+        self.seen.push_back((fingerprint, now));
+        true
+    }
+}
+
+/// Fingerprint of a request: its idempotency key, or a hash of the normalized content
+fn request_fingerprint(content: &str, idempotency_key: Option<&str>) -> String {
+    if let Some(key) = idempotency_key.map(str::trim).filter(|k| !k.is_empty()) {
+        return format!("key:{}", key);
+    }
+    let normalized = content.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    let digest = Sha256::digest(normalized.as_bytes());
+    let hash: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("content:{}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_content_is_duplicate_within_window() {
+        let mut dedupe = RequestDeduplicator::new(Duration::from_secs(10));
+        let start = Instant::now();
+        assert!(dedupe.check("create an event tomorrow at 3pm", None, start));
+        assert!(!dedupe.check("  Create an event   tomorrow at 3pm ", None, start));
+        assert!(dedupe.check("create an event friday at 3pm", None, start));
+        assert!(dedupe.check(
+            "create an event tomorrow at 3pm",
+            None,
+            start + Duration::from_secs(11)
+        ));
+    }
+
+    #[test]
+    fn test_idempotency_key_takes_precedence() {
+        let mut dedupe = RequestDeduplicator::new(Duration::from_secs(10));
+        let now = Instant::now();
+        assert!(dedupe.check("create an event", Some("req-1"), now));
+        assert!(!dedupe.check("something else", Some("req-1"), now));
+        // Same content under a new key is a deliberate repeat
+        assert!(dedupe.check("create an event", Some("req-2"), now));
+    }
+}
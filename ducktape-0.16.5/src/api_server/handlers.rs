@@ -2,27 +2,107 @@
 //
 // This module contains handler functions for API endpoints.
 
-use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
 use chrono::Utc;
-use log::{debug, error};
-use std::fs;
 use std::sync::Arc;
+use tracing::{debug, error};
 
 use super::models::{
-    ApiResponse, ApiState, CalendarResponse, CreateEventRequest, CreateNoteRequest,
-    CreateTodoRequest, NoteResponse, StatusResponse, TodoResponse,
+    ApiResponse, ApiState, CalendarResponse, ContactGroupMembersRequest, ContactGroupResponse,
+    CreateContactGroupRequest, CreateEventRequest, CreateEventsRequest, CreateNoteRequest,
+    CreateTodoRequest, FeedQuery, ImportEventsRequest, NoteResponse, RenameContactGroupRequest,
+    StatusResponse, TodoResponse, UpcomingQuery, UsageQuery, UsageResponse,
 };
+use super::startup::{CHECK_CALENDAR_ACCESS, check_readiness};
+use crate::calendar::{EventAvailability, EventConfigBuilder};
+use crate::command_bus::{Command, dispatch};
+use crate::commands::{CreateEvent, CreateNote, CreateTodo, event_builder};
+use crate::contact_groups::ContactGroupError;
+use crate::error::{DucktapeError, ErrorKind};
+use crate::upcoming::UpcomingKind;
+
+/// HTTP status for a failed request
+pub(crate) fn error_status(e: &DucktapeError) -> StatusCode {
+    match e.kind() {
+        ErrorKind::InvalidInput => StatusCode::BAD_REQUEST,
+        ErrorKind::NotFound => StatusCode::NOT_FOUND,
+        ErrorKind::PermissionDenied => StatusCode::FORBIDDEN,
+        ErrorKind::Config | ErrorKind::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        ErrorKind::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
 
 /// Handle health check requests
 ///
 /// Returns 200 OK if the service is running
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses((status = 200, description = "The server is running", body = String))
+)]
 pub async fn health() -> impl IntoResponse {
     (StatusCode::OK, "OK")
 }
 
+/// Liveness probe
+///
+/// Answers as long as the server runs, without checking anything else
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "health",
+    responses((status = 200, description = "The server is running", body = String))
+)]
+pub async fn healthz() -> impl IntoResponse {
+    (StatusCode::OK, "ok")
+}
+
+/// Readiness probe
+///
+/// Checks that the configuration loads, the language model has an API key and Calendar answers
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "health",
+    responses(
+        (status = 200, description = "Ready to serve requests", body = StartupReport),
+        (status = 503, description = "A check failed", body = StartupReport)
+    )
+)]
+pub async fn readyz() -> impl IntoResponse {
+    let report = check_readiness().await;
+    let status = if report.ready() { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(report))
+}
+
+/// Prometheus metrics
+///
+/// Command counts, parser latency, AppleScript run time and WebSocket connections
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "health",
+    responses((status = 200, description = "Metrics in the Prometheus text format", body = String))
+)]
+pub async fn metrics() -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], crate::metrics::render())
+}
+
 /// Get API server status information
 ///
-/// Returns version, uptime, and status information
+/// Returns version, uptime, and status information along with the startup checks
+#[utoipa::path(
+    get,
+    path = "/status",
+    tag = "health",
+    responses((status = 200, description = "Server status and startup checks", body = StatusResponse))
+)]
 pub async fn status(State(state): State<Arc<ApiState>>) -> impl IntoResponse {
     // Calculate uptime from start time
     let now = Utc::now();
@@ -35,19 +115,185 @@ pub async fn status(State(state): State<Arc<ApiState>>) -> impl IntoResponse {
     let uptime =
         format!("{} days, {} hours, {} minutes, {} seconds", days, hours, minutes, seconds);
 
+    let report = &state.startup_report;
     let response = StatusResponse {
         version: state.version.clone(),
         uptime,
-        status: "online".to_string(),
-        calendars_available: true,
+        status: if report.ready() { "online" } else { "degraded" }.to_string(),
+        calendars_available: report.passed(CHECK_CALENDAR_ACCESS),
+        ready: report.ready(),
+        startup: report.clone(),
     };
 
     (StatusCode::OK, Json(response))
 }
 
+/// Language model token usage and estimated cost for a month
+///
+/// Takes an optional `month` query parameter (YYYY-MM), defaulting to the current month
+#[utoipa::path(
+    get,
+    path = "/usage",
+    tag = "usage",
+    params(UsageQuery),
+    responses(
+        (status = 200, description = "Usage of the month", body = UsageResponse),
+        (status = 400, description = "Invalid month", body = ApiResponse)
+    )
+)]
+pub async fn usage(
+    State(state): State<Arc<ApiState>>,
+    Query(query): Query<UsageQuery>,
+) -> impl IntoResponse {
+    let month = query.month.unwrap_or_else(|| chrono::Local::now().format("%Y-%m").to_string());
+    if chrono::NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").is_err() {
+        let response = ApiResponse {
+            success: false,
+            message: format!("Invalid month: {} (expected YYYY-MM)", month),
+            data: None,
+        };
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+
+    match crate::usage::UsageLedger::load() {
+        Ok(ledger) => {
+            let total_cost_usd = ledger.month_cost(&month);
+            let monthly_budget_usd = state.shared.config().await.usage.monthly_budget_usd;
+            let response = UsageResponse {
+                records: ledger.month(&month).into_iter().cloned().collect(),
+                month,
+                total_cost_usd,
+                monthly_budget_usd,
+                over_budget: monthly_budget_usd.is_some_and(|budget| total_cost_usd >= budget),
+            };
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to load usage records: {}", e);
+            let response = ApiResponse {
+                success: false,
+                message: format!("Failed to load usage records: {}", e),
+                data: None,
+            };
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+/// The upcoming items of `kind` as a response
+async fn upcoming_response(
+    query: UpcomingQuery,
+    kind: Option<UpcomingKind>,
+) -> axum::response::Response {
+    let within = match crate::upcoming::parse_within(query.within.as_deref().unwrap_or("24h")) {
+        Ok(within) => within,
+        Err(e) => {
+            let response = ApiResponse { success: false, message: e.to_string(), data: None };
+            return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+        }
+    };
+    match crate::upcoming::upcoming(within, kind, query.refresh.unwrap_or(false)).await {
+        Ok(feed) => (StatusCode::OK, [(header::CACHE_CONTROL, "private, max-age=60")], Json(feed))
+            .into_response(),
+        Err(e) => {
+            error!("Failed to read upcoming items: {}", e);
+            let response = ApiResponse {
+                success: false,
+                message: format!("Failed to read upcoming items: {}", e),
+                data: None,
+            };
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+/// Reminders due soon
+///
+/// Open reminders due within the time span, and those already overdue, soonest first.
+#[utoipa::path(
+    get,
+    path = "/api/reminders/upcoming",
+    tag = "todo",
+    params(UpcomingQuery),
+    responses(
+        (status = 200, description = "Reminders due soon", body = UpcomingFeed),
+        (status = 400, description = "Invalid time span", body = ApiResponse)
+    )
+)]
+pub async fn upcoming_reminders(Query(query): Query<UpcomingQuery>) -> impl IntoResponse {
+    upcoming_response(query, Some(UpcomingKind::Reminder)).await
+}
+
+/// Events and reminders coming up, for widgets
+///
+/// Events going on or starting within the time span and reminders due in it, merged and
+/// sorted soonest first. Made to be polled: the apps are read at most once a minute.
+#[utoipa::path(
+    get,
+    path = "/api/upcoming",
+    tag = "calendar",
+    params(UpcomingQuery),
+    responses(
+        (status = 200, description = "Events and reminders coming up", body = UpcomingFeed),
+        (status = 400, description = "Invalid time span", body = ApiResponse)
+    )
+)]
+pub async fn upcoming(Query(query): Query<UpcomingQuery>) -> impl IntoResponse {
+    upcoming_response(query, None).await
+}
+
+/// Serve an iCalendar feed of created events
+///
+/// The `token` query parameter selects the feed; without a valid one nothing is served.
+#[utoipa::path(
+    get,
+    path = "/calendar.ics",
+    tag = "calendar",
+    params(FeedQuery),
+    responses(
+        (status = 200, description = "iCalendar feed", body = String, content_type = "text/calendar"),
+        (status = 401, description = "Unknown or missing feed token")
+    )
+)]
+pub async fn calendar_feed(
+    State(state): State<Arc<ApiState>>,
+    Query(query): Query<FeedQuery>,
+) -> impl IntoResponse {
+    let feeds = state.shared.config().await.feeds;
+    let token = query.token.unwrap_or_default();
+    let Some(feed) = crate::calendar::find_feed(&feeds, &token) else {
+        return (StatusCode::UNAUTHORIZED, "Unknown or missing feed token").into_response();
+    };
+
+    match crate::calendar::build_feed(feed) {
+        Ok(ics) => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, "text/calendar; charset=utf-8"),
+                (header::CACHE_CONTROL, "private, max-age=300"),
+            ],
+            ics,
+        )
+            .into_response(),
+        Err(e) => {
+            error!("Failed to build feed {}: {}", feed.name, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to build the feed").into_response()
+        }
+    }
+}
+
 /// List available calendars
 ///
 /// Returns a list of calendars from macOS Calendar.app
+#[utoipa::path(
+    get,
+    path = "/calendars",
+    tag = "calendar",
+    responses(
+        (status = 200, description = "Available calendars", body = CalendarResponse),
+        (status = 503, description = "Calendar is not available", body = CalendarResponse)
+    )
+)]
 pub async fn list_calendars() -> impl IntoResponse {
     match crate::calendar::get_available_calendars().await {
         Ok(calendars) => {
@@ -59,54 +305,70 @@ pub async fn list_calendars() -> impl IntoResponse {
             (StatusCode::OK, Json(response))
         }
         Err(e) => {
+            let e = DucktapeError::from(e);
             error!("Failed to list calendars: {}", e);
             let response = CalendarResponse {
                 success: false,
                 message: format!("Failed to list calendars: {}", e),
                 calendars: None,
             };
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(response))
+            (error_status(&e), Json(response))
         }
     }
 }
 
+/// The event a create request describes, once it is checked
+fn checked_event(payload: &CreateEventRequest) -> anyhow::Result<CreateEvent> {
+    let availability = match payload.availability.as_deref().map(EventAvailability::from_str) {
+        Some(Ok(availability)) => Some(availability),
+        Some(Err(e)) => {
+            debug!("Ignoring availability: {}", e);
+            None
+        }
+        None => None,
+    };
+    let config = event_builder(
+        &payload.title,
+        &payload.date,
+        &payload.start_time,
+        payload.end_time.as_deref(),
+    )?
+    .calendars(payload.calendars.iter().flatten())
+    .maybe(payload.location.as_deref(), EventConfigBuilder::location)
+    .maybe(payload.url.as_deref(), EventConfigBuilder::url)
+    .maybe(availability, EventConfigBuilder::availability)
+    .maybe(payload.description.as_deref(), EventConfigBuilder::description)
+    .attendees(payload.emails.iter().flatten())
+    .zoom_meeting(payload.create_zoom_meeting == Some(true))
+    .build()?;
+
+    let mut event = CreateEvent::new(config);
+    event.groups = payload.groups.clone().unwrap_or_default();
+    Ok(event)
+}
+
 /// Create a new calendar event
 ///
 /// Creates an event in macOS Calendar.app
+#[utoipa::path(
+    post,
+    path = "/calendar/event",
+    tag = "calendar",
+    request_body = CreateEventRequest,
+    responses(
+        (status = 201, description = "Event created", body = ApiResponse),
+        (status = 400, description = "Invalid event", body = ApiResponse)
+    )
+)]
 pub async fn create_calendar_event(Json(payload): Json<CreateEventRequest>) -> impl IntoResponse {
     debug!("Create event request: {:?}", payload);
 
-    // Create an EventConfig from the request
-    let mut event_config =
-        crate::calendar::EventConfig::new(&payload.title, &payload.date, &payload.start_time);
-
-    // Apply optional fields if present
-    if let Some(end_time) = &payload.end_time {
-        event_config.end_time = Some(end_time.clone());
-    }
-
-    if let Some(calendars) = &payload.calendars {
-        event_config.calendars = calendars.clone();
-    }
-
-    if let Some(location) = &payload.location {
-        event_config.location = Some(location.clone());
-    }
-
-    if let Some(description) = &payload.description {
-        event_config.description = Some(description.clone());
-    }
-
-    if let Some(emails) = &payload.emails {
-        event_config.emails = emails.clone();
-    }
-
-    if let Some(true) = payload.create_zoom_meeting {
-        event_config.create_zoom_meeting = true;
-    }
-
     // Create the calendar event
-    match crate::calendar::create_event(event_config).await {
+    let result = match checked_event(&payload) {
+        Ok(event) => dispatch(Command::CreateEvent(event), None).await,
+        Err(e) => Err(e),
+    };
+    match result {
         Ok(_) => {
             let response = ApiResponse {
                 success: true,
@@ -116,13 +378,181 @@ pub async fn create_calendar_event(Json(payload): Json<CreateEventRequest>) -> i
             (StatusCode::CREATED, Json(response))
         }
         Err(e) => {
+            let e = DucktapeError::from(e);
             error!("Failed to create event: {}", e);
             let response = ApiResponse {
                 success: false,
                 message: format!("Failed to create event: {}", e),
                 data: None,
             };
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(response))
+            (error_status(&e), Json(response))
+        }
+    }
+}
+
+/// Response to a request that started a task: 202 with the task ID and where to follow it
+fn task_started(id: String) -> axum::response::Response {
+    let response = ApiResponse {
+        success: true,
+        message: format!("Task {} started", id),
+        data: Some(serde_json::json!({
+            "task_id": id,
+            "status_url": format!("/api/tasks/{}", id),
+        })),
+    };
+    (StatusCode::ACCEPTED, Json(response)).into_response()
+}
+
+fn bad_request(message: String) -> axum::response::Response {
+    let response = ApiResponse { success: false, message, data: None };
+    (StatusCode::BAD_REQUEST, Json(response)).into_response()
+}
+
+/// Create several calendar events
+///
+/// Runs as a task; the response carries its ID
+#[utoipa::path(
+    post,
+    path = "/calendar/events",
+    tag = "calendar",
+    request_body = CreateEventsRequest,
+    responses(
+        (status = 202, description = "Task started; `data` holds `task_id` and `status_url`", body = ApiResponse),
+        (status = 400, description = "No events to create, or an invalid event", body = ApiResponse)
+    )
+)]
+pub async fn create_calendar_events(Json(payload): Json<CreateEventsRequest>) -> impl IntoResponse {
+    debug!("Create {} events request", payload.events.len());
+    if payload.events.is_empty() {
+        return bad_request("No events to create".to_string());
+    }
+    // Every event is checked before any is created
+    let mut events = Vec::new();
+    for (index, payload) in payload.events.iter().enumerate() {
+        match checked_event(payload) {
+            Ok(event) => events.push(event),
+            Err(e) => return bad_request(format!("Event {}: {}", index + 1, e)),
+        }
+    }
+    task_started(crate::tasks::spawn(
+        "calendar create",
+        crate::webhooks::with_source(
+            "api",
+            crate::audit::with_actor(crate::audit::current_actor(), create_events(events)),
+        ),
+    ))
+}
+
+/// Create events one after another, reporting progress; fails only when none was created
+async fn create_events(events: Vec<CreateEvent>) -> anyhow::Result<String> {
+    let total = events.len();
+    let mut failures = Vec::new();
+    for (index, event) in events.into_iter().enumerate() {
+        crate::tasks::progress(index, total, &event.config.title);
+        let title = event.config.title.clone();
+        if let Err(e) = dispatch(Command::CreateEvent(event), None).await {
+            error!("Failed to create event '{}': {}", title, e);
+            failures.push(format!("{}: {}", title, e));
+        }
+    }
+    crate::tasks::progress(total, total, "");
+
+    let created = total - failures.len();
+    if created == 0 {
+        return Err(anyhow::anyhow!("No events were created: {}", failures.join("; ")));
+    }
+    let mut summary = format!("Created {} of {} events", created, total);
+    for failure in failures {
+        summary.push_str(&format!("\n  failed: {}", failure));
+    }
+    Ok(summary)
+}
+
+/// Import the events of an .ics or CSV file
+///
+/// The file is checked at once; the import runs as a task and the response carries its ID
+#[utoipa::path(
+    post,
+    path = "/calendar/import",
+    tag = "calendar",
+    request_body = ImportEventsRequest,
+    responses(
+        (status = 202, description = "Task started; `data` holds `task_id` and `status_url`", body = ApiResponse),
+        (status = 400, description = "Unreadable file or options", body = ApiResponse)
+    )
+)]
+pub async fn import_calendar(
+    State(state): State<Arc<ApiState>>,
+    Json(payload): Json<ImportEventsRequest>,
+) -> impl IntoResponse {
+    let format = payload.format.as_deref().unwrap_or("ics").to_lowercase();
+    debug!("Import request: {} bytes of {}", payload.content.len(), format);
+    let on_duplicate = match payload
+        .on_duplicate
+        .as_deref()
+        .map(str::parse::<crate::calendar::OnDuplicate>)
+        .transpose()
+    {
+        Ok(on_duplicate) => on_duplicate.unwrap_or_default(),
+        Err(e) => return bad_request(e.to_string()),
+    };
+    let events = match format.as_str() {
+        "ics" => crate::calendar::parse_ics_events(&payload.content),
+        "csv" => crate::calendar::load_column_map(payload.map.as_deref(), None)
+            .and_then(|map| crate::calendar::parse_csv_events(&payload.content, &map)),
+        _ => return bad_request(format!("Unsupported format: {}. Use ics or csv", format)),
+    };
+    let events = match events {
+        Ok(events) => events,
+        Err(e) => return bad_request(e.to_string()),
+    };
+
+    let shared = state.shared.clone();
+    let calendar = payload.calendar;
+    // Not a `Command`, so recorded in the audit log here
+    let description = format!("calendar import --format {} ({} events)", format, events.len());
+    let id = crate::logging::correlation_id();
+    let import = async move {
+        let import = crate::calendar::import_events(events, calendar, on_duplicate);
+        let summary = shared.exclusive(import).await?;
+        anyhow::Ok(summary.report().trim_end().to_string())
+    };
+    let audited = async move {
+        let result = import.await;
+        let outcome = if result.is_ok() { "ok" } else { "error" };
+        crate::audit::record(&id, &description, outcome, result.as_ref().err());
+        result
+    };
+    task_started(crate::tasks::spawn(
+        "calendar import",
+        crate::webhooks::with_source(
+            "api",
+            crate::audit::with_actor(crate::audit::current_actor(), audited),
+        ),
+    ))
+}
+
+/// Progress of a task started by an earlier request
+#[utoipa::path(
+    get,
+    path = "/api/tasks/{id}",
+    tag = "tasks",
+    params(("id" = String, Path, description = "Task ID")),
+    responses(
+        (status = 200, description = "The task as last reported", body = Task),
+        (status = 404, description = "No such task, or it finished over an hour ago", body = ApiResponse)
+    )
+)]
+pub async fn get_task(Path(id): Path<String>) -> impl IntoResponse {
+    match crate::tasks::get(&id) {
+        Some(task) => (StatusCode::OK, Json(task)).into_response(),
+        None => {
+            let response = ApiResponse {
+                success: false,
+                message: format!("Task {} not found", id),
+                data: None,
+            };
+            (StatusCode::NOT_FOUND, Json(response)).into_response()
         }
     }
 }
@@ -130,63 +560,272 @@ pub async fn create_calendar_event(Json(payload): Json<CreateEventRequest>) -> i
 /// Create a new todo item
 ///
 /// Creates a todo in Reminders.app
+#[utoipa::path(
+    post,
+    path = "/todo",
+    tag = "todo",
+    request_body = CreateTodoRequest,
+    responses(
+        (status = 201, description = "Todo created", body = TodoResponse),
+        (status = 400, description = "Invalid todo", body = TodoResponse)
+    )
+)]
 pub async fn create_todo(Json(payload): Json<CreateTodoRequest>) -> impl IntoResponse {
     debug!("Create todo request: {:?}", payload);
 
-    // This is a stub - would connect to actual todo module
-    let response = TodoResponse {
-        success: true,
-        message: format!("Todo '{}' created successfully", payload.title),
+    let todo = CreateTodo {
+        title: payload.title.clone(),
+        lists: payload.lists.clone().unwrap_or_default(),
+        reminder_time: payload.reminder_time.clone(),
+        notes: payload.notes.clone(),
+        ..Default::default()
     };
 
-    (StatusCode::CREATED, Json(response))
+    let result = match todo.checked() {
+        Ok(todo) => dispatch(Command::CreateTodo(todo), None).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(outcome) => {
+            let message = outcome.map(|outcome| outcome.message()).unwrap_or_default();
+            let response = TodoResponse { success: true, message };
+            (StatusCode::CREATED, Json(response))
+        }
+        Err(e) => {
+            let e = DucktapeError::from(e);
+            error!("Failed to create todo: {}", e);
+            let response =
+                TodoResponse { success: false, message: format!("Failed to create todo: {}", e) };
+            (error_status(&e), Json(response))
+        }
+    }
 }
 
 /// Create a new note
 ///
 /// Creates a note in Notes.app
+#[utoipa::path(
+    post,
+    path = "/note",
+    tag = "notes",
+    request_body = CreateNoteRequest,
+    responses(
+        (status = 201, description = "Note created", body = NoteResponse),
+        (status = 400, description = "Invalid note", body = NoteResponse)
+    )
+)]
 pub async fn create_note(Json(payload): Json<CreateNoteRequest>) -> impl IntoResponse {
     debug!("Create note request: {:?}", payload);
 
-    // This is a stub - would connect to actual notes module
-    let response = NoteResponse {
-        success: true,
-        message: format!("Note '{}' created successfully", payload.title),
+    let note = CreateNote {
+        title: payload.title.clone(),
+        content: payload.content.clone(),
+        folder: payload.folder.clone(),
+        ..Default::default()
     };
 
-    (StatusCode::CREATED, Json(response))
-}
-
-/// Serve the OpenAPI documentation
-///
-/// Returns the OpenAPI JSON specification
-pub async fn api_docs() -> impl IntoResponse {
-    // Read the OpenAPI spec from the api_docs.json file
-    match fs::read_to_string("src/api_docs.json") {
-        Ok(content) => {
-            match serde_json::from_str::<serde_json::Value>(&content) {
-                Ok(json) => (StatusCode::OK, Json(json)),
-                Err(_) => {
-                    let error = ApiResponse {
-                        success: false,
-                        message: "Invalid API documentation format".to_string(),
-                        data: None,
-                    };
-                    // Convert to serde_json::Value to match the other branch
-                    let json_value = serde_json::to_value(error).unwrap_or_default();
-                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json_value))
-                }
-            }
-        }
-        Err(_) => {
-            let error = ApiResponse {
-                success: false,
-                message: "API documentation not found".to_string(),
-                data: None,
+    let result = match note.checked() {
+        Ok(note) => dispatch(Command::CreateNote(note), None).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(outcome) => {
+            let message = outcome.map(|outcome| outcome.message()).unwrap_or_default();
+            let response = NoteResponse { success: true, message };
+            (StatusCode::CREATED, Json(response))
+        }
+        Err(e) => {
+            let e = DucktapeError::from(e);
+            error!("Failed to create note: {}", e);
+            let response =
+                NoteResponse { success: false, message: format!("Failed to create note: {}", e) };
+            (error_status(&e), Json(response))
+        }
+    }
+}
+
+/// Build a contact group response, mapping group errors to HTTP status codes
+fn contact_group_result(
+    result: anyhow::Result<String>,
+    success_status: StatusCode,
+) -> (StatusCode, Json<ContactGroupResponse>) {
+    match result {
+        Ok(message) => (
+            success_status,
+            Json(ContactGroupResponse { success: true, message, groups: None, members: None }),
+        ),
+        Err(e) => {
+            let status = match e.downcast_ref::<ContactGroupError>() {
+                Some(ContactGroupError::NotFound(_)) => StatusCode::NOT_FOUND,
+                Some(ContactGroupError::AlreadyExists(_)) => StatusCode::CONFLICT,
+                Some(_) => StatusCode::BAD_REQUEST,
+                None => StatusCode::INTERNAL_SERVER_ERROR,
             };
-            // Convert to serde_json::Value to match the other branch
-            let json_value = serde_json::to_value(error).unwrap_or_default();
-            (StatusCode::NOT_FOUND, Json(json_value))
+            error!("Contact group request failed: {}", e);
+            (
+                status,
+                Json(ContactGroupResponse {
+                    success: false,
+                    message: e.to_string(),
+                    groups: None,
+                    members: None,
+                }),
+            )
         }
     }
 }
+
+/// List contact groups
+///
+/// Returns DuckTape and Contacts.app group names
+#[utoipa::path(
+    get,
+    path = "/contacts/groups",
+    tag = "contacts",
+    responses((status = 200, description = "Group names", body = ContactGroupResponse))
+)]
+pub async fn list_contact_groups() -> impl IntoResponse {
+    match crate::contact_groups::list_all_groups().await {
+        Ok(groups) => (
+            StatusCode::OK,
+            Json(ContactGroupResponse {
+                success: true,
+                message: "Contact groups retrieved successfully".to_string(),
+                groups: Some(groups),
+                members: None,
+            }),
+        ),
+        Err(e) => contact_group_result(Err(e), StatusCode::OK),
+    }
+}
+
+/// Show the members of a contact group
+#[utoipa::path(
+    get,
+    path = "/contacts/groups/{name}",
+    tag = "contacts",
+    params(("name" = String, Path, description = "Group name")),
+    responses(
+        (status = 200, description = "Group members", body = ContactGroupResponse),
+        (status = 404, description = "No such group", body = ContactGroupResponse)
+    )
+)]
+pub async fn get_contact_group(Path(name): Path<String>) -> impl IntoResponse {
+    match crate::contact_groups::resolve_group_emails(&name).await {
+        Ok(Some(members)) => (
+            StatusCode::OK,
+            Json(ContactGroupResponse {
+                success: true,
+                message: format!("Contact group '{}' retrieved successfully", name),
+                groups: None,
+                members: Some(members),
+            }),
+        ),
+        Ok(None) => contact_group_result(
+            Err(anyhow::anyhow!(ContactGroupError::NotFound(name))),
+            StatusCode::OK,
+        ),
+        Err(e) => contact_group_result(Err(e), StatusCode::OK),
+    }
+}
+
+/// Create a contact group
+#[utoipa::path(
+    post,
+    path = "/contacts/groups",
+    tag = "contacts",
+    request_body = CreateContactGroupRequest,
+    responses(
+        (status = 201, description = "Group created", body = ContactGroupResponse),
+        (status = 409, description = "The group already exists", body = ContactGroupResponse)
+    )
+)]
+pub async fn create_contact_group(
+    Json(payload): Json<CreateContactGroupRequest>,
+) -> impl IntoResponse {
+    debug!("Create contact group request: {:?}", payload);
+    let result = crate::contact_groups::create_group(&payload.name, &payload.emails)
+        .map(|_| format!("Contact group '{}' created successfully", payload.name));
+    contact_group_result(result, StatusCode::CREATED)
+}
+
+/// Add members to a contact group
+#[utoipa::path(
+    post,
+    path = "/contacts/groups/{name}/members",
+    tag = "contacts",
+    params(("name" = String, Path, description = "Group name")),
+    request_body = ContactGroupMembersRequest,
+    responses(
+        (status = 200, description = "Members added", body = ContactGroupResponse),
+        (status = 404, description = "No such group", body = ContactGroupResponse)
+    )
+)]
+pub async fn add_contact_group_members(
+    Path(name): Path<String>,
+    Json(payload): Json<ContactGroupMembersRequest>,
+) -> impl IntoResponse {
+    let result = crate::contact_groups::add_members(&name, &payload.emails)
+        .map(|count| format!("Added {} member(s) to contact group '{}'", count, name));
+    contact_group_result(result, StatusCode::OK)
+}
+
+/// Remove members from a contact group
+#[utoipa::path(
+    delete,
+    path = "/contacts/groups/{name}/members",
+    tag = "contacts",
+    params(("name" = String, Path, description = "Group name")),
+    request_body = ContactGroupMembersRequest,
+    responses(
+        (status = 200, description = "Members removed", body = ContactGroupResponse),
+        (status = 404, description = "No such group", body = ContactGroupResponse)
+    )
+)]
+pub async fn remove_contact_group_members(
+    Path(name): Path<String>,
+    Json(payload): Json<ContactGroupMembersRequest>,
+) -> impl IntoResponse {
+    let result = crate::contact_groups::remove_members(&name, &payload.emails)
+        .map(|count| format!("Removed {} member(s) from contact group '{}'", count, name));
+    contact_group_result(result, StatusCode::OK)
+}
+
+/// Rename a contact group
+#[utoipa::path(
+    put,
+    path = "/contacts/groups/{name}",
+    tag = "contacts",
+    params(("name" = String, Path, description = "Group name")),
+    request_body = RenameContactGroupRequest,
+    responses(
+        (status = 200, description = "Group renamed", body = ContactGroupResponse),
+        (status = 404, description = "No such group", body = ContactGroupResponse),
+        (status = 409, description = "A group with the new name exists", body = ContactGroupResponse)
+    )
+)]
+pub async fn rename_contact_group(
+    Path(name): Path<String>,
+    Json(payload): Json<RenameContactGroupRequest>,
+) -> impl IntoResponse {
+    let result = crate::contact_groups::rename_group(&name, &payload.new_name)
+        .map(|_| format!("Renamed contact group '{}' to '{}'", name, payload.new_name));
+    contact_group_result(result, StatusCode::OK)
+}
+
+/// Delete a contact group
+#[utoipa::path(
+    delete,
+    path = "/contacts/groups/{name}",
+    tag = "contacts",
+    params(("name" = String, Path, description = "Group name")),
+    responses(
+        (status = 200, description = "Group deleted", body = ContactGroupResponse),
+        (status = 404, description = "No such group", body = ContactGroupResponse)
+    )
+)]
+pub async fn delete_contact_group(Path(name): Path<String>) -> impl IntoResponse {
+    let result = crate::contact_groups::delete_group(&name)
+        .map(|_| format!("Deleted contact group '{}'", name));
+    contact_group_result(result, StatusCode::OK)
+}
@@ -0,0 +1,306 @@
+// Request limits
+//
+// Rate limits per client address and per API key, so the server can be reached from more than
+// localhost. The limits are read from `api` in the configuration when the server starts.
+//
+// Browsers let any web page send requests to localhost, so changes and WebSocket connections
+// coming from a web page are refused unless its origin is in `api.allowed_origins`. Clients
+// other than browsers send no `Origin` header and are not affected.
+
+use axum::{
+    Json,
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::config::{ApiConfig, WebSocketLimitsConfig};
+
+use super::models::{ApiResponse, ApiState};
+
+/// Clients tracked before idle ones are forgotten
+const PRUNE_AT: usize = 1024;
+
+/// Probes and metrics, which monitoring requests regularly
+const UNLIMITED_PATHS: &[&str] = &["/health", "/healthz", "/readyz", "/metrics"];
+
+/// Token bucket rate limiter, one bucket per client
+pub struct RateLimiter {
+    /// Tokens added per second
+    rate: f64,
+    /// Tokens a bucket holds at most
+    burst: f64,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// Allow `per_minute` requests a minute, and up to `burst` at once
+    pub fn new(per_minute: u32, burst: u32) -> Self {
+        Self {
+            rate: f64::from(per_minute.max(1)) / 60.0,
+            burst: f64::from(burst.max(1)),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a request of `client` at `now`; when over the limit, how long until it is allowed
+    pub fn check(&self, client: &str, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= PRUNE_AT {
+            self.prune(&mut buckets, now);
+        }
+        let bucket = buckets
+            .entry(client.to_string())
+            .or_insert(Bucket { tokens: self.burst, updated: now });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate))
+        }
+    }
+
+    /// Forget clients whose buckets have filled up again; they start out full anyway
+    fn prune(&self, buckets: &mut HashMap<String, Bucket>, now: Instant) {
+        buckets.retain(|_, bucket| {
+            let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+            bucket.tokens + elapsed * self.rate < self.burst
+        });
+    }
+}
+
+/// Limits applied by the server, built from the configuration at startup
+pub struct Limits {
+    /// Largest request body, in bytes
+    pub max_body_bytes: usize,
+    pub trust_proxy: bool,
+    /// Origins of the web pages allowed to make changes, without a trailing slash
+    pub allowed_origins: Vec<String>,
+    per_ip: Option<RateLimiter>,
+    per_key: Option<RateLimiter>,
+    pub websocket: WebSocketLimitsConfig,
+}
+
+impl Limits {
+    pub fn new(config: &ApiConfig) -> Self {
+        let rate_limit = &config.rate_limit;
+        let limiter =
+            |per_minute| rate_limit.enabled.then(|| RateLimiter::new(per_minute, rate_limit.burst));
+        Self {
+            max_body_bytes: config.max_body_bytes,
+            trust_proxy: config.trust_proxy,
+            allowed_origins: config
+                .allowed_origins
+                .iter()
+                .map(|origin| origin.trim().trim_end_matches('/').to_string())
+                .collect(),
+            per_ip: limiter(rate_limit.per_ip_per_minute),
+            per_key: limiter(rate_limit.per_key_per_minute),
+            websocket: config.websocket.clone(),
+        }
+    }
+
+    /// Take a request from `ip`, made with `key` if any
+    pub fn check(&self, ip: &str, key: Option<&str>, now: Instant) -> Result<(), Duration> {
+        if let Some(limiter) = &self.per_ip {
+            limiter.check(ip, now)?;
+        }
+        if let (Some(limiter), Some(key)) = (&self.per_key, key) {
+            limiter.check(key, now)?;
+        }
+        Ok(())
+    }
+
+    /// Whether a request with these headers may come from the web page that sent it
+    pub fn allows_origin(&self, headers: &HeaderMap) -> bool {
+        match headers.get(header::ORIGIN).map(|value| value.to_str()) {
+            None => true,
+            Some(Ok(origin)) => {
+                self.allowed_origins.iter().any(|allowed| allowed.eq_ignore_ascii_case(origin))
+            }
+            Some(Err(_)) => false,
+        }
+    }
+}
+
+/// Address of the client, from `X-Forwarded-For` when behind a trusted proxy
+pub(super) fn client_ip(
+    headers: &HeaderMap,
+    peer: Option<SocketAddr>,
+    trust_proxy: bool,
+) -> String {
+    let forwarded = headers
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .map(str::trim)
+        .filter(|ip| !ip.is_empty());
+    match (forwarded, peer) {
+        (Some(ip), _) if trust_proxy => ip.to_string(),
+        (_, Some(peer)) => peer.ip().to_string(),
+        _ => "unknown".to_string(),
+    }
+}
+
+/// API key of the request, from `X-Api-Key` or a bearer token
+pub(super) fn api_key(headers: &HeaderMap) -> Option<&str> {
+    if let Some(key) = headers.get("x-api-key").and_then(|value| value.to_str().ok()) {
+        return Some(key.trim());
+    }
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+/// Answer changes and WebSocket connections from other web pages with 403 Forbidden
+pub async fn check_origin(
+    State(state): State<Arc<ApiState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let reads = matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+    let upgrade = request.headers().contains_key(header::UPGRADE);
+    if (reads && !upgrade) || state.limits.allows_origin(request.headers()) {
+        return next.run(request).await;
+    }
+    let origin = request.headers().get(header::ORIGIN).and_then(|value| value.to_str().ok());
+    warn!("Refused {} {} from {:?}", request.method(), request.uri().path(), origin);
+    let response = ApiResponse {
+        success: false,
+        message: "Origin not allowed; add it to api.allowed_origins".to_string(),
+        data: None,
+    };
+    (StatusCode::FORBIDDEN, Json(response)).into_response()
+}
+
+/// Answer requests over the rate limit with 429 Too Many Requests
+pub async fn rate_limit(
+    State(state): State<Arc<ApiState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if UNLIMITED_PATHS.contains(&request.uri().path()) {
+        return next.run(request).await;
+    }
+    let peer = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0);
+    let ip = client_ip(request.headers(), peer, state.limits.trust_proxy);
+    let key = api_key(request.headers());
+    match state.limits.check(&ip, key, Instant::now()) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => {
+            let seconds = retry_after.as_secs_f64().ceil() as u64;
+            warn!("Rate limited {} {} from {}", request.method(), request.uri().path(), ip);
+            let response = ApiResponse {
+                success: false,
+                message: format!("Too many requests, try again in {} seconds", seconds),
+                data: None,
+            };
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, seconds.to_string())],
+                Json(response),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_burst_then_rate() {
+        let limiter = RateLimiter::new(60, 3);
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert!(limiter.check("a", start).is_ok());
+        }
+        let retry = limiter.check("a", start).unwrap_err();
+        assert_eq!(retry.as_secs_f64().ceil() as u64, 1);
+        // Other clients have their own bucket
+        assert!(limiter.check("b", start).is_ok());
+        // One request a second comes back
+        assert!(limiter.check("a", start + Duration::from_secs(1)).is_ok());
+        assert!(limiter.check("a", start + Duration::from_secs(1)).is_err());
+    }
+
+    #[test]
+    fn test_prune_forgets_full_buckets() {
+        let limiter = RateLimiter::new(60, 2);
+        let start = Instant::now();
+        let mut buckets = HashMap::new();
+        buckets.insert("idle".to_string(), Bucket { tokens: 0.0, updated: start });
+        buckets.insert("busy".to_string(), Bucket { tokens: 0.0, updated: start });
+        limiter.prune(&mut buckets, start + Duration::from_secs(1));
+        assert_eq!(buckets.len(), 2);
+        buckets.get_mut("busy").unwrap().updated = start + Duration::from_secs(2);
+        limiter.prune(&mut buckets, start + Duration::from_secs(2));
+        assert_eq!(buckets.keys().collect::<Vec<_>>(), vec!["busy"]);
+    }
+
+    #[test]
+    fn test_key_limit_applies_across_addresses() {
+        let mut config = ApiConfig::default();
+        config.rate_limit.per_key_per_minute = 1;
+        config.rate_limit.burst = 1;
+        let limits = Limits::new(&config);
+        let now = Instant::now();
+        assert!(limits.check("10.0.0.1", Some("k"), now).is_ok());
+        assert!(limits.check("10.0.0.2", Some("k"), now).is_err());
+        assert!(limits.check("10.0.0.3", None, now).is_ok());
+
+        config.rate_limit.enabled = false;
+        let limits = Limits::new(&config);
+        assert!((0..10).all(|_| limits.check("10.0.0.1", Some("k"), now).is_ok()));
+    }
+
+    #[test]
+    fn test_allowed_origins() {
+        let config = ApiConfig {
+            allowed_origins: vec!["http://localhost:5173/".to_string()],
+            ..ApiConfig::default()
+        };
+        let limits = Limits::new(&config);
+        let mut headers = HeaderMap::new();
+        assert!(limits.allows_origin(&headers));
+        headers.insert(header::ORIGIN, HeaderValue::from_static("http://localhost:5173"));
+        assert!(limits.allows_origin(&headers));
+        headers.insert(header::ORIGIN, HeaderValue::from_static("https://evil.example"));
+        assert!(!limits.allows_origin(&headers));
+        assert!(!Limits::new(&ApiConfig::default()).allows_origin(&headers));
+    }
+
+    #[test]
+    fn test_client_ip_and_key() {
+        let peer: SocketAddr = "192.168.1.5:5000".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_static("203.0.113.7, 10.0.0.1"));
+        assert_eq!(client_ip(&headers, Some(peer), false), "192.168.1.5");
+        assert_eq!(client_ip(&headers, Some(peer), true), "203.0.113.7");
+        assert_eq!(client_ip(&HeaderMap::new(), None, true), "unknown");
+
+        assert_eq!(api_key(&headers), None);
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer abc"));
+        assert_eq!(api_key(&headers), Some("abc"));
+        headers.insert("x-api-key", HeaderValue::from_static("xyz"));
+        assert_eq!(api_key(&headers), Some("xyz"));
+    }
+}
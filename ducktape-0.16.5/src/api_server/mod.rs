@@ -3,10 +3,15 @@
 // This module provides a complete HTTP and WebSocket API for frontend applications
 // to interact with DuckTape's functionality.
 
+mod dedupe;
 mod handlers;
+mod limits;
 mod models;
+mod openapi;
 mod routes;
 mod server;
+pub(crate) mod slack;
+pub(crate) mod startup;
 mod websocket;
 
 // Re-export the main types and functions needed by consumers of this module
@@ -15,5 +20,28 @@ pub use server::start_api_server;
 
 #[cfg(test)]
 mod tests {
-    // API server tests will be implemented here
+    use super::handlers::error_status;
+    use crate::calendar::CalendarError;
+    use crate::error::DucktapeError;
+    use crate::script_runner::ScriptRunnerError;
+    use axum::http::StatusCode;
+
+    #[test]
+    fn test_error_status() {
+        let status = |e: anyhow::Error| error_status(&DucktapeError::from(e));
+        assert_eq!(
+            status(CalendarError::InvalidDateTime("25:00".into()).into()),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(CalendarError::CalendarNotFound("Work".into()).into()),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(status(CalendarError::NotRunning.into()), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            status(ScriptRunnerError::AutomationDenied { app: "Calendar".into() }.into()),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(status(anyhow::anyhow!("boom")), StatusCode::INTERNAL_SERVER_ERROR);
+    }
 }
@@ -4,20 +4,26 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
 
 /// Shared application state for the API server
 #[derive(Clone)]
 pub struct ApiState {
-    /// Application configuration
-    pub config: crate::config::Config,
+    /// Configuration and changes shared with the terminal
+    pub shared: crate::app_state::SharedState,
     /// Application version from Cargo.toml
     pub version: String,
     /// Server start time for uptime calculation
     pub start_time: DateTime<Utc>,
+    /// Configuration and permission checks run at startup
+    pub startup_report: super::startup::StartupReport,
+    /// Rate and size limits, from the configuration at startup
+    pub limits: Arc<super::limits::Limits>,
 }
 
 /// Generic API response
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct ApiResponse {
     /// Whether the operation was successful
     pub success: bool,
@@ -29,7 +35,7 @@ pub struct ApiResponse {
 }
 
 /// Status response with server information
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct StatusResponse {
     /// API server version
     pub version: String,
@@ -39,10 +45,14 @@ pub struct StatusResponse {
     pub status: String,
     /// Whether calendars are available
     pub calendars_available: bool,
+    /// Whether every startup check passed without errors
+    pub ready: bool,
+    /// Startup configuration and permission checks
+    pub startup: super::startup::StartupReport,
 }
 
 /// Calendar listing response
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct CalendarResponse {
     /// Whether the operation was successful
     pub success: bool,
@@ -54,7 +64,7 @@ pub struct CalendarResponse {
 }
 
 /// Create event request
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, ToSchema)]
 pub struct CreateEventRequest {
     /// Event title
     pub title: String,
@@ -71,12 +81,21 @@ pub struct CreateEventRequest {
     /// Event location (optional)
     #[serde(default)]
     pub location: Option<String>,
+    /// Link shown with the event (optional)
+    #[serde(default)]
+    pub url: Option<String>,
+    /// busy, free, tentative or unavailable (optional)
+    #[serde(default)]
+    pub availability: Option<String>,
     /// Event description (optional)
     #[serde(default)]
     pub description: Option<String>,
     /// Email addresses for attendees (optional)
     #[serde(default)]
     pub emails: Option<Vec<String>>,
+    /// Contact groups whose members are invited (optional)
+    #[serde(default)]
+    pub groups: Option<Vec<String>>,
     /// Reminder time in minutes before event (optional)
     #[serde(default)]
     pub reminder: Option<i32>,
@@ -85,8 +104,34 @@ pub struct CreateEventRequest {
     pub create_zoom_meeting: Option<bool>,
 }
 
+/// Request to create several events, run as a task
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct CreateEventsRequest {
+    /// Events to create, in order
+    pub events: Vec<CreateEventRequest>,
+}
+
+/// Request to import the events of an .ics or CSV file, run as a task
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct ImportEventsRequest {
+    /// Content of the file
+    pub content: String,
+    /// "ics" (default) or "csv"
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Calendar to import into (defaults to the calendar of each event, then calendar.default)
+    #[serde(default)]
+    pub calendar: Option<String>,
+    /// What to do with events that already exist: "skip" (default), "update" or "duplicate"
+    #[serde(default)]
+    pub on_duplicate: Option<String>,
+    /// CSV columns to use, e.g. "Subject=title,Start Date=date"
+    #[serde(default)]
+    pub map: Option<String>,
+}
+
 /// Create todo request
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, ToSchema)]
 pub struct CreateTodoRequest {
     /// Todo item title
     pub title: String,
@@ -102,7 +147,7 @@ pub struct CreateTodoRequest {
 }
 
 /// Todo response
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct TodoResponse {
     /// Whether the operation was successful
     pub success: bool,
@@ -111,7 +156,7 @@ pub struct TodoResponse {
 }
 
 /// Create note request
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, ToSchema)]
 pub struct CreateNoteRequest {
     /// Note title
     pub title: String,
@@ -123,7 +168,7 @@ pub struct CreateNoteRequest {
 }
 
 /// Note response
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct NoteResponse {
     /// Whether the operation was successful
     pub success: bool,
@@ -131,8 +176,93 @@ pub struct NoteResponse {
     pub message: String,
 }
 
+/// Create contact group request
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct CreateContactGroupRequest {
+    /// Group name
+    pub name: String,
+    /// Member email addresses
+    #[serde(default)]
+    pub emails: Vec<String>,
+}
+
+/// Add or remove contact group members request
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct ContactGroupMembersRequest {
+    /// Member email addresses
+    pub emails: Vec<String>,
+}
+
+/// Rename contact group request
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct RenameContactGroupRequest {
+    /// New group name
+    pub new_name: String,
+}
+
+/// Contact group response
+#[derive(Serialize, ToSchema)]
+pub struct ContactGroupResponse {
+    /// Whether the operation was successful
+    pub success: bool,
+    /// Response message
+    pub message: String,
+    /// Group names (for list requests)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<String>>,
+    /// Group members (for show requests)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub members: Option<Vec<String>>,
+}
+
+/// Query parameters for the iCalendar feed
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FeedQuery {
+    /// Token of the feed
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+/// Query parameters for the usage endpoint
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UsageQuery {
+    /// Month in YYYY-MM format, defaults to the current month
+    #[serde(default)]
+    pub month: Option<String>,
+}
+
+/// Query parameters for the upcoming items endpoints
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UpcomingQuery {
+    /// How far to look ahead, e.g. 90m, 24h or 3d; defaults to 24h
+    #[serde(default)]
+    pub within: Option<String>,
+    /// Read Calendar.app and Reminders.app even when the copy in memory is recent
+    #[serde(default)]
+    pub refresh: Option<bool>,
+}
+
+/// Language model usage for a month
+#[derive(Serialize, ToSchema)]
+pub struct UsageResponse {
+    /// Month in YYYY-MM format
+    pub month: String,
+    /// Usage per day, provider and model
+    pub records: Vec<crate::state::UsageRecord>,
+    /// Estimated cost of the month in USD
+    pub total_cost_usd: f64,
+    /// Monthly budget in USD, if configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monthly_budget_usd: Option<f64>,
+    /// Whether the month's cost has reached the budget
+    pub over_budget: bool,
+}
+
 /// Generic WebSocket message format
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 pub struct SwiftMessage {
     /// Message type (e.g., "chat", "command", "create")
     #[serde(default)]
@@ -146,10 +276,13 @@ pub struct SwiftMessage {
     /// Structured data payload
     #[serde(default)]
     pub data: Option<serde_json::Value>,
+    /// Client supplied key; requests repeating a key are only executed once
+    #[serde(default, alias = "idempotencyKey", skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
 }
 
 /// Event data for WebSocket event creation
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct SwiftEventData {
     /// Event title
     pub title: String,
@@ -168,7 +301,7 @@ pub struct SwiftEventData {
 }
 
 /// WebSocket chat message
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct SwiftChatMessage {
     /// Sender identifier
     pub sender: String,
@@ -180,8 +313,51 @@ pub struct SwiftChatMessage {
     pub message_type: String,
 }
 
+/// WebSocket progress update, sent while natural language input is being parsed
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SwiftProgressMessage {
+    /// Message type (always "progress")
+    pub message_type: String,
+    /// "status" for a parsing stage, "thinking" for model reasoning, "partial" for the answer
+    pub stage: String,
+    /// Status text, or everything streamed so far in this stage
+    pub content: String,
+    /// ISO 8601 timestamp
+    pub timestamp: String,
+}
+
+/// WebSocket question, sent instead of running a command when the input left something open
+///
+/// The next chat message is the answer: an option's number or label, or "cancel".
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SwiftClarificationMessage {
+    /// Message type (always "clarification")
+    pub message_type: String,
+    /// The question, e.g. "Which Tuesday — the 6th or the 13th?"
+    pub question: String,
+    /// The answers offered, in order
+    pub options: Vec<String>,
+    /// ISO 8601 timestamp
+    pub timestamp: String,
+}
+
+/// WebSocket task update, sent when a watched task makes progress or finishes
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SwiftTaskMessage {
+    /// Message type (always "task")
+    pub message_type: String,
+    /// The task as last reported
+    #[serde(flatten)]
+    pub task: crate::tasks::Task,
+    /// How far the task got, from 0 to 100, once its size is known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percent: Option<u8>,
+    /// ISO 8601 timestamp
+    pub timestamp: String,
+}
+
 /// WebSocket event response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct SwiftEventResponse {
     /// Message type (always "event")
     pub message_type: String,
@@ -195,7 +371,7 @@ pub struct SwiftEventResponse {
 }
 
 /// WebSocket error response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct SwiftErrorResponse {
     /// Message type (always "error")
     pub message_type: String,
@@ -0,0 +1,154 @@
+// OpenAPI specification
+//
+// The specification is generated from the annotations on the handlers and models, so clients
+// can be generated from it instead of following the payloads by hand. The WebSocket messages
+// are listed as schemas too, although OpenAPI cannot describe the conversation itself.
+
+use axum::{Json, response::Html};
+use utoipa::OpenApi;
+
+use super::models::{
+    ApiResponse, CalendarResponse, ContactGroupMembersRequest, ContactGroupResponse,
+    CreateContactGroupRequest, CreateEventRequest, CreateEventsRequest, CreateNoteRequest,
+    CreateTodoRequest, ImportEventsRequest, NoteResponse, RenameContactGroupRequest,
+    StatusResponse, SwiftChatMessage, SwiftClarificationMessage, SwiftErrorResponse,
+    SwiftEventData, SwiftEventResponse, SwiftMessage, SwiftProgressMessage, SwiftTaskMessage,
+    TodoResponse, UsageResponse,
+};
+use super::startup::{CheckStatus, StartupCheck, StartupReport};
+use super::{handlers, slack, websocket};
+use crate::state::UsageRecord;
+use crate::tasks::{Task, TaskStatus};
+use crate::upcoming::{UpcomingFeed, UpcomingItem, UpcomingKind, UpcomingSource};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "DuckTape API",
+        description = "Calendar events, reminders and notes on macOS. Requests over the rate \
+            limits get 429 with a Retry-After header, bodies over the size limit get 413."
+    ),
+    paths(
+        handlers::health,
+        handlers::healthz,
+        handlers::readyz,
+        handlers::metrics,
+        handlers::status,
+        handlers::usage,
+        handlers::list_calendars,
+        handlers::create_calendar_event,
+        handlers::create_calendar_events,
+        handlers::import_calendar,
+        handlers::get_task,
+        handlers::calendar_feed,
+        handlers::upcoming,
+        handlers::upcoming_reminders,
+        handlers::create_todo,
+        handlers::create_note,
+        handlers::list_contact_groups,
+        handlers::create_contact_group,
+        handlers::get_contact_group,
+        handlers::rename_contact_group,
+        handlers::delete_contact_group,
+        handlers::add_contact_group_members,
+        handlers::remove_contact_group_members,
+        slack::slash_command,
+        websocket::websocket_handler,
+    ),
+    components(schemas(
+        ApiResponse,
+        StatusResponse,
+        StartupReport,
+        StartupCheck,
+        CheckStatus,
+        UsageResponse,
+        UsageRecord,
+        CalendarResponse,
+        CreateEventRequest,
+        CreateEventsRequest,
+        ImportEventsRequest,
+        Task,
+        TaskStatus,
+        UpcomingFeed,
+        UpcomingItem,
+        UpcomingKind,
+        UpcomingSource,
+        CreateTodoRequest,
+        TodoResponse,
+        CreateNoteRequest,
+        NoteResponse,
+        CreateContactGroupRequest,
+        ContactGroupMembersRequest,
+        RenameContactGroupRequest,
+        ContactGroupResponse,
+        SwiftMessage,
+        SwiftEventData,
+        SwiftChatMessage,
+        SwiftProgressMessage,
+        SwiftClarificationMessage,
+        SwiftTaskMessage,
+        SwiftEventResponse,
+        SwiftErrorResponse,
+    ))
+)]
+pub struct ApiDoc;
+
+/// Serve the OpenAPI specification
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// Serve Swagger UI for the specification
+///
+/// The page loads Swagger UI from a CDN, so the browser needs to reach it.
+pub async fn swagger_ui() -> Html<&'static str> {
+    Html(SWAGGER_UI)
+}
+
+const SWAGGER_UI: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <title>DuckTape API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script>
+    SwaggerUIBundle({ url: "/api/openapi.json", dom_id: "#swagger-ui" });
+  </script>
+</body>
+</html>
+"##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_covers_the_routes() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        for path in [
+            "/health",
+            "/status",
+            "/calendar/event",
+            "/calendar/import",
+            "/api/tasks/{id}",
+            "/todo",
+            "/api/upcoming",
+            "/api/reminders/upcoming",
+            "/note",
+            "/contacts/groups/{name}/members",
+            "/api/integrations/slack",
+            "/chat",
+        ] {
+            assert!(spec["paths"].get(path).is_some(), "{} is not documented", path);
+        }
+        let schemas = &spec["components"]["schemas"];
+        for schema in ["CreateEventRequest", "Task", "SwiftMessage", "SwiftTaskMessage"] {
+            assert!(schemas.get(schema).is_some(), "{} is not documented", schema);
+        }
+        assert_eq!(spec["info"]["version"], env!("CARGO_PKG_VERSION"));
+    }
+}
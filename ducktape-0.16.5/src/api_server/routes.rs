@@ -5,43 +5,133 @@
 
 use axum::{
     Router,
+    extract::{ConnectInfo, DefaultBodyLimit, Request, State},
     http::Method,
+    middleware::{self, Next},
+    response::Response,
     routing::{get, post},
 };
+use std::net::SocketAddr;
 use std::sync::Arc;
-use tower_http::cors::{Any, CorsLayer};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tower_http::limit::RequestBodyLimitLayer;
 
 use super::handlers;
+use super::limits;
 use super::models::ApiState;
+use super::openapi;
+use super::slack;
 use super::websocket::websocket_handler;
 
 /// Create application routes with proper CORS configuration
 pub fn create_routes(state: Arc<ApiState>) -> Router {
-    // Configure CORS for web and mobile clients
+    let max_body_bytes = state.limits.max_body_bytes;
+
+    // Only the configured web pages may call the API from a browser
+    let origins = state.limits.allowed_origins.iter().filter_map(|origin| origin.parse().ok());
     let cors = CorsLayer::new()
-        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE, Method::OPTIONS])
         .allow_headers(Any)
-        .allow_origin(Any);
+        .allow_origin(AllowOrigin::list(origins));
 
     // Define routes with proper handler functions
     Router::new()
         // Health check endpoint
         .route("/health", get(handlers::health))
+        // Probes and metrics for orchestrators and monitoring
+        .route("/healthz", get(handlers::healthz))
+        .route("/readyz", get(handlers::readyz))
+        .route("/metrics", get(handlers::metrics))
         // API status endpoint
         .route("/status", get(handlers::status))
+        .route("/api/status", get(handlers::status))
+        // Language model usage
+        .route("/usage", get(handlers::usage))
         // Calendar APIs
         .route("/calendars", get(handlers::list_calendars))
         .route("/calendar/event", post(handlers::create_calendar_event))
+        .route("/calendar/events", post(handlers::create_calendar_events))
+        .route("/calendar/import", post(handlers::import_calendar))
+        // Progress of long-running tasks
+        .route("/api/tasks/:id", get(handlers::get_task))
+        // Events and reminders coming up, for widgets and launchers
+        .route("/api/upcoming", get(handlers::upcoming))
+        .route("/api/reminders/upcoming", get(handlers::upcoming_reminders))
+        // iCalendar feed for subscriptions
+        .route("/calendar.ics", get(handlers::calendar_feed))
         // Todo API
         .route("/todo", post(handlers::create_todo))
         // Notes API
         .route("/note", post(handlers::create_note))
+        // Slack slash commands
+        .route("/api/integrations/slack", post(slack::slash_command))
+        // Contact group APIs
+        .route(
+            "/contacts/groups",
+            get(handlers::list_contact_groups).post(handlers::create_contact_group),
+        )
+        .route(
+            "/contacts/groups/:name",
+            get(handlers::get_contact_group)
+                .put(handlers::rename_contact_group)
+                .delete(handlers::delete_contact_group),
+        )
+        .route(
+            "/contacts/groups/:name/members",
+            post(handlers::add_contact_group_members)
+                .delete(handlers::remove_contact_group_members),
+        )
         // WebSocket endpoint for real-time communications
         .route("/chat", get(websocket_handler))
-        // API docs
-        .route("/api-docs", get(handlers::api_docs))
+        // OpenAPI specification and Swagger UI
+        .route("/api/openapi.json", get(openapi::openapi_json))
+        .route("/api-docs", get(openapi::openapi_json))
+        .route("/api/docs", get(openapi::swagger_ui))
+        // Report changes made through the API as such to webhooks
+        .layer(middleware::from_fn(tag_webhook_source))
+        // Record commands in the audit log as run by the API key or client address
+        .layer(middleware::from_fn_with_state(state.clone(), tag_actor))
+        // Run changes one at a time, also with those made in the terminal
+        .layer(middleware::from_fn_with_state(state.clone(), serialize_changes))
+        // Refuse bodies over the configured size with 413 Payload Too Large
+        .layer(DefaultBodyLimit::max(max_body_bytes))
+        .layer(RequestBodyLimitLayer::new(max_body_bytes))
+        // Answer clients over their rate limit with 429 Too Many Requests
+        .layer(middleware::from_fn_with_state(state.clone(), limits::rate_limit))
+        // Refuse changes that other web pages make through the user's browser
+        .layer(middleware::from_fn_with_state(state.clone(), limits::check_origin))
         // Apply CORS middleware
         .layer(cors)
         // Attach shared application state
         .with_state(state)
 }
+
+/// Run requests other than reads while no other change is running
+async fn serialize_changes(
+    State(state): State<Arc<ApiState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return next.run(request).await;
+    }
+    state.shared.exclusive(next.run(request)).await
+}
+
+/// Run a request with its changes reported to webhooks as made by the API
+async fn tag_webhook_source(request: Request, next: Next) -> Response {
+    crate::webhooks::with_source("api", next.run(request)).await
+}
+
+/// Run a request with its commands recorded as run by its API key, or its address without one
+async fn tag_actor(State(state): State<Arc<ApiState>>, request: Request, next: Next) -> Response {
+    let actor = match limits::api_key(request.headers()) {
+        Some(key) => crate::audit::api_key_actor(key),
+        None => {
+            let peer = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0);
+            let ip = limits::client_ip(request.headers(), peer, state.limits.trust_proxy);
+            format!("address {}", ip)
+        }
+    };
+    crate::audit::with_actor(actor, next.run(request)).await
+}
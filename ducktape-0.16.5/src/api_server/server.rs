@@ -4,32 +4,47 @@
 
 use axum::serve;
 use chrono::Utc;
-use log::info;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use tracing::info;
 
+use crate::app_state::SharedState;
+
+use super::limits::Limits;
 use super::models::ApiState;
 use super::routes::create_routes;
+use super::startup::{check_port, validate_startup};
 
 /// Start the API server on the specified address
 ///
 /// # Arguments
 ///
-/// * `config` - Application configuration
+/// * `shared` - State shared with the terminal, usually `SharedState::global()`
 /// * `address` - Socket address to bind to (e.g., "127.0.0.1:3000")
 ///
 /// # Returns
 ///
 /// Result indicating success or error
-pub async fn start_api_server(config: crate::config::Config, address: &str) -> anyhow::Result<()> {
+pub async fn start_api_server(shared: SharedState, address: &str) -> anyhow::Result<()> {
     // Parse the address
     let addr: SocketAddr = address.parse()?;
 
+    // Validate configuration and permissions before serving requests
+    let bind_result = tokio::net::TcpListener::bind(addr).await;
+    let config = shared.config().await;
+    let mut startup_report = validate_startup(&config).await;
+    startup_report.checks.push(check_port(addr, bind_result.as_ref().err()));
+    startup_report.log_summary();
+    let listener = bind_result.map_err(|e| anyhow::anyhow!("Cannot bind {}: {}", addr, e))?;
+
     // Create the shared application state
     let state = Arc::new(ApiState {
-        config,
+        shared,
         version: env!("CARGO_PKG_VERSION").to_string(),
         start_time: Utc::now(),
+        startup_report,
+        // Changes to the limits take effect when the server is restarted
+        limits: Arc::new(Limits::new(&config.api)),
     });
 
     // Create the application with routes
@@ -38,8 +53,9 @@ pub async fn start_api_server(config: crate::config::Config, address: &str) -> a
     info!("API server starting on {}", addr);
 
     // Start the server
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    serve(listener, app).await.map_err(|e| anyhow::anyhow!("Server error: {}", e))?;
+    serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .await
+        .map_err(|e| anyhow::anyhow!("Server error: {}", e))?;
 
     Ok(())
 }
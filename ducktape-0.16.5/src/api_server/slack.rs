@@ -0,0 +1,317 @@
+// Slack slash-command integration
+//
+// `POST /api/integrations/slack` receives slash-command payloads. Requests are verified with
+// the app's signing secret (`ducktape keys set slack`). Slack wants an answer within three
+// seconds, which is less than a language model can promise, so the endpoint acknowledges
+// at once and posts the outcome to the command's `response_url` as a Block Kit message.
+
+use axum::{
+    Json,
+    body::Bytes,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use hmac::{Hmac, Mac};
+use serde_json::{Value, json};
+use sha2::Sha256;
+use tracing::{error, info, warn};
+
+use crate::calendar::EventConfig;
+use crate::command_bus::{Command, Outcome, dispatch};
+use crate::parser::{ParseResult, ParserFactory};
+
+/// Variable holding the Slack app's signing secret
+pub const SIGNING_SECRET_VAR: &str = "SLACK_SIGNING_SECRET";
+
+/// Requests signed longer ago than this are rejected as possible replays
+const MAX_REQUEST_AGE_SECS: i64 = 5 * 60;
+
+/// Why a request was not accepted as coming from Slack
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum SlackError {
+    #[error("Slack signing secret is not set. Store it with `ducktape keys set slack`")]
+    NotConfigured,
+
+    #[error("Missing X-Slack-Signature or X-Slack-Request-Timestamp header")]
+    MissingHeaders,
+
+    #[error("Slack request timestamp is too old")]
+    Stale,
+
+    #[error("Invalid Slack signature")]
+    BadSignature,
+}
+
+/// The fields of a slash-command payload DuckTape uses
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SlashCommand {
+    pub command: String,
+    pub text: String,
+    pub user_name: String,
+    pub response_url: String,
+}
+
+impl SlashCommand {
+    /// Read a form-encoded slash-command body
+    pub fn from_form(body: &[u8]) -> Self {
+        let mut command = SlashCommand::default();
+        for (key, value) in url::form_urlencoded::parse(body) {
+            let value = value.into_owned();
+            match key.as_ref() {
+                "command" => command.command = value,
+                "text" => command.text = value,
+                "user_name" => command.user_name = value,
+                "response_url" => command.response_url = value,
+                _ => {}
+            }
+        }
+        command
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Check a request against Slack's `v0` signing scheme
+///
+/// `now` is the current Unix time; requests more than five minutes from it are refused.
+pub fn verify_signature(
+    secret: &str,
+    timestamp: &str,
+    body: &[u8],
+    signature: &str,
+    now: i64,
+) -> Result<(), SlackError> {
+    let sent: i64 = timestamp.trim().parse().map_err(|_| SlackError::MissingHeaders)?;
+    if (now - sent).abs() > MAX_REQUEST_AGE_SECS {
+        return Err(SlackError::Stale);
+    }
+    let expected = signature
+        .strip_prefix("v0=")
+        .and_then(decode_hex)
+        .ok_or(SlackError::BadSignature)?;
+
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("v0:{}:", timestamp.trim()).as_bytes());
+    mac.update(body);
+    mac.verify_slice(&expected).map_err(|_| SlackError::BadSignature)
+}
+
+fn verify_request(headers: &HeaderMap, body: &[u8]) -> Result<(), SlackError> {
+    let secret = crate::secrets::get(SIGNING_SECRET_VAR).ok_or(SlackError::NotConfigured)?;
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let (Some(timestamp), Some(signature)) =
+        (header("X-Slack-Request-Timestamp"), header("X-Slack-Signature"))
+    else {
+        return Err(SlackError::MissingHeaders);
+    };
+    verify_signature(&secret, timestamp, body, signature, chrono::Utc::now().timestamp())
+}
+
+/// Block Kit message for a created event
+pub fn event_blocks(config: &EventConfig, user_name: &str) -> Value {
+    let when = if config.all_day {
+        format!("{} (all day)", config.start_date)
+    } else {
+        match &config.end_time {
+            Some(end) => format!("{} {}–{}", config.start_date, config.start_time, end),
+            None => format!("{} {}", config.start_date, config.start_time),
+        }
+    };
+    let mut fields = vec![json!({ "type": "mrkdwn", "text": format!("*When*\n{}", when) })];
+    if !config.calendars.is_empty() {
+        fields.push(json!({
+            "type": "mrkdwn",
+            "text": format!("*Calendar*\n{}", config.calendars.join(", ")),
+        }));
+    }
+    if let Some(location) = &config.location {
+        fields.push(json!({ "type": "mrkdwn", "text": format!("*Where*\n{}", location) }));
+    }
+    if !config.emails.is_empty() {
+        fields.push(json!({
+            "type": "mrkdwn",
+            "text": format!("*Invited*\n{}", config.emails.join(", ")),
+        }));
+    }
+
+    let title = format!("Created \"{}\"", config.title.trim_matches('"'));
+    json!({
+        "response_type": "in_channel",
+        "replace_original": true,
+        "text": format!("{} on {}", title, when),
+        "blocks": [
+            {
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": format!(":white_check_mark: *{}*", title) },
+            },
+            { "type": "section", "fields": fields },
+            {
+                "type": "context",
+                "elements": [
+                    { "type": "mrkdwn", "text": format!("Added by @{} with DuckTape", user_name) },
+                ],
+            },
+        ],
+    })
+}
+
+/// Block Kit message for a command that could not be run
+pub fn error_blocks(text: &str, error: &str) -> Value {
+    json!({
+        "response_type": "ephemeral",
+        "replace_original": true,
+        "text": format!("Could not schedule \"{}\": {}", text, error),
+        "blocks": [
+            {
+                "type": "section",
+                "text": {
+                    "type": "mrkdwn",
+                    "text": format!(":x: Could not schedule _{}_\n{}", text, error),
+                },
+            },
+        ],
+    })
+}
+
+/// Parse the text with the configured parsers and create the event it describes
+async fn create_from_text(text: &str) -> anyhow::Result<EventConfig> {
+    let parser = ParserFactory::create_parser()?;
+    let result = parser.parse_input(text).await?;
+    match &result {
+        ParseResult::CommandString(command) => info!("Slack: parsed command: {}", command),
+        // A slash command cannot be answered, so the question goes back with the error
+        ParseResult::NeedsClarification(clarification) => {
+            anyhow::bail!("{} Send it again with the answer included.", clarification.question)
+        }
+        ParseResult::StructuredCommand(_) => {}
+    }
+    let command = Command::from_parse_result(result)?;
+    if !matches!(command, Command::CreateEvent(_)) {
+        anyhow::bail!("that does not describe an event (understood as `{}`)", command.name());
+    }
+    match dispatch(command, None).await? {
+        Some(Outcome::EventCreated(config)) => Ok(config),
+        outcome => Err(anyhow::anyhow!("unexpected outcome {:?}", outcome)),
+    }
+}
+
+/// Run a slash command and post the outcome to its response URL
+async fn respond(command: SlashCommand) {
+    let message = match create_from_text(&command.text).await {
+        Ok(config) => event_blocks(&config, &command.user_name),
+        Err(e) => {
+            warn!("Slack: {} {} failed: {}", command.command, command.text, e);
+            error_blocks(&command.text, &e.to_string())
+        }
+    };
+    let client = reqwest::Client::new();
+    match client.post(&command.response_url).json(&message).send().await {
+        Ok(response) if response.status().is_success() => {}
+        Ok(response) => error!("Slack: response_url answered {}", response.status()),
+        Err(e) => error!("Slack: failed to post to response_url: {}", e),
+    }
+}
+
+/// Handle a Slack slash command
+#[utoipa::path(
+    post,
+    path = "/api/integrations/slack",
+    tag = "integrations",
+    request_body(content = String, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Acknowledged; the outcome is posted to `response_url`"),
+        (status = 401, description = "Missing, stale or wrong signature"),
+        (status = 503, description = "No signing secret is stored")
+    )
+)]
+pub async fn slash_command(headers: HeaderMap, body: Bytes) -> impl IntoResponse {
+    if let Err(e) = verify_request(&headers, &body) {
+        warn!("Slack: rejected request: {}", e);
+        let status = match e {
+            SlackError::NotConfigured => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::UNAUTHORIZED,
+        };
+        return (status, Json(json!({ "text": e.to_string() })));
+    }
+
+    let command = SlashCommand::from_form(&body);
+    if command.text.trim().is_empty() {
+        let usage = format!(
+            "Describe the event, e.g. `{} lunch with Sam tomorrow at 12:30`",
+            if command.command.is_empty() { "/ducktape" } else { command.command.as_str() }
+        );
+        return (StatusCode::OK, Json(json!({ "response_type": "ephemeral", "text": usage })));
+    }
+    if url::Url::parse(&command.response_url).is_err() {
+        return (StatusCode::BAD_REQUEST, Json(json!({ "text": "Missing response_url" })));
+    }
+
+    info!("Slack: {} {} from @{}", command.command, command.text, command.user_name);
+    let ack = format!("Scheduling \"{}\"…", command.text.trim());
+    let actor = format!("slack @{}", command.user_name);
+    let response = crate::audit::with_actor(actor, respond(command));
+    tokio::spawn(crate::webhooks::with_source("slack", response));
+    (StatusCode::OK, Json(json!({ "response_type": "ephemeral", "text": ack })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(secret: &str, timestamp: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(format!("v0:{}:", timestamp).as_bytes());
+        mac.update(body);
+        let bytes = mac.finalize().into_bytes();
+        format!("v0={}", bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>())
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let body = b"command=%2Fducktape&text=lunch+tomorrow+at+noon";
+        let signature = sign("secret", "1700000000", body);
+        assert_eq!(verify_signature("secret", "1700000000", body, &signature, 1700000060), Ok(()));
+        assert_eq!(
+            verify_signature("other", "1700000000", body, &signature, 1700000060),
+            Err(SlackError::BadSignature)
+        );
+        assert_eq!(
+            verify_signature("secret", "1700000000", b"text=changed", &signature, 1700000060),
+            Err(SlackError::BadSignature)
+        );
+        assert_eq!(
+            verify_signature("secret", "1700000000", body, &signature, 1700001000),
+            Err(SlackError::Stale)
+        );
+        assert_eq!(
+            verify_signature("secret", "1700000000", body, "v0=zz", 1700000000),
+            Err(SlackError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn test_slash_command_form_and_blocks() {
+        let command = SlashCommand::from_form(
+            b"command=%2Fducktape&text=lunch+with+Sam+tomorrow&user_name=jane&response_url=https%3A%2F%2Fhooks.slack.com%2Fcommands%2F1",
+        );
+        assert_eq!(command.text, "lunch with Sam tomorrow");
+        assert_eq!(command.response_url, "https://hooks.slack.com/commands/1");
+
+        let mut config = EventConfig::new("Lunch with Sam", "2025-04-16", "12:30");
+        config.end_time = Some("13:30".to_string());
+        config.calendars = vec!["Work".to_string()];
+        let message = event_blocks(&config, "jane");
+        assert_eq!(message["response_type"], "in_channel");
+        assert_eq!(message["text"], "Created \"Lunch with Sam\" on 2025-04-16 12:30–13:30");
+        assert_eq!(message["blocks"][1]["fields"].as_array().map(Vec::len), Some(2));
+    }
+}
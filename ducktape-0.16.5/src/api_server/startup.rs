@@ -0,0 +1,382 @@
+// Startup validation for API server mode
+//
+// Before serving requests the server checks that DuckTape is actually usable: API keys are
+// present, Calendar and Reminders can be reached, the default calendar exists and the port
+// is free. The results are logged as a summary table and served from `/api/status` so that
+// frontends can show actionable setup errors instead of opaque command failures later.
+
+use crate::config::{Config, LLMProvider, MeetingService};
+use chrono::Utc;
+use serde::Serialize;
+use std::net::SocketAddr;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+/// Outcome of a single startup check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+impl CheckStatus {
+    fn label(&self) -> &'static str {
+        match self {
+            CheckStatus::Ok => "ok",
+            CheckStatus::Warning => "warning",
+            CheckStatus::Error => "error",
+        }
+    }
+}
+
+/// A single startup check with a hint on how to fix it
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct StartupCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl StartupCheck {
+    pub(crate) fn ok(name: &str, message: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Ok,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    pub(crate) fn warning(name: &str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Warning,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    pub(crate) fn error(name: &str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Error,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// Results of all startup checks
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct StartupReport {
+    /// When the checks ran (RFC 3339)
+    pub checked_at: String,
+    pub checks: Vec<StartupCheck>,
+}
+
+impl StartupReport {
+    /// Whether no check failed outright (warnings are allowed)
+    pub fn ready(&self) -> bool {
+        !self.checks.iter().any(|c| c.status == CheckStatus::Error)
+    }
+
+    /// Whether the named check passed
+    pub fn passed(&self, name: &str) -> bool {
+        self.checks.iter().any(|c| c.name == name && c.status == CheckStatus::Ok)
+    }
+
+    /// Render the report as a plain text table
+    pub fn summary_table(&self) -> String {
+        let name_width = self.checks.iter().map(|c| c.name.len()).max().unwrap_or(0).max(5);
+        let mut out = format!("{:<name_width$}  {:<7}  Details\n", "Check", "Status");
+        for check in &self.checks {
+            out.push_str(&format!(
+                "{:<name_width$}  {:<7}  {}\n",
+                check.name,
+                check.status.label(),
+                check.message
+            ));
+            if let Some(hint) = &check.hint {
+                out.push_str(&format!("{:<name_width$}  {:<7}  → {}\n", "", "", hint));
+            }
+        }
+        out
+    }
+
+    /// Log the summary table, as a warning when something needs attention
+    pub fn log_summary(&self) {
+        let needs_attention = self.checks.iter().any(|c| c.status != CheckStatus::Ok);
+        for line in format!("Startup checks:\n{}", self.summary_table()).lines() {
+            if needs_attention {
+                warn!("{}", line);
+            } else {
+                info!("{}", line);
+            }
+        }
+    }
+}
+
+/// Names of the individual checks, as served from `/api/status`
+pub const CHECK_API_KEY: &str = "api_key";
+pub const CHECK_MEETING_CREDENTIALS: &str = "meeting_credentials";
+pub const CHECK_CALENDAR_ACCESS: &str = "calendar_access";
+pub const CHECK_DEFAULT_CALENDAR: &str = "default_calendar";
+pub const CHECK_REMINDERS_ACCESS: &str = "reminders_access";
+pub const CHECK_PORT: &str = "port";
+pub const CHECK_CONFIG: &str = "config";
+
+/// Longest wait for Calendar to answer a readiness probe
+const READINESS_TIMEOUT_SECS: u64 = 5;
+
+/// Run the configuration and permission checks
+///
+/// The port check is added separately by the server once it has tried to bind.
+pub async fn validate_startup(config: &Config) -> StartupReport {
+    let lookup = crate::secrets::get;
+    let mut checks = vec![check_api_key(config, lookup), check_meeting_credentials(config, lookup)];
+
+    let calendars = crate::calendar::get_available_calendars().await;
+    checks.extend(check_calendars(config, &calendars));
+
+    checks.push(match crate::todo::list_reminder_lists().await {
+        Ok(lists) => StartupCheck::ok(
+            CHECK_REMINDERS_ACCESS,
+            format!("{} reminder list(s) available", lists.len()),
+        ),
+        Err(e) => StartupCheck::error(
+            CHECK_REMINDERS_ACCESS,
+            format!("Cannot read Reminders: {}", e),
+            "Allow access in System Settings → Privacy & Security → Reminders",
+        ),
+    });
+
+    StartupReport { checked_at: Utc::now().to_rfc3339(), checks }
+}
+
+/// Check, now, whether the server can serve requests, for `/readyz`
+///
+/// Unlike the startup checks this reloads the configuration and only asks Calendar whether it
+/// answers, so it stays cheap enough to be probed regularly.
+pub async fn check_readiness() -> StartupReport {
+    let (config, config_check) = match Config::load() {
+        Ok(config) => (config, StartupCheck::ok(CHECK_CONFIG, "Configuration loaded")),
+        Err(e) => (
+            Config::default(),
+            StartupCheck::error(
+                CHECK_CONFIG,
+                format!("Cannot load the configuration: {}", e),
+                "Fix config.toml; `ducktape config show` prints what is read",
+            ),
+        ),
+    };
+
+    let timeout = std::time::Duration::from_secs(READINESS_TIMEOUT_SECS);
+    let calendars =
+        match tokio::time::timeout(timeout, crate::calendar::get_available_calendars()).await {
+            Ok(calendars) => calendars,
+            Err(_) => Err(anyhow::anyhow!(
+                "Calendar did not answer within {} seconds",
+                READINESS_TIMEOUT_SECS
+            )),
+        };
+    let calendar_access = check_calendars(&config, &calendars).swap_remove(0);
+
+    StartupReport {
+        checked_at: Utc::now().to_rfc3339(),
+        checks: vec![config_check, check_api_key(&config, crate::secrets::get), calendar_access],
+    }
+}
+
+/// Environment variable holding the API key of the configured language model
+fn api_key_var(config: &Config) -> &'static str {
+    match config.language_model.provider.clone().unwrap_or_default() {
+        LLMProvider::Grok => "XAI_API_KEY",
+        LLMProvider::DeepSeek => "DEEPSEEK_API_KEY",
+    }
+}
+
+fn is_set(lookup: impl Fn(&str) -> Option<String>, name: &str) -> bool {
+    lookup(name).is_some_and(|v| !v.trim().is_empty())
+}
+
+/// Check that the configured language model has an API key
+pub(crate) fn check_api_key(
+    config: &Config,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> StartupCheck {
+    let var = api_key_var(config);
+    if is_set(&lookup, var) {
+        StartupCheck::ok(CHECK_API_KEY, format!("{} is set", var))
+    } else {
+        StartupCheck::error(
+            CHECK_API_KEY,
+            format!("{} is not set; natural language requests will fail", var),
+            format!(
+                "Store it with `ducktape keys set {}` or set {} in your environment",
+                if var == "XAI_API_KEY" { "xai" } else { "deepseek" },
+                var
+            ),
+        )
+    }
+}
+
+/// Check that credentials for the configured meeting provider are present
+pub(crate) fn check_meeting_credentials(
+    config: &Config,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> StartupCheck {
+    let service = config.calendar.meeting_provider;
+    let required: &[&str] = match service {
+        MeetingService::Zoom => &["ZOOM_ACCOUNT_ID", "ZOOM_CLIENT_ID", "ZOOM_CLIENT_SECRET"],
+        MeetingService::Teams => {
+            &["MS_TENANT_ID", "MS_CLIENT_ID", "MS_CLIENT_SECRET", "MS_TEAMS_USER_ID"]
+        }
+        MeetingService::Meet => &["GOOGLE_MEET_ACCESS_TOKEN"],
+    };
+    let missing: Vec<&str> =
+        required.iter().copied().filter(|name| !is_set(&lookup, name)).collect();
+
+    if missing.is_empty() {
+        StartupCheck::ok(CHECK_MEETING_CREDENTIALS, format!("{} credentials set", service.label()))
+    } else {
+        StartupCheck::warning(
+            CHECK_MEETING_CREDENTIALS,
+            format!(
+                "Missing {}; {} meetings cannot be created",
+                missing.join(", "),
+                service.label()
+            ),
+            "Set the missing variables (`ducktape keys set zoom` for Zoom) or change calendar.meeting_provider",
+        )
+    }
+}
+
+/// Check Calendar access and that the default calendar exists
+pub(crate) fn check_calendars(
+    config: &Config,
+    calendars: &anyhow::Result<Vec<String>>,
+) -> Vec<StartupCheck> {
+    let calendars = match calendars {
+        Ok(calendars) => calendars,
+        Err(e) => {
+            return vec![
+                StartupCheck::error(
+                    CHECK_CALENDAR_ACCESS,
+                    format!("Cannot read Calendar: {}", e),
+                    "Allow access in System Settings → Privacy & Security → Calendars",
+                ),
+                StartupCheck::warning(
+                    CHECK_DEFAULT_CALENDAR,
+                    "Not checked without Calendar access",
+                    "Fix Calendar access first",
+                ),
+            ];
+        }
+    };
+
+    let access = StartupCheck::ok(
+        CHECK_CALENDAR_ACCESS,
+        format!("{} calendar(s) available", calendars.len()),
+    );
+    let default = match config.calendar.default_calendar.as_deref() {
+        None => StartupCheck::warning(
+            CHECK_DEFAULT_CALENDAR,
+            "No default calendar configured",
+            "Run: ducktape config set calendar.default \"<name>\"",
+        ),
+        Some(name) if calendars.iter().any(|c| c.eq_ignore_ascii_case(name)) => {
+            StartupCheck::ok(CHECK_DEFAULT_CALENDAR, format!("'{}' exists", name))
+        }
+        Some(name) => StartupCheck::error(
+            CHECK_DEFAULT_CALENDAR,
+            format!("Default calendar '{}' was not found", name),
+            format!("Choose one of: {}", calendars.join(", ")),
+        ),
+    };
+    vec![access, default]
+}
+
+/// Result of binding the server address
+pub fn check_port(addr: SocketAddr, bind_error: Option<&std::io::Error>) -> StartupCheck {
+    match bind_error {
+        None => StartupCheck::ok(CHECK_PORT, format!("Listening on {}", addr)),
+        Some(e) => StartupCheck::error(
+            CHECK_PORT,
+            format!("Cannot bind {}: {}", addr, e),
+            format!("Stop the process using port {} or choose another port", addr.port()),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    fn env_with<'a>(vars: &'a [&'a str]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| vars.contains(&name).then(|| "value".to_string())
+    }
+
+    #[test]
+    fn test_api_key_check_follows_provider() {
+        let mut config = Config::default();
+        assert_eq!(check_api_key(&config, env_with(&["XAI_API_KEY"])).status, CheckStatus::Ok);
+
+        config.language_model.provider = Some(LLMProvider::DeepSeek);
+        let check = check_api_key(&config, env_with(&["XAI_API_KEY"]));
+        assert_eq!(check.status, CheckStatus::Error);
+        assert!(check.message.contains("DEEPSEEK_API_KEY"));
+    }
+
+    #[test]
+    fn test_meeting_credentials_lists_missing_variables() {
+        let mut config = Config::default();
+        config.calendar.meeting_provider = MeetingService::Teams;
+        let check = check_meeting_credentials(&config, env_with(&["MS_TENANT_ID"]));
+        assert_eq!(check.status, CheckStatus::Warning);
+        assert!(check.message.contains("MS_CLIENT_ID, MS_CLIENT_SECRET, MS_TEAMS_USER_ID"));
+    }
+
+    #[test]
+    fn test_calendar_checks() {
+        let mut config = Config::default();
+        config.calendar.default_calendar = Some("Work".to_string());
+
+        let checks = check_calendars(&config, &Ok(vec!["Home".into(), "work".into()]));
+        assert!(checks.iter().all(|c| c.status == CheckStatus::Ok));
+
+        let checks = check_calendars(&config, &Ok(vec!["Home".into()]));
+        assert_eq!(checks[1].status, CheckStatus::Error);
+        assert_eq!(checks[1].hint.as_deref(), Some("Choose one of: Home"));
+
+        let checks = check_calendars(&config, &Err(anyhow!("not authorized")));
+        assert_eq!(checks[0].status, CheckStatus::Error);
+    }
+
+    #[test]
+    fn test_report_readiness_and_table() {
+        let report = StartupReport {
+            checked_at: String::new(),
+            checks: vec![
+                StartupCheck::ok(CHECK_API_KEY, "XAI_API_KEY is set"),
+                StartupCheck::warning(
+                    CHECK_MEETING_CREDENTIALS,
+                    "Missing ZOOM_CLIENT_ID",
+                    "Set it",
+                ),
+            ],
+        };
+        assert!(report.ready());
+        assert!(report.passed(CHECK_API_KEY));
+        assert!(!report.passed(CHECK_MEETING_CREDENTIALS));
+
+        let table = report.summary_table();
+        assert!(table.contains("api_key"));
+        assert!(table.contains("warning"));
+        assert!(table.contains("→ Set it"));
+    }
+}
@@ -4,41 +4,89 @@
 // allowing real-time commands and notifications.
 
 use axum::{
-    extract::WebSocketUpgrade,
     extract::ws::{Message, WebSocket},
+    extract::{State, WebSocketUpgrade},
     response::IntoResponse,
 };
-use clap::Parser; // Add this missing import for try_parse_from
-use log::{debug, error, info};
 use serde::Serialize;
-use std::time::Duration;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
 use tokio::time::interval;
+use tracing::{Instrument, debug, error, info, info_span, warn};
 use uuid::Uuid;
 
-use crate::calendar::{EventConfig, create_event, import_csv_events, import_ics_events};
-use crate::cli;
+use crate::calendar::EventConfigBuilder;
+use crate::command_bus::{Command, dispatch, parse_command_string};
 use crate::command_processor::CommandArgs;
+use crate::commands::{CreateEvent, event_builder};
+use crate::config::WebSocketLimitsConfig;
 use crate::parser;
-use std::path::Path;
+use crate::parser::clarify::{Clarification, is_cancel};
+use crate::tasks::{self, Task};
 
+use super::dedupe::RequestDeduplicator;
+use super::limits::RateLimiter;
 use super::models::{
-    SwiftChatMessage, SwiftErrorResponse, SwiftEventData, SwiftEventResponse, SwiftMessage,
+    ApiState, SwiftChatMessage, SwiftClarificationMessage, SwiftErrorResponse, SwiftEventData,
+    SwiftEventResponse, SwiftMessage, SwiftProgressMessage, SwiftTaskMessage,
 };
 
+/// A question sent to the client, answered by its next chat message
+struct PendingQuestion {
+    clarification: Clarification,
+    /// Idempotency key of the message the question is about
+    idempotency_key: Option<String>,
+}
+
 /// WebSocket handler for chat interface
 ///
 /// Upgrades an HTTP request to a WebSocket connection
-pub async fn websocket_handler(ws: WebSocketUpgrade) -> impl IntoResponse {
+#[utoipa::path(
+    get,
+    path = "/chat",
+    tag = "websocket",
+    responses(
+        (status = 101, description = "Switched to WebSocket; messages are `SwiftMessage` in, \
+            `SwiftChatMessage`, `SwiftProgressMessage`, `SwiftClarificationMessage`, \
+            `SwiftTaskMessage`, `SwiftEventResponse` and `SwiftErrorResponse` out")
+    )
+)]
+pub async fn websocket_handler(
+    State(state): State<Arc<ApiState>>,
+    ws: WebSocketUpgrade,
+) -> impl IntoResponse {
     info!("New WebSocket upgrade request received");
-    ws.on_upgrade(handle_socket)
+    let limits = state.limits.websocket.clone();
+    let client = crate::audit::current_actor();
+    // Larger messages close the connection
+    ws.max_message_size(limits.max_message_bytes)
+        .max_frame_size(limits.max_message_bytes)
+        .on_upgrade(move |socket| {
+            let connection_id = Uuid::new_v4();
+            // Carried into the spans of the commands run on this connection
+            let span = info_span!("websocket", connection_id = %connection_id);
+            let actor = format!("websocket {} ({})", connection_id, client);
+            let connection = handle_socket(socket, limits, connection_id);
+            crate::webhooks::with_source("websocket", crate::audit::with_actor(actor, connection))
+                .instrument(span)
+        })
 }
 
 /// Handle an active WebSocket connection
 ///
 /// Processes messages and maintains the connection with the client
-async fn handle_socket(mut socket: WebSocket) {
-    let connection_id = Uuid::new_v4();
+async fn handle_socket(mut socket: WebSocket, limits: WebSocketLimitsConfig, connection_id: Uuid) {
     info!("WebSocket[{}]: Connection established", connection_id);
+    let _connection = crate::metrics::websocket_connected();
+    let mut dedupe = RequestDeduplicator::default();
+    let rate_limiter = RateLimiter::new(limits.messages_per_minute, limits.messages_per_minute);
+    // Tasks this client started or asked to watch, whose progress is sent to it
+    let mut watched: HashSet<String> = HashSet::new();
+    // A question waiting for the client's answer
+    let mut pending: Option<PendingQuestion> = None;
+    let mut task_updates = tasks::subscribe();
 
     // Send a welcome message
     let welcome_message = SwiftChatMessage {
@@ -68,14 +116,43 @@ async fn handle_socket(mut socket: WebSocket) {
                 }
             }
 
+            // Forward the progress of watched tasks
+            update = task_updates.recv() => match update {
+                Ok(task) if watched.contains(&task.id) => {
+                    if task.finished_at.is_some() {
+                        watched.remove(&task.id);
+                    }
+                    send_response(&mut socket, task_message(&task)).await;
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(missed)) => {
+                    warn!("WebSocket[{}]: Missed {} task updates", connection_id, missed);
+                }
+                Err(RecvError::Closed) => {}
+            },
+
             // Handle incoming messages
             msg_result = socket.recv() => {
+                // Drop messages over the rate limit, telling the client when to try again
+                let limited = match &msg_result {
+                    Some(Ok(Message::Text(_) | Message::Binary(_))) => {
+                        rate_limiter.check("", Instant::now()).err()
+                    }
+                    _ => None,
+                };
+                if let Some(retry_after) = limited {
+                    warn!("WebSocket[{}]: Message over the rate limit dropped", connection_id);
+                    let seconds = retry_after.as_secs_f64().ceil() as u64;
+                    let message = format!("Too many messages, try again in {} seconds", seconds);
+                    send_error_response(&mut socket, &message).await;
+                    continue;
+                }
                 match msg_result {
                     Some(Ok(Message::Text(text))) => {
                         info!("WebSocket[{}]: Received text message ({} bytes)", connection_id, text.len());
                         debug!("WebSocket[{}]: Message content: {}", connection_id, text);
 
-                        process_message(connection_id, text, &mut socket).await;
+                        process_message(connection_id, text, &mut socket, &mut dedupe, &mut pending, &mut watched).await;
                     },
                     Some(Ok(Message::Binary(bin))) => {
                         info!("WebSocket[{}]: Received binary message of {} bytes", connection_id, bin.len());
@@ -83,7 +160,7 @@ async fn handle_socket(mut socket: WebSocket) {
                         match String::from_utf8(bin) {
                             Ok(text) => {
                                 debug!("WebSocket[{}]: Decoded binary content: {}", connection_id, text);
-                                process_message(connection_id, text, &mut socket).await;
+                                process_message(connection_id, text, &mut socket, &mut dedupe, &mut pending, &mut watched).await;
                             },
                             Err(e) => {
                                 error!("WebSocket[{}]: Failed to decode binary as UTF-8: {}", connection_id, e);
@@ -132,9 +209,37 @@ async fn handle_socket(mut socket: WebSocket) {
 /// Process received WebSocket messages
 ///
 /// Handles both natural language commands and structured JSON messages
-async fn process_message(connection_id: Uuid, message: String, socket: &mut WebSocket) {
+async fn process_message(
+    connection_id: Uuid,
+    message: String,
+    socket: &mut WebSocket,
+    dedupe: &mut RequestDeduplicator,
+    pending: &mut Option<PendingQuestion>,
+    watched: &mut HashSet<String>,
+) {
     match serde_json::from_str::<SwiftMessage>(&message) {
         Ok(swift_message) => {
+            // While a question is open, the next chat message answers it; answers like "1"
+            // repeat, so they skip the duplicate check
+            if let Some(content) = &swift_message.content {
+                if let Some(question) = pending.take() {
+                    answer_question(connection_id, content, question, socket, pending, watched)
+                        .await;
+                    return;
+                }
+            }
+
+            let idempotency_key = swift_message.idempotency_key.clone();
+            if !dedupe.check(
+                &message_fingerprint_source(&swift_message),
+                idempotency_key.as_deref(),
+                Instant::now(),
+            ) {
+                info!("WebSocket[{}]: Ignoring duplicate request", connection_id);
+                send_duplicate_response(socket, idempotency_key.as_deref()).await;
+                return;
+            }
+
             // Check if it's a chat message with natural language command
             if let Some(content) = swift_message.content {
                 info!("WebSocket[{}]: Received text command: {}", connection_id, content);
@@ -159,15 +264,35 @@ async fn process_message(connection_id: Uuid, message: String, socket: &mut WebS
                         }
                     };
 
-                    // Parse the input using the configured parser
-                    match parser.parse_input(&content).await {
+                    // Parse the input using the configured parser, forwarding its progress
+                    match parse_with_progress(connection_id, parser.as_ref(), &content, socket)
+                        .await
+                    {
                         Ok(parser::ParseResult::CommandString(command)) => {
                             info!("WebSocket[{}]: Parsed command: {}", connection_id, command);
-                            handle_parsed_command(connection_id, command, socket).await;
+                            handle_parsed_command(
+                                connection_id,
+                                command,
+                                idempotency_key.as_deref(),
+                                socket,
+                                watched,
+                            )
+                            .await;
+                        }
+                        Ok(parser::ParseResult::NeedsClarification(clarification)) => {
+                            info!(
+                                "WebSocket[{}]: Asking before running {}",
+                                connection_id, clarification.command
+                            );
+                            ask_question(socket, pending, clarification, idempotency_key).await;
                         }
                         Ok(parser::ParseResult::StructuredCommand(args)) => {
                             info!("WebSocket[{}]: Got structured command directly", connection_id);
-                            handle_websocket_command(connection_id, args, socket).await;
+                            let args = match idempotency_key.as_deref() {
+                                Some(key) => args.with_idempotency_key(key),
+                                None => args,
+                            };
+                            handle_websocket_command(connection_id, args, socket, watched).await;
                         }
                         Err(e) => {
                             error!("WebSocket[{}]: Failed to parse command: {}", connection_id, e);
@@ -196,7 +321,15 @@ async fn process_message(connection_id: Uuid, message: String, socket: &mut WebS
             {
                 // Check if it's an event creation request
                 if message_type == "create" && action == "event" {
-                    handle_event_creation(connection_id, data.clone(), socket).await;
+                    handle_event_creation(
+                        connection_id,
+                        data.clone(),
+                        idempotency_key.as_deref(),
+                        socket,
+                    )
+                    .await;
+                } else if message_type == "watch" && action == "task" {
+                    watch_task(data, socket, watched).await;
                 } else {
                     // If we got here, it's an unknown message type
                     error!("WebSocket[{}]: Unknown message format", connection_id);
@@ -212,103 +345,208 @@ async fn process_message(connection_id: Uuid, message: String, socket: &mut WebS
     }
 }
 
+/// Parse natural language input, sending the parser's progress to the client meanwhile
+///
+/// Language model parsers stream their answer, so the client can show what the model is
+/// doing before the final command result arrives.
+async fn parse_with_progress(
+    connection_id: Uuid,
+    parser: &(dyn parser::Parser + Send + Sync),
+    content: &str,
+    socket: &mut WebSocket,
+) -> anyhow::Result<parser::ParseResult> {
+    let (progress_tx, mut progress_rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut parsing = parser.parse_input_with_progress(content, progress_tx);
+
+    let result = loop {
+        tokio::select! {
+            result = &mut parsing => break result,
+            Some(update) = progress_rx.recv() => {
+                debug!("WebSocket[{}]: Parse progress: {:?}", connection_id, update);
+                send_response(socket, progress_message(update)).await;
+            }
+        }
+    };
+
+    // Updates sent right before the parser finished
+    while let Ok(update) = progress_rx.try_recv() {
+        send_response(socket, progress_message(update)).await;
+    }
+    result
+}
+
+fn progress_message(update: parser::ParseProgress) -> SwiftProgressMessage {
+    let (stage, content) = match update {
+        parser::ParseProgress::Status(text) => ("status", text),
+        parser::ParseProgress::Thinking(text) => ("thinking", text),
+        parser::ParseProgress::Partial(text) => ("partial", text),
+    };
+    SwiftProgressMessage {
+        message_type: "progress".to_string(),
+        stage: stage.to_string(),
+        content,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }
+}
+
 /// Handle event creation from structured WebSocket messages
 async fn handle_event_creation(
     connection_id: Uuid,
     data: serde_json::Value,
+    idempotency_key: Option<&str>,
     socket: &mut WebSocket,
 ) {
     info!("WebSocket[{}]: Received event creation request", connection_id);
-    match serde_json::from_value::<SwiftEventData>(data) {
-        Ok(event_data) => {
-            info!("WebSocket[{}]: Creating event: {}", connection_id, event_data.title);
-
-            // Create EventConfig
-            let mut event_config =
-                EventConfig::new(&event_data.title, &event_data.date, &event_data.start_time);
-
-            event_config.end_time = Some(event_data.end_time);
-
-            if let Some(location) = event_data.location {
-                event_config.location = Some(location);
-            }
+    let event_data = match serde_json::from_value::<SwiftEventData>(data) {
+        Ok(event_data) => event_data,
+        Err(e) => {
+            error!("WebSocket[{}]: Failed to parse event data: {}", connection_id, e);
+            send_error_response(socket, &format!("Invalid event data format: {}", e)).await;
+            return;
+        }
+    };
+    info!("WebSocket[{}]: Creating event: {}", connection_id, event_data.title);
+
+    let config = event_builder(
+        &event_data.title,
+        &event_data.date,
+        &event_data.start_time,
+        Some(event_data.end_time.as_str()),
+    )
+    .and_then(|builder| {
+        builder
+            .maybe(event_data.location, EventConfigBuilder::location)
+            .maybe(event_data.description, EventConfigBuilder::description)
+            .build()
+            .map_err(anyhow::Error::from)
+    });
+    let event = match config {
+        Ok(config) => CreateEvent::new(config),
+        Err(e) => {
+            error!("WebSocket[{}]: Invalid event: {}", connection_id, e);
+            send_error_response(socket, &e.to_string()).await;
+            return;
+        }
+    };
+    match dispatch(Command::CreateEvent(event), idempotency_key).await {
+        Ok(Some(_)) => {
+            info!("WebSocket[{}]: Event created successfully", connection_id);
+            let response = SwiftEventResponse {
+                message_type: "event".to_string(),
+                status: "success".to_string(),
+                message: "Event created successfully".to_string(),
+                event_id: Some(Uuid::new_v4().to_string()),
+            };
+            send_response(socket, response).await;
+        }
+        Ok(None) => {
+            info!("WebSocket[{}]: Idempotency key already used", connection_id);
+            send_duplicate_response(socket, idempotency_key).await;
+        }
+        Err(e) => {
+            error!("WebSocket[{}]: Failed to create event: {}", connection_id, e);
+            let response = SwiftEventResponse {
+                message_type: "event".to_string(),
+                status: "error".to_string(),
+                message: format!("Failed to create event: {}", e),
+                event_id: None,
+            };
+            send_response(socket, response).await;
+        }
+    }
+}
 
-            if let Some(description) = event_data.description {
-                event_config.description = Some(description);
-            }
+/// Send a question about a parsed command and wait for the answer
+async fn ask_question(
+    socket: &mut WebSocket,
+    pending: &mut Option<PendingQuestion>,
+    clarification: Clarification,
+    idempotency_key: Option<String>,
+) {
+    let message = SwiftClarificationMessage {
+        message_type: "clarification".to_string(),
+        question: clarification.question.clone(),
+        options: clarification.options.iter().map(|option| option.label.clone()).collect(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    };
+    send_response(socket, message).await;
+    *pending = Some(PendingQuestion { clarification, idempotency_key });
+}
 
-            // Create the event
-            match create_event(event_config).await {
-                Ok(_) => {
-                    info!("WebSocket[{}]: Event created successfully", connection_id);
-                    let response = SwiftEventResponse {
-                        message_type: "event".to_string(),
-                        status: "success".to_string(),
-                        message: "Event created successfully".to_string(),
-                        event_id: Some(Uuid::new_v4().to_string()),
-                    };
-                    send_response(socket, response).await;
-                }
-                Err(e) => {
-                    error!("WebSocket[{}]: Failed to create event: {}", connection_id, e);
-                    let response = SwiftEventResponse {
-                        message_type: "event".to_string(),
-                        status: "error".to_string(),
-                        message: format!("Failed to create event: {}", e),
-                        event_id: None,
-                    };
-                    send_response(socket, response).await;
-                }
+/// Merge the client's answer into the command, then run it or ask what is still open
+async fn answer_question(
+    connection_id: Uuid,
+    reply: &str,
+    question: PendingQuestion,
+    socket: &mut WebSocket,
+    pending: &mut Option<PendingQuestion>,
+    watched: &mut HashSet<String>,
+) {
+    if is_cancel(reply) {
+        info!("WebSocket[{}]: Question cancelled", connection_id);
+        let response = SwiftChatMessage {
+            sender: "ducktape".to_string(),
+            content: "Cancelled.".to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            message_type: "chat".to_string(),
+        };
+        send_response(socket, response).await;
+        return;
+    }
+    let PendingQuestion { clarification, idempotency_key } = question;
+    let now = chrono::Local::now().naive_local();
+    match clarification.answer(reply, now.date()) {
+        Ok(command) => match clarification.follow_up(&command, now).await {
+            Some(next) => ask_question(socket, pending, next, idempotency_key).await,
+            None => {
+                info!("WebSocket[{}]: Answered command: {}", connection_id, command);
+                handle_parsed_command(
+                    connection_id,
+                    command,
+                    idempotency_key.as_deref(),
+                    socket,
+                    watched,
+                )
+                .await;
             }
-        }
+        },
         Err(e) => {
-            error!("WebSocket[{}]: Failed to parse event data: {}", connection_id, e);
-            send_error_response(socket, &format!("Invalid event data format: {}", e)).await;
+            // Ask the same question again
+            send_error_response(socket, &e.to_string()).await;
+            ask_question(socket, pending, clarification, idempotency_key).await;
         }
     }
 }
 
 /// Handle parsed commands from natural language input
-async fn handle_parsed_command(connection_id: Uuid, command: String, socket: &mut WebSocket) {
-    // Parse the command into arguments using Clap first
+async fn handle_parsed_command(
+    connection_id: Uuid,
+    command: String,
+    idempotency_key: Option<&str>,
+    socket: &mut WebSocket,
+    watched: &mut HashSet<String>,
+) {
     match parse_command_string(&command) {
         Ok(args) => {
-            // Log the parsed args to help debug
+            let args = match idempotency_key {
+                Some(key) => args.with_idempotency_key(key),
+                None => args,
+            };
             info!(
                 "WebSocket[{}]: Parsed args: command={}, args={:?}, flags={:?}",
                 connection_id, args.command, args.args, args.flags
             );
-
-            handle_websocket_command(connection_id, args, socket).await;
+            handle_websocket_command(connection_id, args, socket, watched).await;
         }
-        Err(_) => {
-            // Fall back to legacy parser if Clap fails
-            match CommandArgs::parse(&command) {
-                Ok(args) => {
-                    info!(
-                        "WebSocket[{}]: Parsed args (legacy): command={}, args={:?}, flags={:?}",
-                        connection_id, args.command, args.args, args.flags
-                    );
-
-                    handle_websocket_command(connection_id, args, socket).await;
-                }
-                Err(e) => {
-                    error!(
-                        "WebSocket[{}]: Failed to parse command arguments: {}",
-                        connection_id, e
-                    );
-                    let response = SwiftChatMessage {
-                        sender: "ducktape".to_string(),
-                        content: format!(
-                            "❌ Failed to parse command: {}. Raw command was: {}",
-                            e, command
-                        ),
-                        timestamp: chrono::Utc::now().to_rfc3339(),
-                        message_type: "error".to_string(),
-                    };
-                    send_response(socket, response).await;
-                }
-            }
+        Err(e) => {
+            error!("WebSocket[{}]: Failed to parse command arguments: {}", connection_id, e);
+            let response = SwiftChatMessage {
+                sender: "ducktape".to_string(),
+                content: format!("❌ Failed to parse command: {}. Raw command was: {}", e, command),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                message_type: "error".to_string(),
+            };
+            send_response(socket, response).await;
         }
     }
 }
@@ -374,6 +612,34 @@ async fn send_response<T: Serialize>(socket: &mut WebSocket, response: T) {
     }
 }
 
+/// Tell the client that a request was recognized as a duplicate and not executed again
+async fn send_duplicate_response(socket: &mut WebSocket, idempotency_key: Option<&str>) {
+    let content = match idempotency_key {
+        Some(key) => format!("Request with idempotency key '{}' was already processed", key),
+        None => "Duplicate request ignored; an identical request was just processed".to_string(),
+    };
+    let response = SwiftChatMessage {
+        sender: "ducktape".to_string(),
+        content,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        message_type: "duplicate".to_string(),
+    };
+    send_response(socket, response).await;
+}
+
+/// Text used to recognize repeated messages: the content, or the structured payload
+fn message_fingerprint_source(message: &SwiftMessage) -> String {
+    match &message.content {
+        Some(content) => content.clone(),
+        None => format!(
+            "{}|{}|{}",
+            message.message_type.as_deref().unwrap_or_default(),
+            message.action.as_deref().unwrap_or_default(),
+            message.data.as_ref().map(|d| d.to_string()).unwrap_or_default()
+        ),
+    }
+}
+
 /// Send an error response to the WebSocket client
 async fn send_error_response(socket: &mut WebSocket, message: &str) {
     let error_response =
@@ -391,268 +657,98 @@ async fn send_error_response(socket: &mut WebSocket, message: &str) {
     }
 }
 
-/// Helper function to parse commands using Clap instead of deprecated CommandArgs::parse
-fn parse_command_string(input: &str) -> Result<CommandArgs, anyhow::Error> {
-    use anyhow::anyhow;
-
-    // Format the input into argv style for clap
-    let args = shell_words::split(input).map_err(|e| anyhow!("Failed to parse command: {}", e))?;
-
-    // Check if we have any arguments
-    if args.is_empty() {
-        return Err(anyhow!("Empty command"));
+fn task_message(task: &Task) -> SwiftTaskMessage {
+    SwiftTaskMessage {
+        message_type: "task".to_string(),
+        percent: task.percent(),
+        task: task.clone(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
     }
-
-    // Parse using Clap - corrected the parsing approach
-    let cli = match cli::Cli::try_parse_from(args) {
-        Ok(cli) => cli,
-        Err(e) => {
-            // This is likely not a structured command but a natural language input
-            return Err(anyhow!("Not a structured command: {}", e));
-        }
-    };
-
-    // Convert from Clap command to CommandArgs
-    cli::convert_to_command_args(&cli)
-        .ok_or_else(|| anyhow!("Failed to convert parsed command to CommandArgs"))
 }
 
-/// Function to handle websocket commands
-async fn handle_websocket_command(connection_id: Uuid, args: CommandArgs, socket: &mut WebSocket) {
-    if args.command == "calendar" {
-        // Handle different calendar subcommands
-        match args.args.get(0).map(|s| s.as_str()) {
-            Some("create") => {
-                handle_calendar_create(connection_id, args, socket).await;
-            }
-            Some("import") => {
-                handle_calendar_import(connection_id, args, socket).await;
-            }
-            Some(cmd) => {
-                // Handle other calendar commands (list, delete, etc.)
-                let response = SwiftChatMessage {
-                    sender: "ducktape".to_string(),
-                    content: format!(
-                        "Command '{}' parsed but not yet implemented in WebSocket server",
-                        cmd
-                    ),
-                    timestamp: chrono::Utc::now().to_rfc3339(),
-                    message_type: "chat".to_string(),
-                };
-                send_response(socket, response).await;
-            }
-            None => {
-                let response = SwiftChatMessage {
-                    sender: "ducktape".to_string(),
-                    content: "❌ Invalid calendar command format".to_string(),
-                    timestamp: chrono::Utc::now().to_rfc3339(),
-                    message_type: "error".to_string(),
-                };
-                send_response(socket, response).await;
+/// Send the progress of a task to this client from now on, starting with where it is
+async fn watch_task(
+    data: &serde_json::Value,
+    socket: &mut WebSocket,
+    watched: &mut HashSet<String>,
+) {
+    let Some(id) = data.get("task_id").and_then(|id| id.as_str()) else {
+        send_error_response(socket, "Missing task_id").await;
+        return;
+    };
+    match tasks::get(id) {
+        Some(task) => {
+            if task.finished_at.is_none() {
+                watched.insert(task.id.clone());
             }
+            send_response(socket, task_message(&task)).await;
         }
-    } else {
-        // For other command types (todo, notes, etc.)
-        let response = SwiftChatMessage {
-            sender: "ducktape".to_string(),
-            content: format!(
-                "Command '{}' parsed but not yet implemented in WebSocket server",
-                args.command
-            ),
-            timestamp: chrono::Utc::now().to_rfc3339(),
-            message_type: "chat".to_string(),
-        };
-        send_response(socket, response).await;
+        None => send_error_response(socket, &format!("Task {} not found", id)).await,
     }
 }
 
-/// Handle calendar create command
-async fn handle_calendar_create(connection_id: Uuid, args: CommandArgs, socket: &mut WebSocket) {
-    // Skip "create" (which is args[0]) and process the rest of the args
-    if args.args.len() >= 4 {
-        // Needs at least title, date, start_time
-        let title = &args.args[1]; // "title" is the second arg
-        let date = &args.args[2]; // Date is the third arg
-        let start_time = &args.args[3]; // Start time is the fourth arg
-
-        // End time and calendar are optional
-        let end_time = args.args.get(4).map(|s| s.as_str());
-        let calendar = args.args.get(5).map(|s| s.as_str());
-
-        info!(
-            "WebSocket[{}]: Creating event: {} on {} at {}",
-            connection_id,
-            title.trim_matches('"'),
-            date,
-            start_time
-        );
-
-        // Create the event config
-        let mut config = crate::calendar::EventConfig::new(title, date, start_time);
-
-        // Set optional fields
-        if let Some(end) = end_time {
-            config.end_time = Some(end.to_string());
-        }
-
-        if let Some(cal) = calendar {
-            let cal_str = cal.trim_matches('"');
-            config.calendars = vec![cal_str.to_string()];
-        }
-
-        // Handle the email flag
-        if let Some(Some(emails_str)) = args.flags.get("email") {
-            let emails: Vec<String> =
-                emails_str.split(',').map(|e| e.trim().trim_matches('"').to_string()).collect();
-
-            if !emails.is_empty() {
-                info!("WebSocket[{}]: Adding email attendees: {:?}", connection_id, emails);
-                config.emails = emails;
+/// Run a command through the command bus and tell the client what it did
+///
+/// Imports run as tasks, whose progress is sent to the client as it goes.
+async fn handle_websocket_command(
+    connection_id: Uuid,
+    args: CommandArgs,
+    socket: &mut WebSocket,
+    watched: &mut HashSet<String>,
+) {
+    let idempotency_key = args.idempotency_key().map(str::to_string);
+    let (content, message_type) = match Command::from_args(args) {
+        Ok(Command::Run(args)) => (
+            format!(
+                "Command '{}' parsed but not yet implemented in WebSocket server",
+                args.command
+            ),
+            "chat",
+        ),
+        Ok(command @ Command::ImportEvents(_)) => {
+            let id = tasks::spawn(
+                "calendar import",
+                crate::webhooks::with_source(
+                    "websocket",
+                    crate::audit::with_actor(crate::audit::current_actor(), async move {
+                        match dispatch(command, idempotency_key.as_deref()).await? {
+                            Some(outcome) => Ok(outcome.message()),
+                            None => Ok("Skipped: the idempotency key was already used".to_string()),
+                        }
+                    }),
+                ),
+            );
+            info!("WebSocket[{}]: Started import task {}", connection_id, id);
+            if let Some(task) = tasks::get(&id) {
+                if task.finished_at.is_none() {
+                    watched.insert(id);
+                }
+                send_response(socket, task_message(&task)).await;
             }
+            return;
         }
-
-        // Handle the zoom flag
-        if args.flags.contains_key("zoom") {
-            info!("WebSocket[{}]: Enabling Zoom meeting creation", connection_id);
-            config.create_zoom_meeting = true;
-        }
-
-        // Execute the calendar creation
-        match crate::calendar::create_event(config).await {
-            Ok(_) => {
-                info!("WebSocket[{}]: Event created successfully", connection_id);
-                let response = SwiftChatMessage {
-                    sender: "ducktape".to_string(),
-                    content: format!(
-                        "✅ Created event \"{}\" for {} at {}",
-                        title.trim_matches('"'),
-                        date,
-                        start_time
-                    ),
-                    timestamp: chrono::Utc::now().to_rfc3339(),
-                    message_type: "chat".to_string(),
-                };
-                send_response(socket, response).await;
+        Ok(command) => match dispatch(command, idempotency_key.as_deref()).await {
+            Ok(Some(outcome)) => {
+                info!("WebSocket[{}]: {}", connection_id, outcome.message());
+                (format!("✅ {}", outcome.message()), "chat")
+            }
+            Ok(None) => {
+                info!("WebSocket[{}]: Idempotency key already used", connection_id);
+                send_duplicate_response(socket, idempotency_key.as_deref()).await;
+                return;
             }
             Err(e) => {
-                error!("WebSocket[{}]: Failed to create event: {}", connection_id, e);
-                let response = SwiftChatMessage {
-                    sender: "ducktape".to_string(),
-                    content: format!("❌ Failed to create event: {}", e),
-                    timestamp: chrono::Utc::now().to_rfc3339(),
-                    message_type: "error".to_string(),
-                };
-                send_response(socket, response).await;
+                error!("WebSocket[{}]: Command failed: {}", connection_id, e);
+                (format!("❌ {}", e), "error")
             }
-        }
-    } else {
-        error!("WebSocket[{}]: Invalid command format - not enough arguments", connection_id);
-        let response = SwiftChatMessage {
-            sender: "ducktape".to_string(),
-            content: "❌ Invalid command format".to_string(),
-            timestamp: chrono::Utc::now().to_rfc3339(),
-            message_type: "error".to_string(),
-        };
-        send_response(socket, response).await;
-    }
-}
-
-/// Handle calendar import command
-async fn handle_calendar_import(connection_id: Uuid, args: CommandArgs, socket: &mut WebSocket) {
-    info!("WebSocket[{}]: Processing calendar import command", connection_id);
-
-    if args.args.len() < 2 {
-        let response = SwiftChatMessage {
-            sender: "ducktape".to_string(),
-            content: "❌ Usage: calendar import \"<file_path>\" [--format csv|ics] [--calendar \"<calendar_name>\"]".to_string(),
-            timestamp: chrono::Utc::now().to_rfc3339(),
-            message_type: "error".to_string(),
-        };
-        send_response(socket, response).await;
-        return;
-    }
-
-    // Get the file path and expand it if needed
-    let mut file_path_str = args.args[1].clone();
-    file_path_str = file_path_str.trim_matches('"').to_string();
-
-    // Expand tilde to home directory
-    if file_path_str.starts_with('~') {
-        if let Some(home_dir) = dirs::home_dir() {
-            file_path_str = file_path_str.replacen("~", home_dir.to_string_lossy().as_ref(), 1);
-        }
-    }
-
-    let file_path = Path::new(&file_path_str);
-
-    if !file_path.exists() {
-        let response = SwiftChatMessage {
-            sender: "ducktape".to_string(),
-            content: format!("❌ File not found: {}", file_path_str),
-            timestamp: chrono::Utc::now().to_rfc3339(),
-            message_type: "error".to_string(),
-        };
-        send_response(socket, response).await;
-        return;
-    }
-
-    // Get format from --format flag, default to csv
-    let format = args
-        .flags
-        .get("format")
-        .and_then(|f| f.as_ref())
-        .map(|f| f.to_lowercase())
-        .unwrap_or_else(|| "csv".to_string());
-
-    if !["csv", "ics"].contains(&format.as_str()) {
-        let response = SwiftChatMessage {
-            sender: "ducktape".to_string(),
-            content: "❌ Unsupported format. Use --format csv or --format ics".to_string(),
-            timestamp: chrono::Utc::now().to_rfc3339(), // Fixed: removed .to
-            message_type: "error".to_string(),
-        };
-        send_response(socket, response).await;
-        return;
-    }
-
-    // Get target calendar if specified
-    let calendar = args
-        .flags
-        .get("calendar")
-        .and_then(|c| c.as_ref())
-        .map(|c| c.trim_matches('"').to_string());
-
-    info!(
-        "WebSocket[{}]: Importing {} file: {} to calendar: {:?}",
-        connection_id, format, file_path_str, calendar
-    );
-
-    // Call the appropriate import function
-    let result = match format.as_str() {
-        "csv" => import_csv_events(file_path, calendar).await,
-        "ics" => import_ics_events(file_path, calendar).await,
-        _ => unreachable!(),
+        },
+        Err(e) => (format!("❌ {}", e), "error"),
     };
-
-    match result {
-        Ok(_) => {
-            let response = SwiftChatMessage {
-                sender: "ducktape".to_string(),
-                content: format!("✅ Successfully imported events from {}", file_path_str),
-                timestamp: chrono::Utc::now().to_rfc3339(),
-                message_type: "chat".to_string(),
-            };
-            send_response(socket, response).await;
-        }
-        Err(e) => {
-            error!("WebSocket[{}]: Failed to import events: {}", connection_id, e);
-            let response = SwiftChatMessage {
-                sender: "ducktape".to_string(),
-                content: format!("❌ Failed to import events: {}", e),
-                timestamp: chrono::Utc::now().to_rfc3339(), // Fixed from .to.rfc3339()
-                message_type: "error".to_string(),
-            };
-            send_response(socket, response).await;
-        }
-    }
+    let response = SwiftChatMessage {
+        sender: "ducktape".to_string(),
+        content,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        message_type: message_type.to_string(),
+    };
+    send_response(socket, response).await;
 }
@@ -1,25 +1,36 @@
-use crate::command_processor::{CommandArgs, CommandProcessor};
+use crate::app_state::SharedState;
+use crate::command_bus::{self, Command, CommandBus, is_usage_error};
+use crate::command_processor::CommandArgs;
 use crate::config::{Config, LLMProvider};
-use crate::parser::{Parser, ParserFactory};
+use crate::palette::{PALETTE_PREFIX, PaletteTrigger, run_palette};
+use crate::parser::clarify::{Clarification, is_cancel};
+use crate::parser::trace::{self, ParseTrace, TraceLevel};
+use crate::parser::{ParseResult, Parser, ParserFactory};
 use anyhow::{Result, anyhow};
 use clap::Parser as ClapParser;
 use rustyline::DefaultEditor;
+use std::time::Instant;
+
+/// Shows the full trace of the last natural language input
+const LAST_TRACE_COMMAND: &str = ":last";
+/// Provider named in the trace of inputs recognized without a parser
+const LOCAL_RULES: &str = "local rules";
 
 pub struct Application {
-    command_processor: CommandProcessor,
+    bus: CommandBus,
 }
 
 impl Application {
     pub fn new() -> Self {
-        Self { command_processor: CommandProcessor::new() }
+        Self { bus: CommandBus::new() }
     }
 
     pub async fn run(&self) -> Result<()> {
-        log::info!("Starting DuckTape Terminal");
+        tracing::info!("Starting DuckTape Terminal");
         let config = Config::load()?;
 
-        let use_natural_language = config.language_model.provider.is_some();
-        log::debug!(
+        let use_natural_language = config.uses_language_model();
+        tracing::debug!(
             "Provider: {:?}, use_natural_language: {}",
             config.language_model.provider,
             use_natural_language
@@ -27,54 +38,28 @@ impl Application {
 
         match Config::load()?.language_model.provider {
             Some(LLMProvider::Grok) => {
-                log::info!("Using Grok for natural language processing");
+                tracing::info!("Using Grok for natural language processing");
             }
             Some(LLMProvider::DeepSeek) => {
-                log::info!("Using DeepSeek for natural language processing");
+                tracing::info!("Using DeepSeek for natural language processing");
             }
             None => {
-                log::info!("Terminal Mode enabled (no API key required)");
+                tracing::info!("Terminal Mode enabled (no API key required)");
             }
         }
 
-        // Start the API server in a background thread
-        log::info!("Starting API server on port 3000");
-        let config_clone = config.clone();
+        // Start the API server in a background thread, sharing state with the terminal
+        tracing::info!("Starting API server on port 3000");
+        let shared = SharedState::global().clone();
         let api_handle = tokio::spawn(async move {
-            if let Err(e) =
-                crate::api_server::start_api_server(config_clone, "127.0.0.1:3000").await
-            {
-                log::error!("API server error: {:?}", e);
+            if let Err(e) = crate::api_server::start_api_server(shared, "127.0.0.1:3000").await {
+                tracing::error!("API server error: {:?}", e);
             }
         });
 
-        let mut rl = DefaultEditor::new()?;
-
         println!("Welcome to DuckTape Terminal! Type 'help' for commands.");
-        let prompt = "🦆 ";
-
-        loop {
-            match rl.readline(prompt) {
-                Ok(line) => {
-                    let _ = rl.add_history_entry(line.as_str());
-                    if let Err(err) = self.process_input(&line, use_natural_language).await {
-                        log::error!("Failed to process command: {:?}", err);
-                    }
-                }
-                Err(rustyline::error::ReadlineError::Interrupted) => {
-                    println!("CTRL-C");
-                    break;
-                }
-                Err(rustyline::error::ReadlineError::Eof) => {
-                    println!("CTRL-D");
-                    break;
-                }
-                Err(err) => {
-                    println!("Error: {:?}", err);
-                    break;
-                }
-            }
-        }
+        println!("Press Ctrl-P or type '>' to search commands.");
+        self.repl(use_natural_language).await?;
 
         // Signal API server to shutdown if needed
         api_handle.abort();
@@ -83,29 +68,52 @@ impl Application {
     }
 
     pub async fn run_terminal_only(&self) -> Result<()> {
-        log::info!("Starting DuckTape Terminal");
+        tracing::info!("Starting DuckTape Terminal");
 
         let config = Config::load()?;
-        let use_natural_language = config.language_model.provider.is_some();
-        log::debug!(
+        let use_natural_language = config.uses_language_model();
+        tracing::debug!(
             "Provider: {:?}, use_natural_language: {}",
             config.language_model.provider,
             use_natural_language
         );
 
-        let mut rl = DefaultEditor::new()?;
-
         println!("Welcome to DuckTape! How can I assist you today?");
         println!("Example: schedule a meeting with Siya tomorrow at 3pm about project review");
+        println!("Press Ctrl-P or type '>' to search commands.");
+
+        self.repl(use_natural_language).await
+    }
+
+    /// Read and process lines until the user exits
+    ///
+    /// Ctrl-P or a line starting with `>` opens the command palette; the chosen entry is
+    /// pre-filled in the next prompt.
+    async fn repl(&self, use_natural_language: bool) -> Result<()> {
+        let mut rl = DefaultEditor::new()?;
+        let palette = PaletteTrigger::default();
+        palette.bind(&mut rl);
 
         let prompt = "🦆 ";
+        let mut initial = String::new();
 
         loop {
-            match rl.readline(prompt) {
+            let line = rl.readline_with_initial(prompt, (&initial, ""));
+            initial.clear();
+            match line {
                 Ok(line) => {
+                    let trimmed = line.trim_start();
+                    if palette.take() || trimmed.starts_with(PALETTE_PREFIX) {
+                        let query = trimmed.trim_start_matches(PALETTE_PREFIX);
+                        if let Some(selection) = run_palette(&mut rl, query)? {
+                            initial = format!("{} ", selection);
+                        }
+                        continue;
+                    }
+
                     let _ = rl.add_history_entry(line.as_str());
                     if let Err(err) = self.process_input(&line, use_natural_language).await {
-                        log::error!("Failed to process command: {:?}", err);
+                        tracing::error!("Failed to process command: {:?}", err);
                     }
                 }
                 Err(rustyline::error::ReadlineError::Interrupted) => {
@@ -127,7 +135,7 @@ impl Application {
     }
 
     async fn process_input(&self, input: &str, use_natural_language: bool) -> Result<()> {
-        log::debug!("Inside process_input: use_natural_language = {}", use_natural_language);
+        tracing::debug!("Inside process_input: use_natural_language = {}", use_natural_language);
 
         // Check for direct exit command regardless of mode
         let preprocessed = crate::command_processor::preprocess_input(input);
@@ -136,25 +144,33 @@ impl Application {
             || preprocessed == "ducktape exit"
             || preprocessed == "ducktape quit"
         {
-            log::info!("Exit command detected, bypassing language processing");
+            tracing::info!("Exit command detected, bypassing language processing");
             // Create command args for exit command
             let command_args = crate::command_processor::CommandArgs::new(
                 "exit".to_string(),
                 vec![],
                 std::collections::HashMap::new(),
             );
-            return self.command_processor.execute(command_args).await;
+            return self.bus.execute_args(command_args).await;
+        }
+
+        if preprocessed == LAST_TRACE_COMMAND {
+            match trace::last() {
+                Some(parse_trace) => println!("{}", trace::format(&parse_trace, TraceLevel::Full)),
+                None => println!("No natural language input has been processed yet"),
+            }
+            return Ok(());
         }
 
         if !use_natural_language {
-            log::info!("Skipping natural language processing as Terminal Mode is enabled");
+            tracing::info!("Skipping natural language processing as Terminal Mode is enabled");
             println!(
                 "Note: To enable natural language processing, update and enable the 'provider' field in the 'language_model' section of your config.toml file."
             );
             return self.process_command(input).await;
         }
 
-        log::info!("Proceeding with natural language processing");
+        tracing::info!("Proceeding with natural language processing");
 
         // Proceed with natural language processing if enabled
         self.process_natural_language(input).await
@@ -162,78 +178,144 @@ impl Application {
 
     /// Process a direct command string - now public for CLI use
     pub async fn process_command(&self, input: &str) -> Result<()> {
-        log::info!("Processing command: {}", input);
+        tracing::info!("Processing command: {}", input);
 
         // Preprocess the input for normalization
         let preprocessed_input = crate::command_processor::preprocess_input(input);
 
-        if Config::load()?.language_model.provider.is_none() {
-            log::info!("Terminal Mode: Direct command processing only");
+        if !Config::load()?.uses_language_model() {
+            tracing::info!("Terminal Mode: Direct command processing only");
             // Try to parse with Clap first
-            let command_args = match self.parse_command_string(&preprocessed_input) {
-                Ok(args) => args,
+            return match self.parse_command_string(&preprocessed_input) {
+                Ok(command) => self.bus.execute(command, None).await,
+                Err(e) if is_usage_error(&e) => command_bus::report(Err(e), None),
                 Err(_) => {
                     // Fall back to the legacy parser if Clap parsing fails
                     // This is useful for backward compatibility
-                    CommandArgs::parse(&preprocessed_input)?
+                    self.bus.execute_args(CommandArgs::parse(&preprocessed_input)?).await
                 }
             };
-            return self.command_processor.execute(command_args).await;
         }
 
         // Create appropriate parser using factory
         let parser = ParserFactory::create_parser()?;
 
         // Process input through parser
-        match parser.parse_input(&preprocessed_input).await? {
-            crate::parser::ParseResult::CommandString(cmd) => {
-                log::debug!("Processed command string: {}", cmd);
+        let parsed = match parser.parse_input(&preprocessed_input).await? {
+            ParseResult::NeedsClarification(clarification) => {
+                match ask_clarification(clarification).await {
+                    Some(command) => ParseResult::CommandString(command),
+                    None => return Ok(()),
+                }
+            }
+            parsed => parsed,
+        };
+        match parsed {
+            ParseResult::CommandString(cmd) => {
+                tracing::debug!("Processed command string: {}", cmd);
 
                 // Try to parse with Clap first
-                let command_args = match self.parse_command_string(&cmd) {
-                    Ok(args) => args,
+                match self.parse_command_string(&cmd) {
+                    Ok(command) => self.bus.execute(command, None).await,
+                    Err(e) if is_usage_error(&e) => command_bus::report(Err(e), None),
                     Err(_) => {
                         // Fall back to legacy parser
-                        CommandArgs::parse(&cmd)?
+                        self.bus.execute_args(CommandArgs::parse(&cmd)?).await
                     }
-                };
-
-                // Execute the command
-                self.command_processor.execute(command_args).await
+                }
             }
-            crate::parser::ParseResult::StructuredCommand(args) => {
-                log::debug!("Got pre-parsed command arguments: {:?}", args);
+            ParseResult::StructuredCommand(args) => {
+                tracing::debug!("Got pre-parsed command arguments: {:?}", args);
 
                 // Execute directly with the structured command
-                self.command_processor.execute(args).await
+                self.bus.execute_args(args).await
             }
+            ParseResult::NeedsClarification(_) => unreachable!("answered above"),
         }
     }
 
     async fn process_natural_language(&self, input: &str) -> Result<()> {
         println!("Processing natural language: '{}'", input);
+        let config = Config::load()?;
+        let started = Instant::now();
+        trace::begin();
+
+        // Bulk postpone requests are recognized locally and always previewed before applying
+        if let Some((due, to)) = crate::todo::parse_postpone_request(input) {
+            let command = format!("ducktape todo postpone --due \"{}\" --to \"{}\"", due, to);
+            let mut parse_trace = trace::finish(input, LOCAL_RULES, started.elapsed());
+            parse_trace.command = Some(command.clone());
+            show_trace(parse_trace, &config);
+            let command = self.parse_command_string(&command)?;
+            return self.bus.execute(command, None).await;
+        }
+
+        // So are requests to move or delete many events at once
+        let today = chrono::Local::now().date_naive();
+        if let Some(operation) = crate::parser::bulk::parse_bulk_request(input, today) {
+            let command = operation.to_command();
+            let mut parse_trace = trace::finish(input, LOCAL_RULES, started.elapsed());
+            parse_trace.command = Some(command.clone());
+            show_trace(parse_trace, &config);
+            let command = self.parse_command_string(&command)?;
+            return self.bus.execute(command, None).await;
+        }
+
+        // Hashtags are tags for the item, not part of its title
+        let (input, tags) = crate::tag::strip_hashtags(input);
+        if !tags.is_empty() {
+            tracing::debug!("Tags from natural language: {:?}", tags);
+        }
 
         // Create appropriate parser using factory
         let parser = ParserFactory::create_parser()?;
 
+        // Several requests in one input are parsed one by one and run together
+        if config.nlp.split_requests {
+            let parts = crate::parser::multi::split_intents(&input);
+            if parts.len() > 1 {
+                return self
+                    .process_intents(parser.as_ref(), &input, &parts, &tags, &config, started)
+                    .await;
+            }
+        }
+
         // Process input through parser
-        match parser.parse_input(input).await {
-            Ok(crate::parser::ParseResult::CommandString(command)) => {
-                println!("Translated to command: {}", command);
+        let parsed = parser.parse_input(&input).await;
+        let provider = config.parser_chain().into_iter().next();
+        let mut parse_trace =
+            trace::finish(&input, provider.as_deref().unwrap_or("terminal"), started.elapsed());
+
+        // What the input left open is asked before anything runs
+        let parsed = match parsed {
+            Ok(ParseResult::NeedsClarification(clarification)) => {
+                match ask_clarification(clarification).await {
+                    Some(command) => Ok(ParseResult::CommandString(command)),
+                    None => return Ok(()),
+                }
+            }
+            parsed => parsed,
+        };
+        match parsed {
+            Ok(ParseResult::CommandString(command)) => {
+                let command = crate::tag::add_tags_flag(&command, &tags);
 
                 // Sanitize the NLP-generated command to remove unnecessary quotes
                 let sanitized_command = crate::parser::sanitize_nlp_command(&command);
-                println!("Sanitized command: {}", sanitized_command);
-                log::debug!("Sanitized NLP command: {}", sanitized_command);
+                tracing::debug!("Sanitized NLP command: {}", sanitized_command);
+                parse_trace.generated = Some(command);
+                parse_trace.command = Some(sanitized_command.clone());
+                show_trace(parse_trace, &config);
 
                 // Check if the generated command starts with ducktape
                 if sanitized_command.starts_with("ducktape") {
                     // Try to use the Clap parser first
                     match self.parse_command_string(&sanitized_command) {
-                        Ok(args) => {
-                            log::debug!("Final parsed arguments: {:?}", args);
-                            self.command_processor.execute(args).await
+                        Ok(command) => {
+                            tracing::debug!("Final parsed command: {:?}", command);
+                            self.bus.execute(command, None).await
                         }
+                        Err(e) if is_usage_error(&e) => command_bus::report(Err(e), None),
                         Err(_) => {
                             // Fall back to legacy parser if Clap fails
                             let mut args = CommandArgs::parse(&sanitized_command)?;
@@ -245,8 +327,8 @@ impl Application {
                                 .map(|arg| arg.trim_matches('"').to_string())
                                 .collect();
 
-                            log::debug!("Final parsed arguments (legacy): {:?}", args);
-                            self.command_processor.execute(args).await
+                            tracing::debug!("Final parsed arguments (legacy): {:?}", args);
+                            self.bus.execute_args(args).await
                         }
                     }
                 } else {
@@ -257,14 +339,25 @@ impl Application {
                     Ok(())
                 }
             }
-            Ok(crate::parser::ParseResult::StructuredCommand(args)) => {
-                log::debug!("Got pre-parsed structured command: {:?}", args);
-                println!("Processed command structure from natural language");
+            Ok(ParseResult::StructuredCommand(mut args)) => {
+                tracing::debug!("Got pre-parsed structured command: {:?}", args);
+                let creates = args.args.first().is_some_and(|a| a == "create");
+                if creates
+                    && !tags.is_empty()
+                    && ["calendar", "todo", "note"].contains(&args.command.as_str())
+                {
+                    args.flags.entry("tags".to_string()).or_insert_with(|| Some(tags.join(",")));
+                }
+                parse_trace.command = Some(trace::command_line(&args));
+                show_trace(parse_trace, &config);
 
                 // Execute directly with the structured command
-                self.command_processor.execute(args).await
+                self.bus.execute_args(args).await
             }
+            Ok(ParseResult::NeedsClarification(_)) => unreachable!("answered above"),
             Err(e) => {
+                parse_trace.error = Some(e.to_string());
+                trace::record(parse_trace);
                 println!("Error processing natural language: {}", e);
                 println!("Type 'help' for a list of available commands or try rephrasing.");
                 Ok(())
@@ -272,8 +365,73 @@ impl Application {
         }
     }
 
+    /// Parse the requests split from one input and run them in order as one change
+    ///
+    /// Questions about each part are asked before anything runs; when a command fails, the
+    /// ones before it are undone.
+    async fn process_intents(
+        &self,
+        parser: &(dyn Parser + Send + Sync),
+        input: &str,
+        parts: &[String],
+        tags: &[String],
+        config: &Config,
+        started: Instant,
+    ) -> Result<()> {
+        let parsed = crate::parser::multi::parse_intents(parser, parts).await;
+        let provider = config.parser_chain().into_iter().next();
+        let mut parse_trace =
+            trace::finish(input, provider.as_deref().unwrap_or("terminal"), started.elapsed());
+        let results = match parsed {
+            Ok(results) => results,
+            Err(e) => {
+                parse_trace.error = Some(e.to_string());
+                trace::record(parse_trace);
+                println!("Error processing natural language: {:#}", e);
+                println!("Type 'help' for a list of available commands or try rephrasing.");
+                return Ok(());
+            }
+        };
+
+        let mut lines = Vec::with_capacity(results.len());
+        for result in results {
+            let line = match result {
+                ParseResult::CommandString(command) => command,
+                ParseResult::StructuredCommand(args) => trace::command_line(&args),
+                ParseResult::NeedsClarification(clarification) => {
+                    match ask_clarification(clarification).await {
+                        Some(command) => command,
+                        None => return Ok(()),
+                    }
+                }
+            };
+            let line = crate::tag::add_tags_flag(&line, tags);
+            lines.push(crate::parser::sanitize_nlp_command(&line));
+        }
+        parse_trace.command = Some(lines.join("\n"));
+        print_trace(parse_trace, config);
+
+        let mut commands = Vec::with_capacity(lines.len());
+        for line in &lines {
+            if !line.starts_with("ducktape") {
+                println!("Generated command doesn't start with 'ducktape': {}", line);
+                return Ok(());
+            }
+            let command = match self.parse_command_string(line) {
+                Ok(command) => Ok(command),
+                Err(e) if is_usage_error(&e) => Err(e),
+                Err(_) => CommandArgs::parse(line).and_then(Command::from_args),
+            };
+            match command {
+                Ok(command) => commands.push(command),
+                Err(e) => return command_bus::report(Err(e), None),
+            }
+        }
+        self.bus.execute_all(commands).await
+    }
+
     /// Helper method to parse a command string using Clap instead of the deprecated CommandArgs::parse
-    fn parse_command_string(&self, input: &str) -> Result<CommandArgs> {
+    fn parse_command_string(&self, input: &str) -> Result<Command> {
         // Format the input into argv style for clap
         let args =
             shell_words::split(input).map_err(|e| anyhow!("Failed to parse command: {}", e))?;
@@ -292,24 +450,65 @@ impl Application {
             }
         };
 
-        // Convert from Clap command to CommandArgs
-        crate::cli::convert_to_command_args(&cli)
-            .ok_or_else(|| anyhow!("Failed to convert parsed command to CommandArgs"))
+        // Typed where the command has a typed form, CommandArgs otherwise
+        Command::from_cli(&cli)
+    }
+}
+
+/// Ask the questions the parser raised about a command and merge in the answers
+///
+/// Gives the merged command, or `None` when the user cancels. Without a terminal to ask on,
+/// the parser's guess is used.
+async fn ask_clarification(mut clarification: Clarification) -> Option<String> {
+    use std::io::{IsTerminal, Write};
+
+    if !std::io::stdin().is_terminal() {
+        return Some(clarification.command);
+    }
+    loop {
+        println!("{}", clarification.prompt());
+        print!("? ");
+        let _ = std::io::stdout().flush();
+        let mut reply = String::new();
+        match std::io::stdin().read_line(&mut reply) {
+            Ok(0) | Err(_) => return None,
+            Ok(_) if is_cancel(&reply) => {
+                println!("Cancelled.");
+                return None;
+            }
+            Ok(_) => {}
+        }
+        let now = chrono::Local::now().naive_local();
+        match clarification.answer(&reply, now.date()) {
+            Ok(command) => match clarification.follow_up(&command, now).await {
+                Some(next) => clarification = next,
+                None => return Some(command),
+            },
+            Err(e) => println!("{}", e),
+        }
+    }
+}
+
+/// Keep the trace for `:last` and the command for `redo`, and print the trace as `ui.trace` asks
+fn show_trace(parse_trace: ParseTrace, config: &Config) {
+    if let Some(command) = &parse_trace.command {
+        if let Err(e) = crate::redo::remember(&parse_trace.input, command) {
+            tracing::warn!("Failed to remember the command for redo: {}", e);
+        }
+    }
+    print_trace(parse_trace, config);
+}
+
+/// Keep the trace for `:last` and print it as `ui.trace` asks
+fn print_trace(parse_trace: ParseTrace, config: &Config) {
+    let shown = trace::format(&parse_trace, config.ui.trace);
+    if !shown.is_empty() {
+        println!("{}", shown);
     }
+    trace::record(parse_trace);
 }
 
 #[allow(dead_code)] // Kept for future use when logging is expanded
 pub fn init_logger() {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
-        .format(|buf, record| {
-            use std::io::Write;
-            writeln!(
-                buf,
-                "{} [{}] {}",
-                chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
-                record.level(),
-                record.args()
-            )
-        })
-        .init();
+    crate::logging::init(&crate::logging::LogOptions::default());
 }
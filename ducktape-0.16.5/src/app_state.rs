@@ -0,0 +1,189 @@
+//! Application state shared by the terminal and the API server.
+//
+// `ducktape --full` runs the REPL and the API server in one process, and both change
+// config.toml and the state files. Everything that changes them goes through `SharedState`:
+// changes are serialized, so one never interleaves with another (no torn read-modify-write of
+// config.toml or of the calendar, todo and note records), and readers get the configuration as
+// last saved, reloaded when config.toml is edited by hand or by another process.
+
+use crate::config::{Config, get_config_path};
+use anyhow::Result;
+use once_cell::sync::OnceCell;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{debug, warn};
+
+tokio::task_local! {
+    /// Set while a task holds the mutation lock, so that nested changes do not wait on it
+    static MUTATING: ();
+}
+
+static GLOBAL: OnceCell<SharedState> = OnceCell::new();
+
+/// State the terminal and the API server share
+#[derive(Debug, Clone)]
+pub struct AppState {
+    /// Effective configuration, with the active profile applied
+    pub config: Config,
+    /// Modification time of config.toml when `config` was loaded
+    loaded_at: Option<SystemTime>,
+}
+
+impl AppState {
+    fn new(config: Config) -> Self {
+        Self { config, loaded_at: config_modified() }
+    }
+}
+
+/// Handle to the state shared within the process; clones refer to the same state
+#[derive(Debug, Clone)]
+pub struct SharedState {
+    state: Arc<RwLock<AppState>>,
+    mutations: Arc<Mutex<()>>,
+}
+
+fn config_modified() -> Option<SystemTime> {
+    get_config_path().ok()?.metadata().ok()?.modified().ok()
+}
+
+impl SharedState {
+    pub fn new(config: Config) -> Self {
+        Self {
+            state: Arc::new(RwLock::new(AppState::new(config))),
+            mutations: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// The state of this process, loaded from config.toml on first use
+    pub fn global() -> &'static SharedState {
+        GLOBAL.get_or_init(|| {
+            let config = Config::load().unwrap_or_else(|e| {
+                warn!("Using the default configuration: {}", e);
+                Config::default()
+            });
+            SharedState::new(config)
+        })
+    }
+
+    /// The current configuration
+    ///
+    /// Reloaded first when config.toml changed since it was last read; if it no longer
+    /// parses, the last good configuration is kept.
+    pub async fn config(&self) -> Config {
+        {
+            let state = self.state.read().await;
+            if state.loaded_at == config_modified() {
+                return state.config.clone();
+            }
+        }
+        let mut state = self.state.write().await;
+        let modified = config_modified();
+        if state.loaded_at != modified {
+            match Config::load() {
+                Ok(config) => {
+                    debug!("config.toml changed, reloaded it");
+                    state.config = config;
+                }
+                Err(e) => warn!("Keeping the previous configuration: {}", e),
+            }
+            state.loaded_at = modified;
+        }
+        state.config.clone()
+    }
+
+    /// Change config.toml and the shared configuration
+    ///
+    /// `change` gets the configuration as stored, without a profile applied; it is saved
+    /// when `change` succeeds.
+    pub async fn update_config<T>(
+        &self,
+        change: impl FnOnce(&mut Config) -> Result<T>,
+    ) -> Result<T> {
+        self.exclusive(async {
+            let mut stored = Config::load_global()?;
+            let result = change(&mut stored)?;
+            stored.save()?;
+            self.reload().await?;
+            Ok(result)
+        })
+        .await
+    }
+
+    /// Read config.toml again, e.g. after a command saved it
+    pub async fn reload(&self) -> Result<()> {
+        let config = Config::load()?;
+        *self.state.write().await = AppState::new(config);
+        Ok(())
+    }
+
+    /// Run `mutation` once no other change is running
+    ///
+    /// Changes made from within `mutation`, such as `update_config`, run directly.
+    pub async fn exclusive<F: Future>(&self, mutation: F) -> F::Output {
+        if MUTATING.try_with(|_| ()).is_ok() {
+            return mutation.await;
+        }
+        let _guard = self.mutations.lock().await;
+        MUTATING.scope((), mutation).await
+    }
+
+    /// Whether a change is running
+    pub fn is_busy(&self) -> bool {
+        self.mutations.try_lock().is_err()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_exclusive_serializes_changes() {
+        let shared = SharedState::new(Config::default());
+        let running = Arc::new(AtomicUsize::new(0));
+        let tasks: Vec<_> = (0..4)
+            .map(|_| {
+                let (shared, running) = (shared.clone(), running.clone());
+                tokio::spawn(async move {
+                    shared
+                        .exclusive(async {
+                            assert_eq!(running.fetch_add(1, Ordering::SeqCst), 0);
+                            tokio::time::sleep(Duration::from_millis(5)).await;
+                            running.fetch_sub(1, Ordering::SeqCst);
+                        })
+                        .await
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert!(!shared.is_busy());
+    }
+
+    #[tokio::test]
+    async fn test_exclusive_is_reentrant() {
+        let shared = SharedState::new(Config::default());
+        let value = shared
+            .exclusive(async {
+                assert!(shared.is_busy());
+                shared.exclusive(async { 42 }).await
+            })
+            .await;
+        assert_eq!(value, 42);
+        assert!(!shared.is_busy());
+    }
+
+    #[tokio::test]
+    async fn test_config_is_readable_during_a_change() {
+        let shared = SharedState::new(Config::default());
+        let read = shared.exclusive(async {
+            tokio::time::timeout(Duration::from_secs(1), shared.config()).await
+        });
+        assert!(read.await.is_ok());
+    }
+}
@@ -0,0 +1,337 @@
+//! Building AppleScript from user input without letting it become code.
+//
+// Titles, notes, list names and addresses end up inside scripts run by osascript. Inserted
+// as they are, a quote in a title ends the string and whatever follows runs as AppleScript.
+// Scripts are therefore written as `Template`s whose `${name}` placeholders are filled in
+// as escaped string literals, numbers, or code that is itself a `Script`. A `Script` is
+// only made from a string literal in the source or by rendering a template, so text from
+// outside never reaches osascript unescaped.
+
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Text safe to place between double quotes in AppleScript
+///
+/// Backslashes and quotes are escaped; control characters other than newlines and tabs are
+/// dropped.
+pub fn escape(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' | '\t' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// `input` as an AppleScript string literal, quotes included
+pub fn quote(input: &str) -> String {
+    format!("\"{}\"", escape(input))
+}
+
+/// AppleScript code, from the source or rendered from a `Template`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Script(String);
+
+impl Script {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Scripts one after another, each on its own line
+    pub fn lines(scripts: impl IntoIterator<Item = Script>) -> Script {
+        Script::join(scripts, "\n")
+    }
+
+    /// Scripts with `separator` between them, e.g. conditions joined by " and "
+    pub fn join(scripts: impl IntoIterator<Item = Script>, separator: &'static str) -> Script {
+        Script(scripts.into_iter().map(|s| s.0).collect::<Vec<_>>().join(separator))
+    }
+}
+
+impl From<&'static str> for Script {
+    fn from(code: &'static str) -> Self {
+        Script(code.to_string())
+    }
+}
+
+impl AsRef<str> for Script {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Script {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A script with `${name}` placeholders
+///
+/// Every placeholder must be given a value, and every value must have a placeholder.
+pub struct Template {
+    source: &'static str,
+    values: HashMap<&'static str, String>,
+}
+
+impl Template {
+    pub fn new(source: &'static str) -> Self {
+        Self { source, values: HashMap::new() }
+    }
+
+    /// Fill `name` with `value` as a string literal
+    pub fn text(mut self, name: &'static str, value: &str) -> Self {
+        self.values.insert(name, quote(value));
+        self
+    }
+
+    /// Fill `name` with a list of string literals, e.g. `{"a", "b"}`
+    pub fn text_list(mut self, name: &'static str, values: &[&str]) -> Self {
+        let items: Vec<String> = values.iter().map(|value| quote(value)).collect();
+        self.values.insert(name, format!("{{{}}}", items.join(", ")));
+        self
+    }
+
+    /// Fill `name` with a number
+    pub fn number(mut self, name: &'static str, value: impl Into<f64>) -> Self {
+        self.values.insert(name, format_number(value.into()));
+        self
+    }
+
+    /// Fill `name` with `true` or `false`
+    pub fn boolean(mut self, name: &'static str, value: bool) -> Self {
+        self.values.insert(name, value.to_string());
+        self
+    }
+
+    /// Fill `name` with code
+    pub fn script(mut self, name: &'static str, value: impl Into<Script>) -> Self {
+        self.values.insert(name, value.into().0);
+        self
+    }
+
+    pub fn render(self) -> Result<Script> {
+        let mut out = String::with_capacity(self.source.len());
+        let mut used = Vec::new();
+        let mut rest = self.source;
+        while let Some(start) = rest.find("${") {
+            out.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| anyhow!("Unterminated placeholder in AppleScript template"))?;
+            let name = &rest[start + 2..start + end];
+            let value = self
+                .values
+                .get(name)
+                .ok_or_else(|| anyhow!("No value for ${{{}}} in AppleScript template", name))?;
+            out.push_str(value);
+            used.push(name);
+            rest = &rest[start + end + 1..];
+        }
+        out.push_str(rest);
+        if let Some(unused) = self.values.keys().find(|name| !used.contains(name)) {
+            return Err(anyhow!("AppleScript template has no ${{{}}}", unused));
+        }
+        Ok(Script(out))
+    }
+}
+
+/// Whole numbers without a fraction, so they read as integers in AppleScript
+fn format_number(value: f64) -> String {
+    if value.is_finite() && value.fract() == 0.0 {
+        format!("{}", value as i64)
+    } else if value.is_finite() {
+        value.to_string()
+    } else {
+        "0".to_string()
+    }
+}
+
+/// A record of properties, e.g. `{summary:"Lunch", start date:startDate}`
+#[derive(Default)]
+pub struct Properties {
+    entries: Vec<(&'static str, String)>,
+}
+
+impl Properties {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(mut self, name: &'static str, value: &str) -> Self {
+        self.entries.push((name, quote(value)));
+        self
+    }
+
+    /// A string property, left out when `value` is `None` or empty
+    pub fn optional_text(self, name: &'static str, value: Option<&str>) -> Self {
+        match value.filter(|v| !v.is_empty()) {
+            Some(value) => self.text(name, value),
+            None => self,
+        }
+    }
+
+    pub fn number(mut self, name: &'static str, value: impl Into<f64>) -> Self {
+        self.entries.push((name, format_number(value.into())));
+        self
+    }
+
+    pub fn boolean(mut self, name: &'static str, value: bool) -> Self {
+        self.entries.push((name, value.to_string()));
+        self
+    }
+
+    /// A property set to code, such as a variable holding a date
+    pub fn script(mut self, name: &'static str, value: impl Into<Script>) -> Self {
+        self.entries.push((name, value.into().0));
+        self
+    }
+
+    pub fn render(&self) -> Script {
+        let entries: Vec<String> =
+            self.entries.iter().map(|(name, value)| format!("{}:{}", name, value)).collect();
+        Script(format!("{{{}}}", entries.join(", ")))
+    }
+}
+
+impl From<Properties> for Script {
+    fn from(properties: Properties) -> Self {
+        properties.render()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// The string literal at the start of `code` and what follows it, as AppleScript reads it
+    fn read_literal(code: &str) -> Option<(String, &str)> {
+        let mut chars = code.char_indices();
+        if chars.next()?.1 != '"' {
+            return None;
+        }
+        let mut value = String::new();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => return Some((value, &code[i + 1..])),
+                '\\' => match chars.next()?.1 {
+                    'n' => value.push('\n'),
+                    't' => value.push('\t'),
+                    'r' => value.push('\r'),
+                    c => value.push(c),
+                },
+                c => value.push(c),
+            }
+        }
+        None
+    }
+
+    /// `code` with each string literal replaced by `""`, leaving only what would run
+    fn code_outside_literals(code: &str) -> String {
+        let mut out = String::new();
+        let mut rest = code;
+        while let Some(start) = rest.find('"') {
+            out.push_str(&rest[..start]);
+            let (_, after) = read_literal(&rest[start..]).expect("unterminated string literal");
+            out.push_str("\"\"");
+            rest = after;
+        }
+        out.push_str(rest);
+        out
+    }
+
+    fn without_controls(input: &str) -> String {
+        input.chars().filter(|&c| !c.is_control() || c == '\n' || c == '\t').collect()
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("Hello"), "Hello");
+        assert_eq!(escape("Say \"hi\""), "Say \\\"hi\\\"");
+        assert_eq!(escape("C:\\temp\\"), "C:\\\\temp\\\\");
+        assert_eq!(escape("Line 1\nLine 2\u{0007}"), "Line 1\nLine 2");
+        assert_eq!(
+            quote("\" & (do shell script \"id\") & \""),
+            "\"\\\" & (do shell script \\\"id\\\") & \\\"\""
+        );
+    }
+
+    #[test]
+    fn test_template() {
+        let script = Template::new("set t to ${title}\nset n to ${count}\n${body}")
+            .text("title", "Lunch \"at noon\"")
+            .number("count", 3)
+            .script("body", Template::new("log ${title}").text("title", "x").render().unwrap())
+            .render()
+            .unwrap();
+        assert_eq!(script.as_str(), "set t to \"Lunch \\\"at noon\\\"\"\nset n to 3\nlog \"x\"");
+
+        let script =
+            Template::new("repeat with a in ${emails}").text_list("emails", &["a@x.com", "\"b"]);
+        assert_eq!(script.render().unwrap().as_str(), "repeat with a in {\"a@x.com\", \"\\\"b\"}");
+
+        assert!(Template::new("log ${title}").render().is_err());
+        assert!(Template::new("log 1").text("title", "x").render().is_err());
+        assert!(Template::new("log ${title").text("title", "x").render().is_err());
+    }
+
+    #[test]
+    fn test_properties() {
+        let properties = Properties::new()
+            .text("summary", "Team \"sync\"")
+            .script("start date", "startDate")
+            .optional_text("location", Some(""))
+            .optional_text("url", None)
+            .boolean("allday event", true)
+            .number("priority", 1.5);
+        assert_eq!(
+            properties.render().as_str(),
+            "{summary:\"Team \\\"sync\\\"\", start date:startDate, allday event:true, priority:1.5}"
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn fuzz_quote_reads_back_as_one_literal(input in any::<String>()) {
+            let quoted = quote(&input);
+            let (value, rest) = read_literal(&quoted).expect("not a string literal");
+            prop_assert_eq!(value, without_controls(&input));
+            prop_assert_eq!(rest, "");
+        }
+
+        #[test]
+        fn fuzz_template_values_stay_strings(
+            title in any::<String>(),
+            location in any::<String>(),
+            email in "[\"\\\\a-z@. &()]{0,40}",
+        ) {
+            let render = |title: &str, location: &str, email: &str| {
+                let properties = Properties::new()
+                    .text("summary", title)
+                    .script("start date", "startDate")
+                    .text("location", location);
+                Template::new(
+                    "tell application \"Calendar\"\n    set e to make new event with properties ${properties}\n    make new attendee at end of attendees of e with properties {email:${email}}\nend tell",
+                )
+                .script("properties", properties)
+                .text("email", email)
+                .render()
+                .unwrap()
+            };
+            // Whatever the input, only the contents of string literals change
+            let fuzzed = render(&title, &location, &email);
+            let plain = render("", "", "");
+            prop_assert_eq!(
+                code_outside_literals(fuzzed.as_str()),
+                code_outside_literals(plain.as_str())
+            );
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! Who ran which command, and how it went.
+//
+// Every command run through the command bus or the processor adds a line to
+// ~/.ducktape/audit.jsonl with where it came from (the terminal, an API key, a WebSocket
+// connection, Slack), what it was and whether it worked. The log is only ever appended to;
+// past `audit.max_bytes` it is rotated, keeping `audit.keep_files` older logs. API keys are
+// recorded by fingerprint, never in full. `ducktape audit show` reads the log back.
+
+use crate::command_processor::CommandArgs;
+use crate::config::{AuditConfig, Config};
+use crate::state::StateManager;
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::future::Future;
+use tracing::warn;
+
+/// The log, in the state directory
+pub const AUDIT_FILE: &str = "audit.jsonl";
+
+/// Longest command text recorded; note bodies and the like are cut off
+const MAX_COMMAND_CHARS: usize = 200;
+
+tokio::task_local! {
+    /// Who is running commands in the current task
+    static ACTOR: String;
+}
+
+/// One executed command
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Local>,
+    /// Correlation ID of the command in the logs
+    pub id: String,
+    /// "cli", "api", "websocket" or "slack"
+    pub source: String,
+    /// Who ran it, e.g. "terminal user sam", "api key 3f2a9c1e" or "slack @sam"
+    pub actor: String,
+    pub command: String,
+    /// "ok", "error", "duplicate" or "unrecognized"
+    pub outcome: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Run `future` with its commands recorded as run by `actor`
+///
+/// Commands run outside such a scope are recorded as run by the terminal user.
+pub async fn with_actor<F: Future>(actor: String, future: F) -> F::Output {
+    ACTOR.scope(actor, future).await
+}
+
+/// Who is running commands in the current task
+pub fn current_actor() -> String {
+    ACTOR.try_with(Clone::clone).unwrap_or_else(|_| match std::env::var("USER") {
+        Ok(user) if !user.is_empty() => format!("terminal user {}", user),
+        _ => "terminal".to_string(),
+    })
+}
+
+/// Actor for a request with an API key, recorded by the start of its SHA-256 hash
+pub fn api_key_actor(key: &str) -> String {
+    let digest = Sha256::digest(key.as_bytes());
+    let hex: String = digest.iter().take(4).map(|b| format!("{:02x}", b)).collect();
+    format!("api key {}", hex)
+}
+
+/// A command line for the log, cut off after `MAX_COMMAND_CHARS`
+pub fn describe(args: &CommandArgs) -> String {
+    let mut words = vec![args.command.clone()];
+    words.extend(args.args.iter().map(|arg| shell_words::quote(arg).into_owned()));
+    let mut flags: Vec<_> = args.flags.iter().collect();
+    flags.sort();
+    for (name, value) in flags {
+        words.push(format!("--{}", name));
+        if let Some(value) = value {
+            words.push(shell_words::quote(value).into_owned());
+        }
+    }
+    truncate(&words.join(" "))
+}
+
+pub(crate) fn truncate(command: &str) -> String {
+    match command.char_indices().nth(MAX_COMMAND_CHARS) {
+        Some((end, _)) => format!("{}…", &command[..end]),
+        None => command.to_string(),
+    }
+}
+
+/// Add a finished command to the log
+///
+/// Failing to write the log is logged rather than failing the command.
+pub fn record(id: &str, command: &str, outcome: &str, error: Option<&anyhow::Error>) {
+    let config = Config::load().map(|c| c.audit).unwrap_or_default();
+    if !config.enabled {
+        return;
+    }
+    let entry = AuditEntry {
+        timestamp: Local::now(),
+        id: id.to_string(),
+        source: crate::webhooks::current_source().to_string(),
+        actor: current_actor(),
+        command: command.to_string(),
+        outcome: outcome.to_string(),
+        error: error.map(|e| e.to_string()),
+    };
+    if let Err(e) = StateManager::new().and_then(|state| append(&state, &config, &entry)) {
+        warn!("Could not write the audit log: {}", e);
+    }
+}
+
+fn append(state: &StateManager, config: &AuditConfig, entry: &AuditEntry) -> Result<()> {
+    let line = serde_json::to_string(entry)?;
+    state.append_line(AUDIT_FILE, &line, config.max_bytes, config.keep_files)
+}
+
+/// Entries from `since` on, oldest first; unreadable lines are skipped
+pub fn load(state: &StateManager, since: Option<NaiveDate>) -> Result<Vec<AuditEntry>> {
+    Ok(state
+        .read_lines(AUDIT_FILE)?
+        .iter()
+        .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok())
+        .filter(|entry| match since {
+            Some(since) => entry.timestamp.date_naive() >= since,
+            None => true,
+        })
+        .collect())
+}
+
+/// One line of `ducktape audit show`
+pub fn format_entry(entry: &AuditEntry) -> String {
+    let mut line = format!(
+        "{}  {:<9} {:<24} {}  {}",
+        entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+        entry.source,
+        entry.actor,
+        entry.command,
+        entry.outcome
+    );
+    if let Some(error) = &entry.error {
+        line.push_str(&format!(": {}", error));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::tempdir;
+
+    fn entry(timestamp: &str, command: &str) -> AuditEntry {
+        AuditEntry {
+            timestamp: DateTime::parse_from_rfc3339(timestamp).unwrap().with_timezone(&Local),
+            id: "0123456789ab".to_string(),
+            source: "api".to_string(),
+            actor: api_key_actor("secret"),
+            command: command.to_string(),
+            outcome: "ok".to_string(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_entries_are_filtered_by_date() -> Result<()> {
+        let dir = tempdir()?;
+        let state = StateManager::with_dir(dir.path())?;
+        let config = AuditConfig::default();
+        append(&state, &config, &entry("2026-03-01T12:00:00+00:00", "calendar list"))?;
+        append(&state, &config, &entry("2026-03-03T12:00:00+00:00", "todo create Milk"))?;
+
+        assert_eq!(load(&state, None)?.len(), 2);
+        let since = NaiveDate::from_ymd_opt(2026, 3, 2);
+        let entries = load(&state, since)?;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].command, "todo create Milk");
+        Ok(())
+    }
+
+    #[test]
+    fn test_api_keys_are_not_recorded() {
+        let actor = api_key_actor("secret");
+        assert!(actor.starts_with("api key "));
+        assert_eq!(actor.len(), "api key ".len() + 8);
+        assert!(!actor.contains("secret"));
+        assert_eq!(actor, api_key_actor("secret"));
+    }
+
+    #[test]
+    fn test_describe_quotes_and_truncates() {
+        let mut flags = HashMap::new();
+        flags.insert("calendar".to_string(), Some("Family Plans".to_string()));
+        flags.insert("all-day".to_string(), None);
+        let args = CommandArgs::new(
+            "calendar".to_string(),
+            vec!["create".to_string(), "Team lunch".to_string()],
+            flags,
+        );
+        assert_eq!(
+            describe(&args),
+            "calendar create 'Team lunch' --all-day --calendar 'Family Plans'"
+        );
+
+        let long = "x".repeat(MAX_COMMAND_CHARS + 10);
+        assert_eq!(truncate(&long).chars().count(), MAX_COMMAND_CHARS + 1);
+    }
+
+    #[tokio::test]
+    async fn test_actor_scope() {
+        assert!(current_actor().starts_with("terminal"));
+        let actor = with_actor("slack @sam".to_string(), async { current_actor() }).await;
+        assert_eq!(actor, "slack @sam");
+    }
+}
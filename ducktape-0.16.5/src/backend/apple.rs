@@ -0,0 +1,154 @@
+//! Backends for Calendar.app, Reminders.app and Notes.app, using the AppleScript modules.
+
+use super::{CalendarBackend, NotesBackend, TodoBackend};
+use crate::calendar::EventConfig;
+use crate::notes::{NoteConfig, NoteItem};
+use crate::state::CalendarItem;
+use crate::todo::{TodoConfig, TodoItem};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+
+/// Calendar.app
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AppleCalendar;
+
+#[async_trait]
+impl CalendarBackend for AppleCalendar {
+    async fn calendars(&self) -> Result<Vec<String>> {
+        crate::calendar::calendar_app_calendars().await
+    }
+
+    async fn default_calendar(&self) -> Result<String> {
+        crate::calendar::detect_system_default_calendar().await
+    }
+
+    /// Accounts such as iCloud and Exchange send them; local calendars need `invite_emails`
+    fn sends_invitations(&self) -> bool {
+        true
+    }
+
+    /// Dates added to a repeating event are events of their own; the UID is the first event's
+    async fn create_event(&self, config: EventConfig) -> Result<String> {
+        crate::calendar::ensure_calendar_running().await?;
+        let mut config = config;
+        let extra_dates = config
+            .recurrence
+            .as_mut()
+            .map(|r| std::mem::take(&mut r.extra_dates))
+            .unwrap_or_default();
+        // A series of one, as imported from RDATEs without a rule, is a single event
+        if config.recurrence.as_ref().is_some_and(|r| r.count == Some(1)) {
+            config.recurrence = None;
+        }
+        let uid = crate::calendar::create_single_event(config.clone()).await?;
+
+        // Calendar.app has no RDATE, so each extra date is an event of its own
+        let start = NaiveDate::parse_from_str(&config.start_date, "%Y-%m-%d")?;
+        for date in extra_dates {
+            let mut single = config.clone();
+            let day = NaiveDate::parse_from_str(&date, "%Y-%m-%d")?;
+            single.end_date = config
+                .end_date
+                .as_deref()
+                .and_then(|end| NaiveDate::parse_from_str(end, "%Y-%m-%d").ok())
+                .map(|end| (day + (end - start)).format("%Y-%m-%d").to_string());
+            single.start_date = date;
+            single.recurrence = None;
+            crate::calendar::create_single_event(single).await?;
+        }
+        Ok(uid)
+    }
+
+    async fn update_event(&self, existing: &CalendarItem, config: EventConfig) -> Result<()> {
+        crate::calendar::ensure_calendar_running().await?;
+        crate::calendar::update_single_event(existing, &config).await
+    }
+
+    async fn delete_event(&self, existing: &CalendarItem) -> Result<()> {
+        crate::calendar::ensure_calendar_running().await?;
+        crate::calendar::delete_single_event(existing).await
+    }
+}
+
+/// Reminders.app
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AppleReminders;
+
+#[async_trait]
+impl TodoBackend for AppleReminders {
+    async fn lists(&self) -> Result<Vec<String>> {
+        crate::todo::todo_applescript::get_reminder_lists().await
+    }
+
+    async fn todos(&self, list: Option<&str>) -> Result<Vec<TodoItem>> {
+        crate::todo::todo_applescript::fetch_todos(list).await
+    }
+
+    async fn create_todo(&self, config: TodoConfig<'_>) -> Result<()> {
+        crate::todo::todo_applescript::create_single_todo(config).await
+    }
+
+    async fn delete_todo(&self, title: &str, list: Option<&str>) -> Result<()> {
+        crate::todo::todo_applescript::delete_todo(title, list).await
+    }
+}
+
+/// Notes.app
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AppleNotes;
+
+#[async_trait]
+impl NotesBackend for AppleNotes {
+    async fn create_note(&self, config: NoteConfig<'_>) -> Result<()> {
+        crate::notes::notes_applescript::create_note(config).await
+    }
+
+    async fn list_notes(&self) -> Result<Vec<NoteItem>> {
+        crate::notes::notes_applescript::list_notes().await
+    }
+
+    async fn folders(&self) -> Result<Vec<String>> {
+        crate::notes::notes_applescript::get_note_folders().await
+    }
+
+    async fn create_folder(&self, name: &str) -> Result<()> {
+        crate::notes::notes_applescript::create_folder(name).await
+    }
+
+    async fn delete_folder(&self, name: &str) -> Result<()> {
+        crate::notes::notes_applescript::delete_folder(name).await
+    }
+
+    async fn move_note(&self, title: &str, from: Option<&str>, to: &str) -> Result<()> {
+        crate::notes::notes_applescript::move_note(title, from, to).await
+    }
+
+    async fn delete_note(&self, title: &str, folder: Option<&str>) -> Result<()> {
+        crate::notes::notes_applescript::delete_note(title, folder).await
+    }
+
+    async fn append_note(
+        &self,
+        title: &str,
+        text: &str,
+        folder: Option<&str>,
+        markdown: bool,
+    ) -> Result<()> {
+        crate::notes::notes_applescript::append_to_note(title, text, folder, markdown).await
+    }
+
+    async fn edit_note(
+        &self,
+        title: &str,
+        content: &str,
+        folder: Option<&str>,
+        markdown: bool,
+    ) -> Result<()> {
+        crate::notes::notes_applescript::edit_note(title, content, folder, markdown).await
+    }
+
+    async fn search_notes(&self, keyword: &str) -> Result<Vec<NoteItem>> {
+        crate::notes::notes_applescript::search_notes(keyword).await
+    }
+}
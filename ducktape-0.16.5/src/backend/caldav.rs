@@ -0,0 +1,211 @@
+//! Calendar backend for a CalDAV server (Nextcloud, Fastmail, iCloud, Radicale, ...).
+//
+// Calendars are the collections below `backend.caldav.url` found with a PROPFIND, and events
+// are PUT into them as .ics resources. The password is a credential like the API keys:
+// CALDAV_PASSWORD in the environment or `ducktape keys set caldav`.
+
+use super::CalendarBackend;
+use super::ics::{new_uid, vcalendar, vevent};
+use crate::calendar::EventConfig;
+use crate::config::CalDavConfig;
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::{Client, Method, StatusCode, Url};
+use secrecy::{ExposeSecret, Secret};
+
+/// Credential holding the CalDAV password
+pub const PASSWORD_VAR: &str = "CALDAV_PASSWORD";
+
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop><d:displayname/><d:resourcetype/></d:prop>
+</d:propfind>"#;
+
+static RESPONSE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)<(?:\w+:)?response\b.*?</(?:\w+:)?response>").unwrap());
+static HREF: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)<(?:\w+:)?href>\s*(.*?)\s*</(?:\w+:)?href>").unwrap());
+static DISPLAY_NAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?s)<(?:\w+:)?displayname>\s*(.*?)\s*</(?:\w+:)?displayname>").unwrap()
+});
+static CALENDAR_TYPE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<(?:\w+:)?calendar(?:\s[^>]*)?/>").unwrap());
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Calendar collections in a PROPFIND multistatus, as (name, href)
+///
+/// Collections without a display name are named after the last segment of their href.
+pub fn parse_collections(xml: &str) -> Vec<(String, String)> {
+    RESPONSE
+        .find_iter(xml)
+        .filter(|response| CALENDAR_TYPE.is_match(response.as_str()))
+        .filter_map(|response| {
+            let response = response.as_str();
+            let href = unescape_xml(HREF.captures(response)?.get(1)?.as_str());
+            let name = DISPLAY_NAME
+                .captures(response)
+                .and_then(|c| c.get(1))
+                .map(|m| unescape_xml(m.as_str()))
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| {
+                    href.trim_end_matches('/').rsplit('/').next().unwrap_or_default().to_string()
+                });
+            Some((name, href))
+        })
+        .collect()
+}
+
+pub struct CalDavCalendar {
+    url: Url,
+    username: String,
+    password: Secret<String>,
+    client: Client,
+}
+
+impl CalDavCalendar {
+    pub fn from_config(config: &CalDavConfig) -> Result<Self> {
+        let url = config
+            .url
+            .as_deref()
+            .ok_or_else(|| anyhow!("Set backend.caldav.url in config.toml to use CalDAV"))?;
+        let mut url = Url::parse(url).with_context(|| format!("Invalid CalDAV URL: {}", url))?;
+        if !url.path().ends_with('/') {
+            url.set_path(&format!("{}/", url.path()));
+        }
+        let username = config
+            .username
+            .clone()
+            .ok_or_else(|| anyhow!("Set backend.caldav.username in config.toml to use CalDAV"))?;
+        let password = crate::secrets::get(PASSWORD_VAR).ok_or_else(|| {
+            anyhow!(
+                "{} not found in environment or keychain; store it with `ducktape keys set caldav`",
+                PASSWORD_VAR
+            )
+        })?;
+        Ok(Self { url, username, password: Secret::new(password), client: Client::new() })
+    }
+
+    /// Calendar collections on the server, as (name, URL)
+    async fn collections(&self) -> Result<Vec<(String, Url)>> {
+        let propfind = Method::from_bytes(b"PROPFIND").expect("valid method");
+        let response = self
+            .client
+            .request(propfind, self.url.clone())
+            .basic_auth(&self.username, Some(self.password.expose_secret()))
+            .header("Depth", "1")
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .body(PROPFIND_BODY)
+            .send()
+            .await
+            .context("Failed to reach the CalDAV server")?;
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        if status != StatusCode::MULTI_STATUS {
+            return Err(anyhow!("CalDAV server answered {} to PROPFIND: {}", status, body.trim()));
+        }
+        parse_collections(&body)
+            .into_iter()
+            .map(|(name, href)| Ok((name, self.url.join(&href)?)))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl CalendarBackend for CalDavCalendar {
+    async fn calendars(&self) -> Result<Vec<String>> {
+        Ok(self.collections().await?.into_iter().map(|(name, _)| name).collect())
+    }
+
+    async fn default_calendar(&self) -> Result<String> {
+        self.calendars()
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("No calendars found at {}", self.url))
+    }
+
+    async fn create_event(&self, config: EventConfig) -> Result<String> {
+        let wanted = match config.calendars.first() {
+            Some(calendar) => calendar.clone(),
+            None => self.default_calendar().await?,
+        };
+        let collection = self
+            .collections()
+            .await?
+            .into_iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(&wanted))
+            .map(|(_, url)| url)
+            .ok_or_else(|| anyhow!("CalDAV calendar '{}' not found", wanted))?;
+
+        let uid = new_uid();
+        let body = vcalendar(&vevent(&config, &uid, Utc::now())?);
+        let resource = collection.join(&format!("{}.ics", uid.replace('@', "-")))?;
+        let response = self
+            .client
+            .put(resource)
+            .basic_auth(&self.username, Some(self.password.expose_secret()))
+            .header("Content-Type", "text/calendar; charset=utf-8")
+            .header("If-None-Match", "*")
+            .body(body)
+            .send()
+            .await
+            .context("Failed to reach the CalDAV server")?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(anyhow!("CalDAV server refused the event ({}): {}", status, text.trim()));
+        }
+        println!("Event '{}' created in CalDAV calendar '{}'", config.title, wanted);
+        Ok(uid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_collections() {
+        let xml = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:" xmlns:cal="urn:ietf:params:xml:ns:caldav">
+  <d:response>
+    <d:href>/calendars/jane/</d:href>
+    <d:propstat><d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop></d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/calendars/jane/work/</d:href>
+    <d:propstat><d:prop>
+      <d:displayname>Work &amp; Projects</d:displayname>
+      <d:resourcetype><d:collection/><cal:calendar/></d:resourcetype>
+    </d:prop></d:propstat>
+  </d:response>
+  <response xmlns="DAV:">
+    <href>/calendars/jane/home/</href>
+    <propstat><prop><displayname/><resourcetype><collection/><calendar xmlns="urn:ietf:params:xml:ns:caldav"/></resourcetype></prop></propstat>
+  </response>
+</d:multistatus>"#;
+        assert_eq!(
+            parse_collections(xml),
+            vec![
+                ("Work & Projects".to_string(), "/calendars/jane/work/".to_string()),
+                ("home".to_string(), "/calendars/jane/home/".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_is_required() {
+        let error = CalDavCalendar::from_config(&CalDavConfig::default()).err().unwrap();
+        assert!(error.to_string().contains("backend.caldav.url"));
+    }
+}
@@ -0,0 +1,221 @@
+//! Reminders backend keeping each list in a Markdown checklist, e.g. ~/.ducktape/todos/Work.md
+//
+// One reminder per line, `- [ ] Title` or `- [x] Title`, optionally followed by the reminder
+// time in parentheses. Notes follow on lines indented by two spaces. The files stay readable
+// and editable by hand.
+
+use super::{TodoBackend, file_name};
+use crate::todo::{TodoConfig, TodoError, TodoItem};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::fs;
+use std::path::PathBuf;
+
+/// List used when neither the command nor todo.default_list names one
+pub const DEFAULT_LIST: &str = "Reminders";
+
+static ITEM: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^- \[([ xX])\] (.*?)(?: \((\d{4}-\d{2}-\d{2}(?: \d{2}:\d{2})?)\))?$").unwrap()
+});
+
+/// Reminders in the content of a list file
+pub fn parse_list(list: &str, content: &str) -> Vec<TodoItem> {
+    let mut items: Vec<TodoItem> = Vec::new();
+    for line in content.lines() {
+        if let Some(captures) = ITEM.captures(line.trim_end()) {
+            items.push(TodoItem {
+                title: captures[2].to_string(),
+                notes: None,
+                lists: vec![list.to_string()],
+                reminder_time: captures.get(3).map(|m| m.as_str().to_string()),
+                completed: &captures[1] != " ",
+            });
+        } else if let (Some(notes), Some(item)) = (line.strip_prefix("  "), items.last_mut()) {
+            match &mut item.notes {
+                Some(existing) => {
+                    existing.push('\n');
+                    existing.push_str(notes);
+                }
+                None => item.notes = Some(notes.to_string()),
+            }
+        }
+    }
+    items
+}
+
+/// A reminder as lines of a list file
+pub fn format_item(item: &TodoItem) -> String {
+    let mut line = format!("- [{}] {}", if item.completed { "x" } else { " " }, item.title);
+    if let Some(time) = &item.reminder_time {
+        line.push_str(&format!(" ({})", time));
+    }
+    line.push('\n');
+    for notes in item.notes.iter().flat_map(|notes| notes.lines()) {
+        line.push_str(&format!("  {}\n", notes));
+    }
+    line
+}
+
+#[derive(Debug, Clone)]
+pub struct FileTodos {
+    dir: PathBuf,
+    default_list: String,
+}
+
+impl FileTodos {
+    pub fn new(dir: impl Into<PathBuf>, default_list: &str) -> Self {
+        Self { dir: dir.into(), default_list: default_list.to_string() }
+    }
+
+    fn path(&self, list: &str) -> PathBuf {
+        self.dir.join(format!("{}.md", file_name(list)))
+    }
+
+    fn list_names(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        if self.dir.exists() {
+            for entry in fs::read_dir(&self.dir)
+                .with_context(|| format!("Failed to read {}", self.dir.display()))?
+            {
+                let path = entry?.path();
+                if path.extension().is_some_and(|ext| ext == "md") {
+                    if let Some(stem) = path.file_stem() {
+                        names.push(stem.to_string_lossy().to_string());
+                    }
+                }
+            }
+        }
+        if !names.iter().any(|name| name.eq_ignore_ascii_case(&self.default_list)) {
+            names.push(self.default_list.clone());
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    fn read_list(&self, list: &str) -> Result<Vec<TodoItem>> {
+        let path = self.path(list);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(parse_list(list, &content))
+    }
+
+    fn write_list(&self, list: &str, items: &[TodoItem]) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let path = self.path(list);
+        let content: String = items.iter().map(format_item).collect();
+        fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+#[async_trait]
+impl TodoBackend for FileTodos {
+    async fn lists(&self) -> Result<Vec<String>> {
+        self.list_names()
+    }
+
+    async fn todos(&self, list: Option<&str>) -> Result<Vec<TodoItem>> {
+        match list {
+            Some(list) => {
+                if !self.path(list).exists() && !list.eq_ignore_ascii_case(&self.default_list) {
+                    return Err(TodoError::ListNotFound(list.to_string()).into());
+                }
+                self.read_list(list)
+            }
+            None => {
+                let mut items = Vec::new();
+                for list in self.list_names()? {
+                    items.extend(self.read_list(&list)?);
+                }
+                Ok(items)
+            }
+        }
+    }
+
+    async fn create_todo(&self, config: TodoConfig<'_>) -> Result<()> {
+        let lists =
+            if config.lists.is_empty() { vec![self.default_list.as_str()] } else { config.lists };
+        for list in lists {
+            let mut items = self.read_list(list)?;
+            items.push(TodoItem {
+                title: config.title.to_string(),
+                notes: config.notes.clone(),
+                lists: vec![list.to_string()],
+                reminder_time: config.reminder_time.or(config.due_date).map(str::to_string),
+                completed: false,
+            });
+            self.write_list(list, &items)?;
+            println!("Reminder '{}' added to {}", config.title, self.path(list).display());
+        }
+        Ok(())
+    }
+
+    async fn delete_todo(&self, title: &str, list: Option<&str>) -> Result<()> {
+        let lists = match list {
+            Some(list) => vec![list.to_string()],
+            None => self.list_names()?,
+        };
+        for list in lists {
+            let mut items = self.read_list(&list)?;
+            if let Some(index) =
+                items.iter().position(|item| item.title.eq_ignore_ascii_case(title))
+            {
+                items.remove(index);
+                return self.write_list(&list, &items);
+            }
+        }
+        Err(TodoError::TodoNotFound(title.to_string()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_and_format() {
+        let content = "- [ ] Buy milk (2025-04-15 18:00)\n  2 litres\n  oat\n- [x] Ship v1.0\n\n";
+        let items = parse_list("Home", content);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].title, "Buy milk");
+        assert_eq!(items[0].reminder_time.as_deref(), Some("2025-04-15 18:00"));
+        assert_eq!(items[0].notes.as_deref(), Some("2 litres\noat"));
+        assert_eq!(items[0].lists, vec!["Home"]);
+        assert!(items[1].completed);
+        assert_eq!(items[1].reminder_time, None);
+
+        let formatted: String = items.iter().map(format_item).collect();
+        assert_eq!(formatted, content.trim_end().to_string() + "\n");
+    }
+
+    #[tokio::test]
+    async fn test_create_list_and_delete() -> Result<()> {
+        let dir = tempdir()?;
+        let backend = FileTodos::new(dir.path(), DEFAULT_LIST);
+        assert_eq!(backend.lists().await?, vec![DEFAULT_LIST]);
+
+        let mut config = TodoConfig::new("Call Sam");
+        config.lists = vec!["Work"];
+        config.reminder_time = Some("2025-04-15 09:30");
+        backend.create_todo(config).await?;
+        backend.create_todo(TodoConfig::new("Water plants")).await?;
+
+        assert_eq!(backend.lists().await?, vec![DEFAULT_LIST, "Work"]);
+        let work = backend.todos(Some("Work")).await?;
+        assert_eq!(work.len(), 1);
+        assert_eq!(work[0].reminder_time.as_deref(), Some("2025-04-15 09:30"));
+        assert_eq!(backend.todos(None).await?.len(), 2);
+        assert!(backend.todos(Some("Errands")).await.is_err());
+
+        backend.delete_todo("call sam", None).await?;
+        assert!(backend.todos(Some("Work")).await?.is_empty());
+        assert!(backend.delete_todo("Call Sam", None).await.is_err());
+        Ok(())
+    }
+}
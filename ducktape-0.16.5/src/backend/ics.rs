@@ -0,0 +1,256 @@
+//! iCalendar (RFC 5545) text for events, shared by the .ics store and CalDAV.
+
+use crate::calendar::{EventAvailability, EventConfig};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+
+const PRODID: &str = "-//DuckTape//DuckTape//EN";
+
+/// Escape a TEXT value
+pub fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line to at most 75 bytes per line
+fn fold(line: &str) -> String {
+    let mut out = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+    out
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .with_context(|| format!("Invalid date '{}' (expected YYYY-MM-DD)", date))
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M")
+        .with_context(|| format!("Invalid time '{}' (expected HH:MM)", time))
+}
+
+/// A VEVENT for `config`
+pub fn vevent(config: &EventConfig, uid: &str, stamp: DateTime<Utc>) -> Result<String> {
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", uid),
+        format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")),
+        format!("SUMMARY:{}", escape_text(&config.title)),
+    ];
+
+    let start_date = parse_date(&config.start_date)?;
+    let end_date = config.end_date.as_deref().map(parse_date).transpose()?;
+    if config.all_day {
+        let end = end_date.unwrap_or(start_date) + Duration::days(1);
+        lines.push(format!("DTSTART;VALUE=DATE:{}", start_date.format("%Y%m%d")));
+        lines.push(format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")));
+    } else {
+        let start = start_date.and_time(parse_time(&config.start_time)?);
+        let end = match &config.end_time {
+            Some(time) => end_date.unwrap_or(start_date).and_time(parse_time(time)?),
+            None => start + Duration::hours(1),
+        };
+        let tzid = config.timezone.as_deref().map(|tz| format!(";TZID={}", tz)).unwrap_or_default();
+        lines.push(format!("DTSTART{}:{}", tzid, start.format("%Y%m%dT%H%M%S")));
+        lines.push(format!("DTEND{}:{}", tzid, end.format("%Y%m%dT%H%M%S")));
+    }
+
+    if let Some(recurrence) = &config.recurrence {
+        let mut rule =
+            format!("FREQ={};INTERVAL={}", recurrence.frequency.to_rfc5545(), recurrence.interval);
+        if let Some(count) = recurrence.count {
+            rule.push_str(&format!(";COUNT={}", count));
+        } else if let Some(until) = &recurrence.end_date {
+            rule.push_str(&format!(";UNTIL={}", parse_date(until)?.format("%Y%m%d")));
+        }
+        rule.push_str(&recurrence.by_rules());
+        lines.push(format!("RRULE:{}", rule));
+
+        for (name, dates) in
+            [("EXDATE", &recurrence.exception_dates), ("RDATE", &recurrence.extra_dates)]
+        {
+            if dates.is_empty() {
+                continue;
+            }
+            let (params, format, time) = if config.all_day {
+                (";VALUE=DATE".to_string(), "%Y%m%d", NaiveTime::MIN)
+            } else {
+                let tzid = config.timezone.as_deref().map(|tz| format!(";TZID={}", tz));
+                (tzid.unwrap_or_default(), "%Y%m%dT%H%M%S", parse_time(&config.start_time)?)
+            };
+            let values = dates
+                .iter()
+                .map(|date| Ok(parse_date(date)?.and_time(time).format(format).to_string()))
+                .collect::<Result<Vec<_>>>()?;
+            lines.push(format!("{}{}:{}", name, params, values.join(",")));
+        }
+    }
+    if let Some(location) = &config.location {
+        lines.push(format!("LOCATION:{}", escape_text(location)));
+    }
+    if let Some(url) = &config.url {
+        lines.push(format!("URL:{}", url));
+    }
+    if config.availability == Some(EventAvailability::Free) {
+        lines.push("TRANSP:TRANSPARENT".to_string());
+    }
+    let mut description = config.description.clone().unwrap_or_default();
+    if let Some(url) = config.zoom_join_url.as_ref().filter(|url| !description.contains(*url)) {
+        if !description.is_empty() {
+            description.push_str("\n\n");
+        }
+        description.push_str(&format!("Join: {}", url));
+    }
+    if !description.is_empty() {
+        lines.push(format!("DESCRIPTION:{}", escape_text(&description)));
+    }
+    for email in &config.emails {
+        lines.push(format!("ATTENDEE;RSVP=TRUE:mailto:{}", email.trim()));
+    }
+    if let Some(minutes) = config.reminder {
+        lines.extend([
+            "BEGIN:VALARM".to_string(),
+            "ACTION:DISPLAY".to_string(),
+            format!("DESCRIPTION:{}", escape_text(&config.title)),
+            format!("TRIGGER:-PT{}M", minutes),
+            "END:VALARM".to_string(),
+        ]);
+    }
+    lines.push("END:VEVENT".to_string());
+
+    Ok(lines.iter().map(|line| fold(line)).collect())
+}
+
+/// A VCALENDAR around already formatted components
+pub fn vcalendar(components: &str) -> String {
+    format!(
+        "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:{}\r\nCALSCALE:GREGORIAN\r\n{}END:VCALENDAR\r\n",
+        PRODID, components
+    )
+}
+
+/// A VCALENDAR to subscribe to, named `name` in calendar applications
+pub fn feed_calendar(name: &str, components: &str) -> String {
+    format!(
+        "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:{}\r\nCALSCALE:GREGORIAN\r\nMETHOD:PUBLISH\r\n{}REFRESH-INTERVAL;VALUE=DURATION:PT1H\r\nX-PUBLISHED-TTL:PT1H\r\n{}END:VCALENDAR\r\n",
+        PRODID,
+        fold(&format!("X-WR-CALNAME:{}", escape_text(name))),
+        components
+    )
+}
+
+/// A VCALENDAR inviting the attendees of its events to respond (iTIP REQUEST)
+pub fn invitation(components: &str) -> String {
+    format!(
+        "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:{}\r\nCALSCALE:GREGORIAN\r\nMETHOD:REQUEST\r\n{}END:VCALENDAR\r\n",
+        PRODID, components
+    )
+}
+
+/// `vevent` with `email` as its organizer, which an invitation needs for replies to reach them
+pub fn with_organizer(vevent: &str, email: &str) -> String {
+    let organizer = fold(&format!("ORGANIZER:mailto:{}", email.trim()));
+    vevent.replacen("END:VEVENT\r\n", &format!("{}END:VEVENT\r\n", organizer), 1)
+}
+
+/// A new unique event UID
+pub fn new_uid() -> String {
+    format!("{}@ducktape", uuid::Uuid::new_v4())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::{RecurrenceFrequency, RecurrencePattern};
+    use chrono::TimeZone;
+
+    fn stamp() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 4, 1, 8, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_vevent() -> Result<()> {
+        let mut config = EventConfig::new("Sync; planning", "2025-04-15", "10:00");
+        config.location = Some("Room 1, HQ".to_string());
+        config.url = Some("https://example.com/plan".to_string());
+        config.availability = Some(EventAvailability::Free);
+        config.emails = vec!["jane@example.com".to_string()];
+        config.reminder = Some(15);
+        config.recurrence = Some(RecurrencePattern {
+            frequency: RecurrenceFrequency::Weekly,
+            interval: 2,
+            end_date: None,
+            count: Some(5),
+            days_of_week: vec![1, 3],
+            ..RecurrencePattern::new(RecurrenceFrequency::Weekly)
+        });
+
+        let event = vevent(&config, "abc@ducktape", stamp())?;
+        assert!(event.starts_with("BEGIN:VEVENT\r\nUID:abc@ducktape\r\n"));
+        assert!(event.contains("DTSTAMP:20250401T080000Z\r\n"));
+        assert!(event.contains("SUMMARY:Sync\\; planning\r\n"));
+        assert!(event.contains("DTSTART:20250415T100000\r\nDTEND:20250415T110000\r\n"));
+        assert!(event.contains("RRULE:FREQ=WEEKLY;INTERVAL=2;COUNT=5;BYDAY=MO,WE\r\n"));
+        assert!(event.contains("LOCATION:Room 1\\, HQ\r\n"));
+        assert!(event.contains("URL:https://example.com/plan\r\nTRANSP:TRANSPARENT\r\n"));
+        assert!(event.contains("ATTENDEE;RSVP=TRUE:mailto:jane@example.com\r\n"));
+        assert!(event.contains("TRIGGER:-PT15M\r\n"));
+        assert!(event.ends_with("END:VEVENT\r\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invitation_requests_a_reply() -> Result<()> {
+        let mut config = EventConfig::new("Review", "2025-04-15", "10:00");
+        config.emails = vec!["jane@example.com".to_string()];
+        let ics = invitation(&vevent(&config, "x", stamp())?);
+        assert!(ics.contains("CALSCALE:GREGORIAN\r\nMETHOD:REQUEST\r\nBEGIN:VEVENT\r\n"));
+        assert!(ics.contains("ATTENDEE;RSVP=TRUE:mailto:jane@example.com\r\n"));
+        assert!(!vcalendar("").contains("METHOD"));
+
+        let event = with_organizer(&vevent(&config, "x", stamp())?, "bob@example.com");
+        assert!(event.ends_with("ORGANIZER:mailto:bob@example.com\r\nEND:VEVENT\r\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_all_day_and_timezone() -> Result<()> {
+        let mut config = EventConfig::new("Offsite", "2025-04-15", "00:00");
+        config.all_day = true;
+        config.end_date = Some("2025-04-16".to_string());
+        let event = vevent(&config, "x", stamp())?;
+        assert!(event.contains("DTSTART;VALUE=DATE:20250415\r\nDTEND;VALUE=DATE:20250417\r\n"));
+
+        let mut config = EventConfig::new("Call", "2025-04-15", "09:30");
+        config.end_time = Some("10:00".to_string());
+        config.timezone = Some("Europe/London".to_string());
+        let event = vevent(&config, "x", stamp())?;
+        assert!(event.contains("DTSTART;TZID=Europe/London:20250415T093000\r\n"));
+        assert!(event.contains("DTEND;TZID=Europe/London:20250415T100000\r\n"));
+
+        assert!(vevent(&EventConfig::new("Bad", "15/04/2025", "09:30"), "x", stamp()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_fold() {
+        let line = format!("DESCRIPTION:{}", "a".repeat(100));
+        let folded = fold(&line);
+        let lines: Vec<&str> = folded.trim_end().split("\r\n").collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), 75);
+        assert!(lines[1].starts_with(' '));
+    }
+}
@@ -0,0 +1,131 @@
+//! Calendar backend keeping each calendar in an .ics file, e.g. ~/.ducktape/calendars/Work.ics
+//
+// A calendar exists when its file does; `Calendar.ics` is created for the first event when
+// there are none. The files can be subscribed to or imported by any calendar application.
+
+use super::ics::{new_uid, vcalendar, vevent};
+use super::{CalendarBackend, file_name};
+use crate::calendar::EventConfig;
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use chrono::Utc;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Calendar used when no .ics file exists yet
+pub const DEFAULT_CALENDAR: &str = "Calendar";
+
+#[derive(Debug, Clone)]
+pub struct IcsCalendar {
+    dir: PathBuf,
+}
+
+impl IcsCalendar {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// File of a calendar
+    pub fn path(&self, calendar: &str) -> PathBuf {
+        self.dir.join(format!("{}.ics", file_name(calendar)))
+    }
+
+    /// Names of the .ics files, or the default calendar when there are none
+    pub fn calendar_names(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        if self.dir.exists() {
+            for entry in fs::read_dir(&self.dir)
+                .with_context(|| format!("Failed to read {}", self.dir.display()))?
+            {
+                let path = entry?.path();
+                if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("ics")) {
+                    if let Some(stem) = path.file_stem() {
+                        names.push(stem.to_string_lossy().to_string());
+                    }
+                }
+            }
+        }
+        names.sort();
+        if names.is_empty() {
+            names.push(DEFAULT_CALENDAR.to_string());
+        }
+        Ok(names)
+    }
+
+    /// Add an event to the calendar named in `config.calendars`; returns its UID
+    pub fn add_event(&self, config: &EventConfig) -> Result<String> {
+        let calendar = config.calendars.first().map(String::as_str).unwrap_or(DEFAULT_CALENDAR);
+        let path = self.path(calendar);
+        let uid = new_uid();
+        let event = vevent(config, &uid, Utc::now())?;
+
+        let content = if path.exists() {
+            let existing = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let end = existing
+                .rfind("END:VCALENDAR")
+                .ok_or_else(|| anyhow!("{} is not an iCalendar file", path.display()))?;
+            format!("{}{}{}", &existing[..end], event, &existing[end..])
+        } else {
+            fs::create_dir_all(&self.dir)
+                .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+            vcalendar(&event)
+        };
+        fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(uid)
+    }
+}
+
+#[async_trait]
+impl CalendarBackend for IcsCalendar {
+    async fn calendars(&self) -> Result<Vec<String>> {
+        self.calendar_names()
+    }
+
+    async fn default_calendar(&self) -> Result<String> {
+        Ok(self.calendar_names()?.remove(0))
+    }
+
+    async fn create_event(&self, config: EventConfig) -> Result<String> {
+        let uid = self.add_event(&config)?;
+        println!(
+            "Event '{}' added to {}",
+            config.title,
+            self.path(config.calendars.first().map(String::as_str).unwrap_or(DEFAULT_CALENDAR))
+                .display()
+        );
+        tracing::debug!("Created event {} in the .ics store", uid);
+        Ok(uid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_add_events() -> Result<()> {
+        let dir = tempdir()?;
+        let store = IcsCalendar::new(dir.path().join("calendars"));
+        assert_eq!(store.calendar_names()?, vec![DEFAULT_CALENDAR]);
+
+        let mut config = EventConfig::new("Standup", "2025-04-15", "09:00");
+        config.calendars = vec!["Work".to_string()];
+        store.add_event(&config)?;
+        config.title = "Retro".to_string();
+        store.add_event(&config)?;
+
+        assert_eq!(store.calendar_names()?, vec!["Work"]);
+        let content = fs::read_to_string(store.path("Work"))?;
+        assert!(content.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(content.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(content.matches("BEGIN:VEVENT").count(), 2);
+        assert!(content.find("SUMMARY:Standup").unwrap() < content.find("SUMMARY:Retro").unwrap());
+        Ok(())
+    }
+}
@@ -0,0 +1,226 @@
+//! Storage backends for calendar events, reminders and notes.
+//
+// DuckTape drives Calendar.app, Reminders.app and Notes.app with AppleScript, which only
+// works on macOS. Each domain has a backend trait here, implemented for the Apple apps and for
+// storage that works anywhere: .ics files or a CalDAV server for events, and text files for
+// reminders and notes. `calendar`, `todo` and `notes` go through the backend chosen in the
+// `[backend]` section of config.toml. The default is the Apple apps on macOS and local files
+// elsewhere, and choosing an Apple backend on another platform fails with a `PlatformError`.
+
+pub mod apple;
+pub mod caldav;
+pub mod file_todos;
+pub mod ics;
+pub mod ics_store;
+pub mod text_notes;
+
+use crate::calendar::EventConfig;
+use crate::config::{CalendarBackendKind, Config, NotesBackendKind, TodoBackendKind};
+use crate::notes::{NoteConfig, NoteItem};
+use crate::state::CalendarItem;
+use crate::todo::{TodoConfig, TodoItem};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+
+/// Features that need an Apple app
+#[derive(Debug, thiserror::Error)]
+pub enum PlatformError {
+    #[error(
+        "{0} is only available on macOS. Use local storage instead by setting backend.calendar, backend.todo or backend.notes in config.toml"
+    )]
+    AppleOnly(String),
+}
+
+/// Where calendar events are created
+#[async_trait]
+pub trait CalendarBackend: Send + Sync {
+    /// Names of the calendars events can be created in
+    async fn calendars(&self) -> Result<Vec<String>>;
+
+    /// Calendar used when neither the command nor calendar.default names one
+    async fn default_calendar(&self) -> Result<String>;
+
+    /// Create an event in the calendar named in `config.calendars`, returning its UID
+    async fn create_event(&self, config: EventConfig) -> Result<String>;
+
+    /// Whether attendees added to an event are sent an invitation by the calendar itself
+    fn sends_invitations(&self) -> bool {
+        false
+    }
+
+    /// Change an event DuckTape knows about to match `config`
+    async fn update_event(&self, existing: &CalendarItem, _config: EventConfig) -> Result<()> {
+        Err(anyhow!(
+            "This calendar backend cannot update '{}'; import with --on-duplicate skip or duplicate",
+            existing.title
+        ))
+    }
+
+    /// Delete an event DuckTape knows about
+    async fn delete_event(&self, existing: &CalendarItem) -> Result<()> {
+        Err(anyhow!("This calendar backend cannot delete '{}'", existing.title))
+    }
+}
+
+/// Where reminders are kept
+#[async_trait]
+pub trait TodoBackend: Send + Sync {
+    async fn lists(&self) -> Result<Vec<String>>;
+
+    /// Reminders of one list, or of all lists
+    async fn todos(&self, list: Option<&str>) -> Result<Vec<TodoItem>>;
+
+    async fn create_todo(&self, config: TodoConfig<'_>) -> Result<()>;
+
+    async fn delete_todo(&self, title: &str, list: Option<&str>) -> Result<()>;
+}
+
+/// Where notes are kept
+#[async_trait]
+pub trait NotesBackend: Send + Sync {
+    async fn create_note(&self, config: NoteConfig<'_>) -> Result<()>;
+
+    async fn list_notes(&self) -> Result<Vec<NoteItem>>;
+
+    async fn folders(&self) -> Result<Vec<String>>;
+
+    async fn create_folder(&self, name: &str) -> Result<()>;
+
+    /// Delete a folder together with the notes in it
+    async fn delete_folder(&self, name: &str) -> Result<()>;
+
+    /// Move a note, found in `from` or in any folder, to the folder `to`
+    async fn move_note(&self, title: &str, from: Option<&str>, to: &str) -> Result<()>;
+
+    async fn delete_note(&self, title: &str, folder: Option<&str>) -> Result<()>;
+
+    async fn append_note(
+        &self,
+        title: &str,
+        text: &str,
+        folder: Option<&str>,
+        markdown: bool,
+    ) -> Result<()>;
+
+    async fn edit_note(
+        &self,
+        title: &str,
+        content: &str,
+        folder: Option<&str>,
+        markdown: bool,
+    ) -> Result<()>;
+
+    async fn search_notes(&self, keyword: &str) -> Result<Vec<NoteItem>>;
+}
+
+/// Fail with a `PlatformError` unless the Apple apps are available
+///
+/// A script handler (see `script_runner::set_script_handler`) stands in for the apps anywhere.
+pub(crate) fn apple_only(feature: &str) -> Result<()> {
+    if cfg!(target_os = "macos") || crate::script_runner::has_script_handler() {
+        Ok(())
+    } else {
+        Err(PlatformError::AppleOnly(feature.to_string()).into())
+    }
+}
+
+/// Name usable as a file name, for calendars, lists, folders and note titles
+pub(crate) fn file_name(name: &str) -> String {
+    let cleaned: String = name
+        .trim()
+        .chars()
+        .map(|c| if matches!(c, '/' | '\\' | ':' | '\0') { '-' } else { c })
+        .collect();
+    cleaned.trim_start_matches('.').to_string()
+}
+
+/// The calendar backend configured in `config`
+pub fn calendar_for(config: &Config) -> Result<Box<dyn CalendarBackend>> {
+    Ok(match config.backend.calendar_kind() {
+        CalendarBackendKind::Apple => {
+            apple_only("Calendar.app")?;
+            Box::new(apple::AppleCalendar)
+        }
+        CalendarBackendKind::Ics => {
+            Box::new(ics_store::IcsCalendar::new(config.backend.data_dir()?.join("calendars")))
+        }
+        CalendarBackendKind::CalDav => {
+            Box::new(caldav::CalDavCalendar::from_config(&config.backend.caldav)?)
+        }
+    })
+}
+
+/// The reminders backend configured in `config`
+pub fn todos_for(config: &Config) -> Result<Box<dyn TodoBackend>> {
+    Ok(match config.backend.todo_kind() {
+        TodoBackendKind::Apple => {
+            apple_only("Reminders.app")?;
+            Box::new(apple::AppleReminders)
+        }
+        TodoBackendKind::File => Box::new(file_todos::FileTodos::new(
+            config.backend.data_dir()?.join("todos"),
+            config.todo.default_list.as_deref().unwrap_or(file_todos::DEFAULT_LIST),
+        )),
+    })
+}
+
+/// The notes backend configured in `config`
+pub fn notes_for(config: &Config) -> Result<Box<dyn NotesBackend>> {
+    Ok(match config.backend.notes_kind() {
+        NotesBackendKind::Apple => {
+            apple_only("Notes.app")?;
+            Box::new(apple::AppleNotes)
+        }
+        NotesBackendKind::Text => Box::new(text_notes::TextNotes::new(
+            config.backend.data_dir()?.join("notes"),
+            config.notes.default_folder.as_deref().unwrap_or(text_notes::DEFAULT_FOLDER),
+        )),
+    })
+}
+
+/// The calendar backend of the current configuration
+pub fn calendar() -> Result<Box<dyn CalendarBackend>> {
+    calendar_for(&Config::load()?)
+}
+
+/// The reminders backend of the current configuration
+pub fn todos() -> Result<Box<dyn TodoBackend>> {
+    todos_for(&Config::load()?)
+}
+
+/// The notes backend of the current configuration
+pub fn notes() -> Result<Box<dyn NotesBackend>> {
+    notes_for(&Config::load()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(file_name(" Work/Personal "), "Work-Personal");
+        assert_eq!(file_name("../secrets"), "-secrets");
+        assert_eq!(file_name("Q1: plans"), "Q1- plans");
+    }
+
+    #[test]
+    fn test_apple_backends_need_macos() {
+        let mut config = Config::default();
+        config.backend.calendar = Some(CalendarBackendKind::Apple);
+        config.backend.data_dir = Some(std::env::temp_dir().display().to_string());
+        let result = calendar_for(&config);
+        if cfg!(target_os = "macos") {
+            assert!(result.is_ok());
+        } else {
+            let e = result.err().unwrap();
+            assert!(matches!(e.downcast_ref(), Some(PlatformError::AppleOnly(_))));
+        }
+
+        config.backend.calendar = Some(CalendarBackendKind::Ics);
+        assert!(calendar_for(&config).is_ok());
+        // CalDAV needs a server URL
+        config.backend.calendar = Some(CalendarBackendKind::CalDav);
+        assert!(calendar_for(&config).is_err());
+    }
+}
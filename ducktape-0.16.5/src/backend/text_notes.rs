@@ -0,0 +1,287 @@
+//! Notes backend keeping each note in a text file, e.g. ~/.ducktape/notes/Work/Ideas.md
+//
+// Folders are directories and the note title is the file name. Markdown notes are kept as
+// Markdown rather than rendered to HTML like in Notes.app.
+
+use super::{NotesBackend, file_name};
+use crate::notes::{NoteConfig, NoteItem, NotesError};
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Folder used when neither the command nor notes.default_folder names one
+pub const DEFAULT_FOLDER: &str = "Notes";
+
+fn format_time(time: std::io::Result<SystemTime>) -> Option<String> {
+    time.ok()
+        .map(|time| DateTime::<Local>::from(time).format("%Y-%m-%d %H:%M").to_string())
+}
+
+#[derive(Debug, Clone)]
+pub struct TextNotes {
+    dir: PathBuf,
+    default_folder: String,
+}
+
+impl TextNotes {
+    pub fn new(dir: impl Into<PathBuf>, default_folder: &str) -> Self {
+        Self { dir: dir.into(), default_folder: default_folder.to_string() }
+    }
+
+    fn path(&self, title: &str, folder: &str) -> PathBuf {
+        self.dir.join(file_name(folder)).join(format!("{}.md", file_name(title)))
+    }
+
+    fn folder_names(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        if self.dir.exists() {
+            for entry in fs::read_dir(&self.dir)
+                .with_context(|| format!("Failed to read {}", self.dir.display()))?
+            {
+                let entry = entry?;
+                if entry.file_type()?.is_dir() {
+                    names.push(entry.file_name().to_string_lossy().to_string());
+                }
+            }
+        }
+        if !names.iter().any(|name| name.eq_ignore_ascii_case(&self.default_folder)) {
+            names.push(self.default_folder.clone());
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    fn notes_in(&self, folder: &str) -> Result<Vec<(NoteItem, PathBuf)>> {
+        let dir = self.dir.join(file_name(folder));
+        let mut notes = Vec::new();
+        if !dir.exists() {
+            return Ok(notes);
+        }
+        for entry in
+            fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "md") {
+                continue;
+            }
+            let Some(title) = path.file_stem().map(|stem| stem.to_string_lossy().to_string())
+            else {
+                continue;
+            };
+            let metadata = fs::metadata(&path)?;
+            notes.push((
+                NoteItem {
+                    title,
+                    folder: folder.to_string(),
+                    created: format_time(metadata.created()),
+                    modified: format_time(metadata.modified()),
+                    locked: false,
+                },
+                path,
+            ));
+        }
+        notes.sort_by(|a, b| a.0.title.cmp(&b.0.title));
+        Ok(notes)
+    }
+
+    fn all_notes(&self) -> Result<Vec<(NoteItem, PathBuf)>> {
+        let mut notes = Vec::new();
+        for folder in self.folder_names()? {
+            notes.extend(self.notes_in(&folder)?);
+        }
+        Ok(notes)
+    }
+
+    /// File of an existing note, in `folder` or in any folder
+    fn find(&self, title: &str, folder: Option<&str>) -> Result<PathBuf> {
+        let notes = match folder {
+            Some(folder) => self.notes_in(folder)?,
+            None => self.all_notes()?,
+        };
+        notes
+            .into_iter()
+            .find(|(note, _)| note.title.eq_ignore_ascii_case(&file_name(title)))
+            .map(|(_, path)| path)
+            .ok_or_else(|| NotesError::NoteNotFound(title.to_string()).into())
+    }
+
+    fn write(path: &Path, content: &str) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+#[async_trait]
+impl NotesBackend for TextNotes {
+    async fn create_note(&self, config: NoteConfig<'_>) -> Result<()> {
+        let folder = config.folder.unwrap_or(&self.default_folder);
+        let path = self.path(config.title, folder);
+        if path.exists() {
+            return Err(anyhow!("Note '{}' already exists in {}", config.title, folder));
+        }
+        let mut content = config.content.to_string();
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        Self::write(&path, &content)?;
+        println!("Note '{}' saved to {}", config.title, path.display());
+        Ok(())
+    }
+
+    async fn list_notes(&self) -> Result<Vec<NoteItem>> {
+        Ok(self.all_notes()?.into_iter().map(|(note, _)| note).collect())
+    }
+
+    async fn folders(&self) -> Result<Vec<String>> {
+        self.folder_names()
+    }
+
+    async fn create_folder(&self, name: &str) -> Result<()> {
+        let dir = self.dir.join(file_name(name));
+        if dir.exists() {
+            return Err(anyhow!("Folder '{}' already exists", name));
+        }
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))
+    }
+
+    async fn delete_folder(&self, name: &str) -> Result<()> {
+        let dir = self.dir.join(file_name(name));
+        if !dir.is_dir() {
+            return Err(NotesError::FolderNotFound(name.to_string()).into());
+        }
+        fs::remove_dir_all(&dir).with_context(|| format!("Failed to delete {}", dir.display()))
+    }
+
+    async fn move_note(&self, title: &str, from: Option<&str>, to: &str) -> Result<()> {
+        if !self.folder_names()?.iter().any(|folder| folder.eq_ignore_ascii_case(to)) {
+            return Err(NotesError::FolderNotFound(to.to_string()).into());
+        }
+        let source = self.find(title, from)?;
+        let target = self.path(&source.file_stem().unwrap_or_default().to_string_lossy(), to);
+        if target.exists() {
+            return Err(anyhow!("Note '{}' already exists in {}", title, to));
+        }
+        if let Some(dir) = target.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        fs::rename(&source, &target).with_context(|| format!("Failed to move {}", source.display()))
+    }
+
+    async fn delete_note(&self, title: &str, folder: Option<&str>) -> Result<()> {
+        let path = self.find(title, folder)?;
+        fs::remove_file(&path).with_context(|| format!("Failed to delete {}", path.display()))
+    }
+
+    async fn append_note(
+        &self,
+        title: &str,
+        text: &str,
+        folder: Option<&str>,
+        _markdown: bool,
+    ) -> Result<()> {
+        let path = self.find(title, folder)?;
+        let mut content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(text);
+        content.push('\n');
+        Self::write(&path, &content)
+    }
+
+    async fn edit_note(
+        &self,
+        title: &str,
+        content: &str,
+        folder: Option<&str>,
+        _markdown: bool,
+    ) -> Result<()> {
+        let path = self.find(title, folder)?;
+        Self::write(&path, &format!("{}\n", content.trim_end()))
+    }
+
+    async fn search_notes(&self, keyword: &str) -> Result<Vec<NoteItem>> {
+        let keyword = keyword.to_lowercase();
+        let mut found = Vec::new();
+        for (note, path) in self.all_notes()? {
+            let content = fs::read_to_string(&path).unwrap_or_default();
+            if note.title.to_lowercase().contains(&keyword)
+                || content.to_lowercase().contains(&keyword)
+            {
+                found.push(note);
+            }
+        }
+        Ok(found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_note_lifecycle() -> Result<()> {
+        let dir = tempdir()?;
+        let backend = TextNotes::new(dir.path(), DEFAULT_FOLDER);
+        assert_eq!(backend.folders().await?, vec![DEFAULT_FOLDER]);
+
+        backend.create_note(NoteConfig::new("Groceries", "milk")).await?;
+        let mut config = NoteConfig::new("Ideas", "# Launch plan");
+        config.folder = Some("Work");
+        backend.create_note(config).await?;
+        assert!(backend.create_note(NoteConfig::new("Groceries", "eggs")).await.is_err());
+
+        assert_eq!(backend.folders().await?, vec![DEFAULT_FOLDER, "Work"]);
+        let notes = backend.list_notes().await?;
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[1].folder, "Work");
+        assert!(notes[0].modified.is_some());
+
+        backend.append_note("groceries", "bread", None, false).await?;
+        let path = backend.path("Groceries", DEFAULT_FOLDER);
+        assert_eq!(fs::read_to_string(&path)?, "milk\nbread\n");
+        assert_eq!(backend.search_notes("BREAD").await?[0].title, "Groceries");
+        assert_eq!(backend.search_notes("launch").await?[0].title, "Ideas");
+
+        backend.edit_note("Ideas", "Ship it", Some("Work"), true).await?;
+        assert_eq!(fs::read_to_string(backend.path("Ideas", "Work"))?, "Ship it\n");
+
+        backend.delete_note("Ideas", Some("Work")).await?;
+        let error = backend.delete_note("Ideas", None).await.err().unwrap();
+        assert!(matches!(error.downcast_ref(), Some(NotesError::NoteNotFound(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_folders_and_moving_notes() -> Result<()> {
+        let dir = tempdir()?;
+        let backend = TextNotes::new(dir.path(), DEFAULT_FOLDER);
+        backend.create_note(NoteConfig::new("Groceries", "milk")).await?;
+
+        let error = backend.move_note("Groceries", None, "Archive").await.err().unwrap();
+        assert!(matches!(error.downcast_ref(), Some(NotesError::FolderNotFound(_))));
+
+        backend.create_folder("Archive").await?;
+        assert!(backend.create_folder("Archive").await.is_err());
+        backend.move_note("groceries", Some(DEFAULT_FOLDER), "Archive").await?;
+        let notes = backend.list_notes().await?;
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].folder, "Archive");
+        assert_eq!(fs::read_to_string(backend.path("Groceries", "Archive"))?, "milk\n");
+
+        backend.delete_folder("Archive").await?;
+        assert!(backend.list_notes().await?.is_empty());
+        let error = backend.delete_folder("Archive").await.err().unwrap();
+        assert!(matches!(error.downcast_ref(), Some(NotesError::FolderNotFound(_))));
+        Ok(())
+    }
+}
@@ -1,9 +1,10 @@
 use anyhow::Result;
 use clap::Parser;
 use ducktape::api_server;
+use ducktape::app_state::SharedState;
 use ducktape::config::Config;
-use log::{error, info};
 use std::path::PathBuf;
+use tracing::{error, info};
 
 /// Command line arguments for the API server
 #[derive(Parser, Debug)]
@@ -25,7 +26,7 @@ struct Cli {
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize logger
-    env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
+    ducktape::logging::init(&ducktape::logging::LogOptions::default());
 
     // Parse command line arguments
     let cli = Cli::parse();
@@ -49,7 +50,7 @@ async fn main() -> Result<()> {
     };
 
     // Start the API server
-    if let Err(e) = api_server::start_api_server(config, &address).await {
+    if let Err(e) = api_server::start_api_server(SharedState::new(config), &address).await {
         error!("API server error: {}", e);
     }
 
@@ -1,13 +1,13 @@
 use anyhow::Result;
 use ducktape::command_processor::{CommandArgs, CommandProcessor};
-use log::{debug, info};
 use std::env;
 use std::path::Path;
+use tracing::{debug, info};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize logging
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    ducktape::logging::init(&ducktape::logging::LogOptions::default());
 
     // Load environment variables from .env file
     if let Err(e) = load_env_file() {
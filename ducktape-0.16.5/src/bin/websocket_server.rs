@@ -1,7 +1,7 @@
 use anyhow::Result;
-use ducktape::{api_server, config::Config};
-use log::{error, info};
+use ducktape::{api_server, app_state::SharedState, config::Config};
 use tokio::sync::mpsc;
+use tracing::{error, info};
 
 /// API server entry point for DuckTape
 ///
@@ -12,7 +12,7 @@ use tokio::sync::mpsc;
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize logger
-    env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
+    ducktape::logging::init(&ducktape::logging::LogOptions::default());
 
     info!("Starting WebSocket server");
 
@@ -24,11 +24,11 @@ async fn main() -> Result<()> {
 
     // Start the API server with websocket support
     let api_server_handle = {
-        let config = config.clone();
+        let shared = SharedState::new(config.clone());
         tokio::spawn(async move {
             // Start the API server on localhost:3000
             const API_ADDRESS: &str = "127.0.0.1:3000";
-            if let Err(e) = api_server::start_api_server(config, API_ADDRESS).await {
+            if let Err(e) = api_server::start_api_server(shared, API_ADDRESS).await {
                 error!("API server error: {}", e);
             }
         })
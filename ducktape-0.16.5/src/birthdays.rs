@@ -0,0 +1,251 @@
+//! Birthdays and anniversaries from Contacts.app as repeating calendar events.
+//
+// `ducktape birthdays sync` reads every person's birthday and anniversary from Contacts.app
+// and creates a yearly all-day event for each, optionally with an alert some days before.
+// Each created event is recorded in ~/.ducktape/birthdays.json, keyed by person, occasion and
+// day, so running sync again only adds dates that are new or have changed. Contacts.app
+// stores birthdays without a year as 1604; only the month and day are used.
+
+use crate::calendar::{EventConfig, RecurrenceFrequency, RecurrencePattern};
+use crate::script_runner::run_applescript_async;
+use crate::state::{BirthdayRecord, StateManager};
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, Local, NaiveDate};
+use std::collections::HashSet;
+use std::fmt;
+use tracing::{debug, info, warn};
+
+/// Alerts go off at 09:00, this many minutes after the start of an all-day event
+const ALERT_MINUTES_INTO_DAY: i32 = 9 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Occasion {
+    Birthday,
+    Anniversary,
+}
+
+impl Occasion {
+    fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "birthday" => Some(Occasion::Birthday),
+            "anniversary" => Some(Occasion::Anniversary),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Occasion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Occasion::Birthday => "birthday",
+            Occasion::Anniversary => "anniversary",
+        })
+    }
+}
+
+/// A birthday or anniversary read from Contacts.app
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContactDate {
+    pub name: String,
+    pub occasion: Occasion,
+    pub date: NaiveDate,
+}
+
+impl ContactDate {
+    /// Identifies the date in `birthdays.json`, e.g. `birthday:Jane Doe:03-14`
+    pub fn key(&self) -> String {
+        format!("{}:{}:{}", self.occasion, self.name.trim(), self.date.format("%m-%d"))
+    }
+
+    pub fn title(&self) -> String {
+        format!("{}'s {}", self.name.trim(), self.occasion)
+    }
+}
+
+const CONTACT_DATES_SCRIPT: &str = r#"on pad(n)
+    return text -2 thru -1 of ("0" & (n as integer))
+end pad
+
+on fmt(d)
+    return (year of d as text) & "-" & my pad(month of d as integer) & "-" & my pad(day of d)
+end fmt
+
+tell application "Contacts"
+    try
+        set output to ""
+        repeat with p in people
+            set theName to name of p
+            set theBirthday to birth date of p
+            if theBirthday is not missing value then
+                set output to output & theName & tab & "birthday" & tab & my fmt(theBirthday) & linefeed
+            end if
+            repeat with d in custom dates of p
+                if (label of d as text) contains "Anniversary" then
+                    set output to output & theName & tab & "anniversary" & tab & my fmt(value of d) & linefeed
+                end if
+            end repeat
+        end repeat
+        return output
+    on error errMsg
+        return "Error: " & errMsg
+    end try
+end tell"#;
+
+/// Parse the tab separated `name, occasion, YYYY-MM-DD` lines from Contacts.app
+pub fn parse_contact_dates(output: &str) -> Vec<ContactDate> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() < 3 || fields[0].trim().is_empty() {
+                return None;
+            }
+            Some(ContactDate {
+                name: fields[0].trim().to_string(),
+                occasion: Occasion::parse(fields[1])?,
+                date: NaiveDate::parse_from_str(fields[2].trim(), "%Y-%m-%d").ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Read every birthday and anniversary from Contacts.app
+pub async fn fetch_contact_dates() -> Result<Vec<ContactDate>> {
+    let output = run_applescript_async(CONTACT_DATES_SCRIPT).await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || stdout.starts_with("Error: ") {
+        let error = if stdout.starts_with("Error: ") {
+            stdout.replace("Error: ", "")
+        } else {
+            String::from_utf8_lossy(&output.stderr).to_string()
+        };
+        return Err(anyhow!("Failed to read birthdays from Contacts.app: {}", error.trim()));
+    }
+    let dates = parse_contact_dates(&stdout);
+    debug!("Found {} birthday(s) and anniversaries in Contacts.app", dates.len());
+    Ok(dates)
+}
+
+/// The first day on or after `today` with the month and day of `date`
+///
+/// February 29 falls on February 28 in years without it, so the yearly event starts there.
+pub fn next_occurrence(date: NaiveDate, today: NaiveDate) -> NaiveDate {
+    let on = |year: i32| {
+        NaiveDate::from_ymd_opt(year, date.month(), date.day())
+            .or_else(|| NaiveDate::from_ymd_opt(year, date.month(), date.day() - 1))
+            .unwrap_or(today)
+    };
+    let this_year = on(today.year());
+    if this_year >= today { this_year } else { on(today.year() + 1) }
+}
+
+/// The dates that have no event yet, each once
+pub fn plan_sync(dates: &[ContactDate], synced: &[BirthdayRecord]) -> Vec<ContactDate> {
+    let mut seen: HashSet<String> = synced.iter().map(|record| record.key.clone()).collect();
+    dates.iter().filter(|date| seen.insert(date.key())).cloned().collect()
+}
+
+/// A yearly all-day event for `date`, alerting `remind_days` before at 09:00
+pub fn birthday_event(
+    date: &ContactDate,
+    calendar: Option<&str>,
+    remind_days: Option<u32>,
+    today: NaiveDate,
+) -> EventConfig {
+    let start = next_occurrence(date.date, today).format("%Y-%m-%d").to_string();
+    let mut config = EventConfig::new(&date.title(), &start, "00:00");
+    config.all_day = true;
+    config.calendars = calendar.map(|c| vec![c.to_string()]).unwrap_or_default();
+    config.recurrence = Some(RecurrencePattern::new(RecurrenceFrequency::Yearly));
+    config.reminder = remind_days.map(|days| days as i32 * 24 * 60 - ALERT_MINUTES_INTO_DAY);
+    config.description = Some(format!("{} from Contacts, synced by Ducktape 🦆", date.title()));
+    config
+}
+
+/// Create the events for `dates`, recording each in state, and return how many were created
+pub async fn create_birthday_events(
+    dates: &[ContactDate],
+    calendar: Option<&str>,
+    remind_days: Option<u32>,
+) -> Result<usize> {
+    let manager = StateManager::new()?;
+    let today = Local::now().date_naive();
+    let mut created = 0;
+    for date in dates {
+        let config = birthday_event(date, calendar, remind_days, today);
+        match crate::calendar::create_event(config).await {
+            Ok(()) => {
+                manager.add(BirthdayRecord {
+                    key: date.key(),
+                    title: date.title(),
+                    calendar: calendar.map(str::to_string),
+                    created_at: Local::now().to_rfc3339(),
+                })?;
+                created += 1;
+            }
+            Err(e) => warn!("Failed to create '{}': {}", date.title(), e),
+        }
+    }
+    info!("Created {} of {} birthday event(s)", created, dates.len());
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_parse_contact_dates() {
+        let output = "Jane Doe\tbirthday\t1990-03-14\n\
+                      Sam Lee\tanniversary\t2015-06-20\n\
+                      Kim Park\tbirthday\tnot a date\n\
+                      \tbirthday\t1990-01-01\n";
+        let dates = parse_contact_dates(output);
+        assert_eq!(dates.len(), 2);
+        assert_eq!(dates[0].key(), "birthday:Jane Doe:03-14");
+        assert_eq!(dates[1].title(), "Sam Lee's anniversary");
+    }
+
+    #[test]
+    fn test_next_occurrence() {
+        let today = day("2025-03-14");
+        assert_eq!(next_occurrence(day("1990-03-14"), today), day("2025-03-14"));
+        assert_eq!(next_occurrence(day("1604-01-02"), today), day("2026-01-02"));
+        assert_eq!(next_occurrence(day("2000-02-29"), day("2025-01-01")), day("2025-02-28"));
+        assert_eq!(next_occurrence(day("2000-02-29"), day("2027-03-01")), day("2028-02-29"));
+    }
+
+    #[test]
+    fn test_plan_sync_skips_synced_dates() {
+        let dates = parse_contact_dates(
+            "Jane Doe\tbirthday\t1990-03-14\nJane Doe\tbirthday\t1990-03-14\nSam Lee\tbirthday\t1985-07-01\n",
+        );
+        let synced = vec![BirthdayRecord {
+            key: "birthday:Sam Lee:07-01".to_string(),
+            title: "Sam Lee's birthday".to_string(),
+            calendar: None,
+            created_at: String::new(),
+        }];
+        let planned = plan_sync(&dates, &synced);
+        assert_eq!(planned.len(), 1);
+        assert_eq!(planned[0].name, "Jane Doe");
+    }
+
+    #[test]
+    fn test_birthday_event() {
+        let date = &parse_contact_dates("Jane Doe\tbirthday\t1990-03-10\n")[0];
+        let config = birthday_event(date, Some("Birthdays"), Some(2), day("2025-03-14"));
+        assert_eq!(config.title, "Jane Doe's birthday");
+        assert_eq!(config.start_date, "2026-03-10");
+        assert!(config.all_day);
+        assert_eq!(config.calendars, vec!["Birthdays"]);
+        assert_eq!(config.recurrence.unwrap().frequency, RecurrenceFrequency::Yearly);
+        // Two days before, at 09:00
+        assert_eq!(config.reminder, Some(2 * 24 * 60 - 9 * 60));
+        assert_eq!(birthday_event(date, None, None, day("2025-03-14")).reminder, None);
+    }
+}
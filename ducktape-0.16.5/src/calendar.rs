@@ -1,92 +1,103 @@
-use crate::config::Config;
+use crate::applescript::{Properties, Script, Template};
+use crate::config::{Config, MeetingService};
+use crate::meeting::{MeetingRequest, create_meeting};
+use crate::script_runner::{run_applescript_async, run_jxa_async};
 use crate::state::{CalendarItem, StateManager};
-use crate::zoom::{ZoomClient, ZoomMeetingOptions, calculate_meeting_duration, format_zoom_time};
+use crate::webhooks::{Action, ItemKind};
 use anyhow::{Result, anyhow};
-use chrono::{Datelike, Local, NaiveDateTime, TimeZone};
+use chrono::{Datelike, Local, NaiveDateTime, TimeZone, Timelike};
 use chrono_tz::Tz;
-use log::{debug, error, info};
-use std::process::Command;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
 
+mod calendar_accounts;
+mod calendar_adopt;
+mod calendar_agenda;
 mod calendar_applescript;
+mod calendar_attendees;
+mod calendar_bulk;
 mod calendar_contacts;
+mod calendar_description;
+mod calendar_feed;
 mod calendar_import;
+mod calendar_sync;
 #[cfg(test)]
 mod calendar_tests;
 mod calendar_types;
 mod calendar_validation;
+mod calendar_view;
 
+pub use calendar_accounts::*;
+pub use calendar_adopt::*;
+pub use calendar_agenda::*;
 pub use calendar_applescript::*;
+pub use calendar_attendees::*;
+pub use calendar_bulk::*;
 pub use calendar_contacts::*;
+pub use calendar_description::*;
+pub use calendar_feed::*;
 pub use calendar_import::*;
+pub use calendar_sync::*;
 pub use calendar_types::*;
 pub use calendar_validation::*;
+pub use calendar_view::*;
 
-/// Custom error type for calendar operations
-#[derive(Debug, thiserror::Error)]
-pub enum CalendarError {
-    #[error("Calendar application is not running")]
-    NotRunning,
+/// List calendars; `refresh` asks the backend again instead of using the cached names
+pub async fn list_calendars(refresh: bool) -> Result<()> {
+    let calendars = fetch_calendars(refresh).await?;
 
-    #[error("Calendar '{0}' not found")]
-    #[allow(dead_code)] // Kept for future use
-    CalendarNotFound(String),
-
-    #[error("Invalid date/time format: {0}")]
-    InvalidDateTime(String),
-
-    #[error("AppleScript execution failed: {0}")]
-    ScriptError(String),
-}
-
-pub async fn list_calendars() -> Result<()> {
-    // First ensure Calendar.app is running
-    ensure_calendar_running().await?;
-
-    let script = r#"tell application "Calendar"
-        try
-            set output to {}
-            repeat with aCal in calendars
-                set calInfo to name of aCal
-                copy calInfo to end of output
-            end repeat
-            return output
-        on error errMsg
-            error "Failed to list calendars: " & errMsg
-        end try
-    end tell"#;
-
-    let output = tokio::process::Command::new("osascript").arg("-e").arg(script).output().await?;
+    let config = Config::load().unwrap_or_default();
+    // Calendar.app calendars are shown with their account, e.g. "Work (iCloud)"
+    let accounts = if config.backend.calendar_kind() == crate::config::CalendarBackendKind::Apple {
+        calendar_accounts().await.unwrap_or_else(|e| {
+            debug!("Listing calendars without accounts: {}", e);
+            Vec::new()
+        })
+    } else {
+        Vec::new()
+    };
 
-    if output.status.success() {
-        println!("Available calendars:");
-        let calendars = String::from_utf8_lossy(&output.stdout);
-        if calendars.trim().is_empty() {
-            println!("  No calendars found. Please ensure Calendar.app is properly configured.");
-        } else {
-            // Create a HashSet for deduplication
-            let mut unique_calendars: std::collections::HashSet<String> =
-                std::collections::HashSet::new();
-            for calendar in calendars.trim_matches('{').trim_matches('}').split(", ") {
-                unique_calendars.insert(calendar.trim_matches('"').to_string());
+    println!("{}", crate::i18n::text(crate::i18n::Message::AvailableCalendars));
+    if calendars.is_empty() {
+        println!("  No calendars found. Please ensure Calendar.app is properly configured.");
+    } else {
+        // Deduplicate and sort the calendars for consistent display
+        let unique_calendars: std::collections::BTreeSet<String> = calendars.into_iter().collect();
+        for calendar in unique_calendars {
+            let mut labels: Vec<String> = accounts
+                .iter()
+                .filter(|c| c.name == calendar)
+                .map(CalendarAccount::label)
+                .collect();
+            if labels.is_empty() {
+                labels.push(calendar.clone());
             }
-            // Sort the calendars for consistent display
-            let mut sorted_calendars: Vec<_> = unique_calendars.into_iter().collect();
-            sorted_calendars.sort();
-            for calendar in sorted_calendars {
-                println!("  - {}", calendar);
+            let aliases = config.calendar.aliases_of(&calendar);
+            for label in labels {
+                match aliases.as_slice() {
+                    [] => println!("  - {}", label),
+                    names => println!("  - {} (alias: {})", label, names.join(", ")),
+                }
             }
         }
-        Ok(())
-    } else {
-        Err(anyhow!(
-            "Failed to list calendars: {}\nPlease ensure Calendar.app is running and properly configured.",
-            String::from_utf8_lossy(&output.stderr)
-        ))
     }
+    Ok(())
+}
+
+/// Whether `requested` names one of `available`, directly or as "Name (Account)"
+pub fn calendar_exists(available: &[String], requested: &str) -> bool {
+    let (name, account) = split_account(requested);
+    available.iter().any(|calendar| {
+        calendar.eq_ignore_ascii_case(requested)
+            || (account.is_some() && calendar.eq_ignore_ascii_case(name))
+    })
 }
 
-pub async fn create_event(config: EventConfig) -> Result<()> {
+pub async fn create_event(config: EventConfig) -> crate::error::Result<()> {
     debug!("Creating event with config: {:?}", config);
 
     // Fix: Bring validate into scope for EventConfig
@@ -95,25 +106,33 @@ pub async fn create_event(config: EventConfig) -> Result<()> {
     // Validate the event configuration first
     validate_event_config(&config)?;
 
-    // First verify Calendar.app is running and get available calendars
-    ensure_calendar_running().await?;
-
-    // Get list of available calendars first
-    let available_calendars = get_available_calendars().await?;
+    // Load configuration and the list of available calendars
+    let app_config = Config::load()?;
+    let backend = crate::backend::calendar_for(&app_config)?;
+    let mut available_calendars = get_available_calendars().await?;
+    // A calendar added since the names were cached is only found after a refresh
+    let missing = config.calendars.iter().any(|requested| {
+        !calendar_exists(&available_calendars, &app_config.calendar.resolve_calendar(requested))
+    });
+    if missing {
+        available_calendars = fetch_calendars(true).await?;
+    }
     debug!("Available calendars: {:?}", available_calendars);
 
-    // Load configuration and get default calendar if none specified
-    let app_config = Config::load()?;
+    // Get default calendar if none specified
     let requested_calendars = if config.calendars.is_empty() {
-        vec![app_config.calendar.default_calendar.unwrap_or_else(|| "Calendar".to_string())]
+        vec![default_calendar(&app_config, &available_calendars).await?]
     } else {
-        // Validate that specified calendars exist
-        let requested: Vec<String> = config.calendars.iter().map(|s| s.to_string()).collect();
+        // Resolve aliases, then validate that specified calendars exist
+        let requested: Vec<String> = config
+            .calendars
+            .iter()
+            .map(|s| app_config.calendar.resolve_calendar(s))
+            .collect();
         let valid_calendars: Vec<String> = requested
             .into_iter()
             .filter(|cal| {
-                let exists =
-                    available_calendars.iter().any(|available| available.eq_ignore_ascii_case(cal));
+                let exists = calendar_exists(&available_calendars, cal);
                 if !exists {
                     error!("Calendar '{}' not found in available calendars", cal);
                 }
@@ -125,36 +144,79 @@ pub async fn create_event(config: EventConfig) -> Result<()> {
             return Err(anyhow!(
                 "None of the specified calendars were found. Available calendars: {}",
                 available_calendars.join(", ")
-            ));
+            )
+            .into());
         }
         valid_calendars
     };
 
-    let mut last_error = None;
-    let mut success_count = 0;
     let total_calendars = requested_calendars.len();
 
-    // Clone the calendars Vec for state management
-    let calendars_for_state = requested_calendars.clone();
+    // Variables in the description, or in each calendar's default description, are expanded
+    let descriptions =
+        expand_descriptions(&config, &requested_calendars, &app_config.calendar).await;
 
-    for calendar in requested_calendars {
+    // Each calendar is its own osascript run, so create them all at once
+    let creations = requested_calendars.iter().zip(&descriptions).map(|(calendar, description)| {
         info!("Attempting to create event in calendar: {}", calendar);
-        let this_config = EventConfig { calendars: vec![calendar.clone()], ..config.clone() };
+        let this_config = EventConfig {
+            calendars: vec![calendar.clone()],
+            description: description.clone(),
+            ..config.clone()
+        };
+        backend.create_event(this_config)
+    });
+    let results = futures::future::join_all(creations).await;
 
-        match create_single_event(this_config).await {
-            Ok(_) => {
-                success_count += 1;
-                info!("Successfully created event in calendar '{}'", calendar);
+    // Only calendars that actually got the event are recorded in state
+    let mut calendars_for_state = Vec::new();
+    let mut uids = Vec::new();
+    let mut failures = Vec::new();
+    for (calendar, result) in requested_calendars.iter().zip(results) {
+        match result {
+            Ok(uid) => {
+                info!("Successfully created event in calendar '{}' ({})", calendar, uid);
+                calendars_for_state.push(calendar.clone());
+                uids.push(uid);
             }
             Err(e) => {
                 error!("Failed to create event in calendar '{}': {}", calendar, e);
-                last_error = Some(e);
+                failures.push((calendar.clone(), e));
             }
         }
     }
+    let success_count = total_calendars - failures.len();
+
+    if success_count > 0 && !failures.is_empty() {
+        println!(
+            "Warning: event created in {} of {} calendars; failed in:",
+            success_count, total_calendars
+        );
+        for (calendar, e) in &failures {
+            println!("  - {}: {}", calendar, e);
+        }
+    }
 
     if success_count > 0 {
-        // Save the event to state
+        // Attendees only hear of events on calendars that do not send invitations by email
+        let invite = app_config.calendar.invite_emails
+            || (!backend.sends_invitations() && can_email_invitations(&app_config));
+        if invite && !config.emails.is_empty() {
+            let emails: Vec<&str> = config.emails.iter().map(String::as_str).collect();
+            let invited = EventConfig {
+                description: descriptions.iter().flatten().next().cloned(),
+                ..config.clone()
+            };
+            if let Err(e) = send_invite_email(&invited, &emails).await {
+                warn!("Failed to email invitations: {}", e);
+                println!("Warning: event created, but the invitation email failed: {}", e);
+            }
+        }
+
+        let hook = crate::webhooks::event_data(&config, &calendars_for_state);
+
+        // Save the event to state, with the UID of its copy in the first calendar, which is
+        // the calendar updates and deletes look in
         let calendar_item = CalendarItem {
             title: config.title.clone(),
             date: config.start_date.clone(),
@@ -162,19 +224,287 @@ pub async fn create_event(config: EventConfig) -> Result<()> {
             calendars: calendars_for_state,
             all_day: config.all_day,
             location: config.location,
-            description: config.description,
+            description: descriptions.into_iter().flatten().next(),
             email: if !config.emails.is_empty() { Some(config.emails.join(", ")) } else { None },
             reminder: config.reminder,
+            uid: uids.into_iter().next().filter(|uid| !uid.is_empty()),
+            end_time: config.end_time.clone(),
+            recurrence: config.recurrence.as_ref().map(|r| {
+                let mut rule = format!("FREQ={};INTERVAL={}", r.frequency.to_rfc5545(), r.interval);
+                if let Some(count) = r.count {
+                    rule.push_str(&format!(";COUNT={}", count));
+                }
+                rule.push_str(&r.by_rules());
+                rule
+            }),
+            url: config.url.clone(),
+            availability: config.availability.map(|a| a.as_str().to_string()),
         };
         StateManager::new()?.add(calendar_item)?;
         info!("Calendar event created in {}/{} calendars", success_count, total_calendars);
+        crate::webhooks::notify(ItemKind::Event, Action::Created, hook);
         Ok(())
     } else {
-        Err(last_error.unwrap_or_else(|| anyhow!("Failed to create event in any calendar")))
+        let reasons: Vec<String> =
+            failures.iter().map(|(calendar, e)| format!("{}: {}", calendar, e)).collect();
+        Err(anyhow!("Failed to create event in any calendar ({})", reasons.join("; ")).into())
+    }
+}
+
+/// The tracked event `id` names: the one with that UID, or else the one with that title
+///
+/// Titles match ignoring case, only in `calendar` when given. When several events share the
+/// title, the error lists them with their UIDs so that one can be picked.
+pub fn find_event_by_id<'a>(
+    items: &'a [CalendarItem],
+    id: &str,
+    calendar: Option<&str>,
+) -> Result<&'a CalendarItem> {
+    let id = id.trim();
+    if let Some(item) = items.iter().find(|item| item.uid.as_deref() == Some(id)) {
+        return Ok(item);
+    }
+    let matches: Vec<&CalendarItem> = items
+        .iter()
+        .filter(|item| item.title.eq_ignore_ascii_case(id))
+        .filter(|item| {
+            calendar.is_none_or(|calendar| {
+                item.calendars.iter().any(|c| c.eq_ignore_ascii_case(calendar))
+            })
+        })
+        .collect();
+    match matches.as_slice() {
+        [] => Err(anyhow!("No tracked event has the UID or title '{}'", id)),
+        [item] => Ok(item),
+        _ => {
+            let listed: Vec<String> = matches
+                .iter()
+                .map(|item| {
+                    format!(
+                        "  {} {}  {}  {}",
+                        item.date,
+                        item.time,
+                        item.calendars.join(", "),
+                        item.uid.as_deref().unwrap_or("(no UID)")
+                    )
+                })
+                .collect();
+            Err(anyhow!(
+                "{} tracked events are titled '{}'; give the UID of one:\n{}",
+                matches.len(),
+                id,
+                listed.join("\n")
+            ))
+        }
+    }
+}
+
+/// The tracked copy of the event just created from `config`, which has its UID
+pub fn created_event<'a>(
+    items: &'a [CalendarItem],
+    config: &EventConfig,
+) -> Option<&'a CalendarItem> {
+    items.iter().rev().find(|item| {
+        item.title == config.title
+            && item.date == config.start_date
+            && item.time == config.start_time
+    })
+}
+
+/// Change a tracked event in its calendar to `updated`, and in state
+///
+/// `invite` are added to the event's attendees, so that its account invites them.
+pub async fn update_event(
+    existing: &CalendarItem,
+    updated: &CalendarItem,
+    invite: &[String],
+) -> Result<()> {
+    let config = Config::load()?;
+    let backend = crate::backend::calendar_for(&config)?;
+    backend.update_event(existing, calendar_sync::event_config(updated)).await?;
+    if !invite.is_empty() {
+        let emails: Vec<&str> = invite.iter().map(String::as_str).collect();
+        add_attendees(existing, &emails).await?;
+    }
+
+    let same = |other: &CalendarItem| match &existing.uid {
+        Some(uid) => other.uid.as_ref() == Some(uid),
+        None => {
+            other.uid.is_none()
+                && other.title == existing.title
+                && other.date == existing.date
+                && other.time == existing.time
+        }
+    };
+    StateManager::new()?.update(|items: &mut Vec<CalendarItem>| {
+        for item in items.iter_mut().filter(|item| same(item)) {
+            *item = updated.clone();
+        }
+    })?;
+    let hook = serde_json::json!({
+        "title": updated.title,
+        "uid": updated.uid,
+        "start_date": updated.date,
+        "start_time": (!updated.all_day).then_some(&updated.time),
+        "end_time": updated.end_time,
+        "all_day": updated.all_day,
+        "location": updated.location,
+        "calendars": updated.calendars,
+    });
+    crate::webhooks::notify(ItemKind::Event, Action::Updated, hook);
+    Ok(())
+}
+
+/// Delete a tracked event from its calendar and stop tracking it
+pub async fn delete_event(item: &CalendarItem) -> Result<()> {
+    let config = Config::load()?;
+    crate::backend::calendar_for(&config)?.delete_event(item).await?;
+
+    let same = |other: &CalendarItem| match &item.uid {
+        Some(uid) => other.uid.as_ref() == Some(uid),
+        None => {
+            other.uid.is_none()
+                && other.title == item.title
+                && other.date == item.date
+                && other.time == item.time
+        }
+    };
+    StateManager::new()?
+        .update(|items: &mut Vec<CalendarItem>| items.retain(|other| !same(other)))?;
+    let hook = serde_json::json!({
+        "title": item.title,
+        "uid": item.uid,
+        "start_date": item.date,
+        "start_time": (!item.all_day).then_some(&item.time),
+        "end_time": item.end_time,
+        "all_day": item.all_day,
+        "calendars": item.calendars,
+    });
+    crate::webhooks::notify(ItemKind::Event, Action::Deleted, hook);
+    Ok(())
+}
+
+/// Pick a calendar from `available`: the requested one, else the configured default
+///
+/// Names match case-insensitively and are returned as Calendar.app spells them. `None` means
+/// neither exists, so the system default calendar should be used.
+pub fn select_calendar(
+    requested: Option<&str>,
+    configured: Option<&str>,
+    available: &[String],
+) -> Option<String> {
+    [requested, configured].into_iter().flatten().find_map(|name| {
+        let found = |name: &str| {
+            available
+                .iter()
+                .find(|calendar| calendar.eq_ignore_ascii_case(name.trim()))
+                .cloned()
+        };
+        found(name).or_else(|| match split_account(name) {
+            // "Work (iCloud)" keeps its account so the event lands in the right calendar
+            (base, Some(account)) => found(base).map(|calendar| with_account(&calendar, account)),
+            (_, None) => None,
+        })
+    })
+}
+
+/// Ask Calendar.app for the calendar new events go to when none is chosen
+///
+/// Calendar.app does not expose its default calendar to scripts, so the first calendar that
+/// accepts new events is used.
+pub async fn detect_system_default_calendar() -> Result<String> {
+    let script = r#"tell application "Calendar"
+        set writableCalendars to (calendars whose writable is true)
+        if (count of writableCalendars) is 0 then error "No writable calendars"
+        return name of item 1 of writableCalendars
+    end tell"#;
+    let output = run_applescript_async(script).await?;
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() && !name.is_empty() {
+        Ok(name)
+    } else {
+        Err(anyhow!(
+            "Could not find a calendar for new events: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Calendar to use when none is given: `calendar.default` (or the alias it names) if it
+/// exists, otherwise the default calendar of the configured backend
+pub async fn default_calendar(config: &Config, available: &[String]) -> Result<String> {
+    let configured = config
+        .calendar
+        .default_calendar
+        .as_deref()
+        .map(|c| config.calendar.resolve_calendar(c));
+    match select_calendar(None, configured.as_deref(), available) {
+        Some(calendar) => Ok(calendar),
+        None => {
+            if let Some(configured) = configured {
+                warn!(
+                    "Default calendar '{}' not found, using the system default calendar",
+                    configured
+                );
+            }
+            crate::backend::calendar_for(config)?.default_calendar().await
+        }
+    }
+}
+
+/// How long calendar names are reused before the backend is asked again
+pub const CALENDAR_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Calendar names, with when they were fetched
+type CachedCalendars = (Instant, Vec<String>);
+
+/// Calendar names per backend
+static CALENDAR_CACHE: Lazy<Mutex<HashMap<&'static str, CachedCalendars>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn cached_calendars(backend: &str, now: Instant) -> Option<Vec<String>> {
+    let cache = CALENDAR_CACHE.lock().ok()?;
+    cache
+        .get(backend)
+        .filter(|(fetched, _)| now.duration_since(*fetched) < CALENDAR_CACHE_TTL)
+        .map(|(_, calendars)| calendars.clone())
+}
+
+fn store_calendars(backend: &'static str, calendars: &[String], now: Instant) {
+    if let Ok(mut cache) = CALENDAR_CACHE.lock() {
+        cache.insert(backend, (now, calendars.to_vec()));
     }
 }
 
+/// Forget cached calendar names, e.g. after calendars were added or renamed
+pub fn clear_calendar_cache() {
+    if let Ok(mut cache) = CALENDAR_CACHE.lock() {
+        cache.clear();
+    }
+}
+
+/// Names of the calendars of the configured backend, cached for `CALENDAR_CACHE_TTL`
 pub async fn get_available_calendars() -> Result<Vec<String>> {
+    fetch_calendars(false).await
+}
+
+/// Names of the calendars of the configured backend; `refresh` bypasses the cache
+pub async fn fetch_calendars(refresh: bool) -> Result<Vec<String>> {
+    let config = Config::load()?;
+    let kind = config.backend.calendar_kind().as_str();
+    if !refresh {
+        if let Some(calendars) = cached_calendars(kind, Instant::now()) {
+            debug!("Using cached calendar names for {}", kind);
+            return Ok(calendars);
+        }
+    }
+    let calendars = crate::backend::calendar_for(&config)?.calendars().await?;
+    store_calendars(kind, &calendars, Instant::now());
+    Ok(calendars)
+}
+
+/// Names of the calendars in Calendar.app
+pub(crate) async fn calendar_app_calendars() -> Result<Vec<String>> {
     let script = r#"tell application "Calendar"
         try
             set output to {}
@@ -188,7 +518,7 @@ pub async fn get_available_calendars() -> Result<Vec<String>> {
         end try
     end tell"#;
 
-    let output = tokio::process::Command::new("osascript").arg("-e").arg(script).output().await?;
+    let output = run_applescript_async(script).await?;
 
     if output.status.success() {
         let calendars = String::from_utf8_lossy(&output.stdout);
@@ -197,6 +527,7 @@ pub async fn get_available_calendars() -> Result<Vec<String>> {
             .trim_matches('}')
             .split(", ")
             .map(|s| s.trim_matches('"').to_string())
+            .filter(|s| !s.is_empty())
             .collect())
     } else {
         Err(anyhow!(
@@ -206,9 +537,13 @@ pub async fn get_available_calendars() -> Result<Vec<String>> {
     }
 }
 
-async fn create_single_event(config: EventConfig) -> Result<()> {
+/// Create the event in the single calendar of `config`, returning its Calendar.app UID
+pub(crate) async fn create_single_event(config: EventConfig) -> Result<String> {
     debug!("Creating event with config: {:?}", config);
 
+    // "Work (iCloud)" is created in "Work" and then moved to the iCloud calendar
+    let (calendar_name, account_calendar) = resolve_account_calendar(&config.calendars[0]).await;
+
     // Parse start datetime with improved date handling
     let start_datetime = format!(
         "{} {}",
@@ -338,37 +673,30 @@ async fn create_single_event(config: EventConfig) -> Result<()> {
     debug!("Final start time: {}", local_start.format("%Y-%m-%d %H:%M"));
     debug!("Final end time: {}", end_dt.format("%Y-%m-%d %H:%M"));
 
-    // Create Zoom meeting if requested
+    // Create an online meeting if requested
     let mut zoom_meeting_info = String::new();
-    if config.create_zoom_meeting {
-        info!("Creating Zoom meeting for event: {}", config.title);
-        let mut client = ZoomClient::new()?;
-        let zoom_start_time = format_zoom_time(&config.start_date, &config.start_time)?;
-        let duration = if let Some(end_time) = &config.end_time {
-            calculate_meeting_duration(&config.start_time, end_time)?
-        } else {
-            60 // Default 1 hour
-        };
-        let meeting_options = ZoomMeetingOptions {
+    let mut meeting_url = config.zoom_join_url.clone();
+    let meeting_service = config
+        .meeting_service
+        .or(config.create_zoom_meeting.then_some(MeetingService::Zoom));
+    if let Some(service) = meeting_service {
+        info!("Creating {} meeting for event: {}", service.label(), config.title);
+        let request = MeetingRequest {
             topic: config.title.to_string(),
-            start_time: zoom_start_time,
-            duration,
-            password: None,
+            date: config.start_date.clone(),
+            start_time: config.start_time.clone(),
+            end_time: config.end_time.clone(),
             agenda: config.description.clone(),
         };
-        match client.create_meeting(meeting_options).await {
+        match create_meeting(service, &request).await {
             Ok(meeting) => {
-                info!("Created Zoom meeting: ID={}, URL={}", meeting.id, meeting.join_url);
-                let password_info =
-                    meeting.password.map_or(String::new(), |p| format!("\nPassword: {}", p));
-                zoom_meeting_info = format!(
-                    "\n\n--------------------\nZoom Meeting\n--------------------\nJoin URL: {}{}",
-                    meeting.join_url, password_info
-                );
+                zoom_meeting_info = meeting.description_block();
+                meeting_url = Some(meeting.join_url);
             }
             Err(e) => {
-                error!("Failed to create Zoom meeting: {}", e);
-                zoom_meeting_info = "\n\nNote: Zoom meeting creation failed.".to_string();
+                error!("Failed to create {} meeting: {}", service.label(), e);
+                zoom_meeting_info =
+                    format!("\n\nNote: {} meeting creation failed.", service.label());
             }
         }
     } else if let Some(url) = &config.zoom_join_url {
@@ -382,26 +710,38 @@ async fn create_single_event(config: EventConfig) -> Result<()> {
         );
     }
 
-    // Build description with Zoom info
+    // A description with {{zoom_url}} gets the link there instead of the meeting block
+    let description = config
+        .description
+        .as_deref()
+        .map(|d| fill_meeting_url(d, meeting_url.as_deref()));
+    if let (Some(description), Some(url)) = (&description, &meeting_url) {
+        if description.contains(url.as_str()) {
+            zoom_meeting_info.clear();
+        }
+    }
+
+    // Build description with meeting info
     let full_description = if !zoom_meeting_info.is_empty() {
-        match &config.description {
+        match &description {
             Some(desc) if !desc.is_empty() => format!("{}{}", desc, zoom_meeting_info),
             _ => format!("Created by Ducktape 🦆{}", zoom_meeting_info),
         }
     } else {
-        config.description.as_deref().unwrap_or("Created by Ducktape 🦆").to_string()
+        description.unwrap_or_else(|| "Created by Ducktape 🦆".to_string())
     };
 
-    // Build extra properties (location)
-    let mut extra = String::new();
-    if let Some(loc) = &config.location {
-        if !loc.is_empty() {
-            extra.push_str(&format!(", location:\"{}\"", loc));
-        }
-    }
+    // Event properties; every value is escaped by the template layer
+    let properties = Properties::new()
+        .text("summary", &config.title)
+        .script("start date", "startDate")
+        .script("end date", "endDate")
+        .text("description", &full_description)
+        .optional_text("location", config.location.as_deref())
+        .optional_text("url", config.url.as_deref());
 
     // Build attendees block
-    let mut attendees_block = String::new();
+    let mut attendees = Vec::new();
     if !config.emails.is_empty() {
         info!("Adding {} attendee(s): {}", config.emails.len(), config.emails.join(", "));
         for email in &config.emails {
@@ -411,17 +751,20 @@ async fn create_single_event(config: EventConfig) -> Result<()> {
                 debug!("Skipping calendar owner {} as explicit attendee", email);
                 continue;
             }
-            attendees_block.push_str(&format!(
-                r#"
+            attendees.push(
+                Template::new(
+                    r#"
                     try
                         tell newEvent
-                            make new attendee at end of attendees with properties {{email:"{}"}}
+                            make new attendee at end of attendees with properties {email:${email}}
                         end tell
                     on error errMsg
-                        log "Failed to add attendee {}: " & errMsg
+                        log "Failed to add attendee " & ${email} & ": " & errMsg
                     end try"#,
-                email, email
-            ));
+                )
+                .text("email", email)
+                .render()?,
+            );
         }
     }
 
@@ -440,114 +783,122 @@ async fn create_single_event(config: EventConfig) -> Result<()> {
                     .map_err(|e| anyhow!("Invalid recurrence end date: {}", e))?;
             parts.push(format!("UNTIL={}", end_naive.format("%Y%m%dT%H%M%SZ")));
         }
-        if recurrence.frequency == RecurrenceFrequency::Weekly
-            && !recurrence.days_of_week.is_empty()
-        {
-            let days: Vec<&str> = recurrence
-                .days_of_week
-                .iter()
-                .map(|&d| match d {
-                    0 => "SU",
-                    1 => "MO",
-                    2 => "TU",
-                    3 => "WE",
-                    4 => "TH",
-                    5 => "FR",
-                    6 => "SA",
-                    _ => "MO",
-                })
-                .collect();
-            parts.push(format!("BYDAY={}", days.join(",")));
+        let rule_string = format!("{}{}", parts.join(";"), recurrence.by_rules());
+        // Calendar.app has no RDATE; extra dates are created as separate events by the backend
+        let mut excluded = Vec::new();
+        for date in &recurrence.exception_dates {
+            let day = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|e| anyhow!("Invalid excluded date {}: {}", date, e))?;
+            excluded.push(
+                Template::new(
+                    r#"
+                        copy startDate to exDate
+                        set day of exDate to 1
+                        set year of exDate to ${year}
+                        set month of exDate to ${month}
+                        set day of exDate to ${day}
+                        set excluded dates of newEvent to (excluded dates of newEvent) & {exDate}"#,
+                )
+                .number("year", day.year())
+                .number("month", day.month())
+                .number("day", day.day())
+                .render()?,
+            );
         }
-        let rule_string = parts.join(";");
-        format!(
+        Template::new(
             r#"
                     tell newEvent
-                        set its recurrence to "{}"
-                    end tell"#,
-            rule_string
+                        set its recurrence to ${rule}
+                    end tell${excluded}"#,
         )
+        .text("rule", &rule_string)
+        .script("excluded", Script::lines(excluded))
+        .render()?
     } else {
-        String::new()
+        Script::default()
+    };
+
+    let reminder_code = match config.reminder {
+        Some(minutes) => Template::new(
+            r#"set theAlarm to make new display alarm at end of newEvent
+                    set trigger interval of theAlarm to ${interval}"#,
+        )
+        .number("interval", -minutes * 60)
+        .render()?,
+        None => Script::default(),
     };
 
     // Generate AppleScript
-    let script = format!(
+    let script = Template::new(
         r#"tell application "Calendar"
             try
                 set calFound to false
                 repeat with cal in calendars
-                    if name of cal is "{calendar_name}" then
+                    if name of cal is ${calendar_name} then
                         set calFound to true
                         tell cal
                             set startDate to current date
-                            set year of startDate to {start_year}
-                            set month of startDate to {start_month}
-                            set day of startDate to {start_day}
-                            set hours of startDate to {start_hours}
-                            set minutes of startDate to {start_minutes}
+                            set year of startDate to ${start_year}
+                            set month of startDate to ${start_month}
+                            set day of startDate to ${start_day}
+                            set hours of startDate to ${start_hours}
+                            set minutes of startDate to ${start_minutes}
                             set seconds of startDate to 0
                             
                             set endDate to current date
-                            set year of endDate to {end_year}
-                            set month of endDate to {end_month}
-                            set day of endDate to {end_day}
-                            set hours of endDate to {end_hours}
-                            set minutes of endDate to {end_minutes}
+                            set year of endDate to ${end_year}
+                            set month of endDate to ${end_month}
+                            set day of endDate to ${end_day}
+                            set hours of endDate to ${end_hours}
+                            set minutes of endDate to ${end_minutes}
                             set seconds of endDate to 0
                             
-                            set newEvent to make new event with properties {{summary:"{title}", start date:startDate, end date:endDate, description:"{description}"{extra}}}
-                            {all_day_code}
-                            {reminder_code}
-                            {recurrence_code}
-                            {attendees_block}
+                            set newEvent to make new event with properties ${properties}
+                            ${all_day_code}
+                            ${reminder_code}
+                            ${recurrence_code}
+                            ${attendees_block}
                         end tell
                         exit repeat
                     end if
                 end repeat
                 
                 if not calFound then
-                    error "Calendar '{calendar_name}' not found in available calendars"
+                    error "Calendar '" & ${calendar_name} & "' not found in available calendars"
                 end if
                 
-                return "Success: Event created"
+                return "Success: Event created " & (uid of newEvent)
             on error errMsg
                 log errMsg
                 error "Failed to create event: " & errMsg
             end try
         end tell"#,
-        calendar_name = config.calendars[0],
-        title = config.title,
-        description = full_description,
-        start_year = local_start.format("%Y"),
-        start_month = local_start.format("%-m"),
-        start_day = local_start.format("%-d"),
-        start_hours = local_start.format("%-H"),
-        start_minutes = local_start.format("%-M"),
-        end_year = end_dt.format("%Y"),
-        end_month = end_dt.format("%-m"),
-        end_day = end_dt.format("%-d"),
-        end_hours = end_dt.format("%-H"),
-        end_minutes = end_dt.format("%-M"),
-        extra = extra,
-        all_day_code = if config.all_day { "set allday event of newEvent to true" } else { "" },
-        reminder_code = if let Some(minutes) = config.reminder {
-            format!(
-                r#"set theAlarm to make new display alarm at end of newEvent
-                    set trigger interval of theAlarm to -{}"#,
-                minutes * 60
-            )
-        } else {
-            String::new()
-        },
-        recurrence_code = recurrence_code,
-        attendees_block = attendees_block,
-    );
+    )
+    .text("calendar_name", &calendar_name)
+    .script("properties", properties)
+    .number("start_year", local_start.year())
+    .number("start_month", local_start.month())
+    .number("start_day", local_start.day())
+    .number("start_hours", local_start.hour())
+    .number("start_minutes", local_start.minute())
+    .number("end_year", end_dt.year())
+    .number("end_month", end_dt.month())
+    .number("end_day", end_dt.day())
+    .number("end_hours", end_dt.hour())
+    .number("end_minutes", end_dt.minute())
+    .script(
+        "all_day_code",
+        if config.all_day { "set allday event of newEvent to true" } else { "" },
+    )
+    .script("reminder_code", reminder_code)
+    .script("recurrence_code", recurrence_code)
+    .script("attendees_block", Script::lines(attendees))
+    .render()?;
 
     debug!("Generated AppleScript:\n{}", script);
 
     // Execute AppleScript
-    let output = Command::new("osascript").arg("-e").arg(&script).output()?;
+    let output = run_applescript_async(script).await?;
     let result = String::from_utf8_lossy(&output.stdout);
     let error_output = String::from_utf8_lossy(&output.stderr);
 
@@ -557,14 +908,85 @@ async fn create_single_event(config: EventConfig) -> Result<()> {
             config.title,
             local_start.format("%Y-%m-%d %H:%M")
         );
-        Ok(())
+        let uid = result.trim().trim_start_matches("Success: Event created").trim();
+        if let Some(identifier) = account_calendar {
+            if let Err(e) = move_event_to_calendar(uid, &identifier).await {
+                warn!("Event created in '{}' of another account: {}", calendar_name, e);
+                println!(
+                    "Warning: event created in a '{}' calendar, but not the one of {}",
+                    calendar_name, config.calendars[0]
+                );
+            }
+        }
+        // Calendar.app's dictionary has no availability, so it is set through EventKit
+        if let Some(availability) = config.availability {
+            if let Err(e) = apply_availability(uid, availability).await {
+                warn!("Event created, but setting it {} failed: {}", availability.as_str(), e);
+            }
+        }
+        Ok(uid.to_string())
     } else {
         error!("AppleScript error: STDOUT: {} | STDERR: {}", result, error_output);
         Err(anyhow!("Failed to create event: {}", error_output))
     }
 }
 
-async fn ensure_calendar_running() -> Result<()> {
+/// Build the JavaScript for Automation script that sets an event's availability
+///
+/// `uid` is the event's Calendar.app UID, which EventKit calls its external identifier.
+/// Repeating events are changed from this occurrence on.
+pub fn availability_script(uid: &str, availability: EventAvailability) -> Result<String> {
+    let uid: String = uid
+        .trim()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '@' | '.'))
+        .collect();
+    if uid.is_empty() {
+        return Err(anyhow!("Missing event UID"));
+    }
+
+    Ok(format!(
+        r#"ObjC.import('EventKit');
+function run() {{
+    const store = $.EKEventStore.alloc.init;
+    const items = store.calendarItemsWithExternalIdentifier('{uid}');
+    if (items.isNil() || items.count === 0) {{
+        return 'Error: Event not found. Allow Calendar access for osascript in System Settings';
+    }}
+    const error = Ref();
+    for (let i = 0; i < items.count; i++) {{
+        const item = items.objectAtIndex(i);
+        item.availability = {availability};
+        if (!store.saveEventSpanCommitError(item, 1, true, error)) {{
+            return 'Error: ' + ObjC.unwrap(error[0].localizedDescription);
+        }}
+    }}
+    return 'Success: Availability set';
+}}"#,
+        uid = uid,
+        availability = availability.to_eventkit()
+    ))
+}
+
+/// Set the availability of an existing event
+pub async fn apply_availability(uid: &str, availability: EventAvailability) -> Result<()> {
+    let script = availability_script(uid, availability)?;
+    debug!("Executing JXA: {}", script);
+
+    let output = run_jxa_async(&script).await?;
+    let result = String::from_utf8_lossy(&output.stdout);
+
+    if result.contains("Success") {
+        info!("Availability of event {} set to {}", uid, availability.as_str());
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let error_msg = if result.trim().is_empty() { stderr.trim() } else { result.trim() };
+        Err(CalendarError::ScriptError(error_msg.replace("Error: ", "")).into())
+    }
+}
+
+pub(crate) async fn ensure_calendar_running() -> Result<()> {
     let check_script = r#"tell application "Calendar"
         if it is not running then
             launch
@@ -573,12 +995,7 @@ async fn ensure_calendar_running() -> Result<()> {
         return true
     end tell"#;
 
-    let output = tokio::process::Command::new("osascript")
-        .arg("-e")
-        .arg(check_script)
-        .output()
-        .await
-        .map_err(|e| CalendarError::ScriptError(e.to_string()))?;
+    let output = run_applescript_async(check_script).await?;
 
     if output.status.success() { Ok(()) } else { Err(CalendarError::NotRunning.into()) }
 }
@@ -586,11 +1003,11 @@ async fn ensure_calendar_running() -> Result<()> {
 /// Lookup a contact by name and return their email addresses
 pub async fn lookup_contact(name: &str) -> Result<Vec<String>> {
     debug!("Looking up contact: {}", name);
-    let script = format!(
+    let script = Template::new(
         r#"tell application "Contacts"
-            set the_emails to {{}}
+            set the_emails to {}
             try
-                set the_people to (every person whose name contains "{}")
+                set the_people to (every person whose name contains ${name})
                 repeat with the_person in the_people
                     if exists email of the_person then
                         repeat with the_email in (get every email of the_person)
@@ -603,18 +1020,14 @@ pub async fn lookup_contact(name: &str) -> Result<Vec<String>> {
                 return the_emails
             on error errMsg
                 log "Error looking up contact: " & errMsg
-                return {{}}
+                return {}
             end try
         end tell"#,
-        name.replace("\"", "\\\"")
-    );
+    )
+    .text("name", name)
+    .render()?;
 
-    let output = tokio::process::Command::new("osascript")
-        .arg("-e")
-        .arg(&script)
-        .output()
-        .await
-        .map_err(|e| anyhow!("Failed to execute AppleScript: {}", e))?;
+    let output = run_applescript_async(script).await?;
 
     if output.status.success() {
         let emails = String::from_utf8_lossy(&output.stdout);
@@ -0,0 +1,193 @@
+//! Accounts (iCloud, Google, Exchange, ...) of the calendars in Calendar.app.
+//
+// Calendar.app's AppleScript dictionary does not say which account a calendar belongs to, so
+// two calendars called "Work" cannot be told apart there. Accounts are read through EventKit
+// instead. A calendar of a particular account is named "Work (iCloud)" or chosen with
+// `--account`; Calendar.app creates the event in a calendar with that name and EventKit then
+// moves it to the calendar of the requested account.
+
+use crate::script_runner::run_jxa_async;
+use anyhow::{Result, anyhow};
+use tracing::debug;
+
+/// A calendar with the account it belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalendarAccount {
+    pub name: String,
+    /// Account name as shown in Calendar.app, e.g. "iCloud" or "me@example.com"
+    pub account: String,
+    /// EventKit calendar identifier
+    pub identifier: String,
+}
+
+impl CalendarAccount {
+    /// "Work (iCloud)", the name accepted wherever a calendar is asked for
+    pub fn label(&self) -> String {
+        with_account(&self.name, &self.account)
+    }
+}
+
+/// Calendar name qualified with its account, e.g. "Work (iCloud)"
+pub fn with_account(calendar: &str, account: &str) -> String {
+    format!("{} ({})", calendar.trim(), account.trim())
+}
+
+/// Split "Work (iCloud)" into the calendar name and the account
+///
+/// Names without a trailing parenthesis have no account. A calendar whose own name ends in
+/// parentheses is still split; callers check the full name first.
+pub fn split_account(label: &str) -> (&str, Option<&str>) {
+    let label = label.trim();
+    if let Some(rest) = label.strip_suffix(')') {
+        if let Some((name, account)) = rest.rsplit_once(" (") {
+            if !name.trim().is_empty() && !account.trim().is_empty() {
+                return (name.trim(), Some(account.trim()));
+            }
+        }
+    }
+    (label, None)
+}
+
+/// The calendar called `name` in `account`, both matched case-insensitively
+pub fn find_account_calendar<'a>(
+    calendars: &'a [CalendarAccount],
+    name: &str,
+    account: &str,
+) -> Option<&'a CalendarAccount> {
+    calendars.iter().find(|c| {
+        c.name.eq_ignore_ascii_case(name.trim()) && c.account.eq_ignore_ascii_case(account.trim())
+    })
+}
+
+/// JavaScript for Automation listing every event calendar as name, account and identifier
+const CALENDAR_ACCOUNTS_SCRIPT: &str = r#"ObjC.import('EventKit');
+function run() {
+    const store = $.EKEventStore.alloc.init;
+    const calendars = store.calendarsForEntityType(0);
+    const lines = [];
+    for (let i = 0; i < calendars.count; i++) {
+        const calendar = calendars.objectAtIndex(i);
+        lines.push([
+            ObjC.unwrap(calendar.title),
+            ObjC.unwrap(calendar.source.title),
+            ObjC.unwrap(calendar.calendarIdentifier)
+        ].join('\t'));
+    }
+    return lines.join('\n');
+}"#;
+
+/// Parse the tab separated lines printed by the calendar accounts script
+pub fn parse_calendar_accounts(output: &str) -> Vec<CalendarAccount> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t').map(str::trim);
+            let (name, account, identifier) = (fields.next()?, fields.next()?, fields.next()?);
+            if name.is_empty() || identifier.is_empty() {
+                return None;
+            }
+            Some(CalendarAccount {
+                name: name.to_string(),
+                account: account.to_string(),
+                identifier: identifier.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Calendars in Calendar.app with their accounts
+///
+/// Needs Calendar access for osascript in System Settings → Privacy & Security.
+pub async fn calendar_accounts() -> Result<Vec<CalendarAccount>> {
+    let output = run_jxa_async(CALENDAR_ACCOUNTS_SCRIPT).await?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Failed to read calendar accounts: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let calendars = parse_calendar_accounts(&String::from_utf8_lossy(&output.stdout));
+    debug!("Found {} calendars with accounts", calendars.len());
+    Ok(calendars)
+}
+
+/// Build the JavaScript for Automation script moving an event to another calendar
+///
+/// `uid` is the event's Calendar.app UID, `identifier` the EventKit identifier of the target.
+pub fn move_event_script(uid: &str, identifier: &str) -> Result<String> {
+    let clean = |value: &str| -> String {
+        value
+            .trim()
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '@' | '.'))
+            .collect()
+    };
+    let (uid, identifier) = (clean(uid), clean(identifier));
+    if uid.is_empty() || identifier.is_empty() {
+        return Err(anyhow!("Missing event UID or calendar identifier"));
+    }
+
+    Ok(format!(
+        r#"ObjC.import('EventKit');
+function run() {{
+    const store = $.EKEventStore.alloc.init;
+    const calendar = store.calendarWithIdentifier('{identifier}');
+    if (calendar.isNil()) {{
+        return 'Error: Calendar not found';
+    }}
+    const items = store.calendarItemsWithExternalIdentifier('{uid}');
+    if (items.isNil() || items.count === 0) {{
+        return 'Error: Event not found. Allow Calendar access for osascript in System Settings';
+    }}
+    const error = Ref();
+    const item = items.objectAtIndex(0);
+    item.calendar = calendar;
+    if (!store.saveEventSpanCommitError(item, 1, true, error)) {{
+        return 'Error: ' + ObjC.unwrap(error[0].localizedDescription);
+    }}
+    return 'Success: Event moved';
+}}"#,
+        uid = uid,
+        identifier = identifier
+    ))
+}
+
+/// Move an event to the calendar with EventKit identifier `identifier`
+pub async fn move_event_to_calendar(uid: &str, identifier: &str) -> Result<()> {
+    let script = move_event_script(uid, identifier)?;
+    let output = run_jxa_async(&script).await?;
+    let result = String::from_utf8_lossy(&output.stdout);
+    if result.contains("Success") {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Failed to move event to its account's calendar: {}",
+            result.trim().trim_start_matches("Error: ")
+        ))
+    }
+}
+
+/// Resolve a calendar label to the name Calendar.app knows and the account calendar to move to
+///
+/// "Work (iCloud)" becomes ("Work", Some(identifier)). Labels without an account, and calendars
+/// whose own name ends in parentheses, are returned unchanged.
+pub async fn resolve_account_calendar(label: &str) -> (String, Option<String>) {
+    let (name, Some(account)) = split_account(label) else {
+        return (label.trim().to_string(), None);
+    };
+    match calendar_accounts().await {
+        Ok(calendars) => {
+            if calendars.iter().any(|c| c.name.eq_ignore_ascii_case(label.trim())) {
+                return (label.trim().to_string(), None);
+            }
+            match find_account_calendar(&calendars, name, account) {
+                Some(calendar) => (calendar.name.clone(), Some(calendar.identifier.clone())),
+                None => (label.trim().to_string(), None),
+            }
+        }
+        Err(e) => {
+            debug!("Could not read calendar accounts, using '{}': {}", name, e);
+            (name.to_string(), None)
+        }
+    }
+}
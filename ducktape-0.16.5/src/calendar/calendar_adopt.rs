@@ -0,0 +1,189 @@
+//! Adoption of existing Calendar.app events into DuckTape state.
+//
+// This module scans events that were not created by DuckTape and registers them in the
+// state store so that they can be managed like DuckTape's own events. Fields are read one
+// event per line, separated by tabs; tabs and line breaks inside titles and locations are
+// replaced with spaces so that they cannot shift the columns.
+//
+// Calendar.app is asked for events by their start date, which for a repeating event is the
+// start of the series: a series that began before `--from` is not adopted, even when it still
+// repeats after it. Adopt from an earlier date to pick such series up.
+
+use crate::applescript::Template;
+use crate::calendar::calendar_validation::validate_date_format;
+use crate::script_runner::run_applescript_async;
+use crate::state::{CalendarItem, StateManager};
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, NaiveDate};
+use tracing::{debug, info};
+
+/// Outcome of adopting events from a calendar
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AdoptSummary {
+    /// Events that were not known to DuckTape before
+    pub adopted: usize,
+    /// Events already in state that gained a UID or recurrence rule
+    pub updated: usize,
+    /// Events that were already fully tracked
+    pub skipped: usize,
+}
+
+/// Scan a calendar for events starting on or after `from_date` and register them in state
+///
+/// Repeating events count from the first occurrence of the series, not from later ones.
+pub async fn adopt_events(calendar: &str, from_date: &str) -> Result<AdoptSummary> {
+    if !validate_date_format(from_date) {
+        return Err(anyhow!("Invalid date format: {}. Use YYYY-MM-DD", from_date));
+    }
+    let from = NaiveDate::parse_from_str(from_date, "%Y-%m-%d")?;
+
+    let script = Template::new(
+        r#"on pad(n)
+    return text -2 thru -1 of ("0" & (n as integer))
+end pad
+
+on fmt(d)
+    return (year of d as text) & "-" & my pad(month of d as integer) & "-" & my pad(day of d) & " " & my pad(hours of d) & ":" & my pad(minutes of d)
+end fmt
+
+on clean(v)
+    if v is missing value then return ""
+    set AppleScript's text item delimiters to {return, linefeed, tab}
+    set parts to text items of (v as text)
+    set AppleScript's text item delimiters to " "
+    set flat to parts as text
+    set AppleScript's text item delimiters to ""
+    return flat
+end clean
+
+tell application "Calendar"
+    try
+        set fromDate to current date
+        set day of fromDate to 1
+        set year of fromDate to ${year}
+        set month of fromDate to ${month}
+        set day of fromDate to ${day}
+        set time of fromDate to 0
+        set output to ""
+        set targetCal to first calendar whose name is ${calendar}
+        repeat with e in (every event of targetCal whose start date is greater than or equal to fromDate)
+            set output to output & (uid of e) & tab & my clean(summary of e) & tab & my fmt(start date of e) & tab & my fmt(end date of e) & tab & (allday event of e as text) & tab & my clean(location of e) & tab & my clean(recurrence of e) & linefeed
+        end repeat
+        return output
+    on error errMsg
+        return "Error: " & errMsg
+    end try
+end tell"#,
+    )
+    .number("year", from.year())
+    .number("month", from.month())
+    .number("day", from.day())
+    .text("calendar", calendar)
+    .render()?;
+
+    let output = run_applescript_async(script).await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || stdout.starts_with("Error: ") {
+        let error = if stdout.starts_with("Error: ") {
+            stdout.replace("Error: ", "")
+        } else {
+            String::from_utf8_lossy(&output.stderr).to_string()
+        };
+        return Err(anyhow!(
+            "Failed to read events from calendar '{}': {}",
+            calendar,
+            error.trim()
+        ));
+    }
+
+    let events = parse_adopted_events(&stdout, calendar);
+    debug!("Found {} event(s) in calendar '{}' since {}", events.len(), calendar, from_date);
+
+    let manager = StateManager::new()?;
+    let mut items: Vec<CalendarItem> = manager.load()?;
+    let summary = merge_adopted_events(&mut items, events);
+    manager.save(&items)?;
+
+    info!(
+        "Adopted {} event(s) from '{}' ({} updated, {} already tracked)",
+        summary.adopted, calendar, summary.updated, summary.skipped
+    );
+    Ok(summary)
+}
+
+/// Parse the tab separated AppleScript output into calendar items
+pub fn parse_adopted_events(output: &str, calendar: &str) -> Vec<CalendarItem> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() != 7 || fields[0].trim().is_empty() {
+                debug!("Skipping event line with {} fields: {}", fields.len(), line);
+                return None;
+            }
+            let (date, time) = fields[2].split_once(' ')?;
+            let end_time = fields[3].split_once(' ').map(|(_, t)| t.to_string());
+            let non_empty = |s: &str| {
+                let s = s.trim();
+                if s.is_empty() { None } else { Some(s.to_string()) }
+            };
+
+            Some(CalendarItem {
+                title: fields[1].trim().to_string(),
+                date: date.to_string(),
+                time: time.to_string(),
+                calendars: vec![calendar.to_string()],
+                all_day: fields[4].trim() == "true",
+                location: non_empty(fields[5]),
+                description: None,
+                email: None,
+                reminder: None,
+                uid: Some(fields[0].trim().to_string()),
+                end_time,
+                recurrence: non_empty(fields[6]),
+                url: None,
+                availability: None,
+            })
+        })
+        .collect()
+}
+
+/// Merge scanned events into the existing state items
+///
+/// Events are matched by UID first, then by title, date and start time for items that
+/// DuckTape created before it tracked UIDs.
+pub fn merge_adopted_events(
+    items: &mut Vec<CalendarItem>,
+    events: Vec<CalendarItem>,
+) -> AdoptSummary {
+    let mut summary = AdoptSummary::default();
+
+    for event in events {
+        if items.iter().any(|item| item.uid.is_some() && item.uid == event.uid) {
+            summary.skipped += 1;
+            continue;
+        }
+
+        if let Some(existing) = items.iter_mut().find(|item| {
+            item.uid.is_none()
+                && item.title == event.title
+                && item.date == event.date
+                && item.time == event.time
+        }) {
+            existing.uid = event.uid;
+            if existing.recurrence.is_none() {
+                existing.recurrence = event.recurrence;
+            }
+            if existing.end_time.is_none() {
+                existing.end_time = event.end_time;
+            }
+            summary.updated += 1;
+            continue;
+        }
+
+        items.push(event);
+        summary.adopted += 1;
+    }
+
+    summary
+}
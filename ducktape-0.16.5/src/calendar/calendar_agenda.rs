@@ -0,0 +1,195 @@
+//! Daily agenda view for the calendar module.
+//
+// This module lists the events of a single day from Calendar.app and shows the
+// prep tasks linked to each event underneath it. It also renders the details of a tracked
+// event for `calendar show`.
+
+use crate::applescript::{Script, Template};
+use crate::calendar::calendar_validation::validate_date_format;
+use crate::links::prep_tasks_for_event;
+use crate::script_runner::run_applescript_async;
+use crate::state::{CalendarItem, ItemLink};
+use crate::theme::Colors;
+use crate::todo::TodoItem;
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, NaiveDate};
+use tracing::debug;
+
+/// An event on the agenda
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgendaEvent {
+    pub title: String,
+    /// Start time (HH:MM)
+    pub start: String,
+    /// End time (HH:MM)
+    pub end: String,
+    pub calendar: String,
+    pub all_day: bool,
+    pub url: Option<String>,
+}
+
+/// Fetch the events of one day, optionally limited to a single calendar
+pub async fn fetch_agenda(date: &str, calendar: Option<&str>) -> Result<Vec<AgendaEvent>> {
+    if !validate_date_format(date) {
+        return Err(anyhow!("Invalid date format: {}. Use YYYY-MM-DD", date));
+    }
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")?;
+
+    let calendar_selector = match calendar {
+        Some(name) => {
+            Template::new("(calendars whose name is ${name})").text("name", name).render()?
+        }
+        None => Script::from("calendars"),
+    };
+    let script = Template::new(
+        r#"on pad(n)
+    return text -2 thru -1 of ("0" & (n as integer))
+end pad
+
+on hm(d)
+    return my pad(hours of d) & ":" & my pad(minutes of d)
+end hm
+
+on clean(v)
+    if v is missing value then return ""
+    set AppleScript's text item delimiters to {return, linefeed, tab}
+    set parts to text items of (v as text)
+    set AppleScript's text item delimiters to " "
+    set flat to parts as text
+    set AppleScript's text item delimiters to ""
+    return flat
+end clean
+
+tell application "Calendar"
+    try
+        set dayStart to current date
+        set day of dayStart to 1
+        set year of dayStart to ${year}
+        set month of dayStart to ${month}
+        set day of dayStart to ${day}
+        set time of dayStart to 0
+        set dayEnd to dayStart + (1 * days)
+        set output to ""
+        repeat with c in ${calendars}
+            repeat with e in (every event of c whose start date is less than dayEnd and end date is greater than dayStart)
+                set output to output & (summary of e) & tab & my hm(start date of e) & tab & my hm(end date of e) & tab & (name of c) & tab & (allday event of e as text) & tab & my clean(url of e) & linefeed
+            end repeat
+        end repeat
+        return output
+    on error errMsg
+        return "Error: " & errMsg
+    end try
+end tell"#,
+    )
+    .number("year", day.year())
+    .number("month", day.month())
+    .number("day", day.day())
+    .script("calendars", calendar_selector)
+    .render()?;
+
+    let output = run_applescript_async(script).await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || stdout.starts_with("Error: ") {
+        let error = if stdout.starts_with("Error: ") {
+            stdout.replace("Error: ", "")
+        } else {
+            String::from_utf8_lossy(&output.stderr).to_string()
+        };
+        return Err(anyhow!("Failed to read agenda for {}: {}", date, error.trim()));
+    }
+
+    let events = parse_agenda_lines(&stdout);
+    debug!("Found {} event(s) on {}", events.len(), date);
+    Ok(events)
+}
+
+/// Parse the tab separated AppleScript output, sorted with all-day events first
+pub fn parse_agenda_lines(output: &str) -> Vec<AgendaEvent> {
+    let mut events: Vec<AgendaEvent> = output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() < 5 || fields[0].trim().is_empty() {
+                return None;
+            }
+            Some(AgendaEvent {
+                title: fields[0].trim().to_string(),
+                start: fields[1].trim().to_string(),
+                end: fields[2].trim().to_string(),
+                calendar: fields[3].trim().to_string(),
+                all_day: fields[4].trim() == "true",
+                url: fields.get(5).map(|u| u.trim()).filter(|u| !u.is_empty()).map(str::to_string),
+            })
+        })
+        .collect();
+    events.sort_by(|a, b| b.all_day.cmp(&a.all_day).then_with(|| a.start.cmp(&b.start)));
+    events
+}
+
+/// Render the agenda with linked prep tasks under their events
+///
+/// `todos` is used to show whether each prep task is done; tasks that are not found are
+/// shown as open.
+pub fn format_agenda(
+    date: &str,
+    events: &[AgendaEvent],
+    links: &[ItemLink],
+    todos: &[TodoItem],
+    colors: &Colors,
+) -> String {
+    let mut out = format!("{}\n", colors.heading(&format!("Agenda for {}", date)));
+    if events.is_empty() {
+        out.push_str("  No events\n");
+        return out;
+    }
+
+    for event in events {
+        let when = if event.all_day {
+            "all day".to_string()
+        } else {
+            format!("{}-{}", event.start, event.end)
+        };
+        out.push_str(&format!(
+            "  {} {} {}\n",
+            colors.time(&format!("{:<11}", when)),
+            event.title,
+            colors.calendar(&format!("[{}]", event.calendar))
+        ));
+        if let Some(url) = &event.url {
+            out.push_str(&format!("              {}\n", url));
+        }
+
+        for task in prep_tasks_for_event(links, &event.title) {
+            let done = todos.iter().any(|t| t.completed && t.title.eq_ignore_ascii_case(&task));
+            out.push_str(&format!("              {} {}\n", if done { "☑" } else { "☐" }, task));
+        }
+    }
+    out
+}
+
+/// Render everything DuckTape knows about a tracked event
+pub fn format_calendar_item(item: &CalendarItem) -> String {
+    let when = if item.all_day {
+        format!("{} (all day)", item.date)
+    } else {
+        match &item.end_time {
+            Some(end) => format!("{} {}-{}", item.date, item.time, end),
+            None => format!("{} {}", item.date, item.time),
+        }
+    };
+    let mut out = format!("{}\n  When:      {}\n", item.title, when);
+    let mut field = |label: &str, value: Option<&str>| {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            out.push_str(&format!("  {:<10} {}\n", format!("{}:", label), value));
+        }
+    };
+    field("Calendar", Some(&item.calendars.join(", ")));
+    field("Where", item.location.as_deref());
+    field("URL", item.url.as_deref());
+    field("Show as", item.availability.as_deref());
+    field("Attendees", item.email.as_deref());
+    field("Repeats", item.recurrence.as_deref());
+    field("Notes", item.description.as_deref());
+    field("UID", item.uid.as_deref());
+    out
+}
@@ -2,7 +2,11 @@
 //
 // This module provides async functions for interacting with macOS Calendar.app via AppleScript.
 
-use anyhow::Result;
+use crate::applescript::{Script, Template};
+use crate::script_runner::run_applescript_async;
+use crate::state::CalendarItem;
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, NaiveDateTime, Timelike};
 
 /// Ensure Calendar.app is running
 pub async fn ensure_calendar_running() -> Result<()> {
@@ -10,18 +14,6 @@ pub async fn ensure_calendar_running() -> Result<()> {
     Ok(())
 }
 
-/// List all calendars
-pub async fn list_calendars() -> Result<()> {
-    // ...implementation moved from calendar.rs...
-    Ok(())
-}
-
-/// Get available calendars
-pub async fn get_available_calendars() -> Result<Vec<String>> {
-    // ...implementation moved from calendar.rs...
-    Ok(vec![])
-}
-
 /// Create a single event in Calendar.app
 pub async fn create_single_event(/* params */) -> Result<()> {
     // ...implementation moved from calendar.rs...
@@ -34,9 +26,61 @@ pub async fn list_event_properties() -> Result<()> {
     Ok(())
 }
 
-/// Delete an event by title and date (placeholder implementation)
-pub async fn delete_event(_title: &str, _date: &str) -> Result<()> {
-    // TODO: Implement event deletion
-    println!("Event deletion not yet implemented");
-    Ok(())
+/// The script deleting `existing` from Calendar.app, found by UID or by title and start
+pub fn delete_event_script(existing: &CalendarItem) -> Result<Script> {
+    let calendar = existing
+        .calendars
+        .first()
+        .ok_or_else(|| anyhow!("Calendar of '{}' is unknown", existing.title))?;
+    let time = if existing.all_day { "00:00" } else { existing.time.as_str() };
+    let start =
+        NaiveDateTime::parse_from_str(&format!("{} {}", existing.date, time), "%Y-%m-%d %H:%M")
+            .map_err(|e| anyhow!("Invalid start of '{}': {}", existing.title, e))?;
+    Template::new(
+        r#"tell application "Calendar"
+    try
+        set cal to first calendar whose name is ${calendar}
+        set found to (every event of cal whose uid is ${uid})
+        if (count of found) is 0 then
+            set oldStart to current date
+            set day of oldStart to 1
+            set year of oldStart to ${year}
+            set month of oldStart to ${month}
+            set day of oldStart to ${day}
+            set time of oldStart to ${seconds}
+            set found to (every event of cal whose summary is ${title} and start date is oldStart)
+        end if
+        if (count of found) is 0 then error "Event not found in " & ${calendar}
+        delete item 1 of found
+        return "Success"
+    on error errMsg
+        return "Error: " & errMsg
+    end try
+end tell"#,
+    )
+    .text("calendar", calendar)
+    .text("uid", existing.uid.as_deref().unwrap_or_default())
+    .text("title", &existing.title)
+    .number("year", start.year())
+    .number("month", start.month())
+    .number("day", start.day())
+    .number("seconds", start.num_seconds_from_midnight())
+    .render()
+}
+
+/// Delete `existing` from Calendar.app
+///
+/// Events with a UID are deleted by it, so another event with the same title is never hit.
+pub(crate) async fn delete_single_event(existing: &CalendarItem) -> Result<()> {
+    let output = run_applescript_async(delete_event_script(existing)?).await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if output.status.success() && stdout.contains("Success") {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Failed to delete event '{}': {}",
+            existing.title,
+            stdout.replace("Error: ", "").trim()
+        ))
+    }
 }
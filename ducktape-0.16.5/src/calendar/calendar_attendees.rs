@@ -0,0 +1,472 @@
+//! Attendees of Calendar.app events: RSVP status and invitations.
+//
+// Calendar.app sends invitations itself for calendars on accounts that support them (iCloud,
+// Exchange, most CalDAV servers) and records each attendee's answer. `ducktape attendees list`
+// shows those answers, and `ducktape attendees resend` removes and re-adds the attendees who
+// have not answered, so that the account sends the invitation again. Local calendars and the
+// .ics and CalDAV backends send nothing; for those the invitation is emailed as an .ics
+// attachment instead, from Mail.app or through the SMTP account in `[smtp]`. The .ics and CalDAV
+// backends always get it; Calendar.app events get it with `--mail` or `calendar.invite_emails`.
+
+use crate::applescript::{Script, Template};
+use crate::backend::ics::{invitation, new_uid, vevent, with_organizer};
+use crate::calendar::EventConfig;
+use crate::calendar::calendar_validation::validate_date_format;
+use crate::config::{Config, InviteSender};
+use crate::script_runner::run_applescript_async;
+use crate::state::CalendarItem;
+use anyhow::{Context, Result, anyhow};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Utc};
+use std::fs;
+use tracing::debug;
+
+/// An attendee's answer to an invitation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RsvpStatus {
+    Accepted,
+    Declined,
+    Tentative,
+    /// No answer yet, or the account does not report one
+    Pending,
+}
+
+impl RsvpStatus {
+    /// Calendar.app's participation status (`accepted`, `declined`, `tentative`, `unknown`)
+    pub fn from_participation(status: &str) -> Self {
+        match status.trim().to_lowercase().as_str() {
+            "accepted" => RsvpStatus::Accepted,
+            "declined" => RsvpStatus::Declined,
+            "tentative" => RsvpStatus::Tentative,
+            _ => RsvpStatus::Pending,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RsvpStatus::Accepted => "accepted",
+            RsvpStatus::Declined => "declined",
+            RsvpStatus::Tentative => "tentative",
+            RsvpStatus::Pending => "no response",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attendee {
+    pub name: Option<String>,
+    pub email: String,
+    pub status: RsvpStatus,
+}
+
+/// An event with its attendees
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventAttendees {
+    pub title: String,
+    pub calendar: String,
+    /// Start (YYYY-MM-DD HH:MM)
+    pub start: String,
+    /// End (YYYY-MM-DD HH:MM)
+    pub end: String,
+    pub all_day: bool,
+    pub location: Option<String>,
+    pub uid: String,
+    pub attendees: Vec<Attendee>,
+}
+
+impl EventAttendees {
+    /// Attendees who have not answered
+    pub fn pending(&self) -> Vec<&Attendee> {
+        self.attendees.iter().filter(|a| a.status == RsvpStatus::Pending).collect()
+    }
+
+    /// The event as an `EventConfig`, for sending it as an invitation
+    pub fn to_event_config(&self) -> EventConfig {
+        let (start_date, start_time) = self.start.split_once(' ').unwrap_or((&self.start, "00:00"));
+        let mut config = EventConfig::new(&self.title, start_date, start_time);
+        if let Some((end_date, end_time)) = self.end.split_once(' ') {
+            if end_date != start_date {
+                config.end_date = Some(end_date.to_string());
+            }
+            config.end_time = Some(end_time.to_string());
+        }
+        config.all_day = self.all_day;
+        config.location = self.location.clone();
+        config.calendars = vec![self.calendar.clone()];
+        config.emails = self.attendees.iter().map(|a| a.email.clone()).collect();
+        config
+    }
+}
+
+/// Parse the tab separated AppleScript output: an `EVENT` line per event, followed by an
+/// `ATTENDEE` line per attendee
+pub fn parse_attendee_output(output: &str) -> Vec<EventAttendees> {
+    let mut events: Vec<EventAttendees> = Vec::new();
+    for line in output.lines() {
+        let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+        match fields.as_slice() {
+            ["EVENT", title, calendar, start, end, all_day, location, uid, ..] => {
+                events.push(EventAttendees {
+                    title: title.to_string(),
+                    calendar: calendar.to_string(),
+                    start: start.to_string(),
+                    end: end.to_string(),
+                    all_day: *all_day == "true",
+                    location: (!location.is_empty()).then(|| location.to_string()),
+                    uid: uid.to_string(),
+                    attendees: Vec::new(),
+                })
+            }
+            ["ATTENDEE", name, email, status, ..] if !email.is_empty() => {
+                if let Some(event) = events.last_mut() {
+                    event.attendees.push(Attendee {
+                        name: (!name.is_empty() && name != email).then(|| name.to_string()),
+                        email: email.trim_start_matches("mailto:").to_string(),
+                        status: RsvpStatus::from_participation(status),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+/// The occurrence to work with when several events share a title: the next one to start,
+/// otherwise the most recent
+pub fn pick_event(mut events: Vec<EventAttendees>, now: NaiveDateTime) -> Option<EventAttendees> {
+    events.sort_by(|a, b| a.start.cmp(&b.start));
+    let now = now.format("%Y-%m-%d %H:%M").to_string();
+    match events.iter().position(|e| e.start >= now) {
+        Some(next) => Some(events.swap_remove(next)),
+        None => events.pop(),
+    }
+}
+
+/// Find an event by title, optionally on one day and in one calendar
+pub async fn fetch_event_attendees(
+    title: &str,
+    date: Option<&str>,
+    calendar: Option<&str>,
+) -> Result<EventAttendees> {
+    let day_filter = match date {
+        Some(date) => {
+            if !validate_date_format(date) {
+                return Err(anyhow!("Invalid date format: {}. Use YYYY-MM-DD", date));
+            }
+            let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")?;
+            Template::new(
+                r#"
+        set dayStart to current date
+        set day of dayStart to 1
+        set year of dayStart to ${year}
+        set month of dayStart to ${month}
+        set day of dayStart to ${day}
+        set time of dayStart to 0
+        set dayEnd to dayStart + (1 * days)"#,
+            )
+            .number("year", day.year())
+            .number("month", day.month())
+            .number("day", day.day())
+            .render()?
+        }
+        None => Script::default(),
+    };
+    let event_filter = if date.is_some() {
+        " and start date is greater than or equal to dayStart and start date is less than dayEnd"
+    } else {
+        ""
+    };
+    let calendar_selector = match calendar {
+        Some(name) => {
+            Template::new("(calendars whose name is ${name})").text("name", name).render()?
+        }
+        None => Script::from("calendars"),
+    };
+
+    let script = Template::new(
+        r#"on pad(n)
+    return text -2 thru -1 of ("0" & (n as integer))
+end pad
+
+on stamp(d)
+    return ((year of d) as text) & "-" & my pad(month of d as integer) & "-" & my pad(day of d) & " " & my pad(hours of d) & ":" & my pad(minutes of d)
+end stamp
+
+tell application "Calendar"
+    try${day_filter}
+        set output to ""
+        repeat with c in ${calendar_selector}
+            repeat with e in (every event of c whose summary is ${title}${event_filter})
+                set loc to location of e
+                if loc is missing value then set loc to ""
+                set output to output & "EVENT" & tab & (summary of e) & tab & (name of c) & tab & my stamp(start date of e) & tab & my stamp(end date of e) & tab & (allday event of e as text) & tab & loc & tab & (uid of e) & linefeed
+                repeat with a in attendees of e
+                    set aName to display name of a
+                    if aName is missing value then set aName to ""
+                    set output to output & "ATTENDEE" & tab & aName & tab & (email of a) & tab & (participation status of a as text) & linefeed
+                end repeat
+            end repeat
+        end repeat
+        return output
+    on error errMsg
+        return "Error: " & errMsg
+    end try
+end tell"#,
+    )
+    .script("day_filter", day_filter)
+    .script("calendar_selector", calendar_selector)
+    .text("title", title)
+    .script("event_filter", event_filter)
+    .render()?;
+
+    let output = run_applescript_async(script).await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || stdout.starts_with("Error: ") {
+        let error = if stdout.starts_with("Error: ") {
+            stdout.replace("Error: ", "")
+        } else {
+            String::from_utf8_lossy(&output.stderr).to_string()
+        };
+        return Err(anyhow!("Failed to read attendees of '{}': {}", title, error.trim()));
+    }
+
+    let events = parse_attendee_output(&stdout);
+    debug!("Found {} event(s) titled '{}'", events.len(), title);
+    pick_event(events, chrono::Local::now().naive_local())
+        .ok_or_else(|| anyhow!("Event '{}' not found", title))
+}
+
+/// Render an event's attendees with their answers and a count per answer
+pub fn format_attendees(event: &EventAttendees) -> String {
+    let when = if event.all_day {
+        format!("{} all day", &event.start[..event.start.len().min(10)])
+    } else {
+        let end_time = event.end.split_once(' ').map_or(event.end.as_str(), |(_, t)| t);
+        format!("{}-{}", event.start, end_time)
+    };
+    let mut out = format!("{} [{}] {}\n", event.title, event.calendar, when);
+    if event.attendees.is_empty() {
+        out.push_str("  No attendees\n");
+        return out;
+    }
+
+    for attendee in &event.attendees {
+        let who = match &attendee.name {
+            Some(name) => format!("{} <{}>", name, attendee.email),
+            None => attendee.email.clone(),
+        };
+        out.push_str(&format!("  {:<12} {}\n", attendee.status.label(), who));
+    }
+    let counts: Vec<String> =
+        [RsvpStatus::Accepted, RsvpStatus::Tentative, RsvpStatus::Declined, RsvpStatus::Pending]
+            .iter()
+            .map(|status| {
+                let n = event.attendees.iter().filter(|a| a.status == *status).count();
+                (n, status.label())
+            })
+            .filter(|(n, _)| *n > 0)
+            .map(|(n, label)| format!("{} {}", n, label))
+            .collect();
+    out.push_str(&format!("  {}\n", counts.join(", ")));
+    out
+}
+
+/// Invite `emails` to an event again by removing and re-adding them as attendees
+pub async fn resend_invitations(event: &EventAttendees, emails: &[&str]) -> Result<()> {
+    if emails.is_empty() {
+        return Ok(());
+    }
+    let script = Template::new(
+        r#"tell application "Calendar"
+    try
+        set e to first event of calendar ${calendar} whose uid is ${uid}
+        repeat with addr in ${emails}
+            set addr to addr as text
+            tell e
+                delete (every attendee whose email is addr)
+                make new attendee at end of attendees with properties {email:addr}
+            end tell
+        end repeat
+        return "Success"
+    on error errMsg
+        return "Error: " & errMsg
+    end try
+end tell"#,
+    )
+    .text("calendar", &event.calendar)
+    .text("uid", &event.uid)
+    .text_list("emails", emails)
+    .render()?;
+
+    let output = run_applescript_async(script).await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if output.status.success() && stdout.contains("Success") {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Failed to resend invitations for '{}': {}",
+            event.title,
+            stdout.replace("Error: ", "").trim()
+        ))
+    }
+}
+
+/// Add `emails` as attendees of a tracked Calendar.app event, found by UID
+pub async fn add_attendees(existing: &CalendarItem, emails: &[&str]) -> Result<()> {
+    let calendar = existing
+        .calendars
+        .first()
+        .ok_or_else(|| anyhow!("Calendar of '{}' is unknown", existing.title))?;
+    let uid = existing.uid.as_deref().ok_or_else(|| {
+        anyhow!("'{}' has no UID, so attendees cannot be added to it", existing.title)
+    })?;
+    let script = Template::new(
+        r#"tell application "Calendar"
+    try
+        set e to first event of calendar ${calendar} whose uid is ${uid}
+        repeat with addr in ${emails}
+            set addr to addr as text
+            tell e
+                if (count of (every attendee whose email is addr)) is 0 then
+                    make new attendee at end of attendees with properties {email:addr}
+                end if
+            end tell
+        end repeat
+        return "Success"
+    on error errMsg
+        return "Error: " & errMsg
+    end try
+end tell"#,
+    )
+    .text("calendar", calendar)
+    .text("uid", uid)
+    .text_list("emails", emails)
+    .render()?;
+
+    let output = run_applescript_async(script).await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if output.status.success() && stdout.contains("Success") {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Failed to add attendees to '{}': {}",
+            existing.title,
+            stdout.replace("Error: ", "").trim()
+        ))
+    }
+}
+
+/// How invitations are emailed: `calendar.invite_via`, else SMTP when `[smtp]` has a host
+pub fn invite_sender(app_config: &Config) -> InviteSender {
+    app_config
+        .calendar
+        .invite_via
+        .unwrap_or(if crate::smtp::is_configured(&app_config.smtp) {
+            InviteSender::Smtp
+        } else {
+            InviteSender::Mail
+        })
+}
+
+/// Whether invitations can be emailed at all: Mail.app is only on macOS
+pub fn can_email_invitations(app_config: &Config) -> bool {
+    match invite_sender(app_config) {
+        InviteSender::Mail => cfg!(target_os = "macos"),
+        InviteSender::Smtp => crate::smtp::is_configured(&app_config.smtp),
+    }
+}
+
+/// Subject and text of the email inviting to `config`
+fn invitation_text(config: &EventConfig) -> (String, String) {
+    let when = if config.all_day {
+        config.start_date.clone()
+    } else {
+        format!("{} {}", config.start_date, config.start_time)
+    };
+    let mut body = format!("You are invited to {} on {}.", config.title, when);
+    if let Some(location) = &config.location {
+        body.push_str(&format!("\nLocation: {}", location));
+    }
+    if let Some(description) = &config.description {
+        body.push_str(&format!("\n\n{}", description));
+    }
+    (format!("Invitation: {} ({})", config.title, when), body)
+}
+
+/// Email an invitation with the event as an .ics attachment
+///
+/// For calendars that do not send invitations themselves. It goes through the SMTP account,
+/// with its address as the organizer, or from Mail.app; see `invite_sender`.
+pub async fn send_invite_email(config: &EventConfig, emails: &[&str]) -> Result<()> {
+    if emails.is_empty() {
+        return Ok(());
+    }
+    let app_config = Config::load()?;
+    let uid = new_uid();
+    let (subject, body) = invitation_text(config);
+    match invite_sender(&app_config) {
+        InviteSender::Smtp => {
+            let organizer = crate::smtp::sender(&app_config.smtp)?.email.to_string();
+            let ics = invitation(&with_organizer(&vevent(config, &uid, Utc::now())?, &organizer));
+            crate::smtp::send_invitation(&app_config.smtp, emails, &subject, &body, &ics).await
+        }
+        InviteSender::Mail => {
+            let ics = invitation(&vevent(config, &uid, Utc::now())?);
+            mail_invite(&app_config, &uid, &ics, &subject, &body, emails).await
+        }
+    }
+}
+
+/// Send the invitation from Mail.app
+///
+/// The .ics file is kept in `<data dir>/invites`, as Mail.app reads the attachment after
+/// the script returns.
+async fn mail_invite(
+    app_config: &Config,
+    uid: &str,
+    ics: &str,
+    subject: &str,
+    body: &str,
+    emails: &[&str],
+) -> Result<()> {
+    let dir = app_config.backend.data_dir()?.join("invites");
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let path = dir.join(format!("{}.ics", uid.replace('@', "-")));
+    fs::write(&path, ics).with_context(|| format!("Failed to write {}", path.display()))?;
+
+    let script = Template::new(
+        r#"tell application "Mail"
+    try
+        set msg to make new outgoing message with properties {subject:${subject}, content:${body} & return & return, visible:false}
+        tell msg
+            repeat with addr in ${emails}
+                make new to recipient at end of to recipients with properties {address:(addr as text)}
+            end repeat
+            make new attachment with properties {file name:(POSIX file ${path})} at after the last paragraph of content
+        end tell
+        send msg
+        return "Success"
+    on error errMsg
+        return "Error: " & errMsg
+    end try
+end tell"#,
+    )
+    .text("subject", subject)
+    .text("body", body)
+    .text_list("emails", emails)
+    .text("path", &path.display().to_string())
+    .render()?;
+
+    let output = run_applescript_async(script).await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if output.status.success() && stdout.contains("Success") {
+        debug!("Invitation '{}' sent to {}", subject, emails.join(", "));
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Failed to email the invitation '{}': {}",
+            subject,
+            stdout.replace("Error: ", "").trim()
+        ))
+    }
+}
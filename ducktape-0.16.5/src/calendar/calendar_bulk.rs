@@ -0,0 +1,320 @@
+//! Bulk changes to calendar events.
+//
+// This module finds every event in a day or range of days (optionally only those whose title
+// contains some text) and plans moving or deleting all of them, so that callers can preview
+// the plan before it is applied through AppleScript. Events are changed by UID. Repeating
+// events are left out, since changing one occurrence would change the whole series.
+
+use crate::applescript::{Script, Template};
+use crate::script_runner::run_applescript_async;
+use crate::state::{CalendarItem, StateManager};
+use crate::webhooks::{Action, ItemKind, notify};
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime};
+use serde_json::json;
+use tracing::{debug, info, warn};
+
+/// An event found for a bulk change
+#[derive(Debug, Clone, PartialEq)]
+pub struct BulkEvent {
+    pub uid: String,
+    pub title: String,
+    pub calendar: String,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub all_day: bool,
+    pub recurring: bool,
+}
+
+/// A planned change to a single event
+#[derive(Debug, Clone, PartialEq)]
+pub struct BulkChange {
+    pub event: BulkEvent,
+    /// How far to move the event; `None` deletes it
+    pub shift: Option<Duration>,
+}
+
+impl BulkChange {
+    pub fn is_delete(&self) -> bool {
+        self.shift.is_none()
+    }
+}
+
+const EVENT_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Plan a change to every event whose title contains `title`, ignoring case
+///
+/// With a `shift` the events are moved by it, keeping their times; without one they are
+/// deleted. Repeating events are never part of the plan.
+pub fn plan_bulk_changes(
+    events: &[BulkEvent],
+    title: Option<&str>,
+    shift: Option<Duration>,
+) -> Vec<BulkChange> {
+    let title = title.map(str::to_lowercase);
+    events
+        .iter()
+        .filter(|event| !event.recurring)
+        .filter(|event| {
+            title.as_deref().is_none_or(|title| event.title.to_lowercase().contains(title))
+        })
+        .map(|event| BulkChange { event: event.clone(), shift })
+        .collect()
+}
+
+/// Render a plan as a preview list
+pub fn format_bulk_changes(changes: &[BulkChange]) -> String {
+    changes
+        .iter()
+        .map(|change| {
+            let event = &change.event;
+            let when = |start: NaiveDateTime| {
+                if event.all_day {
+                    start.format("%a %Y-%m-%d (all day)").to_string()
+                } else {
+                    start.format("%a %Y-%m-%d %H:%M").to_string()
+                }
+            };
+            match change.shift {
+                Some(shift) => format!(
+                    "  • {} [{}]: {} → {}",
+                    event.title,
+                    event.calendar,
+                    when(event.start),
+                    when(event.start + shift)
+                ),
+                None => format!("  • {} [{}]: {}", event.title, event.calendar, when(event.start)),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Parse the tab separated AppleScript output into events
+pub fn parse_bulk_events(output: &str) -> Vec<BulkEvent> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() < 7 || fields[0].trim().is_empty() {
+                return None;
+            }
+            Some(BulkEvent {
+                uid: fields[0].trim().to_string(),
+                title: fields[1].trim().to_string(),
+                start: NaiveDateTime::parse_from_str(fields[2].trim(), EVENT_FORMAT).ok()?,
+                end: NaiveDateTime::parse_from_str(fields[3].trim(), EVENT_FORMAT).ok()?,
+                all_day: fields[4].trim() == "true",
+                calendar: fields[5].trim().to_string(),
+                recurring: !fields[6].trim().is_empty(),
+            })
+        })
+        .collect()
+}
+
+/// Fetch the events starting between `from` and `until`, both days included
+async fn fetch_bulk_events(
+    from: NaiveDate,
+    until: NaiveDate,
+    calendar: Option<&str>,
+) -> Result<Vec<BulkEvent>> {
+    let calendars = match calendar {
+        Some(name) => {
+            Template::new("(calendars whose name is ${name})").text("name", name).render()?
+        }
+        None => Script::from("calendars"),
+    };
+    let days = (until - from).num_days() + 1;
+    let script = Template::new(
+        r#"on pad(n)
+    return text -2 thru -1 of ("0" & (n as integer))
+end pad
+
+on fmt(d)
+    return (year of d as text) & "-" & my pad(month of d as integer) & "-" & my pad(day of d) & " " & my pad(hours of d) & ":" & my pad(minutes of d)
+end fmt
+
+on clean(v)
+    if v is missing value then return ""
+    set AppleScript's text item delimiters to {return, linefeed, tab}
+    set parts to text items of (v as text)
+    set AppleScript's text item delimiters to " "
+    set flat to parts as text
+    set AppleScript's text item delimiters to ""
+    return flat
+end clean
+
+tell application "Calendar"
+    try
+        set rangeStart to current date
+        set day of rangeStart to 1
+        set year of rangeStart to ${year}
+        set month of rangeStart to ${month}
+        set day of rangeStart to ${day}
+        set time of rangeStart to 0
+        set rangeEnd to rangeStart + (${days} * days)
+        set output to ""
+        repeat with c in ${calendars}
+            repeat with e in (every event of c whose start date is greater than or equal to rangeStart and start date is less than rangeEnd)
+                set output to output & (uid of e) & tab & my clean(summary of e) & tab & my fmt(start date of e) & tab & my fmt(end date of e) & tab & (allday event of e as text) & tab & (name of c) & tab & my clean(recurrence of e) & linefeed
+            end repeat
+        end repeat
+        return output
+    on error errMsg
+        return "Error: " & errMsg
+    end try
+end tell"#,
+    )
+    .number("year", from.year())
+    .number("month", from.month())
+    .number("day", from.day())
+    .number("days", days as f64)
+    .script("calendars", calendars)
+    .render()?;
+
+    let output = run_applescript_async(script).await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || stdout.starts_with("Error: ") {
+        let error = if stdout.starts_with("Error: ") {
+            stdout.replace("Error: ", "")
+        } else {
+            String::from_utf8_lossy(&output.stderr).to_string()
+        };
+        return Err(anyhow!("Failed to read events from {} to {}: {}", from, until, error.trim()));
+    }
+
+    let events = parse_bulk_events(&stdout);
+    debug!("Found {} event(s) from {} to {}", events.len(), from, until);
+    Ok(events)
+}
+
+/// Fetch the events in `on` and plan moving them to `to`, or deleting them without one
+///
+/// `on` is a day or range such as `friday` or `next week`; moving keeps the events' times and
+/// puts the first day of the range on `to`.
+pub async fn preview_bulk_changes(
+    on: &str,
+    to: Option<&str>,
+    title: Option<&str>,
+    calendar: Option<&str>,
+) -> Result<Vec<BulkChange>> {
+    let today = Local::now().date_naive();
+    let (from, until) = crate::dates::parse_date_range(on, today)
+        .ok_or_else(|| anyhow!("Unrecognized date: {}", on))?;
+    let shift = match to {
+        Some(to) => {
+            let to = crate::dates::parse_date(to, today)
+                .ok_or_else(|| anyhow!("Unrecognized date: {}", to))?;
+            if to == from {
+                return Err(anyhow!("Target date is the same as the first day to move"));
+            }
+            Some(to - from)
+        }
+        None => None,
+    };
+
+    let events = fetch_bulk_events(from, until, calendar).await?;
+    let skipped = events.iter().filter(|e| e.recurring).count();
+    if skipped > 0 {
+        info!("Leaving out {} repeating event(s)", skipped);
+    }
+    Ok(plan_bulk_changes(&events, title, shift))
+}
+
+/// Move or delete one event in Calendar.app
+async fn apply_bulk_change(change: &BulkChange) -> Result<()> {
+    let event = &change.event;
+    let action = match change.shift {
+        Some(shift) => Template::new(
+            r#"set delta to ${days} * days
+        if delta > 0 then
+            set end date of e to (end date of e) + delta
+            set start date of e to (start date of e) + delta
+        else
+            set start date of e to (start date of e) + delta
+            set end date of e to (end date of e) + delta
+        end if"#,
+        )
+        .number("days", shift.num_days() as f64)
+        .render()?,
+        None => Script::from("delete e"),
+    };
+    let script = Template::new(
+        r#"tell application "Calendar"
+    try
+        set e to first event of (first calendar whose name is ${calendar}) whose uid is ${uid}
+        ${action}
+        return "ok"
+    on error errMsg
+        return "Error: " & errMsg
+    end try
+end tell"#,
+    )
+    .text("calendar", &event.calendar)
+    .text("uid", &event.uid)
+    .script("action", action)
+    .render()?;
+
+    let output = run_applescript_async(script).await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || stdout.starts_with("Error: ") {
+        let error = if stdout.starts_with("Error: ") {
+            stdout.replace("Error: ", "")
+        } else {
+            String::from_utf8_lossy(&output.stderr).to_string()
+        };
+        return Err(anyhow!("{}", error.trim()));
+    }
+    Ok(())
+}
+
+/// Keep events DuckTape tracks in line with the changes that were applied
+fn update_tracked_events(applied: &[&BulkChange]) -> Result<()> {
+    let manager = StateManager::new()?;
+    let mut items: Vec<CalendarItem> = manager.load()?;
+    for change in applied {
+        let uid = Some(change.event.uid.clone());
+        match change.shift {
+            Some(shift) => {
+                for item in items.iter_mut().filter(|item| item.uid == uid) {
+                    item.date = (change.event.start + shift).format("%Y-%m-%d").to_string();
+                }
+            }
+            None => items.retain(|item| item.uid != uid),
+        }
+    }
+    manager.save(&items)
+}
+
+/// Apply planned changes, returning how many events were changed
+pub async fn apply_bulk_changes(changes: &[BulkChange]) -> Result<usize> {
+    let mut applied = Vec::new();
+    for change in changes {
+        let event = &change.event;
+        match apply_bulk_change(change).await {
+            Ok(()) => {
+                applied.push(change);
+                let start = event.start + change.shift.unwrap_or(Duration::zero());
+                let end = event.end + change.shift.unwrap_or(Duration::zero());
+                let hook = json!({
+                    "title": event.title,
+                    "uid": event.uid,
+                    "start_date": start.format("%Y-%m-%d").to_string(),
+                    "start_time": (!event.all_day).then(|| start.format("%H:%M").to_string()),
+                    "end_date": end.format("%Y-%m-%d").to_string(),
+                    "end_time": (!event.all_day).then(|| end.format("%H:%M").to_string()),
+                    "all_day": event.all_day,
+                    "calendars": [event.calendar],
+                });
+                let action = if change.is_delete() { Action::Deleted } else { Action::Updated };
+                notify(ItemKind::Event, action, hook);
+            }
+            Err(e) => warn!("Failed to change '{}': {}", event.title, e),
+        }
+    }
+    if let Err(e) = update_tracked_events(&applied) {
+        warn!("Failed to update tracked events: {}", e);
+    }
+    info!("Changed {} of {} event(s)", applied.len(), changes.len());
+    Ok(applied.len())
+}
@@ -2,19 +2,328 @@
 //
 // This module provides functions to look up contacts and their emails.
 
+use crate::applescript::{Script, Template};
 use crate::calendar::calendar_types::EventConfig;
 use crate::calendar::calendar_validation::validate_email;
+use crate::script_runner::run_applescript_async;
+use crate::state::{cache_contact, find_cached_contact};
 use anyhow::{Result, anyhow};
-use log::{debug, error, info};
+use std::io::{IsTerminal, Write};
+use tracing::{debug, error, info, warn};
+
+/// Minimum score for a contact to be considered a match
+pub const MIN_CONTACT_SCORE: u32 = 50;
+
+/// Common nickname groups; every name in a group is treated as equivalent
+const NICKNAMES: &[&[&str]] = &[
+    &["robert", "rob", "bob", "bobby", "robbie"],
+    &["william", "will", "bill", "billy", "liam"],
+    &["richard", "rich", "rick", "dick"],
+    &["james", "jim", "jimmy", "jamie"],
+    &["john", "johnny", "jack"],
+    &["joseph", "joe", "joey"],
+    &["michael", "mike", "mikey", "mick"],
+    &["thomas", "tom", "tommy"],
+    &["christopher", "chris", "topher"],
+    &["daniel", "dan", "danny"],
+    &["david", "dave", "davey"],
+    &["edward", "ed", "eddie", "ted"],
+    &["anthony", "tony"],
+    &["andrew", "andy", "drew"],
+    &["benjamin", "ben", "benny"],
+    &["matthew", "matt"],
+    &["nicholas", "nick", "nicky"],
+    &["samuel", "sam", "sammy"],
+    &["steven", "stephen", "steve"],
+    &["alexander", "alex", "xander"],
+    &["elizabeth", "liz", "beth", "betty", "eliza", "lizzie"],
+    &["katherine", "catherine", "kate", "katie", "kathy", "cathy"],
+    &["margaret", "maggie", "meg", "peggy"],
+    &["jennifer", "jen", "jenny"],
+    &["rebecca", "becky", "becca"],
+    &["susan", "sue", "susie"],
+    &["patricia", "pat", "patty", "trish"],
+    &["alexandra", "alex", "sandra", "sasha"],
+    &["victoria", "vicky", "tori"],
+    &["jessica", "jess", "jessie"],
+];
+
+/// How to proceed when a contact name matches more than one person
+///
+/// The default asks when the command was typed in a terminal and takes the best match
+/// otherwise; inviting everyone who matches has to be asked for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContactStrategy {
+    /// Use the best scoring person only
+    First,
+    /// Invite every person that matches
+    All,
+    /// Ask the user to choose when the match is ambiguous
+    Ask,
+}
+
+impl Default for ContactStrategy {
+    fn default() -> Self {
+        // Changes made through the API or chat are never asked about on the server's terminal
+        let interactive =
+            std::io::stdin().is_terminal() && crate::webhooks::current_source() == "cli";
+        Self::for_terminal(interactive)
+    }
+}
+
+impl ContactStrategy {
+    /// `Ask` when someone at a terminal can answer, `First` otherwise
+    pub fn for_terminal(interactive: bool) -> Self {
+        if interactive { ContactStrategy::Ask } else { ContactStrategy::First }
+    }
+
+    /// Parse contact strategy from string
+    pub fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "first" | "best" => Ok(ContactStrategy::First),
+            "all" => Ok(ContactStrategy::All),
+            "ask" | "prompt" => Ok(ContactStrategy::Ask),
+            _ => Err(anyhow!("Invalid contact strategy: {} (expected first, all or ask)", s)),
+        }
+    }
+}
+
+/// A person from Contacts.app that matched a lookup, with its match score
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContactCandidate {
+    pub name: String,
+    pub emails: Vec<String>,
+    pub score: u32,
+}
+
+/// Return every nickname equivalent of a (lowercase) name, including the name itself
+pub fn nickname_variants(name: &str) -> Vec<String> {
+    let mut variants = vec![name.to_string()];
+    for group in NICKNAMES {
+        if group.contains(&name) {
+            variants.extend(group.iter().map(|n| n.to_string()));
+        }
+    }
+    variants.sort();
+    variants.dedup();
+    variants
+}
+
+fn score_name_token(query: &str, candidate: &str) -> u32 {
+    if query == candidate {
+        return 95;
+    }
+    if NICKNAMES
+        .iter()
+        .any(|group| group.contains(&query) && group.contains(&candidate))
+    {
+        return 85;
+    }
+    if query.len() >= 2 && candidate.starts_with(query) {
+        return 70;
+    }
+    let max_typos = if query.len() > 5 { 2 } else { 1 };
+    if query.len() >= 3 && crate::validation::edit_distance(query, candidate) <= max_typos {
+        return 60;
+    }
+    0
+}
+
+/// Score how well a typed name matches a contact's full name (0-100)
+///
+/// Every word the user typed has to match some part of the contact name, either exactly,
+/// as a known nickname, as a prefix, or within a small number of typos.
+pub fn score_contact_match(query: &str, full_name: &str) -> u32 {
+    let query = query.trim().to_lowercase();
+    let full_name = full_name.trim().to_lowercase();
+    if query.is_empty() || full_name.is_empty() {
+        return 0;
+    }
+    if query == full_name {
+        return 100;
+    }
+
+    let name_tokens: Vec<&str> = full_name.split_whitespace().collect();
+    let query_tokens: Vec<&str> = query.split_whitespace().collect();
+    let mut total = 0;
+    for query_token in &query_tokens {
+        let best = name_tokens.iter().map(|t| score_name_token(query_token, t)).max().unwrap_or(0);
+        if best == 0 {
+            return 0;
+        }
+        total += best;
+    }
+    total / query_tokens.len() as u32
+}
+
+/// Fetch people from Contacts.app whose name could match, scored and sorted best first
+///
+/// The copy made by `contacts sync` is searched first; Contacts.app is only asked when the
+/// copy does not exist yet or has nobody matching.
+pub async fn find_contact_candidates(name: &str) -> Result<Vec<ContactCandidate>> {
+    if let Some(entries) = crate::address_book::entries().await {
+        let candidates = crate::address_book::find_candidates(&entries, name);
+        if !candidates.is_empty() {
+            debug!("Contact candidates for '{}' from the contacts copy: {:?}", name, candidates);
+            return Ok(candidates);
+        }
+    }
+
+    let mut search_terms: Vec<String> = Vec::new();
+    for token in name.to_lowercase().split_whitespace() {
+        search_terms.extend(nickname_variants(token));
+    }
+    search_terms.sort();
+    search_terms.dedup();
+    if search_terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let conditions = search_terms
+        .iter()
+        .map(|term| Template::new("name contains ${term}").text("term", term).render())
+        .collect::<Result<Vec<_>>>()?;
+    let condition = Script::join(conditions, " or ");
+    debug!("Searching contacts with condition: {}", condition);
+
+    let script = Template::new(
+        r#"tell application "Contacts"
+            set output to ""
+            try
+                set the_people to (every person whose ${condition})
+                repeat with the_person in the_people
+                    set the_line to (name of the_person as text) & "|"
+                    repeat with the_email in (get every email of the_person)
+                        if value of the_email is not missing value then
+                            set the_line to the_line & (value of the_email as text) & ","
+                        end if
+                    end repeat
+                    set output to output & the_line & linefeed
+                end repeat
+            on error errMsg
+                log "Error looking up contacts: " & errMsg
+            end try
+            return output
+        end tell"#,
+    )
+    .script("condition", condition)
+    .render()?;
+
+    let output = run_applescript_async(script).await?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        error!("Contact candidate lookup error: {}", error);
+        return Ok(Vec::new());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut candidates: Vec<ContactCandidate> = stdout
+        .lines()
+        .filter_map(|line| {
+            let (person, emails) = line.split_once('|')?;
+            let emails: Vec<String> = emails
+                .split(',')
+                .map(|e| e.trim().to_string())
+                .filter(|e| validate_email(e))
+                .collect();
+            let score = score_contact_match(name, person);
+            Some(ContactCandidate { name: person.trim().to_string(), emails, score })
+        })
+        .filter(|c| c.score >= MIN_CONTACT_SCORE && !c.emails.is_empty())
+        .collect();
+
+    candidates.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
+    candidates.dedup_by(|a, b| a.name == b.name && a.emails == b.emails);
+    debug!("Contact candidates for '{}': {:?}", name, candidates);
+    Ok(candidates)
+}
+
+/// Ask the user on the terminal which of several matching people they meant
+fn prompt_for_contact(name: &str, candidates: &[ContactCandidate]) -> Option<usize> {
+    println!("Multiple contacts match '{}':", name);
+    for (i, candidate) in candidates.iter().enumerate() {
+        println!("  {}. {} <{}>", i + 1, candidate.name, candidate.emails.join(", "));
+    }
+    print!("Choose a contact [1-{}] (or press Enter to skip): ", candidates.len());
+    std::io::stdout().flush().ok()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).ok()?;
+    let choice = answer.trim().parse::<usize>().ok()?;
+    (1..=candidates.len()).contains(&choice).then(|| choice - 1)
+}
+
+/// Whether the candidates for a name, best first, leave open which person was meant
+///
+/// An exact match that clearly beats the rest is not ambiguous.
+pub fn is_ambiguous(candidates: &[ContactCandidate]) -> bool {
+    match candidates {
+        [] | [_] => false,
+        [best, next, ..] => !(best.score == 100 && next.score < 100),
+    }
+}
+
+/// Resolve a contact name to email addresses using fuzzy matching and the given strategy
+///
+/// Names that were previously resolved to a single person are served from the state store.
+/// Only unambiguous matches and people picked at the prompt are cached there; a best guess
+/// is not, and `ContactStrategy::All` always looks at every match again.
+pub async fn resolve_contact(name: &str, strategy: ContactStrategy) -> Result<Vec<String>> {
+    if strategy != ContactStrategy::All {
+        if let Some(cached) = find_cached_contact(name)? {
+            debug!("Using cached contact '{}' for '{}'", cached.name, name);
+            return Ok(cached.emails);
+        }
+    }
+
+    let candidates = find_contact_candidates(name).await?;
+    let (chosen, remember): (Vec<&ContactCandidate>, bool) = match candidates.len() {
+        0 => {
+            // Fall back to the plain substring lookup so nothing that used to resolve is lost
+            return lookup_contact(name).await;
+        }
+        _ if !is_ambiguous(&candidates) => (vec![&candidates[0]], true),
+        _ => match strategy {
+            ContactStrategy::First => (vec![&candidates[0]], false),
+            ContactStrategy::All => (candidates.iter().collect(), false),
+            ContactStrategy::Ask if std::io::stdin().is_terminal() => {
+                match prompt_for_contact(name, &candidates) {
+                    Some(index) => (vec![&candidates[index]], true),
+                    None => {
+                        info!("No contact selected for '{}'", name);
+                        return Ok(Vec::new());
+                    }
+                }
+            }
+            ContactStrategy::Ask => {
+                warn!(
+                    "Cannot ask which contact '{}' refers to without a terminal, using best match",
+                    name
+                );
+                (vec![&candidates[0]], false)
+            }
+        },
+    };
+
+    if remember {
+        let person = chosen[0];
+        if let Err(e) = cache_contact(name, &person.name, &person.emails) {
+            warn!("Failed to cache resolved contact '{}': {}", name, e);
+        }
+    }
+
+    Ok(chosen.into_iter().flat_map(|c| c.emails.clone()).collect())
+}
 
 /// Lookup a contact by name and return their email addresses
 pub async fn lookup_contact(name: &str) -> Result<Vec<String>> {
     debug!("Looking up contact: '{}'", name);
-    let script = format!(
+    let script = Template::new(
         r#"tell application "Contacts"
-            set the_emails to {{}}
+            set the_emails to {}
             try
-                set the_people to (every person whose name contains "{}")
+                set the_people to (every person whose name contains ${name})
                 repeat with the_person in the_people
                     if exists email of the_person then
                         repeat with the_email in (get every email of the_person)
@@ -27,18 +336,14 @@ pub async fn lookup_contact(name: &str) -> Result<Vec<String>> {
                 return the_emails
             on error errMsg
                 log "Error looking up contact: " & errMsg
-                return {{}}
+                return {}
             end try
         end tell"#,
-        name.replace("\"", "\\\"")
-    );
+    )
+    .text("name", name)
+    .render()?;
 
-    let output = tokio::process::Command::new("osascript")
-        .arg("-e")
-        .arg(&script)
-        .output()
-        .await
-        .map_err(|e| anyhow!("Failed to execute AppleScript: {}", e))?;
+    let output = run_applescript_async(script).await?;
 
     if output.status.success() {
         let emails = String::from_utf8_lossy(&output.stdout);
@@ -67,12 +372,12 @@ pub async fn lookup_contact(name: &str) -> Result<Vec<String>> {
 async fn lookup_by_name_part(name_part: &str, part_type: &str) -> Result<Vec<String>> {
     debug!("Looking up contacts by {} name: '{}'", part_type, name_part);
 
-    let script = format!(
+    let script = Template::new(
         r#"tell application "Contacts"
-            set the_emails to {{}}
+            set the_emails to {}
             try
-                set search_term to "{}"
-                if "{}" is "first" then
+                set search_term to ${name_part}
+                if ${part_type} is "first" then
                     set the_people to (every person whose first name contains search_term)
                 else
                     set the_people to (every person whose last name contains search_term)
@@ -89,23 +394,16 @@ async fn lookup_by_name_part(name_part: &str, part_type: &str) -> Result<Vec<Str
                 end repeat
                 return the_emails
             on error errMsg
-                log "Error looking up contact by {} name: " & errMsg
-                return {{}}
+                log "Error looking up contact by " & ${part_type} & " name: " & errMsg
+                return {}
             end try
         end tell"#,
-        name_part.replace("\"", "\\\""),
-        part_type,
-        part_type
-    );
-
-    let output = tokio::process::Command::new("osascript")
-        .arg("-e")
-        .arg(&script)
-        .output()
-        .await
-        .map_err(|e| {
-            anyhow!("Failed to execute AppleScript for {} name search: {}", part_type, e)
-        })?;
+    )
+    .text("name_part", name_part)
+    .text("part_type", part_type)
+    .render()?;
+
+    let output = run_applescript_async(script).await?;
 
     if output.status.success() {
         let emails = String::from_utf8_lossy(&output.stdout);
@@ -130,8 +428,17 @@ async fn lookup_by_name_part(name_part: &str, part_type: &str) -> Result<Vec<Str
 
 /// Enhanced event creation with contact lookup
 pub async fn create_event_with_contacts(
+    config: EventConfig,
+    contact_names: &[&str],
+) -> anyhow::Result<()> {
+    create_event_with_contacts_strategy(config, contact_names, ContactStrategy::default()).await
+}
+
+/// Event creation with contact lookup, disambiguating matches with the given strategy
+pub async fn create_event_with_contacts_strategy(
     mut config: EventConfig,
     contact_names: &[&str],
+    strategy: ContactStrategy,
 ) -> anyhow::Result<()> {
     use crate::calendar::create_event;
 
@@ -140,7 +447,7 @@ pub async fn create_event_with_contacts(
 
     for name in contact_names {
         info!("Looking up contact: '{}'", name);
-        match lookup_contact(name).await {
+        match resolve_contact(name, strategy).await {
             Ok(emails) => {
                 if emails.is_empty() {
                     info!("No email found for contact: '{}'", name);
@@ -178,5 +485,5 @@ pub async fn create_event_with_contacts(
     config.emails = all_emails;
 
     // Create the event with the updated config
-    create_event(config).await
+    Ok(create_event(config).await?)
 }
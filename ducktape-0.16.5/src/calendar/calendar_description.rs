@@ -0,0 +1,268 @@
+//! Variables in event descriptions.
+//
+// A description, or the default description configured for a calendar in
+// `calendar.description_templates`, may contain `{{date}}`, `{{attendees}}`, `{{zoom_url}}`,
+// `{{weather}}` and the other variables of `VARIABLES`; they are expanded when the event is
+// created. `{{name|text}}` gives text to use when the value is empty. The meeting link is only
+// known once the meeting exists, so `{{zoom_url}}` is filled by `fill_meeting_url` after that.
+
+use super::EventConfig;
+use crate::config::CalendarConfig;
+use chrono::{NaiveDate, NaiveTime, Timelike};
+use std::collections::HashMap;
+use tracing::debug;
+
+/// Variables a description may use
+pub const VARIABLES: &[&str] = &[
+    "title",
+    "date",
+    "start_time",
+    "end_time",
+    "calendar",
+    "location",
+    "url",
+    "attendees",
+    "zoom_url",
+    "meeting_url",
+    "weather",
+];
+
+/// Variables holding the link of the event's online meeting
+const MEETING_VARIABLES: &[&str] = &["zoom_url", "meeting_url"];
+
+/// Whether `text` uses the variable `name`
+pub fn uses_variable(text: &str, name: &str) -> bool {
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else { break };
+        let inner = &rest[start + 2..start + end];
+        if inner.split('|').next().unwrap_or_default().trim() == name {
+            return true;
+        }
+        rest = &rest[start + end + 2..];
+    }
+    false
+}
+
+/// Replace the variables of `template` found in `values`
+///
+/// An empty value is replaced by the text after `|`, if any. Variables without a value are
+/// left as they are, to be filled later or to show the mistake.
+pub fn expand_template(template: &str, values: &HashMap<&str, String>) -> String {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else { break };
+        let placeholder = &rest[start..start + end + 2];
+        let (name, fallback) = match placeholder[2..placeholder.len() - 2].split_once('|') {
+            Some((name, fallback)) => (name.trim(), Some(fallback.trim())),
+            None => (placeholder[2..placeholder.len() - 2].trim(), None),
+        };
+        expanded.push_str(&rest[..start]);
+        match values.get(name) {
+            Some(value) if !value.is_empty() => expanded.push_str(value),
+            Some(_) => expanded.push_str(fallback.unwrap_or_default()),
+            None => {
+                debug!("Leaving description variable '{}' unexpanded", name);
+                expanded.push_str(placeholder);
+            }
+        }
+        rest = &rest[start + end + 2..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Values of the variables for `config` in `calendar`
+///
+/// The meeting variables are left out while a meeting is still to be created.
+pub fn description_values(
+    config: &EventConfig,
+    calendar: &str,
+    weather: Option<&str>,
+) -> HashMap<&'static str, String> {
+    let mut values = HashMap::from([
+        ("title", config.title.clone()),
+        ("date", config.start_date.clone()),
+        (
+            "start_time",
+            if config.all_day { String::new() } else { config.start_time.clone() },
+        ),
+        ("end_time", config.end_time.clone().unwrap_or_default()),
+        ("calendar", calendar.to_string()),
+        ("location", config.location.clone().unwrap_or_default()),
+        ("url", config.url.clone().unwrap_or_default()),
+        ("attendees", config.emails.join(", ")),
+        ("weather", weather.unwrap_or_default().to_string()),
+    ]);
+    let meeting_pending = config.meeting_service.is_some() || config.create_zoom_meeting;
+    if !meeting_pending || config.zoom_join_url.is_some() {
+        for name in MEETING_VARIABLES {
+            values.insert(*name, config.zoom_join_url.clone().unwrap_or_default());
+        }
+    }
+    values
+}
+
+/// The description of `config` in each of `calendars`, with its variables expanded
+///
+/// Without a description of its own the event gets the calendar's default description, if
+/// one is configured. The weather is looked up once, and only when a description asks for it.
+pub async fn expand_descriptions(
+    config: &EventConfig,
+    calendars: &[String],
+    settings: &CalendarConfig,
+) -> Vec<Option<String>> {
+    let templates: Vec<Option<&str>> = calendars
+        .iter()
+        .map(|calendar| {
+            config
+                .description
+                .as_deref()
+                .or_else(|| settings.description_template(calendar))
+        })
+        .collect();
+    let weather = if templates.iter().flatten().any(|t| uses_variable(t, "weather")) {
+        weather(config).await
+    } else {
+        None
+    };
+    calendars
+        .iter()
+        .zip(templates)
+        .map(|(calendar, template)| {
+            let values = description_values(config, calendar, weather.as_deref());
+            template.map(|template| expand_template(template, &values))
+        })
+        .collect()
+}
+
+/// Put the link of the meeting created for the event in place of `{{zoom_url}}`
+pub fn fill_meeting_url(description: &str, url: Option<&str>) -> String {
+    let values: HashMap<&str, String> = MEETING_VARIABLES
+        .iter()
+        .map(|name| (*name, url.unwrap_or_default().to_string()))
+        .collect();
+    expand_template(description, &values)
+}
+
+/// Summarize the forecast in wttr.in's JSON for `date` around `time`, e.g. "Sunny, 12–19°C"
+pub fn parse_weather(json: &serde_json::Value, date: NaiveDate, time: NaiveTime) -> Option<String> {
+    let date = date.format("%Y-%m-%d").to_string();
+    let day = json["weather"].as_array()?.iter().find(|day| day["date"] == date.as_str())?;
+    // Forecasts are given every three hours, at "0", "300", ... "2100"
+    let hourly = day["hourly"].as_array()?;
+    let slot = hourly.get(time.hour() as usize / 3).or_else(|| hourly.first())?;
+    let conditions = slot["weatherDesc"][0]["value"].as_str().unwrap_or_default().trim();
+    let (low, high) = (day["mintempC"].as_str()?, day["maxtempC"].as_str()?);
+    if conditions.is_empty() {
+        Some(format!("{}–{}°C", low, high))
+    } else {
+        Some(format!("{}, {}–{}°C", conditions, low, high))
+    }
+}
+
+/// Forecast for the event's location, or where this Mac is, from wttr.in
+///
+/// Forecasts reach three days ahead; later events, and failures, give no weather.
+async fn weather(config: &EventConfig) -> Option<String> {
+    let date = NaiveDate::parse_from_str(&config.start_date, "%Y-%m-%d").ok()?;
+    let time = NaiveTime::parse_from_str(&config.start_time, "%H:%M").unwrap_or_default();
+    let mut url = url::Url::parse("https://wttr.in/").ok()?;
+    if let Some(location) = config.location.as_deref().filter(|l| !l.trim().is_empty()) {
+        url.path_segments_mut().ok()?.clear().push(location.trim());
+    }
+    url.query_pairs_mut().append_pair("format", "j1");
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(5))
+        .build()
+        .ok()?;
+    let result = async {
+        client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<serde_json::Value>()
+            .await
+    }
+    .await;
+    match result {
+        Ok(json) => parse_weather(&json, date, time),
+        Err(e) => {
+            debug!("No weather for the description: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event() -> EventConfig {
+        let mut config = EventConfig::new("Standup", "2025-04-22", "09:30");
+        config.end_time = Some("09:45".to_string());
+        config.emails = vec!["a@example.com".to_string(), "b@example.com".to_string()];
+        config
+    }
+
+    #[test]
+    fn test_expand_template() {
+        let values = description_values(&event(), "Work", None);
+        let expanded = expand_template(
+            "{{ title }} on {{date}} at {{start_time}} with {{attendees}} in {{location|the office}}",
+            &values,
+        );
+        assert_eq!(
+            expanded,
+            "Standup on 2025-04-22 at 09:30 with a@example.com, b@example.com in the office"
+        );
+        assert_eq!(expand_template("Bring {{snacks}}", &values), "Bring {{snacks}}");
+        assert_eq!(expand_template("Unclosed {{title", &values), "Unclosed {{title");
+    }
+
+    #[test]
+    fn test_meeting_url_waits_for_the_meeting() {
+        let mut config = event();
+        config.create_zoom_meeting = true;
+        let values = description_values(&config, "Work", None);
+        let description = expand_template("{{title}}: {{zoom_url}}", &values);
+        assert_eq!(description, "Standup: {{zoom_url}}");
+        assert_eq!(
+            fill_meeting_url(&description, Some("https://zoom.us/j/1")),
+            "Standup: https://zoom.us/j/1"
+        );
+
+        let values = description_values(&event(), "Work", None);
+        assert_eq!(expand_template("Join: {{zoom_url|in person}}", &values), "Join: in person");
+    }
+
+    #[test]
+    fn test_uses_variable() {
+        assert!(uses_variable("Forecast: {{ weather|unknown }}", "weather"));
+        assert!(!uses_variable("Weather permitting", "weather"));
+    }
+
+    #[test]
+    fn test_parse_weather() {
+        let json = serde_json::json!({
+            "weather": [{
+                "date": "2025-04-22",
+                "mintempC": "12",
+                "maxtempC": "19",
+                "hourly": [
+                    {"time": "0", "weatherDesc": [{"value": "Clear"}]},
+                    {"time": "300", "weatherDesc": [{"value": "Clear"}]},
+                    {"time": "600", "weatherDesc": [{"value": "Mist"}]},
+                    {"time": "900", "weatherDesc": [{"value": "Sunny"}]}
+                ]
+            }]
+        });
+        let date = NaiveDate::from_ymd_opt(2025, 4, 22).unwrap();
+        let time = NaiveTime::from_hms_opt(9, 30, 0).unwrap();
+        assert_eq!(parse_weather(&json, date, time).as_deref(), Some("Sunny, 12–19°C"));
+        assert!(parse_weather(&json, date.succ_opt().unwrap(), time).is_none());
+    }
+}
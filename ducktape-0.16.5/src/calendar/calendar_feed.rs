@@ -0,0 +1,165 @@
+//! Read-only iCalendar feeds of the events DuckTape has created.
+//
+// The API server answers `GET /calendar.ics?token=...` with the events recorded in the state
+// store plus those in the local .ics calendars, so that other applications can subscribe to
+// them. Each feed in config.toml has its own token and, optionally, the calendars it shows;
+// without a matching token the feed is not served.
+
+use crate::backend::ics::{feed_calendar, vevent};
+use crate::calendar::{EventConfig, ImportedEvent, find_duplicate, parse_ical_recurrence};
+use crate::config::{Config, FeedConfig};
+use crate::state::{CalendarItem, StateManager};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use tracing::warn;
+
+/// Where the API server started by `ducktape --api-server` serves feeds
+pub const FEED_URL: &str = "http://127.0.0.1:3000/calendar.ics";
+
+/// Subscription URL of a feed
+pub fn feed_url(feed: &FeedConfig) -> String {
+    format!("{}?token={}", FEED_URL, feed.token)
+}
+
+/// A new random feed token
+pub fn new_feed_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// The feed `token` belongs to, compared in constant time
+pub fn find_feed<'a>(feeds: &'a [FeedConfig], token: &str) -> Option<&'a FeedConfig> {
+    feeds.iter().find(|feed| {
+        let (a, b) = (feed.token.as_bytes(), token.as_bytes());
+        !a.is_empty()
+            && a.len() == b.len()
+            && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+    })
+}
+
+/// UID of a recorded event: its Calendar.app UID, else one derived from what it is
+///
+/// A derived UID stays the same between refreshes, so subscribers update events in place.
+pub fn item_uid(item: &CalendarItem) -> String {
+    if let Some(uid) = item.uid.as_deref().filter(|uid| !uid.is_empty()) {
+        return uid.to_string();
+    }
+    let key = format!("{}\n{}\n{}\n{}", item.title, item.date, item.time, item.calendars.join(","));
+    let digest = Sha256::digest(key.as_bytes());
+    let hex: String = digest.iter().take(16).map(|byte| format!("{:02x}", byte)).collect();
+    format!("{}@ducktape", hex)
+}
+
+/// The event a recorded item describes
+pub fn item_config(item: &CalendarItem) -> EventConfig {
+    let mut config = EventConfig::new(&item.title, &item.date, &item.time);
+    config.calendars = item.calendars.clone();
+    config.all_day = item.all_day;
+    config.end_time = item.end_time.clone();
+    config.location = item.location.clone();
+    config.description = item.description.clone();
+    config.reminder = item.reminder;
+    config.emails = item
+        .email
+        .iter()
+        .flat_map(|emails| emails.split(','))
+        .map(|email| email.trim().to_string())
+        .filter(|email| !email.is_empty())
+        .collect();
+    config.recurrence = item.recurrence.as_deref().and_then(|rule| {
+        parse_ical_recurrence(rule, None)
+            .map_err(|e| warn!("Ignoring recurrence '{}' of '{}': {}", rule, item.title, e))
+            .ok()
+    });
+    config
+}
+
+/// Events in the .ics calendars of `dir`, each with its calendar set from the file name
+pub fn local_events(dir: &Path) -> Vec<ImportedEvent> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut events = Vec::new();
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if !path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("ics")) {
+            continue;
+        }
+        let calendar = path.file_stem().map(|s| s.to_string_lossy().to_string());
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| crate::calendar::parse_ics_events(&content));
+        match parsed {
+            Ok(parsed) => {
+                for mut event in parsed.into_iter().flatten() {
+                    event.config.calendars = calendar.iter().cloned().collect();
+                    events.push(event);
+                }
+            }
+            Err(e) => warn!("Skipping {} in the feed: {}", path.display(), e),
+        }
+    }
+    events
+}
+
+fn in_calendars(config: &EventConfig, calendars: &[String]) -> bool {
+    calendars.is_empty()
+        || config
+            .calendars
+            .iter()
+            .any(|c| calendars.iter().any(|f| f.eq_ignore_ascii_case(c)))
+}
+
+/// The events of a feed as (UID, event), recorded events first
+///
+/// Local events that duplicate a recorded one are left out.
+pub fn feed_events(
+    items: &[CalendarItem],
+    local: Vec<ImportedEvent>,
+    calendars: &[String],
+) -> Vec<(String, EventConfig)> {
+    let mut events: Vec<(String, EventConfig)> =
+        items.iter().map(|item| (item_uid(item), item_config(item))).collect();
+    for event in local {
+        if find_duplicate(&event, items).is_some() {
+            continue;
+        }
+        let uid = event.uid.clone().unwrap_or_else(|| {
+            item_uid(&CalendarItem {
+                title: event.config.title.clone(),
+                date: event.config.start_date.clone(),
+                time: event.config.start_time.clone(),
+                calendars: event.config.calendars.clone(),
+                all_day: event.config.all_day,
+                ..Default::default()
+            })
+        });
+        if !events.iter().any(|(known, _)| *known == uid) {
+            events.push((uid, event.config));
+        }
+    }
+    events.retain(|(_, config)| in_calendars(config, calendars));
+    events
+}
+
+/// The iCalendar text of a feed; events that cannot be written are skipped
+pub fn render_feed(name: &str, events: &[(String, EventConfig)], stamp: DateTime<Utc>) -> String {
+    let components: String = events
+        .iter()
+        .filter_map(|(uid, config)| {
+            vevent(config, uid, stamp)
+                .map_err(|e| warn!("Leaving '{}' out of the feed: {}", config.title, e))
+                .ok()
+        })
+        .collect();
+    feed_calendar(name, &components)
+}
+
+/// Build a feed from the state store and the local .ics calendars
+pub fn build_feed(feed: &FeedConfig) -> Result<String> {
+    let config = Config::load()?;
+    let items: Vec<CalendarItem> = StateManager::new()?.load()?;
+    let local = local_events(&config.backend.data_dir()?.join("calendars"));
+    let events = feed_events(&items, local, &feed.calendars);
+    Ok(render_feed(&feed.name, &events, Utc::now()))
+}
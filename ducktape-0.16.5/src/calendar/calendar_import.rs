@@ -1,33 +1,1006 @@
 //! CSV and ICS import logic for DuckTape calendar module.
 //
-// This module provides functions to import events from CSV and ICS files.
+// This module provides functions to import events from CSV and ICS files. Events already known
+// to DuckTape (created by it, imported before or adopted from Calendar.app) are detected by UID
+// or by title and start, and `OnDuplicate` decides whether they are skipped, updated in place
+// or created again. Every import returns an `ImportSummary` for the CLI and WebSocket callers.
 
-use crate::calendar::calendar_types::RecurrencePattern;
-use anyhow::Result;
+use crate::applescript::{Script, Template};
+use crate::calendar::EventConfig;
+use crate::calendar::calendar_types::{RecurrenceFrequency, RecurrencePattern};
+use crate::script_runner::run_applescript_async;
+use crate::state::{CalendarItem, StateManager};
+use crate::webhooks::{Action, ItemKind};
+use anyhow::{Context, Result, anyhow};
+use chrono::{
+    Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc,
+};
+use chrono_tz::Tz;
+use ical::property::Property;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::fmt;
+use std::fs;
 use std::path::Path;
+use std::str::FromStr;
+use tracing::{debug, info, warn};
+
+/// What to do with an imported event that already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnDuplicate {
+    /// Leave the existing event alone
+    #[default]
+    Skip,
+    /// Change the existing event to match the imported one
+    Update,
+    /// Create the event anyway
+    Duplicate,
+}
+
+impl FromStr for OnDuplicate {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "skip" => Ok(OnDuplicate::Skip),
+            "update" => Ok(OnDuplicate::Update),
+            "duplicate" | "create" => Ok(OnDuplicate::Duplicate),
+            _ => Err(anyhow!(
+                "Invalid duplicate handling: {}. Valid options are: skip, update, duplicate",
+                s
+            )),
+        }
+    }
+}
+
+/// Outcome of an import
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ImportSummary {
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    /// Why events failed, one entry per failed event
+    pub errors: Vec<String>,
+}
+
+impl ImportSummary {
+    fn fail(&mut self, title: &str, error: impl fmt::Display) {
+        self.failed += 1;
+        self.errors.push(format!("{}: {}", title, error));
+    }
+
+    /// The counts followed by one line per failed event
+    pub fn report(&self) -> String {
+        let mut report = format!("Import finished: {}\n", self);
+        for error in &self.errors {
+            report.push_str(&format!("  failed: {}\n", error));
+        }
+        report
+    }
+}
+
+impl fmt::Display for ImportSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} created, {} updated, {} skipped, {} failed",
+            self.created, self.updated, self.skipped, self.failed
+        )
+    }
+}
+
+/// An event read from an import file
+#[derive(Debug, Clone)]
+pub struct ImportedEvent {
+    /// UID from the file, when it has one
+    pub uid: Option<String>,
+    pub config: EventConfig,
+}
+
+/// Index of the known event an imported event duplicates
+///
+/// Events match by UID, or by title (ignoring case) and start. All-day events match on the
+/// date alone.
+pub fn find_duplicate(event: &ImportedEvent, known: &[CalendarItem]) -> Option<usize> {
+    known.iter().position(|item| {
+        let same_uid = event.uid.is_some() && item.uid == event.uid;
+        let same_start = item.date == event.config.start_date
+            && ((item.all_day && event.config.all_day) || item.time == event.config.start_time);
+        same_uid || (same_start && item.title.eq_ignore_ascii_case(&event.config.title))
+    })
+}
+
+fn parse_date(value: &str) -> Result<String> {
+    ["%Y-%m-%d", "%m/%d/%Y", "%Y/%m/%d", "%d.%m.%Y", "%Y%m%d"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value.trim(), format).ok())
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .ok_or_else(|| anyhow!("Invalid date: {}", value))
+}
+
+fn parse_time(value: &str) -> Result<String> {
+    ["%H:%M", "%H:%M:%S", "%I:%M %p", "%I:%M:%S %p", "%I:%M%p"]
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(value.trim(), format).ok())
+        .map(|time| time.format("%H:%M").to_string())
+        .ok_or_else(|| anyhow!("Invalid time: {}", value))
+}
+
+/// A value of an event that a CSV column can hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CsvField {
+    Title,
+    Date,
+    Start,
+    EndDate,
+    End,
+    AllDay,
+    Location,
+    Description,
+    Calendar,
+    Uid,
+}
+
+impl CsvField {
+    const ALL: [CsvField; 10] = [
+        CsvField::Title,
+        CsvField::Date,
+        CsvField::Start,
+        CsvField::EndDate,
+        CsvField::End,
+        CsvField::AllDay,
+        CsvField::Location,
+        CsvField::Description,
+        CsvField::Calendar,
+        CsvField::Uid,
+    ];
+
+    /// Headers used by DuckTape and by the Google Calendar and Outlook exports
+    fn headers(&self) -> &'static [&'static str] {
+        match self {
+            CsvField::Title => &["title", "subject", "summary", "name"],
+            CsvField::Date => &["date", "start date", "start_date"],
+            CsvField::Start => &["start time", "start_time", "start", "time"],
+            CsvField::EndDate => &["end date", "end_date"],
+            CsvField::End => &["end time", "end_time", "end"],
+            CsvField::AllDay => &["all day", "all day event", "all_day"],
+            CsvField::Location => &["location"],
+            CsvField::Description => &["description", "notes"],
+            CsvField::Calendar => &["calendar"],
+            CsvField::Uid => &["uid", "id"],
+        }
+    }
+
+    /// Field suggested by the words in a header, e.g. "Meeting Subject" or "EndsAt"
+    fn guess(header: &str) -> Option<Self> {
+        // Words split at punctuation, spaces and camel case humps
+        let mut words: Vec<String> = Vec::new();
+        let mut previous_lower = false;
+        for c in header.chars() {
+            if !c.is_alphanumeric() {
+                previous_lower = false;
+                words.push(String::new());
+                continue;
+            }
+            if (c.is_uppercase() && previous_lower) || words.is_empty() {
+                words.push(String::new());
+            }
+            previous_lower = c.is_lowercase();
+            words.last_mut().unwrap().extend(c.to_lowercase());
+        }
+        let has = |candidates: &[&str]| {
+            words.iter().any(|w| {
+                candidates.iter().any(|c| w.as_str() == *c || w.strip_suffix('s') == Some(*c))
+            })
+        };
+
+        let field = if (has(&["all"]) && has(&["day"])) || has(&["allday"]) {
+            CsvField::AllDay
+        } else if has(&["id", "uid"]) {
+            CsvField::Uid
+        } else if has(&["end", "finish", "until", "to"]) {
+            if has(&["date", "day"]) { CsvField::EndDate } else { CsvField::End }
+        } else if has(&["start", "begin", "from"]) {
+            if has(&["date", "day"]) { CsvField::Date } else { CsvField::Start }
+        } else if has(&["date", "day", "when"]) {
+            CsvField::Date
+        } else if has(&["time"]) {
+            CsvField::Start
+        } else if has(&["calendar"]) {
+            CsvField::Calendar
+        } else if has(&["subject", "title", "summary", "event", "name", "what"]) {
+            CsvField::Title
+        } else if has(&["location", "where", "place", "venue", "room", "address"]) {
+            CsvField::Location
+        } else if has(&["description", "note", "detail", "body", "comment"]) {
+            CsvField::Description
+        } else {
+            return None;
+        };
+        Some(field)
+    }
+}
+
+impl FromStr for CsvField {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().replace([' ', '-'], "_").as_str() {
+            "title" | "subject" | "summary" => Ok(CsvField::Title),
+            "date" | "start_date" => Ok(CsvField::Date),
+            "start" | "start_time" | "time" => Ok(CsvField::Start),
+            "end_date" => Ok(CsvField::EndDate),
+            "end" | "end_time" => Ok(CsvField::End),
+            "all_day" | "allday" => Ok(CsvField::AllDay),
+            "location" => Ok(CsvField::Location),
+            "description" | "notes" => Ok(CsvField::Description),
+            "calendar" => Ok(CsvField::Calendar),
+            "uid" | "id" => Ok(CsvField::Uid),
+            _ => Err(anyhow!(
+                "Unknown event field: {}. Valid fields are: title, date, start, end_date, end, all_day, location, description, calendar, uid",
+                s
+            )),
+        }
+    }
+}
+
+/// Parse a column mapping such as `Subject=title,Start Date=date`
+///
+/// Entries are separated by commas or new lines, so a mapping file can hold one per line.
+/// Lines starting with `#` are comments.
+pub fn parse_column_map(spec: &str) -> Result<Vec<(String, CsvField)>> {
+    spec.lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .flat_map(|line| line.split(','))
+        .filter(|entry| !entry.trim().is_empty())
+        .map(|entry| {
+            let (header, field) = entry
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Invalid column mapping '{}', use Header=field", entry))?;
+            Ok((header.trim().to_string(), field.parse()?))
+        })
+        .collect()
+}
+
+/// Column mapping from a mapping file and a `--map` value; `--map` wins where both name a field
+pub fn load_column_map(
+    map: Option<&str>,
+    map_file: Option<&Path>,
+) -> Result<Vec<(String, CsvField)>> {
+    let mut entries = match map_file {
+        Some(path) => parse_column_map(
+            &fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?,
+        )?,
+        None => Vec::new(),
+    };
+    entries.extend(map.map(parse_column_map).transpose()?.unwrap_or_default());
+    Ok(entries)
+}
+
+/// A date, optionally followed by a time: "2025-04-15", "4/15/2025 9:00 AM", "2025-04-15T09:00"
+fn split_datetime(value: &str) -> Result<(String, Option<String>)> {
+    let value = value.trim();
+    if let Ok(date) = parse_date(value) {
+        return Ok((date, None));
+    }
+    let (date, time) =
+        value.split_once(['T', ' ']).ok_or_else(|| anyhow!("Invalid date: {}", value))?;
+    let date = parse_date(date)?;
+    let time = parse_time(time.trim_end_matches('Z'))?;
+    Ok((date, Some(time)))
+}
+
+/// A time, or the time of a date and time
+fn time_of(value: &str) -> Result<String> {
+    parse_time(value)
+        .or_else(|_| split_datetime(value)?.1.ok_or_else(|| anyhow!("Invalid time: {}", value)))
+}
+
+/// Positions of the event fields in a CSV header
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CsvColumns(HashMap<CsvField, usize>);
+
+impl CsvColumns {
+    /// Find the columns of a header
+    ///
+    /// `map` comes first, then the usual header names, then words in the header. When no
+    /// column is a date, the first column whose value in `sample` (the first row) is a date
+    /// is used; a single "Start" column can hold both the date and the time.
+    pub fn detect(
+        headers: &csv::StringRecord,
+        map: &[(String, CsvField)],
+        sample: Option<&csv::StringRecord>,
+    ) -> Result<Self> {
+        let found = || headers.iter().collect::<Vec<_>>().join(", ");
+        let mut columns = HashMap::new();
+        for (header, field) in map {
+            let index =
+                headers.iter().position(|h| h.eq_ignore_ascii_case(header.trim())).ok_or_else(
+                    || anyhow!("Column '{}' is not in the CSV header (found: {})", header, found()),
+                )?;
+            columns.insert(*field, index);
+        }
+
+        let taken = |columns: &HashMap<CsvField, usize>, index: usize| {
+            columns.values().any(|&taken| taken == index)
+        };
+        for field in CsvField::ALL {
+            if columns.contains_key(&field) {
+                continue;
+            }
+            if let Some(index) = headers.iter().enumerate().position(|(index, h)| {
+                !taken(&columns, index) && field.headers().iter().any(|n| h.eq_ignore_ascii_case(n))
+            }) {
+                columns.insert(field, index);
+            }
+        }
+        for (index, header) in headers.iter().enumerate() {
+            match CsvField::guess(header) {
+                Some(field) if !columns.contains_key(&field) && !taken(&columns, index) => {
+                    columns.insert(field, index);
+                }
+                _ => {}
+            }
+        }
+        if let Entry::Vacant(date) = columns.entry(CsvField::Date) {
+            let dated = sample.and_then(|row| {
+                row.iter().position(|value| !value.is_empty() && split_datetime(value).is_ok())
+            });
+            if let Some(index) = dated {
+                date.insert(index);
+            }
+        }
+
+        if !columns.contains_key(&CsvField::Title) || !columns.contains_key(&CsvField::Date) {
+            return Err(anyhow!(
+                "The CSV file needs a title and a date column (found: {}); name them with --map, e.g. --map \"Subject=title,Start Date=date\"",
+                found()
+            ));
+        }
+        Ok(Self(columns))
+    }
+
+    pub fn get(&self, field: CsvField) -> Option<usize> {
+        self.0.get(&field).copied()
+    }
+
+    fn event(&self, record: &csv::StringRecord) -> Result<ImportedEvent> {
+        let field = |field: CsvField| {
+            self.get(field).and_then(|c| record.get(c)).filter(|value| !value.is_empty())
+        };
+        let title = field(CsvField::Title).ok_or_else(|| anyhow!("No title"))?;
+        let (date, date_time) =
+            split_datetime(field(CsvField::Date).ok_or_else(|| anyhow!("No date"))?)?;
+        let start = match field(CsvField::Start) {
+            Some(start) if self.get(CsvField::Start) != self.get(CsvField::Date) => {
+                Some(time_of(start)?)
+            }
+            _ => date_time,
+        };
+        let all_day = field(CsvField::AllDay)
+            .is_some_and(|v| matches!(v.to_lowercase().as_str(), "true" | "yes" | "1" | "y"))
+            || start.is_none();
+
+        // An end column may hold the end date as well as the time
+        let (end_date, end_time) = match field(CsvField::End) {
+            Some(end) => match split_datetime(end) {
+                Ok((date, time)) => (Some(date), time),
+                Err(_) => (None, Some(parse_time(end)?)),
+            },
+            None => (None, None),
+        };
+        let end_date = match field(CsvField::EndDate) {
+            Some(end_date) => Some(split_datetime(end_date)?.0),
+            None => end_date,
+        };
+
+        let mut config = match start {
+            Some(start) if !all_day => {
+                let mut config = EventConfig::new(title, &date, &start);
+                config.end_time = end_time;
+                config
+            }
+            _ => {
+                let mut config = EventConfig::new(title, &date, "00:00");
+                config.all_day = true;
+                config
+            }
+        };
+        config.end_date = end_date.filter(|end| *end != date);
+        config.location = field(CsvField::Location).map(str::to_string);
+        config.description = field(CsvField::Description).map(str::to_string);
+        config.calendars = field(CsvField::Calendar).map(str::to_string).into_iter().collect();
+        Ok(ImportedEvent { uid: field(CsvField::Uid).map(str::to_string), config })
+    }
+}
+
+/// Events in CSV content with a header row
+///
+/// Columns are found with `CsvColumns::detect`; `map` names the ones it cannot guess. Rows
+/// without a start time are all-day events. A row that cannot be read is an `Err` entry
+/// starting with its line number; the others are still imported.
+pub fn parse_csv_events(
+    content: &str,
+    map: &[(String, CsvField)],
+) -> Result<Vec<Result<ImportedEvent>>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+    let headers = reader.headers().context("Failed to read the CSV header")?.clone();
+    let records: Vec<_> = reader.records().collect();
+    let sample = records.iter().find_map(|record| record.as_ref().ok());
+    let columns = CsvColumns::detect(&headers, map, sample)?;
+
+    Ok(records
+        .into_iter()
+        .enumerate()
+        .map(|(index, record)| {
+            // Quoted values can span lines, so use the reader's line numbers where it has them
+            let line = match &record {
+                Ok(record) => record.position().map(|p| p.line()),
+                Err(e) => e.position().map(|p| p.line()),
+            }
+            .unwrap_or(index as u64 + 2);
+            record
+                .map_err(anyhow::Error::from)
+                .and_then(|record| columns.event(&record))
+                .map_err(|e| anyhow!("Line {}: {}", line, e))
+        })
+        .collect())
+}
+
+/// Text of a property with the iCalendar escapes (`\n`, `\,`, `\;`, `\\`) undone
+fn ics_text(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => text.push('\n'),
+            Some(other) => text.push(other),
+            None => text.push('\\'),
+        }
+    }
+    text
+}
+
+fn ics_param<'a>(property: &'a Property, name: &str) -> Option<&'a str> {
+    property
+        .params
+        .as_ref()?
+        .iter()
+        .find(|(param, _)| param.eq_ignore_ascii_case(name))
+        .and_then(|(_, values)| values.first())
+        .map(String::as_str)
+}
+
+/// A date or date-time value: the date and time (`None` for a date), and the zone it is in
+///
+/// UTC times are converted to local time. Zones outside the IANA database (e.g. Windows
+/// names) are read as local time.
+fn ics_value(
+    name: &str,
+    value: &str,
+    is_date: bool,
+    tzid: Option<&str>,
+) -> Result<(NaiveDate, Option<NaiveTime>, Option<Tz>)> {
+    let value = value.trim();
+    if value.len() == 8 || is_date {
+        let date = NaiveDate::parse_from_str(&value[..value.len().min(8)], "%Y%m%d")
+            .with_context(|| format!("Invalid {}: {}", name, value))?;
+        return Ok((date, None, None));
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let time = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+            .with_context(|| format!("Invalid {}: {}", name, value))?;
+        let local = Utc.from_utc_datetime(&time).with_timezone(&Local).naive_local();
+        return Ok((local.date(), Some(local.time()), None));
+    }
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .with_context(|| format!("Invalid {}: {}", name, value))?;
+    let zone = tzid.and_then(|tzid| Tz::from_str(tzid).ok());
+    Ok((time.date(), Some(time.time()), zone))
+}
+
+/// A DTSTART, DTEND or RECURRENCE-ID
+fn ics_datetime(property: &Property) -> Result<(NaiveDate, Option<NaiveTime>, Option<Tz>)> {
+    ics_value(
+        &property.name,
+        property.value.as_deref().unwrap_or_default(),
+        ics_param(property, "VALUE").is_some_and(|v| v == "DATE"),
+        ics_param(property, "TZID"),
+    )
+}
+
+/// The dates of an EXDATE or RDATE, which can list several
+fn ics_dates(property: &Property) -> Result<Vec<String>> {
+    if ics_param(property, "VALUE").is_some_and(|v| v == "PERIOD") {
+        return Err(anyhow!("{} periods are not supported", property.name));
+    }
+    property
+        .value
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .filter(|value| !value.trim().is_empty())
+        .map(|value| {
+            let (date, _, _) = ics_value(
+                &property.name,
+                value,
+                ics_param(property, "VALUE").is_some_and(|v| v == "DATE"),
+                ics_param(property, "TZID"),
+            )?;
+            Ok(date.format("%Y-%m-%d").to_string())
+        })
+        .collect()
+}
+
+fn ics_event(properties: &[Property]) -> Result<ImportedEvent> {
+    let find = |name: &str| properties.iter().find(|p| p.name.eq_ignore_ascii_case(name));
+    let text = |name: &str| {
+        find(name)
+            .and_then(|p| p.value.as_deref())
+            .map(ics_text)
+            .filter(|v| !v.is_empty())
+    };
+    // EXDATE and RDATE may each appear more than once
+    let dates = |name: &str| -> Result<Vec<String>> {
+        let mut dates = Vec::new();
+        for property in properties.iter().filter(|p| p.name.eq_ignore_ascii_case(name)) {
+            dates.extend(ics_dates(property)?);
+        }
+        Ok(dates)
+    };
+
+    let title = text("SUMMARY").ok_or_else(|| anyhow!("Event without a SUMMARY"))?;
+    let start = find("DTSTART").ok_or_else(|| anyhow!("{}: no DTSTART", title))?;
+    let (start_date, start_time, zone) =
+        ics_datetime(start).map_err(|e| anyhow!("{}: {}", title, e))?;
+    let end = find("DTEND")
+        .map(ics_datetime)
+        .transpose()
+        .map_err(|e| anyhow!("{}: {}", title, e))?;
+
+    let mut config = match start_time {
+        Some(time) => {
+            let mut config = EventConfig::new(
+                &title,
+                &start_date.format("%Y-%m-%d").to_string(),
+                &time.format("%H:%M").to_string(),
+            );
+            if let Some((end_date, Some(end_time), _)) = end {
+                config.end_time = Some(end_time.format("%H:%M").to_string());
+                if end_date != start_date {
+                    config.end_date = Some(end_date.format("%Y-%m-%d").to_string());
+                }
+            }
+            config.timezone = zone.map(|tz| tz.name().to_string());
+            config
+        }
+        None => {
+            let mut config =
+                EventConfig::new(&title, &start_date.format("%Y-%m-%d").to_string(), "00:00");
+            config.all_day = true;
+            // DTEND of an all-day event is the day after the last one
+            if let Some((end_date, None, _)) = end {
+                let last = end_date.pred_opt().unwrap_or(end_date);
+                if last > start_date {
+                    config.end_date = Some(last.format("%Y-%m-%d").to_string());
+                }
+            }
+            config
+        }
+    };
+    config.location = text("LOCATION");
+    config.description = text("DESCRIPTION");
+
+    let extra_dates = dates("RDATE").map_err(|e| anyhow!("{}: {}", title, e))?;
+    if let Some(rule) = text("RRULE") {
+        let mut recurrence =
+            parse_ical_recurrence(&rule, zone).map_err(|e| anyhow!("{}: {}", title, e))?;
+        recurrence.exception_dates = dates("EXDATE").map_err(|e| anyhow!("{}: {}", title, e))?;
+        recurrence.extra_dates = extra_dates;
+        config.recurrence = Some(recurrence);
+    } else if !extra_dates.is_empty() {
+        // Without a rule the extra dates are a series of their own, kept as one event per date
+        let mut recurrence = RecurrencePattern::new(RecurrenceFrequency::Daily);
+        recurrence.count = Some(1);
+        recurrence.extra_dates = extra_dates;
+        config.recurrence = Some(recurrence);
+    }
+    Ok(ImportedEvent { uid: text("UID"), config })
+}
+
+/// Events in iCalendar content
+///
+/// Attendees are not imported, so that importing does not send invitations. Occurrences of a
+/// series that the file changes (events with a RECURRENCE-ID) are left out of the series and
+/// imported as events of their own. An event that cannot be read is an `Err` entry; the others
+/// are still imported.
+pub fn parse_ics_events(content: &str) -> Result<Vec<Result<ImportedEvent>>> {
+    let mut raw = Vec::new();
+    for calendar in ical::IcalParser::new(content.as_bytes()) {
+        let calendar = calendar.map_err(|e| anyhow!("Invalid iCalendar file: {}", e))?;
+        raw.extend(calendar.events);
+    }
+
+    let property = |event: &ical::parser::ical::component::IcalEvent, name: &str| {
+        event.properties.iter().find(|p| p.name.eq_ignore_ascii_case(name)).cloned()
+    };
+    // (UID, date) of each changed occurrence
+    let moved: Vec<(String, String)> = raw
+        .iter()
+        .filter_map(|event| {
+            let uid = property(event, "UID")?.value?;
+            let (date, _, _) = ics_datetime(&property(event, "RECURRENCE-ID")?).ok()?;
+            Some((uid, date.format("%Y-%m-%d").to_string()))
+        })
+        .collect();
+
+    Ok(raw
+        .iter()
+        .map(|event| {
+            let mut imported = ics_event(&event.properties)?;
+            let recurrence_id = property(event, "RECURRENCE-ID")
+                .map(|p| ics_datetime(&p))
+                .transpose()?
+                .map(|(date, _, _)| date.format("%Y-%m-%d").to_string());
+            match (recurrence_id, &mut imported.config.recurrence) {
+                (Some(date), _) => {
+                    imported.uid = imported.uid.map(|uid| format!("{}/{}", uid, date));
+                    imported.config.recurrence = None;
+                }
+                (None, Some(recurrence)) => {
+                    for (uid, date) in &moved {
+                        if imported.uid.as_ref() == Some(uid)
+                            && !recurrence.exception_dates.contains(date)
+                        {
+                            recurrence.exception_dates.push(date.clone());
+                        }
+                    }
+                }
+                (None, None) => {}
+            }
+            Ok(imported)
+        })
+        .collect())
+}
 
 /// Import calendar events from a CSV file
-pub async fn import_csv_events(_file_path: &Path, _target_calendar: Option<String>) -> Result<i32> {
-    // TODO: Implement CSV import
-    println!("CSV import not yet implemented");
-    Ok(0)
+///
+/// `column_map` names columns whose headers are not recognised, see `parse_column_map`.
+pub async fn import_csv_events(
+    file_path: &Path,
+    target_calendar: Option<String>,
+    on_duplicate: OnDuplicate,
+    column_map: &[(String, CsvField)],
+) -> Result<ImportSummary> {
+    let content = fs::read_to_string(file_path)
+        .with_context(|| format!("Failed to read {}", file_path.display()))?;
+    import_events(parse_csv_events(&content, column_map)?, target_calendar, on_duplicate).await
 }
 
 /// Import calendar events from an iCalendar (.ics) file
-pub async fn import_ics_events(_file_path: &Path, _target_calendar: Option<String>) -> Result<i32> {
-    // TODO: Implement ICS import
-    println!("ICS import not yet implemented");
-    Ok(0)
+pub async fn import_ics_events(
+    file_path: &Path,
+    target_calendar: Option<String>,
+    on_duplicate: OnDuplicate,
+) -> Result<ImportSummary> {
+    let content = fs::read_to_string(file_path)
+        .with_context(|| format!("Failed to read {}", file_path.display()))?;
+    import_events(parse_ics_events(&content)?, target_calendar, on_duplicate).await
 }
 
-/// Import a single iCal event
-pub async fn import_ical_event(/* params */) -> Result<()> {
-    // ...implementation moved from calendar.rs...
-    Ok(())
+/// Create or update parsed events, checking each against the events in state
+pub async fn import_events(
+    events: Vec<Result<ImportedEvent>>,
+    target_calendar: Option<String>,
+    on_duplicate: OnDuplicate,
+) -> Result<ImportSummary> {
+    let state = StateManager::new()?;
+    let mut known: Vec<CalendarItem> = state.load()?;
+    let mut summary = ImportSummary::default();
+
+    let total = events.len();
+    for (index, event) in events.into_iter().enumerate() {
+        let title = event.as_ref().map_or("", |event| event.config.title.as_str());
+        crate::tasks::progress(index, total, title);
+        let mut event = match event {
+            Ok(event) => event,
+            Err(e) => {
+                summary.failed += 1;
+                summary.errors.push(e.to_string());
+                continue;
+            }
+        };
+        if let Some(calendar) = &target_calendar {
+            event.config.calendars = vec![calendar.clone()];
+        }
+
+        match (find_duplicate(&event, &known), on_duplicate) {
+            (Some(_), OnDuplicate::Skip) => {
+                debug!("Skipping '{}': already exists", event.config.title);
+                summary.skipped += 1;
+            }
+            (Some(index), OnDuplicate::Update) => {
+                let backend = crate::backend::calendar()?;
+                match backend.update_event(&known[index], event.config.clone()).await {
+                    Ok(()) => {
+                        apply_update(&mut known[index], &event);
+                        state.save(&known)?;
+                        summary.updated += 1;
+                        let hook =
+                            crate::webhooks::event_data(&event.config, &known[index].calendars);
+                        crate::webhooks::notify(ItemKind::Event, Action::Updated, hook);
+                    }
+                    Err(e) => summary.fail(&event.config.title, e),
+                }
+            }
+            _ => match crate::calendar::create_event(event.config.clone()).await {
+                Ok(()) => {
+                    // create_event records the event in state; add the UID from the file
+                    known = state.load()?;
+                    if let Some(item) = known.iter_mut().rev().find(|item| {
+                        item.uid.is_none()
+                            && item.title == event.config.title
+                            && item.date == event.config.start_date
+                    }) {
+                        item.uid = event.uid.clone();
+                        state.save(&known)?;
+                    }
+                    summary.created += 1;
+                }
+                Err(e) => summary.fail(&event.config.title, e),
+            },
+        }
+    }
+
+    crate::tasks::progress(total, total, "");
+    info!("Import finished: {}", summary);
+    if summary.failed > 0 {
+        warn!("{} events could not be imported", summary.failed);
+    }
+    Ok(summary)
+}
+
+/// Bring the state entry of an updated event up to date
+fn apply_update(item: &mut CalendarItem, event: &ImportedEvent) {
+    let config = &event.config;
+    item.title = config.title.clone();
+    item.date = config.start_date.clone();
+    item.time = config.start_time.clone();
+    item.end_time = config.end_time.clone();
+    item.all_day = config.all_day;
+    item.location = config.location.clone();
+    item.description = config.description.clone();
+    if item.uid.is_none() {
+        item.uid = event.uid.clone();
+    }
+}
+
+/// Start and end of an event in local time
+fn local_span(config: &EventConfig) -> Result<(NaiveDateTime, NaiveDateTime)> {
+    let start_time = if config.all_day { "00:00" } else { config.start_time.as_str() };
+    let naive_start = NaiveDateTime::parse_from_str(
+        &format!("{} {}", config.start_date, start_time),
+        "%Y-%m-%d %H:%M",
+    )
+    .map_err(|e| anyhow!("Invalid start datetime: {}", e))?;
+    let end_date = config.end_date.as_deref().unwrap_or(&config.start_date);
+    let mut naive_end = if config.all_day {
+        NaiveDate::parse_from_str(end_date, "%Y-%m-%d")?.and_time(NaiveTime::MIN)
+            + Duration::days(1)
+    } else if let Some(end_time) = &config.end_time {
+        NaiveDateTime::parse_from_str(&format!("{} {}", end_date, end_time), "%Y-%m-%d %H:%M")
+            .map_err(|e| anyhow!("Invalid end datetime: {}", e))?
+    } else {
+        naive_start + Duration::hours(1)
+    };
+    if naive_end <= naive_start {
+        naive_end += Duration::days(1);
+    }
+
+    let to_local = |time: NaiveDateTime| -> Result<NaiveDateTime> {
+        match config.timezone.as_deref().and_then(|tz| Tz::from_str(tz).ok()) {
+            Some(tz) if !config.all_day => Ok(tz
+                .from_local_datetime(&time)
+                .single()
+                .ok_or_else(|| anyhow!("Invalid or ambiguous time in timezone {}", tz))?
+                .with_timezone(&Local)
+                .naive_local()),
+            _ => Ok(time),
+        }
+    };
+    Ok((to_local(naive_start)?, to_local(naive_end)?))
 }
 
-/// Parse iCal recurrence rule
-pub fn parse_ical_recurrence(_rrule: &str) -> Option<RecurrencePattern> {
-    // ...implementation moved from calendar.rs...
-    None
+/// AppleScript setting the date variable `name` to `time`
+fn applescript_date(name: &'static str, time: NaiveDateTime) -> Result<Script> {
+    Template::new(
+        "set ${name} to current date\n        set day of ${name} to 1\n        set year of ${name} to ${year}\n        set month of ${name} to ${month}\n        set day of ${name} to ${day}\n        set time of ${name} to ${time}",
+    )
+    .script("name", name)
+    .number("year", time.year())
+    .number("month", time.month())
+    .number("day", time.day())
+    .number("time", time.num_seconds_from_midnight())
+    .render()
+}
+
+/// Change a Calendar.app event, found by UID or by title and start, to match `config`
+pub(crate) async fn update_single_event(
+    existing: &CalendarItem,
+    config: &EventConfig,
+) -> Result<()> {
+    let calendar = existing
+        .calendars
+        .first()
+        .or(config.calendars.first())
+        .ok_or_else(|| anyhow!("Calendar of '{}' is unknown", existing.title))?;
+    let old_time = if existing.all_day { "00:00" } else { existing.time.as_str() };
+    let old_start =
+        NaiveDateTime::parse_from_str(&format!("{} {}", existing.date, old_time), "%Y-%m-%d %H:%M")
+            .map_err(|e| anyhow!("Invalid start of '{}': {}", existing.title, e))?;
+    let (start, end) = local_span(config)?;
+
+    let script = Template::new(
+        r#"tell application "Calendar"
+    try
+        ${old_start}
+        ${new_start}
+        ${new_end}
+        set cal to calendar ${calendar}
+        set found to (every event of cal whose uid is ${uid})
+        if (count of found) is 0 then set found to (every event of cal whose summary is ${old_title} and start date is oldStart)
+        if (count of found) is 0 then error "Event not found in " & ${calendar}
+        set e to item 1 of found
+        set summary of e to ${title}
+        set allday event of e to ${all_day}
+        set start date of e to newStart
+        set end date of e to newEnd
+        set location of e to ${location}
+        set description of e to ${description}
+        return "Success"
+    on error errMsg
+        return "Error: " & errMsg
+    end try
+end tell"#,
+    )
+    .script("old_start", applescript_date("oldStart", old_start)?)
+    .script("new_start", applescript_date("newStart", start)?)
+    .script("new_end", applescript_date("newEnd", end)?)
+    .text("calendar", calendar)
+    .text("uid", existing.uid.as_deref().unwrap_or_default())
+    .text("old_title", &existing.title)
+    .text("title", &config.title)
+    .boolean("all_day", config.all_day)
+    .text("location", config.location.as_deref().unwrap_or_default())
+    .text("description", config.description.as_deref().unwrap_or_default())
+    .render()?;
+
+    let output = run_applescript_async(script).await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if output.status.success() && stdout.contains("Success") {
+        println!("Event '{}' updated in calendar '{}'", config.title, calendar);
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Failed to update event '{}': {}",
+            existing.title,
+            stdout.replace("Error: ", "").trim()
+        ))
+    }
+}
+
+/// The last day of a recurrence from an UNTIL value, in local time for UTC values and in the
+/// event's zone for floating ones
+fn until_date(value: &str, zone: Option<Tz>) -> Result<String> {
+    let (date, time, _) = ics_value("UNTIL", value, false, None)?;
+    let date = match (time, zone) {
+        (Some(time), Some(zone)) if !value.ends_with('Z') => zone
+            .from_local_datetime(&date.and_time(time))
+            .earliest()
+            .map_or(date, |at| at.with_timezone(&Local).date_naive()),
+        _ => date,
+    };
+    Ok(date.format("%Y-%m-%d").to_string())
+}
+
+/// Parse an RFC 5545 recurrence rule, e.g. `FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1`
+///
+/// `zone` is the zone of the event's DTSTART, used for an UNTIL in floating time. Rules with
+/// parts DuckTape cannot express (hourly series, BYWEEKNO, BYHOUR, ...) are an error rather
+/// than being imported as something else.
+pub fn parse_ical_recurrence(rrule: &str, zone: Option<Tz>) -> Result<RecurrencePattern> {
+    const DAYS: [&str; 7] = ["SU", "MO", "TU", "WE", "TH", "FR", "SA"];
+    fn numbers<T: FromStr>(key: &str, value: &str) -> Result<Vec<T>> {
+        value
+            .split(',')
+            .map(|n| n.trim().parse().map_err(|_| anyhow!("Invalid {} value: {}", key, n)))
+            .collect()
+    }
+
+    let rule = rrule.trim().trim_start_matches("RRULE:");
+    let mut parts = Vec::new();
+    for part in rule.split(';').filter(|part| !part.trim().is_empty()) {
+        let (key, value) = part
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Invalid recurrence rule part: {}", part))?;
+        parts.push((key.trim().to_uppercase(), value.trim().to_uppercase()));
+    }
+    let frequency = match parts.iter().find(|(key, _)| key == "FREQ").map(|(_, v)| v.as_str()) {
+        Some("DAILY") => RecurrenceFrequency::Daily,
+        Some("WEEKLY") => RecurrenceFrequency::Weekly,
+        Some("MONTHLY") => RecurrenceFrequency::Monthly,
+        Some("YEARLY") => RecurrenceFrequency::Yearly,
+        Some(other) => return Err(anyhow!("Unsupported recurrence frequency: {}", other)),
+        None => return Err(anyhow!("Recurrence rule without FREQ: {}", rrule)),
+    };
+
+    let mut pattern = RecurrencePattern::new(frequency);
+    for (key, value) in &parts {
+        match key.as_str() {
+            "FREQ" | "WKST" => {}
+            "INTERVAL" => {
+                pattern.interval = value
+                    .parse()
+                    .ok()
+                    .filter(|n| *n > 0)
+                    .ok_or_else(|| anyhow!("Invalid INTERVAL value: {}", value))?
+            }
+            "COUNT" => {
+                pattern.count =
+                    Some(value.parse().map_err(|_| anyhow!("Invalid COUNT value: {}", value))?)
+            }
+            "UNTIL" => pattern.end_date = Some(until_date(value, zone)?),
+            "BYDAY" => {
+                for day in value.split(',').map(str::trim) {
+                    let split = day.len().saturating_sub(2);
+                    let (ordinal, code) = day.split_at(split);
+                    let weekday = DAYS
+                        .iter()
+                        .position(|d| *d == code)
+                        .ok_or_else(|| anyhow!("Invalid BYDAY value: {}", day))?
+                        as u8;
+                    if ordinal.is_empty() {
+                        pattern.days_of_week.push(weekday);
+                    } else {
+                        let n: i8 = ordinal
+                            .trim_start_matches('+')
+                            .parse()
+                            .ok()
+                            .filter(|n: &i8| *n != 0 && n.abs() <= 53)
+                            .ok_or_else(|| anyhow!("Invalid BYDAY value: {}", day))?;
+                        pattern.ordinal_days.push((n, weekday));
+                    }
+                }
+            }
+            "BYMONTHDAY" => {
+                pattern.month_days = numbers(key, value)?;
+                if pattern.month_days.iter().any(|d| *d == 0 || d.abs() > 31) {
+                    return Err(anyhow!("Invalid BYMONTHDAY value: {}", value));
+                }
+            }
+            "BYMONTH" => {
+                pattern.months = numbers(key, value)?;
+                if pattern.months.iter().any(|m| !(1..=12).contains(m)) {
+                    return Err(anyhow!("Invalid BYMONTH value: {}", value));
+                }
+            }
+            "BYSETPOS" => {
+                pattern.set_positions = numbers(key, value)?;
+                if pattern.set_positions.iter().any(|p| *p == 0 || p.abs() > 366) {
+                    return Err(anyhow!("Invalid BYSETPOS value: {}", value));
+                }
+            }
+            other => return Err(anyhow!("Unsupported recurrence rule part: {}", other)),
+        }
+    }
+    Ok(pattern)
 }
@@ -0,0 +1,254 @@
+//! Two-way sync between the events DuckTape tracks and Calendar.app.
+//
+// `ducktape sync` reads the events of the synced days from Calendar.app and compares each
+// tracked event, by UID, with its Calendar.app version and with the version both sides had
+// at the last sync (kept in ~/.ducktape/sync_base.json). That tells which side changed: a
+// change made in Calendar.app is copied to the tracked event, an event deleted there is no
+// longer tracked, and a change made in DuckTape (for example by `state import`) is written to
+// Calendar.app. When both sides changed, `sync.conflicts` decides. Events never synced before
+// take Calendar.app's version. Moving an event to another calendar or changing its repeat
+// rule cannot be written to Calendar.app, so such local changes are reported instead.
+
+use crate::calendar::EventConfig;
+use crate::config::{CalendarBackendKind, Config, SyncConflicts};
+use crate::state::{CalendarItem, StateManager, SyncBaseEntry};
+use crate::watch::EventChange;
+use anyhow::{Result, anyhow};
+use chrono::{Duration, Local, NaiveDate};
+use std::collections::{HashMap, HashSet};
+use tracing::{info, warn};
+
+/// Days before today that are synced
+pub const SYNC_PAST_DAYS: i64 = 30;
+
+/// The fields sync compares, as one string
+pub fn fingerprint(item: &CalendarItem) -> String {
+    let (time, end_time) = if item.all_day {
+        ("", "")
+    } else {
+        (item.time.as_str(), item.end_time.as_deref().unwrap_or_default())
+    };
+    [
+        item.title.trim(),
+        &item.date,
+        time,
+        end_time,
+        if item.all_day { "all-day" } else { "" },
+        item.location.as_deref().unwrap_or_default(),
+        &item.calendars.join(","),
+        item.recurrence.as_deref().unwrap_or_default(),
+    ]
+    .join("\u{1f}")
+}
+
+/// What `sync` does, worked out before anything changes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncPlan {
+    /// Changes made in Calendar.app, for the tracked events
+    pub pull: Vec<EventChange>,
+    /// Events changed in DuckTape, as (Calendar.app's version, DuckTape's version)
+    pub push: Vec<(CalendarItem, CalendarItem)>,
+    /// Changes left alone, and why
+    pub conflicts: Vec<String>,
+    /// Tracked events compared
+    pub checked: usize,
+}
+
+impl SyncPlan {
+    pub fn is_empty(&self) -> bool {
+        self.pull.is_empty() && self.push.is_empty() && self.conflicts.is_empty()
+    }
+}
+
+/// Work out what to change for the events on `from` up to (not including) `until`
+///
+/// `base` maps UIDs to the fingerprint both sides had at the last sync. With `adopt`,
+/// events in Calendar.app that are not tracked yet start being tracked.
+pub fn plan_sync(
+    local: &[CalendarItem],
+    remote: &[CalendarItem],
+    base: &HashMap<String, String>,
+    (from, until): (NaiveDate, NaiveDate),
+    conflicts: SyncConflicts,
+    adopt: bool,
+) -> SyncPlan {
+    let (from, until) = (from.format("%Y-%m-%d").to_string(), until.format("%Y-%m-%d").to_string());
+    let in_window = |item: &&CalendarItem| item.date >= from && item.date < until;
+    let mut plan = SyncPlan::default();
+
+    for item in local.iter().filter(in_window) {
+        let Some(uid) = &item.uid else { continue };
+        plan.checked += 1;
+        let ours = fingerprint(item);
+        let base = base.get(uid);
+        let changed_here = base.is_some_and(|base| *base != ours);
+
+        let Some(theirs) = remote.iter().find(|event| event.uid.as_ref() == Some(uid)) else {
+            // Deleted in Calendar.app, or moved out of the synced days
+            if changed_here && conflicts != SyncConflicts::Calendar {
+                plan.conflicts.push(format!(
+                    "{}: deleted in Calendar.app but changed in DuckTape, kept",
+                    item.title
+                ));
+            } else {
+                plan.pull.push(EventChange::Removed(item.clone()));
+            }
+            continue;
+        };
+        let their_fingerprint = fingerprint(theirs);
+        if ours == their_fingerprint {
+            continue;
+        }
+
+        let changed_there = base.is_none_or(|base| *base != their_fingerprint);
+        let keep_ours = match (changed_here, changed_there) {
+            (false, _) => false,
+            (true, false) => true,
+            (true, true) => match conflicts {
+                SyncConflicts::Calendar => false,
+                SyncConflicts::Local => true,
+                SyncConflicts::Skip => {
+                    plan.conflicts.push(format!(
+                        "{}: changed in both DuckTape and Calendar.app, left alone",
+                        item.title
+                    ));
+                    continue;
+                }
+            },
+        };
+        if !keep_ours {
+            plan.pull
+                .push(EventChange::Changed { before: item.clone(), after: theirs.clone() });
+        } else if item.calendars != theirs.calendars || item.recurrence != theirs.recurrence {
+            plan.conflicts.push(format!(
+                "{}: a new calendar or repeat rule cannot be written to Calendar.app, left alone",
+                item.title
+            ));
+        } else {
+            plan.push.push((theirs.clone(), item.clone()));
+        }
+    }
+
+    // Events in Calendar.app that are not tracked, or tracked without a UID
+    let tracked: HashSet<&str> = local.iter().filter_map(|item| item.uid.as_deref()).collect();
+    for event in remote.iter().filter(in_window) {
+        if event.uid.as_deref().is_some_and(|uid| tracked.contains(uid)) {
+            continue;
+        }
+        let untracked_copy = local.iter().any(|item| {
+            item.uid.is_none()
+                && item.title == event.title
+                && item.date == event.date
+                && item.time == event.time
+        });
+        if adopt || untracked_copy {
+            plan.pull.push(EventChange::Added(event.clone()));
+        }
+    }
+    plan
+}
+
+/// Settings for an event that makes Calendar.app's version match `item`
+pub(crate) fn event_config(item: &CalendarItem) -> EventConfig {
+    let mut config = EventConfig::new(&item.title, &item.date, &item.time);
+    config.end_time = item.end_time.clone();
+    config.all_day = item.all_day;
+    config.location = item.location.clone();
+    config.description = item.description.clone();
+    config.calendars = item.calendars.clone();
+    config
+}
+
+/// Sync the tracked events of the last `SYNC_PAST_DAYS` and the next `days` days
+///
+/// With `dry_run` the plan is returned without changing anything.
+pub async fn sync_events(days: u32, adopt: bool, dry_run: bool) -> Result<SyncPlan> {
+    let config = Config::load()?;
+    if config.backend.calendar_kind() != CalendarBackendKind::Apple {
+        return Err(anyhow!(
+            "sync works with Calendar.app; backend.calendar is {}",
+            config.backend.calendar_kind().as_str()
+        ));
+    }
+    let today = Local::now().date_naive();
+    let from = today - Duration::days(SYNC_PAST_DAYS);
+    let until = today + Duration::days(days as i64);
+    let remote = crate::watch::snapshot(from, (until - from).num_days() as u32, None).await?;
+
+    let manager = StateManager::new()?;
+    let local: Vec<CalendarItem> = manager.load()?;
+    let mut base: HashMap<String, String> = manager
+        .load::<SyncBaseEntry>()?
+        .into_iter()
+        .map(|entry| (entry.uid, entry.fingerprint))
+        .collect();
+    let plan = plan_sync(&local, &remote, &base, (from, until), config.sync.conflicts, adopt);
+    if dry_run {
+        return Ok(plan);
+    }
+
+    let backend = crate::backend::calendar_for(&config)?;
+    let mut pushed = HashSet::new();
+    for (theirs, ours) in &plan.push {
+        match backend.update_event(theirs, event_config(ours)).await {
+            Ok(()) => {
+                pushed.extend(ours.uid.clone());
+            }
+            Err(e) => {
+                warn!("Failed to write '{}' to Calendar.app: {}", ours.title, e);
+                println!("Failed to write '{}' to Calendar.app: {}", ours.title, e);
+            }
+        }
+    }
+
+    let items = manager.update(|items: &mut Vec<CalendarItem>| {
+        crate::watch::apply_changes(items, &plan.pull);
+        items.clone()
+    })?;
+
+    // Remember what both sides now agree on; conflicts keep their old base
+    for change in &plan.pull {
+        if let EventChange::Removed(item) = change {
+            base.retain(|uid, _| item.uid.as_ref() != Some(uid));
+        }
+    }
+    for item in &items {
+        let Some(uid) = &item.uid else { continue };
+        let ours = fingerprint(item);
+        let agreed = pushed.contains(uid)
+            || remote
+                .iter()
+                .any(|event| event.uid.as_ref() == Some(uid) && fingerprint(event) == ours);
+        if agreed {
+            base.insert(uid.clone(), ours);
+        }
+    }
+    let synced_at = Local::now().to_rfc3339();
+    let mut entries: Vec<SyncBaseEntry> = base
+        .into_iter()
+        .map(|(uid, fingerprint)| SyncBaseEntry { uid, fingerprint, synced_at: synced_at.clone() })
+        .collect();
+    entries.sort_by(|a, b| a.uid.cmp(&b.uid));
+    manager.save(&entries)?;
+
+    info!(
+        "Synced {} event(s): {} from Calendar.app, {} to Calendar.app, {} conflict(s)",
+        plan.checked,
+        plan.pull.len(),
+        pushed.len(),
+        plan.conflicts.len()
+    );
+    Ok(plan)
+}
+
+/// One line per change in `plan`
+pub fn format_sync_plan(plan: &SyncPlan) -> String {
+    let mut lines: Vec<String> =
+        plan.pull.iter().map(|change| format!("Calendar.app  {}", change)).collect();
+    lines.extend(plan.push.iter().map(|(theirs, ours)| {
+        let change = EventChange::Changed { before: theirs.clone(), after: ours.clone() };
+        format!("DuckTape      {}", change)
+    }));
+    lines.extend(plan.conflicts.iter().map(|conflict| format!("Conflict      {}", conflict)));
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
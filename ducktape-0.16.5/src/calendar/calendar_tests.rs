@@ -4,5 +4,962 @@
 
 #[cfg(test)]
 mod tests {
-    // ...tests moved from calendar.rs...
+    use crate::calendar::{BulkEvent, format_bulk_changes, parse_bulk_events, plan_bulk_changes};
+    use crate::calendar::{CalendarView, ViewAction, ViewMode, occurs_on};
+    use crate::calendar::{
+        ContactCandidate, ContactStrategy, CsvField, ImportSummary, OnDuplicate, RsvpStatus,
+        find_duplicate, format_agenda, format_attendees, is_ambiguous, merge_adopted_events,
+        nickname_variants, parse_adopted_events, parse_agenda_lines, parse_attendee_output,
+        parse_column_map, parse_csv_events, parse_ical_recurrence, parse_ics_events, pick_event,
+        score_contact_match, select_calendar,
+    };
+    use crate::calendar::{EventAvailability, availability_script, format_calendar_item};
+    use crate::calendar::{
+        calendar_exists, find_account_calendar, parse_calendar_accounts, split_account,
+    };
+    use crate::calendar::{feed_events, find_feed, item_uid, render_feed};
+    use crate::config::FeedConfig;
+    use crate::state::CalendarItem;
+    use crate::state::{ItemLink, LinkRelation, LinkTarget};
+    use crate::theme::Colors;
+    use crate::todo::TodoItem;
+
+    #[test]
+    fn test_exact_contact_match_scores_highest() {
+        assert_eq!(score_contact_match("Jane Doe", "Jane Doe"), 100);
+        assert_eq!(score_contact_match("jane doe", "Jane Doe"), 100);
+        assert!(score_contact_match("Jane", "Jane Doe") < 100);
+    }
+
+    #[test]
+    fn test_nickname_contact_match() {
+        assert!(score_contact_match("Bob Smith", "Robert Smith") >= 85);
+        assert!(score_contact_match("Liz", "Elizabeth Taylor") >= 85);
+        assert!(nickname_variants("bob").contains(&"robert".to_string()));
+    }
+
+    #[test]
+    fn test_fuzzy_contact_match() {
+        // Prefix and small typos still match
+        assert!(score_contact_match("Jon", "Jonathan Reed") >= 50);
+        assert!(score_contact_match("Shaun Stewart", "Shaun Stuart") >= 50);
+        // Every typed word has to match something
+        assert_eq!(score_contact_match("Jane Smith", "Jane Doe"), 0);
+        assert_eq!(score_contact_match("", "Jane Doe"), 0);
+    }
+
+    #[test]
+    fn test_select_calendar_order() {
+        let available = vec!["Home".to_string(), "Work".to_string()];
+        // An explicit calendar wins, spelled as Calendar.app spells it
+        assert_eq!(
+            select_calendar(Some("work"), Some("Home"), &available).as_deref(),
+            Some("Work")
+        );
+        // A missing explicit calendar falls back to the configured default
+        assert_eq!(select_calendar(Some("Gym"), Some("Home"), &available).as_deref(), Some("Home"));
+        // Without either, the caller asks Calendar.app for its default
+        assert_eq!(select_calendar(None, Some("Calendar"), &available), None);
+        assert_eq!(select_calendar(None, None, &available), None);
+    }
+
+    #[test]
+    fn test_select_calendar_with_account() {
+        let available = vec!["Home".to_string(), "Work".to_string(), "Trip (2025)".to_string()];
+        assert_eq!(
+            select_calendar(Some("work (iCloud)"), None, &available).as_deref(),
+            Some("Work (iCloud)")
+        );
+        // A calendar whose own name ends in parentheses is matched as is
+        assert_eq!(
+            select_calendar(Some("Trip (2025)"), None, &available).as_deref(),
+            Some("Trip (2025)")
+        );
+        assert_eq!(select_calendar(Some("Gym (iCloud)"), None, &available), None);
+        assert!(calendar_exists(&available, "Work (Google)"));
+        assert!(!calendar_exists(&available, "Gym"));
+    }
+
+    #[test]
+    fn test_split_account() {
+        assert_eq!(split_account("Work (iCloud)"), ("Work", Some("iCloud")));
+        assert_eq!(split_account(" Work (me@example.com) "), ("Work", Some("me@example.com")));
+        assert_eq!(split_account("Work"), ("Work", None));
+        assert_eq!(split_account("(iCloud)"), ("(iCloud)", None));
+    }
+
+    #[test]
+    fn test_parse_calendar_accounts() {
+        let output = "Work\tiCloud\tA-1\nWork\tGoogle\tB-2\nbroken line\n";
+        let calendars = parse_calendar_accounts(output);
+        assert_eq!(calendars.len(), 2);
+        assert_eq!(calendars[1].label(), "Work (Google)");
+        let found = find_account_calendar(&calendars, "work", "google").unwrap();
+        assert_eq!(found.identifier, "B-2");
+        assert!(find_account_calendar(&calendars, "Work", "Exchange").is_none());
+    }
+
+    #[test]
+    fn test_is_ambiguous() {
+        let candidate = |name: &str, score| ContactCandidate {
+            name: name.to_string(),
+            emails: vec![format!("{}@example.com", name.to_lowercase())],
+            score,
+        };
+        assert!(!is_ambiguous(&[]));
+        assert!(!is_ambiguous(&[candidate("Sam", 70)]));
+        // An exact match wins over partial ones
+        assert!(!is_ambiguous(&[candidate("Sam", 100), candidate("Samuel", 85)]));
+        assert!(is_ambiguous(&[candidate("Sam Lee", 85), candidate("Sam Ortiz", 85)]));
+        assert!(is_ambiguous(&[candidate("Sam", 100), candidate("SAM", 100)]));
+    }
+
+    #[test]
+    fn test_contact_strategy_from_str() {
+        assert_eq!(ContactStrategy::from_str("first").unwrap(), ContactStrategy::First);
+        assert_eq!(ContactStrategy::from_str("ALL").unwrap(), ContactStrategy::All);
+        assert_eq!(ContactStrategy::from_str("Ask").unwrap(), ContactStrategy::Ask);
+        assert!(ContactStrategy::from_str("random").is_err());
+        assert_eq!(ContactStrategy::for_terminal(true), ContactStrategy::Ask);
+        assert_eq!(ContactStrategy::for_terminal(false), ContactStrategy::First);
+    }
+
+    #[test]
+    fn test_parse_adopted_events() {
+        let output = "ABC-123\tStandup\t2025-01-06 09:00\t2025-01-06 09:15\tfalse\t\tFREQ=DAILY;INTERVAL=1\n\
+                      DEF-456\tOffsite\t2025-02-01 00:00\t2025-02-02 00:00\ttrue\tWellington\t\n\
+                      garbage line\n\
+                      GHI-789\tTab\tin title\t2025-03-01 10:00\t2025-03-01 11:00\tfalse\t\t\n";
+        // A row with a column too many is dropped rather than read shifted
+        let events = parse_adopted_events(output, "Work");
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].uid.as_deref(), Some("ABC-123"));
+        assert_eq!(events[0].date, "2025-01-06");
+        assert_eq!(events[0].time, "09:00");
+        assert_eq!(events[0].end_time.as_deref(), Some("09:15"));
+        assert_eq!(events[0].recurrence.as_deref(), Some("FREQ=DAILY;INTERVAL=1"));
+        assert_eq!(events[0].location, None);
+        assert!(events[1].all_day);
+        assert_eq!(events[1].location.as_deref(), Some("Wellington"));
+        assert_eq!(events[1].calendars, vec!["Work".to_string()]);
+    }
+
+    #[test]
+    fn test_merge_adopted_events() {
+        let scanned = parse_adopted_events(
+            "A\tStandup\t2025-01-06 09:00\t2025-01-06 09:15\tfalse\t\t\n\
+             B\tReview\t2025-01-07 13:00\t2025-01-07 14:00\tfalse\t\t\n\
+             C\tLunch\t2025-01-08 12:00\t2025-01-08 13:00\tfalse\t\t\n",
+            "Work",
+        );
+        // "Standup" is already tracked by UID, "Review" was created by DuckTape without one
+        let mut items = vec![scanned[0].clone(), scanned[1].clone()];
+        items[1].uid = None;
+
+        let summary = merge_adopted_events(&mut items, scanned);
+        assert_eq!(summary.skipped, 1);
+        assert_eq!(summary.updated, 1);
+        assert_eq!(summary.adopted, 1);
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[1].uid.as_deref(), Some("B"));
+    }
+
+    #[test]
+    fn test_parse_agenda_lines() {
+        let output = "Board meeting\t14:00\t15:00\tWork\tfalse\n\
+                      Standup\t09:00\t09:15\tWork\tfalse\n\
+                      Holiday\t00:00\t00:00\tHome\ttrue\n";
+        let events = parse_agenda_lines(output);
+        let titles: Vec<&str> = events.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, vec!["Holiday", "Standup", "Board meeting"]);
+        assert!(events[0].all_day);
+        assert_eq!(events[2].end, "15:00");
+    }
+
+    #[test]
+    fn test_format_agenda_with_prep_tasks() {
+        let events = parse_agenda_lines("Board meeting\t14:00\t15:00\tWork\tfalse\n");
+        let links = vec![
+            ItemLink {
+                source: LinkTarget::parse("todo:Prepare deck").unwrap(),
+                target: LinkTarget::parse("event:Board meeting").unwrap(),
+                relation: LinkRelation::RelatesTo,
+                created_at: String::new(),
+            },
+            ItemLink {
+                source: LinkTarget::parse("todo:Print handouts").unwrap(),
+                target: LinkTarget::parse("event:Board meeting").unwrap(),
+                relation: LinkRelation::RelatesTo,
+                created_at: String::new(),
+            },
+        ];
+        let todos = vec![TodoItem {
+            title: "Prepare deck".to_string(),
+            notes: None,
+            lists: vec!["Work".to_string()],
+            reminder_time: None,
+            completed: true,
+        }];
+
+        let agenda = format_agenda("2025-03-14", &events, &links, &todos, &Colors::plain());
+        assert!(agenda.contains("14:00-15:00 Board meeting [Work]"));
+        assert!(agenda.contains("☑ Prepare deck"));
+        assert!(agenda.contains("☐ Print handouts"));
+        let empty = format_agenda("2025-03-15", &[], &links, &todos, &Colors::plain());
+        assert!(empty.contains("No events"));
+    }
+
+    #[test]
+    fn test_agenda_shows_event_url() {
+        let events = parse_agenda_lines(
+            "Launch\t10:00\t11:00\tWork\tfalse\thttps://example.com/doc\nLunch\t12:00\t13:00\tHome\tfalse\t\n",
+        );
+        assert_eq!(events[0].url.as_deref(), Some("https://example.com/doc"));
+        assert_eq!(events[1].url, None);
+        let agenda = format_agenda("2025-03-14", &events, &[], &[], &Colors::plain());
+        assert!(agenda.contains("Launch [Work]\n              https://example.com/doc\n"));
+    }
+
+    #[test]
+    fn test_event_availability() {
+        assert_eq!(EventAvailability::from_str("Free").unwrap(), EventAvailability::Free);
+        assert_eq!(EventAvailability::from_str("ooo").unwrap(), EventAvailability::Unavailable);
+        assert_eq!(EventAvailability::Tentative.to_eventkit(), 2);
+        assert!(EventAvailability::from_str("away").is_err());
+
+        let script = availability_script("ABC-123\"); evil", EventAvailability::Free).unwrap();
+        assert!(script.contains("calendarItemsWithExternalIdentifier('ABC-123evil')"));
+        assert!(script.contains("item.availability = 1;"));
+        assert!(availability_script("  ", EventAvailability::Busy).is_err());
+    }
+
+    #[test]
+    fn test_format_calendar_item() {
+        let item = CalendarItem {
+            title: "Launch review".to_string(),
+            date: "2025-04-16".to_string(),
+            time: "10:00".to_string(),
+            calendars: vec!["Work".to_string()],
+            end_time: Some("11:00".to_string()),
+            url: Some("https://example.com/doc".to_string()),
+            availability: Some("tentative".to_string()),
+            ..Default::default()
+        };
+        let details = format_calendar_item(&item);
+        assert!(details.starts_with("Launch review\n  When:      2025-04-16 10:00-11:00\n"));
+        assert!(details.contains("  URL:       https://example.com/doc\n"));
+        assert!(details.contains("  Show as:   tentative\n"));
+        assert!(!details.contains("Where:"));
+    }
+
+    const ATTENDEE_OUTPUT: &str = "EVENT\tDesign Review\tWork\t2025-03-10 13:00\t2025-03-10 14:00\tfalse\t\tA1\n\
+        ATTENDEE\tSam Lee\tsam@example.com\taccepted\n\
+        ATTENDEE\t\tmailto:kim@example.com\tdeclined\n\
+        ATTENDEE\tali@example.com\tali@example.com\tunknown\n\
+        EVENT\tDesign Review\tWork\t2025-03-20 13:00\t2025-03-20 14:00\tfalse\tRoom 4\tA2\n\
+        ATTENDEE\tSam Lee\tsam@example.com\ttentative\n";
+
+    #[test]
+    fn test_parse_attendee_output() {
+        let events = parse_attendee_output(ATTENDEE_OUTPUT);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].uid, "A1");
+        assert_eq!(events[0].location, None);
+        assert_eq!(events[1].location.as_deref(), Some("Room 4"));
+
+        let attendees = &events[0].attendees;
+        assert_eq!(attendees.len(), 3);
+        assert_eq!(attendees[0].name.as_deref(), Some("Sam Lee"));
+        assert_eq!(attendees[1].email, "kim@example.com");
+        assert_eq!(attendees[1].status, RsvpStatus::Declined);
+        assert_eq!(attendees[2].name, None);
+        assert_eq!(attendees[2].status, RsvpStatus::Pending);
+        assert_eq!(events[0].pending().len(), 1);
+
+        let config = events[1].to_event_config();
+        assert_eq!(config.start_date, "2025-03-20");
+        assert_eq!(config.end_time.as_deref(), Some("14:00"));
+        assert_eq!(config.end_date, None);
+        assert_eq!(config.emails, vec!["sam@example.com"]);
+    }
+
+    #[test]
+    fn test_pick_event_prefers_next_occurrence() {
+        let at = |s: &str| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        let events = parse_attendee_output(ATTENDEE_OUTPUT);
+        assert_eq!(pick_event(events.clone(), at("2025-03-15 09:00")).unwrap().uid, "A2");
+        assert_eq!(pick_event(events.clone(), at("2025-03-01 09:00")).unwrap().uid, "A1");
+        assert_eq!(pick_event(events, at("2025-04-01 09:00")).unwrap().uid, "A2");
+        assert!(pick_event(Vec::new(), at("2025-04-01 09:00")).is_none());
+    }
+
+    #[test]
+    fn test_format_attendees_counts_responses() {
+        let events = parse_attendee_output(ATTENDEE_OUTPUT);
+        let text = format_attendees(&events[0]);
+        assert!(text.starts_with("Design Review [Work] 2025-03-10 13:00-14:00\n"));
+        assert!(text.contains("accepted     Sam Lee <sam@example.com>"));
+        assert!(text.contains("no response  ali@example.com"));
+        assert!(text.ends_with("  1 accepted, 1 declined, 1 no response\n"));
+    }
+
+    #[test]
+    fn test_parse_csv_events() -> anyhow::Result<()> {
+        let csv = "Subject,Start Date,Start Time,End Date,End Time,All Day Event,Location\n\
+                   Standup,04/15/2025,9:00 AM,04/15/2025,9:15 AM,False,Room 1\n\
+                   Offsite,2025-04-18,,2025-04-19,,True,\n\
+                   Broken,someday,10:00,,,,\n";
+        let events = parse_csv_events(csv, &[])?;
+        assert_eq!(events.len(), 3);
+
+        let standup = &events[0].as_ref().unwrap().config;
+        assert_eq!(standup.start_date, "2025-04-15");
+        assert_eq!(standup.start_time, "09:00");
+        assert_eq!(standup.end_time.as_deref(), Some("09:15"));
+        assert_eq!(standup.end_date, None);
+        assert_eq!(standup.location.as_deref(), Some("Room 1"));
+
+        let offsite = &events[1].as_ref().unwrap().config;
+        assert!(offsite.all_day);
+        assert_eq!(offsite.end_date.as_deref(), Some("2025-04-19"));
+
+        let error = events[2].as_ref().unwrap_err().to_string();
+        assert!(error.starts_with("Line 4: Invalid date"), "{}", error);
+        assert!(parse_csv_events("who,what\nSam,x\n", &[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_column_map_and_header_guessing() -> anyhow::Result<()> {
+        let map = parse_column_map("# Outlook (German)\nBetreff=title\nDatum=date, Beginn=start")?;
+        assert_eq!(
+            map,
+            vec![
+                ("Betreff".to_string(), CsvField::Title),
+                ("Datum".to_string(), CsvField::Date),
+                ("Beginn".to_string(), CsvField::Start),
+            ]
+        );
+        assert!(parse_column_map("Betreff").is_err());
+        assert!(parse_column_map("Betreff=topic").is_err());
+
+        let german = "Betreff,Datum,Beginn\nStandup,15.04.2025,09:00\n";
+        assert!(parse_csv_events(german, &[]).is_err());
+        let events = parse_csv_events(german, &map)?;
+        let standup = &events[0].as_ref().unwrap().config;
+        assert_eq!(
+            (standup.start_date.as_str(), standup.start_time.as_str()),
+            ("2025-04-15", "09:00")
+        );
+        assert!(parse_csv_events(german, &[("Subject".to_string(), CsvField::Title)]).is_err());
+
+        // Headers are guessed from their words, and a start column may hold the date too
+        let csv = "Meeting Subject,Begins,EndsAt,Where\n\
+                   Planning,2025-04-15 09:00,2025-04-15 10:30,Room 1\n";
+        let events = parse_csv_events(csv, &[])?;
+        let planning = &events[0].as_ref().unwrap().config;
+        assert_eq!(planning.title, "Planning");
+        assert_eq!(planning.start_date, "2025-04-15");
+        assert_eq!(planning.start_time, "09:00");
+        assert_eq!(planning.end_time.as_deref(), Some("10:30"));
+        assert_eq!(planning.end_date, None);
+        assert_eq!(planning.location.as_deref(), Some("Room 1"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_ics_events() -> anyhow::Result<()> {
+        let ics = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+                   BEGIN:VEVENT\r\nUID:abc@example.com\r\nSUMMARY:Sync\\, weekly\r\n\
+                   DTSTART;TZID=Europe/London:20250415T100000\r\n\
+                   DTEND;TZID=Europe/London:20250415T103000\r\n\
+                   LOCATION:Room 2\r\nDESCRIPTION:Line one\\nLine two\r\nEND:VEVENT\r\n\
+                   BEGIN:VEVENT\r\nSUMMARY:Holiday\r\nDTSTART;VALUE=DATE:20250418\r\n\
+                   DTEND;VALUE=DATE:20250422\r\nEND:VEVENT\r\n\
+                   BEGIN:VEVENT\r\nUID:no-summary\r\nDTSTART:20250415T100000\r\nEND:VEVENT\r\n\
+                   END:VCALENDAR\r\n";
+        let events = parse_ics_events(ics)?;
+        assert_eq!(events.len(), 3);
+
+        let sync = events[0].as_ref().unwrap();
+        assert_eq!(sync.uid.as_deref(), Some("abc@example.com"));
+        assert_eq!(sync.config.title, "Sync, weekly");
+        assert_eq!(sync.config.start_time, "10:00");
+        assert_eq!(sync.config.end_time.as_deref(), Some("10:30"));
+        assert_eq!(sync.config.timezone.as_deref(), Some("Europe/London"));
+        assert_eq!(sync.config.description.as_deref(), Some("Line one\nLine two"));
+
+        let holiday = &events[1].as_ref().unwrap().config;
+        assert!(holiday.all_day);
+        assert_eq!(holiday.start_date, "2025-04-18");
+        assert_eq!(holiday.end_date.as_deref(), Some("2025-04-21"));
+        assert!(events[2].is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_duplicate_by_uid_or_title_and_start() -> anyhow::Result<()> {
+        let item = |title: &str, date: &str, time: &str, uid: Option<&str>| CalendarItem {
+            title: title.to_string(),
+            date: date.to_string(),
+            time: time.to_string(),
+            calendars: vec!["Work".to_string()],
+            uid: uid.map(str::to_string),
+            ..Default::default()
+        };
+        let known = vec![
+            item("Standup", "2025-04-15", "09:00", None),
+            item("Renamed sync", "2025-04-01", "10:00", Some("abc@example.com")),
+        ];
+        let csv = "title,date,start,uid\n\
+                   standup,2025-04-15,09:00,\n\
+                   Sync,2025-04-15,10:00,abc@example.com\n\
+                   Standup,2025-04-16,09:00,\n";
+        let events: Vec<_> = parse_csv_events(csv, &[])?.into_iter().map(Result::unwrap).collect();
+        assert_eq!(find_duplicate(&events[0], &known), Some(0));
+        assert_eq!(find_duplicate(&events[1], &known), Some(1));
+        assert_eq!(find_duplicate(&events[2], &known), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_options_and_summary() {
+        assert_eq!("Update".parse::<OnDuplicate>().unwrap(), OnDuplicate::Update);
+        assert_eq!(OnDuplicate::default(), OnDuplicate::Skip);
+        assert!("merge".parse::<OnDuplicate>().is_err());
+
+        let summary = ImportSummary {
+            created: 2,
+            skipped: 1,
+            failed: 1,
+            errors: vec!["Line 3: Invalid date: someday".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            summary.report(),
+            "Import finished: 2 created, 0 updated, 1 skipped, 1 failed\n  failed: Line 3: Invalid date: someday\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_ical_recurrence() -> anyhow::Result<()> {
+        // Last weekday of the month
+        let last = parse_ical_recurrence("FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1", None)?;
+        assert_eq!(last.days_of_week, vec![1, 2, 3, 4, 5]);
+        assert_eq!(last.set_positions, vec![-1]);
+        assert_eq!(last.by_rules(), ";BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1");
+
+        let days = parse_ical_recurrence("FREQ=MONTHLY;INTERVAL=2;BYMONTHDAY=15,-1;COUNT=6", None)?;
+        assert_eq!(days.interval, 2);
+        assert_eq!(days.count, Some(6));
+        assert_eq!(days.month_days, vec![15, -1]);
+
+        let second =
+            parse_ical_recurrence("FREQ=YEARLY;BYMONTH=11;BYDAY=+2TU;UNTIL=20301231", None)?;
+        assert_eq!(second.ordinal_days, vec![(2, 2)]);
+        assert_eq!(second.months, vec![11]);
+        assert_eq!(second.end_date.as_deref(), Some("2030-12-31"));
+        assert_eq!(second.by_rules(), ";BYDAY=2TU;BYMONTH=11");
+
+        assert!(parse_ical_recurrence("FREQ=HOURLY", None).is_err());
+        assert!(parse_ical_recurrence("FREQ=WEEKLY;BYWEEKNO=20", None).is_err());
+        assert!(parse_ical_recurrence("INTERVAL=2", None).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_ics_exceptions_and_extra_dates() -> anyhow::Result<()> {
+        let ics = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+                   BEGIN:VEVENT\r\nUID:standup\r\nSUMMARY:Standup\r\n\
+                   DTSTART;TZID=Europe/London:20250407T090000\r\n\
+                   RRULE:FREQ=WEEKLY;BYDAY=MO,WE;UNTIL=20250630\r\n\
+                   EXDATE;TZID=Europe/London:20250409T090000,20250414T090000\r\n\
+                   EXDATE;VALUE=DATE:20250421\r\n\
+                   RDATE;VALUE=DATE:20250412\r\nEND:VEVENT\r\n\
+                   BEGIN:VEVENT\r\nUID:standup\r\nSUMMARY:Standup (moved)\r\n\
+                   RECURRENCE-ID;TZID=Europe/London:20250416T090000\r\n\
+                   DTSTART;TZID=Europe/London:20250417T090000\r\nEND:VEVENT\r\n\
+                   BEGIN:VEVENT\r\nUID:talks\r\nSUMMARY:Talks\r\n\
+                   DTSTART;VALUE=DATE:20250501\r\nRDATE;VALUE=DATE:20250508,20250515\r\n\
+                   END:VEVENT\r\nEND:VCALENDAR\r\n";
+        let events = parse_ics_events(ics)?;
+        assert_eq!(events.len(), 3);
+
+        let standup = events[0].as_ref().unwrap();
+        let recurrence = standup.config.recurrence.as_ref().unwrap();
+        assert_eq!(recurrence.days_of_week, vec![1, 3]);
+        assert_eq!(recurrence.end_date.as_deref(), Some("2025-06-30"));
+        assert_eq!(
+            recurrence.exception_dates,
+            vec!["2025-04-09", "2025-04-14", "2025-04-21", "2025-04-16"]
+        );
+        assert_eq!(recurrence.extra_dates, vec!["2025-04-12"]);
+
+        // The changed occurrence is an event of its own
+        let moved = events[1].as_ref().unwrap();
+        assert_eq!(moved.uid.as_deref(), Some("standup/2025-04-16"));
+        assert_eq!(moved.config.start_date, "2025-04-17");
+        assert!(moved.config.recurrence.is_none());
+
+        let talks = events[2].as_ref().unwrap();
+        let recurrence = talks.config.recurrence.as_ref().unwrap();
+        assert_eq!(recurrence.count, Some(1));
+        assert_eq!(recurrence.extra_dates, vec!["2025-05-08", "2025-05-15"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_feed_token_lookup() {
+        let feed = |name: &str, token: &str| FeedConfig {
+            name: name.to_string(),
+            token: token.to_string(),
+            calendars: Vec::new(),
+        };
+        let feeds = vec![feed("Family", "abc123"), feed("Broken", "")];
+        assert_eq!(find_feed(&feeds, "abc123").map(|f| f.name.as_str()), Some("Family"));
+        assert!(find_feed(&feeds, "abc124").is_none());
+        assert!(find_feed(&feeds, "abc").is_none());
+        assert!(find_feed(&feeds, "").is_none());
+    }
+
+    #[test]
+    fn test_feed_events_and_rendering() -> anyhow::Result<()> {
+        let item = |title: &str, calendar: &str, recurrence: Option<&str>| CalendarItem {
+            title: title.to_string(),
+            date: "2025-04-15".to_string(),
+            time: "10:00".to_string(),
+            calendars: vec![calendar.to_string()],
+            location: Some("Room 1".to_string()),
+            email: Some("jane@example.com, sam@example.com".to_string()),
+            end_time: Some("11:00".to_string()),
+            recurrence: recurrence.map(str::to_string),
+            ..Default::default()
+        };
+        let items = vec![
+            item("Standup", "Work", Some("FREQ=WEEKLY;INTERVAL=1;BYDAY=TU")),
+            item("Piano", "Home", None),
+        ];
+        // The local calendar holds a copy of Piano and an event of its own
+        let mut local: Vec<_> = parse_ics_events(
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:piano@ducktape\r\nSUMMARY:Piano\r\n\
+             DTSTART:20250415T100000\r\nEND:VEVENT\r\nBEGIN:VEVENT\r\nUID:dentist@ducktape\r\n\
+             SUMMARY:Dentist\r\nDTSTART:20250418T090000\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+        )?
+        .into_iter()
+        .collect::<anyhow::Result<_>>()?;
+        for event in &mut local {
+            event.config.calendars = vec!["Home".to_string()];
+        }
+
+        assert_eq!(item_uid(&items[0]), item_uid(&items[0].clone()));
+        assert_ne!(item_uid(&items[0]), item_uid(&items[1]));
+
+        let events = feed_events(&items, local.clone(), &[]);
+        let titles: Vec<&str> = events.iter().map(|(_, e)| e.title.as_str()).collect();
+        assert_eq!(titles, vec!["Standup", "Piano", "Dentist"]);
+        assert_eq!(events[2].0, "dentist@ducktape");
+        assert_eq!(events[0].1.emails, vec!["jane@example.com", "sam@example.com"]);
+
+        let home = feed_events(&items, local, &["home".to_string()]);
+        let titles: Vec<&str> = home.iter().map(|(_, e)| e.title.as_str()).collect();
+        assert_eq!(titles, vec!["Piano", "Dentist"]);
+
+        let stamp = chrono::TimeZone::with_ymd_and_hms(&chrono::Utc, 2025, 4, 1, 8, 0, 0).unwrap();
+        let ics = render_feed("Family", &events, stamp);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.contains("METHOD:PUBLISH\r\nX-WR-CALNAME:Family\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 3);
+        assert!(ics.contains("RRULE:FREQ=WEEKLY;INTERVAL=1;BYDAY=TU\r\n"));
+        assert!(ics.contains("DTEND:20250415T110000\r\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_bulk_events() {
+        let output = "u1\tStandup\t2025-03-17 09:00\t2025-03-17 09:15\tfalse\tWork\t\n\
+                      u2\tWeekly sync\t2025-03-18 10:00\t2025-03-18 11:00\tfalse\tWork\tFREQ=WEEKLY\n\
+                      broken line\n";
+        let events = parse_bulk_events(output);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].uid, "u1");
+        assert_eq!(events[0].calendar, "Work");
+        assert!(!events[0].recurring);
+        assert!(events[1].recurring);
+    }
+
+    #[test]
+    fn test_plan_bulk_changes() {
+        let at = |d: u32, h: u32| {
+            chrono::NaiveDate::from_ymd_opt(2025, 3, d)
+                .unwrap()
+                .and_hms_opt(h, 0, 0)
+                .unwrap()
+        };
+        let event = |uid: &str, title: &str, day: u32, recurring: bool| BulkEvent {
+            uid: uid.to_string(),
+            title: title.to_string(),
+            calendar: "Work".to_string(),
+            start: at(day, 9),
+            end: at(day, 10),
+            all_day: false,
+            recurring,
+        };
+        let events = vec![
+            event("1", "Daily Standup", 14, false),
+            event("2", "Lunch", 14, false),
+            event("3", "standup", 14, true),
+        ];
+
+        let changes = plan_bulk_changes(&events, Some("STANDUP"), None);
+        assert_eq!(changes.len(), 1);
+        assert!(changes[0].is_delete());
+        assert_eq!(format_bulk_changes(&changes), "  • Daily Standup [Work]: Fri 2025-03-14 09:00");
+
+        let changes = plan_bulk_changes(&events, None, Some(chrono::Duration::days(3)));
+        assert_eq!(changes.len(), 2);
+        assert!(
+            format_bulk_changes(&changes)
+                .contains("Lunch [Work]: Fri 2025-03-14 09:00 → Mon 2025-03-17 09:00")
+        );
+    }
+
+    fn view_item(title: &str, date: &str, time: &str, recurrence: Option<&str>) -> CalendarItem {
+        CalendarItem {
+            title: title.to_string(),
+            date: date.to_string(),
+            time: time.to_string(),
+            calendars: vec!["Work".to_string()],
+            uid: Some(format!("{}@example.com", title)),
+            recurrence: recurrence.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_occurs_on_repeats() {
+        let day = |d: &str| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        // Tuesday
+        let standup = view_item("Standup", "2025-03-11", "09:00", Some("FREQ=WEEKLY;INTERVAL=2"));
+        assert!(occurs_on(&standup, day("2025-03-11")));
+        assert!(!occurs_on(&standup, day("2025-03-18")));
+        assert!(occurs_on(&standup, day("2025-03-25")));
+        assert!(!occurs_on(&standup, day("2025-03-04")));
+
+        let gym = view_item("Gym", "2025-03-10", "07:00", Some("FREQ=WEEKLY;BYDAY=MO,WE;COUNT=3"));
+        assert!(occurs_on(&gym, day("2025-03-12")));
+        assert!(occurs_on(&gym, day("2025-03-17")));
+        assert!(!occurs_on(&gym, day("2025-03-19")));
+
+        let rent = view_item("Rent", "2025-01-31", "09:00", Some("FREQ=MONTHLY;UNTIL=20250501"));
+        assert!(occurs_on(&rent, day("2025-03-31")));
+        assert!(!occurs_on(&rent, day("2025-02-28")));
+        assert!(!occurs_on(&rent, day("2025-05-31")));
+        assert!(!occurs_on(&view_item("Once", "2025-03-11", "09:00", None), day("2025-03-12")));
+    }
+
+    #[test]
+    fn test_calendar_view_navigation() {
+        use ratatui::crossterm::event::KeyCode;
+        let day = |d: &str| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        let events = vec![
+            view_item("Lunch", "2025-03-14", "12:30", None),
+            view_item("Standup", "2025-03-14", "09:00", None),
+        ];
+        // Friday
+        let mut view =
+            CalendarView::new(ViewMode::Week, day("2025-03-14"), day("2025-03-14"), events);
+        assert_eq!(view.visible_days().first(), Some(&day("2025-03-10")));
+        assert_eq!(view.visible_days().len(), 7);
+
+        let titles: Vec<&str> =
+            view.events_on(view.cursor).iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, vec!["Standup", "Lunch"]);
+
+        view.handle_key(KeyCode::Right);
+        view.handle_key(KeyCode::Down);
+        assert_eq!(view.cursor, day("2025-03-22"));
+        view.handle_key(KeyCode::Char('t'));
+        view.handle_key(KeyCode::Char('m'));
+        assert_eq!(view.visible_days().first(), Some(&day("2025-02-24")));
+        assert_eq!(view.visible_days().len(), 42);
+        view.handle_key(KeyCode::Char(']'));
+        assert_eq!(view.cursor, day("2025-04-14"));
+        view.handle_key(KeyCode::Char('['));
+
+        // Drill into the day, select the second event and delete it after confirming
+        view.handle_key(KeyCode::Enter);
+        view.handle_key(KeyCode::Down);
+        assert_eq!(view.selected, 1);
+        assert_eq!(view.handle_key(KeyCode::Char('d')), None);
+        match view.handle_key(KeyCode::Char('y')) {
+            Some(ViewAction::Delete(item)) => assert_eq!(item.title, "Lunch"),
+            other => panic!("expected a delete, got {:?}", other),
+        }
+
+        // Type a new event
+        view.handle_key(KeyCode::Char('n'));
+        for c in "Gym 7am".chars() {
+            view.handle_key(KeyCode::Char(c));
+        }
+        assert_eq!(
+            view.handle_key(KeyCode::Enter),
+            Some(ViewAction::Create { date: day("2025-03-14"), text: "Gym 7am".to_string() })
+        );
+        assert_eq!(view.handle_key(KeyCode::Esc), None);
+        assert_eq!(view.handle_key(KeyCode::Char('q')), Some(ViewAction::Quit));
+    }
+
+    #[test]
+    fn test_calendar_cache_expires() {
+        use crate::calendar::{CALENDAR_CACHE_TTL, cached_calendars, store_calendars};
+        use std::time::{Duration, Instant};
+
+        let fetched = Instant::now();
+        let names = vec!["Work".to_string(), "Home".to_string()];
+        store_calendars("cache-test", &names, fetched);
+        assert_eq!(cached_calendars("cache-test", fetched + Duration::from_secs(60)), Some(names));
+        assert_eq!(cached_calendars("cache-test", fetched + CALENDAR_CACHE_TTL), None);
+        assert_eq!(cached_calendars("other-backend", fetched), None);
+    }
+
+    fn synced_event(uid: &str, title: &str, time: &str, location: &str) -> CalendarItem {
+        let line = format!(
+            "{}\t{}\t2025-03-12 {}\t2025-03-12 23:00\tfalse\t{}\t",
+            uid, title, time, location
+        );
+        parse_adopted_events(&line, "Work").remove(0)
+    }
+
+    #[test]
+    fn test_plan_sync() {
+        use crate::calendar::{fingerprint, plan_sync};
+        use crate::config::SyncConflicts;
+        use crate::watch::EventChange;
+        use std::collections::HashMap;
+        use std::slice::from_ref;
+
+        let day = |d: &str| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        let window = (day("2025-03-01"), day("2025-04-01"));
+        let synced = synced_event("A1", "Standup", "09:00", "Room 4");
+        let base = HashMap::from([("A1".to_string(), fingerprint(&synced))]);
+        let moved = synced_event("A1", "Standup", "09:30", "Room 4");
+        let renamed_room = synced_event("A1", "Standup", "09:00", "Room 7");
+        let plan = |local: &CalendarItem, remote: &CalendarItem, rule| {
+            plan_sync(from_ref(local), from_ref(remote), &base, window, rule, false)
+        };
+
+        // Changed in Calendar.app only
+        let pulled = plan(&synced, &moved, SyncConflicts::Local);
+        assert_eq!(
+            pulled.pull,
+            vec![EventChange::Changed { before: synced.clone(), after: moved.clone() }]
+        );
+        assert!(pulled.push.is_empty());
+
+        // Changed in DuckTape only
+        let pushed = plan(&moved, &synced, SyncConflicts::Calendar);
+        assert_eq!(pushed.push, vec![(synced.clone(), moved.clone())]);
+        assert!(pushed.pull.is_empty());
+
+        // Changed on both sides
+        assert_eq!(plan(&moved, &renamed_room, SyncConflicts::Calendar).pull.len(), 1);
+        assert_eq!(plan(&moved, &renamed_room, SyncConflicts::Local).push.len(), 1);
+        let skipped = plan(&moved, &renamed_room, SyncConflicts::Skip);
+        assert!(skipped.pull.is_empty() && skipped.push.is_empty());
+        assert_eq!(skipped.conflicts.len(), 1);
+
+        // In sync
+        assert!(plan(&synced, &synced, SyncConflicts::Calendar).is_empty());
+    }
+
+    #[test]
+    fn test_plan_sync_deleted_and_untracked() {
+        use crate::calendar::{fingerprint, plan_sync};
+        use crate::config::SyncConflicts;
+        use crate::watch::EventChange;
+        use std::collections::HashMap;
+        use std::slice::from_ref;
+
+        let day = |d: &str| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        let window = (day("2025-03-01"), day("2025-04-01"));
+        let synced = synced_event("A1", "Standup", "09:00", "");
+        let base = HashMap::from([("A1".to_string(), fingerprint(&synced))]);
+
+        // Deleted in Calendar.app
+        let plan = plan_sync(from_ref(&synced), &[], &base, window, SyncConflicts::Skip, false);
+        assert_eq!(plan.pull, vec![EventChange::Removed(synced.clone())]);
+
+        // ...after it changed in DuckTape
+        let mut changed = synced.clone();
+        changed.time = "10:00".to_string();
+        let plan = plan_sync(&[changed], &[], &base, window, SyncConflicts::Skip, false);
+        assert!(plan.pull.is_empty());
+        assert_eq!(plan.conflicts.len(), 1);
+
+        // A new calendar cannot be written to Calendar.app
+        let mut moved = synced.clone();
+        moved.calendars = vec!["Home".to_string()];
+        let plan =
+            plan_sync(&[moved], from_ref(&synced), &base, window, SyncConflicts::Skip, false);
+        assert!(plan.push.is_empty());
+        assert_eq!(plan.conflicts.len(), 1);
+
+        // Untracked events are only picked up with adopt or when tracked without a UID
+        let lunch = synced_event("B2", "Lunch", "12:00", "");
+        assert!(
+            plan_sync(&[], from_ref(&lunch), &base, window, SyncConflicts::Skip, false).is_empty()
+        );
+        assert_eq!(
+            plan_sync(&[], from_ref(&lunch), &base, window, SyncConflicts::Skip, true).pull,
+            vec![EventChange::Added(lunch.clone())]
+        );
+        let mut without_uid = lunch.clone();
+        without_uid.uid = None;
+        let plan = plan_sync(&[without_uid], &[lunch], &base, window, SyncConflicts::Skip, false);
+        assert_eq!(plan.pull.len(), 1);
+    }
+
+    #[test]
+    fn test_sync_fingerprint_ignores_time_of_all_day_events() {
+        use crate::calendar::fingerprint;
+
+        let mut event = synced_event("A1", "Offsite", "00:00", "");
+        event.all_day = true;
+        let mut tracked = event.clone();
+        tracked.time = String::new();
+        tracked.end_time = None;
+        assert_eq!(fingerprint(&event), fingerprint(&tracked));
+    }
+
+    #[test]
+    fn test_find_event_by_id() {
+        use crate::calendar::find_event_by_id;
+
+        let standup = synced_event("A1", "Standup", "09:00", "Room 4");
+        let mut home = synced_event("B2", "Standup", "18:00", "");
+        home.calendars = vec!["Home".to_string()];
+        let mut untracked = synced_event("C3", "Retro", "15:00", "");
+        untracked.uid = None;
+        let items = vec![standup, home, untracked];
+
+        assert_eq!(find_event_by_id(&items, "B2", None).unwrap().time, "18:00");
+        assert_eq!(
+            find_event_by_id(&items, "standup", Some("work")).unwrap().uid.as_deref(),
+            Some("A1")
+        );
+        assert_eq!(find_event_by_id(&items, "Retro", None).unwrap().uid, None);
+
+        let ambiguous = find_event_by_id(&items, "Standup", None).unwrap_err().to_string();
+        assert!(ambiguous.contains("2 tracked events are titled 'Standup'"));
+        assert!(ambiguous.contains("A1") && ambiguous.contains("B2"));
+        // Titles must match exactly
+        assert!(find_event_by_id(&items, "Stand", None).is_err());
+    }
+
+    #[test]
+    fn test_delete_event_script() {
+        use crate::calendar::delete_event_script;
+
+        let event = synced_event("A1", "Bob's \"1:1\"", "09:30", "");
+        let script = delete_event_script(&event).unwrap().to_string();
+        assert!(script.contains("whose uid is \"A1\""));
+        assert!(script.contains("whose summary is \"Bob's \\\"1:1\\\"\""));
+        assert!(script.contains("set time of oldStart to 34200"));
+        assert!(script.contains("delete item 1 of found"));
+
+        let mut no_calendar = event;
+        no_calendar.calendars.clear();
+        assert!(delete_event_script(&no_calendar).is_err());
+    }
+
+    #[test]
+    fn test_event_config_builder() {
+        use crate::calendar::{
+            EventConfig, EventConfigBuilder, RecurrenceFrequency, RecurrencePattern,
+        };
+        use chrono::{Duration, NaiveDate, NaiveTime};
+
+        let may_1 = NaiveDate::from_ymd_opt(2025, 5, 1).unwrap();
+        let time = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+
+        let config = EventConfig::builder("  Standup ")
+            .on(may_1)
+            .at(time(23, 30))
+            .lasting(Duration::minutes(45))
+            .calendar("Work")
+            .attendee("jane@example.com")
+            .recurrence(RecurrencePattern::new(RecurrenceFrequency::Weekly))
+            .build()
+            .unwrap();
+        assert_eq!(config.title, "Standup");
+        assert_eq!(
+            (config.start_date.as_str(), config.start_time.as_str()),
+            ("2025-05-01", "23:30")
+        );
+        assert_eq!(config.end_time.as_deref(), Some("00:15"));
+        assert_eq!(config.end_date.as_deref(), Some("2025-05-02"));
+        assert_eq!(config.calendars, ["Work"]);
+        assert_eq!(config.emails, ["jane@example.com"]);
+        assert!(config.recurrence.is_some());
+
+        // An hour by default, and an end before the start is the next day
+        let config = EventConfig::builder("Call").on(may_1).at(time(9, 0)).build().unwrap();
+        assert_eq!((config.end_time.as_deref(), config.end_date), (Some("10:00"), None));
+        let late = EventConfig::builder("Gig")
+            .on(may_1)
+            .at(time(22, 0))
+            .until(time(1, 0))
+            .build()
+            .unwrap();
+        assert_eq!(late.end_date.as_deref(), Some("2025-05-02"));
+
+        let all_day = EventConfig::builder("Offsite").on(may_1).all_day().build().unwrap();
+        assert!(all_day.all_day);
+        assert!(
+            EventConfig::builder("Offsite")
+                .on(may_1)
+                .at(time(9, 0))
+                .all_day()
+                .build()
+                .is_err()
+        );
+
+        let config = EventConfig::builder("Lunch")
+            .on(may_1)
+            .at(time(12, 0))
+            .maybe(Some("Cafe"), EventConfigBuilder::location)
+            .maybe(None::<&str>, EventConfigBuilder::url)
+            .build()
+            .unwrap();
+        assert_eq!((config.location.as_deref(), config.url), (Some("Cafe"), None));
+
+        let errors = [
+            EventConfig::builder("").on(may_1).at(time(9, 0)).build(),
+            EventConfig::builder("Standup").at(time(9, 0)).build(),
+            EventConfig::builder("Standup").on(may_1).build(),
+            EventConfig::builder("Standup")
+                .on(may_1)
+                .at(time(9, 0))
+                .until(time(9, 0))
+                .build(),
+            EventConfig::builder("Standup")
+                .on(may_1)
+                .at(time(9, 0))
+                .lasting(Duration::hours(30))
+                .build(),
+            EventConfig::builder("Standup")
+                .on(may_1)
+                .at(time(9, 0))
+                .attendee("jane@gmial.com")
+                .build(),
+        ];
+        let messages: Vec<String> =
+            errors.into_iter().map(|e| e.unwrap_err().to_string()).collect();
+        assert_eq!(messages[0], "Invalid title: it cannot be empty");
+        assert!(messages[1].starts_with("Invalid date"));
+        assert!(messages[2].starts_with("Invalid start time"));
+        assert!(messages[3].starts_with("Invalid end time"));
+        assert!(messages[4].starts_with("Invalid duration"));
+        assert!(messages[5].contains("Did you mean jane@gmail.com?"));
+    }
 }
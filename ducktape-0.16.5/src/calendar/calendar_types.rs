@@ -2,6 +2,9 @@
 //
 // This module contains all core types, enums, and error types used by the calendar system.
 
+use crate::config::MeetingService;
+use crate::validation::{self, ValidationError};
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
 use thiserror::Error;
 
 /// Custom error type for calendar operations
@@ -10,7 +13,7 @@ pub enum CalendarError {
     #[error("Calendar application is not running")]
     NotRunning,
 
-    #[error("Calendar '{{0}}' not found")]
+    #[error("Calendar '{0}' not found")]
     CalendarNotFound(String),
 
     #[error("Invalid date/time format: {0}")]
@@ -74,12 +77,77 @@ pub struct RecurrencePattern {
     pub count: Option<u32>,
     /// Days of the week for weekly recurrence (0=Sunday, 1=Monday, etc.)
     pub days_of_week: Vec<u8>,
+    /// Numbered days of the week, e.g. (2, 2) for the second Tuesday or (-1, 5) for the last
+    /// Friday of the month
+    pub ordinal_days: Vec<(i8, u8)>,
+    /// Days of the month (1 to 31, or -1 for the last day)
+    pub month_days: Vec<i8>,
+    /// Months of the year (1 to 12)
+    pub months: Vec<u8>,
+    /// Which of the days picked by the other rules to keep in each period, e.g. -1 for the last
+    pub set_positions: Vec<i16>,
+    /// Dates (YYYY-MM-DD) on which an occurrence is left out
+    pub exception_dates: Vec<String>,
+    /// Dates (YYYY-MM-DD) with an occurrence outside the rule
+    pub extra_dates: Vec<String>,
 }
 
 impl RecurrencePattern {
     /// Create a new simple recurrence pattern with the given frequency
     pub fn new(frequency: RecurrenceFrequency) -> Self {
-        Self { frequency, interval: 1, end_date: None, count: None, days_of_week: Vec::new() }
+        Self {
+            frequency,
+            interval: 1,
+            end_date: None,
+            count: None,
+            days_of_week: Vec::new(),
+            ordinal_days: Vec::new(),
+            month_days: Vec::new(),
+            months: Vec::new(),
+            set_positions: Vec::new(),
+            exception_dates: Vec::new(),
+            extra_dates: Vec::new(),
+        }
+    }
+
+    /// The BYDAY, BYMONTHDAY, BYMONTH and BYSETPOS parts of an RFC 5545 rule, each starting
+    /// with `;`
+    pub fn by_rules(&self) -> String {
+        const DAYS: [&str; 7] = ["SU", "MO", "TU", "WE", "TH", "FR", "SA"];
+        let join = |values: Vec<String>| values.join(",");
+        let mut rules = String::new();
+        let days: Vec<String> = self
+            .days_of_week
+            .iter()
+            .filter_map(|d| DAYS.get(*d as usize).map(|day| day.to_string()))
+            .chain(
+                self.ordinal_days
+                    .iter()
+                    .filter_map(|(n, d)| DAYS.get(*d as usize).map(|day| format!("{}{}", n, day))),
+            )
+            .collect();
+        if !days.is_empty() {
+            rules.push_str(&format!(";BYDAY={}", join(days)));
+        }
+        if !self.month_days.is_empty() {
+            rules.push_str(&format!(
+                ";BYMONTHDAY={}",
+                join(self.month_days.iter().map(i8::to_string).collect())
+            ));
+        }
+        if !self.months.is_empty() {
+            rules.push_str(&format!(
+                ";BYMONTH={}",
+                join(self.months.iter().map(u8::to_string).collect())
+            ));
+        }
+        if !self.set_positions.is_empty() {
+            rules.push_str(&format!(
+                ";BYSETPOS={}",
+                join(self.set_positions.iter().map(i16::to_string).collect())
+            ));
+        }
+        rules
     }
     /// Set the interval for recurrence
     pub fn with_interval(mut self, interval: u32) -> Self {
@@ -103,6 +171,49 @@ impl RecurrencePattern {
     }
 }
 
+/// How an event counts when others look up free/busy times
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventAvailability {
+    Busy,
+    Free,
+    Tentative,
+    Unavailable,
+}
+
+impl EventAvailability {
+    pub fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "busy" => Ok(EventAvailability::Busy),
+            "free" | "available" => Ok(EventAvailability::Free),
+            "tentative" | "maybe" => Ok(EventAvailability::Tentative),
+            "unavailable" | "out-of-office" | "ooo" => Ok(EventAvailability::Unavailable),
+            _ => Err(anyhow::anyhow!(
+                "Invalid availability: {}. Use busy, free, tentative or unavailable",
+                s
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventAvailability::Busy => "busy",
+            EventAvailability::Free => "free",
+            EventAvailability::Tentative => "tentative",
+            EventAvailability::Unavailable => "unavailable",
+        }
+    }
+
+    /// The matching `EKEventAvailability` value
+    pub fn to_eventkit(&self) -> i32 {
+        match self {
+            EventAvailability::Busy => 0,
+            EventAvailability::Free => 1,
+            EventAvailability::Tentative => 2,
+            EventAvailability::Unavailable => 3,
+        }
+    }
+}
+
 /// Configuration for a calendar event
 #[derive(Debug, Clone)]
 pub struct EventConfig {
@@ -114,6 +225,9 @@ pub struct EventConfig {
     pub calendars: Vec<String>,
     pub all_day: bool,
     pub location: Option<String>,
+    /// Link shown with the event in Calendar.app
+    pub url: Option<String>,
+    pub availability: Option<EventAvailability>,
     pub description: Option<String>,
     pub emails: Vec<String>,
     pub reminder: Option<i32>,
@@ -124,6 +238,8 @@ pub struct EventConfig {
     pub zoom_meeting_id: Option<u64>,
     pub zoom_join_url: Option<String>,
     pub zoom_password: Option<String>,
+    /// Online meeting service to create a meeting with (takes precedence over Zoom)
+    pub meeting_service: Option<MeetingService>,
 }
 
 impl EventConfig {
@@ -137,6 +253,8 @@ impl EventConfig {
             calendars: Vec::new(),
             all_day: false,
             location: None,
+            url: None,
+            availability: None,
             description: None,
             emails: Vec::new(),
             reminder: None,
@@ -146,6 +264,7 @@ impl EventConfig {
             zoom_meeting_id: None,
             zoom_join_url: None,
             zoom_password: None,
+            meeting_service: None,
         }
     }
     pub fn with_recurrence(mut self, recurrence: RecurrencePattern) -> Self {
@@ -156,4 +275,256 @@ impl EventConfig {
         self.create_zoom_meeting = enable;
         self
     }
+
+    /// An event built from chrono values and checked by `EventConfigBuilder::build`
+    pub fn builder(title: &str) -> EventConfigBuilder {
+        EventConfigBuilder::new(title)
+    }
+}
+
+/// When an event ends
+#[derive(Debug, Clone, Copy)]
+enum EventEnd {
+    Duration(Duration),
+    Time(NaiveTime),
+}
+
+/// Builds an `EventConfig`; see `EventConfig::builder`
+///
+/// Optional input can be passed on with `maybe`:
+/// `.maybe(payload.location.as_deref(), EventConfigBuilder::location)`.
+#[derive(Debug, Clone)]
+pub struct EventConfigBuilder {
+    title: String,
+    date: Option<NaiveDate>,
+    start: Option<NaiveTime>,
+    end: Option<EventEnd>,
+    all_day: bool,
+    calendars: Vec<String>,
+    location: Option<String>,
+    description: Option<String>,
+    url: Option<String>,
+    availability: Option<EventAvailability>,
+    attendees: Vec<String>,
+    reminder: Option<i32>,
+    timezone: Option<String>,
+    recurrence: Option<RecurrencePattern>,
+    zoom_meeting: bool,
+    meeting_service: Option<MeetingService>,
+}
+
+impl EventConfigBuilder {
+    fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            date: None,
+            start: None,
+            end: None,
+            all_day: false,
+            calendars: Vec::new(),
+            location: None,
+            description: None,
+            url: None,
+            availability: None,
+            attendees: Vec::new(),
+            reminder: None,
+            timezone: None,
+            recurrence: None,
+            zoom_meeting: false,
+            meeting_service: None,
+        }
+    }
+
+    /// Day of the event, or of its first occurrence
+    pub fn on(mut self, date: NaiveDate) -> Self {
+        self.date = Some(date);
+        self
+    }
+
+    pub fn at(mut self, start: NaiveTime) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Start at `start`, on its day
+    pub fn starting(self, start: NaiveDateTime) -> Self {
+        self.on(start.date()).at(start.time())
+    }
+
+    /// Events without a length or end time last an hour
+    pub fn lasting(mut self, duration: Duration) -> Self {
+        self.end = Some(EventEnd::Duration(duration));
+        self
+    }
+
+    /// End at `end`; an end before the start is the next day, for events past midnight
+    pub fn until(mut self, end: NaiveTime) -> Self {
+        self.end = Some(EventEnd::Time(end));
+        self
+    }
+
+    pub fn all_day(mut self) -> Self {
+        self.all_day = true;
+        self
+    }
+
+    /// Add the event to `calendar` too; without calendars the default one is used
+    pub fn calendar(mut self, calendar: impl Into<String>) -> Self {
+        self.calendars.push(calendar.into());
+        self
+    }
+
+    pub fn calendars<S: Into<String>>(mut self, calendars: impl IntoIterator<Item = S>) -> Self {
+        self.calendars.extend(calendars.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn location(mut self, location: impl Into<String>) -> Self {
+        self.location = Some(location.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    pub fn availability(mut self, availability: EventAvailability) -> Self {
+        self.availability = Some(availability);
+        self
+    }
+
+    /// Invite `email`
+    pub fn attendee(mut self, email: impl Into<String>) -> Self {
+        self.attendees.push(email.into());
+        self
+    }
+
+    pub fn attendees<S: Into<String>>(mut self, emails: impl IntoIterator<Item = S>) -> Self {
+        self.attendees.extend(emails.into_iter().map(Into::into));
+        self
+    }
+
+    /// Alert `minutes` before the event starts
+    pub fn reminder(mut self, minutes: i32) -> Self {
+        self.reminder = Some(minutes);
+        self
+    }
+
+    /// Time zone the date and times are in; the local one otherwise
+    pub fn timezone(mut self, timezone: chrono_tz::Tz) -> Self {
+        self.timezone = Some(timezone.name().to_string());
+        self
+    }
+
+    pub fn recurrence(mut self, recurrence: RecurrencePattern) -> Self {
+        self.recurrence = Some(recurrence);
+        self
+    }
+
+    /// Create a Zoom meeting with the event, as `--zoom` does
+    pub fn zoom_meeting(mut self, enable: bool) -> Self {
+        self.zoom_meeting = enable;
+        self
+    }
+
+    /// Create an online meeting with `service` and put its link in the event
+    pub fn meeting(mut self, service: MeetingService) -> Self {
+        self.meeting_service = Some(service);
+        self
+    }
+
+    /// Apply `set` with `value` when there is one
+    pub fn maybe<T>(self, value: Option<T>, set: impl FnOnce(Self, T) -> Self) -> Self {
+        match value {
+            Some(value) => set(self, value),
+            None => self,
+        }
+    }
+
+    /// The event, or why it cannot be created
+    ///
+    /// Dates and times are written as YYYY-MM-DD and HH:MM, with an end date for events that
+    /// end after midnight.
+    pub fn build(self) -> crate::error::Result<EventConfig> {
+        const DATE_FORMAT: &str = "%Y-%m-%d";
+        const TIME_FORMAT: &str = "%H:%M";
+
+        let title = validation::title("title", &self.title)?;
+        let date = self.date.ok_or_else(|| {
+            ValidationError::new("date", format!("'{}' has none", title))
+                .suggest("Set it with on() or starting()")
+        })?;
+        let mut config = if self.all_day {
+            if self.start.is_some() || self.end.is_some() {
+                return Err(ValidationError::new(
+                    "start time",
+                    format!("all-day event '{}' cannot have times", title),
+                )
+                .into());
+            }
+            let mut config =
+                EventConfig::new(&title, &date.format(DATE_FORMAT).to_string(), "00:00");
+            config.all_day = true;
+            config
+        } else {
+            let start = self.start.ok_or_else(|| {
+                ValidationError::new("start time", format!("'{}' has none", title))
+                    .suggest("Set it with at(), or make the event all day")
+            })?;
+            let starts = date.and_time(start);
+            let ends = match self.end.unwrap_or(EventEnd::Duration(Duration::hours(1))) {
+                EventEnd::Duration(duration) => starts + duration,
+                EventEnd::Time(end) if end < start => date.and_time(end) + Duration::days(1),
+                EventEnd::Time(end) => date.and_time(end),
+            };
+            if ends <= starts {
+                return Err(ValidationError::new(
+                    "end time",
+                    format!("'{}' ends when it starts or before", title),
+                )
+                .suggest("Give the time the event ends")
+                .into());
+            }
+            if ends - starts >= Duration::days(1) {
+                return Err(ValidationError::new(
+                    "duration",
+                    format!("'{}' lasts a day or more", title),
+                )
+                .suggest("Make it an all-day event, or repeat it")
+                .into());
+            }
+            let mut config = EventConfig::new(
+                &title,
+                &date.format(DATE_FORMAT).to_string(),
+                &start.format(TIME_FORMAT).to_string(),
+            );
+            config.end_time = Some(ends.format(TIME_FORMAT).to_string());
+            config.end_date =
+                (ends.date() != date).then(|| ends.date().format(DATE_FORMAT).to_string());
+            config
+        };
+        config.calendars = self.calendars;
+        config.location = self.location;
+        config.description = self.description;
+        config.url = self.url;
+        config.availability = self.availability;
+        config.emails = self
+            .attendees
+            .iter()
+            .map(|email| validation::email(email))
+            .collect::<Result<_, _>>()?;
+        config.reminder = self.reminder;
+        config.timezone = self.timezone;
+        config.recurrence = self.recurrence;
+        config.create_zoom_meeting = self.zoom_meeting;
+        config.meeting_service = self.meeting_service;
+        crate::calendar::validate_event_config(&config)?;
+        Ok(config)
+    }
 }
@@ -77,7 +77,7 @@ pub fn validate_event_config(
         validate_email, validate_time_format,
     };
     use anyhow::anyhow;
-    use log::debug;
+    use tracing::debug;
 
     // Validate date format (YYYY-MM-DD)
     if !validate_date_format(&config.start_date) {
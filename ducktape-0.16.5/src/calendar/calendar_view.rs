@@ -0,0 +1,550 @@
+//! Interactive week and month view of the calendar in the terminal.
+//
+// `ducktape calendar view` draws the events DuckTape keeps in its local state with ratatui,
+// so the view opens instantly and works without Calendar.app being scriptable. The screen is
+// driven by `CalendarView`, which only knows about dates, events and keys; the terminal loop
+// draws it and runs the actions it asks for (creating, deleting or opening an event), then
+// reloads the events. Repeating events are shown on every day their rule matches, for rules
+// made of a frequency, interval, weekdays, end date and count.
+
+use crate::app_state::SharedState;
+use crate::applescript::Template;
+use crate::calendar::{RecurrenceFrequency, create_event};
+use crate::calendar::{delete_event, parse_ical_recurrence};
+use crate::parser::quick::parse_quick;
+use crate::script_runner::run_applescript_async;
+use crate::state::CalendarItem;
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveTime};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::io::IsTerminal;
+
+/// Days searched for the occurrences of a rule with a COUNT
+const MAX_RECURRENCE_DAYS: i64 = 3660;
+
+const WEEK_HELP: &str = "←→ day  ↑↓ week  m month  t today  enter day  n new  o open  q quit";
+const MONTH_HELP: &str = "←→ day  ↑↓ week  [] month  w week  t today  enter day  n new  q quit";
+const DAY_HELP: &str = "↑↓ event  ←→ day  n new  d delete  o open  esc back  q quit";
+
+/// How many days the view shows at once
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewMode {
+    Week,
+    Month,
+}
+
+/// Something the view asks the terminal loop to do
+#[derive(Debug, Clone, PartialEq)]
+pub enum ViewAction {
+    Quit,
+    /// Create an event on `date` from a quick-add phrase
+    Create {
+        date: NaiveDate,
+        text: String,
+    },
+    Delete(CalendarItem),
+    /// Show the event, or the day when there is none, in Calendar.app
+    Open {
+        date: NaiveDate,
+        event: Option<CalendarItem>,
+    },
+}
+
+/// The state of the calendar screen
+pub struct CalendarView {
+    pub mode: ViewMode,
+    pub cursor: NaiveDate,
+    pub today: NaiveDate,
+    /// Whether the events of the cursor's day are listed
+    pub day_open: bool,
+    /// Selected event in the day list
+    pub selected: usize,
+    events: Vec<CalendarItem>,
+    /// Text typed for a new event
+    input: Option<String>,
+    /// Event waiting for the user to confirm its deletion
+    pending_delete: Option<CalendarItem>,
+    status: Option<String>,
+}
+
+impl CalendarView {
+    pub fn new(
+        mode: ViewMode,
+        cursor: NaiveDate,
+        today: NaiveDate,
+        events: Vec<CalendarItem>,
+    ) -> Self {
+        Self {
+            mode,
+            cursor,
+            today,
+            day_open: false,
+            selected: 0,
+            events,
+            input: None,
+            pending_delete: None,
+            status: None,
+        }
+    }
+
+    pub fn set_events(&mut self, events: Vec<CalendarItem>) {
+        self.events = events;
+        let count = self.events_on(self.cursor).len();
+        self.selected = self.selected.min(count.saturating_sub(1));
+    }
+
+    pub fn set_status(&mut self, status: impl Into<String>) {
+        self.status = Some(status.into());
+    }
+
+    /// The events on `date`, all-day events first, then by start time
+    pub fn events_on(&self, date: NaiveDate) -> Vec<&CalendarItem> {
+        let mut events: Vec<&CalendarItem> =
+            self.events.iter().filter(|item| occurs_on(item, date)).collect();
+        events.sort_by(|a, b| b.all_day.cmp(&a.all_day).then_with(|| a.time.cmp(&b.time)));
+        events
+    }
+
+    /// The days on screen: the week of the cursor, or six weeks covering its month
+    pub fn visible_days(&self) -> Vec<NaiveDate> {
+        let (first, count) = match self.mode {
+            ViewMode::Week => (monday_of(self.cursor), 7),
+            ViewMode::Month => (monday_of(self.cursor.with_day(1).unwrap_or(self.cursor)), 42),
+        };
+        (0..count).map(|n| first + Duration::days(n)).collect()
+    }
+
+    fn selected_event(&self) -> Option<CalendarItem> {
+        self.events_on(self.cursor).get(self.selected).map(|item| (*item).clone())
+    }
+
+    fn move_cursor(&mut self, days: i64) {
+        self.cursor += Duration::days(days);
+        self.selected = 0;
+    }
+
+    fn move_months(&mut self, months: i32) {
+        let month = self.cursor.year() * 12 + self.cursor.month0() as i32 + months;
+        let (year, month) = (month.div_euclid(12), month.rem_euclid(12) as u32 + 1);
+        // Keep the day, or the last day of a shorter month
+        self.cursor = (0..4)
+            .find_map(|back| NaiveDate::from_ymd_opt(year, month, self.cursor.day() - back))
+            .unwrap_or(self.cursor);
+        self.selected = 0;
+    }
+
+    /// Update the view for a key press, returning what the terminal loop should do
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<ViewAction> {
+        if let Some(input) = &mut self.input {
+            match key {
+                KeyCode::Enter => {
+                    let text = input.trim().to_string();
+                    self.input = None;
+                    if !text.is_empty() {
+                        return Some(ViewAction::Create { date: self.cursor, text });
+                    }
+                }
+                KeyCode::Esc => self.input = None,
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) => input.push(c),
+                _ => {}
+            }
+            return None;
+        }
+        if let Some(event) = self.pending_delete.take() {
+            if matches!(key, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                return Some(ViewAction::Delete(event));
+            }
+            self.set_status("Delete cancelled");
+            return None;
+        }
+
+        self.status = None;
+        match key {
+            KeyCode::Char('q') => return Some(ViewAction::Quit),
+            KeyCode::Esc if self.day_open => self.day_open = false,
+            KeyCode::Esc => return Some(ViewAction::Quit),
+            KeyCode::Left | KeyCode::Char('h') => self.move_cursor(-1),
+            KeyCode::Right | KeyCode::Char('l') => self.move_cursor(1),
+            KeyCode::Up | KeyCode::Char('k') if self.day_open => {
+                self.selected = self.selected.saturating_sub(1)
+            }
+            KeyCode::Down | KeyCode::Char('j') if self.day_open => {
+                let count = self.events_on(self.cursor).len();
+                self.selected = (self.selected + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.move_cursor(-7),
+            KeyCode::Down | KeyCode::Char('j') => self.move_cursor(7),
+            KeyCode::PageUp | KeyCode::Char('[') => match self.mode {
+                ViewMode::Week => self.move_cursor(-7),
+                ViewMode::Month => self.move_months(-1),
+            },
+            KeyCode::PageDown | KeyCode::Char(']') => match self.mode {
+                ViewMode::Week => self.move_cursor(7),
+                ViewMode::Month => self.move_months(1),
+            },
+            KeyCode::Char('t') => {
+                self.cursor = self.today;
+                self.selected = 0;
+            }
+            KeyCode::Char('w') => self.mode = ViewMode::Week,
+            KeyCode::Char('m') => self.mode = ViewMode::Month,
+            KeyCode::Enter => {
+                self.day_open = true;
+                self.selected = 0;
+            }
+            KeyCode::Char('n') | KeyCode::Char('c') => self.input = Some(String::new()),
+            KeyCode::Char('d') if self.day_open => match self.selected_event() {
+                Some(event) => {
+                    let series =
+                        if event.recurrence.is_some() { " and every repeat of it" } else { "" };
+                    self.set_status(format!("Delete '{}'{}? (y/n)", event.title, series));
+                    self.pending_delete = Some(event);
+                }
+                None => self.set_status("No event selected"),
+            },
+            KeyCode::Char('o') => {
+                let event = if self.day_open { self.selected_event() } else { None };
+                return Some(ViewAction::Open { date: self.cursor, event });
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+/// The Monday on or before `date`
+fn monday_of(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// Whether a cached event, or one of its repeats, falls on `date`
+///
+/// Rules with parts beyond a frequency, interval, weekdays, end date and count (such as "the
+/// last Friday of the month") only show the first occurrence.
+pub fn occurs_on(item: &CalendarItem, date: NaiveDate) -> bool {
+    let Ok(start) = NaiveDate::parse_from_str(&item.date, "%Y-%m-%d") else {
+        return false;
+    };
+    if date == start {
+        return true;
+    }
+    let Some(rule) = item.recurrence.as_deref() else {
+        return false;
+    };
+    let Ok(pattern) = parse_ical_recurrence(rule, None) else {
+        return false;
+    };
+    if !pattern.ordinal_days.is_empty()
+        || !pattern.month_days.is_empty()
+        || !pattern.months.is_empty()
+        || !pattern.set_positions.is_empty()
+    {
+        return false;
+    }
+    let until = pattern
+        .end_date
+        .as_deref()
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+    let interval = pattern.interval.max(1) as i64;
+    let matches = |day: NaiveDate| {
+        if day < start || until.is_some_and(|until| day > until) {
+            return false;
+        }
+        match pattern.frequency {
+            RecurrenceFrequency::Daily => (day - start).num_days() % interval == 0,
+            RecurrenceFrequency::Weekly => {
+                let weeks = (monday_of(day) - monday_of(start)).num_days() / 7;
+                let weekday = day.weekday().num_days_from_sunday() as u8;
+                let on_day = if pattern.days_of_week.is_empty() {
+                    day.weekday() == start.weekday()
+                } else {
+                    pattern.days_of_week.contains(&weekday)
+                };
+                weeks % interval == 0 && on_day
+            }
+            RecurrenceFrequency::Monthly => {
+                let months = (day.year() - start.year()) as i64 * 12 + day.month() as i64
+                    - start.month() as i64;
+                months % interval == 0 && day.day() == start.day()
+            }
+            RecurrenceFrequency::Yearly => {
+                (day.year() - start.year()) as i64 % interval == 0
+                    && (day.month(), day.day()) == (start.month(), start.day())
+            }
+        }
+    };
+    if !matches(date) {
+        return false;
+    }
+    match pattern.count {
+        Some(count) => {
+            let days = (date - start).num_days();
+            days <= MAX_RECURRENCE_DAYS
+                && (0..=days).filter(|n| matches(start + Duration::days(*n))).count()
+                    <= count as usize
+        }
+        None => true,
+    }
+}
+
+fn event_time(item: &CalendarItem) -> String {
+    if item.all_day {
+        return "all day".to_string();
+    }
+    match &item.end_time {
+        Some(end) => format!("{}-{}", item.time, end),
+        None => item.time.clone(),
+    }
+}
+
+fn render(frame: &mut Frame, view: &CalendarView) {
+    let [header, body, footer] =
+        Layout::vertical([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)])
+            .areas(frame.area());
+
+    let title = match view.mode {
+        ViewMode::Week => {
+            let monday = monday_of(view.cursor);
+            format!(
+                "Week of {} – {}",
+                monday.format("%b %-d"),
+                (monday + Duration::days(6)).format("%b %-d, %Y")
+            )
+        }
+        ViewMode::Month => view.cursor.format("%B %Y").to_string(),
+    };
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled(" DuckTape ", Style::new().add_modifier(Modifier::BOLD)),
+            Span::raw(title),
+        ])),
+        header,
+    );
+
+    if view.day_open {
+        render_day(frame, view, body);
+    } else {
+        render_grid(frame, view, body);
+    }
+
+    let footer_line = if let Some(input) = &view.input {
+        Line::from(vec![
+            Span::styled(
+                format!(" New event on {}: ", view.cursor.format("%a %b %-d")),
+                Style::new().fg(Color::Yellow),
+            ),
+            Span::raw(format!("{}█", input)),
+        ])
+    } else if let Some(status) = &view.status {
+        Line::from(Span::styled(format!(" {}", status), Style::new().fg(Color::Yellow)))
+    } else {
+        let help = match (view.day_open, view.mode) {
+            (true, _) => DAY_HELP,
+            (false, ViewMode::Week) => WEEK_HELP,
+            (false, ViewMode::Month) => MONTH_HELP,
+        };
+        Line::from(Span::styled(format!(" {}", help), Style::new().fg(Color::DarkGray)))
+    };
+    frame.render_widget(Paragraph::new(footer_line), footer);
+}
+
+fn day_block(view: &CalendarView, date: NaiveDate, title: String) -> Block<'static> {
+    let mut style = Style::new();
+    if date == view.today {
+        style = style.fg(Color::Cyan);
+    }
+    if view.mode == ViewMode::Month && date.month() != view.cursor.month() {
+        style = style.fg(Color::DarkGray);
+    }
+    let border = if date == view.cursor {
+        Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    } else {
+        Style::new()
+    };
+    Block::bordered().border_style(border).title(Span::styled(title, style))
+}
+
+fn render_grid(frame: &mut Frame, view: &CalendarView, area: Rect) {
+    let days = view.visible_days();
+    let rows =
+        Layout::vertical(vec![Constraint::Ratio(1, (days.len() / 7) as u32); days.len() / 7])
+            .split(area);
+    for (week, row) in days.chunks(7).zip(rows.iter()) {
+        let cells = Layout::horizontal([Constraint::Ratio(1, 7); 7]).split(*row);
+        for (date, cell) in week.iter().zip(cells.iter()) {
+            let title = match view.mode {
+                ViewMode::Week => date.format(" %a %-d ").to_string(),
+                ViewMode::Month => format!(" {} ", date.day()),
+            };
+            let block = day_block(view, *date, title);
+            let capacity = block.inner(*cell).height as usize;
+            let events = view.events_on(*date);
+            let mut lines: Vec<Line> = events
+                .iter()
+                .map(|item| match view.mode {
+                    ViewMode::Week if !item.all_day => Line::from(vec![
+                        Span::styled(format!("{} ", item.time), Style::new().fg(Color::Green)),
+                        Span::raw(item.title.clone()),
+                    ]),
+                    _ => Line::from(format!("• {}", item.title)),
+                })
+                .collect();
+            if lines.len() > capacity && capacity > 0 {
+                let hidden = lines.len() - capacity + 1;
+                lines.truncate(capacity - 1);
+                lines.push(Line::styled(
+                    format!("+{} more", hidden),
+                    Style::new().fg(Color::DarkGray),
+                ));
+            }
+            frame.render_widget(Paragraph::new(lines).block(block), *cell);
+        }
+    }
+}
+
+fn render_day(frame: &mut Frame, view: &CalendarView, area: Rect) {
+    let events = view.events_on(view.cursor);
+    let block = day_block(view, view.cursor, view.cursor.format(" %A, %B %-d, %Y ").to_string());
+    if events.is_empty() {
+        frame.render_widget(Paragraph::new(" No events. Press n to add one.").block(block), area);
+        return;
+    }
+    let items: Vec<ListItem> = events
+        .iter()
+        .map(|item| {
+            let mut spans = vec![
+                Span::styled(format!("{:<12}", event_time(item)), Style::new().fg(Color::Green)),
+                Span::styled(item.title.clone(), Style::new().add_modifier(Modifier::BOLD)),
+            ];
+            if !item.calendars.is_empty() {
+                spans.push(Span::styled(
+                    format!("  [{}]", item.calendars.join(", ")),
+                    Style::new().fg(Color::DarkGray),
+                ));
+            }
+            if let Some(location) = &item.location {
+                spans.push(Span::raw(format!("  @ {}", location)));
+            }
+            if item.recurrence.is_some() {
+                spans.push(Span::raw("  ↻"));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("▶ ");
+    let mut state = ListState::default().with_selected(Some(view.selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+/// Create an event on `date` from a quick-add phrase
+///
+/// Days and times in the phrase are read relative to `date`, and a phrase without either is an
+/// all-day event on `date`.
+async fn create_on(date: NaiveDate, text: &str) -> Result<String> {
+    let midnight = date.and_time(NaiveTime::MIN);
+    let event = parse_quick(text, midnight)
+        .or_else(|_| parse_quick(&format!("{} {}", text, date.format("%Y-%m-%d")), midnight))?;
+    create_event(event.to_event_config()).await?;
+    Ok(format!("Created {}", event))
+}
+
+async fn delete(item: &CalendarItem) -> Result<String> {
+    delete_event(item).await?;
+    Ok(format!("Deleted '{}'", item.title))
+}
+
+async fn open_in_calendar(date: NaiveDate, event: Option<&CalendarItem>) -> Result<String> {
+    let show = match event.and_then(|e| Some((e.uid.as_deref()?, e.calendars.first()?))) {
+        Some((uid, calendar)) => Template::new(
+            r#"try
+        show (first event of (first calendar whose name is ${calendar}) whose uid is ${uid})
+    end try"#,
+        )
+        .text("calendar", calendar)
+        .text("uid", uid)
+        .render()?,
+        None => "".into(),
+    };
+    let script = Template::new(
+        r#"tell application "Calendar"
+    activate
+    set d to current date
+    set day of d to 1
+    set year of d to ${year}
+    set month of d to ${month}
+    set day of d to ${day}
+    switch view to day view
+    view calendar at d
+    ${show}
+end tell"#,
+    )
+    .number("year", date.year())
+    .number("month", date.month())
+    .number("day", date.day())
+    .script("show", show)
+    .render()?;
+
+    let output = run_applescript_async(script).await?;
+    if !output.status.success() {
+        return Err(anyhow!("{}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(match event {
+        Some(event) => format!("Opened '{}' in Calendar", event.title),
+        None => format!("Opened {} in Calendar", date.format("%a %b %-d")),
+    })
+}
+
+async fn event_loop(terminal: &mut DefaultTerminal, view: &mut CalendarView) -> Result<()> {
+    loop {
+        terminal.draw(|frame| render(frame, view))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Ok(());
+        }
+        let result = match view.handle_key(key.code) {
+            None => continue,
+            Some(ViewAction::Quit) => return Ok(()),
+            // The view stays open without the mutation lock; each change takes it
+            Some(ViewAction::Create { date, text }) => {
+                SharedState::global().exclusive(create_on(date, &text)).await
+            }
+            Some(ViewAction::Delete(item)) => SharedState::global().exclusive(delete(&item)).await,
+            Some(ViewAction::Open { date, event }) => open_in_calendar(date, event.as_ref()).await,
+        };
+        view.set_status(result.unwrap_or_else(|e| format!("Error: {}", e)));
+        if let Ok(events) = crate::state::load_events() {
+            view.set_events(events);
+        }
+        // Creating an event may print to the terminal, so draw everything again
+        terminal.clear()?;
+    }
+}
+
+/// Open the interactive calendar on the week or month of `start`
+pub async fn run_calendar_view(mode: ViewMode, start: NaiveDate) -> Result<()> {
+    if !std::io::stdout().is_terminal() {
+        return Err(anyhow!("The calendar view needs an interactive terminal"));
+    }
+    let events = crate::state::load_events()?;
+    let mut view = CalendarView::new(mode, start, Local::now().date_naive(), events);
+    let mut terminal = ratatui::try_init()?;
+    let result = event_loop(&mut terminal, &mut view).await;
+    ratatui::restore();
+    result
+}
@@ -4,6 +4,8 @@
 //! using the clap crate for argument parsing.
 
 use crate::command_processor::CommandArgs;
+use crate::launcher::OutputFormat;
+use crate::logging::{LogFormat, LogOptions};
 use clap::{Parser, Subcommand, ValueEnum};
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -13,6 +15,7 @@ use std::path::PathBuf;
 #[command(name = "ducktape")]
 #[command(about = "AI-powered terminal tool for Apple Calendar, Reminders and Notes", long_about = None)]
 #[command(version)]
+#[command(disable_help_subcommand = true)]
 pub struct Cli {
     /// Command to execute (if not specified, enters interactive terminal mode)
     #[command(subcommand)]
@@ -25,6 +28,39 @@ pub struct Cli {
     /// Start both terminal and API server
     #[arg(long = "full", conflicts_with = "api_server")]
     pub full: bool,
+
+    /// Configuration profile to use for this run
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
+    /// Log more: -v for debug output, -vv for everything
+    #[arg(short, long, global = true, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    pub verbose: u8,
+
+    /// Log errors only
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Log as plain text or one JSON object per line
+    #[arg(long, global = true, value_enum)]
+    pub log_format: Option<LogFormat>,
+
+    /// Delete or change without asking for confirmation
+    #[arg(short, long, global = true)]
+    pub yes: bool,
+
+    /// Print agenda, search and quick results as text or as Alfred Script Filter JSON
+    ///
+    /// Given before the command, as `report time` has its own `--output`.
+    #[arg(long, value_enum)]
+    pub output: Option<OutputFormat>,
+}
+
+impl Cli {
+    /// Logging as the flags ask
+    pub fn log_options(&self) -> LogOptions {
+        LogOptions { verbose: self.verbose, quiet: self.quiet, format: self.log_format }
+    }
 }
 
 #[derive(Debug, Subcommand)]
@@ -36,7 +72,7 @@ pub enum Commands {
     },
 
     /// Manage reminders/todos
-    #[command(alias = "todos")]
+    #[command(aliases = ["todos", "reminder", "reminders"])]
     Todo {
         #[command(subcommand)]
         action: TodoActions,
@@ -68,12 +104,738 @@ pub enum Commands {
         #[command(subcommand)]
         action: UtilityActions,
     },
+
+    /// Register existing Calendar.app events in DuckTape's state
+    Adopt {
+        /// Calendar to scan (defaults to the configured default calendar)
+        #[arg(long)]
+        calendar: Option<String>,
+
+        /// Only adopt events starting on or after this date (YYYY-MM-DD); repeating events
+        /// count from the start of the series
+        #[arg(long)]
+        from: Option<String>,
+    },
+
+    /// Link a reminder and an event, e.g. todo:"Prepare deck" event:"Board meeting"
+    Link {
+        /// First item (todo:"<title>" or event:"<title>")
+        #[arg(required = true)]
+        from: String,
+
+        /// Second item (todo:"<title>" or event:"<title>")
+        #[arg(required = true)]
+        to: String,
+
+        /// How the first item relates to the second
+        #[arg(long, value_enum, default_value = "relates-to")]
+        relation: LinkRelationArg,
+    },
+
+    /// Remove the link between two items
+    Unlink {
+        /// First item (todo:"<title>" or event:"<title>")
+        #[arg(required = true)]
+        from: String,
+
+        /// Second item (todo:"<title>" or event:"<title>")
+        #[arg(required = true)]
+        to: String,
+    },
+
+    /// List all links between items
+    Links,
+
+    /// Show the events of a day with their linked prep tasks
+    Agenda {
+        /// Date (YYYY-MM-DD, defaults to today)
+        date: Option<String>,
+
+        /// Only show events from this calendar
+        #[arg(long)]
+        calendar: Option<String>,
+    },
+
+    /// Open an event, note or reminder in Calendar, Notes or Reminders
+    Open {
+        /// What to open
+        #[arg(value_enum)]
+        kind: OpenKindArg,
+
+        /// Title, or part of it
+        #[arg(required = true, num_args = 1..)]
+        title: Vec<String>,
+    },
+
+    /// List the events, reminders and notes carrying a tag
+    List {
+        /// Tag to look for, with or without the #
+        #[arg(long, required = true)]
+        tag: String,
+
+        /// Only list events, reminders or notes
+        #[arg(long, value_enum)]
+        kind: Option<OpenKindArg>,
+    },
+
+    /// Create an event from one phrase, e.g. "Lunch with Sara Fri 12:30-13:30 at Blue Bottle"
+    Quick {
+        /// Title, day, time or time range and "at <location>", in any order
+        #[arg(required = true, num_args = 1..)]
+        text: Vec<String>,
+
+        /// Calendar to create the event in (defaults to the configured default calendar)
+        #[arg(long)]
+        calendar: Option<String>,
+
+        /// Show the event that would be created without creating it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Search the events DuckTape tracks by title, place, notes or calendar
+    Search {
+        /// Words that must all appear
+        #[arg(required = true, num_args = 1..)]
+        query: Vec<String>,
+
+        /// Also search events that are over
+        #[arg(long)]
+        past: bool,
+    },
+
+    /// Agenda, search and quick create as JSON for a Raycast extension
+    Raycast {
+        #[command(subcommand)]
+        action: RaycastActions,
+    },
+
+    /// Run the last command made from natural language again
+    Redo {
+        /// Show the command without running it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Change the last command made from natural language and run it again
+    ///
+    /// Without changes, the command is shown on a prompt to edit.
+    EditLast {
+        /// Set an argument or option, e.g. date=2025-03-14, calendar=Work or zoom
+        #[arg(long, value_name = "NAME=VALUE")]
+        set: Vec<String>,
+
+        /// Remove an option, e.g. location
+        #[arg(long, value_name = "NAME")]
+        unset: Vec<String>,
+
+        /// New date of the event
+        #[arg(long)]
+        date: Option<String>,
+
+        /// New start time (keeping the length), or start and end as HH:MM-HH:MM
+        #[arg(long)]
+        time: Option<String>,
+
+        /// Show the changed command without running it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Show attendee responses and resend invitations
+    Attendees {
+        #[command(subcommand)]
+        action: AttendeeActions,
+    },
+
+    /// Share templates, aliases, routines and defaults as packs
+    Pack {
+        #[command(subcommand)]
+        action: PackActions,
+    },
+
+    /// Manage the cache of commands generated by language models
+    Cache {
+        #[command(subcommand)]
+        action: CacheActions,
+    },
+
+    /// Show language model token usage and estimated cost
+    Usage {
+        #[command(subcommand)]
+        action: UsageActions,
+    },
+
+    /// Check how well natural language input is understood
+    Nlp {
+        #[command(subcommand)]
+        action: NlpActions,
+    },
+
+    /// Walk through choosing a provider, API keys and default calendar and list
+    Setup,
+
+    /// Check permissions, API keys, Calendar and network access
+    Doctor,
+
+    /// Store API keys and credentials in the keychain
+    Keys {
+        #[command(subcommand)]
+        action: KeysActions,
+    },
+
+    /// Send changes to events, reminders and notes to webhook URLs
+    Webhooks {
+        #[command(subcommand)]
+        action: WebhooksActions,
+    },
+
+    /// Run create-event, agenda or search for Apple Shortcuts, printing the result as JSON
+    Shortcut {
+        /// create-event, agenda or search (not needed with --url)
+        action: Option<String>,
+
+        /// Parameters as key=value, e.g. title=Lunch time=12:30
+        params: Vec<String>,
+
+        /// Read parameters from stdin, as a JSON dictionary or key=value lines
+        #[arg(long)]
+        stdin: bool,
+
+        /// A ducktape://x-callback-url/<action>?... URL; x-success and x-error are opened
+        #[arg(long)]
+        url: Option<String>,
+    },
+
+    /// Back up or restore the state kept in ~/.ducktape
+    State {
+        #[command(subcommand)]
+        action: StateActions,
+    },
+
+    /// Show who ran which command, from the terminal, the API server or Slack
+    Audit {
+        #[command(subcommand)]
+        action: AuditActions,
+    },
+
+    /// Reports on the events DuckTape tracks
+    Report {
+        #[command(subcommand)]
+        action: ReportActions,
+    },
+
+    /// Review notes gathered from your reminders, events and notes
+    Review {
+        #[command(subcommand)]
+        action: ReviewActions,
+    },
+
+    /// Time-boxed focus sessions that block your calendar
+    Focus {
+        #[command(subcommand)]
+        action: FocusActions,
+    },
+
+    /// Turn birthdays and anniversaries from Contacts.app into yearly calendar events
+    Birthdays {
+        #[command(subcommand)]
+        action: BirthdaysActions,
+    },
+
+    /// Public holidays of the country set in holidays.country
+    Holidays {
+        #[command(subcommand)]
+        action: HolidaysActions,
+    },
+
+    /// Run DuckTape commands on a schedule, like cron
+    Schedule {
+        #[command(subcommand)]
+        action: ScheduleActions,
+    },
+
+    /// Print events added, changed or removed in Calendar.app as it happens
+    Watch {
+        /// Seconds between checks
+        #[arg(long, default_value_t = 60)]
+        interval: u64,
+
+        /// Days ahead to watch, starting today
+        #[arg(long, default_value_t = 14)]
+        days: u32,
+
+        /// Only watch this calendar
+        #[arg(long)]
+        calendar: Option<String>,
+
+        /// Also show each change as a notification
+        #[arg(long)]
+        notify: bool,
+    },
+
+    /// Sync the tracked events with Calendar.app, both ways
+    Sync {
+        /// Days ahead to sync, besides the last 30 days
+        #[arg(long, default_value_t = 180)]
+        days: u32,
+
+        /// Also start tracking events created in Calendar.app
+        #[arg(long)]
+        adopt: bool,
+
+        /// Show what would change without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Share created events as read-only iCalendar feeds served by the API server
+    Feeds {
+        #[command(subcommand)]
+        action: FeedsActions,
+    },
+
+    /// Propose calendar events from messages that describe a meeting
+    Ingest {
+        #[command(subcommand)]
+        action: IngestActions,
+    },
+
+    /// Show the commands, or a command's options with examples
+    Help {
+        /// Command and subcommand, e.g. `calendar create`
+        command: Vec<String>,
+    },
+
+    /// Print the man page, or write man pages for every command to a directory
+    Man {
+        /// Directory to write ducktape.1 and ducktape-<command>.1 into
+        #[arg(long)]
+        out_dir: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum KeysActions {
+    /// Store the credentials of a service (xai, deepseek, zoom, caldav, smtp, webhook, slack, serpapi, brave or bing)
+    Set {
+        /// Service to store credentials for
+        service: String,
+    },
+    /// Show where each credential is found
+    List,
+    /// Remove the stored credentials of a service
+    Delete {
+        /// Service to remove credentials for
+        service: String,
+    },
+    /// Move credentials from a .env file to the keychain
+    Migrate {
+        /// The .env file to migrate
+        #[arg(long, default_value = ".env")]
+        env_file: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum RaycastActions {
+    /// The events of a day
+    Agenda {
+        /// Day, e.g. 2025-04-22, today or friday (defaults to today)
+        date: Option<String>,
+
+        /// Only show events from this calendar
+        #[arg(long)]
+        calendar: Option<String>,
+    },
+    /// Tracked events matching the words
+    Search {
+        /// Words that must all appear
+        query: Vec<String>,
+
+        /// Also search events that are over
+        #[arg(long)]
+        past: bool,
+    },
+    /// The event `quick` would create, with the command that creates it
+    Create {
+        /// Title, day, time or time range and "at <location>", in any order
+        text: Vec<String>,
+
+        /// Calendar to create the event in
+        #[arg(long)]
+        calendar: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum WebhooksActions {
+    /// Show the webhook URLs and settings
+    List,
+    /// POST changes to a URL
+    Add {
+        /// URL to send changes to
+        url: String,
+    },
+    /// Stop sending changes to a URL
+    Remove {
+        /// URL to remove
+        url: String,
+    },
+    /// Send a test delivery to every URL, or to one
+    Test {
+        /// URL to test instead of the configured ones
+        url: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum StateActions {
+    /// Write the state to a JSON file, or to stdout
+    Export {
+        /// File to write
+        file: Option<PathBuf>,
+    },
+    /// Replace the state with an export
+    Import {
+        /// File written by `ducktape state export`
+        file: PathBuf,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AuditActions {
+    /// Show the commands in the audit log, oldest first
+    Show {
+        /// Only commands from this day on, e.g. yesterday, monday or 2025-04-01
+        #[arg(long)]
+        since: Option<String>,
+        /// Only the most recent commands
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ReportActions {
+    /// Add up the time spent in events, like a time tracker
+    Time {
+        /// How to group the time
+        #[arg(long, value_enum, default_value = "calendar")]
+        group_by: ReportGroupArg,
+        /// Period before now: week, month, year, or e.g. "30 days" (defaults to "7 days")
+        #[arg(long, conflicts_with_all = ["from", "to"])]
+        last: Option<String>,
+        /// First day (YYYY-MM-DD, monday, ...)
+        #[arg(long)]
+        from: Option<String>,
+        /// Last day, included (defaults to today)
+        #[arg(long)]
+        to: Option<String>,
+        /// Only events in this calendar
+        #[arg(long)]
+        calendar: Option<String>,
+        /// Only events with this text in the title or notes
+        #[arg(long)]
+        keyword: Option<String>,
+        /// Only events with this #tag in the title or notes
+        #[arg(long)]
+        tag: Option<String>,
+        /// Output format
+        #[arg(long, value_enum, default_value = "table")]
+        format: ReportFormatArg,
+        /// Write the report to a file instead of printing it
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ReviewActions {
+    /// Write last week's completed reminders, events and new notes into a note
+    Weekly {
+        /// Folder to create the note in (defaults to notes.default_folder)
+        #[arg(long)]
+        folder: Option<String>,
+        /// Leave out the summary written by the language model
+        #[arg(long)]
+        no_summary: bool,
+        /// Only show the note that would be created
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum ReportGroupArg {
+    Calendar,
+    Title,
+    Day,
+    Week,
+    Tag,
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum ReportFormatArg {
+    Table,
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum FocusActions {
+    /// Block the time in the calendar and count down in the terminal
+    Start {
+        /// Session length, e.g. 25m, 1h or 1h30m
+        duration: String,
+        /// What to focus on
+        #[arg(required = true)]
+        title: Vec<String>,
+        /// Calendar for the block (defaults to focus.calendar, then calendar.default)
+        #[arg(long)]
+        calendar: Option<String>,
+        /// Run the focus.dnd_on_shortcut and focus.dnd_off_shortcut shortcuts around the session
+        #[arg(long)]
+        dnd: bool,
+    },
+    /// Show how much time was spent in focus sessions
+    Stats {
+        /// Only sessions from this day on, e.g. monday or 2025-04-01
+        #[arg(long)]
+        since: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum BirthdaysActions {
+    /// Create an event for every birthday and anniversary that has none yet
+    Sync {
+        /// Calendar to create the events in (defaults to calendar.default)
+        #[arg(long)]
+        calendar: Option<String>,
+        /// Alert this many days before, at 09:00
+        #[arg(long)]
+        remind_days: Option<u32>,
+        /// Only show the events that would be created
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum HolidaysActions {
+    /// List the public holidays of a year
+    List {
+        /// Year (defaults to this year)
+        year: Option<i32>,
+        /// Country code or name, e.g. NZ (defaults to holidays.country)
+        #[arg(long)]
+        country: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ScheduleActions {
+    /// Run a command on a schedule
+    Add {
+        /// The command without `ducktape`, e.g. "agenda" or "report time --last week"
+        command: String,
+        /// When to run it: "07:30 daily", "18:00 weekdays", "monday 09:00", "hourly" or cron
+        #[arg(long, required = true)]
+        at: String,
+    },
+    /// Show the scheduled commands and when they run next
+    List,
+    /// Stop running a scheduled command
+    Remove {
+        /// ID shown by `schedule list`
+        id: u32,
+    },
+    /// Run the scheduled commands as they come due, until stopped with Ctrl+C
+    Run,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum FeedsActions {
+    /// Show the feeds and their subscription URLs
+    List,
+    /// Create a feed with a new token
+    Add {
+        /// Name shown by calendar applications
+        name: String,
+        /// Calendar to include (repeatable); every calendar when omitted
+        #[arg(long = "calendar")]
+        calendars: Vec<String>,
+    },
+    /// Give a feed a new token, cutting off everyone subscribed with the old one
+    Rotate {
+        /// Feed name
+        name: String,
+    },
+    /// Delete a feed
+    Remove {
+        /// Feed name
+        name: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum IngestActions {
+    /// Read an email (.eml or raw RFC 822) and propose the meeting it describes
+    Email {
+        /// The .eml file; reads stdin when omitted or `-`
+        file: Option<PathBuf>,
+
+        /// Create the event instead of only showing it
+        #[arg(long)]
+        create: bool,
+
+        /// Calendar to create the event in
+        #[arg(long)]
+        calendar: Option<String>,
+
+        /// Do not invite the sender and recipients
+        #[arg(long)]
+        no_attendees: bool,
+    },
+    /// Read an airline confirmation and propose an event for each flight
+    Itinerary {
+        /// Text or .eml file with the confirmation; reads stdin when omitted or `-`
+        file: Option<PathBuf>,
+
+        /// Create the events instead of only showing them
+        #[arg(long)]
+        create: bool,
+
+        /// Calendar to create the events in
+        #[arg(long)]
+        calendar: Option<String>,
+
+        /// Remind to check in this many hours before each flight; 0 for no reminders
+        #[arg(long, default_value_t = 24)]
+        checkin_hours: u32,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum UsageActions {
+    /// Show usage per day, provider and model for a month
+    Show {
+        /// Month to show (YYYY-MM, defaults to the current month)
+        #[arg(long)]
+        month: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum NlpActions {
+    /// Run the phrases of a fixtures file through a parser and score the commands it gives
+    Eval {
+        /// TOML file with [[case]] entries of input and expected command
+        fixtures: PathBuf,
+        /// Provider to evaluate (defaults to the first configured one)
+        #[arg(long)]
+        provider: Option<String>,
+        /// Fail when fewer than this share of the cases pass (0.0 to 1.0)
+        #[arg(long)]
+        min_accuracy: Option<f64>,
+        /// Show the cases that passed too
+        #[arg(long)]
+        verbose: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CacheActions {
+    /// Remove all cached commands and calendar names
+    Clear,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AttendeeActions {
+    /// List attendees of an event and whether they accepted
+    List {
+        /// Event title
+        title: String,
+
+        /// Day of the event (YYYY-MM-DD), when the title repeats
+        #[arg(long)]
+        date: Option<String>,
+
+        /// Calendar the event is in
+        #[arg(long)]
+        calendar: Option<String>,
+    },
+
+    /// Invite attendees who have not answered again
+    Resend {
+        /// Event title
+        title: String,
+
+        /// Day of the event (YYYY-MM-DD), when the title repeats
+        #[arg(long)]
+        date: Option<String>,
+
+        /// Calendar the event is in
+        #[arg(long)]
+        calendar: Option<String>,
+
+        /// Invite every attendee, not only those without an answer
+        #[arg(long)]
+        all: bool,
+
+        /// Email the invitation (Mail.app or [smtp]) instead of through the calendar account
+        #[arg(long)]
+        mail: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PackActions {
+    /// Export the local setup to a pack file, e.g. consulting.toml
+    Export {
+        /// Pack file to write
+        file: String,
+
+        /// Pack name (defaults to the file name)
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Short description of the pack
+        #[arg(long)]
+        description: Option<String>,
+    },
+
+    /// Install a pack from a file or URL
+    Install {
+        /// Pack file or http(s) URL
+        source: String,
+
+        /// How to handle entries that already exist
+        #[arg(long, value_enum, default_value = "ask")]
+        on_conflict: ConflictArg,
+    },
+
+    /// List installed packs and the local library
+    List,
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum ConflictArg {
+    Ask,
+    Skip,
+    Overwrite,
+    Rename,
 }
 
 #[derive(Debug, Subcommand)]
 pub enum CalendarActions {
     /// List available calendars
-    List,
+    List {
+        /// Ask the calendar backend again instead of using cached names
+        #[arg(long)]
+        refresh: bool,
+    },
 
     /// List available event properties
     #[command(alias = "properties")]
@@ -86,7 +848,7 @@ pub enum CalendarActions {
         #[arg(required = true)]
         title: String,
 
-        /// Event date (YYYY-MM-DD)
+        /// Event date (YYYY-MM-DD or e.g. "next tuesday", "in 3 days")
         #[arg(required = true)]
         date: String,
 
@@ -98,14 +860,25 @@ pub enum CalendarActions {
         #[arg(required = true)]
         end_time: String,
 
-        /// Calendar name
-        #[arg(default_value = "Work")]
-        calendar: String,
+        /// Calendar name (defaults to calendar.default, then the system default calendar)
+        calendar: Option<String>,
+
+        /// Account of the calendar when several accounts have one with that name
+        #[arg(long)]
+        account: Option<String>,
 
         /// Contact names to invite
         #[arg(long, value_delimiter = ',')]
         contacts: Option<Vec<String>>,
 
+        /// Contact groups to invite (DuckTape or Contacts.app groups)
+        #[arg(long, value_delimiter = ',')]
+        group: Option<Vec<String>>,
+
+        /// How to handle contact names that match several people
+        #[arg(long, value_enum)]
+        contact_strategy: Option<ContactStrategyArg>,
+
         /// Email addresses to invite
         #[arg(long, value_delimiter = ',')]
         email: Option<Vec<String>>,
@@ -114,45 +887,167 @@ pub enum CalendarActions {
         #[arg(long)]
         location: Option<String>,
 
+        /// Link to show with the event
+        #[arg(long)]
+        url: Option<String>,
+
+        /// Show the event as busy, free, tentative or unavailable
+        #[arg(long, value_enum)]
+        availability: Option<AvailabilityArg>,
+
         /// Event notes/description
         #[arg(long)]
         notes: Option<String>,
 
+        /// Tags to add as #hashtags to the notes (comma-separated, e.g. deep-work,clientX)
+        #[arg(long)]
+        tags: Option<String>,
+
+        /// Keep HTML in the notes as-is instead of converting it to plain text
+        #[arg(long)]
+        raw: bool,
+
         /// Create a Zoom meeting for this event
         #[arg(long)]
         zoom: bool,
 
+        /// Create an online meeting (zoom, teams, meet); defaults to calendar.meeting_provider
+        #[arg(long, num_args = 0..=1)]
+        meeting: Option<Option<MeetingArg>>,
+
         /// Recurrence frequency (daily, weekly, monthly, yearly)
         #[arg(long, alias = "recurring")]
         repeat: Option<RecurrenceFreq>,
 
         /// Recurrence interval (e.g., every 2 weeks)
-        #[arg(long)]
+        #[arg(long, requires = "repeat")]
         interval: Option<u32>,
 
-        /// End date for recurrence (YYYY-MM-DD)
-        #[arg(long)]
+        /// End date for recurrence (YYYY-MM-DD or e.g. "end of month")
+        #[arg(long, requires = "repeat", conflicts_with = "count")]
         until: Option<String>,
 
         /// Number of occurrences
-        #[arg(long)]
+        #[arg(long, requires = "repeat")]
         count: Option<u32>,
 
         /// Days of week (0=Sun, 1=Mon, etc.)
-        #[arg(long, value_delimiter = ',')]
+        #[arg(long, value_delimiter = ',', requires = "repeat")]
         days: Option<Vec<u8>>,
     },
 
-    /// Delete a calendar event
+    /// Delete a tracked calendar event
     #[command(alias = "remove")]
     Delete {
-        /// Event ID or title to delete
+        /// UID of the event, as `calendar show` prints it, or its exact title
         #[arg(required = true)]
         event_id: String,
 
-        /// Calendar name
-        #[arg(default_value = "Work")]
-        calendar: String,
+        /// Only look for the title in this calendar
+        calendar: Option<String>,
+    },
+
+    /// Change a tracked event
+    #[command(alias = "edit")]
+    Update {
+        /// UID of the event, its exact title, or @last for the item created or changed last
+        #[arg(required = true)]
+        event_id: String,
+
+        /// Only look for the title in this calendar
+        calendar: Option<String>,
+
+        /// New title
+        #[arg(long)]
+        title: Option<String>,
+
+        /// New date (YYYY-MM-DD or e.g. "next tuesday")
+        #[arg(long)]
+        date: Option<String>,
+
+        /// New start time (HH:MM); without --end-time the event keeps its length
+        #[arg(long)]
+        start_time: Option<String>,
+
+        /// New end time (HH:MM)
+        #[arg(long)]
+        end_time: Option<String>,
+
+        /// New location
+        #[arg(long)]
+        location: Option<String>,
+
+        /// Contact names to invite as well
+        #[arg(long, value_delimiter = ',')]
+        contacts: Option<Vec<String>>,
+
+        /// Email addresses to invite as well
+        #[arg(long, value_delimiter = ',')]
+        email: Option<Vec<String>>,
+    },
+
+    /// Show the details of a tracked event, such as its URL and availability
+    Show {
+        /// Event title, or part of it
+        #[arg(required = true)]
+        title: Vec<String>,
+    },
+
+    /// Browse events in an interactive week or month view
+    View {
+        /// Show a week (the default)
+        #[arg(long, conflicts_with = "month")]
+        week: bool,
+
+        /// Show a month
+        #[arg(long)]
+        month: bool,
+
+        /// Day to start on (today, next monday, YYYY-MM-DD)
+        #[arg(long)]
+        date: Option<String>,
+    },
+
+    /// Move every event in a day or range of days to another day, keeping their times
+    MoveAll {
+        /// Day or range the events are in (friday, tomorrow, next week, YYYY-MM-DD)
+        #[arg(long, required = true)]
+        on: String,
+
+        /// Day to move the first day's events to; later days follow
+        #[arg(long, required = true)]
+        to: String,
+
+        /// Only move events whose title contains this
+        #[arg(long)]
+        title: Option<String>,
+
+        /// Only move events in this calendar
+        #[arg(long)]
+        calendar: Option<String>,
+
+        /// Show the changes without applying them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Delete every event in a day or range of days
+    DeleteAll {
+        /// Day or range the events are in (friday, tomorrow, next week, YYYY-MM-DD)
+        #[arg(long, required = true)]
+        on: String,
+
+        /// Only delete events whose title contains this
+        #[arg(long)]
+        title: Option<String>,
+
+        /// Only delete events in this calendar
+        #[arg(long)]
+        calendar: Option<String>,
+
+        /// Show the events without deleting them
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Import events from a file
@@ -161,13 +1056,24 @@ pub enum CalendarActions {
         #[arg(required = true)]
         file: PathBuf,
 
-        /// Calendar name
-        #[arg(default_value = "Work")]
-        calendar: String,
+        /// Calendar name (defaults to calendar.default)
+        calendar: Option<String>,
 
         /// File format (ics, csv)
         #[arg(long, default_value = "ics")]
         format: String,
+
+        /// What to do with events that already exist
+        #[arg(long, value_enum, default_value = "skip")]
+        on_duplicate: DuplicateArg,
+
+        /// CSV columns to use, e.g. "Subject=title,Start Date=date,Start Time=start"
+        #[arg(long)]
+        map: Option<String>,
+
+        /// File with one Header=field column mapping per line
+        #[arg(long)]
+        map_file: Option<PathBuf>,
     },
 
     /// Set the default calendar
@@ -178,6 +1084,13 @@ pub enum CalendarActions {
     },
 }
 
+#[derive(Debug, Clone, ValueEnum)]
+pub enum DuplicateArg {
+    Skip,
+    Update,
+    Duplicate,
+}
+
 #[derive(Debug, Clone, ValueEnum)]
 pub enum RecurrenceFreq {
     Daily,
@@ -186,6 +1099,124 @@ pub enum RecurrenceFreq {
     Yearly,
 }
 
+#[derive(Debug, Clone, ValueEnum)]
+pub enum PriorityArg {
+    High,
+    Medium,
+    Low,
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum DueArg {
+    Today,
+    Overdue,
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum ReminderSortArg {
+    Due,
+    Priority,
+    Title,
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum LinkRelationArg {
+    RelatesTo,
+    BlockedBy,
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum OpenKindArg {
+    Event,
+    Note,
+    #[value(alias = "todo")]
+    Reminder,
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum MeetingArg {
+    Zoom,
+    Teams,
+    Meet,
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum AvailabilityArg {
+    Busy,
+    Free,
+    Tentative,
+    Unavailable,
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum ContactStrategyArg {
+    First,
+    All,
+    Ask,
+}
+
+impl From<DuplicateArg> for crate::calendar::OnDuplicate {
+    fn from(arg: DuplicateArg) -> Self {
+        match arg {
+            DuplicateArg::Skip => Self::Skip,
+            DuplicateArg::Update => Self::Update,
+            DuplicateArg::Duplicate => Self::Duplicate,
+        }
+    }
+}
+
+impl From<RecurrenceFreq> for crate::calendar::RecurrenceFrequency {
+    fn from(arg: RecurrenceFreq) -> Self {
+        match arg {
+            RecurrenceFreq::Daily => Self::Daily,
+            RecurrenceFreq::Weekly => Self::Weekly,
+            RecurrenceFreq::Monthly => Self::Monthly,
+            RecurrenceFreq::Yearly => Self::Yearly,
+        }
+    }
+}
+
+impl From<PriorityArg> for crate::todo::TodoPriority {
+    fn from(arg: PriorityArg) -> Self {
+        match arg {
+            PriorityArg::High => Self::High,
+            PriorityArg::Medium => Self::Medium,
+            PriorityArg::Low => Self::Low,
+        }
+    }
+}
+
+impl From<MeetingArg> for crate::config::MeetingService {
+    fn from(arg: MeetingArg) -> Self {
+        match arg {
+            MeetingArg::Zoom => Self::Zoom,
+            MeetingArg::Teams => Self::Teams,
+            MeetingArg::Meet => Self::Meet,
+        }
+    }
+}
+
+impl From<AvailabilityArg> for crate::calendar::EventAvailability {
+    fn from(arg: AvailabilityArg) -> Self {
+        match arg {
+            AvailabilityArg::Busy => Self::Busy,
+            AvailabilityArg::Free => Self::Free,
+            AvailabilityArg::Tentative => Self::Tentative,
+            AvailabilityArg::Unavailable => Self::Unavailable,
+        }
+    }
+}
+
+impl From<ContactStrategyArg> for crate::calendar::ContactStrategy {
+    fn from(arg: ContactStrategyArg) -> Self {
+        match arg {
+            ContactStrategyArg::First => Self::First,
+            ContactStrategyArg::All => Self::All,
+            ContactStrategyArg::Ask => Self::Ask,
+        }
+    }
+}
+
 #[derive(Debug, Subcommand)]
 pub enum TodoActions {
     /// List available reminder lists
@@ -195,6 +1226,22 @@ pub enum TodoActions {
     List {
         /// List name
         list: Option<String>,
+
+        /// Only open reminders due today or overdue
+        #[arg(long, value_enum)]
+        due: Option<DueArg>,
+
+        /// Only reminders completed in the last N days
+        #[arg(long, value_name = "DAYS", conflicts_with = "due")]
+        completed_within: Option<u32>,
+
+        /// Only reminders with this priority
+        #[arg(long)]
+        priority: Option<PriorityArg>,
+
+        /// Sort by due date, priority or title
+        #[arg(long, value_enum)]
+        sort: Option<ReminderSortArg>,
     },
 
     /// Create a new reminder
@@ -208,13 +1255,45 @@ pub enum TodoActions {
         #[arg(value_delimiter = ',')]
         lists: Vec<String>,
 
-        /// Set a reminder time
+        /// Reminder time (YYYY-MM-DD HH:MM or e.g. "tomorrow at 3pm"); also the due date by default
         #[arg(long)]
         remind: Option<String>,
 
+        /// Due date (YYYY-MM-DD for all day, YYYY-MM-DD HH:MM, or e.g. "friday at 5pm")
+        #[arg(long)]
+        due: Option<String>,
+
+        /// Priority
+        #[arg(long)]
+        priority: Option<PriorityArg>,
+
+        /// Repeat the reminder (daily, weekly, monthly, yearly)
+        #[arg(long)]
+        repeat: Option<RecurrenceFreq>,
+
+        /// Repeat interval (e.g., every 2 weeks)
+        #[arg(long, requires = "repeat")]
+        interval: Option<u32>,
+
+        /// Stop repeating after this date (YYYY-MM-DD or e.g. "end of year")
+        #[arg(long, requires = "repeat")]
+        until: Option<String>,
+
+        /// Stop repeating after this many occurrences
+        #[arg(long, requires = "repeat")]
+        count: Option<u32>,
+
         /// Notes for the reminder
         #[arg(long)]
         notes: Option<String>,
+
+        /// Tags to add as #hashtags to the notes (comma-separated)
+        #[arg(long)]
+        tags: Option<String>,
+
+        /// Keep HTML in the notes as-is instead of converting it to plain text
+        #[arg(long)]
+        raw: bool,
     },
 
     /// Mark a reminder as completed
@@ -239,6 +1318,43 @@ pub enum TodoActions {
         list: Option<String>,
     },
 
+    /// Show reminders as a Backlog/Doing/Done board
+    Board,
+
+    /// Move a reminder to a board column or list
+    Move {
+        /// Reminder title
+        #[arg(required = true)]
+        title: String,
+
+        /// Column (backlog, doing, done) or list name
+        #[arg(required = true)]
+        column: String,
+    },
+
+    /// Move the due date of every open reminder due on one day to another
+    Postpone {
+        /// Day the reminders are currently due (today, tomorrow, monday, YYYY-MM-DD)
+        #[arg(long, default_value = "today")]
+        due: String,
+
+        /// Day to move them to
+        #[arg(long, required = true)]
+        to: String,
+
+        /// Only postpone reminders in this list
+        #[arg(long)]
+        list: Option<String>,
+
+        /// Also postpone overdue reminders
+        #[arg(long)]
+        overdue: bool,
+
+        /// Show the changes without applying them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
     /// Set the default reminder list
     #[command(alias = "set-default")]
     SetList {
@@ -270,6 +1386,65 @@ pub enum NoteActions {
         /// Folder name
         #[arg(long)]
         folder: Option<String>,
+
+        /// Tags to add as #hashtags to the content (comma-separated)
+        #[arg(long)]
+        tags: Option<String>,
+
+        /// Render the content as Markdown
+        #[arg(long)]
+        markdown: bool,
+
+        /// Keep HTML in the content as-is instead of converting it to plain text
+        #[arg(long, conflicts_with = "markdown")]
+        raw: bool,
+    },
+
+    /// Append text to the end of a note
+    Append {
+        /// Note title
+        #[arg(required = true)]
+        title: String,
+
+        /// Text to append
+        #[arg(required = true, num_args = 1..)]
+        text: Vec<String>,
+
+        /// Folder name
+        #[arg(long)]
+        folder: Option<String>,
+
+        /// Render the text as Markdown
+        #[arg(long)]
+        markdown: bool,
+
+        /// Keep HTML in the text as-is instead of converting it to plain text
+        #[arg(long, conflicts_with = "markdown")]
+        raw: bool,
+    },
+
+    /// Replace the content of a note
+    #[command(alias = "update")]
+    Edit {
+        /// Note title
+        #[arg(required = true, num_args = 1.., value_delimiter = ' ')]
+        title: Vec<String>,
+
+        /// New content
+        #[arg(long, required = true)]
+        content: String,
+
+        /// Folder name
+        #[arg(long)]
+        folder: Option<String>,
+
+        /// Render the content as Markdown
+        #[arg(long)]
+        markdown: bool,
+
+        /// Keep HTML in the content as-is instead of converting it to plain text
+        #[arg(long, conflicts_with = "markdown")]
+        raw: bool,
     },
 
     /// Search for notes
@@ -295,6 +1470,46 @@ pub enum NoteActions {
         #[arg(long)]
         folder: Option<String>,
     },
+
+    /// Move a note to another folder
+    Move {
+        /// Note title
+        #[arg(required = true, num_args = 1.., value_delimiter = ' ')]
+        title: Vec<String>,
+
+        /// Folder to move the note to
+        #[arg(long, required = true)]
+        to: String,
+
+        /// Folder the note is in, when the title repeats
+        #[arg(long)]
+        folder: Option<String>,
+    },
+
+    /// List, create and delete note folders
+    Folders {
+        #[command(subcommand)]
+        action: Option<NoteFolderActions>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum NoteFolderActions {
+    /// List note folders
+    List,
+
+    /// Create a note folder
+    Create {
+        /// Folder name
+        name: String,
+    },
+
+    /// Delete a note folder and the notes in it
+    #[command(alias = "remove")]
+    Delete {
+        /// Folder name
+        name: String,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -316,6 +1531,47 @@ pub enum ConfigActions {
         #[arg(required = true)]
         value: String,
     },
+
+    /// Manage configuration profiles
+    Profile {
+        #[command(subcommand)]
+        action: ProfileActions,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ProfileActions {
+    /// Create a profile overriding some global settings
+    Create {
+        /// Profile name
+        name: String,
+
+        /// Default calendar
+        #[arg(long)]
+        calendar: Option<String>,
+
+        /// Default reminder list
+        #[arg(long)]
+        list: Option<String>,
+
+        /// Default notes folder
+        #[arg(long)]
+        folder: Option<String>,
+
+        /// Language model provider (grok or deepseek)
+        #[arg(long)]
+        provider: Option<String>,
+    },
+    /// Make a profile active ("none" returns to the global configuration)
+    Switch {
+        /// Profile name
+        name: String,
+    },
+    /// Show the profile in effect, or the settings of one profile
+    Show {
+        /// Profile name
+        name: Option<String>,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -340,6 +1596,53 @@ pub enum ContactActions {
         #[arg(required = true)]
         group_name: String,
     },
+
+    /// Add members to a contact group
+    Add {
+        /// Group name
+        #[arg(required = true)]
+        group_name: String,
+
+        /// Email addresses
+        #[arg(required = true, num_args = 1..)]
+        emails: Vec<String>,
+    },
+
+    /// Remove members from a contact group
+    Remove {
+        /// Group name
+        #[arg(required = true)]
+        group_name: String,
+
+        /// Email addresses
+        #[arg(required = true, num_args = 1..)]
+        emails: Vec<String>,
+    },
+
+    /// Rename a contact group
+    Rename {
+        /// Current group name
+        #[arg(required = true)]
+        group_name: String,
+
+        /// New group name
+        #[arg(required = true)]
+        new_name: String,
+    },
+
+    /// Delete a contact group
+    Delete {
+        /// Group name
+        #[arg(required = true)]
+        group_name: String,
+    },
+
+    /// Copy people from Contacts.app for fast lookups, only those changed since the last sync
+    Sync {
+        /// Read every person again
+        #[arg(long)]
+        full: bool,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -352,13 +1655,57 @@ pub enum UtilityActions {
 
     /// Display current date and time
     DateTime,
+
+    /// Convert a time between time zones, e.g. `tz 14:00 EST to CET on 2025-05-01`
+    Tz {
+        /// <time>[-<end>] <from-zone> to <to-zone> [on YYYY-MM-DD]
+        #[arg(required = true, num_args = 1.., allow_hyphen_values = true)]
+        expression: Vec<String>,
+
+        /// Print only the converted date and time(s), for use with `calendar create`
+        #[arg(long)]
+        plain: bool,
+    },
 }
 
 /// Convert a Cli object to CommandArgs for use with the command processor
 ///
 /// This function extracts relevant information from the Cli struct and
 /// converts it to a CommandArgs struct that can be used by the command processor.
+/// Command line arguments without the global options, which are applied before parsing input
+///
+/// Those are `--profile <name>`, the logging flags `-v`, `-q` and `--log-format <format>`, and
+/// `--output <format>` when it comes before the command.
+pub fn without_global_args(args: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let is_verbose =
+            arg.len() > 1 && arg.starts_with('-') && arg[1..].chars().all(|c| c == 'v');
+        let before_command = result.is_empty();
+        if arg == "--profile" || arg == "--log-format" || (before_command && arg == "--output") {
+            args.next();
+        } else if !(arg.starts_with("--profile=")
+            || (before_command && arg.starts_with("--output="))
+            || arg.starts_with("--log-format=")
+            || is_verbose
+            || matches!(arg.as_str(), "--verbose" | "-q" | "--quiet"))
+        {
+            result.push(arg);
+        }
+    }
+    result
+}
+
 pub fn convert_to_command_args(cli: &Cli) -> Option<CommandArgs> {
+    let mut args = command_args(cli)?;
+    if cli.yes {
+        args.flags.insert("yes".to_string(), None);
+    }
+    Some(args)
+}
+
+fn command_args(cli: &Cli) -> Option<CommandArgs> {
     match &cli.command {
         Some(cmd) => match cmd {
             Commands::Calendar { action } => {
@@ -366,8 +1713,11 @@ pub fn convert_to_command_args(cli: &Cli) -> Option<CommandArgs> {
                 let mut flags = HashMap::new();
 
                 match action {
-                    CalendarActions::List => {
+                    CalendarActions::List { refresh } => {
                         args.push("list".to_string());
+                        if *refresh {
+                            flags.insert("refresh".to_string(), None);
+                        }
                     }
                     CalendarActions::Props => {
                         args.push("props".to_string());
@@ -378,11 +1728,19 @@ pub fn convert_to_command_args(cli: &Cli) -> Option<CommandArgs> {
                         start_time,
                         end_time,
                         calendar,
+                        account,
                         contacts,
+                        group,
+                        contact_strategy,
                         email,
                         location,
+                        url,
+                        availability,
                         notes,
+                        tags,
+                        raw,
                         zoom,
+                        meeting,
                         repeat,
                         interval,
                         until,
@@ -394,17 +1752,38 @@ pub fn convert_to_command_args(cli: &Cli) -> Option<CommandArgs> {
                         args.push(date.clone());
                         args.push(start_time.clone());
                         args.push(end_time.clone());
-                        args.push(calendar.clone());
+                        args.extend(calendar.clone());
 
+                        if let Some(a) = account {
+                            flags.insert("account".to_string(), Some(a.clone()));
+                        }
                         if let Some(loc) = location {
                             flags.insert("location".to_string(), Some(loc.clone()));
                         }
+                        if let Some(u) = url {
+                            flags.insert("url".to_string(), Some(u.clone()));
+                        }
+                        if let Some(a) = availability {
+                            flags.insert("availability".to_string(), Some(format!("{:?}", a)));
+                        }
                         if let Some(n) = notes {
                             flags.insert("notes".to_string(), Some(n.clone()));
                         }
+                        if let Some(t) = tags {
+                            flags.insert("tags".to_string(), Some(t.clone()));
+                        }
+                        if *raw {
+                            flags.insert("raw".to_string(), None);
+                        }
                         if *zoom {
                             flags.insert("zoom".to_string(), Some("true".to_string()));
                         }
+                        if let Some(service) = meeting {
+                            flags.insert(
+                                "meeting".to_string(),
+                                service.as_ref().map(|s| format!("{:?}", s)),
+                            );
+                        }
                         if let Some(r) = repeat {
                             flags.insert("repeat".to_string(), Some(format!("{:?}", r)));
                         }
@@ -429,21 +1808,120 @@ pub fn convert_to_command_args(cli: &Cli) -> Option<CommandArgs> {
                             let contacts_str = c.join(",");
                             flags.insert("contacts".to_string(), Some(contacts_str));
                         }
-                        if let Some(e) = email {
-                            let email_str = e.join(",");
-                            flags.insert("email".to_string(), Some(email_str));
+                        if let Some(g) = group {
+                            flags.insert("group".to_string(), Some(g.join(",")));
+                        }
+                        if let Some(s) = contact_strategy {
+                            flags.insert("contact-strategy".to_string(), Some(format!("{:?}", s)));
+                        }
+                        if let Some(e) = email {
+                            let email_str = e.join(",");
+                            flags.insert("email".to_string(), Some(email_str));
+                        }
+                    }
+                    CalendarActions::Delete { event_id, calendar } => {
+                        args.push("delete".to_string());
+                        args.push(event_id.clone());
+                        args.extend(calendar.clone());
+                    }
+                    CalendarActions::Update {
+                        event_id,
+                        calendar,
+                        title,
+                        date,
+                        start_time,
+                        end_time,
+                        location,
+                        contacts,
+                        email,
+                    } => {
+                        args.push("update".to_string());
+                        args.push(event_id.clone());
+                        args.extend(calendar.clone());
+                        let options = [
+                            ("title", title),
+                            ("date", date),
+                            ("start-time", start_time),
+                            ("end-time", end_time),
+                            ("location", location),
+                        ];
+                        for (name, value) in options {
+                            if let Some(value) = value {
+                                flags.insert(name.to_string(), Some(value.clone()));
+                            }
+                        }
+                        if let Some(c) = contacts {
+                            flags.insert("contacts".to_string(), Some(c.join(",")));
+                        }
+                        if let Some(e) = email {
+                            flags.insert("email".to_string(), Some(e.join(",")));
+                        }
+                    }
+                    CalendarActions::Show { title } => {
+                        args.push("show".to_string());
+                        args.extend(title.iter().cloned());
+                    }
+                    CalendarActions::View { week: _, month, date } => {
+                        args.push("view".to_string());
+                        if *month {
+                            flags.insert("month".to_string(), None);
+                        }
+                        if let Some(d) = date {
+                            flags.insert("date".to_string(), Some(d.clone()));
+                        }
+                    }
+                    CalendarActions::MoveAll { on, to, title, calendar, dry_run } => {
+                        args.push("move-all".to_string());
+                        flags.insert("on".to_string(), Some(on.clone()));
+                        flags.insert("to".to_string(), Some(to.clone()));
+                        if let Some(t) = title {
+                            flags.insert("title".to_string(), Some(t.clone()));
+                        }
+                        if let Some(c) = calendar {
+                            flags.insert("calendar".to_string(), Some(c.clone()));
+                        }
+                        if *dry_run {
+                            flags.insert("dry-run".to_string(), None);
                         }
                     }
-                    CalendarActions::Delete { event_id, calendar } => {
-                        args.push("delete".to_string());
-                        args.push(event_id.clone());
-                        args.push(calendar.clone());
+                    CalendarActions::DeleteAll { on, title, calendar, dry_run } => {
+                        args.push("delete-all".to_string());
+                        flags.insert("on".to_string(), Some(on.clone()));
+                        if let Some(t) = title {
+                            flags.insert("title".to_string(), Some(t.clone()));
+                        }
+                        if let Some(c) = calendar {
+                            flags.insert("calendar".to_string(), Some(c.clone()));
+                        }
+                        if *dry_run {
+                            flags.insert("dry-run".to_string(), None);
+                        }
                     }
-                    CalendarActions::Import { file, calendar, format } => {
+                    CalendarActions::Import {
+                        file,
+                        calendar,
+                        format,
+                        on_duplicate,
+                        map,
+                        map_file,
+                    } => {
                         args.push("import".to_string());
                         args.push(file.to_string_lossy().to_string());
-                        args.push(calendar.clone());
+                        args.extend(calendar.clone());
                         flags.insert("format".to_string(), Some(format.clone()));
+                        flags.insert(
+                            "on-duplicate".to_string(),
+                            Some(format!("{:?}", on_duplicate).to_lowercase()),
+                        );
+                        if let Some(m) = map {
+                            flags.insert("map".to_string(), Some(m.clone()));
+                        }
+                        if let Some(f) = map_file {
+                            flags.insert(
+                                "map-file".to_string(),
+                                Some(f.to_string_lossy().to_string()),
+                            );
+                        }
                     }
                     CalendarActions::SetDefault { calendar } => {
                         args.push("set-default".to_string());
@@ -461,13 +1939,45 @@ pub fn convert_to_command_args(cli: &Cli) -> Option<CommandArgs> {
                     TodoActions::Lists => {
                         args.push("lists".to_string());
                     }
-                    TodoActions::List { list } => {
+                    TodoActions::List { list, due, completed_within, priority, sort } => {
                         args.push("list".to_string());
                         if let Some(l) = list {
                             args.push(l.clone());
                         }
+                        if let Some(d) = due {
+                            flags
+                                .insert("due".to_string(), Some(format!("{:?}", d).to_lowercase()));
+                        }
+                        if let Some(days) = completed_within {
+                            flags.insert("completed-within".to_string(), Some(days.to_string()));
+                        }
+                        if let Some(p) = priority {
+                            flags.insert(
+                                "priority".to_string(),
+                                Some(format!("{:?}", p).to_lowercase()),
+                            );
+                        }
+                        if let Some(s) = sort {
+                            flags.insert(
+                                "sort".to_string(),
+                                Some(format!("{:?}", s).to_lowercase()),
+                            );
+                        }
                     }
-                    TodoActions::Create { title, lists, remind, notes } => {
+                    TodoActions::Create {
+                        title,
+                        lists,
+                        remind,
+                        due,
+                        priority,
+                        repeat,
+                        interval,
+                        until,
+                        count,
+                        notes,
+                        tags,
+                        raw,
+                    } => {
                         args.push("create".to_string());
                         args.push(title.clone());
                         for list in lists {
@@ -476,9 +1986,33 @@ pub fn convert_to_command_args(cli: &Cli) -> Option<CommandArgs> {
                         if let Some(r) = remind {
                             flags.insert("remind".to_string(), Some(r.clone()));
                         }
+                        if let Some(d) = due {
+                            flags.insert("due".to_string(), Some(d.clone()));
+                        }
+                        if let Some(p) = priority {
+                            flags.insert("priority".to_string(), Some(format!("{:?}", p)));
+                        }
+                        if let Some(r) = repeat {
+                            flags.insert("repeat".to_string(), Some(format!("{:?}", r)));
+                        }
+                        if let Some(i) = interval {
+                            flags.insert("interval".to_string(), Some(i.to_string()));
+                        }
+                        if let Some(u) = until {
+                            flags.insert("until".to_string(), Some(u.clone()));
+                        }
+                        if let Some(c) = count {
+                            flags.insert("count".to_string(), Some(c.to_string()));
+                        }
                         if let Some(n) = notes {
                             flags.insert("notes".to_string(), Some(n.clone()));
                         }
+                        if let Some(t) = tags {
+                            flags.insert("tags".to_string(), Some(t.clone()));
+                        }
+                        if *raw {
+                            flags.insert("raw".to_string(), None);
+                        }
                     }
                     TodoActions::Complete { reminder_id, list } => {
                         args.push("complete".to_string());
@@ -494,6 +2028,28 @@ pub fn convert_to_command_args(cli: &Cli) -> Option<CommandArgs> {
                             args.push(l.clone());
                         }
                     }
+                    TodoActions::Board => {
+                        args.push("board".to_string());
+                    }
+                    TodoActions::Move { title, column } => {
+                        args.push("move".to_string());
+                        args.push(title.clone());
+                        args.push(column.clone());
+                    }
+                    TodoActions::Postpone { due, to, list, overdue, dry_run } => {
+                        args.push("postpone".to_string());
+                        flags.insert("due".to_string(), Some(due.clone()));
+                        flags.insert("to".to_string(), Some(to.clone()));
+                        if let Some(l) = list {
+                            flags.insert("list".to_string(), Some(l.clone()));
+                        }
+                        if *overdue {
+                            flags.insert("overdue".to_string(), None);
+                        }
+                        if *dry_run {
+                            flags.insert("dry-run".to_string(), None);
+                        }
+                    }
                     TodoActions::SetList { list } => {
                         args.push("set-list".to_string());
                         args.push(list.clone());
@@ -513,7 +2069,7 @@ pub fn convert_to_command_args(cli: &Cli) -> Option<CommandArgs> {
                             args.push(f.clone());
                         }
                     }
-                    NoteActions::Create { title, content, folder } => {
+                    NoteActions::Create { title, content, folder, tags, markdown, raw } => {
                         args.push("create".to_string());
                         let title_str = title.join(" ");
                         args.push(title_str);
@@ -524,6 +2080,45 @@ pub fn convert_to_command_args(cli: &Cli) -> Option<CommandArgs> {
                         if let Some(content_val) = content {
                             flags.insert("content".to_string(), Some(content_val.clone()));
                         }
+                        if let Some(t) = tags {
+                            flags.insert("tags".to_string(), Some(t.clone()));
+                        }
+                        if *markdown {
+                            flags.insert("markdown".to_string(), None);
+                        }
+                        if *raw {
+                            flags.insert("raw".to_string(), None);
+                        }
+                    }
+                    NoteActions::Append { title, text, folder, markdown, raw } => {
+                        args.push("append".to_string());
+                        args.push(title.clone());
+                        args.push(text.join(" "));
+
+                        if let Some(f) = folder {
+                            flags.insert("folder".to_string(), Some(f.clone()));
+                        }
+                        if *markdown {
+                            flags.insert("markdown".to_string(), None);
+                        }
+                        if *raw {
+                            flags.insert("raw".to_string(), None);
+                        }
+                    }
+                    NoteActions::Edit { title, content, folder, markdown, raw } => {
+                        args.push("edit".to_string());
+                        args.push(title.join(" "));
+                        flags.insert("content".to_string(), Some(content.clone()));
+
+                        if let Some(f) = folder {
+                            flags.insert("folder".to_string(), Some(f.clone()));
+                        }
+                        if *markdown {
+                            flags.insert("markdown".to_string(), None);
+                        }
+                        if *raw {
+                            flags.insert("raw".to_string(), None);
+                        }
                     }
                     NoteActions::Search { query, folder } => {
                         args.push("search".to_string());
@@ -543,13 +2138,35 @@ pub fn convert_to_command_args(cli: &Cli) -> Option<CommandArgs> {
                             flags.insert("folder".to_string(), Some(f.clone()));
                         }
                     }
+                    NoteActions::Move { title, to, folder } => {
+                        args.push("move".to_string());
+                        args.push(title.join(" "));
+                        flags.insert("to".to_string(), Some(to.clone()));
+                        if let Some(f) = folder {
+                            flags.insert("folder".to_string(), Some(f.clone()));
+                        }
+                    }
+                    NoteActions::Folders { action } => {
+                        args.push("folders".to_string());
+                        match action {
+                            None | Some(NoteFolderActions::List) => {}
+                            Some(NoteFolderActions::Create { name }) => {
+                                args.push("create".to_string());
+                                args.push(name.clone());
+                            }
+                            Some(NoteFolderActions::Delete { name }) => {
+                                args.push("delete".to_string());
+                                args.push(name.clone());
+                            }
+                        }
+                    }
                 }
 
                 Some(CommandArgs { command: "note".to_string(), args, flags })
             }
             Commands::Config { action } => {
                 let mut args = Vec::new();
-                let flags = HashMap::new();
+                let mut flags = HashMap::new();
 
                 match action {
                     ConfigActions::Show { key } => {
@@ -563,13 +2180,42 @@ pub fn convert_to_command_args(cli: &Cli) -> Option<CommandArgs> {
                         args.push(key.clone());
                         args.push(value.clone());
                     }
+                    ConfigActions::Profile { action } => {
+                        args.push("profile".to_string());
+                        match action {
+                            ProfileActions::Create { name, calendar, list, folder, provider } => {
+                                args.push("create".to_string());
+                                args.push(name.clone());
+                                for (key, value) in [
+                                    ("calendar", calendar),
+                                    ("list", list),
+                                    ("folder", folder),
+                                    ("provider", provider),
+                                ] {
+                                    if let Some(value) = value {
+                                        flags.insert(key.to_string(), Some(value.clone()));
+                                    }
+                                }
+                            }
+                            ProfileActions::Switch { name } => {
+                                args.push("switch".to_string());
+                                args.push(name.clone());
+                            }
+                            ProfileActions::Show { name } => {
+                                args.push("show".to_string());
+                                if let Some(name) = name {
+                                    args.push(name.clone());
+                                }
+                            }
+                        }
+                    }
                 }
 
                 Some(CommandArgs { command: "config".to_string(), args, flags })
             }
             Commands::Contact { action } => {
                 let mut args = Vec::new();
-                let flags = HashMap::new();
+                let mut flags = HashMap::new();
 
                 match action {
                     ContactActions::List => {
@@ -586,13 +2232,38 @@ pub fn convert_to_command_args(cli: &Cli) -> Option<CommandArgs> {
                         args.push("show".to_string());
                         args.push(group_name.clone());
                     }
+                    ContactActions::Add { group_name, emails } => {
+                        args.push("add".to_string());
+                        args.push(group_name.clone());
+                        args.extend(emails.iter().cloned());
+                    }
+                    ContactActions::Remove { group_name, emails } => {
+                        args.push("remove".to_string());
+                        args.push(group_name.clone());
+                        args.extend(emails.iter().cloned());
+                    }
+                    ContactActions::Rename { group_name, new_name } => {
+                        args.push("rename".to_string());
+                        args.push(group_name.clone());
+                        args.push(new_name.clone());
+                    }
+                    ContactActions::Delete { group_name } => {
+                        args.push("delete".to_string());
+                        args.push(group_name.clone());
+                    }
+                    ContactActions::Sync { full } => {
+                        args.push("sync".to_string());
+                        if *full {
+                            flags.insert("full".to_string(), None);
+                        }
+                    }
                 }
 
                 Some(CommandArgs { command: "contact".to_string(), args, flags })
             }
             Commands::Utility { action } => {
                 let mut args = Vec::new();
-                let flags = HashMap::new();
+                let mut flags = HashMap::new();
 
                 match action {
                     UtilityActions::Date => {
@@ -604,10 +2275,545 @@ pub fn convert_to_command_args(cli: &Cli) -> Option<CommandArgs> {
                     UtilityActions::DateTime => {
                         args.push("datetime".to_string());
                     }
+                    UtilityActions::Tz { expression, plain } => {
+                        args.push("tz".to_string());
+                        args.extend(expression.iter().cloned());
+                        if *plain {
+                            flags.insert("plain".to_string(), None);
+                        }
+                    }
                 }
 
                 Some(CommandArgs { command: "utility".to_string(), args, flags })
             }
+            Commands::Adopt { calendar, from } => {
+                let mut flags = HashMap::new();
+                if let Some(c) = calendar {
+                    flags.insert("calendar".to_string(), Some(c.clone()));
+                }
+                if let Some(f) = from {
+                    flags.insert("from".to_string(), Some(f.clone()));
+                }
+
+                Some(CommandArgs { command: "adopt".to_string(), args: Vec::new(), flags })
+            }
+            Commands::Link { from, to, relation } => {
+                let mut flags = HashMap::new();
+                let relation = match relation {
+                    LinkRelationArg::RelatesTo => "relates-to",
+                    LinkRelationArg::BlockedBy => "blocked-by",
+                };
+                flags.insert("relation".to_string(), Some(relation.to_string()));
+
+                Some(CommandArgs {
+                    command: "link".to_string(),
+                    args: vec![from.clone(), to.clone()],
+                    flags,
+                })
+            }
+            Commands::Unlink { from, to } => Some(CommandArgs {
+                command: "unlink".to_string(),
+                args: vec![from.clone(), to.clone()],
+                flags: HashMap::new(),
+            }),
+            Commands::Links => Some(CommandArgs {
+                command: "links".to_string(),
+                args: Vec::new(),
+                flags: HashMap::new(),
+            }),
+            Commands::Agenda { date, calendar } => {
+                let mut flags = HashMap::new();
+                if let Some(c) = calendar {
+                    flags.insert("calendar".to_string(), Some(c.clone()));
+                }
+
+                Some(CommandArgs {
+                    command: "agenda".to_string(),
+                    args: date.iter().cloned().collect(),
+                    flags,
+                })
+            }
+            Commands::Open { kind, title } => {
+                let kind = match kind {
+                    OpenKindArg::Event => "event",
+                    OpenKindArg::Note => "note",
+                    OpenKindArg::Reminder => "reminder",
+                };
+                let mut args = vec![kind.to_string()];
+                args.extend(title.iter().cloned());
+
+                Some(CommandArgs { command: "open".to_string(), args, flags: HashMap::new() })
+            }
+            Commands::List { tag, kind } => {
+                let mut flags = HashMap::new();
+                flags.insert("tag".to_string(), Some(tag.clone()));
+                if let Some(kind) = kind {
+                    let kind = match kind {
+                        OpenKindArg::Event => "event",
+                        OpenKindArg::Note => "note",
+                        OpenKindArg::Reminder => "reminder",
+                    };
+                    flags.insert("kind".to_string(), Some(kind.to_string()));
+                }
+
+                Some(CommandArgs { command: "list".to_string(), args: Vec::new(), flags })
+            }
+            Commands::Quick { text, calendar, dry_run } => {
+                let mut flags = HashMap::new();
+                if let Some(c) = calendar {
+                    flags.insert("calendar".to_string(), Some(c.clone()));
+                }
+                if *dry_run {
+                    flags.insert("dry-run".to_string(), None);
+                }
+
+                Some(CommandArgs { command: "quick".to_string(), args: text.clone(), flags })
+            }
+            Commands::Search { query, past } => {
+                let mut flags = HashMap::new();
+                if *past {
+                    flags.insert("past".to_string(), None);
+                }
+
+                Some(CommandArgs { command: "search".to_string(), args: query.clone(), flags })
+            }
+            Commands::Raycast { action } => {
+                let mut flags = HashMap::new();
+                let args = match action {
+                    RaycastActions::Agenda { date, calendar } => {
+                        if let Some(c) = calendar {
+                            flags.insert("calendar".to_string(), Some(c.clone()));
+                        }
+                        std::iter::once("agenda".to_string()).chain(date.clone()).collect()
+                    }
+                    RaycastActions::Search { query, past } => {
+                        if *past {
+                            flags.insert("past".to_string(), None);
+                        }
+                        std::iter::once("search".to_string()).chain(query.clone()).collect()
+                    }
+                    RaycastActions::Create { text, calendar } => {
+                        if let Some(c) = calendar {
+                            flags.insert("calendar".to_string(), Some(c.clone()));
+                        }
+                        std::iter::once("create".to_string()).chain(text.clone()).collect()
+                    }
+                };
+
+                Some(CommandArgs { command: "raycast".to_string(), args, flags })
+            }
+            Commands::Redo { dry_run } => {
+                let mut flags = HashMap::new();
+                if *dry_run {
+                    flags.insert("dry-run".to_string(), None);
+                }
+
+                Some(CommandArgs { command: "redo".to_string(), args: Vec::new(), flags })
+            }
+            Commands::EditLast { set, unset, date, time, dry_run } => {
+                let mut flags = HashMap::new();
+                if !unset.is_empty() {
+                    flags.insert("unset".to_string(), Some(unset.join(",")));
+                }
+                if let Some(d) = date {
+                    flags.insert("date".to_string(), Some(d.clone()));
+                }
+                if let Some(t) = time {
+                    flags.insert("time".to_string(), Some(t.clone()));
+                }
+                if *dry_run {
+                    flags.insert("dry-run".to_string(), None);
+                }
+
+                Some(CommandArgs { command: "edit-last".to_string(), args: set.clone(), flags })
+            }
+            Commands::Attendees { action } => {
+                let mut flags = HashMap::new();
+                let (action, title, date, calendar) = match action {
+                    AttendeeActions::List { title, date, calendar } => {
+                        ("list", title, date, calendar)
+                    }
+                    AttendeeActions::Resend { title, date, calendar, all, mail } => {
+                        if *all {
+                            flags.insert("all".to_string(), None);
+                        }
+                        if *mail {
+                            flags.insert("mail".to_string(), None);
+                        }
+                        ("resend", title, date, calendar)
+                    }
+                };
+                if let Some(d) = date {
+                    flags.insert("date".to_string(), Some(d.clone()));
+                }
+                if let Some(c) = calendar {
+                    flags.insert("calendar".to_string(), Some(c.clone()));
+                }
+
+                Some(CommandArgs {
+                    command: "attendees".to_string(),
+                    args: vec![action.to_string(), title.clone()],
+                    flags,
+                })
+            }
+            Commands::Pack { action } => {
+                let mut args = Vec::new();
+                let mut flags = HashMap::new();
+
+                match action {
+                    PackActions::Export { file, name, description } => {
+                        args.push("export".to_string());
+                        args.push(file.clone());
+                        if let Some(n) = name {
+                            flags.insert("name".to_string(), Some(n.clone()));
+                        }
+                        if let Some(d) = description {
+                            flags.insert("description".to_string(), Some(d.clone()));
+                        }
+                    }
+                    PackActions::Install { source, on_conflict } => {
+                        args.push("install".to_string());
+                        args.push(source.clone());
+                        flags.insert(
+                            "on-conflict".to_string(),
+                            Some(format!("{:?}", on_conflict).to_lowercase()),
+                        );
+                    }
+                    PackActions::List => {
+                        args.push("list".to_string());
+                    }
+                }
+
+                Some(CommandArgs { command: "pack".to_string(), args, flags })
+            }
+            Commands::Cache { action } => {
+                let args = match action {
+                    CacheActions::Clear => vec!["clear".to_string()],
+                };
+                Some(CommandArgs { command: "cache".to_string(), args, flags: HashMap::new() })
+            }
+            Commands::Usage { action } => {
+                let mut flags = HashMap::new();
+                let args = match action {
+                    UsageActions::Show { month } => {
+                        if let Some(m) = month {
+                            flags.insert("month".to_string(), Some(m.clone()));
+                        }
+                        vec!["show".to_string()]
+                    }
+                };
+                Some(CommandArgs { command: "usage".to_string(), args, flags })
+            }
+            Commands::Nlp { action } => {
+                let mut flags = HashMap::new();
+                let args = match action {
+                    NlpActions::Eval { fixtures, provider, min_accuracy, verbose } => {
+                        if let Some(p) = provider {
+                            flags.insert("provider".to_string(), Some(p.clone()));
+                        }
+                        if let Some(m) = min_accuracy {
+                            flags.insert("min-accuracy".to_string(), Some(m.to_string()));
+                        }
+                        if *verbose {
+                            flags.insert("verbose".to_string(), None);
+                        }
+                        vec!["eval".to_string(), fixtures.to_string_lossy().to_string()]
+                    }
+                };
+                Some(CommandArgs { command: "nlp".to_string(), args, flags })
+            }
+            Commands::Doctor => Some(CommandArgs {
+                command: "doctor".to_string(),
+                args: vec![],
+                flags: HashMap::new(),
+            }),
+            Commands::Setup => Some(CommandArgs {
+                command: "setup".to_string(),
+                args: vec![],
+                flags: HashMap::new(),
+            }),
+            Commands::Keys { action } => {
+                let mut flags = HashMap::new();
+                let args = match action {
+                    KeysActions::Set { service } => vec!["set".to_string(), service.clone()],
+                    KeysActions::List => vec!["list".to_string()],
+                    KeysActions::Delete { service } => {
+                        vec!["delete".to_string(), service.clone()]
+                    }
+                    KeysActions::Migrate { env_file } => {
+                        flags.insert("env-file".to_string(), Some(env_file.clone()));
+                        vec!["migrate".to_string()]
+                    }
+                };
+                Some(CommandArgs { command: "keys".to_string(), args, flags })
+            }
+            Commands::Shortcut { action, params, stdin, url } => {
+                let mut flags = HashMap::new();
+                if *stdin {
+                    flags.insert("stdin".to_string(), None);
+                }
+                if let Some(url) = url {
+                    flags.insert("url".to_string(), Some(url.clone()));
+                }
+                Some(CommandArgs {
+                    command: "shortcut".to_string(),
+                    args: action.iter().chain(params).cloned().collect(),
+                    flags,
+                })
+            }
+            Commands::State { action } => {
+                let args = match action {
+                    StateActions::Export { file } => std::iter::once("export".to_string())
+                        .chain(file.as_ref().map(|f| f.to_string_lossy().to_string()))
+                        .collect(),
+                    StateActions::Import { file } => {
+                        vec!["import".to_string(), file.to_string_lossy().to_string()]
+                    }
+                };
+                Some(CommandArgs { command: "state".to_string(), args, flags: HashMap::new() })
+            }
+            Commands::Audit { action } => {
+                let mut flags = HashMap::new();
+                let args = match action {
+                    AuditActions::Show { since, limit } => {
+                        if let Some(since) = since {
+                            flags.insert("since".to_string(), Some(since.clone()));
+                        }
+                        if let Some(limit) = limit {
+                            flags.insert("limit".to_string(), Some(limit.to_string()));
+                        }
+                        vec!["show".to_string()]
+                    }
+                };
+                Some(CommandArgs { command: "audit".to_string(), args, flags })
+            }
+            Commands::Report { action } => {
+                let mut flags = HashMap::new();
+                let args = match action {
+                    ReportActions::Time {
+                        group_by,
+                        last,
+                        from,
+                        to,
+                        calendar,
+                        keyword,
+                        tag,
+                        format,
+                        output,
+                    } => {
+                        flags.insert(
+                            "group-by".to_string(),
+                            Some(format!("{:?}", group_by).to_lowercase()),
+                        );
+                        flags.insert(
+                            "format".to_string(),
+                            Some(format!("{:?}", format).to_lowercase()),
+                        );
+                        for (name, value) in [
+                            ("last", last),
+                            ("from", from),
+                            ("to", to),
+                            ("calendar", calendar),
+                            ("keyword", keyword),
+                            ("tag", tag),
+                        ] {
+                            if let Some(value) = value {
+                                flags.insert(name.to_string(), Some(value.clone()));
+                            }
+                        }
+                        if let Some(output) = output {
+                            flags.insert(
+                                "output".to_string(),
+                                Some(output.to_string_lossy().to_string()),
+                            );
+                        }
+                        vec!["time".to_string()]
+                    }
+                };
+                Some(CommandArgs { command: "report".to_string(), args, flags })
+            }
+            Commands::Review { action } => {
+                let mut flags = HashMap::new();
+                let args = match action {
+                    ReviewActions::Weekly { folder, no_summary, dry_run } => {
+                        if let Some(folder) = folder {
+                            flags.insert("folder".to_string(), Some(folder.clone()));
+                        }
+                        if *no_summary {
+                            flags.insert("no-summary".to_string(), None);
+                        }
+                        if *dry_run {
+                            flags.insert("dry-run".to_string(), None);
+                        }
+                        vec!["weekly".to_string()]
+                    }
+                };
+                Some(CommandArgs { command: "review".to_string(), args, flags })
+            }
+            Commands::Focus { action } => {
+                let mut flags = HashMap::new();
+                let args = match action {
+                    FocusActions::Start { duration, title, calendar, dnd } => {
+                        if let Some(calendar) = calendar {
+                            flags.insert("calendar".to_string(), Some(calendar.clone()));
+                        }
+                        if *dnd {
+                            flags.insert("dnd".to_string(), None);
+                        }
+                        ["start".to_string(), duration.clone()]
+                            .into_iter()
+                            .chain(title.iter().cloned())
+                            .collect()
+                    }
+                    FocusActions::Stats { since } => {
+                        if let Some(since) = since {
+                            flags.insert("since".to_string(), Some(since.clone()));
+                        }
+                        vec!["stats".to_string()]
+                    }
+                };
+                Some(CommandArgs { command: "focus".to_string(), args, flags })
+            }
+            Commands::Birthdays { action } => {
+                let mut flags = HashMap::new();
+                let args = match action {
+                    BirthdaysActions::Sync { calendar, remind_days, dry_run } => {
+                        if let Some(calendar) = calendar {
+                            flags.insert("calendar".to_string(), Some(calendar.clone()));
+                        }
+                        if let Some(days) = remind_days {
+                            flags.insert("remind-days".to_string(), Some(days.to_string()));
+                        }
+                        if *dry_run {
+                            flags.insert("dry-run".to_string(), None);
+                        }
+                        vec!["sync".to_string()]
+                    }
+                };
+                Some(CommandArgs { command: "birthdays".to_string(), args, flags })
+            }
+            Commands::Holidays { action } => {
+                let mut flags = HashMap::new();
+                let args = match action {
+                    HolidaysActions::List { year, country } => {
+                        if let Some(country) = country {
+                            flags.insert("country".to_string(), Some(country.clone()));
+                        }
+                        std::iter::once("list".to_string())
+                            .chain(year.map(|year| year.to_string()))
+                            .collect()
+                    }
+                };
+                Some(CommandArgs { command: "holidays".to_string(), args, flags })
+            }
+            Commands::Schedule { action } => {
+                let mut flags = HashMap::new();
+                let args = match action {
+                    ScheduleActions::Add { command, at } => {
+                        flags.insert("at".to_string(), Some(at.clone()));
+                        vec!["add".to_string(), command.clone()]
+                    }
+                    ScheduleActions::List => vec!["list".to_string()],
+                    ScheduleActions::Remove { id } => vec!["remove".to_string(), id.to_string()],
+                    ScheduleActions::Run => vec!["run".to_string()],
+                };
+                Some(CommandArgs { command: "schedule".to_string(), args, flags })
+            }
+            Commands::Watch { interval, days, calendar, notify } => {
+                let mut flags = HashMap::new();
+                flags.insert("interval".to_string(), Some(interval.to_string()));
+                flags.insert("days".to_string(), Some(days.to_string()));
+                if let Some(c) = calendar {
+                    flags.insert("calendar".to_string(), Some(c.clone()));
+                }
+                if *notify {
+                    flags.insert("notify".to_string(), None);
+                }
+
+                Some(CommandArgs { command: "watch".to_string(), args: Vec::new(), flags })
+            }
+            Commands::Sync { days, adopt, dry_run } => {
+                let mut flags = HashMap::new();
+                flags.insert("days".to_string(), Some(days.to_string()));
+                if *adopt {
+                    flags.insert("adopt".to_string(), None);
+                }
+                if *dry_run {
+                    flags.insert("dry-run".to_string(), None);
+                }
+
+                Some(CommandArgs { command: "sync".to_string(), args: Vec::new(), flags })
+            }
+            Commands::Feeds { action } => {
+                let mut flags = HashMap::new();
+                let args = match action {
+                    FeedsActions::List => vec!["list".to_string()],
+                    FeedsActions::Add { name, calendars } => {
+                        if !calendars.is_empty() {
+                            flags.insert("calendar".to_string(), Some(calendars.join(",")));
+                        }
+                        vec!["add".to_string(), name.clone()]
+                    }
+                    FeedsActions::Rotate { name } => vec!["rotate".to_string(), name.clone()],
+                    FeedsActions::Remove { name } => vec!["remove".to_string(), name.clone()],
+                };
+                Some(CommandArgs { command: "feeds".to_string(), args, flags })
+            }
+            Commands::Ingest { action } => {
+                let mut flags = HashMap::new();
+                let args = match action {
+                    IngestActions::Email { file, create, calendar, no_attendees } => {
+                        if *create {
+                            flags.insert("create".to_string(), None);
+                        }
+                        if let Some(calendar) = calendar {
+                            flags.insert("calendar".to_string(), Some(calendar.clone()));
+                        }
+                        if *no_attendees {
+                            flags.insert("no-attendees".to_string(), None);
+                        }
+                        std::iter::once("email".to_string())
+                            .chain(file.as_ref().map(|f| f.to_string_lossy().to_string()))
+                            .collect()
+                    }
+                    IngestActions::Itinerary { file, create, calendar, checkin_hours } => {
+                        if *create {
+                            flags.insert("create".to_string(), None);
+                        }
+                        if let Some(calendar) = calendar {
+                            flags.insert("calendar".to_string(), Some(calendar.clone()));
+                        }
+                        flags.insert("checkin-hours".to_string(), Some(checkin_hours.to_string()));
+                        std::iter::once("itinerary".to_string())
+                            .chain(file.as_ref().map(|f| f.to_string_lossy().to_string()))
+                            .collect()
+                    }
+                };
+                Some(CommandArgs { command: "ingest".to_string(), args, flags })
+            }
+            Commands::Webhooks { action } => {
+                let args = match action {
+                    WebhooksActions::List => vec!["list".to_string()],
+                    WebhooksActions::Add { url } => vec!["add".to_string(), url.clone()],
+                    WebhooksActions::Remove { url } => vec!["remove".to_string(), url.clone()],
+                    WebhooksActions::Test { url } => {
+                        std::iter::once("test".to_string()).chain(url.clone()).collect()
+                    }
+                };
+                Some(CommandArgs { command: "webhooks".to_string(), args, flags: HashMap::new() })
+            }
+            Commands::Help { command } => Some(CommandArgs {
+                command: "help".to_string(),
+                args: command.clone(),
+                flags: HashMap::new(),
+            }),
+            Commands::Man { out_dir } => {
+                let mut flags = HashMap::new();
+                if let Some(dir) = out_dir {
+                    flags.insert("out-dir".to_string(), Some(dir.to_string_lossy().to_string()));
+                }
+                Some(CommandArgs { command: "man".to_string(), args: vec![], flags })
+            }
         },
         None => {
             // No command specified, enter interactive mode
@@ -615,3 +2821,76 @@ pub fn convert_to_command_args(cli: &Cli) -> Option<CommandArgs> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_without_global_args() {
+        let args = ["--profile", "work", "calendar", "list", "--profile=home"];
+        assert_eq!(
+            without_global_args(args.iter().map(|s| s.to_string())),
+            vec!["calendar", "list"]
+        );
+        let args = ["-vv", "--log-format", "json", "todo", "list", "-q", "--log-format=text"];
+        assert_eq!(without_global_args(args.iter().map(|s| s.to_string())), vec!["todo", "list"]);
+        let args = ["--output", "alfred", "agenda"];
+        assert_eq!(without_global_args(args.iter().map(|s| s.to_string())), vec!["agenda"]);
+        // After the command, --output is the command's own
+        let args = ["report", "time", "--output", "time.csv"];
+        assert_eq!(without_global_args(args.iter().map(|s| s.to_string())).len(), 4);
+        // A lone dash is an argument, not a flag
+        assert_eq!(without_global_args(["-".to_string()]), vec!["-"]);
+    }
+
+    #[test]
+    fn test_logging_flags() {
+        let cli =
+            Cli::try_parse_from(["ducktape", "-vv", "--log-format", "json", "calendar", "list"])
+                .unwrap();
+        let options = cli.log_options();
+        assert_eq!((options.verbose, options.quiet), (2, false));
+        assert_eq!(options.format, Some(LogFormat::Json));
+        assert!(Cli::try_parse_from(["ducktape", "-v", "-q"]).is_err());
+    }
+
+    #[test]
+    fn test_launcher_commands() {
+        let cli =
+            Cli::try_parse_from(["ducktape", "--output", "alfred", "search", "design", "review"])
+                .unwrap();
+        assert_eq!(cli.output, Some(OutputFormat::Alfred));
+        let args = convert_to_command_args(&cli).unwrap();
+        assert_eq!((args.command.as_str(), args.args.len()), ("search", 2));
+
+        let cli = Cli::try_parse_from([
+            "ducktape",
+            "raycast",
+            "create",
+            "Lunch",
+            "tomorrow",
+            "12:30",
+            "--calendar",
+            "Home",
+        ])
+        .unwrap();
+        let args = convert_to_command_args(&cli).unwrap();
+        assert_eq!(args.command, "raycast");
+        assert_eq!(args.args, vec!["create", "Lunch", "tomorrow", "12:30"]);
+        assert_eq!(args.flags.get("calendar"), Some(&Some("Home".to_string())));
+    }
+
+    #[test]
+    fn test_yes_flag_anywhere() {
+        for line in [
+            &["ducktape", "-y", "note", "delete", "Ideas"][..],
+            &["ducktape", "note", "delete", "Ideas", "--yes"],
+        ] {
+            let args = convert_to_command_args(&Cli::try_parse_from(line).unwrap()).unwrap();
+            assert!(args.flags.contains_key("yes"));
+        }
+        let cli = Cli::try_parse_from(["ducktape", "note", "delete", "Ideas"]).unwrap();
+        assert!(!convert_to_command_args(&cli).unwrap().flags.contains_key("yes"));
+    }
+}
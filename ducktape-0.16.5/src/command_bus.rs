@@ -0,0 +1,639 @@
+//! One execution layer for commands, wherever they come from.
+//
+// The CLI, the terminal, the WebSocket chat, the REST API and Slack all turn their input into
+// a `Command` and hand it to the `CommandBus`. Commands that other front ends share (creating
+// events, todos and notes, importing calendars) are typed, see `commands`, and run here;
+// everything else is passed to the `CommandProcessor` handlers as `CommandArgs`. The bus also
+// applies what every front end needs: idempotency keys, serializing changes through the
+// `SharedState`, and follow-ups: the item a command created or changed is remembered, and
+// "@last", "it" or "that meeting" in the next command is replaced by its UID or title, see
+// `context`.
+
+use crate::app_state::SharedState;
+use crate::calendar::{EventConfig, ImportSummary};
+use crate::cli::{CalendarActions, Cli, Commands, NoteActions, TodoActions};
+use crate::command_processor::{
+    CommandArgs, CommandProcessor, claim_idempotency_key, release_idempotency_key,
+};
+use crate::commands::{CreateEvent, CreateNote, CreateTodo, ImportEvents, UpdateEvent, usage};
+use crate::error::DucktapeError;
+use crate::parser::ParseResult;
+use crate::state::{CalendarItem, LinkedKind, RecentItem};
+use anyhow::{Result, anyhow};
+use clap::Parser as ClapParser;
+use tracing::{Instrument, debug, info, info_span, warn};
+
+/// Whether `e` is a mistake in the command rather than a failure to run it
+pub fn is_usage_error(e: &anyhow::Error) -> bool {
+    matches!(
+        e.downcast_ref::<DucktapeError>(),
+        Some(DucktapeError::Parse(_) | DucktapeError::Validation(_))
+    ) || e.is::<crate::validation::ValidationError>()
+}
+
+/// Arguments of a command line such as `ducktape calendar create ...`
+///
+/// Parsed with the Clap model first, falling back to the legacy tokenizer.
+pub fn parse_command_string(input: &str) -> Result<CommandArgs> {
+    parse_with(input, command_args, Ok)
+}
+
+/// The arguments a parsed command line describes
+fn command_args(cli: &Cli) -> Result<CommandArgs> {
+    crate::cli::convert_to_command_args(cli)
+        .ok_or_else(|| anyhow!("Failed to convert parsed command to CommandArgs"))
+}
+
+/// Parse a command line with the Clap model and `structured`, or else with the legacy
+/// tokenizer and `legacy`
+fn parse_with<T>(
+    input: &str,
+    structured: impl FnOnce(&Cli) -> Result<T>,
+    legacy: impl FnOnce(CommandArgs) -> Result<T>,
+) -> Result<T> {
+    let words = shell_words::split(input).map_err(|e| anyhow!("Failed to parse command: {}", e))?;
+    if words.is_empty() {
+        return Err(anyhow!("Empty command"));
+    }
+    match Cli::try_parse_from(&words) {
+        Ok(cli) => structured(&cli),
+        Err(e) => {
+            debug!("Not a structured command ({}), using the legacy parser", e);
+            legacy(CommandArgs::parse(input)?)
+        }
+    }
+}
+
+/// What DuckTape was asked to do
+#[derive(Debug, Clone)]
+pub enum Command {
+    CreateEvent(CreateEvent),
+    UpdateEvent(UpdateEvent),
+    ImportEvents(ImportEvents),
+    CreateTodo(CreateTodo),
+    CreateNote(CreateNote),
+    /// Any other command, run by its `CommandHandler`
+    Run(CommandArgs),
+}
+
+impl Command {
+    /// The command a parsed command line describes
+    pub fn from_cli(cli: &Cli) -> Result<Self> {
+        match &cli.command {
+            Some(Commands::Calendar { action: action @ CalendarActions::Create { .. } }) => {
+                Ok(Command::CreateEvent(action.try_into()?))
+            }
+            Some(Commands::Calendar { action: action @ CalendarActions::Update { .. } }) => {
+                Ok(Command::UpdateEvent(action.try_into()?))
+            }
+            Some(Commands::Calendar { action: action @ CalendarActions::Import { .. } }) => {
+                Ok(Command::ImportEvents(action.try_into()?))
+            }
+            Some(Commands::Todo { action: action @ TodoActions::Create { .. } }) => {
+                Ok(Command::CreateTodo(action.try_into()?))
+            }
+            Some(Commands::Note { action: action @ NoteActions::Create { .. } }) => {
+                Ok(Command::CreateNote(action.try_into()?))
+            }
+            _ => command_args(cli).map(Command::Run),
+        }
+    }
+
+    /// The command legacy arguments describe
+    pub fn from_args(args: CommandArgs) -> Result<Self> {
+        let kind = match args.command.as_str() {
+            "calendar" | "calendars" => "calendar",
+            "todo" | "todos" => "todo",
+            "note" | "notes" => "note",
+            _ => return Ok(Command::Run(args)),
+        };
+        match (kind, args.args.first().map(String::as_str)) {
+            ("calendar", Some("create")) => {
+                Ok(Command::CreateEvent(CreateEvent::from_args(&args)?))
+            }
+            ("calendar", Some("update" | "edit")) => {
+                Ok(Command::UpdateEvent(UpdateEvent::from_args(&args)?))
+            }
+            ("calendar", Some("import")) => {
+                Ok(Command::ImportEvents(ImportEvents::from_args(&args)?))
+            }
+            ("todo", Some("create" | "add")) => {
+                Ok(Command::CreateTodo(CreateTodo::from_args(&args)?))
+            }
+            ("note", Some("create" | "add")) => {
+                Ok(Command::CreateNote(CreateNote::from_args(&args)?))
+            }
+            _ => Ok(Command::Run(args)),
+        }
+    }
+
+    /// The command a command line such as `ducktape todo create ...` describes
+    ///
+    /// Parsed with the Clap model first, falling back to the legacy tokenizer.
+    pub fn parse(input: &str) -> Result<Self> {
+        parse_with(input, Self::from_cli, Self::from_args)
+    }
+
+    /// The command a parser recognized
+    ///
+    /// A command the parser has a question about is taken as it guessed it.
+    pub fn from_parse_result(result: ParseResult) -> Result<Self> {
+        match result {
+            ParseResult::CommandString(command)
+            | ParseResult::NeedsClarification(crate::parser::clarify::Clarification {
+                command,
+                ..
+            }) => Self::parse(&crate::parser::sanitize_nlp_command(&command)),
+            ParseResult::StructuredCommand(args) => Self::from_args(args),
+        }
+    }
+
+    /// Name of the command, as typed on the command line
+    pub fn name(&self) -> &str {
+        match self {
+            Command::CreateEvent(_) | Command::UpdateEvent(_) | Command::ImportEvents(_) => {
+                "calendar"
+            }
+            Command::CreateTodo(_) => "todo",
+            Command::CreateNote(_) => "note",
+            Command::Run(args) => &args.command,
+        }
+    }
+
+    /// The command as a command line, for the audit log
+    pub fn describe(&self) -> String {
+        let quote = |s: &str| shell_words::quote(s).into_owned();
+        let line = match self {
+            Command::CreateEvent(command) => format!(
+                "calendar create {} {} {}",
+                quote(&command.config.title),
+                command.config.start_date,
+                command.config.start_time
+            ),
+            Command::UpdateEvent(command) => format!("calendar update {}", quote(&command.id)),
+            Command::ImportEvents(command) => {
+                format!("calendar import {}", quote(&command.path.display().to_string()))
+            }
+            Command::CreateTodo(command) => format!("todo create {}", quote(&command.title)),
+            Command::CreateNote(command) => format!("note create {}", quote(&command.title)),
+            Command::Run(args) => return crate::audit::describe(args),
+        };
+        crate::audit::truncate(&line)
+    }
+
+    /// The command with a reference to the recent item, such as `@last`, replaced by its id
+    pub fn resolve_references(self) -> Result<Self> {
+        self.resolve_references_with(crate::context::current)
+    }
+
+    fn resolve_references_with(self, recent: impl FnOnce() -> Option<RecentItem>) -> Result<Self> {
+        match self {
+            Command::UpdateEvent(mut command) => {
+                command.id =
+                    crate::context::resolve_id_with(&command.id, LinkedKind::Event, recent)?;
+                Ok(Command::UpdateEvent(command))
+            }
+            Command::Run(mut args) => {
+                crate::context::resolve_args_with(&mut args, recent)?;
+                Ok(Command::Run(args))
+            }
+            command => Ok(command),
+        }
+    }
+
+    /// Whether what the command does can be undone, so that it can run in `dispatch_all`
+    pub fn can_undo(&self) -> bool {
+        matches!(self, Command::CreateEvent(_) | Command::CreateTodo(_) | Command::CreateNote(_))
+    }
+
+    /// Run a typed command; `Run` commands need the `CommandBus`
+    async fn run(self) -> Result<Outcome> {
+        match self {
+            Command::CreateEvent(command) => command.run().await,
+            Command::UpdateEvent(command) => command.run().await,
+            Command::ImportEvents(command) => command.run().await,
+            Command::CreateTodo(command) => command.run().await,
+            Command::CreateNote(command) => command.run().await,
+            Command::Run(args) => {
+                Err(anyhow!("'{}' can only be run through the command bus", args.command))
+            }
+        }
+    }
+}
+
+/// What a command did
+#[derive(Debug, Clone)]
+pub enum Outcome {
+    EventCreated(EventConfig),
+    EventUpdated(CalendarItem),
+    Imported(ImportSummary),
+    TodoCreated(String),
+    NoteCreated(String),
+    /// A `Run` command finished; it printed what it did
+    Done,
+    /// What each command of `dispatch_all` did, in order
+    Batch(Vec<Outcome>),
+}
+
+impl Outcome {
+    /// A one-line summary for chat and API clients
+    pub fn message(&self) -> String {
+        match self {
+            Outcome::EventCreated(config) => format!(
+                "Created event \"{}\" for {} at {}",
+                config.title, config.start_date, config.start_time
+            ),
+            Outcome::EventUpdated(item) => format!(
+                "Updated event \"{}\" to {} at {}",
+                item.title,
+                item.date,
+                if item.all_day { "all day" } else { &item.time }
+            ),
+            Outcome::Imported(summary) => summary.report().trim_end().to_string(),
+            Outcome::TodoCreated(title) => format!("Todo '{}' created successfully", title),
+            Outcome::NoteCreated(title) => format!("Note '{}' created successfully", title),
+            Outcome::Done => "Done".to_string(),
+            Outcome::Batch(outcomes) => {
+                let steps: Vec<String> = outcomes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, outcome)| format!("  {}. {}", i + 1, outcome.message()))
+                    .collect();
+                format!("Done {} requests:\n{}", outcomes.len(), steps.join("\n"))
+            }
+        }
+    }
+
+    /// Undo what a command did, when a later command of `dispatch_all` fails
+    async fn undo(&self) -> Result<()> {
+        match self {
+            Outcome::EventCreated(config) => {
+                let items = crate::state::load_events()?;
+                match crate::calendar::created_event(&items, config) {
+                    Some(item) => crate::calendar::delete_event(item).await,
+                    None => Err(anyhow!("event \"{}\" is not tracked", config.title)),
+                }
+            }
+            Outcome::TodoCreated(title) => crate::todo::delete_todo(title, None).await,
+            Outcome::NoteCreated(title) => crate::notes::delete_note(title, None).await,
+            outcome => Err(anyhow!("'{}' cannot be undone", outcome.message())),
+        }
+    }
+}
+
+/// Run a typed command, unless `idempotency_key` was already used
+///
+/// A duplicate gives `None`. The command runs one at a time with other changes. Front ends
+/// without the `CommandHandler`s, such as the API server, cannot run `Command::Run`.
+pub async fn dispatch(command: Command, idempotency_key: Option<&str>) -> Result<Option<Outcome>> {
+    let name = command.name().to_string();
+    let description = command.describe();
+    let id = crate::logging::correlation_id();
+    let span = info_span!("command", id = %id, name = %name);
+    let result = run_once(command, idempotency_key).instrument(span).await;
+    let outcome = match &result {
+        Ok(Some(_)) => "ok",
+        Ok(None) => "duplicate",
+        Err(_) => "error",
+    };
+    crate::metrics::command_finished(&name, outcome);
+    crate::audit::record(&id, &description, outcome, result.as_ref().err());
+    result
+}
+
+/// Run `commands` in order as one change
+///
+/// Every command must be one that can be undone. When one fails, the commands before it are
+/// undone, newest first, and the error names the step that failed.
+pub async fn dispatch_all(commands: Vec<Command>) -> Result<Outcome> {
+    if let Some(command) = commands.iter().find(|command| !command.can_undo()) {
+        return Err(usage(format!(
+            "'{}' cannot be combined with other requests; ask for it on its own",
+            command.describe()
+        )));
+    }
+    let total = commands.len();
+    let mut done = Vec::new();
+    for (step, command) in commands.into_iter().enumerate() {
+        let description = command.describe();
+        match dispatch(command, None).await {
+            Ok(outcome) => done.extend(outcome),
+            Err(e) => {
+                let message = format!(
+                    "Step {} of {} ({}) failed: {}. {}",
+                    step + 1,
+                    total,
+                    description,
+                    e,
+                    undo_all(&done).await
+                );
+                return Err(if is_usage_error(&e) { usage(message) } else { anyhow!(message) });
+            }
+        }
+    }
+    Ok(Outcome::Batch(done))
+}
+
+/// Undo `outcomes`, newest first, and say how that went
+async fn undo_all(outcomes: &[Outcome]) -> String {
+    if outcomes.is_empty() {
+        return "Nothing was changed".to_string();
+    }
+    let mut failed = Vec::new();
+    for outcome in outcomes.iter().rev() {
+        if let Err(e) = outcome.undo().await {
+            warn!("Failed to undo '{}': {}", outcome.message(), e);
+            failed.push(format!("{} ({})", outcome.message(), e));
+        }
+    }
+    if failed.is_empty() {
+        format!("The {} step(s) before it were undone", outcomes.len())
+    } else {
+        format!("These could not be undone: {}", failed.join("; "))
+    }
+}
+
+/// `dispatch` without counting the command in the metrics or the audit log
+async fn run_once(command: Command, idempotency_key: Option<&str>) -> Result<Option<Outcome>> {
+    run_once_with(command, idempotency_key, crate::context::current).await
+}
+
+async fn run_once_with(
+    command: Command,
+    idempotency_key: Option<&str>,
+    recent: impl FnOnce() -> Option<RecentItem>,
+) -> Result<Option<Outcome>> {
+    if let Command::Run(args) = &command {
+        return Err(usage(format!("Command '{}' is not available here", args.command)));
+    }
+    // Resolved before the key is claimed, so that a command that never ran can be retried
+    let command = command.resolve_references_with(recent)?;
+    if let Some(key) = idempotency_key {
+        if !claim_idempotency_key(key) {
+            info!("Skipping command: idempotency key {} already used", key);
+            return Ok(None);
+        }
+    }
+    let result = SharedState::global().exclusive(command.run()).await;
+    match (&result, idempotency_key) {
+        (Ok(outcome), _) => crate::context::remember_outcome(outcome),
+        (Err(_), Some(key)) => release_idempotency_key(key),
+        (Err(_), None) => {}
+    }
+    result.map(Some)
+}
+
+/// Print what a command run from the terminal or the command line did
+///
+/// Mistakes in the command are printed rather than returned.
+pub fn report(outcome: Result<Option<Outcome>>, idempotency_key: Option<&str>) -> Result<()> {
+    match outcome {
+        Ok(Some(Outcome::Imported(summary))) => print!("{}", summary.report()),
+        Ok(Some(outcome @ (Outcome::TodoCreated(_) | Outcome::NoteCreated(_)))) => {
+            println!("{}", outcome.message())
+        }
+        Ok(Some(outcome @ Outcome::Batch(_))) => println!("{}", outcome.message()),
+        Ok(Some(_)) => {}
+        Ok(None) => println!(
+            "Skipping duplicate request (idempotency key {})",
+            idempotency_key.unwrap_or_default()
+        ),
+        Err(e) if is_usage_error(&e) => println!("{}", e),
+        Err(e) => return Err(e),
+    }
+    Ok(())
+}
+
+/// Build a typed command from legacy arguments, run it and print what it did
+///
+/// For the `CommandHandler`s of commands that are typed; the `CommandProcessor` has already
+/// checked the idempotency key.
+pub async fn run_legacy(args: CommandArgs) -> Result<()> {
+    let outcome = match Command::from_args(args) {
+        // The processor counted and recorded the command already
+        Ok(command) => crate::tasks::with_progress_bar(run_once(command, None)).await,
+        Err(e) => Err(e),
+    };
+    report(outcome, None)
+}
+
+/// Runs every command, typed or handled by a `CommandHandler`
+pub struct CommandBus {
+    processor: CommandProcessor,
+}
+
+impl CommandBus {
+    pub fn new() -> Self {
+        Self { processor: CommandProcessor::new() }
+    }
+
+    /// Run `command`, unless `idempotency_key` was already used
+    ///
+    /// Like `dispatch`, but `Command::Run` is passed to the `CommandProcessor`.
+    pub async fn dispatch(
+        &self,
+        command: Command,
+        idempotency_key: Option<&str>,
+    ) -> Result<Option<Outcome>> {
+        match command {
+            Command::Run(mut args) => {
+                crate::context::resolve_args(&mut args)?;
+                // The processor checks the key itself
+                let args = match idempotency_key {
+                    Some(key) => args.with_idempotency_key(key),
+                    None => args,
+                };
+                self.processor.execute(args).await?;
+                Ok(Some(Outcome::Done))
+            }
+            command => dispatch(command, idempotency_key).await,
+        }
+    }
+
+    /// Run a command from the terminal or the command line, printing what it did
+    ///
+    /// Mistakes in the command are printed rather than returned.
+    pub async fn execute(&self, command: Command, idempotency_key: Option<&str>) -> Result<()> {
+        match command {
+            Command::Run(mut args) => {
+                if let Err(e) = crate::context::resolve_args(&mut args) {
+                    return report(Err(e), None);
+                }
+                let args = match idempotency_key {
+                    Some(key) => args.with_idempotency_key(key),
+                    None => args,
+                };
+                self.processor.execute(args).await
+            }
+            command => {
+                let outcome =
+                    crate::tasks::with_progress_bar(dispatch(command, idempotency_key)).await;
+                report(outcome, idempotency_key)
+            }
+        }
+    }
+
+    /// Run the commands of one input as one change, printing what they did
+    pub async fn execute_all(&self, commands: Vec<Command>) -> Result<()> {
+        let outcome = crate::tasks::with_progress_bar(dispatch_all(commands)).await;
+        report(outcome.map(Some), None)
+    }
+
+    /// Like `execute`, for arguments from the legacy tokenizer or a structured parser result
+    pub async fn execute_args(&self, args: CommandArgs) -> Result<()> {
+        let key = args.idempotency_key().map(str::to_string);
+        match Command::from_args(args) {
+            Ok(command) => self.execute(command, key.as_deref()).await,
+            Err(e) => report(Err(e), None),
+        }
+    }
+}
+
+impl Default for CommandBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::OnDuplicate;
+    use std::collections::HashMap;
+
+    fn args(words: &[&str], flags: &[(&str, Option<&str>)]) -> CommandArgs {
+        CommandArgs::new(
+            words[0].to_string(),
+            words[1..].iter().map(|w| w.to_string()).collect(),
+            flags
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.map(str::to_string)))
+                .collect::<HashMap<_, _>>(),
+        )
+    }
+
+    #[test]
+    fn test_create_event_from_args() {
+        let command = Command::from_args(args(
+            &["calendar", "create", "Team", "Sync", "2025-04-22", "10:00", "11:00", "Work"],
+            &[
+                ("location", Some("\"Room 4\"")),
+                ("email", Some("a@example.com")),
+                ("group", Some("team, leads")),
+            ],
+        ))
+        .unwrap();
+        let Command::CreateEvent(event) = command else {
+            panic!("expected an event, got {:?}", command);
+        };
+        assert_eq!(event.config.title, "Team Sync");
+        assert_eq!(event.config.start_date, "2025-04-22");
+        assert_eq!(event.config.end_time.as_deref(), Some("11:00"));
+        assert_eq!(event.config.location.as_deref(), Some("Room 4"));
+        assert_eq!(event.config.emails, vec!["a@example.com"]);
+        assert_eq!(event.calendar.as_deref(), Some("Work"));
+        assert_eq!(event.groups, vec!["team", "leads"]);
+
+        let e = Command::from_args(args(
+            &["calendar", "create", "Team", "Sync", "2025-04-22", "10:00", "11:00"],
+            &[("email", Some("a@example.com, not-an-email"))],
+        ))
+        .unwrap_err();
+        assert!(e.to_string().contains("not-an-email"));
+    }
+
+    #[test]
+    fn test_usage_errors() {
+        let e = Command::from_args(args(&["calendar", "create", "Lunch"], &[])).unwrap_err();
+        assert!(is_usage_error(&e));
+
+        let e = Command::from_args(args(
+            &["calendar", "import", "events.txt"],
+            &[("format", Some("xml"))],
+        ))
+        .unwrap_err();
+        assert!(is_usage_error(&e));
+        assert!(e.to_string().contains("Unsupported format"));
+    }
+
+    #[test]
+    fn test_other_commands_run_through_handlers() {
+        let command = Command::from_args(args(&["todo", "create", "Buy milk"], &[])).unwrap();
+        assert!(matches!(command, Command::CreateTodo(todo) if todo.title == "Buy milk"));
+
+        let command = Command::from_args(args(&["todo", "list"], &[])).unwrap();
+        assert!(matches!(command, Command::Run(args) if args.command == "todo"));
+
+        let command = Command::from_args(args(&["calendar", "list"], &[])).unwrap();
+        assert!(matches!(command, Command::Run(_)));
+    }
+
+    #[test]
+    fn test_import_from_args() {
+        let Command::ImportEvents(import) = Command::from_args(args(
+            &["calendar", "import", "team.CSV"],
+            &[("calendar", Some("\"Work\"")), ("on-duplicate", Some("update"))],
+        ))
+        .unwrap() else {
+            panic!("expected an import");
+        };
+        assert_eq!(import.format, "csv");
+        assert_eq!(import.calendar.as_deref(), Some("Work"));
+        assert_eq!(import.on_duplicate, OnDuplicate::Update);
+    }
+
+    #[test]
+    fn test_parse_uses_the_clap_model() {
+        let command = Command::parse("ducktape note create Weekly plan --folder Work").unwrap();
+        let Command::CreateNote(note) = command else {
+            panic!("expected a note, got {:?}", command);
+        };
+        assert_eq!(note.title, "Weekly plan");
+        assert_eq!(note.folder.as_deref(), Some("Work"));
+
+        let command = Command::parse(
+            "ducktape calendar import events.csv --format csv --on-duplicate update",
+        )
+        .unwrap();
+        assert!(matches!(command, Command::ImportEvents(import) if import.format == "csv"));
+
+        let command = Command::parse("ducktape todo lists").unwrap();
+        assert!(matches!(command, Command::Run(args) if args.command == "todo"));
+    }
+
+    #[tokio::test]
+    async fn test_unresolved_reference_keeps_the_idempotency_key_free() {
+        let update = || Command::parse("ducktape calendar update @last --title Lunch").unwrap();
+        assert!(matches!(update(), Command::UpdateEvent(_)));
+        let key = "test-unresolved-reference";
+        // The retry runs again rather than being skipped as a duplicate
+        for _ in 0..2 {
+            let e = run_once_with(update(), Some(key), || None).await.unwrap_err();
+            assert!(e.to_string().contains("Nothing was created or changed recently"));
+        }
+        assert!(claim_idempotency_key(key));
+        release_idempotency_key(key);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_all() {
+        let todo = Command::parse("ducktape todo create \"Prepare slides\"").unwrap();
+        let lists = Command::parse("ducktape todo lists").unwrap();
+        assert!(todo.can_undo());
+        assert!(!lists.can_undo());
+
+        // Refused before anything runs
+        let e = dispatch_all(vec![todo, lists]).await.unwrap_err();
+        assert!(is_usage_error(&e));
+        assert!(e.to_string().contains("cannot be combined with other requests"));
+
+        let batch = Outcome::Batch(vec![
+            Outcome::TodoCreated("Prepare slides".to_string()),
+            Outcome::NoteCreated("Agenda".to_string()),
+        ]);
+        assert_eq!(
+            batch.message(),
+            "Done 2 requests:\n  1. Todo 'Prepare slides' created successfully\n  2. Note 'Agenda' created successfully"
+        );
+    }
+}
@@ -1,11 +1,51 @@
+use crate::app_state::SharedState;
 use anyhow::{Result, anyhow};
-use log::{debug, info, warn};
+use once_cell::sync::Lazy;
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{Instrument, debug, info, info_span, warn};
+
+/// Flag carrying a client supplied idempotency key
+pub const IDEMPOTENCY_KEY_FLAG: &str = "idempotency-key";
+
+// How long an idempotency key is remembered after it was first used
+const IDEMPOTENCY_KEY_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+// Idempotency keys seen by this process and when they were first used
+static IDEMPOTENCY_KEYS: Lazy<Mutex<HashMap<String, Instant>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Record an idempotency key, returning false if it was already used within the TTL
+pub fn claim_idempotency_key(key: &str) -> bool {
+    let now = Instant::now();
+    let Ok(mut keys) = IDEMPOTENCY_KEYS.lock() else {
+        // A poisoned lock must not block commands
+        return true;
+    };
+    keys.retain(|_, used_at| now.duration_since(*used_at) < IDEMPOTENCY_KEY_TTL);
+    if keys.contains_key(key) {
+        return false;
+    }
+    keys.insert(key.to_string(), now);
+    true
+}
+
+/// Forget an idempotency key so that a failed command can be retried with it
+pub fn release_idempotency_key(key: &str) {
+    if let Ok(mut keys) = IDEMPOTENCY_KEYS.lock() {
+        keys.remove(key);
+    }
+}
 
 /// Command line arguments structure
+///
+/// The untyped form of a command, kept for the legacy tokenizer, structured parser results
+/// and the `CommandHandler`s. Commands that have a typed form in `commands` are converted to
+/// it by `Command::from_args` before they run.
 #[derive(Debug, Clone)]
 pub struct CommandArgs {
     pub command: String,
@@ -19,6 +59,20 @@ impl CommandArgs {
         Self { command, args, flags }
     }
 
+    /// Attach an idempotency key; the command is skipped if the key was already used
+    pub fn with_idempotency_key(mut self, key: &str) -> Self {
+        self.flags.insert(IDEMPOTENCY_KEY_FLAG.to_string(), Some(key.to_string()));
+        self
+    }
+
+    /// The idempotency key attached to this command, if any
+    pub fn idempotency_key(&self) -> Option<&str> {
+        self.flags
+            .get(IDEMPOTENCY_KEY_FLAG)
+            .and_then(|k| k.as_deref())
+            .filter(|k| !k.is_empty())
+    }
+
     /// Legacy method for parsing command arguments from a string
     /// This is deprecated in favor of using the Clap-based command line parser
     #[deprecated(note = "Use the Clap-based command line parser instead")]
@@ -32,7 +86,9 @@ impl CommandArgs {
         debug!("Tokenized input: {:?}", tokens);
 
         if tokens.is_empty() {
-            return Err(anyhow!("No command provided"));
+            return Err(
+                crate::error::DucktapeError::Parse("No command provided".to_string()).into()
+            );
         }
 
         // Extract command, removing 'ducktape' if present
@@ -43,7 +99,11 @@ impl CommandArgs {
         let command = if first_token.eq_ignore_ascii_case("ducktape") {
             tokens_iter
                 .next()
-                .ok_or_else(|| anyhow!("No command provided after 'ducktape'"))?
+                .ok_or_else(|| {
+                    crate::error::DucktapeError::Parse(
+                        "No command provided after 'ducktape'".to_string(),
+                    )
+                })?
                 .to_lowercase()
         } else {
             first_token.to_lowercase()
@@ -101,6 +161,65 @@ impl CommandArgs {
     }
 }
 
+/// The event a parsed `calendar create` command describes
+///
+/// `None` when the title, date or start time is missing.
+pub fn event_config_from_args(args: &CommandArgs) -> Option<crate::calendar::EventConfig> {
+    // Skip "create" (which is args[0]); needs at least title, date, start_time
+    let [_, title, date, start_time, rest @ ..] = args.args.as_slice() else {
+        return None;
+    };
+    let mut config = crate::calendar::EventConfig::new(title, date, start_time);
+
+    // End time and calendar are optional
+    if let Some(end) = rest.first() {
+        config.end_time = Some(end.to_string());
+    }
+    if let Some(cal) = rest.get(1) {
+        config.calendars = vec![cal.trim_matches('"').to_string()];
+    }
+
+    // Handle the email flag
+    if let Some(Some(emails_str)) = args.flags.get("email") {
+        let emails: Vec<String> =
+            emails_str.split(',').map(|e| e.trim().trim_matches('"').to_string()).collect();
+        if !emails.is_empty() {
+            debug!("Adding email attendees: {:?}", emails);
+            config.emails = emails;
+        }
+    }
+
+    if let Some(Some(url)) = args.flags.get("url") {
+        config.url = Some(url.trim_matches('"').to_string());
+    }
+    if let Some(Some(availability)) = args.flags.get("availability") {
+        match crate::calendar::EventAvailability::from_str(availability) {
+            Ok(availability) => config.availability = Some(availability),
+            Err(e) => debug!("Ignoring availability: {}", e),
+        }
+    }
+
+    // Handle the zoom flag
+    if args.flags.contains_key("zoom") {
+        config.create_zoom_meeting = true;
+    }
+
+    // Handle the meeting flag, falling back to the configured provider
+    if let Some(service) = args.flags.get("meeting") {
+        let service = service
+            .as_deref()
+            .and_then(|s| crate::config::MeetingService::from_str(s).ok())
+            .unwrap_or_else(|| {
+                crate::config::Config::load()
+                    .map(|c| c.calendar.meeting_provider)
+                    .unwrap_or_default()
+            });
+        debug!("Enabling {} meeting creation", service.label());
+        config.meeting_service = Some(service);
+    }
+    Some(config)
+}
+
 /// Tokenizes the input command line, properly handling both escaped and regular quotes
 /// Tokenizes the input command line, properly handling both escaped and regular quotes
 fn tokenize_input(input: &str) -> Result<Vec<String>> {
@@ -218,7 +337,7 @@ fn postprocess_flags(tokens: &[String]) -> Vec<String> {
         }
         // Special handling for other flags that might need quoted values
         else if token.starts_with("--")
-            && ["location", "notes", "email", "contacts"].contains(&&token[2..])
+            && ["location", "url", "notes", "email", "contacts"].contains(&&token[2..])
             && i + 1 < tokens.len()
         {
             debug!("Found special flag: {}", token);
@@ -286,250 +405,140 @@ impl CommandHandler for CalendarHandler {
     fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
         Box::pin(async move {
             match args.args.first().map(|s| s.as_str()) {
-                Some("create") => {
-                    if args.args.len() < 5 {
-                        println!("Not enough arguments for calendar create command");
-                        println!(
-                            "Usage: ducktape calendar create <title> <date> <start_time> <end_time> [calendar]"
-                        );
+                // Shared with the API server, which runs the same commands
+                Some("create") | Some("import") | Some("update") | Some("edit") => {
+                    crate::command_bus::run_legacy(args).await
+                }
+                Some("list") => {
+                    crate::calendar::list_calendars(args.flags.contains_key("refresh")).await
+                }
+                Some("props") | None if args.command == "calendar-props" => {
+                    crate::calendar::list_event_properties().await
+                }
+                Some("show") => {
+                    let title = args.args[1..].join(" ");
+                    let title = title.trim().trim_matches('"').to_lowercase();
+                    if title.is_empty() {
+                        println!("Usage: ducktape calendar show <title>");
                         return Ok(());
                     }
-
-                    // Special handling for multi-word titles in calendar create command
-                    let mut combined_title = String::new();
-                    let mut _title_index = 1;
-                    let mut date_index = 2;
-                    if args.args.len() >= 6
-                        && !args.args[1].contains('-')
-                        && !args.args[1].contains(':')
-                        && !args.args[2].contains('-')
-                        && !args.args[2].contains(':')
-                        && (args.args[3].contains('-') || args.args[3].contains('/'))
-                    {
-                        debug!("Detected potential multi-word title");
-                        combined_title = format!("{} {}", args.args[1], args.args[2]);
-                        _title_index = 0;
-                        date_index = 3;
+                    let mut items: Vec<_> = crate::state::load_events()?
+                        .into_iter()
+                        .filter(|item| item.title.to_lowercase().contains(&title))
+                        .collect();
+                    if items.is_empty() {
+                        println!("No tracked event matches '{}'", title);
+                        return Ok(());
+                    }
+                    items.sort_by(|a, b| (&a.date, &a.time).cmp(&(&b.date, &b.time)));
+                    let details: Vec<String> =
+                        items.iter().map(crate::calendar::format_calendar_item).collect();
+                    print!("{}", details.join("\n"));
+                    Ok(())
+                }
+                Some("delete") => {
+                    let Some(id) = args.args.get(1).map(|id| id.trim_matches('"')) else {
+                        println!("Usage: ducktape calendar delete <uid or title> [calendar]");
+                        return Ok(());
+                    };
+                    let calendar = args.args.get(2).map(|c| c.trim_matches('"'));
+                    let items = crate::state::load_events()?;
+                    let item = match crate::calendar::find_event_by_id(&items, id, calendar) {
+                        Ok(item) => item,
+                        Err(e) => {
+                            println!("{}", e);
+                            return Ok(());
+                        }
+                    };
+                    match crate::calendar::delete_event(item).await {
+                        Ok(()) => {
+                            println!("Deleted event '{}' on {}", item.title, item.date);
+                            Ok(())
+                        }
+                        Err(e) => {
+                            println!("Failed to delete event: {}", e);
+                            Err(e)
+                        }
                     }
-                    let raw_title = if !combined_title.is_empty() {
-                        combined_title
+                }
+                Some("view") => {
+                    let mode = if args.flags.contains_key("month") {
+                        crate::calendar::ViewMode::Month
                     } else {
-                        args.args[1].clone()
+                        crate::calendar::ViewMode::Week
                     };
-                    let title = raw_title.trim_matches('"');
-                    let mut date = args.args[date_index].clone();
-                    let start_time = &args.args[date_index + 1];
-                    let end_time = &args.args[date_index + 2];
-
-                    // --- NEW: resolve relative date strings ---
-                    if date.eq_ignore_ascii_case("today") || date.eq_ignore_ascii_case("tomorrow") {
-                        match crate::reminder::resolve_relative_date(&date) {
-                            Ok(resolved) => {
-                                debug!("Resolved relative date '{}' to '{}'.", date, resolved);
-                                date = resolved;
-                            }
-                            Err(e) => {
-                                log::warn!("Could not resolve relative date '{}': {}", date, e);
-                                println!("Invalid date: {}", date);
+                    let today = chrono::Local::now().date_naive();
+                    let start = match args.flags.get("date") {
+                        Some(Some(date)) => match crate::dates::parse_date(date, today) {
+                            Some(start) => start,
+                            None => {
+                                println!("Unrecognized date: {}", date);
                                 return Ok(());
                             }
-                        }
+                        },
+                        _ => today,
+                    };
+                    crate::calendar::run_calendar_view(mode, start).await
+                }
+                Some(command @ ("move-all" | "delete-all")) => {
+                    let Some(Some(on)) = args.flags.get("on") else {
+                        println!("Missing day for calendar {} command", command);
+                        println!(
+                            "Usage: ducktape calendar {} --on <day or range>{}",
+                            command,
+                            if command == "move-all" { " --to <day>" } else { "" }
+                        );
+                        return Ok(());
+                    };
+                    let to = args.flags.get("to").and_then(|t| t.as_deref());
+                    if command == "move-all" && to.is_none() {
+                        println!("Missing target date for calendar move-all command");
+                        println!(
+                            "Usage: ducktape calendar move-all --on <day or range> --to <day>"
+                        );
+                        return Ok(());
                     }
-                    // --- END NEW ---
+                    let title = args.flags.get("title").and_then(|t| t.as_deref());
+                    let calendar = args.flags.get("calendar").and_then(|c| c.as_deref());
 
-                    // Check if the date_index + 3 argument is a calendar or part of a flag
-                    let calendar = if args
-                        .args
-                        .get(date_index + 3)
-                        .map_or(false, |arg| !arg.starts_with("--"))
+                    let changes = match crate::calendar::preview_bulk_changes(
+                        on, to, title, calendar,
+                    )
+                    .await
                     {
-                        args.args
-                            .get(date_index + 3)
-                            .cloned()
-                            .map(|cal| cal.trim_matches('"').to_string())
-                    } else {
-                        debug!("No explicit calendar specified, will use default calendar");
-                        None
-                    };
-
-                    log::info!(
-                        "Processing calendar event with title: '{}', date: {}, times: {} to {}",
-                        title,
-                        date,
-                        start_time,
-                        end_time
-                    );
-
-                    // Build flags and trim surrounding quotes if present
-                    let location = args
-                        .flags
-                        .get("location")
-                        .cloned()
-                        .flatten()
-                        .map(|loc| loc.trim_matches('"').to_string());
-                    let description = args
-                        .flags
-                        .get("notes")
-                        .cloned()
-                        .flatten()
-                        .map(|desc| desc.trim_matches('"').to_string());
-                    let emails = args
-                        .flags
-                        .get("email")
-                        .cloned()
-                        .flatten()
-                        .map(|email| email.trim_matches('"').to_string());
-
-                    let contacts = args.flags.get("contacts").cloned().flatten().map(|contact| {
-                        // Properly trim surrounding quotes and maintain multi-word names
-                        let trimmed = contact.trim_matches('"').trim_matches('\'').to_string();
-                        debug!("Contacts flag value after trimming quotes: '{}'", trimmed);
-                        trimmed
-                    });
-
-                    debug!("Contacts flag value: {:?}", contacts);
-
-                    // Handle recurrence options
-                    let recurrence_frequency =
-                        args.flags.get("repeat").or(args.flags.get("recurring")).cloned().flatten();
-                    let interval = args.flags.get("interval").cloned().flatten();
-                    let until_date = args.flags.get("until").cloned().flatten();
-                    let count = args.flags.get("count").cloned().flatten();
-                    let days = args.flags.get("days").cloned().flatten();
-
-                    // Create event config and pass to calendar module
-                    let mut config = crate::calendar::EventConfig::new(title, &date, start_time);
-                    config.end_time = Some(end_time.clone());
-
-                    // Validate calendar name
-                    let available_calendars = crate::calendar::get_available_calendars().await?;
-                    if let Some(cal) = &calendar {
-                        if !available_calendars.contains(cal) {
-                            warn!(
-                                "Specified calendar '{}' not found. Falling back to default calendar.",
-                                cal
-                            );
-                            println!(
-                                "Warning: Calendar '{}' not found. Using default calendar.",
-                                cal
-                            );
-                            config.calendars = vec!["Work".to_string()]; // Fallback to default calendar
-                        } else {
-                            config.calendars = vec![cal.clone()];
+                        Ok(changes) => changes,
+                        Err(e) => {
+                            println!("Failed to find events: {}", e);
+                            return Ok(());
                         }
-                    } else {
-                        config.calendars = vec!["Work".to_string()]; // Use default calendar if none specified
-                    }
-
-                    config.location = location;
-                    config.description = description;
-
-                    // Check for --zoom flag and set create_zoom_meeting property
-                    if args.flags.contains_key("zoom") {
-                        info!("Zoom flag detected, creating event with Zoom meeting");
-                        config.create_zoom_meeting = true;
-                    }
-
-                    // Process recurrence information if provided
-                    if let Some(freq_str) = recurrence_frequency {
-                        match crate::calendar::RecurrenceFrequency::from_str(&freq_str) {
-                            Ok(frequency) => {
-                                info!("Creating recurring event with frequency: {}", freq_str);
-                                let mut recurrence =
-                                    crate::calendar::RecurrencePattern::new(frequency);
-
-                                // Add interval if specified
-                                if let Some(interval_str) = interval {
-                                    if let Ok(interval_val) = interval_str.parse::<u32>() {
-                                        recurrence = recurrence.with_interval(interval_val);
-                                        debug!("Setting recurrence interval: {}", interval_val);
-                                    }
-                                }
-
-                                // Add end date if specified
-                                if let Some(until) = until_date {
-                                    recurrence = recurrence.with_end_date(&until);
-                                    debug!("Setting recurrence end date: {}", until);
-                                }
-
-                                // Add count if specified
-                                if let Some(count_str) = count {
-                                    if let Ok(count_val) = count_str.parse::<u32>() {
-                                        recurrence = recurrence.with_count(count_val);
-                                        debug!("Setting recurrence count: {}", count_val);
-                                    }
-                                }
-
-                                // Add days if specified
-                                if let Some(days_str) = days {
-                                    let day_values: Vec<u8> = days_str
-                                        .split(',')
-                                        .filter_map(|s| s.trim().parse::<u8>().ok())
-                                        .collect();
-                                    if !day_values.is_empty() {
-                                        recurrence = recurrence.with_days_of_week(&day_values);
-                                        debug!("Setting recurrence days: {:?}", day_values);
-                                    }
-                                }
-
-                                config.recurrence = Some(recurrence);
-                            }
-                            Err(e) => {
-                                warn!("Invalid recurrence frequency '{}': {}", freq_str, e);
-                            }
+                    };
+                    if changes.is_empty() {
+                        match title {
+                            Some(title) => println!("No events matching '{}' {}", title, on),
+                            None => println!("No events {}", on),
                         }
+                        return Ok(());
                     }
 
-                    // Process emails if provided
-                    if let Some(email_str) = emails {
-                        config.emails = email_str
-                            .split(',')
-                            .map(|s| s.trim().to_string())
-                            .filter(|email| crate::calendar::validate_email(email))
-                            .collect();
-                        debug!("Added {} email attendees", config.emails.len());
+                    let verb = if to.is_some() { "Move" } else { "Delete" };
+                    println!("Events to {} ({}):", verb.to_lowercase(), changes.len());
+                    println!("{}", crate::calendar::format_bulk_changes(&changes));
+                    if args.flags.contains_key("dry-run") {
+                        return Ok(());
                     }
-
-                    // If contacts are specified, use create_event_with_contacts
-                    if let Some(contacts_str) = contacts {
-                        info!("Processing contacts string: '{}'", contacts_str);
-
-                        // Process contact string and convert to a vector of string slices
-                        // First split by commas to handle multiple contacts
-                        let contact_vec: Vec<&str> = contacts_str
-                            .split(',')
-                            .map(|s| s.trim())
-                            .filter(|s| !s.is_empty())
-                            .collect();
-
-                        if !contact_vec.is_empty() {
-                            info!(
-                                "Creating event with {} contact(s): {:?}",
-                                contact_vec.len(),
-                                contact_vec
-                            );
-                            return crate::calendar::create_event_with_contacts(
-                                config,
-                                &contact_vec,
-                            )
-                            .await;
-                        }
+                    if !confirmed(&args, &format!("{} {} event(s)?", verb, changes.len())) {
+                        println!("{} cancelled", verb);
+                        return Ok(());
                     }
 
-                    crate::calendar::create_event(config).await
-                }
-                Some("list") => crate::calendar::list_calendars().await,
-                Some("props") | None if args.command == "calendar-props" => {
-                    crate::calendar::list_event_properties().await
-                }
-                Some("show") => {
-                    // TODO: Implement show calendar functionality
-                    println!("Show calendar functionality is not implemented yet.");
+                    let changed = crate::calendar::apply_bulk_changes(&changes).await?;
+                    let done = if to.is_some() { "Moved" } else { "Deleted" };
+                    println!("{} {} of {} event(s)", done, changed, changes.len());
                     Ok(())
                 }
                 _ => {
                     println!(
-                        "Unknown calendar command. Available commands: create, list, show, props"
+                        "Unknown calendar command. Available commands: create, import, update, list, show, delete, props, view, move-all, delete-all"
                     );
                     Ok(())
                 }
@@ -550,107 +559,135 @@ impl CommandHandler for TodoHandler {
     fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
         Box::pin(async move {
             match args.args.first().map(|s| s.as_str()) {
-                Some("create") | Some("add") => {
-                    if args.args.len() < 2 {
-                        println!("Not enough arguments for todo create command");
-                        println!("Usage: ducktape todo create <title> [list1] [list2] ...");
-                        return Ok(());
-                    }
-
-                    let title = &args.args[1];
-
-                    // Create a new TodoConfig with the title
-                    let mut config = crate::todo::TodoConfig::new(title);
-
-                    // Set lists if provided in arguments (args[2] and beyond are list names)
-                    if args.args.len() > 2 {
-                        // Only collect lists that don't start with "--" (those are flags)
-                        let list_names: Vec<&str> = args.args[2..]
-                            .iter()
-                            .map(|s| s.as_str())
-                            .filter(|s| !s.starts_with("--"))
-                            .collect();
-
-                        if !list_names.is_empty() {
-                            config.lists = list_names;
+                Some("create") | Some("add") => crate::command_bus::run_legacy(args).await,
+                Some("list") if has_reminder_filters(&args) => list_reminders(&args).await,
+                Some("list") => {
+                    let list = args.args.get(1).map(|l| l.trim_matches('"'));
+                    let todos = match crate::todo::get_todos(list).await {
+                        Ok(todos) => todos,
+                        Err(e) => {
+                            println!("Failed to list todos: {}", e);
+                            return Err(e);
                         }
+                    };
+                    if todos.is_empty() {
+                        println!("{}", crate::i18n::text(crate::i18n::Message::NoTodos));
+                        return Ok(());
                     }
 
-                    // Process standard flags (like --remind)
-                    // Look for --remind flag in both formats: as key in flags HashMap or as commandline arg
-                    let reminder_time = if let Some(Some(time)) = args.flags.get("remind") {
-                        // Found in the flags HashMap
-                        debug!("Found reminder time in flags HashMap: {}", time);
-                        Some(time.as_str())
-                    } else if let Some(remind_idx) =
-                        args.args.iter().position(|arg| arg == "--remind")
-                    {
-                        // Found as a commandline arg
-                        if remind_idx + 1 < args.args.len() {
-                            let time = &args.args[remind_idx + 1];
-                            debug!("Found reminder time as arg: {}", time);
-                            Some(time.trim_matches('"').trim_matches('\''))
+                    let links = crate::links::load_links().unwrap_or_default();
+                    let colors = crate::theme::Colors::stdout();
+                    let now = chrono::Local::now().naive_local();
+                    for todo in &todos {
+                        let due = todo
+                            .reminder_time
+                            .as_ref()
+                            .map_or(String::new(), |d| format!(" (due {})", colors.time(d)));
+                        let title = if todo.is_overdue(now) {
+                            colors.overdue(&todo.title)
                         } else {
-                            None
+                            todo.title.clone()
+                        };
+                        println!(
+                            "{} {} {}{}",
+                            if todo.completed { "☑" } else { "☐" },
+                            title,
+                            colors.calendar(&format!("[{}]", todo.lists.join(", "))),
+                            due
+                        );
+                        for link in crate::links::describe_links(
+                            &links,
+                            crate::state::LinkedKind::Todo,
+                            &todo.title,
+                        ) {
+                            println!("    ↳ {}", link);
                         }
-                    } else {
-                        None
-                    };
-
-                    if let Some(time_str) = reminder_time {
-                        debug!("Setting reminder time: {}", time_str);
-                        config.reminder_time = Some(time_str);
                     }
-
-                    // Set notes if provided via --notes flag (similar approach as reminder time)
-                    let notes = if let Some(Some(note_text)) = args.flags.get("notes") {
-                        // Found in the flags HashMap
-                        Some(note_text.clone())
-                    } else if let Some(notes_idx) =
-                        args.args.iter().position(|arg| arg == "--notes")
-                    {
-                        // Found as a commandline arg
-                        if notes_idx + 1 < args.args.len() {
-                            Some(args.args[notes_idx + 1].clone())
-                        } else {
-                            None
+                    Ok(())
+                }
+                Some("delete") => {
+                    // Implementation for deleting todos would go here using async/await
+                    println!("Deleting todo... (not implemented yet)");
+                    Ok(())
+                }
+                Some("board") => {
+                    let board = crate::config::Config::load()?.todo.board;
+                    match crate::todo::show_board(&board).await {
+                        Ok(rendered) => {
+                            print!("{}", rendered);
+                            Ok(())
+                        }
+                        Err(e) => {
+                            println!("Failed to show todo board: {}", e);
+                            Err(e)
                         }
-                    } else {
-                        None
-                    };
-
-                    if let Some(note_text) = notes {
-                        debug!("Setting notes: {}", note_text);
-                        config.notes =
-                            Some(note_text.trim_matches('"').trim_matches('\'').to_string());
+                    }
+                }
+                Some("move") => {
+                    if args.args.len() < 3 {
+                        println!("Not enough arguments for todo move command");
+                        println!("Usage: ducktape todo move <title> <backlog|doing|done|list>");
+                        return Ok(());
                     }
 
-                    debug!("Final todo config: {:?}", config);
-
-                    // Use await with the async create_todo function
-                    match crate::todo::create_todo(config).await {
-                        Ok(_) => {
-                            println!("Todo '{}' created successfully", title);
+                    let title = args.args[1].trim_matches('"');
+                    let target = args.args[2].trim_matches('"');
+                    let board = crate::config::Config::load()?.todo.board;
+                    match crate::todo::move_to_column(title, target, &board).await {
+                        Ok(list) => {
+                            println!("Moved '{}' to {}", title, list);
                             Ok(())
                         }
                         Err(e) => {
-                            println!("Failed to create todo: {}", e);
-                            Err(anyhow!("Failed to create todo: {}", e))
+                            println!("Failed to move todo: {}", e);
+                            Err(e)
                         }
                     }
                 }
-                Some("list") => {
-                    // Implementation for listing todos would go here using async/await
-                    println!("Listing todos... (not implemented yet)");
-                    Ok(())
-                }
-                Some("delete") => {
-                    // Implementation for deleting todos would go here using async/await
-                    println!("Deleting todo... (not implemented yet)");
+                Some("postpone") => {
+                    let due = match args.flags.get("due") {
+                        Some(Some(due)) => due.as_str(),
+                        _ => "today",
+                    };
+                    let Some(Some(to)) = args.flags.get("to") else {
+                        println!("Missing target date for todo postpone command");
+                        println!("Usage: ducktape todo postpone --due <day> --to <day>");
+                        return Ok(());
+                    };
+                    let list = args.flags.get("list").and_then(|l| l.as_deref());
+                    let include_overdue = args.flags.contains_key("overdue");
+
+                    let changes =
+                        match crate::todo::preview_postpone(due, to, list, include_overdue).await {
+                            Ok(changes) => changes,
+                            Err(e) => {
+                                println!("Failed to plan postpone: {}", e);
+                                return Ok(());
+                            }
+                        };
+                    if changes.is_empty() {
+                        println!("No open reminders due {}", due);
+                        return Ok(());
+                    }
+
+                    println!("Reminders to postpone ({}):", changes.len());
+                    println!("{}", crate::todo::format_due_date_changes(&changes));
+                    if args.flags.contains_key("dry-run") {
+                        return Ok(());
+                    }
+                    if !confirmed(&args, &format!("Postpone {} reminder(s)?", changes.len())) {
+                        println!("Postpone cancelled");
+                        return Ok(());
+                    }
+
+                    let updated = crate::todo::apply_due_date_changes(&changes).await?;
+                    println!("Postponed {} of {} reminder(s)", updated, changes.len());
                     Ok(())
                 }
                 _ => {
-                    println!("Unknown todo command. Available commands: create/add, list, delete");
+                    println!(
+                        "Unknown todo command. Available commands: create/add, list, delete, board, move, postpone"
+                    );
                     Ok(())
                 }
             }
@@ -662,6 +699,53 @@ impl CommandHandler for TodoHandler {
     }
 }
 
+/// Due date, priority and recurrence options shared by `todo create` and `reminder create`
+pub(crate) struct ReminderOptions {
+    /// "YYYY-MM-DD" for an all-day due date or "YYYY-MM-DD HH:MM"
+    pub(crate) due_date: Option<String>,
+    pub(crate) priority: Option<crate::todo::TodoPriority>,
+    pub(crate) recurrence: Option<crate::calendar::RecurrencePattern>,
+}
+
+impl ReminderOptions {
+    /// Read `--due`, `--priority`, `--repeat`, `--interval`, `--until` and `--count`
+    ///
+    /// Dates may be given in natural language ("friday", "end of month at 5pm").
+    pub(crate) fn from_flags(flags: &HashMap<String, Option<String>>) -> Result<Self> {
+        let flag = |name: &str| flags.get(name).and_then(|v| v.as_deref());
+
+        let priority = flag("priority").map(crate::todo::TodoPriority::from_str).transpose()?;
+        crate::validation::exclusive_flags(flags, &[("until", "count")])?;
+        let recurrence = match flag("repeat").or(flag("recurring")) {
+            Some(freq) => {
+                let frequency = crate::calendar::RecurrenceFrequency::from_str(freq)?;
+                let mut recurrence = crate::calendar::RecurrencePattern::new(frequency);
+                if let Some(interval) = flag("interval") {
+                    let interval = interval
+                        .parse::<u32>()
+                        .map_err(|_| anyhow!("Invalid repeat interval: {}", interval))?;
+                    recurrence = recurrence.with_interval(interval);
+                }
+                if let Some(until) = flag("until") {
+                    recurrence =
+                        recurrence.with_end_date(&crate::validation::date("--until", until)?);
+                }
+                if let Some(count) = flag("count") {
+                    let count = count
+                        .parse::<u32>()
+                        .map_err(|_| anyhow!("Invalid repeat count: {}", count))?;
+                    recurrence = recurrence.with_count(count);
+                }
+                Some(recurrence)
+            }
+            None => None,
+        };
+
+        let due_date = flag("due").map(crate::dates::resolve_due).transpose()?;
+        Ok(Self { due_date, priority, recurrence })
+    }
+}
+
 // Notes handler
 #[derive(Debug)]
 pub struct NotesHandler;
@@ -670,72 +754,21 @@ impl CommandHandler for NotesHandler {
     fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
         Box::pin(async move {
             match args.args.first().map(|s| s.as_str()) {
-                Some("create") | Some("add") => {
-                    if args.args.len() < 2 {
-                        println!("Not enough arguments for note create command");
-                        println!(
-                            "Usage: ducktape note create <title> [content] [--folder <folder_name>]"
-                        );
-                        return Ok(());
-                    }
-
-                    // Combine all non-flag arguments after "create" into a single title if not quoted
-                    // This handles cases like "ducktape note create Project ideas for Q2"
-                    let mut title_parts = Vec::new();
-                    let mut i = 1;
-                    while i < args.args.len() && !args.args[i].starts_with("--") {
-                        title_parts.push(args.args[i].trim_matches('"'));
-                        i += 1;
-                    }
-
-                    // If we have multiple parts and the first doesn't contain spaces (which would indicate quotes were used)
-                    let title = if title_parts.len() > 1 && !args.args[1].contains(' ') {
-                        title_parts.join(" ")
-                    } else {
-                        args.args[1].trim_matches('"').to_string()
-                    };
-
-                    // Get content from --content flag or as the next positional argument after title
-                    let content = if let Some(Some(content_val)) = args.flags.get("content") {
-                        content_val.trim_matches('"')
-                    } else if args.args.len() > 2 && !args.args[2].starts_with("--") {
-                        args.args[2].trim_matches('"')
-                    } else {
-                        ""
-                    };
-
-                    // Get folder from --folder flag
-                    let folder = args.flags.get("folder").and_then(|f| f.as_deref());
-
-                    debug!(
-                        "Creating note: title='{}', content_length={}, folder={:?}",
-                        title,
-                        content.len(),
-                        folder
-                    );
-
-                    // Create note config using the new structure
-                    let config = crate::notes::NoteConfig { title: &title, content, folder };
-
-                    match crate::notes::create_note(config).await {
-                        Ok(_) => {
-                            println!("Note created successfully: {}", title);
-                            Ok(())
-                        }
-                        Err(e) => {
-                            println!("Failed to create note: {}", e);
-                            Err(anyhow!("Failed to create note: {}", e))
-                        }
-                    }
-                }
+                Some("create") | Some("add") => crate::command_bus::run_legacy(args).await,
                 Some("list") => match crate::notes::list_notes().await {
                     Ok(notes) => {
                         if notes.is_empty() {
-                            println!("No notes found");
+                            println!("{}", crate::i18n::text(crate::i18n::Message::NoNotes));
                         } else {
                             println!("Notes:");
+                            let colors = crate::theme::Colors::stdout();
                             for note in notes {
-                                println!("  - {} (in folder: {})", note.title, note.folder);
+                                println!(
+                                    "  - {} {}{}",
+                                    note.title,
+                                    colors.calendar(&format!("(in folder: {})", note.folder)),
+                                    if note.locked { " [locked]" } else { "" }
+                                );
                             }
                         }
                         Ok(())
@@ -745,6 +778,47 @@ impl CommandHandler for NotesHandler {
                         Err(e)
                     }
                 },
+                Some("folders") if args.args.get(1).is_some_and(|a| a == "create") => {
+                    let Some(name) = args.args.get(2).map(|n| n.trim_matches('"')) else {
+                        println!("Usage: ducktape note folders create <name>");
+                        return Ok(());
+                    };
+                    match crate::notes::create_folder(name).await {
+                        Ok(_) => {
+                            println!("Folder created: {}", name);
+                            Ok(())
+                        }
+                        Err(e) => {
+                            println!("Failed to create folder: {}", e);
+                            Err(e)
+                        }
+                    }
+                }
+                Some("folders") if args.args.get(1).is_some_and(|a| a == "delete") => {
+                    let Some(name) = args.args.get(2).map(|n| n.trim_matches('"')) else {
+                        println!("Usage: ducktape note folders delete <name> [--yes]");
+                        return Ok(());
+                    };
+                    let notes = crate::notes::list_notes().await?;
+                    let count =
+                        notes.iter().filter(|n| n.folder.eq_ignore_ascii_case(name)).count();
+                    let prompt =
+                        format!("Delete folder '{}' and the {} note(s) in it?", name, count);
+                    if !confirmed(&args, &prompt) {
+                        println!("{}", crate::i18n::text(crate::i18n::Message::NothingDeleted));
+                        return Ok(());
+                    }
+                    match crate::notes::delete_folder(name).await {
+                        Ok(_) => {
+                            println!("Folder deleted: {}", name);
+                            Ok(())
+                        }
+                        Err(e) => {
+                            println!("Failed to delete folder: {}", e);
+                            Err(e)
+                        }
+                    }
+                }
                 Some("folders") => match crate::notes::get_note_folders().await {
                     Ok(folders) => {
                         if folders.is_empty() {
@@ -797,6 +871,94 @@ impl CommandHandler for NotesHandler {
                         }
                     }
                 }
+                Some("move") => {
+                    let to = args.flags.get("to").cloned().flatten();
+                    let (Some(to), true) = (to, args.args.len() >= 2) else {
+                        println!("Not enough arguments for note move command");
+                        println!(
+                            "Usage: ducktape note move <title> --to <folder_name> [--folder <folder_name>]"
+                        );
+                        return Ok(());
+                    };
+
+                    let title = args.args[1..].join(" ");
+                    let title = title.trim_matches('"');
+                    let folder = args.flags.get("folder").and_then(|f| f.as_deref());
+
+                    match crate::notes::move_note(title, folder, &to).await {
+                        Ok(_) => {
+                            println!("Moved note '{}' to {}", title, to);
+                            Ok(())
+                        }
+                        Err(e) => {
+                            println!("Failed to move note: {}", e);
+                            Err(e)
+                        }
+                    }
+                }
+                Some("append") => {
+                    if args.args.len() < 3 {
+                        println!("Not enough arguments for note append command");
+                        println!(
+                            "Usage: ducktape note append <title> <text> [--folder <folder_name>] [--markdown | --raw]"
+                        );
+                        return Ok(());
+                    }
+
+                    let title = args.args[1].trim_matches('"');
+                    let text = args.args[2..].join(" ");
+                    let folder = args.flags.get("folder").and_then(|f| f.as_deref());
+                    let markdown = args.flags.contains_key("markdown");
+                    let text = text.trim_matches('"');
+                    let text = if markdown {
+                        text.to_string()
+                    } else {
+                        crate::utils::sanitize_notes(text, args.flags.contains_key("raw"))
+                    };
+
+                    match crate::notes::append_note(title, &text, folder, markdown).await {
+                        Ok(_) => {
+                            println!("Appended to note: {}", title);
+                            Ok(())
+                        }
+                        Err(e) => {
+                            println!("Failed to append to note: {}", e);
+                            Err(e)
+                        }
+                    }
+                }
+                Some("edit") | Some("update") => {
+                    let content = args.flags.get("content").cloned().flatten();
+                    let (Some(content), true) = (content, args.args.len() >= 2) else {
+                        println!("Not enough arguments for note edit command");
+                        println!(
+                            "Usage: ducktape note edit <title> --content <text> [--folder <folder_name>] [--markdown | --raw]"
+                        );
+                        return Ok(());
+                    };
+
+                    let title = args.args[1..].join(" ");
+                    let title = title.trim_matches('"');
+                    let folder = args.flags.get("folder").and_then(|f| f.as_deref());
+                    let markdown = args.flags.contains_key("markdown");
+                    let content = content.trim_matches('"');
+                    let content = if markdown {
+                        content.to_string()
+                    } else {
+                        crate::utils::sanitize_notes(content, args.flags.contains_key("raw"))
+                    };
+
+                    match crate::notes::edit_note(title, &content, folder, markdown).await {
+                        Ok(_) => {
+                            println!("Note updated: {}", title);
+                            Ok(())
+                        }
+                        Err(e) => {
+                            println!("Failed to update note: {}", e);
+                            Err(e)
+                        }
+                    }
+                }
                 Some("search") => {
                     if args.args.len() < 2 {
                         println!("Not enough arguments for note search command");
@@ -825,8 +987,14 @@ impl CommandHandler for NotesHandler {
                                 println!("No notes found matching '{}'", keyword);
                             } else {
                                 println!("Notes matching '{}':", keyword);
+                                let colors = crate::theme::Colors::stdout();
                                 for note in notes {
-                                    println!("  - {} (in folder: {})", note.title, note.folder);
+                                    println!(
+                                        "  - {} {}{}",
+                                        note.title,
+                                        colors.calendar(&format!("(in folder: {})", note.folder)),
+                                        if note.locked { " [locked]" } else { "" }
+                                    );
                                 }
                             }
                             Ok(())
@@ -839,7 +1007,7 @@ impl CommandHandler for NotesHandler {
                 }
                 _ => {
                     println!(
-                        "Unknown notes command. Available commands: create/add, list, folders, delete, search"
+                        "Unknown notes command. Available commands: create/add, list, folders, move, append, edit, delete, search"
                     );
                     Ok(())
                 }
@@ -870,8 +1038,8 @@ impl CommandHandler for ConfigHandler {
                     let key = &args.args[1];
                     let value = &args.args[2];
 
-                    // Load config
-                    let mut config = crate::config::Config::load()?;
+                    // Load config without the active profile, which must not be saved globally
+                    let mut config = crate::config::Config::load_global()?;
 
                     // Update config based on key
                     match key.as_str() {
@@ -894,150 +1062,3064 @@ impl CommandHandler for ConfigHandler {
                                 return Ok(());
                             }
                         }
-                        "todo.default_list" => {
-                            config.todo.default_list = Some(value.clone());
+                        "calendar.meeting_provider" => {
+                            match crate::config::MeetingService::from_str(value) {
+                                Ok(service) => config.calendar.meeting_provider = service,
+                                Err(e) => {
+                                    println!("{}", e);
+                                    return Ok(());
+                                }
+                            }
                         }
-                        "notes.default_folder" => {
-                            config.notes.default_folder = Some(value.clone());
+                        "calendar.invite_emails" => {
+                            if let Ok(invite) = value.parse::<bool>() {
+                                config.calendar.invite_emails = invite;
+                            } else {
+                                println!("Invalid value: {} (expected true or false)", value);
+                                return Ok(());
+                            }
                         }
-                        "language_model.provider" => match value.to_lowercase().as_str() {
-                            "grok" => {
-                                config.language_model.provider =
-                                    Some(crate::config::LLMProvider::Grok);
+                        "calendar.invite_via" => {
+                            if value == "none" || value.is_empty() {
+                                config.calendar.invite_via = None;
+                            } else {
+                                match crate::config::InviteSender::from_str(value) {
+                                    Ok(sender) => config.calendar.invite_via = Some(sender),
+                                    Err(e) => {
+                                        println!("{}", e);
+                                        return Ok(());
+                                    }
+                                }
                             }
-                            "deepseek" => {
-                                config.language_model.provider =
-                                    Some(crate::config::LLMProvider::DeepSeek);
+                        }
+                        "smtp.host" | "smtp.username" | "smtp.from" => {
+                            let setting = match key.as_str() {
+                                "smtp.host" => &mut config.smtp.host,
+                                "smtp.username" => &mut config.smtp.username,
+                                _ => &mut config.smtp.from,
+                            };
+                            *setting =
+                                (value != "none" && !value.is_empty()).then(|| value.to_string());
+                        }
+                        "smtp.port" => {
+                            if value == "none" || value.is_empty() {
+                                config.smtp.port = None;
+                            } else if let Ok(port) = value.parse::<u16>() {
+                                config.smtp.port = Some(port);
+                            } else {
+                                println!("Invalid port: {}", value);
+                                return Ok(());
                             }
-                            _ => {
-                                println!("Invalid language model provider: {}", value);
-                                println!("Valid options are: grok, deepseek");
+                        }
+                        "smtp.security" => match crate::config::SmtpSecurity::from_str(value) {
+                            Ok(security) => config.smtp.security = security,
+                            Err(e) => {
+                                println!("{}", e);
                                 return Ok(());
                             }
                         },
-                        _ => {
-                            println!("Unknown config key: {}", key);
-                            return Ok(());
+                        alias_key if alias_key.starts_with("calendar.alias.") => {
+                            let alias = alias_key["calendar.alias.".len()..].trim().to_lowercase();
+                            if alias.is_empty() {
+                                println!(
+                                    "Usage: ducktape config set calendar.alias.<alias> <calendar>"
+                                );
+                                return Ok(());
+                            }
+                            if value == "none" || value.is_empty() {
+                                config.calendar.aliases.remove(&alias);
+                            } else {
+                                config.calendar.aliases.insert(alias, value.clone());
+                            }
                         }
-                    }
-
-                    // Save updated config
-                    config.save()?;
-                    println!("Config updated: {} = {}", key, value);
-                    Ok(())
-                }
-                Some("get") | Some("show") => {
-                    if args.args.len() < 2 {
-                        println!("Not enough arguments for config get/show command");
-                        println!("Usage: ducktape config get <key> or ducktape config show <key>");
-                        return Ok(());
-                    }
-
-                    let key = &args.args[1];
-                    let config = crate::config::Config::load()?;
-
-                    // Get config value based on key
-                    match key.as_str() {
-                        "calendar.default" => {
-                            println!(
-                                "calendar.default = {}",
+                        template_key if template_key.starts_with("calendar.description.") => {
+                            let calendar = template_key["calendar.description.".len()..].trim();
+                            if calendar.is_empty() {
+                                println!(
+                                    "Usage: ducktape config set calendar.description.<calendar|*> <template>"
+                                );
+                                return Ok(());
+                            }
+                            if value == "none" || value.is_empty() {
+                                config.calendar.description_templates.remove(calendar);
+                            } else {
                                 config
                                     .calendar
-                                    .default_calendar
-                                    .unwrap_or_else(|| "Not set".to_string())
-                            );
+                                    .description_templates
+                                    .insert(calendar.to_string(), value.clone());
+                            }
                         }
-                        "calendar.reminder" => {
-                            println!(
-                                "calendar.reminder = {}",
-                                config
-                                    .calendar
-                                    .default_reminder_minutes
-                                    .map_or_else(|| "Not set".to_string(), |m| m.to_string())
-                            );
+                        "todo.default_list" => {
+                            config.todo.default_list = Some(value.clone());
                         }
-                        "calendar.duration" => {
-                            println!(
-                                "calendar.duration = {}",
-                                config
-                                    .calendar
-                                    .default_duration_minutes
-                                    .map_or_else(|| "Not set".to_string(), |m| m.to_string())
-                            );
+                        "todo.board.backlog" => {
+                            config.todo.board.backlog = value.clone();
                         }
-                        "todo.default_list" => {
-                            println!(
-                                "todo.default_list = {}",
-                                config.todo.default_list.unwrap_or_else(|| "Not set".to_string())
-                            );
+                        "todo.board.doing" => {
+                            config.todo.board.doing = value.clone();
+                        }
+                        "todo.board.done" => {
+                            config.todo.board.done = value.clone();
                         }
                         "notes.default_folder" => {
-                            println!(
-                                "notes.default_folder = {}",
-                                config
-                                    .notes
-                                    .default_folder
-                                    .unwrap_or_else(|| "Not set".to_string())
-                            );
+                            config.notes.default_folder = Some(value.clone());
                         }
-                        "language_model.provider" => {
-                            let provider = match config.language_model.provider {
-                                Some(crate::config::LLMProvider::Grok) => "grok",
-                                Some(crate::config::LLMProvider::DeepSeek) => "deepseek",
-                                None => "none",
-                            };
-                            println!("language_model.provider = {}", provider);
+                        "scripting.osascript_path" => {
+                            config.scripting.osascript_path = Some(value.clone());
                         }
-                        "all" => {
-                            println!("Current Configuration:");
-                            println!("======================");
-                            println!(
-                                "calendar.default = {}",
-                                config
-                                    .calendar
-                                    .default_calendar
-                                    .unwrap_or_else(|| "Not set".to_string())
-                            );
-                            println!(
-                                "calendar.reminder = {}",
-                                config
-                                    .calendar
-                                    .default_reminder_minutes
-                                    .map_or_else(|| "Not set".to_string(), |m| m.to_string())
-                            );
-                            println!(
-                                "calendar.duration = {}",
-                                config
-                                    .calendar
-                                    .default_duration_minutes
-                                    .map_or_else(|| "Not set".to_string(), |m| m.to_string())
-                            );
-                            println!(
-                                "todo.default_list = {}",
-                                config.todo.default_list.unwrap_or_else(|| "Not set".to_string())
-                            );
-                            println!(
-                                "notes.default_folder = {}",
-                                config
-                                    .notes
-                                    .default_folder
-                                    .unwrap_or_else(|| "Not set".to_string())
-                            );
-                            let provider = match config.language_model.provider {
-                                Some(crate::config::LLMProvider::Grok) => "grok",
-                                Some(crate::config::LLMProvider::DeepSeek) => "deepseek",
-                                None => "none",
-                            };
-                            println!("language_model.provider = {}", provider);
+                        "backend.calendar" => {
+                            match crate::config::CalendarBackendKind::from_str(value) {
+                                Ok(kind) => config.backend.calendar = Some(kind),
+                                Err(e) => {
+                                    println!("{}", e);
+                                    return Ok(());
+                                }
+                            }
                         }
-                        _ => {
-                            println!("Unknown config key: {}", key);
+                        "backend.todo" => match crate::config::TodoBackendKind::from_str(value) {
+                            Ok(kind) => config.backend.todo = Some(kind),
+                            Err(e) => {
+                                println!("{}", e);
+                                return Ok(());
+                            }
+                        },
+                        "backend.notes" => match crate::config::NotesBackendKind::from_str(value) {
+                            Ok(kind) => config.backend.notes = Some(kind),
+                            Err(e) => {
+                                println!("{}", e);
+                                return Ok(());
+                            }
+                        },
+                        "backend.data_dir" => {
+                            config.backend.data_dir = Some(value.clone());
+                        }
+                        "backend.caldav.url" => {
+                            config.backend.caldav.url = Some(value.clone());
+                        }
+                        "backend.caldav.username" => {
+                            config.backend.caldav.username = Some(value.clone());
+                        }
+                        "scripting.osascript_args" => {
+                            config.scripting.osascript_args =
+                                value.split_whitespace().map(String::from).collect();
+                        }
+                        "scripting.max_concurrent" => match value.parse::<usize>() {
+                            Ok(limit) if limit > 0 => config.scripting.max_concurrent = limit,
+                            _ => {
+                                println!("Invalid max concurrent scripts value: {}", value);
+                                return Ok(());
+                            }
+                        },
+                        "notifications.cooldown_minutes" => {
+                            if let Ok(minutes) = value.parse::<u32>() {
+                                config.notifications.cooldown_minutes = minutes;
+                            } else {
+                                println!("Invalid cooldown minutes value: {}", value);
+                                return Ok(());
+                            }
+                        }
+                        "notifications.max_per_item_per_day" => {
+                            if let Ok(max) = value.parse::<u32>() {
+                                config.notifications.max_per_item_per_day = max;
+                            } else {
+                                println!("Invalid notifications per day value: {}", value);
+                                return Ok(());
+                            }
+                        }
+                        "webhooks.events" => {
+                            config.webhooks.events = value
+                                .split(',')
+                                .map(|e| e.trim().to_lowercase())
+                                .filter(|e| !e.is_empty())
+                                .collect();
+                        }
+                        "webhooks.max_attempts" => match value.parse::<u32>() {
+                            Ok(attempts) if attempts > 0 => config.webhooks.max_attempts = attempts,
+                            _ => {
+                                println!("Invalid webhook attempts value: {}", value);
+                                return Ok(());
+                            }
+                        },
+                        "webhooks.timeout_secs" => match value.parse::<u64>() {
+                            Ok(seconds) if seconds > 0 => config.webhooks.timeout_secs = seconds,
+                            _ => {
+                                println!("Invalid webhook timeout value: {}", value);
+                                return Ok(());
+                            }
+                        },
+                        "api.max_body_bytes" => match value.parse::<usize>() {
+                            Ok(bytes) if bytes > 0 => config.api.max_body_bytes = bytes,
+                            _ => {
+                                println!("Invalid body size: {}", value);
+                                return Ok(());
+                            }
+                        },
+                        "api.trust_proxy" => match value.parse::<bool>() {
+                            Ok(trust) => config.api.trust_proxy = trust,
+                            Err(_) => {
+                                println!("Invalid value: {} (expected true or false)", value);
+                                return Ok(());
+                            }
+                        },
+                        "api.rate_limit.enabled" => match value.parse::<bool>() {
+                            Ok(enabled) => config.api.rate_limit.enabled = enabled,
+                            Err(_) => {
+                                println!("Invalid value: {} (expected true or false)", value);
+                                return Ok(());
+                            }
+                        },
+                        "api.rate_limit.per_ip_per_minute" => match value.parse::<u32>() {
+                            Ok(rate) if rate > 0 => config.api.rate_limit.per_ip_per_minute = rate,
+                            _ => {
+                                println!("Invalid rate limit: {}", value);
+                                return Ok(());
+                            }
+                        },
+                        "api.rate_limit.per_key_per_minute" => match value.parse::<u32>() {
+                            Ok(rate) if rate > 0 => config.api.rate_limit.per_key_per_minute = rate,
+                            _ => {
+                                println!("Invalid rate limit: {}", value);
+                                return Ok(());
+                            }
+                        },
+                        "locale" => match crate::i18n::Locale::from_tag(value) {
+                            Ok(locale) => {
+                                config.locale = Some(locale.tag.clone());
+                                crate::i18n::Locale::set_current(locale);
+                            }
+                            Err(e) => {
+                                println!("{}", e);
+                                return Ok(());
+                            }
+                        },
+                        "holidays.country" => {
+                            if value == "none" || value.is_empty() {
+                                config.holidays.country = None;
+                            } else {
+                                match crate::holidays::Country::from_str(value) {
+                                    Ok(country) => {
+                                        config.holidays.country = Some(country.code().to_string())
+                                    }
+                                    Err(e) => {
+                                        println!("{}", e);
+                                        return Ok(());
+                                    }
+                                }
+                            }
+                        }
+                        "ui.theme" => match crate::theme::Theme::from_str(value) {
+                            Ok(theme) => config.ui.theme = theme,
+                            Err(e) => {
+                                println!("{}", e);
+                                return Ok(());
+                            }
+                        },
+                        "sync.conflicts" => match crate::config::SyncConflicts::from_str(value) {
+                            Ok(rule) => config.sync.conflicts = rule,
+                            Err(e) => {
+                                println!("{}", e);
+                                return Ok(());
+                            }
+                        },
+                        "ui.trace" => match crate::parser::trace::TraceLevel::from_str(value) {
+                            Ok(level) => config.ui.trace = level,
+                            Err(e) => {
+                                println!("{}", e);
+                                return Ok(());
+                            }
+                        },
+                        "ui.confirm" => match value.parse::<bool>() {
+                            Ok(confirm) => config.ui.confirm = confirm,
+                            Err(_) => {
+                                println!("Invalid value: {} (expected true or false)", value);
+                                return Ok(());
+                            }
+                        },
+                        "audit.enabled" => match value.parse::<bool>() {
+                            Ok(enabled) => config.audit.enabled = enabled,
+                            Err(_) => {
+                                println!("Invalid value: {} (expected true or false)", value);
+                                return Ok(());
+                            }
+                        },
+                        "audit.max_bytes" => match value.parse::<u64>() {
+                            Ok(bytes) if bytes > 0 => config.audit.max_bytes = bytes,
+                            _ => {
+                                println!("Invalid size: {}", value);
+                                return Ok(());
+                            }
+                        },
+                        "audit.keep_files" => match value.parse::<usize>() {
+                            Ok(keep) => config.audit.keep_files = keep,
+                            Err(_) => {
+                                println!("Invalid number of files: {}", value);
+                                return Ok(());
+                            }
+                        },
+                        "focus.calendar" => {
+                            config.focus.calendar =
+                                Some(value.clone()).filter(|v| !v.trim().is_empty());
+                        }
+                        "focus.dnd_on_shortcut" => config.focus.dnd_on_shortcut = value.clone(),
+                        "focus.dnd_off_shortcut" => config.focus.dnd_off_shortcut = value.clone(),
+                        "event_search.providers" => {
+                            let providers: Vec<String> = value
+                                .split(',')
+                                .map(|p| p.trim().to_lowercase())
+                                .filter(|p| !p.is_empty())
+                                .collect();
+                            if let Some(unknown) = providers
+                                .iter()
+                                .find(|p| crate::event_search::SearchProvider::parse(p).is_none())
+                            {
+                                println!("Invalid event search provider: {}", unknown);
+                                println!("Valid options are: grok, serpapi, brave, bing");
+                                return Ok(());
+                            }
+                            config.event_search.providers = providers;
+                        }
+                        "event_search.max_results" => match value.parse::<usize>() {
+                            Ok(max) if max > 0 => config.event_search.max_results = max,
+                            _ => {
+                                println!("Invalid number of results: {}", value);
+                                return Ok(());
+                            }
+                        },
+                        "nlp.providers" => {
+                            let providers: Vec<String> = value
+                                .split(',')
+                                .map(|p| p.trim().to_lowercase())
+                                .filter(|p| !p.is_empty())
+                                .collect();
+                            if let Some(unknown) = providers.iter().find(|p| {
+                                !["grok", "deepseek", "terminal", "command"].contains(&p.as_str())
+                            }) {
+                                println!("Invalid parser provider: {}", unknown);
+                                println!("Valid options are: grok, deepseek, terminal, command");
+                                return Ok(());
+                            }
+                            config.nlp.providers = providers;
+                        }
+                        "nlp.timeout_secs" => {
+                            if let Ok(secs) = value.parse::<u64>() {
+                                config.nlp.timeout_secs = secs;
+                            } else {
+                                println!("Invalid timeout value: {}", value);
+                                return Ok(());
+                            }
+                        }
+                        "nlp.cache_ttl_hours" => {
+                            if let Ok(hours) = value.parse::<u64>() {
+                                config.nlp.cache_ttl_hours = hours;
+                            } else {
+                                println!("Invalid cache TTL value: {}", value);
+                                return Ok(());
+                            }
+                        }
+                        "nlp.context_minutes" => {
+                            if let Ok(minutes) = value.parse::<u64>() {
+                                config.nlp.context_minutes = minutes;
+                            } else {
+                                println!("Invalid number of minutes: {}", value);
+                                return Ok(());
+                            }
+                        }
+                        "nlp.clarify" => match value.parse::<bool>() {
+                            Ok(clarify) => config.nlp.clarify = clarify,
+                            Err(_) => {
+                                println!("Invalid value: {} (expected true or false)", value);
+                                return Ok(());
+                            }
+                        },
+                        "nlp.split_requests" => match value.parse::<bool>() {
+                            Ok(split) => config.nlp.split_requests = split,
+                            Err(_) => {
+                                println!("Invalid value: {} (expected true or false)", value);
+                                return Ok(());
+                            }
+                        },
+                        "nlp.extra_instructions" => {
+                            let value = value.trim().trim_matches('"').trim();
+                            config.nlp.extra_instructions =
+                                (value != "none" && !value.is_empty()).then(|| value.to_string());
+                        }
+                        "usage.monthly_budget_usd" => {
+                            if value == "none" || value.is_empty() {
+                                config.usage.monthly_budget_usd = None;
+                            } else if let Ok(budget) = value.parse::<f64>() {
+                                config.usage.monthly_budget_usd = Some(budget);
+                            } else {
+                                println!("Invalid budget value: {}", value);
+                                return Ok(());
+                            }
+                        }
+                        "usage.block_over_budget" => {
+                            if let Ok(block) = value.parse::<bool>() {
+                                config.usage.block_over_budget = block;
+                            } else {
+                                println!("Invalid value: {} (expected true or false)", value);
+                                return Ok(());
+                            }
+                        }
+                        "nlp.disabled_enhancements" => {
+                            let passes: Vec<String> = value
+                                .split(',')
+                                .map(|p| p.trim().to_lowercase())
+                                .filter(|p| !p.is_empty())
+                                .collect();
+                            let known: Vec<&str> =
+                                crate::parser::enhance::PASSES.iter().map(|p| p.name).collect();
+                            if let Some(unknown) =
+                                passes.iter().find(|p| !known.contains(&p.as_str()))
+                            {
+                                println!("Unknown enhancement: {}", unknown);
+                                println!("Valid options are: {}", known.join(", "));
+                                return Ok(());
+                            }
+                            config.nlp.disabled_enhancements = passes;
+                        }
+                        "language_model.provider" => match value.to_lowercase().as_str() {
+                            "grok" => {
+                                config.language_model.provider =
+                                    Some(crate::config::LLMProvider::Grok);
+                            }
+                            "deepseek" => {
+                                config.language_model.provider =
+                                    Some(crate::config::LLMProvider::DeepSeek);
+                            }
+                            _ => {
+                                println!("Invalid language model provider: {}", value);
+                                println!("Valid options are: grok, deepseek");
+                                return Ok(());
+                            }
+                        },
+                        _ => {
+                            println!("Unknown config key: {}", key);
+                            return Ok(());
+                        }
+                    }
+
+                    // Save updated config
+                    config.save()?;
+                    println!("Config updated: {} = {}", key, value);
+                    Ok(())
+                }
+                Some("get") | Some("show") => {
+                    if args.args.len() < 2 {
+                        println!("Not enough arguments for config get/show command");
+                        println!("Usage: ducktape config get <key> or ducktape config show <key>");
+                        return Ok(());
+                    }
+
+                    let key = &args.args[1];
+                    let config = crate::config::Config::load()?;
+
+                    // Get config value based on key
+                    match key.as_str() {
+                        "calendar.default" => {
+                            println!(
+                                "calendar.default = {}",
+                                config
+                                    .calendar
+                                    .default_calendar
+                                    .unwrap_or_else(|| "Not set".to_string())
+                            );
+                        }
+                        "calendar.reminder" => {
+                            println!(
+                                "calendar.reminder = {}",
+                                config
+                                    .calendar
+                                    .default_reminder_minutes
+                                    .map_or_else(|| "Not set".to_string(), |m| m.to_string())
+                            );
+                        }
+                        "calendar.duration" => {
+                            println!(
+                                "calendar.duration = {}",
+                                config
+                                    .calendar
+                                    .default_duration_minutes
+                                    .map_or_else(|| "Not set".to_string(), |m| m.to_string())
+                            );
+                        }
+                        "calendar.meeting_provider" => {
+                            println!(
+                                "calendar.meeting_provider = {}",
+                                config.calendar.meeting_provider.as_str()
+                            );
+                        }
+                        "calendar.invite_emails" => {
+                            println!("calendar.invite_emails = {}", config.calendar.invite_emails);
+                        }
+                        "calendar.invite_via" => {
+                            println!(
+                                "calendar.invite_via = {}",
+                                crate::calendar::invite_sender(&config).as_str()
+                            );
+                        }
+                        "smtp" => {
+                            println!(
+                                "smtp.host = {}",
+                                config.smtp.host.as_deref().unwrap_or("Not set")
+                            );
+                            println!(
+                                "smtp.port = {}",
+                                config
+                                    .smtp
+                                    .port
+                                    .map_or_else(|| "Not set".to_string(), |p| p.to_string())
+                            );
+                            println!(
+                                "smtp.username = {}",
+                                config.smtp.username.as_deref().unwrap_or("Not set")
+                            );
+                            println!(
+                                "smtp.from = {}",
+                                config.smtp.from.as_deref().unwrap_or("Not set")
+                            );
+                            println!("smtp.security = {}", config.smtp.security.as_str());
+                        }
+                        "calendar.aliases" => {
+                            if config.calendar.aliases.is_empty() {
+                                println!("No calendar aliases set");
+                            }
+                            for (alias, calendar) in &config.calendar.aliases {
+                                println!("calendar.alias.{} = {}", alias, calendar);
+                            }
+                        }
+                        "calendar.descriptions" => {
+                            if config.calendar.description_templates.is_empty() {
+                                println!("No description templates set");
+                            }
+                            for (calendar, template) in &config.calendar.description_templates {
+                                println!("calendar.description.{} = {}", calendar, template);
+                            }
+                            println!("calendar.invite_emails = {}", config.calendar.invite_emails);
+                            println!(
+                                "calendar.invite_via = {}",
+                                crate::calendar::invite_sender(&config).as_str()
+                            );
+                            println!(
+                                "smtp.host = {}",
+                                config.smtp.host.as_deref().unwrap_or("Not set")
+                            );
+                        }
+                        template_key if template_key.starts_with("calendar.description.") => {
+                            let calendar = &template_key["calendar.description.".len()..];
+                            println!(
+                                "{} = {}",
+                                template_key,
+                                config
+                                    .calendar
+                                    .description_templates
+                                    .get(calendar)
+                                    .cloned()
+                                    .unwrap_or_else(|| "Not set".to_string())
+                            );
+                        }
+                        alias_key if alias_key.starts_with("calendar.alias.") => {
+                            let alias = &alias_key["calendar.alias.".len()..];
+                            println!(
+                                "{} = {}",
+                                alias_key,
+                                config
+                                    .calendar
+                                    .aliases
+                                    .get(&alias.to_lowercase())
+                                    .cloned()
+                                    .unwrap_or_else(|| "Not set".to_string())
+                            );
+                        }
+                        "todo.default_list" => {
+                            println!(
+                                "todo.default_list = {}",
+                                config.todo.default_list.unwrap_or_else(|| "Not set".to_string())
+                            );
+                        }
+                        "todo.board.backlog" => {
+                            println!("todo.board.backlog = {}", config.todo.board.backlog);
+                        }
+                        "todo.board.doing" => {
+                            println!("todo.board.doing = {}", config.todo.board.doing);
+                        }
+                        "todo.board.done" => {
+                            println!("todo.board.done = {}", config.todo.board.done);
+                        }
+                        "notes.default_folder" => {
+                            println!(
+                                "notes.default_folder = {}",
+                                config
+                                    .notes
+                                    .default_folder
+                                    .unwrap_or_else(|| "Not set".to_string())
+                            );
+                        }
+                        "language_model.provider" => {
+                            let provider = match config.language_model.provider {
+                                Some(crate::config::LLMProvider::Grok) => "grok",
+                                Some(crate::config::LLMProvider::DeepSeek) => "deepseek",
+                                None => "none",
+                            };
+                            println!("language_model.provider = {}", provider);
+                        }
+                        "scripting.osascript_path" => {
+                            println!(
+                                "scripting.osascript_path = {}",
+                                config
+                                    .scripting
+                                    .osascript_path
+                                    .as_deref()
+                                    .unwrap_or(crate::script_runner::DEFAULT_OSASCRIPT)
+                            );
+                        }
+                        "scripting.osascript_args" => {
+                            println!(
+                                "scripting.osascript_args = {}",
+                                config.scripting.osascript_args.join(" ")
+                            );
+                        }
+                        "scripting.max_concurrent" => {
+                            println!(
+                                "scripting.max_concurrent = {}",
+                                config.scripting.max_concurrent
+                            );
+                        }
+                        "backend.calendar" => {
+                            println!(
+                                "backend.calendar = {}",
+                                config.backend.calendar_kind().as_str()
+                            );
+                        }
+                        "backend.todo" => {
+                            println!("backend.todo = {}", config.backend.todo_kind().as_str());
+                        }
+                        "backend.notes" => {
+                            println!("backend.notes = {}", config.backend.notes_kind().as_str());
+                        }
+                        "backend.data_dir" => match config.backend.data_dir() {
+                            Ok(dir) => println!("backend.data_dir = {}", dir.display()),
+                            Err(e) => println!("backend.data_dir = Not set ({})", e),
+                        },
+                        "backend.caldav.url" => {
+                            println!(
+                                "backend.caldav.url = {}",
+                                config.backend.caldav.url.as_deref().unwrap_or("Not set")
+                            );
+                        }
+                        "backend.caldav.username" => {
+                            println!(
+                                "backend.caldav.username = {}",
+                                config.backend.caldav.username.as_deref().unwrap_or("Not set")
+                            );
+                        }
+                        "notifications.cooldown_minutes" => {
+                            println!(
+                                "notifications.cooldown_minutes = {}",
+                                config.notifications.cooldown_minutes
+                            );
+                        }
+                        "notifications.max_per_item_per_day" => {
+                            println!(
+                                "notifications.max_per_item_per_day = {}",
+                                config.notifications.max_per_item_per_day
+                            );
+                        }
+                        "webhooks.urls" => {
+                            println!("webhooks.urls = {}", config.webhooks.urls.join(","));
+                        }
+                        "webhooks.events" => {
+                            println!("webhooks.events = {}", config.webhooks.events.join(","));
+                        }
+                        "webhooks.max_attempts" => {
+                            println!("webhooks.max_attempts = {}", config.webhooks.max_attempts);
+                        }
+                        "webhooks.timeout_secs" => {
+                            println!("webhooks.timeout_secs = {}", config.webhooks.timeout_secs);
+                        }
+                        "api.max_body_bytes" => {
+                            println!("api.max_body_bytes = {}", config.api.max_body_bytes);
+                        }
+                        "api.trust_proxy" => {
+                            println!("api.trust_proxy = {}", config.api.trust_proxy);
+                        }
+                        "api.rate_limit.enabled" => {
+                            println!("api.rate_limit.enabled = {}", config.api.rate_limit.enabled);
+                        }
+                        "api.rate_limit.per_ip_per_minute" => {
+                            println!(
+                                "api.rate_limit.per_ip_per_minute = {}",
+                                config.api.rate_limit.per_ip_per_minute
+                            );
+                        }
+                        "api.rate_limit.per_key_per_minute" => {
+                            println!(
+                                "api.rate_limit.per_key_per_minute = {}",
+                                config.api.rate_limit.per_key_per_minute
+                            );
+                        }
+                        "locale" => {
+                            let locale = crate::i18n::Locale::current();
+                            println!(
+                                "locale = {} (dates as {})",
+                                config.locale.as_deref().unwrap_or("Not set, en-US"),
+                                locale.date_pattern()
+                            );
+                        }
+                        "ui.confirm" => {
+                            println!("ui.confirm = {}", config.ui.confirm);
+                        }
+                        "ui.theme" => {
+                            println!("ui.theme = {}", config.ui.theme.as_str());
+                        }
+                        "ui.trace" => {
+                            println!("ui.trace = {}", config.ui.trace.as_str());
+                        }
+                        "holidays.country" => {
+                            println!(
+                                "holidays.country = {}",
+                                config.holidays.country.as_deref().unwrap_or("Not set")
+                            );
+                        }
+                        "sync.conflicts" => {
+                            println!("sync.conflicts = {}", config.sync.conflicts.as_str());
+                        }
+                        "audit.enabled" => {
+                            println!("audit.enabled = {}", config.audit.enabled);
+                        }
+                        "audit.max_bytes" => {
+                            println!("audit.max_bytes = {}", config.audit.max_bytes);
+                        }
+                        "audit.keep_files" => {
+                            println!("audit.keep_files = {}", config.audit.keep_files);
+                        }
+                        "focus.calendar" => {
+                            println!(
+                                "focus.calendar = {}",
+                                config.focus.calendar.as_deref().unwrap_or("Not set")
+                            );
+                        }
+                        "focus.dnd_on_shortcut" => {
+                            println!("focus.dnd_on_shortcut = {}", config.focus.dnd_on_shortcut);
+                        }
+                        "focus.dnd_off_shortcut" => {
+                            println!("focus.dnd_off_shortcut = {}", config.focus.dnd_off_shortcut);
+                        }
+                        "event_search.providers" => {
+                            println!(
+                                "event_search.providers = {}",
+                                config.event_search.providers.join(",")
+                            );
+                        }
+                        "event_search.max_results" => {
+                            println!(
+                                "event_search.max_results = {}",
+                                config.event_search.max_results
+                            );
+                        }
+                        "nlp.providers" => {
+                            println!("nlp.providers = {}", config.parser_chain().join(","));
+                        }
+                        "nlp.timeout_secs" => {
+                            println!("nlp.timeout_secs = {}", config.nlp.timeout_secs);
+                        }
+                        "nlp.cache_ttl_hours" => {
+                            println!("nlp.cache_ttl_hours = {}", config.nlp.cache_ttl_hours);
+                        }
+                        "nlp.clarify" => {
+                            println!("nlp.clarify = {}", config.nlp.clarify);
+                        }
+                        "nlp.context_minutes" => {
+                            println!("nlp.context_minutes = {}", config.nlp.context_minutes);
+                        }
+                        "nlp.split_requests" => {
+                            println!("nlp.split_requests = {}", config.nlp.split_requests);
+                        }
+                        "nlp.extra_instructions" => {
+                            println!(
+                                "nlp.extra_instructions = {}",
+                                config.nlp.extra_instructions.as_deref().unwrap_or("Not set")
+                            );
+                        }
+                        "nlp.disabled_enhancements" => {
+                            println!(
+                                "nlp.disabled_enhancements = {}",
+                                config.nlp.disabled_enhancements.join(",")
+                            );
+                        }
+                        "usage.monthly_budget_usd" => {
+                            println!(
+                                "usage.monthly_budget_usd = {}",
+                                config
+                                    .usage
+                                    .monthly_budget_usd
+                                    .map_or_else(|| "Not set".to_string(), |b| b.to_string())
+                            );
+                        }
+                        "usage.block_over_budget" => {
+                            println!(
+                                "usage.block_over_budget = {}",
+                                config.usage.block_over_budget
+                            );
+                        }
+                        "nlp.metrics" => {
+                            let metrics = crate::parser::fallback::provider_metrics();
+                            if metrics.is_empty() {
+                                println!("No parser providers have been used in this session");
+                            }
+                            for (provider, stats) in metrics {
+                                let average_ms = if stats.answered > 0 {
+                                    stats.answer_time.as_millis() / stats.answered as u128
+                                } else {
+                                    0
+                                };
+                                println!(
+                                    "{}: answered {} (avg {} ms), failed {}, skipped {}{}",
+                                    provider,
+                                    stats.answered,
+                                    average_ms,
+                                    stats.failed,
+                                    stats.skipped,
+                                    stats
+                                        .last_error
+                                        .map_or(String::new(), |e| format!(", last error: {}", e))
+                                );
+                            }
+                        }
+                        "all" => {
+                            println!("Current Configuration:");
+                            println!("======================");
+                            println!(
+                                "profile = {}",
+                                config.selected_profile().map_or("none".to_string(), |(n, _)| n)
+                            );
+                            println!(
+                                "calendar.default = {}",
+                                config
+                                    .calendar
+                                    .default_calendar
+                                    .unwrap_or_else(|| "Not set".to_string())
+                            );
+                            println!(
+                                "calendar.reminder = {}",
+                                config
+                                    .calendar
+                                    .default_reminder_minutes
+                                    .map_or_else(|| "Not set".to_string(), |m| m.to_string())
+                            );
+                            println!(
+                                "calendar.duration = {}",
+                                config
+                                    .calendar
+                                    .default_duration_minutes
+                                    .map_or_else(|| "Not set".to_string(), |m| m.to_string())
+                            );
+                            println!(
+                                "calendar.meeting_provider = {}",
+                                config.calendar.meeting_provider.as_str()
+                            );
+                            for (alias, calendar) in &config.calendar.aliases {
+                                println!("calendar.alias.{} = {}", alias, calendar);
+                            }
+                            for (calendar, template) in &config.calendar.description_templates {
+                                println!("calendar.description.{} = {}", calendar, template);
+                            }
+                            println!(
+                                "todo.default_list = {}",
+                                config.todo.default_list.unwrap_or_else(|| "Not set".to_string())
+                            );
+                            println!(
+                                "notes.default_folder = {}",
+                                config
+                                    .notes
+                                    .default_folder
+                                    .unwrap_or_else(|| "Not set".to_string())
+                            );
+                            let provider = match config.language_model.provider {
+                                Some(crate::config::LLMProvider::Grok) => "grok",
+                                Some(crate::config::LLMProvider::DeepSeek) => "deepseek",
+                                None => "none",
+                            };
+                            println!("language_model.provider = {}", provider);
+                            println!(
+                                "scripting.osascript_path = {}",
+                                config
+                                    .scripting
+                                    .osascript_path
+                                    .as_deref()
+                                    .unwrap_or(crate::script_runner::DEFAULT_OSASCRIPT)
+                            );
+                            println!(
+                                "backend.calendar = {}",
+                                config.backend.calendar_kind().as_str()
+                            );
+                            println!("backend.todo = {}", config.backend.todo_kind().as_str());
+                            println!("backend.notes = {}", config.backend.notes_kind().as_str());
+                            println!("ui.confirm = {}", config.ui.confirm);
+                            println!("ui.theme = {}", config.ui.theme.as_str());
+                            println!("ui.trace = {}", config.ui.trace.as_str());
+                            println!("locale = {}", config.locale.as_deref().unwrap_or("en-US"));
+                            println!(
+                                "holidays.country = {}",
+                                config.holidays.country.as_deref().unwrap_or("Not set")
+                            );
+                            println!("sync.conflicts = {}", config.sync.conflicts.as_str());
+                            println!(
+                                "notifications.cooldown_minutes = {}",
+                                config.notifications.cooldown_minutes
+                            );
+                            println!(
+                                "notifications.max_per_item_per_day = {}",
+                                config.notifications.max_per_item_per_day
+                            );
+                            println!("webhooks.urls = {}", config.webhooks.urls.join(","));
+                            println!("nlp.providers = {}", config.nlp.providers.join(","));
+                            println!("nlp.timeout_secs = {}", config.nlp.timeout_secs);
+                            println!("nlp.clarify = {}", config.nlp.clarify);
+                            println!("nlp.context_minutes = {}", config.nlp.context_minutes);
+                            println!("nlp.split_requests = {}", config.nlp.split_requests);
+                            println!(
+                                "nlp.extra_instructions = {}",
+                                config.nlp.extra_instructions.as_deref().unwrap_or("Not set")
+                            );
+                            println!(
+                                "usage.monthly_budget_usd = {}",
+                                config
+                                    .usage
+                                    .monthly_budget_usd
+                                    .map_or_else(|| "Not set".to_string(), |b| b.to_string())
+                            );
+                        }
+                        _ => {
+                            println!("Unknown config key: {}", key);
+                        }
+                    }
+                    Ok(())
+                }
+                Some("profile") => handle_profile_command(&args),
+                _ => {
+                    println!("Unknown config command. Available commands: set, get, show, profile");
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "config"
+    }
+}
+
+/// `config profile create|switch|show`
+fn handle_profile_command(args: &CommandArgs) -> Result<()> {
+    use crate::config::{Config, LLMProvider, ProfileConfig, ProfileSource};
+
+    let name = args.args.get(2).map(|s| s.trim().to_string());
+    let flag = |key: &str| args.flags.get(key).cloned().flatten();
+
+    match args.args.get(1).map(|s| s.as_str()).unwrap_or("show") {
+        "create" => {
+            let Some(name) = name else {
+                println!(
+                    "Usage: ducktape config profile create <name> [--calendar <name>] [--list <name>] [--folder <name>] [--provider grok|deepseek]"
+                );
+                return Ok(());
+            };
+            if name.is_empty()
+                || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                println!("Invalid profile name: {} (use letters, digits, - and _)", name);
+                return Ok(());
+            }
+            let provider = match flag("provider").map(|p| LLMProvider::from_str(&p)).transpose() {
+                Ok(provider) => provider,
+                Err(e) => {
+                    println!("{}", e);
+                    return Ok(());
+                }
+            };
+
+            let mut config = Config::load_global()?;
+            if config.profiles.contains_key(&name) {
+                println!("Profile '{}' already exists", name);
+                return Ok(());
+            }
+            config.profiles.insert(
+                name.clone(),
+                ProfileConfig {
+                    default_calendar: flag("calendar"),
+                    default_list: flag("list"),
+                    notes_folder: flag("folder"),
+                    provider,
+                },
+            );
+            config.save()?;
+            println!("Created profile '{}'", name);
+            println!(
+                "Use it with `ducktape --profile {} ...` or `ducktape config profile switch {}`",
+                name, name
+            );
+            Ok(())
+        }
+        "switch" => {
+            let Some(name) = name else {
+                println!("Usage: ducktape config profile switch <name|none>");
+                return Ok(());
+            };
+            let mut config = Config::load_global()?;
+            if name == "none" || name == "global" {
+                config.active_profile = None;
+                config.save()?;
+                println!("Using the global configuration");
+                return Ok(());
+            }
+            if !config.profiles.contains_key(&name) {
+                println!("Unknown profile: {}", name);
+                return Ok(());
+            }
+            config.active_profile = Some(name.clone());
+            config.save()?;
+            println!("Switched to profile '{}'", name);
+            if let Some((selected, source)) = config.selected_profile() {
+                if source != ProfileSource::Config {
+                    println!(
+                        "Note: '{}' is selected here by {:?} and takes precedence",
+                        selected, source
+                    );
+                }
+            }
+            Ok(())
+        }
+        "show" => {
+            let config = Config::load_global()?;
+            if let Some(name) = name {
+                let Some(profile) = config.profiles.get(&name) else {
+                    println!("Unknown profile: {}", name);
+                    return Ok(());
+                };
+                let show = |value: &Option<String>| {
+                    value.clone().unwrap_or_else(|| "(global)".to_string())
+                };
+                println!("Profile '{}':", name);
+                println!("  calendar.default = {}", show(&profile.default_calendar));
+                println!("  todo.default_list = {}", show(&profile.default_list));
+                println!("  notes.default_folder = {}", show(&profile.notes_folder));
+                println!(
+                    "  language_model.provider = {}",
+                    profile.provider.as_ref().map_or("(global)", |p| p.as_str())
+                );
+                return Ok(());
+            }
+
+            let selected = config.selected_profile();
+            match &selected {
+                Some((name, ProfileSource::Environment)) => {
+                    println!("Profile in effect: {} (from --profile or DUCKTAPE_PROFILE)", name)
+                }
+                Some((name, ProfileSource::Directory(path))) => {
+                    println!("Profile in effect: {} (from {})", name, path.display())
+                }
+                Some((name, ProfileSource::Config)) => {
+                    println!("Profile in effect: {} (active profile)", name)
+                }
+                None => println!("Profile in effect: none (global configuration)"),
+            }
+            if config.profiles.is_empty() {
+                println!(
+                    "No profiles defined. Create one with `ducktape config profile create <name>`"
+                );
+            } else {
+                println!("Profiles:");
+                for name in config.profiles.keys() {
+                    let marker = match &selected {
+                        Some((selected, _)) if selected == name => "*",
+                        _ => " ",
+                    };
+                    println!("  {} {}", marker, name);
+                }
+            }
+
+            let effective = Config::load()?;
+            println!("Effective settings:");
+            println!(
+                "  calendar.default = {}",
+                effective.calendar.default_calendar.unwrap_or_else(|| "Not set".to_string())
+            );
+            println!(
+                "  todo.default_list = {}",
+                effective.todo.default_list.unwrap_or_else(|| "Not set".to_string())
+            );
+            println!(
+                "  notes.default_folder = {}",
+                effective.notes.default_folder.unwrap_or_else(|| "Not set".to_string())
+            );
+            println!(
+                "  language_model.provider = {}",
+                effective.language_model.provider.as_ref().map_or("none", |p| p.as_str())
+            );
+            Ok(())
+        }
+        _ => {
+            println!("Unknown profile command. Available commands: create, switch, show");
+            Ok(())
+        }
+    }
+}
+
+// Utilities handler
+#[derive(Debug)]
+pub struct UtilitiesHandler;
+
+impl CommandHandler for UtilitiesHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            match args.args.first().map(|s| s.as_str()) {
+                Some("date") => {
+                    println!("Current date: {}", chrono::Local::now().format("%Y-%m-%d"));
+                    Ok(())
+                }
+                Some("time") => {
+                    println!("Current time: {}", chrono::Local::now().format("%H:%M:%S"));
+                    Ok(())
+                }
+                Some("datetime") => {
+                    println!(
+                        "Current date and time: {}",
+                        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+                    );
+                    Ok(())
+                }
+                Some("tz") => {
+                    let request = match crate::timezone::parse_tz_request(&args.args[1..]) {
+                        Ok(request) => request,
+                        Err(e) => {
+                            println!("{}", e);
+                            println!("Example: ducktape utils tz 14:00 EST to CET on 2025-05-01");
+                            return Ok(());
+                        }
+                    };
+                    let conversion = crate::timezone::convert(&request)?;
+                    if args.flags.contains_key("plain") {
+                        println!("{}", conversion.plain());
+                    } else {
+                        println!("{}", conversion.describe());
+                    }
+                    Ok(())
+                }
+                _ => {
+                    println!(
+                        "Unknown utility command. Available commands: date, time, datetime, tz"
+                    );
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "utility" || command == "utils"
+    }
+}
+
+// Contact groups handler
+#[derive(Debug)]
+pub struct ContactGroupsHandler;
+
+impl CommandHandler for ContactGroupsHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            match args.args.first().map(|s| s.as_str()) {
+                Some("create") => {
+                    if args.args.len() < 3 {
+                        println!("Not enough arguments for contact group create command");
+                        println!("Usage: ducktape contacts create <group_name> <emails...>");
+                        return Ok(());
+                    }
+
+                    let group_name = &args.args[1];
+                    let emails: Vec<String> = args.args.iter().skip(2).cloned().collect();
+
+                    if emails.is_empty() {
+                        println!("No email addresses provided");
+                        return Ok(());
+                    }
+
+                    // Validate email addresses
+                    for email in &emails {
+                        if let Err(e) = crate::validation::email(email) {
+                            println!("{}", e);
+                            return Ok(());
+                        }
+                    }
+
+                    // Create contact group
+                    let result = crate::contact_groups::create_group(group_name, &emails);
+                    match result {
+                        Ok(_) => {
+                            println!(
+                                "Created contact group '{}' with {} members",
+                                group_name,
+                                emails.len()
+                            );
+                        }
+                        Err(e) => {
+                            println!("Failed to create contact group: {}", e);
+                        }
+                    }
+                    Ok(())
+                }
+                Some("list") => {
+                    match crate::contact_groups::list_all_groups().await {
+                        Ok(groups) => {
+                            if groups.is_empty() {
+                                println!("No contact groups found");
+                            } else {
+                                println!("Available contact groups:");
+                                for group in groups {
+                                    println!("  - {}", group);
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            println!("Failed to list contact groups: {}", e);
+                        }
+                    }
+                    Ok(())
+                }
+                Some("show") => {
+                    if args.args.len() < 2 {
+                        println!("Not enough arguments for contact group show command");
+                        println!("Usage: ducktape contacts show <group_name>");
+                        return Ok(());
+                    }
+
+                    let group_name = &args.args[1];
+                    match crate::contact_groups::resolve_group_emails(group_name).await {
+                        Ok(Some(members)) => {
+                            println!("Members of contact group '{}':", group_name);
+                            for member in members {
+                                println!("  - {}", member);
+                            }
+                        }
+                        Ok(None) => {
+                            println!("Contact group '{}' not found", group_name);
+                        }
+                        Err(e) => {
+                            println!("Failed to show contact group: {}", e);
+                        }
+                    }
+                    Ok(())
+                }
+                Some("add") | Some("remove") => {
+                    let action = args.args[0].as_str();
+                    if args.args.len() < 3 {
+                        println!("Not enough arguments for contact group {} command", action);
+                        println!("Usage: ducktape contacts {} <group_name> <emails...>", action);
+                        return Ok(());
+                    }
+
+                    let group_name = &args.args[1];
+                    let emails: Vec<String> = args.args.iter().skip(2).cloned().collect();
+                    let result = if action == "add" {
+                        crate::contact_groups::add_members(group_name, &emails)
+                    } else {
+                        crate::contact_groups::remove_members(group_name, &emails)
+                    };
+                    match result {
+                        Ok(count) if action == "add" => {
+                            println!("Added {} member(s) to contact group '{}'", count, group_name)
+                        }
+                        Ok(count) => println!(
+                            "Removed {} member(s) from contact group '{}'",
+                            count, group_name
+                        ),
+                        Err(e) => println!("Failed to update contact group: {}", e),
+                    }
+                    Ok(())
+                }
+                Some("rename") => {
+                    if args.args.len() < 3 {
+                        println!("Not enough arguments for contact group rename command");
+                        println!("Usage: ducktape contacts rename <group_name> <new_name>");
+                        return Ok(());
+                    }
+
+                    let group_name = &args.args[1];
+                    let new_name = &args.args[2];
+                    match crate::contact_groups::rename_group(group_name, new_name) {
+                        Ok(_) => {
+                            println!("Renamed contact group '{}' to '{}'", group_name, new_name)
+                        }
+                        Err(e) => println!("Failed to rename contact group: {}", e),
+                    }
+                    Ok(())
+                }
+                Some("delete") => {
+                    if args.args.len() < 2 {
+                        println!("Not enough arguments for contact group delete command");
+                        println!("Usage: ducktape contacts delete <group_name>");
+                        return Ok(());
+                    }
+
+                    let group_name = &args.args[1];
+                    match crate::contact_groups::delete_group(group_name) {
+                        Ok(_) => println!("Deleted contact group '{}'", group_name),
+                        Err(e) => println!("Failed to delete contact group: {}", e),
+                    }
+                    Ok(())
+                }
+                Some("sync") => {
+                    let full = args.flags.contains_key("full");
+                    println!("Syncing contacts from Contacts.app...");
+                    match crate::address_book::sync(full).await {
+                        Ok(summary) => println!(
+                            "Synced {} contact(s): {} added, {} updated, {} removed",
+                            summary.total, summary.added, summary.updated, summary.removed
+                        ),
+                        Err(e) => println!("Failed to sync contacts: {}", e),
+                    }
+                    Ok(())
+                }
+                _ => {
+                    println!(
+                        "Unknown contacts command. Available commands: create, list, show, add, remove, rename, delete, sync"
+                    );
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "contacts" || command == "contact"
+    }
+}
+
+// Adopt handler for registering existing Calendar.app events in state
+#[derive(Debug)]
+pub struct AdoptHandler;
+
+impl CommandHandler for AdoptHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            let calendar = match args.flags.get("calendar").cloned().flatten() {
+                Some(cal) => cal.trim_matches('"').to_string(),
+                None => {
+                    let available = crate::calendar::get_available_calendars().await?;
+                    crate::calendar::default_calendar(&crate::config::Config::load()?, &available)
+                        .await?
+                }
+            };
+            let from = args
+                .flags
+                .get("from")
+                .cloned()
+                .flatten()
+                .unwrap_or_else(|| chrono::Local::now().format("%Y-%m-%d").to_string());
+
+            println!("Scanning calendar '{}' for events since {}...", calendar, from);
+            match crate::calendar::adopt_events(&calendar, &from).await {
+                Ok(summary) => {
+                    println!(
+                        "Adopted {} event(s), updated {}, {} already tracked",
+                        summary.adopted, summary.updated, summary.skipped
+                    );
+                    Ok(())
+                }
+                Err(e) => {
+                    println!("Failed to adopt events: {}", e);
+                    Err(e)
+                }
+            }
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "adopt"
+    }
+}
+
+// Link handler
+#[derive(Debug)]
+pub struct LinkHandler;
+
+impl CommandHandler for LinkHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            if args.command == "links" {
+                let links = crate::links::load_links()?;
+                if links.is_empty() {
+                    println!("No links found");
+                }
+                for link in &links {
+                    let relation = match link.relation {
+                        crate::state::LinkRelation::RelatesTo => "relates to",
+                        crate::state::LinkRelation::BlockedBy => "blocked by",
+                    };
+                    println!("{} {} {}", link.source, relation, link.target);
+                }
+                return Ok(());
+            }
+
+            if args.args.len() < 2 {
+                println!("Not enough arguments for {} command", args.command);
+                println!("Usage: ducktape {} todo:\"<title>\" event:\"<title>\"", args.command);
+                return Ok(());
+            }
+            let (source, target) = match (
+                crate::state::LinkTarget::parse(&args.args[0]),
+                crate::state::LinkTarget::parse(&args.args[1]),
+            ) {
+                (Ok(source), Ok(target)) => (source, target),
+                (Err(e), _) | (_, Err(e)) => {
+                    println!("{}", e);
+                    return Ok(());
+                }
+            };
+
+            if args.command == "unlink" {
+                match crate::links::remove_link(&source, &target)? {
+                    0 => println!("{} and {} are not linked", source, target),
+                    _ => println!("Unlinked {} and {}", source, target),
+                }
+                return Ok(());
+            }
+
+            let relation = match args.flags.get("relation") {
+                Some(Some(value)) => match crate::state::LinkRelation::from_str(value) {
+                    Ok(relation) => relation,
+                    Err(e) => {
+                        println!("{}", e);
+                        return Ok(());
+                    }
+                },
+                _ => crate::state::LinkRelation::default(),
+            };
+            match crate::links::add_link(source.clone(), target.clone(), relation) {
+                Ok(true) => println!("Linked {} and {}", source, target),
+                Ok(false) => println!("{} and {} are already linked", source, target),
+                Err(e) => println!("Failed to link items: {}", e),
+            }
+            Ok(())
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "link" || command == "unlink" || command == "links"
+    }
+}
+
+// Open handler
+#[derive(Debug)]
+pub struct OpenHandler;
+
+impl CommandHandler for OpenHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            let title = args.args.get(1..).unwrap_or_default().join(" ");
+            let Some(kind) = args.args.first().filter(|_| !title.trim().is_empty()) else {
+                println!("Usage: ducktape open <event|note|reminder> <title>");
+                return Ok(());
+            };
+            let kind = match crate::open::OpenKind::parse(kind) {
+                Ok(kind) => kind,
+                Err(e) => {
+                    println!("{}", e);
+                    return Ok(());
+                }
+            };
+
+            match crate::open::open_item(kind, title.trim()).await {
+                Ok(opened) => {
+                    println!("Opened {} '{}' in {}", kind, opened, kind.app());
+                    Ok(())
+                }
+                Err(e) => {
+                    println!("{}", e);
+                    Err(e)
+                }
+            }
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "open"
+    }
+}
+
+// List handler
+#[derive(Debug)]
+pub struct ListHandler;
+
+impl CommandHandler for ListHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            let tag = args.flags.get("tag").cloned().flatten();
+            let Some(tag) = tag.as_deref().and_then(crate::tag::normalize_tag) else {
+                println!("Usage: ducktape list --tag <tag> [--kind event|note|reminder]");
+                return Ok(());
+            };
+            let kind = match args.flags.get("kind").cloned().flatten() {
+                Some(kind) => match crate::open::OpenKind::parse(&kind) {
+                    Ok(kind) => Some(kind),
+                    Err(e) => {
+                        println!("{}", e);
+                        return Ok(());
+                    }
+                },
+                None => None,
+            };
+
+            let found = crate::tag::find_tagged(&tag, kind).await?;
+            print!("{}", crate::tag::format_tagged(&tag, &found, &crate::theme::Colors::stdout()));
+            Ok(())
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "list"
+    }
+}
+
+// Agenda handler
+#[derive(Debug)]
+pub struct AgendaHandler;
+
+impl CommandHandler for AgendaHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            let date = args
+                .args
+                .first()
+                .cloned()
+                .unwrap_or_else(|| chrono::Local::now().format("%Y-%m-%d").to_string());
+            let calendar = args.flags.get("calendar").cloned().flatten().map(|name| {
+                crate::config::Config::load()
+                    .map(|config| config.calendar.resolve_calendar(&name))
+                    .unwrap_or(name)
+            });
+
+            if crate::launcher::output() == crate::launcher::OutputFormat::Alfred {
+                let items = crate::launcher::agenda(&date, calendar.as_deref()).await;
+                crate::launcher::print_items(&items, crate::launcher::OutputFormat::Alfred);
+                return Ok(());
+            }
+
+            let events = match crate::calendar::fetch_agenda(&date, calendar.as_deref()).await {
+                Ok(events) => events,
+                Err(e) => {
+                    println!("Failed to load agenda: {}", e);
+                    return Err(e);
+                }
+            };
+            let links = crate::links::load_links().unwrap_or_default();
+            // Reminder status is only needed when an event has linked prep tasks
+            let todos = if events
+                .iter()
+                .any(|e| !crate::links::prep_tasks_for_event(&links, &e.title).is_empty())
+            {
+                crate::todo::get_todos(None).await.unwrap_or_default()
+            } else {
+                Vec::new()
+            };
+
+            let colors = crate::theme::Colors::stdout();
+            print!("{}", crate::calendar::format_agenda(&date, &events, &links, &todos, &colors));
+            Ok(())
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "agenda"
+    }
+}
+
+// Quick add handler
+#[derive(Debug)]
+pub struct QuickHandler;
+
+impl CommandHandler for QuickHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            let text = args.args.join(" ");
+            // Alfred runs this on every keystroke, so it only shows what would be created
+            if crate::launcher::output() == crate::launcher::OutputFormat::Alfred {
+                let calendar = args.flags.get("calendar").cloned().flatten();
+                let items = crate::launcher::create_items(
+                    &text,
+                    calendar.as_deref(),
+                    chrono::Local::now().naive_local(),
+                );
+                crate::launcher::print_items(&items, crate::launcher::OutputFormat::Alfred);
+                return Ok(());
+            }
+            if text.trim().is_empty() {
+                println!("Usage: ducktape quick \"<title> <day> <time> [at <location>]\"");
+                return Ok(());
+            }
+            let event = match crate::parser::quick::parse_quick(
+                &text,
+                chrono::Local::now().naive_local(),
+            ) {
+                Ok(event) => event,
+                Err(e) => {
+                    println!("{}", e);
+                    return Ok(());
+                }
+            };
+
+            if args.flags.contains_key("dry-run") {
+                println!("Would create: {}", event);
+                return Ok(());
+            }
+            println!("Creating: {}", event);
+            let mut config = event.to_event_config();
+            if let Some(Some(calendar)) = args.flags.get("calendar") {
+                config.calendars = vec![calendar.clone()];
+            }
+            Ok(crate::calendar::create_event(config).await?)
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "quick"
+    }
+}
+
+// Search handler
+#[derive(Debug)]
+pub struct SearchHandler;
+
+impl CommandHandler for SearchHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            let items =
+                crate::launcher::search(&args.args.join(" "), args.flags.contains_key("past"));
+            crate::launcher::print_items(&items, crate::launcher::output());
+            Ok(())
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "search"
+    }
+}
+
+// Raycast handler
+#[derive(Debug)]
+pub struct RaycastHandler;
+
+impl CommandHandler for RaycastHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            let calendar = args.flags.get("calendar").cloned().flatten().map(|name| {
+                crate::config::Config::load()
+                    .map(|config| config.calendar.resolve_calendar(&name))
+                    .unwrap_or(name)
+            });
+            let rest = args.args.get(1..).unwrap_or_default().join(" ");
+            let items = match args.args.first().map(String::as_str) {
+                Some("agenda") => {
+                    let date = if rest.is_empty() { "today" } else { rest.as_str() };
+                    crate::launcher::agenda(date, calendar.as_deref()).await
+                }
+                Some("search") => crate::launcher::search(&rest, args.flags.contains_key("past")),
+                Some("create") => crate::launcher::create_items(
+                    &rest,
+                    calendar.as_deref(),
+                    chrono::Local::now().naive_local(),
+                ),
+                _ => {
+                    println!(
+                        "Usage: ducktape raycast <agenda [date]|search <query>|create <text>>"
+                    );
+                    return Ok(());
+                }
+            };
+            println!("{}", crate::launcher::raycast_json(&items));
+            Ok(())
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "raycast"
+    }
+}
+
+// Redo handler
+#[derive(Debug)]
+pub struct RedoHandler;
+
+impl CommandHandler for RedoHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            let Some(last) = crate::redo::last()? else {
+                println!("No command made from natural language yet");
+                return Ok(());
+            };
+
+            let command = if args.command == "edit-last" {
+                let edits = crate::redo::Edits::from_args(&args);
+                let edited = if edits.is_empty() {
+                    crate::redo::edit_inline(&last.command)
+                } else {
+                    edits.apply(&last.command).map(Some)
+                };
+                match edited {
+                    Ok(Some(command)) => command,
+                    Ok(None) => {
+                        println!("The command was not changed");
+                        return Ok(());
+                    }
+                    Err(e) => {
+                        println!("{}", e);
+                        return Ok(());
+                    }
+                }
+            } else {
+                last.command.clone()
+            };
+
+            if args.flags.contains_key("dry-run") {
+                println!("Would run: ducktape {}", command);
+                return Ok(());
+            }
+            if command != last.command {
+                crate::redo::replace(&last, &command)?;
+            }
+            println!("Running: ducktape {}", command);
+            let command = crate::command_bus::Command::parse(&format!("ducktape {}", command))?;
+            crate::command_bus::CommandBus::new().execute(command, None).await
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "redo" || command == "edit-last"
+    }
+}
+
+// Attendees handler
+#[derive(Debug)]
+pub struct AttendeesHandler;
+
+impl CommandHandler for AttendeesHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            let (Some(action), Some(title)) = (args.args.first(), args.args.get(1)) else {
+                println!(
+                    "Usage: ducktape attendees <list|resend> \"<event title>\" [--date YYYY-MM-DD]"
+                );
+                return Ok(());
+            };
+            if action != "list" && action != "resend" {
+                println!("Unknown attendees command: {}. Use list or resend", action);
+                return Ok(());
+            }
+            let date = args.flags.get("date").cloned().flatten();
+            let calendar = args.flags.get("calendar").cloned().flatten();
+            let event =
+                crate::calendar::fetch_event_attendees(title, date.as_deref(), calendar.as_deref())
+                    .await?;
+
+            if action == "list" {
+                print!("{}", crate::calendar::format_attendees(&event));
+                return Ok(());
+            }
+
+            let emails: Vec<&str> = if args.flags.contains_key("all") {
+                event.attendees.iter().map(|a| a.email.as_str()).collect()
+            } else {
+                event.pending().into_iter().map(|a| a.email.as_str()).collect()
+            };
+            if emails.is_empty() {
+                println!("Everyone invited to '{}' has answered", event.title);
+                return Ok(());
+            }
+            if args.flags.contains_key("mail") {
+                crate::calendar::send_invite_email(&event.to_event_config(), &emails).await?;
+                println!("Emailed the invitation for '{}' to {}", event.title, emails.join(", "));
+            } else {
+                crate::calendar::resend_invitations(&event, &emails).await?;
+                println!("Invited {} again to '{}'", emails.join(", "), event.title);
+            }
+            Ok(())
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "attendees"
+    }
+}
+
+// Pack handler
+#[derive(Debug)]
+pub struct PackHandler;
+
+impl CommandHandler for PackHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            match args.args.first().map(|s| s.as_str()) {
+                Some("export") => {
+                    let Some(file) = args.args.get(1) else {
+                        println!("Usage: ducktape pack export <name>.toml [--name <name>]");
+                        return Ok(());
+                    };
+                    let name = args.flags.get("name").cloned().flatten();
+                    let description = args.flags.get("description").cloned().flatten();
+                    let pack = crate::packs::export_pack(
+                        std::path::Path::new(file),
+                        name.as_deref(),
+                        description.as_deref(),
+                    )?;
+                    println!(
+                        "Exported pack '{}' to {} ({} templates, {} aliases, {} routines)",
+                        pack.pack.name,
+                        file,
+                        pack.templates.len(),
+                        pack.aliases.len(),
+                        pack.routines.len()
+                    );
+                    Ok(())
+                }
+                Some("install") => {
+                    let Some(source) = args.args.get(1) else {
+                        println!(
+                            "Usage: ducktape pack install <file|url> [--on-conflict ask|skip|overwrite|rename]"
+                        );
+                        return Ok(());
+                    };
+                    let strategy = match args.flags.get("on-conflict").cloned().flatten() {
+                        Some(value) => match crate::packs::ConflictStrategy::from_str(&value) {
+                            Ok(strategy) => strategy,
+                            Err(e) => {
+                                println!("{}", e);
+                                return Ok(());
+                            }
+                        },
+                        None => crate::packs::ConflictStrategy::Ask,
+                    };
+
+                    let pack = crate::packs::fetch_pack(source).await?;
+                    let mut library = crate::packs::Library::load()?;
+                    let mut config = crate::config::Config::load_global()?;
+                    let summary = crate::packs::install_pack(
+                        &pack,
+                        &mut library,
+                        &mut config,
+                        strategy,
+                        ask_pack_conflict,
+                    );
+                    crate::packs::save_installed(&pack, source, &mut library, &config)?;
+
+                    println!("Installed pack '{}'", pack.pack.name);
+                    for added in &summary.added {
+                        println!("  + {}", added);
+                    }
+                    for overwritten in &summary.overwritten {
+                        println!("  ~ {} (overwritten)", overwritten);
+                    }
+                    for (entry, new_name) in &summary.renamed {
+                        println!("  + {} (installed as {})", entry, new_name);
+                    }
+                    for skipped in &summary.skipped {
+                        println!("  = {} (kept existing)", skipped);
+                    }
+                    Ok(())
+                }
+                Some("list") => {
+                    let library = crate::packs::Library::load()?;
+                    if library.installed.is_empty() {
+                        println!("No packs installed");
+                    } else {
+                        println!("Installed packs:");
+                        for pack in &library.installed {
+                            println!(
+                                "  {} (from {}, {})",
+                                pack.name, pack.source, pack.installed_at
+                            );
+                        }
+                    }
+                    println!(
+                        "Library: {} templates, {} aliases, {} routines",
+                        library.templates.len(),
+                        library.aliases.len(),
+                        library.routines.len()
+                    );
+                    Ok(())
+                }
+                _ => {
+                    println!("Unknown pack command. Available commands: export, install, list");
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "pack" || command == "packs"
+    }
+}
+
+// Cache handler
+#[derive(Debug)]
+pub struct CacheHandler;
+
+impl CommandHandler for CacheHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            match args.args.first().map(|s| s.as_str()) {
+                Some("clear") => {
+                    let removed = crate::parser::cache::clear()?;
+                    crate::calendar::clear_calendar_cache();
+                    println!("Removed {} cached command(s) and cached calendar names", removed);
+                    Ok(())
+                }
+                _ => {
+                    println!("Unknown cache command. Available commands: clear");
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "cache"
+    }
+}
+
+// Usage handler
+#[derive(Debug)]
+pub struct UsageHandler;
+
+impl CommandHandler for UsageHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            match args.args.first().map(|s| s.as_str()).unwrap_or("show") {
+                "show" => {
+                    let month = args
+                        .flags
+                        .get("month")
+                        .cloned()
+                        .flatten()
+                        .unwrap_or_else(|| chrono::Local::now().format("%Y-%m").to_string());
+                    if chrono::NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+                        .is_err()
+                    {
+                        println!("Invalid month: {} (expected YYYY-MM)", month);
+                        return Ok(());
+                    }
+
+                    let config = crate::config::Config::load()?;
+                    let ledger = crate::usage::UsageLedger::load()?;
+                    let records = ledger.month(&month);
+                    if records.is_empty() {
+                        println!("No language model usage recorded for {}", month);
+                    } else {
+                        println!("Language model usage for {}:", month);
+                        for r in &records {
+                            println!(
+                                "  {}  {:<9} {:<18} {:>4} requests  {:>8} prompt  {:>7} completion  ${:.4}",
+                                r.date,
+                                r.provider,
+                                r.model,
+                                r.requests,
+                                r.prompt_tokens,
+                                r.completion_tokens,
+                                r.cost_usd
+                            );
+                        }
+                    }
+
+                    let total = ledger.month_cost(&month);
+                    match config.usage.monthly_budget_usd {
+                        Some(budget) => println!(
+                            "Total: ${:.4} of ${:.2} budget{}",
+                            total,
+                            budget,
+                            if total >= budget { " (exceeded)" } else { "" }
+                        ),
+                        None => println!("Total: ${:.4} (no monthly budget set)", total),
+                    }
+                    Ok(())
+                }
+                _ => {
+                    println!("Unknown usage command. Available commands: show");
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "usage"
+    }
+}
+
+// Natural language evaluation handler
+#[derive(Debug)]
+pub struct NlpHandler;
+
+impl CommandHandler for NlpHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            match args.args.first().map(|s| s.as_str()).unwrap_or("") {
+                "eval" => {
+                    let Some(path) = args.args.get(1) else {
+                        println!("Usage: ducktape nlp eval <fixtures.toml> [--provider <name>]");
+                        return Ok(());
+                    };
+                    let min_accuracy = match args.flags.get("min-accuracy").cloned().flatten() {
+                        Some(value) => match value.parse::<f64>() {
+                            Ok(m) if (0.0..=1.0).contains(&m) => Some(m),
+                            _ => {
+                                return Err(anyhow!(
+                                    "Invalid --min-accuracy: {} (expected 0.0 to 1.0)",
+                                    value
+                                ));
+                            }
+                        },
+                        None => None,
+                    };
+                    let fixtures = crate::parser::eval::Fixtures::load(std::path::Path::new(path))?;
+                    let (provider, parser) = match args.flags.get("provider").cloned().flatten() {
+                        Some(name) => {
+                            let parser =
+                                crate::parser::ParserFactory::create_parser_by_name(&name)?;
+                            (name.to_lowercase(), parser)
+                        }
+                        None => {
+                            let chain = crate::config::Config::load()?.parser_chain();
+                            let name = if chain.is_empty() {
+                                "terminal".to_string()
+                            } else {
+                                chain.join(" -> ")
+                            };
+                            (name, crate::parser::ParserFactory::create_parser()?)
+                        }
+                    };
+
+                    println!("Evaluating {} phrases with {}...", fixtures.cases.len(), provider);
+                    let today = chrono::Local::now().date_naive();
+                    let report =
+                        crate::parser::eval::evaluate(parser.as_ref(), &provider, &fixtures, today)
+                            .await?;
+                    println!("{}", report.format(args.flags.contains_key("verbose")));
+                    if let Some(min) = min_accuracy {
+                        if report.accuracy() < min {
+                            return Err(anyhow!(
+                                "Accuracy {:.1}% is below the required {:.1}%",
+                                report.accuracy() * 100.0,
+                                min * 100.0
+                            ));
+                        }
+                    }
+                    Ok(())
+                }
+                _ => {
+                    println!("Unknown nlp command. Available commands: eval");
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "nlp"
+    }
+}
+
+// Audit handler
+#[derive(Debug)]
+pub struct AuditHandler;
+
+impl CommandHandler for AuditHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            match args.args.first().map(|s| s.as_str()).unwrap_or("show") {
+                "show" => {
+                    let since = match args.flags.get("since").cloned().flatten() {
+                        Some(expr) => {
+                            let today = chrono::Local::now().date_naive();
+                            match crate::dates::parse_date(&expr, today) {
+                                Some(date) => Some(date),
+                                None => {
+                                    println!(
+                                        "Invalid date: {} (e.g. yesterday or 2025-04-01)",
+                                        expr
+                                    );
+                                    return Ok(());
+                                }
+                            }
+                        }
+                        None => None,
+                    };
+                    let limit = match args.flags.get("limit").cloned().flatten() {
+                        Some(limit) => match limit.parse::<usize>() {
+                            Ok(limit) => Some(limit),
+                            Err(_) => {
+                                println!("Invalid limit: {}", limit);
+                                return Ok(());
+                            }
+                        },
+                        None => None,
+                    };
+
+                    let state = crate::state::StateManager::new()?;
+                    let entries = crate::audit::load(&state, since)?;
+                    let skip = limit.map(|limit| entries.len().saturating_sub(limit)).unwrap_or(0);
+                    if entries.is_empty() {
+                        println!("No commands in the audit log");
+                    }
+                    for entry in &entries[skip..] {
+                        println!("{}", crate::audit::format_entry(entry));
+                    }
+                    Ok(())
+                }
+                _ => {
+                    println!("Unknown audit command. Available commands: show");
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "audit"
+    }
+}
+
+// Report handler
+#[derive(Debug)]
+pub struct ReportHandler;
+
+impl CommandHandler for ReportHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            use crate::report::{GroupBy, TimeFilter};
+
+            match args.args.first().map(|s| s.as_str()).unwrap_or("time") {
+                "time" => {
+                    let flag = |name: &str| args.flags.get(name).cloned().flatten();
+                    let today = chrono::Local::now().date_naive();
+                    let group_by = match flag("group-by").map(|g| GroupBy::parse(&g)) {
+                        Some(Ok(group_by)) => group_by,
+                        Some(Err(e)) => {
+                            println!("{}", e);
+                            return Ok(());
+                        }
+                        None => GroupBy::Calendar,
+                    };
+                    let day = |expr: &str| crate::dates::parse_date(expr, today);
+                    let period = match (flag("last"), flag("from"), flag("to")) {
+                        (Some(last), _, _) => crate::report::last_period(&last, today),
+                        (None, None, None) => crate::report::last_period("7 days", today),
+                        (None, from, to) => {
+                            let until = match to {
+                                Some(to) => day(&to),
+                                None => Some(today),
+                            };
+                            let from = match from {
+                                Some(from) => day(&from),
+                                None => until.map(|until| until - chrono::Duration::days(6)),
+                            };
+                            from.zip(until)
+                        }
+                    };
+                    let Some((from, until)) = period.filter(|(from, until)| from <= until) else {
+                        println!(
+                            "Invalid period. Use --last week|month|year|\"30 days\" or --from/--to"
+                        );
+                        return Ok(());
+                    };
+
+                    let filter = TimeFilter {
+                        calendar: flag("calendar"),
+                        keyword: flag("keyword"),
+                        tag: flag("tag"),
+                    };
+                    let default_minutes = crate::config::Config::load()?
+                        .calendar
+                        .default_duration_minutes
+                        .unwrap_or(60) as i64;
+                    let items = crate::state::load_events()?;
+                    let entries =
+                        crate::report::time_entries(&items, from, until, &filter, default_minutes);
+                    let rows = crate::report::group_time(&entries, group_by);
+
+                    let report = match flag("format").as_deref().unwrap_or("table") {
+                        "table" => crate::report::format_time_table(&rows, group_by, from, until),
+                        "csv" => crate::report::time_csv(&rows, group_by)?,
+                        "json" => format!(
+                            "{}\n",
+                            serde_json::to_string_pretty(&crate::report::time_json(
+                                &rows, group_by, from, until
+                            ))?
+                        ),
+                        other => {
+                            println!("Unknown format: {} (table, csv or json)", other);
+                            return Ok(());
+                        }
+                    };
+                    match flag("output") {
+                        Some(path) => {
+                            std::fs::write(&path, report)?;
+                            println!("Wrote the report for {} to {} to {}", from, until, path);
+                        }
+                        None => print!("{}", report),
+                    }
+                    Ok(())
+                }
+                _ => {
+                    println!("Unknown report command. Available commands: time");
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "report"
+    }
+}
+
+// Review handler
+#[derive(Debug)]
+pub struct ReviewHandler;
+
+impl CommandHandler for ReviewHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            match args.args.first().map(|s| s.as_str()).unwrap_or("weekly") {
+                "weekly" => {
+                    let config = crate::config::Config::load()?;
+                    let now = chrono::Local::now().naive_local();
+                    let (from, until) = crate::review::last_week(now.date());
+                    let mut review = crate::review::gather(from, until, now).await?;
+                    if review.is_empty() {
+                        println!("Nothing to review from {} to {}", from, until);
+                        return Ok(());
+                    }
+                    if !args.flags.contains_key("no-summary") {
+                        match crate::review::summarize(&review, &config).await {
+                            Ok(summary) => review.summary = summary,
+                            Err(e) => println!("Writing the review without a summary: {}", e),
+                        }
+                    }
+
+                    let title = review.title();
+                    let content = review.to_markdown();
+                    if args.flags.contains_key("dry-run") {
+                        println!("{}\n\n{}", title, content);
+                        println!("Dry run: no note was created");
+                        return Ok(());
+                    }
+                    let folder = args
+                        .flags
+                        .get("folder")
+                        .cloned()
+                        .flatten()
+                        .or(config.notes.default_folder.clone());
+                    let mut note = crate::notes::NoteConfig::new(&title, &content);
+                    note.folder = folder.as_deref();
+                    crate::notes::create_note(note.with_markdown(true)).await?;
+                    println!(
+                        "Created note '{}': {} reminder(s) completed, {} event(s), {} new note(s)",
+                        title,
+                        review.reminders.len(),
+                        review.events.len(),
+                        review.notes.len()
+                    );
+                    Ok(())
+                }
+                _ => {
+                    println!("Unknown review command. Available commands: weekly");
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "review"
+    }
+}
+
+// Focus handler
+#[derive(Debug)]
+pub struct FocusHandler;
+
+impl CommandHandler for FocusHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            match args.args.first().map(|s| s.as_str()).unwrap_or("stats") {
+                "start" => {
+                    let usage = "Usage: ducktape focus start <duration> <title> [--dnd]";
+                    let Some(duration) = args.args.get(1) else {
+                        println!("{}", usage);
+                        return Ok(());
+                    };
+                    let Some(length) = crate::focus::parse_focus_duration(duration) else {
+                        println!(
+                            "Invalid duration: {} (e.g. 25m, 1h or 1h30m, up to 12h)",
+                            duration
+                        );
+                        return Ok(());
+                    };
+                    let title = args.args[2..].join(" ");
+                    let title = title.trim().trim_matches('"');
+                    if title.is_empty() {
+                        println!("{}", usage);
+                        return Ok(());
+                    }
+
+                    let config = crate::config::Config::load()?;
+                    let calendar = args.flags.get("calendar").cloned().flatten();
+                    println!(
+                        "Focusing on {} for {} minute(s). Press Ctrl+C to stop early.",
+                        title,
+                        length.num_minutes()
+                    );
+                    let session = crate::focus::start_focus(
+                        title,
+                        length,
+                        calendar.as_deref(),
+                        args.flags.contains_key("dnd"),
+                        &config.focus,
+                    )
+                    .await?;
+                    if session.completed {
+                        println!(
+                            "Session complete: {} minute(s) of focus",
+                            session.focused_minutes
+                        );
+                    } else {
+                        println!(
+                            "Session stopped after {} of {} minute(s)",
+                            session.focused_minutes, session.planned_minutes
+                        );
+                    }
+                    Ok(())
+                }
+                "stats" => {
+                    let today = chrono::Local::now().date_naive();
+                    let since = match args.flags.get("since").cloned().flatten() {
+                        Some(expr) => match crate::dates::parse_date(&expr, today) {
+                            Some(date) => Some(date),
+                            None => {
+                                println!("Invalid date: {} (e.g. monday or 2025-04-01)", expr);
+                                return Ok(());
+                            }
+                        },
+                        None => None,
+                    };
+                    let sessions: Vec<crate::state::FocusSession> =
+                        crate::state::StateManager::new()?.load()?;
+                    let stats = crate::focus::focus_stats(&sessions, since, today);
+                    print!("{}", crate::focus::format_focus_stats(&stats));
+                    Ok(())
+                }
+                _ => {
+                    println!("Unknown focus command. Available commands: start, stats");
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "focus"
+    }
+}
+
+// Holidays handler
+#[derive(Debug)]
+pub struct HolidaysHandler;
+
+impl CommandHandler for HolidaysHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            match args.args.first().map(|s| s.as_str()).unwrap_or("list") {
+                "list" => {
+                    let year = match args.args.get(1) {
+                        Some(year) => match year.parse::<i32>() {
+                            Ok(year) => year,
+                            Err(_) => {
+                                println!("Invalid year: {}", year);
+                                return Ok(());
+                            }
+                        },
+                        None => chrono::Datelike::year(&chrono::Local::now()),
+                    };
+                    let country = match args.flags.get("country").cloned().flatten() {
+                        Some(country) => crate::holidays::Country::from_str(&country)?,
+                        None => match crate::holidays::configured_country() {
+                            Some(country) => country,
+                            None => {
+                                println!(
+                                    "No country set. Use --country or: ducktape config set holidays.country NZ"
+                                );
+                                return Ok(());
+                            }
+                        },
+                    };
+
+                    println!("Public holidays in {} in {}:", country.name(), year);
+                    for holiday in crate::holidays::holidays(country, year) {
+                        println!(
+                            "  {} {}  {}",
+                            holiday.date.format("%Y-%m-%d"),
+                            holiday.date.format("%a"),
+                            holiday.name
+                        );
+                    }
+                    Ok(())
+                }
+                _ => {
+                    println!("Unknown holidays command. Available commands: list");
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "holidays"
+    }
+}
+
+// Birthdays handler
+#[derive(Debug)]
+pub struct BirthdaysHandler;
+
+impl CommandHandler for BirthdaysHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            match args.args.first().map(|s| s.as_str()).unwrap_or("sync") {
+                "sync" => {
+                    let calendar = args.flags.get("calendar").cloned().flatten();
+                    let remind_days = match args.flags.get("remind-days").cloned().flatten() {
+                        Some(days) => match days.parse::<u32>() {
+                            Ok(days) => Some(days),
+                            Err(_) => {
+                                println!("Invalid number of days: {}", days);
+                                return Ok(());
+                            }
+                        },
+                        None => None,
+                    };
+
+                    let dates = crate::birthdays::fetch_contact_dates().await?;
+                    let synced: Vec<crate::state::BirthdayRecord> =
+                        crate::state::StateManager::new()?.load()?;
+                    let planned = crate::birthdays::plan_sync(&dates, &synced);
+                    if planned.is_empty() {
+                        println!(
+                            "All {} birthday(s) and anniversaries already have events",
+                            dates.len()
+                        );
+                        return Ok(());
+                    }
+
+                    let today = chrono::Local::now().date_naive();
+                    println!("Events to create:");
+                    for date in &planned {
+                        let next = crate::birthdays::next_occurrence(date.date, today);
+                        println!("  • {} (yearly from {})", date.title(), next.format("%Y-%m-%d"));
+                    }
+                    if args.flags.contains_key("dry-run") {
+                        println!("Dry run: no events were created");
+                        return Ok(());
+                    }
+
+                    let created = crate::birthdays::create_birthday_events(
+                        &planned,
+                        calendar.as_deref(),
+                        remind_days,
+                    )
+                    .await?;
+                    println!("Created {} of {} event(s)", created, planned.len());
+                    Ok(())
+                }
+                _ => {
+                    println!("Unknown birthdays command. Available commands: sync");
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "birthdays"
+    }
+}
+
+// Watch handler
+#[derive(Debug)]
+pub struct WatchHandler;
+
+impl CommandHandler for WatchHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            let number = |name: &str, default: u64| {
+                args.flags.get(name).cloned().flatten().map_or(Ok(default), |value| {
+                    value.parse::<u64>().map_err(|_| {
+                        anyhow!("Invalid --{}: {} (expected a whole number)", name, value)
+                    })
+                })
+            };
+            let (interval_secs, days) = match (number("interval", 60), number("days", 14)) {
+                (Ok(interval), Ok(days)) => (interval, days),
+                (Err(e), _) | (_, Err(e)) => {
+                    println!("{}", e);
+                    return Ok(());
+                }
+            };
+            if interval_secs < crate::watch::MIN_INTERVAL_SECS {
+                println!(
+                    "Checking every {} seconds instead, the shortest interval",
+                    crate::watch::MIN_INTERVAL_SECS
+                );
+            }
+            let options = crate::watch::WatchOptions {
+                interval_secs,
+                days: days.clamp(1, 366) as u32,
+                calendar: args.flags.get("calendar").cloned().flatten(),
+                notify: args.flags.contains_key("notify"),
+            };
+            crate::watch::run_watch(&options).await
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "watch"
+    }
+}
+
+// Sync handler
+#[derive(Debug)]
+pub struct SyncHandler;
+
+impl CommandHandler for SyncHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            let days = match args.flags.get("days").cloned().flatten() {
+                Some(value) => match value.parse::<u32>() {
+                    Ok(days) => days.clamp(1, 3660),
+                    Err(_) => {
+                        println!("Invalid --days: {} (expected a whole number)", value);
+                        return Ok(());
+                    }
+                },
+                None => 180,
+            };
+            let adopt = args.flags.contains_key("adopt");
+            let dry_run = args.flags.contains_key("dry-run");
+
+            let plan = crate::calendar::sync_events(days, adopt, dry_run).await?;
+            print!("{}", crate::calendar::format_sync_plan(&plan));
+            if plan.is_empty() {
+                println!("{} tracked event(s) already match Calendar.app", plan.checked);
+                return Ok(());
+            }
+            println!(
+                "{} {} tracked event(s): {} from Calendar.app, {} to Calendar.app, {} conflict(s)",
+                if dry_run { "Would sync" } else { "Synced" },
+                plan.checked,
+                plan.pull.len(),
+                plan.push.len(),
+                plan.conflicts.len()
+            );
+            if dry_run {
+                println!("Nothing was changed (--dry-run)");
+            }
+            Ok(())
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "sync"
+    }
+}
+
+// Schedule handler
+#[derive(Debug)]
+pub struct ScheduleHandler;
+
+impl CommandHandler for ScheduleHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            match args.args.first().map(|s| s.as_str()).unwrap_or("list") {
+                "add" => {
+                    let command = args.args.get(1).cloned().unwrap_or_default();
+                    let Some(at) = args.flags.get("at").cloned().flatten() else {
+                        println!("Usage: ducktape schedule add \"<command>\" --at \"07:30 daily\"");
+                        return Ok(());
+                    };
+                    match crate::schedule::add_job(&command, &at) {
+                        Ok(job) => {
+                            let next = crate::schedule::next_run(&job)
+                                .map(|next| next.format("%Y-%m-%d %H:%M").to_string())
+                                .unwrap_or_else(|| "never".to_string());
+                            println!(
+                                "Scheduled {}: `ducktape {}` ({}), next run {}",
+                                job.id, job.command, job.schedule, next
+                            );
+                            println!("Commands run while `ducktape schedule run` is running");
+                            Ok(())
+                        }
+                        Err(e) => {
+                            println!("{}", e);
+                            Ok(())
+                        }
+                    }
+                }
+                "list" => {
+                    let jobs: Vec<crate::state::ScheduledCommand> =
+                        crate::state::StateManager::new()?.load()?;
+                    print!("{}", crate::schedule::format_jobs(&jobs));
+                    Ok(())
+                }
+                "remove" => {
+                    let Some(id) = args.args.get(1).and_then(|id| id.parse::<u32>().ok()) else {
+                        println!("Usage: ducktape schedule remove <id>");
+                        return Ok(());
+                    };
+                    if crate::schedule::remove_job(id)? {
+                        println!("Removed scheduled command {}", id);
+                    } else {
+                        println!("No scheduled command with ID {}", id);
+                    }
+                    Ok(())
+                }
+                "run" => crate::schedule::run_scheduler().await,
+                _ => {
+                    println!(
+                        "Unknown schedule command. Available commands: add, list, remove, run"
+                    );
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "schedule"
+    }
+}
+
+// Setup handler
+#[derive(Debug)]
+pub struct SetupHandler;
+
+impl CommandHandler for SetupHandler {
+    fn execute(&self, _args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move { crate::setup::run_setup().await })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "setup"
+    }
+}
+
+// Doctor handler
+#[derive(Debug)]
+pub struct DoctorHandler;
+
+impl CommandHandler for DoctorHandler {
+    fn execute(&self, _args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            let config = crate::config::Config::load()?;
+            println!("Checking DuckTape's setup (macOS may ask for permissions)...");
+            let report = crate::doctor::diagnose(&config).await;
+            println!();
+            print!("{}", report.summary_table());
+            println!();
+            let problems = report
+                .checks
+                .iter()
+                .filter(|c| c.status != crate::api_server::startup::CheckStatus::Ok)
+                .count();
+            if problems == 0 {
+                println!("Everything looks good.");
+            } else {
+                println!("{} check(s) need attention; follow the hints marked with →", problems);
+            }
+            Ok(())
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "doctor"
+    }
+}
+
+// Keys handler
+#[derive(Debug)]
+pub struct KeysHandler;
+
+impl CommandHandler for KeysHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            use crate::secrets::{KeyService, KeySource};
+
+            let service = || -> Option<KeyService> {
+                match args.args.get(1).map(|s| KeyService::from_str(s)) {
+                    Some(Ok(service)) => Some(service),
+                    Some(Err(e)) => {
+                        println!("{}", e);
+                        None
+                    }
+                    None => {
+                        println!(
+                            "Usage: ducktape keys {} <xai|deepseek|zoom|caldav|smtp|webhook|slack>",
+                            args.args[0]
+                        );
+                        None
+                    }
+                }
+            };
+
+            match args.args.first().map(|s| s.as_str()).unwrap_or("list") {
+                "set" => {
+                    let Some(service) = service() else {
+                        return Ok(());
+                    };
+                    for var in service.vars() {
+                        let Some(value) = crate::secrets::read_secret(&format!("{}: ", var)) else {
+                            println!("No value entered, {} left unchanged", var);
+                            continue;
+                        };
+                        crate::secrets::set(var, &value)?;
+                        println!("Stored {} in the keychain", var);
+                    }
+                    Ok(())
+                }
+                "list" => {
+                    for service in KeyService::ALL {
+                        println!("{}:", service.as_str());
+                        for var in service.vars() {
+                            let status = match crate::secrets::source(var) {
+                                Some(KeySource::Environment) => "set in the environment",
+                                Some(KeySource::Keychain) => "stored in the keychain",
+                                None => "not set",
+                            };
+                            println!("  {:<20} {}", var, status);
+                        }
+                    }
+                    Ok(())
+                }
+                "delete" => {
+                    let Some(service) = service() else {
+                        return Ok(());
+                    };
+                    for var in service.vars() {
+                        crate::secrets::delete(var);
+                    }
+                    println!("Removed stored {} credentials", service.as_str());
+                    Ok(())
+                }
+                "migrate" => {
+                    let path = args
+                        .flags
+                        .get("env-file")
+                        .cloned()
+                        .flatten()
+                        .unwrap_or_else(|| ".env".to_string());
+                    let moved = crate::secrets::migrate_env_file(std::path::Path::new(&path))?;
+                    if moved.is_empty() {
+                        println!("No credentials found in {}", path);
+                    } else {
+                        println!("Moved {} from {} to the keychain", moved.join(", "), path);
+                    }
+                    Ok(())
+                }
+                _ => {
+                    println!(
+                        "Unknown keys command. Available commands: set, list, delete, migrate"
+                    );
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "keys"
+    }
+}
+
+// Shortcut handler
+#[derive(Debug)]
+pub struct ShortcutHandler;
+
+impl CommandHandler for ShortcutHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            use crate::shortcuts::{ShortcutAction, ShortcutParams};
+            use std::io::Read;
+
+            let request = || -> Result<(ShortcutAction, ShortcutParams)> {
+                let (action, mut params, rest) = match args.flags.get("url").cloned().flatten() {
+                    Some(url) => {
+                        let (action, params) = crate::shortcuts::parse_url(&url)?;
+                        (action, params, args.args.as_slice())
+                    }
+                    None => {
+                        let Some((action, rest)) = args.args.split_first() else {
+                            return Err(anyhow!(
+                                "Usage: ducktape shortcut <create-event|agenda|search> [key=value ...] [--stdin]"
+                            ));
+                        };
+                        (action.parse()?, ShortcutParams::default(), rest)
+                    }
+                };
+                if args.flags.contains_key("stdin") {
+                    let mut input = String::new();
+                    std::io::stdin().read_to_string(&mut input)?;
+                    params.extend_input(&input)?;
+                }
+                params.extend_args(rest)?;
+                Ok((action, params))
+            };
+
+            let (params, result) = match request() {
+                Ok((action, params)) => {
+                    let result = crate::shortcuts::run(action, &params).await;
+                    (params, result)
+                }
+                Err(e) => (ShortcutParams::default(), Err(e)),
+            };
+            // Shortcuts reads stdout, so errors are JSON too
+            let output = match &result {
+                Ok(value) => serde_json::json!({ "ok": true, "result": value }),
+                Err(e) => serde_json::json!({ "ok": false, "error": e.to_string() }),
+            };
+            println!("{}", serde_json::to_string_pretty(&output)?);
+            if let Some(callback) = crate::shortcuts::callback_url(&params, &result) {
+                crate::shortcuts::open_callback(&callback).await;
+            }
+            Ok(())
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "shortcut" || command == "shortcuts"
+    }
+}
+
+// Ingest handler
+#[derive(Debug)]
+pub struct IngestHandler;
+
+impl CommandHandler for IngestHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            use std::io::Read;
+
+            let kind = args.args.first().map(|s| s.as_str());
+            if !matches!(kind, Some("email") | Some("itinerary")) {
+                println!(
+                    "Usage: ducktape ingest email [file.eml] [--create] [--calendar <name>] [--no-attendees]"
+                );
+                println!(
+                    "       ducktape ingest itinerary [file] [--create] [--calendar <name>] [--checkin-hours <hours>]"
+                );
+                return Ok(());
+            }
+            let raw = match args.args.get(1).map(|s| s.as_str()) {
+                Some(path) if path != "-" => std::fs::read(path)
+                    .map_err(|e| anyhow!("Failed to read {}: {}", path, e))
+                    .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())?,
+                _ => {
+                    let mut input = String::new();
+                    std::io::stdin().read_to_string(&mut input)?;
+                    input
+                }
+            };
+            if kind == Some("itinerary") {
+                return ingest_itinerary(&raw, &args).await;
+            }
+
+            let mut details =
+                crate::ingest::email::propose_event(&raw, chrono::Local::now().naive_local())
+                    .await?;
+            if args.flags.contains_key("no-attendees") {
+                details.attendees.clear();
+            }
+            let mut config = details.event_config()?;
+            if let Some(Some(calendar)) = args.flags.get("calendar") {
+                config.calendars = vec![calendar.clone()];
+            }
+
+            println!("Proposed event:");
+            println!("  Title:     {}", config.title);
+            if config.all_day {
+                println!("  When:      {} (all day)", config.start_date);
+            } else {
+                match &config.end_time {
+                    Some(end) => {
+                        println!("  When:      {} {}-{}", config.start_date, config.start_time, end)
+                    }
+                    None => println!("  When:      {} {}", config.start_date, config.start_time),
+                }
+            }
+            if let Some(location) = &config.location {
+                println!("  Where:     {}", location);
+            }
+            if let Some(url) = &config.url {
+                println!("  URL:       {}", url);
+            }
+            if let Some(availability) = config.availability {
+                println!("  Show as:   {}", availability.as_str());
+            }
+            if !config.emails.is_empty() {
+                println!("  Attendees: {}", config.emails.join(", "));
+            }
+            if !config.calendars.is_empty() {
+                println!("  Calendar:  {}", config.calendars.join(", "));
+            }
+
+            if !args.flags.contains_key("create") {
+                println!("Run again with --create to add it to your calendar.");
+                return Ok(());
+            }
+            crate::calendar::create_event(config).await?;
+            println!("Event created");
+            Ok(())
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "ingest"
+    }
+}
+
+/// Show the flights of an itinerary, and create their events and check-in reminders
+async fn ingest_itinerary(raw: &str, args: &CommandArgs) -> Result<()> {
+    let now = chrono::Local::now().naive_local();
+    let itinerary = crate::ingest::itinerary::propose_itinerary(raw, now).await?;
+    let calendar = args.flags.get("calendar").cloned().flatten();
+    let checkin_hours = match args.flags.get("checkin-hours").cloned().flatten() {
+        Some(hours) => match hours.parse::<u32>() {
+            Ok(hours) => hours,
+            Err(_) => {
+                println!("Invalid number of hours: {}", hours);
+                return Ok(());
+            }
+        },
+        None => 24,
+    };
+
+    match &itinerary.confirmation {
+        Some(confirmation) => println!("Proposed flights (confirmation {}):", confirmation),
+        None => println!("Proposed flights:"),
+    }
+    let mut events = Vec::new();
+    for segment in &itinerary.segments {
+        let mut config = match segment.event_config(itinerary.confirmation.as_deref()) {
+            Ok(config) => config,
+            Err(e) => {
+                println!("  {}: {}", segment.title(), e);
+                continue;
+            }
+        };
+        if let Some(calendar) = &calendar {
+            config.calendars = vec![calendar.clone()];
+        }
+        let check_in = segment.check_in(checkin_hours).filter(|at| checkin_hours > 0 && *at > now);
+        println!("  {}", config.title);
+        match (&config.end_date, &config.end_time) {
+            (Some(end_date), Some(end)) => println!(
+                "    When:      {} {} - {} {}",
+                config.start_date, config.start_time, end_date, end
+            ),
+            (None, Some(end)) => {
+                println!("    When:      {} {}-{}", config.start_date, config.start_time, end)
+            }
+            _ => println!("    When:      {} {}", config.start_date, config.start_time),
+        }
+        if let Some(location) = &config.location {
+            println!("    Where:     {}", location);
+        }
+        if let Some(check_in) = check_in {
+            println!("    Check in:  {}", check_in.format("%Y-%m-%d %H:%M"));
+        }
+        events.push((config, check_in));
+    }
+    if events.is_empty() {
+        return Err(anyhow!("Could not tell when any of the flights leave"));
+    }
+
+    if !args.flags.contains_key("create") {
+        println!("Times are shown in your local time zone.");
+        println!("Run again with --create to add them to your calendar.");
+        return Ok(());
+    }
+    for (config, check_in) in events {
+        let title = config.title.clone();
+        crate::calendar::create_event(config).await?;
+        if let Some(check_in) = check_in {
+            let todo_title = format!("Check in: {}", title);
+            let time = check_in.format("%Y-%m-%d %H:%M").to_string();
+            crate::todo::create_todo(
+                crate::todo::TodoConfig::new(&todo_title).with_reminder(&time),
+            )
+            .await?;
+        }
+        println!("Created {}", title);
+    }
+    Ok(())
+}
+
+// State handler
+#[derive(Debug)]
+pub struct StateHandler;
+
+impl CommandHandler for StateHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            use crate::state::{StateExport, StateManager};
+
+            let path = args.args.get(1);
+            match args.args.first().map(|s| s.as_str()) {
+                Some("export") => {
+                    let export = StateManager::new()?.export()?;
+                    let json = serde_json::to_string_pretty(&export)?;
+                    match path {
+                        Some(path) => {
+                            std::fs::write(path, json)
+                                .map_err(|e| anyhow!("Failed to write {}: {}", path, e))?;
+                            let count: usize = export.files.values().map(Vec::len).sum();
+                            println!("Exported {} item(s) to {}", count, path);
                         }
+                        None => println!("{}", json),
+                    }
+                    Ok(())
+                }
+                Some("import") => {
+                    let Some(path) = path else {
+                        println!("Usage: ducktape state import <file> [--yes]");
+                        return Ok(());
+                    };
+                    let content = std::fs::read_to_string(path)
+                        .map_err(|e| anyhow!("Failed to read {}: {}", path, e))?;
+                    let export: StateExport = serde_json::from_str(&content)
+                        .map_err(|e| anyhow!("{} is not a DuckTape state export: {}", path, e))?;
+                    let files: Vec<&str> = export.files.keys().map(String::as_str).collect();
+                    let prompt = format!(
+                        "Replace {} with the export from {}?",
+                        files.join(", "),
+                        export.exported_at
+                    );
+                    if !confirmed(&args, &prompt) {
+                        return Ok(());
                     }
+                    let count = StateManager::new()?.import(export)?;
+                    println!("Imported {} item(s)", count);
                     Ok(())
                 }
                 _ => {
-                    println!("Unknown config command. Available commands: set, get, show");
+                    println!("Unknown state command. Available commands: export, import");
                     Ok(())
                 }
             }
@@ -1045,137 +4127,185 @@ impl CommandHandler for ConfigHandler {
     }
 
     fn can_handle(&self, command: &str) -> bool {
-        command == "config"
+        command == "state"
     }
 }
 
-// Utilities handler
+// Feeds handler
 #[derive(Debug)]
-pub struct UtilitiesHandler;
+pub struct FeedsHandler;
 
-impl CommandHandler for UtilitiesHandler {
+impl CommandHandler for FeedsHandler {
     fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
         Box::pin(async move {
-            match args.args.first().map(|s| s.as_str()) {
-                Some("date") => {
-                    println!("Current date: {}", chrono::Local::now().format("%Y-%m-%d"));
-                    Ok(())
+            use crate::calendar::{feed_url, new_feed_token};
+            use crate::config::{Config, FeedConfig};
+
+            let action = args.args.first().map(|s| s.as_str()).unwrap_or("list");
+            if action == "list" {
+                let feeds = Config::load()?.feeds;
+                if feeds.is_empty() {
+                    println!("No feeds. Create one with `ducktape feeds add <name>`");
+                    return Ok(());
                 }
-                Some("time") => {
-                    println!("Current time: {}", chrono::Local::now().format("%H:%M:%S"));
-                    Ok(())
+                for feed in &feeds {
+                    let calendars = if feed.calendars.is_empty() {
+                        "all calendars".to_string()
+                    } else {
+                        feed.calendars.join(", ")
+                    };
+                    println!("  - {} ({})", feed.name, calendars);
+                    println!("    {}", feed_url(feed));
                 }
-                Some("datetime") => {
-                    println!(
-                        "Current date and time: {}",
-                        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
-                    );
-                    Ok(())
+                println!("Feeds are served while `ducktape --api-server` is running");
+                return Ok(());
+            }
+
+            let Some(name) = args.args.get(1).map(|s| s.trim().to_string()) else {
+                println!("Usage: ducktape feeds {} <name>", action);
+                return Ok(());
+            };
+            let mut config = Config::load_global()?;
+            let existing = config.feeds.iter().position(|f| f.name.eq_ignore_ascii_case(&name));
+            match (action, existing) {
+                ("add", Some(_)) => println!("A feed named {} already exists", name),
+                ("add", None) => {
+                    let calendars = args
+                        .flags
+                        .get("calendar")
+                        .cloned()
+                        .flatten()
+                        .map(|c| c.split(',').map(|c| c.trim().to_string()).collect())
+                        .unwrap_or_default();
+                    let feed = FeedConfig { name, token: new_feed_token(), calendars };
+                    println!("Subscribe to {} at {}", feed.name, feed_url(&feed));
+                    config.feeds.push(feed);
+                    config.save()?;
+                }
+                ("rotate", Some(index)) => {
+                    config.feeds[index].token = new_feed_token();
+                    println!("New URL for {}: {}", name, feed_url(&config.feeds[index]));
+                    config.save()?;
                 }
+                ("remove", Some(index)) => {
+                    config.feeds.remove(index);
+                    config.save()?;
+                    println!("Removed feed {}", name);
+                }
+                ("rotate" | "remove", None) => println!("No feed named {}", name),
                 _ => {
-                    println!("Unknown utility command. Available commands: date, time, datetime");
-                    Ok(())
+                    println!("Unknown feeds command. Available commands: list, add, rotate, remove")
                 }
             }
+            Ok(())
         })
     }
 
     fn can_handle(&self, command: &str) -> bool {
-        command == "utility" || command == "utils"
+        command == "feeds" || command == "feed"
     }
 }
 
-// Contact groups handler
+// Webhooks handler
 #[derive(Debug)]
-pub struct ContactGroupsHandler;
+pub struct WebhooksHandler;
 
-impl CommandHandler for ContactGroupsHandler {
+impl CommandHandler for WebhooksHandler {
     fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
         Box::pin(async move {
-            match args.args.first().map(|s| s.as_str()) {
-                Some("create") => {
-                    if args.args.len() < 3 {
-                        println!("Not enough arguments for contact group create command");
-                        println!("Usage: ducktape contacts create <group_name> <emails...>");
+            use crate::config::Config;
+            use crate::webhooks::{Action, ItemKind, WebhookPayload};
+
+            let url = args.args.get(1).map(|s| s.trim().to_string());
+            match args.args.first().map(|s| s.as_str()).unwrap_or("list") {
+                "list" => {
+                    let config = Config::load()?.webhooks;
+                    if config.urls.is_empty() {
+                        println!(
+                            "No webhooks configured. Add one with `ducktape webhooks add <url>`"
+                        );
                         return Ok(());
                     }
-
-                    let group_name = &args.args[1];
-                    let emails: Vec<String> = args.args.iter().skip(2).cloned().collect();
-
-                    if emails.is_empty() {
-                        println!("No email addresses provided");
-                        return Ok(());
+                    for url in &config.urls {
+                        println!("  - {}", url);
                     }
-
-                    // Validate email addresses
-                    for email in &emails {
-                        if !crate::calendar::validate_email(email) {
-                            println!("Invalid email address: {}", email);
-                            return Ok(());
-                        }
+                    if config.events.is_empty() {
+                        println!("Changes sent: all");
+                    } else {
+                        println!("Changes sent: {}", config.events.join(", "));
                     }
-
-                    // Create contact group
-                    let result = crate::contact_groups::create_group(group_name, &emails);
-                    match result {
-                        Ok(_) => {
-                            println!(
-                                "Created contact group '{}' with {} members",
-                                group_name,
-                                emails.len()
-                            );
-                        }
-                        Err(e) => {
-                            println!("Failed to create contact group: {}", e);
-                        }
+                    let signed = crate::secrets::get(crate::webhooks::SECRET_VAR).is_some();
+                    println!(
+                        "Signed: {}",
+                        if signed { "yes" } else { "no (ducktape keys set webhook)" }
+                    );
+                    Ok(())
+                }
+                "add" => {
+                    let Some(url) = url else {
+                        println!("Usage: ducktape webhooks add <url>");
+                        return Ok(());
+                    };
+                    if !reqwest::Url::parse(&url)
+                        .is_ok_and(|u| matches!(u.scheme(), "http" | "https"))
+                    {
+                        println!("Invalid webhook URL: {} (expected http:// or https://)", url);
+                        return Ok(());
+                    }
+                    let mut config = Config::load_global()?;
+                    if config.webhooks.urls.contains(&url) {
+                        println!("{} is already a webhook", url);
+                        return Ok(());
                     }
+                    config.webhooks.urls.push(url.clone());
+                    config.save()?;
+                    println!("Changes will be sent to {}", url);
                     Ok(())
                 }
-                Some("list") => {
-                    match crate::contact_groups::list_groups() {
-                        Ok(groups) => {
-                            if groups.is_empty() {
-                                println!("No contact groups found");
-                            } else {
-                                println!("Available contact groups:");
-                                for group in groups {
-                                    println!("  - {}", group);
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            println!("Failed to list contact groups: {}", e);
-                        }
+                "remove" => {
+                    let Some(url) = url else {
+                        println!("Usage: ducktape webhooks remove <url>");
+                        return Ok(());
+                    };
+                    let mut config = Config::load_global()?;
+                    let before = config.webhooks.urls.len();
+                    config.webhooks.urls.retain(|u| u != &url);
+                    if config.webhooks.urls.len() == before {
+                        println!("{} is not a webhook", url);
+                        return Ok(());
                     }
+                    config.save()?;
+                    println!("Removed webhook {}", url);
                     Ok(())
                 }
-                Some("show") => {
-                    if args.args.len() < 2 {
-                        println!("Not enough arguments for contact group show command");
-                        println!("Usage: ducktape contacts show <group_name>");
+                "test" => {
+                    let mut config = Config::load()?.webhooks;
+                    if let Some(url) = url {
+                        config.urls = vec![url];
+                    }
+                    if config.urls.is_empty() {
+                        println!(
+                            "No webhooks configured. Add one with `ducktape webhooks add <url>`"
+                        );
                         return Ok(());
                     }
-
-                    let group_name = &args.args[1];
-                    match crate::contact_groups::get_group(group_name) {
-                        Ok(Some(members)) => {
-                            println!("Members of contact group '{}':", group_name);
-                            for member in members {
-                                println!("  - {}", member);
-                            }
-                        }
-                        Ok(None) => {
-                            println!("Contact group '{}' not found", group_name);
-                        }
-                        Err(e) => {
-                            println!("Failed to show contact group: {}", e);
+                    let payload = WebhookPayload::new(
+                        ItemKind::Event,
+                        Action::Created,
+                        serde_json::json!({ "title": "DuckTape webhook test", "test": true }),
+                    );
+                    for (url, result) in crate::webhooks::deliver_all(&config, &payload).await {
+                        match result {
+                            Ok(()) => println!("{}: delivered", url),
+                            Err(e) => println!("{}: {:#}", url, e),
                         }
                     }
                     Ok(())
                 }
                 _ => {
-                    println!("Unknown contacts command. Available commands: create, list, show");
+                    println!(
+                        "Unknown webhooks command. Available commands: list, add, remove, test"
+                    );
                     Ok(())
                 }
             }
@@ -1183,7 +4313,7 @@ impl CommandHandler for ContactGroupsHandler {
     }
 
     fn can_handle(&self, command: &str) -> bool {
-        command == "contacts" || command == "contact"
+        command == "webhooks" || command == "webhook"
     }
 }
 
@@ -1214,9 +4344,18 @@ impl CommandHandler for VersionHandler {
 pub struct HelpHandler;
 
 impl CommandHandler for HelpHandler {
-    fn execute(&self, _args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
         Box::pin(async move {
-            print_help()?;
+            if args.args.is_empty() {
+                return print_help();
+            }
+            match crate::help::command_help(&args.args) {
+                Some(help) => print!("{}", help),
+                None => {
+                    println!("Unknown command: {}", args.args.join(" "));
+                    println!("Run 'ducktape help' to see the available commands.");
+                }
+            }
             Ok(())
         })
     }
@@ -1226,6 +4365,30 @@ impl CommandHandler for HelpHandler {
     }
 }
 
+// Man page handler
+#[derive(Debug)]
+pub struct ManHandler;
+
+impl CommandHandler for ManHandler {
+    fn execute(&self, args: CommandArgs) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+        Box::pin(async move {
+            match args.flags.get("out-dir").cloned().flatten() {
+                Some(dir) => {
+                    for path in crate::help::write_man_pages(std::path::Path::new(&dir))? {
+                        println!("Wrote {}", path.display());
+                    }
+                }
+                None => print!("{}", crate::help::man_page()?),
+            }
+            Ok(())
+        })
+    }
+
+    fn can_handle(&self, command: &str) -> bool {
+        command == "man"
+    }
+}
+
 // Exit handler
 #[derive(Debug)]
 pub struct ExitHandler;
@@ -1243,6 +4406,72 @@ impl CommandHandler for ExitHandler {
     }
 }
 
+/// Whether a reminder listing asks for filters or an order
+fn has_reminder_filters(args: &CommandArgs) -> bool {
+    ["due", "completed-within", "priority", "sort"]
+        .iter()
+        .any(|f| args.flags.contains_key(*f))
+}
+
+/// The reminder query described by `list` arguments and flags
+fn reminder_query(args: &CommandArgs) -> Result<crate::reminder::ReminderQuery> {
+    let flag = |name: &str| args.flags.get(name).cloned().flatten();
+    Ok(crate::reminder::ReminderQuery {
+        list: args.args.get(1).map(|l| l.trim_matches('"').to_string()),
+        due: flag("due").map(|d| crate::reminder::DueFilter::from_str(&d)).transpose()?,
+        completed_within_days: flag("completed-within")
+            .map(|d| d.parse::<u32>().map_err(|_| anyhow!("Invalid number of days: {}", d)))
+            .transpose()?,
+        priority: flag("priority").map(|p| crate::todo::TodoPriority::from_str(&p)).transpose()?,
+        sort: flag("sort").map(|s| crate::reminder::ReminderSort::from_str(&s)).transpose()?,
+    })
+}
+
+/// Print the reminders matching the filters in `args`
+async fn list_reminders(args: &CommandArgs) -> Result<()> {
+    let query = match reminder_query(args) {
+        Ok(query) => query,
+        Err(e) => {
+            println!("{}", e);
+            return Ok(());
+        }
+    };
+    let reminders = crate::reminder::get_reminders(&query).await?;
+    if reminders.is_empty() {
+        println!("{}", crate::i18n::text(crate::i18n::Message::NoReminders));
+        return Ok(());
+    }
+    let colors = crate::theme::Colors::stdout();
+    let now = chrono::Local::now().naive_local();
+    for reminder in &reminders {
+        let priority = match reminder.priority {
+            Some(crate::todo::TodoPriority::High) => " !!!",
+            Some(crate::todo::TodoPriority::Medium) => " !!",
+            Some(crate::todo::TodoPriority::Low) => " !",
+            None => "",
+        };
+        let when = match (&reminder.completion_date, &reminder.due_date) {
+            (Some(done), _) if reminder.completed => format!(" (completed {})", colors.time(done)),
+            (_, Some(due)) => format!(" (due {})", colors.time(due)),
+            _ => String::new(),
+        };
+        let title = if reminder.is_overdue(now) {
+            colors.overdue(&reminder.title)
+        } else {
+            reminder.title.clone()
+        };
+        println!(
+            "{} {} {}{}{}",
+            if reminder.completed { "☑" } else { "☐" },
+            title,
+            colors.calendar(&format!("[{}]", reminder.lists.join(", "))),
+            when,
+            priority
+        );
+    }
+    Ok(())
+}
+
 // Reminder handler (using Apple's terminology "Reminders" for the app)
 #[derive(Debug)]
 pub struct ReminderHandler;
@@ -1254,7 +4483,9 @@ impl CommandHandler for ReminderHandler {
                 Some("create") | Some("add") => {
                     if args.args.len() < 2 {
                         println!("Not enough arguments for reminder create command");
-                        println!("Usage: ducktape reminder create <title> [list1] [list2] ...");
+                        println!(
+                            "Usage: ducktape reminder create <title> [list1] [list2] ... [--remind <time>] [--due <date>] [--priority high|medium|low] [--repeat <frequency>]"
+                        );
                         return Ok(());
                     }
 
@@ -1298,7 +4529,16 @@ impl CommandHandler for ReminderHandler {
                         None
                     };
 
-                    if let Some(time_str) = reminder_time {
+                    // Natural language times such as "tomorrow at 3pm" become YYYY-MM-DD HH:MM
+                    let reminder_time =
+                        match reminder_time.map(crate::dates::resolve_datetime).transpose() {
+                            Ok(time) => time,
+                            Err(e) => {
+                                println!("--remind: {}", e);
+                                return Ok(());
+                            }
+                        };
+                    if let Some(time_str) = &reminder_time {
                         debug!("Setting reminder time: {}", time_str);
                         config.reminder_time = Some(time_str);
                     }
@@ -1322,10 +4562,24 @@ impl CommandHandler for ReminderHandler {
 
                     if let Some(note_text) = notes {
                         debug!("Setting notes: {}", note_text);
-                        config.notes =
-                            Some(note_text.trim_matches('"').trim_matches('\'').to_string());
+                        let note_text = note_text.trim_matches('"').trim_matches('\'');
+                        config.notes = Some(crate::utils::sanitize_notes(
+                            note_text,
+                            args.flags.contains_key("raw"),
+                        ));
                     }
 
+                    let options = match ReminderOptions::from_flags(&args.flags) {
+                        Ok(options) => options,
+                        Err(e) => {
+                            println!("{}", e);
+                            return Ok(());
+                        }
+                    };
+                    config.due_date = options.due_date.as_deref();
+                    config.priority = options.priority;
+                    config.recurrence = options.recurrence;
+
                     debug!("Final reminder config: {:?}", config);
 
                     // Use await with the async create_reminder function
@@ -1340,11 +4594,7 @@ impl CommandHandler for ReminderHandler {
                         }
                     }
                 }
-                Some("list") => {
-                    // Implementation for listing reminders would go here using async/await
-                    println!("Listing reminders... (not implemented yet)");
-                    Ok(())
-                }
+                Some("list") => list_reminders(&args).await,
                 Some("delete") => {
                     // Implementation for deleting reminders would go here using async/await
                     println!("Deleting reminder... (not implemented yet)");
@@ -1367,30 +4617,7 @@ impl CommandHandler for ReminderHandler {
 
 // Print help information
 pub fn print_help() -> Result<()> {
-    println!("DuckTape - A tool for interacting with Apple Calendar, Notes, and Reminders");
-    println!();
-    println!("USAGE:");
-    println!("  ducktape [COMMAND] [SUBCOMMAND] [OPTIONS]");
-    println!();
-    println!("COMMANDS:");
-    println!("  calendar  Manage calendar events");
-    println!("  todo      Manage todo items");
-    println!("  notes     Manage notes");
-    println!("  config    Manage configuration");
-    println!("  contacts  Manage contact groups");
-    println!("  utils     Utility commands");
-    println!("  help      Show this help message");
-    println!("  version   Show version information");
-    println!("  exit      Exit the application");
-    println!();
-    println!("For more information on a specific command, run:");
-    println!("  ducktape [COMMAND] --help");
-    println!();
-    println!("EXAMPLES:");
-    println!("  ducktape calendar create \"Meeting with Team\" 2025-04-15 10:00 11:00");
-    println!("  ducktape todo add \"Buy groceries\" tomorrow 18:00");
-    println!("  ducktape notes create \"Meeting Notes\" \"Points discussed in the meeting\"");
-    println!("  ducktape config set calendar.default \"Personal\"");
+    print!("{}", crate::help::overview());
     Ok(())
 }
 
@@ -1411,10 +4638,113 @@ fn process_contact_string(contacts_str: &str) -> Vec<&str> {
     contacts
 }
 
+/// Ask how to resolve a pack conflict; keeps the existing entry without a terminal
+fn ask_pack_conflict(conflict: &crate::packs::Conflict) -> crate::packs::ConflictStrategy {
+    use crate::packs::ConflictStrategy;
+    use std::io::{IsTerminal, Write};
+
+    if !std::io::stdin().is_terminal() {
+        return ConflictStrategy::Skip;
+    }
+    println!("{} '{}' already exists", conflict.section, conflict.name);
+    println!("  existing: {}", conflict.existing);
+    println!("  pack:     {}", conflict.incoming);
+    loop {
+        print!("[s]kip, [o]verwrite or [r]ename? ");
+        let _ = std::io::stdout().flush();
+        let mut answer = String::new();
+        if std::io::stdin().read_line(&mut answer).is_err() {
+            return ConflictStrategy::Skip;
+        }
+        match answer.trim().to_lowercase().as_str() {
+            "" | "s" | "skip" => return ConflictStrategy::Skip,
+            "o" | "overwrite" => return ConflictStrategy::Overwrite,
+            "r" | "rename" if conflict.section != "default" => return ConflictStrategy::Rename,
+            _ => println!("Please answer s, o or r"),
+        }
+    }
+}
+
+/// Ask the user to confirm an action on stdin
+///
+/// Returns false when stdin is not a terminal, so scripted runs must pass `--yes`.
+fn confirm_action(prompt: &str) -> bool {
+    use std::io::{IsTerminal, Write};
+
+    if !std::io::stdin().is_terminal() {
+        println!("{} Re-run with --yes to apply without a terminal.", prompt);
+        return false;
+    }
+    print!("{} [y/N] ", prompt);
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Whether a destructive command may go ahead
+///
+/// It may with `--yes`, with `ui.confirm = false`, or when the user answers yes.
+fn confirmed(args: &CommandArgs, prompt: &str) -> bool {
+    if args.flags.contains_key("yes")
+        || !crate::config::Config::load().map(|c| c.ui.confirm).unwrap_or(true)
+    {
+        return true;
+    }
+    confirm_action(prompt)
+}
+
+/// The question asked before deleting a single event, reminder, note or contact group
+///
+/// Bulk commands ask in their handlers, once they know how many items they change.
+fn destructive_prompt(args: &CommandArgs) -> Option<String> {
+    if args.args.first().map(String::as_str) != Some("delete") {
+        return None;
+    }
+    let kind = match args.command.as_str() {
+        "calendar" | "calendars" => "event",
+        "todo" | "todos" | "reminder" | "reminders" => "reminder",
+        "note" | "notes" => "note",
+        "contact" | "contacts" => "contact group",
+        _ => return None,
+    };
+    Some(format!("Delete {} '{}'?", kind, args.args.get(1)?))
+}
+
 // Command processor that manages handlers and executes commands
 #[derive(Debug)]
 pub struct CommandProcessor {
     handlers: Vec<Box<dyn CommandHandler>>,
+    shared: SharedState,
+}
+
+/// Whether a command only reads, and so need not wait for changes made through the API
+fn is_read_only(args: &CommandArgs) -> bool {
+    match args.command.as_str() {
+        "help" | "--help" | "-h" | "version" | "--version" | "-v" | "man" | "exit" | "quit"
+        | "agenda" | "open" | "list" | "search" | "raycast" | "usage" | "nlp" | "doctor" => true,
+        "focus" => args.args.first().is_some_and(|sub| sub == "stats"),
+        "report" => !args.flags.contains_key("output"),
+        "review" => args.flags.contains_key("dry-run"),
+        "schedule" => args.args.first().is_some_and(|sub| sub == "list"),
+        _ => args.args.first().is_some_and(|sub| {
+            matches!(sub.as_str(), "list" | "show" | "search" | "export" | "get" | "props")
+        }),
+    }
+}
+
+/// Whether a command runs until stopped, and so takes the mutation lock only for each change
+fn is_long_running(args: &CommandArgs) -> bool {
+    let sub = args.args.first().map(String::as_str);
+    match args.command.as_str() {
+        "watch" => true,
+        "focus" => sub == Some("start"),
+        "schedule" => sub == Some("run"),
+        "calendar" => sub == Some("view"),
+        _ => false,
+    }
 }
 
 impl CommandProcessor {
@@ -1426,15 +4756,70 @@ impl CommandProcessor {
             Box::new(ConfigHandler),
             Box::new(UtilitiesHandler),
             Box::new(ContactGroupsHandler),
+            Box::new(AdoptHandler),
+            Box::new(LinkHandler),
+            Box::new(AgendaHandler),
+            Box::new(OpenHandler),
+            Box::new(ListHandler),
+            Box::new(QuickHandler),
+            Box::new(SearchHandler),
+            Box::new(RaycastHandler),
+            Box::new(RedoHandler),
+            Box::new(WatchHandler),
+            Box::new(SyncHandler),
+            Box::new(AttendeesHandler),
+            Box::new(PackHandler),
+            Box::new(CacheHandler),
+            Box::new(UsageHandler),
+            Box::new(NlpHandler),
+            Box::new(AuditHandler),
+            Box::new(BirthdaysHandler),
+            Box::new(HolidaysHandler),
+            Box::new(ScheduleHandler),
+            Box::new(FocusHandler),
+            Box::new(ReportHandler),
+            Box::new(ReviewHandler),
+            Box::new(SetupHandler),
+            Box::new(DoctorHandler),
+            Box::new(KeysHandler),
+            Box::new(WebhooksHandler),
+            Box::new(FeedsHandler),
+            Box::new(StateHandler),
+            Box::new(ShortcutHandler),
+            Box::new(IngestHandler),
             Box::new(VersionHandler),
             Box::new(HelpHandler),
+            Box::new(ManHandler),
             Box::new(ExitHandler),
             Box::new(ReminderHandler),
         ];
-        Self { handlers }
+        Self { handlers, shared: SharedState::global().clone() }
+    }
+
+    /// State shared with the API server
+    pub fn shared(&self) -> &SharedState {
+        &self.shared
     }
 
+    /// Run a command with its handler, in a span tying together what it logs
     pub async fn execute(&self, args: CommandArgs) -> Result<()> {
+        let id = crate::logging::correlation_id();
+        let description = crate::audit::describe(&args);
+        let span = info_span!("command", id = %id, name = %args.command);
+        match self.run(args).instrument(span).await {
+            Ok(outcome) => {
+                crate::audit::record(&id, &description, outcome, None);
+                Ok(())
+            }
+            Err(e) => {
+                crate::audit::record(&id, &description, "error", Some(&e));
+                Err(e)
+            }
+        }
+    }
+
+    /// Run a command, returning "ok", "duplicate", "cancelled" or "unrecognized"
+    async fn run(&self, args: CommandArgs) -> Result<&'static str> {
         debug!("Attempting to execute command: {}", args.command);
         debug!("Parsed arguments: {:?}", args.args);
         debug!("Parsed flags: {:?}", args.flags);
@@ -1442,6 +4827,24 @@ impl CommandProcessor {
         let command_name = args.command.clone(); // Clone the command name for logging
         let args_debug = format!("{:?}", args.args); // Format args for debug logging
 
+        if let Some(prompt) = destructive_prompt(&args) {
+            if !confirmed(&args, &prompt) {
+                println!("{}", crate::i18n::text(crate::i18n::Message::NothingDeleted));
+                crate::metrics::command_finished(&command_name, "cancelled");
+                return Ok("cancelled");
+            }
+        }
+
+        let idempotency_key = args.idempotency_key().map(str::to_string);
+        if let Some(key) = &idempotency_key {
+            if !claim_idempotency_key(key) {
+                info!("Skipping command '{}': idempotency key {} already used", command_name, key);
+                println!("Skipping duplicate request (idempotency key {})", key);
+                crate::metrics::command_finished(&command_name, "duplicate");
+                return Ok("duplicate");
+            }
+        }
+
         for handler in &self.handlers {
             if handler.can_handle(&command_name) {
                 info!("Executing command '{}' with arguments: {}", command_name, args_debug);
@@ -1449,13 +4852,29 @@ impl CommandProcessor {
                 // Use the args directly - our tokenizer should have handled quoted strings correctly
                 let args_to_use = args.clone();
 
-                match handler.execute(args_to_use).await {
+                // Changes wait for those made through the API server, and vice versa
+                let result = if is_read_only(&args_to_use) || is_long_running(&args_to_use) {
+                    handler.execute(args_to_use).await
+                } else {
+                    let result = self.shared.exclusive(handler.execute(args_to_use)).await;
+                    if let Err(e) = self.shared.reload().await {
+                        warn!("Could not reload the configuration: {}", e);
+                    }
+                    result
+                };
+
+                match result {
                     Ok(()) => {
                         debug!("Command '{}' executed successfully", command_name);
-                        return Ok(());
+                        crate::metrics::command_finished(&command_name, "ok");
+                        return Ok("ok");
                     }
                     Err(e) => {
-                        log::error!("Failed to execute command '{}': {:?}", command_name, e);
+                        tracing::error!("Failed to execute command '{}': {:?}", command_name, e);
+                        crate::metrics::command_finished(&command_name, "error");
+                        if let Some(key) = &idempotency_key {
+                            release_idempotency_key(key);
+                        }
                         return Err(e);
                     }
                 }
@@ -1464,7 +4883,7 @@ impl CommandProcessor {
 
         warn!("Unrecognized command: {}", command_name);
         println!("Unrecognized command. Type 'help' for a list of available commands.");
-        Ok(())
+        Ok("unrecognized")
     }
 }
 
@@ -1475,47 +4894,23 @@ impl Default for CommandProcessor {
 }
 
 /// Centralized function to resolve contacts from input
-pub fn resolve_contacts(input: &str) -> Vec<String> {
-    let mut contacts = Vec::new();
-
+///
+/// The input is first treated as a contact group name (DuckTape or Contacts.app group); if no
+/// group matches it is looked up as a person in Contacts.app.
+pub async fn resolve_contacts(input: &str) -> Result<Vec<String>> {
+    let input = input.trim().trim_matches('"');
     debug!("resolve_contacts called with input: '{}'", input);
 
-    // Example logic for resolving contacts with exact matching
-    let name_to_emails = vec![
-        (
-            "Shaun Stuart",
-            vec!["joe.duck@gmail.com", "joe.duck@live.com", "joe@ducktapeai.com"],
-        ),
-        (
-            "Joe Bloggs",
-            vec![
-                "joe.blogs@gmail.com",
-                "joe.blogs@company.com",
-                "joe.blogs@live.com",
-                "joe@freelancer.com",
-            ],
-        ),
-        (
-            "Jane Doe",
-            vec![
-                "jane.doe@gmail.com",
-                "jane.doe@company.com",
-                "jane.doe@live.com",
-                "jane@freelancer.com",
-            ],
-        ),
-    ];
-
-    for (name, emails) in name_to_emails {
-        debug!("Checking if '{}' matches '{}'", input.trim(), name);
-        if input.trim().eq_ignore_ascii_case(name) {
-            debug!("Match found for '{}', adding emails: {:?}", name, emails);
-            contacts.extend(emails.into_iter().map(String::from));
-        }
+    if let Some(emails) = crate::contact_groups::resolve_group_emails(input).await? {
+        debug!("Resolved group '{}' to {:?}", input, emails);
+        return Ok(emails);
     }
 
+    let contacts =
+        crate::calendar::resolve_contact(input, crate::calendar::ContactStrategy::default())
+            .await?;
     debug!("Resolved contacts: {:?}", contacts);
-    contacts
+    Ok(contacts)
 }
 
 /// Standardized input preprocessing function
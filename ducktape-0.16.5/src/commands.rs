@@ -0,0 +1,1141 @@
+//! Typed commands.
+//
+// Each struct here is one thing DuckTape can be asked to do, with its arguments already
+// checked and resolved: dates are YYYY-MM-DD, notes are sanitized, enums are enums. They are
+// built from the Clap model in `cli` (`TryFrom<&CalendarActions>` and friends), from API
+// payloads, or, for input that only the legacy tokenizer understands, from `CommandArgs` with
+// `from_args`. Either way a mistake in the input is a `DucktapeError::Parse` with a message for
+// the user, or a `ValidationError` from `validation` suggesting a fix. Running a command is up
+// to the `CommandBus`.
+
+use crate::calendar::{
+    ContactStrategy, CsvField, EventAvailability, EventConfig, EventConfigBuilder, OnDuplicate,
+    RecurrenceFrequency, RecurrencePattern,
+};
+use crate::cli::{CalendarActions, NoteActions, TodoActions};
+use crate::command_bus::Outcome;
+use crate::command_processor::{CommandArgs, ReminderOptions};
+use crate::config::MeetingService;
+use crate::error::DucktapeError;
+use crate::state::CalendarItem;
+use crate::todo::TodoPriority;
+use crate::validation;
+use anyhow::{Result, anyhow};
+use chrono::{Duration, NaiveDate, NaiveTime};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+/// A mistake in a command, with a message for the user
+pub(crate) fn usage(message: impl Into<String>) -> anyhow::Error {
+    DucktapeError::Parse(message.into()).into()
+}
+
+/// The meeting service asked for, else the configured one
+fn meeting_service(service: Option<MeetingService>) -> MeetingService {
+    service.unwrap_or_else(|| {
+        crate::config::Config::load()
+            .map(|c| c.calendar.meeting_provider)
+            .unwrap_or_default()
+    })
+}
+
+fn recurrence(
+    frequency: RecurrenceFrequency,
+    interval: Option<u32>,
+    until: Option<&str>,
+    count: Option<u32>,
+    days: &[u8],
+) -> Result<RecurrencePattern> {
+    let mut recurrence = RecurrencePattern::new(frequency);
+    if let Some(interval) = interval {
+        recurrence = recurrence.with_interval(interval);
+    }
+    if let Some(until) = until {
+        let until = validation::date("--until", until)?;
+        recurrence = recurrence.with_end_date(&until);
+    }
+    if let Some(count) = count {
+        recurrence = recurrence.with_count(count);
+    }
+    if !days.is_empty() {
+        recurrence = recurrence.with_days_of_week(days);
+    }
+    Ok(recurrence)
+}
+
+/// Check every address in `emails`
+fn valid_emails<'a>(emails: impl IntoIterator<Item = &'a String>) -> Result<Vec<String>> {
+    Ok(emails
+        .into_iter()
+        .map(|email| validation::email(email))
+        .collect::<Result<_, _>>()?)
+}
+
+/// A builder for the event on `date` from `start` to `end`, as the user typed them
+///
+/// Relative dates and times such as `tomorrow` and `3pm` are resolved here, and mistakes in
+/// them get the messages of `validation`.
+pub(crate) fn event_builder(
+    title: &str,
+    date: &str,
+    start: &str,
+    end: Option<&str>,
+) -> Result<EventConfigBuilder> {
+    let date = validation::date("date", date)?;
+    let (start, end) = match end {
+        Some(end) => {
+            let (start, end) = validation::time_range(start, end)?;
+            (start, Some(end))
+        }
+        None => (validation::time("start time", start)?, None),
+    };
+    let time = |value: &str| NaiveTime::parse_from_str(value, "%H:%M");
+    Ok(EventConfig::builder(title)
+        .on(NaiveDate::parse_from_str(&date, "%Y-%m-%d")?)
+        .at(time(&start)?)
+        .maybe(end.as_deref().map(time).transpose()?, EventConfigBuilder::until))
+}
+
+/// Items of a comma separated list
+fn split_list(value: Option<&str>) -> Vec<String> {
+    value
+        .iter()
+        .flat_map(|v| v.split(','))
+        .map(|s| s.trim().trim_matches(['"', '\'']).to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Notes with the `--tags` appended as #hashtags
+fn with_tags(notes: Option<String>, tags: Option<&str>) -> Option<String> {
+    match tags.map(crate::tag::parse_tag_list) {
+        Some(tags) => crate::tag::append_tags(notes.as_deref(), &tags),
+        None => notes,
+    }
+}
+
+/// The value of `--<name>` in legacy arguments, without surrounding quotes
+fn flag(args: &CommandArgs, name: &str) -> Option<String> {
+    args.flags
+        .get(name)
+        .cloned()
+        .flatten()
+        .map(|value| value.trim_matches('"').to_string())
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) => PathBuf::from(format!("{}{}", home.to_string_lossy(), rest)),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// An event to create
+#[derive(Debug, Clone)]
+pub struct CreateEvent {
+    pub config: EventConfig,
+    /// Calendar or alias asked for; the configured default when `None` and `config` has none
+    pub calendar: Option<String>,
+    /// Account of the calendar, for calendars of the same name in several accounts
+    pub account: Option<String>,
+    /// Contact groups whose members are invited
+    pub groups: Vec<String>,
+    /// Contacts looked up in Contacts.app and invited
+    pub contacts: Vec<String>,
+    pub contact_strategy: ContactStrategy,
+}
+
+impl CreateEvent {
+    pub fn new(config: EventConfig) -> Self {
+        Self {
+            config,
+            calendar: None,
+            account: None,
+            groups: Vec::new(),
+            contacts: Vec::new(),
+            contact_strategy: ContactStrategy::default(),
+        }
+    }
+
+    /// Legacy adapter for `calendar create <title> <date> <start> <end> [calendar] [flags]`
+    pub fn from_args(args: &CommandArgs) -> Result<Self> {
+        if args.args.len() < 5 {
+            return Err(usage(
+                "Not enough arguments for calendar create command\nUsage: ducktape calendar create <title> <date> <start_time> <end_time> [calendar]",
+            ));
+        }
+
+        // Multi-word titles that were not quoted, e.g. `create Team Sync 2025-04-22 ...`
+        let (title, date_index) = if args.args.len() >= 6
+            && !args.args[1].contains(['-', ':'])
+            && !args.args[2].contains(['-', ':'])
+            && args.args[3].contains(['-', '/'])
+        {
+            debug!("Detected potential multi-word title");
+            (format!("{} {}", args.args[1], args.args[2]), 3)
+        } else {
+            (args.args[1].clone(), 2)
+        };
+        let availability = flag(args, "availability")
+            .map(|a| EventAvailability::from_str(&a))
+            .transpose()
+            .map_err(|e| usage(e.to_string()))?;
+        let notes = args.flags.get("notes").cloned().flatten().map(|desc| {
+            crate::utils::sanitize_notes(desc.trim_matches('"'), args.flags.contains_key("raw"))
+        });
+        let meeting = match args.flags.get("meeting") {
+            Some(service) => Some(meeting_service(
+                service
+                    .as_deref()
+                    .map(MeetingService::from_str)
+                    .transpose()
+                    .map_err(|e| usage(e.to_string()))?,
+            )),
+            None => None,
+        };
+        let mut builder = event_builder(
+            &title,
+            &args.args[date_index],
+            &args.args[date_index + 1],
+            Some(args.args[date_index + 2].as_str()),
+        )?
+        .maybe(flag(args, "location"), EventConfigBuilder::location)
+        .maybe(flag(args, "url"), EventConfigBuilder::url)
+        .maybe(availability, EventConfigBuilder::availability)
+        .maybe(with_tags(notes, flag(args, "tags").as_deref()), EventConfigBuilder::description)
+        .attendees(split_list(flag(args, "email").as_deref()))
+        .zoom_meeting(args.flags.contains_key("zoom"))
+        .maybe(meeting, EventConfigBuilder::meeting);
+        validation::exclusive_flags(&args.flags, &[("until", "count")])?;
+        if !args.flags.contains_key("recurring") {
+            for option in ["interval", "until", "count", "days"] {
+                validation::flag_requires(&args.flags, option, "repeat")?;
+            }
+        }
+        if let Some(frequency) = flag(args, "repeat").or_else(|| flag(args, "recurring")) {
+            match RecurrenceFrequency::from_str(&frequency) {
+                Ok(frequency) => {
+                    let number = |name: &str| flag(args, name).and_then(|v| v.parse::<u32>().ok());
+                    let days: Vec<u8> = split_list(flag(args, "days").as_deref())
+                        .iter()
+                        .filter_map(|d| d.parse().ok())
+                        .collect();
+                    builder = builder.recurrence(recurrence(
+                        frequency,
+                        number("interval"),
+                        flag(args, "until").as_deref(),
+                        number("count"),
+                        &days,
+                    )?);
+                }
+                Err(e) => warn!("Invalid recurrence frequency '{}': {}", frequency, e),
+            }
+        }
+
+        let contact_strategy = match flag(args, "contact-strategy") {
+            Some(value) => ContactStrategy::from_str(&value).map_err(|e| usage(e.to_string()))?,
+            None => ContactStrategy::default(),
+        };
+        Ok(Self {
+            config: builder.build()?,
+            // The calendar follows the end time, unless that is already a flag
+            calendar: args
+                .args
+                .get(date_index + 3)
+                .filter(|arg| !arg.starts_with("--"))
+                .map(|cal| cal.trim_matches('"').to_string()),
+            account: flag(args, "account"),
+            groups: split_list(flag(args, "group").as_deref()),
+            contacts: split_list(flag(args, "contacts").as_deref()),
+            contact_strategy,
+        })
+    }
+
+    /// Pick the calendar, invite groups and contacts, and create the event
+    pub(crate) async fn run(self) -> Result<Outcome> {
+        let Self { mut config, calendar, account, groups, contacts, contact_strategy } = self;
+
+        // Explicit calendar, then the configured default, then the system default
+        if calendar.is_some() || account.is_some() || config.calendars.is_empty() {
+            let app_config = crate::config::Config::load()?;
+            let available = crate::calendar::get_available_calendars().await?;
+            let requested = calendar.as_deref().map(|c| app_config.calendar.resolve_calendar(c));
+            let selected =
+                match crate::calendar::select_calendar(requested.as_deref(), None, &available) {
+                    Some(selected) => selected,
+                    None => {
+                        if let Some(cal) = &calendar {
+                            warn!("Calendar '{}' not found, using the default calendar", cal);
+                            let hint = validation::calendar_name(cal, &available)
+                                .err()
+                                .and_then(|e| e.suggestion)
+                                .map(|suggestion| format!(" {}", suggestion))
+                                .unwrap_or_default();
+                            println!(
+                                "Warning: Calendar '{}' not found.{} Using default calendar.",
+                                cal, hint
+                            );
+                        }
+                        crate::calendar::default_calendar(&app_config, &available).await?
+                    }
+                };
+            config.calendars = match &account {
+                Some(account) if crate::calendar::split_account(&selected).1.is_none() => {
+                    vec![crate::calendar::with_account(&selected, account)]
+                }
+                _ => vec![selected],
+            };
+        }
+
+        // Only a warning: some events are meant to be on a holiday
+        if let Some(warning) = crate::holidays::holiday_warning(&config.start_date) {
+            warn!("{}", warning);
+            println!("Warning: {}", warning);
+        }
+
+        if !groups.is_empty() {
+            let mut members = Vec::new();
+            for group in &groups {
+                let Some(emails) = crate::contact_groups::resolve_group_emails(group).await? else {
+                    return Err(usage(format!("Contact group '{}' not found", group)));
+                };
+                info!("Adding {} attendee(s) from group '{}'", emails.len(), group);
+                members.extend(emails);
+            }
+            config.emails = crate::contact_groups::merge_attendees(&config.emails, &members);
+
+            // Without a description of its own the event would get the calendar's template
+            let description = config.description.clone().or_else(|| {
+                let app_config = crate::config::Config::load().ok()?;
+                let calendar = config.calendars.first()?;
+                app_config.calendar.description_template(calendar).map(str::to_string)
+            });
+            config.description =
+                Some(crate::contact_groups::group_note(description.as_deref(), &groups));
+        }
+
+        if contacts.is_empty() {
+            crate::calendar::create_event(config.clone()).await?;
+        } else {
+            let names: Vec<&str> = contacts.iter().map(String::as_str).collect();
+            info!(
+                "Creating event with {} contact(s): {:?} (strategy: {:?})",
+                names.len(),
+                names,
+                contact_strategy
+            );
+            crate::calendar::create_event_with_contacts_strategy(
+                config.clone(),
+                &names,
+                contact_strategy,
+            )
+            .await?;
+        }
+        Ok(Outcome::EventCreated(config))
+    }
+}
+
+impl TryFrom<&CalendarActions> for CreateEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(action: &CalendarActions) -> Result<Self> {
+        let CalendarActions::Create {
+            title,
+            date,
+            start_time,
+            end_time,
+            calendar,
+            account,
+            contacts,
+            group,
+            contact_strategy,
+            email,
+            location,
+            url,
+            availability,
+            notes,
+            tags,
+            raw,
+            zoom,
+            meeting,
+            repeat,
+            interval,
+            until,
+            count,
+            days,
+        } = action
+        else {
+            return Err(anyhow!("Not a calendar create command"));
+        };
+
+        let recurrence = repeat
+            .as_ref()
+            .map(|frequency| {
+                recurrence(
+                    frequency.clone().into(),
+                    *interval,
+                    until.as_deref(),
+                    *count,
+                    days.as_deref().unwrap_or_default(),
+                )
+            })
+            .transpose()?;
+        let config = event_builder(title, date, start_time, Some(end_time.as_str()))?
+            .maybe(location.as_deref(), EventConfigBuilder::location)
+            .maybe(url.as_deref(), EventConfigBuilder::url)
+            .maybe(availability.clone().map(Into::into), EventConfigBuilder::availability)
+            .maybe(
+                with_tags(
+                    notes.as_deref().map(|n| crate::utils::sanitize_notes(n, *raw)),
+                    tags.as_deref(),
+                ),
+                EventConfigBuilder::description,
+            )
+            .attendees(email.iter().flatten())
+            .zoom_meeting(*zoom)
+            .maybe(
+                meeting.as_ref().map(|service| meeting_service(service.clone().map(Into::into))),
+                EventConfigBuilder::meeting,
+            )
+            .maybe(recurrence, EventConfigBuilder::recurrence)
+            .build()?;
+
+        Ok(Self {
+            config,
+            calendar: calendar.clone(),
+            account: account.clone(),
+            groups: group.clone().unwrap_or_default(),
+            contacts: contacts.clone().unwrap_or_default(),
+            contact_strategy: contact_strategy.clone().map(Into::into).unwrap_or_default(),
+        })
+    }
+}
+
+/// Changes to an event DuckTape tracks
+#[derive(Debug, Clone, Default)]
+pub struct UpdateEvent {
+    /// UID or exact title of the event; `@last` or a pronoun until the bus resolves it
+    pub id: String,
+    /// Only look for the title in this calendar
+    pub calendar: Option<String>,
+    pub title: Option<String>,
+    /// YYYY-MM-DD
+    pub date: Option<String>,
+    /// HH:MM; without `end_time` the event keeps its length
+    pub start_time: Option<String>,
+    /// HH:MM
+    pub end_time: Option<String>,
+    pub location: Option<String>,
+    /// Contacts looked up in Contacts.app and invited
+    pub contacts: Vec<String>,
+    pub emails: Vec<String>,
+}
+
+/// Attendee addresses of a tracked event
+fn attendee_emails(item: &CalendarItem) -> Vec<String> {
+    split_list(item.email.as_deref())
+}
+
+impl UpdateEvent {
+    /// The changes once the new title, date, times and addresses are checked
+    pub fn checked(mut self) -> Result<Self> {
+        let changes = [&self.title, &self.date, &self.start_time, &self.end_time, &self.location];
+        if changes.iter().all(|change| change.is_none())
+            && self.contacts.is_empty()
+            && self.emails.is_empty()
+        {
+            return Err(usage(
+                "Nothing to change. Pass --title, --date, --start-time, --end-time, --location, --contacts or --email",
+            ));
+        }
+        self.title = self.title.map(|title| validation::title("title", &title)).transpose()?;
+        self.date = self.date.map(|date| validation::date("date", &date)).transpose()?;
+        self.start_time =
+            self.start_time.map(|time| validation::time("start time", &time)).transpose()?;
+        self.end_time =
+            self.end_time.map(|time| validation::time("end time", &time)).transpose()?;
+        self.emails = valid_emails(&self.emails)?;
+        Ok(self)
+    }
+
+    /// Legacy adapter for `calendar update <id> [calendar] [flags]`
+    pub fn from_args(args: &CommandArgs) -> Result<Self> {
+        let Some(id) = args.args.get(1) else {
+            return Err(usage(
+                "Usage: ducktape calendar update <uid, title or @last> [calendar] [--title <title>] [--date <date>] [--start-time HH:MM] [--end-time HH:MM] [--location <location>] [--contacts <names>] [--email <addresses>]",
+            ));
+        };
+        Self {
+            id: id.trim_matches('"').to_string(),
+            calendar: args
+                .args
+                .get(2)
+                .filter(|arg| !arg.starts_with("--"))
+                .map(|cal| cal.trim_matches('"').to_string()),
+            title: flag(args, "title"),
+            date: flag(args, "date"),
+            start_time: flag(args, "start-time"),
+            end_time: flag(args, "end-time"),
+            location: flag(args, "location"),
+            contacts: split_list(flag(args, "contacts").as_deref()),
+            emails: split_list(flag(args, "email").as_deref()),
+        }
+        .checked()
+    }
+
+    /// `existing` with the changes made and `invited` added to its attendees
+    pub fn apply(&self, existing: &CalendarItem, invited: &[String]) -> Result<CalendarItem> {
+        let mut item = existing.clone();
+        if let Some(title) = &self.title {
+            item.title = title.clone();
+        }
+        if let Some(date) = &self.date {
+            item.date = date.clone();
+        }
+        if let Some(location) = &self.location {
+            item.location = Some(location.clone());
+        }
+        if let Some(start) = &self.start_time {
+            let parse = |time: &str| NaiveTime::parse_from_str(time, "%H:%M").ok();
+            // An all-day event becomes an hour long
+            let length = match (item.all_day, parse(&item.time), item.end_time.as_deref()) {
+                (false, Some(old_start), Some(old_end)) => match parse(old_end) {
+                    Some(old_end) if old_end < old_start => old_end - old_start + Duration::days(1),
+                    Some(old_end) => old_end - old_start,
+                    None => Duration::hours(1),
+                },
+                _ => Duration::hours(1),
+            };
+            let start_time =
+                parse(start).ok_or_else(|| usage(format!("Invalid time {}", start)))?;
+            item.time = start.clone();
+            item.end_time = Some((start_time + length).format("%H:%M").to_string());
+            item.all_day = false;
+        }
+        if let Some(end) = &self.end_time {
+            item.end_time = Some(end.clone());
+            if item.all_day {
+                return Err(usage("Give a --start-time to turn an all-day event into a timed one"));
+            }
+        }
+        if let Some(end) = &item.end_time {
+            validation::time_range(&item.time, end)?;
+        }
+
+        let mut emails = attendee_emails(&item);
+        for email in invited {
+            if !emails.iter().any(|known| known.eq_ignore_ascii_case(email)) {
+                emails.push(email.clone());
+            }
+        }
+        item.email = (!emails.is_empty()).then(|| emails.join(", "));
+        Ok(item)
+    }
+
+    /// Find the event, look up the contacts and change the event in its calendar
+    pub(crate) async fn run(self) -> Result<Outcome> {
+        let items = crate::state::load_events()?;
+        let existing =
+            crate::calendar::find_event_by_id(&items, &self.id, self.calendar.as_deref())
+                .map_err(|e| usage(e.to_string()))?
+                .clone();
+
+        let mut invited = self.emails.clone();
+        for name in &self.contacts {
+            let emails = crate::calendar::resolve_contact(name, ContactStrategy::default()).await?;
+            if emails.is_empty() {
+                warn!("No contact found for '{}'", name);
+                println!("Warning: no contact found for '{}'", name);
+            }
+            invited.extend(emails);
+        }
+        let updated = self.apply(&existing, &invited)?;
+        let known = attendee_emails(&existing);
+        let added: Vec<String> = attendee_emails(&updated)
+            .into_iter()
+            .filter(|email| !known.iter().any(|k| k.eq_ignore_ascii_case(email)))
+            .collect();
+        crate::calendar::update_event(&existing, &updated, &added).await?;
+        Ok(Outcome::EventUpdated(updated))
+    }
+}
+
+impl TryFrom<&CalendarActions> for UpdateEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(action: &CalendarActions) -> Result<Self> {
+        let CalendarActions::Update {
+            event_id,
+            calendar,
+            title,
+            date,
+            start_time,
+            end_time,
+            location,
+            contacts,
+            email,
+        } = action
+        else {
+            return Err(anyhow!("Not a calendar update command"));
+        };
+        Self {
+            id: event_id.clone(),
+            calendar: calendar.clone(),
+            title: title.clone(),
+            date: date.clone(),
+            start_time: start_time.clone(),
+            end_time: end_time.clone(),
+            location: location.clone(),
+            contacts: contacts.clone().unwrap_or_default(),
+            emails: email.clone().unwrap_or_default(),
+        }
+        .checked()
+    }
+}
+
+/// Events to import from an .ics or CSV file
+#[derive(Debug, Clone)]
+pub struct ImportEvents {
+    pub path: PathBuf,
+    /// `ics` or `csv`
+    pub format: String,
+    pub calendar: Option<String>,
+    pub on_duplicate: OnDuplicate,
+    pub column_map: Vec<(String, CsvField)>,
+}
+
+impl ImportEvents {
+    fn new(
+        path: PathBuf,
+        format: &str,
+        calendar: Option<String>,
+        on_duplicate: OnDuplicate,
+        map: Option<&str>,
+        map_file: Option<&Path>,
+    ) -> Result<Self> {
+        let format = format.to_lowercase();
+        if format != "ics" && format != "csv" {
+            return Err(usage(format!("Unsupported format: {}. Use --format ics or csv", format)));
+        }
+        let column_map =
+            crate::calendar::load_column_map(map, map_file).map_err(|e| usage(e.to_string()))?;
+        Ok(Self { path, format, calendar, on_duplicate, column_map })
+    }
+
+    /// Legacy adapter for `calendar import <file> [calendar] [flags]`
+    pub fn from_args(args: &CommandArgs) -> Result<Self> {
+        let Some(file) = args.args.get(1) else {
+            return Err(usage(
+                "Usage: ducktape calendar import <file> [calendar] [--format ics|csv] [--on-duplicate skip|update|duplicate] [--map \"Header=field,...\"] [--map-file <file>]",
+            ));
+        };
+        let path = expand_home(file.trim_matches('"'));
+        let format = flag(args, "format").unwrap_or_else(|| {
+            path.extension()
+                .map_or("ics".to_string(), |ext| ext.to_string_lossy().to_string())
+        });
+        let on_duplicate = match flag(args, "on-duplicate") {
+            Some(value) => value.parse::<OnDuplicate>().map_err(|e| usage(e.to_string()))?,
+            None => OnDuplicate::default(),
+        };
+        let calendar = args
+            .args
+            .get(2)
+            .map(|c| c.trim_matches('"').to_string())
+            .or_else(|| flag(args, "calendar"));
+        let map_file = flag(args, "map-file");
+        Self::new(
+            path,
+            &format,
+            calendar,
+            on_duplicate,
+            flag(args, "map").as_deref(),
+            map_file.as_deref().map(Path::new),
+        )
+    }
+
+    pub(crate) async fn run(self) -> Result<Outcome> {
+        if !self.path.exists() {
+            return Err(usage(format!("File not found: {}", self.path.display())));
+        }
+        let summary = if self.format == "csv" {
+            crate::calendar::import_csv_events(
+                &self.path,
+                self.calendar,
+                self.on_duplicate,
+                &self.column_map,
+            )
+            .await?
+        } else {
+            crate::calendar::import_ics_events(&self.path, self.calendar, self.on_duplicate).await?
+        };
+        Ok(Outcome::Imported(summary))
+    }
+}
+
+impl TryFrom<&CalendarActions> for ImportEvents {
+    type Error = anyhow::Error;
+
+    fn try_from(action: &CalendarActions) -> Result<Self> {
+        let CalendarActions::Import { file, calendar, format, on_duplicate, map, map_file } =
+            action
+        else {
+            return Err(anyhow!("Not a calendar import command"));
+        };
+        Self::new(
+            expand_home(&file.to_string_lossy()),
+            format,
+            calendar.clone(),
+            on_duplicate.clone().into(),
+            map.as_deref(),
+            map_file.as_deref(),
+        )
+    }
+}
+
+/// A todo to create
+#[derive(Debug, Clone, Default)]
+pub struct CreateTodo {
+    pub title: String,
+    /// Lists to add it to; the default list when empty
+    pub lists: Vec<String>,
+    /// "YYYY-MM-DD HH:MM"
+    pub reminder_time: Option<String>,
+    /// "YYYY-MM-DD" for an all-day due date or "YYYY-MM-DD HH:MM"
+    pub due_date: Option<String>,
+    pub priority: Option<TodoPriority>,
+    pub recurrence: Option<RecurrencePattern>,
+    pub notes: Option<String>,
+}
+
+/// Check every reminder list name in `lists`
+fn list_names<'a>(lists: impl IntoIterator<Item = &'a String>) -> Result<Vec<String>> {
+    Ok(lists
+        .into_iter()
+        .map(|list| validation::list_name(list))
+        .collect::<Result<_, _>>()?)
+}
+
+/// A time such as "tomorrow at 3pm" as YYYY-MM-DD HH:MM
+fn reminder_time(time: Option<&str>) -> Result<Option<String>> {
+    time.map(crate::dates::resolve_datetime)
+        .transpose()
+        .map_err(|e| usage(format!("--remind: {}", e)))
+}
+
+impl CreateTodo {
+    /// Legacy adapter for `todo create <title> [list ...] [flags]`
+    pub fn from_args(args: &CommandArgs) -> Result<Self> {
+        let Some(title) = args.args.get(1) else {
+            return Err(usage(
+                "Not enough arguments for todo create command\nUsage: ducktape todo create <title> [list1] [list2] ... [--remind <time>] [--due <date>] [--priority high|medium|low] [--repeat <frequency>]",
+            ));
+        };
+
+        // Flags the legacy tokenizer left among the arguments, e.g. `--remind <time>`
+        let inline = |name: &str| {
+            let at = args.args.iter().position(|arg| *arg == format!("--{}", name))?;
+            args.args.get(at + 1).map(|v| v.trim_matches(['"', '\'']).to_string())
+        };
+        let remind = args.flags.get("remind").cloned().flatten().or_else(|| inline("remind"));
+        let notes = args.flags.get("notes").cloned().flatten().or_else(|| inline("notes"));
+        let options = ReminderOptions::from_flags(&args.flags).map_err(|e| usage(e.to_string()))?;
+
+        Self {
+            title: title.clone(),
+            lists: args.args[2..]
+                .iter()
+                .take_while(|arg| !arg.starts_with("--"))
+                .cloned()
+                .collect(),
+            reminder_time: reminder_time(remind.as_deref())?,
+            due_date: options.due_date,
+            priority: options.priority,
+            recurrence: options.recurrence,
+            notes: with_tags(
+                notes.map(|text| {
+                    crate::utils::sanitize_notes(
+                        text.trim_matches(['"', '\'']),
+                        args.flags.contains_key("raw"),
+                    )
+                }),
+                flag(args, "tags").as_deref(),
+            ),
+        }
+        .checked()
+    }
+
+    /// The todo once its title and lists are checked
+    pub fn checked(mut self) -> Result<Self> {
+        self.title = validation::title("title", &self.title)?;
+        self.lists = list_names(&self.lists)?;
+        Ok(self)
+    }
+
+    pub(crate) async fn run(self) -> Result<Outcome> {
+        let mut config = crate::todo::TodoConfig::new(&self.title);
+        config.lists = self.lists.iter().map(String::as_str).collect();
+        config.reminder_time = self.reminder_time.as_deref();
+        config.due_date = self.due_date.as_deref();
+        config.priority = self.priority;
+        config.recurrence = self.recurrence.clone();
+        config.notes = self.notes.clone();
+        debug!("Final todo config: {:?}", config);
+        crate::todo::create_todo(config).await?;
+        Ok(Outcome::TodoCreated(self.title))
+    }
+}
+
+impl TryFrom<&TodoActions> for CreateTodo {
+    type Error = anyhow::Error;
+
+    fn try_from(action: &TodoActions) -> Result<Self> {
+        let TodoActions::Create {
+            title,
+            lists,
+            remind,
+            due,
+            priority,
+            repeat,
+            interval,
+            until,
+            count,
+            notes,
+            tags,
+            raw,
+        } = action
+        else {
+            return Err(anyhow!("Not a todo create command"));
+        };
+        Self {
+            title: title.clone(),
+            lists: lists.clone(),
+            reminder_time: reminder_time(remind.as_deref())?,
+            due_date: due
+                .as_deref()
+                .map(crate::dates::resolve_due)
+                .transpose()
+                .map_err(|e| usage(e.to_string()))?,
+            priority: priority.clone().map(Into::into),
+            recurrence: repeat
+                .as_ref()
+                .map(|frequency| {
+                    recurrence(frequency.clone().into(), *interval, until.as_deref(), *count, &[])
+                })
+                .transpose()?,
+            notes: with_tags(
+                notes.as_deref().map(|text| crate::utils::sanitize_notes(text, *raw)),
+                tags.as_deref(),
+            ),
+        }
+        .checked()
+    }
+}
+
+/// A note to create
+#[derive(Debug, Clone, Default)]
+pub struct CreateNote {
+    pub title: String,
+    pub content: String,
+    pub folder: Option<String>,
+    /// Whether `content` is Markdown to render as HTML
+    pub markdown: bool,
+}
+
+impl CreateNote {
+    fn new(
+        title: String,
+        content: &str,
+        folder: Option<String>,
+        tags: Option<&str>,
+        markdown: bool,
+        raw: bool,
+    ) -> Result<Self> {
+        let content =
+            if markdown { content.to_string() } else { crate::utils::sanitize_notes(content, raw) };
+        let content = with_tags(Some(content), tags).unwrap_or_default();
+        Self { title, content, folder, markdown }.checked()
+    }
+
+    /// The note once its title is checked
+    pub fn checked(mut self) -> Result<Self> {
+        self.title = validation::title("title", &self.title)?;
+        Ok(self)
+    }
+
+    /// Legacy adapter for `note create <title> [content] [flags]`
+    pub fn from_args(args: &CommandArgs) -> Result<Self> {
+        if args.args.len() < 2 {
+            return Err(usage(
+                "Not enough arguments for note create command\nUsage: ducktape note create <title> [content] [--folder <folder_name>]",
+            ));
+        }
+
+        // Unquoted multi-word titles, e.g. `note create Project ideas for Q2`
+        let words: Vec<&str> = args.args[1..]
+            .iter()
+            .take_while(|arg| !arg.starts_with("--"))
+            .map(|arg| arg.trim_matches('"'))
+            .collect();
+        let title = if words.len() > 1 && !args.args[1].contains(' ') {
+            words.join(" ")
+        } else {
+            args.args[1].trim_matches('"').to_string()
+        };
+
+        // Content from --content or the argument after a quoted title
+        let content = flag(args, "content")
+            .or_else(|| {
+                args.args
+                    .get(2)
+                    .filter(|arg| !arg.starts_with("--"))
+                    .map(|arg| arg.trim_matches('"').to_string())
+            })
+            .unwrap_or_default();
+
+        Self::new(
+            title,
+            &content,
+            flag(args, "folder"),
+            flag(args, "tags").as_deref(),
+            args.flags.contains_key("markdown"),
+            args.flags.contains_key("raw"),
+        )
+    }
+
+    pub(crate) async fn run(self) -> Result<Outcome> {
+        debug!(
+            "Creating note: title='{}', content_length={}, folder={:?}",
+            self.title,
+            self.content.len(),
+            self.folder
+        );
+        let mut config = crate::notes::NoteConfig::new(&self.title, &self.content);
+        config.folder = self.folder.as_deref();
+        config.markdown = self.markdown;
+        crate::notes::create_note(config).await?;
+        Ok(Outcome::NoteCreated(self.title))
+    }
+}
+
+impl TryFrom<&NoteActions> for CreateNote {
+    type Error = anyhow::Error;
+
+    fn try_from(action: &NoteActions) -> Result<Self> {
+        let NoteActions::Create { title, content, folder, tags, markdown, raw } = action else {
+            return Err(anyhow!("Not a note create command"));
+        };
+        Self::new(
+            title.join(" "),
+            content.as_deref().unwrap_or_default(),
+            folder.clone(),
+            tags.as_deref(),
+            *markdown,
+            *raw,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{Cli, Commands};
+    use clap::Parser;
+    use std::collections::HashMap;
+
+    fn args(words: &[&str], flags: &[(&str, Option<&str>)]) -> CommandArgs {
+        CommandArgs::new(
+            words[0].to_string(),
+            words[1..].iter().map(|w| w.to_string()).collect(),
+            flags
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.map(str::to_string)))
+                .collect::<HashMap<_, _>>(),
+        )
+    }
+
+    fn cli(line: &str) -> Cli {
+        Cli::try_parse_from(shell_words::split(line).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_create_event_from_args() {
+        let event = CreateEvent::from_args(&args(
+            &["calendar", "create", "Team", "Sync", "2025-04-22", "10:00", "11:00", "Work"],
+            &[
+                ("location", Some("\"Room 4\"")),
+                ("email", Some("a@example.com")),
+                ("group", Some("team, leads")),
+            ],
+        ))
+        .unwrap();
+        assert_eq!(event.config.title, "Team Sync");
+        assert_eq!(event.config.start_date, "2025-04-22");
+        assert_eq!(event.config.end_time.as_deref(), Some("11:00"));
+        assert_eq!(event.config.location.as_deref(), Some("Room 4"));
+        assert_eq!(event.config.emails, vec!["a@example.com"]);
+        assert_eq!(event.calendar.as_deref(), Some("Work"));
+        assert_eq!(event.groups, vec!["team", "leads"]);
+    }
+
+    #[test]
+    fn test_create_event_from_cli() {
+        let cli = cli(
+            "ducktape calendar create \"Team Sync\" 2025-04-22 10:00 11:00 Work --email a@example.com,b@example.com --contacts \"Jane Doe\" --repeat weekly --count 4 --days 2,4",
+        );
+        let Some(Commands::Calendar { action }) = &cli.command else {
+            panic!("expected a calendar command");
+        };
+        let event = CreateEvent::try_from(action).unwrap();
+        assert_eq!(event.config.title, "Team Sync");
+        assert_eq!(event.config.emails, vec!["a@example.com", "b@example.com"]);
+        assert_eq!(event.contacts, vec!["Jane Doe"]);
+        assert_eq!(event.calendar.as_deref(), Some("Work"));
+        let recurrence = event.config.recurrence.unwrap();
+        assert_eq!(recurrence.frequency, RecurrenceFrequency::Weekly);
+        assert_eq!(recurrence.count, Some(4));
+        assert_eq!(recurrence.days_of_week, vec![2, 4]);
+    }
+
+    #[test]
+    fn test_create_todo_and_note() {
+        let cli = cli(
+            "ducktape todo create \"Buy milk\" Groceries --priority high --notes \"<b>2%</b>\"",
+        );
+        let Some(Commands::Todo { action }) = &cli.command else {
+            panic!("expected a todo command");
+        };
+        let todo = CreateTodo::try_from(action).unwrap();
+        assert_eq!(todo.lists, vec!["Groceries"]);
+        assert_eq!(todo.priority, Some(TodoPriority::High));
+        assert_eq!(todo.notes.as_deref(), Some("2%"));
+
+        let legacy = CreateTodo::from_args(&args(
+            &["todo", "create", "Buy milk", "Groceries", "--remind", "2025-04-22 09:00"],
+            &[],
+        ))
+        .unwrap();
+        assert_eq!(legacy.lists, vec!["Groceries"]);
+        assert_eq!(legacy.reminder_time.as_deref(), Some("2025-04-22 09:00"));
+
+        let note = CreateNote::from_args(&args(
+            &["note", "create", "Project", "ideas"],
+            &[("folder", Some("Work"))],
+        ))
+        .unwrap();
+        assert_eq!(note.title, "Project ideas");
+        assert_eq!(note.folder.as_deref(), Some("Work"));
+    }
+
+    #[test]
+    fn test_create_with_tags() {
+        let event = cli(
+            "ducktape calendar create \"Deep work\" 2025-04-22 09:00 11:00 --notes Outline --tags deep-work,clientX",
+        );
+        let Some(Commands::Calendar { action }) = &event.command else {
+            panic!("expected a calendar command");
+        };
+        let event = CreateEvent::try_from(action).unwrap();
+        assert_eq!(event.config.description.as_deref(), Some("Outline\n#deep-work #clientx"));
+
+        let todo = cli("ducktape todo create \"Send invoice\" --tags clientX");
+        let Some(Commands::Todo { action }) = &todo.command else {
+            panic!("expected a todo command");
+        };
+        assert_eq!(CreateTodo::try_from(action).unwrap().notes.as_deref(), Some("#clientx"));
+
+        let note = CreateNote::from_args(&args(
+            &["note", "create", "Kickoff"],
+            &[("content", Some("Goals")), ("tags", Some("clientX"))],
+        ))
+        .unwrap();
+        assert_eq!(note.content, "Goals\n#clientx");
+    }
+
+    #[test]
+    fn test_usage_errors() {
+        let e = CreateEvent::from_args(&args(&["calendar", "create", "Lunch"], &[])).unwrap_err();
+        assert!(crate::command_bus::is_usage_error(&e));
+
+        let e = ImportEvents::from_args(&args(
+            &["calendar", "import", "events.txt"],
+            &[("format", Some("xml"))],
+        ))
+        .unwrap_err();
+        assert!(crate::command_bus::is_usage_error(&e));
+        assert!(e.to_string().contains("Unsupported format"));
+
+        let e = CreateEvent::from_args(&args(
+            &["calendar", "create", "Lunch", "2025-04-22", "12:00", "13:00"],
+            &[("email", Some("a@example.com, jane@gmial.com"))],
+        ))
+        .unwrap_err();
+        assert!(crate::command_bus::is_usage_error(&e));
+        assert!(e.to_string().contains("Did you mean jane@gmail.com?"));
+
+        let e = CreateEvent::from_args(&args(
+            &["calendar", "create", "Lunch", "2025-04-22", "12", "13:00"],
+            &[],
+        ))
+        .unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "Invalid start time: '12' is not a time of day. Did you mean 12:00?"
+        );
+
+        let e = CreateEvent::from_args(&args(
+            &["calendar", "create", "Standup", "2025-04-22", "09:00", "09:15"],
+            &[("count", Some("5"))],
+        ))
+        .unwrap_err();
+        assert!(e.to_string().contains("only applies together with --repeat"));
+
+        let e = CreateTodo::from_args(&args(&["todo", "create", "Pay rent", "Home/Bills"], &[]))
+            .unwrap_err();
+        assert!(crate::command_bus::is_usage_error(&e));
+
+        let e = UpdateEvent::from_args(&args(&["calendar", "update", "@last"], &[])).unwrap_err();
+        assert!(e.to_string().starts_with("Nothing to change"));
+    }
+
+    #[test]
+    fn test_update_event() {
+        let existing = CalendarItem {
+            title: "Lunch with Sam".to_string(),
+            date: "2025-05-06".to_string(),
+            time: "12:00".to_string(),
+            calendars: vec!["Work".to_string()],
+            email: Some("sam@example.com".to_string()),
+            uid: Some("8E2F1C34".to_string()),
+            end_time: Some("13:30".to_string()),
+            ..Default::default()
+        };
+
+        let update = UpdateEvent::from_args(&args(
+            &["calendar", "update", "8E2F1C34"],
+            &[("start-time", Some("15:00")), ("contacts", Some("Jane Doe"))],
+        ))
+        .unwrap();
+        assert_eq!(update.contacts, ["Jane Doe"]);
+        let invited = ["jane@example.com".to_string(), "SAM@example.com".to_string()];
+        let updated = update.apply(&existing, &invited).unwrap();
+        assert_eq!((updated.time.as_str(), updated.end_time.as_deref()), ("15:00", Some("16:30")));
+        assert_eq!(updated.email.as_deref(), Some("sam@example.com, jane@example.com"));
+        assert_eq!(updated.uid, existing.uid);
+
+        let all_day = CalendarItem { all_day: true, end_time: None, ..existing.clone() };
+        let update = UpdateEvent { end_time: Some("14:00".to_string()), ..Default::default() };
+        assert!(update.apply(&all_day, &[]).is_err());
+        let update = UpdateEvent { start_time: Some("09:00".to_string()), ..Default::default() };
+        let updated = update.apply(&all_day, &[]).unwrap();
+        assert!(!updated.all_day);
+        assert_eq!(updated.end_time.as_deref(), Some("10:00"));
+
+        let cli = cli("ducktape calendar edit @last --title Brunch");
+        let Some(Commands::Calendar { action }) = &cli.command else {
+            panic!("expected a calendar command");
+        };
+        let command = UpdateEvent::try_from(action).unwrap();
+        assert_eq!((command.id.as_str(), command.title.as_deref()), ("@last", Some("Brunch")));
+    }
+}
@@ -1,10 +1,38 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Environment variable selecting a profile; `--profile` sets it
+pub const PROFILE_ENV: &str = "DUCKTAPE_PROFILE";
+/// File naming the profile to use in a directory and its subdirectories
+pub const PROFILE_FILE: &str = ".ducktape-profile";
+
+/// Problems with config.toml or a value given for it
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Failed to parse config file: {0}")]
+    Invalid(String),
+
+    #[error("Unknown profile '{0}'. Create it with `ducktape config profile create {0}`")]
+    UnknownProfile(String),
+
+    #[error("Invalid meeting provider: {0}. Valid options are: zoom, teams, meet")]
+    InvalidMeetingProvider(String),
+
+    #[error("Invalid language model provider: {0} (expected grok or deepseek)")]
+    InvalidProvider(String),
+
+    #[error("Invalid backend: {0}. Valid options are: {1}")]
+    InvalidBackend(String, &'static str),
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
+    /// Profile used when none is selected by `--profile` or a `.ducktape-profile` file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_profile: Option<String>,
     #[serde(default)]
     pub calendar: CalendarConfig,
     #[serde(default)]
@@ -13,6 +41,66 @@ pub struct Config {
     pub notes: NotesConfig,
     #[serde(default)]
     pub language_model: LanguageModelConfig,
+    #[serde(default)]
+    pub scripting: ScriptingConfig,
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    #[serde(default)]
+    pub nlp: NlpConfig,
+    #[serde(default)]
+    pub usage: UsageConfig,
+    #[serde(default)]
+    pub backend: BackendConfig,
+    #[serde(default)]
+    pub webhooks: WebhooksConfig,
+    #[serde(default)]
+    pub api: ApiConfig,
+    #[serde(default)]
+    pub audit: AuditConfig,
+    #[serde(default)]
+    pub focus: FocusConfig,
+    #[serde(default)]
+    pub event_search: EventSearchConfig,
+    #[serde(default)]
+    pub ui: UiConfig,
+    #[serde(default)]
+    pub holidays: HolidaysConfig,
+    #[serde(default)]
+    pub smtp: SmtpConfig,
+    #[serde(default)]
+    pub sync: SyncConfig,
+    /// Locale of date input and messages, e.g. "de-DE"; en-US when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// iCalendar feeds served by the API server at /calendar.ics
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub feeds: Vec<FeedConfig>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+}
+
+/// Settings a profile overrides; unset fields fall back to the global configuration
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ProfileConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_calendar: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_list: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes_folder: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<LLMProvider>,
+}
+
+/// Where the profile in effect was selected
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProfileSource {
+    /// `--profile` or DUCKTAPE_PROFILE
+    Environment,
+    /// A `.ducktape-profile` file in this directory or a parent
+    Directory(PathBuf),
+    /// `active_profile` in config.toml
+    Config,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -20,12 +108,154 @@ pub struct CalendarConfig {
     pub default_calendar: Option<String>,
     pub default_reminder_minutes: Option<i32>,
     pub default_duration_minutes: Option<i32>,
+    #[serde(default)]
+    pub meeting_provider: MeetingService,
+    /// Short names for calendars, e.g. `work = "jane.doe@company.com"`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, String>,
+    /// Email invitations for every event with attendees, even where the calendar sends them
+    #[serde(default)]
+    pub invite_emails: bool,
+    /// How invitations are emailed; SMTP when `[smtp]` has a host, otherwise Mail.app
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invite_via: Option<InviteSender>,
+    /// Descriptions of events created without one, by calendar or alias; `*` for any other
+    ///
+    /// They may use variables such as `{{date}}` and `{{zoom_url}}`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub description_templates: BTreeMap<String, String>,
+}
+
+impl CalendarConfig {
+    /// The calendar an alias stands for, or `name` itself when it is not an alias
+    pub fn resolve_calendar(&self, name: &str) -> String {
+        self.aliases
+            .iter()
+            .find(|(alias, _)| alias.eq_ignore_ascii_case(name.trim()))
+            .map_or_else(|| name.to_string(), |(_, calendar)| calendar.clone())
+    }
+
+    /// The description template for events in `calendar`, else the one for every calendar
+    ///
+    /// Templates are looked up by calendar name, by alias, and for "Work (iCloud)" by "Work".
+    pub fn description_template(&self, calendar: &str) -> Option<&str> {
+        let base = crate::calendar::split_account(calendar).0;
+        let matches = |key: &str| {
+            let target = self.resolve_calendar(key);
+            [calendar, base].iter().any(|name| {
+                key.eq_ignore_ascii_case(name.trim()) || target.eq_ignore_ascii_case(name.trim())
+            })
+        };
+        self.description_templates
+            .iter()
+            .find(|(key, _)| matches(key))
+            .or_else(|| self.description_templates.get_key_value("*"))
+            .map(|(_, template)| template.as_str())
+    }
+
+    /// Aliases of a calendar
+    pub fn aliases_of(&self, calendar: &str) -> Vec<&str> {
+        self.aliases
+            .iter()
+            .filter(|(_, target)| target.eq_ignore_ascii_case(calendar))
+            .map(|(alias, _)| alias.as_str())
+            .collect()
+    }
+}
+
+/// Online meeting service used for `--meeting` and meeting keywords
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MeetingService {
+    #[default]
+    Zoom,
+    Teams,
+    Meet,
+}
+
+impl MeetingService {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "zoom" => Ok(MeetingService::Zoom),
+            "teams" | "msteams" | "microsoft-teams" => Ok(MeetingService::Teams),
+            "meet" | "google-meet" | "gmeet" => Ok(MeetingService::Meet),
+            _ => Err(ConfigError::InvalidMeetingProvider(s.to_string()).into()),
+        }
+    }
+
+    /// Name shown in event descriptions
+    pub fn label(&self) -> &'static str {
+        match self {
+            MeetingService::Zoom => "Zoom",
+            MeetingService::Teams => "Microsoft Teams",
+            MeetingService::Meet => "Google Meet",
+        }
+    }
+
+    /// Name used in config files and on the command line
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MeetingService::Zoom => "zoom",
+            MeetingService::Teams => "teams",
+            MeetingService::Meet => "meet",
+        }
+    }
+}
+
+/// How invitation emails are sent
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum InviteSender {
+    Mail,
+    Smtp,
+}
+
+impl InviteSender {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "mail" | "mail.app" => Ok(InviteSender::Mail),
+            "smtp" => Ok(InviteSender::Smtp),
+            _ => Err(ConfigError::Invalid(format!(
+                "invalid invitation sender '{}' (expected mail or smtp)",
+                s
+            ))
+            .into()),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InviteSender::Mail => "mail",
+            InviteSender::Smtp => "smtp",
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct TodoConfig {
     pub default_list: Option<String>,
     pub default_reminder: bool,
+    #[serde(default)]
+    pub board: BoardConfig,
+}
+
+/// Reminder lists backing the columns of `todo board`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct BoardConfig {
+    pub backlog: String,
+    pub doing: String,
+    pub done: String,
+}
+
+impl Default for BoardConfig {
+    fn default() -> Self {
+        Self {
+            backlog: "Backlog".to_string(),
+            doing: "Doing".to_string(),
+            done: "Done".to_string(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -33,6 +263,537 @@ pub struct NotesConfig {
     pub default_folder: Option<String>,
 }
 
+/// Where calendar events are stored
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CalendarBackendKind {
+    /// Calendar.app (macOS only)
+    Apple,
+    /// .ics files in the data directory
+    Ics,
+    /// A CalDAV server
+    CalDav,
+}
+
+/// Where reminders are stored
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TodoBackendKind {
+    /// Reminders.app (macOS only)
+    Apple,
+    /// One text file per list in the data directory
+    File,
+}
+
+/// Where notes are stored
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NotesBackendKind {
+    /// Notes.app (macOS only)
+    Apple,
+    /// Plain-text files in the data directory
+    Text,
+}
+
+impl CalendarBackendKind {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "apple" => Ok(CalendarBackendKind::Apple),
+            "ics" => Ok(CalendarBackendKind::Ics),
+            "caldav" => Ok(CalendarBackendKind::CalDav),
+            other => {
+                Err(ConfigError::InvalidBackend(other.to_string(), "apple, ics, caldav").into())
+            }
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CalendarBackendKind::Apple => "apple",
+            CalendarBackendKind::Ics => "ics",
+            CalendarBackendKind::CalDav => "caldav",
+        }
+    }
+}
+
+impl TodoBackendKind {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "apple" => Ok(TodoBackendKind::Apple),
+            "file" => Ok(TodoBackendKind::File),
+            other => Err(ConfigError::InvalidBackend(other.to_string(), "apple, file").into()),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TodoBackendKind::Apple => "apple",
+            TodoBackendKind::File => "file",
+        }
+    }
+}
+
+impl NotesBackendKind {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "apple" => Ok(NotesBackendKind::Apple),
+            "text" => Ok(NotesBackendKind::Text),
+            other => Err(ConfigError::InvalidBackend(other.to_string(), "apple, text").into()),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotesBackendKind::Apple => "apple",
+            NotesBackendKind::Text => "text",
+        }
+    }
+}
+
+/// CalDAV server used by the `caldav` calendar backend
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct CalDavConfig {
+    /// Calendar home URL, e.g. https://caldav.example.com/calendars/jane/
+    pub url: Option<String>,
+    pub username: Option<String>,
+}
+
+/// Storage used per domain; unset backends are the Apple apps on macOS and local files elsewhere
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct BackendConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calendar: Option<CalendarBackendKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub todo: Option<TodoBackendKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<NotesBackendKind>,
+    /// Directory of the local backends, ~/.ducktape when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_dir: Option<String>,
+    pub caldav: CalDavConfig,
+}
+
+impl BackendConfig {
+    pub fn calendar_kind(&self) -> CalendarBackendKind {
+        self.calendar.unwrap_or(if cfg!(target_os = "macos") {
+            CalendarBackendKind::Apple
+        } else {
+            CalendarBackendKind::Ics
+        })
+    }
+
+    pub fn todo_kind(&self) -> TodoBackendKind {
+        self.todo.unwrap_or(if cfg!(target_os = "macos") {
+            TodoBackendKind::Apple
+        } else {
+            TodoBackendKind::File
+        })
+    }
+
+    pub fn notes_kind(&self) -> NotesBackendKind {
+        self.notes.unwrap_or(if cfg!(target_os = "macos") {
+            NotesBackendKind::Apple
+        } else {
+            NotesBackendKind::Text
+        })
+    }
+
+    /// Directory of the local backends
+    pub fn data_dir(&self) -> Result<PathBuf> {
+        match self.data_dir.as_deref().map(str::trim).filter(|d| !d.is_empty()) {
+            Some(dir) => Ok(PathBuf::from(dir)),
+            None => Ok(dirs::home_dir()
+                .ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?
+                .join(".ducktape")),
+        }
+    }
+}
+
+/// Interpreter used to run AppleScript, for Macs where /usr/bin/osascript is restricted
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ScriptingConfig {
+    /// Path to osascript or an approved wrapper; `osascript` from PATH when unset
+    pub osascript_path: Option<String>,
+    /// Extra arguments passed before the script
+    pub osascript_args: Vec<String>,
+    /// Scripts allowed to run at the same time
+    pub max_concurrent: usize,
+}
+
+impl Default for ScriptingConfig {
+    fn default() -> Self {
+        Self {
+            osascript_path: None,
+            osascript_args: Vec::new(),
+            max_concurrent: crate::script_runner::DEFAULT_MAX_CONCURRENT,
+        }
+    }
+}
+
+/// How often the same reminder or event may be surfaced by notifications and digests
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// Minutes before the same item can be surfaced again
+    pub cooldown_minutes: u32,
+    /// Times the same item can be surfaced per day; 0 means no limit
+    pub max_per_item_per_day: u32,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self { cooldown_minutes: 60, max_per_item_per_day: 3 }
+    }
+}
+
+/// URLs told about events, reminders and notes being created, updated or deleted
+///
+/// The signing secret is a credential (`ducktape keys set webhook`), not part of this file.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct WebhooksConfig {
+    /// URLs each change is POSTed to
+    pub urls: Vec<String>,
+    /// Changes sent, e.g. `["event.created", "note.*"]`; empty sends all of them
+    pub events: Vec<String>,
+    /// Attempts per URL before a delivery is given up
+    pub max_attempts: u32,
+    /// Seconds to wait for a URL to answer
+    pub timeout_secs: u64,
+}
+
+impl Default for WebhooksConfig {
+    fn default() -> Self {
+        Self { urls: Vec::new(), events: Vec::new(), max_attempts: 3, timeout_secs: 10 }
+    }
+}
+
+/// Limits the API server puts on its clients, read when it starts
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ApiConfig {
+    /// Largest request body accepted, in bytes
+    pub max_body_bytes: usize,
+    /// Whether client addresses are taken from the first `X-Forwarded-For` entry, behind a proxy
+    pub trust_proxy: bool,
+    /// Web pages allowed to make changes, e.g. `http://localhost:5173`; none by default
+    pub allowed_origins: Vec<String>,
+    pub rate_limit: RateLimitConfig,
+    pub websocket: WebSocketLimitsConfig,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            max_body_bytes: 1024 * 1024,
+            trust_proxy: false,
+            allowed_origins: Vec::new(),
+            rate_limit: RateLimitConfig::default(),
+            websocket: WebSocketLimitsConfig::default(),
+        }
+    }
+}
+
+/// Requests allowed per client address and per API key
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    /// Requests per minute from one address
+    pub per_ip_per_minute: u32,
+    /// Requests per minute with one API key, from any address
+    pub per_key_per_minute: u32,
+    /// Requests allowed at once before the per-minute rate applies
+    pub burst: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self { enabled: true, per_ip_per_minute: 120, per_key_per_minute: 600, burst: 20 }
+    }
+}
+
+/// Limits on each WebSocket connection
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct WebSocketLimitsConfig {
+    /// Largest message accepted, in bytes
+    pub max_message_bytes: usize,
+    /// Messages per minute on one connection
+    pub messages_per_minute: u32,
+}
+
+impl Default for WebSocketLimitsConfig {
+    fn default() -> Self {
+        Self { max_message_bytes: 64 * 1024, messages_per_minute: 100 }
+    }
+}
+
+/// The log of executed commands, see `ducktape audit show`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct AuditConfig {
+    pub enabled: bool,
+    /// Size at which the log is rotated, in bytes
+    pub max_bytes: u64,
+    /// Rotated logs kept
+    pub keep_files: usize,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self { enabled: true, max_bytes: 1024 * 1024, keep_files: 5 }
+    }
+}
+
+/// Focus sessions, see `ducktape focus start`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct FocusConfig {
+    /// Calendar for focus blocks; `None` uses calendar.default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calendar: Option<String>,
+    /// Shortcut run by `--dnd` when a session starts, e.g. one that turns on Do Not Disturb
+    pub dnd_on_shortcut: String,
+    /// Shortcut run by `--dnd` when a session ends
+    pub dnd_off_shortcut: String,
+}
+
+impl Default for FocusConfig {
+    fn default() -> Self {
+        Self {
+            calendar: None,
+            dnd_on_shortcut: "Focus On".to_string(),
+            dnd_off_shortcut: "Focus Off".to_string(),
+        }
+    }
+}
+
+/// How DuckTape talks to the person at the terminal
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct UiConfig {
+    /// Ask before deleting events, reminders, notes and groups, and before bulk changes
+    pub confirm: bool,
+    /// Colors of agenda, list and search output
+    pub theme: crate::theme::Theme,
+    /// How much of the parser trace terminal mode shows after natural language input
+    pub trace: crate::parser::trace::TraceLevel,
+}
+
+/// How the connection to the SMTP server is secured
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// Upgrade a plain connection, port 587 by default
+    #[default]
+    StartTls,
+    /// TLS from the start, port 465 by default
+    Tls,
+    /// No encryption, port 25 by default; only for a relay on this machine or network
+    None,
+}
+
+impl SmtpSecurity {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "starttls" => Ok(SmtpSecurity::StartTls),
+            "tls" | "ssl" => Ok(SmtpSecurity::Tls),
+            "none" => Ok(SmtpSecurity::None),
+            _ => Err(ConfigError::Invalid(format!(
+                "invalid SMTP security '{}' (expected starttls, tls or none)",
+                s
+            ))
+            .into()),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SmtpSecurity::StartTls => "starttls",
+            SmtpSecurity::Tls => "tls",
+            SmtpSecurity::None => "none",
+        }
+    }
+}
+
+/// Account for emailing invitations; the password is the SMTP_PASSWORD key
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct SmtpConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    /// Port of the server; 587, 465 or 25 depending on `security` when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Sender and organizer of invitations, e.g. "Jane Doe <jane@example.com>"; the username when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    pub security: SmtpSecurity,
+}
+
+/// Whose version wins when an event changed both in DuckTape and in Calendar.app
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncConflicts {
+    /// Calendar.app's version replaces DuckTape's
+    #[default]
+    Calendar,
+    /// DuckTape's version is written to Calendar.app
+    Local,
+    /// Neither is changed; the conflict is reported
+    Skip,
+}
+
+impl SyncConflicts {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "calendar" | "remote" => Ok(SyncConflicts::Calendar),
+            "local" | "ducktape" => Ok(SyncConflicts::Local),
+            "skip" | "none" => Ok(SyncConflicts::Skip),
+            _ => Err(ConfigError::Invalid(format!(
+                "invalid sync conflict rule '{}' (expected calendar, local or skip)",
+                s
+            ))
+            .into()),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncConflicts::Calendar => "calendar",
+            SyncConflicts::Local => "local",
+            SyncConflicts::Skip => "skip",
+        }
+    }
+}
+
+/// `ducktape sync` between the tracked events and Calendar.app
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct SyncConfig {
+    pub conflicts: SyncConflicts,
+}
+
+/// Public holidays to warn about
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct HolidaysConfig {
+    /// Country whose public holidays apply, e.g. "NZ"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            confirm: true,
+            theme: crate::theme::Theme::default(),
+            trace: crate::parser::trace::TraceLevel::default(),
+        }
+    }
+}
+
+/// Web searches for events, see `crate::event_search`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct EventSearchConfig {
+    /// Providers asked, e.g. `["grok", "serpapi", "brave", "bing"]`; all are asked at once
+    pub providers: Vec<String>,
+    /// Most results returned after merging
+    pub max_results: usize,
+}
+
+impl Default for EventSearchConfig {
+    fn default() -> Self {
+        Self { providers: vec!["grok".to_string()], max_results: 10 }
+    }
+}
+
+/// A read-only iCalendar feed, served at /calendar.ics?token=<token>
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct FeedConfig {
+    pub name: String,
+    /// Secret that gives access to the feed
+    pub token: String,
+    /// Calendars included; empty includes every calendar
+    pub calendars: Vec<String>,
+}
+
+/// Parsers tried in order for natural language input
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct NlpConfig {
+    /// Parser names, e.g. `["grok", "deepseek", "terminal"]`; empty uses language_model.provider
+    pub providers: Vec<String>,
+    /// Seconds to wait for a provider before moving on to the next one
+    pub timeout_secs: u64,
+    /// Per-provider overrides of `timeout_secs`, e.g. `{ grok = 10 }`
+    pub timeouts: BTreeMap<String, u64>,
+    /// Command enhancement passes to skip, e.g. `["invitees"]`
+    pub disabled_enhancements: Vec<String>,
+    /// Hours a generated command is reused for the same input; 0 disables the disk cache
+    pub cache_ttl_hours: u64,
+    /// Ask when the input leaves the day or a contact open instead of running the guess
+    pub clarify: bool,
+    /// Minutes the last created or changed item can be referred to as "it" or "that meeting";
+    /// 0 turns follow-ups off
+    pub context_minutes: u64,
+    /// Run "create a meeting at 10 and remind me to prepare slides" as two commands
+    pub split_requests: bool,
+    /// Instructions appended to the system prompt, before those in ~/.ducktape/prompt.md
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extra_instructions: Option<String>,
+}
+
+impl Default for NlpConfig {
+    fn default() -> Self {
+        Self {
+            providers: Vec::new(),
+            timeout_secs: 20,
+            timeouts: BTreeMap::new(),
+            disabled_enhancements: Vec::new(),
+            cache_ttl_hours: 24,
+            clarify: true,
+            context_minutes: 10,
+            split_requests: true,
+            extra_instructions: None,
+        }
+    }
+}
+
+impl NlpConfig {
+    /// Timeout for the named provider
+    pub fn timeout_for(&self, provider: &str) -> u64 {
+        self.timeouts.get(provider).copied().unwrap_or(self.timeout_secs)
+    }
+}
+
+/// Spending limit for language model calls, see `ducktape usage show`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct UsageConfig {
+    /// Estimated USD that may be spent per calendar month; unset means no budget
+    pub monthly_budget_usd: Option<f64>,
+    /// Refuse language model calls once the budget is spent instead of only warning
+    pub block_over_budget: bool,
+    /// Prices overriding the built-in ones, keyed by model name
+    pub prices: BTreeMap<String, ModelPrice>,
+}
+
+/// USD per million tokens
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct ModelPrice {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
 #[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum LLMProvider {
@@ -41,6 +802,23 @@ pub enum LLMProvider {
     DeepSeek,
 }
 
+impl LLMProvider {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "grok" => Ok(LLMProvider::Grok),
+            "deepseek" => Ok(LLMProvider::DeepSeek),
+            other => Err(ConfigError::InvalidProvider(other.to_string()).into()),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LLMProvider::Grok => "grok",
+            LLMProvider::DeepSeek => "deepseek",
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct LanguageModelConfig {
     pub provider: Option<LLMProvider>,
@@ -49,52 +827,174 @@ pub struct LanguageModelConfig {
 impl Default for Config {
     fn default() -> Self {
         Self {
+            active_profile: None,
             calendar: CalendarConfig {
                 default_calendar: Some("Calendar".to_string()),
                 default_reminder_minutes: Some(15),
                 default_duration_minutes: Some(60),
+                meeting_provider: MeetingService::default(),
+                aliases: BTreeMap::new(),
+                invite_emails: false,
+                invite_via: None,
+                description_templates: BTreeMap::new(),
             },
             todo: TodoConfig {
                 default_list: Some("Reminders".to_string()),
                 default_reminder: true,
+                board: BoardConfig::default(),
             },
             notes: NotesConfig { default_folder: None },
             language_model: LanguageModelConfig::default(),
+            scripting: ScriptingConfig::default(),
+            notifications: NotificationsConfig::default(),
+            nlp: NlpConfig::default(),
+            usage: UsageConfig::default(),
+            backend: BackendConfig::default(),
+            webhooks: WebhooksConfig::default(),
+            api: ApiConfig::default(),
+            audit: AuditConfig::default(),
+            focus: FocusConfig::default(),
+            event_search: EventSearchConfig::default(),
+            ui: UiConfig::default(),
+            holidays: HolidaysConfig::default(),
+            smtp: SmtpConfig::default(),
+            sync: SyncConfig::default(),
+            locale: None,
+            feeds: Vec::new(),
+            profiles: BTreeMap::new(),
         }
     }
 }
 
+/// The profile named in the nearest `.ducktape-profile` at or above `dir`
+pub fn find_profile_file(dir: &Path) -> Option<(String, PathBuf)> {
+    dir.ancestors().find_map(|dir| {
+        let path = dir.join(PROFILE_FILE);
+        let name = fs::read_to_string(&path).ok()?;
+        let name = name.lines().next()?.trim().to_string();
+        (!name.is_empty()).then_some((name, path))
+    })
+}
+
 impl Config {
+    /// Parser names to try in order for natural language input
+    ///
+    /// `nlp.providers` when set, otherwise the single `language_model.provider`.
+    pub fn parser_chain(&self) -> Vec<String> {
+        if !self.nlp.providers.is_empty() {
+            return self
+                .nlp
+                .providers
+                .iter()
+                .map(|p| p.trim().to_lowercase())
+                .filter(|p| !p.is_empty())
+                .collect();
+        }
+        match self.language_model.provider {
+            Some(LLMProvider::Grok) => vec!["grok".to_string()],
+            Some(LLMProvider::DeepSeek) => vec!["deepseek".to_string()],
+            None => Vec::new(),
+        }
+    }
+
+    /// Whether input is translated by a language model rather than parsed as a command
+    pub fn uses_language_model(&self) -> bool {
+        self.parser_chain().iter().any(|p| p != "terminal" && p != "command")
+    }
+
+    /// Profile in effect: `--profile`/DUCKTAPE_PROFILE, then `.ducktape-profile`, then
+    /// `active_profile`
+    pub fn selected_profile(&self) -> Option<(String, ProfileSource)> {
+        if let Some(name) = std::env::var(PROFILE_ENV).ok().filter(|n| !n.trim().is_empty()) {
+            return Some((name.trim().to_string(), ProfileSource::Environment));
+        }
+        if let Some((name, path)) =
+            std::env::current_dir().ok().and_then(|dir| find_profile_file(&dir))
+        {
+            return Some((name, ProfileSource::Directory(path)));
+        }
+        self.active_profile.clone().map(|name| (name, ProfileSource::Config))
+    }
+
+    /// Override the global settings with a profile's
+    pub fn apply_profile(&mut self, profile: &ProfileConfig) {
+        if let Some(calendar) = &profile.default_calendar {
+            self.calendar.default_calendar = Some(calendar.clone());
+        }
+        if let Some(list) = &profile.default_list {
+            self.todo.default_list = Some(list.clone());
+        }
+        if let Some(folder) = &profile.notes_folder {
+            self.notes.default_folder = Some(folder.clone());
+        }
+        if let Some(provider) = &profile.provider {
+            self.language_model.provider = Some(provider.clone());
+            // The profile's provider replaces a global fallback chain
+            self.nlp.providers.clear();
+        }
+    }
+
+    /// Apply the named profile; an unknown name is an error unless it came from config.toml
+    pub fn with_profile(mut self, name: &str, source: &ProfileSource) -> Result<Self> {
+        match self.profiles.get(name).cloned() {
+            Some(profile) => {
+                tracing::debug!("Using profile '{}' ({:?})", name, source);
+                self.apply_profile(&profile);
+            }
+            None if *source == ProfileSource::Config => {
+                tracing::warn!("Active profile '{}' does not exist, using global settings", name);
+            }
+            None => {
+                return Err(ConfigError::UnknownProfile(name.to_string()).into());
+            }
+        }
+        Ok(self)
+    }
+
+    /// Configuration with the selected profile applied
+    ///
+    /// Use `load_global` when the configuration is going to be changed and saved, so that
+    /// profile values are not written into the global settings.
     pub fn load() -> Result<Self> {
+        let config = Self::load_global()?;
+        match config.selected_profile() {
+            Some((name, source)) => config.with_profile(&name, &source),
+            None => Ok(config),
+        }
+    }
+
+    /// Configuration as stored in config.toml, without a profile applied
+    pub fn load_global() -> Result<Self> {
         let config_path = get_config_path()?;
 
-        log::info!("Using configuration file at: {:?}", config_path);
+        tracing::info!("Using configuration file at: {:?}", config_path);
 
         // If config doesn't exist, create default
         if !config_path.exists() {
             let default_config = Config::default();
             default_config.save()?;
-            log::info!("Default config created at: {:?}", config_path);
+            tracing::info!("Default config created at: {:?}", config_path);
             return Ok(default_config);
         }
 
         // Read and parse config file
         let content = fs::read_to_string(&config_path).context("Failed to read config file")?;
-        let config: Config = toml::from_str(&content).context("Failed to parse config file")?;
+        let config: Config =
+            toml::from_str(&content).map_err(|e| ConfigError::Invalid(e.to_string()))?;
 
         // Handle missing provider field explicitly
         if config.language_model.provider.is_none() {
-            log::info!("Defaulting to Terminal Mode as no provider is set");
+            tracing::info!("Defaulting to Terminal Mode as no provider is set");
         }
 
         // Debug log the loaded configuration
-        log::debug!("Loaded configuration: {:?}", config);
+        tracing::debug!("Loaded configuration: {:?}", config);
 
         // Check if the provider field is set to a valid value
         if let Some(provider) = &config.language_model.provider {
             match provider {
                 LLMProvider::Grok | LLMProvider::DeepSeek => {
-                    log::info!("Natural Language Mode detected: provider is {:?}", provider);
+                    tracing::info!("Natural Language Mode detected: provider is {:?}", provider);
                 }
             }
         }
@@ -110,15 +1010,17 @@ impl Config {
             fs::create_dir_all(parent)?;
         }
 
-        // Serialize and save config
+        // Serialize and save config, replacing the file at once so that it is never read half-written
         let content = toml::to_string_pretty(self)?;
-        fs::write(&config_path, content).context("Failed to write config file")?;
+        let temp_path = config_path.with_extension("toml.tmp");
+        fs::write(&temp_path, content).context("Failed to write config file")?;
+        fs::rename(&temp_path, &config_path).context("Failed to write config file")?;
 
         Ok(())
     }
 }
 
-fn get_config_path() -> Result<PathBuf> {
+pub(crate) fn get_config_path() -> Result<PathBuf> {
     Ok(std::env::current_dir()?.join("config.toml"))
 }
 
@@ -135,6 +1037,21 @@ mod tests {
         assert_eq!(config.calendar.default_reminder_minutes, Some(15));
         assert_eq!(config.todo.default_list, Some("Reminders".to_string()));
         assert!(matches!(config.language_model.provider, None));
+        assert!(!config.uses_language_model());
+    }
+
+    #[test]
+    fn test_parser_chain() {
+        let mut config = Config::default();
+        config.language_model.provider = Some(LLMProvider::DeepSeek);
+        assert_eq!(config.parser_chain(), vec!["deepseek"]);
+
+        config.nlp.providers = vec!["Grok".to_string(), " terminal ".to_string()];
+        assert_eq!(config.parser_chain(), vec!["grok", "terminal"]);
+        assert!(config.uses_language_model());
+
+        config.nlp.providers = vec!["terminal".to_string()];
+        assert!(!config.uses_language_model());
     }
 
     #[test]
@@ -145,17 +1062,100 @@ mod tests {
 
         // Create the test config
         let test_config = Config {
+            active_profile: Some("work".to_string()),
             calendar: CalendarConfig {
                 default_calendar: Some("TestCalendar".to_string()),
                 default_reminder_minutes: Some(30),
                 default_duration_minutes: Some(45),
+                meeting_provider: MeetingService::Teams,
+                aliases: BTreeMap::from([("work".to_string(), "jane.doe@company.com".to_string())]),
+                invite_emails: false,
+                invite_via: None,
+                description_templates: BTreeMap::new(),
             },
             todo: TodoConfig {
                 default_list: Some("TestList".to_string()),
                 default_reminder: false,
+                board: BoardConfig::default(),
             },
             notes: NotesConfig { default_folder: Some("TestFolder".to_string()) },
             language_model: LanguageModelConfig { provider: Some(LLMProvider::Grok) },
+            scripting: ScriptingConfig {
+                osascript_path: Some("/usr/local/bin/osascript-wrapper".to_string()),
+                osascript_args: vec!["--quiet".to_string()],
+                max_concurrent: 2,
+            },
+            notifications: NotificationsConfig { cooldown_minutes: 30, max_per_item_per_day: 1 },
+            nlp: NlpConfig {
+                providers: vec!["grok".to_string(), "terminal".to_string()],
+                timeout_secs: 15,
+                timeouts: BTreeMap::from([("grok".to_string(), 5)]),
+                disabled_enhancements: vec!["invitees".to_string()],
+                cache_ttl_hours: 6,
+                clarify: false,
+                context_minutes: 30,
+                split_requests: false,
+                extra_instructions: Some("Meetings default to 25 minutes".to_string()),
+            },
+            usage: UsageConfig {
+                monthly_budget_usd: Some(5.0),
+                block_over_budget: true,
+                prices: BTreeMap::from([(
+                    "deepseek-chat".to_string(),
+                    ModelPrice { input_per_million: 0.5, output_per_million: 1.5 },
+                )]),
+            },
+            backend: BackendConfig {
+                calendar: Some(CalendarBackendKind::CalDav),
+                notes: Some(NotesBackendKind::Text),
+                caldav: CalDavConfig {
+                    url: Some("https://caldav.example.com/calendars/jane/".to_string()),
+                    username: Some("jane".to_string()),
+                },
+                ..BackendConfig::default()
+            },
+            webhooks: WebhooksConfig {
+                urls: vec!["https://hooks.example.com/ducktape".to_string()],
+                events: vec!["event.*".to_string()],
+                ..WebhooksConfig::default()
+            },
+            api: ApiConfig {
+                trust_proxy: true,
+                rate_limit: RateLimitConfig { per_ip_per_minute: 30, ..RateLimitConfig::default() },
+                ..ApiConfig::default()
+            },
+            audit: AuditConfig { keep_files: 2, ..AuditConfig::default() },
+            focus: FocusConfig { calendar: Some("Focus".to_string()), ..FocusConfig::default() },
+            event_search: EventSearchConfig {
+                providers: vec!["serpapi".to_string(), "brave".to_string()],
+                max_results: 5,
+            },
+            ui: UiConfig {
+                confirm: false,
+                theme: crate::theme::Theme::Light,
+                trace: crate::parser::trace::TraceLevel::Full,
+            },
+            holidays: HolidaysConfig { country: Some("NZ".to_string()) },
+            smtp: SmtpConfig {
+                host: Some("smtp.example.com".to_string()),
+                username: Some("jane@example.com".to_string()),
+                ..SmtpConfig::default()
+            },
+            sync: SyncConfig { conflicts: SyncConflicts::Skip },
+            locale: Some("de-DE".to_string()),
+            feeds: vec![FeedConfig {
+                name: "Family".to_string(),
+                token: "0123456789abcdef".to_string(),
+                calendars: vec!["Home".to_string()],
+            }],
+            profiles: BTreeMap::from([(
+                "work".to_string(),
+                ProfileConfig {
+                    default_calendar: Some("Work".to_string()),
+                    provider: Some(LLMProvider::DeepSeek),
+                    ..ProfileConfig::default()
+                },
+            )]),
         };
 
         // Serialize and write directly to file
@@ -176,13 +1176,123 @@ mod tests {
             loaded_config.calendar.default_duration_minutes,
             test_config.calendar.default_duration_minutes
         );
+        assert_eq!(loaded_config.calendar.meeting_provider, MeetingService::Teams);
+        assert_eq!(loaded_config.calendar.aliases, test_config.calendar.aliases);
         assert_eq!(loaded_config.todo.default_list, test_config.todo.default_list);
         assert_eq!(loaded_config.todo.default_reminder, test_config.todo.default_reminder);
         assert_eq!(loaded_config.notes.default_folder, test_config.notes.default_folder);
 
         // Test that different LLM providers are correctly serialized/deserialized
         assert!(matches!(loaded_config.language_model.provider, Some(LLMProvider::Grok)));
+        assert_eq!(loaded_config.scripting, test_config.scripting);
+        assert_eq!(loaded_config.notifications, test_config.notifications);
+        assert_eq!(loaded_config.nlp, test_config.nlp);
+        assert_eq!(loaded_config.nlp.timeout_for("grok"), 5);
+        assert_eq!(loaded_config.nlp.timeout_for("terminal"), 15);
+        assert_eq!(loaded_config.usage, test_config.usage);
+        assert_eq!(loaded_config.backend, test_config.backend);
+        assert_eq!(loaded_config.webhooks, test_config.webhooks);
+        assert_eq!(loaded_config.api, test_config.api);
+        assert_eq!(loaded_config.audit.keep_files, 2);
+        assert_eq!(loaded_config.focus.calendar.as_deref(), Some("Focus"));
+        assert_eq!(loaded_config.focus.dnd_on_shortcut, "Focus On");
+        assert_eq!(loaded_config.feeds, test_config.feeds);
+        assert_eq!(loaded_config.smtp, test_config.smtp);
+        assert_eq!(loaded_config.sync, test_config.sync);
+        assert!(file_content.contains("calendar = \"caldav\""));
+        assert_eq!(loaded_config.active_profile.as_deref(), Some("work"));
+        assert_eq!(loaded_config.profiles, test_config.profiles);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_profile_overrides_global_settings() -> Result<()> {
+        let mut config = Config::default();
+        config.nlp.providers = vec!["grok".to_string(), "terminal".to_string()];
+        config.profiles.insert(
+            "work".to_string(),
+            ProfileConfig {
+                default_calendar: Some("Work".to_string()),
+                notes_folder: Some("Projects".to_string()),
+                provider: Some(LLMProvider::DeepSeek),
+                ..ProfileConfig::default()
+            },
+        );
+
+        let work = config.clone().with_profile("work", &ProfileSource::Environment)?;
+        assert_eq!(work.calendar.default_calendar.as_deref(), Some("Work"));
+        assert_eq!(work.notes.default_folder.as_deref(), Some("Projects"));
+        // Settings the profile leaves out come from the global configuration
+        assert_eq!(work.todo.default_list.as_deref(), Some("Reminders"));
+        assert_eq!(work.parser_chain(), vec!["deepseek"]);
+
+        assert!(config.clone().with_profile("home", &ProfileSource::Environment).is_err());
+        let stale = config.clone().with_profile("home", &ProfileSource::Config)?;
+        assert_eq!(stale.calendar.default_calendar.as_deref(), Some("Calendar"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_calendar_aliases() {
+        let mut calendar = Config::default().calendar;
+        calendar.aliases.insert("work".to_string(), "jane.doe@company.com".to_string());
+        calendar.aliases.insert("job".to_string(), "jane.doe@company.com".to_string());
+
+        assert_eq!(calendar.resolve_calendar("Work"), "jane.doe@company.com");
+        assert_eq!(calendar.resolve_calendar("Home"), "Home");
+        assert_eq!(calendar.aliases_of("Jane.Doe@company.com"), vec!["job", "work"]);
+    }
+
+    #[test]
+    fn test_description_templates() {
+        let mut calendar = Config::default().calendar;
+        assert_eq!(calendar.description_template("Work"), None);
+
+        calendar.aliases.insert("work".to_string(), "jane.doe@company.com".to_string());
+        calendar
+            .description_templates
+            .insert("work".to_string(), "Agenda: {{title}}".to_string());
+        calendar
+            .description_templates
+            .insert("*".to_string(), "Created {{date}}".to_string());
+        assert_eq!(
+            calendar.description_template("jane.doe@company.com"),
+            Some("Agenda: {{title}}")
+        );
+        assert_eq!(calendar.description_template("Work (iCloud)"), Some("Agenda: {{title}}"));
+        assert_eq!(calendar.description_template("Home"), Some("Created {{date}}"));
+    }
+
+    #[test]
+    fn test_find_profile_file() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let project = temp_dir.path().join("project");
+        let nested = project.join("src").join("bin");
+        fs::create_dir_all(&nested)?;
+        assert!(find_profile_file(&nested).is_none());
+
+        fs::write(project.join(PROFILE_FILE), "work\n")?;
+        let (name, path) = find_profile_file(&nested).unwrap();
+        assert_eq!(name, "work");
+        assert_eq!(path, project.join(PROFILE_FILE));
+        Ok(())
+    }
+
+    #[test]
+    fn test_backend_kinds() -> Result<()> {
+        let mut backend = BackendConfig::default();
+        let local = !cfg!(target_os = "macos");
+        assert_eq!(backend.calendar_kind() == CalendarBackendKind::Ics, local);
+        assert_eq!(backend.todo_kind() == TodoBackendKind::File, local);
+        assert_eq!(backend.notes_kind() == NotesBackendKind::Text, local);
+
+        backend.calendar = Some(CalendarBackendKind::from_str("CalDAV")?);
+        assert_eq!(backend.calendar_kind(), CalendarBackendKind::CalDav);
+        assert!(TodoBackendKind::from_str("icloud").is_err());
 
+        backend.data_dir = Some("/tmp/ducktape".to_string());
+        assert_eq!(backend.data_dir()?, PathBuf::from("/tmp/ducktape"));
         Ok(())
     }
 }
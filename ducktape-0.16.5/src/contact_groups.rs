@@ -1,11 +1,34 @@
 use anyhow::{Result, anyhow};
-use log::{debug, info};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
 
-use crate::calendar::{EventConfig, create_event_with_contacts};
+use crate::calendar::{
+    ContactStrategy, EventConfig, create_event_with_contacts, resolve_contact, validate_email,
+};
+use crate::script_runner::run_applescript_async;
+
+/// Name of the contact groups file in the DuckTape directory
+const GROUPS_FILE: &str = "contact_groups.json";
+
+/// Errors for contact group management
+#[derive(Debug, thiserror::Error)]
+pub enum ContactGroupError {
+    #[error("Contact group '{0}' not found")]
+    NotFound(String),
+
+    #[error("Contact group '{0}' already exists")]
+    AlreadyExists(String),
+
+    #[error("Invalid email address: {0}")]
+    InvalidEmail(String),
+
+    #[error("Invalid group name: {0}")]
+    InvalidName(String),
+}
 
 /// Represents a group of contacts
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,54 +64,19 @@ impl ContactGroups {
         self.groups.get(id)
     }
 
-    #[allow(dead_code)]
     /// Remove a contact group by ID
     pub fn remove_group(&mut self, id: &str) -> Option<ContactGroup> {
         self.groups.remove(id)
     }
 
-    /// Load contact groups from file
+    /// Load contact groups from ~/.ducktape
     pub fn load() -> Result<Self> {
-        let config_path = Self::get_config_path()?;
-
-        if !config_path.exists() {
-            info!("Contact groups file doesn't exist, creating a default one");
-            let groups = Self::new();
-            groups.save()?;
-            return Ok(groups);
-        }
-
-        let contents = fs::read_to_string(&config_path)?;
-        let groups: ContactGroups = serde_json::from_str(&contents)
-            .map_err(|e| anyhow!("Failed to parse contact groups: {}", e))?;
-
-        debug!("Loaded {} contact groups", groups.groups.len());
-        Ok(groups)
+        GroupStore::new()?.load()
     }
 
-    /// Save contact groups to file
+    /// Save contact groups to ~/.ducktape
     pub fn save(&self) -> Result<()> {
-        let config_path = Self::get_config_path()?;
-
-        // Ensure directory exists
-        if let Some(parent) = config_path.parent() {
-            if !parent.exists() {
-                fs::create_dir_all(parent)?;
-            }
-        }
-
-        let json = serde_json::to_string_pretty(self)?;
-        let mut file = File::create(&config_path)?;
-        file.write_all(json.as_bytes())?;
-
-        debug!("Saved {} contact groups", self.groups.len());
-        Ok(())
-    }
-
-    /// Get the path to the contact groups config file
-    fn get_config_path() -> Result<std::path::PathBuf> {
-        let home_dir = dirs::home_dir().ok_or_else(|| anyhow!("Failed to get home directory"))?;
-        Ok(home_dir.join(".ducktape").join("contact_groups.json"))
+        GroupStore::new()?.save(self)
     }
 
     #[allow(dead_code)]
@@ -139,40 +127,448 @@ pub async fn create_event_with_group(config: EventConfig, group_id: &str) -> Res
     create_event_with_contacts(config, &contacts).await
 }
 
+/// Validate a group name for use as a storage key
+pub fn validate_group_name(name: &str) -> Result<()> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err(anyhow!(ContactGroupError::InvalidName("name cannot be empty".to_string())));
+    }
+    if trimmed.len() > 100 {
+        return Err(anyhow!(ContactGroupError::InvalidName(format!(
+            "'{}' is longer than 100 characters",
+            trimmed
+        ))));
+    }
+    if trimmed.chars().any(|c| c.is_control()) {
+        return Err(anyhow!(ContactGroupError::InvalidName(format!(
+            "'{}' contains control characters",
+            trimmed
+        ))));
+    }
+    Ok(())
+}
+
+fn validate_member_emails(emails: &[String]) -> Result<()> {
+    if let Some(invalid) = emails.iter().find(|e| !validate_email(e)) {
+        return Err(anyhow!(ContactGroupError::InvalidEmail(invalid.clone())));
+    }
+    Ok(())
+}
+
+/// The file contact groups are kept in
+pub struct GroupStore {
+    path: PathBuf,
+}
+
+impl GroupStore {
+    /// The groups in ~/.ducktape
+    pub fn new() -> Result<Self> {
+        let home_dir = dirs::home_dir().ok_or_else(|| anyhow!("Failed to get home directory"))?;
+        Ok(Self::with_dir(home_dir.join(".ducktape")))
+    }
+
+    /// Keep the groups in `dir` instead of ~/.ducktape
+    pub fn with_dir(dir: impl AsRef<Path>) -> Self {
+        Self { path: dir.as_ref().join(GROUPS_FILE) }
+    }
+
+    /// Load contact groups from file
+    pub fn load(&self) -> Result<ContactGroups> {
+        if !self.path.exists() {
+            info!("Contact groups file doesn't exist, creating a default one");
+            let groups = ContactGroups::new();
+            self.save(&groups)?;
+            return Ok(groups);
+        }
+
+        let contents = fs::read_to_string(&self.path)?;
+        let groups: ContactGroups = serde_json::from_str(&contents)
+            .map_err(|e| anyhow!("Failed to parse contact groups: {}", e))?;
+
+        debug!("Loaded {} contact groups", groups.groups.len());
+        Ok(groups)
+    }
+
+    /// Save contact groups to file
+    pub fn save(&self, groups: &ContactGroups) -> Result<()> {
+        // Ensure directory exists
+        if let Some(parent) = self.path.parent() {
+            if !parent.exists() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+
+        // Write to a temporary file and rename it into place so a crash or a concurrent
+        // reader never sees a partially written file
+        let json = serde_json::to_string_pretty(groups)?;
+        let tmp_path = self.path.with_extension("json.tmp");
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(json.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &self.path)?;
+
+        debug!("Saved {} contact groups", groups.groups.len());
+        Ok(())
+    }
+
+    /// Create a new contact group
+    pub fn create_group(&self, group_name: &str, emails: &[String]) -> Result<()> {
+        validate_group_name(group_name)?;
+        validate_member_emails(emails)?;
+
+        // Load existing groups
+        let mut groups = self.load()?;
+
+        // Create a new group
+        let group = ContactGroup {
+            name: group_name.to_string(),
+            contacts: emails.to_vec(),
+            description: None,
+        };
+
+        // Add the group
+        groups.add_group(group_name.to_string(), group);
+
+        // Save the updated groups
+        self.save(&groups)?;
+
+        info!("Created contact group '{}' with {} members", group_name, emails.len());
+        Ok(())
+    }
+
+    /// Add members to an existing contact group, returning how many were new
+    pub fn add_members(&self, group_name: &str, emails: &[String]) -> Result<usize> {
+        validate_member_emails(emails)?;
+
+        let mut groups = self.load()?;
+        let group = groups
+            .groups
+            .get_mut(group_name)
+            .ok_or_else(|| anyhow!(ContactGroupError::NotFound(group_name.to_string())))?;
+
+        let mut added = 0;
+        for email in emails {
+            if !group.contacts.iter().any(|c| c.eq_ignore_ascii_case(email)) {
+                group.contacts.push(email.clone());
+                added += 1;
+            }
+        }
+
+        self.save(&groups)?;
+        info!("Added {} member(s) to contact group '{}'", added, group_name);
+        Ok(added)
+    }
+
+    /// Remove members from a contact group, returning how many were removed
+    pub fn remove_members(&self, group_name: &str, emails: &[String]) -> Result<usize> {
+        let mut groups = self.load()?;
+        let group = groups
+            .groups
+            .get_mut(group_name)
+            .ok_or_else(|| anyhow!(ContactGroupError::NotFound(group_name.to_string())))?;
+
+        let before = group.contacts.len();
+        group.contacts.retain(|c| !emails.iter().any(|e| e.eq_ignore_ascii_case(c)));
+        let removed = before - group.contacts.len();
+
+        self.save(&groups)?;
+        info!("Removed {} member(s) from contact group '{}'", removed, group_name);
+        Ok(removed)
+    }
+
+    /// Rename a contact group
+    pub fn rename_group(&self, group_name: &str, new_name: &str) -> Result<()> {
+        validate_group_name(new_name)?;
+
+        let mut groups = self.load()?;
+        if groups.groups.contains_key(new_name) {
+            return Err(anyhow!(ContactGroupError::AlreadyExists(new_name.to_string())));
+        }
+        let mut group = groups
+            .remove_group(group_name)
+            .ok_or_else(|| anyhow!(ContactGroupError::NotFound(group_name.to_string())))?;
+        group.name = new_name.to_string();
+        groups.add_group(new_name.to_string(), group);
+
+        self.save(&groups)?;
+        info!("Renamed contact group '{}' to '{}'", group_name, new_name);
+        Ok(())
+    }
+
+    /// Delete a contact group
+    pub fn delete_group(&self, group_name: &str) -> Result<()> {
+        let mut groups = self.load()?;
+        groups
+            .remove_group(group_name)
+            .ok_or_else(|| anyhow!(ContactGroupError::NotFound(group_name.to_string())))?;
+
+        self.save(&groups)?;
+        info!("Deleted contact group '{}'", group_name);
+        Ok(())
+    }
+
+    /// List all available contact groups
+    pub fn list_groups(&self) -> Result<Vec<String>> {
+        let groups = self.load()?;
+
+        let group_names: Vec<String> = groups.groups.keys().cloned().collect();
+        Ok(group_names)
+    }
+
+    /// Get a specific contact group by name
+    pub fn get_group(&self, group_name: &str) -> Result<Option<Vec<String>>> {
+        let groups = self.load()?;
+
+        if let Some(group) = groups.get_group(group_name) {
+            Ok(Some(group.contacts.clone()))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
 /// Create a new contact group
 pub fn create_group(group_name: &str, emails: &[String]) -> Result<()> {
-    // Load existing groups
-    let mut groups = ContactGroups::load()?;
+    GroupStore::new()?.create_group(group_name, emails)
+}
 
-    // Create a new group
-    let group =
-        ContactGroup { name: group_name.to_string(), contacts: emails.to_vec(), description: None };
+/// Add members to an existing contact group, returning how many were new
+pub fn add_members(group_name: &str, emails: &[String]) -> Result<usize> {
+    GroupStore::new()?.add_members(group_name, emails)
+}
 
-    // Add the group
-    groups.add_group(group_name.to_string(), group);
+/// Remove members from a contact group, returning how many were removed
+pub fn remove_members(group_name: &str, emails: &[String]) -> Result<usize> {
+    GroupStore::new()?.remove_members(group_name, emails)
+}
 
-    // Save the updated groups
-    groups.save()?;
+/// Rename a contact group
+pub fn rename_group(group_name: &str, new_name: &str) -> Result<()> {
+    GroupStore::new()?.rename_group(group_name, new_name)
+}
 
-    info!("Created contact group '{}' with {} members", group_name, emails.len());
-    Ok(())
+/// Delete a contact group
+pub fn delete_group(group_name: &str) -> Result<()> {
+    GroupStore::new()?.delete_group(group_name)
 }
 
 /// List all available contact groups
 pub fn list_groups() -> Result<Vec<String>> {
-    let groups = ContactGroups::load()?;
-
-    let group_names: Vec<String> = groups.groups.keys().cloned().collect();
-    Ok(group_names)
+    GroupStore::new()?.list_groups()
 }
 
 /// Get a specific contact group by name
 pub fn get_group(group_name: &str) -> Result<Option<Vec<String>>> {
+    GroupStore::new()?.get_group(group_name)
+}
+
+/// Read contact groups from Contacts.app, mapping each group name to its members' emails
+pub async fn fetch_system_groups() -> Result<HashMap<String, Vec<String>>> {
+    let script = r#"tell application "Contacts"
+        set output to ""
+        try
+            repeat with the_group in groups
+                set the_line to (name of the_group as text) & "|"
+                repeat with the_person in (get every person of the_group)
+                    repeat with the_email in (get every email of the_person)
+                        if value of the_email is not missing value then
+                            set the_line to the_line & (value of the_email as text) & ","
+                        end if
+                    end repeat
+                end repeat
+                set output to output & the_line & linefeed
+            end repeat
+        on error errMsg
+            log "Error reading contact groups: " & errMsg
+        end try
+        return output
+    end tell"#;
+
+    let output = run_applescript_async(script).await?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Failed to read Contacts.app groups: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(parse_system_groups(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse "group|email,email," lines produced by the Contacts.app group script
+pub fn parse_system_groups(output: &str) -> HashMap<String, Vec<String>> {
+    let mut groups = HashMap::new();
+    for line in output.lines() {
+        if let Some((name, emails)) = line.split_once('|') {
+            let name = name.trim();
+            if name.is_empty() {
+                continue;
+            }
+            let mut emails: Vec<String> = emails
+                .split(',')
+                .map(|e| e.trim().to_string())
+                .filter(|e| validate_email(e))
+                .collect();
+            emails.sort();
+            emails.dedup();
+            groups.insert(name.to_string(), emails);
+        }
+    }
+    groups
+}
+
+/// List group names from both DuckTape's storage and Contacts.app, sorted and deduplicated
+pub async fn list_all_groups() -> Result<Vec<String>> {
+    let mut names = list_groups()?;
+    match fetch_system_groups().await {
+        Ok(system_groups) => names.extend(system_groups.into_keys()),
+        Err(e) => debug!("Skipping Contacts.app groups: {}", e),
+    }
+    names.sort_by_key(|n| n.to_lowercase());
+    names.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+    Ok(names)
+}
+
+/// Resolve a group name to email addresses
+///
+/// Members of a DuckTape group with the same name (case-insensitive) are merged with the
+/// members of the Contacts.app group. DuckTape group entries may be email addresses or
+/// contact names; names are looked up in Contacts.app.
+pub async fn resolve_group_emails(group_name: &str) -> Result<Option<Vec<String>>> {
+    let mut found = false;
+    let mut emails = Vec::new();
+
     let groups = ContactGroups::load()?;
+    let local = groups.get_group(group_name).or_else(|| {
+        groups
+            .groups
+            .iter()
+            .find(|(id, g)| {
+                id.eq_ignore_ascii_case(group_name) || g.name.eq_ignore_ascii_case(group_name)
+            })
+            .map(|(_, g)| g)
+    });
+    if let Some(group) = local {
+        found = true;
+        for member in &group.contacts {
+            if validate_email(member) {
+                emails.push(member.clone());
+            } else {
+                // The best match of an ambiguous name is used but not cached as the member
+                emails.extend(resolve_contact(member, ContactStrategy::First).await?);
+            }
+        }
+    }
+
+    match fetch_system_groups().await {
+        Ok(system_groups) => {
+            if let Some((_, members)) = system_groups
+                .into_iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(group_name))
+            {
+                found = true;
+                emails.extend(members);
+            }
+        }
+        Err(e) => debug!("Skipping Contacts.app groups: {}", e),
+    }
+
+    if !found {
+        return Ok(None);
+    }
+
+    emails.sort();
+    emails.dedup();
+    info!("Resolved group '{}' to {} email(s)", group_name, emails.len());
+    Ok(Some(emails))
+}
+
+/// Add the members of groups to explicitly given attendees, each address once
+///
+/// Addresses differing only in case are the same; the explicitly given spelling is kept.
+pub fn merge_attendees(explicit: &[String], members: &[String]) -> Vec<String> {
+    let mut attendees: Vec<String> = explicit.iter().chain(members).cloned().collect();
+    attendees.sort_by_key(|email| email.to_lowercase());
+    attendees.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+    attendees
+}
+
+/// `description` with a line naming the groups that were invited
+pub fn group_note(description: Option<&str>, groups: &[String]) -> String {
+    let label = if groups.len() == 1 { "Invited group" } else { "Invited groups" };
+    let note = format!("{}: {}", label, groups.join(", "));
+    match description.map(str::trim_end).filter(|d| !d.is_empty()) {
+        Some(description) => format!("{}\n\n{}", description, note),
+        None => note,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_group_member_management() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let store = GroupStore::with_dir(temp_dir.path());
+
+        store.create_group("team", &["jane@example.com".to_string()])?;
+        assert_eq!(
+            store.add_members(
+                "team",
+                &["joe@example.com".to_string(), "JANE@example.com".to_string()]
+            )?,
+            1
+        );
+        assert!(store.add_members("team", &["not-an-email".to_string()]).is_err());
+        assert_eq!(store.remove_members("team", &["jane@example.com".to_string()])?, 1);
+
+        store.rename_group("team", "leads")?;
+        assert_eq!(store.get_group("team")?, None);
+        assert_eq!(store.get_group("leads")?, Some(vec!["joe@example.com".to_string()]));
+
+        store.create_group("other", &[])?;
+        assert!(store.rename_group("other", "leads").is_err());
+
+        store.delete_group("leads")?;
+        assert!(store.delete_group("leads").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_system_groups() {
+        let output = "Team|jane@example.com,joe@example.com,jane@example.com,\nEmpty|\n|x@y.com\n";
+        let groups = parse_system_groups(output);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(
+            groups.get("Team"),
+            Some(&vec!["jane@example.com".to_string(), "joe@example.com".to_string()])
+        );
+        assert_eq!(groups.get("Empty"), Some(&Vec::new()));
+    }
+
+    #[test]
+    fn test_merge_attendees() {
+        let explicit = vec!["Jane@Example.com".to_string()];
+        let members = vec!["bob@example.com".to_string(), "jane@example.com".to_string()];
+        assert_eq!(
+            merge_attendees(&explicit, &members),
+            vec!["bob@example.com", "Jane@Example.com"]
+        );
+    }
 
-    if let Some(group) = groups.get_group(group_name) {
-        Ok(Some(group.contacts.clone()))
-    } else {
-        Ok(None)
+    #[test]
+    fn test_group_note() {
+        let groups = vec!["Leadership".to_string()];
+        assert_eq!(group_note(None, &groups), "Invited group: Leadership");
+        assert_eq!(
+            group_note(
+                Some("Quarterly planning\n"),
+                &["Leadership".to_string(), "Ops".to_string()]
+            ),
+            "Quarterly planning\n\nInvited groups: Leadership, Ops"
+        );
     }
 }
@@ -0,0 +1,281 @@
+//! What the last command created or changed, so that the next request can refer to it.
+//
+// After "lunch with Sam tomorrow at noon", requests like "actually make it 3pm" or "add Jane
+// to that meeting" are about the event just created. The command bus remembers the item each
+// command created or changed in ~/.ducktape/context.json, and for `nlp.context_minutes` the
+// language model is told about it. The model answers with `@last` where the command names the
+// item (`ducktape calendar update @last --start-time 15:00`), and the bus replaces `@last`, or
+// a pronoun the model copied from the input such as "it" or "that meeting", with the item's
+// UID or title before the command runs. Cached answers hold `@last` rather than a UID, so they
+// stay right for the next item.
+
+use crate::command_bus::Outcome;
+use crate::command_processor::CommandArgs;
+use crate::commands::usage;
+use crate::config::Config;
+use crate::state::{CalendarItem, LinkedKind, RecentItem, StateManager};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local};
+use tracing::{debug, warn};
+
+/// How the language model refers to the recent item
+pub const LAST: &str = "@last";
+
+/// Words that stand for the recent item where a command names an item
+const PRONOUNS: [&str; 12] = [
+    "it",
+    "that",
+    "this",
+    "last",
+    "that meeting",
+    "this meeting",
+    "the meeting",
+    "that event",
+    "this event",
+    "the event",
+    "that reminder",
+    "the reminder",
+];
+
+/// Whether `id` refers to the recent item instead of naming one
+pub fn is_reference(id: &str) -> bool {
+    let id = id.trim().trim_matches(['"', '\'']).to_lowercase();
+    id == LAST || PRONOUNS.contains(&id.as_str())
+}
+
+fn kind_name(kind: LinkedKind) -> &'static str {
+    match kind {
+        LinkedKind::Event => "event",
+        LinkedKind::Todo => "reminder",
+    }
+}
+
+/// The recent item for an event DuckTape tracks
+pub fn event(item: &CalendarItem) -> RecentItem {
+    let when = match (&item.end_time, item.all_day) {
+        (_, true) => format!("{} all day", item.date),
+        (Some(end), false) => format!("{} {}-{}", item.date, item.time, end),
+        (None, false) => format!("{} {}", item.date, item.time),
+    };
+    RecentItem {
+        kind: LinkedKind::Event,
+        id: item.uid.clone().unwrap_or_else(|| item.title.clone()),
+        title: item.title.clone(),
+        when: Some(when),
+        container: item.calendars.first().cloned(),
+        updated_at: Local::now().to_rfc3339(),
+    }
+}
+
+/// The recent item for what a command did, if it created or changed an item
+fn recent_item(outcome: &Outcome) -> Result<Option<RecentItem>> {
+    Ok(match outcome {
+        Outcome::EventCreated(config) => {
+            crate::calendar::created_event(&crate::state::load_events()?, config).map(event)
+        }
+        Outcome::EventUpdated(item) => Some(event(item)),
+        Outcome::TodoCreated(title) => Some(RecentItem {
+            kind: LinkedKind::Todo,
+            id: title.clone(),
+            title: title.clone(),
+            when: None,
+            container: None,
+            updated_at: Local::now().to_rfc3339(),
+        }),
+        _ => None,
+    })
+}
+
+/// Remember the item a command created or changed; failures are only logged
+pub fn remember_outcome(outcome: &Outcome) {
+    let enabled = Config::load().map(|config| config.nlp.context_minutes > 0).unwrap_or(true);
+    if !enabled {
+        return;
+    }
+    let remembered = recent_item(outcome).and_then(|item| match item {
+        Some(item) => StateManager::new()?.save(&[item]),
+        None => Ok(()),
+    });
+    if let Err(e) = remembered {
+        warn!("Failed to remember the item for follow-ups: {}", e);
+    }
+}
+
+/// Whether `item` changed at most `minutes` before `now`
+fn is_fresh(item: &RecentItem, now: DateTime<Local>, minutes: u64) -> bool {
+    minutes > 0
+        && DateTime::parse_from_rfc3339(&item.updated_at)
+            .is_ok_and(|at| now.signed_duration_since(at) <= Duration::minutes(minutes as i64))
+}
+
+/// The item created or changed last, unless that was longer ago than `nlp.context_minutes`
+pub fn current() -> Option<RecentItem> {
+    let minutes = Config::load().map(|config| config.nlp.context_minutes).unwrap_or_default();
+    let items = match StateManager::new().and_then(|manager| manager.load::<RecentItem>()) {
+        Ok(items) => items,
+        Err(e) => {
+            warn!("Failed to read the recent item: {}", e);
+            return None;
+        }
+    };
+    items.into_iter().last().filter(|item| is_fresh(item, Local::now(), minutes))
+}
+
+/// What the language model is told about the recent item
+pub fn prompt_note(item: &RecentItem) -> String {
+    let mut about = format!("{} \"{}\"", kind_name(item.kind), item.title);
+    if let Some(when) = &item.when {
+        about.push_str(&format!(" on {}", when));
+    }
+    if let Some(container) = &item.container {
+        about.push_str(&format!(" in \"{}\"", container));
+    }
+    let commands = match item.kind {
+        LinkedKind::Event => {
+            r#"ducktape calendar update @last <changes>
+ducktape calendar delete @last
+where <changes> is one or more of --title "<title>", --date <YYYY-MM-DD>, --start-time <HH:MM>,
+--end-time <HH:MM>, --location "<location>", --contacts "<name1>,<name2>", --email "<email>""#
+        }
+        LinkedKind::Todo => "ducktape todo move @last <backlog|doing|done|list>",
+    };
+    format!(
+        r#"The user just created or changed the {about}.
+If the input refers to it ("it", "that meeting", "actually make it 3pm", "add Jane to that"),
+change it with @last instead of creating a new one:
+{commands}"#
+    )
+}
+
+/// The id `reference` stands for, given the recent item
+fn resolve_with(reference: &str, kind: LinkedKind, recent: Option<&RecentItem>) -> Result<String> {
+    match recent {
+        Some(item) if item.kind == kind => {
+            debug!("'{}' refers to {} '{}' ({})", reference, kind_name(kind), item.title, item.id);
+            Ok(item.id.clone())
+        }
+        Some(item) => Err(usage(format!(
+            "'{}' refers to the {} \"{}\", not to a {}",
+            reference,
+            kind_name(item.kind),
+            item.title,
+            kind_name(kind)
+        ))),
+        None => Err(usage(format!(
+            "Nothing was created or changed recently for '{}' to refer to; name the {} instead",
+            reference,
+            kind_name(kind)
+        ))),
+    }
+}
+
+/// `id`, or the id of the recent item when `id` refers to it
+pub fn resolve(id: &str, kind: LinkedKind) -> Result<String> {
+    resolve_id_with(id, kind, current)
+}
+
+pub(crate) fn resolve_id_with(
+    id: &str,
+    kind: LinkedKind,
+    recent: impl FnOnce() -> Option<RecentItem>,
+) -> Result<String> {
+    if !is_reference(id) {
+        return Ok(id.to_string());
+    }
+    resolve_with(id, kind, recent().as_ref())
+}
+
+/// The kind of item the first argument names, for commands that take one
+fn item_argument(args: &CommandArgs) -> Option<LinkedKind> {
+    match (args.command.as_str(), args.args.first()?.as_str()) {
+        ("calendar", "update" | "edit" | "delete" | "remove") => Some(LinkedKind::Event),
+        ("todo", "move" | "delete") => Some(LinkedKind::Todo),
+        _ => None,
+    }
+}
+
+pub(crate) fn resolve_args_with(
+    args: &mut CommandArgs,
+    recent: impl FnOnce() -> Option<RecentItem>,
+) -> Result<()> {
+    let Some(kind) = item_argument(args) else {
+        return Ok(());
+    };
+    if let Some(id) = args.args.get_mut(1).filter(|id| is_reference(id)) {
+        *id = resolve_with(id, kind, recent().as_ref())?;
+    }
+    Ok(())
+}
+
+/// Replace a reference to the recent item in the arguments of a command with its id
+pub fn resolve_args(args: &mut CommandArgs) -> Result<()> {
+    resolve_args_with(args, current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn lunch() -> RecentItem {
+        let item = CalendarItem {
+            title: "Lunch with Sam".to_string(),
+            date: "2025-05-06".to_string(),
+            time: "12:00".to_string(),
+            calendars: vec!["Work".to_string()],
+            uid: Some("8E2F1C34".to_string()),
+            end_time: Some("13:00".to_string()),
+            ..Default::default()
+        };
+        event(&item)
+    }
+
+    fn args(words: &[&str]) -> CommandArgs {
+        CommandArgs::new(
+            words[0].to_string(),
+            words[1..].iter().map(|w| w.to_string()).collect(),
+            HashMap::new(),
+        )
+    }
+
+    #[test]
+    fn test_references() {
+        assert!(is_reference("@last"));
+        assert!(is_reference("\"That meeting\""));
+        assert!(!is_reference("Team sync"));
+    }
+
+    #[test]
+    fn test_resolve_args() -> Result<()> {
+        let mut update = args(&["calendar", "update", "@last"]);
+        resolve_args_with(&mut update, || Some(lunch()))?;
+        assert_eq!(update.args[1], "8E2F1C34");
+
+        // Only arguments that name an item are replaced
+        let mut create = args(&["calendar", "create", "it", "2025-05-06", "12:00", "13:00"]);
+        resolve_args_with(&mut create, || panic!("not a reference"))?;
+        assert_eq!(create.args[1], "it");
+
+        let mut todo = args(&["todo", "move", "it", "done"]);
+        let e = resolve_args_with(&mut todo, || Some(lunch())).unwrap_err();
+        assert!(
+            e.to_string()
+                .contains("refers to the event \"Lunch with Sam\", not to a reminder")
+        );
+        assert!(resolve_args_with(&mut args(&["calendar", "delete", "that"]), || None).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_freshness_and_prompt() {
+        let item = lunch();
+        let now = Local::now();
+        assert!(is_fresh(&item, now, 10));
+        assert!(!is_fresh(&item, now + Duration::minutes(11), 10));
+        assert!(!is_fresh(&item, now, 0));
+
+        let note = prompt_note(&item);
+        assert!(note.contains("event \"Lunch with Sam\" on 2025-05-06 12:00-13:00 in \"Work\""));
+        assert!(note.contains("ducktape calendar update @last"));
+    }
+}
@@ -0,0 +1,530 @@
+//! Natural language dates and times without a language model.
+//
+// Terminal mode accepts phrases like "next Tuesday", "in 3 days", "end of month" or
+// "tomorrow at 3pm" wherever a date or `--remind` time is expected, so they work without an
+// API key. Everything is resolved relative to the local clock into the `YYYY-MM-DD` and
+// `YYYY-MM-DD HH:MM` strings used by the calendar and reminder modules.
+
+use crate::i18n::Locale;
+use crate::timezone::parse_clock_time;
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, Duration, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Time used for reminders when only a day is given
+pub const DEFAULT_TIME: (u32, u32) = (9, 0);
+
+static OFFSET: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:in\s+)?(\d+|an?|one|two|three|four|five|six|seven|eight|nine|ten|eleven|twelve)\s+(minute|min|hour|hr|day|week|month|year)s?(\s+(?:from\s+(?:now|today)|later))?$").unwrap()
+});
+static AGO: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(\d+|an?|one|two|three|four|five|six|seven|eight|nine|ten|eleven|twelve)\s+(day|week|month|year)s?\s+ago$").unwrap()
+});
+static MONTH_DAY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:(?:on|the)\s+)?(?:([a-z]+)\s+(\d{1,2})(?:st|nd|rd|th)?|(\d{1,2})(?:st|nd|rd|th)?\s+(?:of\s+)?([a-z]+))(?:,?\s+(\d{4}))?$").unwrap()
+});
+static DAY_OF_MONTH: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?:on\s+)?the\s+(\d{1,2})(?:st|nd|rd|th)$").unwrap());
+
+/// A parsed date with an optional time of day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateTimeExpr {
+    pub date: NaiveDate,
+    pub time: Option<NaiveTime>,
+}
+
+impl DateTimeExpr {
+    /// The date and time, using `default` when no time was given
+    pub fn with_default_time(&self, default: NaiveTime) -> NaiveDateTime {
+        self.date.and_time(self.time.unwrap_or(default))
+    }
+}
+
+fn normalize(expr: &str) -> String {
+    expr.trim()
+        .trim_end_matches(['.', '!', ','])
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn number(word: &str) -> Option<i64> {
+    let n = match word {
+        "a" | "an" | "one" => 1,
+        "two" => 2,
+        "three" => 3,
+        "four" => 4,
+        "five" => 5,
+        "six" => 6,
+        "seven" => 7,
+        "eight" => 8,
+        "nine" => 9,
+        "ten" => 10,
+        "eleven" => 11,
+        "twelve" => 12,
+        _ => return word.parse().ok(),
+    };
+    Some(n)
+}
+
+fn weekday(word: &str) -> Option<Weekday> {
+    let day = match word {
+        "monday" | "mon" => Weekday::Mon,
+        "tuesday" | "tue" | "tues" => Weekday::Tue,
+        "wednesday" | "wed" => Weekday::Wed,
+        "thursday" | "thu" | "thur" | "thurs" => Weekday::Thu,
+        "friday" | "fri" => Weekday::Fri,
+        "saturday" | "sat" => Weekday::Sat,
+        "sunday" | "sun" => Weekday::Sun,
+        _ => return None,
+    };
+    Some(day)
+}
+
+fn month(word: &str) -> Option<u32> {
+    let month = match word {
+        "january" | "jan" => 1,
+        "february" | "feb" => 2,
+        "march" | "mar" => 3,
+        "april" | "apr" => 4,
+        "may" => 5,
+        "june" | "jun" => 6,
+        "july" | "jul" => 7,
+        "august" | "aug" => 8,
+        "september" | "sep" | "sept" => 9,
+        "october" | "oct" => 10,
+        "november" | "nov" => 11,
+        "december" | "dec" => 12,
+        _ => return None,
+    };
+    Some(month)
+}
+
+/// The next `day` after `today`; today itself counts as a week away
+fn next_weekday(today: NaiveDate, day: Weekday) -> NaiveDate {
+    let ahead = (day.num_days_from_monday() as i64 - today.weekday().num_days_from_monday() as i64)
+        .rem_euclid(7);
+    today + Duration::days(if ahead == 0 { 7 } else { ahead })
+}
+
+/// `today` if it falls on `day`, otherwise the next `day`
+fn this_or_next(today: NaiveDate, day: Weekday) -> NaiveDate {
+    if today.weekday() == day { today } else { next_weekday(today, day) }
+}
+
+fn add_months(date: NaiveDate, months: i64) -> Option<NaiveDate> {
+    if months >= 0 {
+        date.checked_add_months(Months::new(months as u32))
+    } else {
+        date.checked_sub_months(Months::new(months.unsigned_abs() as u32))
+    }
+}
+
+fn first_of_month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+fn last_of_month(date: NaiveDate) -> Option<NaiveDate> {
+    add_months(first_of_month(date), 1).map(|d| d - Duration::days(1))
+}
+
+/// Parse a date expression relative to `today`
+///
+/// Accepts `YYYY-MM-DD`, `today`, `tomorrow`, `yesterday`, `day after tomorrow`, weekday names
+/// (optionally with `this`/`next`, meaning the next occurrence), `in 3 days`, `2 weeks from
+/// now`, `+3`, `next week|month|year`, `end of week|month|year`, `start of next month`,
+/// `weekend`, month names with a day (`April 22`, `22nd of april 2026`) and `the 15th`.
+/// Numeric dates and weekday and month names are also read as the configured locale writes
+/// them, see `parse_date_in`.
+pub fn parse_date(expr: &str, today: NaiveDate) -> Option<NaiveDate> {
+    parse_date_in(expr, today, &Locale::current())
+}
+
+/// Parse a date expression relative to `today` as written in `locale`
+///
+/// Besides everything `parse_date` accepts in English, this reads numeric dates in the
+/// locale's order (14.03.2025 for de-DE, 03/14/2025 for en-US) and the locale's weekday,
+/// month and relative day names (`nächsten Montag`, `14 de marzo`).
+pub fn parse_date_in(expr: &str, today: NaiveDate, locale: &Locale) -> Option<NaiveDate> {
+    let expr = normalize(expr);
+    let expr = expr.strip_prefix("on ").unwrap_or(&expr);
+
+    if let Ok(date) = NaiveDate::parse_from_str(expr, "%Y-%m-%d") {
+        return Some(date);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(expr, "%Y/%m/%d") {
+        return Some(date);
+    }
+    if let Some(date) = locale.parse_numeric_date(expr) {
+        return Some(date);
+    }
+    let expr = locale.to_english(expr);
+    let expr = expr.as_str();
+
+    match expr {
+        "today" | "tonight" | "now" => return Some(today),
+        "tomorrow" | "tmrw" | "tmr" => return Some(today + Duration::days(1)),
+        "yesterday" => return Some(today - Duration::days(1)),
+        "day after tomorrow" | "the day after tomorrow" => return Some(today + Duration::days(2)),
+        "next week" => return Some(today + Duration::days(7)),
+        "next month" => return add_months(today, 1),
+        "next year" => return add_months(today, 12),
+        "weekend" | "this weekend" => return Some(this_or_next(today, Weekday::Sat)),
+        "next weekend" => return Some(this_or_next(today, Weekday::Sat) + Duration::days(7)),
+        // The working week ends on Friday
+        "end of week" | "end of the week" | "eow" => {
+            return Some(this_or_next(today, Weekday::Fri));
+        }
+        "end of next week" => return Some(this_or_next(today, Weekday::Fri) + Duration::days(7)),
+        "end of month" | "end of the month" | "eom" => return last_of_month(today),
+        "end of next month" => return add_months(today, 1).and_then(last_of_month),
+        "end of year" | "end of the year" | "eoy" => {
+            return NaiveDate::from_ymd_opt(today.year(), 12, 31);
+        }
+        "start of next week" | "beginning of next week" => {
+            return Some(next_weekday(today, Weekday::Mon));
+        }
+        "start of next month" | "beginning of next month" => {
+            return add_months(first_of_month(today), 1);
+        }
+        "start of next year" | "beginning of next year" => {
+            return NaiveDate::from_ymd_opt(today.year() + 1, 1, 1);
+        }
+        _ => {}
+    }
+
+    if let Some(days) = expr.strip_prefix('+').and_then(|n| n.parse::<i64>().ok()) {
+        return Some(today + Duration::days(days));
+    }
+
+    if let Some(caps) = OFFSET.captures(expr) {
+        let n = number(&caps[1])?;
+        return match &caps[2] {
+            "day" => Some(today + Duration::days(n)),
+            "week" => Some(today + Duration::weeks(n)),
+            "month" => add_months(today, n),
+            "year" => add_months(today, n * 12),
+            // Minutes and hours only make sense with a time of day
+            _ => None,
+        };
+    }
+    if let Some(caps) = AGO.captures(expr) {
+        let n = number(&caps[1])?;
+        return match &caps[2] {
+            "day" => Some(today - Duration::days(n)),
+            "week" => Some(today - Duration::weeks(n)),
+            "month" => add_months(today, -n),
+            _ => add_months(today, -n * 12),
+        };
+    }
+
+    let day_name = expr
+        .strip_prefix("this ")
+        .or_else(|| expr.strip_prefix("next "))
+        .or_else(|| expr.strip_prefix("coming "))
+        .unwrap_or(expr);
+    if let Some(day) = weekday(day_name) {
+        return Some(next_weekday(today, day));
+    }
+
+    if let Some(caps) = DAY_OF_MONTH.captures(expr) {
+        let day = caps[1].parse::<u32>().ok()?;
+        let this_month = today.with_day(day);
+        return match this_month {
+            Some(date) if date >= today => Some(date),
+            _ => (1..=12)
+                .filter_map(|n| add_months(first_of_month(today), n)?.with_day(day))
+                .next(),
+        };
+    }
+
+    if let Some(caps) = MONTH_DAY.captures(expr) {
+        let (month_name, day) = match (caps.get(1), caps.get(2)) {
+            (Some(m), Some(d)) => (m.as_str(), d.as_str()),
+            _ => (caps.get(4)?.as_str(), caps.get(3)?.as_str()),
+        };
+        let month = month(month_name)?;
+        let day = day.parse::<u32>().ok()?;
+        return match caps.get(5) {
+            Some(year) => NaiveDate::from_ymd_opt(year.as_str().parse().ok()?, month, day),
+            None => {
+                // Without a year, a date that has already passed means next year
+                let date = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+                if date < today {
+                    NaiveDate::from_ymd_opt(today.year() + 1, month, day)
+                } else {
+                    Some(date)
+                }
+            }
+        };
+    }
+
+    None
+}
+
+/// Parse a span of days relative to `today`, first and last day included
+///
+/// Accepts `this week` (today until Sunday), `next week` (Monday to Sunday), `this month`,
+/// `next month`, `this weekend` and `next weekend`; anything `parse_date` accepts is a single
+/// day.
+pub fn parse_date_range(expr: &str, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    let expr = normalize(expr);
+    let expr = expr
+        .strip_prefix("during ")
+        .or_else(|| expr.strip_prefix("for "))
+        .unwrap_or(&expr);
+    match expr {
+        "this week" | "the rest of the week" | "rest of the week" => {
+            return Some((today, this_or_next(today, Weekday::Sun)));
+        }
+        "next week" => {
+            let monday = next_weekday(today, Weekday::Mon);
+            return Some((monday, monday + Duration::days(6)));
+        }
+        "this month" => return Some((today, last_of_month(today)?)),
+        "next month" => {
+            let first = add_months(first_of_month(today), 1)?;
+            return Some((first, last_of_month(first)?));
+        }
+        "weekend" | "this weekend" | "next weekend" => {
+            let saturday = parse_date(expr, today)?;
+            return Some((saturday, saturday + Duration::days(1)));
+        }
+        _ => {}
+    }
+    parse_date(expr, today).map(|date| (date, date))
+}
+
+/// Parse a time of day such as `15:30`, `3pm`, `9:30 am`, `noon` or `morning`
+///
+/// A bare number is not accepted, so that "April 22" is never read as 22:00.
+pub fn parse_time(expr: &str) -> Option<NaiveTime> {
+    let expr = normalize(expr);
+    let expr = expr.strip_prefix("at ").unwrap_or(&expr);
+    let named = match expr {
+        "noon" | "midday" => Some((12, 0)),
+        "midnight" => Some((0, 0)),
+        "morning" | "this morning" => Some((9, 0)),
+        "afternoon" | "this afternoon" => Some((14, 0)),
+        "evening" | "this evening" | "eod" | "end of day" => Some((17, 0)),
+        "night" | "tonight" => Some((20, 0)),
+        _ => None,
+    };
+    if let Some((hour, minute)) = named {
+        return NaiveTime::from_hms_opt(hour, minute, 0);
+    }
+
+    let explicit = expr.contains(':') || expr.ends_with("am") || expr.ends_with("pm");
+    if !explicit {
+        return None;
+    }
+    parse_clock_time(expr).ok()
+}
+
+/// Parse a date and optional time relative to `now`
+///
+/// The date and time can come in either order, optionally joined by "at" (`tomorrow at 3pm`,
+/// `3pm friday`, `next tuesday 09:30`, `2025-04-22 15:30`). `in 2 hours` and `in 30 minutes`
+/// are relative to `now`, and a time on its own means its next occurrence.
+pub fn parse_datetime(expr: &str, now: NaiveDateTime) -> Option<DateTimeExpr> {
+    let expr = normalize(expr);
+    let today = now.date();
+
+    if let Some(caps) = OFFSET.captures(&expr) {
+        let n = number(&caps[1])?;
+        let offset = match &caps[2] {
+            "minute" | "min" => Some(Duration::minutes(n)),
+            "hour" | "hr" => Some(Duration::hours(n)),
+            _ => None,
+        };
+        if let Some(offset) = offset {
+            let at = now + offset;
+            return Some(DateTimeExpr { date: at.date(), time: Some(at.time()) });
+        }
+    }
+
+    if expr == "tonight" {
+        return Some(DateTimeExpr { date: today, time: parse_time(&expr) });
+    }
+    if let Some(date) = parse_date(&expr, today) {
+        return Some(DateTimeExpr { date, time: None });
+    }
+    if let Some(time) = parse_time(&expr) {
+        let date = if time <= now.time() { today + Duration::days(1) } else { today };
+        return Some(DateTimeExpr { date, time: Some(time) });
+    }
+
+    // Split the words into a date part and a time part, trying both orders
+    let words: Vec<&str> = expr.split(' ').filter(|w| *w != "at").collect();
+    for split in 1..words.len() {
+        let (head, tail) = (words[..split].join(" "), words[split..].join(" "));
+        for (date_part, time_part) in [(&head, &tail), (&tail, &head)] {
+            if let (Some(date), Some(time)) = (parse_date(date_part, today), parse_time(time_part))
+            {
+                return Some(DateTimeExpr { date, time: Some(time) });
+            }
+        }
+    }
+    None
+}
+
+fn unrecognized(expr: &str) -> anyhow::Error {
+    anyhow!(
+        "Unrecognized date: '{}'. Try YYYY-MM-DD, {}, 'tomorrow', 'next tuesday', 'in 3 days' or 'end of month'",
+        expr.trim(),
+        Locale::current().date_pattern()
+    )
+}
+
+/// Resolve a date expression to `YYYY-MM-DD`
+pub fn resolve_date(expr: &str) -> Result<String> {
+    let today = Local::now().date_naive();
+    parse_date(expr, today)
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .ok_or_else(|| unrecognized(expr))
+}
+
+/// Resolve a date and time expression to `YYYY-MM-DD HH:MM`, defaulting to 09:00
+pub fn resolve_datetime(expr: &str) -> Result<String> {
+    let default = NaiveTime::from_hms_opt(DEFAULT_TIME.0, DEFAULT_TIME.1, 0).unwrap();
+    parse_datetime(expr, Local::now().naive_local())
+        .map(|parsed| parsed.with_default_time(default).format("%Y-%m-%d %H:%M").to_string())
+        .ok_or_else(|| unrecognized(expr))
+}
+
+/// Resolve a due date: `YYYY-MM-DD` for a day, `YYYY-MM-DD HH:MM` when a time is given
+pub fn resolve_due(expr: &str) -> Result<String> {
+    let parsed =
+        parse_datetime(expr, Local::now().naive_local()).ok_or_else(|| unrecognized(expr))?;
+    Ok(match parsed.time {
+        Some(time) => parsed.date.and_time(time).format("%Y-%m-%d %H:%M").to_string(),
+        None => parsed.date.format("%Y-%m-%d").to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_date() {
+        // Friday
+        let today = date(2025, 3, 14);
+        assert_eq!(parse_date("today", today), Some(today));
+        assert_eq!(parse_date("Tomorrow", today), Some(date(2025, 3, 15)));
+        assert_eq!(parse_date("day after tomorrow", today), Some(date(2025, 3, 16)));
+        assert_eq!(parse_date("next Tuesday", today), Some(date(2025, 3, 18)));
+        assert_eq!(parse_date("friday", today), Some(date(2025, 3, 21)));
+        assert_eq!(parse_date("in 3 days", today), Some(date(2025, 3, 17)));
+        assert_eq!(parse_date("two weeks from now", today), Some(date(2025, 3, 28)));
+        assert_eq!(parse_date("in a month", today), Some(date(2025, 4, 14)));
+        assert_eq!(parse_date("+3", today), Some(date(2025, 3, 17)));
+        assert_eq!(parse_date("end of month", today), Some(date(2025, 3, 31)));
+        assert_eq!(parse_date("end of next month", today), Some(date(2025, 4, 30)));
+        assert_eq!(parse_date("end of week", today), Some(today));
+        assert_eq!(parse_date("start of next month", today), Some(date(2025, 4, 1)));
+        assert_eq!(parse_date("this weekend", today), Some(date(2025, 3, 15)));
+        assert_eq!(parse_date("2025-04-01", today), Some(date(2025, 4, 1)));
+        assert_eq!(parse_date("someday", today), None);
+    }
+
+    #[test]
+    fn test_parse_date_range() {
+        // Friday
+        let today = date(2025, 3, 14);
+        assert_eq!(
+            parse_date_range("next week", today),
+            Some((date(2025, 3, 17), date(2025, 3, 23)))
+        );
+        assert_eq!(parse_date_range("this week", today), Some((today, date(2025, 3, 16))));
+        assert_eq!(
+            parse_date_range("next month", today),
+            Some((date(2025, 4, 1), date(2025, 4, 30)))
+        );
+        assert_eq!(
+            parse_date_range("this weekend", today),
+            Some((date(2025, 3, 15), date(2025, 3, 16)))
+        );
+        assert_eq!(
+            parse_date_range("on friday", today),
+            Some((date(2025, 3, 21), date(2025, 3, 21)))
+        );
+        assert_eq!(parse_date_range("someday", today), None);
+    }
+
+    #[test]
+    fn test_parse_date_month_names() {
+        let today = date(2025, 3, 14);
+        assert_eq!(parse_date("April 22", today), Some(date(2025, 4, 22)));
+        assert_eq!(parse_date("on the 3rd of May", today), Some(date(2025, 5, 3)));
+        assert_eq!(parse_date("Jan 5, 2026", today), Some(date(2026, 1, 5)));
+        // Dates that already passed this year roll over to next year
+        assert_eq!(parse_date("march 1", today), Some(date(2026, 3, 1)));
+        assert_eq!(parse_date("the 20th", today), Some(date(2025, 3, 20)));
+        assert_eq!(parse_date("the 10th", today), Some(date(2025, 4, 10)));
+        assert_eq!(parse_date("the 31st", date(2025, 4, 2)), Some(date(2025, 5, 31)));
+        assert_eq!(parse_date("february 30", today), None);
+    }
+
+    #[test]
+    fn test_parse_date_in_locale() {
+        let today = date(2025, 3, 14);
+        let german = Locale::from_tag("de-DE").unwrap();
+        assert_eq!(parse_date_in("17.03.2025", today, &german), Some(date(2025, 3, 17)));
+        assert_eq!(parse_date_in("nächsten Montag", today, &german), Some(date(2025, 3, 17)));
+        assert_eq!(parse_date_in("am 1. April", today, &german), Some(date(2025, 4, 1)));
+        // English keeps working in every locale
+        assert_eq!(parse_date_in("tomorrow", today, &german), Some(date(2025, 3, 15)));
+        let us = Locale::default();
+        assert_eq!(parse_date_in("03/17/2025", today, &us), Some(date(2025, 3, 17)));
+        assert_eq!(parse_date_in("17/03/2025", today, &us), None);
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("15:30"), Some(time(15, 30)));
+        assert_eq!(parse_time("at 3pm"), Some(time(15, 0)));
+        assert_eq!(parse_time("9:30 am"), Some(time(9, 30)));
+        assert_eq!(parse_time("noon"), Some(time(12, 0)));
+        assert_eq!(parse_time("evening"), Some(time(17, 0)));
+        assert_eq!(parse_time("22"), None);
+    }
+
+    #[test]
+    fn test_parse_datetime() {
+        let now = date(2025, 3, 14).and_time(time(10, 0));
+        let at = |expr| parse_datetime(expr, now).map(|p| (p.date, p.time));
+
+        assert_eq!(at("tomorrow at 3pm"), Some((date(2025, 3, 15), Some(time(15, 0)))));
+        assert_eq!(at("3pm next tuesday"), Some((date(2025, 3, 18), Some(time(15, 0)))));
+        assert_eq!(at("2025-04-22 15:30"), Some((date(2025, 4, 22), Some(time(15, 30)))));
+        assert_eq!(at("April 22 at 9:15am"), Some((date(2025, 4, 22), Some(time(9, 15)))));
+        assert_eq!(at("friday morning"), Some((date(2025, 3, 21), Some(time(9, 0)))));
+        assert_eq!(at("in 2 hours"), Some((date(2025, 3, 14), Some(time(12, 0)))));
+        assert_eq!(at("tonight"), Some((date(2025, 3, 14), Some(time(20, 0)))));
+        assert_eq!(at("end of month"), Some((date(2025, 3, 31), None)));
+        // A time that already passed today means tomorrow
+        assert_eq!(at("9am"), Some((date(2025, 3, 15), Some(time(9, 0)))));
+        assert_eq!(at("11:00"), Some((date(2025, 3, 14), Some(time(11, 0)))));
+        assert_eq!(at("whenever"), None);
+    }
+
+    #[test]
+    fn test_resolve_formats() {
+        assert!(resolve_date("in 3 days").unwrap().len() == 10);
+        assert!(resolve_datetime("tomorrow").unwrap().ends_with(" 09:00"));
+        assert_eq!(resolve_due("2025-04-22").unwrap(), "2025-04-22");
+        assert_eq!(resolve_due("2025-04-22 at 17:45").unwrap(), "2025-04-22 17:45");
+        assert!(resolve_date("someday").unwrap_err().to_string().contains("someday"));
+    }
+}
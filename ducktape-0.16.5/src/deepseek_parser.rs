@@ -45,6 +45,7 @@ pub async fn parse_natural_language(input: &str) -> Result<String> {
     let parser = crate::parser::deepseek::DeepSeekParser::new()?;
     match parser.parse_input(input).await? {
         ParseResult::CommandString(cmd) => Ok(cmd),
+        ParseResult::NeedsClarification(clarification) => Ok(clarification.command),
         ParseResult::StructuredCommand(_) => {
             Err(anyhow!("Expected command string but got structured command"))
         }
@@ -0,0 +1,164 @@
+//! `ducktape doctor`: checks everything DuckTape needs before a command fails on it.
+//
+// The report reuses the API server's startup checks (API keys, meeting credentials, Calendar
+// and the default calendar) and adds the Automation permission of every app DuckTape
+// controls and whether the configured language model and meeting APIs can be reached.
+
+use crate::api_server::startup::{
+    StartupCheck, StartupReport, check_api_key, check_calendars, check_meeting_credentials,
+};
+use crate::config::{Config, MeetingService};
+use crate::permissions::{AutomationApp, PermissionStatus};
+use chrono::Utc;
+use std::time::Duration;
+
+/// How long to wait for a TCP connection to an API
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Check name for an app's Automation permission, e.g. `automation_calendar`
+fn permission_check_name(app: AutomationApp) -> String {
+    format!("automation_{}", app.name().to_lowercase())
+}
+
+/// Turn a permission probe into a check
+pub fn permission_check(app: AutomationApp, status: &PermissionStatus) -> StartupCheck {
+    let name = permission_check_name(app);
+    match status {
+        PermissionStatus::Granted => {
+            StartupCheck::ok(&name, format!("{} can be controlled", app.name()))
+        }
+        PermissionStatus::Denied => StartupCheck::error(
+            &name,
+            format!("Not allowed to control {}", app.name()),
+            app.guidance(),
+        ),
+        PermissionStatus::Unknown(detail) => StartupCheck::warning(
+            &name,
+            format!("Could not check {}: {}", app.name(), detail),
+            format!("Open {} once and run `ducktape doctor` again", app.name()),
+        ),
+    }
+}
+
+/// APIs used with this configuration, as (label, base URL)
+pub fn endpoints(
+    config: &Config,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Vec<(String, String)> {
+    let mut endpoints = Vec::new();
+    for provider in config.parser_chain() {
+        let url = match provider.as_str() {
+            "grok" | "xai" => lookup("XAI_API_BASE")
+                .unwrap_or_else(|| crate::parser::grok::api::DEFAULT_API_BASE.to_string()),
+            "deepseek" => lookup("DEEPSEEK_API_BASE")
+                .unwrap_or_else(|| crate::parser::deepseek::api::DEFAULT_API_BASE.to_string()),
+            _ => continue,
+        };
+        endpoints.push((provider, url));
+    }
+    if config.calendar.meeting_provider == MeetingService::Zoom {
+        endpoints.push(("zoom".to_string(), "https://api.zoom.us/v2".to_string()));
+    }
+    endpoints
+}
+
+/// Check that a TCP connection to an API can be opened
+async fn check_reachable(label: &str, url: &str) -> StartupCheck {
+    let name = format!("network_{}", label);
+    let Some((host, port)) = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| Some((u.host_str()?.to_string(), u.port_or_known_default()?)))
+    else {
+        return StartupCheck::error(&name, format!("Invalid API URL: {}", url), "Fix the URL");
+    };
+
+    let connect = tokio::net::TcpStream::connect((host.as_str(), port));
+    match tokio::time::timeout(CONNECT_TIMEOUT, connect).await {
+        Ok(Ok(_)) => StartupCheck::ok(&name, format!("{} is reachable", host)),
+        Ok(Err(e)) => StartupCheck::error(
+            &name,
+            format!("Cannot connect to {}: {}", host, e),
+            "Check your network connection, proxy or firewall",
+        ),
+        Err(_) => StartupCheck::error(
+            &name,
+            format!("Timed out connecting to {}", host),
+            "Check your network connection, proxy or firewall",
+        ),
+    }
+}
+
+/// Run every check
+pub async fn diagnose(config: &Config) -> StartupReport {
+    let mut checks = Vec::new();
+
+    for (app, status) in crate::permissions::check_all().await {
+        checks.push(permission_check(app, &status));
+    }
+
+    if config.uses_language_model() {
+        checks.push(check_api_key(config, crate::secrets::get));
+    } else {
+        checks.push(StartupCheck::ok(
+            crate::api_server::startup::CHECK_API_KEY,
+            "Terminal Mode, no API key needed",
+        ));
+    }
+    checks.push(check_meeting_credentials(config, crate::secrets::get));
+
+    let calendars = crate::calendar::get_available_calendars().await;
+    checks.extend(check_calendars(config, &calendars));
+
+    for (label, url) in endpoints(config, |name| std::env::var(name).ok()) {
+        checks.push(check_reachable(&label, &url).await);
+    }
+
+    StartupReport { checked_at: Utc::now().to_rfc3339(), checks }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_server::startup::CheckStatus;
+    use crate::config::LLMProvider;
+
+    #[test]
+    fn test_permission_check() {
+        let check = permission_check(AutomationApp::Notes, &PermissionStatus::Denied);
+        assert_eq!(check.name, "automation_notes");
+        assert_eq!(check.status, CheckStatus::Error);
+        assert!(check.hint.unwrap().contains("Automation"));
+
+        let check = permission_check(
+            AutomationApp::Contacts,
+            &PermissionStatus::Unknown("timed out".to_string()),
+        );
+        assert_eq!(check.status, CheckStatus::Warning);
+        assert!(check.message.contains("timed out"));
+    }
+
+    #[test]
+    fn test_endpoints_follow_config() {
+        let mut config = Config::default();
+        config.nlp.providers = vec!["deepseek".to_string(), "grok".to_string()];
+        let custom = |name: &str| {
+            (name == "DEEPSEEK_API_BASE").then(|| "https://llm.example.com/v1".to_string())
+        };
+        assert_eq!(
+            endpoints(&config, custom),
+            vec![
+                ("deepseek".to_string(), "https://llm.example.com/v1".to_string()),
+                ("grok".to_string(), "https://api.x.ai/v1".to_string()),
+                ("zoom".to_string(), "https://api.zoom.us/v2".to_string()),
+            ]
+        );
+
+        config.nlp.providers.clear();
+        config.language_model.provider = None;
+        config.calendar.meeting_provider = MeetingService::Teams;
+        assert!(endpoints(&config, |_| None).is_empty());
+
+        config.language_model.provider = Some(LLMProvider::Grok);
+        assert_eq!(endpoints(&config, |_| None).len(), 1);
+    }
+}
@@ -1,5 +1,5 @@
-use log::info;
 use std::io::BufRead;
+use tracing::info;
 
 #[allow(dead_code)]
 pub fn print_env_status() {
@@ -54,21 +54,3 @@ pub fn print_env_status() {
         }
     }
 }
-
-/// Sets a placeholder API key for development/testing only
-///
-/// # Warning
-/// This function should only be used during development and testing,
-/// never in production environments. Proper API keys should be set
-/// through environment variables or the .env file.
-pub fn force_set_api_key() -> bool {
-    // If environment variable is not set, set a placeholder for development
-    if std::env::var("XAI_API_KEY").is_err() {
-        let api_key = "xai-placeholder-development-key-not-for-production-use";
-        std::env::set_var("XAI_API_KEY", api_key);
-        info!("🔑 Set placeholder XAI_API_KEY for development (length: {})", api_key.len());
-        return true;
-    }
-
-    false
-}
@@ -1,9 +1,9 @@
 use anyhow::Result;
-use log::{error, info};
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
+use tracing::{error, info};
 
 pub struct EnvLoader;
 
@@ -1,8 +1,8 @@
-use log::info;
 use std::env;
 use std::fs::{File, OpenOptions};
 use std::io::{self, Write};
 use std::path::PathBuf;
+use tracing::info;
 
 /// Required environment variables that the application needs
 pub const REQUIRED_ENV_VARS: &[&str] =
@@ -56,3 +56,105 @@ pub fn restore_env_vars() {
         }
     }
 }
+
+// Keychain service under which DuckTape stores secrets
+const KEYCHAIN_SERVICE: &str = "ducktape";
+
+// Helper function to store a secret in the macOS keychain, falling back to a file that only
+// the current user can read (~/.ducktape/secrets/<name>) where no keychain is available
+pub fn store_secret(name: &str, value: &str) -> anyhow::Result<()> {
+    use std::io::Write;
+
+    // `security -i` reads one command per line, so a newline would end the command and start
+    // another; no API key or password holds control characters anyway
+    if value.chars().any(char::is_control) {
+        return Err(anyhow::anyhow!("The value for {} contains control characters", name));
+    }
+
+    if cfg!(target_os = "macos") {
+        // Commands are fed through stdin so the secret never shows up in the process list
+        let mut child = std::process::Command::new("security")
+            .arg("-i")
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()?;
+        if let Some(stdin) = child.stdin.as_mut() {
+            writeln!(
+                stdin,
+                "add-generic-password -U -s {} -a {} -w \"{}\"",
+                KEYCHAIN_SERVICE,
+                name,
+                value.replace('\\', "\\\\").replace('"', "\\\"")
+            )?;
+        }
+        if child.wait()?.success() {
+            return Ok(());
+        }
+        tracing::warn!("Failed to store {} in the keychain, falling back to file storage", name);
+    }
+
+    let path = secret_file_path(name)?;
+    if let Some(parent) = path.parent() {
+        let mut builder = std::fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::DirBuilderExt;
+            builder.mode(0o700);
+        }
+        builder.create(parent)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(&path)?.write_all(value.as_bytes())?;
+    Ok(())
+}
+
+// Helper function to load a secret stored with store_secret
+pub fn load_secret(name: &str) -> Option<String> {
+    if cfg!(target_os = "macos") {
+        let output = std::process::Command::new("security")
+            .args(["find-generic-password", "-s", KEYCHAIN_SERVICE, "-a", name, "-w"])
+            .output()
+            .ok()?;
+        if output.status.success() {
+            return Some(String::from_utf8_lossy(&output.stdout).trim_end().to_string());
+        }
+    }
+
+    std::fs::read_to_string(secret_file_path(name).ok()?).ok()
+}
+
+// Helper function to remove a secret from the keychain and the fallback file
+pub fn delete_secret(name: &str) {
+    if cfg!(target_os = "macos") {
+        let _ = std::process::Command::new("security")
+            .args(["delete-generic-password", "-s", KEYCHAIN_SERVICE, "-a", name])
+            .output();
+    }
+    if let Ok(path) = secret_file_path(name) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+fn secret_file_path(name: &str) -> anyhow::Result<std::path::PathBuf> {
+    let home = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
+    Ok(home.join(".ducktape").join("secrets").join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_secret_rejects_control_characters() {
+        let e = store_secret("TEST_SECRET", "abc\ndelete-keychain login.keychain").unwrap_err();
+        assert!(e.to_string().contains("control characters"));
+    }
+}
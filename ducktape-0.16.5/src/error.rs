@@ -0,0 +1,239 @@
+//! Error type of DuckTape's public API.
+//
+// Internally most functions return `anyhow::Result` and attach a domain error such as
+// `CalendarError` or `ScriptRunnerError` where the failure is known. Only the entry points
+// meant for other programs return `DucktapeError`: `crate::api`, `crate::run`,
+// `EventConfigBuilder::build` and the create functions of calendar, todo and notes. It
+// recovers that domain error, so callers can tell a user's mistake from a broken environment.
+// The other public modules keep `anyhow::Result`; `DucktapeError::from` turns their errors
+// into one. The API server maps its `ErrorKind` to an HTTP status and `main` to an exit code.
+
+use crate::backend::PlatformError;
+use crate::calendar::CalendarError;
+use crate::config::ConfigError;
+use crate::notes::NotesError;
+use crate::reminder::ReminderError;
+use crate::script_runner::ScriptRunnerError;
+use crate::todo::TodoError;
+use crate::validation::ValidationError;
+use crate::zoom::ZoomError;
+
+/// Result with a `DucktapeError`
+pub type Result<T, E = DucktapeError> = std::result::Result<T, E>;
+
+/// What went wrong, independent of the domain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The input was invalid or could not be understood
+    InvalidInput,
+    /// A calendar, list, note or other named item does not exist
+    NotFound,
+    /// macOS or device management refused access
+    PermissionDenied,
+    /// The configuration is invalid or incomplete
+    Config,
+    /// An app or external service is not available or failed
+    Unavailable,
+    /// Anything else
+    Internal,
+}
+
+/// Errors returned by DuckTape, by domain
+#[derive(Debug, thiserror::Error)]
+pub enum DucktapeError {
+    /// The input could not be turned into a command
+    #[error("{0}")]
+    Parse(String),
+
+    /// An argument failed validation
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+
+    #[error(transparent)]
+    Calendar(#[from] CalendarError),
+
+    #[error(transparent)]
+    Reminder(#[from] ReminderError),
+
+    #[error(transparent)]
+    Notes(#[from] NotesError),
+
+    #[error(transparent)]
+    Zoom(#[from] ZoomError),
+
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    #[error(transparent)]
+    Permission(#[from] ScriptRunnerError),
+
+    /// The feature needs an Apple app on another platform
+    #[error(transparent)]
+    Platform(#[from] PlatformError),
+
+    #[error(transparent)]
+    Other(anyhow::Error),
+}
+
+impl DucktapeError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            DucktapeError::Parse(_) | DucktapeError::Validation(_) => ErrorKind::InvalidInput,
+            DucktapeError::Calendar(e) => match e {
+                CalendarError::InvalidDateTime(_) => ErrorKind::InvalidInput,
+                CalendarError::CalendarNotFound(_) => ErrorKind::NotFound,
+                CalendarError::NotRunning | CalendarError::ScriptError(_) => ErrorKind::Unavailable,
+            },
+            DucktapeError::Reminder(e) => match e {
+                ReminderError::InvalidInput(_) => ErrorKind::InvalidInput,
+                ReminderError::ListNotFound(_) | ReminderError::ReminderNotFound(_) => {
+                    ErrorKind::NotFound
+                }
+                ReminderError::NotRunning | ReminderError::ScriptError(_) => ErrorKind::Unavailable,
+                ReminderError::General(_) => ErrorKind::Internal,
+            },
+            DucktapeError::Notes(e) => match e {
+                NotesError::NoteNotFound(_) | NotesError::FolderNotFound(_) => ErrorKind::NotFound,
+                NotesError::NoteLocked(_) => ErrorKind::PermissionDenied,
+                NotesError::NotRunning | NotesError::ScriptError(_) => ErrorKind::Unavailable,
+                NotesError::ParseError(_) => ErrorKind::Internal,
+            },
+            DucktapeError::Zoom(e) => match e {
+                ZoomError::MissingCredential(_) => ErrorKind::Config,
+                ZoomError::Auth { .. } | ZoomError::Api { .. } => ErrorKind::Unavailable,
+            },
+            DucktapeError::Config(_) => ErrorKind::Config,
+            DucktapeError::Permission(e) => match e {
+                ScriptRunnerError::InterpreterNotFound(_) => ErrorKind::Config,
+                _ => ErrorKind::PermissionDenied,
+            },
+            DucktapeError::Platform(_) => ErrorKind::Unavailable,
+            DucktapeError::Other(_) => ErrorKind::Internal,
+        }
+    }
+
+    /// Whether the user can fix the error by changing the input
+    pub fn is_user_error(&self) -> bool {
+        matches!(self.kind(), ErrorKind::InvalidInput | ErrorKind::NotFound)
+    }
+
+    /// Process exit code, following sysexits.h
+    pub fn exit_code(&self) -> i32 {
+        match self.kind() {
+            ErrorKind::InvalidInput => 65,     // EX_DATAERR
+            ErrorKind::NotFound => 66,         // EX_NOINPUT
+            ErrorKind::Unavailable => 69,      // EX_UNAVAILABLE
+            ErrorKind::Internal => 70,         // EX_SOFTWARE
+            ErrorKind::PermissionDenied => 77, // EX_NOPERM
+            ErrorKind::Config => 78,           // EX_CONFIG
+        }
+    }
+}
+
+impl From<TodoError> for DucktapeError {
+    fn from(e: TodoError) -> Self {
+        DucktapeError::Reminder(match e {
+            TodoError::NotRunning => ReminderError::NotRunning,
+            TodoError::ListNotFound(list) => ReminderError::ListNotFound(list),
+            TodoError::TodoNotFound(title) => ReminderError::ReminderNotFound(title),
+            TodoError::ScriptError(message) => ReminderError::ScriptError(message),
+            TodoError::General(message) => ReminderError::General(message),
+        })
+    }
+}
+
+/// Recover the domain error attached to an `anyhow::Error`
+impl From<anyhow::Error> for DucktapeError {
+    fn from(e: anyhow::Error) -> Self {
+        let e = match e.downcast::<DucktapeError>() {
+            Ok(e) => return e,
+            Err(e) => e,
+        };
+        let e = match e.downcast::<ValidationError>() {
+            Ok(e) => return e.into(),
+            Err(e) => e,
+        };
+        let e = match e.downcast::<CalendarError>() {
+            Ok(e) => return e.into(),
+            Err(e) => e,
+        };
+        let e = match e.downcast::<ReminderError>() {
+            Ok(e) => return e.into(),
+            Err(e) => e,
+        };
+        let e = match e.downcast::<TodoError>() {
+            Ok(e) => return e.into(),
+            Err(e) => e,
+        };
+        let e = match e.downcast::<NotesError>() {
+            Ok(e) => return e.into(),
+            Err(e) => e,
+        };
+        let e = match e.downcast::<ZoomError>() {
+            Ok(e) => return e.into(),
+            Err(e) => e,
+        };
+        let e = match e.downcast::<ConfigError>() {
+            Ok(e) => return e.into(),
+            Err(e) => e,
+        };
+        let e = match e.downcast::<ScriptRunnerError>() {
+            Ok(e) => return e.into(),
+            Err(e) => e,
+        };
+        match e.downcast::<PlatformError>() {
+            Ok(e) => e.into(),
+            Err(e) => DucktapeError::Other(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{Context, anyhow};
+
+    #[test]
+    fn test_from_anyhow_recovers_domain_errors() {
+        let e: DucktapeError = anyhow!(CalendarError::InvalidDateTime("25:00".into())).into();
+        assert!(matches!(e, DucktapeError::Calendar(_)));
+        assert_eq!(e.kind(), ErrorKind::InvalidInput);
+        assert!(e.is_user_error());
+
+        let e: DucktapeError = anyhow!(TodoError::ListNotFound("Work".into())).into();
+        assert!(matches!(e, DucktapeError::Reminder(ReminderError::ListNotFound(_))));
+        assert_eq!(e.exit_code(), 66);
+
+        let denied = ScriptRunnerError::AutomationDenied { app: "Notes".into() };
+        let e: DucktapeError = anyhow!(denied).into();
+        assert_eq!(e.kind(), ErrorKind::PermissionDenied);
+        assert_eq!(e.exit_code(), 77);
+
+        let e: DucktapeError = anyhow!(PlatformError::AppleOnly("Notes.app".into())).into();
+        assert_eq!(e.kind(), ErrorKind::Unavailable);
+        assert_eq!(e.exit_code(), 69);
+
+        let e: DucktapeError = anyhow!(DucktapeError::Parse("No command provided".into())).into();
+        assert_eq!(e.to_string(), "No command provided");
+
+        let e: DucktapeError = anyhow!(ValidationError::new("end time", "too early")).into();
+        assert_eq!(e.kind(), ErrorKind::InvalidInput);
+        assert_eq!(e.to_string(), "Invalid end time: too early");
+    }
+
+    #[test]
+    fn test_context_keeps_the_domain_error() {
+        let result: anyhow::Result<()> = Err(NotesError::NoteNotFound("Plans".into()).into());
+        let e: DucktapeError = result.context("Failed to open note").unwrap_err().into();
+        assert_eq!(e.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_unknown_errors_are_internal() {
+        let e: DucktapeError = anyhow!("disk on fire").into();
+        assert!(matches!(e, DucktapeError::Other(_)));
+        assert!(!e.is_user_error());
+        assert_eq!(e.exit_code(), 70);
+        assert_eq!(e.to_string(), "disk on fire");
+    }
+}
@@ -1,18 +1,35 @@
+//! Searching the web for events, such as a team's next matches or a band's tour dates.
+//
+// Each search service implements `EventSearchProvider`: Grok answers from its own web search,
+// SerpAPI reads Google's event listings, and Brave Search and Bing return web pages whose title
+// or snippet names a date. `search_events` asks the providers in `event_search.providers` at
+// once, skipping those without an API key (`ducktape keys set serpapi|brave|bing|xai`), and
+// merges their results so an event found by several providers is listed once.
+
+use crate::config::Config;
 use anyhow::{Result, anyhow};
-use chrono::{Datelike, Local, NaiveDate, NaiveTime}; // Added missing imports
-use log::{debug, info};
+use async_trait::async_trait;
+use chrono::{Local, NaiveDate, NaiveTime};
+use once_cell::sync::Lazy;
+use regex::Regex;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
+use std::collections::HashMap;
 use std::io::Read;
 use std::path::Path;
 use std::{fs::File, io::Write};
+use tracing::{debug, info, warn};
 
-#[allow(dead_code)]
 // Maximum size for response data to prevent DoS attacks (5MB)
 const MAX_RESPONSE_SIZE: usize = 5 * 1024 * 1024;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Environment variables (or keychain entries) holding the search API keys
+pub const SERPAPI_KEY_VAR: &str = "SERPAPI_API_KEY";
+pub const BRAVE_KEY_VAR: &str = "BRAVE_SEARCH_API_KEY";
+pub const BING_KEY_VAR: &str = "BING_SEARCH_API_KEY";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EventSearchResult {
     pub title: String,
     pub date: String,               // YYYY-MM-DD format
@@ -23,102 +40,402 @@ pub struct EventSearchResult {
     pub url: Option<String>,
 }
 
-#[allow(dead_code)]
-/// Search for events on the internet using a search query
-/// Returns a list of potential events found
+/// A service that finds upcoming events for a query
+#[async_trait]
+pub trait EventSearchProvider: Send + Sync {
+    /// Name used in `event_search.providers` and in messages
+    fn name(&self) -> &'static str;
+
+    /// Events matching `query` on or after `today`
+    async fn search(&self, query: &str, today: NaiveDate) -> Result<Vec<EventSearchResult>>;
+}
+
+/// The providers `event_search.providers` can name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchProvider {
+    Grok,
+    SerpApi,
+    Brave,
+    Bing,
+}
+
+impl SearchProvider {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "grok" | "xai" => Some(SearchProvider::Grok),
+            "serpapi" | "google" => Some(SearchProvider::SerpApi),
+            "brave" => Some(SearchProvider::Brave),
+            "bing" => Some(SearchProvider::Bing),
+            _ => None,
+        }
+    }
+
+    /// The variable holding the provider's API key
+    pub fn key_var(&self) -> &'static str {
+        match self {
+            SearchProvider::Grok => "XAI_API_KEY",
+            SearchProvider::SerpApi => SERPAPI_KEY_VAR,
+            SearchProvider::Brave => BRAVE_KEY_VAR,
+            SearchProvider::Bing => BING_KEY_VAR,
+        }
+    }
+
+    /// The provider, or `None` when its API key is not set
+    pub fn build(&self) -> Option<Box<dyn EventSearchProvider>> {
+        let api_key = crate::secrets::get(self.key_var())?;
+        Some(match self {
+            SearchProvider::Grok => Box::new(GrokSearch {
+                api_key,
+                api_base: std::env::var("XAI_API_BASE")
+                    .unwrap_or_else(|_| "https://api.x.ai/v1".to_string()),
+            }),
+            SearchProvider::SerpApi => Box::new(SerpApiSearch { api_key }),
+            SearchProvider::Brave => Box::new(BraveSearch { api_key }),
+            SearchProvider::Bing => Box::new(BingSearch { api_key }),
+        })
+    }
+}
+
+/// Search for events with the configured providers, merging and deduplicating their results
 pub async fn search_events(query: &str) -> Result<Vec<EventSearchResult>> {
     info!("Searching for events with query: {}", query);
+    let config = Config::load()?.event_search;
+    let mut providers = Vec::new();
+    for name in &config.providers {
+        match SearchProvider::parse(name) {
+            Some(provider) => match provider.build() {
+                Some(provider) => providers.push(provider),
+                None => debug!("Skipping {}: {} is not set", name, provider.key_var()),
+            },
+            None => warn!("Unknown event search provider in event_search.providers: {}", name),
+        }
+    }
+    search_with(&providers, query, Local::now().date_naive(), config.max_results).await
+}
+
+/// Ask every provider at once and merge what they find
+pub async fn search_with(
+    providers: &[Box<dyn EventSearchProvider>],
+    query: &str,
+    today: NaiveDate,
+    max_results: usize,
+) -> Result<Vec<EventSearchResult>> {
+    if providers.is_empty() {
+        return Err(anyhow!(
+            "No event search provider is available. Store an API key with `ducktape keys set serpapi` (or brave, bing, xai) and list the provider in event_search.providers"
+        ));
+    }
+    let searches = providers.iter().map(|provider| provider.search(query, today));
+    let mut found = Vec::new();
+    let mut errors = Vec::new();
+    for (provider, result) in providers.iter().zip(futures::future::join_all(searches).await) {
+        match result {
+            Ok(events) => {
+                info!("Found {} events via {}", events.len(), provider.name());
+                found.push(events);
+            }
+            Err(e) => {
+                warn!("{} search failed: {}", provider.name(), e);
+                errors.push(format!("{}: {}", provider.name(), e));
+            }
+        }
+    }
+    if found.is_empty() {
+        return Err(anyhow!("Event search failed: {}", errors.join("; ")));
+    }
+    let mut events = merge_results(found);
+    events.retain(|event| {
+        NaiveDate::parse_from_str(&event.date, "%Y-%m-%d").is_ok_and(|date| date >= today)
+    });
+    events.truncate(max_results);
+    Ok(events)
+}
+
+/// Identifies an event across providers: its day and its title without case or punctuation
+fn dedup_key(event: &EventSearchResult) -> (String, String) {
+    let title = event
+        .title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    (event.date.clone(), title)
+}
+
+/// Merge the results of several providers, earlier providers first
+///
+/// An event found more than once is kept once, with details missing from the first result
+/// filled in from the others. The merged events are sorted by date and start time.
+pub fn merge_results(results: Vec<Vec<EventSearchResult>>) -> Vec<EventSearchResult> {
+    let mut merged: Vec<EventSearchResult> = Vec::new();
+    let mut index: HashMap<(String, String), usize> = HashMap::new();
+    for event in results.into_iter().flatten() {
+        match index.get(&dedup_key(&event)) {
+            Some(&at) => {
+                let kept = &mut merged[at];
+                kept.start_time = kept.start_time.take().or(event.start_time);
+                kept.end_time = kept.end_time.take().or(event.end_time);
+                kept.location = kept.location.take().or(event.location);
+                kept.description = kept.description.take().or(event.description);
+                kept.url = kept.url.take().or(event.url);
+            }
+            None => {
+                index.insert(dedup_key(&event), merged.len());
+                merged.push(event);
+            }
+        }
+    }
+    merged.sort_by(|a, b| (&a.date, &a.start_time).cmp(&(&b.date, &b.start_time)));
+    merged
+}
+
+static TIME_RANGE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(\d{1,2})(?::(\d{2}))?\s*([ap]\.?m\.?)?\s*(?:-|–|to)\s*(\d{1,2})(?::(\d{2}))?\s*([ap]\.?m\.?)").unwrap()
+});
+static TIME_12H: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\b(\d{1,2})(?::(\d{2}))?\s*([ap])\.?m\b").unwrap());
+static TIME_24H: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b([01]?\d|2[0-3]):([0-5]\d)\b").unwrap());
+static DATE_IN_TEXT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:\d{4}-\d{2}-\d{2}|(?:jan|feb|mar|apr|may|jun|jul|aug|sep|oct|nov|dec)[a-z]*\.?\s+\d{1,2}(?:st|nd|rd|th)?(?:,?\s+\d{4})?|\d{1,2}(?:st|nd|rd|th)?\s+(?:jan|feb|mar|apr|may|jun|jul|aug|sep|oct|nov|dec)[a-z]*(?:\s+\d{4})?)\b").unwrap()
+});
+
+/// `hour`, `minute` and an optional `a`/`p` as HH:MM
+fn clock(hour: &str, minute: Option<&str>, meridiem: Option<&str>) -> Option<String> {
+    let mut hour = hour.parse::<u32>().ok()?;
+    let minute = minute.map_or(Some(0), |m| m.parse::<u32>().ok())?;
+    match meridiem.map(|m| m.to_lowercase()) {
+        Some(m) if m.starts_with('p') && hour < 12 => hour += 12,
+        Some(m) if m.starts_with('a') && hour == 12 => hour = 0,
+        _ => {}
+    }
+    NaiveTime::from_hms_opt(hour, minute, 0).map(|time| time.format("%H:%M").to_string())
+}
 
-    // Try to use Grok for online search first
-    match search_events_with_grok(query).await {
-        Ok(events) if !events.is_empty() => {
-            info!("Found {} events via Grok search", events.len());
-            return Ok(events);
+/// The start and end time named in text such as "Fri, Mar 14, 7 – 10 PM" or "kick-off 19:30"
+pub fn find_times(text: &str) -> (Option<String>, Option<String>) {
+    if let Some(caps) = TIME_RANGE.captures(text) {
+        let text = |i: usize| caps.get(i).map(|m| m.as_str());
+        // "7 – 10 PM": the start takes the end's AM/PM
+        let start = clock(text(1).unwrap_or_default(), text(2), text(3).or(text(6)));
+        let end = clock(text(4).unwrap_or_default(), text(5), text(6));
+        if start.is_some() {
+            return (start, end);
         }
-        Ok(_) => info!("No events found via Grok search, falling back to mock data"),
-        Err(e) => info!("Grok search failed: {}, falling back to mock data", e),
     }
+    if let Some(caps) = TIME_12H.captures(text) {
+        return (clock(&caps[1], caps.get(2).map(|m| m.as_str()), Some(&caps[3])), None);
+    }
+    match TIME_24H.captures(text) {
+        Some(caps) => (clock(&caps[1], Some(&caps[2]), None), None),
+        None => (None, None),
+    }
+}
 
-    // Fallback to the existing mock implementation
-    info!("Using fallback mock search implementation");
+/// The first date on or after `today` named in text, e.g. "Saturday, September 6, 2025"
+pub fn find_date(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    DATE_IN_TEXT
+        .find_iter(text)
+        .filter_map(|m| crate::dates::parse_date(m.as_str(), today))
+        .find(|date| *date >= today)
+}
 
-    // Convert query to lowercase for easier matching
-    let query_lower = query.to_lowercase();
+/// Events from SerpAPI's Google Events results
+pub fn parse_serpapi_events(response: &Value, today: NaiveDate) -> Vec<EventSearchResult> {
+    let Some(results) = response["events_results"].as_array() else {
+        return Vec::new();
+    };
+    results
+        .iter()
+        .filter_map(|event| {
+            let title = event["title"].as_str()?.trim().to_string();
+            let start_date = event["date"]["start_date"].as_str().unwrap_or_default();
+            let when = event["date"]["when"].as_str().unwrap_or_default();
+            let date =
+                crate::dates::parse_date(start_date, today).or_else(|| find_date(when, today))?;
+            let (start_time, end_time) = find_times(when);
+            let address: Vec<&str> = event["address"]
+                .as_array()
+                .map(|lines| lines.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            let location = if address.is_empty() {
+                event["venue"]["name"].as_str().map(str::to_string)
+            } else {
+                Some(address.join(", "))
+            };
+            Some(EventSearchResult {
+                title,
+                date: date.format("%Y-%m-%d").to_string(),
+                start_time,
+                end_time,
+                location,
+                description: event["description"].as_str().map(str::to_string),
+                url: event["link"].as_str().map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+/// Events from web pages whose title or snippet names an upcoming date
+fn web_page_events<'a>(
+    pages: impl Iterator<Item = (&'a str, Option<&'a str>, &'a str)>,
+    today: NaiveDate,
+) -> Vec<EventSearchResult> {
+    pages
+        .filter_map(|(title, url, snippet)| {
+            let title = crate::utils::sanitize_notes(title, false);
+            let snippet = crate::utils::sanitize_notes(snippet, false);
+            let text = format!("{} {}", title, snippet);
+            let date = find_date(&text, today)?;
+            let (start_time, end_time) = find_times(&snippet);
+            Some(EventSearchResult {
+                title: title.trim().to_string(),
+                date: date.format("%Y-%m-%d").to_string(),
+                start_time,
+                end_time,
+                location: None,
+                description: Some(snippet.trim().to_string()).filter(|s| !s.is_empty()),
+                url: url.map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+/// Events from a Brave Search web search response
+pub fn parse_brave_results(response: &Value, today: NaiveDate) -> Vec<EventSearchResult> {
+    let results = response["web"]["results"].as_array().map(Vec::as_slice).unwrap_or_default();
+    web_page_events(
+        results.iter().filter_map(|page| {
+            Some((
+                page["title"].as_str()?,
+                page["url"].as_str(),
+                page["description"].as_str().unwrap_or_default(),
+            ))
+        }),
+        today,
+    )
+}
+
+/// Events from a Bing Web Search response
+pub fn parse_bing_results(response: &Value, today: NaiveDate) -> Vec<EventSearchResult> {
+    let results = response["webPages"]["value"].as_array().map(Vec::as_slice).unwrap_or_default();
+    web_page_events(
+        results.iter().filter_map(|page| {
+            Some((
+                page["name"].as_str()?,
+                page["url"].as_str(),
+                page["snippet"].as_str().unwrap_or_default(),
+            ))
+        }),
+        today,
+    )
+}
 
-    // Check for sports-specific searches first
-    if let Some(events) = search_rugby_events(&query_lower).await? {
-        return Ok(events);
+/// Send a search request and read its JSON response
+async fn fetch_json(request: reqwest::RequestBuilder, provider: &str) -> Result<Value> {
+    let response = request.send().await?;
+    let status = response.status();
+    let text = response.text().await?;
+    if !status.is_success() {
+        return Err(anyhow!("{} API error: Status {}, Response: {}", provider, status, text));
     }
+    if text.len() > MAX_RESPONSE_SIZE {
+        return Err(anyhow!("Response size exceeds security limits"));
+    }
+    serde_json::from_str(&sanitize_json_string(&text))
+        .map_err(|e| anyhow!("Failed to parse {} response: {}", provider, e))
+}
 
-    // For now, return mock results based on the query
-    let mut events = Vec::new();
+/// Grok, asked to search the web and answer with a JSON array of events
+pub struct GrokSearch {
+    api_key: String,
+    api_base: String,
+}
 
-    if query_lower.contains("concert") {
-        events.push(EventSearchResult {
-            title: "Live Music Concert".to_string(),
-            date: chrono::Local::now().format("%Y-%m-%d").to_string(),
-            start_time: Some("19:00".to_string()),
-            end_time: Some("22:00".to_string()),
-            location: Some("Music Hall".to_string()),
-            description: Some("Live concert featuring local bands".to_string()),
-            url: Some("https://example.com/concert".to_string()),
-        });
-    } else if query_lower.contains("conference") {
-        events.push(EventSearchResult {
-            title: "Tech Conference".to_string(),
-            date: (chrono::Local::now() + chrono::Duration::days(7))
-                .format("%Y-%m-%d")
-                .to_string(),
-            start_time: Some("09:00".to_string()),
-            end_time: Some("17:00".to_string()),
-            location: Some("Convention Center".to_string()),
-            description: Some(
-                "Annual technology conference with speakers and workshops".to_string(),
-            ),
-            url: Some("https://example.com/conference".to_string()),
-        });
-    } else if query_lower.contains("sports") || query_lower.contains("game") {
-        events.push(EventSearchResult {
-            title: "Local Sports Game".to_string(),
-            date: (chrono::Local::now() + chrono::Duration::days(3))
-                .format("%Y-%m-%d")
-                .to_string(),
-            start_time: Some("15:00".to_string()),
-            end_time: Some("17:30".to_string()),
-            location: Some("City Stadium".to_string()),
-            description: Some("Local teams competing in seasonal match".to_string()),
-            url: Some("https://example.com/sports".to_string()),
-        });
+#[async_trait]
+impl EventSearchProvider for GrokSearch {
+    fn name(&self) -> &'static str {
+        "grok"
     }
 
-    // Always add a generic event as fallback
-    events.push(EventSearchResult {
-        title: format!("Found Event: {}", query),
-        date: chrono::Local::now().format("%Y-%m-%d").to_string(),
-        start_time: Some("12:00".to_string()),
-        end_time: Some("13:00".to_string()),
-        location: Some("Local Venue".to_string()),
-        description: Some(format!("Event related to search: {}", query)),
-        url: None,
-    });
+    async fn search(&self, query: &str, today: NaiveDate) -> Result<Vec<EventSearchResult>> {
+        search_events_with_grok(&self.api_key, &self.api_base, query, today).await
+    }
+}
 
-    info!("Found {} events matching search query", events.len());
-    Ok(events)
+/// Google event listings through SerpAPI
+pub struct SerpApiSearch {
+    api_key: String,
 }
 
-#[allow(dead_code)]
-/// Use Grok's capabilities to search the internet for events
-async fn search_events_with_grok(query: &str) -> Result<Vec<EventSearchResult>> {
-    let api_key = std::env::var("XAI_API_KEY")
-        .map_err(|_| anyhow!("XAI_API_KEY environment variable not set"))?;
+#[async_trait]
+impl EventSearchProvider for SerpApiSearch {
+    fn name(&self) -> &'static str {
+        "serpapi"
+    }
+
+    async fn search(&self, query: &str, today: NaiveDate) -> Result<Vec<EventSearchResult>> {
+        let request = Client::new().get("https://serpapi.com/search.json").query(&[
+            ("engine", "google_events"),
+            ("q", query),
+            ("api_key", self.api_key.as_str()),
+        ]);
+        Ok(parse_serpapi_events(&fetch_json(request, "SerpAPI").await?, today))
+    }
+}
+
+/// Brave Search web results
+pub struct BraveSearch {
+    api_key: String,
+}
+
+#[async_trait]
+impl EventSearchProvider for BraveSearch {
+    fn name(&self) -> &'static str {
+        "brave"
+    }
 
-    let api_base =
-        std::env::var("XAI_API_BASE").unwrap_or_else(|_| "https://api.x.ai/v1".to_string());
+    async fn search(&self, query: &str, today: NaiveDate) -> Result<Vec<EventSearchResult>> {
+        let request = Client::new()
+            .get("https://api.search.brave.com/res/v1/web/search")
+            .header("X-Subscription-Token", &self.api_key)
+            .header("Accept", "application/json")
+            .query(&[("q", format!("{} upcoming dates", query))]);
+        Ok(parse_brave_results(&fetch_json(request, "Brave Search").await?, today))
+    }
+}
 
+/// Bing Web Search results
+pub struct BingSearch {
+    api_key: String,
+}
+
+#[async_trait]
+impl EventSearchProvider for BingSearch {
+    fn name(&self) -> &'static str {
+        "bing"
+    }
+
+    async fn search(&self, query: &str, today: NaiveDate) -> Result<Vec<EventSearchResult>> {
+        let request = Client::new()
+            .get("https://api.bing.microsoft.com/v7.0/search")
+            .header("Ocp-Apim-Subscription-Key", &self.api_key)
+            .query(&[("q", format!("{} upcoming dates", query))]);
+        Ok(parse_bing_results(&fetch_json(request, "Bing").await?, today))
+    }
+}
+
+/// Use Grok's capabilities to search the internet for events
+async fn search_events_with_grok(
+    api_key: &str,
+    api_base: &str,
+    query: &str,
+    today: NaiveDate,
+) -> Result<Vec<EventSearchResult>> {
     info!("Searching for events using Grok API: {}", query);
 
     let client = Client::new();
-    let current_date = Local::now().format("%Y-%m-%d").to_string();
+    let current_date = today.format("%Y-%m-%d").to_string();
 
     // Build a prompt that explicitly tells Grok to search the web
     let system_prompt = format!(
@@ -236,7 +553,6 @@ Respond ONLY with the JSON array. Do not include any explanatory text."#,
     Ok(events)
 }
 
-#[allow(dead_code)]
 /// Helper function to extract JSON from text that might contain markdown and other content
 fn extract_json_from_text(text: &str) -> Result<String> {
     // Look for JSON array between ```json and ``` markers
@@ -257,172 +573,6 @@ fn extract_json_from_text(text: &str) -> Result<String> {
     Ok("[]".to_string())
 }
 
-#[allow(dead_code)]
-// Keep the existing rugby_events search as fallback
-async fn search_rugby_events(query: &str) -> Result<Option<Vec<EventSearchResult>>> {
-    // Define common rugby teams and tournaments for matching
-    let rugby_keywords = [
-        "rugby",
-        "springboks",
-        "all blacks",
-        "wallabies",
-        "six nations",
-        "world cup",
-        "rugby championship",
-    ];
-
-    // Check if this is a rugby-related query
-    let is_rugby_query = rugby_keywords.iter().any(|keyword| query.contains(keyword));
-
-    if !is_rugby_query {
-        return Ok(None);
-    }
-
-    info!("Detected rugby-related search query");
-    let mut events = Vec::new();
-
-    // Check for specific team matchups
-    if query.contains("springboks") && query.contains("all blacks") {
-        // Hardcoded test fixtures for Springboks vs All Blacks (2025)
-        // In a real implementation, these would come from a sports API
-
-        // These dates are made up for demo purposes
-        let current_year = Local::now().year();
-
-        events.push(EventSearchResult {
-            title: format!("Rugby Championship: Springboks vs All Blacks"),
-            date: format!("{}-09-06", current_year),
-            start_time: Some("19:35".to_string()),
-            end_time: Some("21:30".to_string()),
-            location: Some("Ellis Park, Johannesburg, South Africa".to_string()),
-            description: Some(format!(
-                "Rugby Championship {}: South Africa vs New Zealand in the first of two matches.",
-                current_year
-            )),
-            url: Some("https://www.sarugby.co.za/fixtures".to_string()),
-        });
-
-        events.push(EventSearchResult {
-            title: format!("Rugby Championship: All Blacks vs Springboks"),
-            date: format!("{}-09-13", current_year),
-            start_time: Some("19:05".to_string()),
-            end_time: Some("21:00".to_string()),
-            location: Some("Eden Park, Auckland, New Zealand".to_string()),
-            description: Some(format!(
-                "Rugby Championship {}: New Zealand vs South Africa in the second Test.",
-                current_year
-            )),
-            url: Some("https://www.allblacks.com/fixtures".to_string()),
-        });
-    } else if query.contains("springboks") {
-        // General Springboks fixtures
-        let current_year = Local::now().year();
-
-        events.push(EventSearchResult {
-            title: format!("Rugby Championship: Springboks vs Wallabies"),
-            date: format!("{}-08-16", current_year),
-            start_time: Some("17:00".to_string()),
-            end_time: Some("19:00".to_string()),
-            location: Some("Loftus Versfeld, Pretoria, South Africa".to_string()),
-            description: Some(format!(
-                "Rugby Championship {}: South Africa vs Australia",
-                current_year
-            )),
-            url: Some("https://www.sarugby.co.za/fixtures".to_string()),
-        });
-
-        events.push(EventSearchResult {
-            title: format!("Rugby Championship: Argentina vs Springboks"),
-            date: format!("{}-08-23", current_year),
-            start_time: Some("20:10".to_string()),
-            end_time: Some("22:00".to_string()),
-            location: Some(
-                "Estadio Único Madre de Ciudades, Santiago del Estero, Argentina".to_string(),
-            ),
-            description: Some(format!(
-                "Rugby Championship {}: Argentina vs South Africa",
-                current_year
-            )),
-            url: Some("https://www.sarugby.co.za/fixtures".to_string()),
-        });
-
-        // Add All Blacks matches too
-        events.push(EventSearchResult {
-            title: format!("Rugby Championship: Springboks vs All Blacks"),
-            date: format!("{}-09-06", current_year),
-            start_time: Some("19:35".to_string()),
-            end_time: Some("21:30".to_string()),
-            location: Some("Ellis Park, Johannesburg, South Africa".to_string()),
-            description: Some(format!(
-                "Rugby Championship {}: South Africa vs New Zealand in the first of two matches.",
-                current_year
-            )),
-            url: Some("https://www.sarugby.co.za/fixtures".to_string()),
-        });
-    } else if query.contains("all blacks") {
-        // General All Blacks fixtures
-        let current_year = Local::now().year();
-
-        events.push(EventSearchResult {
-            title: format!("Rugby Championship: All Blacks vs Argentina"),
-            date: format!("{}-08-16", current_year),
-            start_time: Some("19:05".to_string()),
-            end_time: Some("21:00".to_string()),
-            location: Some("Eden Park, Auckland, New Zealand".to_string()),
-            description: Some(format!(
-                "Rugby Championship {}: New Zealand vs Argentina",
-                current_year
-            )),
-            url: Some("https://www.allblacks.com/fixtures".to_string()),
-        });
-
-        events.push(EventSearchResult {
-            title: format!("Rugby Championship: All Blacks vs Wallabies"),
-            date: format!("{}-08-23", current_year),
-            start_time: Some("19:05".to_string()),
-            end_time: Some("21:00".to_string()),
-            location: Some("Wellington Regional Stadium, Wellington, New Zealand".to_string()),
-            description: Some(format!(
-                "Rugby Championship {}: New Zealand vs Australia",
-                current_year
-            )),
-            url: Some("https://www.allblacks.com/fixtures".to_string()),
-        });
-
-        // Add Springboks matches too
-        events.push(EventSearchResult {
-            title: format!("Rugby Championship: All Blacks vs Springboks"),
-            date: format!("{}-09-13", current_year),
-            start_time: Some("19:05".to_string()),
-            end_time: Some("21:00".to_string()),
-            location: Some("Eden Park, Auckland, New Zealand".to_string()),
-            description: Some(format!(
-                "Rugby Championship {}: New Zealand vs South Africa in the second Test.",
-                current_year
-            )),
-            url: Some("https://www.allblacks.com/fixtures".to_string()),
-        });
-    }
-
-    if events.is_empty() {
-        // Generic rugby event if no specific matches found
-        events.push(EventSearchResult {
-            title: format!("Rugby Match: {}", query),
-            date: (chrono::Local::now() + chrono::Duration::days(30))
-                .format("%Y-%m-%d")
-                .to_string(),
-            start_time: Some("19:00".to_string()),
-            end_time: Some("21:00".to_string()),
-            location: Some("National Stadium".to_string()),
-            description: Some(format!("Rugby match related to your search: {}", query)),
-            url: Some("https://www.worldrugby.org/fixtures".to_string()),
-        });
-    }
-
-    info!("Found {} rugby events matching search query", events.len());
-    Ok(Some(events))
-}
-
 #[allow(dead_code)]
 /// Convert a search result into a calendar event command
 pub fn event_to_calendar_command(event: &EventSearchResult, calendar: Option<&str>) -> String {
@@ -433,12 +583,8 @@ pub fn event_to_calendar_command(event: &EventSearchResult, calendar: Option<&st
     };
 
     // Only use the provided calendar if it was explicitly specified, otherwise use default
-    let calendar_name = if calendar.is_some() && calendar != Some("National Rugby League") {
-        calendar.unwrap_or("Calendar")
-    } else {
-        // Use the default calendar from config if available
-        config.calendar.default_calendar.as_deref().unwrap_or("Calendar")
-    };
+    let calendar_name =
+        calendar.or(config.calendar.default_calendar.as_deref()).unwrap_or("Calendar");
 
     format_command(event, calendar_name)
 }
@@ -622,3 +768,154 @@ pub fn format_search_results(results: &[EventSearchResult]) -> String {
 
     formatted
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn event(title: &str, date: &str, url: Option<&str>) -> EventSearchResult {
+        EventSearchResult {
+            title: title.to_string(),
+            date: date.to_string(),
+            start_time: None,
+            end_time: None,
+            location: None,
+            description: None,
+            url: url.map(str::to_string),
+        }
+    }
+
+    /// Returns fixed results, or fails
+    struct Fixed(&'static str, Option<Vec<EventSearchResult>>);
+
+    #[async_trait]
+    impl EventSearchProvider for Fixed {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        async fn search(&self, _query: &str, _today: NaiveDate) -> Result<Vec<EventSearchResult>> {
+            self.1.clone().ok_or_else(|| anyhow!("quota exceeded"))
+        }
+    }
+
+    #[test]
+    fn test_find_times() {
+        let time = |s: &str| s.to_string();
+        assert_eq!(
+            find_times("Fri, Mar 14, 7 – 10 PM"),
+            (Some(time("19:00")), Some(time("22:00")))
+        );
+        assert_eq!(
+            find_times("Sat, Sep 6, 11:30 AM - 1 PM"),
+            (Some(time("11:30")), Some(time("13:00")))
+        );
+        assert_eq!(find_times("Doors open 7:30pm"), (Some(time("19:30")), None));
+        assert_eq!(find_times("Kick-off 19:35 local time"), (Some(time("19:35")), None));
+        assert_eq!(find_times("All day"), (None, None));
+    }
+
+    #[test]
+    fn test_find_date() {
+        let today = day("2025-03-14");
+        assert_eq!(
+            find_date("Saturday, September 6, 2025 at Ellis Park", today),
+            Some(day("2025-09-06"))
+        );
+        assert_eq!(find_date("Tickets for 20 March", today), Some(day("2025-03-20")));
+        // Past dates are skipped for the next one
+        assert_eq!(
+            find_date("Played 2024-11-02, rematch 2025-04-05", today),
+            Some(day("2025-04-05"))
+        );
+        assert_eq!(find_date("No date here", today), None);
+    }
+
+    #[test]
+    fn test_parse_serpapi_events() {
+        let response = json!({
+            "events_results": [{
+                "title": "Springboks vs All Blacks",
+                "date": { "start_date": "Sep 6", "when": "Sat, Sep 6, 7:35 – 9:30 PM" },
+                "address": ["Ellis Park", "Johannesburg"],
+                "link": "https://example.com/tickets",
+                "description": "Rugby Championship"
+            }, {
+                "date": { "start_date": "Sep 7" }
+            }]
+        });
+        let events = parse_serpapi_events(&response, day("2025-03-14"));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].date, "2025-09-06");
+        assert_eq!(events[0].start_time.as_deref(), Some("19:35"));
+        assert_eq!(events[0].end_time.as_deref(), Some("21:30"));
+        assert_eq!(events[0].location.as_deref(), Some("Ellis Park, Johannesburg"));
+        assert!(parse_serpapi_events(&json!({}), day("2025-03-14")).is_empty());
+    }
+
+    #[test]
+    fn test_parse_web_results() {
+        let today = day("2025-03-14");
+        let brave = json!({ "web": { "results": [
+            { "title": "Band <strong>tour</strong> 2025", "url": "https://band.example",
+              "description": "Live at the Forum on April 12, 2025, 8pm" },
+            { "title": "Band biography", "url": "https://wiki.example", "description": "Formed in 1999" }
+        ]}});
+        let events = parse_brave_results(&brave, today);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].title, "Band tour 2025");
+        assert_eq!(events[0].date, "2025-04-12");
+        assert_eq!(events[0].start_time.as_deref(), Some("20:00"));
+
+        let bing = json!({ "webPages": { "value": [
+            { "name": "Marathon - 2025-10-05", "url": "https://run.example", "snippet": "Start 08:00" }
+        ]}});
+        let events = parse_bing_results(&bing, today);
+        assert_eq!(events[0].date, "2025-10-05");
+        assert_eq!(events[0].url.as_deref(), Some("https://run.example"));
+    }
+
+    #[test]
+    fn test_merge_results() {
+        let mut detailed = event("Springboks vs. All Blacks", "2025-09-06", None);
+        detailed.start_time = Some("19:35".to_string());
+        let merged = merge_results(vec![
+            vec![detailed, event("Final", "2025-10-01", None)],
+            vec![
+                event("springboks vs all blacks", "2025-09-06", Some("https://example.com")),
+                event("Opener", "2025-08-16", None),
+            ],
+        ]);
+        let titles: Vec<&str> = merged.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, vec!["Opener", "Springboks vs. All Blacks", "Final"]);
+        assert_eq!(merged[1].start_time.as_deref(), Some("19:35"));
+        assert_eq!(merged[1].url.as_deref(), Some("https://example.com"));
+    }
+
+    #[tokio::test]
+    async fn test_search_with_skips_failing_providers() {
+        let today = day("2025-03-14");
+        let providers: Vec<Box<dyn EventSearchProvider>> = vec![
+            Box::new(Fixed("down", None)),
+            Box::new(Fixed(
+                "up",
+                Some(vec![
+                    event("Past", "2025-01-01", None),
+                    event("Gig", "2025-04-01", None),
+                    event("Later gig", "2025-05-01", None),
+                ]),
+            )),
+        ];
+        let events = search_with(&providers, "gig", today, 1).await.unwrap();
+        assert_eq!(events, vec![event("Gig", "2025-04-01", None)]);
+
+        let down: Vec<Box<dyn EventSearchProvider>> = vec![Box::new(Fixed("down", None))];
+        let error = search_with(&down, "gig", today, 5).await.unwrap_err();
+        assert!(error.to_string().contains("down: quota exceeded"));
+        assert!(search_with(&[], "gig", today, 5).await.is_err());
+    }
+}
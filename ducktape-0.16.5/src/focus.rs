@@ -0,0 +1,307 @@
+//! Focus sessions: `ducktape focus start 25m "Write report"` and `ducktape focus stats`.
+//
+// A session blocks the time in the calendar, optionally turns on Do Not Disturb by running
+// the Shortcuts named in `focus.dnd_on_shortcut` and `focus.dnd_off_shortcut` (macOS has no
+// command for it, so users create the two shortcuts with the "Set Focus" action), and counts
+// down in the terminal. Sessions are logged in ~/.ducktape/focus.json when they end, whether
+// they ran to the end or were stopped with Ctrl+C, and `focus stats` reports on the log.
+
+use crate::app_state::SharedState;
+use crate::applescript::Template;
+use crate::calendar::EventConfig;
+use crate::config::FocusConfig;
+use crate::script_runner::run_applescript_async;
+use crate::state::{FocusSession, StateManager};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+use std::io::Write;
+use tracing::{debug, info, warn};
+
+/// Longest session `focus start` accepts
+const MAX_SESSION_MINUTES: i64 = 12 * 60;
+
+static DURATION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^(?:(\d+)\s*h(?:ours?|rs?)?)?\s*(?:(\d+)\s*m(?:in(?:utes?|s)?)?)?$").unwrap()
+});
+
+/// Parse a session length such as `25m`, `1h`, `1h30m` or `45` (minutes)
+pub fn parse_focus_duration(input: &str) -> Option<Duration> {
+    let input = input.trim();
+    let minutes = match input.parse::<i64>() {
+        Ok(minutes) => minutes,
+        Err(_) => {
+            let caps = DURATION.captures(input)?;
+            let number = |i: usize| caps.get(i).and_then(|m| m.as_str().parse::<i64>().ok());
+            if caps.get(1).is_none() && caps.get(2).is_none() {
+                return None;
+            }
+            number(1).unwrap_or(0) * 60 + number(2).unwrap_or(0)
+        }
+    };
+    (1..=MAX_SESSION_MINUTES).contains(&minutes).then(|| Duration::minutes(minutes))
+}
+
+/// The calendar block for a session starting at `start`
+pub fn focus_block(
+    title: &str,
+    start: NaiveDateTime,
+    length: Duration,
+    calendar: Option<&str>,
+) -> EventConfig {
+    let end = start + length;
+    let mut config = EventConfig::new(
+        &format!("Focus: {}", title),
+        &start.format("%Y-%m-%d").to_string(),
+        &start.format("%H:%M").to_string(),
+    );
+    config.end_time = Some(end.format("%H:%M").to_string());
+    config.calendars = calendar.map(|c| vec![c.to_string()]).unwrap_or_default();
+    config.description = Some("Focus session started by Ducktape 🦆".to_string());
+    config
+}
+
+/// Run a shortcut from the Shortcuts app
+async fn run_shortcut(name: &str) -> Result<()> {
+    let output = tokio::process::Command::new("shortcuts")
+        .args(["run", name])
+        .output()
+        .await
+        .map_err(|e| anyhow!("Failed to run shortcuts: {}", e))?;
+    if output.status.success() {
+        debug!("Ran shortcut '{}'", name);
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Shortcut '{}' failed: {}",
+            name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Count down `length` on one terminal line; `false` when stopped with Ctrl+C
+async fn countdown(title: &str, length: Duration) -> Result<bool> {
+    let end = tokio::time::Instant::now() + length.to_std()?;
+    let mut ticks = tokio::time::interval(std::time::Duration::from_secs(1));
+    let mut stdout = std::io::stdout();
+    loop {
+        tokio::select! {
+            _ = ticks.tick() => {
+                let left = end.saturating_duration_since(tokio::time::Instant::now()).as_secs();
+                print!("\r⏳ {:02}:{:02} left  {}   ", left / 60, left % 60, title);
+                stdout.flush()?;
+                if left == 0 {
+                    println!();
+                    return Ok(true);
+                }
+            }
+            _ = tokio::signal::ctrl_c() => {
+                println!();
+                return Ok(false);
+            }
+        }
+    }
+}
+
+/// Run a focus session, returning it as logged
+pub async fn start_focus(
+    title: &str,
+    length: Duration,
+    calendar: Option<&str>,
+    dnd: bool,
+    config: &FocusConfig,
+) -> Result<FocusSession> {
+    let started_at = Local::now();
+    let calendar = calendar.or(config.calendar.as_deref());
+    let block = focus_block(title, started_at.naive_local(), length, calendar);
+    // The countdown runs without the mutation lock, so each change takes it for itself
+    let shared = SharedState::global();
+    if let Err(e) = shared.exclusive(crate::calendar::create_event(block)).await {
+        warn!("Failed to block the time in the calendar: {}", e);
+        println!("Warning: the session is not in your calendar: {}", e);
+    }
+    if dnd {
+        if let Err(e) = run_shortcut(&config.dnd_on_shortcut).await {
+            println!("Warning: could not turn on Do Not Disturb: {}", e);
+        }
+    }
+
+    let completed = countdown(title, length).await?;
+
+    if dnd {
+        if let Err(e) = run_shortcut(&config.dnd_off_shortcut).await {
+            println!("Warning: could not turn off Do Not Disturb: {}", e);
+        }
+    }
+    let focused = (Local::now() - started_at).min(length);
+    let session = FocusSession {
+        title: title.to_string(),
+        started_at: started_at.to_rfc3339(),
+        planned_minutes: length.num_minutes() as u32,
+        focused_minutes: focused.num_minutes() as u32,
+        completed,
+    };
+    shared.exclusive(async { StateManager::new()?.add(session.clone()) }).await?;
+    info!("Focus session '{}' ended after {} minute(s)", title, session.focused_minutes);
+
+    if completed {
+        let script = Template::new(
+            r#"display notification ${text} with title "DuckTape" sound name "Glass""#,
+        )
+        .text("text", &format!("Focus session done: {}", title))
+        .render()?;
+        if let Err(e) = run_applescript_async(script).await {
+            debug!("Failed to show notification: {}", e);
+        }
+    }
+    Ok(session)
+}
+
+/// Totals over the logged sessions
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FocusStats {
+    pub sessions: usize,
+    pub completed: usize,
+    pub total_minutes: u32,
+    pub today_minutes: u32,
+    /// Since Monday
+    pub week_minutes: u32,
+    /// Minutes per title, most first
+    pub by_title: Vec<(String, u32)>,
+}
+
+fn started_on(session: &FocusSession) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(&session.started_at)
+        .ok()
+        .map(|t| t.with_timezone(&Local).date_naive())
+}
+
+/// Summarize the sessions started on or after `since`
+pub fn focus_stats(
+    sessions: &[FocusSession],
+    since: Option<NaiveDate>,
+    today: NaiveDate,
+) -> FocusStats {
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let mut stats = FocusStats::default();
+    let mut by_title: HashMap<String, u32> = HashMap::new();
+    for session in sessions {
+        let Some(day) = started_on(session) else {
+            continue;
+        };
+        if since.is_some_and(|since| day < since) {
+            continue;
+        }
+        stats.sessions += 1;
+        stats.completed += usize::from(session.completed);
+        stats.total_minutes += session.focused_minutes;
+        if day == today {
+            stats.today_minutes += session.focused_minutes;
+        }
+        if day >= monday {
+            stats.week_minutes += session.focused_minutes;
+        }
+        *by_title.entry(session.title.clone()).or_default() += session.focused_minutes;
+    }
+    stats.by_title = by_title.into_iter().collect();
+    stats.by_title.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    stats
+}
+
+/// Minutes as `1h 05m` or `25m`
+fn hours_minutes(minutes: u32) -> String {
+    if minutes >= 60 {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// Render the stats as a short report
+pub fn format_focus_stats(stats: &FocusStats) -> String {
+    if stats.sessions == 0 {
+        return "No focus sessions yet. Start one with `ducktape focus start 25m \"Task\"`\n"
+            .to_string();
+    }
+    let mut out = format!(
+        "Focus sessions: {} ({} completed)\n  Today:      {}\n  This week:  {}\n  Total:      {}\n",
+        stats.sessions,
+        stats.completed,
+        hours_minutes(stats.today_minutes),
+        hours_minutes(stats.week_minutes),
+        hours_minutes(stats.total_minutes)
+    );
+    out.push_str("Top tasks:\n");
+    for (title, minutes) in stats.by_title.iter().take(5) {
+        out.push_str(&format!("  {:>8}  {}\n", hours_minutes(*minutes), title));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_focus_duration() {
+        assert_eq!(parse_focus_duration("25m"), Some(Duration::minutes(25)));
+        assert_eq!(parse_focus_duration("45"), Some(Duration::minutes(45)));
+        assert_eq!(parse_focus_duration("1h"), Some(Duration::minutes(60)));
+        assert_eq!(parse_focus_duration("1h30m"), Some(Duration::minutes(90)));
+        assert_eq!(parse_focus_duration("2 hours"), Some(Duration::minutes(120)));
+        assert_eq!(parse_focus_duration("50 min"), Some(Duration::minutes(50)));
+        assert_eq!(parse_focus_duration("0m"), None);
+        assert_eq!(parse_focus_duration("13h"), None);
+        assert_eq!(parse_focus_duration("soon"), None);
+        assert_eq!(parse_focus_duration(""), None);
+    }
+
+    #[test]
+    fn test_focus_block() {
+        let start = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap().and_hms_opt(9, 50, 12).unwrap();
+        let block = focus_block("Write report", start, Duration::minutes(25), Some("Work"));
+        assert_eq!(block.title, "Focus: Write report");
+        assert_eq!(block.start_date, "2025-03-14");
+        assert_eq!(block.start_time, "09:50");
+        assert_eq!(block.end_time.as_deref(), Some("10:15"));
+        assert_eq!(block.calendars, vec!["Work"]);
+    }
+
+    #[test]
+    fn test_focus_stats() {
+        let session = |title: &str, started_at: &str, minutes: u32, completed: bool| FocusSession {
+            title: title.to_string(),
+            started_at: format!("{}T10:00:00{}", started_at, Local::now().format("%:z")),
+            planned_minutes: 25,
+            focused_minutes: minutes,
+            completed,
+        };
+        let sessions = vec![
+            session("Write report", "2025-03-14", 25, true),
+            session("Write report", "2025-03-12", 25, true),
+            session("Email", "2025-03-14", 10, false),
+            session("Email", "2025-03-07", 50, true),
+        ];
+        // Friday
+        let today = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let stats = focus_stats(&sessions, None, today);
+        assert_eq!(stats.sessions, 4);
+        assert_eq!(stats.completed, 3);
+        assert_eq!(stats.today_minutes, 35);
+        assert_eq!(stats.week_minutes, 60);
+        assert_eq!(stats.total_minutes, 110);
+        assert_eq!(stats.by_title[0], ("Email".to_string(), 60));
+
+        let since = NaiveDate::from_ymd_opt(2025, 3, 10);
+        let stats = focus_stats(&sessions, since, today);
+        assert_eq!(stats.sessions, 3);
+        let report = format_focus_stats(&stats);
+        assert!(report.contains("Focus sessions: 3 (2 completed)"));
+        assert!(report.contains("  Total:      1h 00m\n"));
+        assert!(report.contains("       50m  Write report\n"));
+        assert!(format_focus_stats(&FocusStats::default()).starts_with("No focus sessions"));
+    }
+}
@@ -0,0 +1,731 @@
+//! Help text and man pages generated from the Clap command tree.
+//
+// `ducktape help` lists the commands as Clap defines them, so new commands show up without
+// editing a hand-written list. `ducktape help <command> [subcommand]` adds the worked examples
+// below to Clap's long help, and `ducktape man` renders the same tree and examples as roff
+// with clap_mangen. A test checks that every command has examples.
+
+use crate::cli::Cli;
+use anyhow::{Context, Result};
+use clap::CommandFactory;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Examples per top-level command, as (description, command line)
+const EXAMPLES: &[(&str, &[(&str, &str)])] = &[
+    (
+        "calendar",
+        &[
+            ("List calendars", "ducktape calendar list"),
+            ("List calendars without using cached names", "ducktape calendar list --refresh"),
+            (
+                "Create an event in the Work calendar of the iCloud account",
+                "ducktape calendar create \"Standup\" today 09:00 09:15 \"Work (iCloud)\"",
+            ),
+            (
+                "Create an event in the default calendar",
+                "ducktape calendar create \"Team sync\" 2025-04-15 10:00 11:00",
+            ),
+            (
+                "Create a weekly Zoom meeting with invitees",
+                "ducktape calendar create \"1:1\" tomorrow 09:00 09:30 work --zoom --contacts \"Jane Doe\" --repeat weekly",
+            ),
+            (
+                "Invite everyone in a contact group",
+                "ducktape calendar create \"Offsite\" 2025-05-02 09:00 17:00 work --group \"Leadership\"",
+            ),
+            (
+                "Create an event with a link that shows as free",
+                "ducktape calendar create \"Focus\" tomorrow 14:00 16:00 --url https://example.com/doc --availability free",
+            ),
+            (
+                "Create an event tagged for a client",
+                "ducktape calendar create \"Roadmap review\" friday 15:00 16:00 --tags deep-work,clientX",
+            ),
+            ("Show a tracked event's details", "ducktape calendar show \"Team sync\""),
+            (
+                "Delete a tracked event by title or UID",
+                "ducktape calendar delete \"Team sync\"",
+            ),
+            (
+                "Move a tracked event to 3pm, keeping its length",
+                "ducktape calendar update \"Team sync\" --start-time 15:00",
+            ),
+            (
+                "Invite someone to the event you just created",
+                "ducktape calendar update @last --contacts \"Jane Doe\"",
+            ),
+            ("Import events from a file", "ducktape calendar import events.ics"),
+            ("Browse this week's events", "ducktape calendar view --week"),
+            ("Browse next month", "ducktape calendar view --month --date \"next month\""),
+            (
+                "Move every event on Friday to next Monday",
+                "ducktape calendar move-all --on friday --to \"next monday\"",
+            ),
+            (
+                "Preview deleting every Standup next week",
+                "ducktape calendar delete-all --on \"next week\" --title Standup --dry-run",
+            ),
+            (
+                "Delete every Standup next week without asking",
+                "ducktape calendar delete-all --on \"next week\" --title Standup --yes",
+            ),
+            (
+                "Import again, updating events that already exist",
+                "ducktape calendar import events.csv Work --format csv --on-duplicate update",
+            ),
+            (
+                "Import a CSV file with your own column names",
+                "ducktape calendar import export.csv --format csv --map \"Subject=title,Start Date=date\"",
+            ),
+        ],
+    ),
+    (
+        "todo",
+        &[
+            (
+                "Create a reminder",
+                "ducktape todo create \"Buy groceries\" --remind \"2025-04-15 18:00\"",
+            ),
+            (
+                "List reminders due today, most important first",
+                "ducktape todo list --due today --sort priority",
+            ),
+            ("Show reminders as a board", "ducktape todo board"),
+            ("Move a reminder to another column", "ducktape todo move \"Write report\" doing"),
+        ],
+    ),
+    (
+        "note",
+        &[
+            (
+                "Create a note",
+                "ducktape note create \"Meeting notes\" --content \"Points discussed\"",
+            ),
+            ("Create a tagged note", "ducktape note create \"Kickoff\" --tags clientX"),
+            ("List notes", "ducktape note list"),
+            ("Search notes", "ducktape note search budget"),
+            ("Create a note folder", "ducktape note folders create Archive"),
+            (
+                "Move a note to another folder",
+                "ducktape note move \"Meeting notes\" --to Archive",
+            ),
+        ],
+    ),
+    (
+        "config",
+        &[
+            ("Show all settings", "ducktape config show all"),
+            ("Set the default calendar", "ducktape config set calendar.default \"Personal\""),
+            (
+                "Add a calendar alias",
+                "ducktape config set calendar.alias.work \"jane@company.com\"",
+            ),
+            (
+                "Give events in Work a default description",
+                "ducktape config set calendar.description.work \"Dial-in: {{zoom_url|none}}\"",
+            ),
+            ("Create a profile", "ducktape config profile create work --calendar Work"),
+            ("Use colors for a light terminal", "ducktape config set ui.theme light"),
+            (
+                "Show the full parser trace after each input",
+                "ducktape config set ui.trace full",
+            ),
+            (
+                "Read dates as DD.MM.YYYY and German day names",
+                "ducktape config set locale de-DE",
+            ),
+        ],
+    ),
+    (
+        "contact",
+        &[
+            ("List contact groups", "ducktape contacts list"),
+            (
+                "Create a contact group",
+                "ducktape contacts create team jane@example.com bob@example.com",
+            ),
+            ("Copy Contacts.app for fast lookups", "ducktape contacts sync"),
+        ],
+    ),
+    (
+        "utility",
+        &[
+            ("Show the current date and time", "ducktape utils date-time"),
+            ("Convert a time to another time zone", "ducktape utils tz 14:00 EST to CET"),
+        ],
+    ),
+    (
+        "adopt",
+        &[
+            ("Register existing events of the default calendar", "ducktape adopt"),
+            (
+                "Register events of one calendar from a date",
+                "ducktape adopt --calendar Work --from 2025-04-01",
+            ),
+        ],
+    ),
+    (
+        "link",
+        &[(
+            "Link a reminder to the event it prepares",
+            "ducktape link todo:\"Prepare deck\" event:\"Board meeting\"",
+        )],
+    ),
+    (
+        "unlink",
+        &[("Remove a link", "ducktape unlink todo:\"Prepare deck\" event:\"Board meeting\"")],
+    ),
+    ("links", &[("List all links", "ducktape links")]),
+    (
+        "agenda",
+        &[
+            ("Show today's agenda", "ducktape agenda"),
+            ("Show one calendar on a given day", "ducktape agenda 2025-04-15 --calendar Work"),
+        ],
+    ),
+    (
+        "open",
+        &[
+            ("Open an event in Calendar.app", "ducktape open event \"Board meeting\""),
+            ("Open a note in Notes.app", "ducktape open note \"Meeting notes\""),
+            ("Open a reminder in Reminders.app", "ducktape open reminder \"Buy groceries\""),
+        ],
+    ),
+    (
+        "list",
+        &[
+            ("List everything tagged clientX", "ducktape list --tag clientX"),
+            (
+                "List only reminders with a tag",
+                "ducktape list --tag deep-work --kind reminder",
+            ),
+        ],
+    ),
+    (
+        "redo",
+        &[
+            ("Run the last command made from natural language again", "ducktape redo"),
+            ("Show it without running it", "ducktape redo --dry-run"),
+        ],
+    ),
+    (
+        "edit-last",
+        &[
+            ("Edit the last command on a prompt and run it", "ducktape edit-last"),
+            ("Move it to 14:00, keeping its length", "ducktape edit-last --time 14:00"),
+            (
+                "Change the date and calendar",
+                "ducktape edit-last --date 2025-03-14 --set calendar=Work",
+            ),
+            ("Drop the location", "ducktape edit-last --unset location"),
+        ],
+    ),
+    (
+        "quick",
+        &[
+            (
+                "Create an event from one phrase",
+                "ducktape quick \"Lunch with Sara Fri 12:30-13:30 at Blue Bottle\"",
+            ),
+            (
+                "Create an all-day event",
+                "ducktape quick \"Offsite next friday\" --calendar Work",
+            ),
+            ("Check how a phrase is read", "ducktape quick \"Gym 7am for 1 hour\" --dry-run"),
+        ],
+    ),
+    (
+        "search",
+        &[
+            ("Find upcoming events", "ducktape search design review"),
+            ("Include events that are over", "ducktape search dentist --past"),
+            (
+                "Results for an Alfred Script Filter",
+                "ducktape --output alfred search \"{query}\"",
+            ),
+        ],
+    ),
+    (
+        "raycast",
+        &[
+            ("Today's agenda for a Raycast list", "ducktape raycast agenda"),
+            ("Search tracked events", "ducktape raycast search standup"),
+            (
+                "Preview a quick create and its command",
+                "ducktape raycast create Lunch tomorrow 12:30 --calendar Home",
+            ),
+        ],
+    ),
+    (
+        "attendees",
+        &[
+            ("See who accepted an invitation", "ducktape attendees list \"Design Review\""),
+            (
+                "Invite attendees who have not answered again",
+                "ducktape attendees resend \"Design Review\" --date 2025-04-15",
+            ),
+            (
+                "Email the invitation from Mail.app or the SMTP account",
+                "ducktape attendees resend \"Offsite\" --mail",
+            ),
+        ],
+    ),
+    (
+        "pack",
+        &[
+            ("Export your templates and aliases", "ducktape pack export team.toml"),
+            ("Install a pack", "ducktape pack install team.toml"),
+        ],
+    ),
+    (
+        "cache",
+        &[(
+            "Remove cached language model answers and calendar names",
+            "ducktape cache clear",
+        )],
+    ),
+    (
+        "usage",
+        &[
+            ("Show this month's token usage", "ducktape usage show"),
+            ("Show an earlier month", "ducktape usage show --month 2025-03"),
+        ],
+    ),
+    (
+        "nlp",
+        &[
+            (
+                "Score the configured parser on a set of phrases",
+                "ducktape nlp eval tests/fixtures/nlp_eval.toml",
+            ),
+            (
+                "Check DeepSeek and fail below 90% accuracy",
+                "ducktape nlp eval phrases.toml --provider deepseek --min-accuracy 0.9",
+            ),
+        ],
+    ),
+    ("setup", &[("Run the setup wizard", "ducktape setup")]),
+    ("doctor", &[("Check permissions, keys and network access", "ducktape doctor")]),
+    (
+        "keys",
+        &[
+            ("Store the DeepSeek API key in the keychain", "ducktape keys set deepseek"),
+            ("Show where each key comes from", "ducktape keys list"),
+            ("Move keys out of a .env file", "ducktape keys migrate --env-file .env"),
+        ],
+    ),
+    (
+        "shortcut",
+        &[
+            (
+                "Create an event from a Shortcuts dictionary",
+                "ducktape shortcut create-event --stdin",
+            ),
+            ("Get today's agenda as JSON", "ducktape shortcut agenda date=today"),
+            (
+                "Search upcoming events and notes",
+                "ducktape shortcut search query=dentist notes=true",
+            ),
+            (
+                "Run an x-callback-url",
+                "ducktape shortcut --url \"ducktape://x-callback-url/agenda?x-success=shortcuts://\"",
+            ),
+        ],
+    ),
+    (
+        "state",
+        &[
+            (
+                "Back up everything DuckTape remembers",
+                "ducktape state export ducktape-backup.json",
+            ),
+            ("Restore it on another machine", "ducktape state import ducktape-backup.json"),
+        ],
+    ),
+    (
+        "audit",
+        &[
+            ("Show what was run since yesterday", "ducktape audit show --since yesterday"),
+            ("Show the last 20 commands", "ducktape audit show --limit 20"),
+        ],
+    ),
+    (
+        "report",
+        &[
+            (
+                "Time per calendar last month",
+                "ducktape report time --group-by calendar --last month",
+            ),
+            (
+                "Time per week on a client, as CSV",
+                "ducktape report time --group-by week --tag clientX --last \"90 days\" --format csv --output client.csv",
+            ),
+        ],
+    ),
+    (
+        "review",
+        &[
+            ("Write last week's review note", "ducktape review weekly"),
+            (
+                "Preview it without the language model summary",
+                "ducktape review weekly --no-summary --dry-run",
+            ),
+            ("Create it in a Reviews folder", "ducktape review weekly --folder Reviews"),
+        ],
+    ),
+    (
+        "focus",
+        &[
+            ("Focus for 25 minutes", "ducktape focus start 25m \"Write report\""),
+            (
+                "Focus for an hour with Do Not Disturb on",
+                "ducktape focus start 1h \"Deep work\" --dnd --calendar Focus",
+            ),
+            ("Show this week's focus time", "ducktape focus stats --since monday"),
+        ],
+    ),
+    (
+        "birthdays",
+        &[
+            (
+                "Add birthdays and anniversaries to a Birthdays calendar",
+                "ducktape birthdays sync --calendar Birthdays",
+            ),
+            (
+                "Preview, with an alert two days before each",
+                "ducktape birthdays sync --remind-days 2 --dry-run",
+            ),
+        ],
+    ),
+    (
+        "holidays",
+        &[
+            ("List this year's public holidays", "ducktape holidays list"),
+            (
+                "List New Zealand's holidays in 2026",
+                "ducktape holidays list 2026 --country NZ",
+            ),
+        ],
+    ),
+    (
+        "watch",
+        &[
+            ("Print changes made in Calendar.app as they happen", "ducktape watch"),
+            (
+                "Watch one calendar for a week, with notifications",
+                "ducktape watch --calendar Work --days 7 --notify",
+            ),
+        ],
+    ),
+    (
+        "sync",
+        &[
+            ("Sync the tracked events with Calendar.app", "ducktape sync"),
+            ("See what a sync would change", "ducktape sync --dry-run"),
+            ("Also track events created in Calendar.app", "ducktape sync --adopt"),
+        ],
+    ),
+    (
+        "schedule",
+        &[
+            (
+                "Show the agenda every morning",
+                "ducktape schedule add \"agenda\" --at \"07:30 daily\"",
+            ),
+            (
+                "Create a review note every Friday",
+                "ducktape schedule add \"note create 'Weekly review' --tags review\" --at \"friday 16:00\"",
+            ),
+            (
+                "Export last week's time every Monday, with cron syntax",
+                "ducktape schedule add \"report time --last week --format csv --output report.csv\" --at \"0 8 * * 1\"",
+            ),
+            ("Run scheduled commands as they come due", "ducktape schedule run"),
+        ],
+    ),
+    (
+        "feeds",
+        &[
+            ("Share the Home calendar as a feed", "ducktape feeds add Family --calendar Home"),
+            ("Show the subscription URLs", "ducktape feeds list"),
+            ("Cut off old subscribers", "ducktape feeds rotate Family"),
+        ],
+    ),
+    (
+        "ingest",
+        &[
+            (
+                "Propose the meeting a forwarded email describes",
+                "ducktape ingest email invite.eml",
+            ),
+            (
+                "Create it in the Work calendar",
+                "ducktape ingest email invite.eml --create --calendar Work",
+            ),
+            ("Read the message from stdin", "pbpaste | ducktape ingest email"),
+            (
+                "Add the flights of an airline confirmation, with check-in reminders",
+                "pbpaste | ducktape ingest itinerary --create --calendar Travel",
+            ),
+        ],
+    ),
+    (
+        "webhooks",
+        &[
+            (
+                "Send every change to n8n",
+                "ducktape webhooks add https://n8n.example.com/webhook/ducktape",
+            ),
+            ("Sign deliveries with a secret", "ducktape keys set webhook"),
+            ("Check that the URLs answer", "ducktape webhooks test"),
+        ],
+    ),
+    (
+        "help",
+        &[
+            ("List commands", "ducktape help"),
+            ("Show a command with examples", "ducktape help calendar create"),
+        ],
+    ),
+    (
+        "man",
+        &[
+            ("Show the man page", "ducktape man | man -l -"),
+            ("Write man pages for every command", "ducktape man --out-dir ./man"),
+        ],
+    ),
+];
+
+/// Commands only understood in interactive mode
+const INTERACTIVE_COMMANDS: &[(&str, &str)] =
+    &[("version", "Show version information"), ("exit", "Exit the application")];
+
+/// The Clap command tree with bin names filled in
+pub fn command() -> clap::Command {
+    let mut command = Cli::command();
+    command.build();
+    command
+}
+
+/// Examples for a top-level command
+pub fn examples(command: &str) -> &'static [(&'static str, &'static str)] {
+    EXAMPLES
+        .iter()
+        .find(|(name, _)| *name == command)
+        .map_or(&[], |(_, examples)| examples)
+}
+
+fn about(command: &clap::Command) -> String {
+    command.get_about().map(|about| about.to_string()).unwrap_or_default()
+}
+
+/// The command list shown by `ducktape help`
+pub fn overview() -> String {
+    let command = command();
+    let commands: Vec<(String, String)> = command
+        .get_subcommands()
+        .filter(|sub| !sub.is_hide_set())
+        .map(|sub| (sub.get_name().to_string(), about(sub)))
+        .chain(
+            INTERACTIVE_COMMANDS
+                .iter()
+                .map(|(name, about)| (name.to_string(), about.to_string())),
+        )
+        .collect();
+    let width = commands.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+
+    let mut out = format!(
+        "DuckTape - {}\n\nUSAGE:\n  ducktape [COMMAND] [SUBCOMMAND] [OPTIONS]\n\nCOMMANDS:\n",
+        about(&command)
+    );
+    for (name, about) in &commands {
+        out.push_str(&format!("  {:<width$}  {}\n", name, about));
+    }
+    out.push_str(
+        "\nFor details and examples of a command, run:\n  ducktape help <COMMAND> [SUBCOMMAND]\n",
+    );
+    out.push_str(
+        "\nIn interactive mode, press Ctrl-P or type '>' to search commands and history.\n",
+    );
+    out.push_str("\nEXAMPLES:\n");
+    for command in ["calendar", "todo", "note", "config"] {
+        if let Some((_, example)) = examples(command).first() {
+            out.push_str(&format!("  {}\n", example));
+        }
+    }
+    out
+}
+
+/// Find a command by its path, e.g. `["calendar", "create"]`, accepting aliases
+fn find<'a>(command: &'a clap::Command, path: &[String]) -> Option<&'a clap::Command> {
+    path.iter()
+        .try_fold(command, |command, name| command.find_subcommand(name.trim().to_lowercase()))
+}
+
+/// Canonical subcommand names an example command line runs, e.g. `utils tz` -> `utility tz`
+fn example_path(root: &clap::Command, example: &str) -> Vec<String> {
+    let mut path = Vec::new();
+    let mut command = root;
+    for word in example.split_whitespace().skip(1) {
+        match command.find_subcommand(word) {
+            Some(sub) => {
+                path.push(sub.get_name().to_string());
+                command = sub;
+            }
+            None => break,
+        }
+    }
+    path
+}
+
+/// Long help for a command with its examples; `None` for an unknown command
+pub fn command_help(path: &[String]) -> Option<String> {
+    let root = command();
+    let found = find(&root, path)?;
+    let mut out = found.clone().render_long_help().to_string();
+
+    let wanted = example_path(&root, &format!("ducktape {}", path.join(" ")));
+    let examples: Vec<&(&str, &str)> = EXAMPLES
+        .iter()
+        .flat_map(|(_, examples)| examples.iter())
+        .filter(|(_, example)| example_path(&root, example).starts_with(&wanted))
+        .collect();
+    if !examples.is_empty() {
+        out.push_str("\nExamples:\n");
+        for (description, example) in examples {
+            out.push_str(&format!("  # {}\n  {}\n\n", description, example));
+        }
+    }
+    Some(out.trim_end().to_string() + "\n")
+}
+
+/// Escape text for roff
+fn roff_escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
+
+/// Render the man page of a command, with its examples
+pub fn render_man_page(
+    command: clap::Command,
+    examples: &[(&str, &str)],
+    out: &mut dyn Write,
+) -> Result<()> {
+    let man = clap_mangen::Man::new(command.clone());
+    man.render_title(out)?;
+    man.render_name_section(out)?;
+    man.render_synopsis_section(out)?;
+    man.render_description_section(out)?;
+    if command.get_arguments().any(|arg| !arg.is_hide_set()) {
+        man.render_options_section(out)?;
+    }
+    if command.has_subcommands() {
+        man.render_subcommands_section(out)?;
+    }
+    if !examples.is_empty() {
+        writeln!(out, ".SH EXAMPLES")?;
+        for (description, example) in examples {
+            writeln!(out, ".TP\n{}\n\\fB{}\\fR", roff_escape(description), roff_escape(example))?;
+        }
+    }
+    man.render_version_section(out)?;
+    Ok(())
+}
+
+/// The main man page
+pub fn man_page() -> Result<String> {
+    let mut out = Vec::new();
+    let examples: Vec<(&str, &str)> =
+        EXAMPLES.iter().filter_map(|(_, examples)| examples.first().copied()).collect();
+    render_man_page(command(), &examples, &mut out)?;
+    Ok(String::from_utf8_lossy(&out).into_owned())
+}
+
+/// Write `ducktape.1` and a `ducktape-<command>.1` page per command into `dir`
+pub fn write_man_pages(dir: &Path) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let mut written = Vec::new();
+
+    let path = dir.join("ducktape.1");
+    std::fs::write(&path, man_page()?)?;
+    written.push(path);
+
+    for sub in command().get_subcommands().filter(|sub| !sub.is_hide_set()) {
+        let name = format!("ducktape-{}", sub.get_name());
+        let mut out = Vec::new();
+        // Subcommands have no version of their own, which clap_mangen needs for the footer
+        let page = sub.clone().name(name.clone()).version(env!("CARGO_PKG_VERSION"));
+        render_man_page(page, examples(sub.get_name()), &mut out)?;
+        let path = dir.join(format!("{}.1", name));
+        std::fs::write(&path, out)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_every_command_has_examples() {
+        for sub in command().get_subcommands() {
+            assert!(!examples(sub.get_name()).is_empty(), "no examples for {}", sub.get_name());
+        }
+        // And no examples for commands that no longer exist
+        for (name, _) in EXAMPLES {
+            assert!(command().find_subcommand(name).is_some(), "unknown command {}", name);
+        }
+    }
+
+    #[test]
+    fn test_overview_lists_clap_commands() {
+        let overview = overview();
+        for sub in command().get_subcommands() {
+            assert!(
+                overview.contains(&format!("  {} ", sub.get_name())),
+                "{} missing",
+                sub.get_name()
+            );
+        }
+        assert!(overview.contains("exit"));
+    }
+
+    #[test]
+    fn test_command_help() {
+        let help = command_help(&path(&["calendar", "create"])).unwrap();
+        assert!(help.contains("--zoom"));
+        assert!(help.contains("ducktape calendar create \"Team sync\""));
+        assert!(!help.contains("ducktape calendar list"));
+
+        // Aliases are accepted
+        let todos = command_help(&path(&["todos"])).unwrap();
+        assert!(todos.contains("ducktape todo board"));
+
+        assert!(command_help(&path(&["frobnicate"])).is_none());
+    }
+
+    #[test]
+    fn test_man_pages() -> Result<()> {
+        let page = man_page()?;
+        assert!(page.contains(".TH ducktape"));
+        assert!(page.contains(".SH EXAMPLES"));
+        assert!(page.contains("ducktape calendar list"));
+
+        let dir = tempfile::tempdir()?;
+        let written = write_man_pages(dir.path())?;
+        assert!(written.contains(&dir.path().join("ducktape-calendar.1")));
+        let calendar = std::fs::read_to_string(dir.path().join("ducktape-calendar.1"))?;
+        assert!(calendar.contains("ducktape\\-calendar"));
+        Ok(())
+    }
+}
@@ -0,0 +1,429 @@
+//! Public holidays, computed from the rules of each country.
+//
+// The holidays are built in rather than fetched, so they work offline and in tests. Only
+// national holidays are covered: regional ones such as New Zealand's anniversary days or US
+// state holidays are not. A holiday on a weekend that is observed on a weekday appears twice,
+// the second time as "(observed)". The country comes from `holidays.country` and is used to
+// warn about events created on a holiday.
+
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::collections::BTreeSet;
+
+/// Countries whose holidays are known
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Country {
+    Australia,
+    Canada,
+    France,
+    Germany,
+    Netherlands,
+    NewZealand,
+    UnitedKingdom,
+    UnitedStates,
+}
+
+impl Country {
+    pub const ALL: [Country; 8] = [
+        Country::Australia,
+        Country::Canada,
+        Country::France,
+        Country::Germany,
+        Country::Netherlands,
+        Country::NewZealand,
+        Country::UnitedKingdom,
+        Country::UnitedStates,
+    ];
+
+    /// A country from its ISO 3166 code or English name
+    pub fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim().to_lowercase();
+        let name = name.strip_prefix("the ").unwrap_or(&name);
+        Country::ALL
+            .into_iter()
+            .find(|c| {
+                c.code().eq_ignore_ascii_case(name)
+                    || c.name().trim_start_matches("the ").eq_ignore_ascii_case(name)
+            })
+            .or(match name {
+                "uk" | "england" | "britain" | "great britain" => Some(Country::UnitedKingdom),
+                "usa" | "america" => Some(Country::UnitedStates),
+                "aotearoa" => Some(Country::NewZealand),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                let codes: Vec<&str> = Country::ALL.iter().map(|c| c.code()).collect();
+                anyhow!("Unknown country: {}. Holidays are known for {}", s, codes.join(", "))
+            })
+    }
+
+    /// ISO 3166 code, e.g. "NZ"
+    pub fn code(&self) -> &'static str {
+        match self {
+            Country::Australia => "AU",
+            Country::Canada => "CA",
+            Country::France => "FR",
+            Country::Germany => "DE",
+            Country::Netherlands => "NL",
+            Country::NewZealand => "NZ",
+            Country::UnitedKingdom => "GB",
+            Country::UnitedStates => "US",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Country::Australia => "Australia",
+            Country::Canada => "Canada",
+            Country::France => "France",
+            Country::Germany => "Germany",
+            Country::Netherlands => "the Netherlands",
+            Country::NewZealand => "New Zealand",
+            Country::UnitedKingdom => "the United Kingdom",
+            Country::UnitedStates => "the United States",
+        }
+    }
+}
+
+/// A public holiday
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Holiday {
+    pub date: NaiveDate,
+    pub name: String,
+}
+
+/// How a holiday falling on a weekend moves to a weekday
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Observed {
+    /// It does not move
+    Never,
+    /// To the next weekday that is not already a holiday (New Zealand "Mondayisation", UK
+    /// substitute days)
+    NextFreeWeekday,
+    /// Saturday to Friday and Sunday to Monday (US federal holidays)
+    NearestWeekday,
+}
+
+/// Easter Sunday, by the anonymous Gregorian algorithm
+pub fn easter(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let (b, c) = (year / 100, year % 100);
+    let (d, e) = (b / 4, b % 4);
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let (i, k) = (c / 4, c % 4);
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32).expect("Easter is a valid date")
+}
+
+/// The `n`th `weekday` of a month, counting from 1; -1 is the last
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: i8) -> NaiveDate {
+    if n > 0 {
+        NaiveDate::from_weekday_of_month_opt(year, month, weekday, n as u8)
+            .expect("every month has four of each weekday")
+    } else {
+        let next_month = if month == 12 {
+            NaiveDate::from_ymd_opt(year + 1, 1, 1)
+        } else {
+            NaiveDate::from_ymd_opt(year, month + 1, 1)
+        };
+        let mut date = next_month.expect("valid month").pred_opt().expect("valid date");
+        while date.weekday() != weekday {
+            date = date.pred_opt().expect("valid date");
+        }
+        date
+    }
+}
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).expect("holiday dates are valid")
+}
+
+fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// Matariki, set by the Te Kāhui o Matariki Public Holiday Act; built in up to 2030
+fn matariki(year: i32) -> Option<NaiveDate> {
+    let (month, day) = match year {
+        2022 => (6, 24),
+        2023 => (7, 14),
+        2024 => (6, 28),
+        2025 => (6, 20),
+        2026 => (7, 10),
+        2027 => (6, 25),
+        2028 => (7, 14),
+        2029 => (7, 6),
+        2030 => (6, 21),
+        _ => return None,
+    };
+    Some(date(year, month, day))
+}
+
+/// Holidays on their calendar dates, with how each is observed
+fn rules(country: Country, year: i32) -> Vec<(NaiveDate, &'static str, Observed)> {
+    use Observed::*;
+    let easter = easter(year);
+    let mut days = Vec::new();
+    match country {
+        Country::NewZealand => {
+            // Waitangi Day and ANZAC Day are Mondayised since 2014
+            let mondayised = if year >= 2014 { NextFreeWeekday } else { Never };
+            days.extend([
+                (date(year, 1, 1), "New Year's Day", NextFreeWeekday),
+                (date(year, 1, 2), "Day after New Year's Day", NextFreeWeekday),
+                (date(year, 2, 6), "Waitangi Day", mondayised),
+                (easter - Duration::days(2), "Good Friday", Never),
+                (easter + Duration::days(1), "Easter Monday", Never),
+                (date(year, 4, 25), "ANZAC Day", mondayised),
+                (nth_weekday(year, 6, Weekday::Mon, 1), "King's Birthday", Never),
+                (nth_weekday(year, 10, Weekday::Mon, 4), "Labour Day", Never),
+                (date(year, 12, 25), "Christmas Day", NextFreeWeekday),
+                (date(year, 12, 26), "Boxing Day", NextFreeWeekday),
+            ]);
+            if let Some(matariki) = matariki(year) {
+                days.push((matariki, "Matariki", Never));
+            }
+        }
+        Country::Australia => days.extend([
+            (date(year, 1, 1), "New Year's Day", NextFreeWeekday),
+            (date(year, 1, 26), "Australia Day", NextFreeWeekday),
+            (easter - Duration::days(2), "Good Friday", Never),
+            (easter + Duration::days(1), "Easter Monday", Never),
+            (date(year, 4, 25), "ANZAC Day", Never),
+            (nth_weekday(year, 6, Weekday::Mon, 2), "King's Birthday", Never),
+            (date(year, 12, 25), "Christmas Day", NextFreeWeekday),
+            (date(year, 12, 26), "Boxing Day", NextFreeWeekday),
+        ]),
+        Country::UnitedKingdom => days.extend([
+            (date(year, 1, 1), "New Year's Day", NextFreeWeekday),
+            (easter - Duration::days(2), "Good Friday", Never),
+            (easter + Duration::days(1), "Easter Monday", Never),
+            (nth_weekday(year, 5, Weekday::Mon, 1), "Early May bank holiday", Never),
+            (nth_weekday(year, 5, Weekday::Mon, -1), "Spring bank holiday", Never),
+            (nth_weekday(year, 8, Weekday::Mon, -1), "Summer bank holiday", Never),
+            (date(year, 12, 25), "Christmas Day", NextFreeWeekday),
+            (date(year, 12, 26), "Boxing Day", NextFreeWeekday),
+        ]),
+        Country::UnitedStates => {
+            days.extend([
+                (date(year, 1, 1), "New Year's Day", NearestWeekday),
+                (nth_weekday(year, 1, Weekday::Mon, 3), "Martin Luther King Jr. Day", Never),
+                (nth_weekday(year, 2, Weekday::Mon, 3), "Washington's Birthday", Never),
+                (nth_weekday(year, 5, Weekday::Mon, -1), "Memorial Day", Never),
+                (date(year, 7, 4), "Independence Day", NearestWeekday),
+                (nth_weekday(year, 9, Weekday::Mon, 1), "Labor Day", Never),
+                (nth_weekday(year, 10, Weekday::Mon, 2), "Columbus Day", Never),
+                (date(year, 11, 11), "Veterans Day", NearestWeekday),
+                (nth_weekday(year, 11, Weekday::Thu, 4), "Thanksgiving Day", Never),
+                (date(year, 12, 25), "Christmas Day", NearestWeekday),
+            ]);
+            if year >= 2021 {
+                days.push((date(year, 6, 19), "Juneteenth", NearestWeekday));
+            }
+        }
+        Country::Canada => {
+            // Victoria Day is the Monday before May 25
+            let mut victoria = date(year, 5, 24);
+            while victoria.weekday() != Weekday::Mon {
+                victoria = victoria.pred_opt().expect("valid date");
+            }
+            days.extend([
+                (date(year, 1, 1), "New Year's Day", Never),
+                (easter - Duration::days(2), "Good Friday", Never),
+                (victoria, "Victoria Day", Never),
+                (date(year, 7, 1), "Canada Day", NextFreeWeekday),
+                (nth_weekday(year, 9, Weekday::Mon, 1), "Labour Day", Never),
+                (nth_weekday(year, 10, Weekday::Mon, 2), "Thanksgiving", Never),
+                (date(year, 12, 25), "Christmas Day", NextFreeWeekday),
+                (date(year, 12, 26), "Boxing Day", NextFreeWeekday),
+            ]);
+        }
+        Country::Germany => days.extend([
+            (date(year, 1, 1), "New Year's Day", Never),
+            (easter - Duration::days(2), "Good Friday", Never),
+            (easter + Duration::days(1), "Easter Monday", Never),
+            (date(year, 5, 1), "Labour Day", Never),
+            (easter + Duration::days(39), "Ascension Day", Never),
+            (easter + Duration::days(50), "Whit Monday", Never),
+            (date(year, 10, 3), "German Unity Day", Never),
+            (date(year, 12, 25), "Christmas Day", Never),
+            (date(year, 12, 26), "Second Day of Christmas", Never),
+        ]),
+        Country::Netherlands => {
+            // King's Day moves to Saturday when 27 April is a Sunday
+            let kings_day = match date(year, 4, 27) {
+                day if day.weekday() == Weekday::Sun => date(year, 4, 26),
+                day => day,
+            };
+            days.extend([
+                (date(year, 1, 1), "New Year's Day", Never),
+                (easter, "Easter Sunday", Never),
+                (easter + Duration::days(1), "Easter Monday", Never),
+                (kings_day, "King's Day", Never),
+                (date(year, 5, 5), "Liberation Day", Never),
+                (easter + Duration::days(39), "Ascension Day", Never),
+                (easter + Duration::days(49), "Whit Sunday", Never),
+                (easter + Duration::days(50), "Whit Monday", Never),
+                (date(year, 12, 25), "Christmas Day", Never),
+                (date(year, 12, 26), "Second Day of Christmas", Never),
+            ]);
+        }
+        Country::France => days.extend([
+            (date(year, 1, 1), "New Year's Day", Never),
+            (easter + Duration::days(1), "Easter Monday", Never),
+            (date(year, 5, 1), "Labour Day", Never),
+            (date(year, 5, 8), "Victory in Europe Day", Never),
+            (easter + Duration::days(39), "Ascension Day", Never),
+            (easter + Duration::days(50), "Whit Monday", Never),
+            (date(year, 7, 14), "Bastille Day", Never),
+            (date(year, 8, 15), "Assumption of Mary", Never),
+            (date(year, 11, 1), "All Saints' Day", Never),
+            (date(year, 11, 11), "Armistice Day", Never),
+            (date(year, 12, 25), "Christmas Day", Never),
+        ]),
+    }
+    days
+}
+
+/// Public holidays of `country` in `year`, in date order
+pub fn holidays(country: Country, year: i32) -> Vec<Holiday> {
+    let rules = rules(country, year);
+    let mut holidays: Vec<Holiday> = rules
+        .iter()
+        .map(|(date, name, _)| Holiday { date: *date, name: name.to_string() })
+        .collect();
+
+    // Weekend holidays move after the weekday ones are placed, so a substitute day never
+    // lands on another holiday
+    let mut taken: BTreeSet<NaiveDate> = rules
+        .iter()
+        .map(|(date, _, _)| *date)
+        .filter(|date| !is_weekend(*date))
+        .collect();
+    for (date, name, observed) in &rules {
+        if !is_weekend(*date) {
+            continue;
+        }
+        let moved = match observed {
+            Observed::Never => continue,
+            Observed::NearestWeekday if date.weekday() == Weekday::Sat => {
+                date.pred_opt().expect("valid date")
+            }
+            Observed::NearestWeekday => date.succ_opt().expect("valid date"),
+            Observed::NextFreeWeekday => {
+                let mut day = *date;
+                while is_weekend(day) || taken.contains(&day) {
+                    day = day.succ_opt().expect("valid date");
+                }
+                day
+            }
+        };
+        taken.insert(moved);
+        holidays.push(Holiday { date: moved, name: format!("{} (observed)", name) });
+    }
+
+    holidays.sort_by_key(|holiday| holiday.date);
+    holidays
+}
+
+/// The holiday of `country` on `date`, if there is one
+pub fn holiday_on(country: Country, date: NaiveDate) -> Option<Holiday> {
+    // A New Year's Day on a Saturday is observed on the last day of the year before
+    [date.year(), date.year() + 1]
+        .into_iter()
+        .flat_map(|year| holidays(country, year))
+        .find(|holiday| holiday.date == date)
+}
+
+/// The country set in `holidays.country`
+pub fn configured_country() -> Option<Country> {
+    let country = crate::config::Config::load().ok()?.holidays.country?;
+    Country::from_str(&country).ok()
+}
+
+/// Warning for an event on a public holiday in the configured country
+pub fn holiday_warning(date: &str) -> Option<String> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    let country = configured_country()?;
+    let holiday = holiday_on(country, date)?;
+    Some(format!(
+        "{} is a public holiday in {} ({})",
+        date.format("%Y-%m-%d"),
+        country.name(),
+        holiday.name
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names_on(country: Country, year: i32, month: u32, day: u32) -> Vec<String> {
+        holidays(country, year)
+            .into_iter()
+            .filter(|h| h.date == date(year, month, day))
+            .map(|h| h.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_easter() {
+        assert_eq!(easter(2024), date(2024, 3, 31));
+        assert_eq!(easter(2025), date(2025, 4, 20));
+        assert_eq!(easter(2026), date(2026, 4, 5));
+    }
+
+    #[test]
+    fn test_new_zealand() {
+        let holidays = holidays(Country::NewZealand, 2025);
+        assert_eq!(holidays.len(), 11);
+        assert_eq!(names_on(Country::NewZealand, 2025, 6, 20), vec!["Matariki"]);
+        assert_eq!(names_on(Country::NewZealand, 2025, 10, 27), vec!["Labour Day"]);
+        // ANZAC Day 2026 is a Saturday, observed on Monday 27 April
+        assert_eq!(names_on(Country::NewZealand, 2026, 4, 27), vec!["ANZAC Day (observed)"]);
+    }
+
+    #[test]
+    fn test_substitute_days_skip_other_holidays() {
+        // 2022: Christmas on Sunday, Boxing Day on Monday, so Christmas moves to Tuesday
+        assert_eq!(names_on(Country::UnitedKingdom, 2022, 12, 26), vec!["Boxing Day"]);
+        assert_eq!(
+            names_on(Country::UnitedKingdom, 2022, 12, 27),
+            vec!["Christmas Day (observed)"]
+        );
+        // 2021: Christmas on Saturday, Boxing Day on Sunday
+        assert_eq!(names_on(Country::NewZealand, 2021, 12, 27), vec!["Christmas Day (observed)"]);
+        assert_eq!(names_on(Country::NewZealand, 2021, 12, 28), vec!["Boxing Day (observed)"]);
+    }
+
+    #[test]
+    fn test_united_states() {
+        // Independence Day 2026 is a Saturday, observed on Friday
+        assert_eq!(
+            names_on(Country::UnitedStates, 2026, 7, 3),
+            vec!["Independence Day (observed)"]
+        );
+        assert_eq!(names_on(Country::UnitedStates, 2025, 11, 27), vec!["Thanksgiving Day"]);
+        assert_eq!(names_on(Country::UnitedStates, 2025, 5, 26), vec!["Memorial Day"]);
+    }
+
+    #[test]
+    fn test_country_from_str() {
+        assert_eq!(Country::from_str("nz").unwrap(), Country::NewZealand);
+        assert_eq!(Country::from_str("New Zealand").unwrap(), Country::NewZealand);
+        assert_eq!(Country::from_str("UK").unwrap(), Country::UnitedKingdom);
+        assert!(Country::from_str("Atlantis").is_err());
+    }
+
+    #[test]
+    fn test_holiday_on() {
+        let christmas = holiday_on(Country::Germany, date(2025, 12, 25)).unwrap();
+        assert_eq!(christmas.name, "Christmas Day");
+        assert!(holiday_on(Country::Germany, date(2025, 12, 24)).is_none());
+    }
+}
@@ -0,0 +1,399 @@
+//! Locale settings: date input and translated output.
+//
+// `config set locale de-DE` picks how numeric dates are read (14.03.2025 rather than
+// 03/14/2025), lets terminal date phrases use the language's weekday and month names
+// ("nächsten Montag", "14 mars") and translates the messages in `Message`. Dates are still
+// stored and passed to Calendar.app as YYYY-MM-DD. Without a locale DuckTape behaves as en-US.
+
+use anyhow::{Result, anyhow};
+use chrono::NaiveDate;
+use once_cell::sync::Lazy;
+use std::sync::RwLock;
+
+/// Languages with translated messages and date words
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    German,
+    French,
+    Spanish,
+    Dutch,
+}
+
+/// Order of day, month and year in numeric dates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateOrder {
+    /// 03/14/2025
+    MonthFirst,
+    /// 14/03/2025
+    DayFirst,
+    /// 2025/03/14
+    YearFirst,
+}
+
+/// A locale such as `de-DE`, `en-GB` or `fr`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locale {
+    pub tag: String,
+    pub language: Language,
+    pub date_order: DateOrder,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self {
+            tag: "en-US".to_string(),
+            language: Language::English,
+            date_order: DateOrder::MonthFirst,
+        }
+    }
+}
+
+impl Locale {
+    /// Parse a tag such as `de-DE`, `de_DE.UTF-8` or `en`
+    pub fn from_tag(tag: &str) -> Result<Self> {
+        let tag = tag.trim().split('.').next().unwrap_or_default().replace('_', "-");
+        let mut parts = tag.split('-');
+        let language = parts.next().unwrap_or_default().to_lowercase();
+        let region = parts.next().map(str::to_uppercase);
+        let (language, tag_language) = match language.as_str() {
+            "en" => (Language::English, "en"),
+            "de" => (Language::German, "de"),
+            "fr" => (Language::French, "fr"),
+            "es" => (Language::Spanish, "es"),
+            "nl" => (Language::Dutch, "nl"),
+            _ => {
+                return Err(anyhow!(
+                    "Unsupported locale: {}. Supported languages are en, de, fr, es and nl",
+                    tag
+                ));
+            }
+        };
+        // The United States and a few others write the month first; English without a region
+        // keeps DuckTape's original US behaviour
+        let date_order = match (language, region.as_deref()) {
+            (Language::English, None | Some("US" | "PH" | "FM")) => DateOrder::MonthFirst,
+            (Language::English, Some("CA")) => DateOrder::YearFirst,
+            _ => DateOrder::DayFirst,
+        };
+        let tag = match region {
+            Some(region) => format!("{}-{}", tag_language, region),
+            None => tag_language.to_string(),
+        };
+        Ok(Self { tag, language, date_order })
+    }
+
+    /// The locale set with `config set locale`
+    pub fn current() -> Locale {
+        CURRENT.read().map(|locale| locale.clone()).unwrap_or_default()
+    }
+
+    /// Use `locale` for the rest of this process, e.g. after `config set locale`
+    pub fn set_current(locale: Locale) {
+        if let Ok(mut current) = CURRENT.write() {
+            *current = locale;
+        }
+    }
+
+    /// How numeric dates are written, for usage hints
+    pub fn date_pattern(&self) -> &'static str {
+        match self.date_order {
+            DateOrder::MonthFirst => "MM/DD/YYYY",
+            DateOrder::DayFirst => "DD/MM/YYYY",
+            DateOrder::YearFirst => "YYYY/MM/DD",
+        }
+    }
+
+    /// Read a numeric date such as 14/03/2025, 14.03.2025 or 14-03-25 in this locale's order
+    pub fn parse_numeric_date(&self, input: &str) -> Option<NaiveDate> {
+        let parts: Vec<&str> = input.trim().split(['/', '.', '-']).collect();
+        let [a, b, c] = parts.as_slice() else {
+            return None;
+        };
+        let (year, month, day) = match self.date_order {
+            _ if a.len() == 4 => (*a, *b, *c),
+            DateOrder::MonthFirst => (*c, *a, *b),
+            DateOrder::DayFirst => (*c, *b, *a),
+            DateOrder::YearFirst => (*a, *b, *c),
+        };
+        let year: i32 = match year.len() {
+            4 => year.parse().ok()?,
+            2 => 2000 + year.parse::<i32>().ok()?,
+            _ => return None,
+        };
+        NaiveDate::from_ymd_opt(year, month.parse().ok()?, day.parse().ok()?)
+    }
+
+    /// Replace this language's weekday, month and relative day words with English ones
+    ///
+    /// `input` is expected in lower case. English input is returned unchanged.
+    pub fn to_english(&self, input: &str) -> String {
+        let words = date_words(self.language);
+        if words.is_empty() {
+            return input.to_string();
+        }
+        let tokens: Vec<&str> = input.split_whitespace().collect();
+        let mut out: Vec<&str> = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            let pair = tokens.get(i + 1).map(|next| format!("{} {}", tokens[i], next));
+            if let Some(english) = pair.as_deref().and_then(|pair| lookup(words, pair)) {
+                out.push(english);
+                i += 2;
+                continue;
+            }
+            // "14." is a day of the month in German
+            let token = tokens[i].trim_end_matches('.');
+            match lookup(words, token) {
+                Some("") => {}
+                Some(english) => out.push(english),
+                None => out.push(if token.is_empty() { tokens[i] } else { token }),
+            }
+            i += 1;
+        }
+        // "lundi prochain", "semana próxima": "next" comes after the noun
+        if out.len() == 2 && out[1] == "next" {
+            out.swap(0, 1);
+        }
+        out.join(" ")
+    }
+
+    /// A user-facing message in this locale's language
+    pub fn text(&self, message: Message) -> &'static str {
+        use Language::*;
+        use Message::*;
+        match (message, self.language) {
+            (NoTodos, German) => "Keine Erinnerungen gefunden",
+            (NoTodos, French) => "Aucun rappel trouvé",
+            (NoTodos, Spanish) => "No se encontraron recordatorios",
+            (NoTodos, Dutch) => "Geen herinneringen gevonden",
+            (NoTodos, English) => "No todos found",
+            (NoReminders, English) => "No reminders found",
+            (NoReminders, _) => self.text(NoTodos),
+            (NoNotes, German) => "Keine Notizen gefunden",
+            (NoNotes, French) => "Aucune note trouvée",
+            (NoNotes, Spanish) => "No se encontraron notas",
+            (NoNotes, Dutch) => "Geen notities gevonden",
+            (NoNotes, English) => "No notes found",
+            (NothingDeleted, German) => "Nichts gelöscht",
+            (NothingDeleted, French) => "Rien n'a été supprimé",
+            (NothingDeleted, Spanish) => "No se eliminó nada",
+            (NothingDeleted, Dutch) => "Niets verwijderd",
+            (NothingDeleted, English) => "Nothing deleted",
+            (AvailableCalendars, German) => "Verfügbare Kalender:",
+            (AvailableCalendars, French) => "Calendriers disponibles :",
+            (AvailableCalendars, Spanish) => "Calendarios disponibles:",
+            (AvailableCalendars, Dutch) => "Beschikbare agenda's:",
+            (AvailableCalendars, English) => "Available calendars:",
+        }
+    }
+}
+
+static CURRENT: Lazy<RwLock<Locale>> = Lazy::new(|| {
+    let tag = crate::config::Config::load().ok().and_then(|config| config.locale);
+    RwLock::new(tag.and_then(|tag| Locale::from_tag(&tag).ok()).unwrap_or_default())
+});
+
+/// Messages translated for the configured locale
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message {
+    NoTodos,
+    NoReminders,
+    NoNotes,
+    NothingDeleted,
+    AvailableCalendars,
+}
+
+/// A message in the current locale
+pub fn text(message: Message) -> &'static str {
+    Locale::current().text(message)
+}
+
+fn lookup(words: &[(&str, &'static str)], word: &str) -> Option<&'static str> {
+    words.iter().find(|(local, _)| *local == word).map(|(_, english)| *english)
+}
+
+/// Date words of `language` and their English equivalents; "" drops an article
+fn date_words(language: Language) -> &'static [(&'static str, &'static str)] {
+    match language {
+        Language::English => &[],
+        Language::German => &[
+            ("heute", "today"),
+            ("morgen", "tomorrow"),
+            ("übermorgen", "day after tomorrow"),
+            ("gestern", "yesterday"),
+            ("nächsten", "next"),
+            ("nächste", "next"),
+            ("nächster", "next"),
+            ("diesen", "this"),
+            ("diese", "this"),
+            ("am", ""),
+            ("den", ""),
+            ("woche", "week"),
+            ("monat", "month"),
+            ("jahr", "year"),
+            ("wochenende", "weekend"),
+            ("montag", "monday"),
+            ("dienstag", "tuesday"),
+            ("mittwoch", "wednesday"),
+            ("donnerstag", "thursday"),
+            ("freitag", "friday"),
+            ("samstag", "saturday"),
+            ("sonnabend", "saturday"),
+            ("sonntag", "sunday"),
+            ("januar", "january"),
+            ("jänner", "january"),
+            ("februar", "february"),
+            ("märz", "march"),
+            ("mai", "may"),
+            ("juni", "june"),
+            ("juli", "july"),
+            ("oktober", "october"),
+            ("dezember", "december"),
+        ],
+        Language::French => &[
+            ("aujourd'hui", "today"),
+            ("après-demain", "day after tomorrow"),
+            ("demain", "tomorrow"),
+            ("hier", "yesterday"),
+            ("prochain", "next"),
+            ("prochaine", "next"),
+            ("ce", "this"),
+            ("le", ""),
+            ("semaine", "week"),
+            ("mois", "month"),
+            ("année", "year"),
+            ("week-end", "weekend"),
+            ("lundi", "monday"),
+            ("mardi", "tuesday"),
+            ("mercredi", "wednesday"),
+            ("jeudi", "thursday"),
+            ("vendredi", "friday"),
+            ("samedi", "saturday"),
+            ("dimanche", "sunday"),
+            ("janvier", "january"),
+            ("février", "february"),
+            ("mars", "march"),
+            ("avril", "april"),
+            ("mai", "may"),
+            ("juin", "june"),
+            ("juillet", "july"),
+            ("août", "august"),
+            ("septembre", "september"),
+            ("octobre", "october"),
+            ("novembre", "november"),
+            ("décembre", "december"),
+        ],
+        Language::Spanish => &[
+            ("pasado mañana", "day after tomorrow"),
+            ("hoy", "today"),
+            ("mañana", "tomorrow"),
+            ("ayer", "yesterday"),
+            ("próximo", "next"),
+            ("próxima", "next"),
+            ("este", "this"),
+            ("el", ""),
+            ("de", ""),
+            ("semana", "week"),
+            ("mes", "month"),
+            ("año", "year"),
+            ("lunes", "monday"),
+            ("martes", "tuesday"),
+            ("miércoles", "wednesday"),
+            ("jueves", "thursday"),
+            ("viernes", "friday"),
+            ("sábado", "saturday"),
+            ("domingo", "sunday"),
+            ("enero", "january"),
+            ("febrero", "february"),
+            ("marzo", "march"),
+            ("abril", "april"),
+            ("mayo", "may"),
+            ("junio", "june"),
+            ("julio", "july"),
+            ("agosto", "august"),
+            ("septiembre", "september"),
+            ("octubre", "october"),
+            ("noviembre", "november"),
+            ("diciembre", "december"),
+        ],
+        Language::Dutch => &[
+            ("vandaag", "today"),
+            ("morgen", "tomorrow"),
+            ("overmorgen", "day after tomorrow"),
+            ("gisteren", "yesterday"),
+            ("volgende", "next"),
+            ("deze", "this"),
+            ("op", ""),
+            ("maand", "month"),
+            ("jaar", "year"),
+            ("maandag", "monday"),
+            ("dinsdag", "tuesday"),
+            ("woensdag", "wednesday"),
+            ("donderdag", "thursday"),
+            ("vrijdag", "friday"),
+            ("zaterdag", "saturday"),
+            ("zondag", "sunday"),
+            ("januari", "january"),
+            ("februari", "february"),
+            ("maart", "march"),
+            ("mei", "may"),
+            ("juni", "june"),
+            ("juli", "july"),
+            ("augustus", "august"),
+            ("oktober", "october"),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_locale_from_tag() {
+        let german = Locale::from_tag("de_DE.UTF-8").unwrap();
+        assert_eq!((german.tag.as_str(), german.language), ("de-DE", Language::German));
+        assert_eq!(german.date_order, DateOrder::DayFirst);
+        assert_eq!(Locale::from_tag("en").unwrap().date_order, DateOrder::MonthFirst);
+        assert_eq!(Locale::from_tag("en-gb").unwrap().date_order, DateOrder::DayFirst);
+        assert!(Locale::from_tag("xx-XX").is_err());
+    }
+
+    #[test]
+    fn test_parse_numeric_date() {
+        let german = Locale::from_tag("de-DE").unwrap();
+        assert_eq!(german.parse_numeric_date("14.03.2025"), Some(date(2025, 3, 14)));
+        assert_eq!(german.parse_numeric_date("14/03/25"), Some(date(2025, 3, 14)));
+        let us = Locale::default();
+        assert_eq!(us.parse_numeric_date("03/14/2025"), Some(date(2025, 3, 14)));
+        assert_eq!(us.parse_numeric_date("14/03/2025"), None);
+        // A four digit year first is read the same everywhere
+        assert_eq!(german.parse_numeric_date("2025/03/14"), Some(date(2025, 3, 14)));
+        assert_eq!(german.parse_numeric_date("14 march"), None);
+    }
+
+    #[test]
+    fn test_to_english() {
+        let german = Locale::from_tag("de").unwrap();
+        assert_eq!(german.to_english("nächsten montag"), "next monday");
+        assert_eq!(german.to_english("am 14. märz 2026"), "14 march 2026");
+        let french = Locale::from_tag("fr-FR").unwrap();
+        assert_eq!(french.to_english("lundi prochain"), "next monday");
+        let spanish = Locale::from_tag("es").unwrap();
+        assert_eq!(spanish.to_english("pasado mañana"), "day after tomorrow");
+        assert_eq!(spanish.to_english("14 de marzo"), "14 march");
+        assert_eq!(Locale::default().to_english("next monday"), "next monday");
+    }
+
+    #[test]
+    fn test_messages() {
+        let dutch = Locale::from_tag("nl-NL").unwrap();
+        assert_eq!(dutch.text(Message::NoNotes), "Geen notities gevonden");
+        assert_eq!(Locale::default().text(Message::NoReminders), "No reminders found");
+    }
+}
@@ -0,0 +1,452 @@
+// Reading meeting details out of an RFC 822 message (.eml file)
+//
+// Only what is needed to find a meeting is decoded: headers (with RFC 2047 encoded words),
+// the text/plain part of a multipart message (or the text/html part with its tags removed),
+// and quoted-printable or base64 bodies. When the message was forwarded, the headers quoted
+// in the body describe the original and take the place of the envelope's.
+
+use super::{MeetingDetails, excerpt, meeting_link, parse_when};
+use anyhow::{Result, anyhow};
+use base64::Engine;
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static ENCODED_WORD: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"=\?([^?]+)\?([bBqQ])\?([^?]*)\?=").unwrap());
+static ADJACENT_ENCODED_WORDS: Lazy<Regex> = Lazy::new(|| Regex::new(r"\?=\s+=\?").unwrap());
+static ADDRESS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap());
+static BOUNDARY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)boundary\s*=\s*"?([^";]+)"?"#).unwrap());
+static CHARSET: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)charset\s*=\s*"?([^";\s]+)"?"#).unwrap());
+static FORWARD_MARKER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)^(?:-+\s*(?:forwarded message|original message)\s*-+|begin forwarded message:?)$",
+    )
+    .unwrap()
+});
+static SUBJECT_PREFIX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^\s*(?:(?:fwd?|fw|re|aw|wg|tr|invitation|updated invitation)\s*:\s*)+")
+        .unwrap()
+});
+static WHEN_LINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^(?:when|date|time|date\s*/\s*time|date and time|starts?)\s*:\s*(.+)$")
+        .unwrap()
+});
+static WHERE_LINE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)^(?:where|location|venue|place)\s*:\s*(.+)$").unwrap());
+static HTML_BLOCK: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?is)<(style|script|head)\b.*?</(?:style|script|head)>").unwrap());
+static HTML_BREAK: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)<br\s*/?>|</(?:p|div|tr|li|h\d)>").unwrap());
+static HTML_TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]*>").unwrap());
+
+/// Senders that never attend, such as calendar notification robots
+const AUTOMATED_SENDERS: &[&str] =
+    &["noreply", "no-reply", "donotreply", "do-not-reply", "calendar-notification"];
+
+/// The parts of an email that describe a meeting
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EmailMessage {
+    pub subject: String,
+    pub from: Vec<String>,
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    /// When the (original) message was sent, the reference for "tomorrow" and weekdays
+    pub date: Option<DateTime<FixedOffset>>,
+    /// The address that forwarded the message, if it was forwarded
+    pub forwarded_by: Option<String>,
+    /// The readable text of the message
+    pub body: String,
+}
+
+type Headers = Vec<(String, String)>;
+
+fn header<'a>(headers: &'a Headers, name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+/// Split a message into unfolded headers and its body
+fn split_message(raw: &str) -> (Headers, &str) {
+    let (head, body) = match raw.find("\n\n") {
+        Some(at) => (&raw[..at], &raw[at + 2..]),
+        None => (raw, ""),
+    };
+    let mut headers: Headers = Vec::new();
+    for line in head.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    (headers, body)
+}
+
+fn decode_charset(bytes: &[u8], charset: &str) -> String {
+    match charset.to_lowercase().as_str() {
+        "iso-8859-1" | "latin1" | "windows-1252" | "cp1252" => {
+            bytes.iter().map(|&b| b as char).collect()
+        }
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+fn decode_quoted_printable(text: &str) -> Vec<u8> {
+    let text = text.replace("=\n", "");
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'=', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+fn decode_base64(text: &str) -> Vec<u8> {
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    base64::engine::general_purpose::STANDARD.decode(compact).unwrap_or_default()
+}
+
+/// Decode RFC 2047 encoded words such as `=?UTF-8?Q?Caf=C3=A9?=`
+pub fn decode_header(value: &str) -> String {
+    let joined = ADJACENT_ENCODED_WORDS.replace_all(value, "?==?");
+    ENCODED_WORD
+        .replace_all(&joined, |caps: &regex::Captures| {
+            let bytes = if caps[2].eq_ignore_ascii_case("b") {
+                decode_base64(&caps[3])
+            } else {
+                decode_quoted_printable(&caps[3].replace('_', " "))
+            };
+            decode_charset(&bytes, &caps[1])
+        })
+        .into_owned()
+}
+
+/// The lower-cased addresses in a header value
+fn addresses(value: &str) -> Vec<String> {
+    ADDRESS.find_iter(value).map(|m| m.as_str().to_lowercase()).collect()
+}
+
+/// Readable text of an HTML body
+fn strip_html(html: &str) -> String {
+    let text = HTML_BLOCK.replace_all(html, "");
+    let text = HTML_BREAK.replace_all(&text, "\n");
+    let text = HTML_TAG.replace_all(&text, "");
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+        .lines()
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+enum Text {
+    Plain(String),
+    Html(String),
+}
+
+/// The readable text of a message part, preferring text/plain over text/html
+fn part_text(headers: &Headers, body: &str) -> Option<Text> {
+    let content_type = header(headers, "Content-Type").unwrap_or("text/plain");
+    let media_type = content_type.split(';').next().unwrap_or("").trim().to_lowercase();
+    let attachment = header(headers, "Content-Disposition")
+        .is_some_and(|d| d.trim().to_lowercase().starts_with("attachment"));
+    if attachment {
+        return None;
+    }
+
+    if media_type.starts_with("multipart/") {
+        let boundary = BOUNDARY.captures(content_type)?.get(1)?.as_str().to_string();
+        let delimiter = format!("--{}", boundary);
+        let mut html = None;
+        for part in body.split(&delimiter).skip(1) {
+            if part.starts_with("--") {
+                break;
+            }
+            let (part_headers, part_body) = split_message(part.trim_start_matches('\n'));
+            match part_text(&part_headers, part_body) {
+                Some(Text::Plain(text)) if !text.trim().is_empty() => {
+                    return Some(Text::Plain(text));
+                }
+                Some(Text::Html(text)) if html.is_none() => html = Some(text),
+                _ => {}
+            }
+        }
+        return html.map(Text::Html);
+    }
+
+    let is_html = media_type == "text/html";
+    if !media_type.is_empty() && !media_type.starts_with("text/plain") && !is_html {
+        return None;
+    }
+    let charset = CHARSET
+        .captures(content_type)
+        .and_then(|c| c.get(1))
+        .map_or("utf-8", |m| m.as_str());
+    let encoding = header(headers, "Content-Transfer-Encoding").unwrap_or("").to_lowercase();
+    let text = match encoding.trim() {
+        "quoted-printable" => decode_charset(&decode_quoted_printable(body), charset),
+        "base64" => decode_charset(&decode_base64(body), charset),
+        _ => body.to_string(),
+    };
+    Some(if is_html { Text::Html(strip_html(&text)) } else { Text::Plain(text) })
+}
+
+impl EmailMessage {
+    /// Parse a raw RFC 822 message
+    pub fn parse(raw: &str) -> Result<Self> {
+        let raw = raw.replace("\r\n", "\n");
+        let (headers, body) = split_message(raw.trim_start());
+        if headers.is_empty() {
+            return Err(anyhow!("Not an email message: no headers found"));
+        }
+        let text = match part_text(&headers, body) {
+            Some(Text::Plain(text)) | Some(Text::Html(text)) => text,
+            None => String::new(),
+        };
+
+        let mut message = EmailMessage {
+            subject: decode_header(header(&headers, "Subject").unwrap_or_default()),
+            from: addresses(header(&headers, "From").unwrap_or_default()),
+            to: addresses(header(&headers, "To").unwrap_or_default()),
+            cc: addresses(header(&headers, "Cc").unwrap_or_default()),
+            date: header(&headers, "Date").and_then(|d| DateTime::parse_from_rfc2822(d).ok()),
+            forwarded_by: None,
+            body: text,
+        };
+        message.apply_forwarded_headers();
+        Ok(message)
+    }
+
+    /// Replace the envelope's headers with those quoted under a "Forwarded message" line
+    fn apply_forwarded_headers(&mut self) {
+        let lines: Vec<&str> = self.body.lines().collect();
+        let unquote = |line: &str| line.trim_start_matches(['>', ' ']).trim().to_string();
+        let Some(marker) = lines.iter().position(|line| FORWARD_MARKER.is_match(&unquote(line)))
+        else {
+            return;
+        };
+
+        let mut quoted: Headers = Vec::new();
+        let mut end = marker + 1;
+        while end < lines.len() {
+            let line = unquote(lines[end]);
+            end += 1;
+            if line.is_empty() {
+                if quoted.is_empty() {
+                    continue;
+                }
+                break;
+            }
+            match line.split_once(':') {
+                Some((name, value)) if !name.contains(' ') => {
+                    quoted.push((name.trim().to_string(), value.trim().to_string()))
+                }
+                _ => break,
+            }
+        }
+        if quoted.is_empty() {
+            return;
+        }
+
+        self.forwarded_by = self.from.first().cloned();
+        if let Some(subject) = header(&quoted, "Subject") {
+            self.subject = decode_header(subject);
+        }
+        self.from = addresses(header(&quoted, "From").unwrap_or_default());
+        self.to = addresses(header(&quoted, "To").unwrap_or_default());
+        self.cc = addresses(header(&quoted, "Cc").unwrap_or_default());
+        let sent = header(&quoted, "Date").or_else(|| header(&quoted, "Sent"));
+        if let Some(date) = sent.and_then(|d| DateTime::parse_from_rfc2822(d).ok()) {
+            self.date = Some(date);
+        }
+        // The quoted headers include a Date: line that is not the meeting's date
+        self.body = lines[end..].join("\n");
+    }
+
+    /// Everyone on the message except automated senders and whoever forwarded it
+    pub fn participants(&self) -> Vec<String> {
+        let mut participants: Vec<String> = Vec::new();
+        for address in self.from.iter().chain(&self.to).chain(&self.cc) {
+            let automated = AUTOMATED_SENDERS.iter().any(|s| address.contains(s));
+            let forwarder = self.forwarded_by.as_ref() == Some(address);
+            if !automated && !forwarder && !participants.contains(address) {
+                participants.push(address.clone());
+            }
+        }
+        participants
+    }
+
+    /// Read the meeting from headers and `When:`/`Where:` lines, without the NL parser
+    ///
+    /// Relative dates are read from when the message was sent, or `now` if that is unknown.
+    pub fn meeting_details(&self, now: NaiveDateTime) -> MeetingDetails {
+        let reference = self.date.map(|d| d.with_timezone(&Local).naive_local()).unwrap_or(now);
+        let subject = SUBJECT_PREFIX.replace(&self.subject, "").trim().to_string();
+        // Google Calendar subjects read "Title @ Tue Apr 22, 2025 10am - 11am (PDT) (email)"
+        let (title, subject_when) = match subject.split_once(" @ ") {
+            Some((title, when)) => (title.trim().to_string(), Some(when.to_string())),
+            None => (subject, None),
+        };
+
+        let mut when_text = Vec::new();
+        let mut location = None;
+        for line in self.body.lines() {
+            let line = line.trim_start_matches(['>', ' ']).trim();
+            if let Some(caps) = WHEN_LINE.captures(line) {
+                when_text.push(caps[1].trim().to_string());
+            } else if let Some(caps) = WHERE_LINE.captures(line) {
+                if location.is_none() {
+                    location = Some(caps[1].trim().to_string());
+                }
+            }
+        }
+        let when = parse_when(&when_text.join(" "), reference)
+            .or_else(|| parse_when(subject_when.as_deref()?, reference));
+
+        MeetingDetails {
+            title,
+            date: when.and_then(|w| w.date),
+            start_time: when.and_then(|w| w.start),
+            end_time: when.and_then(|w| w.end),
+            location: location.or_else(|| meeting_link(&self.body)),
+            attendees: self.participants(),
+            description: Some(excerpt(&self.body)).filter(|d| !d.is_empty()),
+        }
+    }
+
+    /// Text for the NL parser: the subject and the start of the body
+    pub fn parser_prompt(&self) -> String {
+        let body: String = self.body.chars().take(600).collect();
+        format!("Create a calendar event for this email. Subject: {}\n{}", self.subject, body)
+    }
+}
+
+/// Read an email and propose the event it describes
+///
+/// The NL parser is only asked when the headers and body lines leave the date or time open.
+pub async fn propose_event(raw: &str, now: NaiveDateTime) -> Result<MeetingDetails> {
+    let message = EmailMessage::parse(raw)?;
+    let mut details = message.meeting_details(now);
+    if !details.is_complete() {
+        if let Some(parsed) = super::ask_parser(&message.parser_prompt()).await {
+            details.fill_from(&parsed);
+        }
+    }
+    Ok(details)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, NaiveTime};
+
+    const FORWARDED: &str = "From: Jane Doe <jane@example.com>\r\n\
+To: me@example.com\r\n\
+Subject: Fwd: Quarterly =?UTF-8?Q?planning_caf=C3=A9?=\r\n\
+Date: Mon, 14 Apr 2025 09:12:00 -0700\r\n\
+Content-Type: text/plain; charset=utf-8\r\n\
+Content-Transfer-Encoding: quoted-printable\r\n\
+\r\n\
+FYI, see below.\r\n\
+\r\n\
+---------- Forwarded message ---------\r\n\
+From: Sam Lee <sam@partner.org>\r\n\
+Date: Fri, 11 Apr 2025 16:00:00 -0700\r\n\
+Subject: Quarterly planning caf=C3=A9\r\n\
+To: Jane Doe <jane@example.com>, Priya <priya@partner.org>\r\n\
+Cc: noreply@partner.org\r\n\
+\r\n\
+Hi all,\r\n\
+When: Tuesday, April 22, 2025 10:00 AM - 11:30 AM (PDT)\r\n\
+Where: Room 4, 1 Main St\r\n\
+Zoom: https://partner.zoom.us/j/42\r\n";
+
+    #[test]
+    fn test_decode_header() {
+        assert_eq!(decode_header("=?UTF-8?Q?Caf=C3=A9_au_lait?="), "Café au lait");
+        assert_eq!(decode_header("=?utf-8?B?SGVsbG8=?= =?utf-8?B?IHdvcmxk?="), "Hello world");
+        assert_eq!(decode_header("Plain subject"), "Plain subject");
+    }
+
+    #[test]
+    fn test_forwarded_message_details() {
+        let message = EmailMessage::parse(FORWARDED).unwrap();
+        assert_eq!(message.subject, "Quarterly planning café");
+        assert_eq!(message.forwarded_by.as_deref(), Some("jane@example.com"));
+        assert_eq!(message.participants(), vec!["sam@partner.org", "priya@partner.org"]);
+
+        let now = NaiveDate::from_ymd_opt(2025, 4, 15).unwrap().and_hms_opt(8, 0, 0).unwrap();
+        let details = message.meeting_details(now);
+        assert_eq!(details.title, "Quarterly planning café");
+        assert_eq!(details.date, NaiveDate::from_ymd_opt(2025, 4, 22));
+        assert_eq!(details.start_time, NaiveTime::from_hms_opt(10, 0, 0));
+        assert_eq!(details.end_time, NaiveTime::from_hms_opt(11, 30, 0));
+        assert_eq!(details.location.as_deref(), Some("Room 4, 1 Main St"));
+    }
+
+    #[test]
+    fn test_multipart_prefers_plain_text() {
+        let raw = "Subject: Invitation: Design review @ Thu Apr 24, 2025 2pm - 3pm (PDT)\n\
+From: calendar-notification@google.com\n\
+To: me@example.com\n\
+Content-Type: multipart/alternative; boundary=\"XYZ\"\n\
+\n\
+--XYZ\n\
+Content-Type: text/html\n\
+\n\
+<p>Join <a href=\"https://meet.google.com/abc-defg-hij\">Meet</a></p>\n\
+--XYZ\n\
+Content-Type: text/plain\n\
+Content-Transfer-Encoding: base64\n\
+\n\
+Sm9pbiBodHRwczovL21lZXQuZ29vZ2xlLmNvbS9hYmMtZGVmZy1oaWo=\n\
+--XYZ--\n";
+        let message = EmailMessage::parse(raw).unwrap();
+        assert_eq!(message.body, "Join https://meet.google.com/abc-defg-hij");
+
+        let now = NaiveDate::from_ymd_opt(2025, 4, 14).unwrap().and_hms_opt(8, 0, 0).unwrap();
+        let details = message.meeting_details(now);
+        assert_eq!(details.title, "Design review");
+        assert_eq!(details.date, NaiveDate::from_ymd_opt(2025, 4, 24));
+        assert_eq!(details.start_time, NaiveTime::from_hms_opt(14, 0, 0));
+        assert_eq!(details.location.as_deref(), Some("https://meet.google.com/abc-defg-hij"));
+        assert_eq!(details.attendees, vec!["me@example.com"]);
+    }
+
+    #[test]
+    fn test_strip_html() {
+        assert_eq!(
+            strip_html("<style>p{}</style><p>When: 3pm&nbsp;today</p><br>Bring &amp; share"),
+            "When: 3pm today\n\nBring & share"
+        );
+    }
+
+    #[test]
+    fn test_not_an_email() {
+        assert!(EmailMessage::parse("just some text").is_err());
+    }
+}
@@ -0,0 +1,459 @@
+// Reading flights out of airline confirmation text
+//
+// Each flight number (`BA 117`, `UA901`) starts a segment that runs to the next one. Within
+// a segment the first two airport codes are the origin and destination, the first two times
+// the departure and arrival, and dates are read where they appear; a segment without a date
+// takes the last date mentioned before it. Times are wall clock times at each airport, so
+// departure and arrival are converted through the airports' time zones to local time. An
+// arrival that would come before the departure, or a time marked `+1`, is on a later day.
+
+use super::clock;
+use crate::calendar::EventConfig;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::str::FromStr;
+
+/// IATA code, city and time zone of airports commonly flown through
+const AIRPORTS: &[(&str, &str, &str)] = &[
+    ("AMS", "Amsterdam", "Europe/Amsterdam"),
+    ("ATL", "Atlanta", "America/New_York"),
+    ("AUS", "Austin", "America/Chicago"),
+    ("BCN", "Barcelona", "Europe/Madrid"),
+    ("BKK", "Bangkok", "Asia/Bangkok"),
+    ("BOS", "Boston", "America/New_York"),
+    ("BRU", "Brussels", "Europe/Brussels"),
+    ("CDG", "Paris", "Europe/Paris"),
+    ("CPH", "Copenhagen", "Europe/Copenhagen"),
+    ("DEN", "Denver", "America/Denver"),
+    ("DFW", "Dallas", "America/Chicago"),
+    ("DOH", "Doha", "Asia/Qatar"),
+    ("DUB", "Dublin", "Europe/Dublin"),
+    ("DXB", "Dubai", "Asia/Dubai"),
+    ("EWR", "Newark", "America/New_York"),
+    ("FCO", "Rome", "Europe/Rome"),
+    ("FRA", "Frankfurt", "Europe/Berlin"),
+    ("GRU", "São Paulo", "America/Sao_Paulo"),
+    ("HEL", "Helsinki", "Europe/Helsinki"),
+    ("HKG", "Hong Kong", "Asia/Hong_Kong"),
+    ("HND", "Tokyo", "Asia/Tokyo"),
+    ("IAD", "Washington", "America/New_York"),
+    ("IAH", "Houston", "America/Chicago"),
+    ("ICN", "Seoul", "Asia/Seoul"),
+    ("IST", "Istanbul", "Europe/Istanbul"),
+    ("JFK", "New York", "America/New_York"),
+    ("LAS", "Las Vegas", "America/Los_Angeles"),
+    ("LAX", "Los Angeles", "America/Los_Angeles"),
+    ("LGA", "New York", "America/New_York"),
+    ("LGW", "London", "Europe/London"),
+    ("LHR", "London", "Europe/London"),
+    ("LIS", "Lisbon", "Europe/Lisbon"),
+    ("MAD", "Madrid", "Europe/Madrid"),
+    ("MCO", "Orlando", "America/New_York"),
+    ("MEX", "Mexico City", "America/Mexico_City"),
+    ("MIA", "Miami", "America/New_York"),
+    ("MUC", "Munich", "Europe/Berlin"),
+    ("MXP", "Milan", "Europe/Rome"),
+    ("NRT", "Tokyo", "Asia/Tokyo"),
+    ("ORD", "Chicago", "America/Chicago"),
+    ("OSL", "Oslo", "Europe/Oslo"),
+    ("PDX", "Portland", "America/Los_Angeles"),
+    ("PEK", "Beijing", "Asia/Shanghai"),
+    ("PHX", "Phoenix", "America/Phoenix"),
+    ("PVG", "Shanghai", "Asia/Shanghai"),
+    ("SAN", "San Diego", "America/Los_Angeles"),
+    ("SEA", "Seattle", "America/Los_Angeles"),
+    ("SFO", "San Francisco", "America/Los_Angeles"),
+    ("SIN", "Singapore", "Asia/Singapore"),
+    ("SJC", "San Jose", "America/Los_Angeles"),
+    ("STN", "London", "Europe/London"),
+    ("SYD", "Sydney", "Australia/Sydney"),
+    ("TXL", "Berlin", "Europe/Berlin"),
+    ("BER", "Berlin", "Europe/Berlin"),
+    ("VIE", "Vienna", "Europe/Vienna"),
+    ("YUL", "Montreal", "America/Toronto"),
+    ("YVR", "Vancouver", "America/Vancouver"),
+    ("YYZ", "Toronto", "America/Toronto"),
+    ("ZRH", "Zurich", "Europe/Zurich"),
+];
+
+static FLIGHT_NUMBER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b([A-Z]{2}|[A-Z]\d|\d[A-Z])\s?(\d{1,4})\b").unwrap());
+static AIRPORT_CODE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b[A-Z]{3}\b").unwrap());
+static FLIGHT_TIME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\b(\d{1,2}:\d{2}(?:\s*[ap]\.?m\.?)?|\d{1,2}\s*[ap]\.?m\.?)(?:\s*\(?\+(\d)\)?)?",
+    )
+    .unwrap()
+});
+static FLIGHT_DATE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:\d{4}-\d{2}-\d{2}|\d{1,2}(?:st|nd|rd|th)?\s*(?:jan|feb|mar|apr|may|jun|jul|aug|sep|oct|nov|dec)[a-z]*\.?,?(?:\s*\d{4}|\d{2})?|(?:jan|feb|mar|apr|may|jun|jul|aug|sep|oct|nov|dec)[a-z]*\.?\s+\d{1,2}(?:st|nd|rd|th)?(?:,?\s+\d{4})?)\b").unwrap()
+});
+static COMPACT_DATE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(\d{1,2})\s*([a-z]{3})(\d{2})$").unwrap());
+static CONFIRMATION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i:confirmation|booking|record locator|reservation|pnr)(?i:\s+(?:code|number|reference|ref|no\.?))?\s*[:#]?\s*([A-Z0-9]{6})\b").unwrap()
+});
+
+/// City and time zone of an airport code
+fn airport(code: &str) -> Option<(&'static str, Tz)> {
+    AIRPORTS
+        .iter()
+        .find(|(c, _, _)| *c == code)
+        .and_then(|(_, city, tz)| Some((*city, Tz::from_str(tz).ok()?)))
+}
+
+/// A date as airlines write it: `2025-06-12`, `12 Jun 2025`, `Jun 12, 2025` or `12JUN25`
+fn flight_date(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let text = text.to_lowercase().replace(['.', ','], "");
+    let text = match COMPACT_DATE.captures(&text) {
+        Some(caps) => format!("{} {} 20{}", &caps[1], &caps[2], &caps[3]),
+        None => text,
+    };
+    crate::dates::parse_date(&text, today)
+}
+
+/// A flight from one airport to another
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FlightSegment {
+    /// e.g. `BA 117`
+    pub flight: String,
+    /// IATA code of the departure airport
+    pub origin: Option<String>,
+    /// IATA code of the arrival airport
+    pub destination: Option<String>,
+    pub departure_date: Option<NaiveDate>,
+    /// Wall clock time at the origin
+    pub departure_time: Option<NaiveTime>,
+    /// Set when the text gives the arrival day, by date or `+1`
+    pub arrival_date: Option<NaiveDate>,
+    /// Wall clock time at the destination
+    pub arrival_time: Option<NaiveTime>,
+}
+
+/// The flights in a confirmation
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Itinerary {
+    /// Booking reference, e.g. `X7K9QZ`
+    pub confirmation: Option<String>,
+    pub segments: Vec<FlightSegment>,
+}
+
+/// An instant from a wall clock time at an airport; unknown airports use local time
+fn at_airport(code: Option<&str>, time: NaiveDateTime) -> Option<DateTime<Utc>> {
+    match code.and_then(airport) {
+        Some((_, tz)) => tz.from_local_datetime(&time).earliest().map(|t| t.with_timezone(&Utc)),
+        None => Local.from_local_datetime(&time).earliest().map(|t| t.with_timezone(&Utc)),
+    }
+}
+
+/// The city of an airport, or its code when it is not known
+fn place(code: Option<&str>) -> String {
+    match code {
+        Some(code) => airport(code).map_or_else(|| code.to_string(), |(city, _)| city.to_string()),
+        None => "?".to_string(),
+    }
+}
+
+impl FlightSegment {
+    /// Read one segment from the text of its block, `last_date` being the date before it
+    fn parse(flight: String, block: &str, last_date: Option<NaiveDate>, today: NaiveDate) -> Self {
+        let airports: Vec<String> = AIRPORT_CODE
+            .find_iter(block)
+            .filter(|m| {
+                let quoted = block[..m.start()].ends_with('(');
+                quoted || airport(m.as_str()).is_some()
+            })
+            .map(|m| m.as_str().to_string())
+            .collect();
+        let dates: Vec<NaiveDate> = FLIGHT_DATE
+            .find_iter(block)
+            .filter_map(|m| flight_date(m.as_str(), today))
+            .collect();
+        // Dates are taken out first so that e.g. `12 Jun` is not read as a time
+        let without_dates = FLIGHT_DATE.replace_all(block, " ");
+        let times: Vec<(NaiveTime, i64)> = FLIGHT_TIME
+            .captures_iter(&without_dates)
+            .filter_map(|caps| {
+                let time = clock(&caps[1].to_lowercase().replace('.', ""))?;
+                let days = caps.get(2).and_then(|d| d.as_str().parse().ok()).unwrap_or(0);
+                Some((time, days))
+            })
+            .collect();
+
+        let departure_date = dates.first().copied().or(last_date);
+        let arrival_date = match (dates.get(1), times.get(1)) {
+            (Some(date), _) => Some(*date),
+            (None, Some((_, days))) if *days > 0 => {
+                departure_date.map(|date| date + Duration::days(*days))
+            }
+            _ => None,
+        };
+        FlightSegment {
+            flight,
+            origin: airports.first().cloned(),
+            destination: airports.get(1).cloned(),
+            departure_date,
+            departure_time: times.first().map(|(time, _)| *time),
+            arrival_date,
+            arrival_time: times.get(1).map(|(time, _)| *time),
+        }
+    }
+
+    /// Whether the departure and arrival are known
+    pub fn is_complete(&self) -> bool {
+        self.departure_date.is_some()
+            && self.departure_time.is_some()
+            && self.arrival_time.is_some()
+    }
+
+    /// Fill what is still missing from an event the NL parser proposed
+    pub fn fill_from(&mut self, parsed: &EventConfig) {
+        if self.departure_date.is_none() {
+            self.departure_date = NaiveDate::parse_from_str(&parsed.start_date, "%Y-%m-%d").ok();
+        }
+        if self.departure_time.is_none() && !parsed.all_day {
+            self.departure_time = clock(&parsed.start_time);
+        }
+        if self.arrival_time.is_none() {
+            self.arrival_time = parsed.end_time.as_deref().and_then(clock);
+        }
+        if self.arrival_date.is_none() {
+            self.arrival_date = parsed
+                .end_date
+                .as_deref()
+                .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+        }
+    }
+
+    /// When the flight leaves
+    pub fn departure(&self) -> Option<DateTime<Utc>> {
+        let time = self.departure_date?.and_time(self.departure_time?);
+        at_airport(self.origin.as_deref(), time)
+    }
+
+    /// When the flight lands; without an arrival day, the first one after the departure
+    pub fn arrival(&self) -> Option<DateTime<Utc>> {
+        let departure = self.departure()?;
+        let date = self.arrival_date.or(self.departure_date)?;
+        let arrival = at_airport(self.destination.as_deref(), date.and_time(self.arrival_time?))?;
+        if self.arrival_date.is_none() && arrival <= departure {
+            return Some(arrival + Duration::days(1));
+        }
+        Some(arrival)
+    }
+
+    /// e.g. `Flight BA 117 London → New York`
+    pub fn title(&self) -> String {
+        format!(
+            "Flight {} {} → {}",
+            self.flight,
+            place(self.origin.as_deref()),
+            place(self.destination.as_deref())
+        )
+    }
+
+    /// The event for the flight, in local time
+    pub fn event_config(&self, confirmation: Option<&str>) -> Result<EventConfig> {
+        let departure = self
+            .departure()
+            .ok_or_else(|| anyhow!("Could not tell when flight {} leaves", self.flight))?
+            .with_timezone(&Local)
+            .naive_local();
+        let mut config = EventConfig::new(
+            &self.title(),
+            &departure.format("%Y-%m-%d").to_string(),
+            &departure.format("%H:%M").to_string(),
+        );
+        let mut description = vec![format!("Flight {}", self.flight)];
+        if let (Some(code), Some(time)) = (&self.origin, self.departure_time) {
+            description.push(format!("Departs {} at {} local time", code, time.format("%H:%M")));
+            config.location = Some(format!("{} ({})", place(Some(code)), code));
+        }
+        if let Some(arrival) = self.arrival() {
+            let arrival = arrival.with_timezone(&Local).naive_local();
+            config.end_time = Some(arrival.format("%H:%M").to_string());
+            if arrival.date() != departure.date() {
+                config.end_date = Some(arrival.format("%Y-%m-%d").to_string());
+            }
+        }
+        if let (Some(code), Some(time)) = (&self.destination, self.arrival_time) {
+            description.push(format!("Arrives {} at {} local time", code, time.format("%H:%M")));
+        }
+        if let Some(confirmation) = confirmation {
+            description.push(format!("Confirmation {}", confirmation));
+        }
+        config.description = Some(description.join("\n"));
+        Ok(config)
+    }
+
+    /// When to check in: `hours` before the departure, in local time
+    pub fn check_in(&self, hours: u32) -> Option<NaiveDateTime> {
+        let departure = self.departure()?.with_timezone(&Local).naive_local();
+        Some(departure - Duration::hours(hours as i64))
+    }
+}
+
+/// Read the flights in `text` without the NL parser
+///
+/// Dates without a year are the next such date after `now`.
+pub fn parse_itinerary(text: &str, now: NaiveDateTime) -> Itinerary {
+    let today = now.date();
+    // Blocks start at the beginning of the line with the flight number, so that airports
+    // written before the number on that line belong to it
+    let mut starts: Vec<(usize, String)> = Vec::new();
+    for caps in FLIGHT_NUMBER.captures_iter(text) {
+        let flight = format!("{} {}", &caps[1], &caps[2]);
+        if matches!(&caps[1], "AM" | "PM") || starts.last().is_some_and(|(_, f)| *f == flight) {
+            continue;
+        }
+        let at = caps.get(0).map_or(0, |m| m.start());
+        let line_start = text[..at].rfind('\n').map_or(0, |i| i + 1);
+        // A second flight on the same line starts at its number
+        let start = match starts.last() {
+            Some((last, _)) if line_start <= *last => at,
+            _ => line_start,
+        };
+        starts.push((start, flight));
+    }
+
+    let mut segments = Vec::new();
+    for (i, (start, flight)) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).map_or(text.len(), |(next, _)| *next);
+        let last_date = FLIGHT_DATE
+            .find_iter(&text[..*start])
+            .filter_map(|m| flight_date(m.as_str(), today))
+            .last();
+        segments.push(FlightSegment::parse(flight.clone(), &text[*start..end], last_date, today));
+    }
+
+    Itinerary {
+        confirmation: CONFIRMATION.captures(text).map(|caps| caps[1].to_string()),
+        segments,
+    }
+}
+
+/// Read the flights in `text`, asking the NL parser about segments left incomplete
+pub async fn propose_itinerary(text: &str, now: NaiveDateTime) -> Result<Itinerary> {
+    let mut itinerary = parse_itinerary(text, now);
+    if itinerary.segments.is_empty() {
+        return Err(anyhow!("No flight numbers found in the itinerary"));
+    }
+    for segment in itinerary.segments.iter_mut().filter(|s| !s.is_complete()) {
+        let prompt = format!(
+            "Create a calendar event for flight {} from its departure to its arrival:\n{}",
+            segment.flight,
+            text.chars().take(1500).collect::<String>()
+        );
+        if let Some(parsed) = super::ask_parser(&prompt).await {
+            segment.fill_from(&parsed);
+        }
+    }
+    Ok(itinerary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 4, 14).unwrap().and_hms_opt(9, 0, 0).unwrap()
+    }
+
+    fn day(y: i32, m: u32, d: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(y, m, d)
+    }
+
+    fn time(h: u32, m: u32) -> Option<NaiveTime> {
+        NaiveTime::from_hms_opt(h, m, 0)
+    }
+
+    const CONFIRMATION_EMAIL: &str = "Your trip is confirmed. Booking reference: X7K9QZ\n\
+\n\
+Flight BA 117\n\
+Depart: London Heathrow (LHR) 12 Jun 2025 08:25\n\
+Arrive: New York JFK (JFK) 12 Jun 2025 11:10\n\
+\n\
+Mon, 16 Jun 2025\n\
+UA 901  San Francisco (SFO) 10:30 PM → Frankfurt (FRA) 6:45 PM +1\n";
+
+    #[test]
+    fn test_parse_itinerary() {
+        let itinerary = parse_itinerary(CONFIRMATION_EMAIL, now());
+        assert_eq!(itinerary.confirmation.as_deref(), Some("X7K9QZ"));
+        assert_eq!(itinerary.segments.len(), 2);
+
+        let first = &itinerary.segments[0];
+        assert_eq!(first.flight, "BA 117");
+        assert_eq!(
+            (first.origin.as_deref(), first.destination.as_deref()),
+            (Some("LHR"), Some("JFK"))
+        );
+        assert_eq!(first.departure_date, day(2025, 6, 12));
+        assert_eq!((first.departure_time, first.arrival_time), (time(8, 25), time(11, 10)));
+        assert_eq!(first.title(), "Flight BA 117 London → New York");
+
+        // The date comes from the line before, the arrival day from `+1`
+        let second = &itinerary.segments[1];
+        assert_eq!(second.flight, "UA 901");
+        assert_eq!(second.departure_date, day(2025, 6, 16));
+        assert_eq!(second.departure_time, time(22, 30));
+        assert_eq!(second.arrival_date, day(2025, 6, 17));
+        assert!(second.is_complete());
+    }
+
+    #[test]
+    fn test_times_across_time_zones() {
+        let itinerary = parse_itinerary(CONFIRMATION_EMAIL, now());
+        let first = &itinerary.segments[0];
+        // 08:25 BST is 07:25 UTC, 11:10 EDT is 15:10 UTC
+        assert_eq!(first.departure().unwrap().to_rfc3339(), "2025-06-12T07:25:00+00:00");
+        assert_eq!(first.arrival().unwrap().to_rfc3339(), "2025-06-12T15:10:00+00:00");
+        assert_eq!(
+            first.check_in(24).unwrap(),
+            first.departure().unwrap().with_timezone(&Local).naive_local() - Duration::hours(24)
+        );
+
+        let second = &itinerary.segments[1];
+        assert_eq!(second.arrival().unwrap().to_rfc3339(), "2025-06-17T16:45:00+00:00");
+    }
+
+    #[test]
+    fn test_overnight_arrival_without_marker() {
+        let itinerary = parse_itinerary("DL 44 JFK 2025-06-20 19:00 CDG 08:15", now());
+        let segment = &itinerary.segments[0];
+        assert_eq!(segment.arrival_date, None);
+        // 19:00 EDT is 23:00 UTC; 08:15 CEST on the next day is 06:15 UTC
+        assert_eq!(segment.arrival().unwrap().to_rfc3339(), "2025-06-21T06:15:00+00:00");
+    }
+
+    #[test]
+    fn test_compact_dates_and_unknown_codes() {
+        let segment = &parse_itinerary("LH400 FRA-JFK 03MAY25 1:05pm 3:50pm", now()).segments[0];
+        assert_eq!(segment.flight, "LH 400");
+        assert_eq!(segment.departure_date, day(2025, 5, 3));
+        assert_eq!(segment.departure_time, time(13, 5));
+
+        // Three capital letters are only airports when known or in parentheses
+        let segment = &parse_itinerary("AF 10 DEP (XYZ) ARR (CDG)", now()).segments[0];
+        assert_eq!(segment.origin.as_deref(), Some("XYZ"));
+        assert_eq!(segment.destination.as_deref(), Some("CDG"));
+        assert!(!segment.is_complete());
+    }
+
+    #[test]
+    fn test_event_config() {
+        let itinerary = parse_itinerary(CONFIRMATION_EMAIL, now());
+        let config = itinerary.segments[0].event_config(Some("X7K9QZ")).unwrap();
+        assert_eq!(config.title, "Flight BA 117 London → New York");
+        assert_eq!(config.location.as_deref(), Some("London (LHR)"));
+        let description = config.description.unwrap();
+        assert!(description.contains("Departs LHR at 08:25 local time"));
+        assert!(description.contains("Confirmation X7K9QZ"));
+        assert!(config.end_time.is_some());
+
+        assert!(FlightSegment::default().event_config(None).is_err());
+    }
+}
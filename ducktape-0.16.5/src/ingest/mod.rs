@@ -0,0 +1,319 @@
+//! Turning messages that describe a meeting or a trip into calendar events.
+//
+// Invitations often arrive as plain email rather than ICS. The readers in this module pull
+// what they can out of such messages deterministically (headers, `When:` and `Where:` lines,
+// meeting links, flight numbers and airports) and ask the configured NL parser only for what
+// is still missing. The result is a proposed `EventConfig`, or one per flight of an
+// itinerary, that the CLI shows, and creates when asked to.
+
+use crate::calendar::EventConfig;
+use crate::command_processor::{CommandArgs, event_config_from_args};
+use crate::parser::{ParseResult, ParserFactory};
+use anyhow::Result;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use tracing::{debug, warn};
+
+pub mod email;
+pub mod itinerary;
+
+/// Longest body excerpt kept in the event description
+const DESCRIPTION_LIMIT: usize = 1000;
+
+static ISO_DATE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b\d{4}-\d{2}-\d{2}\b").unwrap());
+static TIME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b\d{1,2}(?::\d{2})?\s*(?:am|pm)\b|\b\d{1,2}:\d{2}\b|\bnoon\b|\bmidnight\b")
+        .unwrap()
+});
+static TIME_RANGE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b(\d{1,2}(?::\d{2})?\s*(?:am|pm)?)\s*(?:-|to|until|till)\s*(\d{1,2}(?::\d{2})?\s*(?:am|pm)?)\b").unwrap()
+});
+static PARENTHESES: Lazy<Regex> = Lazy::new(|| Regex::new(r"\([^)]*\)").unwrap());
+static MEETING_LINK: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"https://[\w.-]*(?:zoom\.us|meet\.google\.com|teams\.microsoft\.com|teams\.live\.com|webex\.com)/[^\s<>)\]]*").unwrap()
+});
+
+/// What a message says about a meeting
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeetingDetails {
+    pub title: String,
+    pub date: Option<NaiveDate>,
+    pub start_time: Option<NaiveTime>,
+    pub end_time: Option<NaiveTime>,
+    pub location: Option<String>,
+    pub attendees: Vec<String>,
+    pub description: Option<String>,
+}
+
+/// When a meeting takes place, as read from text like `Tuesday, April 22, 2025 10:00-11:00`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct When {
+    pub date: Option<NaiveDate>,
+    pub start: Option<NaiveTime>,
+    pub end: Option<NaiveTime>,
+}
+
+fn clock(expr: &str) -> Option<NaiveTime> {
+    crate::dates::parse_time(&expr.replace(' ', ""))
+}
+
+/// Read a start and end time from a range such as `10-11am` or `2:30 pm to 4 pm`
+///
+/// A start without am/pm borrows the end's, unless that would put it after the end.
+fn time_range(start: &str, end: &str) -> Option<(NaiveTime, NaiveTime)> {
+    let end_time = clock(end)?;
+    let start_time = clock(start).or_else(|| {
+        let suffix = ["am", "pm"].into_iter().find(|s| end.ends_with(s))?;
+        let other = if suffix == "am" { "pm" } else { "am" };
+        clock(&format!("{}{}", start, suffix))
+            .filter(|t| *t <= end_time)
+            .or_else(|| clock(&format!("{}{}", start, other)))
+    })?;
+    Some((start_time, end_time))
+}
+
+/// Strip a leading weekday name, e.g. `tuesday april 22` -> `april 22`
+fn without_weekday(expr: &str) -> &str {
+    match expr.split_once(' ') {
+        Some((first, rest))
+            if crate::dates::parse_date(first, NaiveDate::MIN).is_some()
+                && first.chars().all(|c| c.is_ascii_alphabetic()) =>
+        {
+            rest
+        }
+        _ => expr,
+    }
+}
+
+/// Read a date and time range from free text, relative to `now`
+///
+/// Timezone names in parentheses and after the times are ignored. Returns `None` when
+/// neither a date nor a time was found.
+pub fn parse_when(text: &str, now: NaiveDateTime) -> Option<When> {
+    let text = PARENTHESES
+        .replace_all(&text.to_lowercase(), " ")
+        .replace(['–', '—'], "-")
+        .replace("a.m.", "am")
+        .replace("p.m.", "pm");
+
+    let mut date = ISO_DATE
+        .find(&text)
+        .and_then(|m| NaiveDate::parse_from_str(m.as_str(), "%Y-%m-%d").ok());
+    let text = ISO_DATE.replace_all(&text, " ").to_string();
+
+    let range = TIME_RANGE.captures_iter(&text).find_map(|caps| {
+        let (start, end) = time_range(caps[1].trim(), caps[2].trim())?;
+        Some((start, Some(end), caps.get(0)?.range()))
+    });
+    let single = || TIME.find_iter(&text).find_map(|m| Some((clock(m.as_str())?, None, m.range())));
+    let (start, end, date_text) = match range.or_else(single) {
+        Some((start, end, at)) => {
+            // The date usually comes first ("April 22 at 3pm"), but not always ("3pm Friday")
+            let before = &text[..at.start];
+            let before = if before.trim().trim_end_matches("at").trim().is_empty() {
+                &text[at.end..]
+            } else {
+                before
+            };
+            (Some(start), end, before.to_string())
+        }
+        None => (None, None, text.clone()),
+    };
+
+    let date_text = date_text
+        .replace(',', " ")
+        .split_whitespace()
+        .filter(|w| !matches!(*w, "at" | "from" | "on"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let today = now.date();
+    if date.is_none() && !date_text.is_empty() {
+        date = crate::dates::parse_date(&date_text, today)
+            .or_else(|| crate::dates::parse_date(without_weekday(&date_text), today))
+            .or_else(|| {
+                // Trailing words such as a timezone abbreviation
+                let words: Vec<&str> = date_text.split(' ').collect();
+                (1..words.len()).rev().find_map(|n| {
+                    let head = words[..n].join(" ");
+                    crate::dates::parse_date(&head, today)
+                        .or_else(|| crate::dates::parse_date(without_weekday(&head), today))
+                })
+            });
+    }
+
+    if date.is_none() && start.is_none() {
+        return None;
+    }
+    Some(When { date, start, end })
+}
+
+/// The first Zoom, Meet, Teams or Webex link in `text`
+pub fn meeting_link(text: &str) -> Option<String> {
+    MEETING_LINK
+        .find(text)
+        .map(|m| m.as_str().trim_end_matches(['.', ',']).to_string())
+}
+
+/// Shorten `text` to about `DESCRIPTION_LIMIT` characters, ending on a whole line
+pub fn excerpt(text: &str) -> String {
+    let mut out = String::new();
+    for line in text.lines() {
+        if out.len() + line.len() > DESCRIPTION_LIMIT {
+            out.push('…');
+            break;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.trim().to_string()
+}
+
+impl MeetingDetails {
+    /// Fill what is still missing from an event the NL parser proposed
+    pub fn fill_from(&mut self, parsed: &EventConfig) {
+        if self.title.trim().is_empty() {
+            self.title = parsed.title.trim_matches('"').to_string();
+        }
+        if self.date.is_none() {
+            self.date = NaiveDate::parse_from_str(&parsed.start_date, "%Y-%m-%d").ok();
+        }
+        if self.start_time.is_none() && !parsed.all_day {
+            self.start_time = clock(&parsed.start_time);
+        }
+        if self.end_time.is_none() {
+            self.end_time = parsed.end_time.as_deref().and_then(clock);
+        }
+        if self.location.is_none() {
+            self.location = parsed.location.clone();
+        }
+    }
+
+    /// Whether the date and start time are known
+    pub fn is_complete(&self) -> bool {
+        self.date.is_some() && self.start_time.is_some()
+    }
+
+    /// The proposed event; an all-day event when only the date is known
+    pub fn event_config(&self) -> Result<EventConfig> {
+        let date = self
+            .date
+            .ok_or_else(|| anyhow::anyhow!("Could not tell when \"{}\" takes place", self.title))?;
+        let title = if self.title.trim().is_empty() { "Meeting" } else { self.title.trim() };
+        let start = self.start_time.map(|t| t.format("%H:%M").to_string());
+        let mut config =
+            EventConfig::new(title, &date.to_string(), start.as_deref().unwrap_or("00:00"));
+        config.all_day = start.is_none();
+        config.end_time = self
+            .end_time
+            .filter(|end| self.start_time.is_some_and(|start| *end > start))
+            .map(|t| t.format("%H:%M").to_string());
+        config.location = self.location.clone();
+        config.emails = self.attendees.clone();
+        config.description = self.description.clone();
+        Ok(config)
+    }
+}
+
+/// Ask the configured NL parser what event `text` describes
+///
+/// Failures are logged and give `None`, so that ingestion still works without a model.
+pub async fn ask_parser(text: &str) -> Option<EventConfig> {
+    let parsed = async {
+        let parser = ParserFactory::create_parser()?;
+        let args = match parser.parse_input(text).await? {
+            ParseResult::CommandString(command) => CommandArgs::parse(&command)?,
+            ParseResult::StructuredCommand(args) => args,
+            // Nobody is around to answer, so the parser's guess is used
+            ParseResult::NeedsClarification(clarification) => {
+                CommandArgs::parse(&clarification.command)?
+            }
+        };
+        anyhow::Ok(args)
+    };
+    match parsed.await {
+        Ok(args)
+            if args.command == "calendar" && args.args.first().is_some_and(|a| a == "create") =>
+        {
+            debug!("Parser proposed {:?}", args);
+            event_config_from_args(&args)
+        }
+        Ok(args) => {
+            debug!("Parser did not propose an event: {:?}", args);
+            None
+        }
+        Err(e) => {
+            warn!("NL parser could not read the message: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 4, 14).unwrap().and_hms_opt(9, 0, 0).unwrap()
+    }
+
+    fn time(h: u32, m: u32) -> Option<NaiveTime> {
+        NaiveTime::from_hms_opt(h, m, 0)
+    }
+
+    #[test]
+    fn test_parse_when() {
+        let when = parse_when("Tuesday, April 22, 2025 10:00 AM - 11:30 AM (PDT)", now()).unwrap();
+        assert_eq!(when.date, NaiveDate::from_ymd_opt(2025, 4, 22));
+        assert_eq!((when.start, when.end), (time(10, 0), time(11, 30)));
+
+        let when = parse_when("Thu 24 Apr, 2–3pm", now()).unwrap();
+        assert_eq!(when.date, NaiveDate::from_ymd_opt(2025, 4, 24));
+        assert_eq!((when.start, when.end), (time(14, 0), time(15, 0)));
+
+        let when = parse_when("11 - 1pm on 2025-05-02", now()).unwrap();
+        assert_eq!(when.date, NaiveDate::from_ymd_opt(2025, 5, 2));
+        assert_eq!((when.start, when.end), (time(11, 0), time(13, 0)));
+
+        let when = parse_when("tomorrow at 3pm", now()).unwrap();
+        assert_eq!(when.date, NaiveDate::from_ymd_opt(2025, 4, 15));
+        assert_eq!((when.start, when.end), (time(15, 0), None));
+
+        let when = parse_when("3pm Friday", now()).unwrap();
+        assert_eq!(when.date, NaiveDate::from_ymd_opt(2025, 4, 18));
+
+        assert_eq!(parse_when("see you soon", now()), None);
+    }
+
+    #[test]
+    fn test_details_fill_and_config() {
+        let mut details = MeetingDetails {
+            title: "Design review".to_string(),
+            location: Some("Room 4".to_string()),
+            ..Default::default()
+        };
+        assert!(details.event_config().is_err());
+
+        let mut parsed = EventConfig::new("Review", "2025-04-22", "10:00");
+        parsed.end_time = Some("11:00".to_string());
+        parsed.location = Some("Elsewhere".to_string());
+        details.fill_from(&parsed);
+        assert!(details.is_complete());
+
+        let config = details.event_config().unwrap();
+        assert_eq!(config.title, "Design review");
+        assert_eq!(config.start_date, "2025-04-22");
+        assert_eq!(config.end_time.as_deref(), Some("11:00"));
+        assert_eq!(config.location.as_deref(), Some("Room 4"));
+        assert!(!config.all_day);
+    }
+
+    #[test]
+    fn test_meeting_link() {
+        assert_eq!(
+            meeting_link("Join: https://us02web.zoom.us/j/123456?pwd=abc.").as_deref(),
+            Some("https://us02web.zoom.us/j/123456?pwd=abc")
+        );
+        assert_eq!(meeting_link("https://example.com/j/1"), None);
+    }
+}
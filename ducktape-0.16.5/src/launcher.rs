@@ -0,0 +1,357 @@
+//! Output for launchers: Alfred Script Filter JSON with `--output alfred`, and `ducktape raycast`.
+//
+// Alfred runs a Script Filter on every keystroke and lists the `items` it prints; the `arg`
+// of the chosen item goes to the next action of the workflow, usually "Run Script". Each
+// item's arg is therefore the whole DuckTape command that acts on it, so a workflow needs no
+// wrapper script. A Raycast extension runs `ducktape raycast <action>` and turns the items
+// into a List, each with the actions it offers. Quick create only shows what would be
+// created, in both: the event is created when the chosen command runs.
+
+use crate::calendar::AgendaEvent;
+use crate::command_processor::CommandArgs;
+use crate::state::{CalendarItem, StateManager};
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use clap::ValueEnum;
+use once_cell::sync::OnceCell;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+
+/// Events listed by a search
+pub const SEARCH_LIMIT: usize = 20;
+
+/// How commands that support launchers print their results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// For people
+    #[default]
+    Text,
+    /// Alfred Script Filter JSON
+    Alfred,
+}
+
+static OUTPUT: OnceCell<OutputFormat> = OnceCell::new();
+
+/// Set the output format of this run; only the first call counts
+pub fn set_output(format: OutputFormat) {
+    let _ = OUTPUT.set(format);
+}
+
+/// The output format of this run
+pub fn output() -> OutputFormat {
+    OUTPUT.get().copied().unwrap_or_default()
+}
+
+/// One row in a launcher's list
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LauncherItem {
+    pub uid: String,
+    pub title: String,
+    pub subtitle: String,
+    /// Short text shown on the right, e.g. the start time
+    pub accessory: Option<String>,
+    /// What running `command` does, e.g. "Open in Calendar"
+    pub action: String,
+    /// The DuckTape command run when the item is chosen; `None` for an item that only informs
+    pub command: Option<String>,
+    pub url: Option<String>,
+}
+
+impl LauncherItem {
+    /// An item that cannot be chosen, telling why the list is empty or what went wrong
+    pub fn message(title: &str, subtitle: &str) -> Self {
+        Self {
+            uid: format!("message:{}", title),
+            title: title.to_string(),
+            subtitle: subtitle.to_string(),
+            ..Self::default()
+        }
+    }
+}
+
+/// A DuckTape command line, with each argument quoted for the shell
+fn command(name: &str, args: &[&str], flags: &[(&str, Option<&str>)]) -> String {
+    let flags: HashMap<String, Option<String>> = flags
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.map(str::to_string)))
+        .collect();
+    let args = args.iter().map(|arg| arg.to_string()).collect();
+    crate::parser::trace::command_line(&CommandArgs::new(name.to_string(), args, flags))
+}
+
+/// The events of `date` on the agenda
+pub fn agenda_items(date: &str, events: &[AgendaEvent]) -> Vec<LauncherItem> {
+    if events.is_empty() {
+        return vec![LauncherItem::message(&format!("No events on {}", date), "Nothing planned")];
+    }
+    events
+        .iter()
+        .map(|event| {
+            let time = if event.all_day {
+                "All day".to_string()
+            } else {
+                format!("{}–{}", event.start, event.end)
+            };
+            LauncherItem {
+                uid: format!("{}:{}:{}", date, event.start, event.title),
+                title: event.title.clone(),
+                subtitle: format!("{} · {}", time, event.calendar),
+                accessory: Some(if event.all_day {
+                    "All day".to_string()
+                } else {
+                    event.start.clone()
+                }),
+                action: "Open in Calendar".to_string(),
+                command: Some(command("open", &["event", &event.title], &[])),
+                url: event.url.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Tracked events matching `query`, soonest first; events before `from` are left out
+pub fn search_items(
+    items: &[CalendarItem],
+    query: &str,
+    from: Option<NaiveDate>,
+) -> Vec<LauncherItem> {
+    if query.trim().is_empty() {
+        return vec![LauncherItem::message("Search events", "Type part of a title or place")];
+    }
+    let found: Vec<LauncherItem> = crate::shortcuts::search_items(items, query, from)
+        .into_iter()
+        .take(SEARCH_LIMIT)
+        .map(|item| {
+            let when = if item.all_day {
+                item.date.clone()
+            } else {
+                format!("{} {}", item.date, item.time)
+            };
+            let subtitle =
+                [Some(when.clone()), Some(item.calendars.join(", ")), item.location.clone()]
+                    .into_iter()
+                    .flatten()
+                    .filter(|part| !part.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" · ");
+            LauncherItem {
+                uid: item.uid.clone().unwrap_or_else(|| format!("{}:{}", when, item.title)),
+                title: item.title.clone(),
+                subtitle,
+                accessory: Some(item.date.clone()),
+                action: "Open in Calendar".to_string(),
+                command: Some(command("open", &["event", &item.title], &[])),
+                url: item.url.clone(),
+            }
+        })
+        .collect();
+    if found.is_empty() {
+        return vec![LauncherItem::message(
+            &format!("No events match '{}'", query.trim()),
+            "Only events DuckTape created or tracks are searched",
+        )];
+    }
+    found
+}
+
+/// The event `ducktape quick` would create from `text`, or why it would not
+pub fn create_items(text: &str, calendar: Option<&str>, now: NaiveDateTime) -> Vec<LauncherItem> {
+    let event = match crate::parser::quick::parse_quick(text, now) {
+        Ok(event) => event,
+        Err(e) => return vec![LauncherItem::message("Create an event", &e.to_string())],
+    };
+    let mut flags = Vec::new();
+    if let Some(calendar) = calendar {
+        flags.push(("calendar", Some(calendar)));
+    }
+    let subtitle = match calendar {
+        Some(calendar) => format!("Create in {}", calendar),
+        None => "Create in the default calendar".to_string(),
+    };
+    vec![LauncherItem {
+        uid: "quick-create".to_string(),
+        title: event.to_string(),
+        subtitle,
+        accessory: Some(event.date.format("%a %Y-%m-%d").to_string()),
+        action: "Create Event".to_string(),
+        command: Some(command("quick", &[text.trim()], &flags)),
+        url: None,
+    }]
+}
+
+/// The agenda of `date`, any day `resolve_date` understands, or an item telling why it failed
+pub async fn agenda(date: &str, calendar: Option<&str>) -> Vec<LauncherItem> {
+    let date = match crate::dates::resolve_date(date) {
+        Ok(date) => date,
+        Err(e) => return vec![LauncherItem::message("Unrecognized day", &e.to_string())],
+    };
+    match crate::calendar::fetch_agenda(&date, calendar).await {
+        Ok(events) => agenda_items(&date, &events),
+        Err(e) => vec![LauncherItem::message("Failed to load the agenda", &e.to_string())],
+    }
+}
+
+/// Tracked events matching `query`; with `past` also those that are over
+pub fn search(query: &str, past: bool) -> Vec<LauncherItem> {
+    let from = (!past).then(|| Local::now().date_naive());
+    match StateManager::new().and_then(|manager| manager.load::<CalendarItem>()) {
+        Ok(items) => search_items(&items, query, from),
+        Err(e) => vec![LauncherItem::message("Failed to load the tracked events", &e.to_string())],
+    }
+}
+
+/// The items as an Alfred Script Filter result
+pub fn alfred_json(items: &[LauncherItem]) -> Value {
+    let items: Vec<Value> = items
+        .iter()
+        .map(|item| {
+            let mut value = json!({
+                "uid": item.uid,
+                "title": item.title,
+                "subtitle": item.subtitle,
+                "valid": item.command.is_some(),
+            });
+            if let Some(command) = &item.command {
+                value["arg"] = json!(command);
+            }
+            if let Some(url) = &item.url {
+                value["quicklookurl"] = json!(url);
+            }
+            value
+        })
+        .collect();
+    json!({ "items": items })
+}
+
+/// The items as a list for a Raycast extension
+///
+/// Each item has the props of a Raycast `List.Item` and its actions: a command to run in a
+/// shell, or a URL to open.
+pub fn raycast_json(items: &[LauncherItem]) -> Value {
+    let items: Vec<Value> = items
+        .iter()
+        .map(|item| {
+            let mut actions = Vec::new();
+            if let Some(command) = &item.command {
+                actions.push(json!({ "title": item.action, "command": command }));
+            }
+            if let Some(url) = &item.url {
+                actions.push(json!({ "title": "Open Link", "url": url }));
+            }
+            let accessories: Vec<Value> =
+                item.accessory.iter().map(|text| json!({ "text": text })).collect();
+            json!({
+                "id": item.uid,
+                "title": item.title,
+                "subtitle": item.subtitle,
+                "accessories": accessories,
+                "actions": actions,
+            })
+        })
+        .collect();
+    json!({ "items": items })
+}
+
+/// Print the items in the format of `format`; text lists one item per line
+pub fn print_items(items: &[LauncherItem], format: OutputFormat) {
+    match format {
+        OutputFormat::Alfred => println!("{}", alfred_json(items)),
+        OutputFormat::Text => {
+            for item in items {
+                println!("{}  {}", item.title, item.subtitle);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(title: &str, start: &str, all_day: bool) -> AgendaEvent {
+        AgendaEvent {
+            title: title.to_string(),
+            start: start.to_string(),
+            end: "10:00".to_string(),
+            calendar: "Work".to_string(),
+            all_day,
+            url: None,
+        }
+    }
+
+    #[test]
+    fn test_agenda_items() {
+        let mut standup = event("Team sync", "09:30", false);
+        standup.url = Some("https://zoom.us/j/1".to_string());
+        let items = agenda_items("2025-04-22", &[standup, event("Offsite", "00:00", true)]);
+        assert_eq!(items[0].subtitle, "09:30–10:00 · Work");
+        assert_eq!(items[0].command.as_deref(), Some("ducktape open event 'Team sync'"));
+        assert_eq!(items[1].accessory.as_deref(), Some("All day"));
+
+        let empty = agenda_items("2025-04-22", &[]);
+        assert_eq!(empty.len(), 1);
+        assert!(empty[0].command.is_none());
+    }
+
+    #[test]
+    fn test_search_items() {
+        let item = |title: &str, date: &str, location: Option<&str>| CalendarItem {
+            title: title.to_string(),
+            date: date.to_string(),
+            time: "14:00".to_string(),
+            calendars: vec!["Work".to_string()],
+            location: location.map(str::to_string),
+            ..Default::default()
+        };
+        let mut review = item("Design review", "2025-04-22", Some("Room 4"));
+        review.uid = Some("ABC".to_string());
+        let items = vec![review, item("Dentist", "2025-04-23", None)];
+
+        let found = search_items(&items, "review", None);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].uid, "ABC");
+        assert_eq!(found[0].subtitle, "2025-04-22 14:00 · Work · Room 4");
+
+        assert!(search_items(&items, "lunch", None)[0].command.is_none());
+        assert!(search_items(&items, " ", None)[0].command.is_none());
+    }
+
+    #[test]
+    fn test_create_items() {
+        let now = NaiveDate::from_ymd_opt(2025, 4, 22).unwrap().and_hms_opt(8, 0, 0).unwrap();
+        let items = create_items("Lunch with Sam tomorrow 12:30", Some("Home"), now);
+        assert_eq!(
+            items[0].command.as_deref(),
+            Some("ducktape quick 'Lunch with Sam tomorrow 12:30' --calendar Home")
+        );
+        assert!(items[0].title.starts_with("Lunch with Sam: Wed 2025-04-23 12:30"));
+    }
+
+    #[test]
+    fn test_alfred_and_raycast_json() {
+        let items = vec![
+            LauncherItem {
+                uid: "1".to_string(),
+                title: "Team sync".to_string(),
+                subtitle: "09:30–10:00 · Work".to_string(),
+                accessory: Some("09:30".to_string()),
+                action: "Open in Calendar".to_string(),
+                command: Some("ducktape open event 'Team sync'".to_string()),
+                url: Some("https://zoom.us/j/1".to_string()),
+            },
+            LauncherItem::message("No more events", ""),
+        ];
+
+        let alfred = alfred_json(&items);
+        assert_eq!(alfred["items"][0]["arg"], "ducktape open event 'Team sync'");
+        assert_eq!(alfred["items"][0]["valid"], true);
+        assert_eq!(alfred["items"][0]["quicklookurl"], "https://zoom.us/j/1");
+        assert_eq!(alfred["items"][1]["valid"], false);
+        assert!(alfred["items"][1].get("arg").is_none());
+
+        let raycast = raycast_json(&items);
+        assert_eq!(raycast["items"][0]["accessories"][0]["text"], "09:30");
+        assert_eq!(raycast["items"][0]["actions"][0]["command"], "ducktape open event 'Team sync'");
+        assert_eq!(raycast["items"][0]["actions"][1]["url"], "https://zoom.us/j/1");
+        assert_eq!(raycast["items"][1]["actions"].as_array().unwrap().len(), 0);
+    }
+}
@@ -1,29 +1,76 @@
+pub mod address_book;
+pub mod api;
 pub mod api_server;
 pub mod app;
+pub mod app_state;
+pub mod applescript;
+pub mod audit;
+pub mod backend;
+pub mod birthdays;
 pub mod calendar;
 pub mod cli;
+pub mod command_bus;
 #[deprecated(since = "0.14.0", note = "Use parser module instead")]
 pub mod command_parser;
 pub mod command_processor;
+pub mod commands;
 pub mod config;
 pub mod contact_groups;
+pub mod context;
+pub mod dates;
+pub mod doctor;
 // pub mod contacts;  // Commented out if it doesn't exist
 // Removed deepseek_reasoning module
 pub mod env_debug;
 pub mod env_loader;
 pub mod env_manager;
 pub mod env_store;
+pub mod error;
 pub mod event_search;
 pub mod file_search;
+pub mod focus;
+pub mod help;
+pub mod holidays;
+pub mod i18n;
+pub mod ingest;
+pub mod launcher;
+pub mod links;
+pub mod logging;
+pub mod meeting;
+pub mod metrics;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
 pub mod notes;
+pub mod notifications;
+pub mod open;
+pub mod packs;
+pub mod palette;
 pub mod parser; // New modular parser module
+pub mod permissions;
+pub mod redo;
 pub mod reminder;
 pub mod reminders;
+pub mod report;
+pub mod review;
+pub mod schedule;
+pub mod script_runner;
+pub mod secrets;
+pub mod setup;
+pub mod shortcuts;
+pub mod smtp;
 pub mod state;
 pub mod storage;
+pub mod tag;
+pub mod tasks;
+pub mod theme;
+pub mod timezone;
 pub mod todo;
+pub mod upcoming;
+pub mod usage;
 pub mod utils;
 pub mod validation;
+pub mod watch;
+pub mod webhooks;
 pub mod zoom;
 
 // Deprecated modules - will be removed after migration
@@ -37,27 +84,23 @@ pub mod parser_trait;
 #[deprecated(since = "0.13.0", note = "Use crate::parser::terminal module instead")]
 pub mod terminal_parser;
 
-use anyhow::Result;
-use log::*;
 use std::path::PathBuf;
+use tracing::info;
 
-pub async fn run(_config_path: Option<PathBuf>) -> Result<()> {
+pub async fn run(_config_path: Option<PathBuf>) -> error::Result<()> {
     // Create and run the application
     let app = app::Application::new();
     info!("Initializing DuckTape application");
-    app.run().await
+    Ok(app.run().await?)
 }
 
 pub fn init_logger() {
-    env_logger::Builder::new()
-        .filter_level(log::LevelFilter::Debug)
-        .format_timestamp(None)
-        .format_target(false)
-        .init();
+    logging::init(&logging::LogOptions { verbose: 1, ..Default::default() });
 }
 
-// Re-export commonly used types
+// Re-export commonly used types; `api` is the stable interface for other programs
 pub use config::Config;
+pub use error::{DucktapeError, ErrorKind};
 pub use state::{CalendarItem, TodoItem};
 
 // Re-export parser types for convenience
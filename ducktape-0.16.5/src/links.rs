@@ -0,0 +1,218 @@
+//! Links between reminders and calendar events.
+//
+// Links are stored in DuckTape's state directory and are purely local: Calendar.app and
+// Reminders.app know nothing about them. Items are identified by kind and title, e.g.
+// `todo:"Prepare deck"` or `event:"Board meeting"`.
+
+use crate::state::{ItemLink, LinkRelation, LinkTarget, LinkedKind, StateManager};
+use anyhow::{Result, anyhow};
+use chrono::Local;
+use tracing::info;
+
+/// Errors for link management
+#[derive(Debug, thiserror::Error)]
+pub enum LinkError {
+    #[error("Invalid item reference '{0}'. Use todo:\"<title>\" or event:\"<title>\"")]
+    InvalidReference(String),
+
+    #[error("An item cannot be linked to itself")]
+    SelfLink,
+}
+
+impl LinkRelation {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "relates-to" | "relatesto" | "related" => Ok(LinkRelation::RelatesTo),
+            "blocked-by" | "blockedby" | "blocked" => Ok(LinkRelation::BlockedBy),
+            _ => Err(anyhow!(
+                "Invalid link relation: {}. Valid options are: relates-to, blocked-by",
+                s
+            )),
+        }
+    }
+}
+
+impl LinkTarget {
+    /// Parse a `kind:title` reference such as `todo:"Prepare deck"`
+    pub fn parse(reference: &str) -> Result<Self> {
+        let invalid = || anyhow!(LinkError::InvalidReference(reference.to_string()));
+        let (kind, title) = reference.trim().split_once(':').ok_or_else(invalid)?;
+        let kind = match kind.trim().to_lowercase().as_str() {
+            "todo" | "reminder" | "task" => LinkedKind::Todo,
+            "event" | "calendar" | "meeting" => LinkedKind::Event,
+            _ => return Err(invalid()),
+        };
+        let title = title.trim().trim_matches('"').trim_matches('\'').trim();
+        if title.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self { kind, title: title.to_string() })
+    }
+
+    /// Whether this reference points at the given item (titles compare case-insensitively)
+    pub fn matches(&self, kind: LinkedKind, title: &str) -> bool {
+        self.kind == kind && self.title.eq_ignore_ascii_case(title.trim())
+    }
+}
+
+impl std::fmt::Display for LinkTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            LinkedKind::Todo => "todo",
+            LinkedKind::Event => "event",
+        };
+        write!(f, "{}:\"{}\"", kind, self.title)
+    }
+}
+
+fn same_pair(link: &ItemLink, a: &LinkTarget, b: &LinkTarget) -> bool {
+    let eq = |x: &LinkTarget, y: &LinkTarget| x.matches(y.kind, &y.title);
+    (eq(&link.source, a) && eq(&link.target, b)) || (eq(&link.source, b) && eq(&link.target, a))
+}
+
+/// Load all stored links
+pub fn load_links() -> Result<Vec<ItemLink>> {
+    StateManager::new()?.load()
+}
+
+/// Link two items, replacing the relation of an existing link between them
+///
+/// Returns false when the items were already linked with the same relation.
+pub fn add_link(source: LinkTarget, target: LinkTarget, relation: LinkRelation) -> Result<bool> {
+    if source.matches(target.kind, &target.title) {
+        return Err(anyhow!(LinkError::SelfLink));
+    }
+
+    let manager = StateManager::new()?;
+    let mut links: Vec<ItemLink> = manager.load()?;
+    if links.iter().any(|l| {
+        l.relation == relation
+            && l.source.matches(source.kind, &source.title)
+            && same_pair(l, &source, &target)
+    }) {
+        return Ok(false);
+    }
+
+    links.retain(|l| !same_pair(l, &source, &target));
+    info!("Linking {} -> {} ({:?})", source, target, relation);
+    links.push(ItemLink { source, target, relation, created_at: Local::now().to_rfc3339() });
+    manager.save(&links)?;
+    Ok(true)
+}
+
+/// Remove any link between two items, returning how many links were removed
+pub fn remove_link(a: &LinkTarget, b: &LinkTarget) -> Result<usize> {
+    let manager = StateManager::new()?;
+    let mut links: Vec<ItemLink> = manager.load()?;
+    let before = links.len();
+    links.retain(|l| !same_pair(l, a, b));
+    let removed = before - links.len();
+    if removed > 0 {
+        manager.save(&links)?;
+    }
+    Ok(removed)
+}
+
+/// Describe the links of one item from that item's point of view
+///
+/// e.g. "relates to event:\"Board meeting\"" or "blocks todo:\"Ship release\"".
+pub fn describe_links(links: &[ItemLink], kind: LinkedKind, title: &str) -> Vec<String> {
+    links
+        .iter()
+        .filter_map(|link| {
+            if link.source.matches(kind, title) {
+                let verb = match link.relation {
+                    LinkRelation::RelatesTo => "relates to",
+                    LinkRelation::BlockedBy => "blocked by",
+                };
+                Some(format!("{} {}", verb, link.target))
+            } else if link.target.matches(kind, title) {
+                let verb = match link.relation {
+                    LinkRelation::RelatesTo => "relates to",
+                    LinkRelation::BlockedBy => "blocks",
+                };
+                Some(format!("{} {}", verb, link.source))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Titles of the todos linked to an event, in the order they were linked
+pub fn prep_tasks_for_event(links: &[ItemLink], event_title: &str) -> Vec<String> {
+    links
+        .iter()
+        .filter_map(|link| {
+            if link.target.matches(LinkedKind::Event, event_title)
+                && link.source.kind == LinkedKind::Todo
+            {
+                Some(link.source.title.clone())
+            } else if link.source.matches(LinkedKind::Event, event_title)
+                && link.target.kind == LinkedKind::Todo
+            {
+                Some(link.target.title.clone())
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(source: &str, target: &str, relation: LinkRelation) -> ItemLink {
+        ItemLink {
+            source: LinkTarget::parse(source).unwrap(),
+            target: LinkTarget::parse(target).unwrap(),
+            relation,
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_link_target() {
+        let target = LinkTarget::parse("todo:\"Prepare deck\"").unwrap();
+        assert_eq!(target.kind, LinkedKind::Todo);
+        assert_eq!(target.title, "Prepare deck");
+        assert_eq!(LinkTarget::parse("event:Board meeting").unwrap().kind, LinkedKind::Event);
+        assert!(LinkTarget::parse("Board meeting").is_err());
+        assert!(LinkTarget::parse("note:Minutes").is_err());
+        assert!(LinkTarget::parse("todo:").is_err());
+    }
+
+    #[test]
+    fn test_describe_links() {
+        let links = vec![
+            link("todo:Prepare deck", "event:Board meeting", LinkRelation::RelatesTo),
+            link("todo:Ship release", "todo:Prepare deck", LinkRelation::BlockedBy),
+        ];
+        assert_eq!(
+            describe_links(&links, LinkedKind::Todo, "prepare deck"),
+            vec![
+                "relates to event:\"Board meeting\"".to_string(),
+                "blocks todo:\"Ship release\"".to_string()
+            ]
+        );
+        assert_eq!(
+            describe_links(&links, LinkedKind::Todo, "Ship release"),
+            vec!["blocked by todo:\"Prepare deck\"".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_prep_tasks_for_event() {
+        let links = vec![
+            link("todo:Prepare deck", "event:Board meeting", LinkRelation::RelatesTo),
+            link("event:Board meeting", "todo:Book room", LinkRelation::BlockedBy),
+            link("todo:Other", "event:Standup", LinkRelation::RelatesTo),
+        ];
+        assert_eq!(
+            prep_tasks_for_event(&links, "Board Meeting"),
+            vec!["Prepare deck".to_string(), "Book room".to_string()]
+        );
+        assert!(prep_tasks_for_event(&links, "Lunch").is_empty());
+    }
+}
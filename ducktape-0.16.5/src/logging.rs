@@ -0,0 +1,114 @@
+//! Log output, through `tracing`.
+//
+// Commands run in a span carrying a correlation ID, so everything one command logs, from
+// parsing to the AppleScript it runs, can be found together. WebSocket connections add their
+// connection ID around that. Output is plain text or one JSON object per line, on stderr.
+// Records of crates that still use `log` are passed on to the same output.
+
+use clap::ValueEnum;
+use std::str::FromStr;
+use tracing_subscriber::EnvFilter;
+
+/// Variable selecting the output format when no `--log-format` is given
+pub const LOG_FORMAT_ENV: &str = "DUCKTAPE_LOG_FORMAT";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(anyhow::anyhow!("Invalid log format: {}. Use text or json", s)),
+        }
+    }
+}
+
+/// How much to log and how
+#[derive(Debug, Clone, Default)]
+pub struct LogOptions {
+    /// `-v` once for debug output of DuckTape, twice for trace output of everything
+    pub verbose: u8,
+    /// Only errors
+    pub quiet: bool,
+    /// Falls back to `DUCKTAPE_LOG_FORMAT`, then text
+    pub format: Option<LogFormat>,
+}
+
+impl LogOptions {
+    /// Filter directives for the options; `RUST_LOG` applies when neither flag is given
+    fn directives(&self) -> Option<&'static str> {
+        match (self.quiet, self.verbose) {
+            (true, _) => Some("error"),
+            (false, 0) => None,
+            (false, 1) => Some("info,ducktape=debug"),
+            (false, _) => Some("trace"),
+        }
+    }
+
+    fn filter(&self) -> EnvFilter {
+        match self.directives() {
+            Some(directives) => EnvFilter::new(directives),
+            None => EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        }
+    }
+
+    fn format(&self) -> LogFormat {
+        self.format
+            .or_else(|| std::env::var(LOG_FORMAT_ENV).ok().and_then(|f| f.parse().ok()))
+            .unwrap_or_default()
+    }
+}
+
+/// Send log output to stderr; does nothing when logging was set up already
+pub fn init(options: &LogOptions) {
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(options.filter())
+        .with_writer(std::io::stderr);
+    let _ = match options.format() {
+        LogFormat::Text => builder.with_target(false).try_init(),
+        LogFormat::Json => builder.json().with_current_span(true).with_span_list(true).try_init(),
+    };
+}
+
+/// A short ID tying together what one command logs
+pub fn correlation_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..12].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags_select_directives() {
+        let options = |verbose, quiet| LogOptions { verbose, quiet, format: None };
+        assert_eq!(options(0, false).directives(), None);
+        assert_eq!(options(1, false).directives(), Some("info,ducktape=debug"));
+        assert_eq!(options(3, false).directives(), Some("trace"));
+        assert_eq!(options(2, true).directives(), Some("error"));
+    }
+
+    #[test]
+    fn test_log_format_parsing() {
+        assert_eq!("JSON".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert_eq!("text".parse::<LogFormat>().unwrap(), LogFormat::Text);
+        assert!("xml".parse::<LogFormat>().is_err());
+        let options = LogOptions { format: Some(LogFormat::Json), ..Default::default() };
+        assert_eq!(options.format(), LogFormat::Json);
+    }
+
+    #[test]
+    fn test_correlation_ids_are_short_and_distinct() {
+        let (a, b) = (correlation_id(), correlation_id());
+        assert_eq!(a.len(), 12);
+        assert_ne!(a, b);
+    }
+}
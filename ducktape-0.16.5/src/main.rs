@@ -1,43 +1,64 @@
 use ducktape::api_server;
 use ducktape::app::Application;
+use ducktape::app_state::SharedState;
 use ducktape::cli;
 use ducktape::config::Config;
-use ducktape::env_debug;
+use ducktape::error::DucktapeError;
 
 use anyhow::Result;
 use clap::Parser;
-use log::debug;
+use std::process::ExitCode;
+use tracing::debug;
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize logging
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+async fn main() -> ExitCode {
+    let result = run().await;
+    // Webhooks for the changes made are sent in the background; let them finish
+    ducktape::webhooks::flush().await;
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            let e = DucktapeError::from(e);
+            eprintln!("Error: {}", e);
+            ExitCode::from(e.exit_code() as u8)
+        }
+    }
+}
+
+async fn run() -> Result<()> {
+    // Parse command line arguments using Clap
+    let cli = cli::Cli::parse();
+
+    // Initialize logging as the flags ask
+    ducktape::logging::init(&cli.log_options());
 
     // Load environment variables at startup
     if let Err(e) = dotenvy::dotenv() {
         println!("Warning: Failed to load .env file: {}", e);
     }
 
-    // Force set the API key
-    env_debug::force_set_api_key();
-
     // Create a String from all command line args to preserve exact quoting
-    let input = std::env::args().skip(1).collect::<Vec<String>>().join(" ");
+    let input = cli::without_global_args(std::env::args().skip(1)).join(" ");
 
     debug!("Raw input from command line: '{}'", input);
 
-    // Parse command line arguments using Clap
-    let cli = cli::Cli::parse();
+    // Select the profile before any configuration is loaded
+    if let Some(profile) = &cli.profile {
+        std::env::set_var(ducktape::config::PROFILE_ENV, profile);
+    }
+    if let Some(output) = cli.output {
+        ducktape::launcher::set_output(output);
+    }
 
     // Create application instance early so we can use it for commands
     let app = Application::new();
 
     // Handle special flags
     if cli.api_server {
-        // Load config and start API server only
-        let config = Config::load()?;
+        // Check the config, then start the API server only
+        Config::load()?;
         let address = "127.0.0.1:3000";
-        return api_server::start_api_server(config, &address).await;
+        return api_server::start_api_server(SharedState::global().clone(), &address).await;
     }
 
     if cli.full {
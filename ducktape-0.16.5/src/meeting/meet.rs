@@ -0,0 +1,84 @@
+//! Google Meet meeting provider.
+//
+// Creates meeting spaces through the Google Meet REST API. Meet spaces are not tied to
+// a time slot, so the schedule lives only in the calendar event. The API requires an
+// OAuth access token with the `meetings.space.created` scope in `GOOGLE_MEET_ACCESS_TOKEN`.
+
+use super::{MeetingDetails, MeetingProvider, MeetingRequest};
+use crate::config::MeetingService;
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use reqwest::Client;
+use secrecy::{ExposeSecret, Secret};
+use serde::Deserialize;
+use std::env;
+use tracing::{debug, error, info};
+
+const MEET_API_BASE: &str = "https://meet.googleapis.com/v2";
+
+pub struct MeetMeetingProvider {
+    access_token: Secret<String>,
+    client: Client,
+}
+
+#[derive(Debug, Deserialize)]
+struct SpaceResponse {
+    name: String,
+    #[serde(rename = "meetingUri")]
+    meeting_uri: String,
+}
+
+impl MeetMeetingProvider {
+    pub fn new() -> Result<Self> {
+        let access_token = env::var("GOOGLE_MEET_ACCESS_TOKEN")
+            .map(Secret::new)
+            .map_err(|_| anyhow!("GOOGLE_MEET_ACCESS_TOKEN not found in environment"))?;
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .map_err(|e| anyhow!("Failed to create HTTP client: {}", e))?;
+
+        Ok(Self { access_token, client })
+    }
+}
+
+#[async_trait]
+impl MeetingProvider for MeetMeetingProvider {
+    fn service(&self) -> MeetingService {
+        MeetingService::Meet
+    }
+
+    async fn create_meeting(&self, request: &MeetingRequest) -> Result<MeetingDetails> {
+        debug!("Creating Google Meet space for: {}", request.topic);
+
+        let response = self
+            .client
+            .post(format!("{}/spaces", MEET_API_BASE))
+            .header("Authorization", format!("Bearer {}", self.access_token.expose_secret()))
+            .json(&serde_json::json!({}))
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to send Google Meet API request: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text =
+                response.text().await.unwrap_or_else(|_| "Unable to get error response".into());
+            error!("Google Meet API error: {} - {}", status, error_text);
+            return Err(anyhow!("Google Meet API error ({}): {}", status, error_text));
+        }
+
+        let space: SpaceResponse = response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse Google Meet API response: {}", e))?;
+        info!("Created Google Meet space: {} ({})", space.name, space.meeting_uri);
+
+        Ok(MeetingDetails {
+            service: MeetingService::Meet,
+            id: space.name,
+            join_url: space.meeting_uri,
+            password: None,
+        })
+    }
+}
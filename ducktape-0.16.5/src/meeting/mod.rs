@@ -0,0 +1,171 @@
+//! Online meeting providers for calendar events.
+//
+// This module puts Zoom, Microsoft Teams and Google Meet behind a common
+// `MeetingProvider` trait so that calendar events can embed a join link
+// regardless of which service the user prefers. Tests can schedule every
+// meeting with a fake provider set with `set_meeting_provider`.
+
+use crate::config::MeetingService;
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use std::sync::{Arc, RwLock};
+
+pub mod meet;
+pub mod teams;
+pub mod zoom;
+
+/// Details needed to schedule an online meeting
+#[derive(Debug, Clone)]
+pub struct MeetingRequest {
+    pub topic: String,
+    /// Start date (YYYY-MM-DD)
+    pub date: String,
+    /// Start time (HH:MM)
+    pub start_time: String,
+    /// End time (HH:MM)
+    pub end_time: Option<String>,
+    pub agenda: Option<String>,
+}
+
+/// A scheduled online meeting
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeetingDetails {
+    pub service: MeetingService,
+    pub id: String,
+    pub join_url: String,
+    pub password: Option<String>,
+}
+
+impl MeetingDetails {
+    /// Text block appended to the event description
+    pub fn description_block(&self) -> String {
+        let password_info =
+            self.password.as_ref().map_or(String::new(), |p| format!("\nPassword: {}", p));
+        format!(
+            "\n\n--------------------\n{} Meeting\n--------------------\nJoin URL: {}{}",
+            self.service.label(),
+            self.join_url,
+            password_info
+        )
+    }
+}
+
+/// Common interface for online meeting services
+#[async_trait]
+pub trait MeetingProvider: Send + Sync {
+    /// The service this provider talks to
+    fn service(&self) -> MeetingService;
+
+    /// Schedule a meeting and return its join details
+    async fn create_meeting(&self, request: &MeetingRequest) -> Result<MeetingDetails>;
+}
+
+/// Create the provider for a meeting service using credentials from the environment
+pub fn provider_for(service: MeetingService) -> Result<Box<dyn MeetingProvider>> {
+    match service {
+        MeetingService::Zoom => Ok(Box::new(zoom::ZoomMeetingProvider::new()?)),
+        MeetingService::Teams => Ok(Box::new(teams::TeamsMeetingProvider::new()?)),
+        MeetingService::Meet => Ok(Box::new(meet::MeetMeetingProvider::new()?)),
+    }
+}
+
+static MEETING_PROVIDER: Lazy<RwLock<Option<Arc<dyn MeetingProvider>>>> =
+    Lazy::new(|| RwLock::new(None));
+
+/// Schedule every meeting with `provider`, whatever the service; `None` restores the services
+pub fn set_meeting_provider(provider: Option<Arc<dyn MeetingProvider>>) {
+    match MEETING_PROVIDER.write() {
+        Ok(mut current) => *current = provider,
+        Err(poisoned) => *poisoned.into_inner() = provider,
+    }
+}
+
+fn meeting_provider() -> Option<Arc<dyn MeetingProvider>> {
+    MEETING_PROVIDER.read().ok().and_then(|provider| provider.clone())
+}
+
+/// Schedule a meeting with the given service
+pub async fn create_meeting(
+    service: MeetingService,
+    request: &MeetingRequest,
+) -> Result<MeetingDetails> {
+    if let Some(provider) = meeting_provider() {
+        return provider.create_meeting(request).await;
+    }
+    provider_for(service)?.create_meeting(request).await
+}
+
+/// Detect which meeting service natural language input refers to
+///
+/// Returns `None` when the input does not ask for an online meeting. Generic phrases
+/// like "video call" map to Zoom, which is the default service.
+pub fn detect_meeting_service(input: &str) -> Option<MeetingService> {
+    let input_lower = input.to_lowercase();
+
+    let teams_keywords = ["teams meeting", "teams call", "microsoft teams", "ms teams", "on teams"];
+    let meet_keywords = ["google meet", "gmeet", "meet.google", "hangout"];
+    let generic_keywords =
+        ["zoom", "video call", "video meeting", "virtual meeting", "online meeting"];
+
+    if teams_keywords.iter().any(|&keyword| input_lower.contains(keyword)) {
+        Some(MeetingService::Teams)
+    } else if meet_keywords.iter().any(|&keyword| input_lower.contains(keyword)) {
+        Some(MeetingService::Meet)
+    } else if generic_keywords.iter().any(|&keyword| input_lower.contains(keyword)) {
+        Some(MeetingService::Zoom)
+    } else {
+        None
+    }
+}
+
+/// Combine a date and HH:MM time into a local timestamp and convert it to RFC 3339 UTC
+pub(crate) fn to_utc_rfc3339(date: &str, time: &str) -> Result<String> {
+    use chrono::{Local, NaiveDateTime, TimeZone, Utc};
+
+    let naive = NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M")
+        .map_err(|_| anyhow!("Invalid meeting date/time: {} {}", date, time))?;
+    let local = Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .ok_or_else(|| anyhow!("Invalid or ambiguous meeting time: {} {}", date, time))?;
+    Ok(local.with_timezone(&Utc).format("%Y-%m-%dT%H:%M:%SZ").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_meeting_service() {
+        assert_eq!(
+            detect_meeting_service("Set up a Teams meeting with Jane"),
+            Some(MeetingService::Teams)
+        );
+        assert_eq!(detect_meeting_service("google meet at 3pm"), Some(MeetingService::Meet));
+        assert_eq!(detect_meeting_service("zoom call tomorrow"), Some(MeetingService::Zoom));
+        assert_eq!(detect_meeting_service("video call with Bob"), Some(MeetingService::Zoom));
+        assert_eq!(detect_meeting_service("lunch with the team"), None);
+    }
+
+    #[test]
+    fn test_description_block() {
+        let details = MeetingDetails {
+            service: MeetingService::Teams,
+            id: "abc".to_string(),
+            join_url: "https://teams.microsoft.com/l/meetup-join/abc".to_string(),
+            password: None,
+        };
+        let block = details.description_block();
+        assert!(block.contains("Microsoft Teams Meeting"));
+        assert!(block.contains("Join URL: https://teams.microsoft.com/l/meetup-join/abc"));
+        assert!(!block.contains("Password"));
+    }
+
+    #[test]
+    fn test_meeting_service_from_str() {
+        assert_eq!(MeetingService::from_str("Teams").unwrap(), MeetingService::Teams);
+        assert_eq!(MeetingService::from_str("google-meet").unwrap(), MeetingService::Meet);
+        assert!(MeetingService::from_str("webex").is_err());
+    }
+}
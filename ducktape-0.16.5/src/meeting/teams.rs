@@ -0,0 +1,147 @@
+//! Microsoft Teams meeting provider.
+//
+// Creates online meetings through the Microsoft Graph API using an app registration
+// with the client credentials flow. The organizer is the user given by
+// `MS_TEAMS_USER_ID`, which needs an application access policy for online meetings.
+
+use super::{MeetingDetails, MeetingProvider, MeetingRequest, to_utc_rfc3339};
+use crate::config::MeetingService;
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use reqwest::Client;
+use secrecy::{ExposeSecret, Secret};
+use serde::Deserialize;
+use std::env;
+use tracing::{debug, error, info};
+
+const GRAPH_API_BASE: &str = "https://graph.microsoft.com/v1.0";
+
+pub struct TeamsMeetingProvider {
+    tenant_id: Secret<String>,
+    client_id: Secret<String>,
+    client_secret: Secret<String>,
+    user_id: String,
+    client: Client,
+}
+
+#[derive(Debug, Deserialize)]
+struct OnlineMeetingResponse {
+    id: String,
+    #[serde(rename = "joinWebUrl")]
+    join_web_url: String,
+}
+
+impl TeamsMeetingProvider {
+    pub fn new() -> Result<Self> {
+        let read =
+            |name: &str| env::var(name).map_err(|_| anyhow!("{} not found in environment", name));
+
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .map_err(|e| anyhow!("Failed to create HTTP client: {}", e))?;
+
+        Ok(Self {
+            tenant_id: Secret::new(read("MS_TENANT_ID")?),
+            client_id: Secret::new(read("MS_CLIENT_ID")?),
+            client_secret: Secret::new(read("MS_CLIENT_SECRET")?),
+            user_id: read("MS_TEAMS_USER_ID")?,
+            client,
+        })
+    }
+
+    async fn get_access_token(&self) -> Result<String> {
+        #[derive(Deserialize)]
+        struct TokenResponse {
+            access_token: String,
+        }
+
+        let token_url = format!(
+            "https://login.microsoftonline.com/{}/oauth2/v2.0/token",
+            self.tenant_id.expose_secret()
+        );
+        let response = self
+            .client
+            .post(&token_url)
+            .form(&[
+                ("grant_type", "client_credentials"),
+                ("client_id", self.client_id.expose_secret().as_str()),
+                ("client_secret", self.client_secret.expose_secret().as_str()),
+                ("scope", "https://graph.microsoft.com/.default"),
+            ])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text =
+                response.text().await.unwrap_or_else(|_| "Unable to get error response".into());
+            error!("Microsoft OAuth error response: {}", error_text);
+            return Err(anyhow!("Microsoft OAuth error ({}): {}", status, error_text));
+        }
+
+        let token: TokenResponse = response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse Microsoft OAuth response: {}", e))?;
+        Ok(token.access_token)
+    }
+}
+
+#[async_trait]
+impl MeetingProvider for TeamsMeetingProvider {
+    fn service(&self) -> MeetingService {
+        MeetingService::Teams
+    }
+
+    async fn create_meeting(&self, request: &MeetingRequest) -> Result<MeetingDetails> {
+        debug!("Creating Teams meeting with subject: {}", request.topic);
+        let token = self.get_access_token().await?;
+
+        let start = to_utc_rfc3339(&request.date, &request.start_time)?;
+        let end = match &request.end_time {
+            Some(end_time) => to_utc_rfc3339(&request.date, end_time)?,
+            None => {
+                let start_dt = chrono::DateTime::parse_from_rfc3339(&start)?;
+                (start_dt + chrono::Duration::hours(1)).format("%Y-%m-%dT%H:%M:%SZ").to_string()
+            }
+        };
+
+        let body = serde_json::json!({
+            "subject": request.topic,
+            "startDateTime": start,
+            "endDateTime": end,
+        });
+
+        let url = format!("{}/users/{}/onlineMeetings", GRAPH_API_BASE, self.user_id);
+        let response = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to send Microsoft Graph request: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text =
+                response.text().await.unwrap_or_else(|_| "Unable to get error response".into());
+            error!("Microsoft Graph error: {} - {}", status, error_text);
+            return Err(anyhow!("Microsoft Graph error ({}): {}", status, error_text));
+        }
+
+        let meeting: OnlineMeetingResponse = response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse Microsoft Graph response: {}", e))?;
+        info!("Created Teams meeting: ID={}, URL={}", meeting.id, meeting.join_web_url);
+
+        Ok(MeetingDetails {
+            service: MeetingService::Teams,
+            id: meeting.id,
+            join_url: meeting.join_web_url,
+            password: None,
+        })
+    }
+}
@@ -0,0 +1,52 @@
+//! Zoom meeting provider.
+//
+// Thin adapter over `crate::zoom::ZoomClient`.
+
+use super::{MeetingDetails, MeetingProvider, MeetingRequest};
+use crate::config::MeetingService;
+use crate::zoom::{ZoomClient, ZoomMeetingOptions, calculate_meeting_duration, format_zoom_time};
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+use tracing::info;
+
+pub struct ZoomMeetingProvider {
+    client: Mutex<ZoomClient>,
+}
+
+impl ZoomMeetingProvider {
+    pub fn new() -> Result<Self> {
+        Ok(Self { client: Mutex::new(ZoomClient::new()?) })
+    }
+}
+
+#[async_trait]
+impl MeetingProvider for ZoomMeetingProvider {
+    fn service(&self) -> MeetingService {
+        MeetingService::Zoom
+    }
+
+    async fn create_meeting(&self, request: &MeetingRequest) -> Result<MeetingDetails> {
+        let duration = match &request.end_time {
+            Some(end_time) => calculate_meeting_duration(&request.start_time, end_time)?,
+            None => 60, // Default 1 hour
+        };
+        let options = ZoomMeetingOptions {
+            topic: request.topic.clone(),
+            start_time: format_zoom_time(&request.date, &request.start_time)?,
+            duration,
+            password: None,
+            agenda: request.agenda.clone(),
+        };
+
+        let meeting = self.client.lock().await.create_meeting(options).await?;
+        info!("Created Zoom meeting: ID={}, URL={}", meeting.id, meeting.join_url);
+
+        Ok(MeetingDetails {
+            service: MeetingService::Zoom,
+            id: meeting.id.to_string(),
+            join_url: meeting.join_url,
+            password: meeting.password,
+        })
+    }
+}
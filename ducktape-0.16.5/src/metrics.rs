@@ -0,0 +1,226 @@
+//! Counters and timings, served by the API server at `/metrics`.
+//
+// The numbers are kept in memory from the start of the process and rendered in the
+// Prometheus text format. Commands are counted by the command bus and processor, parser
+// latency by the fallback chain, script time by the `ScriptRunner` and connections by the
+// WebSocket server.
+
+use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::Duration;
+
+const COMMANDS: &str = "ducktape_commands_total";
+const PARSER_DURATION: &str = "ducktape_parser_duration_seconds";
+const SCRIPT_DURATION: &str = "ducktape_applescript_duration_seconds";
+const WEBSOCKET_CONNECTIONS: &str = "ducktape_websocket_connections";
+const WEBSOCKET_CONNECTIONS_TOTAL: &str = "ducktape_websocket_connections_total";
+
+/// Upper bounds of the parser latency buckets, in seconds
+const PARSER_BUCKETS: &[f64] = &[0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Upper bounds of the script time buckets, in seconds
+const SCRIPT_BUCKETS: &[f64] = &[0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+static REGISTRY: Lazy<Mutex<Registry>> = Lazy::new(|| Mutex::new(Registry::default()));
+static OPEN_CONNECTIONS: AtomicI64 = AtomicI64::new(0);
+
+#[derive(Default)]
+struct Registry {
+    /// Counters by name and labels
+    counters: BTreeMap<(&'static str, String), u64>,
+    histograms: BTreeMap<(&'static str, String), Histogram>,
+}
+
+struct Histogram {
+    bounds: &'static [f64],
+    /// Observations at or below each bound
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self { bounds, counts: vec![0; bounds.len()], sum: 0.0, count: 0 }
+    }
+
+    fn observe(&mut self, value: f64) {
+        for (bound, count) in self.bounds.iter().zip(&mut self.counts) {
+            if value <= *bound {
+                *count += 1;
+            }
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+/// Labels in the text format, e.g. `command="calendar",outcome="ok"`
+fn labels(pairs: &[(&str, &str)]) -> String {
+    pairs
+        .iter()
+        .map(|(name, value)| {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+            format!("{}=\"{}\"", name, value)
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn increment(name: &'static str, labels: String) {
+    *REGISTRY.lock().unwrap().counters.entry((name, labels)).or_default() += 1;
+}
+
+fn observe(name: &'static str, bounds: &'static [f64], labels: String, elapsed: Duration) {
+    REGISTRY
+        .lock()
+        .unwrap()
+        .histograms
+        .entry((name, labels))
+        .or_insert_with(|| Histogram::new(bounds))
+        .observe(elapsed.as_secs_f64());
+}
+
+/// Count a command that finished with `outcome`: "ok", "error" or "duplicate"
+pub fn command_finished(command: &str, outcome: &str) {
+    increment(COMMANDS, labels(&[("command", command), ("outcome", outcome)]));
+}
+
+/// Time a parser provider took to answer or fail
+pub fn parser_finished(provider: &str, outcome: &str, elapsed: Duration) {
+    let labels = labels(&[("provider", provider), ("outcome", outcome)]);
+    observe(PARSER_DURATION, PARSER_BUCKETS, labels, elapsed);
+}
+
+/// Time an osascript run took
+pub fn script_finished(language: &str, elapsed: Duration) {
+    observe(SCRIPT_DURATION, SCRIPT_BUCKETS, labels(&[("language", language)]), elapsed);
+}
+
+/// An open WebSocket connection, counted until dropped
+pub struct WebSocketConnection(());
+
+impl Drop for WebSocketConnection {
+    fn drop(&mut self) {
+        OPEN_CONNECTIONS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Count a WebSocket connection for as long as the returned value lives
+pub fn websocket_connected() -> WebSocketConnection {
+    OPEN_CONNECTIONS.fetch_add(1, Ordering::Relaxed);
+    increment(WEBSOCKET_CONNECTIONS_TOTAL, String::new());
+    WebSocketConnection(())
+}
+
+fn series(name: &str, labels: &str) -> String {
+    if labels.is_empty() { name.to_string() } else { format!("{}{{{}}}", name, labels) }
+}
+
+/// All metrics in the Prometheus text format
+pub fn render() -> String {
+    let registry = REGISTRY.lock().unwrap();
+    let mut out = String::new();
+    let header = |out: &mut String, name: &str, kind: &str, help: &str| {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    };
+
+    for (name, help) in [
+        (COMMANDS, "Commands run, by command and outcome"),
+        (WEBSOCKET_CONNECTIONS_TOTAL, "WebSocket connections opened"),
+    ] {
+        header(&mut out, name, "counter", help);
+        for ((_, labels), value) in registry.counters.iter().filter(|((n, _), _)| *n == name) {
+            let _ = writeln!(out, "{} {}", series(name, labels), value);
+        }
+    }
+
+    for (name, help) in [
+        (PARSER_DURATION, "Time parser providers took to answer, by provider and outcome"),
+        (SCRIPT_DURATION, "Time osascript runs took, by script language"),
+    ] {
+        header(&mut out, name, "histogram", help);
+        for ((_, labels), histogram) in registry.histograms.iter().filter(|((n, _), _)| *n == name)
+        {
+            let separator = if labels.is_empty() { "" } else { "," };
+            for (bound, count) in histogram.bounds.iter().zip(&histogram.counts) {
+                let bucket = format!("{}{}le=\"{}\"", labels, separator, bound);
+                let _ = writeln!(out, "{} {}", series(&format!("{}_bucket", name), &bucket), count);
+            }
+            let bucket = format!("{}{}le=\"+Inf\"", labels, separator);
+            let _ = writeln!(
+                out,
+                "{} {}",
+                series(&format!("{}_bucket", name), &bucket),
+                histogram.count
+            );
+            let _ = writeln!(out, "{} {}", series(&format!("{}_sum", name), labels), histogram.sum);
+            let _ =
+                writeln!(out, "{} {}", series(&format!("{}_count", name), labels), histogram.count);
+        }
+    }
+
+    header(&mut out, WEBSOCKET_CONNECTIONS, "gauge", "Open WebSocket connections");
+    let _ = writeln!(out, "{} {}", WEBSOCKET_CONNECTIONS, OPEN_CONNECTIONS.load(Ordering::Relaxed));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commands_are_counted_by_label() {
+        command_finished("metrics-test", "ok");
+        command_finished("metrics-test", "ok");
+        command_finished("metrics-test", "error");
+        let text = render();
+        assert!(text.contains("# TYPE ducktape_commands_total counter"));
+        assert!(
+            text.contains("ducktape_commands_total{command=\"metrics-test\",outcome=\"ok\"} 2")
+        );
+        assert!(
+            text.contains("ducktape_commands_total{command=\"metrics-test\",outcome=\"error\"} 1")
+        );
+    }
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        script_finished("metrics-test", Duration::from_millis(200));
+        script_finished("metrics-test", Duration::from_secs(3));
+        let text = render();
+        let bucket = |le: &str| {
+            format!(
+                "ducktape_applescript_duration_seconds_bucket{{language=\"metrics-test\",le=\"{}\"}}",
+                le
+            )
+        };
+        assert!(text.contains(&format!("{} 0", bucket("0.1"))));
+        assert!(text.contains(&format!("{} 1", bucket("0.25"))));
+        assert!(text.contains(&format!("{} 2", bucket("5"))));
+        assert!(text.contains(&format!("{} 2", bucket("+Inf"))));
+        assert!(
+            text.contains(
+                "ducktape_applescript_duration_seconds_count{language=\"metrics-test\"} 2"
+            )
+        );
+    }
+
+    #[test]
+    fn test_label_values_are_escaped() {
+        assert_eq!(labels(&[("command", "say \"hi\"\n")]), "command=\"say \\\"hi\\\"\\n\"");
+    }
+
+    #[test]
+    fn test_websocket_connections_gauge() {
+        let before = OPEN_CONNECTIONS.load(Ordering::Relaxed);
+        let connection = websocket_connected();
+        assert_eq!(OPEN_CONNECTIONS.load(Ordering::Relaxed), before + 1);
+        drop(connection);
+        assert_eq!(OPEN_CONNECTIONS.load(Ordering::Relaxed), before);
+    }
+}
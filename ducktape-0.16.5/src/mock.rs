@@ -0,0 +1,284 @@
+//! Stand-ins for language models, osascript and meeting services.
+//
+// Built with the `mock` feature (and for ducktape's own tests), so that crates using ducktape
+// can test their code without API keys, a network or a Mac. Each mock records what it was
+// asked and answers with canned responses:
+//
+//     let llm = Arc::new(MockLlm::new(["ducktape todo create \"Buy milk\""]));
+//     let parser = GrokParser::with_client(llm.clone());
+//
+//     let scripts = Arc::new(MockScripts::new().on("make new event", "Success: 42"));
+//     script_runner::set_script_handler(Some(scripts.clone()));
+//
+//     meeting::set_meeting_provider(Some(Arc::new(MockMeetings::new(MeetingService::Zoom))));
+//
+// Answers from a mock language model are not cached. `set_llm_client`, `set_script_handler` and
+// `set_meeting_provider` are process-wide, so tests that use them should not run at the same
+// time and should reset them with `None`.
+
+use crate::config::MeetingService;
+use crate::meeting::{MeetingDetails, MeetingProvider, MeetingRequest};
+use crate::parser::llm::LlmClient;
+use crate::parser::traits::{ParseProgress, ProgressSender};
+use crate::script_runner::{ScriptHandler, ScriptLanguage};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use serde_json::{Value, json};
+use std::collections::VecDeque;
+use std::os::unix::process::ExitStatusExt;
+use std::process::{ExitStatus, Output};
+use std::sync::Mutex;
+
+/// Language model that answers with canned commands
+///
+/// Answers are given in order and the last one is repeated; a mock without answers fails
+/// every request.
+#[derive(Debug, Default)]
+pub struct MockLlm {
+    answers: Mutex<VecDeque<Result<String, String>>>,
+    requests: Mutex<Vec<Value>>,
+}
+
+impl MockLlm {
+    pub fn new<S: Into<String>>(answers: impl IntoIterator<Item = S>) -> Self {
+        let answers = answers.into_iter().map(|answer| Ok(answer.into())).collect();
+        Self { answers: Mutex::new(answers), requests: Mutex::new(Vec::new()) }
+    }
+
+    /// Fail the next request with `message`, e.g. to test a fallback provider
+    pub fn fail_next(self, message: &str) -> Self {
+        self.answers.lock().unwrap().push_front(Err(message.to_string()));
+        self
+    }
+
+    /// Every request body, in order
+    pub fn requests(&self) -> Vec<Value> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// What the user asked in each request
+    pub fn inputs(&self) -> Vec<String> {
+        self.requests()
+            .iter()
+            .filter_map(|body| {
+                body["messages"].as_array()?.last()?["content"].as_str().map(str::to_string)
+            })
+            .collect()
+    }
+
+    fn next_answer(&self) -> Result<String> {
+        let mut answers = self.answers.lock().unwrap();
+        let answer = if answers.len() > 1 { answers.pop_front() } else { answers.front().cloned() };
+        match answer {
+            Some(Ok(answer)) => Ok(answer),
+            Some(Err(message)) => Err(anyhow!(message)),
+            None => Err(anyhow!("MockLlm has no answers")),
+        }
+    }
+}
+
+#[async_trait]
+impl LlmClient for MockLlm {
+    async fn complete(&self, body: &Value, progress: Option<&ProgressSender>) -> Result<Value> {
+        self.requests.lock().unwrap().push(body.clone());
+        let answer = self.next_answer()?;
+        if let Some(progress) = progress {
+            let _ = progress.send(ParseProgress::Partial(answer.clone()));
+        }
+        Ok(json!({
+            "model": body["model"],
+            "choices": [{ "index": 0, "message": { "role": "assistant", "content": answer } }]
+        }))
+    }
+}
+
+/// osascript stand-in that records scripts and answers by what they contain
+#[derive(Debug)]
+pub struct MockScripts {
+    responses: Vec<(String, Result<String, String>)>,
+    default_stdout: String,
+    calls: Mutex<Vec<(ScriptLanguage, String)>>,
+}
+
+impl Default for MockScripts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockScripts {
+    /// Answers every script with "Success: mock-id"
+    pub fn new() -> Self {
+        Self {
+            responses: Vec::new(),
+            default_stdout: "Success: mock-id".to_string(),
+            calls: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Answer scripts containing `needle` with `stdout`; the first match wins
+    pub fn on(mut self, needle: &str, stdout: &str) -> Self {
+        self.responses.push((needle.to_string(), Ok(stdout.to_string())));
+        self
+    }
+
+    /// Fail scripts containing `needle` with `stderr`, like an AppleScript error
+    pub fn fail_on(mut self, needle: &str, stderr: &str) -> Self {
+        self.responses.push((needle.to_string(), Err(stderr.to_string())));
+        self
+    }
+
+    /// Answer scripts that match nothing with `stdout`
+    pub fn otherwise(mut self, stdout: &str) -> Self {
+        self.default_stdout = stdout.to_string();
+        self
+    }
+
+    /// Every script run, in order
+    pub fn scripts(&self) -> Vec<String> {
+        self.calls.lock().unwrap().iter().map(|(_, script)| script.clone()).collect()
+    }
+
+    /// Every script run, with its language
+    pub fn calls(&self) -> Vec<(ScriptLanguage, String)> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+impl ScriptHandler for MockScripts {
+    fn run(&self, language: ScriptLanguage, script: &str) -> Result<Output> {
+        self.calls.lock().unwrap().push((language, script.to_string()));
+        let response = self
+            .responses
+            .iter()
+            .find(|(needle, _)| script.contains(needle.as_str()))
+            .map(|(_, response)| response.clone())
+            .unwrap_or_else(|| Ok(self.default_stdout.clone()));
+        Ok(match response {
+            Ok(stdout) => Output {
+                status: ExitStatus::from_raw(0),
+                stdout: stdout.into_bytes(),
+                stderr: Vec::new(),
+            },
+            // osascript exits with 1 when a script fails
+            Err(stderr) => Output {
+                status: ExitStatus::from_raw(1 << 8),
+                stdout: Vec::new(),
+                stderr: stderr.into_bytes(),
+            },
+        })
+    }
+}
+
+/// Meeting service that hands out made-up join links
+#[derive(Debug)]
+pub struct MockMeetings {
+    service: MeetingService,
+    requests: Mutex<Vec<MeetingRequest>>,
+}
+
+impl MockMeetings {
+    pub fn new(service: MeetingService) -> Self {
+        Self { service, requests: Mutex::new(Vec::new()) }
+    }
+
+    /// Every meeting asked for, in order
+    pub fn requests(&self) -> Vec<MeetingRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl MeetingProvider for MockMeetings {
+    fn service(&self) -> MeetingService {
+        self.service
+    }
+
+    async fn create_meeting(&self, request: &MeetingRequest) -> Result<MeetingDetails> {
+        let mut requests = self.requests.lock().unwrap();
+        requests.push(request.clone());
+        let id = format!("mock-{}", requests.len());
+        Ok(MeetingDetails {
+            service: self.service,
+            join_url: format!("https://meetings.example.com/{}", id),
+            id,
+            password: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ParseResult;
+    use crate::parser::Parser as _;
+    use crate::parser::deepseek::DeepSeekParser;
+    use crate::parser::grok::GrokParser;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_mock_llm_answers_parser() -> Result<()> {
+        let llm = Arc::new(
+            MockLlm::new([
+                "ducktape todo create \"Buy milk\"",
+                "ducktape todo create \"Call Sam\"",
+            ])
+            .fail_next("rate limited"),
+        );
+        let parser = DeepSeekParser::with_client(llm.clone());
+
+        assert!(parser.parse_input("remind me to buy milk").await.is_err());
+        let result = parser.parse_input("remind me to buy milk").await?;
+        let ParseResult::CommandString(command) = result else { panic!("no command") };
+        assert!(command.contains("todo create \"Buy milk\""));
+        parser.parse_input("remind me to call Sam").await?;
+        parser.parse_input("remind me to call Sam again").await?;
+        assert_eq!(llm.requests().len(), 4);
+        assert_eq!(llm.inputs().last().map(String::as_str), Some("remind me to call Sam again"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mock_llm_answers_grok_parser() -> Result<()> {
+        let llm = Arc::new(MockLlm::new(["ducktape todo create \"Water plants\" --priority low"]));
+        let parser = GrokParser::with_client(llm.clone());
+
+        let result = parser.parse_input("remind me to water the plants").await?;
+        let ParseResult::CommandString(command) = result else { panic!("no command") };
+        assert!(command.contains("todo create \"Water plants\""));
+        assert_eq!(llm.requests()[0]["model"], crate::parser::grok::api::XAI.model().as_str());
+        assert_eq!(llm.inputs(), ["remind me to water the plants"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mock_scripts() -> Result<()> {
+        let scripts = MockScripts::new().on("get name", "Work, Home").fail_on("delete", "(-1728)");
+        assert_eq!(
+            scripts.run(ScriptLanguage::AppleScript, "get name of calendars")?.stdout,
+            b"Work, Home"
+        );
+        assert!(!scripts.run(ScriptLanguage::AppleScript, "delete event 1")?.status.success());
+        assert_eq!(scripts.run(ScriptLanguage::JavaScript, "1 + 1")?.stdout, b"Success: mock-id");
+        assert_eq!(scripts.scripts().len(), 3);
+        assert_eq!(scripts.calls()[2].0, ScriptLanguage::JavaScript);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mock_meetings() -> Result<()> {
+        let meetings = MockMeetings::new(MeetingService::Teams);
+        let request = MeetingRequest {
+            topic: "Standup".to_string(),
+            date: "2025-05-01".to_string(),
+            start_time: "09:00".to_string(),
+            end_time: None,
+            agenda: None,
+        };
+        let details = meetings.create_meeting(&request).await?;
+        assert_eq!(details.service, MeetingService::Teams);
+        assert_eq!(details.join_url, "https://meetings.example.com/mock-1");
+        assert_eq!(meetings.requests()[0].topic, "Standup");
+        Ok(())
+    }
+}
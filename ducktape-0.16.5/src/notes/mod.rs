@@ -1,10 +1,12 @@
 //! Notes management functionality.
 //!
-//! This module provides integration with macOS Notes.app.
+//! This module provides integration with macOS Notes.app, or with the notes backend chosen in
+//! config.toml (see `crate::backend`).
 
+use crate::webhooks::{Action, ItemKind, note_data, notify};
 use anyhow::Result;
 
-mod notes_applescript;
+pub(crate) mod notes_applescript;
 mod notes_types;
 mod notes_util;
 mod notes_validation;
@@ -12,28 +14,73 @@ mod notes_validation;
 pub use notes_types::*;
 pub use notes_validation::*;
 
-/// Create a new note in Apple Notes
-pub async fn create_note(config: NoteConfig<'_>) -> Result<()> {
-    // Implementation relies on the notes_applescript module
-    notes_applescript::create_note(config).await
+/// Create a new note
+pub async fn create_note(config: NoteConfig<'_>) -> crate::error::Result<()> {
+    let hook = note_data(config.title, config.folder, Some(config.content));
+    crate::backend::notes()?.create_note(config).await?;
+    notify(ItemKind::Note, Action::Created, hook);
+    Ok(())
 }
 
-/// List all notes from Apple Notes
+/// List all notes
 pub async fn list_notes() -> Result<Vec<NoteItem>> {
-    notes_applescript::list_notes().await
+    crate::backend::notes()?.list_notes().await
 }
 
-/// Get notes folders from Apple Notes
+/// Get notes folders
 pub async fn get_note_folders() -> Result<Vec<String>> {
-    notes_applescript::get_note_folders().await
+    crate::backend::notes()?.folders().await
+}
+
+/// Create a notes folder
+pub async fn create_folder(name: &str) -> Result<()> {
+    crate::backend::notes()?.create_folder(name).await
+}
+
+/// Delete a notes folder and the notes in it
+pub async fn delete_folder(name: &str) -> Result<()> {
+    crate::backend::notes()?.delete_folder(name).await
+}
+
+/// Move a note to another folder
+pub async fn move_note(title: &str, from: Option<&str>, to: &str) -> Result<()> {
+    crate::backend::notes()?.move_note(title, from, to).await?;
+    notify(ItemKind::Note, Action::Updated, note_data(title, Some(to), None));
+    Ok(())
 }
 
 /// Delete a note by title
 pub async fn delete_note(title: &str, folder: Option<&str>) -> Result<()> {
-    notes_applescript::delete_note(title, folder).await
+    crate::backend::notes()?.delete_note(title, folder).await?;
+    notify(ItemKind::Note, Action::Deleted, note_data(title, folder, None));
+    Ok(())
+}
+
+/// Append text (plain or Markdown) to a note
+pub async fn append_note(
+    title: &str,
+    text: &str,
+    folder: Option<&str>,
+    markdown: bool,
+) -> Result<()> {
+    crate::backend::notes()?.append_note(title, text, folder, markdown).await?;
+    notify(ItemKind::Note, Action::Updated, note_data(title, folder, None));
+    Ok(())
+}
+
+/// Replace the content of a note (plain or Markdown), keeping its title
+pub async fn edit_note(
+    title: &str,
+    content: &str,
+    folder: Option<&str>,
+    markdown: bool,
+) -> Result<()> {
+    crate::backend::notes()?.edit_note(title, content, folder, markdown).await?;
+    notify(ItemKind::Note, Action::Updated, note_data(title, folder, Some(content)));
+    Ok(())
 }
 
 /// Search notes by keyword
 pub async fn search_notes(keyword: &str) -> Result<Vec<NoteItem>> {
-    notes_applescript::search_notes(keyword).await
+    crate::backend::notes()?.search_notes(keyword).await
 }
@@ -1,13 +1,15 @@
 //! AppleScript integration for Apple Notes.
 
+use crate::applescript::{Properties, Script, Template};
+use crate::script_runner::run_applescript_async;
 use anyhow::{Result, anyhow};
-use log::{debug, error, info};
-use tokio::process::Command;
+use tracing::{debug, error, info};
 
 use crate::notes::notes_types::{NoteConfig, NoteItem, NotesError};
-use crate::notes::notes_util::{escape_applescript_string, parse_notes_list};
+use crate::notes::notes_util::{escape_html, markdown_to_html, parse_note_lines, text_to_html};
 use crate::notes::notes_validation::{
-    validate_folder_name, validate_note_config, validate_note_title, validate_search_keyword,
+    validate_folder_name, validate_note_config, validate_note_content, validate_note_title,
+    validate_search_keyword,
 };
 
 /// Creates a new note in Apple Notes
@@ -19,51 +21,48 @@ pub async fn create_note(config: NoteConfig<'_>) -> Result<()> {
     ensure_notes_running().await?;
 
     let folder_script = if let Some(folder) = config.folder {
-        let escaped_folder = escape_applescript_string(folder);
-        format!(
+        Template::new(
             r#"
             set targetFolder to missing value
             repeat with f in folders
-                if name of f is "{}" then
+                if name of f is ${folder} then
                     set targetFolder to f
                     exit repeat
                 end if
             end repeat
             if targetFolder is missing value then
-                set targetFolder to make new folder with properties {{name:"{}"}}
+                set targetFolder to make new folder with properties {name:${folder}}
             end if
             tell targetFolder"#,
-            escaped_folder, escaped_folder
         )
+        .text("folder", folder)
+        .render()?
     } else {
-        "tell default account".to_string()
+        Script::from("tell default account")
     };
 
-    // Escape title and content to prevent command injection
-    let escaped_title = escape_applescript_string(config.title);
-    let escaped_content = escape_applescript_string(config.content);
+    let content =
+        if config.markdown { markdown_to_html(config.content) } else { config.content.to_string() };
+    let properties = Properties::new().text("name", config.title).text("body", &content);
 
-    let script = format!(
+    let script = Template::new(
         r#"tell application "Notes"
             try
-                {}
-                    make new note with properties {{name:"{}", body:"{}"}}
+                ${folder_script}
+                    make new note with properties ${properties}
                 end tell
                 return "Success: Note created"
             on error errMsg
                 return "Error: " & errMsg
             end try
         end tell"#,
-        folder_script, escaped_title, escaped_content
-    );
+    )
+    .script("folder_script", folder_script)
+    .script("properties", properties)
+    .render()?;
 
-    debug!("Executing AppleScript for note creation: {}", escaped_title);
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg(&script)
-        .output()
-        .await
-        .map_err(|e| NotesError::ScriptError(e.to_string()))?;
+    debug!("Executing AppleScript for note creation: {}", config.title);
+    let output = run_applescript_async(script).await?;
 
     let result = String::from_utf8_lossy(&output.stdout);
 
@@ -78,58 +77,58 @@ pub async fn create_note(config: NoteConfig<'_>) -> Result<()> {
 }
 
 /// Lists all notes from Apple Notes
+///
+/// Password protected notes are listed by title; their content is never read.
 pub async fn list_notes() -> Result<Vec<NoteItem>> {
     // First ensure Notes.app is running
     ensure_notes_running().await?;
 
-    let script = r#"tell application "Notes"
-        try
-            set notesList to {}
-            repeat with n in notes
-                set noteFolder to "Notes"
-                try
-                    set noteFolder to name of container of n
-                end try
-                set noteInfo to {name:name of n, folder:noteFolder}
-                copy noteInfo to end of notesList
-            end repeat
-            return notesList
-        on error errMsg
-            return "Error: " & errMsg
-        end try
-    end tell"#;
-
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg(script)
-        .output()
-        .await
-        .map_err(|e| NotesError::ScriptError(e.to_string()))?;
+    let script = Template::new(
+        r#"${note_line}
+tell application "Notes"
+    try
+        set output to ""
+        repeat with n in notes
+            set output to output & my noteLine(n)
+        end repeat
+        return output
+    on error errMsg
+        return "Error: " & errMsg
+    end try
+end tell"#,
+    )
+    .script("note_line", NOTE_LINE_HANDLER)
+    .render()?;
+
+    let output = run_applescript_async(script).await?;
 
     let result = String::from_utf8_lossy(&output.stdout);
 
-    if result.contains("Error") {
+    if result.starts_with("Error") {
         error!("Failed to list notes: {}", result);
         return Err(anyhow!("Failed to list notes: {}", result));
     }
 
-    // Parse the notes list from the AppleScript output
-    let notes_list = parse_notes_list(&result);
-
-    // Convert to our NoteItem struct
-    let note_items: Vec<NoteItem> = notes_list
-        .into_iter()
-        .map(|(title, folder)| NoteItem {
-            title,
-            folder,
-            created: None, // Apple Notes doesn't easily provide creation date via AppleScript
-            modified: None, // Apple Notes doesn't easily provide modification date via AppleScript
-        })
-        .collect();
-
-    Ok(note_items)
+    Ok(parse_note_lines(&result))
 }
 
+/// AppleScript handler printing a note as a line of title, folder and locked state
+///
+/// Only properties readable on password protected notes are used.
+const NOTE_LINE_HANDLER: &str = r#"on noteLine(n)
+    tell application "Notes"
+        set noteFolder to "Notes"
+        try
+            set noteFolder to name of container of n
+        end try
+        set isLocked to false
+        try
+            set isLocked to password protected of n
+        end try
+        return (name of n) & tab & noteFolder & tab & (isLocked as text) & linefeed
+    end tell
+end noteLine"#;
+
 /// Gets a list of all note folders from Apple Notes
 pub async fn get_note_folders() -> Result<Vec<String>> {
     // First ensure Notes.app is running
@@ -147,12 +146,7 @@ pub async fn get_note_folders() -> Result<Vec<String>> {
         end try
     end tell"#;
 
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg(script)
-        .output()
-        .await
-        .map_err(|e| NotesError::ScriptError(e.to_string()))?;
+    let output = run_applescript_async(script).await?;
 
     let result = String::from_utf8_lossy(&output.stdout);
 
@@ -172,6 +166,118 @@ pub async fn get_note_folders() -> Result<Vec<String>> {
     Ok(folders)
 }
 
+/// Creates a folder in Apple Notes
+pub async fn create_folder(name: &str) -> Result<()> {
+    validate_folder_name(name)?;
+    ensure_notes_running().await?;
+
+    let script = Template::new(
+        r#"tell application "Notes"
+            try
+                if exists folder ${name} then return "Error: Folder already exists"
+                make new folder with properties {name:${name}}
+                return "Success: Folder created"
+            on error errMsg
+                return "Error: " & errMsg
+            end try
+        end tell"#,
+    )
+    .text("name", name)
+    .render()?;
+
+    let output = run_applescript_async(script).await?;
+    let result = String::from_utf8_lossy(&output.stdout);
+
+    if result.contains("Success") {
+        info!("Note folder created: {}", name);
+        Ok(())
+    } else if result.contains("Folder already exists") {
+        Err(anyhow!("Folder '{}' already exists", name))
+    } else {
+        error!("Failed to create note folder: {}", result);
+        Err(anyhow!("Failed to create folder: {}", result.trim()))
+    }
+}
+
+/// Deletes a folder and the notes in it; Notes.app keeps them in Recently Deleted
+pub async fn delete_folder(name: &str) -> Result<()> {
+    validate_folder_name(name)?;
+    ensure_notes_running().await?;
+
+    let script = Template::new(
+        r#"tell application "Notes"
+            try
+                if not (exists folder ${name}) then return "Error: Folder not found"
+                delete folder ${name}
+                return "Success: Folder deleted"
+            on error errMsg
+                return "Error: " & errMsg
+            end try
+        end tell"#,
+    )
+    .text("name", name)
+    .render()?;
+
+    let output = run_applescript_async(script).await?;
+    let result = String::from_utf8_lossy(&output.stdout);
+
+    if result.contains("Success") {
+        info!("Note folder deleted: {}", name);
+        Ok(())
+    } else if result.contains("Folder not found") {
+        Err(NotesError::FolderNotFound(name.to_string()).into())
+    } else {
+        error!("Failed to delete note folder: {}", result);
+        Err(anyhow!("Failed to delete folder: {}", result.trim()))
+    }
+}
+
+/// Moves the first note matching title (and optionally its current folder) to another folder
+pub async fn move_note(title: &str, from: Option<&str>, to: &str) -> Result<()> {
+    validate_note_title(title)?;
+    validate_folder_name(to)?;
+    if let Some(folder_name) = from {
+        validate_folder_name(folder_name)?;
+    }
+    ensure_notes_running().await?;
+
+    let script = Template::new(
+        r#"tell application "Notes"
+            try
+                if not (exists folder ${to}) then return "Error: Folder not found"
+                set targetFolder to folder ${to}
+                repeat with n in notes
+                    if ${condition} then
+                        move n to targetFolder
+                        return "Success: Note moved"
+                    end if
+                end repeat
+                return "Error: Note not found"
+            on error errMsg
+                return "Error: " & errMsg
+            end try
+        end tell"#,
+    )
+    .text("to", to)
+    .script("condition", note_condition(title, from)?)
+    .render()?;
+
+    let output = run_applescript_async(script).await?;
+    let result = String::from_utf8_lossy(&output.stdout);
+
+    if result.contains("Success") {
+        info!("Note '{}' moved to folder '{}'", title, to);
+        Ok(())
+    } else if result.contains("Folder not found") {
+        Err(NotesError::FolderNotFound(to.to_string()).into())
+    } else if result.contains("Note not found") {
+        Err(NotesError::NoteNotFound(title.to_string()).into())
+    } else {
+        error!("Failed to move note: {}", result);
+        Err(anyhow!("Failed to move note: {}", result.trim()))
+    }
+}
+
 /// Deletes a note by title (and optionally folder)
 pub async fn delete_note(title: &str, folder: Option<&str>) -> Result<()> {
     // Validate inputs
@@ -183,24 +289,14 @@ pub async fn delete_note(title: &str, folder: Option<&str>) -> Result<()> {
     // First ensure Notes.app is running
     ensure_notes_running().await?;
 
-    let escaped_title = escape_applescript_string(title);
+    let folder_condition = note_condition(title, folder)?;
 
-    let folder_condition = if let Some(folder_name) = folder {
-        let escaped_folder = escape_applescript_string(folder_name);
-        format!(
-            "name of n is \"{}\" and name of container of n is \"{}\"",
-            escaped_title, escaped_folder
-        )
-    } else {
-        format!("name of n is \"{}\"", escaped_title)
-    };
-
-    let script = format!(
+    let script = Template::new(
         r#"tell application "Notes"
             try
                 set noteFound to false
                 repeat with n in notes
-                    if {} then
+                    if ${condition} then
                         delete n
                         set noteFound to true
                         exit repeat
@@ -216,15 +312,11 @@ pub async fn delete_note(title: &str, folder: Option<&str>) -> Result<()> {
                 return "Error: " & errMsg
             end try
         end tell"#,
-        folder_condition
-    );
+    )
+    .script("condition", folder_condition)
+    .render()?;
 
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg(&script)
-        .output()
-        .await
-        .map_err(|e| NotesError::ScriptError(e.to_string()))?;
+    let output = run_applescript_async(script).await?;
 
     let result = String::from_utf8_lossy(&output.stdout);
 
@@ -242,62 +334,142 @@ pub async fn delete_note(title: &str, folder: Option<&str>) -> Result<()> {
     }
 }
 
-/// Searches notes by keyword
-pub async fn search_notes(keyword: &str) -> Result<Vec<NoteItem>> {
-    // Validate the search keyword
-    validate_search_keyword(keyword)?;
+/// Appends text to the end of a note's body
+pub async fn append_to_note(
+    title: &str,
+    text: &str,
+    folder: Option<&str>,
+    markdown: bool,
+) -> Result<()> {
+    validate_note_content(text)?;
+    let html = if markdown { markdown_to_html(text) } else { text_to_html(text) };
+    let update = Template::new("set body of n to (body of n) & ${html}")
+        .text("html", &html)
+        .render()?;
+    update_note_body(title, folder, update).await?;
+    info!("Appended {} characters to note: {}", text.len(), title);
+    Ok(())
+}
 
-    // First ensure Notes.app is running
-    ensure_notes_running().await?;
+/// Replaces the content of a note, keeping its title
+pub async fn edit_note(
+    title: &str,
+    content: &str,
+    folder: Option<&str>,
+    markdown: bool,
+) -> Result<()> {
+    validate_note_content(content)?;
+    let html = if markdown { markdown_to_html(content) } else { text_to_html(content) };
+    // Notes.app takes the title from the first line of the body
+    let body = format!("<div><h1>{}</h1></div>{}", escape_html(title), html);
+    let update = Template::new("set body of n to ${body}").text("body", &body).render()?;
+    update_note_body(title, folder, update).await?;
+    info!("Note updated: {}", title);
+    Ok(())
+}
 
-    let escaped_keyword = escape_applescript_string(keyword);
+/// Runs an AppleScript statement against the first note matching title and folder
+async fn update_note_body(title: &str, folder: Option<&str>, update: Script) -> Result<()> {
+    validate_note_title(title)?;
+    if let Some(folder_name) = folder {
+        validate_folder_name(folder_name)?;
+    }
+
+    ensure_notes_running().await?;
 
-    let script = format!(
+    let script = Template::new(
         r#"tell application "Notes"
             try
-                set matchingNotes to {{}}
                 repeat with n in notes
-                    if name of n contains "{0}" or body of n contains "{0}" then
-                        set noteFolder to "Notes"
-                        try
-                            set noteFolder to name of container of n
-                        end try
-                        set noteInfo to {{name:name of n, folder:noteFolder}}
-                        copy noteInfo to end of matchingNotes
+                    if ${condition} then
+                        if password protected of n then return "Error: Note is locked"
+                        ${update}
+                        return "Success: Note updated"
                     end if
                 end repeat
-                return matchingNotes
+                return "Error: Note not found"
             on error errMsg
                 return "Error: " & errMsg
             end try
         end tell"#,
-        escaped_keyword
-    );
+    )
+    .script("condition", note_condition(title, folder)?)
+    .script("update", update)
+    .render()?;
 
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg(&script)
-        .output()
-        .await
-        .map_err(|e| NotesError::ScriptError(e.to_string()))?;
+    let output = run_applescript_async(script).await?;
+    let result = String::from_utf8_lossy(&output.stdout);
+
+    if result.contains("Success") {
+        Ok(())
+    } else if result.contains("Note is locked") {
+        Err(NotesError::NoteLocked(title.to_string()).into())
+    } else if result.contains("Note not found") {
+        error!("Note '{}' not found", title);
+        Err(NotesError::NoteNotFound(title.to_string()).into())
+    } else {
+        error!("Failed to update note: {}", result);
+        Err(anyhow!("Failed to update note: {}", result.trim()))
+    }
+}
+
+/// AppleScript condition matching note `n` by title and optional folder
+fn note_condition(title: &str, folder: Option<&str>) -> Result<Script> {
+    if let Some(folder_name) = folder {
+        Template::new("name of n is ${title} and name of container of n is ${folder}")
+            .text("title", title)
+            .text("folder", folder_name)
+            .render()
+    } else {
+        Template::new("name of n is ${title}").text("title", title).render()
+    }
+}
+
+/// Searches notes by keyword
+pub async fn search_notes(keyword: &str) -> Result<Vec<NoteItem>> {
+    // Validate the search keyword
+    validate_search_keyword(keyword)?;
+
+    // First ensure Notes.app is running
+    ensure_notes_running().await?;
+
+    // The body of a locked note cannot be read, so only its title is searched
+    let script = Template::new(
+        r#"${note_line}
+tell application "Notes"
+    try
+        set output to ""
+        repeat with n in notes
+            set isLocked to false
+            try
+                set isLocked to password protected of n
+            end try
+            if name of n contains ${keyword} then
+                set output to output & my noteLine(n)
+            else if not isLocked then
+                if body of n contains ${keyword} then set output to output & my noteLine(n)
+            end if
+        end repeat
+        return output
+    on error errMsg
+        return "Error: " & errMsg
+    end try
+end tell"#,
+    )
+    .text("keyword", keyword)
+    .script("note_line", NOTE_LINE_HANDLER)
+    .render()?;
+
+    let output = run_applescript_async(script).await?;
 
     let result = String::from_utf8_lossy(&output.stdout);
 
-    if result.contains("Error") {
+    if result.starts_with("Error") {
         error!("Failed to search notes: {}", result);
         return Err(anyhow!("Failed to search notes: {}", result));
     }
 
-    // Parse the notes list from the AppleScript output
-    let notes_list = parse_notes_list(&result);
-
-    // Convert to our NoteItem struct
-    let note_items: Vec<NoteItem> = notes_list
-        .into_iter()
-        .map(|(title, folder)| NoteItem { title, folder, created: None, modified: None })
-        .collect();
-
-    Ok(note_items)
+    Ok(parse_note_lines(&result))
 }
 
 /// Ensures the Notes application is running
@@ -310,12 +482,7 @@ async fn ensure_notes_running() -> Result<()> {
         return true
     end tell"#;
 
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg(check_script)
-        .output()
-        .await
-        .map_err(|e| NotesError::ScriptError(e.to_string()))?;
+    let output = run_applescript_async(check_script).await?;
 
     if output.status.success() {
         Ok(())
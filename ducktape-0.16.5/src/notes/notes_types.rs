@@ -11,17 +11,25 @@ pub struct NoteConfig<'a> {
     pub content: &'a str,
     /// Optional folder to store the note in
     pub folder: Option<&'a str>,
+    /// Whether the content is Markdown to be rendered as HTML
+    pub markdown: bool,
 }
 
 impl<'a> NoteConfig<'a> {
     /// Create a new note configuration with default settings
     pub fn new(title: &'a str, content: &'a str) -> Self {
-        Self { title, content, folder: None }
+        Self { title, content, folder: None, markdown: false }
     }
 
     /// Create a new note configuration with a specified folder
     pub fn with_folder(title: &'a str, content: &'a str, folder: &'a str) -> Self {
-        Self { title, content, folder: Some(folder) }
+        Self { title, content, folder: Some(folder), markdown: false }
+    }
+
+    /// Treat the content as Markdown
+    pub fn with_markdown(mut self, markdown: bool) -> Self {
+        self.markdown = markdown;
+        self
     }
 }
 
@@ -36,6 +44,9 @@ pub struct NoteItem {
     pub created: Option<String>,
     /// Modification date of the note (if available)
     pub modified: Option<String>,
+    /// Whether the note is password protected, so its content cannot be read
+    #[serde(default)]
+    pub locked: bool,
 }
 
 /// Custom error type for notes operations
@@ -50,6 +61,9 @@ pub enum NotesError {
     #[error("Folder '{0}' not found")]
     FolderNotFound(String),
 
+    #[error("Note '{0}' is locked. Unlock it in Notes.app to read or change it")]
+    NoteLocked(String),
+
     #[error("AppleScript execution failed: {0}")]
     ScriptError(String),
 
@@ -1,52 +1,155 @@
 //! Utility functions for the notes module.
 
-/// Helper function to escape strings for AppleScript to prevent command injection
-pub fn escape_applescript_string(input: &str) -> String {
-    // First replace double quotes with escaped quotes for AppleScript
-    let escaped = input.replace("\"", "\"\"");
-
-    // Remove any control characters that could interfere with AppleScript execution
-    escaped
-        .chars()
-        .filter(|&c| !c.is_control() || c == '\n' || c == '\t')
-        .collect::<String>()
+use crate::notes::notes_types::NoteItem;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Parse the tab separated note lines produced by the list and search scripts
+///
+/// Each line holds the title, the folder and whether the note is password protected.
+pub fn parse_note_lines(output: &str) -> Vec<NoteItem> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let title = fields.first().map(|t| t.trim()).filter(|t| !t.is_empty())?;
+            Some(NoteItem {
+                title: title.to_string(),
+                folder: fields.get(1).map(|f| f.trim()).unwrap_or("Notes").to_string(),
+                created: None,
+                modified: None,
+                locked: fields.get(2).is_some_and(|l| l.trim() == "true"),
+            })
+        })
+        .collect()
 }
 
-/// Parse a notes list from AppleScript output
-pub fn parse_notes_list(output: &str) -> Vec<(String, String)> {
-    let mut notes = Vec::new();
+static BOLD: Lazy<Regex> = Lazy::new(|| Regex::new(r"\*\*(.+?)\*\*|__(.+?)__").unwrap());
+static ITALIC: Lazy<Regex> = Lazy::new(|| Regex::new(r"\*([^*\s][^*]*)\*|\b_([^_]+)_\b").unwrap());
+static STRIKE: Lazy<Regex> = Lazy::new(|| Regex::new(r"~~(.+?)~~").unwrap());
+static LINK: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[([^\]]+)\]\(([^)\s]+)\)").unwrap());
 
-    // Parse the output format which is expected to be a list of records
-    // in the format {name:"Note Title", folder:"Folder Name"}, ...
-    let output = output.trim_matches('{').trim_matches('}');
+/// Escape text for inclusion in a note's HTML body
+pub fn escape_html(input: &str) -> String {
+    input.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
 
-    if output.is_empty() {
-        return notes;
-    }
+/// Convert plain text to the HTML body format Notes.app uses, one `<div>` per line
+pub fn text_to_html(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.trim().is_empty() {
+                "<div><br></div>".to_string()
+            } else {
+                format!("<div>{}</div>", escape_html(line))
+            }
+        })
+        .collect()
+}
 
-    // Split by record boundaries
-    let records: Vec<&str> = output.split("}, {").collect();
+/// Convert Markdown to the HTML body format Notes.app expects
+///
+/// Supports headings, bullet and numbered lists, block quotes, fenced code blocks and
+/// inline bold, italic, strikethrough, code and links. Attributes use single quotes because
+/// the body is embedded in an AppleScript string.
+pub fn markdown_to_html(markdown: &str) -> String {
+    let mut html = String::new();
+    let mut list: Option<&str> = None;
+    let mut in_code_block = false;
 
-    for record in records {
-        let clean_record = record.replace('{', "").replace('}', "");
-        let mut title = String::new();
-        let mut folder = String::new();
+    for line in markdown.lines() {
+        let trimmed = line.trim();
 
-        // Extract properties
-        for prop in clean_record.split(", ") {
-            if prop.starts_with("name:") {
-                title = prop.trim_start_matches("name:").trim_matches('"').to_string();
-            } else if prop.starts_with("folder:") {
-                folder = prop.trim_start_matches("folder:").trim_matches('"').to_string();
-            }
+        if trimmed.starts_with("```") {
+            close_list(&mut html, &mut list);
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            html.push_str(&format!("<div><tt>{}</tt></div>", escape_html(line)));
+            continue;
         }
 
-        if !title.is_empty() {
-            notes.push((title, folder));
+        if let Some(item) = list_item(trimmed, false) {
+            open_list(&mut html, &mut list, "ul");
+            html.push_str(&format!("<li>{}</li>", render_inline(item)));
+            continue;
+        }
+        if let Some(item) = list_item(trimmed, true) {
+            open_list(&mut html, &mut list, "ol");
+            html.push_str(&format!("<li>{}</li>", render_inline(item)));
+            continue;
+        }
+        close_list(&mut html, &mut list);
+
+        if trimmed.is_empty() {
+            html.push_str("<div><br></div>");
+        } else if let Some((level, text)) = heading(trimmed) {
+            html.push_str(&format!("<h{0}>{1}</h{0}>", level, render_inline(text)));
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            html.push_str(&format!("<blockquote>{}</blockquote>", render_inline(quote.trim())));
+        } else if trimmed.chars().all(|c| c == '-') && trimmed.len() >= 3 {
+            html.push_str("<hr>");
+        } else {
+            html.push_str(&format!("<div>{}</div>", render_inline(trimmed)));
         }
     }
+    close_list(&mut html, &mut list);
+    html
+}
 
-    notes
+fn open_list<'a>(html: &mut String, list: &mut Option<&'a str>, tag: &'a str) {
+    if *list != Some(tag) {
+        close_list(html, list);
+        html.push_str(&format!("<{}>", tag));
+        *list = Some(tag);
+    }
+}
+
+fn close_list(html: &mut String, list: &mut Option<&str>) {
+    if let Some(tag) = list.take() {
+        html.push_str(&format!("</{}>", tag));
+    }
+}
+
+/// Text of a bullet (`- `, `* `, `+ `) or numbered (`1. `) list item
+fn list_item(line: &str, numbered: bool) -> Option<&str> {
+    if numbered {
+        let (number, rest) = line.split_once(". ")?;
+        (!number.is_empty() && number.chars().all(|c| c.is_ascii_digit())).then_some(rest)
+    } else {
+        ["- ", "* ", "+ "].iter().find_map(|marker| line.strip_prefix(marker))
+    }
+}
+
+/// Level and text of an ATX heading; Notes only styles three levels
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let text = line[level..].strip_prefix(' ')?;
+    (1..=6).contains(&level).then_some((level.min(3), text.trim()))
+}
+
+/// Render inline Markdown; code spans are left untouched
+fn render_inline(text: &str) -> String {
+    text.split('`')
+        .enumerate()
+        .map(|(i, part)| {
+            let escaped = escape_html(part);
+            if i % 2 == 1 {
+                return format!("<tt>{}</tt>", escaped);
+            }
+            let linked = LINK.replace_all(&escaped, |caps: &regex::Captures| {
+                format!("<a href='{}'>{}</a>", caps[2].replace('\'', "%27"), &caps[1])
+            });
+            let bold = BOLD.replace_all(&linked, |caps: &regex::Captures| {
+                format!("<b>{}</b>", caps.get(1).or_else(|| caps.get(2)).unwrap().as_str())
+            });
+            let italic = ITALIC.replace_all(&bold, |caps: &regex::Captures| {
+                format!("<i>{}</i>", caps.get(1).or_else(|| caps.get(2)).unwrap().as_str())
+            });
+            STRIKE.replace_all(&italic, "<strike>$1</strike>").into_owned()
+        })
+        .collect()
 }
 
 /// Format text for display in the terminal
@@ -59,23 +162,40 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_escape_applescript_string() {
-        let input = "Note with \"quotes\"";
-        let escaped = escape_applescript_string(input);
-        assert_eq!(escaped, "Note with \"\"quotes\"\"");
-
-        let input_with_control = "Note with \x07 bell";
-        let escaped = escape_applescript_string(input_with_control);
-        assert_eq!(escaped, "Note with  bell");
+    fn test_markdown_to_html() {
+        let html = markdown_to_html(
+            "# Plan\n\nShip **v2** with *care*, see [docs](https://x.io/a?b=1&c=2)\n- one\n- `two`\n1. first",
+        );
+        assert_eq!(
+            html,
+            "<h1>Plan</h1><div><br></div>\
+             <div>Ship <b>v2</b> with <i>care</i>, see <a href='https://x.io/a?b=1&amp;c=2'>docs</a></div>\
+             <ul><li>one</li><li><tt>two</tt></li></ul><ol><li>first</li></ol>"
+        );
+    }
+
+    #[test]
+    fn test_markdown_escapes_html_and_keeps_code_literal() {
+        assert_eq!(markdown_to_html("a < b"), "<div>a &lt; b</div>");
+        assert_eq!(markdown_to_html("```\n**not bold**\n```"), "<div><tt>**not bold**</tt></div>");
+        assert_eq!(markdown_to_html("snake_case_name"), "<div>snake_case_name</div>");
+    }
+
+    #[test]
+    fn test_text_to_html() {
+        assert_eq!(
+            text_to_html("one\n\n<two>"),
+            "<div>one</div><div><br></div><div>&lt;two&gt;</div>"
+        );
     }
 
     #[test]
-    fn test_parse_notes_list() {
-        let input =
-            "{name:\"Note 1\", folder:\"Folder 1\"}, {name:\"Note 2\", folder:\"Folder 2\"}";
-        let notes = parse_notes_list(input);
+    fn test_parse_note_lines() {
+        let notes = parse_note_lines("Note 1\tFolder 1\tfalse\nDiary\tPrivate\ttrue\n\n");
         assert_eq!(notes.len(), 2);
-        assert_eq!(notes[0], ("Note 1".to_string(), "Folder 1".to_string()));
-        assert_eq!(notes[1], ("Note 2".to_string(), "Folder 2".to_string()));
+        assert_eq!((notes[0].title.as_str(), notes[0].folder.as_str()), ("Note 1", "Folder 1"));
+        assert!(!notes[0].locked);
+        assert_eq!(notes[1].title, "Diary");
+        assert!(notes[1].locked);
     }
 }
@@ -2,34 +2,18 @@
 
 use crate::notes::notes_types::NoteConfig;
 use anyhow::{Result, anyhow};
-use log::debug;
+use tracing::debug;
 
 /// Validates a note configuration before creating a note
 pub fn validate_note_config(config: &NoteConfig) -> Result<()> {
-    // Title validation
-    if config.title.is_empty() {
-        return Err(anyhow!("Note title cannot be empty"));
-    }
-
-    if config.title.len() > 255 {
-        return Err(anyhow!("Note title is too long (max 255 characters)"));
-    }
+    validate_note_title(config.title)?;
 
     // Content validation - allow empty content
-    if config.content.len() > 1_000_000 {
-        // 1MB limit for content
-        return Err(anyhow!("Note content is too large (max 1MB)"));
-    }
+    validate_note_content(config.content)?;
 
     // Folder validation if provided
     if let Some(folder) = config.folder {
-        if folder.is_empty() {
-            return Err(anyhow!("Folder name cannot be empty"));
-        }
-
-        if folder.len() > 255 {
-            return Err(anyhow!("Folder name is too long (max 255 characters)"));
-        }
+        validate_folder_name(folder)?;
     }
 
     debug!("Note configuration validated successfully: {:?}", config);
@@ -38,12 +22,14 @@ pub fn validate_note_config(config: &NoteConfig) -> Result<()> {
 
 /// Validates a note title before performing operations
 pub fn validate_note_title(title: &str) -> Result<()> {
-    if title.is_empty() {
-        return Err(anyhow!("Note title cannot be empty"));
-    }
+    crate::validation::title("note title", title)?;
+    Ok(())
+}
 
-    if title.len() > 255 {
-        return Err(anyhow!("Note title is too long (max 255 characters)"));
+/// Validates note content before it is written to a note
+pub fn validate_note_content(content: &str) -> Result<()> {
+    if content.len() > 1_000_000 {
+        return Err(anyhow!("Note content is too large (max 1MB)"));
     }
 
     Ok(())
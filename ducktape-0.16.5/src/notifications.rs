@@ -0,0 +1,191 @@
+//! Notification ledger shared by everything that surfaces reminders and events.
+//
+// Watch-mode alerts, daily digests and any other notifying subsystem record what they have
+// surfaced in ~/.ducktape/notifications.json. Before surfacing an item again they ask the
+// ledger, which applies a per-item cooldown and the `notifications.max_per_item_per_day`
+// limit across all sources, so an overdue reminder is not repeated by every subsystem.
+
+use crate::config::NotificationsConfig;
+use crate::state::{NotificationRecord, StateManager};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local};
+
+/// Records older than this are dropped when the ledger is loaded
+const RETENTION_DAYS: i64 = 2;
+
+/// Whether an item may be surfaced now
+#[derive(Debug, Clone, PartialEq)]
+pub enum NotifyDecision {
+    Allow,
+    /// Surfaced too recently; allowed again after the given time
+    Cooldown(DateTime<Local>),
+    /// Already surfaced `max_per_item_per_day` times today
+    DailyLimit,
+}
+
+/// Stable key for an item, e.g. `todo:Work:Pay rent:2025-03-14 09:00`
+///
+/// The due date is part of the key, so the next occurrence of a repeating reminder is a
+/// new item rather than a repeat of the last one.
+pub fn item_key(kind: &str, container: &str, title: &str, due: Option<&str>) -> String {
+    format!("{}:{}:{}:{}", kind, container.trim(), title.trim(), due.unwrap_or("").trim())
+}
+
+/// What has been surfaced recently, with the configured limits
+#[derive(Debug, Clone)]
+pub struct NotificationLedger {
+    config: NotificationsConfig,
+    records: Vec<NotificationRecord>,
+}
+
+fn surfaced_at(record: &NotificationRecord) -> Option<DateTime<Local>> {
+    DateTime::parse_from_rfc3339(&record.surfaced_at)
+        .ok()
+        .map(|t| t.with_timezone(&Local))
+}
+
+impl NotificationLedger {
+    pub fn new(config: NotificationsConfig, records: Vec<NotificationRecord>) -> Self {
+        Self { config, records }
+    }
+
+    /// Load the ledger from state, dropping records past the retention period
+    pub fn load(config: &NotificationsConfig) -> Result<Self> {
+        let records: Vec<NotificationRecord> = StateManager::new()?.load()?;
+        let mut ledger = Self::new(config.clone(), records);
+        ledger.prune(Local::now());
+        Ok(ledger)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        StateManager::new()?.save(&self.records)
+    }
+
+    pub fn records(&self) -> &[NotificationRecord] {
+        &self.records
+    }
+
+    fn prune(&mut self, now: DateTime<Local>) {
+        let cutoff = now - Duration::days(RETENTION_DAYS);
+        self.records.retain(|r| surfaced_at(r).is_some_and(|t| t > cutoff));
+    }
+
+    /// Decide whether the item with `key` may be surfaced at `now`, by any source
+    pub fn check(&self, key: &str, now: DateTime<Local>) -> NotifyDecision {
+        let times: Vec<DateTime<Local>> =
+            self.records.iter().filter(|r| r.key == key).filter_map(surfaced_at).collect();
+
+        if let Some(last) = times.iter().max() {
+            let next = *last + Duration::minutes(self.config.cooldown_minutes as i64);
+            if next > now {
+                return NotifyDecision::Cooldown(next);
+            }
+        }
+
+        let limit = self.config.max_per_item_per_day as usize;
+        let today = now.date_naive();
+        if limit > 0 && times.iter().filter(|t| t.date_naive() == today).count() >= limit {
+            return NotifyDecision::DailyLimit;
+        }
+        NotifyDecision::Allow
+    }
+
+    /// Record that `source` surfaced the item with `key` at `now`
+    pub fn record(&mut self, key: &str, source: &str, now: DateTime<Local>) {
+        self.records.push(NotificationRecord {
+            key: key.to_string(),
+            source: source.to_string(),
+            surfaced_at: now.to_rfc3339(),
+        });
+    }
+
+    /// Keep the items that may be surfaced now and record them for `source`
+    pub fn filter<T>(
+        &mut self,
+        items: Vec<T>,
+        source: &str,
+        now: DateTime<Local>,
+        key_of: impl Fn(&T) -> String,
+    ) -> Vec<T> {
+        items
+            .into_iter()
+            .filter(|item| {
+                let key = key_of(item);
+                let allowed = self.check(&key, now) == NotifyDecision::Allow;
+                if allowed {
+                    self.record(&key, source, now);
+                }
+                allowed
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2025, 3, 14, hour, minute, 0).unwrap()
+    }
+
+    fn ledger(cooldown_minutes: u32, max_per_item_per_day: u32) -> NotificationLedger {
+        NotificationLedger::new(
+            NotificationsConfig { cooldown_minutes, max_per_item_per_day },
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn test_cooldown_applies_across_sources() {
+        let mut ledger = ledger(60, 0);
+        let key = item_key("todo", "Work", "Pay rent", Some("2025-03-14 09:00"));
+
+        assert_eq!(ledger.check(&key, at(9, 0)), NotifyDecision::Allow);
+        ledger.record(&key, "watch", at(9, 0));
+
+        // The digest must not repeat what watch mode just surfaced
+        assert_eq!(ledger.check(&key, at(9, 30)), NotifyDecision::Cooldown(at(10, 0)));
+        assert_eq!(ledger.check(&key, at(10, 0)), NotifyDecision::Allow);
+
+        // Other items are unaffected
+        let other = item_key("todo", "Work", "Pay rent", Some("2025-04-14 09:00"));
+        assert_eq!(ledger.check(&other, at(9, 30)), NotifyDecision::Allow);
+    }
+
+    #[test]
+    fn test_daily_limit() {
+        let mut ledger = ledger(0, 2);
+        let key = item_key("event", "Work", "Standup", None);
+        ledger.record(&key, "watch", at(8, 0));
+        ledger.record(&key, "digest", at(9, 0));
+        assert_eq!(ledger.check(&key, at(12, 0)), NotifyDecision::DailyLimit);
+
+        // The limit resets the next day
+        let tomorrow = Local.with_ymd_and_hms(2025, 3, 15, 8, 0, 0).unwrap();
+        assert_eq!(ledger.check(&key, tomorrow), NotifyDecision::Allow);
+    }
+
+    #[test]
+    fn test_filter_records_surfaced_items() {
+        let mut ledger = ledger(60, 3);
+        let items = vec!["Pay rent", "Call Joe", "Pay rent"];
+        let key = |title: &&str| item_key("todo", "Reminders", title, None);
+
+        let surfaced = ledger.filter(items, "digest", at(7, 0), key);
+        assert_eq!(surfaced, vec!["Pay rent", "Call Joe"]);
+        assert_eq!(ledger.records().len(), 2);
+        assert!(ledger.filter(vec!["Call Joe"], "watch", at(7, 15), key).is_empty());
+    }
+
+    #[test]
+    fn test_prune_drops_old_records() {
+        let mut ledger = ledger(60, 3);
+        ledger.record("todo:a::", "watch", at(9, 0) - Duration::days(3));
+        ledger.record("todo:b::", "watch", at(9, 0));
+        ledger.prune(at(10, 0));
+        assert_eq!(ledger.records().len(), 1);
+        assert_eq!(ledger.records()[0].key, "todo:b::");
+    }
+}
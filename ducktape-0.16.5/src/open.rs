@@ -0,0 +1,228 @@
+//! Opening events, notes and reminders in their own apps.
+//
+// `ducktape open event "Board meeting"` brings the item up in Calendar.app, Notes.app or
+// Reminders.app, so a title found in the terminal is one command away from the GUI. Events
+// DuckTape tracks are opened by their Calendar.app UID, picking the next occurrence when
+// several share a title; anything else is looked up by title in the app, exact matches first,
+// and shown with AppleScript's `show`.
+
+use crate::applescript::{Script, Template};
+use crate::script_runner::run_applescript_async;
+use crate::state::CalendarItem;
+use anyhow::{Result, anyhow};
+use chrono::NaiveDateTime;
+use std::fmt;
+use tracing::debug;
+
+/// The kind of item to open
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenKind {
+    Event,
+    Note,
+    Reminder,
+}
+
+impl OpenKind {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "event" | "events" | "calendar" | "meeting" => Ok(OpenKind::Event),
+            "note" | "notes" => Ok(OpenKind::Note),
+            "reminder" | "reminders" | "todo" | "task" => Ok(OpenKind::Reminder),
+            _ => Err(anyhow!("Unknown item kind: {}. Use event, note or reminder", s)),
+        }
+    }
+
+    pub fn app(&self) -> &'static str {
+        match self {
+            OpenKind::Event => "Calendar",
+            OpenKind::Note => "Notes",
+            OpenKind::Reminder => "Reminders",
+        }
+    }
+}
+
+impl fmt::Display for OpenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            OpenKind::Event => "event",
+            OpenKind::Note => "note",
+            OpenKind::Reminder => "reminder",
+        };
+        f.write_str(label)
+    }
+}
+
+/// The tracked event to open for `title`: exact titles (ignoring case) before partial ones,
+/// then the next occurrence to start, otherwise the most recent
+pub fn find_tracked_event<'a>(
+    items: &'a [CalendarItem],
+    title: &str,
+    now: NaiveDateTime,
+) -> Option<&'a CalendarItem> {
+    let title = title.to_lowercase();
+    let exact: Vec<&CalendarItem> =
+        items.iter().filter(|item| item.title.to_lowercase() == title).collect();
+    let mut candidates = if exact.is_empty() {
+        items.iter().filter(|item| item.title.to_lowercase().contains(&title)).collect()
+    } else {
+        exact
+    };
+    candidates.retain(|item| item.uid.is_some() && !item.calendars.is_empty());
+    candidates.sort_by(|a, b| (&a.date, &a.time).cmp(&(&b.date, &b.time)));
+    let now = now.format("%Y-%m-%d %H:%M").to_string();
+    candidates
+        .iter()
+        .find(|item| format!("{} {}", item.date, item.time) >= now)
+        .or(candidates.last())
+        .copied()
+}
+
+/// The script that finds the item and shows it, returning its title or "not found"
+fn show_script(kind: OpenKind, title: &str, tracked: Option<&CalendarItem>) -> Result<Script> {
+    let find = match (kind, tracked) {
+        (OpenKind::Event, Some(item)) => Template::new(
+            r#"set found to {}
+    try
+        set found to {first event of (first calendar whose name is ${calendar}) whose uid is ${uid}}
+    end try"#,
+        )
+        .text("calendar", &item.calendars[0])
+        .text("uid", item.uid.as_deref().unwrap_or_default())
+        .render()?,
+        (OpenKind::Event, None) => Template::new(
+            r#"set found to {}
+    repeat with c in calendars
+        set found to found & (every event of c whose summary is ${title})
+    end repeat
+    if found is {} then
+        repeat with c in calendars
+            set found to found & (every event of c whose summary contains ${title})
+        end repeat
+    end if"#,
+        )
+        .text("title", title)
+        .render()?,
+        (OpenKind::Note, _) => Template::new(
+            r#"set found to every note whose name is ${title}
+    if found is {} then set found to every note whose name contains ${title}"#,
+        )
+        .text("title", title)
+        .render()?,
+        (OpenKind::Reminder, _) => Template::new(
+            r#"set found to every reminder whose name is ${title} and completed is false
+    if found is {} then set found to every reminder whose name is ${title}
+    if found is {} then set found to every reminder whose name contains ${title} and completed is false"#,
+        )
+        .text("title", title)
+        .render()?,
+    };
+    let name = match kind {
+        OpenKind::Event => Script::from("summary"),
+        _ => Script::from("name"),
+    };
+    Template::new(
+        r#"tell application ${app}
+    ${find}
+    if found is {} then return "not found"
+    set target to item 1 of found
+    activate
+    show target
+    return ${name} of target
+end tell"#,
+    )
+    .text("app", kind.app())
+    .script("find", find)
+    .script("name", name)
+    .render()
+}
+
+/// Open the item titled `title` in its app, returning the title of the item that was opened
+pub async fn open_item(kind: OpenKind, title: &str) -> Result<String> {
+    let items = match kind {
+        OpenKind::Event => crate::state::load_events().unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    let now = chrono::Local::now().naive_local();
+    let tracked = find_tracked_event(&items, title, now);
+    debug!("Opening {} '{}' (tracked: {:?})", kind, title, tracked.map(|item| &item.uid));
+
+    let mut output = run_applescript_async(show_script(kind, title, tracked)?).await?;
+    if tracked.is_some() && String::from_utf8_lossy(&output.stdout).trim() == "not found" {
+        // The UID is stale, e.g. the event was recreated in Calendar.app
+        output = run_applescript_async(show_script(kind, title, None)?).await?;
+    }
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Failed to open {} in {}: {}",
+            kind,
+            kind.app(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let opened = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if opened == "not found" {
+        return Err(anyhow!("No {} found matching '{}'", kind, title));
+    }
+    Ok(opened)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(title: &str, date: &str, time: &str, uid: Option<&str>) -> CalendarItem {
+        CalendarItem {
+            title: title.to_string(),
+            date: date.to_string(),
+            time: time.to_string(),
+            calendars: vec!["Work".to_string()],
+            uid: uid.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_open_kind_parse() {
+        assert_eq!(OpenKind::parse("Event").unwrap(), OpenKind::Event);
+        assert_eq!(OpenKind::parse("todo").unwrap(), OpenKind::Reminder);
+        assert_eq!(OpenKind::parse("notes").unwrap().app(), "Notes");
+        assert!(OpenKind::parse("contact").is_err());
+    }
+
+    #[test]
+    fn test_find_tracked_event() {
+        let now = chrono::NaiveDate::from_ymd_opt(2025, 3, 14)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        let items = vec![
+            event("Board meeting", "2025-03-01", "10:00", Some("past")),
+            event("Board meeting", "2025-04-01", "10:00", Some("next")),
+            event("Board meeting", "2025-05-01", "10:00", Some("later")),
+            event("Board meeting prep", "2025-03-20", "09:00", Some("prep")),
+            event("Offsite", "2025-03-20", "09:00", None),
+        ];
+        let uid = |title: &str| find_tracked_event(&items, title, now).and_then(|i| i.uid.clone());
+        assert_eq!(uid("board MEETING").as_deref(), Some("next"));
+        assert_eq!(uid("prep").as_deref(), Some("prep"));
+        // Events without a UID are looked up in Calendar.app instead
+        assert_eq!(uid("Offsite"), None);
+
+        let past = [event("Retro", "2025-01-01", "10:00", Some("old"))];
+        assert_eq!(
+            find_tracked_event(&past, "retro", now).and_then(|i| i.uid.as_deref()),
+            Some("old")
+        );
+    }
+
+    #[test]
+    fn test_show_script_escapes_title() {
+        let script = show_script(OpenKind::Note, "Say \"hi\"", None).unwrap();
+        assert!(script.as_str().starts_with("tell application \"Notes\""));
+        assert!(script.as_str().contains("whose name is \"Say \\\"hi\\\"\""));
+        let tracked = event("Standup", "2025-03-14", "09:00", Some("abc"));
+        let script = show_script(OpenKind::Event, "Standup", Some(&tracked)).unwrap();
+        assert!(script.as_str().contains("whose uid is \"abc\""));
+        assert!(script.as_str().contains("return summary of target"));
+    }
+}
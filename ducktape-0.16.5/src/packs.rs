@@ -0,0 +1,598 @@
+//! Shareable packs of templates, aliases, routines and category defaults.
+//
+// A pack is a TOML file that bundles a standard setup, e.g. a "consulting" pack with client
+// meeting templates and a default reminder list. `pack export` writes the local library and
+// defaults to a pack; `pack install` merges a pack from a file or URL into the local library
+// (~/.ducktape/library.json) and config.toml, resolving conflicts with existing entries.
+
+use crate::config::{Config, MeetingService};
+use anyhow::{Context, Result, anyhow};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+/// Version of the pack file format written by `pack export`
+pub const PACK_FORMAT: u32 = 1;
+
+/// Largest pack accepted from a file or URL
+const MAX_PACK_SIZE: usize = 1024 * 1024;
+
+/// Errors for pack import and export
+#[derive(Debug, thiserror::Error)]
+pub enum PackError {
+    #[error("Invalid pack: {0}")]
+    Invalid(String),
+
+    #[error("Pack format {0} is newer than this version of DuckTape supports")]
+    UnsupportedFormat(u32),
+
+    #[error("Pack is too large (maximum {} KB)", MAX_PACK_SIZE / 1024)]
+    TooLarge,
+}
+
+/// A reusable event template
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventTemplate {
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calendar: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_minutes: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reminder_minutes: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meeting: Option<MeetingService>,
+}
+
+/// A named sequence of DuckTape commands
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Routine {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub commands: Vec<String>,
+}
+
+/// Default calendar, list and folder settings carried by a pack
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CategoryDefaults {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calendar: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reminder_minutes: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_minutes: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meeting_provider: Option<MeetingService>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub todo_list: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes_folder: Option<String>,
+}
+
+impl CategoryDefaults {
+    /// The defaults currently set in config.toml
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            calendar: config.calendar.default_calendar.clone(),
+            reminder_minutes: config.calendar.default_reminder_minutes,
+            duration_minutes: config.calendar.default_duration_minutes,
+            meeting_provider: Some(config.calendar.meeting_provider),
+            todo_list: config.todo.default_list.clone(),
+            notes_folder: config.notes.default_folder.clone(),
+        }
+    }
+}
+
+/// Name and description of a pack
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackInfo {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default = "default_format")]
+    pub format: u32,
+}
+
+fn default_format() -> u32 {
+    PACK_FORMAT
+}
+
+/// A shareable pack file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pack {
+    pub pack: PackInfo,
+    #[serde(default)]
+    pub defaults: CategoryDefaults,
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+    #[serde(default)]
+    pub templates: BTreeMap<String, EventTemplate>,
+    #[serde(default)]
+    pub routines: BTreeMap<String, Routine>,
+}
+
+impl Pack {
+    /// Parse and validate a pack from TOML
+    pub fn from_toml(content: &str) -> Result<Self> {
+        if content.len() > MAX_PACK_SIZE {
+            return Err(anyhow!(PackError::TooLarge));
+        }
+        let pack: Pack =
+            toml::from_str(content).map_err(|e| anyhow!(PackError::Invalid(e.to_string())))?;
+        pack.validate()?;
+        Ok(pack)
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+
+    fn validate(&self) -> Result<()> {
+        let invalid = |msg: String| Err(anyhow!(PackError::Invalid(msg)));
+        if self.pack.format > PACK_FORMAT {
+            return Err(anyhow!(PackError::UnsupportedFormat(self.pack.format)));
+        }
+        if self.pack.name.trim().is_empty() {
+            return invalid("the pack has no name".to_string());
+        }
+        let names = self.aliases.keys().chain(self.templates.keys()).chain(self.routines.keys());
+        for name in names {
+            if name.trim().is_empty() || name.chars().any(char::is_whitespace) {
+                return invalid(format!("'{}' is not a valid name", name));
+            }
+        }
+        if let Some((name, _)) = self.aliases.iter().find(|(_, command)| command.trim().is_empty())
+        {
+            return invalid(format!("alias '{}' has no command", name));
+        }
+        if let Some((name, _)) = self.routines.iter().find(|(_, r)| r.commands.is_empty()) {
+            return invalid(format!("routine '{}' has no commands", name));
+        }
+        Ok(())
+    }
+}
+
+/// A pack that has been installed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstalledPack {
+    pub name: String,
+    pub source: String,
+    pub installed_at: String,
+}
+
+/// Local templates, aliases and routines
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Library {
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+    #[serde(default)]
+    pub templates: BTreeMap<String, EventTemplate>,
+    #[serde(default)]
+    pub routines: BTreeMap<String, Routine>,
+    #[serde(default)]
+    pub installed: Vec<InstalledPack>,
+}
+
+impl Library {
+    /// Load the library, empty if it does not exist yet
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)?;
+        serde_json::from_str(&contents).map_err(|e| anyhow!("Failed to parse library: {}", e))
+    }
+
+    /// Save the library atomically
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp_path = path.with_extension("json.tmp");
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(serde_json::to_string_pretty(self)?.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &path)?;
+        debug!("Saved library to {:?}", path);
+        Ok(())
+    }
+
+    fn path() -> Result<PathBuf> {
+        let home_dir = dirs::home_dir().ok_or_else(|| anyhow!("Failed to get home directory"))?;
+        Ok(home_dir.join(".ducktape").join("library.json"))
+    }
+
+    /// Bundle the library and the current defaults into a pack
+    pub fn to_pack(&self, info: PackInfo, config: &Config) -> Pack {
+        Pack {
+            pack: info,
+            defaults: CategoryDefaults::from_config(config),
+            aliases: self.aliases.clone(),
+            templates: self.templates.clone(),
+            routines: self.routines.clone(),
+        }
+    }
+}
+
+/// How to handle pack entries that clash with existing ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictStrategy {
+    /// Ask for each conflict
+    Ask,
+    /// Keep the existing entry
+    Skip,
+    /// Replace the existing entry
+    Overwrite,
+    /// Install the pack entry under a new name (`<name>-<pack>`)
+    Rename,
+}
+
+impl ConflictStrategy {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "ask" | "prompt" => Ok(ConflictStrategy::Ask),
+            "skip" | "keep" => Ok(ConflictStrategy::Skip),
+            "overwrite" | "replace" => Ok(ConflictStrategy::Overwrite),
+            "rename" => Ok(ConflictStrategy::Rename),
+            _ => Err(anyhow!(
+                "Invalid conflict strategy: {}. Valid options are: ask, skip, overwrite, rename",
+                s
+            )),
+        }
+    }
+}
+
+/// An entry that already exists with a different value
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    /// "alias", "template", "routine" or "default"
+    pub section: &'static str,
+    pub name: String,
+    pub existing: String,
+    pub incoming: String,
+}
+
+/// What was changed by an install
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InstallSummary {
+    pub added: Vec<String>,
+    pub overwritten: Vec<String>,
+    pub renamed: Vec<(String, String)>,
+    pub skipped: Vec<String>,
+}
+
+/// Merge a pack into the library and config
+///
+/// `ask` decides conflicts when the strategy is `Ask`; it must return `Skip`, `Overwrite`
+/// or `Rename`. Entries identical to existing ones are left alone. Defaults cannot be
+/// renamed, so `Rename` keeps the existing default.
+pub fn install_pack(
+    pack: &Pack,
+    library: &mut Library,
+    config: &mut Config,
+    strategy: ConflictStrategy,
+    mut ask: impl FnMut(&Conflict) -> ConflictStrategy,
+) -> InstallSummary {
+    let mut summary = InstallSummary::default();
+    let mut resolve = |conflict: Conflict| match strategy {
+        ConflictStrategy::Ask => ask(&conflict),
+        other => other,
+    };
+    let pack_name = pack.pack.name.as_str();
+
+    merge_section(
+        &pack.aliases,
+        &mut library.aliases,
+        "alias",
+        pack_name,
+        &mut resolve,
+        &mut summary,
+    );
+    merge_section(
+        &pack.templates,
+        &mut library.templates,
+        "template",
+        pack_name,
+        &mut resolve,
+        &mut summary,
+    );
+    merge_section(
+        &pack.routines,
+        &mut library.routines,
+        "routine",
+        pack_name,
+        &mut resolve,
+        &mut summary,
+    );
+    merge_defaults(&pack.defaults, config, &mut resolve, &mut summary);
+    summary
+}
+
+fn merge_section<T: Clone + PartialEq + std::fmt::Debug>(
+    incoming: &BTreeMap<String, T>,
+    existing: &mut BTreeMap<String, T>,
+    section: &'static str,
+    pack_name: &str,
+    resolve: &mut impl FnMut(Conflict) -> ConflictStrategy,
+    summary: &mut InstallSummary,
+) {
+    for (name, value) in incoming {
+        let label = format!("{} {}", section, name);
+        match existing.get(name) {
+            None => {
+                existing.insert(name.clone(), value.clone());
+                summary.added.push(label);
+            }
+            Some(current) if current == value => {}
+            Some(current) => {
+                let conflict = Conflict {
+                    section,
+                    name: name.clone(),
+                    existing: format!("{:?}", current),
+                    incoming: format!("{:?}", value),
+                };
+                match resolve(conflict) {
+                    ConflictStrategy::Overwrite => {
+                        existing.insert(name.clone(), value.clone());
+                        summary.overwritten.push(label);
+                    }
+                    ConflictStrategy::Rename => {
+                        let new_name = unique_name(existing, name, pack_name);
+                        existing.insert(new_name.clone(), value.clone());
+                        summary.renamed.push((label, new_name));
+                    }
+                    ConflictStrategy::Skip | ConflictStrategy::Ask => summary.skipped.push(label),
+                }
+            }
+        }
+    }
+}
+
+fn unique_name<T>(existing: &BTreeMap<String, T>, name: &str, pack_name: &str) -> String {
+    let base = format!("{}-{}", name, pack_name.to_lowercase().replace(' ', "-"));
+    let mut candidate = base.clone();
+    let mut n = 2;
+    while existing.contains_key(&candidate) {
+        candidate = format!("{}-{}", base, n);
+        n += 1;
+    }
+    candidate
+}
+
+fn merge_defaults(
+    defaults: &CategoryDefaults,
+    config: &mut Config,
+    resolve: &mut impl FnMut(Conflict) -> ConflictStrategy,
+    summary: &mut InstallSummary,
+) {
+    fn apply<T: Clone + PartialEq + std::fmt::Debug>(
+        key: &str,
+        incoming: &Option<T>,
+        current: &mut Option<T>,
+        resolve: &mut impl FnMut(Conflict) -> ConflictStrategy,
+        summary: &mut InstallSummary,
+    ) {
+        let Some(value) = incoming else { return };
+        let label = format!("default {}", key);
+        match current {
+            None => {
+                *current = Some(value.clone());
+                summary.added.push(label);
+            }
+            Some(existing) if existing == value => {}
+            Some(existing) => {
+                let conflict = Conflict {
+                    section: "default",
+                    name: key.to_string(),
+                    existing: format!("{:?}", existing),
+                    incoming: format!("{:?}", value),
+                };
+                if resolve(conflict) == ConflictStrategy::Overwrite {
+                    *current = Some(value.clone());
+                    summary.overwritten.push(label);
+                } else {
+                    summary.skipped.push(label);
+                }
+            }
+        }
+    }
+
+    apply(
+        "calendar.default",
+        &defaults.calendar,
+        &mut config.calendar.default_calendar,
+        resolve,
+        summary,
+    );
+    apply(
+        "calendar.reminder",
+        &defaults.reminder_minutes,
+        &mut config.calendar.default_reminder_minutes,
+        resolve,
+        summary,
+    );
+    apply(
+        "calendar.duration",
+        &defaults.duration_minutes,
+        &mut config.calendar.default_duration_minutes,
+        resolve,
+        summary,
+    );
+    let mut provider = Some(config.calendar.meeting_provider);
+    apply(
+        "calendar.meeting_provider",
+        &defaults.meeting_provider,
+        &mut provider,
+        resolve,
+        summary,
+    );
+    config.calendar.meeting_provider = provider.unwrap_or_default();
+    apply(
+        "todo.default_list",
+        &defaults.todo_list,
+        &mut config.todo.default_list,
+        resolve,
+        summary,
+    );
+    apply(
+        "notes.default_folder",
+        &defaults.notes_folder,
+        &mut config.notes.default_folder,
+        resolve,
+        summary,
+    );
+}
+
+/// Read a pack from a local file or an http(s) URL
+pub async fn fetch_pack(source: &str) -> Result<Pack> {
+    let content = if source.starts_with("https://") || source.starts_with("http://") {
+        let response = reqwest::get(source)
+            .await
+            .with_context(|| format!("Failed to download pack from {}", source))?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to download pack from {}: {}", source, response.status()));
+        }
+        if response.content_length().is_some_and(|len| len > MAX_PACK_SIZE as u64) {
+            return Err(anyhow!(PackError::TooLarge));
+        }
+        response.text().await?
+    } else {
+        let path = Path::new(source);
+        if fs::metadata(path).map(|m| m.len() > MAX_PACK_SIZE as u64).unwrap_or(false) {
+            return Err(anyhow!(PackError::TooLarge));
+        }
+        fs::read_to_string(path).with_context(|| format!("Failed to read pack {}", source))?
+    };
+    Pack::from_toml(&content)
+}
+
+/// Write the local library and defaults to a pack file
+pub fn export_pack(path: &Path, name: Option<&str>, description: Option<&str>) -> Result<Pack> {
+    let name = name
+        .map(str::to_string)
+        .or_else(|| path.file_stem().map(|s| s.to_string_lossy().to_string()))
+        .ok_or_else(|| anyhow!("Could not derive a pack name from {}", path.display()))?;
+    let info = PackInfo { name, description: description.map(str::to_string), format: PACK_FORMAT };
+    let pack = Library::load()?.to_pack(info, &Config::load()?);
+    fs::write(path, pack.to_toml()?)
+        .with_context(|| format!("Failed to write pack {}", path.display()))?;
+    info!("Exported pack '{}' to {}", pack.pack.name, path.display());
+    Ok(pack)
+}
+
+/// Record an installed pack and save the library and config
+pub fn save_installed(
+    pack: &Pack,
+    source: &str,
+    library: &mut Library,
+    config: &Config,
+) -> Result<()> {
+    library.installed.retain(|p| p.name != pack.pack.name);
+    library.installed.push(InstalledPack {
+        name: pack.pack.name.clone(),
+        source: source.to_string(),
+        installed_at: Local::now().to_rfc3339(),
+    });
+    library.save()?;
+    config.save()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONSULTING: &str = r#"
+[pack]
+name = "consulting"
+description = "Client work"
+
+[defaults]
+calendar = "Clients"
+todo_list = "Client Tasks"
+meeting_provider = "teams"
+
+[aliases]
+cm = "calendar create --calendar Clients"
+
+[templates.kickoff]
+title = "Project kickoff"
+duration_minutes = 90
+meeting = "teams"
+
+[routines.weekly]
+description = "Friday wrap-up"
+commands = ["agenda", "todo board"]
+"#;
+
+    #[test]
+    fn test_pack_round_trips_through_toml() {
+        let pack = Pack::from_toml(CONSULTING).unwrap();
+        assert_eq!(pack.pack.format, PACK_FORMAT);
+        assert_eq!(pack.templates["kickoff"].duration_minutes, Some(90));
+        assert_eq!(pack.defaults.meeting_provider, Some(MeetingService::Teams));
+        assert_eq!(Pack::from_toml(&pack.to_toml().unwrap()).unwrap(), pack);
+    }
+
+    #[test]
+    fn test_invalid_packs_are_rejected() {
+        assert!(Pack::from_toml("[pack]\nname = \"\"").is_err());
+        assert!(Pack::from_toml("[pack]\nname = \"x\"\nformat = 99").is_err());
+        assert!(Pack::from_toml("[pack]\nname = \"x\"\n[aliases]\n\"a b\" = \"agenda\"").is_err());
+        assert!(Pack::from_toml("[pack]\nname = \"x\"\n[routines.r]\ncommands = []").is_err());
+    }
+
+    #[test]
+    fn test_install_adds_and_resolves_conflicts() {
+        let pack = Pack::from_toml(CONSULTING).unwrap();
+        let mut library = Library::default();
+        library.aliases.insert("cm".to_string(), "calendar list".to_string());
+        let mut config = Config::default();
+        config.todo.default_list = None;
+
+        let summary = install_pack(
+            &pack,
+            &mut library,
+            &mut config,
+            ConflictStrategy::Skip,
+            |_| unreachable!(),
+        );
+        assert_eq!(library.aliases["cm"], "calendar list");
+        assert!(summary.skipped.contains(&"alias cm".to_string()));
+        assert!(summary.skipped.contains(&"default calendar.default".to_string()));
+        assert!(summary.added.contains(&"template kickoff".to_string()));
+        assert_eq!(config.todo.default_list.as_deref(), Some("Client Tasks"));
+        assert_eq!(config.calendar.default_calendar.as_deref(), Some("Calendar"));
+
+        let summary = install_pack(
+            &pack,
+            &mut library,
+            &mut config,
+            ConflictStrategy::Rename,
+            |_| unreachable!(),
+        );
+        assert_eq!(library.aliases["cm-consulting"], pack.aliases["cm"]);
+        assert_eq!(summary.renamed, vec![("alias cm".to_string(), "cm-consulting".to_string())]);
+
+        let mut asked = Vec::new();
+        install_pack(&pack, &mut library, &mut config, ConflictStrategy::Ask, |conflict| {
+            asked.push(conflict.name.clone());
+            ConflictStrategy::Overwrite
+        });
+        assert_eq!(library.aliases["cm"], pack.aliases["cm"]);
+        assert_eq!(config.calendar.default_calendar.as_deref(), Some("Clients"));
+        assert_eq!(config.calendar.meeting_provider, MeetingService::Teams);
+        assert!(asked.contains(&"calendar.default".to_string()));
+        // Entries that already match are not conflicts
+        assert!(!asked.contains(&"todo.default_list".to_string()));
+    }
+}
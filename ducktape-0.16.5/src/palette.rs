@@ -0,0 +1,295 @@
+//! Fuzzy command palette for interactive mode.
+//
+// Pressing Ctrl-P (or typing `>` followed by an optional query) opens a fuzzy finder over
+// every command and alias known to the Clap model plus the recent REPL history. The chosen
+// entry is put back into the prompt for editing instead of being executed right away.
+
+use anyhow::Result;
+use clap::CommandFactory;
+use rustyline::{
+    Cmd, ConditionalEventHandler, DefaultEditor, Event, EventContext, EventHandler, KeyEvent,
+    RepeatCount,
+};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Number of matches shown at once
+const PALETTE_LIMIT: usize = 10;
+
+/// Prefix that opens the palette from the prompt
+pub const PALETTE_PREFIX: char = '>';
+
+/// Where a palette entry comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteSource {
+    Command,
+    Alias,
+    History,
+}
+
+impl PaletteSource {
+    fn label(&self) -> &'static str {
+        match self {
+            PaletteSource::Command => "command",
+            PaletteSource::Alias => "alias",
+            PaletteSource::History => "history",
+        }
+    }
+}
+
+/// A selectable palette entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteEntry {
+    /// Text inserted into the prompt
+    pub text: String,
+    pub description: String,
+    pub source: PaletteSource,
+}
+
+/// Build the command index from the Clap model
+pub fn command_index() -> Vec<PaletteEntry> {
+    let mut entries = Vec::new();
+    collect_commands(&crate::cli::Cli::command(), "ducktape", &mut entries);
+    entries
+}
+
+fn collect_commands(command: &clap::Command, prefix: &str, entries: &mut Vec<PaletteEntry>) {
+    for sub in command.get_subcommands().filter(|s| s.get_name() != "help") {
+        let path = format!("{} {}", prefix, sub.get_name());
+        let about = sub.get_about().map(|a| a.to_string()).unwrap_or_default();
+
+        // Leaf commands show their positional arguments as a usage hint
+        if !sub.has_subcommands() {
+            let positionals: Vec<String> = sub
+                .get_positionals()
+                .map(|arg| format!("<{}>", arg.get_id().as_str().to_lowercase()))
+                .collect();
+            let description = if positionals.is_empty() {
+                about.clone()
+            } else {
+                format!("{} ({})", about, positionals.join(" "))
+            };
+            entries.push(PaletteEntry {
+                text: path.clone(),
+                description,
+                source: PaletteSource::Command,
+            });
+        }
+
+        for alias in sub.get_all_aliases() {
+            entries.push(PaletteEntry {
+                text: format!("{} {}", prefix, alias),
+                description: format!("alias for {}", path),
+                source: PaletteSource::Alias,
+            });
+        }
+
+        collect_commands(sub, &path, entries);
+    }
+}
+
+/// Palette entries for recent history, most recent first and without duplicates
+pub fn history_entries<'a>(
+    history: impl DoubleEndedIterator<Item = &'a String>,
+) -> Vec<PaletteEntry> {
+    let mut entries: Vec<PaletteEntry> = Vec::new();
+    for line in history.rev() {
+        let line = line.trim();
+        if line.is_empty()
+            || line.starts_with(PALETTE_PREFIX)
+            || entries.iter().any(|e| e.text == line)
+        {
+            continue;
+        }
+        entries.push(PaletteEntry {
+            text: line.to_string(),
+            description: String::new(),
+            source: PaletteSource::History,
+        });
+    }
+    entries
+}
+
+/// Score how well `query` fuzzily matches `candidate`
+///
+/// Every query character must appear in order. Consecutive matches and matches at word
+/// starts score higher; `None` means no match. An empty query matches everything.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = (position..candidate.len()).find(|&i| candidate[i] == q)?;
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !candidate[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        // Penalize gaps so that tighter matches win
+        score -= (found - position).min(10) as i64;
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+/// Rank entries against a query, best first; ties keep the index order
+pub fn search<'a>(entries: &'a [PaletteEntry], query: &str, limit: usize) -> Vec<&'a PaletteEntry> {
+    let mut matches: Vec<(i64, usize, &PaletteEntry)> = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(i, entry)| {
+            let haystack = format!("{} {}", entry.text, entry.description);
+            fuzzy_score(query, &entry.text)
+                .map(|s| s + 2)
+                .or_else(|| fuzzy_score(query, &haystack))
+                .map(|score| (score, i, entry))
+        })
+        .collect();
+    matches.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    matches.into_iter().take(limit).map(|(_, _, entry)| entry).collect()
+}
+
+/// Key handler that accepts the current line and flags it as a palette query
+#[derive(Clone, Default)]
+pub struct PaletteTrigger(Arc<AtomicBool>);
+
+impl PaletteTrigger {
+    /// Bind the trigger to Ctrl-P on the given editor
+    pub fn bind(&self, rl: &mut DefaultEditor) {
+        rl.bind_sequence(KeyEvent::ctrl('P'), EventHandler::Conditional(Box::new(self.clone())));
+    }
+
+    /// Whether the last accepted line was submitted with Ctrl-P (clears the flag)
+    pub fn take(&self) -> bool {
+        self.0.swap(false, Ordering::SeqCst)
+    }
+}
+
+impl ConditionalEventHandler for PaletteTrigger {
+    fn handle(
+        &self,
+        _evt: &Event,
+        _n: RepeatCount,
+        _positive: bool,
+        _ctx: &EventContext,
+    ) -> Option<Cmd> {
+        self.0.store(true, Ordering::SeqCst);
+        Some(Cmd::AcceptLine)
+    }
+}
+
+/// Run the palette until an entry is chosen or the user cancels
+///
+/// Typing a number picks that match, other text refines the query and an empty line
+/// cancels. Returns the text of the chosen entry.
+pub fn run_palette(rl: &mut DefaultEditor, query: &str) -> Result<Option<String>> {
+    let mut entries = history_entries(rl.history().iter());
+    entries.extend(command_index());
+    let mut query = query.trim().to_string();
+
+    loop {
+        let matches = search(&entries, &query, PALETTE_LIMIT);
+        if matches.is_empty() {
+            println!("No commands match '{}'", query);
+        } else {
+            for (i, entry) in matches.iter().enumerate() {
+                let description = if entry.description.is_empty() {
+                    String::new()
+                } else {
+                    format!(" — {}", entry.description)
+                };
+                println!(
+                    "  {:>2}. {} [{}]{}",
+                    i + 1,
+                    entry.text,
+                    entry.source.label(),
+                    description
+                );
+            }
+        }
+
+        let input = match rl.readline("palette> ") {
+            Ok(input) => input,
+            Err(rustyline::error::ReadlineError::Interrupted)
+            | Err(rustyline::error::ReadlineError::Eof) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let input = input.trim();
+        if input.is_empty() {
+            return Ok(None);
+        }
+        if let Ok(choice) = input.parse::<usize>() {
+            if let Some(entry) = choice.checked_sub(1).and_then(|i| matches.get(i)) {
+                return Ok(Some(entry.text.clone()));
+            }
+            println!("Pick a number between 1 and {}", matches.len());
+            continue;
+        }
+        query = input.to_string();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(text: &str, source: PaletteSource) -> PaletteEntry {
+        PaletteEntry { text: text.to_string(), description: String::new(), source }
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("cc", "ducktape calendar create").is_some());
+        assert!(fuzzy_score("xyz", "ducktape calendar create").is_none());
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        // Word starts and consecutive characters beat scattered matches
+        assert!(
+            fuzzy_score("todo", "ducktape todo add").unwrap()
+                > fuzzy_score("todo", "ducktape note delete").unwrap_or(i64::MIN)
+        );
+    }
+
+    #[test]
+    fn test_search_ranks_best_match_first() {
+        let entries = vec![
+            entry("ducktape calendar list", PaletteSource::Command),
+            entry("ducktape calendar create", PaletteSource::Command),
+            entry("ducktape todo add", PaletteSource::Command),
+        ];
+        let results = search(&entries, "cal create", 5);
+        assert_eq!(results[0].text, "ducktape calendar create");
+        assert_eq!(search(&entries, "", 2).len(), 2);
+        assert!(search(&entries, "zzz", 5).is_empty());
+    }
+
+    #[test]
+    fn test_history_entries_are_recent_first_and_unique() {
+        let history = [
+            "todo list".to_string(),
+            "> cal".to_string(),
+            "calendar list".to_string(),
+            "todo list".to_string(),
+        ];
+        let entries = history_entries(history.iter());
+        let texts: Vec<&str> = entries.iter().map(|e| e.text.as_str()).collect();
+        assert_eq!(texts, vec!["todo list", "calendar list"]);
+    }
+
+    #[test]
+    fn test_command_index_includes_commands_and_aliases() {
+        let index = command_index();
+        assert!(index.iter().any(|e| e.text == "ducktape calendar create"
+            && e.source == PaletteSource::Command));
+        assert!(
+            index
+                .iter()
+                .any(|e| e.text == "ducktape todos" && e.source == PaletteSource::Alias)
+        );
+        assert!(!index.iter().any(|e| e.text.ends_with(" help")));
+    }
+}
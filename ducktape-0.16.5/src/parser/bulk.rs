@@ -0,0 +1,186 @@
+//! Bulk calendar requests, without a language model.
+//
+// "move all my meetings on Friday to next Monday" or "delete every event named Standup next
+// week" change many events at once. A language model tends to turn them into a command for a
+// single event, so they are recognized here first. The request becomes a `BulkOperation`,
+// which runs as `calendar move-all` or `calendar delete-all`; both list the matching events
+// and ask before changing anything. A request is only claimed when its days are understood by
+// `crate::dates`, so anything else still goes to the configured parser.
+
+use crate::dates::{parse_date, parse_date_range};
+use chrono::NaiveDate;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static MOVE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^\s*(?:please\s+)?(?:move|shift|reschedule|push|bump)\s+(?:all|every|each)\s+(.+)\s+to\s+(.+?)\s*[.!]?\s*$").unwrap()
+});
+static DELETE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^\s*(?:please\s+)?(?:delete|remove|cancel|clear)\s+(?:all|every|each)\s+(.+?)\s*[.!]?\s*$").unwrap()
+});
+static CALENDAR: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)\s+(?:in|on|from)\s+(?:my\s+|the\s+)?(?:"([^"]+)"|(\S+))\s+calendar\b"#)
+        .unwrap()
+});
+static EVENTS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^(?:(?:of|my|the)\s+)*(?:(.+?)\s+)?(?:events?|meetings?|appointments?|calls?)(?:\s+(?:named|called|titled)\s+(.+))?$").unwrap()
+});
+
+/// Words that may introduce the days and are left out of them
+const DAY_WORDS: &[&str] = &["on", "from", "for", "during"];
+
+/// What to do with the matching events
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BulkAction {
+    /// Move the events so that the first day of the range lands on `to`, keeping their times
+    Move {
+        to: String,
+    },
+    Delete,
+}
+
+/// A change to every event in a day or range of days, optionally only those with a title
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkOperation {
+    pub action: BulkAction,
+    /// The day or range as written, such as "friday" or "next week"
+    pub when: String,
+    /// Only events whose title contains this, ignoring case
+    pub title: Option<String>,
+    pub calendar: Option<String>,
+}
+
+impl BulkOperation {
+    /// The operation as a `ducktape calendar` command line
+    pub fn to_command(&self) -> String {
+        let mut words = vec!["ducktape", "calendar"];
+        match &self.action {
+            BulkAction::Move { to } => {
+                words.extend(["move-all", "--on", self.when.as_str(), "--to", to.as_str()])
+            }
+            BulkAction::Delete => words.extend(["delete-all", "--on", self.when.as_str()]),
+        }
+        if let Some(title) = &self.title {
+            words.extend(["--title", title.as_str()]);
+        }
+        if let Some(calendar) = &self.calendar {
+            words.extend(["--calendar", calendar.as_str()]);
+        }
+        shell_words::join(words)
+    }
+}
+
+/// Recognize a bulk move or delete request, relative to `today`
+pub fn parse_bulk_request(input: &str, today: NaiveDate) -> Option<BulkOperation> {
+    let (action, selection) = if let Some(caps) = MOVE.captures(input) {
+        let to = caps[2].trim().to_string();
+        parse_date(&to, today)?;
+        (BulkAction::Move { to }, caps[1].to_string())
+    } else {
+        let caps = DELETE.captures(input)?;
+        (BulkAction::Delete, caps[1].to_string())
+    };
+
+    let calendar = CALENDAR
+        .captures(&selection)
+        .and_then(|caps| caps.get(1).or(caps.get(2)))
+        .map(|name| name.as_str().trim().to_string());
+    let selection = CALENDAR.replace(&selection, "").to_string();
+
+    let (what, when) = split_days(&selection, today)?;
+    let title = events_title(&what)?;
+    Some(BulkOperation { action, when, title, calendar })
+}
+
+/// Split "my meetings on friday" into the events and the longest run of days at the end
+fn split_days(selection: &str, today: NaiveDate) -> Option<(String, String)> {
+    let words: Vec<&str> = selection.split_whitespace().collect();
+    (1..words.len()).find_map(|i| {
+        let mut days = &words[i..];
+        while days.len() > 1 && DAY_WORDS.contains(&days[0].to_lowercase().as_str()) {
+            days = &days[1..];
+        }
+        let days = days.join(" ");
+        parse_date_range(&days, today)?;
+        Some((words[..i].join(" "), days))
+    })
+}
+
+/// The title filter in "meetings", "Standup meetings" or "events named Standup"
+///
+/// `None` when the request is not about events; `Some(None)` for every event.
+fn events_title(what: &str) -> Option<Option<String>> {
+    if what.eq_ignore_ascii_case("everything") {
+        return Some(None);
+    }
+    let caps = EVENTS.captures(what.trim())?;
+    let title = caps
+        .get(2)
+        .or(caps.get(1))
+        .map(|m| m.as_str().trim_matches(|c| c == '"' || c == '\'').trim().to_string())
+        .filter(|t| !t.is_empty());
+    Some(title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Friday 2025-03-14
+    fn bulk(input: &str) -> Option<BulkOperation> {
+        parse_bulk_request(input, NaiveDate::from_ymd_opt(2025, 3, 14).unwrap())
+    }
+
+    #[test]
+    fn test_move_meetings() {
+        assert_eq!(
+            bulk("move all my meetings on Friday to next Monday"),
+            Some(BulkOperation {
+                action: BulkAction::Move { to: "next Monday".to_string() },
+                when: "Friday".to_string(),
+                title: None,
+                calendar: None,
+            })
+        );
+        let operation = bulk("Reschedule every Standup meeting tomorrow to Monday.").unwrap();
+        assert_eq!(operation.title.as_deref(), Some("Standup"));
+        assert_eq!(operation.when, "tomorrow");
+    }
+
+    #[test]
+    fn test_delete_named_events() {
+        let operation = bulk("delete every event named Standup next week").unwrap();
+        assert_eq!(operation.action, BulkAction::Delete);
+        assert_eq!(operation.title.as_deref(), Some("Standup"));
+        assert_eq!(operation.when, "next week");
+
+        let operation =
+            bulk("cancel all events called \"Monday review\" in my Work calendar this week");
+        let operation = operation.unwrap();
+        assert_eq!(operation.title.as_deref(), Some("Monday review"));
+        assert_eq!(operation.calendar.as_deref(), Some("Work"));
+        assert_eq!(operation.when, "this week");
+
+        let operation = bulk("delete all meetings on friday in the \"Team Events\" calendar");
+        assert_eq!(operation.unwrap().calendar.as_deref(), Some("Team Events"));
+    }
+
+    #[test]
+    fn test_to_command() {
+        let operation = bulk("delete every event named Team sync next week").unwrap();
+        assert_eq!(
+            operation.to_command(),
+            "ducktape calendar delete-all --on 'next week' --title 'Team sync'"
+        );
+        let operation = bulk("move all meetings on friday to monday").unwrap();
+        assert_eq!(operation.to_command(), "ducktape calendar move-all --on friday --to monday");
+    }
+
+    #[test]
+    fn test_other_requests_are_left_alone() {
+        assert_eq!(bulk("move the meeting on friday to monday"), None);
+        assert_eq!(bulk("delete every event named Standup someday"), None);
+        assert_eq!(bulk("move all my tasks due tomorrow to Monday"), None);
+        assert_eq!(bulk("move all meetings on friday to the moon"), None);
+    }
+}
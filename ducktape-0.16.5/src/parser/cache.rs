@@ -0,0 +1,217 @@
+//! Disk cache for commands generated by language models
+//!
+//! Keeps generated commands in ~/.ducktape/llm_cache.json so that repeating an input costs
+//! no API call, also across runs.
+
+// Entries are keyed by provider, model and the normalized input, and expire after
+// `nlp.cache_ttl_hours`. They also expire at midnight: inputs like "lunch tomorrow" are
+// resolved against the current date, so yesterday's answer would be a day off.
+// `ducktape cache clear` removes the file.
+
+use crate::config::NlpConfig;
+use crate::state::{LlmCacheEntry, StateManager};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::{debug, warn};
+
+/// Entries kept on disk; the oldest are dropped first
+const MAX_ENTRIES: usize = 500;
+
+/// Set while cached commands must not be used, see `bypass`
+static BYPASSED: AtomicBool = AtomicBool::new(false);
+
+/// Keeps cached commands from being used until it is dropped
+#[derive(Debug)]
+pub struct Bypass {
+    was_bypassed: bool,
+}
+
+impl Drop for Bypass {
+    fn drop(&mut self) {
+        BYPASSED.store(self.was_bypassed, Ordering::SeqCst);
+    }
+}
+
+/// Send every input to the model, e.g. while evaluating a parser, until the guard is dropped
+pub fn bypass() -> Bypass {
+    Bypass { was_bypassed: BYPASSED.swap(true, Ordering::SeqCst) }
+}
+
+/// Whether cached commands must not be used right now
+pub fn is_bypassed() -> bool {
+    BYPASSED.load(Ordering::SeqCst)
+}
+
+/// Key for an input, ignoring case and extra whitespace
+pub fn cache_key(provider: &str, model: &str, input: &str) -> String {
+    let normalized = input.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    format!("{}:{}:{}", provider, model, normalized)
+}
+
+fn created_at(entry: &LlmCacheEntry) -> Option<DateTime<Local>> {
+    DateTime::parse_from_rfc3339(&entry.created_at)
+        .ok()
+        .map(|t| t.with_timezone(&Local))
+}
+
+/// Generated commands with their expiry rule
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    ttl: Duration,
+    entries: Vec<LlmCacheEntry>,
+}
+
+impl ResponseCache {
+    pub fn new(ttl_hours: u64, entries: Vec<LlmCacheEntry>) -> Self {
+        Self { ttl: Duration::hours(ttl_hours as i64), entries }
+    }
+
+    /// Load the cache from state, dropping expired entries
+    pub fn load(config: &NlpConfig) -> Result<Self> {
+        let entries: Vec<LlmCacheEntry> = StateManager::new()?.load()?;
+        let mut cache = Self::new(config.cache_ttl_hours, entries);
+        cache.prune(Local::now());
+        Ok(cache)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        StateManager::new()?.save(&self.entries)
+    }
+
+    pub fn entries(&self) -> &[LlmCacheEntry] {
+        &self.entries
+    }
+
+    fn is_fresh(&self, entry: &LlmCacheEntry, now: DateTime<Local>) -> bool {
+        created_at(entry).is_some_and(|t| t + self.ttl > now && t.date_naive() == now.date_naive())
+    }
+
+    fn prune(&mut self, now: DateTime<Local>) {
+        let entries = std::mem::take(&mut self.entries);
+        self.entries = entries.into_iter().filter(|e| self.is_fresh(e, now)).collect();
+    }
+
+    /// The command cached for `key`, if it has not expired at `now`
+    pub fn get(&self, key: &str, now: DateTime<Local>) -> Option<&str> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|e| e.key == key && self.is_fresh(e, now))
+            .map(|e| e.command.as_str())
+    }
+
+    /// Cache `command` for `key`, replacing an older entry
+    pub fn insert(&mut self, key: &str, command: &str, now: DateTime<Local>) {
+        self.entries.retain(|e| e.key != key);
+        self.entries.push(LlmCacheEntry {
+            key: key.to_string(),
+            command: command.to_string(),
+            created_at: now.to_rfc3339(),
+        });
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..excess);
+        }
+    }
+}
+
+/// The command cached on disk for this provider, model and input
+pub fn lookup(config: &NlpConfig, provider: &str, model: &str, input: &str) -> Option<String> {
+    if config.cache_ttl_hours == 0 || is_bypassed() {
+        return None;
+    }
+    match ResponseCache::load(config) {
+        Ok(cache) => {
+            let command = cache.get(&cache_key(provider, model, input), Local::now());
+            if command.is_some() {
+                debug!("Using {} response from the disk cache", provider);
+            }
+            command.map(str::to_string)
+        }
+        Err(e) => {
+            warn!("Failed to read the response cache: {}", e);
+            None
+        }
+    }
+}
+
+/// Store a generated command on disk; failures are logged, not returned
+pub fn store(config: &NlpConfig, provider: &str, model: &str, input: &str, command: &str) {
+    if config.cache_ttl_hours == 0 {
+        return;
+    }
+    let result = ResponseCache::load(config).and_then(|mut cache| {
+        cache.insert(&cache_key(provider, model, input), command, Local::now());
+        cache.save()
+    });
+    if let Err(e) = result {
+        warn!("Failed to write the response cache: {}", e);
+    }
+}
+
+/// Remove every cached command and return how many there were
+pub fn clear() -> Result<usize> {
+    let state = StateManager::new()?;
+    let entries: Vec<LlmCacheEntry> = state.load()?;
+    state.save::<LlmCacheEntry>(&[])?;
+    Ok(entries.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2025, 3, 14, hour, 0, 0).unwrap()
+    }
+
+    const COMMAND: &str = "ducktape calendar create \"Lunch\" 2025-03-15 12:00 13:00 \"Work\"";
+
+    #[test]
+    fn test_cache_key_normalizes_input() {
+        assert_eq!(
+            cache_key("deepseek", "deepseek-chat", "  Lunch   TOMORROW\tat noon "),
+            "deepseek:deepseek-chat:lunch tomorrow at noon"
+        );
+        assert_ne!(
+            cache_key("deepseek", "deepseek-chat", "lunch"),
+            cache_key("grok", "grok-2-latest", "lunch")
+        );
+    }
+
+    #[test]
+    fn test_entries_expire_after_ttl_and_at_midnight() {
+        let mut cache = ResponseCache::new(2, Vec::new());
+        cache.insert("key", COMMAND, at(9));
+
+        assert_eq!(cache.get("key", at(10)), Some(COMMAND));
+        assert_eq!(cache.get("key", at(12)), None);
+        assert_eq!(cache.get("other", at(10)), None);
+
+        let mut long_lived = ResponseCache::new(48, Vec::new());
+        long_lived.insert("key", COMMAND, at(23));
+        let next_morning = at(23) + Duration::hours(2);
+        assert_eq!(long_lived.get("key", next_morning), None);
+
+        long_lived.prune(next_morning);
+        assert!(long_lived.entries().is_empty());
+    }
+
+    #[test]
+    fn test_insert_replaces_and_caps_entries() {
+        let mut cache = ResponseCache::new(24, Vec::new());
+        cache.insert("key", "ducktape calendar list", at(9));
+        cache.insert("key", COMMAND, at(10));
+        assert_eq!(cache.entries().len(), 1);
+        assert_eq!(cache.get("key", at(11)), Some(COMMAND));
+
+        for i in 0..MAX_ENTRIES + 5 {
+            cache.insert(&format!("key{}", i), COMMAND, at(10));
+        }
+        assert_eq!(cache.entries().len(), MAX_ENTRIES);
+        assert_eq!(cache.get("key0", at(11)), None);
+        assert!(cache.get(&format!("key{}", MAX_ENTRIES + 4), at(11)).is_some());
+    }
+}
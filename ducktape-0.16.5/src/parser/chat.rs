@@ -0,0 +1,436 @@
+//! Natural language parsing with OpenAI style chat completion APIs.
+//
+// DeepSeek and Grok (X.AI) take the same requests and answer in the same shape, so their
+// parsers share this pipeline. Cached translations are reused, from memory or the disk cache;
+// otherwise the system prompt is built, the request goes to an `LlmClient`, and the command in
+// the answer is enhanced with flags the model missed and validated. A `ChatProvider` holds
+// what differs between the services: names, endpoint, model and API key.
+
+use crate::config::Config;
+use crate::parser::llm::{self, HttpLlmClient, LlmClient};
+use crate::parser::prompt;
+use crate::parser::traits::{ParseProgress, ProgressSender};
+use crate::state::RecentItem;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Local, Timelike};
+use lru::LruCache;
+use once_cell::sync::Lazy;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::env;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+
+/// A chat completions service and how to reach it
+#[derive(Debug, Clone, Copy)]
+pub struct ChatProvider {
+    /// Name in messages, e.g. "DeepSeek"
+    pub name: &'static str,
+    /// Name in the caches and the usage ledger, e.g. "deepseek"
+    pub id: &'static str,
+    /// Variable overriding `default_api_base`
+    pub api_base_var: &'static str,
+    pub default_api_base: &'static str,
+    /// Variable overriding `default_model`
+    pub model_var: &'static str,
+    pub default_model: &'static str,
+    /// Variable or keychain entry holding the API key
+    pub key_var: &'static str,
+    /// Service name for `ducktape keys set`
+    pub key_service: &'static str,
+}
+
+impl ChatProvider {
+    /// Read the API key from the environment or keychain without showing it in errors
+    pub fn api_key(&self) -> Result<String> {
+        self.api_key_from(crate::secrets::get)
+    }
+
+    /// `api_key`, reading credentials with `lookup` instead of the environment and keychain
+    pub fn api_key_from(&self, lookup: impl Fn(&str) -> Option<String>) -> Result<String> {
+        lookup(self.key_var).ok_or_else(|| {
+            anyhow!(
+                "{var} not set. Store your {name} API key with `ducktape keys set {service}` or export {var}='your-key-here'",
+                var = self.key_var,
+                name = self.name,
+                service = self.key_service
+            )
+        })
+    }
+
+    /// Fail early without an API key, unless a client set with `llm::set_llm_client` answers
+    pub fn check_key(&self) -> Result<()> {
+        if llm::llm_client().is_none() {
+            self.api_key()?;
+        }
+        Ok(())
+    }
+
+    pub fn api_base(&self) -> String {
+        env::var(self.api_base_var).unwrap_or_else(|_| self.default_api_base.to_string())
+    }
+
+    pub fn model(&self) -> String {
+        env::var(self.model_var).unwrap_or_else(|_| self.default_model.to_string())
+    }
+}
+
+/// Recent commands keyed by provider and sanitized input, at most 100 entries
+static RESPONSE_CACHE: Lazy<Mutex<LruCache<String, String>>> =
+    Lazy::new(|| Mutex::new(LruCache::new(NonZeroUsize::new(100).unwrap())));
+
+fn memory_cache_key(provider: &ChatProvider, input: &str) -> String {
+    format!("{}\n{}", provider.id, input)
+}
+
+/// Get the command `provider` generated for an input, if it is still in memory
+pub fn get_cached_response(provider: &ChatProvider, input: &str) -> Option<String> {
+    let key = memory_cache_key(provider, input);
+    RESPONSE_CACHE.lock().ok().and_then(|mut cache| cache.get(&key).cloned())
+}
+
+/// Keep the command `provider` generated for an input in memory
+pub fn store_response(provider: &ChatProvider, input: &str, response: &str) {
+    if let Ok(mut cache) = RESPONSE_CACHE.lock() {
+        cache.put(memory_cache_key(provider, input), response.to_string());
+    }
+}
+
+/// Whether the input asks for a reminder rather than a calendar event
+pub fn is_todo_request(input: &str) -> bool {
+    let lower = input.to_lowercase();
+    lower.contains("todo")
+        || lower.contains("reminder")
+        || lower.contains("task")
+        || (lower.contains("remind") && !lower.contains("meeting"))
+        || lower.contains("checklist")
+}
+
+/// Pull the ducktape command out of a chat completion response
+///
+/// Models sometimes wrap the command in a code fence or add a sentence around it, so the
+/// first line that starts with `ducktape` is used.
+pub fn extract_command(provider: &ChatProvider, response: &Value) -> Result<String> {
+    let content = response["choices"][0]["message"]["content"]
+        .as_str()
+        .ok_or_else(|| anyhow!("Invalid or missing response content from {} API", provider.name))?;
+
+    content
+        .lines()
+        .map(|line| line.trim().trim_matches('`').trim())
+        .find(|line| line.starts_with("ducktape "))
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("{} response did not contain a ducktape command", provider.name))
+}
+
+/// Reject generated commands that are not a single ducktape command
+pub fn validate_command(command: &str) -> Result<()> {
+    if !command.starts_with("ducktape ") {
+        return Err(anyhow!("Generated command does not start with 'ducktape': {}", command));
+    }
+    if ["&&", "|", ";", "`", "$("].iter().any(|s| command.contains(s)) {
+        return Err(anyhow!("Generated command contains potentially unsafe characters"));
+    }
+    Ok(())
+}
+
+/// Build the system prompt for a reminder or calendar request
+///
+/// With `recent`, the model is told about the item just created or changed, so that follow-ups
+/// like "actually make it 3pm" change it.
+pub fn system_prompt(
+    todo: bool,
+    now: DateTime<Local>,
+    calendars: &[String],
+    default_calendar: &str,
+    aliases: &BTreeMap<String, String>,
+    recent: Option<&RecentItem>,
+) -> String {
+    let current_time = now.format("%Y-%m-%d %H:%M");
+    let today = now.format("%Y-%m-%d");
+    let tomorrow = (now + chrono::Duration::days(1)).format("%Y-%m-%d");
+
+    let mut prompt = if todo {
+        format!(
+            r#"You are a command line interface parser that converts natural language into ducktape commands.
+Reply with exactly one command and nothing else.
+Current time is: {current_time}
+Available reminder lists: Reminders, Work, Personal, Urgent
+
+For todo/reminder items, use the format:
+ducktape todo create "<title>" [list1] [list2] [--remind "<YYYY-MM-DD HH:MM>"] [--due "<YYYY-MM-DD>"] [--priority high|medium|low] [--notes "<additional details>"]
+
+Rules:
+1. If no specific time is mentioned, do not add the --remind flag.
+2. If a time is specified, use --remind with format "YYYY-MM-DD HH:MM".
+3. If today or tomorrow is mentioned, use the actual date ({today} or {tomorrow}).
+4. If no list is specified, use just one argument: the title.
+5. If notes or details are provided, add them with --notes flag.
+6. If input mentions "work", add the "Work" list.
+7. If input mentions "personal", add the "Personal" list.
+8. If input mentions "urgent" or "important", add the "Urgent" list and --priority high."#
+        )
+    } else {
+        format!(
+            r#"You are a command line interface parser that converts natural language into ducktape commands.
+Reply with exactly one command and nothing else.
+Current time is: {current_time}
+Available calendars: {calendars}
+Default calendar: {default_calendar}{aliases}
+
+For calendar events, use the format:
+ducktape calendar create "<title>" <date> <start_time> <end_time> "<calendar>" [--email "<email1>,<email2>"] [--contacts "<name1>,<name2>"] [--location "<location>"]
+
+For recurring events, add any of these options:
+--repeat <daily|weekly|monthly|yearly>   Set recurrence frequency
+--interval <number>                      Set interval (e.g., every 2 weeks)
+--until <YYYY-MM-DD>                     Set end date for recurrence
+--count <number>                         Set number of occurrences
+--days <0,1,2...>                        Set days of week (0=Sun, 1=Mon, etc.)
+
+Rules:
+1. If no date is specified, use today's date ({today}).
+2. If no time is specified, use the next available hour ({next_hour}:00) for start time and add 1 hour for end time.
+3. Use 24-hour format (HH:MM) for times and YYYY-MM-DD for dates.
+4. Always include both start and end times.
+5. If a calendar is specified in input, use that exact calendar name; otherwise use the default calendar.
+6. If input mentions "work", use the "Work" calendar.
+7. If input mentions scheduling "with" someone, add their names to --contacts, comma-separated.
+8. If input mentions an email address, add it with --email; separate several with commas.
+9. If the input mentions "zoom", "video call" or "virtual meeting", add the --zoom flag."#,
+            calendars = calendars.join(", "),
+            aliases = if aliases.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    "\nCalendar aliases (the user may name a calendar by its alias): {}",
+                    aliases
+                        .iter()
+                        .map(|(alias, calendar)| format!("\"{}\" = \"{}\"", alias, calendar))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            },
+            next_hour = (now.hour() + 1).min(23)
+        )
+    };
+    if let Some(item) = recent {
+        prompt.push_str("\n\n");
+        prompt.push_str(&crate::context::prompt_note(item));
+    }
+    prompt
+}
+
+/// Parse natural language input into a ducktape command with `provider`
+///
+/// Without a client, the one set with `llm::set_llm_client` or else the provider's API is used.
+/// Answers from those clients are neither cached nor counted as usage.
+pub async fn parse(
+    provider: &ChatProvider,
+    input: &str,
+    progress: Option<&ProgressSender>,
+    client: Option<Arc<dyn LlmClient>>,
+) -> Result<String> {
+    if input.trim().is_empty() {
+        return Err(anyhow!("Empty input provided"));
+    }
+    if input.len() > 1000 {
+        return Err(anyhow!("Input too long (max 1000 characters)"));
+    }
+
+    let sanitized_input: String =
+        input.chars().filter(|&c| !c.is_control() || c == '\n' || c == '\t').collect();
+
+    let config = Config::load().unwrap_or_else(|e| {
+        warn!("Failed to load config: {}, using defaults", e);
+        Config::default()
+    });
+    let client = client.or_else(llm::llm_client);
+    let cacheable = client.is_none();
+    let instructions = prompt::user_instructions(&config.nlp);
+    let model = provider.model();
+    let cache_model = prompt::cache_model(&model, instructions.as_deref());
+    // Answers given under other instructions are kept apart
+    let memory_key = match &instructions {
+        Some(_) => format!("{}\n{}", cache_model, sanitized_input),
+        None => sanitized_input.clone(),
+    };
+
+    let cached = if cacheable && !crate::parser::cache::is_bypassed() {
+        get_cached_response(provider, &memory_key)
+    } else {
+        None
+    };
+    if let Some(cached) = cached {
+        debug!("Using cached {} response for input", provider.name);
+        crate::parser::trace::note_cache_hit();
+        return Ok(cached);
+    }
+
+    let cached = cacheable
+        .then(|| {
+            crate::parser::cache::lookup(&config.nlp, provider.id, &cache_model, &sanitized_input)
+        })
+        .flatten();
+    if let Some(cached) = cached {
+        store_response(provider, &memory_key, &cached);
+        crate::parser::trace::note_cache_hit();
+        return Ok(cached);
+    }
+
+    crate::usage::check_budget(&config.usage)?;
+    let client: Arc<dyn LlmClient> = match client {
+        Some(client) => client,
+        None => {
+            Arc::new(HttpLlmClient::new(provider.name, &provider.api_base(), provider.api_key()?)?)
+        }
+    };
+    let default_calendar = config
+        .calendar
+        .resolve_calendar(config.calendar.default_calendar.as_deref().unwrap_or("Calendar"));
+
+    let todo = is_todo_request(&sanitized_input);
+    let calendars = if todo {
+        Vec::new()
+    } else {
+        crate::calendar::get_available_calendars().await.unwrap_or_else(|e| {
+            warn!("Failed to get available calendars: {}", e);
+            vec![default_calendar.clone()]
+        })
+    };
+
+    let now = Local::now();
+    let recent = crate::context::current();
+    let prompt = prompt::with_instructions(
+        system_prompt(
+            todo,
+            now,
+            &calendars,
+            &default_calendar,
+            &config.calendar.aliases,
+            recent.as_ref(),
+        ),
+        instructions.as_deref(),
+    );
+    let mut body = json!({
+        "model": model,
+        "messages": [
+            { "role": "system", "content": prompt },
+            { "role": "user", "content": sanitized_input }
+        ],
+        "temperature": 0.3,
+        "max_tokens": 200
+    });
+    if let Some(progress) = progress {
+        body["stream"] = json!(true);
+        body["stream_options"] = json!({ "include_usage": true });
+        let _ =
+            progress.send(ParseProgress::Status(format!("Asking {} ({})", provider.name, model)));
+    }
+
+    let response_json = client.complete(&body, progress).await?;
+    if cacheable {
+        crate::usage::record_response(&config.usage, provider.id, &model, &response_json);
+    }
+    let command = extract_command(provider, &response_json)?;
+    debug!("Received command from {} API: {}", provider.name, command);
+
+    let enhanced = crate::parser::enhance::enhance_for(&command, &sanitized_input, &config);
+    validate_command(&enhanced)?;
+
+    if cacheable {
+        store_response(provider, &memory_key, &enhanced);
+        crate::parser::cache::store(
+            &config.nlp,
+            provider.id,
+            &cache_model,
+            &sanitized_input,
+            &enhanced,
+        );
+    }
+    Ok(enhanced)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::deepseek::api::DEEPSEEK;
+    use chrono::TimeZone;
+
+    fn response(content: &str) -> Value {
+        json!({ "choices": [{ "message": { "role": "assistant", "content": content } }] })
+    }
+
+    #[test]
+    fn test_extract_command() {
+        let fenced = "```bash\nducktape todo create \"Buy milk\"\n```";
+        assert_eq!(
+            extract_command(&DEEPSEEK, &response(fenced)).unwrap(),
+            "ducktape todo create \"Buy milk\""
+        );
+
+        let chatty = "Here is the command:\nducktape calendar list";
+        assert_eq!(
+            extract_command(&DEEPSEEK, &response(chatty)).unwrap(),
+            "ducktape calendar list"
+        );
+
+        assert!(extract_command(&DEEPSEEK, &response("I can't help with that")).is_err());
+        assert!(extract_command(&DEEPSEEK, &json!({ "error": "bad" })).is_err());
+    }
+
+    #[test]
+    fn test_validate_command() {
+        assert!(validate_command("ducktape calendar list").is_ok());
+        assert!(validate_command("rm -rf /").is_err());
+        assert!(validate_command("ducktape calendar list; rm -rf /").is_err());
+        assert!(is_todo_request("Remind me to water the plants"));
+        assert!(!is_todo_request("Schedule a meeting to remind the team"));
+    }
+
+    #[test]
+    fn test_system_prompt() {
+        let now = Local.with_ymd_and_hms(2025, 5, 1, 9, 30, 0).unwrap();
+        let calendars = vec!["Work".to_string(), "Home".to_string()];
+
+        let aliases = BTreeMap::new();
+        let event = system_prompt(false, now, &calendars, "Work", &aliases, None);
+        assert!(event.contains("Current time is: 2025-05-01 09:30"));
+        assert!(event.contains("Available calendars: Work, Home"));
+        assert!(event.contains("(10:00)"));
+        assert!(!event.contains("Calendar aliases"));
+
+        let aliases = BTreeMap::from([("job".to_string(), "Work".to_string())]);
+        let event = system_prompt(false, now, &calendars, "Work", &aliases, None);
+        assert!(event.contains(
+            "Calendar aliases (the user may name a calendar by its alias): \"job\" = \"Work\""
+        ));
+
+        let todo = system_prompt(true, now, &[], "Work", &aliases, None);
+        assert!(todo.contains("ducktape todo create"));
+        assert!(todo.contains("2025-05-02"));
+        assert!(!todo.contains("@last"));
+
+        let recent = RecentItem {
+            kind: crate::state::LinkedKind::Todo,
+            id: "Buy milk".to_string(),
+            title: "Buy milk".to_string(),
+            when: None,
+            container: None,
+            updated_at: now.to_rfc3339(),
+        };
+        let event = system_prompt(false, now, &calendars, "Work", &aliases, Some(&recent));
+        assert!(event.contains("The user just created or changed the reminder \"Buy milk\""));
+        assert!(event.contains("ducktape todo move @last"));
+    }
+
+    #[tokio::test]
+    async fn test_cached_input_skips_api() -> Result<()> {
+        let command = "ducktape calendar create \"Cached\" 2025-05-01 10:00 11:00 \"Work\"";
+        store_response(&DEEPSEEK, "cached deepseek input", command);
+        assert_eq!(parse(&DEEPSEEK, "cached deepseek input", None, None).await?, command);
+        assert!(parse(&DEEPSEEK, "  ", None, None).await.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,403 @@
+//! Questions asked instead of guessing when natural language input leaves something open
+//!
+//! The terminal and the WebSocket chat ask the question of a `Clarification` and merge the
+//! reply into the command before running it.
+
+// A language model always answers with a command, guessing where the input is unclear: an
+// event without a day lands on today, "next Tuesday" on whichever Tuesday the model picks,
+// and a name that matches several people invites all of them. `check` compares the input
+// with the parsed command and returns a question for the first such guess. After the reply
+// is merged, `follow_up` checks the command for what is still open, so every unclear part is
+// asked about once, one at a time.
+
+use crate::calendar::{ContactCandidate, find_contact_candidates, is_ambiguous};
+use crate::dates::{parse_date, parse_datetime};
+use crate::parser::traits::ParseResult;
+use crate::redo::{CommandLine, Edits};
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, Weekday};
+use tracing::{debug, warn};
+
+/// Replies that drop the command instead of answering
+const CANCEL: [&str; 5] = ["cancel", "never mind", "nevermind", "stop", "forget it"];
+/// Longest phrase, in words, tried as a day or time
+const MAX_PHRASE_WORDS: usize = 4;
+
+/// What a question is about; each is asked at most once for an input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Topic {
+    /// The day of an event
+    Day,
+    /// Which person a name stands for
+    Contact(String),
+}
+
+/// What choosing an option does to the command
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resolution {
+    /// Set arguments or options, as `name=value` like `edit-last --set`
+    Set(Vec<String>),
+    /// Remember which person a name stands for, so that the contact lookup finds only them
+    Contact { name: String, person: String, emails: Vec<String> },
+}
+
+/// One answer offered with a question
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClarifyOption {
+    pub label: String,
+    pub resolution: Resolution,
+}
+
+/// A question to ask before a command made from natural language runs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clarification {
+    /// The natural language input
+    pub input: String,
+    /// The command as parsed, which the answer is merged into
+    pub command: String,
+    pub topic: Topic,
+    /// Topics answered before this one
+    pub settled: Vec<Topic>,
+    pub question: String,
+    pub options: Vec<ClarifyOption>,
+}
+
+impl Clarification {
+    /// The question with its options numbered, as the terminal shows it
+    pub fn prompt(&self) -> String {
+        let mut lines = vec![self.question.clone()];
+        lines.extend(
+            self.options
+                .iter()
+                .enumerate()
+                .map(|(i, option)| format!("  {}. {}", i + 1, option.label)),
+        );
+        lines.join("\n")
+    }
+
+    /// The option a reply picks: its number, its label or a unique part of the label
+    fn choose(&self, reply: &str) -> Option<&ClarifyOption> {
+        let reply = reply.trim().to_lowercase();
+        if let Ok(number) = reply.parse::<usize>() {
+            return number.checked_sub(1).and_then(|i| self.options.get(i));
+        }
+        if let Some(option) = self.options.iter().find(|o| o.label.to_lowercase() == reply) {
+            return Some(option);
+        }
+        let mut matching = self.options.iter().filter(|o| o.label.to_lowercase().contains(&reply));
+        match (matching.next(), matching.next()) {
+            (Some(option), None) if !reply.is_empty() => Some(option),
+            _ => None,
+        }
+    }
+
+    /// The command with `reply` merged in
+    ///
+    /// A reply is an option's number or label; the day of an event may also be any day
+    /// `parse_date` understands, such as "the 13th" or "friday".
+    pub fn answer(&self, reply: &str, today: NaiveDate) -> Result<String> {
+        let day;
+        let resolution = match self.choose(reply) {
+            Some(option) => &option.resolution,
+            None if self.topic == Topic::Day => {
+                let date = parse_date(reply, today)
+                    .ok_or_else(|| anyhow!("Answer with 1-{} or a day", self.options.len()))?;
+                day = Resolution::Set(vec![format!("date={}", date.format("%Y-%m-%d"))]);
+                &day
+            }
+            None => return Err(anyhow!("Answer with 1-{}", self.options.len())),
+        };
+        match resolution {
+            Resolution::Set(set) => {
+                let edits = Edits { set: set.clone(), ..Edits::default() };
+                Ok(format!("ducktape {}", edits.apply(&self.command)?))
+            }
+            Resolution::Contact { name, person, emails } => {
+                crate::state::cache_contact(name, person, emails)?;
+                Ok(self.command.clone())
+            }
+        }
+    }
+
+    /// The next question for the command the answer was merged into, if any
+    pub async fn follow_up(&self, command: &str, now: NaiveDateTime) -> Option<Clarification> {
+        let mut settled = self.settled.clone();
+        settled.push(self.topic.clone());
+        next_question(&self.input, command, now, settled).await
+    }
+}
+
+/// Whether a reply drops the command instead of answering the question
+pub fn is_cancel(reply: &str) -> bool {
+    CANCEL.contains(&reply.trim().trim_end_matches(['.', '!']).to_lowercase().as_str())
+}
+
+/// The parsed command, or the question to ask about it first when `nlp.clarify` is on
+pub async fn command_or_question(input: &str, command: String) -> ParseResult {
+    let enabled = crate::config::Config::load().map(|config| config.nlp.clarify).unwrap_or(true);
+    if enabled {
+        if let Some(clarification) = check(input, &command, Local::now().naive_local()).await {
+            debug!("Asking before running {}: {}", command, clarification.question);
+            return ParseResult::NeedsClarification(clarification);
+        }
+    }
+    ParseResult::CommandString(command)
+}
+
+/// The first thing the parser had to guess in `command`, as a question
+pub async fn check(input: &str, command: &str, now: NaiveDateTime) -> Option<Clarification> {
+    next_question(input, command, now, Vec::new()).await
+}
+
+async fn next_question(
+    input: &str,
+    command: &str,
+    now: NaiveDateTime,
+    settled: Vec<Topic>,
+) -> Option<Clarification> {
+    let line = CommandLine::parse(command).ok()?;
+    // Only events are checked so far
+    if line.path() != "calendar create" {
+        return None;
+    }
+    let ask = |topic: Topic, question: String, options: Vec<ClarifyOption>| Clarification {
+        input: input.to_string(),
+        command: command.to_string(),
+        topic,
+        settled: settled.clone(),
+        question,
+        options,
+    };
+
+    if !settled.contains(&Topic::Day) {
+        let today = now.date();
+        if let Some((day, dates)) = ambiguous_weekday(input, today) {
+            let question = format!(
+                "Which {} — the {} or the {}?",
+                weekday_name(day),
+                ordinal(dates[0].day()),
+                ordinal(dates[1].day())
+            );
+            return Some(ask(Topic::Day, question, dates.iter().map(|d| day_option(*d)).collect()));
+        }
+        if !mentions_when(input, now) {
+            let title = line.argument("title").unwrap_or("the event");
+            let question = format!("Which day should '{}' be on?", title);
+            let options = vec![day_option(today), day_option(today + Duration::days(1))];
+            return Some(ask(Topic::Day, question, options));
+        }
+    }
+
+    // A strategy given in the command already says how to treat several matches
+    if line.option("contact-strategy").is_some() {
+        return None;
+    }
+    let names = line.option("contacts").flatten().unwrap_or_default();
+    for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        let topic = Topic::Contact(name.to_lowercase());
+        if settled.contains(&topic)
+            || crate::state::find_cached_contact(name).ok().flatten().is_some()
+        {
+            continue;
+        }
+        let candidates = match find_contact_candidates(name).await {
+            Ok(candidates) => candidates,
+            Err(e) => {
+                warn!("Could not look up '{}' to check for several matches: {}", name, e);
+                continue;
+            }
+        };
+        if let Some((question, options)) = contact_question(name, &candidates) {
+            return Some(ask(topic, question, options));
+        }
+    }
+    None
+}
+
+/// Which person `name` stands for, when several match
+fn contact_question(
+    name: &str,
+    candidates: &[ContactCandidate],
+) -> Option<(String, Vec<ClarifyOption>)> {
+    if !is_ambiguous(candidates) {
+        return None;
+    }
+    let question = match candidates {
+        [first, second] => format!("Which {} — {} or {}?", name, first.name, second.name),
+        _ => format!("Which {} do you mean?", name),
+    };
+    let options = candidates
+        .iter()
+        .map(|candidate| ClarifyOption {
+            label: format!("{} <{}>", candidate.name, candidate.emails.join(", ")),
+            resolution: Resolution::Contact {
+                name: name.to_string(),
+                person: candidate.name.clone(),
+                emails: candidate.emails.clone(),
+            },
+        })
+        .collect();
+    Some((question, options))
+}
+
+fn day_option(date: NaiveDate) -> ClarifyOption {
+    ClarifyOption {
+        label: date.format("%A %-d %B").to_string(),
+        resolution: Resolution::Set(vec![format!("date={}", date.format("%Y-%m-%d"))]),
+    }
+}
+
+fn weekday_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+fn ordinal(day: u32) -> String {
+    let suffix = match (day % 10, day % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", day, suffix)
+}
+
+/// "next <weekday>" when the coming one is still this week, so either it or the one a week
+/// later may be meant
+fn ambiguous_weekday(input: &str, today: NaiveDate) -> Option<(Weekday, [NaiveDate; 2])> {
+    let words: Vec<String> = input
+        .split_whitespace()
+        .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase())
+        .collect();
+    words.windows(2).find_map(|pair| {
+        if pair[0] != "next" {
+            return None;
+        }
+        let day = pair[1].parse::<Weekday>().ok()?;
+        let coming = parse_date(&format!("next {}", pair[1]), today)?;
+        (coming.iso_week() == today.iso_week()).then(|| (day, [coming, coming + Duration::days(7)]))
+    })
+}
+
+/// Whether some phrase in the input names a day or a time
+fn mentions_when(input: &str, now: NaiveDateTime) -> bool {
+    let words: Vec<&str> = input.split_whitespace().collect();
+    (1..=MAX_PHRASE_WORDS).any(|size| {
+        words.windows(size).any(|phrase| {
+            let phrase = phrase.join(" ");
+            let phrase =
+                phrase.trim_matches(|c: char| c == ',' || c == '.' || c == '?' || c == '!');
+            parse_datetime(phrase, now).is_some()
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LUNCH: &str =
+        "ducktape calendar create \"Lunch with Sam\" 2025-05-06 12:00 13:00 \"Work\"";
+
+    /// Thursday 1 May 2025, 09:30
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 5, 1).unwrap().and_hms_opt(9, 30, 0).unwrap()
+    }
+
+    fn candidate(name: &str, email: &str, score: u32) -> ContactCandidate {
+        ContactCandidate { name: name.to_string(), emails: vec![email.to_string()], score }
+    }
+
+    #[test]
+    fn test_ambiguous_weekday() {
+        let today = now().date();
+        // Saturday 3 May is still this week
+        let (day, dates) = ambiguous_weekday("lunch next Saturday at noon", today).unwrap();
+        assert_eq!(day, Weekday::Sat);
+        assert_eq!(dates[0], NaiveDate::from_ymd_opt(2025, 5, 3).unwrap());
+        assert_eq!(dates[1], NaiveDate::from_ymd_opt(2025, 5, 10).unwrap());
+        // Tuesday 6 May is in next week already
+        assert!(ambiguous_weekday("lunch next tuesday", today).is_none());
+        assert!(ambiguous_weekday("lunch on saturday", today).is_none());
+    }
+
+    #[test]
+    fn test_mentions_when() {
+        assert!(mentions_when("lunch with Sam tomorrow", now()));
+        assert!(mentions_when("call the bank at 3pm.", now()));
+        assert!(mentions_when("dentist on April 22", now()));
+        assert!(!mentions_when("schedule a meeting with Siya about the budget", now()));
+    }
+
+    #[tokio::test]
+    async fn test_questions_about_the_day() {
+        let question =
+            check("lunch with Sam next saturday", LUNCH, now()).await.expect("a question");
+        assert_eq!(question.question, "Which Saturday — the 3rd or the 10th?");
+        assert_eq!(question.options[1].label, "Saturday 10 May");
+
+        let missing = check("lunch", LUNCH, now()).await.expect("a question");
+        assert_eq!(missing.question, "Which day should 'Lunch with Sam' be on?");
+        assert_eq!(missing.options.len(), 2);
+
+        assert!(check("lunch tomorrow", LUNCH, now()).await.is_none());
+        // Only events are checked
+        assert!(check("lunch", "ducktape todo create \"Lunch\"", now()).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_answers() -> Result<()> {
+        let question = check("lunch next saturday", LUNCH, now()).await.unwrap();
+        let today = now().date();
+        assert!(question.answer("2", today)?.contains(" 2025-05-10 12:00 13:00 "));
+        assert!(question.answer("saturday 3", today)?.contains(" 2025-05-03 "));
+        // Any day is accepted for the day of an event
+        assert!(question.answer("the 17th", today)?.contains(" 2025-05-17 "));
+        assert!(question.answer("3", today).is_err());
+        assert!(question.answer("sometime", today).is_err());
+
+        // The day was answered, so it is not asked again
+        let answered = question.answer("1", today)?;
+        assert!(question.follow_up(&answered, now()).await.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_contact_question() {
+        let candidates = vec![
+            candidate("Sam Lee", "sam@lee.com", 95),
+            candidate("Sam Ortiz", "sam@ortiz.com", 95),
+        ];
+        let (question, options) = contact_question("Sam", &candidates).unwrap();
+        assert_eq!(question, "Which Sam — Sam Lee or Sam Ortiz?");
+        assert_eq!(options[1].label, "Sam Ortiz <sam@ortiz.com>");
+
+        // An exact match that beats the rest needs no question
+        let exact =
+            vec![candidate("Sam", "sam@x.com", 100), candidate("Samuel Lee", "s@lee.com", 85)];
+        assert!(contact_question("Sam", &exact).is_none());
+    }
+
+    #[test]
+    fn test_is_cancel_and_prompt() {
+        assert!(is_cancel(" Never mind."));
+        assert!(!is_cancel("1"));
+        let clarification = Clarification {
+            input: "lunch".to_string(),
+            command: LUNCH.to_string(),
+            topic: Topic::Day,
+            settled: Vec::new(),
+            question: "Which day?".to_string(),
+            options: vec![day_option(now().date())],
+        };
+        assert_eq!(clarification.prompt(), "Which day?\n  1. Thursday 1 May");
+    }
+}
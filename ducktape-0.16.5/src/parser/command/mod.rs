@@ -93,7 +93,7 @@ pub fn parse_command(cmd: &str) -> Result<CommandArgs> {
     match shell_words::split(cmd) {
         Ok(args) => match parse_with_clap(args) {
             Ok(ParseResult::StructuredCommand(cmd_args)) => Ok(cmd_args),
-            Ok(ParseResult::CommandString(_)) => Err(anyhow!("Unexpected parse result type")),
+            Ok(_) => Err(anyhow!("Unexpected parse result type")),
             Err(e) => Err(e),
         },
         Err(e) => Err(anyhow!("Failed to parse command: {}", e)),
@@ -0,0 +1,61 @@
+//! API module for the DeepSeek parser
+//!
+//! Sends natural language input to the DeepSeek chat completions API and turns the answer
+//! into a validated ducktape command.
+
+use crate::parser::chat::{self, ChatProvider};
+use crate::parser::llm::LlmClient;
+use crate::parser::traits::ProgressSender;
+use anyhow::Result;
+use std::sync::Arc;
+
+/// API endpoint used when DEEPSEEK_API_BASE is not set
+pub const DEFAULT_API_BASE: &str = "https://api.deepseek.com/v1";
+/// Model used when DEEPSEEK_MODEL is not set
+pub const DEFAULT_MODEL: &str = "deepseek-chat";
+
+/// The DeepSeek chat completions API
+pub const DEEPSEEK: ChatProvider = ChatProvider {
+    name: "DeepSeek",
+    id: "deepseek",
+    api_base_var: "DEEPSEEK_API_BASE",
+    default_api_base: DEFAULT_API_BASE,
+    model_var: "DEEPSEEK_MODEL",
+    default_model: DEFAULT_MODEL,
+    key_var: "DEEPSEEK_API_KEY",
+    key_service: "deepseek",
+};
+
+/// Read DEEPSEEK_API_KEY from the environment or keychain without showing it in errors
+pub fn api_key() -> Result<String> {
+    DEEPSEEK.api_key()
+}
+
+/// Parse natural language input into a ducktape command
+///
+/// Cached translations are reused, from memory or the disk cache; otherwise the DeepSeek API
+/// is called, the command is extracted from the answer, enhanced with flags the model missed
+/// and validated.
+pub async fn parse_natural_language(input: &str) -> Result<String> {
+    parse_natural_language_with_progress(input, None).await
+}
+
+/// Parse natural language input, streaming the model's answer into `progress`
+pub async fn parse_natural_language_with_progress(
+    input: &str,
+    progress: Option<&ProgressSender>,
+) -> Result<String> {
+    parse_with_client(input, progress, None).await
+}
+
+/// Parse natural language input with `client`
+///
+/// Without a client, the one set with `llm::set_llm_client` or else the DeepSeek API is used.
+/// Answers from those clients are neither cached nor counted as usage.
+pub async fn parse_with_client(
+    input: &str,
+    progress: Option<&ProgressSender>,
+    client: Option<Arc<dyn LlmClient>>,
+) -> Result<String> {
+    chat::parse(&DEEPSEEK, input, progress, client).await
+}
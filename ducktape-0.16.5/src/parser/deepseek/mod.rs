@@ -3,42 +3,67 @@
 //! This module provides natural language processing capabilities
 //! using the DeepSeek API for parsing user input into structured commands.
 
-use crate::parser::traits::{ParseResult, Parser};
+use crate::parser::llm::LlmClient;
+use crate::parser::traits::{ParseResult, Parser, ProgressSender};
 use anyhow::Result;
 use async_trait::async_trait;
-use log::debug;
+use std::sync::Arc;
+use tracing::{debug, error};
+
+pub mod api;
+
+pub use api::parse_natural_language;
 
 /// Parser that uses DeepSeek models for natural language understanding
-pub struct DeepSeekParser;
+pub struct DeepSeekParser {
+    client: Option<Arc<dyn LlmClient>>,
+}
 
 impl DeepSeekParser {
     pub fn new() -> Result<Self> {
-        Ok(Self)
+        // Check for DEEPSEEK_API_KEY upfront to avoid misleading errors
+        api::DEEPSEEK.check_key()?;
+        Ok(Self { client: None })
+    }
+
+    /// A parser that sends its requests to `client`; no API key is needed
+    pub fn with_client(client: Arc<dyn LlmClient>) -> Self {
+        Self { client: Some(client) }
+    }
+
+    async fn parse(&self, input: &str, progress: Option<&ProgressSender>) -> Result<ParseResult> {
+        debug!("DeepSeek parser: Processing input: {}", input);
+
+        match api::parse_with_client(input, progress, self.client.clone()).await {
+            Ok(command) => {
+                let sanitized = crate::parser::utils::sanitize_nlp_command(&command);
+                debug!("DeepSeek parser: Generated command: {}", sanitized);
+                Ok(crate::parser::clarify::command_or_question(input, sanitized).await)
+            }
+            Err(e) => {
+                error!("DeepSeek parser error: {}", e);
+                Err(e)
+            }
+        }
     }
 }
 
 #[async_trait]
 impl Parser for DeepSeekParser {
     async fn parse_input(&self, input: &str) -> Result<ParseResult> {
-        // Note: Previously this used OpenAI parser as fallback, but we've removed that dependency
-        debug!("DeepSeek parser: Processing input: {}", input);
-
-        // Basic implementation that prefixes the input with "ducktape"
-        // This should be replaced with an actual implementation using the DeepSeek API
-        let command = if input.trim().starts_with("ducktape") {
-            input.trim().to_string()
-        } else {
-            format!("ducktape {}", input.trim())
-        };
-
-        // Use our new utility function to sanitize the command
-        let sanitized = crate::parser::utils::sanitize_nlp_command(&command);
-        debug!("DeepSeek parser: Generated command: {}", sanitized);
+        self.parse(input, None).await
+    }
 
-        Ok(ParseResult::CommandString(sanitized))
+    async fn parse_input_with_progress(
+        &self,
+        input: &str,
+        progress: ProgressSender,
+    ) -> Result<ParseResult> {
+        self.parse(input, Some(&progress)).await
     }
 
     fn new() -> Result<Self> {
-        Ok(Self)
+        api::DEEPSEEK.check_key()?;
+        Ok(Self { client: None })
     }
 }
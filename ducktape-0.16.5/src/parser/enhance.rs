@@ -0,0 +1,385 @@
+//! Command enhancement pipeline for LLM parsers
+//!
+//! Language models often leave out flags the user asked for or format arguments slightly
+//! wrong. Every LLM backend runs its generated command through the same passes here, so
+//! fixes apply to all providers alike.
+
+// Each pass takes the generated command and a `Context` with the user's original input and
+// returns the (possibly) changed command. Passes can be switched off by name with
+// `nlp.disabled_enhancements` in config.toml.
+
+use crate::config::Config;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::BTreeMap;
+use tracing::{debug, warn};
+
+static END_TIME_WITH_DATE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"calendar create\s+"([^"]+)"\s+(\d{4}-\d{2}-\d{2})\s+(\d{1,2}:\d{2})\s+(\d{4}-\d{2}-\d{2}\s+)(\d{1,2}:\d{2})"#,
+    )
+    .unwrap()
+});
+static INTERVAL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\bevery (\d+) (day|week|month|year)s?\b").unwrap());
+static EMAIL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"[a-zA-Z0-9_.+-]+@[a-zA-Z0-9-]+\.[a-zA-Z0-9-.]+").unwrap());
+static EMAIL_FLAG_WITHOUT_ADDRESS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"\s*--email\s+"([^@"]*)""#).unwrap());
+static INVITEE_NAMES: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"\b(?:[Ww]ith|[Ii]nvite|[Ii]nviting) ([A-Z][\w'-]*(?: [A-Z][\w'-]*)*(?:(?:, | and |, and )[A-Z][\w'-]*(?: [A-Z][\w'-]*)*)*)",
+    )
+    .unwrap()
+});
+static CALENDAR_ARGUMENT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(calendar create\s+"[^"]+"\s+\d{4}-\d{2}-\d{2}\s+\d{1,2}:\d{2}\s+\d{1,2}:\d{2}\s+)"([^"]+)""#,
+    )
+    .unwrap()
+});
+
+/// What a pass knows besides the generated command
+pub struct Context<'a> {
+    /// The user's original input
+    pub input: &'a str,
+    /// `calendar.aliases` from config.toml
+    pub calendar_aliases: &'a BTreeMap<String, String>,
+}
+
+/// One step of the enhancement pipeline
+pub struct Pass {
+    /// Name used in `nlp.disabled_enhancements`
+    pub name: &'static str,
+    pub description: &'static str,
+    apply: fn(&str, &Context) -> String,
+}
+
+impl Pass {
+    /// Run this pass on a generated command
+    pub fn apply(&self, command: &str, context: &Context) -> String {
+        (self.apply)(command, context)
+    }
+}
+
+/// All passes, in the order they run
+pub const PASSES: [Pass; 5] = [
+    Pass {
+        name: "end_time",
+        description: "Remove a date the model put in front of the end time",
+        apply: |command, context| fix_end_time(command, context.input),
+    },
+    Pass {
+        name: "calendar_alias",
+        description: "Replace a calendar alias with the calendar it stands for",
+        apply: |command, context| resolve_calendar_alias(command, context.calendar_aliases),
+    },
+    Pass {
+        name: "recurrence",
+        description: "Add --repeat and --interval for recurring events",
+        apply: |command, context| add_recurrence(command, context.input),
+    },
+    Pass {
+        name: "meeting",
+        description: "Add --zoom or --meeting when an online meeting is requested",
+        apply: |command, context| add_meeting(command, context.input),
+    },
+    Pass {
+        name: "invitees",
+        description: "Add --email and --contacts for people mentioned in the input",
+        apply: |command, context| add_invitees(command, context.input),
+    },
+];
+
+/// Run the generated command through every pass not listed in `disabled`
+pub fn enhance(command: &str, input: &str, disabled: &[String]) -> String {
+    enhance_with(command, &Context { input, calendar_aliases: &BTreeMap::new() }, disabled)
+}
+
+/// Run the pipeline with the calendar aliases and disabled passes of a configuration
+pub fn enhance_for(command: &str, input: &str, config: &Config) -> String {
+    let context = Context { input, calendar_aliases: &config.calendar.aliases };
+    enhance_with(command, &context, &config.nlp.disabled_enhancements)
+}
+
+/// Run the generated command through every pass not listed in `disabled`
+pub fn enhance_with(command: &str, context: &Context, disabled: &[String]) -> String {
+    for name in disabled {
+        if !PASSES.iter().any(|pass| pass.name.eq_ignore_ascii_case(name)) {
+            warn!("Unknown enhancement in nlp.disabled_enhancements: {}", name);
+        }
+    }
+
+    PASSES
+        .iter()
+        .filter(|pass| !disabled.iter().any(|name| pass.name.eq_ignore_ascii_case(name)))
+        .fold(command.trim().to_string(), |command, pass| {
+            let enhanced = pass.apply(&command, context);
+            if enhanced != command {
+                debug!("Enhancement '{}' changed command to: {}", pass.name, enhanced);
+            }
+            enhanced
+        })
+}
+
+/// Run the pipeline with the aliases and passes configured in config.toml
+pub fn enhance_command(command: &str, input: &str) -> String {
+    let config = Config::load().unwrap_or_default();
+    enhance_for(command, input, &config)
+}
+
+fn is_calendar_create(command: &str) -> bool {
+    command.contains("calendar create")
+}
+
+/// `... 2025-04-22 23:00 2025-04-22 00:00 ...` becomes `... 2025-04-22 23:00 00:00 ...`
+pub fn fix_end_time(command: &str, _input: &str) -> String {
+    if !is_calendar_create(command) {
+        return command.to_string();
+    }
+    END_TIME_WITH_DATE
+        .replace(command, r#"calendar create "$1" $2 $3 $5"#)
+        .into_owned()
+}
+
+/// `... 10:00 11:00 "work"` becomes `... 10:00 11:00 "jane.doe@company.com"` for an alias
+pub fn resolve_calendar_alias(command: &str, aliases: &BTreeMap<String, String>) -> String {
+    if !is_calendar_create(command) || aliases.is_empty() {
+        return command.to_string();
+    }
+    CALENDAR_ARGUMENT
+        .replace(command, |caps: &regex::Captures| {
+            let calendar = aliases
+                .iter()
+                .find(|(alias, _)| alias.eq_ignore_ascii_case(&caps[2]))
+                .map_or(&caps[2], |(_, calendar)| calendar.as_str());
+            format!("{}\"{}\"", &caps[1], calendar)
+        })
+        .into_owned()
+}
+
+/// Add `--repeat` (and `--interval`) when the input or command describes a recurring event
+pub fn add_recurrence(command: &str, input: &str) -> String {
+    if !is_calendar_create(command)
+        || command.contains("--repeat")
+        || command.contains("--recurring")
+    {
+        return command.to_string();
+    }
+
+    let text = format!("{} {}", input, command).to_lowercase();
+    if let Some(caps) = INTERVAL.captures(&text) {
+        let interval: u32 = caps[1].parse().unwrap_or(0);
+        let frequency = match &caps[2] {
+            "day" => "daily",
+            "week" => "weekly",
+            "month" => "monthly",
+            _ => "yearly",
+        };
+        return if (1..100).contains(&interval) && !command.contains("--interval") {
+            format!("{} --repeat {} --interval {}", command.trim(), frequency, interval)
+        } else {
+            format!("{} --repeat {}", command.trim(), frequency)
+        };
+    }
+
+    let frequency = if text.contains("every day") || text.contains("daily") {
+        "daily"
+    } else if text.contains("every week") || text.contains("weekly") {
+        "weekly"
+    } else if text.contains("every month") || text.contains("monthly") {
+        "monthly"
+    } else if text.contains("every year") || text.contains("yearly") || text.contains("annual") {
+        "yearly"
+    } else {
+        return command.to_string();
+    };
+    format!("{} --repeat {}", command.trim(), frequency)
+}
+
+/// Add `--zoom`, or `--meeting teams|meet`, when the input asks for an online meeting
+pub fn add_meeting(command: &str, input: &str) -> String {
+    if !is_calendar_create(command) || command.contains("--zoom") || command.contains("--meeting") {
+        return command.to_string();
+    }
+
+    match crate::meeting::detect_meeting_service(input) {
+        Some(crate::config::MeetingService::Zoom) => format!("{} --zoom", command.trim()),
+        Some(service) => format!("{} --meeting {}", command.trim(), service.as_str()),
+        None => command.to_string(),
+    }
+}
+
+/// Valid email addresses in the input
+pub fn extract_emails(input: &str) -> Vec<String> {
+    EMAIL
+        .find_iter(input)
+        .map(|m| m.as_str().trim_end_matches('.').to_string())
+        .filter(|email| crate::calendar::validate_email(email))
+        .collect()
+}
+
+/// Capitalized names after "with" or "invite", e.g. "with Jane Doe and Bob"
+pub fn extract_contact_names(input: &str) -> Vec<String> {
+    INVITEE_NAMES
+        .captures_iter(input)
+        .flat_map(|caps| {
+            caps[1]
+                .split(", and ")
+                .flat_map(|part| part.split(", "))
+                .flat_map(|part| part.split(" and "))
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Add `--email` and `--contacts` for invitees, dropping `--email` values that are names
+///
+/// Besides capitalized names, people known from `contacts sync` are found however they are typed.
+pub fn add_invitees(command: &str, input: &str) -> String {
+    if !is_calendar_create(command) {
+        return command.to_string();
+    }
+
+    let mut enhanced = EMAIL_FLAG_WITHOUT_ADDRESS.replace_all(command.trim(), "").into_owned();
+
+    let emails = extract_emails(input);
+    if !emails.is_empty() && !enhanced.contains("--email") {
+        enhanced = format!("{} --email \"{}\"", enhanced, emails.join(","));
+    }
+
+    let mut names = extract_contact_names(input);
+    if let Ok(entries) =
+        crate::state::StateManager::new().and_then(|m| m.load::<crate::state::AddressBookEntry>())
+    {
+        for name in crate::address_book::names_in(input, &entries) {
+            if !names.iter().any(|n| n.eq_ignore_ascii_case(&name)) {
+                names.push(name);
+            }
+        }
+    }
+    if !names.is_empty() && !enhanced.contains("--contacts") {
+        enhanced = format!("{} --contacts \"{}\"", enhanced, names.join(","));
+    }
+    enhanced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EVENT: &str = "ducktape calendar create \"Team Meeting\" 2024-03-15 10:00 11:00 \"Work\"";
+
+    #[test]
+    fn test_fix_end_time() {
+        let command =
+            "ducktape calendar create \"Team Meeting\" 2025-04-22 23:00 2025-04-22 00:00 \"Work\"";
+        assert_eq!(
+            fix_end_time(command, ""),
+            "ducktape calendar create \"Team Meeting\" 2025-04-22 23:00 00:00 \"Work\""
+        );
+        assert_eq!(fix_end_time(EVENT, ""), EVENT);
+    }
+
+    #[test]
+    fn test_resolve_calendar_alias() {
+        let aliases = BTreeMap::from([("work".to_string(), "jane.doe@company.com".to_string())]);
+        let command = "ducktape calendar create \"Standup\" 2025-04-22 09:00 09:15 \"Work\" --zoom";
+        assert_eq!(
+            resolve_calendar_alias(command, &aliases),
+            "ducktape calendar create \"Standup\" 2025-04-22 09:00 09:15 \"jane.doe@company.com\" --zoom"
+        );
+
+        let home = "ducktape calendar create \"Dinner\" 2025-04-22 19:00 20:00 \"Home\"";
+        assert_eq!(resolve_calendar_alias(home, &aliases), home);
+    }
+
+    #[test]
+    fn test_add_recurrence() {
+        assert_eq!(
+            add_recurrence(EVENT, "team meeting every week"),
+            format!("{} --repeat weekly", EVENT)
+        );
+        assert_eq!(
+            add_recurrence(EVENT, "sync every 2 weeks"),
+            format!("{} --repeat weekly --interval 2", EVENT)
+        );
+        // Phrases the model left in the command itself are picked up too
+        let leftover = format!("{} monthly", EVENT);
+        assert!(add_recurrence(&leftover, "review").ends_with("--repeat monthly"));
+
+        assert_eq!(add_recurrence(EVENT, "sync tomorrow"), EVENT);
+        let repeating = format!("{} --repeat daily", EVENT);
+        assert_eq!(add_recurrence(&repeating, "every week"), repeating);
+        let todo = "ducktape todo create \"Buy groceries\"";
+        assert_eq!(add_recurrence(todo, "every week"), todo);
+    }
+
+    #[test]
+    fn test_add_meeting() {
+        assert!(add_meeting(EVENT, "Schedule a zoom meeting with the team").ends_with("--zoom"));
+        assert!(add_meeting(EVENT, "Schedule a video call").ends_with("--zoom"));
+        assert!(add_meeting(EVENT, "sync on teams").ends_with("--meeting teams"));
+        assert_eq!(add_meeting(EVENT, "Schedule a regular meeting"), EVENT);
+
+        let zoom = format!("{} --zoom", EVENT);
+        assert_eq!(add_meeting(&zoom, "zoom meeting").matches("--zoom").count(), 1);
+    }
+
+    #[test]
+    fn test_extract_invitees() {
+        assert_eq!(
+            extract_contact_names("Schedule a meeting with John Smith tomorrow at 2pm"),
+            vec!["John Smith"]
+        );
+        assert_eq!(
+            extract_contact_names("create a zoom event called Deadlines and invite Shaun Stuart"),
+            vec!["Shaun Stuart"]
+        );
+        assert_eq!(
+            extract_contact_names("lunch with John Smith and Jane Doe, and Bob"),
+            vec!["John Smith", "Jane Doe", "Bob"]
+        );
+        assert!(extract_contact_names("Schedule a meeting with john.doe@example.com").is_empty());
+        assert!(extract_contact_names("Schedule a meeting with the team").is_empty());
+
+        assert_eq!(
+            extract_emails("Send invite to john@example.com, jane@example.com."),
+            vec!["john@example.com", "jane@example.com"]
+        );
+        assert!(extract_emails("Send to malicious\"@example.com").is_empty());
+    }
+
+    #[test]
+    fn test_add_invitees() {
+        assert_eq!(
+            add_invitees(EVENT, "Meet with John Smith, cc ops@example.com"),
+            format!("{} --email \"ops@example.com\" --contacts \"John Smith\"", EVENT)
+        );
+
+        // Names the model put in --email move to --contacts
+        let misplaced = format!("{} --email \"John Smith\"", EVENT);
+        assert_eq!(
+            add_invitees(&misplaced, "Meet with John Smith"),
+            format!("{} --contacts \"John Smith\"", EVENT)
+        );
+    }
+
+    #[test]
+    fn test_pipeline_and_disabled_passes() {
+        let input = "sync with Jane Doe and Bob every 2 weeks on zoom";
+        assert_eq!(
+            enhance(EVENT, input, &[]),
+            format!("{} --repeat weekly --interval 2 --zoom --contacts \"Jane Doe,Bob\"", EVENT)
+        );
+        assert_eq!(
+            enhance(EVENT, input, &["meeting".to_string(), "Invitees".to_string()]),
+            format!("{} --repeat weekly --interval 2", EVENT)
+        );
+
+        let todo = "ducktape todo create \"Call Joe\"";
+        assert_eq!(enhance(todo, "remind me to call Joe daily with Anna", &[]), todo);
+    }
+}
@@ -0,0 +1,320 @@
+//! Evaluation of a parser against phrases with known commands.
+//
+// `ducktape nlp eval <fixtures.toml>` runs every phrase of a fixtures file through the
+// configured parser (or `--provider`) and compares the command it gives with the expected one,
+// so that a prompt change or another model can be checked without trying phrases by hand.
+// Dates in braces in the expected command, such as `{tomorrow}` or `{next friday}`, are
+// resolved with `crate::dates` against the day the evaluation runs. Commands are compared by
+// their parts: each argument in its place, and each flag with its value, where comma
+// separated values may come in any order and case is ignored. A case passes when the parts
+// are the same; precision and recall tell how close the others came. Cached commands are not
+// used while evaluating.
+
+use crate::parser::{ParseResult, Parser};
+use anyhow::{Context, Result, anyhow};
+use chrono::NaiveDate;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+static PLACEHOLDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{([^{}]+)\}").unwrap());
+
+/// One phrase and the command it should become
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct EvalCase {
+    pub input: String,
+    pub expected: String,
+}
+
+/// A fixtures file
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Fixtures {
+    #[serde(rename = "case", default)]
+    pub cases: Vec<EvalCase>,
+}
+
+impl Fixtures {
+    pub fn from_toml(content: &str) -> Result<Self> {
+        let fixtures: Fixtures =
+            toml::from_str(content).map_err(|e| anyhow!("Invalid fixtures: {}", e))?;
+        if fixtures.cases.is_empty() {
+            return Err(anyhow!("The fixtures have no [[case]] entries"));
+        }
+        Ok(fixtures)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::from_toml(&content)
+    }
+}
+
+/// `expected` with the dates in braces resolved against `today`
+pub fn expand(expected: &str, today: NaiveDate) -> Result<String> {
+    let mut unknown = None;
+    let expanded =
+        PLACEHOLDER.replace_all(expected, |caps: &regex::Captures| match crate::dates::parse_date(
+            &caps[1], today,
+        ) {
+            Some(date) => date.format("%Y-%m-%d").to_string(),
+            None => {
+                unknown.get_or_insert_with(|| caps[1].to_string());
+                caps[0].to_string()
+            }
+        });
+    match unknown {
+        Some(expr) => Err(anyhow!("'{{{}}}' is not a date", expr)),
+        None => Ok(expanded.into_owned()),
+    }
+}
+
+fn normalize(value: &str) -> String {
+    let mut items: Vec<String> = value
+        .split(',')
+        .map(|item| item.trim().to_lowercase())
+        .filter(|i| !i.is_empty())
+        .collect();
+    items.sort();
+    items.join(",")
+}
+
+/// The parts of a command that are compared: `0=calendar`, `1=create`, `--email=a@b.com` ...
+pub fn command_parts(command: &str) -> Result<BTreeSet<String>> {
+    let words =
+        shell_words::split(command).map_err(|e| anyhow!("Failed to split '{}': {}", command, e))?;
+    let mut words = words.into_iter().skip_while(|word| word == "ducktape").peekable();
+    let mut parts = BTreeSet::new();
+    let mut position = 0;
+    while let Some(word) = words.next() {
+        match word.strip_prefix("--") {
+            Some(flag) => {
+                let (name, value) = match flag.split_once('=') {
+                    Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                    None => (flag.to_string(), words.next_if(|next| !next.starts_with("--"))),
+                };
+                parts.insert(match value {
+                    Some(value) => format!("--{}={}", name.to_lowercase(), normalize(&value)),
+                    None => format!("--{}", name.to_lowercase()),
+                });
+            }
+            None => {
+                parts.insert(format!("{}={}", position, word.trim().to_lowercase()));
+                position += 1;
+            }
+        }
+    }
+    Ok(parts)
+}
+
+/// How one case went
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaseResult {
+    pub input: String,
+    pub expected: String,
+    /// The command the parser gave, or why it gave none
+    pub got: Result<String, String>,
+    /// Parts of the expected command that were not generated
+    pub missing: Vec<String>,
+    /// Generated parts that were not expected
+    pub extra: Vec<String>,
+    pub latency: Duration,
+}
+
+impl CaseResult {
+    /// Compare the command the parser gave for `case` with the expected one
+    pub fn compare(
+        input: &str,
+        expected: &str,
+        got: Result<String, String>,
+        latency: Duration,
+    ) -> Result<Self> {
+        let wanted = command_parts(expected)?;
+        let generated = match &got {
+            Ok(command) => command_parts(command).unwrap_or_default(),
+            Err(_) => BTreeSet::new(),
+        };
+        Ok(Self {
+            input: input.to_string(),
+            expected: expected.to_string(),
+            missing: wanted.difference(&generated).cloned().collect(),
+            extra: generated.difference(&wanted).cloned().collect(),
+            got,
+            latency,
+        })
+    }
+
+    pub fn passed(&self) -> bool {
+        self.got.is_ok() && self.missing.is_empty() && self.extra.is_empty()
+    }
+
+    fn matched(&self) -> usize {
+        command_parts(&self.expected).map_or(0, |parts| parts.len()) - self.missing.len()
+    }
+
+    /// Share of the generated parts that were expected
+    pub fn precision(&self) -> f64 {
+        let generated = self.matched() + self.extra.len();
+        if generated == 0 { 0.0 } else { self.matched() as f64 / generated as f64 }
+    }
+
+    /// Share of the expected parts that were generated
+    pub fn recall(&self) -> f64 {
+        let expected = self.matched() + self.missing.len();
+        if expected == 0 { 1.0 } else { self.matched() as f64 / expected as f64 }
+    }
+}
+
+/// Every case of an evaluation
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EvalReport {
+    pub provider: String,
+    pub results: Vec<CaseResult>,
+}
+
+impl EvalReport {
+    /// Share of the cases that passed
+    pub fn accuracy(&self) -> f64 {
+        self.mean(|result| if result.passed() { 1.0 } else { 0.0 })
+    }
+
+    fn mean(&self, value: impl Fn(&CaseResult) -> f64) -> f64 {
+        if self.results.is_empty() {
+            return 0.0;
+        }
+        self.results.iter().map(value).sum::<f64>() / self.results.len() as f64
+    }
+
+    /// The failed cases (all cases with `verbose`) and a summary
+    pub fn format(&self, verbose: bool) -> String {
+        let mut lines = Vec::new();
+        for result in &self.results {
+            if result.passed() && !verbose {
+                continue;
+            }
+            let mark = if result.passed() { "✓" } else { "✗" };
+            lines.push(format!("{} {}", mark, result.input));
+            if result.passed() {
+                continue;
+            }
+            lines.push(format!("    expected: {}", result.expected));
+            match &result.got {
+                Ok(command) => lines.push(format!("    got:      {}", command)),
+                Err(e) => lines.push(format!("    error:    {}", e)),
+            }
+            if result.got.is_ok() {
+                if !result.missing.is_empty() {
+                    lines.push(format!("    missing:  {}", result.missing.join(" ")));
+                }
+                if !result.extra.is_empty() {
+                    lines.push(format!("    extra:    {}", result.extra.join(" ")));
+                }
+            }
+        }
+        let passed = self.results.iter().filter(|result| result.passed()).count();
+        let errors = self.results.iter().filter(|result| result.got.is_err()).count();
+        let latency = self.mean(|result| result.latency.as_secs_f64() * 1000.0);
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(format!(
+            "{}: {}/{} passed ({:.1}%), precision {:.1}%, recall {:.1}%, {} error(s), {:.0} ms per phrase",
+            self.provider,
+            passed,
+            self.results.len(),
+            self.accuracy() * 100.0,
+            self.mean(CaseResult::precision) * 100.0,
+            self.mean(CaseResult::recall) * 100.0,
+            errors,
+            latency
+        ));
+        lines.join("\n")
+    }
+}
+
+/// The command a parser result stands for; the parser's guess when it would ask first
+fn command_of(result: ParseResult) -> String {
+    let command = match result {
+        ParseResult::CommandString(command) => command,
+        ParseResult::StructuredCommand(args) => crate::parser::trace::command_line(&args),
+        ParseResult::NeedsClarification(clarification) => clarification.command,
+    };
+    crate::parser::sanitize_nlp_command(&command)
+}
+
+/// Run every case through `parser`, without cached commands
+pub async fn evaluate(
+    parser: &(dyn Parser + Send + Sync),
+    provider: &str,
+    fixtures: &Fixtures,
+    today: NaiveDate,
+) -> Result<EvalReport> {
+    let _uncached = crate::parser::cache::bypass();
+    let mut report = EvalReport { provider: provider.to_string(), results: Vec::new() };
+    for case in &fixtures.cases {
+        let expected = expand(&case.expected, today)
+            .with_context(|| format!("In the case for \"{}\"", case.input))?;
+        let started = Instant::now();
+        let got = parser.parse_input(&case.input).await.map(command_of).map_err(|e| e.to_string());
+        report
+            .results
+            .push(CaseResult::compare(&case.input, &expected, got, started.elapsed())?);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &str = include_str!("../../tests/fixtures/nlp_eval.toml");
+
+    #[test]
+    fn test_fixtures_and_dates() -> Result<()> {
+        let fixtures = Fixtures::from_toml(FIXTURES)?;
+        assert!(fixtures.cases.len() >= 5);
+        let today = NaiveDate::from_ymd_opt(2025, 5, 1).unwrap();
+        for case in &fixtures.cases {
+            command_parts(&expand(&case.expected, today)?)?;
+        }
+        assert_eq!(
+            expand("ducktape calendar create Lunch {tomorrow} 12:00 13:00", today)?,
+            "ducktape calendar create Lunch 2025-05-02 12:00 13:00"
+        );
+        assert!(expand("ducktape todo create {someday}", today).is_err());
+        assert!(Fixtures::from_toml("").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_compare() -> Result<()> {
+        let expected = "ducktape calendar create \"Lunch\" 2025-05-02 12:00 13:00 Work --contacts \"Sam,Jane\" --zoom";
+        let same = "ducktape calendar create lunch 2025-05-02 12:00 13:00 \"Work\" --zoom --contacts \"Jane, Sam\"";
+        let result = CaseResult::compare("lunch", expected, Ok(same.to_string()), Duration::ZERO)?;
+        assert!(result.passed());
+        assert_eq!((result.precision(), result.recall()), (1.0, 1.0));
+
+        let off =
+            "ducktape calendar create Lunch 2025-05-02 12:00 13:00 Home --zoom --location Cafe";
+        let result = CaseResult::compare("lunch", expected, Ok(off.to_string()), Duration::ZERO)?;
+        assert!(!result.passed());
+        assert_eq!(result.missing, ["--contacts=jane,sam", "6=work"]);
+        assert_eq!(result.extra, ["--location=cafe", "6=home"]);
+        assert_eq!(result.precision(), 7.0 / 9.0);
+        assert_eq!(result.recall(), 7.0 / 9.0);
+
+        let failed = CaseResult::compare("lunch", expected, Err("timeout".into()), Duration::ZERO)?;
+        assert_eq!((failed.precision(), failed.recall()), (0.0, 0.0));
+
+        let report = EvalReport { provider: "deepseek".to_string(), results: vec![result, failed] };
+        assert_eq!(report.accuracy(), 0.0);
+        let shown = report.format(false);
+        assert!(shown.contains("    missing:  --contacts=jane,sam 6=work"));
+        assert!(shown.contains("    error:    timeout"));
+        assert!(shown.ends_with("deepseek: 0/2 passed (0.0%), precision 38.9%, recall 38.9%, 1 error(s), 0 ms per phrase"));
+        Ok(())
+    }
+}
@@ -0,0 +1,434 @@
+//! Parser fallback chain for DuckTape
+//!
+//! Tries the parsers configured in `nlp.providers` in order, each with its own timeout, and
+//! answers with the first one that succeeds.
+
+// Failures are classified so that a provider which is down, rate limited or missing its API
+// key is skipped for a while instead of being tried (and waited for) on every input. The
+// process keeps counts of which provider answered, for `config show nlp.metrics` and logs.
+
+use crate::config::Config;
+use crate::parser::traits::{ParseProgress, ParseResult, Parser, ParserFactory, ProgressSender};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{Instrument, debug, debug_span, info, warn};
+
+/// How long a provider is skipped after a transient failure
+const RETRY_AFTER: Duration = Duration::from_secs(60);
+/// How long a provider is skipped after it rejected or lacked its credentials
+const AUTH_RETRY_AFTER: Duration = Duration::from_secs(15 * 60);
+
+/// Why a provider failed to answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ParserErrorKind {
+    /// No answer within the configured timeout
+    Timeout,
+    /// Missing, invalid or unauthorized API key
+    Auth,
+    /// HTTP 429 or a quota message
+    RateLimited,
+    /// The provider could not be reached
+    Network,
+    /// The provider answered with a server error
+    Unavailable,
+    /// Anything else, e.g. a response that could not be turned into a command
+    Other,
+}
+
+impl ParserErrorKind {
+    /// Whether the provider should be skipped for a while after this failure
+    pub fn retry_after(self) -> Option<Duration> {
+        match self {
+            ParserErrorKind::Auth => Some(AUTH_RETRY_AFTER),
+            ParserErrorKind::Timeout
+            | ParserErrorKind::RateLimited
+            | ParserErrorKind::Network
+            | ParserErrorKind::Unavailable => Some(RETRY_AFTER),
+            ParserErrorKind::Other => None,
+        }
+    }
+}
+
+impl fmt::Display for ParserErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            ParserErrorKind::Timeout => "timeout",
+            ParserErrorKind::Auth => "auth",
+            ParserErrorKind::RateLimited => "rate limited",
+            ParserErrorKind::Network => "network",
+            ParserErrorKind::Unavailable => "unavailable",
+            ParserErrorKind::Other => "error",
+        };
+        f.write_str(label)
+    }
+}
+
+/// Classify a provider error from the underlying HTTP error or its message
+pub fn classify_error(error: &anyhow::Error) -> ParserErrorKind {
+    for cause in error.chain() {
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            if e.is_timeout() {
+                return ParserErrorKind::Timeout;
+            }
+            if let Some(status) = e.status() {
+                return classify_status(status.as_u16());
+            }
+            if e.is_connect() || e.is_request() {
+                return ParserErrorKind::Network;
+            }
+        }
+    }
+
+    let message = format!("{:#}", error).to_lowercase();
+    let status = message
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|word| word.len() == 3)
+        .filter_map(|word| word.parse::<u16>().ok())
+        .find(|code| (400..600).contains(code));
+    if message.contains("api_key")
+        || message.contains("api key")
+        || message.contains("unauthorized")
+        || message.contains("forbidden")
+    {
+        ParserErrorKind::Auth
+    } else if message.contains("rate limit") || message.contains("quota") {
+        ParserErrorKind::RateLimited
+    } else if message.contains("timed out") || message.contains("timeout") {
+        ParserErrorKind::Timeout
+    } else if let Some(code) = status {
+        classify_status(code)
+    } else if message.contains("connect") || message.contains("dns") {
+        ParserErrorKind::Network
+    } else {
+        ParserErrorKind::Other
+    }
+}
+
+fn classify_status(code: u16) -> ParserErrorKind {
+    match code {
+        401 | 403 => ParserErrorKind::Auth,
+        408 => ParserErrorKind::Timeout,
+        429 => ParserErrorKind::RateLimited,
+        500..=599 => ParserErrorKind::Unavailable,
+        _ => ParserErrorKind::Other,
+    }
+}
+
+/// How often each provider answered or failed in this process
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProviderStats {
+    pub answered: u64,
+    pub failed: u64,
+    /// Times the provider was skipped because of an earlier failure
+    pub skipped: u64,
+    pub last_error: Option<ParserErrorKind>,
+    /// Total time spent on answered requests
+    pub answer_time: Duration,
+}
+
+static METRICS: Lazy<Mutex<BTreeMap<String, ProviderStats>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// Providers that are skipped until the given instant
+static UNHEALTHY: Lazy<Mutex<HashMap<String, Instant>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn update_stats(provider: &str, update: impl FnOnce(&mut ProviderStats)) {
+    if let Ok(mut metrics) = METRICS.lock() {
+        update(metrics.entry(provider.to_string()).or_default());
+    }
+}
+
+/// Snapshot of the per-provider counters
+pub fn provider_metrics() -> BTreeMap<String, ProviderStats> {
+    METRICS.lock().map(|metrics| metrics.clone()).unwrap_or_default()
+}
+
+/// Whether the provider may be tried now
+pub fn is_healthy(provider: &str) -> bool {
+    UNHEALTHY
+        .lock()
+        .map(|unhealthy| unhealthy.get(provider).is_none_or(|until| *until <= Instant::now()))
+        .unwrap_or(true)
+}
+
+fn mark_unhealthy(provider: &str, kind: ParserErrorKind) {
+    if let (Some(delay), Ok(mut unhealthy)) = (kind.retry_after(), UNHEALTHY.lock()) {
+        unhealthy.insert(provider.to_string(), Instant::now() + delay);
+    }
+}
+
+fn mark_healthy(provider: &str) {
+    if let Ok(mut unhealthy) = UNHEALTHY.lock() {
+        unhealthy.remove(provider);
+    }
+}
+
+/// One provider in the chain
+pub struct ProviderSlot {
+    name: String,
+    timeout: Duration,
+    /// The parser, or why it could not be created (e.g. a missing API key)
+    parser: Result<Box<dyn Parser + Send + Sync>>,
+}
+
+impl ProviderSlot {
+    pub fn new(
+        name: impl Into<String>,
+        timeout: Duration,
+        parser: Result<Box<dyn Parser + Send + Sync>>,
+    ) -> Self {
+        Self { name: name.into(), timeout, parser }
+    }
+}
+
+/// Parser that tries each provider in turn until one answers
+pub struct FallbackParser {
+    providers: Vec<ProviderSlot>,
+}
+
+impl FallbackParser {
+    pub fn new(providers: Vec<ProviderSlot>) -> Self {
+        Self { providers }
+    }
+
+    /// Build the chain from `nlp.providers` and the configured timeouts
+    pub fn from_config(config: &Config) -> Self {
+        let providers = config
+            .parser_chain()
+            .into_iter()
+            .map(|name| {
+                let timeout = Duration::from_secs(config.nlp.timeout_for(&name));
+                let parser = ParserFactory::create_parser_by_name(&name);
+                ProviderSlot::new(name, timeout, parser)
+            })
+            .collect();
+        Self::new(providers)
+    }
+
+    /// Names of the providers in the order they are tried
+    pub fn provider_names(&self) -> Vec<&str> {
+        self.providers.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    async fn parse(&self, input: &str, progress: Option<&ProgressSender>) -> Result<ParseResult> {
+        let mut failures = Vec::new();
+
+        for slot in &self.providers {
+            if !is_healthy(&slot.name) {
+                debug!("Skipping provider {}: failed recently", slot.name);
+                update_stats(&slot.name, |stats| stats.skipped += 1);
+                failures.push(format!("{}: skipped after a recent failure", slot.name));
+                crate::parser::trace::note_failure(format!("{} (failed recently)", slot.name));
+                continue;
+            }
+
+            let parser = match &slot.parser {
+                Ok(parser) => parser,
+                Err(e) => {
+                    let kind = classify_error(e);
+                    warn!("Provider {} is not available ({}): {}", slot.name, kind, e);
+                    update_stats(&slot.name, |stats| {
+                        stats.failed += 1;
+                        stats.last_error = Some(kind);
+                    });
+                    failures.push(format!("{}: {}", slot.name, e));
+                    crate::parser::trace::note_failure(format!(
+                        "{} (unavailable): {}",
+                        slot.name, e
+                    ));
+                    continue;
+                }
+            };
+
+            if let Some(progress) = progress {
+                let _ = progress.send(ParseProgress::Status(format!("Trying {}", slot.name)));
+            }
+            let started = Instant::now();
+            let parsing = match progress {
+                Some(progress) => parser.parse_input_with_progress(input, progress.clone()),
+                None => parser.parse_input(input),
+            };
+            let parsing = parsing.instrument(debug_span!("parse", provider = %slot.name));
+            let result = match tokio::time::timeout(slot.timeout, parsing).await {
+                Ok(result) => result.map_err(|e| (classify_error(&e), e)),
+                Err(_) => Err((
+                    ParserErrorKind::Timeout,
+                    anyhow!("no answer within {} seconds", slot.timeout.as_secs()),
+                )),
+            };
+
+            match result {
+                Ok(parsed) => {
+                    let elapsed = started.elapsed();
+                    info!("Provider {} answered in {} ms", slot.name, elapsed.as_millis());
+                    crate::metrics::parser_finished(&slot.name, "ok", elapsed);
+                    mark_healthy(&slot.name);
+                    update_stats(&slot.name, |stats| {
+                        stats.answered += 1;
+                        stats.answer_time += elapsed;
+                    });
+                    crate::parser::trace::note_provider(&slot.name);
+                    return Ok(parsed);
+                }
+                Err((kind, e)) => {
+                    warn!("Provider {} failed ({}), trying the next one: {}", slot.name, kind, e);
+                    crate::metrics::parser_finished(&slot.name, "error", started.elapsed());
+                    mark_unhealthy(&slot.name, kind);
+                    update_stats(&slot.name, |stats| {
+                        stats.failed += 1;
+                        stats.last_error = Some(kind);
+                    });
+                    failures.push(format!("{} ({}): {}", slot.name, kind, e));
+                    crate::parser::trace::note_failure(format!("{} ({}): {}", slot.name, kind, e));
+                }
+            }
+        }
+
+        if failures.is_empty() {
+            return Err(anyhow!("No parser providers configured in nlp.providers"));
+        }
+        Err(anyhow!("All parser providers failed: {}", failures.join("; ")))
+    }
+}
+
+#[async_trait]
+impl Parser for FallbackParser {
+    async fn parse_input(&self, input: &str) -> Result<ParseResult> {
+        self.parse(input, None).await
+    }
+
+    async fn parse_input_with_progress(
+        &self,
+        input: &str,
+        progress: ProgressSender,
+    ) -> Result<ParseResult> {
+        self.parse(input, Some(&progress)).await
+    }
+
+    fn new() -> Result<Self> {
+        Ok(Self::from_config(&Config::load()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Answers after `delay`, or fails with `error`
+    struct StubParser {
+        delay: Duration,
+        error: Option<&'static str>,
+    }
+
+    #[async_trait]
+    impl Parser for StubParser {
+        async fn parse_input(&self, _input: &str) -> Result<ParseResult> {
+            tokio::time::sleep(self.delay).await;
+            match self.error {
+                Some(message) => Err(anyhow!(message)),
+                None => Ok(ParseResult::CommandString("ducktape calendar list".to_string())),
+            }
+        }
+
+        fn new() -> Result<Self> {
+            Ok(Self { delay: Duration::ZERO, error: None })
+        }
+    }
+
+    fn slot(name: &str, delay_ms: u64, error: Option<&'static str>) -> ProviderSlot {
+        let parser = StubParser { delay: Duration::from_millis(delay_ms), error };
+        ProviderSlot::new(name, Duration::from_millis(50), Ok(Box::new(parser)))
+    }
+
+    #[test]
+    fn test_classify_error() {
+        assert_eq!(
+            classify_error(&anyhow!("XAI_API_KEY environment variable not set")),
+            ParserErrorKind::Auth
+        );
+        assert_eq!(
+            classify_error(&anyhow!("API request failed: 429 Too Many Requests")),
+            ParserErrorKind::RateLimited
+        );
+        assert_eq!(
+            classify_error(&anyhow!("API request failed: 503 Service Unavailable")),
+            ParserErrorKind::Unavailable
+        );
+        assert_eq!(classify_error(&anyhow!("operation timed out")), ParserErrorKind::Timeout);
+        assert_eq!(
+            classify_error(&anyhow!("Could not extract command from response")),
+            ParserErrorKind::Other
+        );
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_after_timeout_and_records_metrics() {
+        let parser =
+            FallbackParser::new(vec![slot("test-slow", 500, None), slot("test-fast", 0, None)]);
+        assert!(parser.parse_input("list my calendars").await.is_ok());
+
+        let metrics = provider_metrics();
+        assert_eq!(metrics["test-slow"].last_error, Some(ParserErrorKind::Timeout));
+        assert_eq!(metrics["test-fast"].answered, 1);
+
+        // The slow provider is skipped until its retry delay has passed
+        assert!(!is_healthy("test-slow"));
+        assert!(parser.parse_input("list my calendars").await.is_ok());
+        assert_eq!(provider_metrics()["test-slow"].skipped, 1);
+    }
+
+    #[tokio::test]
+    async fn test_unavailable_provider_is_skipped() {
+        let parser = FallbackParser::new(vec![
+            ProviderSlot::new(
+                "test-nokey",
+                Duration::from_secs(1),
+                Err(anyhow!("XAI_API_KEY environment variable not set")),
+            ),
+            slot("test-bad", 0, Some("Could not extract command from response")),
+            slot("test-local", 0, None),
+        ]);
+        assert_eq!(parser.provider_names(), vec!["test-nokey", "test-bad", "test-local"]);
+        assert!(parser.parse_input("list my calendars").await.is_ok());
+
+        let metrics = provider_metrics();
+        assert_eq!(metrics["test-nokey"].last_error, Some(ParserErrorKind::Auth));
+        // Ordinary errors do not take a provider out of the chain
+        assert!(is_healthy("test-bad"));
+        assert_eq!(metrics["test-local"].answered, 1);
+    }
+
+    #[tokio::test]
+    async fn test_reports_each_provider_tried() {
+        let parser = FallbackParser::new(vec![
+            slot("test-progress-bad", 0, Some("bad response")),
+            slot("test-progress-ok", 0, None),
+        ]);
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        assert!(parser.parse_input_with_progress("list my calendars", tx).await.is_ok());
+
+        let mut updates = Vec::new();
+        while let Ok(update) = rx.try_recv() {
+            updates.push(update);
+        }
+        assert_eq!(
+            updates,
+            vec![
+                ParseProgress::Status("Trying test-progress-bad".to_string()),
+                ParseProgress::Status("Trying test-progress-ok".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_all_providers_failing() {
+        let parser = FallbackParser::new(vec![slot("test-broken", 0, Some("bad response"))]);
+        let error = parser.parse_input("hello").await.unwrap_err();
+        assert!(error.to_string().contains("test-broken"));
+    }
+}
@@ -0,0 +1,48 @@
+//! API module for the Grok parser
+//!
+//! Sends natural language input to the X.AI chat completions API and turns the answer into a
+//! validated ducktape command.
+
+use crate::parser::chat::{self, ChatProvider};
+use crate::parser::llm::LlmClient;
+use crate::parser::traits::ProgressSender;
+use anyhow::Result;
+use std::sync::Arc;
+
+/// API endpoint used when XAI_API_BASE is not set
+pub const DEFAULT_API_BASE: &str = "https://api.x.ai/v1";
+/// Model used when XAI_MODEL is not set
+pub const DEFAULT_MODEL: &str = "grok-2-latest";
+
+/// The X.AI chat completions API
+pub const XAI: ChatProvider = ChatProvider {
+    name: "X.AI",
+    id: "grok",
+    api_base_var: "XAI_API_BASE",
+    default_api_base: DEFAULT_API_BASE,
+    model_var: "XAI_MODEL",
+    default_model: DEFAULT_MODEL,
+    key_var: "XAI_API_KEY",
+    key_service: "xai",
+};
+
+/// Read XAI_API_KEY from the environment or keychain without showing it in errors
+pub fn api_key() -> Result<String> {
+    XAI.api_key()
+}
+
+/// Parse natural language input into a ducktape command with the X.AI API
+pub async fn parse_natural_language(input: &str) -> Result<String> {
+    parse_with_client(input, None, None).await
+}
+
+/// Parse natural language input with `client`
+///
+/// Without a client, the one set with `llm::set_llm_client` or else the X.AI API is used.
+pub async fn parse_with_client(
+    input: &str,
+    progress: Option<&ProgressSender>,
+    client: Option<Arc<dyn LlmClient>>,
+) -> Result<String> {
+    chat::parse(&XAI, input, progress, client).await
+}
@@ -0,0 +1,100 @@
+//! Chat completion requests to language model providers.
+//
+// The Grok and DeepSeek parsers build an OpenAI style chat completion request and hand it to
+// an `LlmClient`. `HttpLlmClient` posts it to the provider's API and reads the answer,
+// streaming it into the progress channel when there is one. Code that uses ducktape as a
+// library can answer requests without a network or API key, either for one parser with
+// `GrokParser::with_client` or `DeepSeekParser::with_client`, or for the whole process with
+// `set_llm_client`, e.g. with `crate::mock::MockLlm`.
+
+use crate::parser::traits::ProgressSender;
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use reqwest::Client;
+use secrecy::{ExposeSecret, Secret};
+use serde_json::Value;
+use std::sync::{Arc, RwLock};
+use tracing::error;
+
+/// Sends chat completion requests to a language model
+#[async_trait]
+pub trait LlmClient: Send + Sync {
+    /// Send `body`, a chat completion request, and return the response
+    ///
+    /// With `progress` the request asks for a stream, which is read into the channel and
+    /// returned as one response.
+    async fn complete(&self, body: &Value, progress: Option<&ProgressSender>) -> Result<Value>;
+}
+
+static LLM_CLIENT: Lazy<RwLock<Option<Arc<dyn LlmClient>>>> = Lazy::new(|| RwLock::new(None));
+
+/// Send every parser's requests to `client` instead of the provider's API; `None` restores it
+pub fn set_llm_client(client: Option<Arc<dyn LlmClient>>) {
+    match LLM_CLIENT.write() {
+        Ok(mut current) => *current = client,
+        Err(poisoned) => *poisoned.into_inner() = client,
+    }
+}
+
+/// The client set with `set_llm_client`, if any
+pub fn llm_client() -> Option<Arc<dyn LlmClient>> {
+    LLM_CLIENT.read().ok().and_then(|client| client.clone())
+}
+
+/// Client for an OpenAI compatible chat completions API
+pub struct HttpLlmClient {
+    provider: String,
+    api_base: String,
+    api_key: Secret<String>,
+    client: Client,
+}
+
+impl HttpLlmClient {
+    /// `provider` names the service in errors, e.g. "DeepSeek"
+    pub fn new(provider: &str, api_base: &str, api_key: String) -> Result<Self> {
+        let client = Client::builder().timeout(std::time::Duration::from_secs(30)).build()?;
+        Ok(Self {
+            provider: provider.to_string(),
+            api_base: api_base.trim_end_matches('/').to_string(),
+            api_key: Secret::new(api_key),
+            client,
+        })
+    }
+}
+
+#[async_trait]
+impl LlmClient for HttpLlmClient {
+    async fn complete(&self, body: &Value, progress: Option<&ProgressSender>) -> Result<Value> {
+        let provider = &self.provider;
+        let response = self
+            .client
+            .post(format!("{}/chat/completions", self.api_base))
+            .bearer_auth(self.api_key.expose_secret())
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| {
+                error!("API request to {} failed: {}", provider, e);
+                anyhow!(e).context(format!("{} API request failed", provider))
+            })?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unable to read error response".to_string());
+            error!("{} API error ({}): {}", provider, status, error_text);
+            return Err(anyhow!("{} API error ({}): {}", provider, status, error_text));
+        }
+
+        match progress {
+            Some(progress) => crate::parser::stream::read_chat_stream(response, progress).await,
+            None => response
+                .json()
+                .await
+                .map_err(|e| anyhow!("Failed to parse {} API response: {}", provider, e)),
+        }
+    }
+}
@@ -3,15 +3,28 @@
 //! This module provides a unified interface for parsing various
 //! types of input including natural language and structured commands.
 
+pub mod bulk;
+pub mod cache;
+pub mod chat;
+pub mod clarify;
 pub mod command;
 pub mod deepseek;
+pub mod enhance;
+pub mod eval;
+pub mod fallback;
 pub mod grok;
+pub mod llm;
+pub mod multi;
+pub mod prompt;
+pub mod quick;
+pub mod stream;
 pub mod terminal;
+pub mod trace;
 pub mod traits;
 pub mod utils;
 
 // Re-export core types for easier access
-pub use self::traits::{ParseResult, Parser, ParserFactory};
+pub use self::traits::{ParseProgress, ParseResult, Parser, ParserFactory, ProgressSender};
 
 // Re-export important utility functions
 pub use self::command::parse_with_clap;
@@ -0,0 +1,111 @@
+//! Inputs that ask for more than one thing.
+//
+// "Create a meeting tomorrow at 10 and remind me to prepare slides tonight" asks for an event
+// and a reminder, but a language model answers with one command, which mixes the two. The
+// input is split where a new request starts: after "and", "then", "also", a comma, a semicolon
+// or a full stop, when the words that follow start a request ("remind me", "schedule",
+// "add" ...). "Lunch with Sam and Jane" stays one request, and so does "schedule a call and
+// add Jane to it", since the second part is about the first. Each part is parsed on its own,
+// and the commands run in order as one change, see `command_bus::dispatch_all`.
+
+use crate::parser::{ParseResult, Parser};
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static SEPARATOR: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\s*[,;]\s*(?:and\s+)?(?:then\s+|also\s+)?|\s*\.\s+(?:then\s+|also\s+)?|\s+and\s+(?:then\s+|also\s+)?|\s+(?:then|also)\s+").unwrap()
+});
+static REQUEST: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^(?:please\s+|can\s+you\s+|could\s+you\s+)?(?:remind|remember|schedule|create|add|book|set\s+up|make|put|note|jot|write|plan|block)\b").unwrap()
+});
+static REFERS_BACK: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\b(?:it|that|this|them|him|her)\b").unwrap());
+
+/// Whether `text` is inside double quotes at `at`
+fn quoted(text: &str, at: usize) -> bool {
+    text[..at].matches('"').count() % 2 == 1
+}
+
+/// The requests in `input`, in order; a single request gives `input` itself
+pub fn split_intents(input: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for separator in SEPARATOR.find_iter(input) {
+        let rest = &input[separator.end()..];
+        let starts_request = REQUEST.is_match(rest) && !REFERS_BACK.is_match(next_part(rest));
+        if starts_request && !quoted(input, separator.start()) && separator.start() > start {
+            parts.push(input[start..separator.start()].trim().to_string());
+            start = separator.end();
+        }
+    }
+    parts.push(input[start..].trim().trim_end_matches(['.', '!']).to_string());
+    parts.retain(|part| !part.is_empty());
+    if parts.len() < 2 {
+        return vec![input.to_string()];
+    }
+    parts
+}
+
+/// `rest` up to where another request could start
+fn next_part(rest: &str) -> &str {
+    SEPARATOR
+        .find_iter(rest)
+        .find(|separator| REQUEST.is_match(&rest[separator.end()..]))
+        .map_or(rest, |separator| &rest[..separator.start()])
+}
+
+/// Parse each of `parts` with `parser`, in order
+///
+/// Nothing runs while parsing, so a part the parser does not understand leaves everything as
+/// it was.
+pub async fn parse_intents(
+    parser: &(dyn Parser + Send + Sync),
+    parts: &[String],
+) -> Result<Vec<ParseResult>> {
+    let mut results = Vec::with_capacity(parts.len());
+    for part in parts {
+        let result = parser
+            .parse_input(part)
+            .await
+            .with_context(|| format!("Failed to understand \"{}\"", part))?;
+        results.push(result);
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_intents() {
+        assert_eq!(
+            split_intents(
+                "Create a meeting tomorrow at 10 and remind me to prepare slides tonight"
+            ),
+            ["Create a meeting tomorrow at 10", "remind me to prepare slides tonight"]
+        );
+        assert_eq!(
+            split_intents("schedule lunch with Sam on friday, then book a table. Also add a note"),
+            ["schedule lunch with Sam on friday", "book a table", "add a note"]
+        );
+        assert_eq!(
+            split_intents("remind me to buy milk; remind me to call mom at 6pm."),
+            ["remind me to buy milk", "remind me to call mom at 6pm"]
+        );
+    }
+
+    #[test]
+    fn test_single_request() {
+        for input in [
+            "lunch with Sam and Jane tomorrow at noon",
+            "remind me to buy milk and eggs",
+            "schedule a call with Bob and add Jane to it",
+            "create a todo \"plan and book the trip\"",
+            "and remind me",
+        ] {
+            assert_eq!(split_intents(input), [input]);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Instructions users add to the system prompt.
+//
+// Power users can steer the model beyond what the built-in prompt says, such as "All meetings
+// default to the Work calendar and 25 minutes". The instructions come from
+// `nlp.extra_instructions` and from ~/.ducktape/prompt.md, in that order, and are appended to
+// the system prompt of the language model parsers. They are also part of the response cache
+// key, so that answers given under other instructions are not reused.
+
+use crate::config::NlpConfig;
+use anyhow::{Result, anyhow};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use tracing::warn;
+
+/// File in ~/.ducktape whose text is appended to the system prompt
+pub const PROMPT_FILE: &str = "prompt.md";
+
+/// Where the prompt file is kept
+pub fn prompt_path() -> Result<PathBuf> {
+    let home_dir = dirs::home_dir().ok_or_else(|| anyhow!("Failed to get home directory"))?;
+    Ok(home_dir.join(".ducktape").join(PROMPT_FILE))
+}
+
+/// The text of the prompt file; empty or missing files give `None`
+fn prompt_file() -> Option<String> {
+    let path = prompt_path().ok()?;
+    match std::fs::read_to_string(&path) {
+        Ok(text) => Some(text),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => {
+            warn!("Failed to read {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+fn combine(configured: Option<&str>, file: Option<&str>) -> Option<String> {
+    let parts: Vec<&str> = [configured, file]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("\n\n"))
+}
+
+/// The user's instructions from config.toml and the prompt file, if there are any
+pub fn user_instructions(config: &NlpConfig) -> Option<String> {
+    combine(config.extra_instructions.as_deref(), prompt_file().as_deref())
+}
+
+/// `prompt` with the user's `instructions` after it
+pub fn with_instructions(prompt: String, instructions: Option<&str>) -> String {
+    match instructions {
+        Some(instructions) => format!(
+            "{prompt}\n\nAdditional instructions from the user. Follow them unless they change the command format above:\n{instructions}"
+        ),
+        None => prompt,
+    }
+}
+
+/// `model` as named in cache keys, which tells the user's instructions apart
+pub fn cache_model(model: &str, instructions: Option<&str>) -> String {
+    match instructions {
+        Some(instructions) => {
+            let digest = format!("{:x}", Sha256::digest(instructions.as_bytes()));
+            format!("{}+{}", model, &digest[..12])
+        }
+        None => model.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_instructions() {
+        assert_eq!(combine(None, Some("  \n")), None);
+        assert_eq!(
+            combine(Some("Meetings last 25 minutes"), Some("Use the Work calendar\n")).as_deref(),
+            Some("Meetings last 25 minutes\n\nUse the Work calendar")
+        );
+
+        let prompt = with_instructions("Reply with one command.".to_string(), Some("Be brief"));
+        assert!(prompt.starts_with("Reply with one command.\n\nAdditional instructions"));
+        assert!(prompt.ends_with(":\nBe brief"));
+        assert_eq!(with_instructions("Prompt".to_string(), None), "Prompt");
+
+        assert_eq!(cache_model("deepseek-chat", None), "deepseek-chat");
+        let tagged = cache_model("deepseek-chat", Some("Be brief"));
+        assert!(
+            tagged.starts_with("deepseek-chat+") && tagged.len() == "deepseek-chat+".len() + 12
+        );
+        assert_ne!(tagged, cache_model("deepseek-chat", Some("Be verbose")));
+    }
+}
@@ -0,0 +1,446 @@
+//! Quick add: an event from one compact phrase, without a language model.
+//
+// `ducktape quick "Lunch with Sara Fri 12:30-13:30 at Blue Bottle"` works like Google
+// Calendar's quick add. The phrase is split into words and each pass claims the words it
+// understands: first the time or time range (and a duration such as "for 2 hours"), then the
+// day, then the location after the last "at" or "@". The words left over are the title. Days
+// and times are read by `crate::dates`, so the same phrases work as in the rest of terminal
+// mode. An event without a time is an all-day event.
+
+use crate::calendar::EventConfig;
+use crate::dates::{parse_date, parse_time};
+use anyhow::{Result, anyhow};
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use std::fmt;
+
+/// Words introducing a time
+const TIME_WORDS: &[&str] = &["at", "from", "@"];
+/// Words between the start and end of a time range
+const RANGE_WORDS: &[&str] = &["-", "–", "to", "until", "till"];
+/// Words introducing a day
+const DATE_WORDS: &[&str] = &["on"];
+/// Words introducing a location
+const LOCATION_WORDS: &[&str] = &["at", "@"];
+/// Parts of the day that may follow a day, as in "tomorrow morning"
+const DAY_PARTS: &[&str] = &["morning", "afternoon", "evening", "night", "noon"];
+/// Words that never start or end a title
+const CONNECTORS: &[&str] = &["at", "on", "from", "for", "@", "-", "–"];
+
+const EXAMPLE: &str = "Lunch with Sara Fri 12:30-13:30 at Blue Bottle";
+
+/// An event read from a quick-add phrase
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuickEvent {
+    pub title: String,
+    pub date: NaiveDate,
+    /// Start time; `None` for an all-day event
+    pub start: Option<NaiveTime>,
+    pub end: Option<NaiveTime>,
+    pub location: Option<String>,
+}
+
+impl QuickEvent {
+    pub fn is_all_day(&self) -> bool {
+        self.start.is_none()
+    }
+
+    /// The event as an `EventConfig` for `calendar::create_event`
+    ///
+    /// An end time before the start time ends the event the next day.
+    pub fn to_event_config(&self) -> EventConfig {
+        let date = self.date.format("%Y-%m-%d").to_string();
+        let start = self
+            .start
+            .map_or_else(|| "00:00".to_string(), |t| t.format("%H:%M").to_string());
+        let mut config = EventConfig::new(&self.title, &date, &start);
+        config.all_day = self.is_all_day();
+        if let (Some(start), Some(end)) = (self.start, self.end) {
+            config.end_time = Some(end.format("%H:%M").to_string());
+            if end <= start {
+                config.end_date =
+                    Some((self.date + Duration::days(1)).format("%Y-%m-%d").to_string());
+            }
+        }
+        config.location = self.location.clone();
+        config
+    }
+}
+
+impl fmt::Display for QuickEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.title, self.date.format("%a %Y-%m-%d"))?;
+        match (self.start, self.end) {
+            (Some(start), Some(end)) => {
+                write!(f, " {}-{}", start.format("%H:%M"), end.format("%H:%M"))?
+            }
+            (Some(start), None) => write!(f, " {}", start.format("%H:%M"))?,
+            _ => write!(f, " (all day)")?,
+        }
+        if let Some(location) = &self.location {
+            write!(f, " at {}", location)?;
+        }
+        Ok(())
+    }
+}
+
+/// The phrase split into words, with the words claimed by a pass marked as used
+struct Words {
+    words: Vec<String>,
+    used: Vec<bool>,
+}
+
+impl Words {
+    fn new(text: &str) -> Self {
+        let mut words: Vec<String> = Vec::new();
+        for word in text.split_whitespace() {
+            // "3 pm" is one time
+            let meridiem = matches!(key(word).as_str(), "am" | "pm" | "a.m" | "p.m");
+            match words.last_mut() {
+                Some(last) if meridiem && is_bare_clock(last.as_str()) => {
+                    last.push_str(&key(word).replace('.', ""))
+                }
+                _ => words.push(word.to_string()),
+            }
+        }
+        let used = vec![false; words.len()];
+        Self { words, used }
+    }
+
+    fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    fn key(&self, i: usize) -> String {
+        key(&self.words[i])
+    }
+
+    fn is_free(&self, i: usize) -> bool {
+        i < self.len() && !self.used[i]
+    }
+
+    fn claim(&mut self, from: usize, len: usize) {
+        for used in &mut self.used[from..from + len] {
+            *used = true;
+        }
+    }
+
+    /// Claim the word before `i` too when it is one of `connectors`
+    fn claim_connector(&mut self, i: usize, connectors: &[&str]) {
+        if i > 0 && self.is_free(i - 1) && connectors.contains(&self.key(i - 1).as_str()) {
+            self.used[i - 1] = true;
+        }
+    }
+}
+
+/// A word lowercased without surrounding punctuation
+fn key(word: &str) -> String {
+    word.trim_matches(|c: char| matches!(c, ',' | '.' | '!' | '?' | ';' | '(' | ')' | '"'))
+        .to_lowercase()
+}
+
+/// An hour or hour and minutes without am/pm, such as "9" or "9:30"
+fn is_bare_clock(word: &str) -> bool {
+    let word = key(word);
+    !word.is_empty()
+        && word.len() <= 5
+        && word.chars().all(|c| c.is_ascii_digit() || c == ':')
+        && word.chars().next().is_some_and(|c| c.is_ascii_digit())
+}
+
+/// A time of day written with digits ("12:30", "3pm") or "noon"/"midnight"
+///
+/// Parts of the day such as "morning" only count after a day, so titles like "Morning run"
+/// keep their first word.
+fn clock(word: &str) -> Option<NaiveTime> {
+    let word = key(word);
+    let named = matches!(word.as_str(), "noon" | "midday" | "midnight");
+    if !named && !word.chars().any(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    parse_time(&word)
+}
+
+/// Start and end of a range such as "12:30-13:30", "3pm to 4:30pm" or "11-1pm"
+///
+/// A start without am/pm takes the one of the end if that keeps it before the end, so "9-10pm"
+/// is 21:00-22:00 and "11-1pm" is 11:00-13:00.
+fn time_range(start: &str, end: &str) -> Option<(NaiveTime, NaiveTime)> {
+    let end_time = clock(end)?;
+    if let Some(start_time) = clock(start) {
+        return Some((start_time, end_time));
+    }
+    if !is_bare_clock(start) {
+        return None;
+    }
+    let start = key(start);
+    let end = key(end);
+    let suffixes = if end.ends_with("am") {
+        ["am", "pm"]
+    } else if end.ends_with("pm") {
+        ["pm", "am"]
+    } else {
+        return None;
+    };
+    let start_time = suffixes
+        .iter()
+        .filter_map(|suffix| parse_time(&format!("{}{}", start, suffix)))
+        .find(|time| *time < end_time)?;
+    Some((start_time, end_time))
+}
+
+/// A duration such as "for 2 hours", "for 45 min", "for 90m" or "for an hour" starting at `i`,
+/// with the number of words it takes
+fn duration_at(words: &Words, i: usize) -> Option<(Duration, usize)> {
+    if words.key(i) != "for" || !words.is_free(i + 1) {
+        return None;
+    }
+    let amount = words.key(i + 1);
+    let digits: String = amount.chars().take_while(|c| c.is_ascii_digit()).collect();
+    let (n, unit, len) = if matches!(amount.as_str(), "a" | "an" | "one") {
+        (1, words.is_free(i + 2).then(|| words.key(i + 2))?, 3)
+    } else if digits.is_empty() {
+        return None;
+    } else if digits.len() < amount.len() {
+        (digits.parse::<i64>().ok()?, amount[digits.len()..].to_string(), 2)
+    } else {
+        (digits.parse::<i64>().ok()?, words.is_free(i + 2).then(|| words.key(i + 2))?, 3)
+    };
+    let duration = match unit.as_str() {
+        "m" | "min" | "mins" | "minute" | "minutes" => Duration::minutes(n),
+        "h" | "hr" | "hrs" | "hour" | "hours" => Duration::hours(n),
+        _ => return None,
+    };
+    Some((duration, len))
+}
+
+/// The first time or time range in the phrase
+fn take_time(words: &mut Words) -> Option<(NaiveTime, Option<NaiveTime>)> {
+    for i in 0..words.len() {
+        if !words.is_free(i) {
+            continue;
+        }
+        let word = words.words[i].clone();
+        if let Some((start, end)) = word.split_once(['-', '–']).and_then(|(a, b)| time_range(a, b))
+        {
+            words.claim(i, 1);
+            words.claim_connector(i, TIME_WORDS);
+            return Some((start, Some(end)));
+        }
+        if words.is_free(i + 2) && RANGE_WORDS.contains(&words.key(i + 1).as_str()) {
+            if let Some((start, end)) = time_range(&word, &words.words[i + 2].clone()) {
+                words.claim(i, 3);
+                words.claim_connector(i, TIME_WORDS);
+                return Some((start, Some(end)));
+            }
+        }
+        if let Some(start) = clock(&word) {
+            words.claim(i, 1);
+            words.claim_connector(i, TIME_WORDS);
+            return Some((start, None));
+        }
+    }
+    None
+}
+
+/// The first duration in the phrase
+fn take_duration(words: &mut Words) -> Option<Duration> {
+    (0..words.len()).find_map(|i| {
+        let (duration, len) = duration_at(words, i).filter(|_| words.is_free(i))?;
+        words.claim(i, len);
+        Some(duration)
+    })
+}
+
+/// The longest run of words that is a day, with a part of the day that follows it
+fn take_date(words: &mut Words, today: NaiveDate) -> Option<(NaiveDate, Option<NaiveTime>)> {
+    for len in (1..=4).rev() {
+        for i in 0..words.len().saturating_sub(len - 1) {
+            if !(i..i + len).all(|j| words.is_free(j)) {
+                continue;
+            }
+            let phrase = words.words[i..i + len].join(" ");
+            let Some(date) = parse_date(&phrase, today) else {
+                continue;
+            };
+            words.claim(i, len);
+            words.claim_connector(i, DATE_WORDS);
+
+            // "tonight", "tomorrow morning"
+            let mut time = (key(&phrase) == "tonight").then(|| parse_time("tonight")).flatten();
+            let next = i + len;
+            if words.is_free(next) && DAY_PARTS.contains(&words.key(next).as_str()) {
+                time = parse_time(&words.key(next));
+                words.claim(next, 1);
+            }
+            return Some((date, time));
+        }
+    }
+    None
+}
+
+/// The words after the last "at" or "@", up to the next claimed word
+fn take_location(words: &mut Words) -> Option<String> {
+    let at = (0..words.len()).rev().find(|&i| {
+        words.is_free(i) && words.is_free(i + 1) && LOCATION_WORDS.contains(&words.key(i).as_str())
+    })?;
+    let end = (at + 1..words.len()).find(|&j| !words.is_free(j)).unwrap_or(words.len());
+    let location = words.words[at + 1..end].join(" ");
+    words.claim(at, end - at);
+    let location = location.trim_end_matches([',', '.']).to_string();
+    (!location.is_empty()).then_some(location)
+}
+
+/// Read an event from a quick-add phrase, relative to `now`
+///
+/// Without a day, a time means its next occurrence. A phrase needs a title and a day or time.
+pub fn parse_quick(text: &str, now: NaiveDateTime) -> Result<QuickEvent> {
+    let mut words = Words::new(text);
+
+    let time = take_time(&mut words);
+    let duration = take_duration(&mut words);
+    let day = take_date(&mut words, now.date());
+    let location = take_location(&mut words);
+
+    let mut title: Vec<&str> = (0..words.len())
+        .filter(|&i| words.is_free(i))
+        .map(|i| words.words[i].as_str())
+        .collect();
+    while title.first().is_some_and(|w| CONNECTORS.contains(&key(w).as_str())) {
+        title.remove(0);
+    }
+    while title.last().is_some_and(|w| CONNECTORS.contains(&key(w).as_str())) {
+        title.pop();
+    }
+    let title = title.join(" ").trim_matches(['"', ',']).trim().to_string();
+    if title.is_empty() {
+        return Err(anyhow!("No title found in '{}'. Try \"{}\"", text.trim(), EXAMPLE));
+    }
+
+    let (start, end) = match time {
+        Some((start, end)) => (Some(start), end),
+        None => (day.and_then(|(_, time)| time), None),
+    };
+    let date = match (day, start) {
+        (Some((date, _)), _) => date,
+        (None, Some(start)) if start <= now.time() => now.date() + Duration::days(1),
+        (None, Some(_)) => now.date(),
+        (None, None) => {
+            return Err(anyhow!("No day or time found in '{}'. Try \"{}\"", text.trim(), EXAMPLE));
+        }
+    };
+    let end = end.or_else(|| Some(start? + duration?));
+
+    Ok(QuickEvent { title, date, start, end, location })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn time(h: u32, m: u32) -> Option<NaiveTime> {
+        NaiveTime::from_hms_opt(h, m, 0)
+    }
+
+    /// Friday 2025-03-14, 10:00
+    fn quick(text: &str) -> QuickEvent {
+        parse_quick(text, date(2025, 3, 14).and_hms_opt(10, 0, 0).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_title_day_range_and_location() {
+        let event = quick("Lunch with Sara Fri 12:30-13:30 at Blue Bottle");
+        assert_eq!(
+            event,
+            QuickEvent {
+                title: "Lunch with Sara".to_string(),
+                date: date(2025, 3, 21),
+                start: time(12, 30),
+                end: time(13, 30),
+                location: Some("Blue Bottle".to_string()),
+            }
+        );
+        assert_eq!(event.to_string(), "Lunch with Sara: Fri 2025-03-21 12:30-13:30 at Blue Bottle");
+    }
+
+    #[test]
+    fn test_parts_in_any_order() {
+        let event = quick("at Blue Bottle tomorrow lunch with Sara from 12:30 to 1:30pm");
+        assert_eq!(event.title, "lunch with Sara");
+        assert_eq!(event.date, date(2025, 3, 15));
+        assert_eq!((event.start, event.end), (time(12, 30), time(13, 30)));
+        assert_eq!(event.location.as_deref(), Some("Blue Bottle"));
+
+        let event = quick("Dentist at 3pm on April 22, 2026");
+        assert_eq!(event.title, "Dentist");
+        assert_eq!(event.date, date(2026, 4, 22));
+        assert_eq!((event.start, event.end), (time(15, 0), None));
+        assert_eq!(event.location, None);
+    }
+
+    #[test]
+    fn test_time_ranges() {
+        assert_eq!(quick("Gym 9-10am monday").start, time(9, 0));
+        let event = quick("Call 11-1pm next tuesday");
+        assert_eq!((event.start, event.end), (time(11, 0), time(13, 0)));
+        assert_eq!(event.date, date(2025, 3, 18));
+        let event = quick("Concert 8 pm - 11 pm saturday");
+        assert_eq!((event.start, event.end), (time(20, 0), time(23, 0)));
+        assert_eq!(event.title, "Concert");
+    }
+
+    #[test]
+    fn test_durations() {
+        let event = quick("Review for 45 min tomorrow 16:00");
+        assert_eq!((event.start, event.end), (time(16, 0), time(16, 45)));
+        assert_eq!(event.title, "Review");
+        assert_eq!(quick("Workshop 2pm for 2h friday").end, time(16, 0));
+        assert_eq!(quick("Sync at noon for an hour").end, time(13, 0));
+    }
+
+    #[test]
+    fn test_defaults() {
+        // A time alone is its next occurrence
+        assert_eq!(quick("Standup 9:30").date, date(2025, 3, 15));
+        assert_eq!(quick("Coffee 11:00").date, date(2025, 3, 14));
+
+        // A day alone is an all-day event
+        let event = quick("Offsite next friday");
+        assert!(event.is_all_day());
+        let config = event.to_event_config();
+        assert!(config.all_day);
+        assert_eq!(config.start_date, "2025-03-21");
+
+        assert_eq!(quick("Dinner tonight").start, time(20, 0));
+        let event = quick("Run tomorrow morning");
+        assert_eq!((event.title.as_str(), event.start), ("Run", time(9, 0)));
+        // Parts of the day only count after a day
+        assert_eq!(quick("Morning run 7am").title, "Morning run");
+    }
+
+    #[test]
+    fn test_event_config() {
+        let config = quick("Late shift 22:00-02:00 friday at Depot").to_event_config();
+        assert_eq!(config.title, "Late shift");
+        assert_eq!(config.start_date, "2025-03-21");
+        assert_eq!(config.start_time, "22:00");
+        assert_eq!(config.end_time.as_deref(), Some("02:00"));
+        assert_eq!(config.end_date.as_deref(), Some("2025-03-22"));
+        assert_eq!(config.location.as_deref(), Some("Depot"));
+        assert!(!config.all_day);
+    }
+
+    #[test]
+    fn test_errors() {
+        let now = date(2025, 3, 14).and_hms_opt(10, 0, 0).unwrap();
+        let error = parse_quick("Lunch with Sara", now).unwrap_err();
+        assert!(error.to_string().contains("No day or time"));
+        let error = parse_quick("tomorrow at 3pm", now).unwrap_err();
+        assert!(error.to_string().contains("No title"));
+        // Dates are not mistaken for time ranges
+        assert_eq!(quick("Release 2025-04-22").date, date(2025, 4, 22));
+    }
+}
@@ -0,0 +1,166 @@
+//! Streaming support for chat completion APIs
+//!
+//! DeepSeek and X.AI stream answers as server-sent events in the OpenAI format. The text is
+//! forwarded as `ParseProgress` while it arrives, and the complete answer is returned in the
+//! shape of a non-streamed response so the usual command extraction applies.
+
+use crate::parser::traits::{ParseProgress, ProgressSender};
+use anyhow::{Result, anyhow};
+use serde_json::{Value, json};
+use tracing::debug;
+
+/// Splits a server-sent event stream into the JSON payloads of its `data:` lines
+///
+/// Chunks may end in the middle of a line (or of a UTF-8 character), so only complete lines
+/// are decoded.
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    buffer: Vec<u8>,
+    done: bool,
+}
+
+impl SseDecoder {
+    /// Feed a chunk of the body and return the events completed by it
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<Value> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let Some(data) = line.trim().strip_prefix("data:") else {
+                continue;
+            };
+            let data = data.trim();
+            if data == "[DONE]" {
+                self.done = true;
+            } else if let Ok(event) = serde_json::from_str(data) {
+                events.push(event);
+            } else {
+                debug!("Ignoring malformed stream event: {}", data);
+            }
+        }
+        events
+    }
+
+    /// Whether the `[DONE]` marker has been seen
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+/// Answer and reasoning text accumulated from stream events
+#[derive(Debug, Default, PartialEq)]
+pub struct StreamedAnswer {
+    pub content: String,
+    pub reasoning: String,
+    /// Token counts, sent with the last event when `stream_options.include_usage` is set
+    pub usage: Option<Value>,
+}
+
+impl StreamedAnswer {
+    /// Add the deltas of one event and report what changed
+    pub fn apply(&mut self, event: &Value, progress: &ProgressSender) {
+        if event["usage"].is_object() {
+            self.usage = Some(event["usage"].clone());
+        }
+        let delta = &event["choices"][0]["delta"];
+        if let Some(reasoning) = delta["reasoning_content"].as_str().filter(|s| !s.is_empty()) {
+            self.reasoning.push_str(reasoning);
+            let _ = progress.send(ParseProgress::Thinking(self.reasoning.clone()));
+        }
+        if let Some(content) = delta["content"].as_str().filter(|s| !s.is_empty()) {
+            self.content.push_str(content);
+            let _ = progress.send(ParseProgress::Partial(self.content.clone()));
+        }
+    }
+
+    /// The answer in the shape of a non-streamed chat completion response
+    pub fn into_response(self) -> Value {
+        let mut response =
+            json!({ "choices": [{ "message": { "role": "assistant", "content": self.content } }] });
+        if let Some(usage) = self.usage {
+            response["usage"] = usage;
+        }
+        response
+    }
+}
+
+/// Read a streamed chat completion, reporting the text as it arrives
+pub async fn read_chat_stream(
+    mut response: reqwest::Response,
+    progress: &ProgressSender,
+) -> Result<Value> {
+    let mut decoder = SseDecoder::default();
+    let mut answer = StreamedAnswer::default();
+
+    while let Some(chunk) = response.chunk().await? {
+        for event in decoder.push(&chunk) {
+            if let Some(message) = event["error"]["message"].as_str() {
+                return Err(anyhow!("Streaming API error: {}", message));
+            }
+            answer.apply(&event, progress);
+        }
+        if decoder.is_done() {
+            break;
+        }
+    }
+
+    if answer.content.is_empty() {
+        return Err(anyhow!("Streamed response did not contain any content"));
+    }
+    Ok(answer.into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decoder_handles_split_chunks() {
+        let mut decoder = SseDecoder::default();
+        let first =
+            decoder.push(b": keep-alive\n\ndata: {\"choices\":[{\"delta\":{\"content\":\"duck");
+        assert!(first.is_empty());
+
+        let second = decoder.push(b"tape\"}}]}\n\ndata: [DONE]\n\n");
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0]["choices"][0]["delta"]["content"], "ducktape");
+        assert!(decoder.is_done());
+    }
+
+    #[test]
+    fn test_decoder_keeps_multibyte_characters_intact() {
+        let mut decoder = SseDecoder::default();
+        let line = "data: {\"choices\":[{\"delta\":{\"content\":\"Café\"}}]}\n".as_bytes();
+        let split = line.len() - 7;
+        assert!(decoder.push(&line[..split]).is_empty());
+        let events = decoder.push(&line[split..]);
+        assert_eq!(events[0]["choices"][0]["delta"]["content"], "Café");
+    }
+
+    #[test]
+    fn test_answer_reports_progress() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut answer = StreamedAnswer::default();
+
+        answer.apply(&json!({"choices":[{"delta":{"reasoning_content":"The user wants"}}]}), &tx);
+        answer.apply(&json!({"choices":[{"delta":{"content":"ducktape calendar"}}]}), &tx);
+        answer.apply(&json!({"choices":[{"delta":{"content":" list"}}]}), &tx);
+        answer.apply(&json!({"choices":[{"delta":{},"finish_reason":"stop"}]}), &tx);
+        answer
+            .apply(&json!({"choices":[],"usage":{"prompt_tokens":90,"completion_tokens":6}}), &tx);
+
+        assert_eq!(rx.try_recv().unwrap(), ParseProgress::Thinking("The user wants".to_string()));
+        assert_eq!(rx.try_recv().unwrap(), ParseProgress::Partial("ducktape calendar".to_string()));
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            ParseProgress::Partial("ducktape calendar list".to_string())
+        );
+        assert!(rx.try_recv().is_err());
+
+        let response = answer.into_response();
+        assert_eq!(response["choices"][0]["message"]["content"], "ducktape calendar list");
+        assert_eq!(response["usage"]["prompt_tokens"], 90);
+    }
+}
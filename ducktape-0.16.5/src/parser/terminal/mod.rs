@@ -7,7 +7,7 @@ use crate::parser::command;
 use crate::parser::traits::{ParseResult, Parser};
 use anyhow::Result;
 use async_trait::async_trait;
-use log::debug;
+use tracing::debug;
 
 /// Terminal Parser struct for handling direct terminal commands
 pub struct TerminalParser;
@@ -48,6 +48,7 @@ mod tests {
                 // Either result type is acceptable for terminal input
                 Ok(())
             }
+            ParseResult::NeedsClarification(_) => panic!("Terminal input is never questioned"),
         }
     }
 }
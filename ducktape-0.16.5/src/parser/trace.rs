@@ -0,0 +1,246 @@
+//! Execution trace of natural language input
+//!
+//! Records which parser answered an input, how long it took and which command came out, so
+//! terminal mode can show it after each input and `:last` can show it again in full.
+
+// The parsers note what happened while they run (the provider that answered, cache hits,
+// providers that failed before it) and `finish` turns those notes into a `ParseTrace` once
+// the command is known. Only one input is parsed at a time, so process-wide notes suffice.
+
+use crate::command_processor::CommandArgs;
+use anyhow::{Result, anyhow};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How much of the trace is shown after each input, set by `ui.trace`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceLevel {
+    /// Nothing; `:last` still shows the trace
+    Off,
+    /// One line with the provider, latency and command
+    #[default]
+    Brief,
+    /// Every detail, as `:last` shows it
+    Full,
+}
+
+impl TraceLevel {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "off" | "none" => Ok(TraceLevel::Off),
+            "brief" | "on" => Ok(TraceLevel::Brief),
+            "full" | "verbose" => Ok(TraceLevel::Full),
+            _ => Err(anyhow!("Invalid trace level: {}. Valid options are: off, brief, full", s)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TraceLevel::Off => "off",
+            TraceLevel::Brief => "brief",
+            TraceLevel::Full => "full",
+        }
+    }
+}
+
+/// What happened to one natural language input
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ParseTrace {
+    pub input: String,
+    /// The parser that answered, e.g. "grok", "terminal" or "local rules"
+    pub provider: String,
+    /// Whether the answer came from the response cache
+    pub cached: bool,
+    /// Providers tried before the one that answered, with why they failed
+    pub failures: Vec<String>,
+    pub latency: Duration,
+    /// The command as the parser produced it, before sanitizing
+    pub generated: Option<String>,
+    /// The command that was run
+    pub command: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Notes {
+    provider: Option<String>,
+    cached: bool,
+    failures: Vec<String>,
+}
+
+static NOTES: Lazy<Mutex<Notes>> = Lazy::new(|| Mutex::new(Notes::default()));
+static LAST: Lazy<Mutex<Option<ParseTrace>>> = Lazy::new(|| Mutex::new(None));
+
+fn note(update: impl FnOnce(&mut Notes)) {
+    if let Ok(mut notes) = NOTES.lock() {
+        update(&mut notes);
+    }
+}
+
+/// Forget the notes of the previous input
+pub fn begin() {
+    note(|notes| *notes = Notes::default());
+}
+
+/// Note the provider that answered
+pub fn note_provider(provider: &str) {
+    note(|notes| notes.provider = Some(provider.to_string()));
+}
+
+/// Note that the answer came from the response cache
+pub fn note_cache_hit() {
+    note(|notes| notes.cached = true);
+}
+
+/// Note a provider that did not answer
+pub fn note_failure(failure: impl Into<String>) {
+    let failure = failure.into();
+    note(|notes| notes.failures.push(failure));
+}
+
+/// Build the trace of `input` from the notes taken while parsing it
+///
+/// `provider` names the parser when none was noted, as with a single configured parser.
+pub fn finish(input: &str, provider: &str, latency: Duration) -> ParseTrace {
+    let notes = NOTES.lock().map(|mut notes| std::mem::take(&mut *notes)).unwrap_or_default();
+    ParseTrace {
+        input: input.to_string(),
+        provider: notes.provider.unwrap_or_else(|| provider.to_string()),
+        cached: notes.cached,
+        failures: notes.failures,
+        latency,
+        ..ParseTrace::default()
+    }
+}
+
+/// Keep `trace` for `:last`
+pub fn record(trace: ParseTrace) {
+    if let Ok(mut last) = LAST.lock() {
+        *last = Some(trace);
+    }
+}
+
+/// The trace of the last natural language input
+pub fn last() -> Option<ParseTrace> {
+    LAST.lock().ok().and_then(|last| last.clone())
+}
+
+/// A structured command written out as the command line that runs it
+pub fn command_line(args: &CommandArgs) -> String {
+    let mut words = vec!["ducktape".to_string(), args.command.clone()];
+    words.extend(args.args.iter().map(|arg| shell_words::quote(arg).into_owned()));
+    let mut flags: Vec<_> = args.flags.iter().collect();
+    flags.sort();
+    for (name, value) in flags {
+        words.push(format!("--{}", name));
+        if let Some(value) = value {
+            words.push(shell_words::quote(value).into_owned());
+        }
+    }
+    words.join(" ")
+}
+
+fn source(trace: &ParseTrace) -> String {
+    if trace.cached { format!("{} (cached)", trace.provider) } else { trace.provider.clone() }
+}
+
+/// The trace as shown at `level`; empty when nothing is shown
+pub fn format(trace: &ParseTrace, level: TraceLevel) -> String {
+    let outcome = match (&trace.command, &trace.error) {
+        (_, Some(error)) => format!("failed: {}", error),
+        (Some(command), None) => command.clone(),
+        (None, None) => "no command".to_string(),
+    };
+    match level {
+        TraceLevel::Off => String::new(),
+        TraceLevel::Brief => {
+            format!("↳ {} · {} ms · {}", source(trace), trace.latency.as_millis(), outcome)
+        }
+        TraceLevel::Full => {
+            let mut lines = vec![
+                format!("Input:     {}", trace.input),
+                format!("Parser:    {}", source(trace)),
+            ];
+            for failure in &trace.failures {
+                lines.push(format!("Skipped:   {}", failure));
+            }
+            lines.push(format!("Latency:   {} ms", trace.latency.as_millis()));
+            if let Some(generated) =
+                trace.generated.as_ref().filter(|g| Some(*g) != trace.command.as_ref())
+            {
+                lines.push(format!("Generated: {}", generated));
+            }
+            match (&trace.command, &trace.error) {
+                (_, Some(error)) => lines.push(format!("Error:     {}", error)),
+                (Some(command), None) => lines.push(format!("Command:   {}", command)),
+                (None, None) => {}
+            }
+            lines.join("\n")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn trace() -> ParseTrace {
+        ParseTrace {
+            input: "lunch with bob tomorrow".to_string(),
+            provider: "grok".to_string(),
+            latency: Duration::from_millis(842),
+            generated: Some("ducktape calendar create \"Lunch\" 2024-05-02 12:00".to_string()),
+            command: Some("ducktape calendar create Lunch 2024-05-02 12:00".to_string()),
+            ..ParseTrace::default()
+        }
+    }
+
+    #[test]
+    fn test_trace_level() {
+        assert_eq!(TraceLevel::from_str("Full").unwrap(), TraceLevel::Full);
+        assert_eq!(TraceLevel::from_str("off").unwrap().as_str(), "off");
+        assert_eq!(TraceLevel::default(), TraceLevel::Brief);
+        assert!(TraceLevel::from_str("loud").is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        let mut trace = trace();
+        assert_eq!(format(&trace, TraceLevel::Off), "");
+        assert_eq!(
+            format(&trace, TraceLevel::Brief),
+            "↳ grok · 842 ms · ducktape calendar create Lunch 2024-05-02 12:00"
+        );
+
+        trace.cached = true;
+        trace.failures = vec!["deepseek (timeout): no answer within 10 seconds".to_string()];
+        let full = format(&trace, TraceLevel::Full);
+        assert!(full.contains("Parser:    grok (cached)"));
+        assert!(full.contains("Skipped:   deepseek (timeout)"));
+        assert!(full.contains("Generated: ducktape calendar create \"Lunch\""));
+        assert!(full.ends_with("Command:   ducktape calendar create Lunch 2024-05-02 12:00"));
+
+        trace.error = Some("All parser providers failed".to_string());
+        assert!(format(&trace, TraceLevel::Brief).ends_with("failed: All parser providers failed"));
+    }
+
+    #[test]
+    fn test_command_line() {
+        let mut flags = HashMap::new();
+        flags.insert("calendar".to_string(), Some("Work Team".to_string()));
+        flags.insert("all-day".to_string(), None);
+        let args = CommandArgs::new(
+            "calendar".to_string(),
+            vec!["create".to_string(), "Team offsite".to_string()],
+            flags,
+        );
+        assert_eq!(
+            command_line(&args),
+            "ducktape calendar create 'Team offsite' --all-day --calendar 'Work Team'"
+        );
+    }
+}
@@ -5,9 +5,10 @@
 
 use crate::command_processor::CommandArgs;
 use crate::config::{Config, LLMProvider};
+use crate::parser::clarify::Clarification;
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
-use log::info;
+use tracing::info;
 
 /// Represents the result of parsing an input
 #[derive(Debug)]
@@ -16,8 +17,25 @@ pub enum ParseResult {
     CommandString(String),
     /// A fully structured command ready for execution
     StructuredCommand(CommandArgs),
+    /// A command that guessed at something the input left open; the question should be
+    /// answered before it runs, or `command` used as it is where nobody can be asked
+    NeedsClarification(Clarification),
 }
 
+/// Progress reported while a parser works on an input
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseProgress {
+    /// A parsing stage started, e.g. "Asking deepseek"
+    Status(String),
+    /// Reasoning the model has streamed so far, for models that think before answering
+    Thinking(String),
+    /// Answer text the model has streamed so far
+    Partial(String),
+}
+
+/// Receives progress updates; sending fails silently once the receiver is gone
+pub type ProgressSender = tokio::sync::mpsc::UnboundedSender<ParseProgress>;
+
 /// Core parser trait that all parser implementations must implement
 #[async_trait]
 pub trait Parser: Send + Sync {
@@ -32,6 +50,18 @@ pub trait Parser: Send + Sync {
     /// A Result containing either a ParseResult or an error
     async fn parse_input(&self, input: &str) -> Result<ParseResult>;
 
+    /// Parse an input, reporting progress while waiting for the answer
+    ///
+    /// Parsers that call a language model stream its answer into `progress`; the default
+    /// reports nothing and calls `parse_input`.
+    async fn parse_input_with_progress(
+        &self,
+        input: &str,
+        _progress: ProgressSender,
+    ) -> Result<ParseResult> {
+        self.parse_input(input).await
+    }
+
     /// Create a new instance of the parser
     fn new() -> Result<Self>
     where
@@ -45,10 +75,17 @@ impl ParserFactory {
     /// Create a parser based on the current configuration
     ///
     /// This will return an appropriate parser implementation based on the
-    /// LLMProvider specified in the config.
+    /// LLMProvider specified in the config. When `nlp.providers` is set, the providers are
+    /// tried in that order with their configured timeouts.
     pub fn create_parser() -> Result<Box<dyn Parser + Send + Sync>> {
         let config = Config::load()?;
 
+        if !config.nlp.providers.is_empty() {
+            let parser = crate::parser::fallback::FallbackParser::from_config(&config);
+            info!("Creating fallback parser: {}", parser.provider_names().join(" -> "));
+            return Ok(Box::new(parser));
+        }
+
         match config.language_model.provider {
             Some(LLMProvider::Grok) => {
                 info!("Creating Grok parser");
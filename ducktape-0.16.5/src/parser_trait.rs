@@ -10,4 +10,4 @@ pub use crate::parser::traits::{ParseResult, Parser, ParserFactory};
 pub use crate::command_processor::CommandArgs;
 pub use anyhow::Result;
 pub use async_trait::async_trait;
-pub use log::debug;
+pub use tracing::debug;
@@ -0,0 +1,157 @@
+//! macOS Automation (TCC) permission checks for the apps DuckTape controls.
+//
+// osascript reports a missing Automation permission as an Apple event error such as -1743,
+// which says little about how to fix it. `check` sends each app a harmless query and turns
+// the outcome into a `PermissionStatus` with guidance. `ducktape doctor` uses it so that
+// permission problems show up before a command fails on them.
+
+use crate::script_runner::{ScriptRunnerError, run_applescript_async};
+use anyhow::Result;
+use std::process::Output;
+
+/// An application DuckTape sends Apple events to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutomationApp {
+    Calendar,
+    Reminders,
+    Notes,
+    Contacts,
+}
+
+impl AutomationApp {
+    pub const ALL: [AutomationApp; 4] = [
+        AutomationApp::Calendar,
+        AutomationApp::Reminders,
+        AutomationApp::Notes,
+        AutomationApp::Contacts,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AutomationApp::Calendar => "Calendar",
+            AutomationApp::Reminders => "Reminders",
+            AutomationApp::Notes => "Notes",
+            AutomationApp::Contacts => "Contacts",
+        }
+    }
+
+    /// A read-only query that needs the Automation permission
+    fn probe_script(&self) -> &'static str {
+        match self {
+            AutomationApp::Calendar => "tell application \"Calendar\" to count calendars",
+            AutomationApp::Reminders => "tell application \"Reminders\" to count lists",
+            AutomationApp::Notes => "tell application \"Notes\" to count folders",
+            AutomationApp::Contacts => "tell application \"Contacts\" to count groups",
+        }
+    }
+
+    /// How to grant the permission
+    pub fn guidance(&self) -> String {
+        format!(
+            "Allow your terminal (or DuckTape) to control {app} in System Settings → Privacy & Security → Automation. \
+             If it is not listed, run `tccutil reset AppleEvents` and try again to get the prompt back.",
+            app = self.name()
+        )
+    }
+}
+
+/// Result of probing one app
+#[derive(Debug, Clone, PartialEq)]
+pub enum PermissionStatus {
+    Granted,
+    /// The user or device management refused the Apple events
+    Denied,
+    /// The check failed for another reason, e.g. the app could not be launched
+    Unknown(String),
+}
+
+/// Error codes osascript reports when Apple events to an app are not allowed
+const DENIAL_CODES: [&str; 2] = ["-1743", "-1744"];
+
+/// Classify the outcome of a probe script
+pub fn classify(result: &Result<Output>) -> PermissionStatus {
+    match result {
+        Ok(output) if output.status.success() => PermissionStatus::Granted,
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if DENIAL_CODES.iter().any(|code| stderr.contains(code))
+                || stderr.to_lowercase().contains("not authorized")
+            {
+                PermissionStatus::Denied
+            } else {
+                PermissionStatus::Unknown(stderr.trim().to_string())
+            }
+        }
+        Err(e) => match e.downcast_ref::<ScriptRunnerError>() {
+            Some(ScriptRunnerError::AutomationDenied { .. }) => PermissionStatus::Denied,
+            _ => PermissionStatus::Unknown(e.to_string()),
+        },
+    }
+}
+
+/// Probe whether DuckTape may control `app`
+///
+/// The first call may make macOS show its permission prompt.
+pub async fn check(app: AutomationApp) -> PermissionStatus {
+    classify(&run_applescript_async(app.probe_script()).await)
+}
+
+/// Probe every app
+pub async fn check_all() -> Vec<(AutomationApp, PermissionStatus)> {
+    let mut results = Vec::new();
+    for app in AutomationApp::ALL {
+        results.push((app, check(app).await));
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[cfg(unix)]
+    fn output(code: i32, stderr: &str) -> Output {
+        use std::os::unix::process::ExitStatusExt;
+        Output {
+            status: std::process::ExitStatus::from_raw(code << 8),
+            stdout: Vec::new(),
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify(&Ok(output(0, ""))), PermissionStatus::Granted);
+        assert_eq!(
+            classify(&Ok(output(
+                1,
+                "execution error: Not authorized to send Apple events to Notes. (-1743)"
+            ))),
+            PermissionStatus::Denied
+        );
+        assert_eq!(classify(&Ok(output(1, "execution error: (-1744)"))), PermissionStatus::Denied);
+        assert_eq!(
+            classify(&Ok(output(1, "Application isn't running. (-600)"))),
+            PermissionStatus::Unknown("Application isn't running. (-600)".to_string())
+        );
+
+        let denied = anyhow!(ScriptRunnerError::AutomationDenied { app: "Calendar".to_string() });
+        assert_eq!(classify(&Err(denied)), PermissionStatus::Denied);
+        assert!(matches!(
+            classify(&Err(anyhow!("osascript missing"))),
+            PermissionStatus::Unknown(_)
+        ));
+    }
+
+    #[test]
+    fn test_probe_scripts_name_the_app() {
+        for app in AutomationApp::ALL {
+            assert!(
+                app.probe_script().starts_with(&format!("tell application \"{}\"", app.name()))
+            );
+            assert!(app.guidance().contains(app.name()));
+        }
+    }
+}
@@ -0,0 +1,353 @@
+//! Running the last command made from natural language again: `ducktape redo` and
+//! `ducktape edit-last`.
+//
+// The terminal keeps the command each natural language input turned into in
+// ~/.ducktape/last_command.json, also across runs. When the model got one field wrong, for
+// example the time, `edit-last` changes just that field and runs the command again instead
+// of making the user retype the request. Edits name an argument or option of the command as
+// Clap knows it (`date`, `start_time`, `calendar`, `location`), so they work for any command.
+// Without edits the command is offered for editing on a prompt, prefilled.
+
+use crate::cli::Cli;
+use crate::command_processor::CommandArgs;
+use crate::state::{LastCommand, StateManager};
+use anyhow::{Result, anyhow};
+use chrono::{Local, NaiveTime};
+use clap::CommandFactory;
+use std::io::IsTerminal;
+
+/// Commands that are not remembered, so that `redo` cannot run itself
+const NOT_REMEMBERED: [&str; 2] = ["redo", "edit-last"];
+
+/// Remember the command `input` was turned into
+///
+/// Only DuckTape command lines are kept; `command` may start with `ducktape`.
+pub fn remember(input: &str, command: &str) -> Result<()> {
+    let command = command.trim();
+    let command = command.strip_prefix("ducktape ").unwrap_or(command).trim();
+    let name = command.split_whitespace().next().unwrap_or_default();
+    if name.is_empty() || name == "ducktape" || NOT_REMEMBERED.contains(&name) {
+        return Ok(());
+    }
+    StateManager::new()?.save(&[LastCommand {
+        input: input.to_string(),
+        command: command.to_string(),
+        created_at: Local::now().to_rfc3339(),
+    }])
+}
+
+/// The last command made from natural language
+pub fn last() -> Result<Option<LastCommand>> {
+    Ok(StateManager::new()?.load::<LastCommand>()?.pop())
+}
+
+/// Replace the remembered command with its edited version, so `redo` runs that
+pub fn replace(last: &LastCommand, command: &str) -> Result<()> {
+    StateManager::new()?.save(&[LastCommand {
+        command: command.to_string(),
+        created_at: Local::now().to_rfc3339(),
+        ..last.clone()
+    }])
+}
+
+/// The command line model, built so that every argument has its action
+fn cli() -> clap::Command {
+    let mut command = Cli::command();
+    command.build();
+    command
+}
+
+/// A command line taken apart into the words naming the command, its arguments and options
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandLine {
+    /// Command and subcommand, e.g. `calendar create`
+    path: Vec<String>,
+    /// Arguments by position
+    arguments: Vec<String>,
+    /// Options in the order given, by long name
+    options: Vec<(String, Option<String>)>,
+}
+
+impl CommandLine {
+    /// Take a command line apart; a leading `ducktape` is dropped
+    pub fn parse(line: &str) -> Result<Self> {
+        let mut words = shell_words::split(line)
+            .map_err(|e| anyhow!("Invalid command {}: {}", line, e))?
+            .into_iter()
+            .peekable();
+        if words.peek().is_some_and(|word| word == "ducktape") {
+            words.next();
+        }
+
+        let mut command = cli();
+        let mut path = Vec::new();
+        while let Some(subcommand) = words.peek().and_then(|w| command.find_subcommand(w)).cloned()
+        {
+            path.extend(words.next());
+            command = subcommand;
+        }
+        if path.is_empty() {
+            return Err(anyhow!("Not a DuckTape command: {}", line));
+        }
+
+        let mut arguments = Vec::new();
+        let mut options = Vec::new();
+        while let Some(word) = words.next() {
+            let Some(option) = word.strip_prefix("--").filter(|option| !option.is_empty()) else {
+                arguments.push(word);
+                continue;
+            };
+            let (name, value) = match option.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => {
+                    let takes_value =
+                        match command.get_arguments().find(|a| a.get_long() == Some(option)) {
+                            Some(arg) => arg.get_action().takes_values(),
+                            // Legacy options take a value unless another option follows
+                            None => words.peek().is_some_and(|next| !next.starts_with("--")),
+                        };
+                    (option.to_string(), if takes_value { words.next() } else { None })
+                }
+            };
+            options.push((name, value));
+        }
+        Ok(Self { path, arguments, options })
+    }
+
+    fn command(&self) -> clap::Command {
+        self.path
+            .iter()
+            .fold(cli(), |command, word| command.find_subcommand(word).cloned().unwrap_or(command))
+    }
+
+    /// The argument `id` names, e.g. `date`, if it was given
+    pub fn argument(&self, id: &str) -> Option<&str> {
+        let index = self.command().get_positionals().position(|a| a.get_id().as_str() == id)?;
+        self.arguments.get(index).map(String::as_str)
+    }
+
+    /// The value of an option by long name; `Some(None)` for a switch that is on
+    pub fn option(&self, long: &str) -> Option<Option<&str>> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(name, _)| name == long)
+            .map(|(_, value)| value.as_deref())
+    }
+
+    /// The command and subcommand, e.g. `calendar create`
+    pub fn path(&self) -> String {
+        self.path.join(" ")
+    }
+
+    /// Set an argument or option by name; an option without a value is a switch
+    pub fn set(&mut self, name: &str, value: Option<&str>) -> Result<()> {
+        let command = self.command();
+        let id = name.trim().trim_start_matches("--").replace('-', "_");
+        if let Some(index) = command.get_positionals().position(|a| a.get_id().as_str() == id) {
+            let value = value.ok_or_else(|| anyhow!("Give {} a value, e.g. {}=...", name, name))?;
+            match index.cmp(&self.arguments.len()) {
+                std::cmp::Ordering::Less => self.arguments[index] = value.to_string(),
+                std::cmp::Ordering::Equal => self.arguments.push(value.to_string()),
+                std::cmp::Ordering::Greater => {
+                    return Err(anyhow!("Set the arguments before {} first", name));
+                }
+            }
+            return Ok(());
+        }
+
+        let long = id.replace('_', "-");
+        let arg = command
+            .get_arguments()
+            .find(|a| a.get_long() == Some(long.as_str()))
+            .ok_or_else(|| {
+                anyhow!("'{}' has no argument or option {}", self.path.join(" "), name)
+            })?;
+        let value = if arg.get_action().takes_values() {
+            Some(value.ok_or_else(|| anyhow!("Give --{} a value, e.g. {}=...", long, long))?)
+        } else if value.is_some_and(|v| v.eq_ignore_ascii_case("false")) {
+            self.options.retain(|(name, _)| *name != long);
+            return Ok(());
+        } else {
+            None
+        };
+        self.options.retain(|(name, _)| *name != long);
+        self.options.push((long, value.map(str::to_string)));
+        Ok(())
+    }
+
+    /// Remove an option
+    pub fn unset(&mut self, name: &str) -> Result<()> {
+        let long = name.trim().trim_start_matches("--").replace('_', "-");
+        let before = self.options.len();
+        self.options.retain(|(name, _)| *name != long);
+        if self.options.len() == before {
+            return Err(anyhow!("The last command has no option --{}", long));
+        }
+        Ok(())
+    }
+
+    /// Move the event to `time`: `HH:MM` keeps its length, `HH:MM-HH:MM` sets both ends
+    pub fn set_time(&mut self, time: &str) -> Result<()> {
+        let (start, end) = match time.split_once('-') {
+            Some((start, end)) => (start.trim(), Some(end.trim().to_string())),
+            None => (time.trim(), None),
+        };
+        let end = end.or_else(|| self.shifted_end(start));
+        self.set("start_time", Some(start))?;
+        if let Some(end) = end {
+            self.set("end_time", Some(&end))?;
+        }
+        Ok(())
+    }
+
+    /// The end time that keeps the event's length when it starts at `start`
+    fn shifted_end(&self, start: &str) -> Option<String> {
+        let time = |t: &str| NaiveTime::parse_from_str(t.trim(), "%H:%M").ok();
+        let length = time(self.argument("end_time")?)? - time(self.argument("start_time")?)?;
+        Some((time(start)? + length).format("%H:%M").to_string())
+    }
+}
+
+impl std::fmt::Display for CommandLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut words: Vec<String> = self.path.iter().chain(&self.arguments).cloned().collect();
+        for (name, value) in &self.options {
+            words.push(format!("--{}", name));
+            words.extend(value.clone());
+        }
+        f.write_str(&shell_words::join(words))
+    }
+}
+
+/// Changes `edit-last` makes to the last command
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Edits {
+    /// `name=value` for arguments and options, or `name` to switch an option on
+    pub set: Vec<String>,
+    /// Options to remove
+    pub unset: Vec<String>,
+    pub date: Option<String>,
+    /// `HH:MM` or `HH:MM-HH:MM`
+    pub time: Option<String>,
+}
+
+impl Edits {
+    pub fn from_args(args: &CommandArgs) -> Self {
+        let flag = |name: &str| args.flags.get(name).cloned().flatten();
+        Self {
+            set: args.args.clone(),
+            unset: flag("unset")
+                .map(|names| names.split(',').map(|n| n.trim().to_string()).collect())
+                .unwrap_or_default(),
+            date: flag("date"),
+            time: flag("time"),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty() && self.unset.is_empty() && self.date.is_none() && self.time.is_none()
+    }
+
+    /// `command` with the edits made
+    pub fn apply(&self, command: &str) -> Result<String> {
+        let mut line = CommandLine::parse(command)?;
+        if let Some(date) = &self.date {
+            line.set("date", Some(date))?;
+        }
+        if let Some(time) = &self.time {
+            line.set_time(time)?;
+        }
+        for setting in &self.set {
+            match setting.split_once('=') {
+                Some((name, value)) => line.set(name, Some(value))?,
+                None => line.set(setting, None)?,
+            }
+        }
+        for name in &self.unset {
+            line.unset(name)?;
+        }
+        Ok(line.to_string())
+    }
+}
+
+/// Let the user edit `command` on a prompt prefilled with it
+///
+/// `None` when it was left unchanged or the edit was cancelled.
+pub fn edit_inline(command: &str) -> Result<Option<String>> {
+    if !std::io::stdin().is_terminal() {
+        return Err(anyhow!(
+            "edit-last needs a terminal to edit in; pass --set, --unset, --date or --time instead"
+        ));
+    }
+    let mut editor = rustyline::DefaultEditor::new()?;
+    match editor.readline_with_initial("ducktape ", (command, "")) {
+        Ok(line) => {
+            let line = line.trim();
+            let line = line.strip_prefix("ducktape ").unwrap_or(line).trim();
+            Ok(Some(line.to_string()).filter(|line| !line.is_empty() && line != command))
+        }
+        Err(
+            rustyline::error::ReadlineError::Interrupted | rustyline::error::ReadlineError::Eof,
+        ) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LUNCH: &str =
+        "calendar create 'Lunch with Bob' 2025-03-12 12:00 13:00 --location 'Blue Bottle' --zoom";
+
+    #[test]
+    fn test_parse_and_print() {
+        let line = CommandLine::parse(&format!("ducktape {}", LUNCH)).unwrap();
+        assert_eq!(line.path, vec!["calendar", "create"]);
+        assert_eq!(line.arguments, vec!["Lunch with Bob", "2025-03-12", "12:00", "13:00"]);
+        assert_eq!(
+            line.options,
+            vec![
+                ("location".to_string(), Some("Blue Bottle".to_string())),
+                ("zoom".to_string(), None)
+            ]
+        );
+        assert_eq!(line.to_string(), LUNCH);
+
+        assert!(CommandLine::parse("lunch tomorrow").is_err());
+    }
+
+    #[test]
+    fn test_edits() {
+        let edits = Edits {
+            set: vec!["location=Café Rue".to_string(), "calendar=Work".to_string()],
+            unset: vec!["zoom".to_string()],
+            date: Some("2025-03-14".to_string()),
+            time: Some("12:30".to_string()),
+        };
+        assert_eq!(
+            edits.apply(LUNCH).unwrap(),
+            "calendar create 'Lunch with Bob' 2025-03-14 12:30 13:30 Work --location 'Café Rue'"
+        );
+
+        let edits = Edits { time: Some("09:00-09:15".to_string()), ..Edits::default() };
+        assert!(edits.apply(LUNCH).unwrap().contains(" 09:00 09:15 "));
+    }
+
+    #[test]
+    fn test_invalid_edits() {
+        let unknown = Edits { set: vec!["colour=red".to_string()], ..Edits::default() };
+        assert!(unknown.apply(LUNCH).is_err());
+
+        let missing_value = Edits { set: vec!["location".to_string()], ..Edits::default() };
+        assert!(missing_value.apply(LUNCH).is_err());
+
+        let not_set = Edits { unset: vec!["url".to_string()], ..Edits::default() };
+        assert!(not_set.apply(LUNCH).is_err());
+
+        // Switches are turned off with `false`
+        let off = Edits { set: vec!["zoom=false".to_string()], ..Edits::default() };
+        assert!(!off.apply(LUNCH).unwrap().contains("--zoom"));
+    }
+}
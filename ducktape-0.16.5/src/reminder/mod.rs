@@ -2,6 +2,7 @@
 //
 // This module provides integration with macOS Reminders.app.
 
+use crate::webhooks::{Action, ItemKind, notify, reminder_data};
 use anyhow::Result;
 
 mod reminder_applescript;
@@ -16,8 +17,16 @@ pub use reminder_validation::*;
 
 /// Create a new reminder
 pub async fn create_reminder(config: ReminderConfig<'_>) -> Result<()> {
+    let hook = reminder_data(
+        config.title,
+        &config.lists,
+        config.due_date.or(config.reminder_time),
+        config.notes.as_deref(),
+    );
     // Implementation relies on the reminder_applescript module
-    reminder_applescript::create_single_reminder(config).await
+    reminder_applescript::create_single_reminder(config).await?;
+    notify(ItemKind::Reminder, Action::Created, hook);
+    Ok(())
 }
 
 /// List available reminder lists
@@ -25,12 +34,15 @@ pub async fn list_reminder_lists() -> Result<Vec<String>> {
     reminder_applescript::get_reminder_lists().await
 }
 
-/// Get reminders from a specific list or all lists
-pub async fn get_reminders(list_name: Option<&str>) -> Result<Vec<ReminderItem>> {
-    reminder_applescript::fetch_reminders(list_name).await
+/// Get the reminders matching `query`, e.g. those due today sorted by priority
+pub async fn get_reminders(query: &ReminderQuery) -> Result<Vec<ReminderItem>> {
+    reminder_applescript::fetch_reminders(query).await
 }
 
 /// Delete a reminder by title and list
 pub async fn delete_reminder(title: &str, list_name: Option<&str>) -> Result<()> {
-    reminder_applescript::delete_reminder(title, list_name).await
+    reminder_applescript::delete_reminder(title, list_name).await?;
+    let lists: Vec<&str> = list_name.into_iter().collect();
+    notify(ItemKind::Reminder, Action::Deleted, reminder_data(title, &lists, None, None));
+    Ok(())
 }
@@ -2,11 +2,12 @@
 //
 // This module provides functions to interact with the Reminders application via AppleScript
 
-use super::reminder_types::{ReminderConfig, ReminderError, ReminderItem};
-use super::reminder_util::escape_applescript_string;
+use super::reminder_types::{ReminderConfig, ReminderError, ReminderItem, ReminderQuery};
+use crate::applescript::{Properties, Script, Template};
+use crate::script_runner::run_applescript_async;
+use crate::todo::{TodoPriority, apply_recurrence, format_reminder_properties};
 use anyhow::{Result, anyhow};
-use log::{debug, error, info};
-use std::process::Command;
+use tracing::{debug, error, info};
 
 /// Ensure Reminders.app is running
 pub async fn ensure_reminders_running() -> Result<()> {
@@ -18,7 +19,7 @@ pub async fn ensure_reminders_running() -> Result<()> {
         return "OK"
     end tell"#;
 
-    let output = Command::new("osascript").arg("-e").arg(check_script).output()?;
+    let output = run_applescript_async(check_script).await?;
 
     if output.status.success() { Ok(()) } else { Err(anyhow!(ReminderError::NotRunning)) }
 }
@@ -32,61 +33,46 @@ pub async fn create_single_reminder(config: ReminderConfig<'_>) -> Result<()> {
 
     let target_lists = if config.lists.is_empty() { vec!["Reminders"] } else { config.lists };
 
-    // Format reminder time to AppleScript-friendly string if provided
-    let reminder_prop = if let Some(time_str) = config.reminder_time {
-        // Parse input in format "YYYY-MM-DD HH:MM"
-        match chrono::NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M") {
-            Ok(naive_dt) => {
-                // Format as "date \"MM/dd/yyyy hh:mm:ss AM/PM\""
-                let formatted = naive_dt.format("%m/%d/%Y %I:%M:%S %p").to_string();
-                debug!("Setting reminder time to: {}", formatted);
-                // Use the correct AppleScript syntax for setting a due date on reminders
-                format!(", due date:date \"{}\"", formatted)
-            }
-            Err(e) => {
-                error!("Invalid reminder time format: {}", e);
-                String::new()
-            }
-        }
-    } else {
-        String::new()
-    };
+    // Title, notes, dates and priority as AppleScript properties
+    let properties = format_reminder_properties(
+        Properties::new()
+            .text("name", config.title)
+            .text("body", config.notes.as_deref().unwrap_or("")),
+        config.due_date,
+        config.reminder_time,
+        config.priority,
+    )?
+    .render();
+    debug!("Reminder properties: {}", properties);
 
     let mut success_count = 0;
+    let mut recurrence_errors = Vec::new();
     for list in target_lists {
-        // Escape all inputs to prevent command injection
-        let escaped_list = escape_applescript_string(list);
-        let escaped_title = escape_applescript_string(config.title);
-        let escaped_notes = escape_applescript_string(config.notes.as_deref().unwrap_or(""));
-
-        // Updated AppleScript with escaped inputs
-        let script = format!(
+        let script = Template::new(
             r#"tell application "Reminders"
     try
-        set remLists to lists whose name is "{}"
+        set remLists to lists whose name is ${list}
         if (count of remLists) > 0 then
             set targetList to item 1 of remLists
         else
-            set targetList to make new list with properties {{name:"{}"}}
+            set targetList to make new list with properties {name:${list}}
         end if
         
-        set newReminder to make new reminder in targetList with properties {{name:"{}", body:"{}"{} }}
+        set newReminder to make new reminder in targetList with properties ${properties}
         
-        return "Success: Reminder created"
+        return "Success: " & (id of newReminder)
     on error errMsg
         return "Error: " & errMsg
     end try
 end tell"#,
-            escaped_list, // search for list
-            escaped_list, // create list if not found
-            escaped_title,
-            escaped_notes,
-            reminder_prop
-        );
+        )
+        .text("list", list)
+        .script("properties", properties.clone())
+        .render()?;
 
         debug!("Executing AppleScript: {}", script);
 
-        let output = Command::new("osascript").arg("-e").arg(&script).output()?;
+        let output = run_applescript_async(script).await?;
         let result = String::from_utf8_lossy(&output.stdout);
         let error_output = String::from_utf8_lossy(&output.stderr);
 
@@ -97,13 +83,27 @@ end tell"#,
         if result.contains("Success") {
             info!("Reminder created in list {}: {}", list, config.title);
             success_count += 1;
+
+            if let Some(recurrence) = &config.recurrence {
+                let reminder_id = result.trim().trim_start_matches("Success: ");
+                if let Err(e) = apply_recurrence(reminder_id, recurrence).await {
+                    error!("Failed to set recurrence in list {}: {}", list, e);
+                    recurrence_errors.push(e.to_string());
+                }
+            }
         } else {
             let error_msg = result.replace("Error: ", "");
             error!("Failed to create reminder in list {}: {}", list, error_msg);
         }
     }
 
-    if success_count > 0 {
+    if success_count > 0 && !recurrence_errors.is_empty() {
+        Err(anyhow!(ReminderError::General(format!(
+            "Reminder '{}' was created but could not be set to repeat: {}",
+            config.title,
+            recurrence_errors.join("; ")
+        ))))
+    } else if success_count > 0 {
         Ok(())
     } else {
         Err(anyhow!(ReminderError::General(format!(
@@ -126,7 +126,7 @@ pub async fn get_reminder_lists() -> Result<Vec<String>> {
     return listNames
 end tell"#;
 
-    let output = Command::new("osascript").arg("-e").arg(script).output()?;
+    let output = run_applescript_async(script).await?;
     if !output.status.success() {
         return Err(anyhow!(ReminderError::ScriptError(
             String::from_utf8_lossy(&output.stderr).to_string()
@@ -145,113 +145,111 @@ end tell"#;
     Ok(lists)
 }
 
-/// Fetch reminders from a specific list or all lists
-pub async fn fetch_reminders(list_name: Option<&str>) -> Result<Vec<ReminderItem>> {
+/// Fetch the reminders matching `query`
+///
+/// The filters are applied by Reminders.app, so only matching reminders are read.
+pub async fn fetch_reminders(query: &ReminderQuery) -> Result<Vec<ReminderItem>> {
+    query.validate()?;
+
     // Make sure Reminders app is running
     ensure_reminders_running().await?;
 
-    let script = if let Some(list) = list_name {
-        let escaped_list = escape_applescript_string(list);
-        format!(
-            r#"tell application "Reminders"
-    set reminderList to {}
-    set listObj to first list whose name is "{0}"
-    repeat with r in (reminders in listObj)
-        set reminderTitle to name of r
-        set reminderCompleted to completed of r
-        set reminderBody to ""
-        try
-            set reminderBody to body of r
-        end try
-        set reminderItem to {{title:reminderTitle, notes:reminderBody, completed:reminderCompleted, listName:"{0}"}}
-        copy reminderItem to end of reminderList
-    end repeat
-    return reminderList
-end tell"#,
-            escaped_list
-        )
-    } else {
-        r#"tell application "Reminders"
-    set reminderList to {}
-    repeat with l in lists
-        set listName to name of l
-        repeat with r in (reminders in l)
-            set reminderTitle to name of r
-            set reminderCompleted to completed of r
-            set reminderBody to ""
-            try
-                set reminderBody to body of r
-            end try
-            set reminderItem to {title:reminderTitle, notes:reminderBody, completed:reminderCompleted, listName:listName}
-            copy reminderItem to end of reminderList
-        end repeat
-    end repeat
-    return reminderList
-end tell"#
-        .to_string()
-    };
-
-    let output = Command::new("osascript").arg("-e").arg(script).output()?;
+    let output = run_applescript_async(fetch_reminders_script(query)?).await?;
     if !output.status.success() {
         return Err(anyhow!(ReminderError::ScriptError(
             String::from_utf8_lossy(&output.stderr).to_string()
         )));
     }
 
-    // Parse the AppleScript output into ReminderItem structs
-    let reminders_output = String::from_utf8_lossy(&output.stdout);
-    let mut reminders = Vec::new();
-
-    // Very basic parsing of AppleScript output - in a real implementation
-    // you might want a more robust parser
-    for line in reminders_output.lines() {
-        if line.contains("title:") && line.contains("notes:") {
-            // Extract data from line
-            let mut title = String::new();
-            let mut notes = None;
-            let mut completed = false;
-            let mut list_name = String::new();
-
-            // Super simple parsing - you might want to improve this
-            if let Some(title_start) = line.find("title:") {
-                if let Some(title_end) = line[title_start..].find(",") {
-                    title = line[title_start + 6..title_start + title_end].trim().to_string();
-                }
-            }
-
-            if let Some(notes_start) = line.find("notes:") {
-                if let Some(notes_end) = line[notes_start..].find(",") {
-                    let note_text =
-                        line[notes_start + 6..notes_start + notes_end].trim().to_string();
-                    if !note_text.is_empty() {
-                        notes = Some(note_text);
-                    }
-                }
-            }
+    let mut reminders = parse_reminder_lines(&String::from_utf8_lossy(&output.stdout));
+    query.sort(&mut reminders);
+    debug!("Fetched {} reminders", reminders.len());
+    Ok(reminders)
+}
 
-            if let Some(completed_start) = line.find("completed:") {
-                if let Some(completed_str) = line[completed_start + 10..].split_whitespace().next()
-                {
-                    completed = completed_str == "true";
-                }
-            }
+/// Script listing the reminders matching `query`, one per line
+///
+/// Fields are separated by tabs: title, completed, notes, list, remind date, due date,
+/// priority and completion date. Line breaks and tabs inside the notes are flattened.
+pub fn fetch_reminders_script(query: &ReminderQuery) -> Result<Script> {
+    let list_selector = match &query.list {
+        Some(list) => Template::new("(lists whose name is ${list})").text("list", list).render()?,
+        None => Script::from("lists"),
+    };
+    let reminders = match query.whose_clause() {
+        Some(clause) => Template::new("(reminders in l whose ${clause})")
+            .script("clause", clause)
+            .render()?,
+        None => Script::from("(reminders in l)"),
+    };
+    Template::new(
+        r#"on flatten(v)
+    if v is missing value then return ""
+    set AppleScript's text item delimiters to {return, linefeed, tab}
+    set parts to text items of (v as text)
+    set AppleScript's text item delimiters to " "
+    set flat to parts as text
+    set AppleScript's text item delimiters to ""
+    return flat
+end flatten
+
+on pad(n)
+    return text -2 thru -1 of ("0" & (n as integer))
+end pad
+
+on fmtDate(d)
+    if d is missing value then return ""
+    return (year of d as text) & "-" & my pad(month of d as integer) & "-" & my pad(day of d) & " " & my pad(hours of d) & ":" & my pad(minutes of d)
+end fmtDate
+
+set nowDate to current date
+set todayStart to nowDate - (time of nowDate)
+set tomorrowStart to todayStart + 1 * days
+set completedSince to nowDate - ${days} * days
+
+tell application "Reminders"
+    set output to ""
+    repeat with l in ${lists}
+        set listName to name of l
+        repeat with r in ${reminders}
+            set output to output & my flatten(name of r) & tab & (completed of r as text) & tab & my flatten(body of r) & tab & listName & tab & my fmtDate(remind me date of r) & tab & my fmtDate(due date of r) & tab & (priority of r as text) & tab & my fmtDate(completion date of r) & linefeed
+        end repeat
+    end repeat
+    return output
+end tell"#,
+    )
+    .number("days", query.completed_within_days.unwrap_or(0))
+    .script("lists", list_selector)
+    .script("reminders", reminders)
+    .render()
+}
 
-            if let Some(list_start) = line.find("listName:") {
-                list_name = line[list_start + 9..].trim().to_string();
+/// Parse the tab separated reminder lines produced by `fetch_reminders_script`
+pub fn parse_reminder_lines(output: &str) -> Vec<ReminderItem> {
+    let optional =
+        |value: Option<&str>| value.map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() < 4 || fields[0].trim().is_empty() {
+                return None;
             }
-
-            reminders.push(ReminderItem {
-                title,
-                notes,
-                lists: vec![list_name],
-                reminder_time: None, // We don't parse this in this example
-                completed,
-            });
-        }
-    }
-
-    debug!("Fetched {} reminders", reminders.len());
-    Ok(reminders)
+            Some(ReminderItem {
+                title: fields[0].trim().to_string(),
+                lists: vec![fields[3].trim().to_string()],
+                reminder_time: optional(fields.get(4).copied()),
+                notes: optional(fields.get(2).copied()),
+                completed: fields[1].trim() == "true",
+                due_date: optional(fields.get(5).copied()),
+                priority: fields
+                    .get(6)
+                    .and_then(|p| p.trim().parse().ok())
+                    .and_then(TodoPriority::from_applescript),
+                completion_date: optional(fields.get(7).copied()),
+            })
+        })
+        .collect()
 }
 
 /// Delete a reminder by title and list
@@ -259,15 +257,12 @@ pub async fn delete_reminder(title: &str, list_name: Option<&str>) -> Result<()>
     // Make sure Reminders app is running
     ensure_reminders_running().await?;
 
-    let escaped_title = escape_applescript_string(title);
-
     let script = if let Some(list) = list_name {
-        let escaped_list = escape_applescript_string(list);
-        format!(
+        Template::new(
             r#"tell application "Reminders"
     try
-        set targetList to first list whose name is "{}"
-        set itemsToDelete to (reminders in targetList whose name is "{}")
+        set targetList to first list whose name is ${list}
+        set itemsToDelete to (reminders in targetList whose name is ${title})
         if (count of itemsToDelete) > 0 then
             delete itemsToDelete
             return "Success: Reminder deleted"
@@ -278,15 +273,17 @@ pub async fn delete_reminder(title: &str, list_name: Option<&str>) -> Result<()>
         return "Error: " & errMsg
     end try
 end tell"#,
-            escaped_list, escaped_title
         )
+        .text("list", list)
+        .text("title", title)
+        .render()?
     } else {
-        format!(
+        Template::new(
             r#"tell application "Reminders"
     try
         set foundReminder to false
         repeat with l in lists
-            set itemsToDelete to (reminders in l whose name is "{}")
+            set itemsToDelete to (reminders in l whose name is ${title})
             if (count of itemsToDelete) > 0 then
                 delete itemsToDelete
                 set foundReminder to true
@@ -302,11 +299,12 @@ end tell"#,
         return "Error: " & errMsg
     end try
 end tell"#,
-            escaped_title
         )
+        .text("title", title)
+        .render()?
     };
 
-    let output = Command::new("osascript").arg("-e").arg(&script).output()?;
+    let output = run_applescript_async(script).await?;
     let result = String::from_utf8_lossy(&output.stdout);
 
     if result.contains("Success") {
@@ -1,6 +1,12 @@
 //! Type definitions for reminder functionality
 
+use crate::applescript::Script;
+use crate::calendar::RecurrencePattern;
+use crate::todo::TodoPriority;
+use anyhow::{Result, anyhow};
+use chrono::{Duration, NaiveDateTime};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use thiserror::Error;
 
 /// Configuration for creating a new reminder
@@ -14,12 +20,26 @@ pub struct ReminderConfig<'a> {
     pub reminder_time: Option<&'a str>,
     /// Optional notes/details for the reminder
     pub notes: Option<String>,
+    /// Optional due date, "YYYY-MM-DD" for an all-day due date or "YYYY-MM-DD HH:MM"
+    pub due_date: Option<&'a str>,
+    /// Optional priority
+    pub priority: Option<TodoPriority>,
+    /// Optional recurrence (frequency, interval, end date or count)
+    pub recurrence: Option<RecurrencePattern>,
 }
 
 impl<'a> ReminderConfig<'a> {
     /// Create a new ReminderConfig with just a title
     pub fn new(title: &'a str) -> Self {
-        Self { title, lists: Vec::new(), reminder_time: None, notes: None }
+        Self {
+            title,
+            lists: Vec::new(),
+            reminder_time: None,
+            notes: None,
+            due_date: None,
+            priority: None,
+            recurrence: None,
+        }
     }
 
     /// Set the lists for this reminder
@@ -54,6 +74,168 @@ pub struct ReminderItem {
     pub notes: Option<String>,
     /// Whether the reminder is completed
     pub completed: bool,
+    /// Optional due date, "YYYY-MM-DD HH:MM"
+    pub due_date: Option<String>,
+    /// Optional priority
+    pub priority: Option<TodoPriority>,
+    /// When the reminder was completed, "YYYY-MM-DD HH:MM"
+    pub completion_date: Option<String>,
+}
+
+impl ReminderItem {
+    /// Whether the reminder is not completed and was due before `now`
+    pub fn is_overdue(&self, now: NaiveDateTime) -> bool {
+        !self.completed && parse_time(self.due_date.as_deref()).is_some_and(|d| d < now)
+    }
+}
+
+fn parse_time(value: Option<&str>) -> Option<NaiveDateTime> {
+    value.and_then(|v| NaiveDateTime::parse_from_str(v, "%Y-%m-%d %H:%M").ok())
+}
+
+/// Which due dates a reminder query keeps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DueFilter {
+    /// Not completed and due today
+    Today,
+    /// Not completed and due before now
+    Overdue,
+}
+
+impl DueFilter {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "today" => Ok(DueFilter::Today),
+            "overdue" => Ok(DueFilter::Overdue),
+            _ => Err(anyhow!("Invalid due filter: {}. Valid options are: today, overdue", s)),
+        }
+    }
+}
+
+/// Order of the reminders a query returns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReminderSort {
+    /// Earliest due date first, reminders without one last
+    Due,
+    /// Highest priority first, then by due date
+    Priority,
+    /// Alphabetically by title
+    Title,
+}
+
+impl ReminderSort {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "due" => Ok(ReminderSort::Due),
+            "priority" => Ok(ReminderSort::Priority),
+            "title" => Ok(ReminderSort::Title),
+            _ => Err(anyhow!("Invalid sort order: {}. Valid options are: due, priority, title", s)),
+        }
+    }
+}
+
+/// Which reminders to fetch and in what order
+///
+/// The filters become a `whose` clause so that Reminders.app only returns matching reminders;
+/// sorting happens afterwards. Without a sort order reminders keep the order of Reminders.app.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReminderQuery {
+    /// Only this list; all lists when unset
+    pub list: Option<String>,
+    pub due: Option<DueFilter>,
+    /// Only reminders completed in the last this many days
+    pub completed_within_days: Option<u32>,
+    pub priority: Option<TodoPriority>,
+    pub sort: Option<ReminderSort>,
+}
+
+impl ReminderQuery {
+    /// Check that the filters can match anything
+    pub fn validate(&self) -> Result<()> {
+        if self.due.is_some() && self.completed_within_days.is_some() {
+            return Err(anyhow!(
+                "Due filters only match open reminders and cannot be combined with completed reminders"
+            ));
+        }
+        Ok(())
+    }
+
+    /// AppleScript `whose` condition for the filters, `None` when every reminder matches
+    ///
+    /// Refers to the variables `nowDate`, `todayStart`, `tomorrowStart` and `completedSince`,
+    /// which the fetch script defines.
+    pub fn whose_clause(&self) -> Option<Script> {
+        let mut conditions: Vec<&'static str> = Vec::new();
+        match self.due {
+            Some(DueFilter::Today) => conditions
+                .push("completed is false and due date >= todayStart and due date < tomorrowStart"),
+            Some(DueFilter::Overdue) => {
+                conditions.push("completed is false and due date < nowDate")
+            }
+            None => {}
+        }
+        if self.completed_within_days.is_some() {
+            conditions.push("completed is true and completion date >= completedSince");
+        }
+        match self.priority {
+            Some(TodoPriority::High) => conditions.push("priority >= 1 and priority <= 4"),
+            Some(TodoPriority::Medium) => conditions.push("priority is 5"),
+            Some(TodoPriority::Low) => conditions.push("priority >= 6"),
+            None => {}
+        }
+        if conditions.is_empty() {
+            None
+        } else {
+            Some(Script::join(conditions.into_iter().map(Script::from), " and "))
+        }
+    }
+
+    /// Whether `item` passes the filters at `now`
+    pub fn matches(&self, item: &ReminderItem, now: NaiveDateTime) -> bool {
+        let due = parse_time(item.due_date.as_deref());
+        let due_ok = match self.due {
+            Some(DueFilter::Today) => {
+                !item.completed && due.is_some_and(|d| d.date() == now.date())
+            }
+            Some(DueFilter::Overdue) => item.is_overdue(now),
+            None => true,
+        };
+        let completed_ok = self.completed_within_days.is_none_or(|days| {
+            let since = now - Duration::days(days as i64);
+            item.completed
+                && parse_time(item.completion_date.as_deref()).is_some_and(|c| c >= since)
+        });
+        let priority_ok = self.priority.is_none_or(|p| item.priority == Some(p));
+        due_ok && completed_ok && priority_ok
+    }
+
+    /// Put `items` in the requested order
+    pub fn sort(&self, items: &mut [ReminderItem]) {
+        let by_due = |a: &ReminderItem, b: &ReminderItem| match (
+            parse_time(a.due_date.as_deref()),
+            parse_time(b.due_date.as_deref()),
+        ) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+        let rank = |p: Option<TodoPriority>| match p {
+            Some(TodoPriority::High) => 0,
+            Some(TodoPriority::Medium) => 1,
+            Some(TodoPriority::Low) => 2,
+            None => 3,
+        };
+        match self.sort {
+            Some(ReminderSort::Due) => items.sort_by(by_due),
+            Some(ReminderSort::Priority) => items
+                .sort_by(|a, b| rank(a.priority).cmp(&rank(b.priority)).then_with(|| by_due(a, b))),
+            Some(ReminderSort::Title) => {
+                items.sort_by_key(|item| item.title.to_lowercase());
+            }
+            None => {}
+        }
+    }
 }
 
 /// Error types specific to reminder operations
@@ -83,3 +265,99 @@ pub enum ReminderError {
     #[error("General reminder error: {0}")]
     General(String),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reminder::parse_reminder_lines;
+
+    fn at(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn reminders() -> Vec<ReminderItem> {
+        parse_reminder_lines(
+            "Pay rent\tfalse\t\tHome\t\t2025-03-14 09:00\t1\t\n\
+             Call mom\tfalse\tAsk about\tFamily\t2025-03-14 17:00\t2025-03-14 18:00\t0\t\n\
+             File taxes\tfalse\t\tHome\t\t2025-03-10 12:00\t5\t\n\
+             Buy milk\ttrue\t\tHome\t\t2025-03-13 08:00\t9\t2025-03-13 10:00\n\
+             Old chore\ttrue\t\tHome\t\t\t0\t2025-02-01 10:00\n",
+        )
+    }
+
+    fn titles(items: &[ReminderItem]) -> Vec<&str> {
+        items.iter().map(|item| item.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_parse_reminder_lines() {
+        let items = reminders();
+        assert_eq!(items.len(), 5);
+        assert_eq!(items[0].priority, Some(TodoPriority::High));
+        assert_eq!(items[0].due_date.as_deref(), Some("2025-03-14 09:00"));
+        assert_eq!(items[1].reminder_time.as_deref(), Some("2025-03-14 17:00"));
+        assert_eq!(items[1].notes.as_deref(), Some("Ask about"));
+        assert_eq!(items[1].priority, None);
+        assert!(items[3].completed);
+        assert_eq!(items[3].completion_date.as_deref(), Some("2025-03-13 10:00"));
+    }
+
+    #[test]
+    fn test_query_filters() {
+        let now = at("2025-03-14 12:00");
+        let select = |query: ReminderQuery| {
+            let items: Vec<ReminderItem> =
+                reminders().into_iter().filter(|item| query.matches(item, now)).collect();
+            items.into_iter().map(|item| item.title).collect::<Vec<_>>()
+        };
+
+        let today = ReminderQuery { due: Some(DueFilter::Today), ..Default::default() };
+        assert_eq!(select(today), vec!["Pay rent", "Call mom"]);
+        let overdue = ReminderQuery { due: Some(DueFilter::Overdue), ..Default::default() };
+        assert_eq!(select(overdue), vec!["Pay rent", "File taxes"]);
+        let done = ReminderQuery { completed_within_days: Some(7), ..Default::default() };
+        assert_eq!(select(done), vec!["Buy milk"]);
+        let high = ReminderQuery { priority: Some(TodoPriority::High), ..Default::default() };
+        assert_eq!(select(high), vec!["Pay rent"]);
+    }
+
+    #[test]
+    fn test_query_whose_clause() {
+        assert_eq!(ReminderQuery::default().whose_clause(), None);
+        let query = ReminderQuery {
+            due: Some(DueFilter::Today),
+            priority: Some(TodoPriority::Medium),
+            ..Default::default()
+        };
+        assert_eq!(
+            query.whose_clause().unwrap().as_str(),
+            "completed is false and due date >= todayStart and due date < tomorrowStart and priority is 5"
+        );
+
+        let conflicting = ReminderQuery {
+            due: Some(DueFilter::Overdue),
+            completed_within_days: Some(3),
+            ..Default::default()
+        };
+        assert!(conflicting.validate().is_err());
+    }
+
+    #[test]
+    fn test_query_sort() {
+        let mut items = reminders();
+        ReminderQuery { sort: Some(ReminderSort::Priority), ..Default::default() }.sort(&mut items);
+        assert_eq!(
+            titles(&items),
+            vec!["Pay rent", "File taxes", "Buy milk", "Call mom", "Old chore"]
+        );
+
+        ReminderQuery { sort: Some(ReminderSort::Due), ..Default::default() }.sort(&mut items);
+        assert_eq!(
+            titles(&items),
+            vec!["File taxes", "Buy milk", "Pay rent", "Call mom", "Old chore"]
+        );
+
+        ReminderQuery { sort: Some(ReminderSort::Title), ..Default::default() }.sort(&mut items);
+        assert_eq!(titles(&items)[0], "Buy milk");
+    }
+}
@@ -1,12 +1,12 @@
 //! Utility functions for reminder operations
 
 use anyhow::{Result, anyhow};
-use chrono::{Datelike, Local, NaiveDate};
+use chrono::NaiveDate;
 use regex::Regex;
 
 /// Escape a string for use in AppleScript
 pub fn escape_applescript_string(input: &str) -> String {
-    input.replace("\"", "\\\"")
+    crate::applescript::escape(input)
 }
 
 /// Format a time string for use in AppleScript reminders
@@ -49,19 +49,11 @@ pub fn format_reminder_time(time_str: &str) -> Result<String> {
     }
 }
 
-/// Resolve relative date expressions like "today", "tomorrow"
+/// Resolve relative date expressions like "today", "tomorrow" or "next tuesday"
+///
+/// See `crate::dates::parse_date` for the accepted phrases.
 pub fn resolve_relative_date(date_str: &str) -> Result<String> {
-    let today = Local::now().date_naive();
-
-    match date_str.trim().to_lowercase().as_str() {
-        "today" => Ok(format!("{}-{:02}-{:02}", today.year(), today.month(), today.day())),
-        "tomorrow" => {
-            let tomorrow =
-                today.succ_opt().ok_or_else(|| anyhow!("Error calculating tomorrow's date"))?;
-            Ok(format!("{}-{:02}-{:02}", tomorrow.year(), tomorrow.month(), tomorrow.day()))
-        }
-        _ => Err(anyhow!("Unknown relative date: {}", date_str)),
-    }
+    crate::dates::resolve_date(date_str)
 }
 
 /// Parse the output of the list command to extract reminder items
@@ -77,7 +69,7 @@ pub fn parse_reminder_list_output(_output: &str) -> Vec<super::ReminderItem> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Local;
+    use chrono::{Datelike, Local};
 
     #[test]
     fn test_escape_applescript_string() {
@@ -4,7 +4,7 @@
 
 use super::reminder_types::ReminderError;
 use anyhow::{Result, anyhow};
-use log::warn;
+use tracing::warn;
 
 /// Validate a reminder title
 pub fn validate_title(title: &str) -> Result<()> {
@@ -39,13 +39,8 @@ pub fn validate_reminder_time(time: &str) -> Result<()> {
 
 /// Validate a reminder list name
 pub fn validate_list_name(name: &str) -> Result<()> {
-    if name.is_empty() {
-        return Err(anyhow!(ReminderError::InvalidInput("List name cannot be empty".to_string())));
-    }
-
-    // Check if list exists or can be created
-    // In practice, we might want to check against the actual lists in the system
-
+    crate::validation::list_name(name)
+        .map_err(|e| anyhow!(ReminderError::InvalidInput(e.to_string())))?;
     Ok(())
 }
 
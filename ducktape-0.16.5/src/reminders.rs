@@ -1,3 +1,5 @@
+use crate::applescript::{Properties, Template};
+use crate::script_runner::run_applescript;
 use anyhow::Result;
 
 #[derive(Debug)]
@@ -18,24 +20,26 @@ impl<'a> ReminderConfig<'a> {
 #[allow(dead_code)]
 pub fn create_reminder(config: ReminderConfig) -> Result<()> {
     // Build properties for AppleScript; note that AppleScript requires a proper date format.
-    let mut properties = format!("name:\"{}\"", config.title);
+    let mut properties = Properties::new().text("name", config.title);
     if let Some(date_str) = config.time {
-        properties.push_str(&format!(", remind me date:date \"{}\"", date_str));
+        let date = Template::new("date ${date}").text("date", date_str).render()?;
+        properties = properties.script("remind me date", date);
     }
 
-    let script = format!(
+    let script = Template::new(
         r#"tell application "Reminders"
             try
-                set newReminder to make new reminder with properties {{{}}}
+                set newReminder to make new reminder with properties ${properties}
                 return "Success: Reminder created"
             on error errMsg
                 return "Error: " & errMsg
             end try
         end tell"#,
-        properties
-    );
+    )
+    .script("properties", properties)
+    .render()?;
 
-    let output = std::process::Command::new("osascript").arg("-e").arg(&script).output()?;
+    let output = run_applescript(script)?;
     let result = String::from_utf8_lossy(&output.stdout);
     if result.contains("Success") {
         println!("Reminder created: {}", config.title);
@@ -59,7 +63,7 @@ pub fn list_reminders() -> Result<()> {
         end try
     end tell"#;
 
-    let output = std::process::Command::new("osascript").arg("-e").arg(script).output()?;
+    let output = run_applescript(script)?;
 
     let result = String::from_utf8_lossy(&output.stdout);
     if result.contains("Error") {
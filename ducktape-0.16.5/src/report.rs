@@ -0,0 +1,394 @@
+//! Time reports from the events DuckTape tracks: `ducktape report time`.
+//
+// The events in ~/.ducktape/events.json are added up over a period and grouped by calendar,
+// title, day, week or tag, so the calendar doubles as a lightweight time tracker. Repeating
+// events count once per occurrence in the period. All-day events are left out, and events
+// without an end time count as `calendar.default_duration_minutes`. Tags are the #hashtags in
+// an event's title or notes.
+
+use crate::calendar::occurs_on;
+use crate::state::CalendarItem;
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveTime};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+/// Label of the group for events without tags
+const UNTAGGED: &str = "(untagged)";
+
+/// How to group the time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Calendar,
+    Title,
+    Day,
+    Week,
+    Tag,
+}
+
+impl GroupBy {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "calendar" | "calendars" => Ok(GroupBy::Calendar),
+            "title" | "event" | "events" => Ok(GroupBy::Title),
+            "day" | "date" => Ok(GroupBy::Day),
+            "week" => Ok(GroupBy::Week),
+            "tag" | "tags" => Ok(GroupBy::Tag),
+            _ => Err(anyhow!("Unknown grouping: {}. Use calendar, title, day, week or tag", s)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GroupBy::Calendar => "calendar",
+            GroupBy::Title => "title",
+            GroupBy::Day => "day",
+            GroupBy::Week => "week",
+            GroupBy::Tag => "tag",
+        }
+    }
+}
+
+/// Which events to count; every set filter must match, ignoring case
+#[derive(Debug, Clone, Default)]
+pub struct TimeFilter {
+    pub calendar: Option<String>,
+    /// Text in the title or notes
+    pub keyword: Option<String>,
+    pub tag: Option<String>,
+}
+
+/// One occurrence of an event in the period
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeEntry {
+    pub title: String,
+    pub calendar: String,
+    pub date: NaiveDate,
+    pub minutes: i64,
+    pub tags: Vec<String>,
+}
+
+/// Total time of one group
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimeRow {
+    pub group: String,
+    pub events: usize,
+    pub minutes: i64,
+}
+
+/// The #hashtags in an event's title and notes, lowercased and without the `#`
+pub fn event_tags(item: &CalendarItem) -> Vec<String> {
+    crate::tag::hashtags(&format!(
+        "{} {}",
+        item.title,
+        item.description.as_deref().unwrap_or_default()
+    ))
+}
+
+/// The period for `--last`: week, month or year before the current one, or the last `N`
+/// days or weeks up to today
+pub fn last_period(expr: &str, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    let expr = expr.trim().to_lowercase();
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let first_of_month = today.with_day(1)?;
+    match expr.as_str() {
+        "day" | "yesterday" => Some((today.pred_opt()?, today.pred_opt()?)),
+        "week" => Some((monday - Duration::days(7), monday - Duration::days(1))),
+        "month" => {
+            Some((first_of_month.checked_sub_months(Months::new(1))?, first_of_month.pred_opt()?))
+        }
+        "year" => {
+            let year = today.year() - 1;
+            Some((NaiveDate::from_ymd_opt(year, 1, 1)?, NaiveDate::from_ymd_opt(year, 12, 31)?))
+        }
+        _ => {
+            let (count, unit) = expr
+                .split_once(char::is_whitespace)
+                .map(|(count, unit)| (count.to_string(), unit.trim().to_string()))
+                .unwrap_or_else(|| {
+                    let split = expr.find(|c: char| !c.is_ascii_digit()).unwrap_or(expr.len());
+                    (expr[..split].to_string(), expr[split..].to_string())
+                });
+            let count: i64 = count.parse().ok().filter(|count| *count > 0)?;
+            let days = match unit.as_str() {
+                "d" | "day" | "days" => count,
+                "w" | "week" | "weeks" => count * 7,
+                _ => return None,
+            };
+            Some((today - Duration::days(days - 1), today))
+        }
+    }
+}
+
+fn minutes(item: &CalendarItem, default_minutes: i64) -> Option<i64> {
+    let start = NaiveTime::parse_from_str(&item.time, "%H:%M").ok()?;
+    let Some(end) = item.end_time.as_deref() else {
+        return Some(default_minutes);
+    };
+    let end = NaiveTime::parse_from_str(end, "%H:%M").ok()?;
+    let minutes = (end - start).num_minutes();
+    // An end before the start is on the next day
+    Some(if minutes <= 0 { minutes + 24 * 60 } else { minutes })
+}
+
+fn contains(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+/// Every occurrence of the matching events from `from` to `until`, both days included
+pub fn time_entries(
+    items: &[CalendarItem],
+    from: NaiveDate,
+    until: NaiveDate,
+    filter: &TimeFilter,
+    default_minutes: i64,
+) -> Vec<TimeEntry> {
+    let mut entries = Vec::new();
+    for item in items.iter().filter(|item| !item.all_day) {
+        if let Some(calendar) = &filter.calendar {
+            if !item.calendars.iter().any(|c| c.eq_ignore_ascii_case(calendar)) {
+                continue;
+            }
+        }
+        if let Some(keyword) = &filter.keyword {
+            let notes = item.description.as_deref().unwrap_or_default();
+            if !contains(&item.title, keyword) && !contains(notes, keyword) {
+                continue;
+            }
+        }
+        let tags = event_tags(item);
+        if let Some(tag) = &filter.tag {
+            let tag = tag.trim_start_matches('#').to_lowercase();
+            if !tags.contains(&tag) {
+                continue;
+            }
+        }
+        let Some(minutes) = minutes(item, default_minutes) else {
+            continue;
+        };
+        let calendar = item.calendars.first().cloned().unwrap_or_default();
+        for date in from.iter_days().take_while(|date| *date <= until) {
+            if occurs_on(item, date) {
+                entries.push(TimeEntry {
+                    title: item.title.clone(),
+                    calendar: calendar.clone(),
+                    date,
+                    minutes,
+                    tags: tags.clone(),
+                });
+            }
+        }
+    }
+    entries
+}
+
+/// Add the entries up per group; days and weeks in order, anything else longest first
+pub fn group_time(entries: &[TimeEntry], group_by: GroupBy) -> Vec<TimeRow> {
+    let mut groups: BTreeMap<String, TimeRow> = BTreeMap::new();
+    for entry in entries {
+        let keys = match group_by {
+            GroupBy::Calendar => vec![entry.calendar.clone()],
+            GroupBy::Title => vec![entry.title.clone()],
+            GroupBy::Day => vec![entry.date.format("%Y-%m-%d").to_string()],
+            GroupBy::Week => {
+                let week = entry.date.iso_week();
+                vec![format!("{}-W{:02}", week.year(), week.week())]
+            }
+            GroupBy::Tag if entry.tags.is_empty() => vec![UNTAGGED.to_string()],
+            GroupBy::Tag => entry.tags.clone(),
+        };
+        for key in keys {
+            let row =
+                groups
+                    .entry(key.clone())
+                    .or_insert(TimeRow { group: key, events: 0, minutes: 0 });
+            row.events += 1;
+            row.minutes += entry.minutes;
+        }
+    }
+    let mut rows: Vec<TimeRow> = groups.into_values().collect();
+    if !matches!(group_by, GroupBy::Day | GroupBy::Week) {
+        rows.sort_by(|a, b| b.minutes.cmp(&a.minutes).then_with(|| a.group.cmp(&b.group)));
+    }
+    rows
+}
+
+fn hours(minutes: i64) -> String {
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// Render the rows as a table with a total
+pub fn format_time_table(
+    rows: &[TimeRow],
+    group_by: GroupBy,
+    from: NaiveDate,
+    until: NaiveDate,
+) -> String {
+    let mut out = format!("Time by {} from {} to {}\n", group_by.as_str(), from, until);
+    if rows.is_empty() {
+        out.push_str("  No events\n");
+        return out;
+    }
+    let width = rows.iter().map(|row| row.group.chars().count()).max().unwrap_or(0).max(5);
+    for row in rows {
+        out.push_str(&format!(
+            "  {:<width$}  {:>7}  {} event(s)\n",
+            row.group,
+            hours(row.minutes),
+            row.events,
+            width = width
+        ));
+    }
+    // Tagged events can be in several groups, so the total comes from the groups only
+    // when each event is in one
+    if group_by != GroupBy::Tag {
+        let total: i64 = rows.iter().map(|row| row.minutes).sum();
+        let events: usize = rows.iter().map(|row| row.events).sum();
+        out.push_str(&format!(
+            "  {:<width$}  {:>7}  {} event(s)\n",
+            "Total",
+            hours(total),
+            events,
+            width = width
+        ));
+    }
+    out
+}
+
+/// The rows as CSV with a header
+pub fn time_csv(rows: &[TimeRow], group_by: GroupBy) -> Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record([group_by.as_str(), "events", "minutes", "hours"])?;
+    for row in rows {
+        writer.write_record([
+            row.group.clone(),
+            row.events.to_string(),
+            row.minutes.to_string(),
+            format!("{:.2}", row.minutes as f64 / 60.0),
+        ])?;
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+/// The report as JSON
+pub fn time_json(rows: &[TimeRow], group_by: GroupBy, from: NaiveDate, until: NaiveDate) -> Value {
+    json!({
+        "from": from.format("%Y-%m-%d").to_string(),
+        "until": until.format("%Y-%m-%d").to_string(),
+        "group_by": group_by.as_str(),
+        "rows": rows,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn item(
+        title: &str,
+        date: &str,
+        time: &str,
+        end: Option<&str>,
+        calendar: &str,
+    ) -> CalendarItem {
+        CalendarItem {
+            title: title.to_string(),
+            date: date.to_string(),
+            time: time.to_string(),
+            calendars: vec![calendar.to_string()],
+            end_time: end.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn items() -> Vec<CalendarItem> {
+        let mut standup = item("Standup", "2025-02-03", "09:00", Some("09:15"), "Work");
+        standup.recurrence = Some("FREQ=WEEKLY;INTERVAL=1".to_string());
+        let mut offsite = item("Offsite", "2025-02-10", "00:00", None, "Work");
+        offsite.all_day = true;
+        let mut review = item("Review #clientX", "2025-02-12", "14:00", Some("15:30"), "Work");
+        review.description = Some("Prep for #Deep-Work".to_string());
+        vec![
+            standup,
+            offsite,
+            review,
+            item("Gym", "2025-02-14", "18:00", None, "Home"),
+            item("Late deploy", "2025-02-20", "23:00", Some("01:00"), "Work"),
+            item("Next month", "2025-03-03", "10:00", Some("11:00"), "Work"),
+        ]
+    }
+
+    #[test]
+    fn test_last_period() {
+        let today = day("2025-03-14");
+        assert_eq!(last_period("month", today), Some((day("2025-02-01"), day("2025-02-28"))));
+        assert_eq!(last_period("week", today), Some((day("2025-03-03"), day("2025-03-09"))));
+        assert_eq!(last_period("year", today), Some((day("2024-01-01"), day("2024-12-31"))));
+        assert_eq!(last_period("7 days", today), Some((day("2025-03-08"), day("2025-03-14"))));
+        assert_eq!(last_period("2w", today), Some((day("2025-03-01"), day("2025-03-14"))));
+        assert_eq!(last_period("fortnight", today), None);
+        assert_eq!(last_period("0 days", today), None);
+    }
+
+    #[test]
+    fn test_time_entries_and_grouping() {
+        let (from, until) = (day("2025-02-01"), day("2025-02-28"));
+        let entries = time_entries(&items(), from, until, &TimeFilter::default(), 60);
+        // Four standups, the review, the gym and the deploy; not the all-day offsite
+        assert_eq!(entries.len(), 7);
+
+        let rows = group_time(&entries, GroupBy::Calendar);
+        assert_eq!(rows[0], TimeRow { group: "Work".to_string(), events: 6, minutes: 270 });
+        assert_eq!(rows[1], TimeRow { group: "Home".to_string(), events: 1, minutes: 60 });
+
+        let rows = group_time(&entries, GroupBy::Week);
+        assert_eq!(rows[0].group, "2025-W06");
+        assert_eq!(rows[0].minutes, 15);
+
+        let rows = group_time(&entries, GroupBy::Tag);
+        assert_eq!(rows[0].group, UNTAGGED);
+        assert!(rows.iter().any(|row| row.group == "deep-work" && row.minutes == 90));
+    }
+
+    #[test]
+    fn test_time_filters() {
+        let (from, until) = (day("2025-02-01"), day("2025-02-28"));
+        let filter = TimeFilter { tag: Some("#ClientX".to_string()), ..TimeFilter::default() };
+        let entries = time_entries(&items(), from, until, &filter, 60);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].title, "Review #clientX");
+
+        let filter = TimeFilter {
+            calendar: Some("work".to_string()),
+            keyword: Some("deploy".to_string()),
+            ..TimeFilter::default()
+        };
+        let entries = time_entries(&items(), from, until, &filter, 60);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].minutes, 120);
+    }
+
+    #[test]
+    fn test_exports() {
+        let rows = vec![
+            TimeRow { group: "Work, Inc".to_string(), events: 2, minutes: 90 },
+            TimeRow { group: "Home".to_string(), events: 1, minutes: 30 },
+        ];
+        let csv = time_csv(&rows, GroupBy::Calendar).unwrap();
+        assert_eq!(csv, "calendar,events,minutes,hours\n\"Work, Inc\",2,90,1.50\nHome,1,30,0.50\n");
+
+        let json = time_json(&rows, GroupBy::Calendar, day("2025-02-01"), day("2025-02-28"));
+        assert_eq!(json["group_by"], "calendar");
+        assert_eq!(json["rows"][0]["minutes"], 90);
+
+        let table =
+            format_time_table(&rows, GroupBy::Calendar, day("2025-02-01"), day("2025-02-28"));
+        assert!(table.contains("  Work, Inc     1:30  2 event(s)\n"));
+        assert!(table.contains("  Total         2:00  3 event(s)\n"));
+    }
+}
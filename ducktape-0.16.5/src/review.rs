@@ -0,0 +1,493 @@
+//! Weekly review notes: `ducktape review weekly`.
+//
+// The review covers last week, Monday to Sunday: reminders completed in Reminders.app, the
+// events DuckTape tracks that took place (repeating events once per occurrence), and notes
+// created in Notes.app. They are written as one Markdown note. When a language model is
+// configured it is asked for a short summary that opens the note; without one, or when the
+// request fails, the note has the lists only.
+
+use crate::applescript::{Script, Template};
+use crate::calendar::occurs_on;
+use crate::config::{Config, LLMProvider};
+use crate::script_runner::run_applescript_async;
+use crate::state::CalendarItem;
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use reqwest::Client;
+use serde_json::{Value, json};
+use std::env;
+use tracing::{debug, warn};
+
+/// Items of each kind listed in the prompt for the summary
+const MAX_PROMPT_ITEMS: usize = 50;
+
+/// A reminder marked as done during the week
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletedReminder {
+    pub title: String,
+    pub list: String,
+    pub completed: NaiveDate,
+}
+
+/// A note created during the week
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreatedNote {
+    pub title: String,
+    pub folder: String,
+    pub created: NaiveDate,
+}
+
+/// One occurrence of a tracked event that took place during the week
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttendedEvent {
+    pub title: String,
+    pub calendar: String,
+    pub date: NaiveDate,
+    /// Start time, `None` for all-day events
+    pub time: Option<String>,
+}
+
+/// What happened from `from` to `until`, both days included
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeeklyReview {
+    pub from: NaiveDate,
+    pub until: NaiveDate,
+    pub events: Vec<AttendedEvent>,
+    pub reminders: Vec<CompletedReminder>,
+    pub notes: Vec<CreatedNote>,
+    /// Written by the language model, when one is configured
+    pub summary: Option<String>,
+}
+
+/// Monday to Sunday of the week before the one `today` is in
+pub fn last_week(today: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    (monday - Duration::days(7), monday - Duration::days(1))
+}
+
+/// Every occurrence of the tracked events from `from` to `until` that started before `now`,
+/// in order
+pub fn attended_events(
+    items: &[CalendarItem],
+    from: NaiveDate,
+    until: NaiveDate,
+    now: NaiveDateTime,
+) -> Vec<AttendedEvent> {
+    let mut events = Vec::new();
+    for item in items {
+        let start = NaiveTime::parse_from_str(&item.time, "%H:%M").unwrap_or(NaiveTime::MIN);
+        for date in from.iter_days().take_while(|date| *date <= until) {
+            if date.and_time(start) <= now && occurs_on(item, date) {
+                events.push(AttendedEvent {
+                    title: item.title.clone(),
+                    calendar: item.calendars.first().cloned().unwrap_or_default(),
+                    date,
+                    time: (!item.all_day).then(|| item.time.clone()),
+                });
+            }
+        }
+    }
+    events.sort_by(|a, b| (a.date, &a.time).cmp(&(b.date, &b.time)));
+    events
+}
+
+/// AppleScript setting `periodStart` and `periodEnd` to midnight on `from` and after `until`
+fn period_script(from: NaiveDate, until: NaiveDate) -> Result<Script> {
+    let end = until + Duration::days(1);
+    Template::new(
+        r#"set periodStart to current date
+    set day of periodStart to 1
+    set year of periodStart to ${from_year}
+    set month of periodStart to ${from_month}
+    set day of periodStart to ${from_day}
+    set time of periodStart to 0
+    copy periodStart to periodEnd
+    set day of periodEnd to 1
+    set year of periodEnd to ${end_year}
+    set month of periodEnd to ${end_month}
+    set day of periodEnd to ${end_day}"#,
+    )
+    .number("from_year", from.year())
+    .number("from_month", from.month())
+    .number("from_day", from.day())
+    .number("end_year", end.year())
+    .number("end_month", end.month())
+    .number("end_day", end.day())
+    .render()
+}
+
+const DATE_HANDLERS: &str = r#"on pad(n)
+    return text -2 thru -1 of ("0" & (n as integer))
+end pad
+
+on fmt(d)
+    return (year of d as text) & "-" & my pad(month of d as integer) & "-" & my pad(day of d)
+end fmt"#;
+
+fn completed_reminders_script(from: NaiveDate, until: NaiveDate) -> Result<Script> {
+    Template::new(
+        r#"${handlers}
+
+tell application "Reminders"
+    try
+        ${period}
+        set output to ""
+        repeat with l in lists
+            repeat with r in (every reminder of l whose completed is true and completion date is greater than or equal to periodStart and completion date is less than periodEnd)
+                set output to output & (name of r) & tab & (name of l) & tab & my fmt(completion date of r) & linefeed
+            end repeat
+        end repeat
+        return output
+    on error errMsg
+        return "Error: " & errMsg
+    end try
+end tell"#,
+    )
+    .script("handlers", Script::from(DATE_HANDLERS))
+    .script("period", period_script(from, until)?)
+    .render()
+}
+
+fn created_notes_script(from: NaiveDate, until: NaiveDate) -> Result<Script> {
+    Template::new(
+        r#"${handlers}
+
+tell application "Notes"
+    try
+        ${period}
+        set output to ""
+        repeat with f in folders
+            repeat with n in (every note of f whose creation date is greater than or equal to periodStart and creation date is less than periodEnd)
+                set output to output & (name of n) & tab & (name of f) & tab & my fmt(creation date of n) & linefeed
+            end repeat
+        end repeat
+        return output
+    on error errMsg
+        return "Error: " & errMsg
+    end try
+end tell"#,
+    )
+    .script("handlers", Script::from(DATE_HANDLERS))
+    .script("period", period_script(from, until)?)
+    .render()
+}
+
+/// Split tab separated `title, container, YYYY-MM-DD` lines, skipping malformed ones
+fn parse_dated_lines(output: &str) -> Vec<(String, String, NaiveDate)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() < 3 || fields[0].trim().is_empty() {
+                return None;
+            }
+            let date = NaiveDate::parse_from_str(fields[2].trim(), "%Y-%m-%d").ok()?;
+            Some((fields[0].trim().to_string(), fields[1].trim().to_string(), date))
+        })
+        .collect()
+}
+
+/// Parse the tab separated `title, list, completion date` lines from Reminders.app
+pub fn parse_completed_reminders(output: &str) -> Vec<CompletedReminder> {
+    parse_dated_lines(output)
+        .into_iter()
+        .map(|(title, list, completed)| CompletedReminder { title, list, completed })
+        .collect()
+}
+
+/// Parse the tab separated `title, folder, creation date` lines from Notes.app
+pub fn parse_created_notes(output: &str) -> Vec<CreatedNote> {
+    parse_dated_lines(output)
+        .into_iter()
+        .map(|(title, folder, created)| CreatedNote { title, folder, created })
+        .collect()
+}
+
+async fn run_review_script(script: Script, app: &str) -> Result<String> {
+    let output = run_applescript_async(script).await?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    if !output.status.success() || stdout.starts_with("Error: ") {
+        let error = if stdout.starts_with("Error: ") {
+            stdout.replace("Error: ", "")
+        } else {
+            String::from_utf8_lossy(&output.stderr).to_string()
+        };
+        return Err(anyhow!("Failed to read {}.app: {}", app, error.trim()));
+    }
+    Ok(stdout)
+}
+
+/// The reminders completed from `from` to `until`, in the order they were done
+pub async fn fetch_completed_reminders(
+    from: NaiveDate,
+    until: NaiveDate,
+) -> Result<Vec<CompletedReminder>> {
+    let output = run_review_script(completed_reminders_script(from, until)?, "Reminders").await?;
+    let mut reminders = parse_completed_reminders(&output);
+    reminders.sort_by_key(|reminder| reminder.completed);
+    debug!("Found {} completed reminder(s)", reminders.len());
+    Ok(reminders)
+}
+
+/// The notes created from `from` to `until`, oldest first
+pub async fn fetch_created_notes(from: NaiveDate, until: NaiveDate) -> Result<Vec<CreatedNote>> {
+    let output = run_review_script(created_notes_script(from, until)?, "Notes").await?;
+    let mut notes = parse_created_notes(&output);
+    notes.sort_by_key(|note| note.created);
+    debug!("Found {} created note(s)", notes.len());
+    Ok(notes)
+}
+
+/// Gather last week's review; Reminders.app or Notes.app failing leaves their section empty
+pub async fn gather(from: NaiveDate, until: NaiveDate, now: NaiveDateTime) -> Result<WeeklyReview> {
+    let items = crate::state::load_events()?;
+    let reminders = fetch_completed_reminders(from, until).await.unwrap_or_else(|e| {
+        warn!("{}", e);
+        Vec::new()
+    });
+    let notes = fetch_created_notes(from, until).await.unwrap_or_else(|e| {
+        warn!("{}", e);
+        Vec::new()
+    });
+    Ok(WeeklyReview {
+        from,
+        until,
+        events: attended_events(&items, from, until, now),
+        reminders,
+        notes,
+        summary: None,
+    })
+}
+
+impl WeeklyReview {
+    /// e.g. "Weekly review: Feb 3 – Feb 9, 2025"
+    pub fn title(&self) -> String {
+        format!(
+            "Weekly review: {} – {}",
+            self.from.format("%b %-d"),
+            self.until.format("%b %-d, %Y")
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty() && self.reminders.is_empty() && self.notes.is_empty()
+    }
+
+    /// The body of the note, as Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        if let Some(summary) = &self.summary {
+            out.push_str(&format!("## Summary\n{}\n\n", summary.trim()));
+        }
+
+        out.push_str(&format!("## Completed reminders ({})\n", self.reminders.len()));
+        if self.reminders.is_empty() {
+            out.push_str("Nothing completed\n");
+        }
+        for reminder in &self.reminders {
+            out.push_str(&format!(
+                "- {} _({}, {})_\n",
+                reminder.title,
+                reminder.list,
+                reminder.completed.format("%a")
+            ));
+        }
+
+        out.push_str(&format!("\n## Events ({})\n", self.events.len()));
+        if self.events.is_empty() {
+            out.push_str("No tracked events\n");
+        }
+        let mut day = None;
+        for event in &self.events {
+            if day != Some(event.date) {
+                out.push_str(&format!("**{}**\n", event.date.format("%A %b %-d")));
+                day = Some(event.date);
+            }
+            match &event.time {
+                Some(time) => out.push_str(&format!("- {} {}", time, event.title)),
+                None => out.push_str(&format!("- {} (all day)", event.title)),
+            }
+            if !event.calendar.is_empty() {
+                out.push_str(&format!(" _({})_", event.calendar));
+            }
+            out.push('\n');
+        }
+
+        out.push_str(&format!("\n## Notes created ({})\n", self.notes.len()));
+        if self.notes.is_empty() {
+            out.push_str("No new notes\n");
+        }
+        for note in &self.notes {
+            out.push_str(&format!("- {} _({})_\n", note.title, note.folder));
+        }
+        out
+    }
+
+    /// The week as plain lines for the language model
+    fn prompt(&self) -> String {
+        let mut out =
+            format!("Week from {} to {}\n\nCompleted reminders:\n", self.from, self.until);
+        for reminder in self.reminders.iter().take(MAX_PROMPT_ITEMS) {
+            out.push_str(&format!("- {} ({})\n", reminder.title, reminder.list));
+        }
+        out.push_str("\nEvents:\n");
+        for event in self.events.iter().take(MAX_PROMPT_ITEMS) {
+            out.push_str(&format!("- {} {} ({})\n", event.date, event.title, event.calendar));
+        }
+        out.push_str("\nNotes created:\n");
+        for note in self.notes.iter().take(MAX_PROMPT_ITEMS) {
+            out.push_str(&format!("- {}\n", note.title));
+        }
+        out
+    }
+}
+
+const SUMMARY_PROMPT: &str = "You write the opening of a personal weekly review. Given the \
+reminders completed, events attended and notes created last week, write three to five \
+sentences on what the week was about: main themes, notable accomplishments and where the \
+time went. Write in the second person, plainly, without headings, lists or preamble.";
+
+/// A summary of the week from the configured language model, `None` when there is none
+pub async fn summarize(review: &WeeklyReview, config: &Config) -> Result<Option<String>> {
+    let (provider, api_key, api_base, model) = match config.language_model.provider {
+        Some(LLMProvider::Grok) => (
+            "grok",
+            crate::secrets::get("XAI_API_KEY").ok_or_else(|| anyhow!("XAI_API_KEY not set"))?,
+            env::var("XAI_API_BASE").unwrap_or_else(|_| "https://api.x.ai/v1".to_string()),
+            "grok-2-latest".to_string(),
+        ),
+        Some(LLMProvider::DeepSeek) => (
+            "deepseek",
+            crate::parser::deepseek::api::api_key()?,
+            env::var("DEEPSEEK_API_BASE")
+                .unwrap_or_else(|_| crate::parser::deepseek::api::DEFAULT_API_BASE.to_string()),
+            env::var("DEEPSEEK_MODEL")
+                .unwrap_or_else(|_| crate::parser::deepseek::api::DEFAULT_MODEL.to_string()),
+        ),
+        None => return Ok(None),
+    };
+    crate::usage::check_budget(&config.usage)?;
+
+    let client = Client::builder().timeout(std::time::Duration::from_secs(30)).build()?;
+    let response = client
+        .post(format!("{}/chat/completions", api_base.trim_end_matches('/')))
+        .bearer_auth(api_key)
+        .json(&json!({
+            "model": model,
+            "messages": [
+                { "role": "system", "content": SUMMARY_PROMPT },
+                { "role": "user", "content": review.prompt() }
+            ],
+            "temperature": 0.5,
+            "max_tokens": 300
+        }))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("{} API error: Status {}", provider, response.status()));
+    }
+    let response: Value = response.json().await?;
+    crate::usage::record_response(&config.usage, provider, &model, &response);
+    let summary = response["choices"][0]["message"]["content"]
+        .as_str()
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty());
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn item(title: &str, date: &str, time: &str) -> CalendarItem {
+        CalendarItem {
+            title: title.to_string(),
+            date: date.to_string(),
+            time: time.to_string(),
+            calendars: vec!["Work".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_last_week() {
+        assert_eq!(last_week(day("2025-02-12")), (day("2025-02-03"), day("2025-02-09")));
+        assert_eq!(last_week(day("2025-02-10")), (day("2025-02-03"), day("2025-02-09")));
+        assert_eq!(last_week(day("2025-02-16")), (day("2025-02-03"), day("2025-02-09")));
+    }
+
+    #[test]
+    fn test_attended_events() {
+        let mut standup = item("Standup", "2025-01-27", "09:00");
+        standup.recurrence = Some("FREQ=WEEKLY;INTERVAL=1".to_string());
+        let mut offsite = item("Offsite", "2025-02-05", "00:00");
+        offsite.all_day = true;
+        let items = vec![
+            item("Retro", "2025-02-07", "15:00"),
+            standup,
+            offsite,
+            item("Later", "2025-02-09", "18:00"),
+            item("Next week", "2025-02-10", "10:00"),
+        ];
+        let now = day("2025-02-09").and_hms_opt(12, 0, 0).unwrap();
+        let events = attended_events(&items, day("2025-02-03"), day("2025-02-09"), now);
+        let titles: Vec<&str> = events.iter().map(|e| e.title.as_str()).collect();
+        // "Later" has not started yet
+        assert_eq!(titles, vec!["Standup", "Offsite", "Retro"]);
+        assert_eq!(events[1].time, None);
+        assert_eq!(events[2].time.as_deref(), Some("15:00"));
+    }
+
+    #[test]
+    fn test_parse_script_output() {
+        let reminders = parse_completed_reminders(
+            "Send invoice\tWork\t2025-02-04\nBroken line\n\tWork\t2025-02-04\nCall mum\tHome\tlater\n",
+        );
+        assert_eq!(
+            reminders,
+            vec![CompletedReminder {
+                title: "Send invoice".to_string(),
+                list: "Work".to_string(),
+                completed: day("2025-02-04"),
+            }]
+        );
+        let notes = parse_created_notes("Ideas\tNotes\t2025-02-06\n");
+        assert_eq!(notes[0].folder, "Notes");
+    }
+
+    #[test]
+    fn test_period_script() {
+        let script = period_script(day("2025-12-29"), day("2025-12-31")).unwrap();
+        assert!(script.as_str().contains("set year of periodStart to 2025"));
+        // The end is midnight after the last day
+        assert!(script.as_str().contains("set year of periodEnd to 2026"));
+        assert!(script.as_str().contains("set month of periodEnd to 1"));
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let review = WeeklyReview {
+            from: day("2025-02-03"),
+            until: day("2025-02-09"),
+            events: vec![AttendedEvent {
+                title: "Retro".to_string(),
+                calendar: "Work".to_string(),
+                date: day("2025-02-07"),
+                time: Some("15:00".to_string()),
+            }],
+            reminders: parse_completed_reminders("Send invoice\tWork\t2025-02-04\n"),
+            notes: Vec::new(),
+            summary: Some("A focused week.".to_string()),
+        };
+        assert_eq!(review.title(), "Weekly review: Feb 3 – Feb 9, 2025");
+        let markdown = review.to_markdown();
+        assert!(markdown.starts_with("## Summary\nA focused week.\n"));
+        assert!(markdown.contains("- Send invoice _(Work, Tue)_\n"));
+        assert!(markdown.contains("**Friday Feb 7**\n- 15:00 Retro _(Work)_\n"));
+        assert!(markdown.contains("## Notes created (0)\nNo new notes\n"));
+    }
+}
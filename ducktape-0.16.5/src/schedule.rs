@@ -0,0 +1,388 @@
+//! Commands run on a schedule: `ducktape schedule add "agenda" --at "07:30 daily"`.
+//
+// Scheduled commands are kept in ~/.ducktape/schedule.json. `ducktape schedule run` is the
+// daemon: it stays in the foreground (run it under launchd or in a spare terminal), wakes at
+// the start of every minute and runs each command whose next time has come as a separate
+// `ducktape` process, so a failing command cannot take the scheduler down. A command missed
+// while the daemon was not running runs once when it starts again, not once per missed time.
+//
+// Schedules are either phrases such as `07:30 daily`, `18:00 weekdays`, `monday 09:00`,
+// `hourly` or `09:00 monthly`, or five-field cron expressions (`30 7 * * 1-5`).
+
+use crate::app_state::SharedState;
+use crate::state::{ScheduledCommand, StateManager};
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, Timelike, Weekday};
+use tracing::{debug, info, warn};
+
+/// How far ahead `Schedule::next_after` looks before giving up, e.g. for `0 0 31 2 *`
+const SEARCH_DAYS: i64 = 4 * 366;
+
+/// When a command runs, as the minutes, hours, days, months and weekdays it may run on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    minutes: u64,
+    hours: u32,
+    /// Bits 1 to 31
+    days: u32,
+    /// Bits 1 to 12
+    months: u16,
+    /// Bits 0 (Sunday) to 6
+    weekdays: u8,
+    /// Day of month and weekday were both restricted, so either one matching is enough
+    day_or_weekday: bool,
+}
+
+fn bits(values: impl IntoIterator<Item = u32>) -> u64 {
+    values.into_iter().fold(0, |bits, value| bits | (1 << value))
+}
+
+/// Parse one cron field such as `*`, `5`, `1-5`, `*/15` or `0,30`
+fn cron_field(field: &str, min: u32, max: u32) -> Result<u64> {
+    let mut set = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|s| *s > 0)),
+            None => (part, Some(1)),
+        };
+        let step = step.ok_or_else(|| anyhow!("Invalid step in cron field: {}", field))?;
+        let number = |s: &str| {
+            s.parse::<u32>()
+                .ok()
+                .filter(|n| (min..=max).contains(n))
+                .ok_or_else(|| anyhow!("Invalid cron field: {} (expected {}-{})", field, min, max))
+        };
+        let (from, to) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((from, to)) => (number(from)?, number(to)?),
+                None => {
+                    let n = number(range)?;
+                    (n, if part.contains('/') { max } else { n })
+                }
+            },
+        };
+        set |= bits((from..=to).step_by(step as usize));
+    }
+    Ok(set)
+}
+
+impl Schedule {
+    /// Parse a cron expression or a phrase such as `07:30 daily`
+    pub fn parse(expr: &str) -> Result<Self> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let cron_like = fields.len() == 5
+            && fields
+                .iter()
+                .all(|f| f.chars().all(|c| c.is_ascii_digit() || "*/,-".contains(c)));
+        if cron_like { Self::parse_cron(&fields) } else { Self::parse_phrase(expr) }
+    }
+
+    fn parse_cron(fields: &[&str]) -> Result<Self> {
+        // Sunday is 0 or 7
+        let weekdays = cron_field(fields[4], 0, 7)?;
+        let weekdays = (weekdays | (weekdays >> 7)) & 0x7f;
+        Ok(Self {
+            minutes: cron_field(fields[0], 0, 59)?,
+            hours: cron_field(fields[1], 0, 23)? as u32,
+            days: cron_field(fields[2], 1, 31)? as u32,
+            months: cron_field(fields[3], 1, 12)? as u16,
+            weekdays: weekdays as u8,
+            day_or_weekday: fields[2] != "*" && fields[4] != "*",
+        })
+    }
+
+    fn parse_phrase(expr: &str) -> Result<Self> {
+        let invalid = || {
+            anyhow!(
+                "Invalid schedule: {}. Use e.g. \"07:30 daily\", \"18:00 weekdays\", \"monday 09:00\", \"hourly\" or a cron expression such as \"30 7 * * *\"",
+                expr
+            )
+        };
+        let mut time = None;
+        let mut hourly = false;
+        let mut monthly = false;
+        let mut weekdays = 0u64;
+        let lower = expr.to_lowercase();
+        for word in lower.split(|c: char| c.is_whitespace() || c == ',') {
+            match word {
+                "" | "at" | "every" | "on" | "and" | "daily" | "day" | "everyday" => {}
+                "hourly" => hourly = true,
+                "monthly" => monthly = true,
+                "weekdays" | "weekday" => weekdays |= bits(1..=5),
+                "weekends" | "weekend" => weekdays |= bits([0, 6]),
+                _ => {
+                    let day = word.strip_suffix('s').unwrap_or(word).parse::<Weekday>();
+                    match (day, crate::dates::parse_time(word)) {
+                        (Ok(day), _) => weekdays |= bits([day.num_days_from_sunday()]),
+                        (_, Some(at)) if time.is_none() => time = Some(at),
+                        _ => return Err(invalid()),
+                    }
+                }
+            }
+        }
+
+        let all_hours = bits(0..=23) as u32;
+        let (minutes, hours) = match (time, hourly) {
+            (Some(at), true) => (bits([at.minute()]), all_hours),
+            (None, true) => (1, all_hours),
+            (Some(at), false) => (bits([at.minute()]), 1 << at.hour()),
+            (None, false) => return Err(invalid()),
+        };
+        Ok(Self {
+            minutes,
+            hours,
+            days: if monthly { 1 << 1 } else { bits(1..=31) as u32 },
+            months: bits(1..=12) as u16,
+            weekdays: if weekdays == 0 { 0x7f } else { weekdays as u8 },
+            day_or_weekday: false,
+        })
+    }
+
+    fn runs_on(&self, date: NaiveDate) -> bool {
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        let month = self.months & (1 << date.month()) != 0;
+        month && if self.day_or_weekday { day || weekday } else { day && weekday }
+    }
+
+    /// The first time strictly after `after`, to the minute
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        (0..SEARCH_DAYS)
+            .map(|days| start.date() + Duration::days(days))
+            .filter(|date| self.runs_on(*date))
+            .find_map(|date| {
+                (0..24u32)
+                    .filter(|hour| self.hours & (1 << hour) != 0)
+                    .flat_map(|hour| {
+                        (0..60u32)
+                            .filter(|minute| self.minutes & (1 << minute) != 0)
+                            .map(move |minute| (hour, minute))
+                    })
+                    .filter_map(|(hour, minute)| date.and_hms_opt(hour, minute, 0))
+                    .find(|time| *time >= start)
+            })
+    }
+}
+
+fn parse_time(time: &str) -> Option<NaiveDateTime> {
+    chrono::DateTime::parse_from_rfc3339(time)
+        .ok()
+        .map(|t| t.with_timezone(&Local).naive_local())
+}
+
+/// When `job` runs next: after its last run, or after it was added
+pub fn next_run(job: &ScheduledCommand) -> Option<NaiveDateTime> {
+    let since = parse_time(job.last_run.as_deref().unwrap_or(&job.created_at))?;
+    Schedule::parse(&job.schedule).ok()?.next_after(since)
+}
+
+/// The jobs whose next run is at or before `now`
+pub fn due_jobs(jobs: &[ScheduledCommand], now: NaiveDateTime) -> Vec<ScheduledCommand> {
+    jobs.iter()
+        .filter(|job| next_run(job).is_some_and(|next| next <= now))
+        .cloned()
+        .collect()
+}
+
+/// Split a command line into arguments, checking that it is a DuckTape command
+///
+/// A leading `ducktape` is dropped. `schedule` itself cannot be scheduled.
+pub fn command_words(command: &str) -> Result<Vec<String>> {
+    use clap::Parser;
+
+    let mut words =
+        shell_words::split(command).map_err(|e| anyhow!("Invalid command {}: {}", command, e))?;
+    if words.first().is_some_and(|word| word == "ducktape") {
+        words.remove(0);
+    }
+    let cli = crate::cli::Cli::try_parse_from(
+        std::iter::once("ducktape".to_string()).chain(words.iter().cloned()),
+    )
+    .map_err(|e| anyhow!("Not a DuckTape command: {}\n{}", command, e.render()))?;
+    match cli.command {
+        None => Err(anyhow!("No command given: {}", command)),
+        Some(crate::cli::Commands::Schedule { .. }) => {
+            Err(anyhow!("Scheduled commands cannot manage the schedule"))
+        }
+        Some(_) => Ok(words),
+    }
+}
+
+/// Add a command to the schedule, returning it as stored
+pub fn add_job(command: &str, schedule: &str) -> Result<ScheduledCommand> {
+    let words = command_words(command)?;
+    Schedule::parse(schedule)?;
+    StateManager::new()?.update(|jobs: &mut Vec<ScheduledCommand>| {
+        let job = ScheduledCommand {
+            id: jobs.iter().map(|job| job.id).max().unwrap_or(0) + 1,
+            command: shell_words::join(&words),
+            schedule: schedule.trim().to_string(),
+            created_at: Local::now().to_rfc3339(),
+            last_run: None,
+        };
+        jobs.push(job.clone());
+        job
+    })
+}
+
+/// Remove a command from the schedule; `false` when there is none with `id`
+pub fn remove_job(id: u32) -> Result<bool> {
+    StateManager::new()?.update(|jobs: &mut Vec<ScheduledCommand>| {
+        let before = jobs.len();
+        jobs.retain(|job| job.id != id);
+        jobs.len() != before
+    })
+}
+
+/// The schedule as a table, with each command's next run
+pub fn format_jobs(jobs: &[ScheduledCommand]) -> String {
+    if jobs.is_empty() {
+        return "No scheduled commands. Add one with `ducktape schedule add \"agenda\" --at \"07:30 daily\"`\n"
+            .to_string();
+    }
+    let mut out = format!("{:>3}  {:<20}  {:<16}  {}\n", "ID", "Schedule", "Next run", "Command");
+    for job in jobs {
+        let next = next_run(job)
+            .map(|next| next.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "never".to_string());
+        out.push_str(&format!(
+            "{:>3}  {:<20}  {:<16}  {}\n",
+            job.id, job.schedule, next, job.command
+        ));
+    }
+    out
+}
+
+/// Run one scheduled command as a separate `ducktape` process
+async fn run_job(job: &ScheduledCommand) -> Result<()> {
+    let words = shell_words::split(&job.command)?;
+    let status = tokio::process::Command::new(std::env::current_exe()?)
+        .args(&words)
+        .stdin(std::process::Stdio::null())
+        .status()
+        .await?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(anyhow!("`ducktape {}` exited with {}", job.command, status))
+    }
+}
+
+/// Run the scheduled commands as they come due until stopped with Ctrl+C
+pub async fn run_scheduler() -> Result<()> {
+    let manager = StateManager::new()?;
+    let jobs: Vec<ScheduledCommand> = manager.load()?;
+    println!("Scheduler running with {} command(s). Press Ctrl+C to stop.", jobs.len());
+    loop {
+        let now = Local::now().naive_local();
+        let jobs: Vec<ScheduledCommand> = manager.load()?;
+        for job in due_jobs(&jobs, now) {
+            info!("Running scheduled command {}: {}", job.id, job.command);
+            println!("[{}] ducktape {}", now.format("%Y-%m-%d %H:%M"), job.command);
+            if let Err(e) = run_job(&job).await {
+                warn!("Scheduled command {} failed: {}", job.id, e);
+                println!("Scheduled command {} failed: {}", job.id, e);
+            }
+            // Recorded even after a failure, so a broken command is not retried every minute
+            // `schedule run` holds no lock between jobs; take it just for this write
+            SharedState::global()
+                .exclusive(async {
+                    manager.update(|jobs: &mut Vec<ScheduledCommand>| {
+                        if let Some(stored) = jobs.iter_mut().find(|stored| stored.id == job.id) {
+                            stored.last_run = Some(Local::now().to_rfc3339());
+                        }
+                    })
+                })
+                .await?;
+        }
+
+        let seconds_left = 60 - Local::now().second() as u64;
+        debug!("Scheduler sleeping {}s", seconds_left);
+        tokio::select! {
+            _ = tokio::time::sleep(std::time::Duration::from_secs(seconds_left)) => {}
+            _ = tokio::signal::ctrl_c() => {
+                println!("Scheduler stopped");
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn next(schedule: &str, after: &str) -> NaiveDateTime {
+        Schedule::parse(schedule).unwrap().next_after(at(after)).unwrap()
+    }
+
+    #[test]
+    fn test_phrases() {
+        // 2025-03-14 is a Friday
+        assert_eq!(next("07:30 daily", "2025-03-14 07:29"), at("2025-03-14 07:30"));
+        assert_eq!(next("daily at 7:30am", "2025-03-14 07:30"), at("2025-03-15 07:30"));
+        assert_eq!(next("18:00 weekdays", "2025-03-14 18:00"), at("2025-03-17 18:00"));
+        assert_eq!(next("monday 09:00", "2025-03-14 12:00"), at("2025-03-17 09:00"));
+        assert_eq!(next("fridays, sundays at 5pm", "2025-03-14 17:00"), at("2025-03-16 17:00"));
+        assert_eq!(next("hourly", "2025-03-14 10:15"), at("2025-03-14 11:00"));
+        assert_eq!(next("09:00 monthly", "2025-03-14 12:00"), at("2025-04-01 09:00"));
+        assert!(Schedule::parse("sometimes").is_err());
+        assert!(Schedule::parse("daily").is_err());
+    }
+
+    #[test]
+    fn test_cron() {
+        assert_eq!(next("30 7 * * 1-5", "2025-03-14 08:00"), at("2025-03-17 07:30"));
+        assert_eq!(next("*/15 * * * *", "2025-03-14 10:16"), at("2025-03-14 10:30"));
+        assert_eq!(next("0 22 1,15 * *", "2025-03-14 12:00"), at("2025-03-15 22:00"));
+        // Sunday as 7
+        assert_eq!(next("0 9 * * 7", "2025-03-14 12:00"), at("2025-03-16 09:00"));
+        // Day of month or weekday, as in cron
+        assert_eq!(next("0 9 20 * 1", "2025-03-14 12:00"), at("2025-03-17 09:00"));
+        assert!(Schedule::parse("61 * * * *").is_err());
+        assert!(
+            Schedule::parse("0 0 31 2 *")
+                .unwrap()
+                .next_after(at("2025-03-14 12:00"))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_due_jobs() {
+        let created = Local::now() - Duration::days(1);
+        let job = |id: u32, schedule: &str, last_run: Option<String>| ScheduledCommand {
+            id,
+            command: "agenda".to_string(),
+            schedule: schedule.to_string(),
+            created_at: created.to_rfc3339(),
+            last_run,
+        };
+        let now = Local::now().naive_local();
+        let jobs = vec![
+            job(1, "*/5 * * * *", None),
+            job(2, "*/5 * * * *", Some(Local::now().to_rfc3339())),
+            job(3, "not a schedule", None),
+        ];
+        let due: Vec<u32> = due_jobs(&jobs, now).iter().map(|job| job.id).collect();
+        assert_eq!(due, vec![1]);
+    }
+
+    #[test]
+    fn test_command_words() {
+        assert_eq!(command_words("agenda").unwrap(), vec!["agenda"]);
+        assert_eq!(
+            command_words("ducktape note create \"Weekly review\"").unwrap(),
+            vec!["note", "create", "Weekly review"]
+        );
+        assert!(command_words("make me a sandwich").is_err());
+        assert!(command_words("schedule run").is_err());
+        assert!(command_words("").is_err());
+    }
+}
@@ -0,0 +1,394 @@
+//! Running AppleScript and JavaScript for Automation through osascript.
+//
+// Every call into Calendar, Reminders, Notes and Contacts goes through the `ScriptRunner`.
+// The interpreter path and extra arguments come from the `[scripting]` section of
+// config.toml, so managed Macs that restrict /usr/bin/osascript can point DuckTape at an
+// approved wrapper. Failures caused by the sandbox, Automation (TCC) permissions or device
+// management are recognized and reported with a message that says how to fix them. Async
+// callers share a semaphore sized by `scripting.max_concurrent`, so a busy API server cannot
+// start dozens of osascript processes at once. Tests can route every script to a fake with
+// `set_script_handler`.
+
+use crate::applescript::Script;
+use crate::config::{Config, ScriptingConfig};
+use anyhow::{Result, anyhow};
+use once_cell::sync::Lazy;
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+use tokio::sync::Semaphore;
+use tracing::{Instrument, debug, debug_span};
+
+/// Interpreter used when none is configured
+pub const DEFAULT_OSASCRIPT: &str = "osascript";
+
+/// Scripts run at the same time when `scripting.max_concurrent` is not set
+pub const DEFAULT_MAX_CONCURRENT: usize = 4;
+
+/// Language of a script passed to osascript
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptLanguage {
+    AppleScript,
+    JavaScript,
+}
+
+impl ScriptLanguage {
+    /// Name used in logs and metrics
+    pub fn label(&self) -> &'static str {
+        match self {
+            ScriptLanguage::AppleScript => "applescript",
+            ScriptLanguage::JavaScript => "javascript",
+        }
+    }
+}
+
+/// Log and count how long a script took
+fn finished(language: ScriptLanguage, started: Instant) {
+    let elapsed = started.elapsed();
+    debug!(elapsed_ms = elapsed.as_millis() as u64, "osascript finished");
+    crate::metrics::script_finished(language.label(), elapsed);
+}
+
+/// Script failures caused by the environment rather than by the script itself
+#[derive(Debug, thiserror::Error)]
+pub enum ScriptRunnerError {
+    #[error(
+        "Script interpreter '{0}' was not found. Set scripting.osascript_path in config.toml to its location"
+    )]
+    InterpreterNotFound(String),
+
+    #[error(
+        "Running '{program}' is not permitted ({detail}). It may be restricted by device management; ask your administrator to allow it or set scripting.osascript_path to an approved wrapper"
+    )]
+    InterpreterBlocked { program: String, detail: String },
+
+    #[error(
+        "DuckTape is not allowed to control {app}. Allow it in System Settings → Privacy & Security → Automation; on managed Macs this permission must be granted by your administrator"
+    )]
+    AutomationDenied { app: String },
+
+    #[error(
+        "The script was blocked by the macOS sandbox ({0}). Run DuckTape from a terminal outside the sandbox or set scripting.osascript_path to an allowed interpreter"
+    )]
+    Sandboxed(String),
+
+    #[error(
+        "'{0}' was terminated before finishing. Endpoint security software may be blocking it; ask your administrator to allow it"
+    )]
+    Terminated(String),
+}
+
+/// Runs scripts with the configured osascript binary
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptRunner {
+    program: PathBuf,
+    args: Vec<String>,
+    max_concurrent: usize,
+}
+
+impl Default for ScriptRunner {
+    fn default() -> Self {
+        Self::new(DEFAULT_OSASCRIPT, Vec::new())
+    }
+}
+
+/// Runs scripts in place of osascript, e.g. a recording fake in tests
+pub trait ScriptHandler: Send + Sync {
+    fn run(&self, language: ScriptLanguage, script: &str) -> Result<Output>;
+}
+
+static SCRIPT_HANDLER: Lazy<RwLock<Option<Arc<dyn ScriptHandler>>>> =
+    Lazy::new(|| RwLock::new(None));
+
+/// Send every script to `handler` instead of the configured runner; `None` restores it
+pub fn set_script_handler(handler: Option<Arc<dyn ScriptHandler>>) {
+    match SCRIPT_HANDLER.write() {
+        Ok(mut current) => *current = handler,
+        Err(poisoned) => *poisoned.into_inner() = handler,
+    }
+}
+
+fn script_handler() -> Option<Arc<dyn ScriptHandler>> {
+    SCRIPT_HANDLER.read().ok().and_then(|handler| handler.clone())
+}
+
+/// Whether scripts currently go to a handler set with `set_script_handler`
+pub(crate) fn has_script_handler() -> bool {
+    script_handler().is_some()
+}
+
+/// Permits for scripts run with `run_async`, shared by the whole process
+static SCRIPT_PERMITS: Lazy<Semaphore> =
+    Lazy::new(|| Semaphore::new(ScriptRunner::global().max_concurrent()));
+
+static GLOBAL_RUNNER: Lazy<ScriptRunner> = Lazy::new(|| match Config::load() {
+    Ok(config) => ScriptRunner::from_config(&config.scripting),
+    Err(e) => {
+        debug!("Using default script runner, config could not be loaded: {}", e);
+        ScriptRunner::default()
+    }
+});
+
+impl ScriptRunner {
+    pub fn new(program: impl Into<PathBuf>, args: Vec<String>) -> Self {
+        Self { program: program.into(), args, max_concurrent: DEFAULT_MAX_CONCURRENT }
+    }
+
+    /// Allow at most `limit` async scripts at once (at least one)
+    pub fn with_max_concurrent(mut self, limit: usize) -> Self {
+        self.max_concurrent = limit.max(1);
+        self
+    }
+
+    pub fn from_config(config: &ScriptingConfig) -> Self {
+        let program = config
+            .osascript_path
+            .as_deref()
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .unwrap_or(DEFAULT_OSASCRIPT);
+        Self::new(program, config.osascript_args.clone()).with_max_concurrent(config.max_concurrent)
+    }
+
+    /// The runner configured in config.toml, loaded once per process
+    pub fn global() -> &'static ScriptRunner {
+        &GLOBAL_RUNNER
+    }
+
+    pub fn program(&self) -> &Path {
+        &self.program
+    }
+
+    pub fn max_concurrent(&self) -> usize {
+        self.max_concurrent
+    }
+
+    /// Arguments passed to the interpreter: configured arguments first, then the script
+    fn command_args(&self, language: ScriptLanguage, script: &str) -> Vec<OsString> {
+        let mut args: Vec<OsString> = self.args.iter().map(OsString::from).collect();
+        if language == ScriptLanguage::JavaScript {
+            args.push("-l".into());
+            args.push("JavaScript".into());
+        }
+        args.push("-e".into());
+        args.push(script.into());
+        args
+    }
+
+    /// Run a script and wait for it to finish
+    ///
+    /// Returns the output for ordinary script errors so that callers can inspect stdout and
+    /// stderr as before; environment failures become a `ScriptRunnerError`.
+    pub fn run(&self, language: ScriptLanguage, script: &str) -> Result<Output> {
+        self.ensure_supported(script)?;
+        let _span = debug_span!("script", language = language.label()).entered();
+        let started = Instant::now();
+        let output = std::process::Command::new(&self.program)
+            .args(self.command_args(language, script))
+            .output()
+            .map_err(|e| self.spawn_error(e))?;
+        finished(language, started);
+        self.check(script, output)
+    }
+
+    /// Run a script without blocking the async runtime
+    ///
+    /// Waits for a free permit first when `max_concurrent` scripts are already running.
+    pub async fn run_async(&self, language: ScriptLanguage, script: &str) -> Result<Output> {
+        self.ensure_supported(script)?;
+        let span = debug_span!("script", language = language.label());
+        let _permit = SCRIPT_PERMITS
+            .acquire()
+            .instrument(span.clone())
+            .await
+            .map_err(|e| anyhow!("Script queue closed: {}", e))?;
+        let started = Instant::now();
+        let output = tokio::process::Command::new(&self.program)
+            .args(self.command_args(language, script))
+            .output()
+            .instrument(span.clone())
+            .await
+            .map_err(|e| self.spawn_error(e))?;
+        span.in_scope(|| finished(language, started));
+        self.check(script, output)
+    }
+
+    /// Fail with a `PlatformError` when osascript itself is used outside macOS
+    ///
+    /// A configured wrapper may forward scripts to a Mac, so it is always tried.
+    fn ensure_supported(&self, script: &str) -> Result<()> {
+        if self.program == Path::new(DEFAULT_OSASCRIPT) {
+            crate::backend::apple_only(&target_application(script))?;
+        }
+        Ok(())
+    }
+
+    fn spawn_error(&self, error: io::Error) -> anyhow::Error {
+        let program = self.program.display().to_string();
+        match error.kind() {
+            io::ErrorKind::NotFound => anyhow!(ScriptRunnerError::InterpreterNotFound(program)),
+            io::ErrorKind::PermissionDenied => {
+                anyhow!(ScriptRunnerError::InterpreterBlocked {
+                    program,
+                    detail: error.to_string()
+                })
+            }
+            _ => anyhow!("Failed to run {}: {}", program, error),
+        }
+    }
+
+    fn check(&self, script: &str, output: Output) -> Result<Output> {
+        if output.status.code().is_none() {
+            return Err(anyhow!(ScriptRunnerError::Terminated(self.program.display().to_string())));
+        }
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if let Some(error) = classify_failure(&self.program, script, &stderr) {
+                return Err(anyhow!(error));
+            }
+        }
+        Ok(output)
+    }
+}
+
+/// Recognize sandbox, Automation and device management failures in osascript's stderr
+pub fn classify_failure(program: &Path, script: &str, stderr: &str) -> Option<ScriptRunnerError> {
+    let lower = stderr.to_lowercase();
+    if lower.contains("-1743")
+        || lower.contains("-1744")
+        || lower.contains("not authorized to send apple events")
+    {
+        return Some(ScriptRunnerError::AutomationDenied { app: target_application(script) });
+    }
+    if lower.contains("-10004")
+        || lower.contains("privilege violation")
+        || lower.contains("sandbox")
+    {
+        return Some(ScriptRunnerError::Sandboxed(stderr.trim().to_string()));
+    }
+    if lower.contains("operation not permitted")
+        || lower.contains("administrator")
+        || lower.contains("managed by your organization")
+    {
+        return Some(ScriptRunnerError::InterpreterBlocked {
+            program: program.display().to_string(),
+            detail: stderr.trim().to_string(),
+        });
+    }
+    None
+}
+
+/// Name of the application a script talks to, for permission messages
+fn target_application(script: &str) -> String {
+    script
+        .split("application \"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .or_else(|| script.split("Application('").nth(1).and_then(|rest| rest.split('\'').next()))
+        .map(str::to_string)
+        .unwrap_or_else(|| "the target application".to_string())
+}
+
+/// Run an AppleScript with the configured runner
+///
+/// Scripts are a string literal or a rendered `Template`, so user input is always escaped.
+pub fn run_applescript(script: impl Into<Script>) -> Result<Output> {
+    let script = script.into();
+    if let Some(handler) = script_handler() {
+        return handler.run(ScriptLanguage::AppleScript, script.as_str());
+    }
+    ScriptRunner::global().run(ScriptLanguage::AppleScript, script.as_str())
+}
+
+/// Run an AppleScript with the configured runner without blocking
+pub async fn run_applescript_async(script: impl Into<Script>) -> Result<Output> {
+    let script = script.into();
+    if let Some(handler) = script_handler() {
+        return handler.run(ScriptLanguage::AppleScript, script.as_str());
+    }
+    ScriptRunner::global()
+        .run_async(ScriptLanguage::AppleScript, script.as_str())
+        .await
+}
+
+/// Run a JavaScript for Automation script with the configured runner
+pub fn run_jxa(script: impl AsRef<str>) -> Result<Output> {
+    if let Some(handler) = script_handler() {
+        return handler.run(ScriptLanguage::JavaScript, script.as_ref());
+    }
+    ScriptRunner::global().run(ScriptLanguage::JavaScript, script.as_ref())
+}
+
+/// Run a JavaScript for Automation script with the configured runner without blocking
+pub async fn run_jxa_async(script: impl AsRef<str>) -> Result<Output> {
+    if let Some(handler) = script_handler() {
+        return handler.run(ScriptLanguage::JavaScript, script.as_ref());
+    }
+    ScriptRunner::global()
+        .run_async(ScriptLanguage::JavaScript, script.as_ref())
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runner_uses_configured_interpreter_and_args() {
+        let config = ScriptingConfig {
+            osascript_path: Some("/opt/corp/bin/osascript-wrapper".to_string()),
+            osascript_args: vec!["--profile".to_string(), "ducktape".to_string()],
+            max_concurrent: 0,
+        };
+        let runner = ScriptRunner::from_config(&config);
+        assert_eq!(runner.program(), Path::new("/opt/corp/bin/osascript-wrapper"));
+        assert_eq!(runner.max_concurrent(), 1);
+        assert_eq!(
+            runner.command_args(ScriptLanguage::JavaScript, "1+1"),
+            vec!["--profile", "ducktape", "-l", "JavaScript", "-e", "1+1"]
+                .into_iter()
+                .map(OsString::from)
+                .collect::<Vec<_>>()
+        );
+
+        let blank = ScriptingConfig { osascript_path: Some(" ".to_string()), ..Default::default() };
+        assert_eq!(ScriptRunner::from_config(&blank), ScriptRunner::default());
+    }
+
+    #[test]
+    fn test_classify_failure() {
+        let program = Path::new(DEFAULT_OSASCRIPT);
+        let script = "tell application \"Calendar\"\nget name of calendars\nend tell";
+
+        let denied = classify_failure(
+            program,
+            script,
+            "execution error: Not authorized to send Apple events to Calendar. (-1743)",
+        );
+        assert!(
+            matches!(denied, Some(ScriptRunnerError::AutomationDenied { ref app }) if app == "Calendar")
+        );
+
+        let sandboxed =
+            classify_failure(program, script, "A privilege violation occurred. (-10004)");
+        assert!(matches!(sandboxed, Some(ScriptRunnerError::Sandboxed(_))));
+
+        let blocked = classify_failure(program, script, "osascript: Operation not permitted");
+        assert!(matches!(blocked, Some(ScriptRunnerError::InterpreterBlocked { .. })));
+
+        // Ordinary script errors are left to the caller
+        assert!(
+            classify_failure(program, script, "Can't get calendar \"Work\". (-1728)").is_none()
+        );
+    }
+
+    #[test]
+    fn test_missing_interpreter_is_reported() {
+        let runner = ScriptRunner::new("/nonexistent/osascript", Vec::new());
+        let error = runner.run(ScriptLanguage::AppleScript, "return 1").unwrap_err();
+        assert!(error.to_string().contains("scripting.osascript_path"));
+    }
+}
@@ -0,0 +1,263 @@
+//! API keys and credentials stored in the macOS keychain.
+//
+// `ducktape keys set xai|deepseek|zoom|caldav|smtp|webhook|slack|serpapi|brave|bing` stores keys with `env_store::store_secret` (the
+// keychain, or a file only the user can read where there is none). Parsers and the Zoom client
+// look keys up with `get`, which prefers the environment so `.env` files and exported
+// variables keep working. `ducktape keys migrate` moves keys out of a `.env` file.
+
+use crate::env_store::{delete_secret, load_secret, store_secret};
+use anyhow::{Result, anyhow};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// A service whose credentials can be stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyService {
+    Xai,
+    DeepSeek,
+    Zoom,
+    CalDav,
+    Smtp,
+    Webhook,
+    Slack,
+    SerpApi,
+    Brave,
+    Bing,
+}
+
+impl KeyService {
+    pub const ALL: [KeyService; 10] = [
+        KeyService::Xai,
+        KeyService::DeepSeek,
+        KeyService::Zoom,
+        KeyService::CalDav,
+        KeyService::Smtp,
+        KeyService::Webhook,
+        KeyService::Slack,
+        KeyService::SerpApi,
+        KeyService::Brave,
+        KeyService::Bing,
+    ];
+
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "xai" | "grok" => Ok(KeyService::Xai),
+            "deepseek" => Ok(KeyService::DeepSeek),
+            "zoom" => Ok(KeyService::Zoom),
+            "caldav" => Ok(KeyService::CalDav),
+            "smtp" => Ok(KeyService::Smtp),
+            "webhook" | "webhooks" => Ok(KeyService::Webhook),
+            "slack" => Ok(KeyService::Slack),
+            "serpapi" => Ok(KeyService::SerpApi),
+            "brave" => Ok(KeyService::Brave),
+            "bing" => Ok(KeyService::Bing),
+            other => Err(anyhow!(
+                "Unknown key service: {} (expected xai, deepseek, zoom, caldav, smtp, webhook, slack, serpapi, brave or bing)",
+                other
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyService::Xai => "xai",
+            KeyService::DeepSeek => "deepseek",
+            KeyService::Zoom => "zoom",
+            KeyService::CalDav => "caldav",
+            KeyService::Smtp => "smtp",
+            KeyService::Webhook => "webhook",
+            KeyService::Slack => "slack",
+            KeyService::SerpApi => "serpapi",
+            KeyService::Brave => "brave",
+            KeyService::Bing => "bing",
+        }
+    }
+
+    /// Environment variables holding the service's credentials
+    pub fn vars(&self) -> &'static [&'static str] {
+        match self {
+            KeyService::Xai => &["XAI_API_KEY"],
+            KeyService::DeepSeek => &["DEEPSEEK_API_KEY"],
+            KeyService::Zoom => &["ZOOM_ACCOUNT_ID", "ZOOM_CLIENT_ID", "ZOOM_CLIENT_SECRET"],
+            KeyService::CalDav => &[crate::backend::caldav::PASSWORD_VAR],
+            KeyService::Smtp => &[crate::smtp::PASSWORD_VAR],
+            KeyService::Webhook => &[crate::webhooks::SECRET_VAR],
+            KeyService::Slack => &[crate::api_server::slack::SIGNING_SECRET_VAR],
+            KeyService::SerpApi => &[crate::event_search::SERPAPI_KEY_VAR],
+            KeyService::Brave => &[crate::event_search::BRAVE_KEY_VAR],
+            KeyService::Bing => &[crate::event_search::BING_KEY_VAR],
+        }
+    }
+}
+
+/// Whether a variable is one of the credentials managed here
+pub fn is_managed(name: &str) -> bool {
+    KeyService::ALL.iter().any(|service| service.vars().contains(&name))
+}
+
+/// Keychain lookups, so the keychain is asked at most once per key and process
+static LOADED: Lazy<Mutex<HashMap<String, Option<String>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Where a credential was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeySource {
+    Environment,
+    Keychain,
+}
+
+fn from_env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.trim().is_empty())
+}
+
+fn from_keychain(name: &str) -> Option<String> {
+    if let Some(cached) = LOADED.lock().ok().and_then(|loaded| loaded.get(name).cloned()) {
+        return cached;
+    }
+    let value = load_secret(name).filter(|v| !v.trim().is_empty());
+    if let Ok(mut loaded) = LOADED.lock() {
+        loaded.insert(name.to_string(), value.clone());
+    }
+    value
+}
+
+/// A credential from the environment, or else from the keychain
+pub fn get(name: &str) -> Option<String> {
+    from_env(name).or_else(|| from_keychain(name))
+}
+
+/// Where `get` would find a credential, if anywhere
+pub fn source(name: &str) -> Option<KeySource> {
+    if from_env(name).is_some() {
+        Some(KeySource::Environment)
+    } else if from_keychain(name).is_some() {
+        Some(KeySource::Keychain)
+    } else {
+        None
+    }
+}
+
+/// Store a credential in the keychain
+pub fn set(name: &str, value: &str) -> Result<()> {
+    store_secret(name, value)?;
+    if let Ok(mut loaded) = LOADED.lock() {
+        loaded.insert(name.to_string(), Some(value.to_string()));
+    }
+    Ok(())
+}
+
+/// Remove a credential from the keychain
+pub fn delete(name: &str) {
+    delete_secret(name);
+    if let Ok(mut loaded) = LOADED.lock() {
+        loaded.insert(name.to_string(), None);
+    }
+}
+
+/// Read a secret from stdin, hiding the input when stdin is a terminal
+pub fn read_secret(prompt: &str) -> Option<String> {
+    use std::io::{IsTerminal, Write};
+
+    let terminal = std::io::stdin().is_terminal();
+    print!("{}", prompt);
+    let _ = std::io::stdout().flush();
+    if terminal {
+        let _ = std::process::Command::new("stty").arg("-echo").status();
+    }
+    let mut value = String::new();
+    let result = std::io::stdin().read_line(&mut value);
+    if terminal {
+        let _ = std::process::Command::new("stty").arg("echo").status();
+        println!();
+    }
+    result.ok()?;
+    let value = value.trim().to_string();
+    if value.is_empty() { None } else { Some(value) }
+}
+
+/// Split `.env` content into managed credentials and the remaining content
+///
+/// Credential lines are replaced with a comment so the file still documents where they went.
+pub fn split_env_file(content: &str) -> (Vec<(String, String)>, String) {
+    let mut found = Vec::new();
+    let mut remaining = Vec::new();
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        let entry = trimmed.strip_prefix("export ").unwrap_or(trimmed).split_once('=');
+        match entry {
+            Some((key, value)) if !trimmed.starts_with('#') && is_managed(key.trim()) => {
+                let key = key.trim().to_string();
+                let value = value.trim().trim_matches('"').trim_matches('\'').to_string();
+                if value.is_empty() {
+                    remaining.push(line.to_string());
+                    continue;
+                }
+                remaining
+                    .push(format!("# {} moved to the keychain by `ducktape keys migrate`", key));
+                found.push((key, value));
+            }
+            _ => remaining.push(line.to_string()),
+        }
+    }
+
+    let mut remaining = remaining.join("\n");
+    if content.ends_with('\n') {
+        remaining.push('\n');
+    }
+    (found, remaining)
+}
+
+/// Move the credentials in a `.env` file to the keychain and return their names
+pub fn migrate_env_file(path: &std::path::Path) -> Result<Vec<String>> {
+    let content = std::fs::read_to_string(path)?;
+    let (found, remaining) = split_env_file(&content);
+    for (key, value) in &found {
+        set(key, value)?;
+    }
+    if !found.is_empty() {
+        std::fs::write(path, remaining)?;
+    }
+    Ok(found.into_iter().map(|(key, _)| key).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_services() {
+        assert_eq!(KeyService::from_str("Grok").unwrap(), KeyService::Xai);
+        assert_eq!(KeyService::from_str("zoom").unwrap().vars().len(), 3);
+        assert!(KeyService::from_str("openai").is_err());
+        assert!(is_managed("DEEPSEEK_API_KEY"));
+        assert!(!is_managed("RUST_LOG"));
+    }
+
+    #[test]
+    fn test_split_env_file() {
+        let content = "# DuckTape settings\nXAI_API_KEY=\"xai-123\"\nRUST_LOG=debug\nexport ZOOM_CLIENT_ID=abc\nDEEPSEEK_API_KEY=\n";
+        let (found, remaining) = split_env_file(content);
+
+        assert_eq!(
+            found,
+            vec![
+                ("XAI_API_KEY".to_string(), "xai-123".to_string()),
+                ("ZOOM_CLIENT_ID".to_string(), "abc".to_string()),
+            ]
+        );
+        assert_eq!(
+            remaining,
+            "# DuckTape settings\n\
+             # XAI_API_KEY moved to the keychain by `ducktape keys migrate`\n\
+             RUST_LOG=debug\n\
+             # ZOOM_CLIENT_ID moved to the keychain by `ducktape keys migrate`\n\
+             DEEPSEEK_API_KEY=\n"
+        );
+
+        let (found, remaining) = split_env_file("# XAI_API_KEY=old\nRUST_LOG=info");
+        assert!(found.is_empty());
+        assert_eq!(remaining, "# XAI_API_KEY=old\nRUST_LOG=info");
+    }
+}
@@ -0,0 +1,286 @@
+//! First-run setup wizard behind `ducktape setup`.
+//
+// The wizard asks for a language model provider and its API key (stored with `secrets`),
+// offers the calendars and reminder lists Calendar.app and Reminders.app report as defaults,
+// and checks that DuckTape may control Calendar, Reminders and Notes. Answers are collected
+// in `SetupChoices` and written to config.toml only at the end, so an interrupted run leaves
+// the existing configuration alone.
+
+use crate::config::{Config, LLMProvider};
+use crate::secrets::KeyService;
+use anyhow::Result;
+use std::io::{IsTerminal, Write};
+
+/// Answers given in the wizard
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SetupChoices {
+    /// `None` selects Terminal Mode
+    pub provider: Option<LLMProvider>,
+    pub default_calendar: Option<String>,
+    pub default_list: Option<String>,
+}
+
+impl SetupChoices {
+    /// Write the answers into a configuration
+    pub fn apply(&self, config: &mut Config) {
+        config.language_model.provider = self.provider.clone();
+        // A single provider was chosen, so an older fallback chain would override it
+        config.nlp.providers.clear();
+        if let Some(calendar) = &self.default_calendar {
+            config.calendar.default_calendar = Some(calendar.clone());
+        }
+        if let Some(list) = &self.default_list {
+            config.todo.default_list = Some(list.clone());
+        }
+    }
+}
+
+/// Outcome of one permission check
+#[derive(Debug, Clone, PartialEq)]
+pub struct PermissionCheck {
+    pub app: &'static str,
+    pub error: Option<String>,
+}
+
+/// Interpret an answer to a numbered menu of `count` options
+///
+/// An empty answer picks `default`; otherwise the answer is a number from 1 to `count`.
+pub fn parse_choice(answer: &str, count: usize, default: usize) -> Option<usize> {
+    let answer = answer.trim();
+    if answer.is_empty() {
+        return Some(default);
+    }
+    match answer.parse::<usize>() {
+        Ok(n) if (1..=count).contains(&n) => Some(n - 1),
+        _ => None,
+    }
+}
+
+fn read_answer(prompt: &str) -> Option<String> {
+    print!("{}", prompt);
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    match std::io::stdin().read_line(&mut answer) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(answer.trim().to_string()),
+    }
+}
+
+fn ask_yes_no(prompt: &str, default: bool) -> bool {
+    let hint = if default { "[Y/n]" } else { "[y/N]" };
+    match read_answer(&format!("{} {} ", prompt, hint)).as_deref() {
+        Some("") | None => default,
+        Some(answer) => matches!(answer.to_lowercase().as_str(), "y" | "yes"),
+    }
+}
+
+/// Show a numbered menu and return the index of the chosen option
+fn choose(title: &str, options: &[String], default: usize) -> usize {
+    println!("{}", title);
+    for (i, option) in options.iter().enumerate() {
+        let marker = if i == default { " (default)" } else { "" };
+        println!("  {}. {}{}", i + 1, option, marker);
+    }
+    loop {
+        let Some(answer) = read_answer(&format!("Choose 1-{} [{}]: ", options.len(), default + 1))
+        else {
+            return default;
+        };
+        match parse_choice(&answer, options.len(), default) {
+            Some(index) => return index,
+            None => println!("Please enter a number from 1 to {}", options.len()),
+        }
+    }
+}
+
+/// Offer the names reported by an app, keeping the current default preselected
+fn choose_from_app(
+    what: &str,
+    names: &Result<Vec<String>>,
+    current: Option<&str>,
+) -> Option<String> {
+    let names = match names {
+        Ok(names) if !names.is_empty() => names,
+        Ok(_) => {
+            println!("No {}s found; keeping the current default", what);
+            return None;
+        }
+        Err(_) => {
+            println!("Could not read {}s; keeping the current default", what);
+            return None;
+        }
+    };
+    let default = current
+        .and_then(|current| names.iter().position(|n| n.eq_ignore_ascii_case(current)))
+        .unwrap_or(0);
+    let index = choose(&format!("Default {}:", what), names, default);
+    Some(names[index].clone())
+}
+
+/// Ask for a service's credentials, offering to keep ones that are already set
+fn ask_for_keys(service: KeyService) -> Result<()> {
+    let stored = service.vars().iter().all(|var| crate::secrets::get(var).is_some());
+    if stored
+        && !ask_yes_no(
+            &format!("{} credentials are already set. Replace them?", service.as_str()),
+            false,
+        )
+    {
+        return Ok(());
+    }
+    for var in service.vars() {
+        match crate::secrets::read_secret(&format!("{}: ", var)) {
+            Some(value) => {
+                crate::secrets::set(var, &value)?;
+                println!("Stored {} in the keychain", var);
+            }
+            None => println!(
+                "Skipped {}; add it later with `ducktape keys set {}`",
+                var,
+                service.as_str()
+            ),
+        }
+    }
+    Ok(())
+}
+
+/// Check that DuckTape may control Calendar, Reminders and Notes
+pub fn permission_checks(
+    calendars: &Result<Vec<String>>,
+    lists: &Result<Vec<String>>,
+    folders: &Result<Vec<String>>,
+) -> Vec<PermissionCheck> {
+    [("Calendar", calendars), ("Reminders", lists), ("Notes", folders)]
+        .into_iter()
+        .map(|(app, result)| PermissionCheck {
+            app,
+            error: result.as_ref().err().map(|e| e.to_string()),
+        })
+        .collect()
+}
+
+/// Run the wizard and save the resulting configuration
+pub async fn run_setup() -> Result<()> {
+    if !std::io::stdin().is_terminal() {
+        println!("ducktape setup is interactive; run it from a terminal");
+        return Ok(());
+    }
+
+    let mut config = Config::load_global()?;
+    let mut choices = SetupChoices {
+        provider: config.language_model.provider.clone(),
+        ..SetupChoices::default()
+    };
+    println!("Welcome to DuckTape! Press Enter to keep the suggested answer.");
+    println!();
+
+    // Language model
+    let providers = [
+        "Terminal Mode (no language model, commands only)".to_string(),
+        "Grok (X.AI)".to_string(),
+        "DeepSeek".to_string(),
+    ];
+    let current = match choices.provider {
+        None => 0,
+        Some(LLMProvider::Grok) => 1,
+        Some(LLMProvider::DeepSeek) => 2,
+    };
+    choices.provider = match choose("Language model provider:", &providers, current) {
+        1 => Some(LLMProvider::Grok),
+        2 => Some(LLMProvider::DeepSeek),
+        _ => None,
+    };
+    match choices.provider {
+        Some(LLMProvider::Grok) => ask_for_keys(KeyService::Xai)?,
+        Some(LLMProvider::DeepSeek) => ask_for_keys(KeyService::DeepSeek)?,
+        None => {}
+    }
+    if ask_yes_no("Set up Zoom credentials for online meetings?", false) {
+        ask_for_keys(KeyService::Zoom)?;
+    }
+    println!();
+
+    // Defaults, pulled from Calendar.app and Reminders.app
+    println!("Reading calendars and reminder lists (macOS may ask for permission)...");
+    let calendars = crate::calendar::get_available_calendars().await;
+    let lists = crate::todo::list_reminder_lists().await;
+    let folders = crate::notes::get_note_folders().await;
+    choices.default_calendar =
+        choose_from_app("calendar", &calendars, config.calendar.default_calendar.as_deref());
+    choices.default_list =
+        choose_from_app("reminder list", &lists, config.todo.default_list.as_deref());
+    println!();
+
+    // Permissions
+    println!("Automation permissions:");
+    let checks = permission_checks(&calendars, &lists, &folders);
+    for check in &checks {
+        match &check.error {
+            None => println!("  ✓ {}", check.app),
+            Some(e) => println!("  ✗ {}: {}", check.app, e),
+        }
+    }
+    if checks.iter().any(|c| c.error.is_some()) {
+        println!(
+            "Allow DuckTape (or your terminal) in System Settings → Privacy & Security → Automation, then run `ducktape setup` again."
+        );
+    }
+    println!();
+
+    choices.apply(&mut config);
+    config.save()?;
+    println!("Configuration saved.");
+    match &choices.provider {
+        Some(_) => println!("Type what you want to do, e.g. \"lunch with Anna tomorrow at noon\"."),
+        None => println!("Run `ducktape help` to see the available commands."),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_parse_choice() {
+        assert_eq!(parse_choice("", 3, 1), Some(1));
+        assert_eq!(parse_choice(" 3 ", 3, 0), Some(2));
+        assert_eq!(parse_choice("0", 3, 0), None);
+        assert_eq!(parse_choice("4", 3, 0), None);
+        assert_eq!(parse_choice("grok", 3, 0), None);
+    }
+
+    #[test]
+    fn test_apply_choices() {
+        let mut config = Config::default();
+        config.nlp.providers = vec!["grok".to_string(), "deepseek".to_string()];
+
+        SetupChoices {
+            provider: Some(LLMProvider::DeepSeek),
+            default_calendar: Some("Work".to_string()),
+            default_list: None,
+        }
+        .apply(&mut config);
+
+        assert_eq!(config.parser_chain(), vec!["deepseek"]);
+        assert_eq!(config.calendar.default_calendar.as_deref(), Some("Work"));
+        assert_eq!(config.todo.default_list.as_deref(), Some("Reminders"));
+
+        SetupChoices::default().apply(&mut config);
+        assert!(!config.uses_language_model());
+    }
+
+    #[test]
+    fn test_permission_checks() {
+        let checks = permission_checks(
+            &Ok(vec!["Work".to_string()]),
+            &Err(anyhow!("not authorized")),
+            &Ok(Vec::new()),
+        );
+        assert_eq!(checks.len(), 3);
+        assert!(checks[0].error.is_none());
+        assert_eq!(checks[1].app, "Reminders");
+        assert_eq!(checks[1].error.as_deref(), Some("not authorized"));
+    }
+}
@@ -0,0 +1,451 @@
+//! Apple Shortcuts integration: `ducktape shortcut <action>` and x-callback-url style URLs.
+//
+// Shortcuts runs DuckTape with "Run Shell Script", passing the shortcut's input on stdin
+// (`--stdin`, a JSON dictionary or `key=value` lines) or as `key=value` arguments. The
+// action's result is printed as JSON for "Get Dictionary from Input". The same actions can be
+// given as `ducktape://x-callback-url/<action>?key=value&x-success=...` with `--url`; the
+// result is then also sent to the x-success or x-error callback with `open`.
+
+use crate::calendar::EventConfig;
+use crate::dates::{parse_datetime, parse_time, resolve_date};
+use crate::state::CalendarItem;
+use anyhow::{Result, anyhow};
+use chrono::{Duration, Local, NaiveDate, NaiveTime};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use tracing::warn;
+
+/// Events returned by `search` unless `limit` says otherwise
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// What a shortcut asks DuckTape to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutAction {
+    CreateEvent,
+    Agenda,
+    Search,
+}
+
+impl ShortcutAction {
+    pub const ALL: [ShortcutAction; 3] =
+        [ShortcutAction::CreateEvent, ShortcutAction::Agenda, ShortcutAction::Search];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ShortcutAction::CreateEvent => "create-event",
+            ShortcutAction::Agenda => "agenda",
+            ShortcutAction::Search => "search",
+        }
+    }
+}
+
+impl FromStr for ShortcutAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "create-event" | "event" | "add-event" => Ok(ShortcutAction::CreateEvent),
+            "agenda" | "today" => Ok(ShortcutAction::Agenda),
+            "search" | "find" => Ok(ShortcutAction::Search),
+            other => Err(anyhow!(
+                "Unknown shortcut action: {} (expected create-event, agenda or search)",
+                other
+            )),
+        }
+    }
+}
+
+/// Named parameters of a shortcut, with keys lowercased and `-` read as `_`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ShortcutParams(BTreeMap<String, String>);
+
+impl ShortcutParams {
+    fn key(name: &str) -> String {
+        name.trim().to_lowercase().replace('-', "_")
+    }
+
+    pub fn insert(&mut self, name: &str, value: &str) {
+        self.0.insert(Self::key(name), value.trim().to_string());
+    }
+
+    /// The first non-empty value among `names`
+    pub fn get(&self, names: &[&str]) -> Option<&str> {
+        names
+            .iter()
+            .find_map(|name| self.0.get(&Self::key(name)))
+            .map(String::as_str)
+            .filter(|v| !v.is_empty())
+    }
+
+    /// Whether one of `names` is set to true, yes, 1 or on
+    pub fn is_set(&self, names: &[&str]) -> bool {
+        self.get(names)
+            .is_some_and(|v| matches!(v.to_lowercase().as_str(), "true" | "yes" | "1" | "on"))
+    }
+
+    /// Comma-separated values of one of `names`
+    pub fn list(&self, names: &[&str]) -> Vec<String> {
+        self.get(names)
+            .map(|v| v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Add `key=value` arguments
+    pub fn extend_args(&mut self, args: &[String]) -> Result<()> {
+        for arg in args {
+            let (name, value) = arg
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Expected key=value, got '{}'", arg))?;
+            self.insert(name, value);
+        }
+        Ok(())
+    }
+
+    /// Add the parameters in a shortcut's input: a JSON dictionary or `key=value` lines
+    pub fn extend_input(&mut self, input: &str) -> Result<()> {
+        let input = input.trim();
+        if input.starts_with('{') {
+            let object: serde_json::Map<String, Value> = serde_json::from_str(input)
+                .map_err(|e| anyhow!("Shortcut input is not a JSON dictionary: {}", e))?;
+            for (name, value) in object {
+                let value = match value {
+                    Value::Null => continue,
+                    Value::String(s) => s,
+                    Value::Array(items) => items
+                        .iter()
+                        .map(|item| item.as_str().map_or_else(|| item.to_string(), str::to_string))
+                        .collect::<Vec<_>>()
+                        .join(","),
+                    other => other.to_string(),
+                };
+                self.insert(&name, &value);
+            }
+            return Ok(());
+        }
+        for line in input.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let (name, value) = line
+                .split_once('=')
+                .or_else(|| line.split_once(':'))
+                .ok_or_else(|| anyhow!("Expected key=value, got '{}'", line))?;
+            self.insert(name, value);
+        }
+        Ok(())
+    }
+}
+
+/// The action and parameters of a `ducktape://` URL
+///
+/// Both `ducktape://x-callback-url/agenda?date=today` and `ducktape://agenda?date=today` work.
+pub fn parse_url(input: &str) -> Result<(ShortcutAction, ShortcutParams)> {
+    let url = url::Url::parse(input.trim()).map_err(|e| anyhow!("Invalid URL {}: {}", input, e))?;
+    if url.scheme() != "ducktape" {
+        return Err(anyhow!("Expected a ducktape:// URL, got {}", input));
+    }
+    let action = match url.host_str() {
+        Some("x-callback-url") | None => url.path().trim_matches('/').to_string(),
+        Some(host) => host.to_string(),
+    };
+    let mut params = ShortcutParams::default();
+    for (name, value) in url.query_pairs() {
+        params.insert(&name, &value);
+    }
+    Ok((action.parse()?, params))
+}
+
+/// The x-success or x-error URL to open after running a shortcut, if one was given
+pub fn callback_url(params: &ShortcutParams, result: &Result<Value>) -> Option<String> {
+    let (name, query) = match result {
+        Ok(value) => ("x-success", vec![("result", value.to_string())]),
+        Err(e) => {
+            ("x-error", vec![("errorCode", "1".to_string()), ("errorMessage", e.to_string())])
+        }
+    };
+    let mut url = url::Url::parse(params.get(&[name])?).ok()?;
+    url.query_pairs_mut().extend_pairs(query);
+    Some(url.to_string())
+}
+
+/// The event a `create-event` shortcut describes
+///
+/// `when` takes a phrase such as "tomorrow 3pm"; otherwise `date` (default today) and `time`
+/// are used. The end is `end`, or `duration` (minutes, or e.g. "1h30m") after the start.
+pub fn event_config(params: &ShortcutParams) -> Result<EventConfig> {
+    let title = params.get(&["title", "name"]).ok_or_else(|| anyhow!("A title is required"))?;
+    let title = crate::validation::title("title", title)?;
+    let all_day = params.is_set(&["all_day", "allday"]);
+
+    let (date, time) = match params.get(&["when"]) {
+        Some(when) => {
+            let parsed = parse_datetime(when, Local::now().naive_local())
+                .ok_or_else(|| anyhow!("Unrecognized date and time: {}", when))?;
+            (parsed.date.format("%Y-%m-%d").to_string(), parsed.time)
+        }
+        None => {
+            let date = resolve_date(params.get(&["date", "day"]).unwrap_or("today"))?;
+            let time = params
+                .get(&["time", "start", "start_time"])
+                .map(|t| parse_time(t).ok_or_else(|| anyhow!("Unrecognized time: {}", t)))
+                .transpose()?;
+            (date, time)
+        }
+    };
+    let start = match (time, all_day) {
+        (_, true) => NaiveTime::default(),
+        (Some(time), false) => time,
+        (None, false) => return Err(anyhow!("A time is required unless all_day is set")),
+    };
+
+    let mut config = EventConfig::new(&title, &date, &start.format("%H:%M").to_string());
+    config.all_day = all_day;
+    if !all_day {
+        if let Some(end) = params.get(&["end", "end_time"]) {
+            let end = parse_time(end).ok_or_else(|| anyhow!("Unrecognized end time: {}", end))?;
+            config.end_time = Some(end.format("%H:%M").to_string());
+        } else if let Some(minutes) = params.get(&["duration", "minutes"]) {
+            let minutes = i64::from(crate::validation::duration_minutes("duration", minutes)?);
+            let day = NaiveDate::parse_from_str(&date, "%Y-%m-%d")?;
+            let end = day.and_time(start) + Duration::minutes(minutes);
+            config.end_time = Some(end.format("%H:%M").to_string());
+            if end.date() != day {
+                config.end_date = Some(end.format("%Y-%m-%d").to_string());
+            }
+        }
+    }
+    config.calendars = params.list(&["calendar", "calendars"]);
+    config.location = params.get(&["location", "where"]).map(str::to_string);
+    config.description = params.get(&["notes", "description"]).map(str::to_string);
+    config.emails = params
+        .list(&["attendees", "emails", "invitees"])
+        .iter()
+        .map(|email| crate::validation::email(email))
+        .collect::<Result<_, _>>()?;
+    Ok(config)
+}
+
+/// Events whose title, location, description or calendar contains every word of `query`
+///
+/// Events before `from` are left out; the rest are sorted by start.
+pub fn search_items<'a>(
+    items: &'a [CalendarItem],
+    query: &str,
+    from: Option<NaiveDate>,
+) -> Vec<&'a CalendarItem> {
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    let mut found: Vec<&CalendarItem> = items
+        .iter()
+        .filter(|item| {
+            let Some(from) = from else { return true };
+            NaiveDate::parse_from_str(&item.date, "%Y-%m-%d").is_ok_and(|date| date >= from)
+        })
+        .filter(|item| {
+            let text = [
+                Some(item.title.as_str()),
+                item.location.as_deref(),
+                item.description.as_deref(),
+                Some(&item.calendars.join(" ")),
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+            words.iter().all(|word| text.contains(word.as_str()))
+        })
+        .collect();
+    found.sort_by(|a, b| (&a.date, &a.time).cmp(&(&b.date, &b.time)));
+    found
+}
+
+fn item_json(item: &CalendarItem) -> Value {
+    json!({
+        "title": item.title,
+        "date": item.date,
+        "time": if item.all_day { None } else { Some(&item.time) },
+        "end_time": item.end_time,
+        "all_day": item.all_day,
+        "calendars": item.calendars,
+        "location": item.location,
+        "description": item.description,
+    })
+}
+
+/// Run a shortcut action, returning its result as JSON
+pub async fn run(action: ShortcutAction, params: &ShortcutParams) -> Result<Value> {
+    match action {
+        ShortcutAction::CreateEvent => {
+            let config = event_config(params)?;
+            let calendars = config.calendars.clone();
+            let data = crate::webhooks::event_data(&config, &calendars);
+            crate::calendar::create_event(config).await?;
+            Ok(data)
+        }
+        ShortcutAction::Agenda => {
+            let date = resolve_date(params.get(&["date", "day"]).unwrap_or("today"))?;
+            let calendar = params.get(&["calendar"]).map(|name| {
+                crate::config::Config::load()
+                    .map(|config| config.calendar.resolve_calendar(name))
+                    .unwrap_or_else(|_| name.to_string())
+            });
+            let events = crate::calendar::fetch_agenda(&date, calendar.as_deref()).await?;
+            let text = crate::calendar::format_agenda(
+                &date,
+                &events,
+                &[],
+                &[],
+                &crate::theme::Colors::plain(),
+            );
+            let events: Vec<Value> = events
+                .iter()
+                .map(|e| {
+                    json!({
+                        "title": e.title,
+                        "start": e.start,
+                        "end": e.end,
+                        "calendar": e.calendar,
+                        "all_day": e.all_day,
+                    })
+                })
+                .collect();
+            Ok(json!({ "date": date, "events": events, "text": text.trim_end() }))
+        }
+        ShortcutAction::Search => {
+            let query = params
+                .get(&["query", "q", "text"])
+                .ok_or_else(|| anyhow!("A query is required"))?;
+            let limit = match params.get(&["limit"]) {
+                Some(limit) => limit.parse().map_err(|_| anyhow!("Invalid limit: {}", limit))?,
+                None => DEFAULT_SEARCH_LIMIT,
+            };
+            let from =
+                (!params.is_set(&["past", "include_past"])).then(|| Local::now().date_naive());
+            let items: Vec<CalendarItem> = crate::state::StateManager::new()?.load()?;
+            let events: Vec<Value> = search_items(&items, query, from)
+                .into_iter()
+                .take(limit)
+                .map(item_json)
+                .collect();
+
+            let mut result = json!({ "query": query, "events": events });
+            if params.is_set(&["notes", "include_notes"]) {
+                match crate::notes::search_notes(query).await {
+                    Ok(notes) => result["notes"] = serde_json::to_value(notes)?,
+                    Err(e) => warn!("Note search failed: {}", e),
+                }
+            }
+            Ok(result)
+        }
+    }
+}
+
+/// Open an x-callback URL, e.g. to return to the Shortcuts app
+pub async fn open_callback(url: &str) {
+    match tokio::process::Command::new("open").arg(url).status().await {
+        Ok(status) if status.success() => {}
+        Ok(status) => warn!("Opening callback {} failed with {}", url, status),
+        Err(e) => warn!("Failed to open callback {}: {}", url, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[&str]) -> ShortcutParams {
+        let mut params = ShortcutParams::default();
+        params
+            .extend_args(&pairs.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+            .unwrap();
+        params
+    }
+
+    #[test]
+    fn test_params_from_json_and_lines() -> Result<()> {
+        let mut json = ShortcutParams::default();
+        json.extend_input(
+            r#"{"Title": "Dentist", "all-day": true, "attendees": ["a@x.com", "b@x.com"], "x": null}"#,
+        )?;
+        assert_eq!(json.get(&["title"]), Some("Dentist"));
+        assert!(json.is_set(&["all_day"]));
+        assert_eq!(json.list(&["attendees"]), vec!["a@x.com", "b@x.com"]);
+        assert_eq!(json.get(&["x"]), None);
+
+        let mut lines = ShortcutParams::default();
+        lines.extend_input("title=Standup\ntime: 09:30\n\n")?;
+        assert_eq!(lines.get(&["title"]), Some("Standup"));
+        assert_eq!(lines.get(&["start", "time"]), Some("09:30"));
+        assert!(lines.extend_input("just text").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_url_and_callback() -> Result<()> {
+        let (action, params) = parse_url(
+            "ducktape://x-callback-url/create-event?title=Lunch%20with%20Sam&time=12:30&x-success=shortcuts://callback",
+        )?;
+        assert_eq!(action, ShortcutAction::CreateEvent);
+        assert_eq!(params.get(&["title"]), Some("Lunch with Sam"));
+
+        let callback = callback_url(&params, &Ok(json!({"ok": true}))).unwrap();
+        assert!(callback.starts_with("shortcuts://callback?result="));
+        // No x-error was given
+        assert_eq!(callback_url(&params, &Err(anyhow!("failed"))), None);
+
+        assert_eq!(parse_url("ducktape://agenda?date=today")?.0, ShortcutAction::Agenda);
+        assert!(parse_url("https://example.com/agenda").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_event_config_from_params() -> Result<()> {
+        let config = event_config(&params(&[
+            "title=Review",
+            "date=2025-04-22",
+            "time=3pm",
+            "duration=90",
+            "calendar=Work, Home",
+        ]))?;
+        assert_eq!(config.start_date, "2025-04-22");
+        assert_eq!(config.start_time, "15:00");
+        assert_eq!(config.end_time.as_deref(), Some("16:30"));
+        assert_eq!(config.calendars, vec!["Work", "Home"]);
+
+        let late = event_config(&params(&[
+            "title=Deploy",
+            "date=2025-04-22",
+            "time=23:30",
+            "duration=60",
+        ]))?;
+        assert_eq!(late.end_date.as_deref(), Some("2025-04-23"));
+
+        let all_day = event_config(&params(&["title=Offsite", "date=2025-05-01", "all_day=yes"]))?;
+        assert!(all_day.all_day);
+        assert!(event_config(&params(&["title=No time", "date=2025-05-01"])).is_err());
+        assert!(event_config(&params(&["time=10:00"])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_items() {
+        let item = |title: &str, date: &str, location: Option<&str>| CalendarItem {
+            title: title.to_string(),
+            date: date.to_string(),
+            time: "10:00".to_string(),
+            calendars: vec!["Work".to_string()],
+            location: location.map(str::to_string),
+            ..Default::default()
+        };
+        let items = vec![
+            item("Design review", "2025-04-24", Some("Room 2")),
+            item("Dentist", "2025-04-20", None),
+            item("Design sync", "2025-04-21", None),
+        ];
+        let titles = |found: Vec<&CalendarItem>| {
+            found.into_iter().map(|i| i.title.clone()).collect::<Vec<_>>()
+        };
+        assert_eq!(titles(search_items(&items, "design", None)), ["Design sync", "Design review"]);
+        assert_eq!(titles(search_items(&items, "room design", None)), ["Design review"]);
+        let from = NaiveDate::from_ymd_opt(2025, 4, 22);
+        assert_eq!(titles(search_items(&items, "DESIGN", from)), ["Design review"]);
+    }
+}
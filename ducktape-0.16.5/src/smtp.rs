@@ -0,0 +1,151 @@
+//! Emailing invitations through an SMTP account.
+//
+// Where neither the calendar nor Mail.app can send an invitation, for example with the .ics
+// backend on Linux, DuckTape sends it itself through the account in the `[smtp]` section of
+// config.toml. The message carries the event both as a `text/calendar; method=REQUEST` part,
+// which mail clients show as an invitation with accept and decline buttons, and as an
+// `invite.ics` attachment for those that do not.
+
+use crate::config::{SmtpConfig, SmtpSecurity};
+use anyhow::{Context, Result, anyhow};
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use tracing::debug;
+
+/// Credential holding the SMTP password
+pub const PASSWORD_VAR: &str = "SMTP_PASSWORD";
+
+/// Whether `[smtp]` names a server
+pub fn is_configured(settings: &SmtpConfig) -> bool {
+    settings.host.as_deref().is_some_and(|host| !host.trim().is_empty())
+}
+
+/// The sender of invitations: `from`, or else the username
+pub fn sender(settings: &SmtpConfig) -> Result<Mailbox> {
+    let from = settings.from.as_deref().or(settings.username.as_deref()).ok_or_else(|| {
+        anyhow!("Set smtp.from or smtp.username in config.toml to email invitations")
+    })?;
+    from.trim().parse().map_err(|e| anyhow!("Invalid smtp.from '{}': {}", from, e))
+}
+
+/// The message inviting `to` to the event in `ics`
+pub fn invitation_message(
+    from: Mailbox,
+    to: &[&str],
+    subject: &str,
+    body: &str,
+    ics: &str,
+) -> Result<Message> {
+    let mut builder = Message::builder().from(from).subject(subject);
+    for address in to {
+        let mailbox: Mailbox = address
+            .trim()
+            .parse()
+            .map_err(|e| anyhow!("Invalid attendee address '{}': {}", address, e))?;
+        builder = builder.to(mailbox);
+    }
+    let request = ContentType::parse("text/calendar; charset=utf-8; method=REQUEST")?;
+    let attachment = ContentType::parse("application/ics")?;
+    let message = builder.multipart(
+        MultiPart::mixed()
+            .multipart(
+                MultiPart::alternative()
+                    .singlepart(SinglePart::plain(body.to_string()))
+                    .singlepart(SinglePart::builder().header(request).body(ics.to_string())),
+            )
+            .singlepart(
+                Attachment::new("invite.ics".to_string()).body(ics.to_string(), attachment),
+            ),
+    )?;
+    Ok(message)
+}
+
+/// Send an invitation to `to` through the account in `settings`
+pub async fn send_invitation(
+    settings: &SmtpConfig,
+    to: &[&str],
+    subject: &str,
+    body: &str,
+    ics: &str,
+) -> Result<()> {
+    let host = settings
+        .host
+        .as_deref()
+        .filter(|host| !host.trim().is_empty())
+        .ok_or_else(|| anyhow!("Set smtp.host in config.toml to email invitations over SMTP"))?
+        .trim();
+    let message = invitation_message(sender(settings)?, to, subject, body, ics)?;
+
+    let mut transport = match settings.security {
+        SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?,
+        SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+        SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+    };
+    if let Some(port) = settings.port {
+        transport = transport.port(port);
+    }
+    if let Some(username) = &settings.username {
+        let password = crate::secrets::get(PASSWORD_VAR).ok_or_else(|| {
+            anyhow!(
+                "{} not found in environment or keychain; store it with `ducktape keys set smtp`",
+                PASSWORD_VAR
+            )
+        })?;
+        transport = transport.credentials(Credentials::new(username.clone(), password));
+    }
+
+    transport
+        .build()
+        .send(message)
+        .await
+        .with_context(|| format!("Failed to send the invitation through {}", host))?;
+    debug!("Invitation '{}' sent through {} to {}", subject, host, to.join(", "));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sender() {
+        let mut settings = SmtpConfig {
+            host: Some("smtp.example.com".to_string()),
+            username: Some("jane@example.com".to_string()),
+            ..SmtpConfig::default()
+        };
+        assert!(is_configured(&settings));
+        assert_eq!(sender(&settings).unwrap().email.to_string(), "jane@example.com");
+
+        settings.from = Some("Jane Doe <jane.doe@example.com>".to_string());
+        let from = sender(&settings).unwrap();
+        assert_eq!(from.name.as_deref(), Some("Jane Doe"));
+        assert_eq!(from.email.to_string(), "jane.doe@example.com");
+
+        assert!(!is_configured(&SmtpConfig::default()));
+        assert!(sender(&SmtpConfig::default()).is_err());
+    }
+
+    #[test]
+    fn test_invitation_message() {
+        let from: Mailbox = "jane@example.com".parse().unwrap();
+        let ics = "BEGIN:VCALENDAR\r\nMETHOD:REQUEST\r\nEND:VCALENDAR\r\n";
+        let message = invitation_message(
+            from,
+            &["bob@example.com", "ann@example.com"],
+            "Invitation: Standup",
+            "You are invited to Standup.",
+            ics,
+        )
+        .unwrap();
+        let formatted = String::from_utf8(message.formatted()).unwrap();
+        assert!(formatted.contains("To: bob@example.com, ann@example.com"));
+        assert!(formatted.contains("text/calendar; charset=utf-8; method=REQUEST"));
+        assert!(formatted.contains("filename=\"invite.ics\""));
+
+        let from: Mailbox = "jane@example.com".parse().unwrap();
+        assert!(invitation_message(from, &["not an address"], "x", "y", ics).is_err());
+    }
+}
@@ -1,17 +1,64 @@
+//! Items DuckTape remembers between runs, kept as JSON files in ~/.ducktape.
+//
+// Each file holds `{"version": N, "items": [...]}`. Older files are migrated when read, and
+// files from a newer DuckTape are refused rather than overwritten. `ducktape state export`
+// and `import` move everything between machines in one JSON document.
+
 use anyhow::{Result, anyhow};
 use chrono::{DateTime, Duration, Local};
+use fs2::FileExt;
 use serde::{Deserialize, Serialize};
-use std::fs::{File, OpenOptions};
-use std::io::Read;
-use std::io::{BufReader, BufWriter};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::path::PathBuf;
+use tracing::debug;
+use utoipa::ToSchema;
+
+/// Schema version of the state files written by this version
+pub const STATE_VERSION: u32 = 1;
 
 const STATE_DIR: &str = ".ducktape";
 const TODOS_FILE: &str = "todos.json";
 const EVENTS_FILE: &str = "events.json";
 const NOTES_FILE: &str = "notes.json";
+const CONTACTS_FILE: &str = "contacts.json";
+const LINKS_FILE: &str = "links.json";
+const NOTIFICATIONS_FILE: &str = "notifications.json";
+const LLM_CACHE_FILE: &str = "llm_cache.json";
+const USAGE_FILE: &str = "usage.json";
+const BIRTHDAYS_FILE: &str = "birthdays.json";
+const FOCUS_FILE: &str = "focus.json";
+const SCHEDULE_FILE: &str = "schedule.json";
+const ADDRESS_BOOK_FILE: &str = "address_book.json";
+const LAST_COMMAND_FILE: &str = "last_command.json";
+const SYNC_BASE_FILE: &str = "sync_base.json";
+const CONTEXT_FILE: &str = "context.json";
+const LOCK_FILE: &str = ".state.lock";
 // Maximum allowed size for state files to prevent DoS attacks (10MB)
 const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+// Maximum number of items in a state file
+const MAX_ITEMS: usize = 10000;
+
+/// Every file kept by `StateManager`
+pub const STATE_FILES: [&str; 15] = [
+    TODOS_FILE,
+    EVENTS_FILE,
+    NOTES_FILE,
+    CONTACTS_FILE,
+    LINKS_FILE,
+    NOTIFICATIONS_FILE,
+    LLM_CACHE_FILE,
+    USAGE_FILE,
+    BIRTHDAYS_FILE,
+    FOCUS_FILE,
+    SCHEDULE_FILE,
+    ADDRESS_BOOK_FILE,
+    LAST_COMMAND_FILE,
+    SYNC_BASE_FILE,
+    CONTEXT_FILE,
+];
 
 // Trait for items that can be persisted
 pub trait Persistent: Sized + Serialize + for<'de> Deserialize<'de> {
@@ -28,7 +75,7 @@ pub struct TodoItem {
 }
 
 // Make the structs public and cloneable
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct CalendarItem {
     pub title: String,
     pub date: String,
@@ -39,6 +86,19 @@ pub struct CalendarItem {
     pub description: Option<String>,
     pub email: Option<String>,
     pub reminder: Option<i32>,
+    /// Calendar.app event UID, when known
+    #[serde(default)]
+    pub uid: Option<String>,
+    #[serde(default)]
+    pub end_time: Option<String>,
+    /// RFC 5545 recurrence rule (e.g. "FREQ=WEEKLY;INTERVAL=1")
+    #[serde(default)]
+    pub recurrence: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    /// busy, free, tentative or unavailable, when set explicitly
+    #[serde(default)]
+    pub availability: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -49,6 +109,171 @@ pub struct NoteItem {
     pub created_at: String,
 }
 
+/// A contact name that has been resolved (and possibly disambiguated) to a person
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ContactCacheItem {
+    /// The name as typed by the user, lowercased
+    pub query: String,
+    /// The full name of the matched person in Contacts.app
+    pub name: String,
+    pub emails: Vec<String>,
+    pub resolved_at: String,
+}
+
+/// A person copied from Contacts.app by `contacts sync`, see `address_book`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AddressBookEntry {
+    /// Contacts.app's id of the person
+    pub id: String,
+    pub name: String,
+    pub emails: Vec<String>,
+    #[serde(default)]
+    pub phones: Vec<String>,
+    /// YYYY-MM-DD; the year is 1604 when Contacts.app has none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub birthday: Option<String>,
+    /// When the person last changed in Contacts.app, YYYY-MM-DDTHH:MM:SS local time
+    pub modified_at: String,
+}
+
+/// The last command made from natural language, for `redo` and `edit-last`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LastCommand {
+    /// What was typed, e.g. `lunch with Bob tomorrow at noon`
+    pub input: String,
+    /// Command line without the leading `ducktape`
+    pub command: String,
+    pub created_at: String,
+}
+
+/// An event as both sides agreed on it at the last `sync`, see `calendar_sync`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SyncBaseEntry {
+    /// Calendar.app event UID
+    pub uid: String,
+    /// The synced fields of the event, see `calendar_sync::fingerprint`
+    pub fingerprint: String,
+    pub synced_at: String,
+}
+
+/// The item the last command created or changed, for follow-ups like "make it 3pm"
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RecentItem {
+    pub kind: LinkedKind,
+    /// UID of an event, or its title when it has none; title of a todo
+    pub id: String,
+    pub title: String,
+    /// Date and times of an event (`2025-05-06 12:00-13:00`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<String>,
+    /// Calendar of an event, or list of a todo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+    pub updated_at: String,
+}
+
+/// The kind of item on either end of a link
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkedKind {
+    Todo,
+    Event,
+}
+
+/// One end of a link, identified by kind and title
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LinkTarget {
+    pub kind: LinkedKind,
+    pub title: String,
+}
+
+/// How the source of a link relates to its target
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinkRelation {
+    #[default]
+    RelatesTo,
+    BlockedBy,
+}
+
+/// A link between two items, e.g. a prep task and the meeting it is for
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ItemLink {
+    pub source: LinkTarget,
+    pub target: LinkTarget,
+    #[serde(default)]
+    pub relation: LinkRelation,
+    pub created_at: String,
+}
+
+/// A reminder or event surfaced by a notification or digest
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NotificationRecord {
+    /// Stable key of the item, see `notifications::item_key`
+    pub key: String,
+    /// The subsystem that surfaced it, e.g. "watch" or "digest"
+    pub source: String,
+    pub surfaced_at: String,
+}
+
+/// A command a language model generated for an input, see `parser::cache`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LlmCacheEntry {
+    /// Provider, model and normalized input, see `parser::cache::cache_key`
+    pub key: String,
+    pub command: String,
+    pub created_at: String,
+}
+
+/// Language model tokens used on one day with one provider and model, see `usage`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct UsageRecord {
+    /// YYYY-MM-DD, local time
+    pub date: String,
+    pub provider: String,
+    pub model: String,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// Estimated cost in USD
+    pub cost_usd: f64,
+}
+
+/// A birthday or anniversary event created by `birthdays sync`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BirthdayRecord {
+    /// See `birthdays::ContactDate::key`
+    pub key: String,
+    pub title: String,
+    /// The calendar asked for; `None` for the default calendar
+    pub calendar: Option<String>,
+    pub created_at: String,
+}
+
+/// A focus session that ended, see `focus`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FocusSession {
+    pub title: String,
+    pub started_at: String,
+    pub planned_minutes: u32,
+    pub focused_minutes: u32,
+    /// Ran to the end rather than being stopped
+    pub completed: bool,
+}
+
+/// A command run on a schedule, see `schedule`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ScheduledCommand {
+    pub id: u32,
+    /// Command line without the leading `ducktape`, e.g. `agenda`
+    pub command: String,
+    /// When it runs, e.g. `07:30 daily` or a cron expression such as `30 7 * * 1-5`
+    pub schedule: String,
+    pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_run: Option<String>,
+}
+
 impl Persistent for TodoItem {
     fn filename() -> &'static str {
         TODOS_FILE
@@ -67,6 +292,162 @@ impl Persistent for NoteItem {
     }
 }
 
+impl Persistent for ContactCacheItem {
+    fn filename() -> &'static str {
+        CONTACTS_FILE
+    }
+}
+
+impl Persistent for ItemLink {
+    fn filename() -> &'static str {
+        LINKS_FILE
+    }
+}
+
+impl Persistent for NotificationRecord {
+    fn filename() -> &'static str {
+        NOTIFICATIONS_FILE
+    }
+}
+
+impl Persistent for LlmCacheEntry {
+    fn filename() -> &'static str {
+        LLM_CACHE_FILE
+    }
+}
+
+impl Persistent for UsageRecord {
+    fn filename() -> &'static str {
+        USAGE_FILE
+    }
+}
+
+impl Persistent for BirthdayRecord {
+    fn filename() -> &'static str {
+        BIRTHDAYS_FILE
+    }
+}
+
+impl Persistent for FocusSession {
+    fn filename() -> &'static str {
+        FOCUS_FILE
+    }
+}
+
+impl Persistent for ScheduledCommand {
+    fn filename() -> &'static str {
+        SCHEDULE_FILE
+    }
+}
+
+impl Persistent for AddressBookEntry {
+    fn filename() -> &'static str {
+        ADDRESS_BOOK_FILE
+    }
+}
+
+impl Persistent for LastCommand {
+    fn filename() -> &'static str {
+        LAST_COMMAND_FILE
+    }
+}
+
+impl Persistent for SyncBaseEntry {
+    fn filename() -> &'static str {
+        SYNC_BASE_FILE
+    }
+}
+
+impl Persistent for RecentItem {
+    fn filename() -> &'static str {
+        CONTEXT_FILE
+    }
+}
+
+/// Rewrites the items of one state file
+type MigrateItems = fn(filename: &str, items: &mut Vec<Value>) -> Result<()>;
+
+/// A change to the layout of the state files
+struct Migration {
+    /// Schema version the files have after this migration
+    to: u32,
+    description: &'static str,
+    /// Rewrite the items of a file; `None` when only the file layout changed
+    apply: Option<MigrateItems>,
+}
+
+/// Migrations in order; the last one's `to` is `STATE_VERSION`
+const MIGRATIONS: &[Migration] =
+    &[Migration { to: 1, description: "wrap item arrays in a versioned file", apply: None }];
+
+/// The on-disk form of a state file
+#[derive(Serialize)]
+struct StateFile<'a, T> {
+    version: u32,
+    items: &'a [T],
+}
+
+/// Everything in the state directory, as written by `ducktape state export`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StateExport {
+    /// Schema version of the items
+    pub version: u32,
+    pub exported_at: String,
+    /// Items per state file, e.g. `events.json`
+    pub files: BTreeMap<String, Vec<Value>>,
+}
+
+/// Bring items written at schema `version` up to `STATE_VERSION`
+pub fn migrate(filename: &str, version: u32, mut items: Vec<Value>) -> Result<Vec<Value>> {
+    if version > STATE_VERSION {
+        return Err(anyhow!(
+            "{} was written by a newer DuckTape (schema {}, this version reads up to {})",
+            filename,
+            version,
+            STATE_VERSION
+        ));
+    }
+    for migration in MIGRATIONS.iter().filter(|m| m.to > version) {
+        debug!("Migrating {} to schema {}: {}", filename, migration.to, migration.description);
+        if let Some(apply) = migration.apply {
+            apply(filename, &mut items)?;
+        }
+    }
+    Ok(items)
+}
+
+/// Check that items parse as what `filename` holds
+fn check_items(filename: &str, items: &[Value]) -> Result<()> {
+    fn check<T: Persistent>(items: &[Value]) -> Result<()> {
+        serde_json::from_value::<Vec<T>>(Value::Array(items.to_vec()))
+            .map(|_| ())
+            .map_err(|e| anyhow!("Invalid items for {}: {}", T::filename(), e))
+    }
+    match filename {
+        TODOS_FILE => check::<TodoItem>(items),
+        EVENTS_FILE => check::<CalendarItem>(items),
+        NOTES_FILE => check::<NoteItem>(items),
+        CONTACTS_FILE => check::<ContactCacheItem>(items),
+        LINKS_FILE => check::<ItemLink>(items),
+        NOTIFICATIONS_FILE => check::<NotificationRecord>(items),
+        LLM_CACHE_FILE => check::<LlmCacheEntry>(items),
+        USAGE_FILE => check::<UsageRecord>(items),
+        BIRTHDAYS_FILE => check::<BirthdayRecord>(items),
+        FOCUS_FILE => check::<FocusSession>(items),
+        SCHEDULE_FILE => check::<ScheduledCommand>(items),
+        ADDRESS_BOOK_FILE => check::<AddressBookEntry>(items),
+        LAST_COMMAND_FILE => check::<LastCommand>(items),
+        SYNC_BASE_FILE => check::<SyncBaseEntry>(items),
+        CONTEXT_FILE => check::<RecentItem>(items),
+        _ => Err(anyhow!("Unknown state file: {}", filename)),
+    }
+}
+
+/// Reads and writes the JSON files in ~/.ducktape
+///
+/// Files carry a schema version and are migrated when read. Writes go to a temporary file
+/// that replaces the old one, and a lock on the directory keeps the terminal and the API
+/// server from interleaving reads and writes.
 pub struct StateManager {
     state_dir: PathBuf,
 }
@@ -74,83 +455,237 @@ pub struct StateManager {
 impl StateManager {
     pub fn new() -> Result<Self> {
         let home_dir = dirs::home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
-        let mut state_dir = home_dir;
-        state_dir.push(STATE_DIR);
-        std::fs::create_dir_all(&state_dir)?;
+        Self::with_dir(home_dir.join(STATE_DIR))
+    }
+
+    /// Keep the state in `dir` instead of ~/.ducktape
+    pub fn with_dir(dir: impl Into<PathBuf>) -> Result<Self> {
+        let state_dir = dir.into();
+        fs::create_dir_all(&state_dir)?;
         Ok(Self { state_dir })
     }
 
-    pub fn load<T: Persistent>(&self) -> Result<Vec<T>> {
-        let path = self.state_dir.join(T::filename());
-        if path.exists() {
-            // Check file size before loading to prevent DoS attacks
-            let metadata = std::fs::metadata(&path)?;
-            if metadata.len() > MAX_FILE_SIZE {
-                return Err(anyhow!("File size exceeds security limits"));
+    /// Lock the state directory until the returned file is dropped
+    fn lock(&self, exclusive: bool) -> Result<File> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(self.state_dir.join(LOCK_FILE))?;
+        let locked = if exclusive { file.lock_exclusive() } else { FileExt::lock_shared(&file) };
+        locked.map_err(|e| anyhow!("Failed to lock {}: {}", self.state_dir.display(), e))?;
+        Ok(file)
+    }
+
+    /// The items of a file, migrated to the current schema
+    fn read_items(&self, filename: &str) -> Result<Vec<Value>> {
+        let path = self.state_dir.join(filename);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        // Check file size before loading to prevent DoS attacks
+        if fs::metadata(&path)?.len() > MAX_FILE_SIZE {
+            return Err(anyhow!("File {} exceeds security limits", filename));
+        }
+        let reader = BufReader::new(File::open(&path)?);
+        let value: Value = serde_json::from_reader(reader)
+            .map_err(|e| anyhow!("Failed to parse {}: {}", filename, e))?;
+
+        // Files written before schema versions were introduced are bare arrays
+        let (version, items) = match value {
+            Value::Array(items) => (0, items),
+            Value::Object(mut file) => {
+                let version = file
+                    .get("version")
+                    .and_then(Value::as_u64)
+                    .ok_or_else(|| anyhow!("{} has no schema version", filename))?;
+                match file.remove("items") {
+                    Some(Value::Array(items)) => (version as u32, items),
+                    _ => return Err(anyhow!("{} has no items", filename)),
+                }
             }
+            _ => return Err(anyhow!("{} is not a DuckTape state file", filename)),
+        };
+        // Count elements to prevent DoS attacks
+        if items.len() > MAX_ITEMS {
+            return Err(anyhow!("Too many items in {} (maximum {})", filename, MAX_ITEMS));
+        }
+        migrate(filename, version, items)
+    }
 
-            let file = File::open(path)?;
-            let reader = BufReader::new(file);
+    /// Replace a file with `items` at the current schema version
+    fn write_items<T: Serialize>(&self, filename: &str, items: &[T], pretty: bool) -> Result<()> {
+        let path = self.state_dir.join(filename);
+        let temp = self.state_dir.join(format!(".{}.{}.tmp", filename, std::process::id()));
+        let file = StateFile { version: STATE_VERSION, items };
+        let write = || -> Result<()> {
+            let mut writer = BufWriter::new(File::create(&temp)?);
+            if pretty {
+                serde_json::to_writer_pretty(&mut writer, &file)?;
+            } else {
+                serde_json::to_writer(&mut writer, &file)?;
+            }
+            writer.flush()?;
+            writer.get_ref().sync_all()?;
+            Ok(())
+        };
+        if let Err(e) = write().and_then(|()| fs::rename(&temp, &path).map_err(Into::into)) {
+            let _ = fs::remove_file(&temp);
+            return Err(e.context(format!("Failed to write {}", path.display())));
+        }
+        Ok(())
+    }
 
-            // Use the from_reader function with proper security limits
-            let json_value: serde_json::Value = serde_json::from_reader(reader)
-                .map_err(|e| anyhow!("Failed to parse JSON data: {}", e))?;
+    pub fn load<T: Persistent>(&self) -> Result<Vec<T>> {
+        let _lock = self.lock(false)?;
+        let items = self.read_items(T::filename())?;
+        serde_json::from_value(Value::Array(items))
+            .map_err(|e| anyhow!("Failed to deserialize {}: {}", T::filename(), e))
+    }
 
-            // Count elements to prevent DoS attacks
-            if let Some(array) = json_value.as_array() {
-                if array.len() > 10000 {
-                    return Err(anyhow!("Too many items in file (maximum 10000)"));
-                }
+    pub fn save<T: Persistent>(&self, items: &[T]) -> Result<()> {
+        let _lock = self.lock(true)?;
+        self.write_items(T::filename(), items, true)
+    }
+
+    /// Load, change and save items without another process writing in between
+    pub fn update<T: Persistent, R>(&self, change: impl FnOnce(&mut Vec<T>) -> R) -> Result<R> {
+        let _lock = self.lock(true)?;
+        let mut items: Vec<T> =
+            serde_json::from_value(Value::Array(self.read_items(T::filename())?))
+                .map_err(|e| anyhow!("Failed to deserialize {}: {}", T::filename(), e))?;
+        let result = change(&mut items);
+        self.write_items(T::filename(), &items, true)?;
+        Ok(result)
+    }
+
+    pub fn add<T: Persistent>(&self, item: T) -> Result<()> {
+        self.update(|items: &mut Vec<T>| items.push(item))
+    }
+
+    /// When the file of `T` was last written, if it exists
+    pub fn last_written<T: Persistent>(&self) -> Option<std::time::SystemTime> {
+        fs::metadata(self.state_dir.join(T::filename())).and_then(|m| m.modified()).ok()
+    }
+
+    /// Every state file's items, for a backup or another machine
+    pub fn export(&self) -> Result<StateExport> {
+        let _lock = self.lock(false)?;
+        let mut files = BTreeMap::new();
+        for filename in STATE_FILES {
+            let items = self.read_items(filename)?;
+            if !items.is_empty() {
+                files.insert(filename.to_string(), items);
             }
+        }
+        Ok(StateExport { version: STATE_VERSION, exported_at: Local::now().to_rfc3339(), files })
+    }
 
-            // Convert to the desired type
-            let items: Vec<T> = serde_json::from_value(json_value)
-                .map_err(|e| anyhow!("Failed to deserialize data: {}", e))?;
+    /// Replace the files in an export with its items; returns the number of items written
+    ///
+    /// Everything is migrated and checked before the first file is written. Files missing
+    /// from the export are left alone.
+    pub fn import(&self, export: StateExport) -> Result<usize> {
+        let mut files = Vec::new();
+        for (filename, items) in export.files {
+            let items = migrate(&filename, export.version, items)?;
+            check_items(&filename, &items)?;
+            files.push((filename, items));
+        }
 
-            Ok(items)
-        } else {
-            Ok(Vec::new())
+        let _lock = self.lock(true)?;
+        let mut count = 0;
+        for (filename, items) in &files {
+            self.write_items(filename, items, true)?;
+            count += items.len();
         }
+        Ok(count)
     }
 
-    pub fn save<T: Persistent>(&self, items: &[T]) -> Result<()> {
-        let path = self.state_dir.join(T::filename());
-        let file = OpenOptions::new().write(true).create(true).truncate(true).open(path)?;
+    /// Append a line to a JSON Lines log such as `audit::AUDIT_FILE`
+    ///
+    /// Once the log has reached `max_bytes` it is moved to `<filename>.1`, older copies to
+    /// `.2`, `.3` and so on, keeping `keep` of them. Logs are not part of `export`.
+    pub fn append_line(
+        &self,
+        filename: &str,
+        line: &str,
+        max_bytes: u64,
+        keep: usize,
+    ) -> Result<()> {
+        let _lock = self.lock(true)?;
+        let path = self.state_dir.join(filename);
+        if fs::metadata(&path).map(|m| m.len() >= max_bytes).unwrap_or(false) {
+            self.rotate(filename, keep)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        writeln!(file, "{}", line.trim_end())
+            .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))
+    }
 
-        let writer = BufWriter::new(file);
-        serde_json::to_writer_pretty(writer, items)?;
+    /// Shift `<filename>`, `<filename>.1`, ... up by one, dropping those past `keep`
+    fn rotate(&self, filename: &str, keep: usize) -> Result<()> {
+        let rotated = |n: usize| self.state_dir.join(format!("{}.{}", filename, n));
+        let _ = fs::remove_file(rotated(keep.max(1)));
+        for n in (1..keep).rev() {
+            if rotated(n).exists() {
+                fs::rename(rotated(n), rotated(n + 1))?;
+            }
+        }
+        if keep == 0 {
+            fs::remove_file(self.state_dir.join(filename))?;
+        } else {
+            fs::rename(self.state_dir.join(filename), rotated(1))?;
+        }
+        debug!("Rotated {}", filename);
         Ok(())
     }
 
-    pub fn add<T: Persistent>(&self, item: T) -> Result<()> {
-        let mut items = self.load::<T>()?;
-        items.push(item);
-        self.save(&items)
+    /// Lines of a log and its rotated copies, oldest first
+    pub fn read_lines(&self, filename: &str) -> Result<Vec<String>> {
+        let _lock = self.lock(false)?;
+        let mut paths = vec![self.state_dir.join(filename)];
+        for n in 1.. {
+            let path = self.state_dir.join(format!("{}.{}", filename, n));
+            if !path.exists() {
+                break;
+            }
+            paths.push(path);
+        }
+        let mut lines = Vec::new();
+        for path in paths.iter().rev().filter(|path| path.exists()) {
+            for line in BufReader::new(File::open(path)?).lines() {
+                let line = line?;
+                if !line.trim().is_empty() {
+                    lines.push(line);
+                }
+            }
+        }
+        Ok(lines)
     }
 
     #[allow(dead_code)]
     pub fn cleanup_old_items(&self) -> Result<()> {
-        // Clean up old calendar events
-        let mut events: Vec<CalendarItem> = self.load()?;
         let now = Local::now();
-        events.retain(|event| {
-            if let Ok(event_date) = DateTime::parse_from_str(
-                &format!("{} {}", event.date, event.time),
-                "%Y-%m-%d %H:%M",
-            ) {
-                event_date > now
-            } else {
-                true // Keep events with invalid dates
-            }
-        });
-        self.save(&events)?;
+        // Clean up old calendar events
+        self.update(|events: &mut Vec<CalendarItem>| {
+            events.retain(|event| {
+                if let Ok(event_date) = DateTime::parse_from_str(
+                    &format!("{} {}", event.date, event.time),
+                    "%Y-%m-%d %H:%M",
+                ) {
+                    event_date > now
+                } else {
+                    true // Keep events with invalid dates
+                }
+            })
+        })?;
 
         // Clean up old todos
-        let todos: Vec<TodoItem> = self.load()?;
         let one_month_ago = now - Duration::days(30);
-        let todos: Vec<_> = todos
-            .into_iter()
-            .filter(|todo| {
+        self.update(|todos: &mut Vec<TodoItem>| {
+            todos.retain(|todo| {
                 if let Some(time) = &todo.reminder_time {
                     if let Ok(todo_date) = DateTime::parse_from_str(time, "%Y-%m-%d %H:%M") {
                         return todo_date > one_month_ago;
@@ -158,43 +693,19 @@ impl StateManager {
                 }
                 true // Keep todos without dates
             })
-            .collect();
-        self.save(&todos)?;
+        })?;
 
         Ok(())
     }
 
     #[allow(dead_code)]
     pub fn vacuum(&self) -> Result<()> {
-        // Compact JSON files by removing whitespace
-        for filename in &[TODOS_FILE, EVENTS_FILE, NOTES_FILE] {
-            let path = self.state_dir.join(filename);
-            if path.exists() {
-                // Check file size before loading to prevent DoS attacks
-                let metadata = std::fs::metadata(&path)?;
-                if metadata.len() > MAX_FILE_SIZE {
-                    return Err(anyhow!("File {} exceeds security limits", filename));
-                }
-
-                // Read file content with size limits
-                let mut file = File::open(&path)?;
-                let mut content = Vec::with_capacity(metadata.len() as usize);
-                file.read_to_end(&mut content)?;
-
-                // Use a depth limit for JSON parsing
-                let items: serde_json::Value = serde_json::from_slice(&content)
-                    .map_err(|e| anyhow!("Error parsing {}: {}", filename, e))?;
-
-                // Count elements to prevent DoS attacks
-                if let Some(array) = items.as_array() {
-                    if array.len() > 10000 {
-                        return Err(anyhow!("Too many items in file {} (maximum 10000)", filename));
-                    }
-                }
-
-                let file =
-                    OpenOptions::new().write(true).truncate(true).create(true).open(&path)?;
-                serde_json::to_writer(file, &items)?;
+        // Compact JSON files by removing whitespace, migrating them on the way
+        let _lock = self.lock(true)?;
+        for filename in STATE_FILES {
+            if self.state_dir.join(filename).exists() {
+                let items = self.read_items(filename)?;
+                self.write_items(filename, &items, false)?;
             }
         }
         Ok(())
@@ -221,6 +732,27 @@ pub fn load_notes() -> Result<Vec<NoteItem>> {
     StateManager::new()?.load()
 }
 
+/// Look up a previously resolved contact by the name the user typed
+pub fn find_cached_contact(query: &str) -> Result<Option<ContactCacheItem>> {
+    let query = query.trim().to_lowercase();
+    let contacts: Vec<ContactCacheItem> = StateManager::new()?.load()?;
+    Ok(contacts.into_iter().find(|c| c.query == query))
+}
+
+/// Remember which person a name resolved to, replacing any earlier entry for the same name
+pub fn cache_contact(query: &str, name: &str, emails: &[String]) -> Result<()> {
+    let query = query.trim().to_lowercase();
+    StateManager::new()?.update(|contacts: &mut Vec<ContactCacheItem>| {
+        contacts.retain(|c| c.query != query);
+        contacts.push(ContactCacheItem {
+            query,
+            name: name.to_string(),
+            emails: emails.to_vec(),
+            resolved_at: Local::now().to_rfc3339(),
+        });
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -254,11 +786,7 @@ mod tests {
             date: "2024-02-21".to_string(),
             time: "14:30".to_string(),
             calendars: vec!["Test Calendar".to_string()],
-            all_day: false,
-            location: None,
-            description: None,
-            email: None,
-            reminder: None,
+            ..Default::default()
         };
         manager.add(event)?;
 
@@ -268,4 +796,168 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_legacy_files_are_migrated() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let manager = StateManager::with_dir(temp_dir.path())?;
+        fs::write(
+            temp_dir.path().join(TODOS_FILE),
+            r#"[{"title": "Old", "notes": null, "lists": [], "reminder_time": null}]"#,
+        )?;
+
+        let todos: Vec<TodoItem> = manager.load()?;
+        assert_eq!(todos[0].title, "Old");
+
+        manager.add(TodoItem {
+            title: "New".to_string(),
+            notes: None,
+            lists: vec![],
+            reminder_time: None,
+        })?;
+        let file: Value =
+            serde_json::from_str(&fs::read_to_string(temp_dir.path().join(TODOS_FILE))?)?;
+        assert_eq!(file["version"], STATE_VERSION);
+        assert_eq!(file["items"].as_array().map(Vec::len), Some(2));
+        // No temporary files are left behind
+        assert_eq!(
+            fs::read_dir(temp_dir.path())?
+                .filter(|e| {
+                    e.as_ref().is_ok_and(|e| e.file_name().to_string_lossy().ends_with(".tmp"))
+                })
+                .count(),
+            0
+        );
+
+        fs::write(temp_dir.path().join(NOTES_FILE), r#"{"version": 99, "items": []}"#)?;
+        assert!(manager.load::<NoteItem>().unwrap_err().to_string().contains("newer DuckTape"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_and_import() -> Result<()> {
+        let source_dir = tempdir()?;
+        let source = StateManager::with_dir(source_dir.path())?;
+        source.add(ContactCacheItem {
+            query: "jane".to_string(),
+            name: "Jane Doe".to_string(),
+            emails: vec!["jane@example.com".to_string()],
+            resolved_at: "2025-04-01T09:00:00+00:00".to_string(),
+        })?;
+        let export = source.export()?;
+        assert_eq!(export.files.keys().collect::<Vec<_>>(), vec![CONTACTS_FILE]);
+
+        let target_dir = tempdir()?;
+        let target = StateManager::with_dir(target_dir.path())?;
+        let json = serde_json::to_string(&export)?;
+        assert_eq!(target.import(serde_json::from_str(&json)?)?, 1);
+        let contacts: Vec<ContactCacheItem> = target.load()?;
+        assert_eq!(contacts[0].name, "Jane Doe");
+
+        // Nothing is written when any file is invalid
+        let mut bad = export.clone();
+        bad.files.insert(EVENTS_FILE.to_string(), vec![serde_json::json!({"title": 1})]);
+        assert!(target.import(bad).is_err());
+        assert!(!target_dir.path().join(EVENTS_FILE).exists());
+        let mut unknown = export;
+        unknown.files.insert("secrets.json".to_string(), vec![]);
+        assert!(target.import(unknown).is_err());
+        Ok(())
+    }
+
+    /// One valid item of each state file
+    fn sample(filename: &str) -> Value {
+        let at = "2025-04-01T09:00:00+00:00";
+        match filename {
+            TODOS_FILE => serde_json::json!({
+                "title": "Buy milk", "notes": null, "lists": ["Errands"], "reminder_time": null
+            }),
+            EVENTS_FILE => serde_json::json!({
+                "title": "Standup", "date": "2025-04-01", "time": "09:00", "calendars": ["Work"],
+                "all_day": false, "location": null, "description": null, "email": null,
+                "reminder": null
+            }),
+            NOTES_FILE => serde_json::json!({
+                "title": "Ideas", "content": "", "folder": null, "created_at": at
+            }),
+            CONTACTS_FILE => serde_json::json!({
+                "query": "jane", "name": "Jane Doe", "emails": [], "resolved_at": at
+            }),
+            LINKS_FILE => serde_json::json!({
+                "source": {"kind": "todo", "title": "Prep"},
+                "target": {"kind": "event", "title": "Standup"},
+                "created_at": at
+            }),
+            NOTIFICATIONS_FILE => serde_json::json!({
+                "key": "event:Standup", "source": "watch", "surfaced_at": at
+            }),
+            LLM_CACHE_FILE => serde_json::json!({
+                "key": "grok:model:lunch", "command": "ducktape calendar create", "created_at": at
+            }),
+            USAGE_FILE => serde_json::json!({
+                "date": "2025-04-01", "provider": "grok", "model": "grok-2-latest", "requests": 1,
+                "prompt_tokens": 10, "completion_tokens": 5, "cost_usd": 0.001
+            }),
+            BIRTHDAYS_FILE => serde_json::json!({
+                "key": "1:birthday", "title": "Jane's birthday", "calendar": null, "created_at": at
+            }),
+            FOCUS_FILE => serde_json::json!({
+                "title": "Writing", "started_at": at, "planned_minutes": 25,
+                "focused_minutes": 25, "completed": true
+            }),
+            SCHEDULE_FILE => serde_json::json!({
+                "id": 1, "command": "agenda", "schedule": "07:30 daily", "created_at": at
+            }),
+            ADDRESS_BOOK_FILE => serde_json::json!({
+                "id": "1:ABPerson", "name": "Jane Doe", "emails": [], "modified_at": at
+            }),
+            LAST_COMMAND_FILE => serde_json::json!({
+                "input": "agenda", "command": "agenda", "created_at": at
+            }),
+            SYNC_BASE_FILE => serde_json::json!({
+                "uid": "ABC", "fingerprint": "Standup", "synced_at": at
+            }),
+            CONTEXT_FILE => serde_json::json!({
+                "kind": "event", "id": "ABC", "title": "Standup", "updated_at": at
+            }),
+            _ => panic!("no sample for {}", filename),
+        }
+    }
+
+    #[test]
+    fn test_every_state_file_round_trips() -> Result<()> {
+        let source_dir = tempdir()?;
+        for filename in STATE_FILES {
+            fs::write(
+                source_dir.path().join(filename),
+                serde_json::to_string(&vec![sample(filename)])?,
+            )?;
+        }
+        let export = StateManager::with_dir(source_dir.path())?.export()?;
+        assert_eq!(export.files.len(), STATE_FILES.len());
+
+        let target_dir = tempdir()?;
+        let target = StateManager::with_dir(target_dir.path())?;
+        assert_eq!(target.import(export.clone())?, STATE_FILES.len());
+        assert_eq!(target.export()?.files, export.files);
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_lines_with_rotation() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let manager = StateManager::with_dir(temp_dir.path())?;
+        for n in 0..7 {
+            manager.append_line("log.jsonl", &format!("line {}", n), 12, 2)?;
+        }
+        // Two lines fit before each rotation, and only two rotated copies are kept
+        assert!(temp_dir.path().join("log.jsonl.2").exists());
+        assert!(!temp_dir.path().join("log.jsonl.3").exists());
+        assert_eq!(
+            manager.read_lines("log.jsonl")?,
+            ["line 2", "line 3", "line 4", "line 5", "line 6"]
+        );
+        assert!(manager.export()?.files.is_empty());
+        Ok(())
+    }
 }
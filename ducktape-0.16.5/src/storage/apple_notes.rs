@@ -1,8 +1,9 @@
+use crate::applescript::Template;
+use crate::script_runner::run_applescript;
 use anyhow::{Context, Result};
 use chrono::{DateTime, Local, TimeZone};
-use log::{debug, error, info};
 use serde::{Deserialize, Serialize};
-use std::process::Command;
+use tracing::{debug, error, info};
 
 /// Represents a note in Apple Notes
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -29,9 +30,7 @@ impl AppleNotesStorage {
         debug!("Listing notes from Apple Notes");
 
         // Use AppleScript to get notes from Apple Notes
-        let output = Command::new("osascript")
-            .arg("-e")
-            .arg(r#"
+        let output = run_applescript(r#"
                 tell application "Notes"
                     set allNotes to {}
                     repeat with theNote in notes
@@ -45,7 +44,6 @@ impl AppleNotesStorage {
                     return allNotes
                 end tell
             "#)
-            .output()
             .context("Failed to execute AppleScript to list notes")?;
 
         if !output.status.success() {
@@ -69,19 +67,23 @@ impl AppleNotesStorage {
         debug!("Adding note to Apple Notes: {}", title);
 
         // Use AppleScript to create note in Apple Notes
-        let output = Command::new("osascript")
-            .arg("-e")
-            .arg(format!(r#"
+        let output = run_applescript(
+            Template::new(
+                r#"
                 tell application "Notes"
-                    set newNote to make new note with properties {{body:"{content}", name:"{title}"}}
+                    set newNote to make new note with properties {body:${content}, name:${title}}
                     set noteId to id of newNote as string
                     set noteCreateDate to creation date of newNote as string
                     set noteModDate to modification date of newNote as string
                     return noteId & "|" & noteCreateDate & "|" & noteModDate
                 end tell
-            "#))
-            .output()
-            .context("Failed to execute AppleScript to add note")?;
+            "#,
+            )
+            .text("title", title)
+            .text("content", content)
+            .render()?,
+        )
+        .context("Failed to execute AppleScript to add note")?;
 
         if !output.status.success() {
             let error_message = String::from_utf8_lossy(&output.stderr);
@@ -116,12 +118,10 @@ impl AppleNotesStorage {
     pub fn get_note(&self, id: &str) -> Result<Option<AppleNote>> {
         debug!("Getting note from Apple Notes with ID: {}", id);
 
-        let output = Command::new("osascript")
-            .arg("-e")
-            .arg(format!(r#"
+        let output = run_applescript(Template::new(r#"
                 tell application "Notes"
                     try
-                        set theNote to note id "{id}"
+                        set theNote to note id ${id}
                         set noteId to id of theNote as string
                         set noteTitle to name of theNote as string
                         set noteContent to body of theNote as string
@@ -132,8 +132,7 @@ impl AppleNotesStorage {
                         return ""
                     end try
                 end tell
-            "#))
-            .output()
+            "#).text("id", id).render()?)
             .context("Failed to execute AppleScript to get note")?;
 
         if !output.status.success() {
@@ -168,24 +167,27 @@ impl AppleNotesStorage {
     pub fn update_note(&self, id: &str, title: &str, content: &str) -> Result<bool> {
         debug!("Updating note in Apple Notes with ID: {}", id);
 
-        let output = Command::new("osascript")
-            .arg("-e")
-            .arg(format!(
+        let output = run_applescript(
+            Template::new(
                 r#"
                 tell application "Notes"
                     try
-                        set theNote to note id "{id}"
-                        set name of theNote to "{title}"
-                        set body of theNote to "{content}"
+                        set theNote to note id ${id}
+                        set name of theNote to ${title}
+                        set body of theNote to ${content}
                         return "success"
                     on error
                         return "not_found"
                     end try
                 end tell
-            "#
-            ))
-            .output()
-            .context("Failed to execute AppleScript to update note")?;
+            "#,
+            )
+            .text("id", id)
+            .text("title", title)
+            .text("content", content)
+            .render()?,
+        )
+        .context("Failed to execute AppleScript to update note")?;
 
         if !output.status.success() {
             let error_message = String::from_utf8_lossy(&output.stderr);
@@ -209,22 +211,23 @@ impl AppleNotesStorage {
     pub fn delete_note(&self, id: &str) -> Result<bool> {
         debug!("Deleting note from Apple Notes with ID: {}", id);
 
-        let output = Command::new("osascript")
-            .arg("-e")
-            .arg(format!(
+        let output = run_applescript(
+            Template::new(
                 r#"
                 tell application "Notes"
                     try
-                        delete note id "{id}"
+                        delete note id ${id}
                         return "success"
                     on error
                         return "not_found"
                     end try
                 end tell
-            "#
-            ))
-            .output()
-            .context("Failed to execute AppleScript to delete note")?;
+            "#,
+            )
+            .text("id", id)
+            .render()?,
+        )
+        .context("Failed to execute AppleScript to delete note")?;
 
         if !output.status.success() {
             let error_message = String::from_utf8_lossy(&output.stderr);
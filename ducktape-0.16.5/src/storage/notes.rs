@@ -25,11 +25,11 @@ impl NotesStorage {
         let notes_dir = storage_dir.join("notes");
 
         if !notes_dir.exists() {
-            log::debug!("Creating notes directory: {:?}", notes_dir);
+            tracing::debug!("Creating notes directory: {:?}", notes_dir);
             fs::create_dir_all(&notes_dir)
                 .context(format!("Failed to create notes directory at {:?}", notes_dir))?;
         } else {
-            log::debug!("Notes directory exists: {:?}", notes_dir);
+            tracing::debug!("Notes directory exists: {:?}", notes_dir);
         }
 
         Ok(Self { storage_path: notes_dir })
@@ -42,10 +42,10 @@ impl NotesStorage {
     /// Lists all notes in storage
     pub fn list_notes(&self) -> Result<Vec<Note>> {
         let file_path = self.get_notes_file();
-        log::debug!("Attempting to read notes from: {:?}", file_path);
+        tracing::debug!("Attempting to read notes from: {:?}", file_path);
 
         if !file_path.exists() {
-            log::debug!("Notes file does not exist yet, returning empty list");
+            tracing::debug!("Notes file does not exist yet, returning empty list");
             return Ok(Vec::new());
         }
 
@@ -53,14 +53,14 @@ impl NotesStorage {
             .context(format!("Failed to read notes file at {:?}", file_path))?;
 
         if content.trim().is_empty() {
-            log::debug!("Notes file is empty, returning empty list");
+            tracing::debug!("Notes file is empty, returning empty list");
             return Ok(Vec::new());
         }
 
         let notes: Vec<Note> = serde_json::from_str(&content)
             .context(format!("Failed to parse notes JSON from {:?}", file_path))?;
 
-        log::debug!("Successfully read {} notes", notes.len());
+        tracing::debug!("Successfully read {} notes", notes.len());
         Ok(notes)
     }
 
@@ -70,7 +70,7 @@ impl NotesStorage {
         notes.push(note.clone());
 
         let file_path = self.get_notes_file();
-        log::debug!("Writing {} notes to: {:?}", notes.len(), file_path);
+        tracing::debug!("Writing {} notes to: {:?}", notes.len(), file_path);
 
         let content =
             serde_json::to_string_pretty(&notes).context("Failed to serialize notes to JSON")?;
@@ -78,7 +78,7 @@ impl NotesStorage {
         fs::write(&file_path, content)
             .context(format!("Failed to write notes file at {:?}", file_path))?;
 
-        log::debug!("Note added successfully with ID: {}", note.id);
+        tracing::debug!("Note added successfully with ID: {}", note.id);
         Ok(())
     }
 
@@ -90,12 +90,12 @@ impl NotesStorage {
         notes.retain(|note| note.id != id);
 
         if notes.len() == initial_len {
-            log::debug!("Note with ID {} not found for deletion", id);
+            tracing::debug!("Note with ID {} not found for deletion", id);
             return Ok(false);
         }
 
         let file_path = self.get_notes_file();
-        log::debug!("Writing {} notes after deletion to: {:?}", notes.len(), file_path);
+        tracing::debug!("Writing {} notes after deletion to: {:?}", notes.len(), file_path);
 
         let content =
             serde_json::to_string_pretty(&notes).context("Failed to serialize notes to JSON")?;
@@ -103,7 +103,7 @@ impl NotesStorage {
         fs::write(&file_path, content)
             .context(format!("Failed to write notes file after deletion at {:?}", file_path))?;
 
-        log::debug!("Note with ID {} deleted successfully", id);
+        tracing::debug!("Note with ID {} deleted successfully", id);
         Ok(true)
     }
 
@@ -113,9 +113,9 @@ impl NotesStorage {
         let note = notes.iter().find(|n| n.id == id).cloned();
 
         if note.is_some() {
-            log::debug!("Found note with ID: {}", id);
+            tracing::debug!("Found note with ID: {}", id);
         } else {
-            log::debug!("Note with ID {} not found", id);
+            tracing::debug!("Note with ID {} not found", id);
         }
 
         Ok(note)
@@ -136,12 +136,12 @@ impl NotesStorage {
         }
 
         if !found {
-            log::debug!("Note with ID {} not found for update", id);
+            tracing::debug!("Note with ID {} not found for update", id);
             return Ok(false);
         }
 
         let file_path = self.get_notes_file();
-        log::debug!("Writing updated notes to: {:?}", file_path);
+        tracing::debug!("Writing updated notes to: {:?}", file_path);
 
         let content = serde_json::to_string_pretty(&notes)
             .context("Failed to serialize updated notes to JSON")?;
@@ -149,7 +149,7 @@ impl NotesStorage {
         fs::write(&file_path, content)
             .context(format!("Failed to write updated notes file at {:?}", file_path))?;
 
-        log::debug!("Note with ID {} updated successfully", id);
+        tracing::debug!("Note with ID {} updated successfully", id);
         Ok(true)
     }
 }
@@ -0,0 +1,301 @@
+//! Tags shared by events, reminders and notes.
+//
+// Tags live in the text of the item as hashtags, so Calendar.app, Reminders.app and Notes.app
+// keep them without any extra storage: `--tags deep-work,clientX` appends `#deep-work #clientx`
+// on its own line to an event's notes, a reminder's notes or a note's body. Hashtags typed in
+// natural language ("lunch with Sam tomorrow #clientX") are stripped from the input before it
+// is parsed and passed on as `--tags`. `ducktape list --tag clientX` finds them again.
+
+use crate::notes::NoteItem;
+use crate::open::OpenKind;
+use crate::state::CalendarItem;
+use crate::theme::Colors;
+use crate::todo::TodoItem;
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use tracing::warn;
+
+static HASHTAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?:^|\s)#([\w-]+)").unwrap());
+
+/// A tag as stored: lowercase, without the leading `#`
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim().trim_start_matches('#').to_lowercase();
+    let valid = !tag.is_empty() && tag.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+    valid.then_some(tag)
+}
+
+/// Parse a `--tags` value such as `deep-work, #clientX`, skipping anything that is not a tag
+pub fn parse_tag_list(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input.split(',').filter_map(normalize_tag) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// The hashtags in `text`, normalized, sorted and without duplicates
+pub fn hashtags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> =
+        HASHTAG.captures_iter(text).map(|caps| caps[1].to_lowercase()).collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// Remove the hashtags from natural language input, returning the rest and the tags
+pub fn strip_hashtags(text: &str) -> (String, Vec<String>) {
+    let tags = hashtags(text);
+    let rest = HASHTAG.replace_all(text, "");
+    (rest.split_whitespace().collect::<Vec<_>>().join(" "), tags)
+}
+
+/// Whether `text` carries `tag` as a hashtag
+pub fn has_tag(text: &str, tag: &str) -> bool {
+    normalize_tag(tag).is_some_and(|tag| hashtags(text).contains(&tag))
+}
+
+/// Commands that take `--tags`
+const TAGGABLE_COMMANDS: [&str; 3] =
+    ["ducktape calendar create ", "ducktape todo create ", "ducktape note create "];
+
+/// A translated natural language command with `--tags` for the hashtags stripped from the input
+///
+/// Commands other than creating an event, reminder or note are returned as they are.
+pub fn add_tags_flag(command: &str, tags: &[String]) -> String {
+    let taggable = TAGGABLE_COMMANDS.iter().any(|prefix| command.starts_with(prefix));
+    if tags.is_empty() || !taggable || command.contains(" --tags ") {
+        return command.to_string();
+    }
+    format!("{} --tags {}", command.trim_end(), tags.join(","))
+}
+
+/// `text` with a line of hashtags for the `tags` it does not carry yet
+pub fn append_tags(text: Option<&str>, tags: &[String]) -> Option<String> {
+    let present = text.map(hashtags).unwrap_or_default();
+    let missing: Vec<String> = tags
+        .iter()
+        .filter_map(|tag| normalize_tag(tag))
+        .filter(|tag| !present.contains(tag))
+        .map(|tag| format!("#{}", tag))
+        .collect();
+    match (text.filter(|t| !t.trim().is_empty()), missing.is_empty()) {
+        (text, true) => text.map(str::to_string),
+        (Some(text), false) => Some(format!("{}\n{}", text.trim_end(), missing.join(" "))),
+        (None, false) => Some(missing.join(" ")),
+    }
+}
+
+/// Everything carrying a tag, by kind
+#[derive(Debug, Clone, Default)]
+pub struct TaggedItems {
+    pub events: Vec<CalendarItem>,
+    pub reminders: Vec<TodoItem>,
+    pub notes: Vec<NoteItem>,
+}
+
+impl TaggedItems {
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty() && self.reminders.is_empty() && self.notes.is_empty()
+    }
+}
+
+/// The tracked events tagged `tag` in their title or notes, soonest first
+pub fn tagged_events(items: &[CalendarItem], tag: &str) -> Vec<CalendarItem> {
+    let mut events: Vec<CalendarItem> = items
+        .iter()
+        .filter(|item| {
+            let text =
+                format!("{} {}", item.title, item.description.as_deref().unwrap_or_default());
+            has_tag(&text, tag)
+        })
+        .cloned()
+        .collect();
+    events.sort_by(|a, b| (&a.date, &a.time).cmp(&(&b.date, &b.time)));
+    events
+}
+
+/// The open reminders tagged `tag` in their title or notes
+pub fn tagged_reminders(todos: &[TodoItem], tag: &str) -> Vec<TodoItem> {
+    todos
+        .iter()
+        .filter(|todo| {
+            let text = format!("{} {}", todo.title, todo.notes.as_deref().unwrap_or_default());
+            !todo.completed && has_tag(&text, tag)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Find the events, reminders and notes tagged `tag`, or only those of `kind`
+///
+/// Notes.app is searched for the text `#tag`, so a note tagged `#clientx2` also turns up for
+/// `clientx`. A source that cannot be read is skipped with a warning.
+pub async fn find_tagged(tag: &str, kind: Option<OpenKind>) -> Result<TaggedItems> {
+    let wanted = |k: OpenKind| kind.is_none_or(|kind| kind == k);
+    let mut found = TaggedItems::default();
+    if wanted(OpenKind::Event) {
+        found.events = tagged_events(&crate::state::load_events()?, tag);
+    }
+    if wanted(OpenKind::Reminder) {
+        match crate::backend::todos()?.todos(None).await {
+            Ok(todos) => found.reminders = tagged_reminders(&todos, tag),
+            Err(e) => warn!("Failed to read reminders: {}", e),
+        }
+    }
+    if wanted(OpenKind::Note) {
+        match crate::notes::search_notes(&format!("#{}", tag)).await {
+            Ok(notes) => found.notes = notes,
+            Err(e) => warn!("Failed to search notes: {}", e),
+        }
+    }
+    Ok(found)
+}
+
+/// Render the items found for `tag`, one section per kind
+pub fn format_tagged(tag: &str, found: &TaggedItems, colors: &Colors) -> String {
+    if found.is_empty() {
+        return format!("Nothing tagged #{}\n", tag);
+    }
+    let mut out = String::new();
+    if !found.events.is_empty() {
+        out.push_str(&format!("Events tagged #{} ({}):\n", tag, found.events.len()));
+        for event in &found.events {
+            let time = if event.all_day { "all day".to_string() } else { event.time.clone() };
+            let when = format!("{} {:>7}", event.date, time);
+            out.push_str(&format!("  {}  {}\n", colors.time(&when), event.title));
+        }
+    }
+    if !found.reminders.is_empty() {
+        out.push_str(&format!("Reminders tagged #{} ({}):\n", tag, found.reminders.len()));
+        for todo in &found.reminders {
+            let lists = if todo.lists.is_empty() {
+                String::new()
+            } else {
+                format!(" {}", colors.calendar(&format!("[{}]", todo.lists.join(", "))))
+            };
+            out.push_str(&format!("  {}{}\n", todo.title, lists));
+        }
+    }
+    if !found.notes.is_empty() {
+        out.push_str(&format!("Notes tagged #{} ({}):\n", tag, found.notes.len()));
+        for note in &found.notes {
+            let folder = colors.calendar(&format!("[{}]", note.folder));
+            out.push_str(&format!("  {} {}\n", note.title, folder));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tag_list() {
+        assert_eq!(parse_tag_list("deep-work,clientX"), vec!["deep-work", "clientx"]);
+        assert_eq!(parse_tag_list(" #Focus , focus,,bad tag"), vec!["focus"]);
+        assert!(parse_tag_list("").is_empty());
+    }
+
+    #[test]
+    fn test_hashtags() {
+        assert_eq!(hashtags("Review #ClientX budget #q3 #clientx"), vec!["clientx", "q3"]);
+        // Anchors in URLs and issue numbers glued to words are not tags
+        assert!(hashtags("See https://example.com/page#section and C#").is_empty());
+        assert!(has_tag("Notes\n#deep-work", "#Deep-Work"));
+        assert!(!has_tag("Notes #deep-work", "deep"));
+    }
+
+    #[test]
+    fn test_strip_hashtags() {
+        let (rest, tags) = strip_hashtags("lunch with Sam tomorrow at 1pm #clientX #food");
+        assert_eq!(rest, "lunch with Sam tomorrow at 1pm");
+        assert_eq!(tags, vec!["clientx", "food"]);
+        assert_eq!(strip_hashtags("no tags here"), ("no tags here".to_string(), Vec::new()));
+    }
+
+    #[test]
+    fn test_add_tags_flag() {
+        let tags = vec!["clientx".to_string(), "food".to_string()];
+        assert_eq!(
+            add_tags_flag("ducktape calendar create \"Lunch\" 2025-03-14 13:00 14:00", &tags),
+            "ducktape calendar create \"Lunch\" 2025-03-14 13:00 14:00 --tags clientx,food"
+        );
+        assert_eq!(add_tags_flag("ducktape calendar list", &tags), "ducktape calendar list");
+        assert_eq!(
+            add_tags_flag("ducktape todo create \"Call\" --tags work", &tags),
+            "ducktape todo create \"Call\" --tags work"
+        );
+        assert_eq!(add_tags_flag("ducktape note create Ideas", &[]), "ducktape note create Ideas");
+    }
+
+    #[test]
+    fn test_append_tags() {
+        let tags = parse_tag_list("deep-work,clientX");
+        assert_eq!(append_tags(None, &tags).as_deref(), Some("#deep-work #clientx"));
+        assert_eq!(
+            append_tags(Some("Agenda attached\n"), &tags).as_deref(),
+            Some("Agenda attached\n#deep-work #clientx")
+        );
+        assert_eq!(
+            append_tags(Some("Prep #clientx"), &tags).as_deref(),
+            Some("Prep #clientx\n#deep-work")
+        );
+        assert_eq!(append_tags(Some("Prep"), &[]).as_deref(), Some("Prep"));
+        assert_eq!(append_tags(None, &[]), None);
+    }
+
+    fn event(title: &str, date: &str, description: Option<&str>) -> CalendarItem {
+        CalendarItem {
+            title: title.to_string(),
+            date: date.to_string(),
+            time: "10:00".to_string(),
+            calendars: vec!["Work".to_string()],
+            description: description.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn todo(title: &str, notes: Option<&str>, completed: bool) -> TodoItem {
+        TodoItem {
+            title: title.to_string(),
+            notes: notes.map(str::to_string),
+            lists: vec!["Work".to_string()],
+            reminder_time: None,
+            completed,
+        }
+    }
+
+    #[test]
+    fn test_tagged_items() {
+        let events = vec![
+            event("Review", "2025-03-20", Some("Agenda\n#clientx")),
+            event("Kickoff #ClientX", "2025-03-14", None),
+            event("Standup", "2025-03-14", Some("#clientxyz")),
+        ];
+        let tagged = tagged_events(&events, "clientX");
+        let titles: Vec<&str> = tagged.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, vec!["Kickoff #ClientX", "Review"]);
+
+        let todos = vec![
+            todo("Send invoice", Some("#clientx"), false),
+            todo("Old invoice", Some("#clientx"), true),
+            todo("Buy milk", None, false),
+        ];
+        let reminders = tagged_reminders(&todos, "#clientx");
+        assert_eq!(reminders.len(), 1);
+
+        let found = TaggedItems { events: tagged, reminders, notes: Vec::new() };
+        let out = format_tagged("clientx", &found, &Colors::plain());
+        assert!(out.starts_with("Events tagged #clientx (2):\n  2025-03-14   10:00  Kickoff"));
+        assert!(out.contains("Reminders tagged #clientx (1):\n  Send invoice [Work]\n"));
+        assert!(!out.contains("Notes tagged"));
+        assert_eq!(
+            format_tagged("none", &TaggedItems::default(), &Colors::plain()),
+            "Nothing tagged #none\n"
+        );
+    }
+}
@@ -0,0 +1,332 @@
+//! Long-running operations and their progress.
+//
+// Imports of large calendars and batch creations take a while. Over the API they run as tasks:
+// the client gets a task ID at once, asks `GET /api/tasks/<id>` how far it got, or watches it
+// over the WebSocket. The work itself only calls `progress`; who listens is set up around it,
+// a task here, or a progress bar in the terminal.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tracing::{Instrument, debug, info, info_span, warn};
+use utoipa::ToSchema;
+
+/// Width of the terminal progress bar, in characters
+const BAR_WIDTH: usize = 24;
+
+/// Longest item name shown next to the progress bar
+const BAR_ITEM_LENGTH: usize = 40;
+
+type Reporter = Arc<dyn Fn(usize, Option<usize>, &str) + Send + Sync>;
+
+tokio::task_local! {
+    /// Where the current task's progress goes
+    static REPORTER: Reporter;
+}
+
+static TASKS: Lazy<Mutex<HashMap<String, Task>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static UPDATES: Lazy<broadcast::Sender<Task>> = Lazy::new(|| broadcast::channel(256).0);
+
+/// How long a finished task can still be looked up
+fn retention() -> chrono::Duration {
+    chrono::Duration::hours(1)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// A long-running operation, as last reported
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Task {
+    pub id: String,
+    /// What it does, e.g. "calendar import"
+    pub kind: String,
+    pub status: TaskStatus,
+    /// Items done so far
+    pub done: usize,
+    /// Items in all, once known
+    pub total: Option<usize>,
+    /// The item being worked on; a summary of the result when it succeeded
+    pub message: Option<String>,
+    /// Why it failed
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl Task {
+    /// How far it got, from 0 to 100, once the total is known
+    pub fn percent(&self) -> Option<u8> {
+        match (self.status, self.total) {
+            (TaskStatus::Succeeded, _) => Some(100),
+            (_, Some(total)) if total > 0 => Some((self.done.min(total) * 100 / total) as u8),
+            _ => None,
+        }
+    }
+}
+
+/// Report that `done` of `total` items are done and `item` is next
+///
+/// Does nothing unless the caller runs within `spawn` or `with_progress`.
+pub fn progress(done: usize, total: usize, item: &str) {
+    let _ = REPORTER.try_with(|report| report(done, Some(total), item));
+}
+
+/// Run `future`, passing the progress it reports to `report`
+pub async fn with_progress<F: Future>(
+    report: impl Fn(usize, Option<usize>, &str) + Send + Sync + 'static,
+    future: F,
+) -> F::Output {
+    REPORTER.scope(Arc::new(report), future).await
+}
+
+/// Change a task and tell the subscribers
+fn update(id: &str, change: impl FnOnce(&mut Task)) {
+    let snapshot = {
+        let mut tasks = TASKS.lock().unwrap();
+        let Some(task) = tasks.get_mut(id) else {
+            return;
+        };
+        change(task);
+        task.clone()
+    };
+    // Without subscribers there is no one to tell
+    let _ = UPDATES.send(snapshot);
+}
+
+/// Forget tasks that finished longer ago than the retention period
+fn prune(tasks: &mut HashMap<String, Task>, now: DateTime<Utc>) {
+    tasks.retain(|_, task| match task.finished_at {
+        Some(at) => now - at < retention(),
+        None => true,
+    });
+}
+
+/// Run `work` in the background as a task of `kind`, returning the task ID
+///
+/// `work` returns a summary of what it did, which becomes the task's final message.
+pub fn spawn<F>(kind: &str, work: F) -> String
+where
+    F: Future<Output = Result<String>> + Send + 'static,
+{
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    let task = Task {
+        id: id.clone(),
+        kind: kind.to_string(),
+        status: TaskStatus::Running,
+        done: 0,
+        total: None,
+        message: None,
+        error: None,
+        started_at: Utc::now(),
+        finished_at: None,
+    };
+    {
+        let mut tasks = TASKS.lock().unwrap();
+        prune(&mut tasks, Utc::now());
+        tasks.insert(id.clone(), task.clone());
+    }
+    let _ = UPDATES.send(task);
+    info!("Started {} task {}", kind, id);
+
+    let task_id = id.clone();
+    // Within the span of whoever started it, e.g. a WebSocket connection
+    let span = info_span!("task", id = %id, kind = %kind);
+    let run = async move {
+        let reporter_id = task_id.clone();
+        let report = move |done, total, item: &str| {
+            update(&reporter_id, |task| {
+                task.done = done;
+                task.total = total;
+                task.message = Some(item.to_string());
+            })
+        };
+        let result = with_progress(report, work).await;
+        update(&task_id, |task| {
+            task.finished_at = Some(Utc::now());
+            match result {
+                Ok(summary) => {
+                    debug!("Task {} finished: {}", task.id, summary);
+                    task.status = TaskStatus::Succeeded;
+                    task.message = Some(summary);
+                }
+                Err(e) => {
+                    warn!("Task {} failed: {}", task.id, e);
+                    task.status = TaskStatus::Failed;
+                    task.error = Some(e.to_string());
+                }
+            }
+        });
+    };
+    tokio::spawn(run.instrument(span));
+    id
+}
+
+/// A task started within the retention period
+pub fn get(id: &str) -> Option<Task> {
+    TASKS.lock().unwrap().get(id).cloned()
+}
+
+/// Updates of every task from now on
+pub fn subscribe() -> broadcast::Receiver<Task> {
+    UPDATES.subscribe()
+}
+
+/// One line showing how far work got, e.g. `[######------] 5/10 Standup`
+pub fn progress_bar(done: usize, total: Option<usize>, item: &str) -> String {
+    let item: String = if item.chars().count() > BAR_ITEM_LENGTH {
+        item.chars().take(BAR_ITEM_LENGTH - 1).chain(['…']).collect()
+    } else {
+        item.to_string()
+    };
+    match total {
+        Some(total) if total > 0 => {
+            let filled = done.min(total) * BAR_WIDTH / total;
+            format!(
+                "[{}{}] {}/{} {}",
+                "#".repeat(filled),
+                "-".repeat(BAR_WIDTH - filled),
+                done,
+                total,
+                item
+            )
+            .trim_end()
+            .to_string()
+        }
+        _ => format!("{} {}", done, item).trim_end().to_string(),
+    }
+}
+
+/// Run `future`, drawing the progress it reports as a bar on stderr
+///
+/// Nothing is drawn when stderr is not a terminal.
+pub async fn with_progress_bar<F: Future>(future: F) -> F::Output {
+    if !std::io::stderr().is_terminal() {
+        return future.await;
+    }
+    let drawn = Arc::new(AtomicBool::new(false));
+    let draw = {
+        let drawn = drawn.clone();
+        move |done, total, item: &str| {
+            drawn.store(true, Ordering::Relaxed);
+            let mut stderr = std::io::stderr();
+            let _ = write!(stderr, "\r\x1b[2K{}", progress_bar(done, total, item));
+            let _ = stderr.flush();
+        }
+    };
+    let output = with_progress(draw, future).await;
+    if drawn.load(Ordering::Relaxed) {
+        eprintln!();
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_progress_bar() {
+        assert_eq!(
+            progress_bar(5, Some(10), "Standup"),
+            format!("[{}{}] 5/10 Standup", "#".repeat(12), "-".repeat(12))
+        );
+        assert_eq!(progress_bar(3, Some(3), ""), format!("[{}] 3/3", "#".repeat(24)));
+        assert_eq!(progress_bar(2, None, "Lunch"), "2 Lunch");
+        let long = "x".repeat(60);
+        assert!(progress_bar(0, Some(1), &long).ends_with("x…"));
+    }
+
+    #[test]
+    fn test_progress_outside_a_task_is_ignored() {
+        progress(1, 2, "nothing listens");
+    }
+
+    #[tokio::test]
+    async fn test_spawned_task_reports_progress() {
+        let mut updates = subscribe();
+        let id = spawn("test", async {
+            for done in 0..3 {
+                progress(done, 3, &format!("item {}", done));
+            }
+            progress(3, 3, "");
+            Ok("3 items".to_string())
+        });
+
+        let finished = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let task = updates.recv().await.unwrap();
+                if task.id == id && task.status != TaskStatus::Running {
+                    return task;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(finished.status, TaskStatus::Succeeded);
+        assert_eq!((finished.done, finished.total), (3, Some(3)));
+        assert_eq!(finished.message.as_deref(), Some("3 items"));
+        assert_eq!(finished.percent(), Some(100));
+        assert_eq!(get(&id).unwrap().status, TaskStatus::Succeeded);
+    }
+
+    #[tokio::test]
+    async fn test_failed_task_keeps_its_error() {
+        let mut updates = subscribe();
+        let id = spawn("test", async { Err(anyhow::anyhow!("file vanished")) });
+        let failed = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let task = updates.recv().await.unwrap();
+                if task.id == id && task.status != TaskStatus::Running {
+                    return task;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(failed.status, TaskStatus::Failed);
+        assert_eq!(failed.error.as_deref(), Some("file vanished"));
+        assert_eq!(failed.percent(), None);
+    }
+
+    #[test]
+    fn test_prune_keeps_running_and_recent_tasks() {
+        let now = Utc::now();
+        let task = |id: &str, finished_at| Task {
+            id: id.to_string(),
+            kind: "test".to_string(),
+            status: TaskStatus::Running,
+            done: 0,
+            total: None,
+            message: None,
+            error: None,
+            started_at: now,
+            finished_at,
+        };
+        let mut tasks: HashMap<String, Task> = [
+            task("running", None),
+            task("recent", Some(now - chrono::Duration::minutes(5))),
+            task("old", Some(now - chrono::Duration::hours(2))),
+        ]
+        .into_iter()
+        .map(|task| (task.id.clone(), task))
+        .collect();
+        prune(&mut tasks, now);
+        let mut left: Vec<_> = tasks.into_keys().collect();
+        left.sort();
+        assert_eq!(left, vec!["recent", "running"]);
+    }
+}
@@ -0,0 +1,130 @@
+//! Colors of terminal output.
+//
+// Agenda, list and search output is colored here rather than by each handler: event times
+// stand out, overdue reminders are red and calendar names are dimmed. `ui.theme` picks the
+// colors, and NO_COLOR or output that is not a terminal turns them off. Formatting functions
+// take `Colors` so the same text can be produced plain for Shortcuts, files and tests.
+
+use anyhow::{Result, anyhow};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::io::IsTerminal;
+
+/// Color scheme set by `ui.theme`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    /// Colors for dark terminal backgrounds
+    #[default]
+    Default,
+    /// Colors for light terminal backgrounds
+    Light,
+    /// No colors
+    None,
+}
+
+impl Theme {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "default" | "dark" => Ok(Theme::Default),
+            "light" => Ok(Theme::Light),
+            "none" | "off" => Ok(Theme::None),
+            _ => Err(anyhow!("Invalid theme: {}. Valid options are: default, light, none", s)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Theme::Default => "default",
+            Theme::Light => "light",
+            Theme::None => "none",
+        }
+    }
+}
+
+/// Whether the NO_COLOR convention (https://no-color.org) asks for plain output
+fn no_color() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+/// Colors applied to each kind of text in command output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Colors {
+    theme: Theme,
+}
+
+impl Colors {
+    pub fn new(theme: Theme) -> Self {
+        Self { theme }
+    }
+
+    /// Text without colors
+    pub fn plain() -> Self {
+        Self::new(Theme::None)
+    }
+
+    /// Colors for standard output: `ui.theme`, unless NO_COLOR is set or stdout is redirected
+    pub fn stdout() -> Self {
+        if no_color() || !std::io::stdout().is_terminal() {
+            return Self::plain();
+        }
+        Self::new(crate::config::Config::load().map(|c| c.ui.theme).unwrap_or_default())
+    }
+
+    /// Start and end times of events
+    pub fn time(&self, text: &str) -> String {
+        match self.theme {
+            Theme::Default => text.cyan().to_string(),
+            Theme::Light => text.blue().to_string(),
+            Theme::None => text.to_string(),
+        }
+    }
+
+    /// Calendar, list and folder names shown next to an item
+    pub fn calendar(&self, text: &str) -> String {
+        match self.theme {
+            Theme::Default | Theme::Light => text.dimmed().to_string(),
+            Theme::None => text.to_string(),
+        }
+    }
+
+    /// Reminders past their due date
+    pub fn overdue(&self, text: &str) -> String {
+        match self.theme {
+            Theme::Default | Theme::Light => text.red().to_string(),
+            Theme::None => text.to_string(),
+        }
+    }
+
+    /// Headings such as "Agenda for 2025-03-14"
+    pub fn heading(&self, text: &str) -> String {
+        match self.theme {
+            Theme::Default | Theme::Light => text.bold().to_string(),
+            Theme::None => text.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_from_str() {
+        assert_eq!(Theme::from_str("Light").unwrap(), Theme::Light);
+        assert_eq!(Theme::from_str("off").unwrap(), Theme::None);
+        assert!(Theme::from_str("neon").is_err());
+        assert_eq!(Theme::default().as_str(), "default");
+    }
+
+    #[test]
+    fn test_colors() {
+        colored::control::set_override(true);
+        let colors = Colors::new(Theme::Default);
+        assert!(colors.overdue("Pay rent").contains("\x1b[31m"));
+        assert!(colors.time("09:00").ends_with("\x1b[0m"));
+        let plain = Colors::plain();
+        assert_eq!(plain.overdue("Pay rent"), "Pay rent");
+        assert_eq!(plain.calendar("Work"), "Work");
+    }
+}
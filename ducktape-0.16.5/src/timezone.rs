@@ -0,0 +1,319 @@
+//! Time zone conversion for the `utils tz` command.
+//
+// Converts a wall clock time between zones, e.g. `14:00 EST to CET on 2025-05-01`. Common
+// abbreviations map to the IANA zone people usually mean (EST → America/New_York), so the
+// conversion follows daylight saving time on the given date rather than a fixed offset.
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::str::FromStr;
+
+/// A time zone given by the user
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Zone {
+    Named(Tz),
+    /// The system time zone
+    Local,
+}
+
+impl Zone {
+    /// Parse an IANA name (`Europe/Paris`), a common abbreviation (`EST`, `CET`) or `local`
+    pub fn parse(input: &str) -> Result<Self> {
+        let upper = input.trim().to_uppercase();
+        let iana = match upper.as_str() {
+            "LOCAL" | "HERE" => return Ok(Zone::Local),
+            "UTC" | "GMT" | "Z" => "UTC",
+            "ET" | "EST" | "EDT" => "America/New_York",
+            "CT" | "CST" | "CDT" => "America/Chicago",
+            "MT" | "MST" | "MDT" => "America/Denver",
+            "PT" | "PST" | "PDT" => "America/Los_Angeles",
+            "AKST" | "AKDT" => "America/Anchorage",
+            "HST" => "Pacific/Honolulu",
+            "BST" | "UK" => "Europe/London",
+            "WET" | "WEST" => "Europe/Lisbon",
+            "CET" | "CEST" => "Europe/Paris",
+            "EET" | "EEST" => "Europe/Athens",
+            "MSK" => "Europe/Moscow",
+            "IST" => "Asia/Kolkata",
+            "SGT" => "Asia/Singapore",
+            "HKT" => "Asia/Hong_Kong",
+            "JST" => "Asia/Tokyo",
+            "KST" => "Asia/Seoul",
+            "AEST" | "AEDT" => "Australia/Sydney",
+            "NZST" | "NZDT" => "Pacific/Auckland",
+            "SAST" => "Africa/Johannesburg",
+            _ => input.trim(),
+        };
+        Tz::from_str(iana).map(Zone::Named).map_err(|_| {
+            anyhow!("Unknown time zone: {}. Use an IANA name like Europe/Paris", input)
+        })
+    }
+
+    fn to_utc(self, local: &NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            Zone::Named(tz) => {
+                tz.from_local_datetime(local).earliest().map(|d| d.with_timezone(&Utc))
+            }
+            Zone::Local => {
+                Local.from_local_datetime(local).earliest().map(|d| d.with_timezone(&Utc))
+            }
+        }
+    }
+
+    /// Wall clock time and zone abbreviation (or offset) of an instant in this zone
+    fn local_time(&self, instant: &DateTime<Utc>) -> (NaiveDateTime, String) {
+        match self {
+            Zone::Named(tz) => {
+                let dt = instant.with_timezone(tz);
+                (dt.naive_local(), dt.format("%Z").to_string())
+            }
+            Zone::Local => {
+                let dt = instant.with_timezone(&Local);
+                (dt.naive_local(), dt.format("UTC%:z").to_string())
+            }
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Zone::Named(tz) => tz.name().to_string(),
+            Zone::Local => "local".to_string(),
+        }
+    }
+
+    fn today(&self) -> NaiveDate {
+        self.local_time(&Utc::now()).0.date()
+    }
+}
+
+/// A parsed `utils tz` request
+#[derive(Debug, Clone, PartialEq)]
+pub struct TzRequest {
+    pub time: NaiveTime,
+    /// End of a time range such as `14:00-15:00`
+    pub end: Option<NaiveTime>,
+    pub from: Zone,
+    pub to: Zone,
+    /// Date of `time` in the source zone; today when not given
+    pub date: Option<NaiveDate>,
+}
+
+/// Result of a conversion
+#[derive(Debug, Clone, PartialEq)]
+pub struct TzConversion {
+    pub from: NaiveDateTime,
+    pub from_zone: String,
+    pub from_abbreviation: String,
+    pub to: NaiveDateTime,
+    pub to_zone: String,
+    pub to_abbreviation: String,
+    /// Converted end of a time range
+    pub to_end: Option<NaiveDateTime>,
+}
+
+impl TzConversion {
+    /// Human readable summary, e.g. "2025-05-01 14:00 EDT (America/New_York) → ..."
+    pub fn describe(&self) -> String {
+        format!(
+            "{} {} ({}) → {} {} ({})",
+            self.from.format("%Y-%m-%d %H:%M"),
+            self.from_abbreviation,
+            self.from_zone,
+            self.to.format("%Y-%m-%d %H:%M"),
+            self.to_abbreviation,
+            self.to_zone
+        )
+    }
+
+    /// Target date and time as `YYYY-MM-DD HH:MM [HH:MM]`, ready to be passed to
+    /// `calendar create <title> <date> <start_time> <end_time>`
+    pub fn plain(&self) -> String {
+        match self.to_end {
+            Some(end) => format!("{} {}", self.to.format("%Y-%m-%d %H:%M"), end.format("%H:%M")),
+            None => self.to.format("%Y-%m-%d %H:%M").to_string(),
+        }
+    }
+}
+
+/// Parse a clock time such as `14:00`, `9:30`, `2pm` or `2:30pm`
+pub fn parse_clock_time(input: &str) -> Result<NaiveTime> {
+    let lower = input.trim().to_lowercase().replace(' ', "");
+    let (clock, meridiem) = if let Some(t) = lower.strip_suffix("am") {
+        (t, Some(false))
+    } else if let Some(t) = lower.strip_suffix("pm") {
+        (t, Some(true))
+    } else {
+        (lower.as_str(), None)
+    };
+
+    let (hour, minute) = match clock.split_once(':') {
+        Some((h, m)) => (h.parse::<u32>().ok(), m.parse::<u32>().ok()),
+        None => (clock.parse::<u32>().ok(), Some(0)),
+    };
+    let invalid = || anyhow!("Invalid time: {}. Use HH:MM or a time like 2pm", input);
+    let (mut hour, minute) = (hour.ok_or_else(invalid)?, minute.ok_or_else(invalid)?);
+
+    if let Some(pm) = meridiem {
+        if hour == 0 || hour > 12 {
+            return Err(invalid());
+        }
+        hour = match (hour, pm) {
+            (12, false) => 0,
+            (12, true) => 12,
+            (h, true) => h + 12,
+            (h, false) => h,
+        };
+    }
+    NaiveTime::from_hms_opt(hour, minute, 0).ok_or_else(invalid)
+}
+
+/// Parse the words of `utils tz`: `<time>[-<end>] <from> to <to> [on <YYYY-MM-DD>]`
+pub fn parse_tz_request(words: &[String]) -> Result<TzRequest> {
+    let usage = || {
+        anyhow!("Usage: ducktape utils tz <time>[-<end>] <from-zone> to <to-zone> [on YYYY-MM-DD]")
+    };
+    let words: Vec<&str> = words.iter().flat_map(|w| w.split_whitespace()).collect();
+
+    let to_idx = words
+        .iter()
+        .position(|w| w.eq_ignore_ascii_case("to") || *w == "->")
+        .ok_or_else(usage)?;
+    let on_idx = words.iter().position(|w| w.eq_ignore_ascii_case("on"));
+    if to_idx < 2 {
+        return Err(usage());
+    }
+
+    // A time like "2 pm" may span two words
+    let time_text = words[..to_idx - 1].join(" ");
+    let (time, end) = match time_text.split_once('-') {
+        Some((start, end)) => (parse_clock_time(start)?, Some(parse_clock_time(end)?)),
+        None => (parse_clock_time(&time_text)?, None),
+    };
+    let from = Zone::parse(words[to_idx - 1])?;
+    let to_end = on_idx.filter(|&i| i > to_idx).unwrap_or(words.len());
+    if to_end != to_idx + 2 {
+        return Err(usage());
+    }
+    let to = Zone::parse(words[to_idx + 1])?;
+
+    let date = match on_idx {
+        Some(i) => {
+            let value = words.get(i + 1).ok_or_else(usage)?;
+            Some(
+                NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                    .map_err(|_| anyhow!("Invalid date: {}. Use YYYY-MM-DD", value))?,
+            )
+        }
+        None => None,
+    };
+
+    Ok(TzRequest { time, end, from, to, date })
+}
+
+/// Convert the requested time between zones, honoring daylight saving time on that date
+pub fn convert(request: &TzRequest) -> Result<TzConversion> {
+    let date = request.date.unwrap_or_else(|| request.from.today());
+    let source = date.and_time(request.time);
+    let instant = request.from.to_utc(&source).ok_or_else(|| {
+        anyhow!(
+            "{} does not exist in {} (daylight saving time change)",
+            source.format("%Y-%m-%d %H:%M"),
+            request.from.name()
+        )
+    })?;
+
+    let (from, from_abbreviation) = request.from.local_time(&instant);
+    let (to, to_abbreviation) = request.to.local_time(&instant);
+
+    // Ranges that end before they start run past midnight
+    let to_end = match request.end {
+        Some(end) => {
+            let end_date = if end <= request.time { date.succ_opt().unwrap_or(date) } else { date };
+            let end_instant = request
+                .from
+                .to_utc(&end_date.and_time(end))
+                .ok_or_else(|| anyhow!("End time does not exist in {}", request.from.name()))?;
+            Some(request.to.local_time(&end_instant).0)
+        }
+        None => None,
+    };
+    Ok(TzConversion {
+        from,
+        from_zone: request.from.name(),
+        from_abbreviation,
+        to,
+        to_zone: request.to.name(),
+        to_abbreviation,
+        to_end,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(input: &str) -> Vec<String> {
+        input.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_clock_time() {
+        assert_eq!(parse_clock_time("14:00").unwrap(), NaiveTime::from_hms_opt(14, 0, 0).unwrap());
+        assert_eq!(parse_clock_time("2pm").unwrap(), NaiveTime::from_hms_opt(14, 0, 0).unwrap());
+        assert_eq!(
+            parse_clock_time("12:30 am").unwrap(),
+            NaiveTime::from_hms_opt(0, 30, 0).unwrap()
+        );
+        assert!(parse_clock_time("25:00").is_err());
+        assert!(parse_clock_time("13pm").is_err());
+    }
+
+    #[test]
+    fn test_parse_tz_request() {
+        let request = parse_tz_request(&words("14:00 EST to CET on 2025-05-01")).unwrap();
+        assert_eq!(request.from, Zone::Named(chrono_tz::America::New_York));
+        assert_eq!(request.to, Zone::Named(chrono_tz::Europe::Paris));
+        assert_eq!(request.date, NaiveDate::from_ymd_opt(2025, 5, 1));
+
+        let request = parse_tz_request(&words("2 pm Europe/London to local")).unwrap();
+        assert_eq!(request.time, NaiveTime::from_hms_opt(14, 0, 0).unwrap());
+        assert_eq!(request.to, Zone::Local);
+        assert_eq!(request.date, None);
+        assert_eq!(request.end, None);
+
+        assert!(parse_tz_request(&words("14:00 EST CET")).is_err());
+        assert!(parse_tz_request(&words("14:00 Mars/Olympus to CET")).is_err());
+    }
+
+    #[test]
+    fn test_convert_follows_daylight_saving_time() {
+        // New York and Paris are 6 hours apart in May...
+        let summer =
+            convert(&parse_tz_request(&words("14:00 EST to CET on 2025-05-01")).unwrap()).unwrap();
+        assert_eq!(summer.plain(), "2025-05-01 20:00");
+        assert_eq!(summer.from_abbreviation, "EDT");
+        assert_eq!(summer.to_abbreviation, "CEST");
+
+        // ...but only 5 in the weeks when the US has switched and Europe has not
+        let gap =
+            convert(&parse_tz_request(&words("14:00 EST to CET on 2025-03-20")).unwrap()).unwrap();
+        assert_eq!(gap.plain(), "2025-03-20 19:00");
+
+        let range =
+            convert(&parse_tz_request(&words("9:00-10:30 PT to UTC on 2025-01-15")).unwrap())
+                .unwrap();
+        assert_eq!(range.plain(), "2025-01-15 17:00 18:30");
+
+        // Crossing midnight moves the date
+        let late =
+            convert(&parse_tz_request(&words("23:00 UTC to JST on 2025-01-01")).unwrap()).unwrap();
+        assert_eq!(late.plain(), "2025-01-02 08:00");
+    }
+
+    #[test]
+    fn test_convert_rejects_skipped_times() {
+        let request = parse_tz_request(&words("02:30 EST to UTC on 2025-03-09")).unwrap();
+        assert!(convert(&request).is_err());
+    }
+}
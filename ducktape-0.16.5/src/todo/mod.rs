@@ -1,35 +1,232 @@
 //! Todo and reminder management functionality.
 //
-// This module provides integration with macOS Reminders.app.
+// This module provides integration with macOS Reminders.app, or with the reminders backend
+// chosen in config.toml (see `crate::backend`).
 
+use crate::webhooks::{Action, ItemKind, notify, reminder_data};
 use anyhow::Result;
 
-mod todo_applescript;
+pub(crate) mod todo_applescript;
+mod todo_board;
+mod todo_bulk;
 mod todo_types;
 mod todo_util;
 mod todo_validation;
 
 pub use todo_applescript::*;
+pub use todo_board::*;
+pub use todo_bulk::*;
 pub use todo_types::*;
 pub use todo_validation::*;
 
 /// Create a new todo/reminder
-pub async fn create_todo(config: TodoConfig<'_>) -> Result<()> {
-    // Implementation relies on the todo_applescript module
-    todo_applescript::create_single_todo(config).await
+pub async fn create_todo(config: TodoConfig<'_>) -> crate::error::Result<()> {
+    let hook = reminder_data(
+        config.title,
+        &config.lists,
+        config.due_date.or(config.reminder_time),
+        config.notes.as_deref(),
+    );
+    crate::backend::todos()?.create_todo(config).await?;
+    notify(ItemKind::Reminder, Action::Created, hook);
+    Ok(())
 }
 
 /// List available reminder lists
 pub async fn list_reminder_lists() -> Result<Vec<String>> {
-    todo_applescript::get_reminder_lists().await
+    crate::backend::todos()?.lists().await
 }
 
 /// Get todos from a specific list or all lists
 pub async fn get_todos(list_name: Option<&str>) -> Result<Vec<TodoItem>> {
-    todo_applescript::fetch_todos(list_name).await
+    crate::backend::todos()?.todos(list_name).await
 }
 
 /// Delete a todo by title and list
 pub async fn delete_todo(title: &str, list_name: Option<&str>) -> Result<()> {
-    todo_applescript::delete_todo(title, list_name).await
+    crate::backend::todos()?.delete_todo(title, list_name).await?;
+    let lists: Vec<&str> = list_name.into_iter().collect();
+    notify(ItemKind::Reminder, Action::Deleted, reminder_data(title, &lists, None, None));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BoardConfig;
+    use chrono::NaiveDate;
+
+    fn todo(title: &str, list: &str, completed: bool) -> TodoItem {
+        TodoItem {
+            title: title.to_string(),
+            notes: None,
+            lists: vec![list.to_string()],
+            reminder_time: None,
+            completed,
+        }
+    }
+
+    #[test]
+    fn test_parse_todo_lines() {
+        let todos = parse_todo_lines("Buy milk\tfalse\t\tShopping\nShip it\ttrue\tv1.0\tWork\n\n");
+        assert_eq!(todos.len(), 2);
+        assert_eq!(todos[0].title, "Buy milk");
+        assert_eq!(todos[0].notes, None);
+        assert!(!todos[0].completed);
+        assert_eq!(todos[1].notes.as_deref(), Some("v1.0"));
+        assert_eq!(todos[1].lists, vec!["Work".to_string()]);
+        assert!(todos[1].completed);
+        assert_eq!(todos[1].reminder_time, None);
+
+        let todos = parse_todo_lines("Call Sam\tfalse\t\tWork\t2025-03-14 09:30\n");
+        assert_eq!(todos[0].reminder_time.as_deref(), Some("2025-03-14 09:30"));
+    }
+
+    #[test]
+    fn test_resolve_day() {
+        // 2025-03-14 is a Friday
+        let today = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        assert_eq!(resolve_day("today", today), Some(today));
+        assert_eq!(resolve_day("Tomorrow", today), NaiveDate::from_ymd_opt(2025, 3, 15));
+        assert_eq!(resolve_day("monday", today), NaiveDate::from_ymd_opt(2025, 3, 17));
+        assert_eq!(resolve_day("next friday", today), NaiveDate::from_ymd_opt(2025, 3, 21));
+        assert_eq!(resolve_day("+3", today), NaiveDate::from_ymd_opt(2025, 3, 17));
+        assert_eq!(resolve_day("2025-04-01", today), NaiveDate::from_ymd_opt(2025, 4, 1));
+        assert_eq!(resolve_day("someday", today), None);
+    }
+
+    #[test]
+    fn test_plan_postpone() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let tomorrow = NaiveDate::from_ymd_opt(2025, 3, 15).unwrap();
+        let due = |title: &str, when: Option<&str>, completed: bool| TodoItem {
+            reminder_time: when.map(str::to_string),
+            ..todo(title, "Work", completed)
+        };
+        let todos = vec![
+            due("Report", Some("2025-03-14 09:30"), false),
+            due("Done already", Some("2025-03-14 10:00"), true),
+            due("Late", Some("2025-03-12 17:00"), false),
+            due("Later", Some("2025-03-20 08:00"), false),
+            due("Undated", None, false),
+        ];
+
+        let changes = plan_postpone(&todos, today, tomorrow, false);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].title, "Report");
+        assert_eq!(changes[0].to, "2025-03-15 09:30");
+
+        let changes = plan_postpone(&todos, today, tomorrow, true);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[1].to, "2025-03-15 17:00");
+        assert!(format_due_date_changes(&changes).contains("Late [Work]"));
+    }
+
+    #[test]
+    fn test_parse_postpone_request() {
+        assert_eq!(
+            parse_postpone_request("postpone everything due today to tomorrow"),
+            Some(("today".to_string(), "tomorrow".to_string()))
+        );
+        assert_eq!(
+            parse_postpone_request("Move all my tasks due tomorrow to Monday."),
+            Some(("tomorrow".to_string(), "Monday".to_string()))
+        );
+        assert_eq!(parse_postpone_request("postpone the meeting to tomorrow"), None);
+        assert_eq!(parse_postpone_request("create a todo due today"), None);
+    }
+
+    #[test]
+    fn test_group_into_columns() {
+        let board = BoardConfig::default();
+        let todos = vec![
+            todo("Write spec", "Backlog", false),
+            todo("Fix bug", "Doing", false),
+            todo("Old task", "Doing", true),
+            todo("Release", "Done", false),
+        ];
+        let columns = group_into_columns(&todos, &board);
+        assert_eq!(columns[0], (BoardColumn::Backlog, vec!["Write spec".to_string()]));
+        assert_eq!(columns[1], (BoardColumn::Doing, vec!["Fix bug".to_string()]));
+        assert_eq!(
+            columns[2],
+            (BoardColumn::Done, vec!["Old task".to_string(), "Release".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_render_board() {
+        let columns = vec![
+            (
+                BoardColumn::Backlog,
+                vec!["A very long todo title that will not fit".to_string()],
+            ),
+            (BoardColumn::Doing, vec![]),
+            (BoardColumn::Done, vec!["Shipped".to_string(), "Tested".to_string()]),
+        ];
+        let rendered = render_board(&columns, 64);
+        let lines: Vec<&str> = rendered.lines().collect();
+        // Header, separators and two rows, all the same width
+        assert_eq!(lines.len(), 6);
+        assert!(lines.iter().all(|l| l.chars().count() == lines[0].chars().count()));
+        assert!(lines[1].contains("Backlog (1)"));
+        assert!(lines[1].contains("Done (2)"));
+        assert!(rendered.contains('…'));
+        assert_eq!(BoardColumn::from_str("DOING"), Some(BoardColumn::Doing));
+        assert_eq!(BoardColumn::from_str("later"), None);
+    }
+
+    #[test]
+    fn test_format_reminder_properties() {
+        use crate::applescript::Properties;
+        let props = |due, remind_at, priority| {
+            format_reminder_properties(Properties::new(), due, remind_at, priority)
+                .map(|props| props.render().to_string())
+        };
+        assert_eq!(props(None, None, None).unwrap(), "{}");
+
+        // Without a due date the reminder time doubles as the due date
+        let rendered = props(None, Some("2025-04-22 15:30"), None).unwrap();
+        assert!(rendered.starts_with("{due date:date \"04/22/2025 03:30:00 PM\""));
+        assert!(rendered.contains(", remind me date:date \"04/22/2025 03:30:00 PM\""));
+
+        let rendered =
+            props(Some("2025-04-25"), Some("2025-04-22 09:00"), Some(TodoPriority::High)).unwrap();
+        assert!(rendered.contains("allday due date:date \"04/25/2025"));
+        assert!(rendered.contains("remind me date:date \"04/22/2025 09:00:00 AM\""));
+        assert!(rendered.ends_with(", priority:1}"));
+
+        assert!(props(Some("someday"), None, None).is_err());
+    }
+
+    #[test]
+    fn test_todo_priority_from_str() {
+        assert_eq!(TodoPriority::from_str("High").unwrap().to_applescript(), 1);
+        assert_eq!(TodoPriority::from_str("medium").unwrap().to_applescript(), 5);
+        assert_eq!(TodoPriority::from_str("low").unwrap().to_applescript(), 9);
+        assert!(TodoPriority::from_str("urgent").is_err());
+    }
+
+    #[test]
+    fn test_recurrence_script() {
+        use crate::calendar::{RecurrenceFrequency, RecurrencePattern};
+
+        let weekly = RecurrencePattern::new(RecurrenceFrequency::Weekly)
+            .with_interval(2)
+            .with_count(5)
+            .with_days_of_week(&[1, 3]);
+        let script = recurrence_script("x-apple-reminder://ABC-123", &weekly).unwrap();
+        assert!(script.contains("calendarItemWithIdentifier('ABC-123')"));
+        assert!(script.contains(
+            "1, 2, $([$.EKRecurrenceDayOfWeek.dayOfWeek(2), $.EKRecurrenceDayOfWeek.dayOfWeek(4)])"
+        ));
+        assert!(script.contains("recurrenceEndWithOccurrenceCount(5)"));
+
+        let daily = RecurrencePattern::new(RecurrenceFrequency::Daily);
+        let script = recurrence_script("XYZ", &daily).unwrap();
+        assert!(script.contains("0, 1, null, null, null, null, null, null, null"));
+
+        assert!(recurrence_script("", &daily).is_err());
+        assert!(recurrence_script("XYZ", &daily.clone().with_end_date("May 1st")).is_err());
+    }
 }
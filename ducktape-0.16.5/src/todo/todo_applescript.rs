@@ -2,11 +2,14 @@
 //
 // This module provides functions to interact with the Reminders application via AppleScript
 
-use super::todo_types::{TodoConfig, TodoError, TodoItem};
-use super::todo_util::escape_applescript_string;
+use super::todo_types::{TodoConfig, TodoError, TodoItem, TodoPriority};
+use super::todo_util::format_reminder_time;
+use crate::applescript::{Properties, Script, Template};
+use crate::calendar::{RecurrenceFrequency, RecurrencePattern};
+use crate::script_runner::{run_applescript_async, run_jxa_async};
 use anyhow::{Result, anyhow};
-use log::{debug, error, info};
-use std::process::Command;
+use chrono::TimeZone;
+use tracing::{debug, error, info};
 
 /// Ensure Reminders.app is running
 pub async fn ensure_reminders_running() -> Result<()> {
@@ -18,7 +21,7 @@ pub async fn ensure_reminders_running() -> Result<()> {
         return "OK"
     end tell"#;
 
-    let output = Command::new("osascript").arg("-e").arg(check_script).output()?;
+    let output = run_applescript_async(check_script).await?;
 
     if output.status.success() { Ok(()) } else { Err(anyhow!(TodoError::NotRunning)) }
 }
@@ -32,61 +35,46 @@ pub async fn create_single_todo(config: TodoConfig<'_>) -> Result<()> {
 
     let target_lists = if config.lists.is_empty() { vec!["Reminders"] } else { config.lists };
 
-    // Format reminder time to AppleScript-friendly string if provided
-    let reminder_prop = if let Some(time_str) = config.reminder_time {
-        // Parse input in format "YYYY-MM-DD HH:MM"
-        match chrono::NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M") {
-            Ok(naive_dt) => {
-                // Format as "date \"MM/dd/yyyy hh:mm:ss AM/PM\""
-                let formatted = naive_dt.format("%m/%d/%Y %I:%M:%S %p").to_string();
-                debug!("Setting reminder time to: {}", formatted);
-                // Use the correct AppleScript syntax for setting a due date on reminders
-                format!(", due date:date \"{}\"", formatted)
-            }
-            Err(e) => {
-                error!("Invalid reminder time format: {}", e);
-                String::new()
-            }
-        }
-    } else {
-        String::new()
-    };
+    // Title, notes, dates and priority as AppleScript properties
+    let properties = format_reminder_properties(
+        Properties::new()
+            .text("name", config.title)
+            .text("body", config.notes.as_deref().unwrap_or("")),
+        config.due_date,
+        config.reminder_time,
+        config.priority,
+    )?
+    .render();
+    debug!("Reminder properties: {}", properties);
 
     let mut success_count = 0;
+    let mut recurrence_errors = Vec::new();
     for list in target_lists {
-        // Escape all inputs to prevent command injection
-        let escaped_list = escape_applescript_string(list);
-        let escaped_title = escape_applescript_string(config.title);
-        let escaped_notes = escape_applescript_string(config.notes.as_deref().unwrap_or(""));
-
-        // Updated AppleScript with escaped inputs
-        let script = format!(
+        let script = Template::new(
             r#"tell application "Reminders"
     try
-        set remLists to lists whose name is "{}"
+        set remLists to lists whose name is ${list}
         if (count of remLists) > 0 then
             set targetList to item 1 of remLists
         else
-            set targetList to make new list with properties {{name:"{}"}}
+            set targetList to make new list with properties {name:${list}}
         end if
         
-        set newTodo to make new reminder in targetList with properties {{name:"{}", body:"{}"{} }}
+        set newTodo to make new reminder in targetList with properties ${properties}
         
-        return "Success: Todo created"
+        return "Success: " & (id of newTodo)
     on error errMsg
         return "Error: " & errMsg
     end try
 end tell"#,
-            escaped_list, // search for list
-            escaped_list, // create list if not found
-            escaped_title,
-            escaped_notes,
-            reminder_prop
-        );
+        )
+        .text("list", list)
+        .script("properties", properties.clone())
+        .render()?;
 
         debug!("Executing AppleScript: {}", script);
 
-        let output = Command::new("osascript").arg("-e").arg(&script).output()?;
+        let output = run_applescript_async(script).await?;
         let result = String::from_utf8_lossy(&output.stdout);
         let error_output = String::from_utf8_lossy(&output.stderr);
 
@@ -97,13 +85,27 @@ end tell"#,
         if result.contains("Success") {
             info!("Todo created in list {}: {}", list, config.title);
             success_count += 1;
+
+            if let Some(recurrence) = &config.recurrence {
+                let reminder_id = result.trim().trim_start_matches("Success: ");
+                if let Err(e) = apply_recurrence(reminder_id, recurrence).await {
+                    error!("Failed to set recurrence in list {}: {}", list, e);
+                    recurrence_errors.push(e.to_string());
+                }
+            }
         } else {
             let error_msg = result.replace("Error: ", "");
             error!("Failed to create todo in list {}: {}", list, error_msg);
         }
     }
 
-    if success_count > 0 {
+    if success_count > 0 && !recurrence_errors.is_empty() {
+        Err(anyhow!(TodoError::General(format!(
+            "Todo '{}' was created but could not be set to repeat: {}",
+            config.title,
+            recurrence_errors.join("; ")
+        ))))
+    } else if success_count > 0 {
         Ok(())
     } else {
         Err(anyhow!(TodoError::General(format!(
@@ -113,6 +115,128 @@ end tell"#,
     }
 }
 
+/// Add the AppleScript properties for a new reminder's dates and priority to `properties`
+///
+/// `due` is "YYYY-MM-DD" for an all-day due date or "YYYY-MM-DD HH:MM"; `remind_at` is
+/// "YYYY-MM-DD HH:MM". Without a due date the remind-at time also becomes the due date, as
+/// `--remind` always did.
+pub fn format_reminder_properties(
+    properties: Properties,
+    due: Option<&str>,
+    remind_at: Option<&str>,
+    priority: Option<TodoPriority>,
+) -> Result<Properties> {
+    let date = |value: String| Template::new("date ${value}").text("value", &value).render();
+    let mut properties = properties;
+
+    if let Some(due) = due.or(remind_at) {
+        properties = match chrono::NaiveDate::parse_from_str(due, "%Y-%m-%d") {
+            Ok(day) => properties
+                .script("allday due date", date(day.format("%m/%d/%Y 12:00:00 AM").to_string())?),
+            Err(_) => properties.script("due date", date(format_reminder_time(due)?)?),
+        };
+    }
+    if let Some(remind_at) = remind_at {
+        properties = properties.script("remind me date", date(format_reminder_time(remind_at)?)?);
+    }
+    if let Some(priority) = priority {
+        properties = properties.number("priority", priority.to_applescript());
+    }
+    Ok(properties)
+}
+
+/// Build the JavaScript for Automation script that adds a recurrence rule to a reminder
+///
+/// Reminders.app's AppleScript dictionary has no recurrence property, so the rule is set
+/// through EventKit using the identifier returned when the reminder was created.
+pub fn recurrence_script(reminder_id: &str, recurrence: &RecurrencePattern) -> Result<String> {
+    let identifier: String = reminder_id
+        .trim()
+        .trim_start_matches("x-apple-reminder://")
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+    if identifier.is_empty() {
+        return Err(anyhow!("Missing reminder identifier"));
+    }
+
+    let frequency = match recurrence.frequency {
+        RecurrenceFrequency::Daily => 0,
+        RecurrenceFrequency::Weekly => 1,
+        RecurrenceFrequency::Monthly => 2,
+        RecurrenceFrequency::Yearly => 3,
+    };
+    // EventKit numbers weekdays from 1 (Sunday) while DuckTape uses 0 (Sunday)
+    let days = if recurrence.days_of_week.is_empty() {
+        "null".to_string()
+    } else {
+        let days: Vec<String> = recurrence
+            .days_of_week
+            .iter()
+            .map(|d| format!("$.EKRecurrenceDayOfWeek.dayOfWeek({})", (d % 7) + 1))
+            .collect();
+        format!("$([{}])", days.join(", "))
+    };
+    let end = if let Some(count) = recurrence.count {
+        format!("$.EKRecurrenceEnd.recurrenceEndWithOccurrenceCount({})", count)
+    } else if let Some(end_date) = &recurrence.end_date {
+        let date = chrono::NaiveDate::parse_from_str(end_date, "%Y-%m-%d")
+            .map_err(|_| anyhow!("Invalid recurrence end date: {}. Use YYYY-MM-DD", end_date))?;
+        let end_of_day = chrono::Local
+            .from_local_datetime(&date.and_hms_opt(23, 59, 59).unwrap_or_default())
+            .earliest()
+            .ok_or_else(|| anyhow!("Invalid recurrence end date: {}", end_date))?;
+        format!(
+            "$.EKRecurrenceEnd.recurrenceEndWithEndDate($.NSDate.dateWithTimeIntervalSince1970({}))",
+            end_of_day.timestamp()
+        )
+    } else {
+        "null".to_string()
+    };
+
+    Ok(format!(
+        r#"ObjC.import('EventKit');
+function run() {{
+    const store = $.EKEventStore.alloc.init;
+    const item = store.calendarItemWithIdentifier('{identifier}');
+    if (item.isNil()) {{
+        return 'Error: Reminder not found. Allow Reminders access for osascript in System Settings';
+    }}
+    const rule = $.EKRecurrenceRule.alloc.initRecurrenceWithFrequencyIntervalDaysOfTheWeekDaysOfTheMonthMonthsOfTheYearWeeksOfTheYearDaysOfTheYearSetPositionsEnd(
+        {frequency}, {interval}, {days}, null, null, null, null, null, {end});
+    item.addRecurrenceRule(rule);
+    const error = Ref();
+    if (store.saveReminderCommitError(item, true, error)) {{
+        return 'Success: Recurrence set';
+    }}
+    return 'Error: ' + ObjC.unwrap(error[0].localizedDescription);
+}}"#,
+        identifier = identifier,
+        frequency = frequency,
+        interval = recurrence.interval.max(1),
+        days = days,
+        end = end
+    ))
+}
+
+/// Make an existing reminder repeat
+pub async fn apply_recurrence(reminder_id: &str, recurrence: &RecurrencePattern) -> Result<()> {
+    let script = recurrence_script(reminder_id, recurrence)?;
+    debug!("Executing JXA: {}", script);
+
+    let output = run_jxa_async(&script).await?;
+    let result = String::from_utf8_lossy(&output.stdout);
+
+    if result.contains("Success") {
+        info!("Recurrence set for reminder {}", reminder_id);
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let error_msg = if result.trim().is_empty() { stderr.trim() } else { result.trim() };
+        Err(anyhow!(TodoError::ScriptError(error_msg.replace("Error: ", ""))))
+    }
+}
+
 /// Get available reminder lists
 pub async fn get_reminder_lists() -> Result<Vec<String>> {
     // Make sure Reminders app is running
@@ -126,7 +250,7 @@ pub async fn get_reminder_lists() -> Result<Vec<String>> {
     return listNames
 end tell"#;
 
-    let output = Command::new("osascript").arg("-e").arg(script).output()?;
+    let output = run_applescript_async(script).await?;
     if !output.status.success() {
         return Err(anyhow!(TodoError::ScriptError(
             String::from_utf8_lossy(&output.stderr).to_string()
@@ -145,113 +269,215 @@ end tell"#;
     Ok(lists)
 }
 
+/// The lists named `list`, or all lists
+fn lists_named(list: Option<&str>) -> Result<Script> {
+    match list {
+        Some(list) => Template::new("(lists whose name is ${list})").text("list", list).render(),
+        None => Ok(Script::from("lists")),
+    }
+}
+
 /// Fetch todos from a specific list or all lists
 pub async fn fetch_todos(list_name: Option<&str>) -> Result<Vec<TodoItem>> {
     // Make sure Reminders app is running
     ensure_reminders_running().await?;
 
-    let script = if let Some(list) = list_name {
-        let escaped_list = escape_applescript_string(list);
-        format!(
-            r#"tell application "Reminders"
-    set todoList to {}
-    set listObj to first list whose name is "{0}"
-    repeat with t in (reminders in listObj)
-        set todoTitle to name of t
-        set todoCompleted to completed of t
-        set todoBody to ""
-        try
-            set todoBody to body of t
-        end try
-        set todoItem to {{title:todoTitle, notes:todoBody, completed:todoCompleted, listName:"{0}"}}
-        copy todoItem to end of todoList
-    end repeat
-    return todoList
-end tell"#,
-            escaped_list
-        )
-    } else {
-        r#"tell application "Reminders"
-    set todoList to {}
-    repeat with l in lists
+    // One reminder per line, fields separated by tabs: title, completed, notes, list and due
+    // date. Line breaks and tabs inside the notes are flattened so that every reminder stays
+    // on a single line.
+    let list_selector = lists_named(list_name)?;
+    let script = Template::new(
+        r#"on flatten(v)
+    if v is missing value then return ""
+    set AppleScript's text item delimiters to {return, linefeed, tab}
+    set parts to text items of (v as text)
+    set AppleScript's text item delimiters to " "
+    set flat to parts as text
+    set AppleScript's text item delimiters to ""
+    return flat
+end flatten
+
+on pad(n)
+    return text -2 thru -1 of ("0" & (n as integer))
+end pad
+
+on fmtDue(d)
+    if d is missing value then return ""
+    return (year of d as text) & "-" & my pad(month of d as integer) & "-" & my pad(day of d) & " " & my pad(hours of d) & ":" & my pad(minutes of d)
+end fmtDue
+
+tell application "Reminders"
+    set output to ""
+    repeat with l in ${lists}
         set listName to name of l
         repeat with t in (reminders in l)
-            set todoTitle to name of t
-            set todoCompleted to completed of t
-            set todoBody to ""
-            try
-                set todoBody to body of t
-            end try
-            set todoItem to {title:todoTitle, notes:todoBody, completed:todoCompleted, listName:listName}
-            copy todoItem to end of todoList
+            set output to output & my flatten(name of t) & tab & (completed of t as text) & tab & my flatten(body of t) & tab & listName & tab & my fmtDue(due date of t) & linefeed
         end repeat
     end repeat
-    return todoList
-end tell"#
-        .to_string()
-    };
+    return output
+end tell"#,
+    )
+    .script("lists", list_selector)
+    .render()?;
 
-    let output = Command::new("osascript").arg("-e").arg(script).output()?;
+    let output = run_applescript_async(script).await?;
     if !output.status.success() {
         return Err(anyhow!(TodoError::ScriptError(
             String::from_utf8_lossy(&output.stderr).to_string()
         )));
     }
 
-    // Parse the AppleScript output into TodoItem structs
-    let todos_output = String::from_utf8_lossy(&output.stdout);
-    let mut todos = Vec::new();
-
-    // Very basic parsing of AppleScript output - in a real implementation
-    // you might want a more robust parser
-    for line in todos_output.lines() {
-        if line.contains("title:") && line.contains("notes:") {
-            // Extract data from line
-            let mut title = String::new();
-            let mut notes = None;
-            let mut completed = false;
-            let mut list_name = String::new();
-
-            // Super simple parsing - you might want to improve this
-            if let Some(title_start) = line.find("title:") {
-                if let Some(title_end) = line[title_start..].find(",") {
-                    title = line[title_start + 6..title_start + title_end].trim().to_string();
-                }
-            }
+    let todos = parse_todo_lines(&String::from_utf8_lossy(&output.stdout));
+    debug!("Fetched {} todos", todos.len());
+    Ok(todos)
+}
 
-            if let Some(notes_start) = line.find("notes:") {
-                if let Some(notes_end) = line[notes_start..].find(",") {
-                    let note_text =
-                        line[notes_start + 6..notes_start + notes_end].trim().to_string();
-                    if !note_text.is_empty() {
-                        notes = Some(note_text);
-                    }
-                }
+/// Parse the tab separated reminder lines produced by `fetch_todos`
+pub fn parse_todo_lines(output: &str) -> Vec<TodoItem> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() < 4 || fields[0].trim().is_empty() {
+                return None;
             }
+            let notes = fields[2].trim();
+            let due = fields.get(4).map(|d| d.trim()).unwrap_or_default();
+            Some(TodoItem {
+                title: fields[0].trim().to_string(),
+                notes: if notes.is_empty() { None } else { Some(notes.to_string()) },
+                lists: vec![fields[3].trim().to_string()],
+                reminder_time: if due.is_empty() { None } else { Some(due.to_string()) },
+                completed: fields[1].trim() == "true",
+            })
+        })
+        .collect()
+}
 
-            if let Some(completed_start) = line.find("completed:") {
-                if let Some(completed_str) = line[completed_start + 10..].split_whitespace().next()
-                {
-                    completed = completed_str == "true";
-                }
-            }
+/// Move a todo to another list, optionally marking it as completed
+pub async fn move_todo(
+    title: &str,
+    to_list: &str,
+    from_list: Option<&str>,
+    completed: bool,
+) -> Result<()> {
+    // Make sure Reminders app is running
+    ensure_reminders_running().await?;
 
-            if let Some(list_start) = line.find("listName:") {
-                list_name = line[list_start + 9..].trim().to_string();
-            }
+    let script = Template::new(
+        r#"tell application "Reminders"
+    try
+        set targetLists to lists whose name is ${target}
+        if (count of targetLists) > 0 then
+            set targetList to item 1 of targetLists
+        else
+            set targetList to make new list with properties {name:${target}}
+        end if
 
-            todos.push(TodoItem {
-                title,
-                notes,
-                lists: vec![list_name],
-                reminder_time: None, // We don't parse this in this example
-                completed,
-            });
+        repeat with l in ${source}
+            set matches to (reminders in l whose name is ${title})
+            if (count of matches) > 0 then
+                set r to item 1 of matches
+                if (name of l) is not (name of targetList) then
+                    try
+                        move r to targetList
+                    on error
+                        set newTodo to make new reminder in targetList with properties {name:(name of r)}
+                        if body of r is not missing value then set body of newTodo to body of r
+                        if due date of r is not missing value then set due date of newTodo to due date of r
+                        delete r
+                        set r to newTodo
+                    end try
+                end if
+                set completed of r to ${completed}
+                return "Success: Todo moved"
+            end if
+        end repeat
+        return "Error: Todo not found"
+    on error errMsg
+        return "Error: " & errMsg
+    end try
+end tell"#,
+    )
+    .text("target", to_list)
+    .script("source", lists_named(from_list)?)
+    .text("title", title)
+    .boolean("completed", completed)
+    .render()?;
+
+    let output = run_applescript_async(script).await?;
+    let result = String::from_utf8_lossy(&output.stdout);
+
+    if result.contains("Success") {
+        info!("Todo '{}' moved to list {}", title, to_list);
+        Ok(())
+    } else {
+        let error_msg = result.replace("Error: ", "").trim().to_string();
+        error!("Failed to move todo: {}", error_msg);
+
+        if error_msg.contains("not found") {
+            Err(anyhow!(TodoError::TodoNotFound(title.to_string())))
+        } else {
+            Err(anyhow!(TodoError::ScriptError(error_msg)))
         }
     }
+}
 
-    debug!("Fetched {} todos", todos.len());
-    Ok(todos)
+/// Change the due date of a todo, or clear it when `due` is `None`
+///
+/// `due` uses the "YYYY-MM-DD HH:MM" format.
+pub async fn set_todo_due_date(
+    title: &str,
+    list_name: Option<&str>,
+    due: Option<&str>,
+) -> Result<()> {
+    // Make sure Reminders app is running
+    ensure_reminders_running().await?;
+
+    let due_value = match due {
+        Some(time_str) => Template::new("date ${time}")
+            .text("time", &format_reminder_time(time_str)?)
+            .render()?,
+        None => Script::from("missing value"),
+    };
+
+    let script = Template::new(
+        r#"tell application "Reminders"
+    try
+        repeat with l in ${source}
+            set matches to (reminders in l whose name is ${title} and completed is false)
+            if (count of matches) > 0 then
+                set due date of (item 1 of matches) to ${due}
+                return "Success: Due date updated"
+            end if
+        end repeat
+        return "Error: Todo not found"
+    on error errMsg
+        return "Error: " & errMsg
+    end try
+end tell"#,
+    )
+    .script("source", lists_named(list_name)?)
+    .text("title", title)
+    .script("due", due_value)
+    .render()?;
+
+    let output = run_applescript_async(script).await?;
+    let result = String::from_utf8_lossy(&output.stdout);
+
+    if result.contains("Success") {
+        info!("Todo '{}' due date set to {}", title, due.unwrap_or("none"));
+        Ok(())
+    } else {
+        let error_msg = result.replace("Error: ", "").trim().to_string();
+        error!("Failed to update due date: {}", error_msg);
+
+        if error_msg.contains("not found") {
+            Err(anyhow!(TodoError::TodoNotFound(title.to_string())))
+        } else {
+            Err(anyhow!(TodoError::ScriptError(error_msg)))
+        }
+    }
 }
 
 /// Delete a todo by title and list
@@ -259,15 +485,12 @@ pub async fn delete_todo(title: &str, list_name: Option<&str>) -> Result<()> {
     // Make sure Reminders app is running
     ensure_reminders_running().await?;
 
-    let escaped_title = escape_applescript_string(title);
-
     let script = if let Some(list) = list_name {
-        let escaped_list = escape_applescript_string(list);
-        format!(
+        Template::new(
             r#"tell application "Reminders"
     try
-        set targetList to first list whose name is "{}"
-        set itemsToDelete to (reminders in targetList whose name is "{}")
+        set targetList to first list whose name is ${list}
+        set itemsToDelete to (reminders in targetList whose name is ${title})
         if (count of itemsToDelete) > 0 then
             delete itemsToDelete
             return "Success: Todo deleted"
@@ -278,15 +501,17 @@ pub async fn delete_todo(title: &str, list_name: Option<&str>) -> Result<()> {
         return "Error: " & errMsg
     end try
 end tell"#,
-            escaped_list, escaped_title
         )
+        .text("list", list)
+        .text("title", title)
+        .render()?
     } else {
-        format!(
+        Template::new(
             r#"tell application "Reminders"
     try
         set foundTodo to false
         repeat with l in lists
-            set itemsToDelete to (reminders in l whose name is "{}")
+            set itemsToDelete to (reminders in l whose name is ${title})
             if (count of itemsToDelete) > 0 then
                 delete itemsToDelete
                 set foundTodo to true
@@ -302,11 +527,12 @@ end tell"#,
         return "Error: " & errMsg
     end try
 end tell"#,
-            escaped_title
         )
+        .text("title", title)
+        .render()?
     };
 
-    let output = Command::new("osascript").arg("-e").arg(&script).output()?;
+    let output = run_applescript_async(script).await?;
     let result = String::from_utf8_lossy(&output.stdout);
 
     if result.contains("Success") {
@@ -0,0 +1,147 @@
+//! Kanban-style board view over reminder lists.
+//
+// This module maps the Backlog/Doing/Done board columns onto Reminders.app lists
+// and renders them side by side in the terminal.
+
+use super::todo_applescript::{fetch_todos, move_todo};
+use super::todo_types::TodoItem;
+use crate::config::BoardConfig;
+use crate::webhooks::{Action, ItemKind};
+use anyhow::Result;
+
+/// A column on the todo board
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BoardColumn {
+    Backlog,
+    Doing,
+    Done,
+}
+
+impl BoardColumn {
+    /// All columns in display order
+    pub const ALL: [BoardColumn; 3] = [BoardColumn::Backlog, BoardColumn::Doing, BoardColumn::Done];
+
+    /// Parse a column name
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "backlog" | "todo" => Some(BoardColumn::Backlog),
+            "doing" | "in-progress" | "wip" => Some(BoardColumn::Doing),
+            "done" | "complete" | "completed" => Some(BoardColumn::Done),
+            _ => None,
+        }
+    }
+
+    /// Column heading
+    pub fn label(&self) -> &'static str {
+        match self {
+            BoardColumn::Backlog => "Backlog",
+            BoardColumn::Doing => "Doing",
+            BoardColumn::Done => "Done",
+        }
+    }
+
+    /// The reminder list backing this column
+    pub fn list<'a>(&self, board: &'a BoardConfig) -> &'a str {
+        match self {
+            BoardColumn::Backlog => &board.backlog,
+            BoardColumn::Doing => &board.doing,
+            BoardColumn::Done => &board.done,
+        }
+    }
+}
+
+/// Group todos into board columns
+///
+/// Completed todos always land in Done, wherever they live; other todos are placed by list.
+pub fn group_into_columns(
+    todos: &[TodoItem],
+    board: &BoardConfig,
+) -> Vec<(BoardColumn, Vec<String>)> {
+    BoardColumn::ALL
+        .iter()
+        .map(|column| {
+            let titles = todos
+                .iter()
+                .filter(|todo| {
+                    let in_list =
+                        todo.lists.iter().any(|l| l.eq_ignore_ascii_case(column.list(board)));
+                    match column {
+                        BoardColumn::Done => todo.completed || in_list,
+                        _ => !todo.completed && in_list,
+                    }
+                })
+                .map(|todo| todo.title.clone())
+                .collect();
+            (*column, titles)
+        })
+        .collect()
+}
+
+fn fit(text: &str, width: usize) -> String {
+    let count = text.chars().count();
+    if count > width {
+        let truncated: String = text.chars().take(width.saturating_sub(1)).collect();
+        format!("{}…", truncated)
+    } else {
+        format!("{}{}", text, " ".repeat(width - count))
+    }
+}
+
+/// Render board columns side by side within the given terminal width
+pub fn render_board(columns: &[(BoardColumn, Vec<String>)], width: usize) -> String {
+    let column_width = (width.saturating_sub(columns.len() + 1) / columns.len().max(1)).max(12);
+    let separator = format!("+{}", format!("{}+", "-".repeat(column_width)).repeat(columns.len()));
+
+    let mut out = String::new();
+    out.push_str(&separator);
+    out.push('\n');
+    out.push('|');
+    for (column, titles) in columns {
+        out.push_str(&fit(&format!(" {} ({})", column.label(), titles.len()), column_width));
+        out.push('|');
+    }
+    out.push('\n');
+    out.push_str(&separator);
+    out.push('\n');
+
+    let rows = columns.iter().map(|(_, titles)| titles.len()).max().unwrap_or(0);
+    for row in 0..rows {
+        out.push('|');
+        for (_, titles) in columns {
+            let cell = titles.get(row).map(|t| format!(" • {}", t)).unwrap_or_default();
+            out.push_str(&fit(&cell, column_width));
+            out.push('|');
+        }
+        out.push('\n');
+    }
+    if rows > 0 {
+        out.push_str(&separator);
+        out.push('\n');
+    }
+    out
+}
+
+/// Fetch todos from the board lists and render them for the current terminal
+pub async fn show_board(board: &BoardConfig) -> Result<String> {
+    let mut todos = Vec::new();
+    for column in BoardColumn::ALL {
+        todos.extend(fetch_todos(Some(column.list(board))).await?);
+    }
+
+    let width = std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok()).unwrap_or(100);
+    Ok(render_board(&group_into_columns(&todos, board), width))
+}
+
+/// Move a todo to a board column (or directly to a named list)
+///
+/// Returns the name of the list the todo was moved to.
+pub async fn move_to_column(title: &str, target: &str, board: &BoardConfig) -> Result<String> {
+    let (list, completed) = match BoardColumn::from_str(target) {
+        Some(column) => (column.list(board).to_string(), column == BoardColumn::Done),
+        None => (target.to_string(), false),
+    };
+    move_todo(title, &list, None, completed).await?;
+    let hook = crate::webhooks::reminder_data(title, &[list.as_str()], None, None);
+    crate::webhooks::notify(ItemKind::Reminder, Action::Updated, hook);
+    Ok(list)
+}
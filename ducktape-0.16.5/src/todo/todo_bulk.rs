@@ -0,0 +1,124 @@
+//! Bulk operations over reminders.
+//
+// This module plans changes across many reminders at once (e.g. postponing everything due
+// today), so that callers can preview the plan before it is applied through AppleScript.
+
+use super::todo_applescript::{fetch_todos, set_todo_due_date};
+use super::todo_types::TodoItem;
+use crate::webhooks::{Action, ItemKind, notify, reminder_data};
+use anyhow::{Result, anyhow};
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use regex::Regex;
+use tracing::{info, warn};
+
+/// A planned due date change for a single reminder
+#[derive(Debug, Clone, PartialEq)]
+pub struct DueDateChange {
+    pub title: String,
+    pub list: String,
+    /// Current due date ("YYYY-MM-DD HH:MM")
+    pub from: String,
+    /// New due date ("YYYY-MM-DD HH:MM")
+    pub to: String,
+}
+
+/// Resolve a day expression relative to `today`
+///
+/// Accepts everything `dates::parse_date` understands, such as `tomorrow`, weekday names,
+/// `+N` / `in N days` offsets, `end of month` and `YYYY-MM-DD` dates.
+pub fn resolve_day(expr: &str, today: NaiveDate) -> Option<NaiveDate> {
+    crate::dates::parse_date(expr, today)
+}
+
+/// Plan moving every open reminder due on `due` to `to`, keeping its time of day
+///
+/// With `include_overdue`, open reminders due before `due` are moved as well.
+pub fn plan_postpone(
+    todos: &[TodoItem],
+    due: NaiveDate,
+    to: NaiveDate,
+    include_overdue: bool,
+) -> Vec<DueDateChange> {
+    todos
+        .iter()
+        .filter(|todo| !todo.completed)
+        .filter_map(|todo| {
+            let current = todo.reminder_time.as_deref()?;
+            let current_dt = NaiveDateTime::parse_from_str(current, "%Y-%m-%d %H:%M").ok()?;
+            let day = current_dt.date();
+            if day != due && !(include_overdue && day < due) {
+                return None;
+            }
+            Some(DueDateChange {
+                title: todo.title.clone(),
+                list: todo.lists.first().cloned().unwrap_or_default(),
+                from: current.to_string(),
+                to: to.and_time(current_dt.time()).format("%Y-%m-%d %H:%M").to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Render a plan as a preview table
+pub fn format_due_date_changes(changes: &[DueDateChange]) -> String {
+    changes
+        .iter()
+        .map(|c| format!("  • {} [{}]: {} → {}", c.title, c.list, c.from, c.to))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Fetch open reminders and plan a postpone from `due` to `to`
+pub async fn preview_postpone(
+    due: &str,
+    to: &str,
+    list: Option<&str>,
+    include_overdue: bool,
+) -> Result<Vec<DueDateChange>> {
+    let today = Local::now().date_naive();
+    let due_date = resolve_day(due, today).ok_or_else(|| anyhow!("Unrecognized date: {}", due))?;
+    let to_date = resolve_day(to, today).ok_or_else(|| anyhow!("Unrecognized date: {}", to))?;
+    if to_date == due_date && !include_overdue {
+        return Err(anyhow!("Target date is the same as the due date"));
+    }
+
+    let todos = fetch_todos(list).await?;
+    Ok(plan_postpone(&todos, due_date, to_date, include_overdue))
+}
+
+/// Apply planned due date changes, returning how many reminders were updated
+pub async fn apply_due_date_changes(changes: &[DueDateChange]) -> Result<usize> {
+    let mut updated = 0;
+    for change in changes {
+        let list = if change.list.is_empty() { None } else { Some(change.list.as_str()) };
+        match set_todo_due_date(&change.title, list, Some(&change.to)).await {
+            Ok(()) => {
+                updated += 1;
+                let lists: Vec<&str> = list.into_iter().collect();
+                let hook = reminder_data(&change.title, &lists, Some(&change.to), None);
+                notify(ItemKind::Reminder, Action::Updated, hook);
+            }
+            Err(e) => warn!("Failed to postpone '{}': {}", change.title, e),
+        }
+    }
+    info!("Postponed {} of {} reminder(s)", updated, changes.len());
+    Ok(updated)
+}
+
+/// Recognize natural language postpone requests
+///
+/// "postpone everything due today to tomorrow" becomes `Some(("today", "tomorrow"))`.
+pub fn parse_postpone_request(input: &str) -> Option<(String, String)> {
+    let re = Regex::new(
+        r"(?i)^\s*(?:please\s+)?(?:postpone|push|defer|move|bump|reschedule)\s+(?:all\s+|everything\s+|all\s+(?:my\s+)?(?:todos|tasks|reminders)\s+|(?:my\s+)?(?:todos|tasks|reminders)\s+)?(?:that\s+(?:is|are)\s+|which\s+(?:is|are)\s+)?due\s+(?:on\s+)?(.+?)\s+(?:to|until|till)\s+(.+?)\s*[.!]?\s*$",
+    )
+    .ok()?;
+    let caps = re.captures(input)?;
+    let today = Local::now().date_naive();
+    let due = caps.get(1)?.as_str().trim().to_string();
+    let to = caps.get(2)?.as_str().trim().to_string();
+    // Only claim the request when both dates are understood
+    resolve_day(&due, today)?;
+    resolve_day(&to, today)?;
+    Some((due, to))
+}
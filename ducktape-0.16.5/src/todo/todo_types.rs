@@ -2,8 +2,49 @@
 //
 // Contains data structures used for todo/reminder management
 
+use crate::calendar::RecurrencePattern;
+use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
 
+/// Priority of a todo, stored in Reminders.app as 1 (high), 5 (medium) or 9 (low)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TodoPriority {
+    High,
+    Medium,
+    Low,
+}
+
+impl TodoPriority {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "high" | "h" | "!!!" => Ok(TodoPriority::High),
+            "medium" | "med" | "m" | "!!" => Ok(TodoPriority::Medium),
+            "low" | "l" | "!" => Ok(TodoPriority::Low),
+            _ => Err(anyhow!("Invalid priority: {}. Valid options are: high, medium, low", s)),
+        }
+    }
+
+    /// Value of the Reminders.app `priority` property
+    pub fn to_applescript(&self) -> u8 {
+        match self {
+            TodoPriority::High => 1,
+            TodoPriority::Medium => 5,
+            TodoPriority::Low => 9,
+        }
+    }
+
+    /// Priority for a Reminders.app `priority` value; 0 means none
+    pub fn from_applescript(value: u8) -> Option<Self> {
+        match value {
+            1..=4 => Some(TodoPriority::High),
+            5 => Some(TodoPriority::Medium),
+            6..=9 => Some(TodoPriority::Low),
+            _ => None,
+        }
+    }
+}
+
 /// Configuration for creating a new todo/reminder
 #[derive(Debug, Clone)]
 pub struct TodoConfig<'a> {
@@ -14,13 +55,29 @@ pub struct TodoConfig<'a> {
     /// Lists to add the todo item to (empty uses default list)
     pub lists: Vec<&'a str>,
     /// Optional reminder time in format "YYYY-MM-DD HH:MM"
+    ///
+    /// Also used as the due date when no due date is given.
     pub reminder_time: Option<&'a str>,
+    /// Optional due date, "YYYY-MM-DD" for an all-day due date or "YYYY-MM-DD HH:MM"
+    pub due_date: Option<&'a str>,
+    /// Optional priority
+    pub priority: Option<TodoPriority>,
+    /// Optional recurrence (frequency, interval, end date or count)
+    pub recurrence: Option<RecurrencePattern>,
 }
 
 impl<'a> TodoConfig<'a> {
     /// Create a new TodoConfig with just a title
     pub fn new(title: &'a str) -> Self {
-        Self { title, notes: None, lists: Vec::new(), reminder_time: None }
+        Self {
+            title,
+            notes: None,
+            lists: Vec::new(),
+            reminder_time: None,
+            due_date: None,
+            priority: None,
+            recurrence: None,
+        }
     }
 
     /// Set the lists for this todo
@@ -40,6 +97,24 @@ impl<'a> TodoConfig<'a> {
         self.reminder_time = Some(time);
         self
     }
+
+    /// Set the due date for this todo
+    pub fn with_due_date(mut self, due: &'a str) -> Self {
+        self.due_date = Some(due);
+        self
+    }
+
+    /// Set the priority for this todo
+    pub fn with_priority(mut self, priority: TodoPriority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Make this todo repeat
+    pub fn with_recurrence(mut self, recurrence: RecurrencePattern) -> Self {
+        self.recurrence = Some(recurrence);
+        self
+    }
 }
 
 /// Represents a todo/reminder item
@@ -57,6 +132,17 @@ pub struct TodoItem {
     pub completed: bool,
 }
 
+impl TodoItem {
+    /// Whether the todo is not completed and its time, "YYYY-MM-DD HH:MM", has passed
+    pub fn is_overdue(&self, now: chrono::NaiveDateTime) -> bool {
+        !self.completed
+            && self.reminder_time.as_deref().is_some_and(|time| {
+                chrono::NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M")
+                    .is_ok_and(|time| time < now)
+            })
+    }
+}
+
 /// Error types specific to todo operations
 #[derive(Debug, thiserror::Error)]
 pub enum TodoError {
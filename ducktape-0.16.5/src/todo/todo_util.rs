@@ -5,18 +5,6 @@
 use anyhow::Result;
 use chrono::{DateTime, Local};
 
-/// Escape a string for use in AppleScript to prevent command injection
-pub fn escape_applescript_string(input: &str) -> String {
-    // First replace double quotes with escaped quotes for AppleScript
-    let escaped = input.replace("\"", "\"\"");
-
-    // Remove any control characters that could interfere with AppleScript execution
-    escaped
-        .chars()
-        .filter(|&c| !c.is_control() || c == '\n' || c == '\t')
-        .collect::<String>()
-}
-
 /// Format a reminder time from standard format to AppleScript format
 pub fn format_reminder_time(time_str: &str) -> Result<String> {
     // Parse input in format "YYYY-MM-DD HH:MM"
@@ -30,21 +18,17 @@ pub fn format_reminder_time(time_str: &str) -> Result<String> {
     }
 }
 
-/// Calculate a relative date (e.g., "tomorrow", "next week") into a specific date
+/// Calculate a relative date (e.g., "tomorrow", "next week", "end of month") into a specific date
+///
+/// The time of day is kept from now.
 pub fn resolve_relative_date(date_str: &str) -> Result<DateTime<Local>> {
     let now = Local::now();
-
-    match date_str.to_lowercase().as_str() {
-        "today" => Ok(now),
-        "tomorrow" => Ok(now + chrono::Duration::days(1)),
-        "next week" => Ok(now + chrono::Duration::days(7)),
-        "next month" => {
-            // Simple approach - just add 30 days
-            // In a real implementation, you would want to handle month boundaries properly
-            Ok(now + chrono::Duration::days(30))
-        }
-        _ => Err(anyhow::anyhow!("Unsupported relative date: {}", date_str)),
-    }
+    let date = crate::dates::parse_date(date_str, now.date_naive())
+        .ok_or_else(|| anyhow::anyhow!("Unsupported relative date: {}", date_str))?;
+    date.and_time(now.time())
+        .and_local_timezone(Local)
+        .earliest()
+        .ok_or_else(|| anyhow::anyhow!("Invalid local time on {}", date))
 }
 
 /// Parse a list of todo items returned from AppleScript
@@ -61,16 +45,6 @@ pub fn parse_todo_list_output(output: &str) -> Vec<super::TodoItem> {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_escape_applescript_string() {
-        assert_eq!(escape_applescript_string("Hello"), "Hello");
-        assert_eq!(escape_applescript_string("Hello\"World"), "Hello\"\"World");
-        assert_eq!(escape_applescript_string("Line 1\nLine 2"), "Line 1\nLine 2");
-
-        // Test with control characters
-        assert_eq!(escape_applescript_string("Test\u{0007}"), "Test");
-    }
-
     #[test]
     fn test_format_reminder_time() {
         let result = format_reminder_time("2025-04-22 15:30").unwrap();
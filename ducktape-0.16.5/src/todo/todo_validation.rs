@@ -4,19 +4,11 @@
 
 use anyhow::Result;
 use chrono::NaiveDateTime;
-use log::error;
-use regex::Regex;
+use tracing::error;
 
 /// Validate a todo title
 pub fn validate_title(title: &str) -> Result<()> {
-    if title.trim().is_empty() {
-        return Err(anyhow::anyhow!("Todo title cannot be empty"));
-    }
-
-    if title.len() > 255 {
-        return Err(anyhow::anyhow!("Todo title cannot exceed 255 characters"));
-    }
-
+    crate::validation::title("todo title", title)?;
     Ok(())
 }
 
@@ -33,21 +25,19 @@ pub fn validate_reminder_time(time_str: &str) -> Result<()> {
     }
 }
 
-/// Validate a reminder list name
-pub fn validate_list_name(list_name: &str) -> Result<()> {
-    if list_name.trim().is_empty() {
-        return Err(anyhow::anyhow!("List name cannot be empty"));
-    }
-
-    // Check for invalid characters in list name
-    let invalid_chars = r#"[/\:*?"<>|]"#;
-    let re = Regex::new(invalid_chars).unwrap();
-    if re.is_match(list_name) {
-        return Err(anyhow::anyhow!(
-            "List name contains invalid characters. Avoid: / \\ : * ? \" < > |"
-        ));
+/// Validate a due date, either all-day (YYYY-MM-DD) or timed (YYYY-MM-DD HH:MM)
+pub fn validate_due_date(due: &str) -> Result<()> {
+    if chrono::NaiveDate::parse_from_str(due, "%Y-%m-%d").is_ok() {
+        return Ok(());
     }
+    validate_reminder_time(due).map_err(|_| {
+        anyhow::anyhow!("Invalid due date format. Expected YYYY-MM-DD or YYYY-MM-DD HH:MM")
+    })
+}
 
+/// Validate a reminder list name
+pub fn validate_list_name(list_name: &str) -> Result<()> {
+    crate::validation::list_name(list_name)?;
     Ok(())
 }
 
@@ -61,6 +51,11 @@ pub fn validate_todo_config<'a>(config: &super::TodoConfig<'a>) -> Result<()> {
         validate_reminder_time(time_str)?;
     }
 
+    // Validate due date if provided
+    if let Some(due) = config.due_date {
+        validate_due_date(due)?;
+    }
+
     // Validate list names
     for list in &config.lists {
         validate_list_name(list)?;
@@ -93,6 +88,13 @@ mod tests {
         assert!(validate_reminder_time("2025/04/15 14:30").is_err()); // wrong format
     }
 
+    #[test]
+    fn test_validate_due_date() {
+        assert!(validate_due_date("2025-04-15").is_ok());
+        assert!(validate_due_date("2025-04-15 09:00").is_ok());
+        assert!(validate_due_date("next friday").is_err());
+    }
+
     #[test]
     fn test_validate_list_name() {
         assert!(validate_list_name("Work").is_ok());
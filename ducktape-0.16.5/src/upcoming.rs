@@ -0,0 +1,405 @@
+//! Events and reminders coming up soon, for `/api/upcoming` and widgets.
+//
+// Widgets and launcher extensions ask often and want an answer at once, so the events and
+// reminders read for them are kept in memory for `REFRESH_SECS`. When that copy is older, they
+// are read again from Calendar.app and the reminders backend. When an app cannot be read
+// (it is not allowed, times out, or the platform has no Apple apps), the events DuckTape
+// tracks in ~/.ducktape/events.json and the reminders it created stand in for it.
+
+use crate::config::{CalendarBackendKind, Config};
+use crate::state::{CalendarItem, StateManager};
+use crate::todo::TodoItem;
+use anyhow::{Result, anyhow};
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::{debug, warn};
+use utoipa::ToSchema;
+
+/// Seconds the events and reminders read from the apps are reused
+pub const REFRESH_SECS: u64 = 60;
+
+/// The longest look ahead, and the days of events read from Calendar.app
+pub const MAX_WITHIN_DAYS: i64 = 31;
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Whether an item is an event or a reminder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UpcomingKind {
+    Event,
+    Reminder,
+}
+
+/// An event or reminder coming up
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct UpcomingItem {
+    pub kind: UpcomingKind,
+    pub title: String,
+    /// Start of the event or due time of the reminder, YYYY-MM-DD HH:MM
+    pub at: String,
+    /// End of the event, YYYY-MM-DD HH:MM
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+    pub all_day: bool,
+    /// Calendar of the event or list of the reminder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub list: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// A reminder that was due before now and is not completed
+    pub overdue: bool,
+}
+
+/// Where the items of a feed were read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UpcomingSource {
+    /// Just now, from the apps
+    Live,
+    /// From the apps, within the last `REFRESH_SECS`
+    Cache,
+    /// From DuckTape's own state, because an app could not be read
+    State,
+}
+
+/// The items coming up within some time, soonest first
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct UpcomingFeed {
+    /// When the feed was made, RFC 3339
+    pub generated_at: String,
+    pub within_minutes: i64,
+    pub source: UpcomingSource,
+    pub items: Vec<UpcomingItem>,
+}
+
+/// How far to look ahead: `90m`, `24h`, `3d` or `1w`; a bare number is hours
+pub fn parse_within(s: &str) -> Result<Duration> {
+    let s = s.trim().to_lowercase();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: i64 = number
+        .parse()
+        .map_err(|_| anyhow!("Invalid time span: {} (e.g. 90m, 24h, 3d)", s))?;
+    let within = match unit.trim() {
+        "m" | "min" | "mins" | "minutes" => Duration::minutes(number),
+        "" | "h" | "hr" | "hrs" | "hours" => Duration::hours(number),
+        "d" | "day" | "days" => Duration::days(number),
+        "w" | "week" | "weeks" => Duration::weeks(number),
+        _ => return Err(anyhow!("Invalid time span: {} (e.g. 90m, 24h, 3d)", s)),
+    };
+    if within <= Duration::zero() || within > Duration::days(MAX_WITHIN_DAYS) {
+        return Err(anyhow!("Time span must be more than 0 and at most {} days", MAX_WITHIN_DAYS));
+    }
+    Ok(within)
+}
+
+/// A due time as Reminders.app gives it, or a date alone for an all-day reminder
+fn parse_due(due: &str) -> Option<(NaiveDateTime, bool)> {
+    let due = due.trim();
+    NaiveDateTime::parse_from_str(due, TIME_FORMAT)
+        .map(|at| (at, false))
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(due, "%Y-%m-%d")
+                .ok()
+                .map(|day| (day.and_time(NaiveTime::MIN), true))
+        })
+}
+
+/// The occurrences of `item` overlapping `now` up to `until`
+fn event_occurrences(
+    item: &CalendarItem,
+    now: NaiveDateTime,
+    until: NaiveDateTime,
+) -> Vec<UpcomingItem> {
+    let time = |t: &str| NaiveTime::parse_from_str(t.trim(), "%H:%M").ok();
+    let mut day = now.date() - Duration::days(1);
+    let mut found = Vec::new();
+    while day <= until.date() {
+        if crate::calendar::occurs_on(item, day) {
+            let (start, end) = if item.all_day {
+                (day.and_time(NaiveTime::MIN), (day + Duration::days(1)).and_time(NaiveTime::MIN))
+            } else if let Some(start_time) = time(&item.time) {
+                let start = day.and_time(start_time);
+                let mut end = item
+                    .end_time
+                    .as_deref()
+                    .and_then(time)
+                    .map_or(start + Duration::hours(1), |end| day.and_time(end));
+                if end <= start {
+                    end += Duration::days(1);
+                }
+                (start, end)
+            } else {
+                day += Duration::days(1);
+                continue;
+            };
+            if end > now && start < until {
+                found.push(UpcomingItem {
+                    kind: UpcomingKind::Event,
+                    title: item.title.clone(),
+                    at: start.format(TIME_FORMAT).to_string(),
+                    end: Some(end.format(TIME_FORMAT).to_string()),
+                    all_day: item.all_day,
+                    list: item.calendars.first().cloned(),
+                    location: item.location.clone(),
+                    url: item.url.clone(),
+                    overdue: false,
+                });
+            }
+        }
+        day += Duration::days(1);
+    }
+    found
+}
+
+/// The events going on or starting, and the reminders due, before `until`
+///
+/// Open reminders due before `now` are included as overdue. With `kind`, only items of that
+/// kind are returned.
+pub fn upcoming_items(
+    events: &[CalendarItem],
+    todos: &[TodoItem],
+    now: NaiveDateTime,
+    until: NaiveDateTime,
+    kind: Option<UpcomingKind>,
+) -> Vec<UpcomingItem> {
+    let mut items = Vec::new();
+    if kind != Some(UpcomingKind::Reminder) {
+        items.extend(events.iter().flat_map(|item| event_occurrences(item, now, until)));
+    }
+    if kind != Some(UpcomingKind::Event) {
+        items.extend(todos.iter().filter(|todo| !todo.completed).filter_map(|todo| {
+            let (due, all_day) = parse_due(todo.reminder_time.as_deref()?)?;
+            (due < until).then(|| UpcomingItem {
+                kind: UpcomingKind::Reminder,
+                title: todo.title.clone(),
+                at: due.format(TIME_FORMAT).to_string(),
+                end: None,
+                all_day,
+                list: todo.lists.first().cloned(),
+                location: None,
+                url: None,
+                overdue: if all_day { due.date() < now.date() } else { due < now },
+            })
+        }));
+    }
+    items.sort_by(|a, b| {
+        (&a.at, b.all_day, a.kind == UpcomingKind::Reminder, &a.title).cmp(&(
+            &b.at,
+            a.all_day,
+            b.kind == UpcomingKind::Reminder,
+            &b.title,
+        ))
+    });
+    items
+}
+
+struct Sources {
+    read_at: Instant,
+    events: Vec<CalendarItem>,
+    todos: Vec<TodoItem>,
+    source: UpcomingSource,
+}
+
+static SOURCES: Lazy<Mutex<Option<Sources>>> = Lazy::new(|| Mutex::new(None));
+
+/// Events from Calendar.app, or else the tracked ones
+async fn read_events(today: NaiveDate) -> (Vec<CalendarItem>, bool) {
+    let live = match Config::load() {
+        Ok(config) if config.backend.calendar_kind() == CalendarBackendKind::Apple => {
+            crate::watch::snapshot(today - Duration::days(1), MAX_WITHIN_DAYS as u32 + 2, None)
+                .await
+        }
+        Ok(_) => Err(anyhow!("the calendar backend cannot be read")),
+        Err(e) => Err(e),
+    };
+    match live {
+        Ok(events) => (events, true),
+        Err(e) => {
+            warn!("Using tracked events for upcoming items: {}", e);
+            (crate::state::load_events().unwrap_or_default(), false)
+        }
+    }
+}
+
+/// Reminders from the reminders backend, or else the ones DuckTape created
+async fn read_todos() -> (Vec<TodoItem>, bool) {
+    match crate::todo::get_todos(None).await {
+        Ok(todos) => (todos, true),
+        Err(e) => {
+            warn!("Using created reminders for upcoming items: {}", e);
+            let created: Vec<crate::state::TodoItem> =
+                StateManager::new().and_then(|m| m.load()).unwrap_or_default();
+            let todos = created
+                .into_iter()
+                .map(|todo| TodoItem {
+                    title: todo.title,
+                    notes: todo.notes,
+                    lists: todo.lists,
+                    reminder_time: todo.reminder_time,
+                    completed: false,
+                })
+                .collect();
+            (todos, false)
+        }
+    }
+}
+
+/// The events and reminders coming up within `within`
+///
+/// With `refresh` the apps are read even when the copy in memory is recent.
+pub async fn upcoming(
+    within: Duration,
+    kind: Option<UpcomingKind>,
+    refresh: bool,
+) -> Result<UpcomingFeed> {
+    let now = Local::now();
+    let cached = SOURCES.lock().ok().and_then(|sources| {
+        let sources = sources.as_ref()?;
+        let fresh = !refresh && sources.read_at.elapsed().as_secs() < REFRESH_SECS;
+        fresh.then(|| (sources.events.clone(), sources.todos.clone(), sources.source))
+    });
+    let (events, todos, source) = match cached {
+        // A copy read from the apps is served as such; one that fell back to state still did
+        Some((events, todos, UpcomingSource::Live)) => (events, todos, UpcomingSource::Cache),
+        Some(cached) => cached,
+        None => {
+            let ((events, live_events), (todos, live_todos)) =
+                tokio::join!(read_events(now.date_naive()), read_todos());
+            let source = if live_events && live_todos {
+                UpcomingSource::Live
+            } else {
+                UpcomingSource::State
+            };
+            if let Ok(mut sources) = SOURCES.lock() {
+                *sources = Some(Sources {
+                    read_at: Instant::now(),
+                    events: events.clone(),
+                    todos: todos.clone(),
+                    source,
+                });
+            }
+            (events, todos, source)
+        }
+    };
+
+    let now_naive = now.naive_local();
+    let items = upcoming_items(&events, &todos, now_naive, now_naive + within, kind);
+    debug!("{} item(s) coming up within {} minutes", items.len(), within.num_minutes());
+    Ok(UpcomingFeed {
+        generated_at: now.to_rfc3339(),
+        within_minutes: within.num_minutes(),
+        source,
+        items,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, TIME_FORMAT).unwrap()
+    }
+
+    fn event(title: &str, date: &str, time: &str, end: &str) -> CalendarItem {
+        CalendarItem {
+            title: title.to_string(),
+            date: date.to_string(),
+            time: time.to_string(),
+            calendars: vec!["Work".to_string()],
+            end_time: Some(end.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn todo(title: &str, due: Option<&str>, completed: bool) -> TodoItem {
+        TodoItem {
+            title: title.to_string(),
+            notes: None,
+            lists: vec!["Inbox".to_string()],
+            reminder_time: due.map(str::to_string),
+            completed,
+        }
+    }
+
+    #[test]
+    fn test_parse_within() {
+        assert_eq!(parse_within("24h").unwrap(), Duration::hours(24));
+        assert_eq!(parse_within("90m").unwrap(), Duration::minutes(90));
+        assert_eq!(parse_within("3d").unwrap(), Duration::days(3));
+        assert_eq!(parse_within("1w").unwrap(), Duration::weeks(1));
+        assert_eq!(parse_within("12").unwrap(), Duration::hours(12));
+        assert!(parse_within("0h").is_err());
+        assert!(parse_within("60d").is_err());
+        assert!(parse_within("soon").is_err());
+    }
+
+    #[test]
+    fn test_upcoming_items() {
+        let now = at("2025-03-12 10:00");
+        let mut standup = event("Standup", "2025-03-10", "09:30", "10:15");
+        standup.recurrence = Some("FREQ=DAILY;INTERVAL=1".to_string());
+        let events = vec![
+            standup,
+            event("Lunch", "2025-03-12", "12:00", "13:00"),
+            event("Retro", "2025-03-14", "15:00", "16:00"),
+        ];
+        let todos = vec![
+            todo("Send invoice", Some("2025-03-11 17:00"), false),
+            todo("Call Bob", Some("2025-03-12 11:00"), false),
+            todo("Pay rent", Some("2025-03-12 09:00"), true),
+            todo("Someday", None, false),
+        ];
+
+        let items = upcoming_items(&events, &todos, now, now + Duration::hours(24), None);
+        let titles: Vec<(&str, &str)> =
+            items.iter().map(|item| (item.title.as_str(), item.at.as_str())).collect();
+        assert_eq!(
+            titles,
+            vec![
+                ("Send invoice", "2025-03-11 17:00"),
+                ("Standup", "2025-03-12 09:30"),
+                ("Call Bob", "2025-03-12 11:00"),
+                ("Lunch", "2025-03-12 12:00"),
+                ("Standup", "2025-03-13 09:30"),
+            ]
+        );
+        assert!(items[0].overdue);
+        assert_eq!(items[1].end.as_deref(), Some("2025-03-12 10:15"));
+        assert_eq!(items[2].list.as_deref(), Some("Inbox"));
+
+        let reminders = upcoming_items(
+            &events,
+            &todos,
+            now,
+            now + Duration::hours(24),
+            Some(UpcomingKind::Reminder),
+        );
+        assert!(reminders.iter().all(|item| item.kind == UpcomingKind::Reminder));
+        assert_eq!(reminders.len(), 2);
+    }
+
+    #[test]
+    fn test_all_day_items() {
+        let now = at("2025-03-12 10:00");
+        let mut offsite = event("Offsite", "2025-03-12", "00:00", "00:00");
+        offsite.all_day = true;
+        offsite.end_time = None;
+        let todos = vec![todo("Renew passport", Some("2025-03-12"), false)];
+
+        let items = upcoming_items(&[offsite], &todos, now, now + Duration::hours(2), None);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].title, "Offsite");
+        assert_eq!(items[0].end.as_deref(), Some("2025-03-13 00:00"));
+        // Due today, so not overdue yet
+        assert!(items[1].all_day && !items[1].overdue);
+    }
+}
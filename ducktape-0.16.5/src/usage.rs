@@ -0,0 +1,275 @@
+//! Token usage and cost tracking for language model calls.
+//
+// Every answered API call adds its prompt and completion tokens to a record per day, provider
+// and model in ~/.ducktape/usage.json, with a cost estimated from the model's price per
+// million tokens. Before calling an API, parsers check the month's spending against
+// `usage.monthly_budget_usd`: over budget they warn, or refuse when `usage.block_over_budget`
+// is set.
+
+use crate::config::{ModelPrice, UsageConfig};
+use crate::state::{StateManager, UsageRecord};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Local};
+use serde_json::Value;
+use tracing::warn;
+
+/// Built-in prices in USD per million input and output tokens
+const PRICES: &[(&str, f64, f64)] = &[
+    ("deepseek-chat", 0.27, 1.10),
+    ("deepseek-reasoner", 0.55, 2.19),
+    ("grok-2-latest", 2.00, 10.00),
+    ("grok-2", 2.00, 10.00),
+    ("grok-3", 3.00, 15.00),
+    ("grok-3-mini", 0.30, 0.50),
+];
+
+/// Price of a model, from `usage.prices` or the built-in table
+pub fn price_for(config: &UsageConfig, model: &str) -> Option<ModelPrice> {
+    config.prices.get(model).copied().or_else(|| {
+        PRICES
+            .iter()
+            .find(|(name, _, _)| *name == model)
+            .map(|&(_, input, output)| ModelPrice {
+                input_per_million: input,
+                output_per_million: output,
+            })
+    })
+}
+
+/// Tokens used by one API call
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+impl TokenUsage {
+    /// Read the `usage` object of a chat completion response
+    pub fn from_response(response: &Value) -> Option<Self> {
+        let usage = response.get("usage")?;
+        Some(Self {
+            prompt_tokens: usage["prompt_tokens"].as_u64()?,
+            completion_tokens: usage["completion_tokens"].as_u64().unwrap_or(0),
+        })
+    }
+
+    /// Estimated cost in USD; models without a known price cost nothing
+    pub fn cost(&self, price: Option<ModelPrice>) -> f64 {
+        price.map_or(0.0, |p| {
+            (self.prompt_tokens as f64 * p.input_per_million
+                + self.completion_tokens as f64 * p.output_per_million)
+                / 1_000_000.0
+        })
+    }
+}
+
+/// Spending this month compared with the budget
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BudgetStatus {
+    NoBudget { spent: f64 },
+    Within { spent: f64, budget: f64 },
+    Exceeded { spent: f64, budget: f64 },
+}
+
+/// Token usage per day, provider and model
+#[derive(Debug, Clone, Default)]
+pub struct UsageLedger {
+    records: Vec<UsageRecord>,
+}
+
+impl UsageLedger {
+    pub fn new(records: Vec<UsageRecord>) -> Self {
+        Self { records }
+    }
+
+    pub fn load() -> Result<Self> {
+        Ok(Self::new(StateManager::new()?.load()?))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        StateManager::new()?.save(&self.records)
+    }
+
+    /// Add a call's tokens and cost to today's record for the provider and model
+    pub fn record(
+        &mut self,
+        provider: &str,
+        model: &str,
+        usage: TokenUsage,
+        cost_usd: f64,
+        now: DateTime<Local>,
+    ) {
+        let date = now.format("%Y-%m-%d").to_string();
+        let index = match self
+            .records
+            .iter()
+            .position(|r| r.date == date && r.provider == provider && r.model == model)
+        {
+            Some(index) => index,
+            None => {
+                self.records.push(UsageRecord {
+                    date,
+                    provider: provider.to_string(),
+                    model: model.to_string(),
+                    requests: 0,
+                    prompt_tokens: 0,
+                    completion_tokens: 0,
+                    cost_usd: 0.0,
+                });
+                self.records.len() - 1
+            }
+        };
+        let record = &mut self.records[index];
+        record.requests += 1;
+        record.prompt_tokens += usage.prompt_tokens;
+        record.completion_tokens += usage.completion_tokens;
+        record.cost_usd += cost_usd;
+    }
+
+    /// Records of a month (YYYY-MM), oldest first
+    pub fn month(&self, month: &str) -> Vec<&UsageRecord> {
+        let mut records: Vec<&UsageRecord> =
+            self.records.iter().filter(|r| r.date.starts_with(month)).collect();
+        records.sort_by(|a, b| (&a.date, &a.provider).cmp(&(&b.date, &b.provider)));
+        records
+    }
+
+    /// Estimated USD spent in a month (YYYY-MM)
+    pub fn month_cost(&self, month: &str) -> f64 {
+        self.month(month).iter().map(|r| r.cost_usd).sum()
+    }
+
+    pub fn budget_status(&self, config: &UsageConfig, now: DateTime<Local>) -> BudgetStatus {
+        let spent = self.month_cost(&now.format("%Y-%m").to_string());
+        match config.monthly_budget_usd {
+            None => BudgetStatus::NoBudget { spent },
+            Some(budget) if spent >= budget => BudgetStatus::Exceeded { spent, budget },
+            Some(budget) => BudgetStatus::Within { spent, budget },
+        }
+    }
+}
+
+/// Fail when this month's budget is spent and `usage.block_over_budget` is set
+///
+/// Over budget without blocking, a warning is logged and the call may go ahead.
+pub fn check_budget(config: &UsageConfig) -> Result<()> {
+    if config.monthly_budget_usd.is_none() {
+        return Ok(());
+    }
+    let ledger = UsageLedger::load().unwrap_or_else(|e| {
+        warn!("Failed to read usage records: {}", e);
+        UsageLedger::default()
+    });
+    if let BudgetStatus::Exceeded { spent, budget } = ledger.budget_status(config, Local::now()) {
+        if config.block_over_budget {
+            return Err(anyhow!(
+                "Monthly language model budget of ${:.2} reached (${:.2} spent). Raise usage.monthly_budget_usd or wait until next month.",
+                budget,
+                spent
+            ));
+        }
+        warn!("Monthly language model budget of ${:.2} exceeded (${:.2} spent)", budget, spent);
+    }
+    Ok(())
+}
+
+/// Record the tokens reported in a chat completion response; failures are only logged
+pub fn record_response(config: &UsageConfig, provider: &str, model: &str, response: &Value) {
+    let Some(usage) = TokenUsage::from_response(response) else {
+        warn!("{} response did not report token usage", provider);
+        return;
+    };
+    let cost = usage.cost(price_for(config, model));
+    let result = UsageLedger::load().and_then(|mut ledger| {
+        ledger.record(provider, model, usage, cost, Local::now());
+        ledger.save()
+    });
+    if let Err(e) = result {
+        warn!("Failed to record token usage: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    fn on(day: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2025, 3, day, 12, 0, 0).unwrap()
+    }
+
+    fn tokens(prompt_tokens: u64, completion_tokens: u64) -> TokenUsage {
+        TokenUsage { prompt_tokens, completion_tokens }
+    }
+
+    #[test]
+    fn test_usage_and_cost() {
+        let response = json!({
+            "choices": [],
+            "usage": { "prompt_tokens": 1000, "completion_tokens": 200, "total_tokens": 1200 }
+        });
+        let usage = TokenUsage::from_response(&response).unwrap();
+        assert_eq!(usage, tokens(1000, 200));
+        assert!(TokenUsage::from_response(&json!({ "choices": [] })).is_none());
+
+        let config = UsageConfig::default();
+        let cost = usage.cost(price_for(&config, "deepseek-chat"));
+        assert!((cost - (1000.0 * 0.27 + 200.0 * 1.10) / 1_000_000.0).abs() < 1e-12);
+        assert_eq!(usage.cost(price_for(&config, "unknown-model")), 0.0);
+
+        let custom = UsageConfig {
+            prices: BTreeMap::from([(
+                "deepseek-chat".to_string(),
+                ModelPrice { input_per_million: 1.0, output_per_million: 2.0 },
+            )]),
+            ..UsageConfig::default()
+        };
+        assert!((usage.cost(price_for(&custom, "deepseek-chat")) - 0.0014).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_records_are_per_day_provider_and_model() {
+        let mut ledger = UsageLedger::default();
+        ledger.record("deepseek", "deepseek-chat", tokens(100, 10), 0.5, on(1));
+        ledger.record("deepseek", "deepseek-chat", tokens(50, 5), 0.25, on(1));
+        ledger.record("grok", "grok-2-latest", tokens(10, 1), 1.0, on(1));
+        ledger.record("deepseek", "deepseek-chat", tokens(10, 1), 2.0, on(2));
+
+        let march = ledger.month("2025-03");
+        assert_eq!(march.len(), 3);
+        assert_eq!(march[0].requests, 2);
+        assert_eq!(march[0].prompt_tokens, 150);
+        assert_eq!(march[1].provider, "grok");
+        assert_eq!(ledger.month_cost("2025-03"), 3.75);
+        assert!(ledger.month("2025-04").is_empty());
+    }
+
+    #[test]
+    fn test_budget_status() {
+        let mut ledger = UsageLedger::default();
+        ledger.record("deepseek", "deepseek-chat", tokens(100, 10), 4.0, on(1));
+
+        let mut config = UsageConfig::default();
+        assert_eq!(ledger.budget_status(&config, on(2)), BudgetStatus::NoBudget { spent: 4.0 });
+
+        config.monthly_budget_usd = Some(5.0);
+        assert_eq!(
+            ledger.budget_status(&config, on(2)),
+            BudgetStatus::Within { spent: 4.0, budget: 5.0 }
+        );
+
+        ledger.record("deepseek", "deepseek-chat", tokens(100, 10), 1.5, on(3));
+        assert_eq!(
+            ledger.budget_status(&config, on(3)),
+            BudgetStatus::Exceeded { spent: 5.5, budget: 5.0 }
+        );
+        // A new month starts from zero
+        let april = Local.with_ymd_and_hms(2025, 4, 1, 9, 0, 0).unwrap();
+        assert_eq!(
+            ledger.budget_status(&config, april),
+            BudgetStatus::Within { spent: 0.0, budget: 5.0 }
+        );
+    }
+}
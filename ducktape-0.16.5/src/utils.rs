@@ -1 +1,142 @@
+//! Shared text helpers.
+//
+// Descriptions and notes are often pasted from HTML emails or web pages. Calendar.app and
+// Reminders.app store plain text, so markup shows up as raw tags and entities. Text that
+// looks like HTML is converted to readable plain text before it is stored, unless the user
+// passes `--raw`.
 
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static HTML_TAG: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)</?(html|head|body|div|p|br|span|table|tr|td|th|a|b|i|u|strong|em|ul|ol|li|h[1-6]|font|meta|style|img|blockquote|hr)(\s[^>]*)?/?>",
+    )
+    .unwrap()
+});
+static IGNORED_BLOCKS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?is)<!--.*?-->|<(script|style|head)\b[^>]*>.*?</(script|style|head)\s*>").unwrap()
+});
+static PARAGRAPH_END: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)</(p|h[1-6]|blockquote|table)\s*>").unwrap());
+static LINE_BREAK: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)<br\s*/?>|</(div|tr|ul|ol)\s*>|<hr\s*/?>").unwrap());
+static LIST_ITEM: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)<li(\s[^>]*)?>").unwrap());
+static CELL: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)</t[dh]\s*>").unwrap());
+static LINK: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?is)<a\s[^>]*href\s*=\s*["']([^"']*)["'][^>]*>(.*?)</a\s*>"#).unwrap()
+});
+static ANY_TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<[^>]*>").unwrap());
+static ENTITY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").unwrap());
+static SPACES: Lazy<Regex> = Lazy::new(|| Regex::new(r"[ \t\u{a0}]+").unwrap());
+static BLANK_LINES: Lazy<Regex> = Lazy::new(|| Regex::new(r"\n{3,}").unwrap());
+
+/// Whether text contains HTML markup rather than plain text
+pub fn looks_like_html(text: &str) -> bool {
+    HTML_TAG.is_match(text) || text.contains("&nbsp;")
+}
+
+/// Convert HTML to readable plain text
+///
+/// Paragraphs are separated by blank lines, other blocks become line breaks, list items
+/// become `- ` bullets, links keep their target in parentheses and entities are decoded.
+pub fn html_to_text(html: &str) -> String {
+    let text = IGNORED_BLOCKS.replace_all(html, "");
+    // Source line breaks carry no meaning in HTML
+    let text = text.replace(['\r', '\n'], " ");
+    let text = LINK.replace_all(&text, |caps: &regex::Captures| {
+        let label = ANY_TAG.replace_all(&caps[2], "").trim().to_string();
+        let target = caps[1].trim_start_matches("mailto:");
+        if label.is_empty() {
+            target.to_string()
+        } else if label == target || target.starts_with('#') {
+            label
+        } else {
+            format!("{} ({})", label, target)
+        }
+    });
+    let text = LIST_ITEM.replace_all(&text, "\n- ");
+    let text = CELL.replace_all(&text, " ");
+    let text = PARAGRAPH_END.replace_all(&text, "\n\n");
+    let text = LINE_BREAK.replace_all(&text, "\n");
+    let text = ANY_TAG.replace_all(&text, "");
+    let text = decode_entities(&text);
+
+    let lines: Vec<String> = text
+        .lines()
+        .map(|line| SPACES.replace_all(line, " ").trim().to_string())
+        .collect();
+    BLANK_LINES.replace_all(&lines.join("\n"), "\n\n").trim().to_string()
+}
+
+fn decode_entities(text: &str) -> String {
+    ENTITY
+        .replace_all(text, |caps: &regex::Captures| {
+            let entity = &caps[1];
+            let decoded = match entity {
+                "nbsp" => Some(' '),
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "ndash" => Some('–'),
+                "mdash" => Some('—'),
+                "hellip" => Some('…'),
+                "rsquo" | "lsquo" => Some('\''),
+                "rdquo" | "ldquo" => Some('"'),
+                _ if entity.starts_with("#x") || entity.starts_with("#X") => {
+                    u32::from_str_radix(&entity[2..], 16).ok().and_then(char::from_u32)
+                }
+                _ if entity.starts_with('#') => {
+                    entity[1..].parse::<u32>().ok().and_then(char::from_u32)
+                }
+                _ => None,
+            };
+            decoded.map(String::from).unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
+/// Prepare pasted notes or descriptions for storage
+///
+/// HTML is converted to plain text unless `raw` is set; plain text is returned unchanged.
+pub fn sanitize_notes(text: &str, raw: bool) -> String {
+    if raw || !looks_like_html(text) { text.to_string() } else { html_to_text(text) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_looks_like_html() {
+        assert!(looks_like_html("<p>Agenda</p>"));
+        assert!(looks_like_html("Hello<br/>world"));
+        assert!(looks_like_html("Dial&nbsp;in"));
+        assert!(!looks_like_html("Bring snacks & drinks"));
+        assert!(!looks_like_html("a < b and c > d"));
+    }
+
+    #[test]
+    fn test_html_to_text() {
+        let html = r#"<html><head><style>p { color: red }</style></head><body>
+            <p>Hi&nbsp;team,</p>
+            <p>Agenda:<ul><li>Budget &amp; hiring</li><li>Q&#38;A</li></ul></p>
+            <p>Join at <a href="https://zoom.us/j/1">Zoom</a> or mail
+            <a href="mailto:ops@example.com">ops@example.com</a></p>
+            <!-- tracking --></body></html>"#;
+        assert_eq!(
+            html_to_text(html),
+            "Hi team,\n\nAgenda:\n- Budget & hiring\n- Q&A\n\nJoin at Zoom (https://zoom.us/j/1) or mail ops@example.com"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_notes_respects_raw() {
+        assert_eq!(sanitize_notes("<b>Bold</b>", false), "Bold");
+        assert_eq!(sanitize_notes("<b>Bold</b>", true), "<b>Bold</b>");
+        assert_eq!(sanitize_notes("Line 1\nLine 2", false), "Line 1\nLine 2");
+    }
+}
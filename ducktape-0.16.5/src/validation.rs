@@ -1,2 +1,343 @@
-// This file is intentionally left blank as a placeholder for future validation utilities.
-// It will be properly implemented in a future update.
+//! Reusable validators for user input.
+//
+// Handlers, the typed commands in `commands` and the API server check dates, times, durations,
+// emails, names, titles and flag combinations here rather than each trimming quotes and
+// testing on their own. A validator returns the cleaned value, or a `ValidationError` naming
+// the field and suggesting a fix. `DucktapeError` treats it as invalid input, so the API
+// answers 400 and the CLI exits with 65.
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// Longest title accepted for events, reminders and notes
+pub const MAX_TITLE_LEN: usize = 255;
+
+/// Longest duration accepted, one week in minutes
+pub const MAX_DURATION_MINUTES: u32 = 7 * 24 * 60;
+
+/// Input that failed validation, with what to do instead
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// Field or flag that was invalid, e.g. "start time" or "--until"
+    pub field: String,
+    pub message: String,
+    pub suggestion: Option<String>,
+}
+
+impl ValidationError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { field: field.into(), message: message.into(), suggestion: None }
+    }
+
+    pub fn suggest(mut self, suggestion: impl Into<String>) -> Self {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid {}: {}", self.field, self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, ". {}", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+pub type Result<T> = std::result::Result<T, ValidationError>;
+
+/// Strip whitespace and the quotes left around arguments by the legacy tokenizer
+pub fn clean(value: &str) -> &str {
+    value.trim().trim_matches('"').trim()
+}
+
+/// Number of single-character edits between `a` and `b`
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b_chars.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1; b_chars.len() + 1];
+        for (j, cb) in b_chars.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+    prev[b_chars.len()]
+}
+
+/// The candidate closest to `value`, when it is close enough to be a typo
+pub fn closest<'a>(value: &str, candidates: &'a [String]) -> Option<&'a str> {
+    let value = value.to_lowercase();
+    candidates
+        .iter()
+        .map(|c| (edit_distance(&value, &c.to_lowercase()), c))
+        .filter(|(distance, c)| *distance <= (c.chars().count() / 3).max(1))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, c)| c.as_str())
+}
+
+/// A title of an event, reminder or note: not empty and at most `MAX_TITLE_LEN` characters
+pub fn title(field: &str, value: &str) -> Result<String> {
+    let value = clean(value);
+    if value.is_empty() {
+        return Err(ValidationError::new(field, "it cannot be empty"));
+    }
+    let length = value.chars().count();
+    if length > MAX_TITLE_LEN {
+        return Err(ValidationError::new(
+            field,
+            format!("it is {} characters long, the limit is {}", length, MAX_TITLE_LEN),
+        )
+        .suggest("Move the details to --notes"));
+    }
+    if value.chars().any(char::is_control) {
+        return Err(ValidationError::new(field, "it contains control characters")
+            .suggest("Put it on a single line"));
+    }
+    Ok(value.to_string())
+}
+
+/// A date as YYYY-MM-DD; accepts everything `dates::resolve_date` does
+pub fn date(field: &str, value: &str) -> Result<String> {
+    let value = clean(value);
+    let resolved = crate::dates::resolve_date(value).map_err(|_| {
+        ValidationError::new(field, format!("'{}' is not a date", value)).suggest(format!(
+            "Use YYYY-MM-DD, {}, 'tomorrow' or 'next friday'",
+            crate::i18n::Locale::current().date_pattern()
+        ))
+    })?;
+    if !crate::calendar::validate_date_format(&resolved) {
+        return Err(ValidationError::new(field, format!("{} is out of range", resolved))
+            .suggest("Use a year between 2000 and 2100"));
+    }
+    Ok(resolved)
+}
+
+/// A time of day as HH:MM; accepts `9:30`, `3pm` and `noon`
+pub fn time(field: &str, value: &str) -> Result<String> {
+    let value = clean(value);
+    if let Some(time) = crate::dates::parse_time(value) {
+        return Ok(time.format("%H:%M").to_string());
+    }
+    let error = ValidationError::new(field, format!("'{}' is not a time of day", value));
+    Err(match value.parse::<u32>() {
+        Ok(hour) if hour < 24 => error.suggest(format!("Did you mean {:02}:00?", hour)),
+        _ => error.suggest("Use HH:MM, e.g. 14:30, or 2pm"),
+    })
+}
+
+/// Start and end times as HH:MM
+///
+/// An end before the start is the next day, for events past midnight; equal times are rejected.
+pub fn time_range(start: &str, end: &str) -> Result<(String, String)> {
+    let (start, end) = (time("start time", start)?, time("end time", end)?);
+    if end == start {
+        return Err(ValidationError::new(
+            "end time",
+            format!("it is the same as the start time {}", start),
+        )
+        .suggest("Give the time the event ends"));
+    }
+    Ok((start, end))
+}
+
+/// A duration in minutes: `90`, `45m`, `1h`, `1h30m`, `1.5h` or `2 hours`
+pub fn duration_minutes(field: &str, value: &str) -> Result<u32> {
+    let value = clean(value).to_lowercase();
+    let invalid = || {
+        ValidationError::new(field, format!("'{}' is not a duration", value))
+            .suggest("Use minutes (90) or hours and minutes (1h30m)")
+    };
+    let re = regex::Regex::new(r"^(?:(\d+(?:\.\d+)?)\s*(?:h|hrs?|hours?))?\s*(?:(\d+(?:\.\d+)?)\s*(?:m|mins?|minutes?)?)?$")
+        .unwrap();
+    let captures = re.captures(&value).filter(|c| c.get(1).is_some() || c.get(2).is_some());
+    let captures = captures.ok_or_else(invalid)?;
+    let number =
+        |i: usize| captures.get(i).map_or(0.0, |m| m.as_str().parse::<f64>().unwrap_or(0.0));
+    let minutes = number(1) * 60.0 + number(2);
+    if minutes < 1.0 || minutes > MAX_DURATION_MINUTES as f64 {
+        return Err(ValidationError::new(
+            field,
+            format!("it must be between 1 minute and {} hours", MAX_DURATION_MINUTES / 60),
+        ));
+    }
+    Ok(minutes.round() as u32)
+}
+
+/// Misspelled mail domains and the domain meant
+const DOMAIN_TYPOS: &[(&str, &str)] = &[
+    ("gmial.com", "gmail.com"),
+    ("gmai.com", "gmail.com"),
+    ("gamil.com", "gmail.com"),
+    ("gmail.co", "gmail.com"),
+    ("hotmial.com", "hotmail.com"),
+    ("hotmai.com", "hotmail.com"),
+    ("yaho.com", "yahoo.com"),
+    ("yahooo.com", "yahoo.com"),
+    ("icloud.co", "icloud.com"),
+    ("iclod.com", "icloud.com"),
+    ("outlok.com", "outlook.com"),
+];
+
+/// An email address to invite
+pub fn email(value: &str) -> Result<String> {
+    let value = clean(value);
+    let (_, domain) = value.split_once('@').ok_or_else(|| {
+        ValidationError::new("email", format!("'{}' has no @", value))
+            .suggest("Use an address such as jane@example.com, or --contacts for a name")
+    })?;
+    if let Some((_, meant)) =
+        DOMAIN_TYPOS.iter().find(|(typo, _)| domain.eq_ignore_ascii_case(typo))
+    {
+        return Err(ValidationError::new("email", format!("'{}' looks misspelled", value))
+            .suggest(format!("Did you mean {}?", value.replace(domain, meant))));
+    }
+    if !crate::calendar::validate_email(value) {
+        return Err(ValidationError::new("email", format!("'{}' is not an email address", value))
+            .suggest("Use an address such as jane@example.com"));
+    }
+    Ok(value.to_string())
+}
+
+/// Comma separated email addresses
+pub fn emails(value: &str) -> Result<Vec<String>> {
+    value.split(',').filter(|e| !clean(e).is_empty()).map(email).collect()
+}
+
+/// A calendar that exists, as Calendar.app spells it; "Work (iCloud)" keeps its account
+///
+/// With no `available` calendars to compare with, only the name itself is checked.
+pub fn calendar_name(value: &str, available: &[String]) -> Result<String> {
+    let value = clean(value);
+    if value.is_empty() {
+        return Err(ValidationError::new("calendar", "the name cannot be empty"));
+    }
+    if available.is_empty() {
+        return Ok(value.to_string());
+    }
+    crate::calendar::select_calendar(Some(value), None, available).ok_or_else(|| {
+        let error = ValidationError::new("calendar", format!("'{}' does not exist", value));
+        match closest(value, available) {
+            Some(meant) => error.suggest(format!("Did you mean '{}'?", meant)),
+            None => error.suggest("Run `ducktape calendar list` to see your calendars"),
+        }
+    })
+}
+
+/// A reminder list name Reminders.app accepts
+pub fn list_name(value: &str) -> Result<String> {
+    let value = clean(value);
+    if value.is_empty() {
+        return Err(ValidationError::new("list", "the name cannot be empty"));
+    }
+    if let Some(c) = value.chars().find(|c| r#"/\:*?"<>|"#.contains(*c)) {
+        return Err(ValidationError::new("list", format!("'{}' contains '{}'", value, c))
+            .suggest("Avoid / \\ : * ? \" < > |"));
+    }
+    Ok(value.to_string())
+}
+
+/// Fail when two flags that exclude each other are both given
+pub fn exclusive_flags(
+    flags: &HashMap<String, Option<String>>,
+    pairs: &[(&str, &str)],
+) -> Result<()> {
+    match pairs.iter().find(|(a, b)| flags.contains_key(*a) && flags.contains_key(*b)) {
+        Some((a, b)) => Err(ValidationError::new(
+            "flags",
+            format!("--{} and --{} cannot be used together", a, b),
+        )
+        .suggest(format!("Use either --{} or --{}", a, b))),
+        None => Ok(()),
+    }
+}
+
+/// Fail when `flag` is given without the flag it needs
+pub fn flag_requires(
+    flags: &HashMap<String, Option<String>>,
+    flag: &str,
+    required: &str,
+) -> Result<()> {
+    if flags.contains_key(flag) && !flags.contains_key(required) {
+        return Err(ValidationError::new(
+            format!("--{}", flag),
+            format!("it only applies together with --{}", required),
+        )
+        .suggest(format!("Add --{} or leave out --{}", required, flag)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_title() {
+        assert_eq!(title("title", "  \"Team sync\" ").unwrap(), "Team sync");
+        assert!(title("title", "\"\"").is_err());
+        let error = title("title", &"a".repeat(300)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid title: it is 300 characters long, the limit is 255. Move the details to --notes"
+        );
+    }
+
+    #[test]
+    fn test_times() {
+        assert_eq!(time("start time", "9:30").unwrap(), "09:30");
+        assert_eq!(time("start time", "3pm").unwrap(), "15:00");
+        let error = time("start time", "14").unwrap_err();
+        assert_eq!(error.suggestion.as_deref(), Some("Did you mean 14:00?"));
+        assert_eq!(time_range("9am", "10:30").unwrap(), ("09:00".into(), "10:30".into()));
+        assert!(time_range("23:00", "01:00").is_ok());
+        assert_eq!(time_range("10:00", "10am").unwrap_err().field, "end time");
+    }
+
+    #[test]
+    fn test_duration_minutes() {
+        assert_eq!(duration_minutes("duration", "90").unwrap(), 90);
+        assert_eq!(duration_minutes("duration", "45m").unwrap(), 45);
+        assert_eq!(duration_minutes("duration", "1h30m").unwrap(), 90);
+        assert_eq!(duration_minutes("duration", "1.5h").unwrap(), 90);
+        assert_eq!(duration_minutes("duration", "2 hours").unwrap(), 120);
+        assert!(duration_minutes("duration", "0").is_err());
+        assert!(duration_minutes("duration", "soon").is_err());
+    }
+
+    #[test]
+    fn test_email() {
+        assert_eq!(email(" jane@example.com ").unwrap(), "jane@example.com");
+        let error = email("jane@gmial.com").unwrap_err();
+        assert_eq!(error.suggestion.as_deref(), Some("Did you mean jane@gmail.com?"));
+        assert!(email("jane").is_err());
+        assert_eq!(emails("a@example.com, b@example.com,").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_calendar_and_list_names() {
+        let available = vec!["Work".to_string(), "Personal".to_string()];
+        assert_eq!(calendar_name("work", &available).unwrap(), "Work");
+        let error = calendar_name("Personnal", &available).unwrap_err();
+        assert_eq!(error.suggestion.as_deref(), Some("Did you mean 'Personal'?"));
+        assert_eq!(calendar_name("Anything", &[]).unwrap(), "Anything");
+        assert!(list_name("Groceries").is_ok());
+        assert!(list_name("Work/Home").is_err());
+    }
+
+    #[test]
+    fn test_flags() {
+        let flags = HashMap::from([
+            ("count".to_string(), Some("3".to_string())),
+            ("until".to_string(), Some("2025-05-01".to_string())),
+        ]);
+        assert!(exclusive_flags(&flags, &[("count", "until")]).is_err());
+        assert!(exclusive_flags(&flags, &[("zoom", "meeting")]).is_ok());
+        assert!(flag_requires(&flags, "count", "repeat").is_err());
+        assert!(flag_requires(&flags, "interval", "repeat").is_ok());
+    }
+}
@@ -0,0 +1,399 @@
+//! Following changes made to Calendar.app outside DuckTape: `ducktape watch`.
+//
+// Calendar.app offers AppleScript no change notifications, so `watch` polls: every interval
+// it reads the events starting in the next `--days` days and compares them, by UID, with the
+// previous read. Events added, changed or removed in Calendar.app (or synced from another
+// device) are printed, optionally shown as notifications, and written to the events DuckTape
+// tracks in ~/.ducktape/events.json, so that agenda links, reports and feeds see them.
+//
+// Only the days both reads cover are compared: at midnight the window moves on a day, and
+// yesterday's events leaving it or next week's entering it are not changes. An event moved
+// past the end of the window counts as removed.
+
+use crate::app_state::SharedState;
+use crate::applescript::{Script, Template};
+use crate::script_runner::run_applescript_async;
+use crate::state::{CalendarItem, StateManager};
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use std::fmt;
+use tracing::{debug, info, warn};
+
+/// Shortest time between two reads, so Calendar.app is not kept busy
+pub const MIN_INTERVAL_SECS: u64 = 10;
+
+const SNAPSHOT_SCRIPT: &str = r#"on pad(n)
+    return text -2 thru -1 of ("0" & (n as integer))
+end pad
+
+on fmt(d)
+    return (year of d as text) & "-" & my pad(month of d as integer) & "-" & my pad(day of d) & " " & my pad(hours of d) & ":" & my pad(minutes of d)
+end fmt
+
+on clean(v)
+    if v is missing value then return ""
+    set AppleScript's text item delimiters to {return, linefeed, tab}
+    set parts to text items of (v as text)
+    set AppleScript's text item delimiters to " "
+    set flat to parts as text
+    set AppleScript's text item delimiters to ""
+    return flat
+end clean
+
+tell application "Calendar"
+    try
+        set fromDate to current date
+        set day of fromDate to 1
+        set year of fromDate to ${year}
+        set month of fromDate to ${month}
+        set day of fromDate to ${day}
+        set time of fromDate to 0
+        set untilDate to fromDate + (${days} * days)
+        set output to ""
+        repeat with c in ${calendars}
+            repeat with e in (every event of c whose start date is greater than or equal to fromDate and start date is less than untilDate)
+                set output to output & (name of c) & tab & (uid of e) & tab & my clean(summary of e) & tab & my fmt(start date of e) & tab & my fmt(end date of e) & tab & (allday event of e as text) & tab & my clean(location of e) & tab & my clean(recurrence of e) & linefeed
+            end repeat
+        end repeat
+        return output
+    on error errMsg
+        return "Error: " & errMsg
+    end try
+end tell"#;
+
+/// What `watch` looks at and how often
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchOptions {
+    pub interval_secs: u64,
+    /// Days ahead, starting today
+    pub days: u32,
+    /// Only this calendar
+    pub calendar: Option<String>,
+    /// Show each change as a notification too
+    pub notify: bool,
+}
+
+/// A difference between two reads of the calendars
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventChange {
+    Added(CalendarItem),
+    Changed { before: CalendarItem, after: CalendarItem },
+    Removed(CalendarItem),
+}
+
+fn when(item: &CalendarItem) -> String {
+    if item.all_day {
+        return format!("{} (all day)", item.date);
+    }
+    match &item.end_time {
+        Some(end) => format!("{} {}-{}", item.date, item.time, end),
+        None => format!("{} {}", item.date, item.time),
+    }
+}
+
+/// What changed between two versions of an event, e.g. `location 'Room 4' → 'Room 7'`
+fn differences(before: &CalendarItem, after: &CalendarItem) -> Vec<String> {
+    let mut changes = Vec::new();
+    if before.title != after.title {
+        changes.push(format!("title '{}' → '{}'", before.title, after.title));
+    }
+    if (&before.date, &before.time, &before.end_time, before.all_day)
+        != (&after.date, &after.time, &after.end_time, after.all_day)
+    {
+        changes.push(format!("{} → {}", when(before), when(after)));
+    }
+    if before.location != after.location {
+        changes.push(format!(
+            "location '{}' → '{}'",
+            before.location.as_deref().unwrap_or_default(),
+            after.location.as_deref().unwrap_or_default()
+        ));
+    }
+    if before.calendars != after.calendars {
+        changes.push(format!(
+            "calendar {} → {}",
+            before.calendars.join(", "),
+            after.calendars.join(", ")
+        ));
+    }
+    if before.recurrence != after.recurrence {
+        changes.push("repeat rule".to_string());
+    }
+    changes
+}
+
+impl fmt::Display for EventChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventChange::Added(item) => {
+                write!(f, "+ {} on {} ({})", item.title, when(item), item.calendars.join(", "))
+            }
+            EventChange::Changed { before, after } => {
+                write!(f, "~ {}: {}", before.title, differences(before, after).join("; "))
+            }
+            EventChange::Removed(item) => write!(f, "- {} on {}", item.title, when(item)),
+        }
+    }
+}
+
+/// Script reading the events that start in `days` days from `from`
+pub fn snapshot_script(from: NaiveDate, days: u32, calendar: Option<&str>) -> Result<Script> {
+    let calendars = match calendar {
+        Some(name) => {
+            Template::new("(calendars whose name is ${name})").text("name", name).render()?
+        }
+        None => Script::from("calendars"),
+    };
+    Template::new(SNAPSHOT_SCRIPT)
+        .number("year", from.year())
+        .number("month", from.month())
+        .number("day", from.day())
+        .number("days", days)
+        .script("calendars", calendars)
+        .render()
+}
+
+/// Parse the calendar name followed by the fields `adopt` reads, one event per line
+pub fn parse_snapshot(output: &str) -> Vec<CalendarItem> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .flat_map(|(calendar, event)| crate::calendar::parse_adopted_events(event, calendar))
+        .collect()
+}
+
+/// Read the events starting in `days` days from `from`
+pub async fn snapshot(
+    from: NaiveDate,
+    days: u32,
+    calendar: Option<&str>,
+) -> Result<Vec<CalendarItem>> {
+    crate::backend::apple_only("Watching Calendar.app")?;
+    let output = run_applescript_async(snapshot_script(from, days, calendar)?).await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || stdout.starts_with("Error: ") {
+        let error = if stdout.starts_with("Error: ") {
+            stdout.replace("Error: ", "")
+        } else {
+            String::from_utf8_lossy(&output.stderr).to_string()
+        };
+        return Err(anyhow!("Failed to read events from Calendar: {}", error.trim()));
+    }
+    Ok(parse_snapshot(&stdout))
+}
+
+fn find_by_uid(items: &[CalendarItem], uid: &Option<String>) -> Option<CalendarItem> {
+    items.iter().find(|item| item.uid == *uid).cloned()
+}
+
+/// Changes between two reads, looking only at events on `from` up to (not including) `until`
+pub fn diff(
+    before: &[CalendarItem],
+    after: &[CalendarItem],
+    from: NaiveDate,
+    until: NaiveDate,
+) -> Vec<EventChange> {
+    let (from, until) = (from.format("%Y-%m-%d").to_string(), until.format("%Y-%m-%d").to_string());
+    let in_window = |item: &&CalendarItem| item.date >= from && item.date < until;
+
+    let mut changes = Vec::new();
+    for item in after.iter().filter(in_window) {
+        match find_by_uid(before, &item.uid) {
+            None => changes.push(EventChange::Added(item.clone())),
+            Some(old) if !differences(&old, item).is_empty() => {
+                changes.push(EventChange::Changed { before: old, after: item.clone() })
+            }
+            Some(_) => {}
+        }
+    }
+    for item in before.iter().filter(in_window) {
+        if find_by_uid(after, &item.uid).is_none() {
+            changes.push(EventChange::Removed(item.clone()));
+        }
+    }
+    changes
+}
+
+/// Bring the tracked events up to date with `changes`
+pub fn apply_changes(items: &mut Vec<CalendarItem>, changes: &[EventChange]) {
+    for change in changes {
+        match change {
+            EventChange::Added(event) => {
+                crate::calendar::merge_adopted_events(items, vec![event.clone()]);
+            }
+            EventChange::Changed { after, .. } => {
+                match items.iter_mut().find(|item| item.uid.is_some() && item.uid == after.uid) {
+                    Some(item) => {
+                        item.title = after.title.clone();
+                        item.date = after.date.clone();
+                        item.time = after.time.clone();
+                        item.end_time = after.end_time.clone();
+                        item.all_day = after.all_day;
+                        item.location = after.location.clone();
+                        item.calendars = after.calendars.clone();
+                        item.recurrence = after.recurrence.clone();
+                    }
+                    None => {
+                        crate::calendar::merge_adopted_events(items, vec![after.clone()]);
+                    }
+                }
+            }
+            EventChange::Removed(event) => {
+                items.retain(|item| item.uid.is_none() || item.uid != event.uid);
+            }
+        }
+    }
+}
+
+async fn notify(change: &EventChange) {
+    let script = Template::new(r#"display notification ${text} with title "DuckTape""#)
+        .text("text", &change.to_string())
+        .render();
+    match script {
+        Ok(script) => {
+            if let Err(e) = run_applescript_async(script).await {
+                debug!("Failed to show notification: {}", e);
+            }
+        }
+        Err(e) => debug!("Failed to build notification: {}", e),
+    }
+}
+
+/// Print changes to the calendars as they happen until stopped with Ctrl+C
+pub async fn run_watch(options: &WatchOptions) -> Result<()> {
+    let interval = std::time::Duration::from_secs(options.interval_secs.max(MIN_INTERVAL_SECS));
+    let calendar = options.calendar.as_deref();
+    let mut from = Local::now().date_naive();
+    let mut events = snapshot(from, options.days, calendar).await?;
+    println!(
+        "Watching {} event(s) in the next {} day(s), checking every {}s. Press Ctrl+C to stop.",
+        events.len(),
+        options.days,
+        interval.as_secs()
+    );
+
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = tokio::signal::ctrl_c() => {
+                println!("Stopped watching");
+                return Ok(());
+            }
+        }
+
+        let today = Local::now().date_naive();
+        let current = match snapshot(today, options.days, calendar).await {
+            Ok(current) => current,
+            Err(e) => {
+                warn!("Failed to read the calendars: {}", e);
+                continue;
+            }
+        };
+        let until = from + Duration::days(options.days as i64);
+        let changes = diff(&events, &current, today, until);
+        events = current;
+        from = today;
+        if changes.is_empty() {
+            debug!("No calendar changes");
+            continue;
+        }
+
+        let now = Local::now().format("%H:%M");
+        for change in &changes {
+            println!("[{}] {}", now, change);
+            if options.notify {
+                notify(change).await;
+            }
+        }
+        // Only the write waits for changes made through the API, not the whole watch
+        SharedState::global()
+            .exclusive(async {
+                StateManager::new()?
+                    .update(|items: &mut Vec<CalendarItem>| apply_changes(items, &changes))
+            })
+            .await?;
+        info!("Applied {} calendar change(s) to the tracked events", changes.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(uid: &str, title: &str, date: &str, time: &str) -> CalendarItem {
+        let end = chrono::NaiveTime::parse_from_str(time, "%H:%M").unwrap() + Duration::hours(1);
+        let line = format!(
+            "{}\t{}\t{} {}\t{} {}\tfalse\t\t",
+            uid,
+            title,
+            date,
+            time,
+            date,
+            end.format("%H:%M")
+        );
+        crate::calendar::parse_adopted_events(&line, "Work").remove(0)
+    }
+
+    fn day(date: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_parse_snapshot() {
+        let output = "Work\tA1\tStandup\t2025-03-12 09:00\t2025-03-12 09:15\tfalse\tRoom 4\t\n\
+                      Home\tB2\tDentist\t2025-03-13 14:00\t2025-03-13 15:00\tfalse\t\t\n";
+        let events = parse_snapshot(output);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].uid.as_deref(), Some("A1"));
+        assert_eq!(events[0].location.as_deref(), Some("Room 4"));
+        assert_eq!(events[1].calendars, vec!["Home"]);
+    }
+
+    #[test]
+    fn test_diff() {
+        let before = vec![
+            event("A1", "Standup", "2025-03-12", "09:00"),
+            event("B2", "Dentist", "2025-03-13", "14:00"),
+            event("C3", "Old", "2025-03-11", "08:00"),
+        ];
+        let after = vec![
+            event("A1", "Standup", "2025-03-12", "09:30"),
+            event("D4", "Lunch", "2025-03-12", "12:00"),
+            event("E5", "Next week", "2025-03-20", "12:00"),
+        ];
+        let changes = diff(&before, &after, day("2025-03-12"), day("2025-03-19"));
+        assert_eq!(changes.len(), 3);
+        assert!(matches!(&changes[0], EventChange::Changed { after, .. } if after.time == "09:30"));
+        assert!(matches!(&changes[1], EventChange::Added(item) if item.title == "Lunch"));
+        assert!(matches!(&changes[2], EventChange::Removed(item) if item.title == "Dentist"));
+        assert_eq!(
+            changes[0].to_string(),
+            "~ Standup: 2025-03-12 09:00-10:00 → 2025-03-12 09:30-10:30"
+        );
+        assert_eq!(changes[1].to_string(), "+ Lunch on 2025-03-12 12:00-13:00 (Work)");
+    }
+
+    #[test]
+    fn test_apply_changes() {
+        let mut items = vec![
+            event("A1", "Standup", "2025-03-12", "09:00"),
+            event("B2", "Dentist", "2025-03-13", "14:00"),
+        ];
+        items[0].description = Some("Daily".to_string());
+        let changes = vec![
+            EventChange::Changed {
+                before: items[0].clone(),
+                after: event("A1", "Standup", "2025-03-12", "09:30"),
+            },
+            EventChange::Removed(items[1].clone()),
+            EventChange::Added(event("D4", "Lunch", "2025-03-12", "12:00")),
+        ];
+        apply_changes(&mut items, &changes);
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].time, "09:30");
+        // What Calendar.app does not report is kept
+        assert_eq!(items[0].description.as_deref(), Some("Daily"));
+        assert_eq!(items[1].uid.as_deref(), Some("D4"));
+    }
+}
@@ -0,0 +1,360 @@
+//! Webhook notifications for events, reminders and notes being created, updated or deleted.
+//
+// The calendar, todo, reminder and notes modules call `notify` after each change, so a change
+// is sent whether the CLI, the HTTP API or the WebSocket server made it. Each change is POSTed
+// as JSON to every URL in the `[webhooks]` section of config.toml. With a secret stored
+// (`ducktape keys set webhook`) requests carry an HMAC-SHA256 signature of a timestamp and the
+// body for receivers such as n8n or Zapier to check, and to refuse replays with an old
+// timestamp. Deliveries run in the background, off the lock that serializes changes: a slow
+// or unreachable URL never holds up the next change. Failed deliveries are retried with
+// backoff and then logged; they never fail the change itself. `flush` waits for deliveries
+// still running, so that the command line does not exit before they are done.
+
+use crate::calendar::EventConfig;
+use crate::config::{Config, WebhooksConfig};
+use anyhow::{Result, anyhow};
+use chrono::Local;
+use hmac::{Hmac, Mac};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::{Value, json};
+use sha2::Sha256;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Variable holding the signing secret
+pub const SECRET_VAR: &str = "DUCKTAPE_WEBHOOK_SECRET";
+/// Header with `sha256=<hex HMAC of the timestamp, a dot and the body>`
+pub const SIGNATURE_HEADER: &str = "X-DuckTape-Signature";
+/// Header with the Unix time of the attempt, in seconds, as signed
+pub const TIMESTAMP_HEADER: &str = "X-DuckTape-Timestamp";
+/// Header with the change type, e.g. `event.created`
+pub const EVENT_HEADER: &str = "X-DuckTape-Event";
+/// Header with the delivery id, the same for every attempt
+pub const DELIVERY_HEADER: &str = "X-DuckTape-Delivery";
+
+/// Longest wait between two attempts
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Deliveries started by `notify` that may still be running
+static PENDING: Lazy<Mutex<Vec<JoinHandle<()>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+tokio::task_local! {
+    /// What made the changes in the current task
+    static SOURCE: &'static str;
+}
+
+/// What changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemKind {
+    Event,
+    Reminder,
+    Note,
+}
+
+impl ItemKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemKind::Event => "event",
+            ItemKind::Reminder => "reminder",
+            ItemKind::Note => "note",
+        }
+    }
+}
+
+/// How it changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl Action {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Action::Created => "created",
+            Action::Updated => "updated",
+            Action::Deleted => "deleted",
+        }
+    }
+}
+
+/// The JSON body of a webhook request
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookPayload {
+    /// Delivery id, also sent in the `X-DuckTape-Delivery` header
+    pub id: String,
+    /// Change type, e.g. `reminder.deleted`
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// `cli`, `api`, `websocket` or `slack`
+    pub source: String,
+    /// RFC 3339 time of the change
+    pub timestamp: String,
+    /// The item as it is after the change, or as it was for deletions
+    pub data: Value,
+}
+
+impl WebhookPayload {
+    pub fn new(kind: ItemKind, action: Action, data: Value) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            event_type: format!("{}.{}", kind.as_str(), action.as_str()),
+            source: current_source().to_string(),
+            timestamp: Local::now().to_rfc3339(),
+            data,
+        }
+    }
+}
+
+/// Run `future` with its changes reported as made by `source`
+///
+/// Changes made outside such a scope are reported as made by the CLI.
+pub async fn with_source<F: Future>(source: &'static str, future: F) -> F::Output {
+    SOURCE.scope(source, future).await
+}
+
+/// What is making changes in the current task: "cli", "api", "websocket" or "slack"
+pub fn current_source() -> &'static str {
+    SOURCE.try_with(|source| *source).unwrap_or("cli")
+}
+
+/// Whether a change type is selected by the `webhooks.events` filters
+///
+/// Filters are exact types (`note.updated`), a kind (`event.*`) or `*`; no filters select all.
+pub fn is_selected(filters: &[String], event_type: &str) -> bool {
+    filters.is_empty()
+        || filters.iter().any(|filter| {
+            let filter = filter.trim();
+            filter == "*"
+                || filter.eq_ignore_ascii_case(event_type)
+                || filter.strip_suffix(".*").is_some_and(|kind| {
+                    event_type.split('.').next().is_some_and(|k| k.eq_ignore_ascii_case(kind))
+                })
+        })
+}
+
+/// `sha256=` and the hex HMAC-SHA256 of `message` keyed with `secret`
+pub fn sign(secret: &str, message: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    let hex: String =
+        mac.finalize().into_bytes().iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("sha256={}", hex)
+}
+
+/// What the signature covers: the `X-DuckTape-Timestamp` value, a dot and the body
+pub fn signed_message(timestamp: i64, body: &[u8]) -> Vec<u8> {
+    let mut message = format!("{}.", timestamp).into_bytes();
+    message.extend_from_slice(body);
+    message
+}
+
+/// Wait before attempt `attempt` (2 or later): 1s, 2s, 4s, ... up to `MAX_BACKOFF`
+pub fn backoff(attempt: u32) -> Duration {
+    let seconds = 1u64.checked_shl(attempt.saturating_sub(2)).unwrap_or(u64::MAX);
+    Duration::from_secs(seconds).min(MAX_BACKOFF)
+}
+
+/// Whether a response status is worth another attempt
+fn is_retryable(status: reqwest::StatusCode) -> bool {
+    status.is_server_error()
+        || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        || status == reqwest::StatusCode::REQUEST_TIMEOUT
+}
+
+/// POST `payload` to `url`, retrying network errors and 5xx, 408 and 429 responses
+pub async fn deliver(config: &WebhooksConfig, url: &str, payload: &WebhookPayload) -> Result<()> {
+    let body = serde_json::to_vec(payload)?;
+    let secret = crate::secrets::get(SECRET_VAR);
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.timeout_secs.max(1)))
+        .build()?;
+
+    let attempts = config.max_attempts.max(1);
+    let mut last_error = None;
+    for attempt in 1..=attempts {
+        if attempt > 1 {
+            tokio::time::sleep(backoff(attempt)).await;
+        }
+        let mut request = client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, &payload.event_type)
+            .header(DELIVERY_HEADER, &payload.id)
+            .body(body.clone());
+        if let Some(secret) = &secret {
+            // Signed per attempt, so that a retry after a long wait is not taken for a replay
+            let timestamp = chrono::Utc::now().timestamp();
+            request = request
+                .header(TIMESTAMP_HEADER, timestamp.to_string())
+                .header(SIGNATURE_HEADER, sign(secret, &signed_message(timestamp, &body)));
+        }
+
+        match request.send().await {
+            Ok(response) if response.status().is_success() => {
+                debug!("Delivered {} to {} (attempt {})", payload.event_type, url, attempt);
+                return Ok(());
+            }
+            Ok(response) if !is_retryable(response.status()) => {
+                return Err(anyhow!("{} answered {}", url, response.status()));
+            }
+            Ok(response) => last_error = Some(anyhow!("{} answered {}", url, response.status())),
+            Err(e) => last_error = Some(anyhow!("Failed to reach {}: {}", url, e)),
+        }
+    }
+    Err(last_error
+        .unwrap_or_else(|| anyhow!("Failed to deliver to {}", url))
+        .context(format!("gave up after {} attempts", attempts)))
+}
+
+/// Send `payload` to every configured URL, returning the result for each
+pub async fn deliver_all(
+    config: &WebhooksConfig,
+    payload: &WebhookPayload,
+) -> Vec<(String, Result<()>)> {
+    let deliveries = config
+        .urls
+        .iter()
+        .map(|url| async move { (url.clone(), deliver(config, url, payload).await) });
+    futures::future::join_all(deliveries).await
+}
+
+/// Tell the configured webhooks about a change
+///
+/// Delivery runs in the background; failures are logged, not returned: the change has
+/// already been made.
+pub fn notify(kind: ItemKind, action: Action, data: Value) {
+    let config = match Config::load() {
+        Ok(config) => config.webhooks,
+        Err(e) => {
+            warn!("Webhooks skipped, config could not be loaded: {}", e);
+            return;
+        }
+    };
+    if config.urls.is_empty() {
+        return;
+    }
+    let payload = WebhookPayload::new(kind, action, data);
+    if !is_selected(&config.events, &payload.event_type) {
+        return;
+    }
+    let delivery = tokio::spawn(async move {
+        for (url, result) in deliver_all(&config, &payload).await {
+            if let Err(e) = result {
+                warn!("Webhook {} for {} failed: {:#}", payload.event_type, url, e);
+            }
+        }
+    });
+    if let Ok(mut pending) = PENDING.lock() {
+        pending.retain(|delivery| !delivery.is_finished());
+        pending.push(delivery);
+    }
+}
+
+/// Wait for the deliveries `notify` started, e.g. before the process exits
+pub async fn flush() {
+    let deliveries = match PENDING.lock() {
+        Ok(mut pending) => std::mem::take(&mut *pending),
+        Err(_) => return,
+    };
+    if !deliveries.is_empty() {
+        debug!("Waiting for {} webhook deliveries", deliveries.len());
+    }
+    for delivery in deliveries {
+        let _ = delivery.await;
+    }
+}
+
+/// Webhook data for an event
+pub fn event_data(config: &EventConfig, calendars: &[String]) -> Value {
+    json!({
+        "title": config.title,
+        "start_date": config.start_date,
+        "start_time": if config.all_day { None } else { Some(&config.start_time) },
+        "end_date": config.end_date,
+        "end_time": config.end_time,
+        "all_day": config.all_day,
+        "calendars": calendars,
+        "location": config.location,
+        "url": config.url,
+        "availability": config.availability.map(|a| a.as_str()),
+        "description": config.description,
+        "attendees": config.emails,
+        "timezone": config.timezone,
+    })
+}
+
+/// Webhook data for a reminder
+pub fn reminder_data(title: &str, lists: &[&str], due: Option<&str>, notes: Option<&str>) -> Value {
+    json!({ "title": title, "lists": lists, "due": due, "notes": notes })
+}
+
+/// Webhook data for a note
+pub fn note_data(title: &str, folder: Option<&str>, content: Option<&str>) -> Value {
+    json!({ "title": title, "folder": folder, "content": content })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_matches_known_hmac() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_signature_covers_the_timestamp() {
+        let body = br#"{"type":"event.created"}"#;
+        assert_eq!(signed_message(1700000000, body), br#"1700000000.{"type":"event.created"}"#);
+        assert_ne!(
+            sign("secret", &signed_message(1700000000, body)),
+            sign("secret", &signed_message(1700000300, body))
+        );
+    }
+
+    #[test]
+    fn test_event_filters() {
+        assert!(is_selected(&[], "note.deleted"));
+        let filters = vec!["event.*".to_string(), "note.updated".to_string()];
+        assert!(is_selected(&filters, "event.created"));
+        assert!(is_selected(&filters, "note.updated"));
+        assert!(!is_selected(&filters, "note.deleted"));
+        assert!(!is_selected(&filters, "reminder.created"));
+        assert!(is_selected(&["*".to_string()], "reminder.created"));
+    }
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        assert_eq!(backoff(2), Duration::from_secs(1));
+        assert_eq!(backoff(3), Duration::from_secs(2));
+        assert_eq!(backoff(5), Duration::from_secs(8));
+        assert_eq!(backoff(40), MAX_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn test_payload_type_and_source() {
+        let payload = WebhookPayload::new(ItemKind::Reminder, Action::Deleted, json!({}));
+        assert_eq!(payload.event_type, "reminder.deleted");
+        assert_eq!(payload.source, "cli");
+
+        let payload = with_source("websocket", async {
+            WebhookPayload::new(ItemKind::Event, Action::Created, json!({"title": "Standup"}))
+        })
+        .await;
+        assert_eq!(payload.source, "websocket");
+        let body = serde_json::to_value(&payload).unwrap();
+        assert_eq!(body["type"], "event.created");
+        assert_eq!(body["data"]["title"], "Standup");
+    }
+}
@@ -1,35 +1,90 @@
+use crate::env_store::{delete_secret, load_secret, store_secret};
 use anyhow::{Result, anyhow};
-use log::{debug, error, info};
-use reqwest::Client;
+use chrono::{DateTime, Duration, Utc};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
 use secrecy::{ExposeSecret, Secret};
 use serde::{Deserialize, Serialize};
-use std::env;
+use sha2::{Digest, Sha256};
 use std::str::FromStr;
+use tracing::{debug, error, info, warn};
 
 // Constants for Zoom API
 const ZOOM_API_BASE: &str = "https://api.zoom.us/v2";
+// Refresh tokens this many seconds before Zoom says they expire
+const TOKEN_EXPIRY_MARGIN_SECS: i64 = 60;
+
+/// Zoom failures callers may want to tell apart
+#[derive(Debug, thiserror::Error)]
+pub enum ZoomError {
+    #[error("{0} not found in environment or keychain; store it with `ducktape keys set zoom`")]
+    MissingCredential(String),
+
+    #[error("Zoom OAuth error ({status}): {message}")]
+    Auth { status: StatusCode, message: String },
+
+    #[error("Zoom API error ({status}): {message}")]
+    Api { status: StatusCode, message: String },
+}
+
+/// An OAuth access token together with its expiry time
+#[derive(Debug, Clone)]
+struct CachedToken {
+    token: Secret<String>,
+    expires_at: DateTime<Utc>,
+}
+
+/// On-disk form of a cached token
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredToken {
+    access_token: String,
+    expires_at: i64,
+}
+
+impl CachedToken {
+    fn new(token: String, expires_in: u64) -> Self {
+        Self {
+            token: Secret::new(token),
+            expires_at: Utc::now() + Duration::seconds(expires_in as i64),
+        }
+    }
+
+    fn is_valid_at(&self, now: DateTime<Utc>) -> bool {
+        now + Duration::seconds(TOKEN_EXPIRY_MARGIN_SECS) < self.expires_at
+    }
+
+    fn to_stored(&self) -> StoredToken {
+        StoredToken {
+            access_token: self.token.expose_secret().clone(),
+            expires_at: self.expires_at.timestamp(),
+        }
+    }
+
+    fn from_stored(stored: StoredToken) -> Option<Self> {
+        Some(Self {
+            token: Secret::new(stored.access_token),
+            expires_at: DateTime::from_timestamp(stored.expires_at, 0)?,
+        })
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct ZoomCredentials {
     pub account_id: Secret<String>,
     pub client_id: Secret<String>,
     pub client_secret: Secret<String>,
-    access_token: Option<Secret<String>>,
+    access_token: Option<CachedToken>,
 }
 
 impl ZoomCredentials {
     pub fn new() -> Result<Self> {
-        let account_id = env::var("ZOOM_ACCOUNT_ID")
-            .map(Secret::new)
-            .map_err(|_| anyhow!("ZOOM_ACCOUNT_ID not found in environment"))?;
-
-        let client_id = env::var("ZOOM_CLIENT_ID")
-            .map(Secret::new)
-            .map_err(|_| anyhow!("ZOOM_CLIENT_ID not found in environment"))?;
-
-        let client_secret = env::var("ZOOM_CLIENT_SECRET")
-            .map(Secret::new)
-            .map_err(|_| anyhow!("ZOOM_CLIENT_SECRET not found in environment"))?;
+        let credential = |name: &str| {
+            crate::secrets::get(name)
+                .map(Secret::new)
+                .ok_or_else(|| ZoomError::MissingCredential(name.to_string()))
+        };
+        let account_id = credential("ZOOM_ACCOUNT_ID")?;
+        let client_id = credential("ZOOM_CLIENT_ID")?;
+        let client_secret = credential("ZOOM_CLIENT_SECRET")?;
 
         Ok(Self { account_id, client_id, client_secret, access_token: None })
     }
@@ -44,10 +99,43 @@ impl ZoomCredentials {
         }
     }
 
+    /// Name of the secret the token for these credentials is persisted under
+    fn token_secret_name(&self) -> String {
+        let digest = Sha256::digest(
+            format!("{}:{}", self.account_id.expose_secret(), self.client_id.expose_secret())
+                .as_bytes(),
+        );
+        let id: String = digest.iter().take(6).map(|b| format!("{:02x}", b)).collect();
+        format!("zoom-token-{}", id)
+    }
+
+    /// Drop the cached token so that the next request fetches a new one
+    pub fn invalidate_token(&mut self) {
+        debug!("Invalidating cached Zoom access token");
+        self.access_token = None;
+        delete_secret(&self.token_secret_name());
+    }
+
     async fn get_access_token(&mut self) -> Result<String> {
-        // If we already have a token, return it
+        // Reuse the token while it is still valid
         if let Some(token) = &self.access_token {
-            return Ok(token.expose_secret().clone());
+            if token.is_valid_at(Utc::now()) {
+                return Ok(token.token.expose_secret().clone());
+            }
+            debug!("Cached Zoom access token expired at {}", token.expires_at);
+        }
+
+        // Then try the token persisted by an earlier run
+        let secret_name = self.token_secret_name();
+        if let Some(token) = load_secret(&secret_name)
+            .and_then(|stored| serde_json::from_str::<StoredToken>(&stored).ok())
+            .and_then(CachedToken::from_stored)
+            .filter(|token| token.is_valid_at(Utc::now()))
+        {
+            debug!("Using persisted Zoom access token valid until {}", token.expires_at);
+            let value = token.token.expose_secret().clone();
+            self.access_token = Some(token);
+            return Ok(value);
         }
 
         let client = Client::new();
@@ -87,7 +175,7 @@ impl ZoomCredentials {
                 &error_text
             };
 
-            return Err(anyhow!("Zoom OAuth error ({}): {}", status, error_message));
+            return Err(ZoomError::Auth { status, message: error_message.to_string() }.into());
         }
 
         let response_text = response.text().await?;
@@ -98,7 +186,6 @@ impl ZoomCredentials {
             access_token: String,
             #[allow(dead_code)]
             token_type: String,
-            #[allow(dead_code)]
             expires_in: u64,
         }
 
@@ -106,8 +193,18 @@ impl ZoomCredentials {
             anyhow!("Failed to parse OAuth response: {} - Response was: {}", e, response_text)
         })?;
 
-        // Store and return the token
-        self.access_token = Some(Secret::new(token_data.access_token.clone()));
+        // Cache the token in memory and persist it for later runs
+        let token = CachedToken::new(token_data.access_token.clone(), token_data.expires_in);
+        match serde_json::to_string(&token.to_stored()) {
+            Ok(stored) => {
+                if let Err(e) = store_secret(&secret_name, &stored) {
+                    warn!("Failed to persist Zoom access token: {}", e);
+                }
+            }
+            Err(e) => warn!("Failed to serialize Zoom access token: {}", e),
+        }
+        debug!("Fetched Zoom access token valid until {}", token.expires_at);
+        self.access_token = Some(token);
         Ok(token_data.access_token)
     }
 }
@@ -155,6 +252,32 @@ impl ZoomClient {
         Ok(Self { credentials, client })
     }
 
+    // Send an authorized request, fetching a new token and retrying once if Zoom rejects the
+    // cached one with 401 Unauthorized (e.g. after it was revoked)
+    async fn send_authorized<F>(&mut self, build: F) -> Result<Response>
+    where
+        F: Fn(&Client) -> RequestBuilder,
+    {
+        let token = self.credentials.get_access_token().await?;
+        let response = build(&self.client)
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to send Zoom API request: {}", e))?;
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+
+        warn!("Zoom API rejected the access token, fetching a new one");
+        self.credentials.invalidate_token();
+        let token = self.credentials.get_access_token().await?;
+        build(&self.client)
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to send Zoom API request: {}", e))
+    }
+
     // Create a Zoom meeting
     pub async fn create_meeting(
         &mut self,
@@ -162,9 +285,6 @@ impl ZoomClient {
     ) -> Result<ZoomMeetingResponse> {
         debug!("Creating Zoom meeting with topic: {}", options.topic);
 
-        // Get access token
-        let token = self.credentials.get_access_token().await?;
-
         // Sanitize input data
         let sanitized_topic = sanitize_zoom_field(&options.topic, 200);
         let sanitized_agenda = options.agenda.as_deref().map(|a| sanitize_zoom_field(a, 2000));
@@ -190,14 +310,10 @@ impl ZoomClient {
         // Make the API call
         let url = format!("{}/users/me/meetings", ZOOM_API_BASE);
         let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", token))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| anyhow!("Failed to send Zoom API request: {}", e))?;
+            .send_authorized(|client| {
+                client.post(&url).header("Content-Type", "application/json").json(&body)
+            })
+            .await?;
 
         // Check for errors
         if !response.status().is_success() {
@@ -205,7 +321,7 @@ impl ZoomClient {
             let error_text =
                 response.text().await.unwrap_or_else(|_| "Unable to get error response".into());
             error!("Zoom API error: {} - {}", status, error_text);
-            return Err(anyhow!("Zoom API error ({}): {}", status, error_text));
+            return Err(ZoomError::Api { status, message: error_text }.into());
         }
 
         // Parse response
@@ -222,18 +338,9 @@ impl ZoomClient {
     pub async fn delete_meeting(&mut self, meeting_id: u64) -> Result<()> {
         debug!("Deleting Zoom meeting: {}", meeting_id);
 
-        // Get access token
-        let token = self.credentials.get_access_token().await?;
-
         // Make the API call
         let url = format!("{}/meetings/{}", ZOOM_API_BASE, meeting_id);
-        let response = self
-            .client
-            .delete(&url)
-            .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await
-            .map_err(|e| anyhow!("Failed to send Zoom API request: {}", e))?;
+        let response = self.send_authorized(|client| client.delete(&url)).await?;
 
         // Check for errors
         if !response.status().is_success() {
@@ -241,7 +348,7 @@ impl ZoomClient {
             let error_text =
                 response.text().await.unwrap_or_else(|_| "Unable to get error response".into());
             error!("Zoom API error: {} - {}", status, error_text);
-            return Err(anyhow!("Zoom API error ({}): {}", status, error_text));
+            return Err(ZoomError::Api { status, message: error_text }.into());
         }
 
         info!("Successfully deleted Zoom meeting: {}", meeting_id);
@@ -255,21 +362,19 @@ impl ZoomClient {
         method: &str,
         body: Option<&str>,
     ) -> Result<String> {
-        let token = self.credentials.get_access_token().await?;
         let url = format!("{}{}", ZOOM_API_BASE, endpoint);
+        let method = reqwest::Method::from_str(method)?;
 
-        let mut request = self
-            .client
-            .request(reqwest::Method::from_str(method)?, &url)
-            .header("Authorization", format!("Bearer {}", token))
-            .header("Content-Type", "application/json");
-
-        if let Some(body_str) = body {
-            request = request.body(body_str.to_string());
-        }
-
-        let response =
-            request.send().await.map_err(|e| anyhow!("Failed to send request: {}", e))?;
+        let response = self
+            .send_authorized(|client| {
+                let request =
+                    client.request(method.clone(), &url).header("Content-Type", "application/json");
+                match body {
+                    Some(body_str) => request.body(body_str.to_string()),
+                    None => request,
+                }
+            })
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -356,6 +461,29 @@ mod tests {
         assert_eq!(result, 15); // Should use minimum 15 minutes
     }
 
+    #[test]
+    fn test_cached_token_expiry() {
+        let token = CachedToken::new("abc".to_string(), 3600);
+        assert!(token.is_valid_at(Utc::now()));
+        // Tokens are treated as expired shortly before Zoom expires them
+        assert!(!token.is_valid_at(token.expires_at - Duration::seconds(30)));
+        assert!(!token.is_valid_at(token.expires_at + Duration::seconds(1)));
+
+        let restored = CachedToken::from_stored(token.to_stored()).unwrap();
+        assert_eq!(restored.token.expose_secret(), "abc");
+        assert_eq!(restored.expires_at.timestamp(), token.expires_at.timestamp());
+    }
+
+    #[test]
+    fn test_token_secret_name_is_per_account() {
+        let a = ZoomCredentials::with_credentials("acct".into(), "id".into(), "secret".into());
+        let b = ZoomCredentials::with_credentials("other".into(), "id".into(), "secret".into());
+        assert!(a.token_secret_name().starts_with("zoom-token-"));
+        assert_ne!(a.token_secret_name(), b.token_secret_name());
+        // The name must be usable as a file name and never contain the raw credentials
+        assert!(!a.token_secret_name().contains("acct"));
+    }
+
     #[test]
     fn test_sanitize_zoom_field() {
         // Test normal input
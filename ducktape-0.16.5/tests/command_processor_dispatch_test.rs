@@ -0,0 +1,357 @@
+//! Dispatch tests for the CommandProcessor.
+//
+// Commands run through the same path as the terminal (clap, `convert_to_command_args`,
+// `CommandProcessor::execute`) with every AppleScript routed to a recording fake, HOME and
+// the working directory (config.toml) in a temporary directory, and a mock parser in place
+// of the LLM. The Apple backends are configured so this works on any platform. Tests assert
+// on the scripts that would have been sent to Calendar, Reminders and Notes and on the state
+// that was saved.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{Datelike, Duration, Local};
+use clap::Parser as _;
+use ducktape::calendar::clear_calendar_cache;
+use ducktape::cli::{Cli, convert_to_command_args};
+use ducktape::command_processor::{CommandArgs, CommandProcessor};
+use ducktape::config::{CalendarBackendKind, Config, NotesBackendKind, TodoBackendKind};
+use ducktape::parser::{ParseResult, Parser, sanitize_nlp_command};
+use ducktape::script_runner::{ScriptHandler, ScriptLanguage, set_script_handler};
+use ducktape::state::{CalendarItem, StateManager};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::os::unix::process::ExitStatusExt;
+use std::process::{ExitStatus, Output};
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+/// HOME and working directory shared by all tests in this file
+static SANDBOX: Lazy<TempDir> = Lazy::new(|| TempDir::new().expect("temp dir"));
+
+/// The script handler, HOME and working directory are process-wide, so tests run one at a time
+static SERIAL: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+/// Records every script and answers with the first matching canned response
+struct FakeScripts {
+    calls: Mutex<Vec<(ScriptLanguage, String)>>,
+    responses: Vec<(&'static str, &'static str)>,
+}
+
+impl ScriptHandler for FakeScripts {
+    fn run(&self, language: ScriptLanguage, script: &str) -> Result<Output> {
+        self.calls.lock().unwrap().push((language, script.to_string()));
+        let stdout = self
+            .responses
+            .iter()
+            .find(|(needle, _)| script.contains(needle))
+            .map_or("Success: fake-id", |(_, stdout)| stdout);
+        Ok(Output {
+            status: ExitStatus::from_raw(0),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: Vec::new(),
+        })
+    }
+}
+
+struct Harness {
+    scripts: Arc<FakeScripts>,
+    processor: CommandProcessor,
+    _guard: tokio::sync::MutexGuard<'static, ()>,
+}
+
+impl Harness {
+    async fn new() -> Self {
+        let guard = SERIAL.lock().await;
+        std::env::set_var("HOME", SANDBOX.path());
+        std::env::set_current_dir(SANDBOX.path()).unwrap();
+
+        let scripts = Arc::new(FakeScripts {
+            calls: Mutex::new(Vec::new()),
+            responses: vec![
+                ("repeat with aCal in calendars", "Work, Home"),
+                ("calendars whose writable is true", "Work"),
+            ],
+        });
+        set_script_handler(Some(scripts.clone()));
+        // Every test starts from the fake's calendars, not the names another test cached
+        clear_calendar_cache();
+
+        let mut config = Config::load_global().expect("config");
+        config.backend.calendar = Some(CalendarBackendKind::Apple);
+        config.backend.todo = Some(TodoBackendKind::Apple);
+        config.backend.notes = Some(NotesBackendKind::Apple);
+        config.save().expect("config saved");
+        Self { scripts, processor: CommandProcessor::new(), _guard: guard }
+    }
+
+    /// Run a command line exactly as the terminal would
+    async fn run(&self, line: &str) -> Result<()> {
+        let words = shell_words::split(line)?;
+        let cli = Cli::try_parse_from(words)?;
+        let args = convert_to_command_args(&cli).expect("command maps to CommandArgs");
+        self.processor.execute(args).await
+    }
+
+    /// Run commands that have no clap subcommand
+    async fn run_args(&self, command: &str, args: &[&str], flags: &[(&str, &str)]) -> Result<()> {
+        let flags: HashMap<String, Option<String>> =
+            flags.iter().map(|(k, v)| (k.to_string(), Some(v.to_string()))).collect();
+        let args = args.iter().map(|a| a.to_string()).collect();
+        self.processor.execute(CommandArgs::new(command.to_string(), args, flags)).await
+    }
+
+    fn scripts(&self) -> Vec<String> {
+        self.scripts.calls.lock().unwrap().iter().map(|(_, s)| s.clone()).collect()
+    }
+
+    /// Applications the scripts were sent to, in order
+    fn apps(&self) -> Vec<String> {
+        self.scripts()
+            .iter()
+            .filter_map(|s| s.split("tell application \"").nth(1))
+            .filter_map(|rest| rest.split('"').next())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// The script that made the new item
+    fn create_script(&self) -> String {
+        self.scripts()
+            .into_iter()
+            .rev()
+            .find(|s| s.contains("make new"))
+            .expect("a script creating an item")
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        set_script_handler(None);
+    }
+}
+
+fn saved_events() -> Vec<CalendarItem> {
+    StateManager::new().unwrap().load().unwrap()
+}
+
+#[tokio::test]
+async fn test_calendar_create_sends_event_to_calendar() -> Result<()> {
+    let h = Harness::new().await;
+    h.run(
+        "ducktape calendar create \"Team Sync\" 2025-05-01 10:00 11:00 Home --location \"Room 1\"",
+    )
+    .await?;
+
+    // The calendar names are listed once and then cached
+    assert_eq!(h.apps(), vec!["Calendar"; 3]);
+    let script = h.create_script();
+    assert!(script.contains("if name of cal is \"Home\""));
+    assert!(script.contains("summary:\"Team Sync\""));
+    assert!(script.contains("location:\"Room 1\""));
+    assert!(script.contains("set year of startDate to 2025\n"));
+    assert!(script.contains("set month of startDate to 5\n"));
+    assert!(script.contains("set day of startDate to 1\n"));
+    assert!(script.contains("set hours of startDate to 10\n"));
+    assert!(script.contains("set hours of endDate to 11\n"));
+
+    let event = saved_events().into_iter().rev().find(|e| e.title == "Team Sync").unwrap();
+    assert_eq!(event.date, "2025-05-01");
+    assert_eq!(event.calendars, vec!["Home"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_calendar_create_resolves_relative_date() -> Result<()> {
+    let h = Harness::new().await;
+    h.run("ducktape calendar create Retro \"next friday\" 15:00 16:00 Work").await?;
+
+    let expected = ducktape::dates::parse_date("next friday", Local::now().date_naive()).unwrap();
+    let script = h.create_script();
+    assert!(script.contains(&format!("set month of startDate to {}\n", expected.month())));
+    assert!(script.contains(&format!("set day of startDate to {}\n", expected.day())));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_calendar_create_unknown_calendar_falls_back_to_work() -> Result<()> {
+    let h = Harness::new().await;
+    h.run("ducktape calendar create Lunch 2025-05-02 12:00 13:00 Nonexistent")
+        .await?;
+    assert!(h.create_script().contains("if name of cal is \"Work\""));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_calendar_create_sanitizes_html_notes() -> Result<()> {
+    let h = Harness::new().await;
+    h.run(
+        "ducktape calendar create Review 2025-05-03 09:00 10:00 Work --notes \"<p>Agenda</p><ul><li>Budget</li></ul>\"",
+    )
+    .await?;
+    assert!(h.create_script().contains("description:\"Agenda\n\n- Budget\""));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_calendar_create_raw_keeps_html_notes() -> Result<()> {
+    let h = Harness::new().await;
+    h.run(
+        "ducktape calendar create Review 2025-05-03 09:00 10:00 Work --notes \"<b>Raw</b>\" --raw",
+    )
+    .await?;
+    assert!(h.create_script().contains("description:\"<b>Raw</b>\""));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_quick_add_creates_event() -> Result<()> {
+    let h = Harness::new().await;
+    h.run(
+        "ducktape quick \"Lunch with Sara 2025-05-02 12:30-13:30 at Blue Bottle\" --calendar Home",
+    )
+    .await?;
+
+    let script = h.create_script();
+    assert!(script.contains("if name of cal is \"Home\""));
+    assert!(script.contains("summary:\"Lunch with Sara\""));
+    assert!(script.contains("location:\"Blue Bottle\""));
+    assert!(script.contains("set hours of startDate to 12\n"));
+    assert!(script.contains("set hours of endDate to 13\n"));
+
+    let scripts_before = h.scripts().len();
+    h.run("ducktape quick \"Offsite next friday\" --dry-run").await?;
+    assert_eq!(h.scripts().len(), scripts_before);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_calendar_list() -> Result<()> {
+    let h = Harness::new().await;
+    h.run("ducktape calendar list").await?;
+    assert!(!h.apps().is_empty());
+    assert!(h.apps().iter().all(|app| app == "Calendar"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_todo_create_sends_reminder_properties() -> Result<()> {
+    let h = Harness::new().await;
+    h.run("ducktape todo create \"Pay rent\" Home --due 2025-05-01 --priority high")
+        .await?;
+
+    assert_eq!(h.apps(), vec!["Reminders"; 2]);
+    let script = h.create_script();
+    assert!(script.contains("set remLists to lists whose name is \"Home\""));
+    assert!(script.contains("name:\"Pay rent\""));
+    assert!(script.contains(", allday due date:date \"05/01/2025 12:00:00 AM\""));
+    assert!(script.contains("priority:1"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_todo_create_with_remind_time() -> Result<()> {
+    let h = Harness::new().await;
+    h.run("ducktape todo create Standup --remind \"2025-05-01 09:30\" --notes \"<i>daily</i>\"")
+        .await?;
+
+    let script = h.create_script();
+    assert!(script.contains("set remLists to lists whose name is \"Reminders\""));
+    assert!(script.contains("body:\"daily\""));
+    assert!(script.contains("due date:date \"05/01/2025 09:30:00 AM\""));
+    assert!(script.contains("remind me date:date \"05/01/2025 09:30:00 AM\""));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reminder_create() -> Result<()> {
+    let h = Harness::new().await;
+    h.run_args(
+        "reminder",
+        &["create", "Water plants", "Home"],
+        &[("remind", "2025-05-01 18:00"), ("priority", "low")],
+    )
+    .await?;
+
+    let script = h.create_script();
+    assert!(h.apps().iter().all(|app| app == "Reminders"));
+    assert!(script.contains("make new reminder"));
+    assert!(script.contains("name:\"Water plants\""));
+    assert!(script.contains("remind me date:date \"05/01/2025 06:00:00 PM\""));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_note_create_converts_html_content() -> Result<()> {
+    let h = Harness::new().await;
+    h.run("ducktape note create Meeting notes --content \"<p>Ship it</p>\"").await?;
+
+    assert_eq!(h.apps().last().map(String::as_str), Some("Notes"));
+    assert!(
+        h.create_script()
+            .contains("make new note with properties {name:\"Meeting notes\", body:\"Ship it\"}")
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_config_set_is_persisted() -> Result<()> {
+    let h = Harness::new().await;
+    h.run("ducktape config set notifications.cooldown_minutes 15").await?;
+    assert_eq!(Config::load()?.notifications.cooldown_minutes, 15);
+
+    h.run("ducktape config show notifications.cooldown_minutes").await?;
+    h.run("ducktape config set notifications.cooldown_minutes 60").await?;
+    assert_eq!(Config::load()?.notifications.cooldown_minutes, 60);
+    assert!(h.scripts().is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_commands_without_backends_run_no_scripts() -> Result<()> {
+    let h = Harness::new().await;
+    h.run("ducktape utility tz 14:00 EST to CET on 2025-05-01").await?;
+    h.run("ducktape pack list").await?;
+    h.run_args("version", &[], &[]).await?;
+    h.run_args("help", &[], &[]).await?;
+    h.run_args("no-such-command", &["x"], &[]).await?;
+    assert!(h.scripts().is_empty());
+    Ok(())
+}
+
+/// Stands in for an LLM provider with a fixed translation
+struct MockParser;
+
+#[async_trait]
+impl Parser for MockParser {
+    async fn parse_input(&self, _input: &str) -> Result<ParseResult> {
+        Ok(ParseResult::CommandString(
+            "ducktape todo create \"Call Joe\" --remind \"tomorrow at 3pm\"".to_string(),
+        ))
+    }
+
+    fn new() -> Result<Self> {
+        Ok(Self)
+    }
+}
+
+#[tokio::test]
+async fn test_natural_language_command_reaches_reminders() -> Result<()> {
+    let h = Harness::new().await;
+    let parser = MockParser::new()?;
+    let command = match parser.parse_input("remind me to call Joe tomorrow at 3pm").await? {
+        ParseResult::CommandString(command) => sanitize_nlp_command(&command),
+        ParseResult::StructuredCommand(_) | ParseResult::NeedsClarification(_) => unreachable!(),
+    };
+    h.run(&command).await?;
+
+    let tomorrow = Local::now().date_naive() + Duration::days(1);
+    let script = h.create_script();
+    assert!(script.contains("name:\"Call Joe\""));
+    assert!(
+        script.contains(&format!(
+            "remind me date:date \"{} 03:00:00 PM\"",
+            tomorrow.format("%m/%d/%Y")
+        ))
+    );
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use ducktape::DucktapeError;
 use ducktape::calendar::{
     self, CalendarError, EventConfig, RecurrenceFrequency, RecurrencePattern,
 };
@@ -21,8 +22,7 @@ async fn integration_test_create_event_with_invite() -> Result<()> {
     // Expect failure since the calendar does not exist.
     match result {
         Err(e) => {
-            // Try to downcast to CalendarError
-            if let Some(calendar_err) = e.downcast_ref::<CalendarError>() {
+            if let DucktapeError::Calendar(calendar_err) = &e {
                 match calendar_err {
                     CalendarError::CalendarNotFound(_) => (), // Expected error
                     _ => panic!("Unexpected calendar error: {:?}", calendar_err),
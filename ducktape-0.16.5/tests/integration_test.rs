@@ -31,11 +31,7 @@ fn test_calendar_operations() -> Result<()> {
         date: "2024-02-21".to_string(),
         time: "14:30".to_string(),
         calendars: vec!["Test Calendar".to_string()],
-        all_day: false,
-        location: None,
-        description: None,
-        email: None,
-        reminder: None,
+        ..Default::default()
     };
 
     // Save the event and verify it was saved
@@ -29,6 +29,9 @@ async fn test_grok_parser_factory() -> Result<()> {
             // It's also acceptable if the parser returns a structured command
             Ok(())
         }
+        ParseResult::NeedsClarification(clarification) => {
+            panic!("Unexpected question: {}", clarification.question)
+        }
     }
 }
 
@@ -54,6 +57,9 @@ async fn test_terminal_parser() -> Result<()> {
             assert!(args.args.contains(&"create".to_string()));
             Ok(())
         }
+        ParseResult::NeedsClarification(clarification) => {
+            panic!("Unexpected question: {}", clarification.question)
+        }
     }
 }
 
@@ -106,6 +112,9 @@ async fn test_backward_compatibility() -> Result<()> {
             // Also acceptable
             Ok(())
         }
+        ParseResult::NeedsClarification(clarification) => {
+            panic!("Unexpected question: {}", clarification.question)
+        }
     }
 }
 
@@ -132,5 +141,8 @@ async fn test_live_grok_api_integration() -> Result<()> {
             assert_eq!(args.command, "calendar");
             Ok(())
         }
+        ParseResult::NeedsClarification(clarification) => {
+            panic!("Unexpected question: {}", clarification.question)
+        }
     }
 }